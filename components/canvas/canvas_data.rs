@@ -3,6 +3,7 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use crate::canvas_paint_thread::AntialiasMode;
+use crate::gpu_backend::{GpuBackend, GpuContext};
 use crate::raqote_backend::Repetition;
 use canvas_traits::canvas::*;
 use cssparser::RGBA;
@@ -12,6 +13,7 @@ use num_traits::ToPrimitive;
 #[allow(unused_imports)]
 use std::marker::PhantomData;
 use std::mem;
+use std::rc::Rc;
 use std::sync::Arc;
 use webrender::api::DirtyRect;
 use webrender_api::units::RectExt as RectExt_;
@@ -376,8 +378,11 @@ pub struct CanvasData<'a> {
     pub canvas_id: CanvasId,
 }
 
-fn create_backend() -> Box<dyn Backend> {
-    Box::new(crate::raqote_backend::RaqoteBackend)
+fn create_backend(gpu: Option<Rc<GpuContext>>) -> Box<dyn Backend> {
+    match gpu {
+        Some(gpu) => Box::new(GpuBackend::new(gpu)),
+        None => Box::new(crate::raqote_backend::RaqoteBackend),
+    }
 }
 
 impl<'a> CanvasData<'a> {
@@ -386,8 +391,9 @@ impl<'a> CanvasData<'a> {
         webrender_api_sender: webrender_api::RenderApiSender,
         antialias: AntialiasMode,
         canvas_id: CanvasId,
+        gpu: Option<Rc<GpuContext>>,
     ) -> CanvasData<'a> {
-        let backend = create_backend();
+        let backend = create_backend(gpu);
         let draw_target = backend.create_drawtarget(size);
         let webrender_api = webrender_api_sender.create_api();
         CanvasData {
@@ -3,6 +3,7 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use crate::canvas_data::*;
+use crate::gpu_backend::{GpuCanvasInit, GpuContext};
 use canvas_traits::canvas::*;
 use canvas_traits::ConstellationCanvasMsg;
 use crossbeam_channel::{select, unbounded, Sender};
@@ -11,6 +12,7 @@ use ipc_channel::ipc::{self, IpcSender};
 use ipc_channel::router::ROUTER;
 use std::borrow::ToOwned;
 use std::collections::HashMap;
+use std::rc::Rc;
 use std::thread;
 
 pub enum AntialiasMode {
@@ -21,26 +23,32 @@ pub enum AntialiasMode {
 pub struct CanvasPaintThread<'a> {
     canvases: HashMap<CanvasId, CanvasData<'a>>,
     next_canvas_id: CanvasId,
+    /// The GL context which the canvases draw with, if they are drawn on the GPU.
+    gpu: Option<Rc<GpuContext>>,
 }
 
 impl<'a> CanvasPaintThread<'a> {
-    fn new() -> CanvasPaintThread<'a> {
+    fn new(gpu_init: Option<GpuCanvasInit>) -> CanvasPaintThread<'a> {
         CanvasPaintThread {
             canvases: HashMap::new(),
             next_canvas_id: CanvasId(0),
+            gpu: gpu_init.and_then(GpuContext::new),
         }
     }
 
     /// Creates a new `CanvasPaintThread` and returns an `IpcSender` to
-    /// communicate with it.
-    pub fn start() -> (Sender<ConstellationCanvasMsg>, IpcSender<CanvasMsg>) {
+    /// communicate with it. The canvases are drawn on the GPU if `gpu_init`
+    /// is given.
+    pub fn start(
+        gpu_init: Option<GpuCanvasInit>,
+    ) -> (Sender<ConstellationCanvasMsg>, IpcSender<CanvasMsg>) {
         let (ipc_sender, ipc_receiver) = ipc::channel::<CanvasMsg>().unwrap();
         let msg_receiver = ROUTER.route_ipc_receiver_to_new_crossbeam_receiver(ipc_receiver);
         let (create_sender, create_receiver) = unbounded();
         thread::Builder::new()
             .name("CanvasThread".to_owned())
             .spawn(move || {
-                let mut canvas_paint_thread = CanvasPaintThread::new();
+                let mut canvas_paint_thread = CanvasPaintThread::new(gpu_init);
                 loop {
                     select! {
                         recv(msg_receiver) -> msg => {
@@ -114,7 +122,13 @@ impl<'a> CanvasPaintThread<'a> {
         let canvas_id = self.next_canvas_id.clone();
         self.next_canvas_id.0 += 1;
 
-        let canvas_data = CanvasData::new(size, webrender_api_sender, antialias, canvas_id.clone());
+        let canvas_data = CanvasData::new(
+            size,
+            webrender_api_sender,
+            antialias,
+            canvas_id.clone(),
+            self.gpu.clone(),
+        );
         self.canvases.insert(canvas_id.clone(), canvas_data);
 
         canvas_id
@@ -0,0 +1,791 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! A backend which draws the rectangles, images and clears of 2D canvases with OpenGL, so that
+//! large canvases and frequent `drawImage()` calls aren't rasterized on the CPU.
+//!
+//! The other operations are still drawn by raqote: every GPU draw target keeps a raqote draw
+//! target, and its pixels move between the two whenever an operation needs the other one.
+//! Draw targets whose pixels move back and forth too often stay on the CPU.
+
+use crate::canvas_data::{
+    self, Backend, CanvasPaintState, Color, CompositionOp, DrawOptions, ExtendMode, Filter,
+    GenericDrawTarget, GenericPathBuilder, GradientStop, GradientStops, Path, SourceSurface,
+    StrokeOptions, SurfaceFormat,
+};
+use crate::raqote_backend::{self, RaqoteBackend};
+use canvas_traits::canvas::*;
+use cssparser::RGBA;
+use euclid::default::{Point2D, Rect, Size2D, Transform2D, Vector2D};
+use sparkle::gl::{self, GLenum, GLint, GLuint, Gl};
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+use surfman::platform::generic::universal::adapter::Adapter;
+use surfman::platform::generic::universal::connection::Connection;
+use surfman::platform::generic::universal::context::Context;
+use surfman::platform::generic::universal::device::Device;
+use surfman::{ContextAttributeFlags, ContextAttributes, GLVersion};
+
+/// The number of times the pixels of a draw target may move between the CPU and the GPU
+/// before the draw target stops using the GPU.
+const MAX_MIGRATIONS: u32 = 32;
+
+const VERTEX_SHADER: &str = "
+attribute vec2 aPosition;
+attribute vec2 aTexCoord;
+uniform vec2 uSize;
+varying vec2 vTexCoord;
+
+void main() {
+    vTexCoord = aTexCoord;
+    gl_Position = vec4(aPosition / uSize * 2.0 - 1.0, 0.0, 1.0);
+}
+";
+
+const FRAGMENT_SHADER: &str = "
+#ifdef GL_ES
+precision highp float;
+#endif
+
+uniform sampler2D uTexture;
+uniform float uUseTexture;
+uniform vec4 uColor;
+varying vec2 vTexCoord;
+
+void main() {
+    gl_FragColor = mix(uColor, texture2D(uTexture, vTexCoord) * uColor.a, uUseTexture);
+}
+";
+
+const POSITION_ATTRIBUTE: GLuint = 0;
+const TEX_COORD_ATTRIBUTE: GLuint = 1;
+
+/// What the canvas thread needs to create its GL context.
+pub struct GpuCanvasInit {
+    pub connection: Connection,
+    pub adapter: Adapter,
+    pub api_type: gl::GlType,
+}
+
+/// The GL context of the canvas thread, which is shared by all its draw targets and stays
+/// current for the lifetime of the thread.
+pub struct GpuContext {
+    device: Device,
+    context: Context,
+    gl: Rc<Gl>,
+    program: GLuint,
+    vertex_buffer: GLuint,
+    size_uniform: GLint,
+    color_uniform: GLint,
+    use_texture_uniform: GLint,
+    max_texture_size: i32,
+}
+
+impl GpuContext {
+    #[allow(unsafe_code)]
+    pub fn new(init: GpuCanvasInit) -> Option<Rc<GpuContext>> {
+        let device = match Device::new(&init.connection, &init.adapter) {
+            Ok(device) => device,
+            Err(error) => {
+                warn!("Failed to open the canvas GPU device ({:?})", error);
+                return None;
+            },
+        };
+        let context_attributes = ContextAttributes {
+            version: GLVersion::new(2, 0),
+            flags: ContextAttributeFlags::ALPHA,
+        };
+        let mut context = match device
+            .create_context_descriptor(&context_attributes)
+            .and_then(|descriptor| device.create_context(&descriptor))
+        {
+            Ok(context) => context,
+            Err(error) => {
+                warn!("Failed to create the canvas GL context ({:?})", error);
+                return None;
+            },
+        };
+        if let Err(error) = device.make_context_current(&context) {
+            warn!("Failed to make the canvas GL context current ({:?})", error);
+            let _ = device.destroy_context(&mut context);
+            return None;
+        }
+
+        let gl = match init.api_type {
+            gl::GlType::Gl => Gl::gl_fns(gl::ffi_gl::Gl::load_with(|symbol_name| {
+                device.get_proc_address(&context, symbol_name)
+            })),
+            gl::GlType::Gles => Gl::gles_fns(gl::ffi_gles::Gles2::load_with(|symbol_name| {
+                device.get_proc_address(&context, symbol_name)
+            })),
+        };
+        let program = match create_program(&gl) {
+            Ok(program) => program,
+            Err(error) => {
+                warn!("Failed to compile the canvas shaders ({})", error);
+                let _ = device.destroy_context(&mut context);
+                return None;
+            },
+        };
+
+        let mut max_texture_size = [0];
+        unsafe {
+            gl.get_integer_v(gl::MAX_TEXTURE_SIZE, &mut max_texture_size);
+        }
+
+        Some(Rc::new(GpuContext {
+            size_uniform: gl.get_uniform_location(program, "uSize"),
+            color_uniform: gl.get_uniform_location(program, "uColor"),
+            use_texture_uniform: gl.get_uniform_location(program, "uUseTexture"),
+            vertex_buffer: gl.gen_buffers(1)[0],
+            max_texture_size: max_texture_size[0],
+            program,
+            gl,
+            device,
+            context,
+        }))
+    }
+
+    fn create_texture(&self, size: Size2D<i32>, filter: Filter, data: Option<&[u8]>) -> GLuint {
+        let gl = &self.gl;
+        let texture = gl.gen_textures(1)[0];
+        let filter = match filter {
+            Filter::Linear => gl::LINEAR,
+            Filter::Point => gl::NEAREST,
+        };
+        gl.bind_texture(gl::TEXTURE_2D, texture);
+        gl.tex_parameter_i(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, filter as GLint);
+        gl.tex_parameter_i(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, filter as GLint);
+        gl.tex_parameter_i(
+            gl::TEXTURE_2D,
+            gl::TEXTURE_WRAP_S,
+            gl::CLAMP_TO_EDGE as GLint,
+        );
+        gl.tex_parameter_i(
+            gl::TEXTURE_2D,
+            gl::TEXTURE_WRAP_T,
+            gl::CLAMP_TO_EDGE as GLint,
+        );
+        gl.tex_image_2d(
+            gl::TEXTURE_2D,
+            0,
+            gl::RGBA as GLint,
+            size.width,
+            size.height,
+            0,
+            gl::RGBA,
+            gl::UNSIGNED_BYTE,
+            data,
+        );
+        texture
+    }
+
+    /// Draws the given quad, whose corners are in the device space of the framebuffer, with
+    /// either a premultiplied color or a texture multiplied by the alpha of the color.
+    fn draw_quad(
+        &self,
+        framebuffer: GLuint,
+        framebuffer_size: Size2D<i32>,
+        corners: [Point2D<f32>; 4],
+        color: [f32; 4],
+        texture: Option<GLuint>,
+        (source_factor, destination_factor): (GLenum, GLenum),
+    ) {
+        let gl = &self.gl;
+        gl.bind_framebuffer(gl::FRAMEBUFFER, framebuffer);
+        gl.viewport(0, 0, framebuffer_size.width, framebuffer_size.height);
+        gl.enable(gl::BLEND);
+        gl.blend_func(source_factor, destination_factor);
+        gl.use_program(self.program);
+        gl.uniform_2f(
+            self.size_uniform,
+            framebuffer_size.width as f32,
+            framebuffer_size.height as f32,
+        );
+        gl.uniform_4f(self.color_uniform, color[0], color[1], color[2], color[3]);
+        gl.uniform_1f(
+            self.use_texture_uniform,
+            if texture.is_some() { 1. } else { 0. },
+        );
+        if let Some(texture) = texture {
+            gl.active_texture(gl::TEXTURE0);
+            gl.bind_texture(gl::TEXTURE_2D, texture);
+        }
+
+        // The corners are in the order top left, top right, bottom left, bottom right, which
+        // draws the quad as a triangle strip.
+        let tex_coords = [[0., 0.], [1., 0.], [0., 1.], [1., 1.]];
+        let mut vertices = Vec::with_capacity(16);
+        for (corner, tex_coord) in corners.iter().zip(tex_coords.iter()) {
+            vertices.extend_from_slice(&[corner.x, corner.y, tex_coord[0], tex_coord[1]]);
+        }
+        gl.bind_buffer(gl::ARRAY_BUFFER, self.vertex_buffer);
+        gl::buffer_data(gl, gl::ARRAY_BUFFER, &vertices, gl::STREAM_DRAW);
+        let stride = 4 * std::mem::size_of::<f32>() as i32;
+        gl.enable_vertex_attrib_array(POSITION_ATTRIBUTE);
+        gl.vertex_attrib_pointer(POSITION_ATTRIBUTE, 2, gl::FLOAT, false, stride, 0);
+        gl.enable_vertex_attrib_array(TEX_COORD_ATTRIBUTE);
+        gl.vertex_attrib_pointer(
+            TEX_COORD_ATTRIBUTE,
+            2,
+            gl::FLOAT,
+            false,
+            stride,
+            2 * std::mem::size_of::<f32>() as u32,
+        );
+        gl.draw_arrays(gl::TRIANGLE_STRIP, 0, 4);
+    }
+}
+
+impl Drop for GpuContext {
+    fn drop(&mut self) {
+        self.gl.delete_buffers(&[self.vertex_buffer]);
+        self.gl.delete_program(self.program);
+        let _ = self.device.destroy_context(&mut self.context);
+    }
+}
+
+#[allow(unsafe_code)]
+fn compile_shader(gl: &Gl, shader_type: GLenum, source: &str) -> Result<GLuint, String> {
+    let shader = gl.create_shader(shader_type);
+    gl.shader_source(shader, &[source.as_bytes()]);
+    gl.compile_shader(shader);
+    let mut compiled = [0];
+    unsafe {
+        gl.get_shader_iv(shader, gl::COMPILE_STATUS, &mut compiled);
+    }
+    if compiled[0] == 0 {
+        let log = gl.get_shader_info_log(shader);
+        gl.delete_shader(shader);
+        return Err(log);
+    }
+    Ok(shader)
+}
+
+#[allow(unsafe_code)]
+fn create_program(gl: &Gl) -> Result<GLuint, String> {
+    let vertex_shader = compile_shader(gl, gl::VERTEX_SHADER, VERTEX_SHADER)?;
+    let fragment_shader = match compile_shader(gl, gl::FRAGMENT_SHADER, FRAGMENT_SHADER) {
+        Ok(shader) => shader,
+        Err(error) => {
+            gl.delete_shader(vertex_shader);
+            return Err(error);
+        },
+    };
+
+    let program = gl.create_program();
+    gl.attach_shader(program, vertex_shader);
+    gl.attach_shader(program, fragment_shader);
+    gl.bind_attrib_location(program, POSITION_ATTRIBUTE, "aPosition");
+    gl.bind_attrib_location(program, TEX_COORD_ATTRIBUTE, "aTexCoord");
+    gl.link_program(program);
+    gl.delete_shader(vertex_shader);
+    gl.delete_shader(fragment_shader);
+
+    let mut linked = [0];
+    unsafe {
+        gl.get_program_iv(program, gl::LINK_STATUS, &mut linked);
+    }
+    if linked[0] == 0 {
+        let log = gl.get_program_info_log(program);
+        gl.delete_program(program);
+        return Err(log);
+    }
+    Ok(program)
+}
+
+/// The blend functions of the composition operators which only affect the pixels covered by
+/// the drawn shape, for premultiplied colors.
+fn blend_func(blend_mode: raqote::BlendMode) -> Option<(GLenum, GLenum)> {
+    Some(match blend_mode {
+        raqote::BlendMode::SrcOver => (gl::ONE, gl::ONE_MINUS_SRC_ALPHA),
+        raqote::BlendMode::Src => (gl::ONE, gl::ZERO),
+        raqote::BlendMode::DstOver => (gl::ONE_MINUS_DST_ALPHA, gl::ONE),
+        raqote::BlendMode::SrcAtop => (gl::DST_ALPHA, gl::ONE_MINUS_SRC_ALPHA),
+        raqote::BlendMode::DstOut => (gl::ZERO, gl::ONE_MINUS_SRC_ALPHA),
+        raqote::BlendMode::Xor => (gl::ONE_MINUS_DST_ALPHA, gl::ONE_MINUS_SRC_ALPHA),
+        raqote::BlendMode::Add => (gl::ONE, gl::ONE),
+        raqote::BlendMode::Clear => (gl::ZERO, gl::ZERO),
+        _ => return None,
+    })
+}
+
+/// A backend whose draw targets draw on the GPU. The paint state is the one of raqote.
+pub struct GpuBackend {
+    gpu: Rc<GpuContext>,
+}
+
+impl GpuBackend {
+    pub fn new(gpu: Rc<GpuContext>) -> GpuBackend {
+        GpuBackend { gpu }
+    }
+}
+
+impl Backend for GpuBackend {
+    fn get_composition_op(&self, opts: &DrawOptions) -> CompositionOp {
+        RaqoteBackend.get_composition_op(opts)
+    }
+
+    fn need_to_draw_shadow(&self, color: &Color) -> bool {
+        RaqoteBackend.need_to_draw_shadow(color)
+    }
+
+    fn set_shadow_color<'a>(&mut self, color: RGBA, state: &mut CanvasPaintState<'a>) {
+        RaqoteBackend.set_shadow_color(color, state)
+    }
+
+    fn set_fill_style<'a>(
+        &mut self,
+        style: FillOrStrokeStyle,
+        state: &mut CanvasPaintState<'a>,
+        drawtarget: &dyn GenericDrawTarget,
+    ) {
+        RaqoteBackend.set_fill_style(style, state, drawtarget)
+    }
+
+    fn set_stroke_style<'a>(
+        &mut self,
+        style: FillOrStrokeStyle,
+        state: &mut CanvasPaintState<'a>,
+        drawtarget: &dyn GenericDrawTarget,
+    ) {
+        RaqoteBackend.set_stroke_style(style, state, drawtarget)
+    }
+
+    fn set_global_composition<'a>(
+        &mut self,
+        op: CompositionOrBlending,
+        state: &mut CanvasPaintState<'a>,
+    ) {
+        RaqoteBackend.set_global_composition(op, state)
+    }
+
+    fn create_drawtarget(&self, size: Size2D<u64>) -> Box<dyn GenericDrawTarget> {
+        let max_size = self.gpu.max_texture_size as u64;
+        if size.width == 0 || size.height == 0 || size.width > max_size || size.height > max_size {
+            return RaqoteBackend.create_drawtarget(size);
+        }
+        Box::new(GpuDrawTarget::new(self.gpu.clone(), size.to_i32()))
+    }
+
+    fn recreate_paint_state<'a>(&self, state: &CanvasPaintState<'a>) -> CanvasPaintState<'a> {
+        RaqoteBackend.recreate_paint_state(state)
+    }
+}
+
+/// Which copies of the pixels of a draw target are up to date.
+#[derive(Clone, Copy, PartialEq)]
+enum Residency {
+    Cpu,
+    Gpu,
+    Both,
+}
+
+pub struct GpuDrawTarget {
+    gpu: Rc<GpuContext>,
+    size: Size2D<i32>,
+    texture: GLuint,
+    framebuffer: GLuint,
+    /// The draw target of the operations which aren't drawn on the GPU. Its transform and
+    /// clips are always those of this draw target.
+    cpu: RefCell<raqote::DrawTarget>,
+    residency: Cell<Residency>,
+    transform: Transform2D<f32>,
+    /// The number of clips pushed on the draw target. Operations are drawn on the CPU while
+    /// the draw target is clipped.
+    clip_depth: usize,
+    /// The number of times the pixels had to move from one side to the other to draw.
+    migrations: u32,
+}
+
+impl GpuDrawTarget {
+    fn new(gpu: Rc<GpuContext>, size: Size2D<i32>) -> GpuDrawTarget {
+        let texture = gpu.create_texture(size, Filter::Point, None);
+        let gl = &gpu.gl;
+        let framebuffer = gl.gen_framebuffers(1)[0];
+        gl.bind_framebuffer(gl::FRAMEBUFFER, framebuffer);
+        gl.framebuffer_texture_2d(
+            gl::FRAMEBUFFER,
+            gl::COLOR_ATTACHMENT0,
+            gl::TEXTURE_2D,
+            texture,
+            0,
+        );
+        gl.clear_color(0., 0., 0., 0.);
+        gl.clear(gl::COLOR_BUFFER_BIT);
+
+        GpuDrawTarget {
+            cpu: RefCell::new(raqote::DrawTarget::new(size.width, size.height)),
+            residency: Cell::new(Residency::Both),
+            transform: Transform2D::identity(),
+            clip_depth: 0,
+            migrations: 0,
+            gpu,
+            size,
+            texture,
+            framebuffer,
+        }
+    }
+
+    fn is_accelerated(&self) -> bool {
+        self.migrations <= MAX_MIGRATIONS
+    }
+
+    /// Makes the GPU copy of the pixels up to date, if the operations of this draw target can
+    /// still be drawn on the GPU. The caller draws on the GPU if this returns true.
+    fn prepare_gpu(&mut self) -> bool {
+        if !self.is_accelerated() || self.clip_depth > 0 {
+            return false;
+        }
+        if self.residency.get() == Residency::Cpu {
+            self.migrations += 1;
+            if !self.is_accelerated() {
+                debug!("Canvas draw target moved to the CPU for good.");
+                return false;
+            }
+            let gl = &self.gpu.gl;
+            let size = self.size;
+            let texture = self.texture;
+            self.cpu.borrow().snapshot_data(&|bytes| {
+                gl.bind_texture(gl::TEXTURE_2D, texture);
+                gl.tex_sub_image_2d(
+                    gl::TEXTURE_2D,
+                    0,
+                    0,
+                    0,
+                    size.width,
+                    size.height,
+                    gl::RGBA,
+                    gl::UNSIGNED_BYTE,
+                    bytes,
+                );
+                vec![]
+            });
+        }
+        self.residency.set(Residency::Gpu);
+        true
+    }
+
+    /// Makes the CPU copy of the pixels up to date.
+    fn sync_cpu(&self) {
+        if self.residency.get() != Residency::Gpu {
+            return;
+        }
+        let gl = &self.gpu.gl;
+        gl.bind_framebuffer(gl::FRAMEBUFFER, self.framebuffer);
+        let bytes = gl.read_pixels(
+            0,
+            0,
+            self.size.width,
+            self.size.height,
+            gl::RGBA,
+            gl::UNSIGNED_BYTE,
+        );
+        let mut cpu = self.cpu.borrow_mut();
+        for (pixel, bytes) in cpu.get_data_mut().iter_mut().zip(bytes.chunks_exact(4)) {
+            *pixel = u32::from_ne_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+        }
+        self.residency.set(Residency::Both);
+    }
+
+    /// Returns the CPU draw target, to draw an operation which isn't drawn on the GPU.
+    fn cpu_for_drawing(&mut self) -> &mut raqote::DrawTarget {
+        if self.residency.get() == Residency::Gpu {
+            self.migrations += 1;
+            self.sync_cpu();
+        }
+        self.residency.set(Residency::Cpu);
+        self.cpu.get_mut()
+    }
+
+    /// Draws the given rectangle of the user space with a color or a texture on the GPU.
+    fn draw_rect(
+        &self,
+        rect: &Rect<f32>,
+        color: [f32; 4],
+        texture: Option<GLuint>,
+        blend_mode: raqote::BlendMode,
+    ) {
+        let blend_func = blend_func(blend_mode).expect("Unsupported blend mode");
+        let gl = &self.gpu.gl;
+        if blend_mode == raqote::BlendMode::Src {
+            // The copy operator also clears the pixels outside of the shape.
+            gl.bind_framebuffer(gl::FRAMEBUFFER, self.framebuffer);
+            gl.clear_color(0., 0., 0., 0.);
+            gl.clear(gl::COLOR_BUFFER_BIT);
+        }
+        let corners = [
+            self.transform.transform_point(rect.origin),
+            self.transform
+                .transform_point(Point2D::new(rect.max_x(), rect.min_y())),
+            self.transform
+                .transform_point(Point2D::new(rect.min_x(), rect.max_y())),
+            self.transform
+                .transform_point(Point2D::new(rect.max_x(), rect.max_y())),
+        ];
+        self.gpu.draw_quad(
+            self.framebuffer,
+            self.size,
+            corners,
+            color,
+            texture,
+            blend_func,
+        );
+    }
+}
+
+impl Drop for GpuDrawTarget {
+    fn drop(&mut self) {
+        self.gpu.gl.delete_framebuffers(&[self.framebuffer]);
+        self.gpu.gl.delete_textures(&[self.texture]);
+    }
+}
+
+impl GenericDrawTarget for GpuDrawTarget {
+    fn clear_rect(&mut self, rect: &Rect<f32>) {
+        if self.prepare_gpu() {
+            self.draw_rect(rect, [0.; 4], None, raqote::BlendMode::Clear);
+        } else {
+            GenericDrawTarget::clear_rect(self.cpu_for_drawing(), rect);
+        }
+    }
+
+    fn copy_surface(
+        &mut self,
+        surface: SourceSurface,
+        source: Rect<i32>,
+        destination: Point2D<i32>,
+    ) {
+        let destination_rect = Rect::new(destination, source.size);
+        let clipped_rect = destination_rect.intersection(&Rect::from_size(self.size));
+        // Only replace pixels on the GPU if they are only there, since this is as costly as
+        // replacing them on the CPU.
+        if self.residency.get() != Residency::Gpu || !self.prepare_gpu() {
+            GenericDrawTarget::copy_surface(self.cpu_for_drawing(), surface, source, destination);
+            return;
+        }
+        let clipped_rect = match clipped_rect {
+            Some(rect) if !rect.is_empty() => rect,
+            _ => return,
+        };
+        let SourceSurface::Raqote(data) = surface;
+        let source_rect = Rect::new(
+            (clipped_rect.origin - destination).to_point(),
+            clipped_rect.size,
+        );
+        let bytes = pixels::rgba8_get_rect(&data, source.size.cast(), source_rect.cast());
+        let gl = &self.gpu.gl;
+        gl.bind_texture(gl::TEXTURE_2D, self.texture);
+        gl.tex_sub_image_2d(
+            gl::TEXTURE_2D,
+            0,
+            clipped_rect.origin.x,
+            clipped_rect.origin.y,
+            clipped_rect.size.width,
+            clipped_rect.size.height,
+            gl::RGBA,
+            gl::UNSIGNED_BYTE,
+            &bytes,
+        );
+    }
+
+    fn create_gradient_stops(
+        &self,
+        gradient_stops: Vec<GradientStop>,
+        extend_mode: ExtendMode,
+    ) -> GradientStops {
+        self.cpu
+            .borrow()
+            .create_gradient_stops(gradient_stops, extend_mode)
+    }
+
+    fn create_path_builder(&self) -> Box<dyn GenericPathBuilder> {
+        GenericDrawTarget::create_path_builder(&*self.cpu.borrow())
+    }
+
+    fn create_similar_draw_target(
+        &self,
+        size: &Size2D<i32>,
+        format: SurfaceFormat,
+    ) -> Box<dyn GenericDrawTarget> {
+        self.cpu.borrow().create_similar_draw_target(size, format)
+    }
+
+    fn create_source_surface_from_data(
+        &self,
+        data: &[u8],
+        size: Size2D<i32>,
+        stride: i32,
+    ) -> Option<SourceSurface> {
+        self.cpu
+            .borrow()
+            .create_source_surface_from_data(data, size, stride)
+    }
+
+    fn draw_surface(
+        &mut self,
+        surface: SourceSurface,
+        dest: Rect<f64>,
+        source: Rect<f64>,
+        filter: Filter,
+        draw_options: &DrawOptions,
+    ) {
+        let options = *draw_options.as_raqote();
+        let size = source.size.to_i32();
+        let max_texture_size = self.gpu.max_texture_size;
+        if size.width > max_texture_size ||
+            size.height > max_texture_size ||
+            blend_func(options.blend_mode).is_none() ||
+            !self.prepare_gpu()
+        {
+            GenericDrawTarget::draw_surface(
+                self.cpu_for_drawing(),
+                surface,
+                dest,
+                source,
+                filter,
+                draw_options,
+            );
+            return;
+        }
+
+        let SourceSurface::Raqote(data) = surface;
+        let texture = self.gpu.create_texture(size, filter, Some(&data[..]));
+        self.draw_rect(
+            &dest.to_f32(),
+            [options.alpha; 4],
+            Some(texture),
+            options.blend_mode,
+        );
+        self.gpu.gl.delete_textures(&[texture]);
+    }
+
+    fn draw_surface_with_shadow(
+        &self,
+        surface: SourceSurface,
+        dest: &Point2D<f32>,
+        color: &Color,
+        offset: &Vector2D<f32>,
+        sigma: f32,
+        operator: CompositionOp,
+    ) {
+        self.cpu
+            .borrow()
+            .draw_surface_with_shadow(surface, dest, color, offset, sigma, operator)
+    }
+
+    fn fill(&mut self, path: &Path, pattern: canvas_data::Pattern, draw_options: &DrawOptions) {
+        GenericDrawTarget::fill(self.cpu_for_drawing(), path, pattern, draw_options);
+    }
+
+    fn fill_rect(
+        &mut self,
+        rect: &Rect<f32>,
+        pattern: canvas_data::Pattern,
+        draw_options: Option<&DrawOptions>,
+    ) {
+        let options =
+            draw_options.map_or_else(raqote::DrawOptions::new, |options| *options.as_raqote());
+        let color = match pattern {
+            canvas_data::Pattern::Raqote(raqote_backend::Pattern::Color(a, r, g, b)) => {
+                // The pixels are stored as BGRA, with premultiplied alpha.
+                let alpha = a as f32 / 255. * options.alpha;
+                Some([
+                    b as f32 / 255. * alpha,
+                    g as f32 / 255. * alpha,
+                    r as f32 / 255. * alpha,
+                    alpha,
+                ])
+            },
+            _ => None,
+        };
+        match color {
+            Some(color) if blend_func(options.blend_mode).is_some() && self.prepare_gpu() => {
+                self.draw_rect(rect, color, None, options.blend_mode);
+            },
+            _ => GenericDrawTarget::fill_rect(self.cpu_for_drawing(), rect, pattern, draw_options),
+        }
+    }
+
+    fn get_format(&self) -> SurfaceFormat {
+        SurfaceFormat::Raqote(())
+    }
+
+    fn get_size(&self) -> Size2D<i32> {
+        self.size
+    }
+
+    fn get_transform(&self) -> Transform2D<f32> {
+        self.transform
+    }
+
+    fn pop_clip(&mut self) {
+        self.clip_depth = self.clip_depth.saturating_sub(1);
+        GenericDrawTarget::pop_clip(self.cpu.get_mut());
+    }
+
+    fn push_clip(&mut self, path: &Path) {
+        self.clip_depth += 1;
+        GenericDrawTarget::push_clip(self.cpu.get_mut(), path);
+    }
+
+    fn set_transform(&mut self, matrix: &Transform2D<f32>) {
+        self.transform = *matrix;
+        GenericDrawTarget::set_transform(self.cpu.get_mut(), matrix);
+    }
+
+    fn snapshot(&self) -> SourceSurface {
+        self.sync_cpu();
+        self.cpu.borrow().snapshot()
+    }
+
+    fn stroke(
+        &mut self,
+        path: &Path,
+        pattern: canvas_data::Pattern,
+        stroke_options: &StrokeOptions,
+        draw_options: &DrawOptions,
+    ) {
+        GenericDrawTarget::stroke(
+            self.cpu_for_drawing(),
+            path,
+            pattern,
+            stroke_options,
+            draw_options,
+        );
+    }
+
+    fn stroke_line(
+        &mut self,
+        start: Point2D<f32>,
+        end: Point2D<f32>,
+        pattern: canvas_data::Pattern,
+        stroke_options: &StrokeOptions,
+        draw_options: &DrawOptions,
+    ) {
+        self.cpu_for_drawing()
+            .stroke_line(start, end, pattern, stroke_options, draw_options);
+    }
+
+    fn stroke_rect(
+        &mut self,
+        rect: &Rect<f32>,
+        pattern: canvas_data::Pattern,
+        stroke_options: &StrokeOptions,
+        draw_options: &DrawOptions,
+    ) {
+        self.cpu_for_drawing()
+            .stroke_rect(rect, pattern, stroke_options, draw_options);
+    }
+
+    fn snapshot_data(&self, f: &dyn Fn(&[u8]) -> Vec<u8>) -> Vec<u8> {
+        self.sync_cpu();
+        self.cpu.borrow().snapshot_data(f)
+    }
+
+    fn snapshot_data_owned(&self) -> Vec<u8> {
+        self.sync_cpu();
+        self.cpu.borrow().snapshot_data_owned()
+    }
+}
@@ -9,8 +9,10 @@ extern crate bitflags;
 #[macro_use]
 extern crate log;
 
+mod gpu_backend;
 mod raqote_backend;
 
+pub use gpu_backend::GpuCanvasInit;
 pub use webgl_mode::WebGLComm;
 pub use webgl_thread::SurfaceProvider;
 pub use webgl_thread::SurfaceProviders;
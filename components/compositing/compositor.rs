@@ -4,6 +4,7 @@
 
 use crate::compositor_thread::CompositorReceiver;
 use crate::compositor_thread::{InitialCompositorState, Msg};
+use crate::frame_scheduler::FrameScheduler;
 #[cfg(feature = "gl")]
 use crate::gl;
 use crate::touch::{TouchAction, TouchHandler};
@@ -12,7 +13,7 @@ use crate::windowing::{
 };
 use crate::{CompositionPipeline, ConstellationMsg, SendableFrameTree};
 use crossbeam_channel::Sender;
-use embedder_traits::Cursor;
+use embedder_traits::{Cursor, OverscrollBehavior};
 use euclid::{Point2D, Rect, Scale, Vector2D};
 use gfx_traits::Epoch;
 #[cfg(feature = "gl")]
@@ -26,10 +27,12 @@ use num_traits::FromPrimitive;
 #[cfg(feature = "gl")]
 use pixels::PixelFormat;
 use profile_traits::time::{self as profile_time, profile, ProfilerCategory};
-use script_traits::CompositorEvent::{MouseButtonEvent, MouseMoveEvent, TouchEvent, WheelEvent};
-use script_traits::{AnimationState, AnimationTickType, LayoutControlMsg};
+use script_traits::CompositorEvent::{MouseButtonEvent, MouseMoveEvent, PointerLockMoveEvent};
+use script_traits::CompositorEvent::{TouchEvent, WheelEvent};
+use script_traits::{AnimationState, AnimationTickType, FrameTiming, LayoutControlMsg};
 use script_traits::{
-    MouseButton, MouseEventType, ScrollState, TouchEventType, TouchId, WheelDelta,
+    MouseButton, MouseEventType, OverscrollContainer, ScrollSnapContainer, ScrollState,
+    ScrollbarContainer, ScrollbarWidth, TouchEventType, TouchId, WheelDelta,
 };
 use script_traits::{UntrustedNodeAddress, WindowSizeData, WindowSizeType};
 use servo_geometry::{DeviceIndependentPixel, FramebufferUintLength};
@@ -44,8 +47,10 @@ use std::sync::Arc;
 use style_traits::viewport::ViewportConstraints;
 use style_traits::{CSSPixel, DevicePixel, PinchZoomFactor};
 use time::{now, precise_time_ns, precise_time_s};
-use webrender_api::units::{DeviceIntPoint, DeviceIntSize, DevicePoint, LayoutVector2D};
-use webrender_api::{self, HitTestFlags, HitTestResult, ScrollLocation};
+use webrender_api::units::{DeviceIntPoint, DeviceIntSize, DevicePoint, DeviceVector2D};
+use webrender_api::units::{LayoutPoint, LayoutRect, LayoutSize, LayoutVector2D, WorldPoint};
+use webrender_api::{self, ColorF, ExternalScrollId, HitTestFlags, HitTestResult};
+use webrender_api::{ScrollClamping, ScrollLocation};
 use webvr_traits::WebVRMainThreadHeartbeat;
 
 #[derive(Debug, PartialEq)]
@@ -64,6 +69,36 @@ enum NotReadyToPaint {
 const MAX_ZOOM: f32 = 8.0;
 const MIN_ZOOM: f32 = 0.1;
 
+/// The duration of scrolls with a smooth scroll behavior, in seconds.
+const SMOOTH_SCROLL_DURATION: f64 = 0.3;
+
+/// The time without scroll events after which a scroll of the user has ended, in seconds.
+const SCROLL_END_DELAY: f64 = 0.1;
+
+/// How close to a snap position, as a fraction of the scrollport, a scroll has to end for a
+/// scroll container with proximity snapping to snap to it.
+const SCROLL_SNAP_PROXIMITY: f32 = 0.3;
+
+/// How long the overlay scrollbars stay visible after the last scroll event, in seconds.
+const SCROLLBAR_HIDE_DELAY: f64 = 1.0;
+
+/// The width of the overlay scrollbars with a `scrollbar-width` of `auto`, in CSS pixels.
+const SCROLLBAR_WIDTH: f32 = 8.0;
+
+/// The width of the overlay scrollbars with a `scrollbar-width` of `thin`, in CSS pixels.
+const THIN_SCROLLBAR_WIDTH: f32 = 4.0;
+
+/// The smallest length of the thumb of an overlay scrollbar, in CSS pixels.
+const MIN_SCROLLBAR_THUMB_LENGTH: f32 = 16.0;
+
+/// The color of the thumb of the overlay scrollbars with a `scrollbar-color` of `auto`.
+const DEFAULT_SCROLLBAR_THUMB_COLOR: ColorF = ColorF {
+    r: 0.5,
+    g: 0.5,
+    b: 0.5,
+    a: 1.0,
+};
+
 trait ConvertPipelineIdFromWebRender {
     fn from_webrender(&self) -> PipelineId;
 }
@@ -142,6 +177,9 @@ pub struct IOCompositor<Window: WindowMethods + ?Sized> {
     /// Tracks the last composite time.
     last_composite_time: u64,
 
+    /// Decides when the animations are ticked, following the video refresh.
+    frame_scheduler: FrameScheduler,
+
     /// Tracks whether the zoom action has happened recently.
     zoom_action: bool,
 
@@ -166,6 +204,17 @@ pub struct IOCompositor<Window: WindowMethods + ?Sized> {
     /// Whether we're waiting on a recomposite after dispatching a scroll.
     waiting_for_results_of_scroll: bool,
 
+    /// Scrolls with a smooth scroll behavior that are in progress.
+    smooth_scrolls: Vec<SmoothScroll>,
+
+    /// The time at which scroll containers are snapped to their snap positions, once a scroll
+    /// of the user has ended.
+    scroll_snap_time: Option<f64>,
+
+    /// The time at which the overlay scrollbars are hidden, once the user stopped scrolling, or
+    /// `None` if they are hidden.
+    scrollbar_hide_time: Option<f64>,
+
     /// Used by the logic that determines when it is safe to output an
     /// image for the reftest framework.
     ready_to_save_state: ReadyState,
@@ -187,10 +236,10 @@ pub struct IOCompositor<Window: WindowMethods + ?Sized> {
 
     /// Map of the pending paint metrics per layout thread.
     /// The layout thread for each specific pipeline expects the compositor to
-    /// paint frames with specific given IDs (epoch). Once the compositor paints
-    /// these frames, it records the paint time for each of them and sends the
-    /// metric to the corresponding layout thread.
-    pending_paint_metrics: HashMap<PipelineId, Epoch>,
+    /// paint frames with specific given IDs (epoch). Once the compositor presents
+    /// a frame with one of these epochs or a later one, it records the presentation
+    /// time and sends the metric to the corresponding layout thread.
+    pending_paint_metrics: HashMap<PipelineId, Vec<Epoch>>,
 
     /// The coordinates of the native window, its view and the screen.
     embedder_coordinates: EmbedderCoordinates,
@@ -229,6 +278,96 @@ struct ScrollZoomEvent {
     event_count: u32,
 }
 
+/// An animated scroll of a scroll node from one scroll offset to another.
+#[derive(Clone, Copy)]
+struct SmoothScroll {
+    scroll_id: ExternalScrollId,
+    start_offset: LayoutVector2D,
+    end_offset: LayoutVector2D,
+    start_time: f64,
+}
+
+/// The position along an axis that a scroll container at `position` snaps to, given the snap
+/// positions along that axis and the size of the scrollport along it.
+fn snapped_position(position: f32, snap_positions: &[f32], mandatory: bool, size: f32) -> f32 {
+    let nearest =
+        snap_positions
+            .iter()
+            .fold(None, |nearest: Option<f32>, &candidate| match nearest {
+                Some(nearest) if (nearest - position).abs() <= (candidate - position).abs() => {
+                    Some(nearest)
+                },
+                _ => Some(candidate),
+            });
+    match nearest {
+        Some(nearest)
+            if mandatory || (nearest - position).abs() <= size * SCROLL_SNAP_PROXIMITY =>
+        {
+            nearest
+        },
+        _ => position,
+    }
+}
+
+/// The tracks and the thumbs of the overlay scrollbars of `container`, whose scrollport is at
+/// `scrollport` on the screen, given its scroll offset in WebRender.
+fn scrollbar_rects(
+    container: &ScrollbarContainer,
+    scrollport: LayoutRect,
+    scroll_offset: LayoutVector2D,
+) -> Vec<(LayoutRect, LayoutRect)> {
+    let width = match container.width {
+        ScrollbarWidth::Auto => SCROLLBAR_WIDTH,
+        ScrollbarWidth::Thin => THIN_SCROLLBAR_WIDTH,
+        ScrollbarWidth::None => return vec![],
+    };
+    // The scroll offsets of WebRender are the negated scroll positions.
+    let position = -scroll_offset;
+    let size = scrollport.size;
+    let content_size = container.content_size;
+
+    let mut rects = vec![];
+    if container.scrollable_y && content_size.height > size.height {
+        let x = scrollport.max_x() - width;
+        let (start, length) = thumb_extent(position.y, size.height, content_size.height);
+        rects.push((
+            LayoutRect::new(
+                LayoutPoint::new(x, scrollport.min_y()),
+                LayoutSize::new(width, size.height),
+            ),
+            LayoutRect::new(
+                LayoutPoint::new(x, scrollport.min_y() + start),
+                LayoutSize::new(width, length),
+            ),
+        ));
+    }
+    if container.scrollable_x && content_size.width > size.width {
+        let y = scrollport.max_y() - width;
+        let (start, length) = thumb_extent(position.x, size.width, content_size.width);
+        rects.push((
+            LayoutRect::new(
+                LayoutPoint::new(scrollport.min_x(), y),
+                LayoutSize::new(size.width, width),
+            ),
+            LayoutRect::new(
+                LayoutPoint::new(scrollport.min_x() + start, y),
+                LayoutSize::new(length, width),
+            ),
+        ));
+    }
+    rects
+}
+
+/// The start and the length of the thumb of a scrollbar along its track, given the scroll
+/// position and the sizes of the scrollport and of the scrollable area along that axis.
+fn thumb_extent(position: f32, size: f32, content_size: f32) -> (f32, f32) {
+    let length = (size * size / content_size)
+        .max(MIN_SCROLLBAR_THUMB_LENGTH)
+        .min(size);
+    let progress = (position / (content_size - size)).max(0.).min(1.);
+    ((size - length) * progress, length)
+}
+
 #[derive(Debug, PartialEq)]
 enum CompositionRequest {
     NoCompositingNecessary,
@@ -254,6 +393,16 @@ struct PipelineDetails {
 
     /// Whether this pipeline is visible
     visible: bool,
+
+    /// The snap positions of the scroll containers of this pipeline.
+    scroll_snap_containers: Vec<ScrollSnapContainer>,
+
+    /// The overscroll behavior of the scroll containers of this pipeline, starting with its
+    /// viewport.
+    overscroll_containers: Vec<OverscrollContainer>,
+
+    /// The scrollbars of the scroll containers of this pipeline, starting with its viewport.
+    scrollbar_containers: Vec<ScrollbarContainer>,
 }
 
 impl PipelineDetails {
@@ -263,6 +412,9 @@ impl PipelineDetails {
             animations_running: false,
             animation_callbacks_running: false,
             visible: true,
+            scroll_snap_containers: Vec::new(),
+            overscroll_containers: Vec::new(),
+            scrollbar_containers: Vec::new(),
         }
     }
 }
@@ -304,6 +456,9 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
             touch_handler: TouchHandler::new(),
             pending_scroll_zoom_events: Vec::new(),
             waiting_for_results_of_scroll: false,
+            smooth_scrolls: Vec::new(),
+            scroll_snap_time: None,
+            scrollbar_hide_time: None,
             composite_target,
             shutdown_state: ShutdownState::NotShuttingDown,
             page_zoom: Scale::new(1.0),
@@ -316,6 +471,7 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
             constellation_chan: state.constellation_chan,
             time_profiler_chan: state.time_profiler_chan,
             last_composite_time: 0,
+            frame_scheduler: FrameScheduler::new(precise_time_ns()),
             ready_to_save_state: ReadyState::Unknown,
             webrender: state.webrender,
             webrender_document: state.webrender_document,
@@ -535,7 +691,10 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
             },
 
             (Msg::PendingPaintMetric(pipeline_id, epoch), _) => {
-                self.pending_paint_metrics.insert(pipeline_id, epoch);
+                self.pending_paint_metrics
+                    .entry(pipeline_id)
+                    .or_insert_with(Vec::new)
+                    .push(epoch);
             },
 
             (Msg::GetClientWindow(req), ShutdownState::NotShuttingDown) => {
@@ -559,6 +718,28 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
                 }
             },
 
+            (Msg::SmoothScroll(scroll_state), ShutdownState::NotShuttingDown) => {
+                self.start_smooth_scroll(scroll_state);
+            },
+
+            (
+                Msg::ScrollSnapContainers(pipeline_id, containers),
+                ShutdownState::NotShuttingDown,
+            ) => {
+                self.pipeline_details(pipeline_id).scroll_snap_containers = containers;
+            },
+
+            (
+                Msg::OverscrollContainers(pipeline_id, containers),
+                ShutdownState::NotShuttingDown,
+            ) => {
+                self.pipeline_details(pipeline_id).overscroll_containers = containers;
+            },
+
+            (Msg::ScrollbarContainers(pipeline_id, containers), ShutdownState::NotShuttingDown) => {
+                self.pipeline_details(pipeline_id).scrollbar_containers = containers;
+            },
+
             // When we are shutting_down, we need to avoid performing operations
             // such as Paint that may crash because we have begun tearing down
             // the rest of our resources.
@@ -588,7 +769,7 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
                 self.pipeline_details(pipeline_id)
                     .animation_callbacks_running = true;
                 if visible {
-                    self.tick_animations_for_pipeline(pipeline_id);
+                    self.process_animations();
                 }
             },
             AnimationState::NoAnimationsPresent => {
@@ -640,6 +821,7 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
             .send_transaction(self.webrender_document, txn);
 
         self.create_pipeline_details_for_frame_tree(&frame_tree);
+        self.send_pinch_zoom();
 
         self.frame_tree_id.next();
     }
@@ -690,12 +872,16 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
         let old_coords = self.embedder_coordinates;
         self.embedder_coordinates = self.window.get_coordinates();
 
-        // A size change could also mean a resolution change.
-        if self.embedder_coordinates.hidpi_factor != old_coords.hidpi_factor {
+        // A size change could also mean a resolution change, for instance when the window was
+        // moved to a monitor with another pixel density. The pages are told about the new device
+        // pixel ratio, and WebRender rasterizes them again at it.
+        let hidpi_factor_changed =
+            self.embedder_coordinates.hidpi_factor != old_coords.hidpi_factor;
+        if hidpi_factor_changed {
             self.update_zoom_transform();
         }
 
-        if self.embedder_coordinates.viewport == old_coords.viewport {
+        if self.embedder_coordinates.viewport == old_coords.viewport && !hidpi_factor_changed {
             return;
         }
 
@@ -795,6 +981,21 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
         }
     }
 
+    /// Forwards the movement of the mouse while the pointer is locked. The constellation
+    /// sends it to the pipeline which locked the pointer.
+    pub fn on_pointer_lock_move_event(&mut self, movement: DeviceVector2D) {
+        let root_pipeline_id = match self.get_root_pipeline_id() {
+            Some(root_pipeline_id) => root_pipeline_id,
+            None => return,
+        };
+        let dppx = self.page_zoom * self.hidpi_factor();
+        let event = PointerLockMoveEvent((movement / dppx).to_untyped(), 0);
+        let msg = ConstellationMsg::ForwardEvent(root_pipeline_id, event);
+        if let Err(e) = self.constellation_chan.send(msg) {
+            warn!("Sending event to constellation failed ({:?}).", e);
+        }
+    }
+
     fn send_touch_event(
         &self,
         event_type: TouchEventType,
@@ -913,6 +1114,9 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
             TouchEventType::Move => self.on_scroll_window_event(delta, cursor),
             TouchEventType::Up | TouchEventType::Cancel => {
                 self.on_scroll_window_event(delta, cursor);
+                if self.scroll_snap_time.is_some() {
+                    self.scroll_snap_time = Some(precise_time_s());
+                }
             },
             TouchEventType::Down => {
                 self.on_scroll_window_event(delta, cursor);
@@ -927,6 +1131,27 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
             cursor: cursor,
             event_count: 1,
         });
+
+        // Not every platform tells when a scroll ends, so scroll containers are snapped once
+        // there have been no scroll events for a while.
+        let has_snap_containers = self
+            .pipeline_details
+            .values()
+            .any(|details| !details.scroll_snap_containers.is_empty());
+        if has_snap_containers {
+            self.scroll_snap_time = Some(precise_time_s() + SCROLL_END_DELAY);
+            self.process_animations();
+        }
+
+        // The overlay scrollbars are shown while the user scrolls.
+        let has_scrollbar_containers = self
+            .pipeline_details
+            .values()
+            .any(|details| !details.scrollbar_containers.is_empty());
+        if has_scrollbar_containers {
+            self.scrollbar_hide_time = Some(precise_time_s() + SCROLLBAR_HIDE_DELAY);
+            self.process_animations();
+        }
     }
 
     fn process_pending_scroll_events(&mut self) {
@@ -990,11 +1215,18 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
             let cursor = (combined_event.cursor.to_f32() / self.scale).to_untyped();
             let cursor = webrender_api::units::WorldPoint::from_untyped(cursor);
             let mut txn = webrender_api::Transaction::new();
+            let scroll_location = match scroll_location {
+                ScrollLocation::Delta(delta) => {
+                    ScrollLocation::Delta(self.contain_overscroll(delta, cursor, &mut txn))
+                },
+                sl @ ScrollLocation::Start | sl @ ScrollLocation::End => sl,
+            };
             txn.scroll(scroll_location, cursor);
             if combined_event.magnification != 1.0 {
                 let old_zoom = self.pinch_zoom_level();
                 self.set_pinch_zoom_level(old_zoom * combined_event.magnification);
                 txn.set_pinch_zoom(webrender_api::ZoomFactor::new(self.pinch_zoom_level()));
+                self.send_pinch_zoom();
             }
             txn.generate_frame();
             self.webrender_api
@@ -1007,6 +1239,205 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
         }
     }
 
+    /// Scrolls the innermost scroll container under `cursor` by the parts of `delta` along the
+    /// axes that its `overscroll-behavior` keeps from chaining to its ancestors, and returns the
+    /// rest of `delta` for WebRender to scroll as usual.
+    ///
+    /// FIXME: Only the scroll containers of the root pipeline are considered, so the viewport of
+    /// an iframe can't stop scrolls from chaining to the document around it.
+    fn contain_overscroll(
+        &self,
+        delta: LayoutVector2D,
+        cursor: WorldPoint,
+        txn: &mut webrender_api::Transaction,
+    ) -> LayoutVector2D {
+        let containers = match self
+            .get_root_pipeline_id()
+            .and_then(|pipeline_id| self.pipeline_details.get(&pipeline_id))
+        {
+            Some(details) if details.overscroll_containers.len() > 1 => {
+                &details.overscroll_containers
+            },
+            _ => return delta,
+        };
+
+        let scroll_offsets: HashMap<ExternalScrollId, LayoutVector2D> = self
+            .webrender_api
+            .get_scroll_node_state(self.webrender_document)
+            .into_iter()
+            .map(|node_state| (node_state.id, node_state.scroll_offset))
+            .collect();
+        let scroll_offset = |scroll_id: &ExternalScrollId| {
+            scroll_offsets
+                .get(scroll_id)
+                .cloned()
+                .unwrap_or(LayoutVector2D::zero())
+        };
+
+        // Find where the scrollport of every scroll container is on the screen, and which part
+        // of it the scroll containers around it don't clip away. Scroll containers come after
+        // the ones around them.
+        let mut visible_rects: HashMap<ExternalScrollId, (LayoutVector2D, Option<LayoutRect>)> =
+            HashMap::new();
+        for container in containers {
+            let (translation, clip) = match container.parent_scroll_id {
+                Some(ref parent_scroll_id) => match visible_rects.get(parent_scroll_id) {
+                    Some(&(translation, clip)) => {
+                        (translation + scroll_offset(parent_scroll_id), clip)
+                    },
+                    None => continue,
+                },
+                None => (LayoutVector2D::zero(), Some(container.scrollport)),
+            };
+            let visible_rect = clip.and_then(|clip| {
+                container
+                    .scrollport
+                    .translate(translation)
+                    .intersection(&clip)
+            });
+            visible_rects.insert(container.scroll_id, (translation, visible_rect));
+        }
+
+        let point = LayoutPoint::new(cursor.x, cursor.y);
+        let container = match containers.iter().skip(1).rev().find(|container| {
+            visible_rects
+                .get(&container.scroll_id)
+                .and_then(|&(_, visible_rect)| visible_rect)
+                .map_or(false, |visible_rect| visible_rect.contains(point))
+        }) {
+            Some(container) => container,
+            None => return delta,
+        };
+
+        let contain_x = container.behavior_x != OverscrollBehavior::Auto;
+        let contain_y = container.behavior_y != OverscrollBehavior::Auto;
+        if !contain_x && !contain_y {
+            return delta;
+        }
+        let contained_delta = LayoutVector2D::new(
+            if contain_x { delta.x } else { 0. },
+            if contain_y { delta.y } else { 0. },
+        );
+        // The scroll offsets of WebRender are the negated scroll positions, and WebRender
+        // clamps the scroll position to the scroll range of the node, so the part of the scroll
+        // that goes past the edge of the scroll container is dropped.
+        let offset = scroll_offset(&container.scroll_id) + contained_delta;
+        txn.scroll_node_with_id(
+            LayoutPoint::new(-offset.x, -offset.y),
+            container.scroll_id,
+            ScrollClamping::ToContentBounds,
+        );
+        delta - contained_delta
+    }
+
+    /// Starts animating the scroll node of `scroll_state` from its current scroll offset to the
+    /// one of `scroll_state`, replacing any smooth scroll of that node that is in progress.
+    fn start_smooth_scroll(&mut self, scroll_state: ScrollState) {
+        let start_offset = self
+            .webrender_api
+            .get_scroll_node_state(self.webrender_document)
+            .into_iter()
+            .find(|node_state| node_state.id == scroll_state.scroll_id)
+            .map_or(LayoutVector2D::zero(), |node_state| {
+                node_state.scroll_offset
+            });
+        self.smooth_scrolls
+            .retain(|smooth_scroll| smooth_scroll.scroll_id != scroll_state.scroll_id);
+        self.smooth_scrolls.push(SmoothScroll {
+            scroll_id: scroll_state.scroll_id,
+            start_offset,
+            end_offset: LayoutVector2D::from_untyped(scroll_state.scroll_offset.to_untyped()),
+            start_time: precise_time_s(),
+        });
+        self.process_animations();
+    }
+
+    /// Snaps the scroll containers with a `scroll-snap-type` to their snap positions, once the
+    /// scroll of the user has ended.
+    fn snap_scroll_containers(&mut self) {
+        match self.scroll_snap_time {
+            Some(time)
+                if time <= precise_time_s() && self.pending_scroll_zoom_events.is_empty() => {},
+            _ => return,
+        }
+        self.scroll_snap_time = None;
+
+        let node_states = self
+            .webrender_api
+            .get_scroll_node_state(self.webrender_document);
+        let mut snaps = vec![];
+        for details in self.pipeline_details.values() {
+            for container in &details.scroll_snap_containers {
+                let node_state = match node_states
+                    .iter()
+                    .find(|node_state| node_state.id == container.scroll_id)
+                {
+                    Some(node_state) => node_state,
+                    None => continue,
+                };
+                // The scroll offsets of WebRender are the negated scroll positions.
+                let x = -node_state.scroll_offset.x;
+                let y = -node_state.scroll_offset.y;
+                let snapped_x = snapped_position(
+                    x,
+                    &container.x_positions,
+                    container.mandatory,
+                    container.scrollport_size.width,
+                );
+                let snapped_y = snapped_position(
+                    y,
+                    &container.y_positions,
+                    container.mandatory,
+                    container.scrollport_size.height,
+                );
+                if snapped_x != x || snapped_y != y {
+                    snaps.push(ScrollState {
+                        scroll_id: container.scroll_id,
+                        scroll_offset: Vector2D::new(-snapped_x, -snapped_y),
+                    });
+                }
+            }
+        }
+        for scroll_state in snaps {
+            self.start_smooth_scroll(scroll_state);
+        }
+        self.process_animations();
+    }
+
+    /// Moves the smooth scrolls in progress on to their scroll offset for the current time, and
+    /// tells layout about the new scroll offsets.
+    fn process_smooth_scrolls(&mut self) {
+        if self.smooth_scrolls.is_empty() {
+            return;
+        }
+
+        let now = precise_time_s();
+        let mut txn = webrender_api::Transaction::new();
+        for smooth_scroll in &self.smooth_scrolls {
+            let progress = ((now - smooth_scroll.start_time) / SMOOTH_SCROLL_DURATION).min(1.0);
+            // Ease in and out of the scroll.
+            let progress = (progress * progress * (3.0 - 2.0 * progress)) as f32;
+            let offset = smooth_scroll
+                .start_offset
+                .lerp(smooth_scroll.end_offset, progress);
+            txn.scroll_node_with_id(
+                LayoutPoint::new(-offset.x, -offset.y),
+                smooth_scroll.scroll_id,
+                ScrollClamping::ToContentBounds,
+            );
+        }
+        txn.generate_frame();
+        self.webrender_api
+            .send_transaction(self.webrender_document, txn);
+        self.send_viewport_rects();
+
+        self.smooth_scrolls
+            .retain(|smooth_scroll| now - smooth_scroll.start_time < SMOOTH_SCROLL_DURATION);
+        if self.smooth_scrolls.is_empty() {
+            self.process_animations();
+        }
+    }
+
     /// If there are any animations running, dispatches appropriate messages to the constellation.
     fn process_animations(&mut self) {
         let mut pipeline_ids = vec![];
@@ -1018,6 +1449,9 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
             }
         }
         let animation_state = if pipeline_ids.is_empty() &&
+            self.smooth_scrolls.is_empty() &&
+            self.scroll_snap_time.is_none() &&
+            self.scrollbar_hide_time.is_none() &&
             !self.webvr_heartbeats_racing() &&
             !self.webxr_main_thread.running()
         {
@@ -1026,8 +1460,15 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
             windowing::AnimationState::Animating
         };
         self.window.set_animation_state(animation_state);
+        if pipeline_ids.is_empty() {
+            return;
+        }
+        let timing = match self.frame_scheduler.begin_frame(precise_time_ns()) {
+            Some(timing) => timing,
+            None => return,
+        };
         for pipeline_id in &pipeline_ids {
-            self.tick_animations_for_pipeline(*pipeline_id)
+            self.tick_animations_for_pipeline(*pipeline_id, timing)
         }
     }
 
@@ -1035,12 +1476,13 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
         self.webvr_heartbeats.iter().any(|hb| hb.heart_racing())
     }
 
-    fn tick_animations_for_pipeline(&mut self, pipeline_id: PipelineId) {
+    fn tick_animations_for_pipeline(&mut self, pipeline_id: PipelineId, timing: FrameTiming) {
         let animation_callbacks_running = self
             .pipeline_details(pipeline_id)
             .animation_callbacks_running;
         if animation_callbacks_running {
-            let msg = ConstellationMsg::TickAnimation(pipeline_id, AnimationTickType::Script);
+            let msg =
+                ConstellationMsg::TickAnimation(pipeline_id, AnimationTickType::Script(timing));
             if let Err(e) = self.constellation_chan.send(msg) {
                 warn!("Sending tick to constellation failed ({:?}).", e);
             }
@@ -1067,6 +1509,7 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
             self.min_viewport_zoom = constraints.min_zoom;
             self.max_viewport_zoom = constraints.max_zoom;
             self.update_zoom_transform();
+            self.send_pinch_zoom();
         }
     }
 
@@ -1087,18 +1530,25 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
     }
 
     pub fn on_zoom_reset_window_event(&mut self) {
-        self.page_zoom = Scale::new(1.0);
-        self.update_zoom_transform();
-        self.send_window_size(WindowSizeType::Resize);
-        self.update_page_zoom_for_webrender();
+        self.set_page_zoom(1.0);
     }
 
     pub fn on_zoom_window_event(&mut self, magnification: f32) {
-        self.page_zoom = Scale::new(
-            (self.page_zoom.get() * magnification)
-                .max(MIN_ZOOM)
-                .min(MAX_ZOOM),
-        );
+        self.set_page_zoom(self.page_zoom.get() * magnification);
+    }
+
+    pub fn on_set_zoom_window_event(&mut self, zoom: f32) {
+        self.set_page_zoom(zoom);
+    }
+
+    /// Changes the number of device independent pixels in a CSS pixel. Layout is told about the
+    /// new size of the viewport in CSS pixels, so the pages are laid out again at the new scale.
+    fn set_page_zoom(&mut self, zoom: f32) {
+        let zoom = zoom.max(MIN_ZOOM).min(MAX_ZOOM);
+        if zoom == self.page_zoom.get() {
+            return;
+        }
+        self.page_zoom = Scale::new(zoom);
         self.update_zoom_transform();
         self.send_window_size(WindowSizeType::Resize);
         self.update_page_zoom_for_webrender();
@@ -1300,44 +1750,10 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
                 // TODO(gw): Take notice of any errors the renderer returns!
                 self.clear_background();
                 self.webrender.render(size).ok();
+                self.paint_scrollbars();
             },
         );
 
-        // If there are pending paint metrics, we check if any of the painted epochs is
-        // one of the ones that the paint metrics recorder is expecting . In that case,
-        // we get the current time, inform the layout thread about it and remove the
-        // pending metric from the list.
-        if !self.pending_paint_metrics.is_empty() {
-            let paint_time = precise_time_ns();
-            let mut to_remove = Vec::new();
-            // For each pending paint metrics pipeline id
-            for (id, pending_epoch) in &self.pending_paint_metrics {
-                // we get the last painted frame id from webrender
-                if let Some(webrender_api::Epoch(epoch)) = self
-                    .webrender
-                    .current_epoch(self.webrender_document, id.to_webrender())
-                {
-                    // and check if it is the one the layout thread is expecting,
-                    let epoch = Epoch(epoch);
-                    if *pending_epoch != epoch {
-                        continue;
-                    }
-                    // in which case, we remove it from the list of pending metrics,
-                    to_remove.push(id.clone());
-                    if let Some(pipeline) = self.pipeline(*id) {
-                        // and inform the layout thread with the measured paint time.
-                        let msg = LayoutControlMsg::PaintMetric(epoch, paint_time);
-                        if let Err(e) = pipeline.layout_chan.send(msg) {
-                            warn!("Sending PaintMetric message to layout failed ({:?}).", e);
-                        }
-                    }
-                }
-            }
-            for id in to_remove.iter() {
-                self.pending_paint_metrics.remove(id);
-            }
-        }
-
         let (x, y, width, height) = match rect {
             Some(rect) => {
                 let rect = self.device_pixels_per_page_px().transform_rect(&rect);
@@ -1414,6 +1830,8 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
         self.window.present();
 
         self.last_composite_time = precise_time_ns();
+        self.frame_scheduler.presented(self.last_composite_time);
+        self.send_pending_paint_metrics(self.last_composite_time);
 
         self.composition_request = CompositionRequest::NoCompositingNecessary;
 
@@ -1423,6 +1841,46 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
         Ok(rv)
     }
 
+    /// If there are pending paint metrics, we check if the presented frame of any of
+    /// their pipelines includes one of the epochs that the paint metrics recorder is
+    /// expecting. A frame can skip epochs, so any painted epoch at least as recent as
+    /// a pending one counts. In that case, we inform the layout thread about the
+    /// presentation time and remove the pending metrics up to that epoch.
+    fn send_pending_paint_metrics(&mut self, paint_time: u64) {
+        if self.pending_paint_metrics.is_empty() {
+            return;
+        }
+
+        let mut presented = Vec::new();
+        for (id, pending_epochs) in self.pending_paint_metrics.iter_mut() {
+            // We get the last painted frame id from webrender
+            let epoch = match self
+                .webrender
+                .current_epoch(self.webrender_document, id.to_webrender())
+            {
+                Some(webrender_api::Epoch(epoch)) => Epoch(epoch),
+                None => continue,
+            };
+            // and check if it includes any of the ones the layout thread is expecting.
+            let pending_count = pending_epochs.len();
+            pending_epochs.retain(|pending_epoch| *pending_epoch > epoch);
+            if pending_epochs.len() != pending_count {
+                presented.push((*id, epoch));
+            }
+        }
+        self.pending_paint_metrics
+            .retain(|_, pending_epochs| !pending_epochs.is_empty());
+
+        for (id, epoch) in presented {
+            if let Some(pipeline) = self.pipeline(id) {
+                let msg = LayoutControlMsg::PaintMetric(epoch, paint_time);
+                if let Err(e) = pipeline.layout_chan.send(msg) {
+                    warn!("Sending PaintMetric message to layout failed ({:?}).", e);
+                }
+            }
+        }
+    }
+
     fn composite_if_necessary(&mut self, reason: CompositingReason) {
         if self.composition_request == CompositionRequest::NoCompositingNecessary {
             if self.is_running_problem_test {
@@ -1458,6 +1916,107 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
         gl.disable(gleam::gl::SCISSOR_TEST);
     }
 
+    /// Paints the overlay scrollbars of the scroll containers of the root pipeline over the
+    /// rendered frame, while the user scrolls.
+    ///
+    /// FIXME: The scrollbars are opaque rectangles painted over everything else, so the elements
+    /// that cover a scroll container don't hide its scrollbars. The scrollbars of iframes are
+    /// not painted, and pinch zooms are not taken into account.
+    fn paint_scrollbars(&self) {
+        if self.scrollbar_hide_time.is_none() {
+            return;
+        }
+        let containers = match self
+            .get_root_pipeline_id()
+            .and_then(|pipeline_id| self.pipeline_details.get(&pipeline_id))
+        {
+            Some(details) if !details.scrollbar_containers.is_empty() => {
+                &details.scrollbar_containers
+            },
+            _ => return,
+        };
+
+        let scroll_offsets: HashMap<ExternalScrollId, LayoutVector2D> = self
+            .webrender_api
+            .get_scroll_node_state(self.webrender_document)
+            .into_iter()
+            .map(|node_state| (node_state.id, node_state.scroll_offset))
+            .collect();
+        let scroll_offset = |scroll_id: &ExternalScrollId| {
+            scroll_offsets
+                .get(scroll_id)
+                .cloned()
+                .unwrap_or(LayoutVector2D::zero())
+        };
+
+        let scale = self.device_pixels_per_page_px().get();
+        let viewport = self.embedder_coordinates.get_flipped_viewport();
+        let gl = self.window.gl();
+        gl.enable(gleam::gl::SCISSOR_TEST);
+
+        // As in `contain_overscroll`, the scrollbars of a scroll container are clipped to the
+        // part of its scrollport that the scroll containers around it don't clip away.
+        let mut visible_rects: HashMap<ExternalScrollId, (LayoutVector2D, Option<LayoutRect>)> =
+            HashMap::new();
+        for container in containers {
+            let (translation, clip) = match container.parent_scroll_id {
+                Some(ref parent_scroll_id) => match visible_rects.get(parent_scroll_id) {
+                    Some(&(translation, clip)) => {
+                        (translation + scroll_offset(parent_scroll_id), clip)
+                    },
+                    None => continue,
+                },
+                None => (LayoutVector2D::zero(), Some(container.scrollport)),
+            };
+            let scrollport = container.scrollport.translate(translation);
+            let visible_rect = clip.and_then(|clip| scrollport.intersection(&clip));
+            visible_rects.insert(container.scroll_id, (translation, visible_rect));
+
+            let visible_rect = match visible_rect {
+                Some(visible_rect) => visible_rect,
+                None => continue,
+            };
+            let (thumb_color, track_color) = container
+                .colors
+                .unwrap_or((DEFAULT_SCROLLBAR_THUMB_COLOR, ColorF::TRANSPARENT));
+            let rects = scrollbar_rects(container, scrollport, scroll_offset(&container.scroll_id));
+            for (track, thumb) in rects {
+                for &(rect, color) in &[(track, track_color), (thumb, thumb_color)] {
+                    let rect = match rect.intersection(&visible_rect) {
+                        Some(rect) if color.a > 0. => rect,
+                        _ => continue,
+                    };
+                    // The framebuffer has its origin at the bottom left corner.
+                    let x = (rect.min_x() * scale).round() as i32;
+                    let y = (rect.max_y() * scale).round() as i32;
+                    let width = (rect.max_x() * scale).round() as i32 - x;
+                    let height = y - (rect.min_y() * scale).round() as i32;
+                    gl.scissor(
+                        viewport.origin.x + x,
+                        viewport.origin.y + viewport.size.height - y,
+                        width,
+                        height,
+                    );
+                    gl.clear_color(color.r, color.g, color.b, 1.0);
+                    gl.clear(gleam::gl::COLOR_BUFFER_BIT);
+                }
+            }
+        }
+
+        gl.disable(gleam::gl::SCISSOR_TEST);
+    }
+
+    /// Hides the overlay scrollbars once the user stopped scrolling for a while.
+    fn hide_scrollbars(&mut self) {
+        match self.scrollbar_hide_time {
+            Some(time) if time <= precise_time_s() => {},
+            _ => return,
+        }
+        self.scrollbar_hide_time = None;
+        self.composite_if_necessary(CompositingReason::Animation);
+        self.process_animations();
+    }
+
     fn get_root_pipeline_id(&self) -> Option<PipelineId> {
         self.root_pipeline.as_ref().map(|pipeline| pipeline.id)
     }
@@ -1510,6 +2069,13 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
         if !self.pending_scroll_zoom_events.is_empty() && !self.waiting_for_results_of_scroll {
             self.process_pending_scroll_events()
         }
+        self.snap_scroll_containers();
+        self.process_smooth_scrolls();
+        self.hide_scrollbars();
+
+        // The animations are ticked at every video refresh while they run, including the ones
+        // whose last frame didn't change anything that needed compositing.
+        self.process_animations();
         self.shutdown_state != ShutdownState::FinishedShuttingDown
     }
 
@@ -1539,6 +2105,18 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
         self.viewport_zoom.get()
     }
 
+    /// Tells the document of the root pipeline the scale of its visual viewport.
+    fn send_pinch_zoom(&self) {
+        let pipeline_id = match self.get_root_pipeline_id() {
+            Some(pipeline_id) => pipeline_id,
+            None => return,
+        };
+        let msg = ConstellationMsg::SetPinchZoom(pipeline_id, self.pinch_zoom_level());
+        if let Err(e) = self.constellation_chan.send(msg) {
+            warn!("Sending pinch zoom to constellation failed ({:?}).", e);
+        }
+    }
+
     fn set_pinch_zoom_level(&mut self, mut zoom: f32) {
         if let Some(min) = self.min_viewport_zoom {
             zoom = f32::max(min.get(), zoom);
@@ -16,6 +16,7 @@ use net_traits::image::base::Image;
 use profile_traits::mem;
 use profile_traits::time;
 use script_traits::{AnimationState, EventResult, MouseButton, MouseEventType};
+use script_traits::{OverscrollContainer, ScrollSnapContainer, ScrollState, ScrollbarContainer};
 use std::fmt::{Debug, Error, Formatter};
 use std::sync::atomic::AtomicBool;
 use std::sync::Arc;
@@ -121,6 +122,14 @@ pub enum Msg {
     GetScreenSize(IpcSender<DeviceIntSize>),
     /// Get screen available size.
     GetScreenAvailSize(IpcSender<DeviceIntSize>),
+    /// Animate a scroll node to the given scroll offset.
+    SmoothScroll(ScrollState),
+    /// The snap positions of the scroll containers of a pipeline changed.
+    ScrollSnapContainers(PipelineId, Vec<ScrollSnapContainer>),
+    /// The overscroll behavior of the scroll containers of a pipeline changed.
+    OverscrollContainers(PipelineId, Vec<OverscrollContainer>),
+    /// The scrollbars of the scroll containers of a pipeline changed.
+    ScrollbarContainers(PipelineId, Vec<ScrollbarContainer>),
 }
 
 impl Debug for Msg {
@@ -147,6 +156,10 @@ impl Debug for Msg {
             Msg::GetClientWindow(..) => write!(f, "GetClientWindow"),
             Msg::GetScreenSize(..) => write!(f, "GetScreenSize"),
             Msg::GetScreenAvailSize(..) => write!(f, "GetScreenAvailSize"),
+            Msg::SmoothScroll(..) => write!(f, "SmoothScroll"),
+            Msg::ScrollSnapContainers(..) => write!(f, "ScrollSnapContainers"),
+            Msg::OverscrollContainers(..) => write!(f, "OverscrollContainers"),
+            Msg::ScrollbarContainers(..) => write!(f, "ScrollbarContainers"),
         }
     }
 }
@@ -0,0 +1,71 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Decides when the animations of the pipelines are ticked, so that their animation frames
+//! follow the video refresh of the display.
+
+use script_traits::FrameTiming;
+
+/// The time between two video refreshes that is assumed until the presents of the frames
+/// tell it, in nanoseconds.
+const DEFAULT_FRAME_INTERVAL: u64 = 1_000_000_000 / 60;
+
+/// The shortest and longest times between two presents that are taken to be one video
+/// refresh apart, in nanoseconds. Presents that don't wait for the video refresh, like the
+/// ones of headless windows, and frames that took longer than a video refresh, are outside.
+const MIN_FRAME_INTERVAL: u64 = 1_000_000_000 / 240;
+const MAX_FRAME_INTERVAL: u64 = 1_000_000_000 / 24;
+
+pub struct FrameScheduler {
+    /// When the last frame was presented, in `time::precise_time_ns()`. Presenting waits for
+    /// the video refresh, so this tells when it happens.
+    last_vsync: u64,
+    /// The estimated time between two video refreshes, in nanoseconds.
+    frame_interval: u64,
+    /// The video refresh that the animations were last ticked for.
+    last_frame: Option<u64>,
+}
+
+impl FrameScheduler {
+    pub fn new(now: u64) -> FrameScheduler {
+        FrameScheduler {
+            last_vsync: now,
+            frame_interval: DEFAULT_FRAME_INTERVAL,
+            last_frame: None,
+        }
+    }
+
+    /// Records that a frame was presented at the given time.
+    pub fn presented(&mut self, now: u64) {
+        let interval = now.saturating_sub(self.last_vsync);
+        if interval >= MIN_FRAME_INTERVAL && interval <= MAX_FRAME_INTERVAL {
+            self.frame_interval = (self.frame_interval * 7 + interval) / 8;
+        }
+        self.last_vsync = now;
+    }
+
+    /// The latest video refresh at the given time, which is extrapolated from the last present
+    /// when nothing was presented since.
+    fn current_vsync(&self, now: u64) -> u64 {
+        let elapsed = now.saturating_sub(self.last_vsync);
+        self.last_vsync + elapsed / self.frame_interval * self.frame_interval
+    }
+
+    /// Starts the frame of the latest video refresh, and returns its timing, unless the
+    /// animations were already ticked for it. The pipelines get a single frame per video
+    /// refresh, however often they ask for one.
+    pub fn begin_frame(&mut self, now: u64) -> Option<FrameTiming> {
+        let vsync = self.current_vsync(now);
+        if let Some(last_frame) = self.last_frame {
+            if vsync < last_frame + self.frame_interval / 2 {
+                return None;
+            }
+        }
+        self.last_frame = Some(vsync);
+        Some(FrameTiming {
+            vsync_time: vsync,
+            frame_interval: self.frame_interval,
+        })
+    }
+}
@@ -11,10 +11,13 @@ pub use crate::compositor::CompositingReason;
 pub use crate::compositor::IOCompositor;
 pub use crate::compositor::ShutdownState;
 pub use crate::compositor_thread::CompositorProxy;
-use embedder_traits::Cursor;
+use embedder_traits::{CertificateErrorId, Credentials, Cursor, DownloadAction, DownloadId};
+use embedder_traits::{FindOptions, GeolocationPosition};
+use embedder_traits::SensorReading;
 use gfx_traits::Epoch;
 use ipc_channel::ipc::IpcSender;
-use keyboard_types::KeyboardEvent;
+use keyboard_types::{CompositionEvent, KeyboardEvent};
+use msg::constellation_msg::NetworkConditions;
 use msg::constellation_msg::PipelineId;
 use msg::constellation_msg::TopLevelBrowsingContextId;
 use msg::constellation_msg::{BrowsingContextId, TraversalDirection};
@@ -22,16 +25,18 @@ use script_traits::{
     AnimationTickType, LogEntry, WebDriverCommandMsg, WindowSizeData, WindowSizeType,
 };
 use script_traits::{
-    CompositorEvent, ConstellationControlMsg, LayoutControlMsg, MediaSessionActionType,
+    CompositorEvent, ConstellationControlMsg, LayoutControlMsg, MediaSessionActionDetails,
 };
 use servo_url::ServoUrl;
 use std::collections::HashMap;
 use std::fmt;
 use std::time::Duration;
+use style_traits::MediaPreferences;
 use webvr_traits::WebVREvent;
 
 mod compositor;
 pub mod compositor_thread;
+mod frame_scheduler;
 #[cfg(feature = "gl")]
 mod gl;
 mod touch;
@@ -68,8 +73,12 @@ pub enum ConstellationMsg {
     IsReadyToSaveImage(HashMap<PipelineId, Epoch>),
     /// Inform the constellation of a key event.
     Keyboard(KeyboardEvent),
+    /// Inform the constellation of an IME composition event.
+    IMECompositionEvent(CompositionEvent),
     /// Whether to allow script to navigate.
     AllowNavigationResponse(PipelineId, bool),
+    /// Whether to trust the certificate that the document of a pipeline failed to verify.
+    CertificateErrorResponse(CertificateErrorId, bool),
     /// Request to load a page.
     LoadUrl(TopLevelBrowsingContextId, ServoUrl),
     /// Request to traverse the joint session history of the provided browsing context.
@@ -82,6 +91,9 @@ pub enum ConstellationMsg {
     ),
     /// Requests that the constellation instruct layout to begin a new tick of the animation.
     TickAnimation(PipelineId, AnimationTickType),
+    /// Tell the document of the root pipeline the scale of the pinch zoom, which its visual
+    /// viewport exposes.
+    SetPinchZoom(PipelineId, f32),
     /// Dispatch a webdriver command
     WebDriverCommand(WebDriverCommandMsg),
     /// Reload a top-level browsing context.
@@ -108,10 +120,43 @@ pub enum ConstellationMsg {
     DisableProfiler,
     /// Request to exit from fullscreen mode
     ExitFullScreen(TopLevelBrowsingContextId),
+    /// Request to release the pointer locked by a page
+    ExitPointerLock(TopLevelBrowsingContextId),
     /// Media session action.
-    MediaSessionAction(MediaSessionActionType),
+    MediaSessionAction(MediaSessionActionDetails),
     /// Toggle browser visibility.
     ChangeBrowserVisibility(TopLevelBrowsingContextId, bool),
+    /// Search the rendered text of a top level browsing context.
+    Find(TopLevelBrowsingContextId, String, FindOptions),
+    /// Clear the find-in-page state of a top level browsing context.
+    StopFinding(TopLevelBrowsingContextId),
+    /// Extract the main article of a top level browsing context for a reader view.
+    ExtractArticle(TopLevelBrowsingContextId),
+    /// Build the accessibility tree of a top level browsing context.
+    RequestAccessibilityTree(TopLevelBrowsingContextId),
+    /// Find the form controls of a top level browsing context that can be autofilled.
+    RequestFormFields(TopLevelBrowsingContextId),
+    /// Fill in form controls of a top level browsing context, by identifier.
+    FillFormFields(TopLevelBrowsingContextId, Vec<(String, String)>),
+    /// Fill in the username and the password of the login form of a top level browsing context,
+    /// if its document is of the origin of the credentials.
+    FillCredentials(TopLevelBrowsingContextId, Credentials),
+    /// Free as much memory as possible, because the system is running low on it.
+    MemoryPressure,
+    /// The settings of the user that pages can query with media features changed.
+    MediaPreferencesChanged(MediaPreferences),
+    /// The state of the battery or of the network connection of the device changed.
+    DeviceStatusChanged,
+    /// The embedder acquired a new position of the device, or failed to.
+    PositionChanged(Result<GeolocationPosition, String>),
+    /// The embedder read the orientation or the motion of the device.
+    SensorReading(SensorReading),
+    /// Scale the font sizes of every page by the given factor.
+    SetTextZoom(f32),
+    /// Make the network of a browser behave like under the given conditions.
+    SetNetworkConditions(TopLevelBrowsingContextId, Option<NetworkConditions>),
+    /// Pause, resume or cancel a download.
+    DownloadAction(DownloadId, DownloadAction),
 }
 
 impl fmt::Debug for ConstellationMsg {
@@ -124,11 +169,14 @@ impl fmt::Debug for ConstellationMsg {
             GetFocusTopLevelBrowsingContext(..) => "GetFocusTopLevelBrowsingContext",
             IsReadyToSaveImage(..) => "IsReadyToSaveImage",
             Keyboard(..) => "Keyboard",
+            IMECompositionEvent(..) => "IMECompositionEvent",
             AllowNavigationResponse(..) => "AllowNavigationResponse",
+            CertificateErrorResponse(..) => "CertificateErrorResponse",
             LoadUrl(..) => "LoadUrl",
             TraverseHistory(..) => "TraverseHistory",
             WindowSize(..) => "WindowSize",
             TickAnimation(..) => "TickAnimation",
+            SetPinchZoom(..) => "SetPinchZoom",
             WebDriverCommand(..) => "WebDriverCommand",
             Reload(..) => "Reload",
             LogEntry(..) => "LogEntry",
@@ -142,8 +190,24 @@ impl fmt::Debug for ConstellationMsg {
             EnableProfiler(..) => "EnableProfiler",
             DisableProfiler => "DisableProfiler",
             ExitFullScreen(..) => "ExitFullScreen",
+            ExitPointerLock(..) => "ExitPointerLock",
             MediaSessionAction(..) => "MediaSessionAction",
             ChangeBrowserVisibility(..) => "ChangeBrowserVisibility",
+            Find(..) => "Find",
+            StopFinding(..) => "StopFinding",
+            ExtractArticle(..) => "ExtractArticle",
+            RequestAccessibilityTree(..) => "RequestAccessibilityTree",
+            RequestFormFields(..) => "RequestFormFields",
+            FillFormFields(..) => "FillFormFields",
+            FillCredentials(..) => "FillCredentials",
+            MemoryPressure => "MemoryPressure",
+            MediaPreferencesChanged(..) => "MediaPreferencesChanged",
+            DeviceStatusChanged => "DeviceStatusChanged",
+            PositionChanged(..) => "PositionChanged",
+            SensorReading(..) => "SensorReading",
+            SetTextZoom(..) => "SetTextZoom",
+            SetNetworkConditions(..) => "SetNetworkConditions",
+            DownloadAction(..) => "DownloadAction",
         };
         write!(formatter, "ConstellationMsg::{}", variant)
     }
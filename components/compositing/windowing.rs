@@ -5,13 +5,20 @@
 //! Abstract windowing methods. The concrete implementations of these can be found in `platform/`.
 
 use canvas::{SurfaceProviders, WebGlExecutor};
-use embedder_traits::EventLoopWaker;
+use embedder_traits::CertificateErrorId;
+use embedder_traits::Credentials;
+use embedder_traits::SpeechSynthesisProvider;
+use embedder_traits::SpellCheckProvider;
+use embedder_traits::{DeviceStatusProvider, DownloadAction, DownloadId, EventLoopWaker};
+use embedder_traits::{FindOptions, GeolocationPosition};
+use embedder_traits::{PositionProvider, ProtocolRegistry, SensorProvider, SensorReading};
 use euclid::Scale;
 #[cfg(feature = "gl")]
 use gleam::gl;
-use keyboard_types::KeyboardEvent;
-use msg::constellation_msg::{PipelineId, TopLevelBrowsingContextId, TraversalDirection};
-use script_traits::{MediaSessionActionType, MouseButton, TouchEventType, TouchId, WheelDelta};
+use keyboard_types::{CompositionEvent, KeyboardEvent};
+use msg::constellation_msg::TraversalDirection;
+use msg::constellation_msg::{NetworkConditions, PipelineId, TopLevelBrowsingContextId};
+use script_traits::{MediaSessionActionDetails, MouseButton, TouchEventType, TouchId, WheelDelta};
 use servo_geometry::DeviceIndependentPixel;
 use servo_media::player::context::{GlApi, GlContext, NativeDisplay};
 use servo_url::ServoUrl;
@@ -19,11 +26,11 @@ use std::fmt::{Debug, Error, Formatter};
 #[cfg(feature = "gl")]
 use std::rc::Rc;
 use std::time::Duration;
-use style_traits::DevicePixel;
+use style_traits::{DevicePixel, MediaPreferences};
 
 use rust_webvr::VRServiceManager;
-use webrender_api::units::DevicePoint;
 use webrender_api::units::{DeviceIntPoint, DeviceIntRect, DeviceIntSize};
+use webrender_api::units::{DevicePoint, DeviceVector2D};
 use webrender_api::ScrollLocation;
 use webvr_traits::WebVRMainThreadHeartbeat;
 
@@ -59,12 +66,17 @@ pub enum WindowEvent {
     Resize,
     /// Sent when a navigation request from script is allowed/refused.
     AllowNavigationResponse(PipelineId, bool),
+    /// Sent in answer to `EmbedderMsg::CertificateError`: trust the certificate for its host and
+    /// reload the document, or go back from the error page.
+    CertificateErrorResponse(CertificateErrorId, bool),
     /// Sent when a new URL is to be loaded.
     LoadUrl(TopLevelBrowsingContextId, ServoUrl),
     /// Sent when a mouse hit test is to be performed.
     MouseWindowEventClass(MouseWindowEvent),
     /// Sent when a mouse move.
     MouseWindowMoveEventClass(DevicePoint),
+    /// Sent when the mouse moves by the given distance while the pointer is locked.
+    PointerLockMove(DeviceVector2D),
     /// Touch event: type, identifier, point
     Touch(TouchEventType, TouchId, DevicePoint),
     /// Sent when user moves the mouse wheel.
@@ -78,14 +90,25 @@ pub enum WindowEvent {
     PinchZoom(f32),
     /// Sent when the user resets zoom to default.
     ResetZoom,
+    /// Sets the page zoom to the given level, where 1.0 is the default size. Like `Zoom`, this
+    /// changes the size of a CSS pixel, so the pages are laid out again in a smaller or larger
+    /// viewport.
+    SetZoom(f32),
+    /// Sets the factor that the font sizes of the pages are scaled by, without changing the size
+    /// of the other content, where 1.0 is the default size.
+    SetTextZoom(f32),
     /// Sent when the user uses chrome navigation (i.e. backspace or shift-backspace).
     Navigation(TopLevelBrowsingContextId, TraversalDirection),
     /// Sent when the user quits the application
     Quit,
     /// Sent when the user exits from fullscreen mode
     ExitFullScreen(TopLevelBrowsingContextId),
+    /// Sent when the user releases the pointer locked by a page
+    ExitPointerLock(TopLevelBrowsingContextId),
     /// Sent when a key input state changes
     Keyboard(KeyboardEvent),
+    /// Sent when the platform IME starts, updates or commits a composition
+    IMEComposition(CompositionEvent),
     /// Sent when Ctr+R/Apple+R is called to reload the current page.
     Reload(TopLevelBrowsingContextId),
     /// Create a new top level browsing context
@@ -105,9 +128,50 @@ pub enum WindowEvent {
     ToggleSamplingProfiler(Duration, Duration),
     /// Sent when the user triggers a media action through the UA exposed media UI
     /// (play, pause, seek, etc.).
-    MediaSessionAction(MediaSessionActionType),
+    MediaSessionAction(MediaSessionActionDetails),
     /// Set browser visibility. A hidden browser will not tick the animations.
     ChangeBrowserVisibility(TopLevelBrowsingContextId, bool),
+    /// Search the rendered text of a browser for the given string, moving to the next match
+    /// if the same search is repeated.
+    Find(TopLevelBrowsingContextId, String, FindOptions),
+    /// Clear the find-in-page highlights of a browser.
+    StopFinding(TopLevelBrowsingContextId),
+    /// Extract the main article of a browser, which is reported to the embedder with
+    /// `EmbedderMsg::ReaderModeArticle`.
+    ExtractArticle(TopLevelBrowsingContextId),
+    /// Build the accessibility tree of a browser, which is reported to the embedder with
+    /// `EmbedderMsg::AccessibilityTree`.
+    RequestAccessibilityTree(TopLevelBrowsingContextId),
+    /// Find the form controls of a browser that can be filled in for the user, which are
+    /// reported to the embedder with `EmbedderMsg::FormFields`.
+    RequestFormFields(TopLevelBrowsingContextId),
+    /// Fill in form controls of a browser, by the identifiers from `EmbedderMsg::FormFields`,
+    /// as if the user had typed the values, firing `input` and `change` events.
+    FillFormFields(TopLevelBrowsingContextId, Vec<(String, String)>),
+    /// Fill in the username and the password of the login form of a browser, except for the
+    /// fields that the user typed into already. They are only filled in if the document is still
+    /// of the origin of the credentials.
+    FillCredentials(TopLevelBrowsingContextId, Credentials),
+    /// Sent when the system is running low on memory. Servo then purges its caches, discards
+    /// the documents kept for session history and collects the garbage of every script thread.
+    MemoryPressure,
+    /// Sent when the settings of the user that pages can query with media features, like the
+    /// color scheme of the system, changed.
+    MediaPreferencesChanged(MediaPreferences),
+    /// Sent when the state of the battery or of the network connection that the
+    /// `DeviceStatusProvider` of the embedder reports changed.
+    DeviceStatusChanged,
+    /// Sent when the `PositionProvider` of the embedder acquired a new position of the device,
+    /// or failed to, with a message explaining why.
+    PositionChanged(Result<GeolocationPosition, String>),
+    /// Sent when the `SensorProvider` of the embedder read the orientation or the motion of the
+    /// device.
+    SensorReading(SensorReading),
+    /// Sent to make the network of a browser behave like under the given conditions, to test
+    /// how its pages load over slow or missing connections, or like it really does.
+    SetNetworkConditions(TopLevelBrowsingContextId, Option<NetworkConditions>),
+    /// Sent to pause, resume or cancel a download.
+    DownloadAction(DownloadId, DownloadAction),
 }
 
 impl Debug for WindowEvent {
@@ -117,16 +181,21 @@ impl Debug for WindowEvent {
             WindowEvent::Refresh => write!(f, "Refresh"),
             WindowEvent::Resize => write!(f, "Resize"),
             WindowEvent::Keyboard(..) => write!(f, "Keyboard"),
+            WindowEvent::IMEComposition(..) => write!(f, "IMEComposition"),
             WindowEvent::AllowNavigationResponse(..) => write!(f, "AllowNavigationResponse"),
+            WindowEvent::CertificateErrorResponse(..) => write!(f, "CertificateErrorResponse"),
             WindowEvent::LoadUrl(..) => write!(f, "LoadUrl"),
             WindowEvent::MouseWindowEventClass(..) => write!(f, "Mouse"),
             WindowEvent::MouseWindowMoveEventClass(..) => write!(f, "MouseMove"),
+            WindowEvent::PointerLockMove(..) => write!(f, "PointerLockMove"),
             WindowEvent::Touch(..) => write!(f, "Touch"),
             WindowEvent::Wheel(..) => write!(f, "Wheel"),
             WindowEvent::Scroll(..) => write!(f, "Scroll"),
             WindowEvent::Zoom(..) => write!(f, "Zoom"),
             WindowEvent::PinchZoom(..) => write!(f, "PinchZoom"),
             WindowEvent::ResetZoom => write!(f, "ResetZoom"),
+            WindowEvent::SetZoom(..) => write!(f, "SetZoom"),
+            WindowEvent::SetTextZoom(..) => write!(f, "SetTextZoom"),
             WindowEvent::Navigation(..) => write!(f, "Navigation"),
             WindowEvent::Quit => write!(f, "Quit"),
             WindowEvent::Reload(..) => write!(f, "Reload"),
@@ -138,8 +207,23 @@ impl Debug for WindowEvent {
             WindowEvent::CaptureWebRender => write!(f, "CaptureWebRender"),
             WindowEvent::ToggleSamplingProfiler(..) => write!(f, "ToggleSamplingProfiler"),
             WindowEvent::ExitFullScreen(..) => write!(f, "ExitFullScreen"),
+            WindowEvent::ExitPointerLock(..) => write!(f, "ExitPointerLock"),
             WindowEvent::MediaSessionAction(..) => write!(f, "MediaSessionAction"),
             WindowEvent::ChangeBrowserVisibility(..) => write!(f, "ChangeBrowserVisibility"),
+            WindowEvent::Find(..) => write!(f, "Find"),
+            WindowEvent::StopFinding(..) => write!(f, "StopFinding"),
+            WindowEvent::ExtractArticle(..) => write!(f, "ExtractArticle"),
+            WindowEvent::RequestAccessibilityTree(..) => write!(f, "RequestAccessibilityTree"),
+            WindowEvent::RequestFormFields(..) => write!(f, "RequestFormFields"),
+            WindowEvent::FillFormFields(..) => write!(f, "FillFormFields"),
+            WindowEvent::FillCredentials(..) => write!(f, "FillCredentials"),
+            WindowEvent::MemoryPressure => write!(f, "MemoryPressure"),
+            WindowEvent::MediaPreferencesChanged(..) => write!(f, "MediaPreferencesChanged"),
+            WindowEvent::DeviceStatusChanged => write!(f, "DeviceStatusChanged"),
+            WindowEvent::PositionChanged(..) => write!(f, "PositionChanged"),
+            WindowEvent::SensorReading(..) => write!(f, "SensorReading"),
+            WindowEvent::SetNetworkConditions(..) => write!(f, "SetNetworkConditions"),
+            WindowEvent::DownloadAction(..) => write!(f, "DownloadAction"),
         }
     }
 }
@@ -192,6 +276,41 @@ pub trait EmbedderMethods {
         _: SurfaceProviders,
     ) {
     }
+
+    /// Returns the source of the battery and network states reported to pages, which
+    /// otherwise see a fully charged battery and a fast connection.
+    fn device_status_provider(&mut self) -> Option<Box<dyn DeviceStatusProvider>> {
+        None
+    }
+
+    /// Returns the source of the positions reported to pages. Without one, pages are told
+    /// that the position is unavailable.
+    fn position_provider(&mut self) -> Option<Box<dyn PositionProvider>> {
+        None
+    }
+
+    /// Returns the source of the orientation and motion readings reported to pages. Without
+    /// one, pages never receive `deviceorientation` and `devicemotion` events.
+    fn sensor_provider(&mut self) -> Option<Box<dyn SensorProvider>> {
+        None
+    }
+
+    /// Returns the speech synthesizer that speaks the utterances of pages. Without one,
+    /// pages see no voices, and their utterances fail.
+    fn speech_synthesis_provider(&mut self) -> Option<Box<dyn SpeechSynthesisProvider>> {
+        None
+    }
+
+    /// Returns the spell checker of the editable text of pages. Without one, no words are
+    /// flagged as misspelled.
+    fn spell_check_provider(&mut self) -> Option<Box<dyn SpellCheckProvider>> {
+        None
+    }
+
+    /// Returns the handlers of the custom schemes that pages can load, such as `app:`.
+    fn protocol_registry(&mut self) -> ProtocolRegistry {
+        ProtocolRegistry::new()
+    }
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -46,3 +46,14 @@ pub fn default_config_dir() -> Option<PathBuf> {
 pub fn default_config_dir() -> Option<PathBuf> {
     None
 }
+
+/// The directory that the files are downloaded to by default, if the platform has one.
+#[cfg(not(target_os = "android"))]
+pub fn default_downloads_dir() -> Option<PathBuf> {
+    ::dirs::download_dir()
+}
+
+#[cfg(target_os = "android")]
+pub fn default_downloads_dir() -> Option<PathBuf> {
+    None
+}
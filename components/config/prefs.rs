@@ -162,12 +162,20 @@ mod gen {
                 webgpu: {
                     enabled: bool,
                 },
+                battery: {
+                    enabled: bool,
+                },
                 bluetooth: {
                     enabled: bool,
                     testing: {
                         enabled: bool,
                     }
                 },
+                canvas: {
+                    gpu: {
+                        enabled: bool,
+                    }
+                },
                 canvas_text: {
                     #[serde(rename = "dom.canvas-text.enabled")]
                     enabled: bool,
@@ -180,10 +188,16 @@ mod gen {
                     #[serde(rename = "dom.customelements.enabled")]
                     enabled: bool,
                 },
+                device_orientation: {
+                    enabled: bool,
+                },
                 document: {
                     dblclick_timeout: i64,
                     dblclick_dist: i64,
                 },
+                file_system_access: {
+                    enabled: bool,
+                },
                 forcetouch: {
                     enabled: bool,
                 },
@@ -193,6 +207,12 @@ mod gen {
                 gamepad: {
                     enabled: bool,
                 },
+                geolocation: {
+                    enabled: bool,
+                },
+                idle_detection: {
+                    enabled: bool,
+                },
                 microdata: {
                     testing: {
                         enabled: bool,
@@ -207,15 +227,30 @@ mod gen {
                 mutation_observer: {
                     enabled: bool,
                 },
+                netinfo: {
+                    enabled: bool,
+                },
                 offscreen_canvas: {
                     enabled: bool,
                 },
+                password_manager: {
+                    include_passwords: bool,
+                },
                 permissions: {
                     enabled: bool,
                     testing: {
                         allowed_in_nonsecure_contexts: bool,
                     }
                 },
+                picture_in_picture: {
+                    enabled: bool,
+                },
+                popup_blocker: {
+                    enabled: bool,
+                },
+                protocol_handlers: {
+                    enabled: bool,
+                },
                 serviceworker: {
                     enabled: bool,
                     timeout_seconds: i64,
@@ -225,9 +260,15 @@ mod gen {
                         enabled: bool,
                     }
                 },
+                setsinkid: {
+                    enabled: bool,
+                },
                 shadowdom: {
                     enabled: bool,
                 },
+                speech_synthesis: {
+                    enabled: bool,
+                },
                 svg: {
                     enabled: bool,
                 },
@@ -277,6 +318,12 @@ mod gen {
                     #[serde(default)]
                     enabled: bool,
                 },
+                typed_om: {
+                    enabled: bool,
+                },
+                webauthn: {
+                    enabled: bool,
+                },
                 webgl: {
                     dom_to_texture: {
                         enabled: bool,
@@ -285,10 +332,16 @@ mod gen {
                 webgl2: {
                     enabled: bool,
                 },
+                weblocks: {
+                    enabled: bool,
+                },
                 webrtc: {
                     #[serde(default)]
                     enabled: bool,
                 },
+                webshare: {
+                    enabled: bool,
+                },
                 webvr: {
                     enabled: bool,
                     event_polling_interval: i64,
@@ -375,6 +428,11 @@ mod gen {
                         high_frequency_high_limit_mb: i64,
                         high_frequency_low_limit_mb: i64,
                         high_frequency_time_limit_ms: i64,
+                        idle: {
+                            enabled: bool,
+                            min_slice_ms: i64,
+                            slice_ms: i64,
+                        },
                         incremental: {
                             enabled: bool,
                             slice_ms: i64,
@@ -439,6 +497,17 @@ mod gen {
                 columns: {
                     enabled: bool,
                 },
+                forced_colors: bool,
+                prefers_color_scheme: String,
+                prefers_reduced_motion: bool,
+                print: {
+                    page_height: i64,
+                    page_width: i64,
+                },
+                scrollbars: {
+                    enabled: bool,
+                },
+                text_zoom: f64,
                 #[serde(default = "default_layout_threads")]
                 threads: i64,
                 viewport: {
@@ -458,6 +527,14 @@ mod gen {
                 }
             },
             network: {
+                doh: {
+                    enabled: bool,
+                    fallback: bool,
+                    url: String,
+                },
+                downloads: {
+                    directory: String,
+                },
                 enforce_tls: {
                     enabled: bool,
                     localhost: bool,
@@ -467,8 +544,26 @@ mod gen {
                     #[serde(rename = "network.http-cache.disabled")]
                     disabled: bool,
                 },
+                image_cache: {
+                    max_bytes: i64,
+                    max_dimension: i64,
+                },
                 mime: {
                     sniff: bool,
+                },
+                proxy: {
+                    bypass: String,
+                    http: String,
+                    https: String,
+                    mode: String,
+                    pac_url: String,
+                    socks: String,
+                },
+                request_interception: {
+                    enabled: bool,
+                },
+                tls: {
+                    client_identities: String,
                 }
             },
             session_history: {
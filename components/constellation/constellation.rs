@@ -100,6 +100,7 @@ use crate::session_history::{
     JointSessionHistory, NeedsToReload, SessionHistoryChange, SessionHistoryDiff,
 };
 use crate::timer_scheduler::TimerScheduler;
+use crate::web_locks::LockManagers;
 use background_hang_monitor::HangMonitorRegister;
 use backtrace::Backtrace;
 use bluetooth_traits::BluetoothRequest;
@@ -111,16 +112,22 @@ use compositing::compositor_thread::Msg as ToCompositorMsg;
 use compositing::{ConstellationMsg as FromCompositorMsg, SendableFrameTree};
 use crossbeam_channel::{after, never, unbounded, Receiver, Sender};
 use devtools_traits::{ChromeToDevtoolsControlMsg, DevtoolsControlMsg};
-use embedder_traits::{Cursor, EmbedderMsg, EmbedderProxy, EventLoopWaker};
-use embedder_traits::{MediaSessionEvent, MediaSessionPlaybackState};
+use embedder_traits::SpellCheckProvider;
+use embedder_traits::{BatteryStatus, CertificateErrorId, Cursor, DeviceSensor};
+use embedder_traits::{DeviceStatusProvider, EmbedderMsg};
+use embedder_traits::{EmbedderProxy, EventLoopWaker, GeolocationPosition, MediaSessionEvent};
+use embedder_traits::{MediaSessionPlaybackState, NetworkInformation, OverscrollBehavior};
+use embedder_traits::{PositionProvider, SensorProvider, SensorReading};
+use embedder_traits::{SpeechError, SpeechEvent, SpeechSynthesisProvider, SpeechUtterance};
 use euclid::{default::Size2D as UntypedSize2D, Size2D};
 use gfx::font_cache_thread::FontCacheThread;
 use gfx_traits::Epoch;
+use http::Method;
 use ipc_channel::ipc::{self, IpcReceiver, IpcSender};
 use ipc_channel::router::ROUTER;
 use ipc_channel::Error as IpcError;
 use keyboard_types::webdriver::Event as WebDriverInputEvent;
-use keyboard_types::KeyboardEvent;
+use keyboard_types::{CompositionEvent, KeyboardEvent};
 use layout_traits::LayoutThreadFactory;
 use log::{Level, LevelFilter, Log, Metadata, Record};
 use media::{GLPlayerThreads, WindowGLContext};
@@ -130,16 +137,19 @@ use msg::constellation_msg::{
     PipelineNamespaceId, PipelineNamespaceRequest, TraversalDirection,
 };
 use msg::constellation_msg::{
-    BrowsingContextGroupId, BrowsingContextId, HistoryStateId, PipelineId,
+    BrowsingContextGroupId, BrowsingContextId, HistoryStateId, NetworkConditions, PipelineId,
     TopLevelBrowsingContextId,
 };
-use net_traits::pub_domains::reg_host;
+use net_traits::pub_domains::{get_registrable_domain_suffix_of_or_is_equal_to, reg_host};
 use net_traits::request::RequestBuilder;
 use net_traits::storage_thread::{StorageThreadMsg, StorageType};
+use net_traits::webauthn::AuthenticatorMsg;
 use net_traits::{self, FetchResponseMsg, IpcSend, ResourceThreads};
 use profile_traits::mem;
 use profile_traits::time;
-use script_traits::CompositorEvent::{MouseButtonEvent, MouseMoveEvent};
+use script_traits::CompositorEvent::{MouseButtonEvent, MouseMoveEvent, PointerLockMoveEvent};
+use script_traits::SpeechAction;
+use script_traits::UserActivationUpdate;
 use script_traits::{webdriver_msg, LogEntry, ScriptToConstellationChan, ServiceWorkerMsg};
 use script_traits::{
     AnimationState, AnimationTickType, AuxiliaryBrowsingContextLoadInfo, BroadcastMsg,
@@ -152,11 +162,13 @@ use script_traits::{
     IFrameLoadInfo, IFrameLoadInfoWithData, IFrameSandboxState, TimerSchedulerMsg,
 };
 use script_traits::{LayoutMsg as FromLayoutMsg, ScriptMsg as FromScriptMsg, ScriptThreadFactory};
-use script_traits::{MediaSessionActionType, MouseEventType};
-use script_traits::{MessagePortMsg, PortMessageTask, StructuredSerializedData};
+use script_traits::{MediaSessionActionDetails, MouseEventType};
+use script_traits::{
+    MessagePortMsg, OverscrollContainer, PortMessageTask, StructuredSerializedData,
+};
 use script_traits::{SWManagerMsg, ScopeThings, UpdatePipelineIdReason, WebDriverCommandMsg};
 use serde::{Deserialize, Serialize};
-use servo_config::{opts, pref};
+use servo_config::{opts, pref, set_pref};
 use servo_rand::{random, Rng, ServoRng, SliceRandom};
 use servo_remutex::ReentrantMutex;
 use servo_url::{Host, ImmutableOrigin, ServoUrl};
@@ -170,8 +182,9 @@ use std::rc::{Rc, Weak};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::thread;
+use std::time::Instant;
 use style_traits::viewport::ViewportConstraints;
-use style_traits::CSSPixel;
+use style_traits::{CSSPixel, ColorSchemePreference, MediaPreferences};
 use webgpu::{WebGPU, WebGPURequest};
 use webvr_traits::{WebVREvent, WebVRMsg};
 
@@ -225,6 +238,25 @@ struct Browser {
     session_history: JointSessionHistory,
 }
 
+/// A document that is loaded in a hidden pipeline of the browsing context of the top-level
+/// document whose speculation rules listed its URL, so that a navigation of that browsing
+/// context to the URL can show it at once.
+/// <https://wicg.github.io/nav-speculation/prerendering.html>
+struct Prerender {
+    /// The pipeline of the document that asked for the prerender.
+    referrer_pipeline_id: PipelineId,
+
+    /// The URL that is prerendered.
+    url: ServoUrl,
+
+    /// The pipeline that the URL is prerendered in.
+    pipeline_id: PipelineId,
+
+    /// Whether the document of the prerender can be activated, which it can once its
+    /// pipeline has asked for that.
+    ready: bool,
+}
+
 /// A browsing context group.
 ///
 /// https://html.spec.whatwg.org/multipage/#browsing-context-group
@@ -406,6 +438,9 @@ pub struct Constellation<Message, LTF, STF> {
     /// A map of origin to a map of channel-name to a list of relevant routers.
     broadcast_channels: HashMap<ImmutableOrigin, HashMap<String, Vec<BroadcastChannelRouterId>>>,
 
+    /// The lock managers of the Web Locks API of each origin.
+    lock_managers: LockManagers,
+
     /// The set of all the pipelines in the browser.  (See the `pipeline` module
     /// for more details.)
     pipelines: HashMap<PipelineId, Pipeline>,
@@ -472,10 +507,29 @@ pub struct Constellation<Message, LTF, STF> {
     /// Navigation requests from script awaiting approval from the embedder.
     pending_approval_navigations: PendingApprovalNavigations,
 
+    /// The certificates that failed to verify for the documents of pipelines, awaiting the
+    /// decision of the embedder whether to trust them.
+    pending_certificate_errors: HashMap<CertificateErrorId, PipelineId>,
+
+    /// The conditions that the network of these top-level browsing contexts is made to behave
+    /// like, which apply to the pipelines they create from now on as well.
+    network_conditions: HashMap<TopLevelBrowsingContextId, NetworkConditions>,
+
     /// Bitmask which indicates which combination of mouse buttons are
     /// currently being pressed.
     pressed_mouse_buttons: u16,
 
+    /// The pipeline whose document locked the pointer, which gets the mouse events
+    /// until the lock is released.
+    pointer_lock_pipeline: Option<PipelineId>,
+
+    /// The pipelines whose documents hold screen wake locks, while which the embedder is
+    /// asked to keep the screen on.
+    screen_wake_lock_pipelines: HashSet<PipelineId>,
+
+    /// When the user last sent an input event to a page, which tells whether they are idle.
+    last_user_input: Instant,
+
     is_running_problem_test: bool,
 
     /// If True, exits on thread failure instead of displaying about:failure
@@ -496,8 +550,42 @@ pub struct Constellation<Message, LTF, STF> {
     /// Mechanism to force the compositor to process events.
     event_loop_waker: Option<Box<dyn EventLoopWaker>>,
 
+    /// The source of the battery and network states reported to pages.
+    device_status_provider: Option<Box<dyn DeviceStatusProvider>>,
+
+    /// The source of the positions reported to pages.
+    position_provider: Option<Box<dyn PositionProvider>>,
+
+    /// The pipelines waiting for positions, and whether they asked for a high accuracy.
+    position_watchers: HashMap<PipelineId, bool>,
+
+    /// Whether the position provider is started, and with which accuracy.
+    position_provider_accuracy: Option<bool>,
+
+    /// The source of the orientation and motion readings reported to pages.
+    sensor_provider: Option<Box<dyn SensorProvider>>,
+
+    /// The pipelines listening to sensors of the device, and the sensors they listen to.
+    sensor_watchers: HashMap<PipelineId, Vec<DeviceSensor>>,
+
+    /// The sensors the sensor provider was asked to read.
+    active_sensors: HashSet<DeviceSensor>,
+
+    /// The speech synthesizer that speaks the utterances of pages.
+    speech_synthesis_provider: Option<Box<dyn SpeechSynthesisProvider>>,
+
+    /// The pipeline whose utterance was spoken last, which alone may pause, resume or
+    /// cancel speaking.
+    speaking_pipeline: Option<PipelineId>,
+
+    /// The spell checker of the editable text of pages.
+    spell_check_provider: Option<Box<dyn SpellCheckProvider>>,
+
     /// Pipeline ID of the active media session.
     active_media_session: Option<PipelineId>,
+
+    /// The documents that are prerendered for navigations of top-level browsing contexts.
+    prerenders: Vec<Prerender>,
 }
 
 /// State needed to construct a constellation.
@@ -555,6 +643,21 @@ pub struct InitialConstellationState {
     /// Mechanism to force the compositor to process events.
     pub event_loop_waker: Option<Box<dyn EventLoopWaker>>,
 
+    /// The source of the battery and network states reported to pages.
+    pub device_status_provider: Option<Box<dyn DeviceStatusProvider>>,
+
+    /// The source of the positions reported to pages.
+    pub position_provider: Option<Box<dyn PositionProvider>>,
+
+    /// The source of the orientation and motion readings reported to pages.
+    pub sensor_provider: Option<Box<dyn SensorProvider>>,
+
+    /// The speech synthesizer that speaks the utterances of pages.
+    pub speech_synthesis_provider: Option<Box<dyn SpeechSynthesisProvider>>,
+
+    /// The spell checker of the editable text of pages.
+    pub spell_check_provider: Option<Box<dyn SpellCheckProvider>>,
+
     /// A flag share with the compositor to indicate that a WR frame is in progress.
     pub pending_wr_frame: Arc<AtomicBool>,
 }
@@ -707,6 +810,9 @@ fn log_entry(record: &Record) -> Option<LogEntry> {
 /// The number of warnings to include in each crash report.
 const WARNINGS_BUFFER_SIZE: usize = 32;
 
+/// The most documents that a top-level document can prerender at the same time.
+const MAX_PRERENDERS_PER_DOCUMENT: usize = 2;
+
 /// Route an ipc receiver to an mpsc receiver, preserving any errors.
 /// This is the same as `route_ipc_receiver_to_new_mpsc_receiver`,
 /// but does not panic on deserializtion errors.
@@ -970,6 +1076,7 @@ where
                     message_port_routers: HashMap::new(),
                     broadcast_routers: HashMap::new(),
                     broadcast_channels: HashMap::new(),
+                    lock_managers: LockManagers::default(),
                     pipelines: HashMap::new(),
                     browsing_contexts: HashMap::new(),
                     pending_changes: vec![],
@@ -1008,14 +1115,30 @@ where
                     canvas_chan,
                     ipc_canvas_chan,
                     pending_approval_navigations: HashMap::new(),
+                    pending_certificate_errors: HashMap::new(),
+                    network_conditions: HashMap::new(),
                     pressed_mouse_buttons: 0,
+                    pointer_lock_pipeline: None,
+                    screen_wake_lock_pipelines: HashSet::new(),
+                    last_user_input: Instant::now(),
                     is_running_problem_test,
                     hard_fail,
                     enable_canvas_antialiasing,
                     glplayer_threads: state.glplayer_threads,
                     player_context: state.player_context,
                     event_loop_waker: state.event_loop_waker,
+                    device_status_provider: state.device_status_provider,
+                    position_provider: state.position_provider,
+                    position_watchers: HashMap::new(),
+                    position_provider_accuracy: None,
+                    sensor_provider: state.sensor_provider,
+                    sensor_watchers: HashMap::new(),
+                    active_sensors: HashSet::new(),
+                    speech_synthesis_provider: state.speech_synthesis_provider,
+                    speaking_pipeline: None,
+                    spell_check_provider: state.spell_check_provider,
                     active_media_session: None,
+                    prerenders: vec![],
                 };
 
                 constellation.run();
@@ -1287,6 +1410,10 @@ where
             );
         }
 
+        if let Some(conditions) = self.network_conditions.get(&top_level_browsing_context_id) {
+            self.send_network_conditions(vec![pipeline_id], Some(*conditions));
+        }
+
         assert!(!self.pipelines.contains_key(&pipeline_id));
         self.pipelines.insert(pipeline_id, pipeline.pipeline);
     }
@@ -1557,9 +1684,15 @@ where
             FromCompositorMsg::Keyboard(key_event) => {
                 self.handle_key_msg(key_event);
             },
+            FromCompositorMsg::IMECompositionEvent(composition_event) => {
+                self.handle_ime_composition_msg(composition_event);
+            },
             // Perform a navigation previously requested by script, if approved by the embedder.
             // If there is already a pending page (self.pending_changes), it will not be overridden;
             // However, if the id is not encompassed by another change, it will be.
+            FromCompositorMsg::CertificateErrorResponse(id, trusted) => {
+                self.handle_certificate_error_response(id, trusted);
+            },
             FromCompositorMsg::AllowNavigationResponse(pipeline_id, allowed) => {
                 let pending = self.pending_approval_navigations.remove(&pipeline_id);
 
@@ -1682,6 +1815,9 @@ where
             FromCompositorMsg::TickAnimation(pipeline_id, tick_type) => {
                 self.handle_tick_animation(pipeline_id, tick_type)
             },
+            FromCompositorMsg::SetPinchZoom(pipeline_id, pinch_zoom) => {
+                self.handle_set_pinch_zoom(pipeline_id, pinch_zoom)
+            },
             FromCompositorMsg::WebDriverCommand(command) => {
                 self.handle_webdriver_msg(command);
             },
@@ -1715,12 +1851,77 @@ where
             FromCompositorMsg::ExitFullScreen(top_level_browsing_context_id) => {
                 self.handle_exit_fullscreen_msg(top_level_browsing_context_id);
             },
+            FromCompositorMsg::ExitPointerLock(top_level_browsing_context_id) => {
+                self.handle_exit_pointer_lock_msg(top_level_browsing_context_id);
+            },
             FromCompositorMsg::MediaSessionAction(action) => {
                 self.handle_media_session_action_msg(action);
             },
             FromCompositorMsg::ChangeBrowserVisibility(top_level_browsing_context_id, visible) => {
                 self.handle_change_browser_visibility(top_level_browsing_context_id, visible);
             },
+            FromCompositorMsg::Find(top_level_browsing_context_id, string, options) => {
+                let msg = |pipeline_id| ConstellationControlMsg::Find(pipeline_id, string, options);
+                self.handle_top_level_document_msg(top_level_browsing_context_id, msg);
+            },
+            FromCompositorMsg::StopFinding(top_level_browsing_context_id) => {
+                let msg = ConstellationControlMsg::StopFinding;
+                self.handle_top_level_document_msg(top_level_browsing_context_id, msg);
+            },
+            FromCompositorMsg::ExtractArticle(top_level_browsing_context_id) => {
+                let msg = ConstellationControlMsg::ExtractArticle;
+                self.handle_top_level_document_msg(top_level_browsing_context_id, msg);
+            },
+            FromCompositorMsg::RequestAccessibilityTree(top_level_browsing_context_id) => {
+                let msg = ConstellationControlMsg::RequestAccessibilityTree;
+                self.handle_top_level_document_msg(top_level_browsing_context_id, msg);
+            },
+            FromCompositorMsg::RequestFormFields(top_level_browsing_context_id) => {
+                let msg = ConstellationControlMsg::RequestFormFields;
+                self.handle_top_level_document_msg(top_level_browsing_context_id, msg);
+            },
+            FromCompositorMsg::FillFormFields(top_level_browsing_context_id, values) => {
+                let msg =
+                    |pipeline_id| ConstellationControlMsg::FillFormFields(pipeline_id, values);
+                self.handle_top_level_document_msg(top_level_browsing_context_id, msg);
+            },
+            FromCompositorMsg::FillCredentials(top_level_browsing_context_id, credentials) => {
+                // The top-level document may have navigated to another origin since the
+                // embedder found its password field, which script checks.
+                let msg = |pipeline_id| {
+                    ConstellationControlMsg::FillCredentials(pipeline_id, credentials)
+                };
+                self.handle_top_level_document_msg(top_level_browsing_context_id, msg);
+            },
+            FromCompositorMsg::MemoryPressure => {
+                self.handle_memory_pressure();
+            },
+            FromCompositorMsg::MediaPreferencesChanged(preferences) => {
+                self.handle_media_preferences_changed(preferences);
+            },
+            FromCompositorMsg::DeviceStatusChanged => {
+                self.handle_device_status_changed();
+            },
+            FromCompositorMsg::PositionChanged(position) => {
+                self.handle_position_changed(position);
+            },
+            FromCompositorMsg::SensorReading(reading) => {
+                self.handle_sensor_reading(reading);
+            },
+            FromCompositorMsg::SetTextZoom(zoom) => {
+                self.handle_set_text_zoom(zoom);
+            },
+            FromCompositorMsg::SetNetworkConditions(top_level_browsing_context_id, conditions) => {
+                self.handle_set_network_conditions(top_level_browsing_context_id, conditions);
+            },
+            // All the downloads are made by the same resource manager, whichever the
+            // resource threads that started them.
+            FromCompositorMsg::DownloadAction(id, action) => {
+                let msg = net_traits::CoreResourceMsg::DownloadAction(id, action);
+                if let Err(e) = self.public_resource_threads.send(msg) {
+                    warn!("Sending DownloadAction to resource thread failed ({})", e);
+                }
+            },
         }
     }
 
@@ -1799,9 +2000,83 @@ where
             FromScriptMsg::ScheduleBroadcast(router_id, message) => {
                 self.handle_schedule_broadcast(source_pipeline_id, router_id, message);
             },
+            FromScriptMsg::RequestLock(request) => {
+                if self
+                    .check_origin_against_pipeline(&source_pipeline_id, &request.origin)
+                    .is_err()
+                {
+                    return warn!("Attempt to request a lock for another origin.");
+                }
+                self.lock_managers.request(source_pipeline_id, request);
+            },
+            FromScriptMsg::ReleaseLock(origin, id) => {
+                if self
+                    .check_origin_against_pipeline(&source_pipeline_id, &origin)
+                    .is_err()
+                {
+                    return warn!("Attempt to release a lock of another origin.");
+                }
+                self.lock_managers.release(&origin, id);
+            },
+            FromScriptMsg::QueryLocks(origin, sender) => {
+                if self
+                    .check_origin_against_pipeline(&source_pipeline_id, &origin)
+                    .is_err()
+                {
+                    return warn!("Attempt to query the locks of another origin.");
+                }
+                let _ = sender.send(self.lock_managers.query(&origin));
+            },
             FromScriptMsg::ForwardToEmbedder(embedder_msg) => {
-                self.embedder_proxy
-                    .send((Some(source_top_ctx_id), embedder_msg));
+                // Prerendered documents are not shown to the user until they are activated.
+                if !self.is_prerender(source_pipeline_id) {
+                    self.embedder_proxy
+                        .send((Some(source_top_ctx_id), embedder_msg));
+                }
+            },
+            FromScriptMsg::SetPointerLock(locked) => {
+                self.handle_set_pointer_lock(source_top_ctx_id, source_pipeline_id, locked);
+            },
+            FromScriptMsg::SetScreenWakeLock(locked) => {
+                self.handle_set_screen_wake_lock(source_top_ctx_id, source_pipeline_id, locked);
+            },
+            FromScriptMsg::UpdateUserActivation(update) => {
+                self.handle_update_user_activation(source_pipeline_id, update);
+            },
+            FromScriptMsg::GetUserIdleTime(sender) => {
+                let _ = sender.send(self.last_user_input.elapsed());
+            },
+            FromScriptMsg::GetSpeechVoices(sender) => {
+                let voices = match self.speech_synthesis_provider {
+                    Some(ref mut provider) => provider.voices(),
+                    None => vec![],
+                };
+                let _ = sender.send(voices);
+            },
+            FromScriptMsg::Speak(utterance, sender) => {
+                self.handle_speak(source_pipeline_id, utterance, sender);
+            },
+            FromScriptMsg::SpeechAction(action) => {
+                self.handle_speech_action(source_pipeline_id, action);
+            },
+            FromScriptMsg::CheckSpelling(language, words, sender) => {
+                let misspelled = match self.spell_check_provider {
+                    Some(ref mut provider) => provider.check(&language, words),
+                    None => vec![false; words.len()],
+                };
+                let _ = sender.send(misspelled);
+            },
+            FromScriptMsg::GetSpellingSuggestions(language, word, sender) => {
+                let suggestions = match self.spell_check_provider {
+                    Some(ref mut provider) => provider.suggestions(&language, &word),
+                    None => vec![],
+                };
+                let _ = sender.send(suggestions);
+            },
+            FromScriptMsg::AddToDictionary(word) => {
+                if let Some(ref mut provider) = self.spell_check_provider {
+                    provider.add_to_dictionary(word);
+                }
             },
             FromScriptMsg::PipelineExited => {
                 self.handle_pipeline_exited(source_pipeline_id);
@@ -1908,6 +2183,31 @@ where
                 self.compositor_proxy
                     .send(ToCompositorMsg::GetClientWindow(send));
             },
+            FromScriptMsg::GetBatteryStatus(sender) => {
+                if let Err(e) = sender.send(self.battery_status()) {
+                    warn!("Sending battery status to script failed ({:?}).", e);
+                }
+            },
+            FromScriptMsg::WatchPosition(high_accuracy) => {
+                self.handle_watch_position(source_pipeline_id, high_accuracy);
+            },
+            FromScriptMsg::WatchDeviceSensors(sensors) => {
+                self.handle_watch_device_sensors(source_pipeline_id, sensors);
+            },
+            FromScriptMsg::Prerender(loads) => {
+                self.handle_prerender_msg(source_pipeline_id, loads);
+            },
+            FromScriptMsg::CertificateError(id) => {
+                self.handle_certificate_error_msg(source_top_ctx_id, source_pipeline_id, id);
+            },
+            FromScriptMsg::ToAuthenticator(msg) => {
+                self.handle_authenticator_msg(source_top_ctx_id, source_pipeline_id, msg);
+            },
+            FromScriptMsg::GetNetworkInformation(sender) => {
+                if let Err(e) = sender.send(self.network_information()) {
+                    warn!("Sending network information to script failed ({:?}).", e);
+                }
+            },
             FromScriptMsg::GetScreenSize(send) => {
                 self.compositor_proxy
                     .send(ToCompositorMsg::GetScreenSize(send));
@@ -1916,6 +2216,13 @@ where
                 self.compositor_proxy
                     .send(ToCompositorMsg::GetScreenAvailSize(send));
             },
+            FromScriptMsg::SmoothScroll(scroll_state) => {
+                self.compositor_proxy
+                    .send(ToCompositorMsg::SmoothScroll(scroll_state));
+            },
+            FromScriptMsg::SetNetworkConditions(conditions) => {
+                self.handle_set_network_conditions(source_top_ctx_id, conditions);
+            },
             FromScriptMsg::LogEntry(thread_name, entry) => {
                 self.handle_log_entry(Some(source_top_ctx_id), thread_name, entry);
             },
@@ -2250,6 +2557,23 @@ where
             FromLayoutMsg::ViewportConstrained(pipeline_id, constraints) => {
                 self.handle_viewport_constrained_msg(pipeline_id, constraints);
             },
+            FromLayoutMsg::ScrollSnapContainers(pipeline_id, containers) => {
+                self.compositor_proxy
+                    .send(ToCompositorMsg::ScrollSnapContainers(
+                        pipeline_id,
+                        containers,
+                    ));
+            },
+            FromLayoutMsg::OverscrollContainers(pipeline_id, containers) => {
+                self.handle_overscroll_containers_msg(pipeline_id, containers);
+            },
+            FromLayoutMsg::ScrollbarContainers(pipeline_id, containers) => {
+                self.compositor_proxy
+                    .send(ToCompositorMsg::ScrollbarContainers(
+                        pipeline_id,
+                        containers,
+                    ));
+            },
         }
     }
 
@@ -2837,7 +3161,36 @@ where
 
     fn handle_pipeline_exited(&mut self, pipeline_id: PipelineId) {
         debug!("Pipeline {:?} exited.", pipeline_id);
-        self.pipelines.remove(&pipeline_id);
+        if let Some(pipeline) = self.pipelines.remove(&pipeline_id) {
+            if self.pointer_lock_pipeline == Some(pipeline_id) {
+                self.pointer_lock_pipeline = None;
+                self.embedder_proxy.send((
+                    Some(pipeline.top_level_browsing_context_id),
+                    EmbedderMsg::SetPointerLock(false),
+                ));
+            }
+            self.handle_set_screen_wake_lock(
+                pipeline.top_level_browsing_context_id,
+                pipeline_id,
+                false,
+            );
+            if self.position_watchers.remove(&pipeline_id).is_some() {
+                self.update_position_provider();
+            }
+            if self.sensor_watchers.remove(&pipeline_id).is_some() {
+                self.update_sensor_provider();
+            }
+            if self.speaking_pipeline == Some(pipeline_id) {
+                self.handle_speech_action(pipeline_id, SpeechAction::Cancel);
+            }
+            self.lock_managers.remove_pipeline(pipeline_id);
+            if self
+                .network_conditions
+                .contains_key(&pipeline.top_level_browsing_context_id)
+            {
+                self.send_network_conditions(vec![pipeline_id], None);
+            }
+        }
     }
 
     fn handle_send_error(&mut self, pipeline_id: PipelineId, err: IpcError) {
@@ -2971,6 +3324,21 @@ where
     }
 
     fn forward_event(&mut self, destination_pipeline_id: PipelineId, event: CompositorEvent) {
+        match event {
+            CompositorEvent::ResizeEvent(..) => {},
+            _ => self.last_user_input = Instant::now(),
+        }
+
+        // While the pointer is locked, the mouse events go to the pipeline which locked it,
+        // wherever the hidden cursor is.
+        let destination_pipeline_id = match (&event, self.pointer_lock_pipeline) {
+            (MouseButtonEvent(..), Some(pipeline_id)) |
+            (MouseMoveEvent(..), Some(pipeline_id)) |
+            (PointerLockMoveEvent(..), Some(pipeline_id)) => pipeline_id,
+            (PointerLockMoveEvent(..), None) => return,
+            _ => destination_pipeline_id,
+        };
+
         if let MouseButtonEvent(event_type, button, ..) = &event {
             match event_type {
                 MouseEventType::MouseDown | MouseEventType::Click => {
@@ -2996,6 +3364,9 @@ where
             MouseMoveEvent(point, node_address, _) => {
                 MouseMoveEvent(point, node_address, self.pressed_mouse_buttons)
             },
+            PointerLockMoveEvent(movement, _) => {
+                PointerLockMoveEvent(movement, self.pressed_mouse_buttons)
+            },
             _ => event,
         };
 
@@ -3089,6 +3460,8 @@ where
         let browsing_context_id = BrowsingContextId::from(top_level_browsing_context_id);
         self.close_browsing_context(browsing_context_id, ExitPipelineMode::Normal);
         self.browsers.remove(&top_level_browsing_context_id);
+        self.network_conditions
+            .remove(&top_level_browsing_context_id);
         if self.active_browser_id == Some(top_level_browsing_context_id) {
             self.active_browser_id = None;
         }
@@ -3454,10 +3827,21 @@ where
         }
     }
 
+    fn handle_set_pinch_zoom(&mut self, pipeline_id: PipelineId, pinch_zoom: f32) {
+        let msg = ConstellationControlMsg::SetPinchZoom(pipeline_id, pinch_zoom);
+        let result = match self.pipelines.get(&pipeline_id) {
+            Some(pipeline) => pipeline.event_loop.send(msg),
+            None => return warn!("Pipeline {:?} got pinch zoom after closure.", pipeline_id),
+        };
+        if let Err(e) = result {
+            self.handle_send_error(pipeline_id, e);
+        }
+    }
+
     fn handle_tick_animation(&mut self, pipeline_id: PipelineId, tick_type: AnimationTickType) {
         let result = match tick_type {
-            AnimationTickType::Script => {
-                let msg = ConstellationControlMsg::TickAllAnimations(pipeline_id);
+            AnimationTickType::Script(timing) => {
+                let msg = ConstellationControlMsg::TickAllAnimations(pipeline_id, timing);
                 match self.pipelines.get(&pipeline_id) {
                     Some(pipeline) => pipeline.event_loop.send(msg),
                     None => {
@@ -3611,6 +3995,17 @@ where
                     HistoryEntryReplacement::Disabled => None,
                 };
 
+                let prerender = self.activate_prerender(
+                    top_level_browsing_context_id,
+                    source_id,
+                    &load_data,
+                    replace.clone(),
+                    is_visible,
+                );
+                if prerender.is_some() {
+                    return prerender;
+                }
+
                 let new_pipeline_id = PipelineId::new();
                 let sandbox = IFrameSandboxState::IFrameUnsandboxed;
                 self.new_pipeline(
@@ -3656,6 +4051,171 @@ where
         }
     }
 
+    fn is_prerender(&self, pipeline_id: PipelineId) -> bool {
+        self.prerenders
+            .iter()
+            .any(|prerender| prerender.pipeline_id == pipeline_id)
+    }
+
+    /// Starts prerendering the URLs that a top-level document newly lists in its speculation
+    /// rules, and discards the prerenders of the URLs that it no longer lists.
+    /// <https://wicg.github.io/nav-speculation/prerendering.html#start-user-agent-initiated-prerendering>
+    fn handle_prerender_msg(&mut self, source_id: PipelineId, loads: Vec<LoadData>) {
+        let (browsing_context_id, top_level_browsing_context_id) =
+            match self.pipelines.get(&source_id) {
+                Some(pipeline) => (
+                    pipeline.browsing_context_id,
+                    pipeline.top_level_browsing_context_id,
+                ),
+                None => return warn!("Pipeline {} prerendered after closure.", source_id),
+            };
+        let (window_size, opener, is_private) =
+            match self.browsing_contexts.get(&browsing_context_id) {
+                Some(ctx) if ctx.pipeline_id == source_id && ctx.parent_pipeline_id.is_none() => {
+                    let opener = self
+                        .pipelines
+                        .get(&source_id)
+                        .and_then(|pipeline| pipeline.opener);
+                    (ctx.size, opener, ctx.is_private)
+                },
+                // Only the current document of a top-level browsing context can prerender.
+                _ => return,
+            };
+
+        let discarded: Vec<PipelineId> = self
+            .prerenders
+            .iter()
+            .filter(|prerender| {
+                prerender.referrer_pipeline_id == source_id &&
+                    !loads.iter().any(|load_data| load_data.url == prerender.url)
+            })
+            .map(|prerender| prerender.pipeline_id)
+            .collect();
+        for pipeline_id in discarded {
+            self.discard_prerender(pipeline_id);
+        }
+
+        for load_data in loads {
+            let prerendered = self
+                .prerenders
+                .iter()
+                .filter(|prerender| prerender.referrer_pipeline_id == source_id);
+            if prerendered.clone().count() >= MAX_PRERENDERS_PER_DOCUMENT {
+                break;
+            }
+            if prerendered
+                .clone()
+                .any(|prerender| prerender.url == load_data.url)
+            {
+                continue;
+            }
+            debug!("Prerendering {} for pipeline {}.", load_data.url, source_id);
+            let pipeline_id = PipelineId::new();
+            let url = load_data.url.clone();
+            self.new_pipeline(
+                pipeline_id,
+                browsing_context_id,
+                top_level_browsing_context_id,
+                None,
+                opener,
+                window_size,
+                load_data,
+                IFrameSandboxState::IFrameUnsandboxed,
+                is_private,
+                false,
+            );
+            self.prerenders.push(Prerender {
+                referrer_pipeline_id: source_id,
+                url,
+                pipeline_id,
+                ready: false,
+            });
+            let msg = ConstellationControlMsg::SetPrerendering(pipeline_id, true);
+            let result = match self.pipelines.get(&pipeline_id) {
+                Some(pipeline) => pipeline.event_loop.send(msg),
+                None => continue,
+            };
+            if let Err(e) = result {
+                self.handle_send_error(pipeline_id, e);
+            }
+        }
+    }
+
+    /// Closes the pipeline of a prerender that will not be activated.
+    fn discard_prerender(&mut self, pipeline_id: PipelineId) {
+        let index = match self
+            .prerenders
+            .iter()
+            .position(|prerender| prerender.pipeline_id == pipeline_id)
+        {
+            Some(index) => index,
+            None => return,
+        };
+        debug!("Discarding prerender {}.", pipeline_id);
+        self.prerenders.remove(index);
+        self.close_pipeline(
+            pipeline_id,
+            DiscardBrowsingContext::No,
+            ExitPipelineMode::Normal,
+        );
+    }
+
+    /// Shows a prerendered document in place of the document that prerendered it, if that
+    /// navigates to the prerendered URL, and returns the pipeline of the shown document.
+    /// <https://wicg.github.io/nav-speculation/prerendering.html#prerendering-traversable-activate>
+    fn activate_prerender(
+        &mut self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+        source_id: PipelineId,
+        load_data: &LoadData,
+        replace: Option<NeedsToReload>,
+        is_visible: bool,
+    ) -> Option<PipelineId> {
+        // Prerenders were fetched with GET, so only a navigation that does the same can use
+        // them.
+        if load_data.method != Method::GET || load_data.data.is_some() {
+            return None;
+        }
+        let index = self.prerenders.iter().position(|prerender| {
+            prerender.referrer_pipeline_id == source_id && prerender.url == load_data.url
+        })?;
+        let prerender = self.prerenders.remove(index);
+        let (browsing_context_id, completely_loaded, result) =
+            match self.pipelines.get(&prerender.pipeline_id) {
+                Some(pipeline) => {
+                    pipeline.notify_visibility(is_visible);
+                    let msg = ConstellationControlMsg::SetPrerendering(pipeline.id, false);
+                    (
+                        pipeline.browsing_context_id,
+                        pipeline.completely_loaded,
+                        pipeline.event_loop.send(msg),
+                    )
+                },
+                None => return None,
+            };
+        if let Err(e) = result {
+            self.handle_send_error(prerender.pipeline_id, e);
+            return None;
+        }
+        let window_size = self.browsing_contexts.get(&browsing_context_id)?.size;
+        debug!("Activating prerender {}.", prerender.pipeline_id);
+        self.add_pending_change(SessionHistoryChange {
+            top_level_browsing_context_id,
+            browsing_context_id,
+            new_pipeline_id: prerender.pipeline_id,
+            replace,
+            new_browsing_context_info: None,
+            window_size,
+        });
+        if prerender.ready {
+            self.handle_activate_document_msg(prerender.pipeline_id);
+            if completely_loaded {
+                self.handle_load_complete_msg(top_level_browsing_context_id, prerender.pipeline_id);
+            }
+        }
+        Some(prerender.pipeline_id)
+    }
+
     fn handle_load_start_msg(
         &mut self,
         top_level_browsing_context_id: TopLevelBrowsingContextId,
@@ -3699,7 +4259,9 @@ where
             .get(&BrowsingContextId::from(top_level_browsing_context_id))
             .map(|ctx| ctx.pipeline_id == pipeline_id)
             .unwrap_or(false);
-        if pipeline_is_top_level_pipeline {
+        if self.is_prerender(pipeline_id) {
+            // The prerender reports that it loaded once it is activated.
+        } else if pipeline_is_top_level_pipeline {
             // Is there any pending pipeline that will replace the current top level pipeline
             let current_top_level_pipeline_will_be_replaced = self
                 .pending_changes
@@ -4152,46 +4714,247 @@ where
         }
     }
 
-    fn handle_reload_msg(&mut self, top_level_browsing_context_id: TopLevelBrowsingContextId) {
-        let browsing_context_id = BrowsingContextId::from(top_level_browsing_context_id);
-        let pipeline_id = match self.browsing_contexts.get(&browsing_context_id) {
-            Some(browsing_context) => browsing_context.pipeline_id,
-            None => {
-                return warn!(
-                    "Browsing context {} got reload event after closure.",
-                    browsing_context_id
-                );
-            },
+    fn handle_ime_composition_msg(&mut self, event: CompositionEvent) {
+        // Composition only makes sense for the focused browsing context; unlike key
+        // events, there is nothing for the embedder to do with one that nobody handles.
+        let focused_browsing_context_id = self
+            .active_browser_id
+            .and_then(|browser_id| self.browsers.get(&browser_id))
+            .map(|browser| browser.focused_browsing_context_id);
+        let pipeline_id = match focused_browsing_context_id
+            .and_then(|browsing_context_id| self.browsing_contexts.get(&browsing_context_id))
+        {
+            Some(ctx) => ctx.pipeline_id,
+            None => return debug!("Got composition event without a focused browsing context."),
         };
-        let msg = ConstellationControlMsg::Reload(pipeline_id);
+        let event = CompositorEvent::CompositionEvent(event);
+        let msg = ConstellationControlMsg::SendEvent(pipeline_id, event);
         let result = match self.pipelines.get(&pipeline_id) {
-            None => return warn!("Pipeline {} got reload event after closure.", pipeline_id),
             Some(pipeline) => pipeline.event_loop.send(msg),
+            None => {
+                return debug!(
+                    "Pipeline {:?} got composition event after closure.",
+                    pipeline_id
+                );
+            },
         };
         if let Err(e) = result {
             self.handle_send_error(pipeline_id, e);
         }
     }
 
-    fn handle_post_message_msg(
+    fn handle_certificate_error_msg(
         &mut self,
-        browsing_context_id: BrowsingContextId,
-        source_pipeline: PipelineId,
-        origin: Option<ImmutableOrigin>,
-        source_origin: ImmutableOrigin,
-        data: StructuredSerializedData,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+        pipeline_id: PipelineId,
+        id: CertificateErrorId,
     ) {
-        let pipeline_id = match self.browsing_contexts.get(&browsing_context_id) {
-            None => {
-                return warn!(
-                    "PostMessage to closed browsing_context {}.",
-                    browsing_context_id
-                );
+        // A prerender is not shown to the user, who would not know what to decide about. A
+        // navigation to its URL loads it again instead, which asks about the certificate then.
+        if self.is_prerender(pipeline_id) {
+            return self.discard_prerender(pipeline_id);
+        }
+        // The network stack kept the certificate, and only reports it to the embedder if it
+        // failed to verify for a request of this pipeline.
+        let resource_threads = match self.resource_threads_of_pipeline(pipeline_id) {
+            Some(resource_threads) => resource_threads,
+            None => return warn!("Pipeline {} closed.", pipeline_id),
+        };
+        let msg = net_traits::CoreResourceMsg::ReportCertificateError(
+            id,
+            pipeline_id,
+            top_level_browsing_context_id,
+        );
+        if let Err(e) = resource_threads.send(msg) {
+            return warn!(
+                "Sending ReportCertificateError to resource thread failed ({})",
+                e
+            );
+        }
+        self.pending_certificate_errors.insert(id, pipeline_id);
+    }
+
+    /// Forwards an operation with the public key credentials to the authenticator, if its
+    /// relying party is the host of the pipeline or one of its registrable suffixes. The
+    /// sender of the operation is dropped otherwise, which rejects it.
+    /// <https://w3c.github.io/webauthn/#rp-id>
+    fn handle_authenticator_msg(
+        &self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+        pipeline_id: PipelineId,
+        msg: AuthenticatorMsg,
+    ) {
+        // A prerender is not shown to the user, who would not know what they agree to.
+        if self.is_prerender(pipeline_id) {
+            return warn!("Prerender {} used the authenticator.", pipeline_id);
+        }
+        let host = match self.pipelines.get(&pipeline_id) {
+            Some(pipeline) => match pipeline.load_data.url.origin() {
+                ImmutableOrigin::Tuple(_, host, _) => host,
+                ImmutableOrigin::Opaque(_) => {
+                    return warn!("Attempt to use the authenticator from an opaque origin.")
+                },
             },
-            Some(browsing_context) => browsing_context.pipeline_id,
+            None => return warn!("Pipeline {} closed.", pipeline_id),
         };
-        let source_browsing_context = match self.pipelines.get(&source_pipeline) {
-            Some(pipeline) => pipeline.top_level_browsing_context_id,
+        if get_registrable_domain_suffix_of_or_is_equal_to(msg.rp_id(), host).is_none() {
+            return warn!("Attempt to use the credentials of another relying party.");
+        }
+        let resource_threads = match self.resource_threads_of_pipeline(pipeline_id) {
+            Some(resource_threads) => resource_threads,
+            None => return warn!("Pipeline {} closed.", pipeline_id),
+        };
+        let msg = net_traits::CoreResourceMsg::ToAuthenticator(msg, top_level_browsing_context_id);
+        if let Err(e) = resource_threads.send(msg) {
+            warn!("Sending ToAuthenticator to resource thread failed ({})", e);
+        }
+    }
+
+    /// The resource threads that the pipeline fetches with.
+    fn resource_threads_of_pipeline(&self, pipeline_id: PipelineId) -> Option<&ResourceThreads> {
+        let browsing_context_id = self.pipelines.get(&pipeline_id)?.browsing_context_id;
+        let browsing_context = self.browsing_contexts.get(&browsing_context_id)?;
+        Some(if browsing_context.is_private {
+            &self.private_resource_threads
+        } else {
+            &self.public_resource_threads
+        })
+    }
+
+    /// Trusts the certificate that a document failed to verify and reloads it, or goes back
+    /// from its error page.
+    fn handle_certificate_error_response(&mut self, id: CertificateErrorId, trusted: bool) {
+        let pipeline_id = match self.pending_certificate_errors.remove(&id) {
+            Some(pipeline_id) => pipeline_id,
+            None => return warn!("CertificateErrorResponse for unknown request: {:?}", id),
+        };
+        let (top_level_browsing_context_id, browsing_context_id) =
+            match self.pipelines.get(&pipeline_id) {
+                Some(pipeline) => (
+                    pipeline.top_level_browsing_context_id,
+                    pipeline.browsing_context_id,
+                ),
+                None => {
+                    return warn!(
+                        "Pipeline {} closed before its certificate error was answered.",
+                        pipeline_id
+                    )
+                },
+            };
+        let browsing_context = match self.browsing_contexts.get(&browsing_context_id) {
+            Some(browsing_context) => browsing_context,
+            None => return warn!("Browsing context {} closed.", browsing_context_id),
+        };
+        let resource_threads = if browsing_context.is_private {
+            &self.private_resource_threads
+        } else {
+            &self.public_resource_threads
+        };
+        // The network stack trusts the certificate it kept, for the host it kept it for.
+        let msg = net_traits::CoreResourceMsg::AnswerCertificateError(id, pipeline_id, trusted);
+        if let Err(e) = resource_threads.send(msg) {
+            return warn!(
+                "Sending AnswerCertificateError to resource thread failed ({})",
+                e
+            );
+        }
+        if trusted {
+            let result = match self.pipelines.get(&pipeline_id) {
+                Some(pipeline) => pipeline
+                    .event_loop
+                    .send(ConstellationControlMsg::Reload(pipeline_id)),
+                None => return,
+            };
+            if let Err(e) = result {
+                self.handle_send_error(pipeline_id, e);
+            }
+        } else {
+            // Leave the error page of a top-level document for the previous one, if there is
+            // one. The error page of an iframe stays.
+            let is_top_level = browsing_context_id == top_level_browsing_context_id &&
+                browsing_context.pipeline_id == pipeline_id;
+            let has_past = !self
+                .get_joint_session_history(top_level_browsing_context_id)
+                .past
+                .is_empty();
+            if is_top_level && has_past {
+                self.handle_traverse_history_msg(
+                    top_level_browsing_context_id,
+                    TraversalDirection::Back(1),
+                );
+            }
+        }
+    }
+
+    fn handle_reload_msg(&mut self, top_level_browsing_context_id: TopLevelBrowsingContextId) {
+        let browsing_context_id = BrowsingContextId::from(top_level_browsing_context_id);
+        let pipeline_id = match self.browsing_contexts.get(&browsing_context_id) {
+            Some(browsing_context) => browsing_context.pipeline_id,
+            None => {
+                return warn!(
+                    "Browsing context {} got reload event after closure.",
+                    browsing_context_id
+                );
+            },
+        };
+        let msg = ConstellationControlMsg::Reload(pipeline_id);
+        let result = match self.pipelines.get(&pipeline_id) {
+            None => return warn!("Pipeline {} got reload event after closure.", pipeline_id),
+            Some(pipeline) => pipeline.event_loop.send(msg),
+        };
+        if let Err(e) = result {
+            self.handle_send_error(pipeline_id, e);
+        }
+    }
+
+    fn handle_top_level_document_msg<F>(
+        &mut self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+        msg: F,
+    ) where
+        F: FnOnce(PipelineId) -> ConstellationControlMsg,
+    {
+        // Requests about the content of a browser, such as find-in-page, handle same-origin
+        // iframes in the script thread of the top-level document, so they only need to reach
+        // that one pipeline.
+        let browsing_context_id = BrowsingContextId::from(top_level_browsing_context_id);
+        let pipeline_id = match self.browsing_contexts.get(&browsing_context_id) {
+            Some(browsing_context) => browsing_context.pipeline_id,
+            None => {
+                return warn!(
+                    "Browsing context {} got request after closure.",
+                    browsing_context_id
+                );
+            },
+        };
+        let result = match self.pipelines.get(&pipeline_id) {
+            None => return warn!("Pipeline {} got request after closure.", pipeline_id),
+            Some(pipeline) => pipeline.event_loop.send(msg(pipeline_id)),
+        };
+        if let Err(e) = result {
+            self.handle_send_error(pipeline_id, e);
+        }
+    }
+
+    fn handle_post_message_msg(
+        &mut self,
+        browsing_context_id: BrowsingContextId,
+        source_pipeline: PipelineId,
+        origin: Option<ImmutableOrigin>,
+        source_origin: ImmutableOrigin,
+        data: StructuredSerializedData,
+    ) {
+        let pipeline_id = match self.browsing_contexts.get(&browsing_context_id) {
+            None => {
+                return warn!(
+                    "PostMessage to closed browsing_context {}.",
+                    browsing_context_id
+                );
+            },
+            Some(browsing_context) => browsing_context.pipeline_id,
+        };
+        let source_browsing_context = match self.pipelines.get(&source_pipeline) {
+            Some(pipeline) => pipeline.top_level_browsing_context_id,
             None => return warn!("PostMessage from closed pipeline {:?}", source_pipeline),
         };
         let msg = ConstellationControlMsg::PostMessage {
@@ -4520,24 +5283,340 @@ where
         visible: bool,
     ) {
         let browsing_context_id = BrowsingContextId::from(top_level_browsing_context_id);
-        let pipeline_id = match self.browsing_contexts.get(&browsing_context_id) {
-            Some(browsing_context) => browsing_context.pipeline_id,
-            None => {
-                return warn!(
-                    "Browsing context {} got visibility change event after closure.",
-                    browsing_context_id
+        if !self.browsing_contexts.contains_key(&browsing_context_id) {
+            return warn!(
+                "Browsing context {} got visibility change event after closure.",
+                browsing_context_id
+            );
+        }
+
+        // The nested browsing contexts are hidden along with their top-level browsing context,
+        // so that their timers and animations are throttled too.
+        let browsing_context_ids: Vec<BrowsingContextId> = self
+            .all_descendant_browsing_contexts_iter(browsing_context_id)
+            .map(|browsing_context| browsing_context.id)
+            .collect();
+        for browsing_context_id in browsing_context_ids {
+            let pipeline_id = match self.browsing_contexts.get_mut(&browsing_context_id) {
+                Some(browsing_context) => {
+                    browsing_context.is_visible = visible;
+                    browsing_context.pipeline_id
+                },
+                None => continue,
+            };
+            match self.pipelines.get(&pipeline_id) {
+                None => warn!(
+                    "Pipeline {} got visibility change event after closure.",
+                    pipeline_id
+                ),
+                Some(pipeline) => pipeline.notify_visibility(visible),
+            };
+        }
+    }
+
+    /// Frees as much memory as possible: discards the documents kept alive for session history,
+    /// clears the HTTP caches, and asks every layout and script thread to purge its caches.
+    fn handle_memory_pressure(&mut self) {
+        let top_level_ids: Vec<TopLevelBrowsingContextId> = self.browsers.keys().cloned().collect();
+        for top_level_id in top_level_ids {
+            self.evict_session_history(top_level_id, 0);
+        }
+
+        for resource_threads in &[
+            &self.public_resource_threads,
+            &self.private_resource_threads,
+        ] {
+            if let Err(e) = resource_threads.send(net_traits::CoreResourceMsg::ClearHttpCache) {
+                warn!("Sending ClearHttpCache to resource thread failed ({})", e);
+            }
+        }
+
+        let mut event_loops: Vec<Rc<EventLoop>> = vec![];
+        for pipeline in self.pipelines.values() {
+            if let Err(e) = pipeline.layout_chan.send(LayoutControlMsg::MemoryPressure) {
+                warn!("Sending MemoryPressure to layout failed ({:?}).", e);
+            }
+            if !event_loops
+                .iter()
+                .any(|event_loop| Rc::ptr_eq(event_loop, &pipeline.event_loop))
+            {
+                event_loops.push(pipeline.event_loop.clone());
+            }
+        }
+        for event_loop in event_loops {
+            if let Err(e) = event_loop.send(ConstellationControlMsg::MemoryPressure) {
+                warn!("Sending MemoryPressure to script failed ({:?}).", e);
+            }
+        }
+    }
+
+    /// The event loops of the pipelines, each listed once.
+    fn event_loops(&self) -> Vec<Rc<EventLoop>> {
+        let mut event_loops: Vec<Rc<EventLoop>> = vec![];
+        for pipeline in self.pipelines.values() {
+            if !event_loops
+                .iter()
+                .any(|event_loop| Rc::ptr_eq(event_loop, &pipeline.event_loop))
+            {
+                event_loops.push(pipeline.event_loop.clone());
+            }
+        }
+        event_loops
+    }
+
+    /// Stores the new settings of the user in the preferences, which the content processes
+    /// spawned from now on inherit, and lets every script thread re-evaluate its media queries.
+    fn handle_media_preferences_changed(&mut self, preferences: MediaPreferences) {
+        let color_scheme = match preferences.color_scheme {
+            ColorSchemePreference::Light => "light",
+            ColorSchemePreference::Dark => "dark",
+        };
+        set_pref!(layout.prefers_color_scheme, color_scheme.to_owned());
+        set_pref!(layout.prefers_reduced_motion, preferences.reduced_motion);
+        set_pref!(layout.forced_colors, preferences.forced_colors);
+
+        for event_loop in self.event_loops() {
+            let msg = ConstellationControlMsg::MediaPreferencesChanged(preferences);
+            if let Err(e) = event_loop.send(msg) {
+                warn!(
+                    "Sending MediaPreferencesChanged to script failed ({:?}).",
+                    e
+                );
+            }
+        }
+    }
+
+    /// Stores the new text zoom in the preferences, which the content processes spawned from now
+    /// on inherit, and lets every script thread restyle its documents with it.
+    fn handle_set_text_zoom(&mut self, zoom: f32) {
+        set_pref!(layout.text_zoom, zoom as f64);
+
+        for event_loop in self.event_loops() {
+            let msg = ConstellationControlMsg::SetTextZoom(zoom);
+            if let Err(e) = event_loop.send(msg) {
+                warn!("Sending SetTextZoom to script failed ({:?}).", e);
+            }
+        }
+    }
+
+    /// Makes the network behave like under the given conditions for every pipeline of a
+    /// top-level browsing context, and for those it creates from now on.
+    fn handle_set_network_conditions(
+        &mut self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+        conditions: Option<NetworkConditions>,
+    ) {
+        match conditions {
+            Some(conditions) => self
+                .network_conditions
+                .insert(top_level_browsing_context_id, conditions),
+            None => self
+                .network_conditions
+                .remove(&top_level_browsing_context_id),
+        };
+        let pipeline_ids = self
+            .pipelines
+            .values()
+            .filter(|pipeline| {
+                pipeline.top_level_browsing_context_id == top_level_browsing_context_id
+            })
+            .map(|pipeline| pipeline.id)
+            .collect();
+        self.send_network_conditions(pipeline_ids, conditions);
+    }
+
+    fn send_network_conditions(
+        &self,
+        pipeline_ids: Vec<PipelineId>,
+        conditions: Option<NetworkConditions>,
+    ) {
+        // A pipeline fetches through either the private or the public resource threads.
+        for resource_threads in &[
+            &self.public_resource_threads,
+            &self.private_resource_threads,
+        ] {
+            let msg =
+                net_traits::CoreResourceMsg::SetNetworkConditions(pipeline_ids.clone(), conditions);
+            if let Err(e) = resource_threads.send(msg) {
+                warn!(
+                    "Sending SetNetworkConditions to resource thread failed ({})",
+                    e
                 );
+            }
+        }
+    }
+
+    fn battery_status(&self) -> BatteryStatus {
+        self.device_status_provider
+            .as_ref()
+            .map_or_else(BatteryStatus::default, |provider| provider.battery_status())
+    }
+
+    fn network_information(&self) -> NetworkInformation {
+        self.device_status_provider
+            .as_ref()
+            .map_or_else(NetworkInformation::default, |provider| {
+                provider.network_information()
+            })
+    }
+
+    /// Sends the new battery and network states to every script thread, which update the
+    /// objects that pages read them from.
+    fn handle_device_status_changed(&mut self) {
+        let battery_status = self.battery_status();
+        let network_information = self.network_information();
+
+        let mut event_loops: Vec<Rc<EventLoop>> = vec![];
+        for pipeline in self.pipelines.values() {
+            if !event_loops
+                .iter()
+                .any(|event_loop| Rc::ptr_eq(event_loop, &pipeline.event_loop))
+            {
+                event_loops.push(pipeline.event_loop.clone());
+            }
+        }
+        for event_loop in event_loops {
+            let msg =
+                ConstellationControlMsg::DeviceStatusChanged(battery_status, network_information);
+            if let Err(e) = event_loop.send(msg) {
+                warn!("Sending DeviceStatusChanged to script failed ({:?}).", e);
+            }
+        }
+    }
+
+    /// Records whether a pipeline waits for positions, and starts, restarts or stops the
+    /// position provider accordingly.
+    fn handle_watch_position(&mut self, pipeline_id: PipelineId, high_accuracy: Option<bool>) {
+        match high_accuracy {
+            Some(high_accuracy) => {
+                if self.position_provider.is_none() {
+                    let error = Err("No position provider".to_owned());
+                    let msg = ConstellationControlMsg::UpdatePosition(pipeline_id, error);
+                    if let Some(pipeline) = self.pipelines.get(&pipeline_id) {
+                        let _ = pipeline.event_loop.send(msg);
+                    }
+                    return;
+                }
+                self.position_watchers.insert(pipeline_id, high_accuracy);
             },
+            None => {
+                self.position_watchers.remove(&pipeline_id);
+            },
+        }
+        self.update_position_provider();
+    }
+
+    fn update_position_provider(&mut self) {
+        let accuracy = if self.position_watchers.is_empty() {
+            None
+        } else {
+            Some(
+                self.position_watchers
+                    .values()
+                    .any(|high_accuracy| *high_accuracy),
+            )
         };
-        match self.pipelines.get(&pipeline_id) {
+        if accuracy == self.position_provider_accuracy {
+            return;
+        }
+        self.position_provider_accuracy = accuracy;
+        if let Some(ref mut provider) = self.position_provider {
+            match accuracy {
+                Some(high_accuracy) => provider.start(high_accuracy),
+                None => provider.stop(),
+            }
+        }
+    }
+
+    /// Sends a position acquired by the embedder to the pipelines waiting for one.
+    fn handle_position_changed(&mut self, position: Result<GeolocationPosition, String>) {
+        for pipeline_id in self.position_watchers.keys() {
+            let msg = ConstellationControlMsg::UpdatePosition(*pipeline_id, position.clone());
+            if let Some(pipeline) = self.pipelines.get(pipeline_id) {
+                if let Err(e) = pipeline.event_loop.send(msg) {
+                    warn!("Sending UpdatePosition to script failed ({:?}).", e);
+                }
+            }
+        }
+    }
+
+    /// Passes an utterance of a pipeline to the speech synthesizer, which interrupts the
+    /// utterance of any other pipeline.
+    fn handle_speak(
+        &mut self,
+        pipeline_id: PipelineId,
+        utterance: SpeechUtterance,
+        sender: IpcSender<SpeechEvent>,
+    ) {
+        match self.speech_synthesis_provider {
+            Some(ref mut provider) => {
+                self.speaking_pipeline = Some(pipeline_id);
+                provider.speak(utterance, sender);
+            },
             None => {
-                return warn!(
-                    "Pipeline {} got visibility change event after closure.",
-                    pipeline_id
-                )
+                let error = SpeechEvent::Error(SpeechError::SynthesisUnavailable);
+                let _ = sender.send(error);
             },
-            Some(pipeline) => pipeline.notify_visibility(visible),
+        }
+    }
+
+    fn handle_speech_action(&mut self, pipeline_id: PipelineId, action: SpeechAction) {
+        if self.speaking_pipeline != Some(pipeline_id) {
+            return;
+        }
+        let provider = match self.speech_synthesis_provider {
+            Some(ref mut provider) => provider,
+            None => return,
         };
+        match action {
+            SpeechAction::Pause => provider.pause(),
+            SpeechAction::Resume => provider.resume(),
+            SpeechAction::Cancel => {
+                provider.cancel();
+                self.speaking_pipeline = None;
+            },
+        }
+    }
+
+    /// Records the sensors of the device that a pipeline listens to, and starts or stops the
+    /// sensors accordingly.
+    fn handle_watch_device_sensors(&mut self, pipeline_id: PipelineId, sensors: Vec<DeviceSensor>) {
+        if sensors.is_empty() {
+            self.sensor_watchers.remove(&pipeline_id);
+        } else {
+            self.sensor_watchers.insert(pipeline_id, sensors);
+        }
+        self.update_sensor_provider();
+    }
+
+    fn update_sensor_provider(&mut self) {
+        let sensors: HashSet<DeviceSensor> =
+            self.sensor_watchers.values().flatten().cloned().collect();
+        if let Some(ref mut provider) = self.sensor_provider {
+            for sensor in self.active_sensors.difference(&sensors) {
+                provider.stop(*sensor);
+            }
+            for sensor in sensors.difference(&self.active_sensors) {
+                provider.start(*sensor);
+            }
+        }
+        self.active_sensors = sensors;
+    }
+
+    /// Sends a reading of the sensors of the device to the pipelines listening to the sensor.
+    fn handle_sensor_reading(&mut self, reading: SensorReading) {
+        let sensor = reading.sensor();
+        for (pipeline_id, sensors) in &self.sensor_watchers {
+            if !sensors.contains(&sensor) {
+                continue;
+            }
+            let msg = ConstellationControlMsg::SensorReading(*pipeline_id, reading);
+            if let Some(pipeline) = self.pipelines.get(pipeline_id) {
+                if let Err(e) = pipeline.event_loop.send(msg) {
+                    warn!("Sending SensorReading to script failed ({:?}).", e);
+                }
+            }
+        }
     }
 
     fn notify_history_changed(&self, top_level_browsing_context_id: TopLevelBrowsingContextId) {
@@ -4745,6 +5824,17 @@ where
                     pipeline.notify_visibility(false);
                 }
 
+                // The prerenders that the old document did not navigate to are no longer needed.
+                let prerenders: Vec<PipelineId> = self
+                    .prerenders
+                    .iter()
+                    .filter(|prerender| prerender.referrer_pipeline_id == old_pipeline_id)
+                    .map(|prerender| prerender.pipeline_id)
+                    .collect();
+                for prerender in prerenders {
+                    self.discard_prerender(prerender);
+                }
+
                 // https://html.spec.whatwg.org/multipage/#unload-a-document
                 self.unload_document(old_pipeline_id);
                 // Deactivate the old pipeline, and activate the new one.
@@ -4857,11 +5947,20 @@ where
     }
 
     fn trim_history(&mut self, top_level_browsing_context_id: TopLevelBrowsingContextId) {
+        let history_length = pref!(session_history.max_length) as usize;
+        self.evict_session_history(top_level_browsing_context_id, history_length);
+    }
+
+    /// Closes the pipelines of the session history entries that are more than `history_length`
+    /// entries away from the current one. They are reloaded when the entries are traversed to.
+    fn evict_session_history(
+        &mut self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+        history_length: usize,
+    ) {
         let pipelines_to_evict = {
             let session_history = self.get_joint_session_history(top_level_browsing_context_id);
 
-            let history_length = pref!(session_history.max_length) as usize;
-
             // The past is stored with older entries at the front.
             // We reverse the iter so that newer entries are at the front and then
             // skip _n_ entries and evict the remaining entries.
@@ -4918,6 +6017,16 @@ where
     fn handle_activate_document_msg(&mut self, pipeline_id: PipelineId) {
         debug!("Document ready to activate {}", pipeline_id);
 
+        // A prerender is activated by a navigation to its URL.
+        if let Some(prerender) = self
+            .prerenders
+            .iter_mut()
+            .find(|prerender| prerender.pipeline_id == pipeline_id)
+        {
+            prerender.ready = true;
+            return;
+        }
+
         // Find the pending change whose new pipeline id is pipeline_id.
         let pending_index = self
             .pending_changes
@@ -4992,6 +6101,140 @@ where
         self.switch_fullscreen_mode(browsing_context_id);
     }
 
+    fn handle_exit_pointer_lock_msg(
+        &mut self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+    ) {
+        let pipeline = match self.pointer_lock_pipeline {
+            Some(pipeline_id) => self.pipelines.get(&pipeline_id),
+            None => return,
+        };
+        if let Some(pipeline) = pipeline {
+            if pipeline.top_level_browsing_context_id == top_level_browsing_context_id {
+                let msg = ConstellationControlMsg::ExitPointerLock(pipeline.id);
+                let _ = pipeline.event_loop.send(msg);
+            }
+        }
+    }
+
+    /// Records which pipeline locked the pointer, releasing the lock of any other pipeline,
+    /// and asks the embedder to capture or release the mouse.
+    fn handle_set_pointer_lock(
+        &mut self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+        pipeline_id: PipelineId,
+        locked: bool,
+    ) {
+        if locked {
+            let previous = self.pointer_lock_pipeline.replace(pipeline_id);
+            if let Some(previous) = previous.filter(|previous| *previous != pipeline_id) {
+                if let Some(pipeline) = self.pipelines.get(&previous) {
+                    let msg = ConstellationControlMsg::ExitPointerLock(previous);
+                    let _ = pipeline.event_loop.send(msg);
+                }
+            }
+        } else if self.pointer_lock_pipeline == Some(pipeline_id) {
+            self.pointer_lock_pipeline = None;
+        } else {
+            return;
+        }
+        self.embedder_proxy.send((
+            Some(top_level_browsing_context_id),
+            EmbedderMsg::SetPointerLock(locked),
+        ));
+    }
+
+    /// Records whether a pipeline holds screen wake locks, and asks the embedder to keep the
+    /// screen on while any pipeline does.
+    fn handle_set_screen_wake_lock(
+        &mut self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+        pipeline_id: PipelineId,
+        locked: bool,
+    ) {
+        let was_locked = !self.screen_wake_lock_pipelines.is_empty();
+        if locked {
+            self.screen_wake_lock_pipelines.insert(pipeline_id);
+        } else {
+            self.screen_wake_lock_pipelines.remove(&pipeline_id);
+        }
+        let is_locked = !self.screen_wake_lock_pipelines.is_empty();
+        if was_locked != is_locked {
+            self.embedder_proxy.send((
+                Some(top_level_browsing_context_id),
+                EmbedderMsg::SetScreenWakeLock(is_locked),
+            ));
+        }
+    }
+
+    /// Propagates a change of the user activation of the window of a pipeline to the other
+    /// windows of its frame tree: an activation to its ancestors and to its same origin
+    /// descendants, and a consumption to all of them.
+    /// <https://html.spec.whatwg.org/multipage/#activation-notification>
+    /// <https://html.spec.whatwg.org/multipage/#consume-user-activation>
+    fn handle_update_user_activation(
+        &mut self,
+        source_pipeline_id: PipelineId,
+        update: UserActivationUpdate,
+    ) {
+        let (browsing_context_id, top_level_browsing_context_id, origin) =
+            match self.pipelines.get(&source_pipeline_id) {
+                Some(pipeline) => (
+                    pipeline.browsing_context_id,
+                    pipeline.top_level_browsing_context_id,
+                    pipeline.load_data.url.origin(),
+                ),
+                None => return warn!("User activation of closed pipeline {}.", source_pipeline_id),
+            };
+
+        let mut pipeline_ids = vec![];
+        match update {
+            UserActivationUpdate::Notified => {
+                let mut ancestor = self
+                    .browsing_contexts
+                    .get(&browsing_context_id)
+                    .and_then(|browsing_context| browsing_context.parent_pipeline_id);
+                while let Some(pipeline_id) = ancestor {
+                    pipeline_ids.push(pipeline_id);
+                    ancestor = self
+                        .pipelines
+                        .get(&pipeline_id)
+                        .and_then(|pipeline| {
+                            self.browsing_contexts.get(&pipeline.browsing_context_id)
+                        })
+                        .and_then(|browsing_context| browsing_context.parent_pipeline_id);
+                }
+                pipeline_ids.extend(
+                    self.fully_active_descendant_browsing_contexts_iter(browsing_context_id)
+                        .map(|browsing_context| browsing_context.pipeline_id)
+                        .filter(|pipeline_id| {
+                            self.pipelines
+                                .get(pipeline_id)
+                                .map_or(false, |pipeline| pipeline.load_data.url.origin() == origin)
+                        }),
+                );
+            },
+            UserActivationUpdate::Consumed => {
+                pipeline_ids.extend(
+                    self.fully_active_browsing_contexts_iter(top_level_browsing_context_id)
+                        .map(|browsing_context| browsing_context.pipeline_id),
+                );
+            },
+        }
+
+        for pipeline_id in pipeline_ids {
+            if pipeline_id == source_pipeline_id {
+                continue;
+            }
+            if let Some(pipeline) = self.pipelines.get(&pipeline_id) {
+                let msg = ConstellationControlMsg::UpdateUserActivation(pipeline_id, update);
+                if let Err(e) = pipeline.event_loop.send(msg) {
+                    warn!("Sending UpdateUserActivation to script failed ({:?}).", e);
+                }
+            }
+        }
+    }
+
     /// Handle updating actual viewport / zoom due to @viewport rules
     fn handle_viewport_constrained_msg(
         &mut self,
@@ -5005,6 +6248,34 @@ where
             ));
     }
 
+    fn handle_overscroll_containers_msg(
+        &mut self,
+        pipeline_id: PipelineId,
+        containers: Vec<OverscrollContainer>,
+    ) {
+        // The overscroll behavior of the viewport of a top-level document decides which
+        // overscroll effects the embedder may show.
+        if let Some(pipeline) = self.pipelines.get(&pipeline_id) {
+            let top_level_browsing_context_id = pipeline.top_level_browsing_context_id;
+            let top_level_context_id = BrowsingContextId::from(top_level_browsing_context_id);
+            if pipeline.browsing_context_id == top_level_context_id {
+                let (behavior_x, behavior_y) = containers.first().map_or(
+                    (OverscrollBehavior::Auto, OverscrollBehavior::Auto),
+                    |root| (root.behavior_x, root.behavior_y),
+                );
+                self.embedder_proxy.send((
+                    Some(top_level_browsing_context_id),
+                    EmbedderMsg::OverscrollBehaviorChanged(behavior_x, behavior_y),
+                ));
+            }
+        }
+        self.compositor_proxy
+            .send(ToCompositorMsg::OverscrollContainers(
+                pipeline_id,
+                containers,
+            ));
+    }
+
     /// Checks the state of all script and layout pipelines to see if they are idle
     /// and compares the current layout state to what the compositor has. This is used
     /// to check if the output image is "stable" and can be written as a screenshot
@@ -5208,9 +6479,18 @@ where
             }
         }
 
-        // Send resize message to any pending pipelines that aren't loaded yet.
-        for change in &self.pending_changes {
-            let pipeline_id = change.new_pipeline_id;
+        // Send resize message to any pending pipelines that aren't loaded yet, and to the
+        // prerenders, which are shown at the size of the browsing context once activated.
+        let pending_pipelines = self
+            .pending_changes
+            .iter()
+            .map(|change| change.new_pipeline_id)
+            .chain(
+                self.prerenders
+                    .iter()
+                    .map(|prerender| prerender.pipeline_id),
+            );
+        for pipeline_id in pending_pipelines {
             let pipeline = match self.pipelines.get(&pipeline_id) {
                 None => {
                     warn!("Pending pipeline {:?} is closed", pipeline_id);
@@ -5363,6 +6643,35 @@ where
     ) {
         debug!("Closing pipeline {:?}.", pipeline_id);
 
+        // The network stack forgets the certificates that failed to verify for the pipeline.
+        let certificate_errors: Vec<_> = self
+            .pending_certificate_errors
+            .iter()
+            .filter(|&(_, &errored_pipeline_id)| errored_pipeline_id == pipeline_id)
+            .map(|(&id, _)| id)
+            .collect();
+        for id in certificate_errors {
+            self.pending_certificate_errors.remove(&id);
+            if let Some(resource_threads) = self.resource_threads_of_pipeline(pipeline_id) {
+                let msg =
+                    net_traits::CoreResourceMsg::AnswerCertificateError(id, pipeline_id, false);
+                let _ = resource_threads.send(msg);
+            }
+        }
+
+        // Discard the prerenders of the document, and forget the pipeline if it is one.
+        self.prerenders
+            .retain(|prerender| prerender.pipeline_id != pipeline_id);
+        let prerenders: Vec<PipelineId> = self
+            .prerenders
+            .iter()
+            .filter(|prerender| prerender.referrer_pipeline_id == pipeline_id)
+            .map(|prerender| prerender.pipeline_id)
+            .collect();
+        for prerender in prerenders {
+            self.discard_prerender(prerender);
+        }
+
         // Sever connection to browsing context
         let browsing_context_id = self
             .pipelines
@@ -5532,7 +6841,7 @@ where
         }
     }
 
-    fn handle_media_session_action_msg(&mut self, action: MediaSessionActionType) {
+    fn handle_media_session_action_msg(&mut self, action: MediaSessionActionDetails) {
         if let Some(media_session_pipeline_id) = self.active_media_session {
             let result = match self.pipelines.get(&media_session_pipeline_id) {
                 None => {
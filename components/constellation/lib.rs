@@ -26,6 +26,7 @@ mod pipeline;
 mod sandboxing;
 mod session_history;
 mod timer_scheduler;
+mod web_locks;
 
 pub use crate::constellation::{
     Constellation, FromCompositorLogger, FromScriptLogger, InitialConstellationState,
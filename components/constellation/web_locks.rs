@@ -0,0 +1,208 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! The lock managers of the Web Locks API, which coordinate the locks that the windows and
+//! workers of each origin request.
+//! <https://w3c.github.io/web-locks/#lock-managers>
+
+use msg::constellation_msg::{LockRequestId, PipelineId};
+use script_traits::{LockEvent, LockInfo, LockMode, LockRequest};
+use servo_url::ImmutableOrigin;
+use std::collections::{HashMap, VecDeque};
+
+/// A request, and the pipeline of the window or worker that made it.
+struct PipelineLockRequest {
+    pipeline_id: PipelineId,
+    request: LockRequest,
+}
+
+impl PipelineLockRequest {
+    fn info(&self) -> LockInfo {
+        LockInfo {
+            name: self.request.name.clone(),
+            mode: self.request.mode,
+            client_id: self.request.client_id.clone(),
+        }
+    }
+}
+
+/// <https://w3c.github.io/web-locks/#lock-manager>
+#[derive(Default)]
+struct LockManager {
+    /// <https://w3c.github.io/web-locks/#lock-manager-held-lock-set>
+    held: Vec<PipelineLockRequest>,
+    /// <https://w3c.github.io/web-locks/#lock-manager-lock-request-queue-map>
+    queues: HashMap<String, VecDeque<PipelineLockRequest>>,
+}
+
+impl LockManager {
+    /// Whether a request of the given name and mode can be granted once it is at the front of
+    /// its queue.
+    /// <https://w3c.github.io/web-locks/#lock-request-grantable>
+    fn is_grantable(&self, name: &str, mode: LockMode) -> bool {
+        self.held.iter().all(|held| {
+            held.request.name != name ||
+                (mode == LockMode::Shared && held.request.mode == LockMode::Shared)
+        })
+    }
+
+    /// Grants the requests at the front of the queue of the given name, while they can be.
+    /// <https://w3c.github.io/web-locks/#process-the-lock-request-queue>
+    fn process_queue(&mut self, name: &str) {
+        loop {
+            let mode = match self.queues.get(name).and_then(|queue| queue.front()) {
+                Some(request) => request.request.mode,
+                None => break,
+            };
+            if !self.is_grantable(name, mode) {
+                break;
+            }
+            let request = self
+                .queues
+                .get_mut(name)
+                .and_then(|queue| queue.pop_front())
+                .unwrap();
+            // A request whose window or worker went away is dropped rather than granted.
+            if request.request.sender.send(LockEvent::Granted).is_ok() {
+                self.held.push(request);
+            }
+        }
+        if self
+            .queues
+            .get(name)
+            .map_or(false, |queue| queue.is_empty())
+        {
+            self.queues.remove(name);
+        }
+    }
+
+    /// Removes the held locks and the pending requests that match, and grants the requests
+    /// that they were blocking.
+    fn remove_where<F>(&mut self, matches: F)
+    where
+        F: Fn(&PipelineLockRequest) -> bool,
+    {
+        let mut names = vec![];
+        self.held.retain(|held| {
+            if matches(held) {
+                names.push(held.request.name.clone());
+                return false;
+            }
+            true
+        });
+        for (name, queue) in self.queues.iter_mut() {
+            let length = queue.len();
+            queue.retain(|request| !matches(request));
+            if queue.len() != length {
+                names.push(name.clone());
+            }
+        }
+        names.sort();
+        names.dedup();
+        for name in names {
+            self.process_queue(&name);
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.held.is_empty() && self.queues.is_empty()
+    }
+}
+
+/// The lock managers of the origins that requested locks.
+#[derive(Default)]
+pub struct LockManagers {
+    managers: HashMap<ImmutableOrigin, LockManager>,
+}
+
+impl LockManagers {
+    /// <https://w3c.github.io/web-locks/#request-a-lock>
+    pub fn request(&mut self, pipeline_id: PipelineId, request: LockRequest) {
+        let manager = self
+            .managers
+            .entry(request.origin.clone())
+            .or_insert_with(LockManager::default);
+        let name = request.name.clone();
+
+        if request.steal {
+            // Step 1.
+            manager.held.retain(|held| {
+                if held.request.name != name {
+                    return true;
+                }
+                let _ = held.request.sender.send(LockEvent::Stolen);
+                false
+            });
+            // Step 2.
+            manager
+                .queues
+                .entry(name.clone())
+                .or_insert_with(VecDeque::new)
+                .push_front(PipelineLockRequest {
+                    pipeline_id,
+                    request,
+                });
+        } else {
+            // Step 3.
+            let queue_is_empty = manager
+                .queues
+                .get(&name)
+                .map_or(true, |queue| queue.is_empty());
+            if request.if_available &&
+                !(queue_is_empty && manager.is_grantable(&name, request.mode))
+            {
+                let _ = request.sender.send(LockEvent::NotAvailable);
+                return;
+            }
+            manager
+                .queues
+                .entry(name.clone())
+                .or_insert_with(VecDeque::new)
+                .push_back(PipelineLockRequest {
+                    pipeline_id,
+                    request,
+                });
+        }
+
+        // Step 4.
+        manager.process_queue(&name);
+    }
+
+    /// Releases a held lock, or aborts a pending request.
+    /// <https://w3c.github.io/web-locks/#release-the-lock>
+    /// <https://w3c.github.io/web-locks/#abort-the-request>
+    pub fn release(&mut self, origin: &ImmutableOrigin, id: LockRequestId) {
+        if let Some(manager) = self.managers.get_mut(origin) {
+            manager.remove_where(|request| request.request.id == id);
+            if manager.is_empty() {
+                self.managers.remove(origin);
+            }
+        }
+    }
+
+    /// Releases the locks and drops the requests of a pipeline that went away, including the
+    /// ones of its workers.
+    pub fn remove_pipeline(&mut self, pipeline_id: PipelineId) {
+        for manager in self.managers.values_mut() {
+            manager.remove_where(|request| request.pipeline_id == pipeline_id);
+        }
+        self.managers.retain(|_, manager| !manager.is_empty());
+    }
+
+    /// The held locks and the pending requests of an origin.
+    /// <https://w3c.github.io/web-locks/#snapshot-the-lock-state>
+    pub fn query(&self, origin: &ImmutableOrigin) -> (Vec<LockInfo>, Vec<LockInfo>) {
+        let manager = match self.managers.get(origin) {
+            Some(manager) => manager,
+            None => return (vec![], vec![]),
+        };
+        let held = manager.held.iter().map(PipelineLockRequest::info).collect();
+        let pending = manager
+            .queues
+            .values()
+            .flat_map(|queue| queue.iter().map(PipelineLockRequest::info))
+            .collect();
+        (held, pending)
+    }
+}
@@ -9,6 +9,7 @@
 
 use crate::actor::{Actor, ActorMessageStatus, ActorRegistry};
 use crate::actors::console::ConsoleActor;
+use crate::actors::inspector::InspectorActor;
 use crate::protocol::JsonPacketStream;
 use devtools_traits::DevtoolScriptControlMsg::{self, WantsLiveNotifications};
 use serde_json::{Map, Value};
@@ -187,6 +188,9 @@ impl Actor for BrowsingContextActor {
                 };
                 let console_actor = registry.find::<ConsoleActor>(&self.console);
                 console_actor.streams.borrow_mut().pop();
+                registry
+                    .find::<InspectorActor>(&self.inspector)
+                    .handle_detach(registry);
                 stream.write_json_packet(&msg);
                 console_actor
                     .script_chan
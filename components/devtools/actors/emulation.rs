@@ -3,11 +3,41 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use crate::actor::{Actor, ActorMessageStatus, ActorRegistry};
+use crate::protocol::JsonPacketStream;
+use devtools_traits::DevtoolScriptControlMsg;
+use ipc_channel::ipc::IpcSender;
+use msg::constellation_msg::{NetworkConditions, PipelineId};
 use serde_json::{Map, Value};
+use std::cell::Cell;
 use std::net::TcpStream;
+use std::time::Duration;
 
 pub struct EmulationActor {
     pub name: String,
+    pub script_chan: IpcSender<DevtoolScriptControlMsg>,
+    pub pipeline: PipelineId,
+    /// The conditions that the network of the tab was made to behave like.
+    network_conditions: Cell<Option<NetworkConditions>>,
+}
+
+#[derive(Serialize)]
+struct NetworkThrottling {
+    downloadThroughput: u64,
+    uploadThroughput: u64,
+    latency: u64,
+    offline: bool,
+}
+
+#[derive(Serialize)]
+struct GetNetworkThrottlingReply {
+    from: String,
+    value: Option<NetworkThrottling>,
+}
+
+#[derive(Serialize)]
+struct ValueChangedReply {
+    from: String,
+    valueChanged: bool,
 }
 
 impl Actor for EmulationActor {
@@ -19,17 +49,97 @@ impl Actor for EmulationActor {
         &self,
         _registry: &ActorRegistry,
         msg_type: &str,
-        _msg: &Map<String, Value>,
-        _stream: &mut TcpStream,
+        msg: &Map<String, Value>,
+        stream: &mut TcpStream,
     ) -> Result<ActorMessageStatus, ()> {
         Ok(match msg_type {
+            "setNetworkThrottling" => {
+                let options = match msg.get("options").and_then(|options| options.as_object()) {
+                    Some(options) => options,
+                    None => return Err(()),
+                };
+                // Throughputs are in bytes per second, and latencies in milliseconds.
+                let throughput = |name: &str| {
+                    options
+                        .get(name)
+                        .and_then(|value| value.as_u64())
+                        .filter(|throughput| *throughput > 0)
+                };
+                let conditions = NetworkConditions {
+                    offline: options
+                        .get("offline")
+                        .and_then(|value| value.as_bool())
+                        .unwrap_or(false),
+                    latency: Duration::from_millis(
+                        options
+                            .get("latency")
+                            .and_then(|value| value.as_u64())
+                            .unwrap_or(0),
+                    ),
+                    download_throughput: throughput("downloadThroughput"),
+                    upload_throughput: throughput("uploadThroughput"),
+                };
+                let value_changed = self.set_network_conditions(Some(conditions));
+                let msg = ValueChangedReply {
+                    from: self.name(),
+                    valueChanged: value_changed,
+                };
+                stream.write_json_packet(&msg);
+                ActorMessageStatus::Processed
+            },
+            "getNetworkThrottling" => {
+                let value = self
+                    .network_conditions
+                    .get()
+                    .map(|conditions| NetworkThrottling {
+                        downloadThroughput: conditions.download_throughput.unwrap_or(0),
+                        uploadThroughput: conditions.upload_throughput.unwrap_or(0),
+                        latency: conditions.latency.as_millis() as u64,
+                        offline: conditions.offline,
+                    });
+                let msg = GetNetworkThrottlingReply {
+                    from: self.name(),
+                    value,
+                };
+                stream.write_json_packet(&msg);
+                ActorMessageStatus::Processed
+            },
+            "clearNetworkThrottling" => {
+                let value_changed = self.set_network_conditions(None);
+                let msg = ValueChangedReply {
+                    from: self.name(),
+                    valueChanged: value_changed,
+                };
+                stream.write_json_packet(&msg);
+                ActorMessageStatus::Processed
+            },
             _ => ActorMessageStatus::Ignored,
         })
     }
 }
 
 impl EmulationActor {
-    pub fn new(name: String) -> EmulationActor {
-        EmulationActor { name: name }
+    pub fn new(
+        name: String,
+        script_chan: IpcSender<DevtoolScriptControlMsg>,
+        pipeline: PipelineId,
+    ) -> EmulationActor {
+        EmulationActor {
+            name: name,
+            script_chan: script_chan,
+            pipeline: pipeline,
+            network_conditions: Cell::new(None),
+        }
+    }
+
+    /// Makes the network of the tab behave like under the given conditions, returning whether
+    /// they differ from the previous ones.
+    fn set_network_conditions(&self, conditions: Option<NetworkConditions>) -> bool {
+        if self.network_conditions.replace(conditions) == conditions {
+            return false;
+        }
+        let msg = DevtoolScriptControlMsg::SetNetworkConditions(self.pipeline, conditions);
+        self.script_chan.send(msg).unwrap();
+        true
     }
 }
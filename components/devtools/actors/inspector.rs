@@ -6,14 +6,21 @@
 //! (http://mxr.mozilla.org/mozilla-central/source/toolkit/devtools/server/actors/inspector.js).
 
 use crate::actor::{Actor, ActorMessageStatus, ActorRegistry};
+use crate::actors::stylesheets::{StyleSheetInfoToProtocol, StyleSheetMsg};
 use crate::protocol::JsonPacketStream;
+use devtools_traits::DevtoolScriptControlMsg::WantsDomMutations;
+use devtools_traits::DevtoolScriptControlMsg::{GetAppliedRules, GetComputedStyle, GetStyleSheets};
 use devtools_traits::DevtoolScriptControlMsg::{GetChildren, GetDocumentElement, GetRootNode};
-use devtools_traits::DevtoolScriptControlMsg::{GetLayout, ModifyAttribute};
-use devtools_traits::{ComputedNodeLayout, DevtoolScriptControlMsg, NodeInfo};
+use devtools_traits::DevtoolScriptControlMsg::{GetLayout, GetOuterHTML, ModifyAttribute};
+use devtools_traits::DevtoolScriptControlMsg::{ModifyRule, SetRuleText};
+use devtools_traits::DevtoolScriptControlMsg::{RemoveNode, SetNodeValue, SetOuterHTML};
+use devtools_traits::{ComputedNodeLayout, DevtoolScriptControlMsg, DomMutation, NodeInfo};
+use devtools_traits::{CssModification, CssRuleId};
 use ipc_channel::ipc::{self, IpcSender};
 use msg::constellation_msg::PipelineId;
 use serde_json::{self, Map, Value};
 use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::net::TcpStream;
 
 pub struct InspectorActor {
@@ -91,6 +98,11 @@ struct ModifyAttributeReply {
     from: String,
 }
 
+#[derive(Serialize)]
+struct SetNodeValueReply {
+    from: String,
+}
+
 impl Actor for NodeActor {
     fn name(&self) -> String {
         self.name.clone()
@@ -126,6 +138,21 @@ impl Actor for NodeActor {
                 ActorMessageStatus::Processed
             },
 
+            "setNodeValue" => {
+                let target = msg.get("to").unwrap().as_str().unwrap();
+                let value = msg.get("value").unwrap().as_str().unwrap();
+                self.script_chan
+                    .send(SetNodeValue(
+                        self.pipeline,
+                        registry.actor_to_script(target.to_owned()),
+                        value.to_owned(),
+                    ))
+                    .unwrap();
+                let reply = SetNodeValueReply { from: self.name() };
+                stream.write_json_packet(&reply);
+                ActorMessageStatus::Processed
+            },
+
             _ => ActorMessageStatus::Ignored,
         })
     }
@@ -247,10 +274,13 @@ impl NodeInfoToProtocol for NodeInfo {
     }
 }
 
-struct WalkerActor {
+pub struct WalkerActor {
     name: String,
     script_chan: IpcSender<DevtoolScriptControlMsg>,
     pipeline: PipelineId,
+    streams: RefCell<Vec<TcpStream>>,
+    /// The changes to the DOM tree that the client wasn't sent yet.
+    mutations: RefCell<Vec<DomMutation>>,
 }
 
 #[derive(Serialize)]
@@ -277,6 +307,133 @@ struct ChildrenReply {
     from: String,
 }
 
+#[derive(Serialize)]
+struct NewMutationsMsg {
+    from: String,
+    #[serde(rename = "type")]
+    type_: String,
+}
+
+#[derive(Serialize)]
+struct GetMutationsReply {
+    from: String,
+    mutations: Vec<MutationMsg>,
+}
+
+#[derive(Serialize)]
+#[serde(tag = "type")]
+enum MutationMsg {
+    #[serde(rename = "attributes")]
+    Attributes {
+        target: String,
+        attributeName: String,
+        attributeNamespace: String,
+        newValue: Option<String>,
+    },
+    #[serde(rename = "characterData")]
+    CharacterData { target: String, newValue: String },
+    #[serde(rename = "childList")]
+    ChildList {
+        target: String,
+        added: Vec<String>,
+        removed: Vec<String>,
+        numChildren: usize,
+    },
+}
+
+#[derive(Serialize)]
+struct RemoveNodeReply {
+    from: String,
+    nextSibling: Option<NodeActorMsg>,
+}
+
+#[derive(Serialize)]
+struct OuterHTMLReply {
+    from: String,
+    value: String,
+}
+
+#[derive(Serialize)]
+struct SetOuterHTMLReply {
+    from: String,
+}
+
+impl WalkerActor {
+    /// Queues the changes to the DOM tree of the nodes that the client knows about, and tells
+    /// the client that there are changes to get, unless it was told already.
+    pub(crate) fn handle_dom_mutations(
+        &self,
+        registry: &ActorRegistry,
+        mutations: Vec<DomMutation>,
+    ) {
+        let mut pending = self.mutations.borrow_mut();
+        let was_empty = pending.is_empty();
+        pending.extend(mutations.into_iter().filter(|mutation| {
+            let target = match *mutation {
+                DomMutation::Attribute { ref target, .. } |
+                DomMutation::CharacterData { ref target, .. } |
+                DomMutation::ChildList { ref target, .. } => target,
+            };
+            registry.script_actor_registered(target.clone())
+        }));
+        if !was_empty || pending.is_empty() {
+            return;
+        }
+        let msg = NewMutationsMsg {
+            from: self.name(),
+            type_: "newMutations".to_owned(),
+        };
+        for stream in &mut *self.streams.borrow_mut() {
+            stream.write_json_packet(&msg);
+        }
+    }
+
+    /// The changes to send to the client, as the actors of the nodes they involve. Added and
+    /// removed nodes that the client doesn't know about are left out, since it gets the
+    /// children of a node again when they change.
+    fn encode_mutations(&self, registry: &ActorRegistry) -> Vec<MutationMsg> {
+        let known_actors = |nodes: Vec<String>| -> Vec<String> {
+            nodes
+                .into_iter()
+                .filter(|node| registry.script_actor_registered(node.clone()))
+                .map(|node| registry.script_to_actor(node))
+                .collect()
+        };
+        self.mutations
+            .borrow_mut()
+            .drain(..)
+            .map(|mutation| match mutation {
+                DomMutation::Attribute {
+                    target,
+                    name,
+                    namespace,
+                    value,
+                } => MutationMsg::Attributes {
+                    target: registry.script_to_actor(target),
+                    attributeName: name,
+                    attributeNamespace: namespace,
+                    newValue: value,
+                },
+                DomMutation::CharacterData { target, value } => MutationMsg::CharacterData {
+                    target: registry.script_to_actor(target),
+                    newValue: value,
+                },
+                DomMutation::ChildList {
+                    target,
+                    added,
+                    removed,
+                    numChildren,
+                } => MutationMsg::ChildList {
+                    target: registry.script_to_actor(target),
+                    added: known_actors(added),
+                    removed: known_actors(removed),
+                    numChildren,
+                },
+            })
+            .collect()
+    }
+}
+
 impl Actor for WalkerActor {
     fn name(&self) -> String {
         self.name.clone()
@@ -346,6 +503,72 @@ impl Actor for WalkerActor {
                 ActorMessageStatus::Processed
             },
 
+            "getMutations" => {
+                let msg = GetMutationsReply {
+                    from: self.name(),
+                    mutations: self.encode_mutations(registry),
+                };
+                stream.write_json_packet(&msg);
+                ActorMessageStatus::Processed
+            },
+
+            "removeNode" => {
+                let target = msg.get("node").unwrap().as_str().unwrap();
+                let (tx, rx) = ipc::channel().unwrap();
+                self.script_chan
+                    .send(RemoveNode(
+                        self.pipeline,
+                        registry.actor_to_script(target.to_owned()),
+                        tx,
+                    ))
+                    .unwrap();
+                let next_sibling = rx.recv().unwrap();
+
+                let msg = RemoveNodeReply {
+                    from: self.name(),
+                    nextSibling: next_sibling.map(|sibling| {
+                        sibling.encode(registry, true, self.script_chan.clone(), self.pipeline)
+                    }),
+                };
+                stream.write_json_packet(&msg);
+                ActorMessageStatus::Processed
+            },
+
+            "outerHTML" => {
+                let target = msg.get("node").unwrap().as_str().unwrap();
+                let (tx, rx) = ipc::channel().unwrap();
+                self.script_chan
+                    .send(GetOuterHTML(
+                        self.pipeline,
+                        registry.actor_to_script(target.to_owned()),
+                        tx,
+                    ))
+                    .unwrap();
+                let html = rx.recv().unwrap().ok_or(())?;
+
+                let msg = OuterHTMLReply {
+                    from: self.name(),
+                    value: html,
+                };
+                stream.write_json_packet(&msg);
+                ActorMessageStatus::Processed
+            },
+
+            "setOuterHTML" => {
+                let target = msg.get("node").unwrap().as_str().unwrap();
+                let html = msg.get("value").unwrap().as_str().unwrap();
+                self.script_chan
+                    .send(SetOuterHTML(
+                        self.pipeline,
+                        registry.actor_to_script(target.to_owned()),
+                        html.to_owned(),
+                    ))
+                    .unwrap();
+                let msg = SetOuterHTMLReply { from: self.name() };
+                stream.write_json_packet(&msg);
+                ActorMessageStatus::Processed
+            },
+
             _ => ActorMessageStatus::Ignored,
         })
     }
@@ -366,22 +589,31 @@ struct PageStyleActor {
     name: String,
     script_chan: IpcSender<DevtoolScriptControlMsg>,
     pipeline: PipelineId,
+    /// The actors of the rules that the client was told about.
+    rules: RefCell<HashMap<CssRuleId, String>>,
 }
 
 #[derive(Serialize)]
 struct GetAppliedReply {
     entries: Vec<AppliedEntry>,
     rules: Vec<AppliedRule>,
-    sheets: Vec<AppliedSheet>,
+    sheets: Vec<StyleSheetMsg>,
     from: String,
 }
 
 #[derive(Serialize)]
 struct GetComputedReply {
-    computed: Vec<u32>, //XXX all css props
+    computed: BTreeMap<String, ComputedDeclaration>,
     from: String,
 }
 
+#[derive(Serialize)]
+struct ComputedDeclaration {
+    value: String,
+    priority: String,
+    matched: bool,
+}
+
 #[derive(Serialize)]
 struct AppliedEntry {
     rule: String,
@@ -390,29 +622,23 @@ struct AppliedEntry {
     matchedSelectors: Vec<String>,
 }
 
-#[derive(Serialize)]
+#[derive(Clone, Serialize)]
 struct AppliedRule {
     actor: String,
     #[serde(rename = "type")]
-    type_: String,
+    type_: u16,
     href: String,
     cssText: String,
     line: u32,
     column: u32,
-    parentStyleSheet: String,
+    parentStyleSheet: Option<String>,
+    selectors: Vec<String>,
 }
 
-#[derive(Serialize)]
-struct AppliedSheet {
-    actor: String,
-    href: String,
-    nodeHref: String,
-    disabled: bool,
-    title: String,
-    system: bool,
-    styleSheetIndex: isize,
-    ruleCount: usize,
-}
+/// The types of the rules that the client is told about: the `style` attribute of an element,
+/// and a style rule of a style sheet.
+const ELEMENT_STYLE: u16 = 100;
+const STYLE_RULE: u16 = 1;
 
 #[derive(Serialize)]
 struct GetLayoutReply {
@@ -473,11 +699,79 @@ impl Actor for PageStyleActor {
     ) -> Result<ActorMessageStatus, ()> {
         Ok(match msg_type {
             "getApplied" => {
-                //TODO: query script for relevant applied styles to node (msg.node)
+                let target = msg.get("node").unwrap().as_str().unwrap();
+                let (tx, rx) = ipc::channel().unwrap();
+                self.script_chan
+                    .send(GetAppliedRules(
+                        self.pipeline,
+                        registry.actor_to_script(target.to_owned()),
+                        tx,
+                    ))
+                    .unwrap();
+                let applied = rx.recv().unwrap().ok_or(())?;
+
+                let used_sheets: HashSet<usize> = applied
+                    .iter()
+                    .filter_map(|rule| match rule.id {
+                        CssRuleId::StyleRule(index, _) => Some(index),
+                        CssRuleId::ElementStyle(_) => None,
+                    })
+                    .collect();
+                let (tx, rx) = ipc::channel().unwrap();
+                self.script_chan
+                    .send(GetStyleSheets(self.pipeline, tx))
+                    .unwrap();
+                let mut sheet_actors = HashMap::new();
+                let sheets: Vec<_> = rx
+                    .recv()
+                    .unwrap()
+                    .into_iter()
+                    .filter(|sheet| used_sheets.contains(&sheet.index))
+                    .map(|sheet| {
+                        let index = sheet.index;
+                        let href = sheet.href.clone().unwrap_or_else(|| sheet.nodeHref.clone());
+                        let sheet = sheet.encode(registry, self.script_chan.clone(), self.pipeline);
+                        sheet_actors.insert(index, (sheet.actor.clone(), href));
+                        sheet
+                    })
+                    .collect();
+
+                let mut entries = vec![];
+                let mut rules = vec![];
+                for rule in applied {
+                    let (type_, href, parent_sheet) = match rule.id {
+                        CssRuleId::ElementStyle(_) => (ELEMENT_STYLE, String::new(), None),
+                        CssRuleId::StyleRule(index, _) => match sheet_actors.get(&index) {
+                            Some(&(ref actor, ref href)) => {
+                                (STYLE_RULE, href.clone(), Some(actor.clone()))
+                            },
+                            None => continue,
+                        },
+                    };
+                    let form = AppliedRule {
+                        actor: String::new(),
+                        type_: type_,
+                        href: href,
+                        cssText: rule.cssText,
+                        line: 0,
+                        column: 0,
+                        parentStyleSheet: parent_sheet,
+                        selectors: rule.selectors,
+                    };
+                    let form = self.rule_actor(registry, rule.id, form);
+                    entries.push(AppliedEntry {
+                        rule: form.actor.clone(),
+                        pseudoElement: Value::Null,
+                        isSystem: false,
+                        matchedSelectors: rule.matchedSelectors,
+                    });
+                    rules.push(form);
+                }
+
                 let msg = GetAppliedReply {
-                    entries: vec![],
-                    rules: vec![],
-                    sheets: vec![],
+                    entries: entries,
+                    rules: rules,
+                    sheets: sheets,
                     from: self.name(),
                 };
                 stream.write_json_packet(&msg);
@@ -485,9 +779,29 @@ impl Actor for PageStyleActor {
             },
 
             "getComputed" => {
-                //TODO: query script for relevant computed styles on node (msg.node)
+                let target = msg.get("node").unwrap().as_str().unwrap();
+                let (tx, rx) = ipc::channel().unwrap();
+                self.script_chan
+                    .send(GetComputedStyle(
+                        self.pipeline,
+                        registry.actor_to_script(target.to_owned()),
+                        tx,
+                    ))
+                    .unwrap();
+                let properties = rx.recv().unwrap().ok_or(())?;
+
                 let msg = GetComputedReply {
-                    computed: vec![],
+                    computed: properties
+                        .into_iter()
+                        .map(|property| {
+                            let declaration = ComputedDeclaration {
+                                value: property.value,
+                                priority: String::new(),
+                                matched: property.matched,
+                            };
+                            (property.name, declaration)
+                        })
+                        .collect(),
                     from: self.name(),
                 };
                 stream.write_json_packet(&msg);
@@ -584,6 +898,157 @@ impl Actor for PageStyleActor {
     }
 }
 
+impl PageStyleActor {
+    /// Gives `form`, the description of `rule` to send to the client, the actor of the rule,
+    /// which is created unless the client was told about the rule already.
+    fn rule_actor(
+        &self,
+        registry: &ActorRegistry,
+        rule: CssRuleId,
+        form: AppliedRule,
+    ) -> AppliedRule {
+        let existing = self.rules.borrow().get(&rule).cloned();
+        let name = existing
+            .clone()
+            .unwrap_or_else(|| registry.new_name("rule"));
+        let form = AppliedRule {
+            actor: name.clone(),
+            ..form
+        };
+        match existing {
+            Some(_) => *registry.find::<StyleRuleActor>(&name).form.borrow_mut() = form.clone(),
+            None => {
+                self.rules.borrow_mut().insert(rule.clone(), name.clone());
+                let rule_actor = StyleRuleActor {
+                    name: name,
+                    script_chan: self.script_chan.clone(),
+                    pipeline: self.pipeline,
+                    rule: rule,
+                    form: RefCell::new(form.clone()),
+                };
+                registry.register_later(Box::new(rule_actor));
+            },
+        }
+        form
+    }
+}
+
+struct StyleRuleActor {
+    name: String,
+    script_chan: IpcSender<DevtoolScriptControlMsg>,
+    pipeline: PipelineId,
+    rule: CssRuleId,
+    /// The description of the rule that the client was last sent.
+    form: RefCell<AppliedRule>,
+}
+
+#[derive(Serialize)]
+struct ModifyRuleReply {
+    from: String,
+    rule: AppliedRule,
+}
+
+impl StyleRuleActor {
+    /// Replies with the description of the rule, with its new declarations.
+    fn reply_with_rule(&self, css_text: String, stream: &mut TcpStream) {
+        let mut form = self.form.borrow_mut();
+        form.cssText = css_text;
+        let msg = ModifyRuleReply {
+            from: self.name(),
+            rule: form.clone(),
+        };
+        stream.write_json_packet(&msg);
+    }
+}
+
+impl Actor for StyleRuleActor {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn handle_message(
+        &self,
+        _registry: &ActorRegistry,
+        msg_type: &str,
+        msg: &Map<String, Value>,
+        stream: &mut TcpStream,
+    ) -> Result<ActorMessageStatus, ()> {
+        Ok(match msg_type {
+            "modifyProperties" => {
+                let mods = msg.get("modifications").unwrap().as_array().unwrap();
+                let string = |json_mod: &Value, key: &str| {
+                    json_mod
+                        .get(key)
+                        .and_then(Value::as_str)
+                        .unwrap_or("")
+                        .to_owned()
+                };
+                let modifications = mods
+                    .iter()
+                    .map(|json_mod| CssModification {
+                        name: string(json_mod, "name"),
+                        value: match json_mod.get("type").and_then(Value::as_str) {
+                            Some("remove") => None,
+                            _ => Some(string(json_mod, "value")),
+                        },
+                        priority: string(json_mod, "priority"),
+                    })
+                    .collect();
+
+                let (tx, rx) = ipc::channel().unwrap();
+                self.script_chan
+                    .send(ModifyRule(
+                        self.pipeline,
+                        self.rule.clone(),
+                        modifications,
+                        tx,
+                    ))
+                    .unwrap();
+                let css_text = rx.recv().unwrap().ok_or(())?;
+                self.reply_with_rule(css_text, stream);
+                ActorMessageStatus::Processed
+            },
+
+            "setRuleText" => {
+                let text = msg.get("newText").unwrap().as_str().unwrap();
+                let (tx, rx) = ipc::channel().unwrap();
+                self.script_chan
+                    .send(SetRuleText(
+                        self.pipeline,
+                        self.rule.clone(),
+                        text.to_owned(),
+                        tx,
+                    ))
+                    .unwrap();
+                let css_text = rx.recv().unwrap().ok_or(())?;
+                self.reply_with_rule(css_text, stream);
+                ActorMessageStatus::Processed
+            },
+
+            _ => ActorMessageStatus::Ignored,
+        })
+    }
+}
+
+impl InspectorActor {
+    /// Stops sending the changes to the DOM tree to a client that detached, and stops asking
+    /// script for them once no client is left.
+    pub(crate) fn handle_detach(&self, registry: &ActorRegistry) {
+        let walker = match *self.walker.borrow() {
+            Some(ref walker) => registry.find::<WalkerActor>(walker),
+            None => return,
+        };
+        let mut streams = walker.streams.borrow_mut();
+        streams.pop();
+        if streams.is_empty() {
+            walker.mutations.borrow_mut().clear();
+            self.script_chan
+                .send(WantsDomMutations(self.pipeline, false))
+                .unwrap();
+        }
+    }
+}
+
 impl Actor for InspectorActor {
     fn name(&self) -> String {
         self.name.clone()
@@ -598,16 +1063,26 @@ impl Actor for InspectorActor {
     ) -> Result<ActorMessageStatus, ()> {
         Ok(match msg_type {
             "getWalker" => {
+                let stream_clone = stream.try_clone().unwrap();
                 if self.walker.borrow().is_none() {
                     let walker = WalkerActor {
                         name: registry.new_name("walker"),
                         script_chan: self.script_chan.clone(),
                         pipeline: self.pipeline,
+                        streams: RefCell::new(vec![stream_clone]),
+                        mutations: RefCell::new(vec![]),
                     };
                     let mut walker_name = self.walker.borrow_mut();
                     *walker_name = Some(walker.name());
                     registry.register_later(Box::new(walker));
+                } else {
+                    let walker =
+                        registry.find::<WalkerActor>(self.walker.borrow().as_ref().unwrap());
+                    walker.streams.borrow_mut().push(stream_clone);
                 }
+                self.script_chan
+                    .send(WantsDomMutations(self.pipeline, true))
+                    .unwrap();
 
                 let (tx, rx) = ipc::channel().unwrap();
                 self.script_chan
@@ -635,6 +1110,7 @@ impl Actor for InspectorActor {
                         name: registry.new_name("pageStyle"),
                         script_chan: self.script_chan.clone(),
                         pipeline: self.pipeline,
+                        rules: RefCell::new(HashMap::new()),
                     };
                     let mut pageStyle = self.pageStyle.borrow_mut();
                     *pageStyle = Some(style.name());
@@ -4,17 +4,83 @@
 
 use crate::actor::{Actor, ActorMessageStatus, ActorRegistry};
 use crate::protocol::JsonPacketStream;
+use devtools_traits::DevtoolScriptControlMsg::{GetStyleSheetText, GetStyleSheets};
+use devtools_traits::DevtoolScriptControlMsg::{SetStyleSheetText, ToggleStyleSheetDisabled};
+use devtools_traits::{DevtoolScriptControlMsg, StyleSheetInfo};
+use ipc_channel::ipc::{self, IpcSender};
+use msg::constellation_msg::PipelineId;
 use serde_json::{Map, Value};
 use std::net::TcpStream;
 
 #[derive(Serialize)]
 struct GetStyleSheetsReply {
     from: String,
-    styleSheets: Vec<u32>, // TODO: real JSON structure.
+    styleSheets: Vec<StyleSheetMsg>,
+}
+
+#[derive(Serialize)]
+pub struct StyleSheetMsg {
+    pub actor: String,
+    href: Option<String>,
+    nodeHref: String,
+    disabled: bool,
+    title: String,
+    system: bool,
+    styleSheetIndex: isize,
+    ruleCount: usize,
+}
+
+pub trait StyleSheetInfoToProtocol {
+    fn encode(
+        self,
+        actors: &ActorRegistry,
+        script_chan: IpcSender<DevtoolScriptControlMsg>,
+        pipeline: PipelineId,
+    ) -> StyleSheetMsg;
+}
+
+impl StyleSheetInfoToProtocol for StyleSheetInfo {
+    fn encode(
+        self,
+        actors: &ActorRegistry,
+        script_chan: IpcSender<DevtoolScriptControlMsg>,
+        pipeline: PipelineId,
+    ) -> StyleSheetMsg {
+        // Style sheets are identified by their index in the document, which is unique across
+        // pipelines once prefixed with the pipeline.
+        let script_id = format!("{}-stylesheet-{}", pipeline, self.index);
+        let actor_name = if !actors.script_actor_registered(script_id.clone()) {
+            let name = actors.new_name("stylesheet");
+            let sheet_actor = StyleSheetActor {
+                name: name.clone(),
+                script_chan: script_chan,
+                pipeline: pipeline,
+                index: self.index,
+            };
+            actors.register_script_actor(script_id, name.clone());
+            actors.register_later(Box::new(sheet_actor));
+            name
+        } else {
+            actors.script_to_actor(script_id)
+        };
+
+        StyleSheetMsg {
+            actor: actor_name,
+            href: self.href,
+            nodeHref: self.nodeHref,
+            disabled: self.disabled,
+            title: self.title,
+            system: false,
+            styleSheetIndex: self.index as isize,
+            ruleCount: self.ruleCount,
+        }
+    }
 }
 
 pub struct StyleSheetsActor {
     pub name: String,
+    script_chan: IpcSender<DevtoolScriptControlMsg>,
+    pipeline: PipelineId,
 }
 
 impl Actor for StyleSheetsActor {
@@ -23,16 +89,27 @@ impl Actor for StyleSheetsActor {
     }
     fn handle_message(
         &self,
-        _registry: &ActorRegistry,
+        registry: &ActorRegistry,
         msg_type: &str,
         _msg: &Map<String, Value>,
         stream: &mut TcpStream,
     ) -> Result<ActorMessageStatus, ()> {
         Ok(match msg_type {
             "getStyleSheets" => {
+                let (tx, rx) = ipc::channel().unwrap();
+                self.script_chan
+                    .send(GetStyleSheets(self.pipeline, tx))
+                    .unwrap();
+                let sheets = rx.recv().unwrap();
+
                 let msg = GetStyleSheetsReply {
                     from: self.name(),
-                    styleSheets: vec![],
+                    styleSheets: sheets
+                        .into_iter()
+                        .map(|sheet| {
+                            sheet.encode(registry, self.script_chan.clone(), self.pipeline)
+                        })
+                        .collect(),
                 };
                 stream.write_json_packet(&msg);
                 ActorMessageStatus::Processed
@@ -44,7 +121,101 @@ impl Actor for StyleSheetsActor {
 }
 
 impl StyleSheetsActor {
-    pub fn new(name: String) -> StyleSheetsActor {
-        StyleSheetsActor { name: name }
+    pub fn new(
+        name: String,
+        script_chan: IpcSender<DevtoolScriptControlMsg>,
+        pipeline: PipelineId,
+    ) -> StyleSheetsActor {
+        StyleSheetsActor {
+            name: name,
+            script_chan: script_chan,
+            pipeline: pipeline,
+        }
+    }
+}
+
+struct StyleSheetActor {
+    name: String,
+    script_chan: IpcSender<DevtoolScriptControlMsg>,
+    pipeline: PipelineId,
+    index: usize,
+}
+
+#[derive(Serialize)]
+struct GetTextReply {
+    from: String,
+    text: String,
+}
+
+#[derive(Serialize)]
+struct UpdateReply {
+    from: String,
+}
+
+#[derive(Serialize)]
+struct ToggleDisabledReply {
+    from: String,
+    disabled: bool,
+}
+
+impl Actor for StyleSheetActor {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn handle_message(
+        &self,
+        _registry: &ActorRegistry,
+        msg_type: &str,
+        msg: &Map<String, Value>,
+        stream: &mut TcpStream,
+    ) -> Result<ActorMessageStatus, ()> {
+        Ok(match msg_type {
+            "getText" => {
+                let (tx, rx) = ipc::channel().unwrap();
+                self.script_chan
+                    .send(GetStyleSheetText(self.pipeline, self.index, tx))
+                    .unwrap();
+                let text = rx.recv().unwrap().ok_or(())?;
+
+                let msg = GetTextReply {
+                    from: self.name(),
+                    text: text,
+                };
+                stream.write_json_packet(&msg);
+                ActorMessageStatus::Processed
+            },
+
+            "update" => {
+                let text = msg.get("text").unwrap().as_str().unwrap();
+                self.script_chan
+                    .send(SetStyleSheetText(
+                        self.pipeline,
+                        self.index,
+                        text.to_owned(),
+                    ))
+                    .unwrap();
+                let msg = UpdateReply { from: self.name() };
+                stream.write_json_packet(&msg);
+                ActorMessageStatus::Processed
+            },
+
+            "toggleDisabled" => {
+                let (tx, rx) = ipc::channel().unwrap();
+                self.script_chan
+                    .send(ToggleStyleSheetDisabled(self.pipeline, self.index, tx))
+                    .unwrap();
+                let disabled = rx.recv().unwrap().ok_or(())?;
+
+                let msg = ToggleDisabledReply {
+                    from: self.name(),
+                    disabled: disabled,
+                };
+                stream.write_json_packet(&msg);
+                ActorMessageStatus::Processed
+            },
+
+            _ => ActorMessageStatus::Ignored,
+        })
     }
 }
@@ -23,7 +23,7 @@ use crate::actors::console::ConsoleActor;
 use crate::actors::device::DeviceActor;
 use crate::actors::emulation::EmulationActor;
 use crate::actors::framerate::FramerateActor;
-use crate::actors::inspector::InspectorActor;
+use crate::actors::inspector::{InspectorActor, WalkerActor};
 use crate::actors::network_event::{EventActor, NetworkEventActor, ResponseStartMsg};
 use crate::actors::performance::PerformanceActor;
 use crate::actors::preference::PreferenceActor;
@@ -37,7 +37,8 @@ use crate::actors::worker::WorkerActor;
 use crate::protocol::JsonPacketStream;
 use crossbeam_channel::{unbounded, Receiver, Sender};
 use devtools_traits::{ChromeToDevtoolsControlMsg, ConsoleMessage, DevtoolsControlMsg};
-use devtools_traits::{DevtoolScriptControlMsg, DevtoolsPageInfo, LogLevel, NetworkEvent};
+use devtools_traits::{DevtoolScriptControlMsg, DevtoolsPageInfo, DomMutation, LogLevel};
+use devtools_traits::NetworkEvent;
 use devtools_traits::{PageError, ScriptToDevtoolsControlMsg, WorkerId};
 use embedder_traits::{EmbedderMsg, EmbedderProxy, PromptDefinition, PromptOrigin, PromptResult};
 use ipc_channel::ipc::{self, IpcSender};
@@ -278,7 +279,11 @@ fn run_server(
                 cached_events: RefCell::new(Vec::new()),
             };
 
-            let emulation = EmulationActor::new(actors.new_name("emulation"));
+            let emulation = EmulationActor::new(
+                actors.new_name("emulation"),
+                script_sender.clone(),
+                pipeline,
+            );
 
             let inspector = InspectorActor {
                 name: actors.new_name("inspector"),
@@ -289,14 +294,19 @@ fn run_server(
                 pipeline: pipeline,
             };
 
-            let timeline = TimelineActor::new(actors.new_name("timeline"), pipeline, script_sender);
+            let timeline =
+                TimelineActor::new(actors.new_name("timeline"), pipeline, script_sender.clone());
 
             let profiler = ProfilerActor::new(actors.new_name("profiler"));
             let performance = PerformanceActor::new(actors.new_name("performance"));
 
             // the strange switch between styleSheets and stylesheets is due
             // to an inconsistency in devtools. See Bug #1498893 in bugzilla
-            let styleSheets = StyleSheetsActor::new(actors.new_name("stylesheets"));
+            let styleSheets = StyleSheetsActor::new(
+                actors.new_name("stylesheets"),
+                script_sender,
+                pipeline,
+            );
             let thread = ThreadActor::new(actors.new_name("context"));
 
             let DevtoolsPageInfo { title, url } = page_info;
@@ -391,6 +401,25 @@ fn run_server(
         console_actor.handle_console_api(console_message);
     }
 
+    fn handle_dom_mutations(
+        actors: Arc<Mutex<ActorRegistry>>,
+        id: PipelineId,
+        mutations: Vec<DomMutation>,
+        actor_pipelines: &HashMap<PipelineId, String>,
+    ) {
+        let actors = actors.lock().unwrap();
+        let actor_name = match actor_pipelines.get(&id) {
+            Some(name) => name,
+            None => return,
+        };
+        let inspector_name = &actors.find::<BrowsingContextActor>(actor_name).inspector;
+        let inspector = actors.find::<InspectorActor>(inspector_name);
+        if let Some(ref walker_name) = *inspector.walker.borrow() {
+            let walker = actors.find::<WalkerActor>(walker_name);
+            walker.handle_dom_mutations(&actors, mutations);
+        }
+    }
+
     fn find_console_actor(
         actors: Arc<Mutex<ActorRegistry>>,
         id: PipelineId,
@@ -630,6 +659,10 @@ fn run_server(
                 id,
                 page_error,
             )) => handle_page_error(actors.clone(), id, page_error, &actor_pipelines),
+            DevtoolsControlMsg::FromScript(ScriptToDevtoolsControlMsg::DomMutations(
+                id,
+                mutations,
+            )) => handle_dom_mutations(actors.clone(), id, mutations, &actor_pipelines),
             DevtoolsControlMsg::FromScript(ScriptToDevtoolsControlMsg::ReportCSSError(
                 id,
                 css_error,
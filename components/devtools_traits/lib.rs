@@ -21,7 +21,7 @@ extern crate serde;
 use http::method::Method;
 use http::HeaderMap;
 use ipc_channel::ipc::IpcSender;
-use msg::constellation_msg::PipelineId;
+use msg::constellation_msg::{NetworkConditions, PipelineId};
 use servo_url::ServoUrl;
 use std::net::TcpStream;
 use time::{self, Duration, Tm};
@@ -86,6 +86,9 @@ pub enum ScriptToDevtoolsControlMsg {
 
     /// Report a page error for the given pipeline
     ReportPageError(PipelineId, PageError),
+
+    /// Report changes to the DOM tree of the document of the given pipeline.
+    DomMutations(PipelineId, Vec<DomMutation>),
 }
 
 /// Serialized JS return values
@@ -129,6 +132,28 @@ pub struct NodeInfo {
     pub incompleteValue: bool,
 }
 
+/// A change to a node of a document, with the values that the node had once the task that
+/// made it was over.
+#[derive(Debug, Deserialize, Serialize)]
+pub enum DomMutation {
+    /// An attribute of an element was set, or removed if there is no value.
+    Attribute {
+        target: String,
+        name: String,
+        namespace: String,
+        value: Option<String>,
+    },
+    /// The data of a text, comment or processing instruction node was set.
+    CharacterData { target: String, value: String },
+    /// Nodes were added to or removed from the children of a node.
+    ChildList {
+        target: String,
+        added: Vec<String>,
+        removed: Vec<String>,
+        numChildren: usize,
+    },
+}
+
 pub struct StartedTimelineMarker {
     name: String,
     start_time: PreciseTime,
@@ -186,6 +211,53 @@ pub struct AutoMargins {
     pub left: bool,
 }
 
+/// The computed value of a CSS property of an element.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ComputedProperty {
+    pub name: String,
+    pub value: String,
+    /// Whether a rule that applies to the element declares the property.
+    pub matched: bool,
+}
+
+/// A style sheet of a document.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct StyleSheetInfo {
+    pub index: usize,
+    pub href: Option<String>,
+    pub nodeHref: String,
+    pub title: String,
+    pub disabled: bool,
+    pub ruleCount: usize,
+}
+
+/// A CSS rule whose declarations can be inspected and edited.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum CssRuleId {
+    /// The `style` attribute of the element with the given unique id.
+    ElementStyle(String),
+    /// A style rule, as the index of its style sheet in the document, followed by its index
+    /// in the rules of each of its ancestor grouping rules and in the rules of its parent.
+    StyleRule(usize, Vec<usize>),
+}
+
+/// A CSS rule that applies to an element.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct AppliedRuleInfo {
+    pub id: CssRuleId,
+    pub selectors: Vec<String>,
+    pub matchedSelectors: Vec<String>,
+    pub cssText: String,
+}
+
+/// A declaration to set in a CSS rule, or to remove if there is no value.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct CssModification {
+    pub name: String,
+    pub value: Option<String>,
+    pub priority: String,
+}
+
 /// Messages to process in a particular script thread, as instructed by a devtools client.
 /// TODO: better error handling, e.g. if pipeline id lookup fails?
 #[derive(Debug, Deserialize, Serialize)]
@@ -202,6 +274,40 @@ pub enum DevtoolScriptControlMsg {
     GetLayout(PipelineId, String, IpcSender<Option<ComputedNodeLayout>>),
     /// Update a given node's attributes with a list of modifications.
     ModifyAttribute(PipelineId, String, Vec<Modification>),
+    /// Set the value of a given text, comment or processing instruction node.
+    SetNodeValue(PipelineId, String, String),
+    /// Remove a given node from its parent, replying with the details of its next sibling.
+    RemoveNode(PipelineId, String, IpcSender<Option<NodeInfo>>),
+    /// Retrieve the serialization of a given element and its descendants.
+    GetOuterHTML(PipelineId, String, IpcSender<Option<String>>),
+    /// Replace a given element with the nodes parsed from the given markup.
+    SetOuterHTML(PipelineId, String, String),
+    /// Request changes to the DOM tree of a given pipeline (true if desired, false otherwise).
+    WantsDomMutations(PipelineId, bool),
+    /// Retrieve the details of the style sheets of the document of the given pipeline.
+    GetStyleSheets(PipelineId, IpcSender<Vec<StyleSheetInfo>>),
+    /// Retrieve the serialization of the rules of the style sheet with the given index.
+    GetStyleSheetText(PipelineId, usize, IpcSender<Option<String>>),
+    /// Replace the rules of the style sheet with the given index with the ones parsed from the
+    /// given text.
+    SetStyleSheetText(PipelineId, usize, String),
+    /// Enable or disable the style sheet with the given index, replying with whether it is
+    /// disabled now.
+    ToggleStyleSheetDisabled(PipelineId, usize, IpcSender<Option<bool>>),
+    /// Retrieve the rules that apply to the given element in the given pipeline, the ones that
+    /// win first.
+    GetAppliedRules(PipelineId, String, IpcSender<Option<Vec<AppliedRuleInfo>>>),
+    /// Retrieve the computed values of the CSS properties of the given element.
+    GetComputedStyle(PipelineId, String, IpcSender<Option<Vec<ComputedProperty>>>),
+    /// Set or remove declarations of the given rule, replying with its new declarations.
+    ModifyRule(
+        PipelineId,
+        CssRuleId,
+        Vec<CssModification>,
+        IpcSender<Option<String>>,
+    ),
+    /// Replace the declarations of the given rule, replying with its new declarations.
+    SetRuleText(PipelineId, CssRuleId, String, IpcSender<Option<String>>),
     /// Request live console messages for a given pipeline (true if desired, false otherwise).
     WantsLiveNotifications(PipelineId, bool),
     /// Request live notifications for a given set of timeline events for a given pipeline.
@@ -217,6 +323,9 @@ pub enum DevtoolScriptControlMsg {
     RequestAnimationFrame(PipelineId, String),
     /// Direct the given pipeline to reload the current page.
     Reload(PipelineId),
+    /// Make the network of the tab of the given pipeline behave like under the given
+    /// conditions, or like it really does.
+    SetNetworkConditions(PipelineId, Option<NetworkConditions>),
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -14,12 +14,16 @@ extern crate serde;
 pub mod resources;
 
 use crossbeam_channel::{Receiver, Sender};
-use ipc_channel::ipc::IpcSender;
+use http::{HeaderMap, Method, StatusCode};
+use ipc_channel::ipc::{IpcReceiver, IpcSender, IpcSharedMemory};
 use keyboard_types::KeyboardEvent;
 use msg::constellation_msg::{InputMethodType, PipelineId, TopLevelBrowsingContextId};
 use servo_url::ServoUrl;
+use std::collections::HashMap;
 use std::fmt::{Debug, Error, Formatter};
-use webrender_api::units::{DeviceIntPoint, DeviceIntSize};
+use std::path::PathBuf;
+use std::sync::Arc;
+use webrender_api::units::{DeviceIntPoint, DeviceIntRect, DeviceIntSize};
 
 pub use webxr_api::MainThreadWaker as EventLoopWaker;
 
@@ -173,6 +177,11 @@ pub enum EmbedderMsg {
     HistoryChanged(Vec<ServoUrl>, usize),
     /// Enter or exit fullscreen
     SetFullscreenState(bool),
+    /// Hide the cursor and capture the mouse, reporting its movements with
+    /// `WindowEvent::PointerLockMove`, or release it
+    SetPointerLock(bool),
+    /// Keep the screen on, inhibiting the screensaver and display sleep, or stop doing so
+    SetScreenWakeLock(bool),
     /// The load of a page has begun
     LoadStart,
     /// The load of a page has completed
@@ -191,6 +200,8 @@ pub enum EmbedderMsg {
     ShowIME(InputMethodType),
     /// Request to hide the IME when the editable element is blurred.
     HideIME,
+    /// Report where the caret of the element receiving IME input is, in device pixels.
+    IMECaretRect(DeviceIntRect),
     /// Servo has shut down
     Shutdown,
     /// Report a complete sampled profile
@@ -200,6 +211,64 @@ pub enum EmbedderMsg {
     MediaSessionEvent(MediaSessionEvent),
     /// Report the status of Devtools Server
     OnDevtoolsStarted(Result<u16, ()>),
+    /// Report the result of a find-in-page search: the index of the active match, if any,
+    /// and the total number of matches.
+    FindInPageResult(Option<usize>, usize),
+    /// Deliver the PDF file that a document was printed to by `window.print()`.
+    PrintDocument(Vec<u8>),
+    /// Report the main article of the document after `WindowEvent::ExtractArticle`, or `None`
+    /// if the document does not look like an article.
+    ReaderModeArticle(Option<ReaderArticle>),
+    /// Report the accessibility tree of the document after
+    /// `WindowEvent::RequestAccessibilityTree`.
+    AccessibilityTree(AccessibilityNode),
+    /// Report the form controls of the document that can be filled in for the user after
+    /// `WindowEvent::RequestFormFields`.
+    FormFields(Vec<FormField>),
+    /// The top-level document, whose origin is given, has a password field, so the password
+    /// manager can fill in the credentials saved for it with `WindowEvent::FillCredentials`.
+    PasswordFieldFound(String),
+    /// A form with a password was submitted, so the password manager can offer to save it.
+    LoginSubmitted(LoginSubmission),
+    /// The `overscroll-behavior` of the viewport of the top-level document has changed, along
+    /// the horizontal and the vertical axis.
+    OverscrollBehaviorChanged(OverscrollBehavior, OverscrollBehavior),
+    /// Show the share sheet of the platform for the given data, and report whether the user
+    /// shared it.
+    Share(ShareData, IpcSender<ShareResult>),
+    /// The certificate of a document failed to verify, and an error page is shown in its
+    /// place. The embedder answers with `WindowEvent::CertificateErrorResponse` to either trust
+    /// the certificate for the host and reload the document, or to give up on it.
+    CertificateError(CertificateError),
+    /// A server asked for a client certificate. The user picks the identity to authenticate
+    /// with, from those of the configured store or from elsewhere, or chooses to send none.
+    SelectClientCertificate(
+        ClientCertificateRequest,
+        IpcSender<Option<ClientIdentityChoice>>,
+    ),
+    /// A request is about to be fetched, which the embedder may let through, rewrite, block, or
+    /// respond to itself, whenever it is ready. Only sent when the
+    /// `network.request_interception.enabled` preference is set.
+    WebResourceRequested(WebResourceRequest, IpcSender<WebResourceResponseMsg>),
+    /// A site asks to handle the navigations to the URLs of a scheme. The embedder answers
+    /// whether the user allows it, in which case the handler replaces the one that the user
+    /// allowed before for the scheme, if any.
+    RegisterProtocolHandler(ProtocolHandlerRequest, IpcSender<bool>),
+    /// A response is to be saved to a file instead of being rendered. The embedder answers with
+    /// the path to save it to, which the user may pick starting from the suggested one in the
+    /// downloads directory, or with none to not download it. An existing file is never
+    /// overwritten; a number is added to the name of the file instead.
+    AllowDownload(DownloadId, ServoUrl, PathBuf, IpcSender<Option<PathBuf>>),
+    /// A response is being saved to a file instead of being rendered, or the download of it
+    /// was paused, resumed, finished or stopped.
+    Download(DownloadId, DownloadEvent),
+    /// Show a video in a floating window that stays above the other windows, with the frames
+    /// that are sent for it, until the sender of the frames hangs up.
+    EnterPictureInPicture(PictureInPictureRequest),
+    /// Show a context menu for what the user pressed the secondary button on, unless the page
+    /// canceled its `contextmenu` event, and report the action that the user picked in it that
+    /// the page carries out, if any.
+    ShowContextMenu(ContextMenu, IpcSender<Option<ContextMenuAction>>),
 }
 
 impl Debug for EmbedderMsg {
@@ -221,6 +290,8 @@ impl Debug for EmbedderMsg {
             EmbedderMsg::CloseBrowser => write!(f, "CloseBrowser"),
             EmbedderMsg::HistoryChanged(..) => write!(f, "HistoryChanged"),
             EmbedderMsg::SetFullscreenState(..) => write!(f, "SetFullscreenState"),
+            EmbedderMsg::SetPointerLock(..) => write!(f, "SetPointerLock"),
+            EmbedderMsg::SetScreenWakeLock(..) => write!(f, "SetScreenWakeLock"),
             EmbedderMsg::LoadStart => write!(f, "LoadStart"),
             EmbedderMsg::LoadComplete => write!(f, "LoadComplete"),
             EmbedderMsg::Panic(..) => write!(f, "Panic"),
@@ -229,16 +300,220 @@ impl Debug for EmbedderMsg {
             EmbedderMsg::PromptPermission(..) => write!(f, "PromptPermission"),
             EmbedderMsg::ShowIME(..) => write!(f, "ShowIME"),
             EmbedderMsg::HideIME => write!(f, "HideIME"),
+            EmbedderMsg::IMECaretRect(..) => write!(f, "IMECaretRect"),
             EmbedderMsg::Shutdown => write!(f, "Shutdown"),
             EmbedderMsg::AllowOpeningBrowser(..) => write!(f, "AllowOpeningBrowser"),
             EmbedderMsg::BrowserCreated(..) => write!(f, "BrowserCreated"),
             EmbedderMsg::ReportProfile(..) => write!(f, "ReportProfile"),
             EmbedderMsg::MediaSessionEvent(..) => write!(f, "MediaSessionEvent"),
             EmbedderMsg::OnDevtoolsStarted(..) => write!(f, "OnDevtoolsStarted"),
+            EmbedderMsg::FindInPageResult(..) => write!(f, "FindInPageResult"),
+            EmbedderMsg::PrintDocument(..) => write!(f, "PrintDocument"),
+            EmbedderMsg::ReaderModeArticle(..) => write!(f, "ReaderModeArticle"),
+            EmbedderMsg::AccessibilityTree(..) => write!(f, "AccessibilityTree"),
+            EmbedderMsg::FormFields(..) => write!(f, "FormFields"),
+            EmbedderMsg::PasswordFieldFound(..) => write!(f, "PasswordFieldFound"),
+            EmbedderMsg::LoginSubmitted(..) => write!(f, "LoginSubmitted"),
+            EmbedderMsg::OverscrollBehaviorChanged(..) => write!(f, "OverscrollBehaviorChanged"),
+            EmbedderMsg::Share(..) => write!(f, "Share"),
+            EmbedderMsg::CertificateError(..) => write!(f, "CertificateError"),
+            EmbedderMsg::SelectClientCertificate(..) => write!(f, "SelectClientCertificate"),
+            EmbedderMsg::WebResourceRequested(..) => write!(f, "WebResourceRequested"),
+            EmbedderMsg::RegisterProtocolHandler(..) => write!(f, "RegisterProtocolHandler"),
+            EmbedderMsg::AllowDownload(..) => write!(f, "AllowDownload"),
+            EmbedderMsg::Download(..) => write!(f, "Download"),
+            EmbedderMsg::EnterPictureInPicture(..) => write!(f, "EnterPictureInPicture"),
+            EmbedderMsg::ShowContextMenu(..) => write!(f, "ShowContextMenu"),
         }
     }
 }
 
+/// What happens when a scroll reaches the edge of a scroll container, as the
+/// `overscroll-behavior` property asks for.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum OverscrollBehavior {
+    /// The scroll chains to the ancestors of the scroll container, and the embedder may show
+    /// its own overscroll effects, such as pull-to-refresh or swipe navigation.
+    Auto,
+    /// The scroll doesn't chain, and the embedder only shows effects local to the scroll
+    /// container, such as a bounce or a glow.
+    Contain,
+    /// The scroll doesn't chain, and the embedder shows no overscroll effects.
+    None,
+}
+
+/// Options for a find-in-page search.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct FindOptions {
+    /// Only match text with the same case as the query.
+    pub case_sensitive: bool,
+    /// Move to the previous match instead of the next one when repeating a search.
+    pub backwards: bool,
+}
+
+/// The role of a node of an accessibility tree, which tells assistive technology what kind of
+/// object it is. These are the WAI-ARIA roles that Servo exposes, named as in the
+/// specification, which platform accessibility APIs have equivalents for.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum AccessibilityRole {
+    Alert,
+    Article,
+    Banner,
+    Button,
+    Cell,
+    CheckBox,
+    ColumnHeader,
+    ComboBox,
+    Complementary,
+    ContentInfo,
+    Dialog,
+    Document,
+    Figure,
+    Form,
+    Group,
+    Heading,
+    Iframe,
+    Image,
+    Link,
+    List,
+    ListBox,
+    ListItem,
+    Main,
+    Menu,
+    MenuBar,
+    MenuItem,
+    Meter,
+    Navigation,
+    Option,
+    Paragraph,
+    ProgressBar,
+    RadioButton,
+    Region,
+    Row,
+    RowHeader,
+    SearchBox,
+    Separator,
+    Slider,
+    SpinButton,
+    StaticText,
+    Status,
+    Switch,
+    Tab,
+    TabList,
+    TabPanel,
+    Table,
+    TextBox,
+    Toolbar,
+    Tooltip,
+    Tree,
+    TreeItem,
+}
+
+/// The states of a node of an accessibility tree.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct AccessibilityStates {
+    pub focusable: bool,
+    pub focused: bool,
+    pub disabled: bool,
+    pub readonly: bool,
+    pub required: bool,
+    pub selected: bool,
+    /// Whether a check box, radio button or switch is checked, if the node can be checked.
+    pub checked: Option<bool>,
+    /// Whether the content that the node controls is expanded, if it can be collapsed.
+    pub expanded: Option<bool>,
+}
+
+/// A node of the accessibility tree of a document.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct AccessibilityNode {
+    pub role: AccessibilityRole,
+    /// The accessible name of the node, such as the label of a control or the alternative
+    /// text of an image.
+    pub name: String,
+    pub description: String,
+    /// The current value of a control.
+    pub value: Option<String>,
+    /// The level of a heading or of an item of a tree.
+    pub level: Option<u32>,
+    pub states: AccessibilityStates,
+    /// The bounding box of the node in device pixels, relative to the viewport, if the node is
+    /// rendered.
+    pub bounds: Option<DeviceIntRect>,
+    pub children: Vec<AccessibilityNode>,
+}
+
+/// The main article of a document, extracted for a reader view.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ReaderArticle {
+    /// The title of the article, without the name of the site.
+    pub title: String,
+    /// The author of the article, if the document names one.
+    pub byline: Option<String>,
+    /// The content of the article as simple HTML, without scripts and with absolute URLs.
+    pub content: String,
+    /// The number of characters of text in the article.
+    pub length: usize,
+}
+
+/// A form control that the embedder can fill in for the user, such as with a saved address.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct FormField {
+    /// Identifies the control in `WindowEvent::FillFormFields`.
+    pub id: String,
+    /// The index of the form of the control among the forms of the document, or `None` if the
+    /// control is not in a form.
+    pub form: Option<usize>,
+    /// The type of the control as reported by its `type` attribute in script, such as
+    /// `email`, `select-one` or `textarea`.
+    pub type_: String,
+    /// The `name` attribute of the control.
+    pub name: String,
+    /// The label of the control, or its title or placeholder if it has none.
+    pub label: String,
+    /// The tokens of the `autocomplete` attribute of the control, in lowercase.
+    pub autocomplete: Vec<String>,
+    /// The autofill field name of the control, such as `email` or `postal-code`, from its
+    /// `autocomplete` attribute, or else guessed from its type, name, identifier and label.
+    /// It is `None` if autofilling is turned off or the guess failed.
+    /// <https://html.spec.whatwg.org/multipage/#autofill-field-name>
+    pub field_name: Option<String>,
+    /// The current value of the control.
+    pub value: String,
+    /// The values of the options of a select element.
+    pub options: Vec<String>,
+    /// The maximum length of the value, if the control has one.
+    pub max_length: Option<u32>,
+}
+
+/// The credentials of a submitted login form.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct LoginSubmission {
+    /// The origin of the document of the form, which the credentials are saved for.
+    pub origin: String,
+    /// The origin that the form was submitted to.
+    pub action_origin: String,
+    /// The username, if the form has a field for it that isn't empty.
+    pub username: Option<String>,
+    /// The password, which is only included if the `dom.password_manager.include_passwords`
+    /// preference is set.
+    pub password: Option<String>,
+    /// Whether the form sets a new password, such as to sign up or to change the password, in
+    /// which case `password` is the new one.
+    pub new_password: bool,
+}
+
+/// The credentials that the password manager saved for an origin, to fill in with
+/// `WindowEvent::FillCredentials`.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Credentials {
+    /// The origin that the credentials were saved for, as serialized by
+    /// `EmbedderMsg::PasswordFieldFound`. They are only filled in a document of that origin.
+    pub origin: String,
+    pub username: String,
+    pub password: String,
+}
+
 /// Filter for file selection;
 /// the `String` content is expected to be extension (e.g, "doc", without the prefixing ".")
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -253,6 +528,9 @@ pub struct MediaMetadata {
     pub artist: String,
     /// Album
     pub album: String,
+    /// Images of the media, like the cover of the album, from which the controls of the
+    /// platform pick the one that suits them best.
+    pub artwork: Vec<MediaImage>,
 }
 
 impl MediaMetadata {
@@ -261,10 +539,22 @@ impl MediaMetadata {
             title,
             artist: "".to_owned(),
             album: "".to_owned(),
+            artwork: vec![],
         }
     }
 }
 
+/// https://w3c.github.io/mediasession/#dictdef-mediaimage
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct MediaImage {
+    pub src: ServoUrl,
+    /// The sizes of the image, as in the `sizes` attribute of a link, or an empty string if
+    /// they are unknown.
+    pub sizes: String,
+    /// The MIME type of the image, or an empty string if it is unknown.
+    pub type_: String,
+}
+
 /// https://w3c.github.io/mediasession/#enumdef-mediasessionplaybackstate
 #[repr(i32)]
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -304,6 +594,81 @@ pub enum MediaSessionEvent {
     PlaybackStateChange(MediaSessionPlaybackState),
     /// Indicates that the position state is set.
     SetPositionState(MediaPositionState),
+    /// Indicates the actions that the page handles, which the controls of the platform should
+    /// offer, besides the ones that the media elements handle by default.
+    SetActionHandlers(Vec<MediaSessionActionType>),
+}
+
+/// The type of MediaSession action.
+/// https://w3c.github.io/mediasession/#enumdef-mediasessionaction
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum MediaSessionActionType {
+    /// The action intent is to resume playback.
+    Play,
+    /// The action intent is to pause the currently active playback.
+    Pause,
+    /// The action intent is to move the playback time backward by a short period (i.e. a few
+    /// seconds).
+    SeekBackward,
+    /// The action intent is to move the playback time forward by a short period (i.e. a few
+    /// seconds).
+    SeekForward,
+    /// The action intent is to either start the current playback from the beginning if the
+    /// playback has a notion, of beginning, or move to the previous item in the playlist if the
+    /// playback has a notion of playlist.
+    PreviousTrack,
+    /// The action is to move to the playback to the next item in the playlist if the playback has
+    /// a notion of playlist.
+    NextTrack,
+    /// The action intent is to skip the advertisement that is currently playing.
+    SkipAd,
+    /// The action intent is to stop the playback and clear the state if appropriate.
+    Stop,
+    /// The action intent is to move the playback time to a specific time.
+    SeekTo,
+}
+
+impl From<i32> for MediaSessionActionType {
+    fn from(value: i32) -> MediaSessionActionType {
+        match value {
+            1 => MediaSessionActionType::Play,
+            2 => MediaSessionActionType::Pause,
+            3 => MediaSessionActionType::SeekBackward,
+            4 => MediaSessionActionType::SeekForward,
+            5 => MediaSessionActionType::PreviousTrack,
+            6 => MediaSessionActionType::NextTrack,
+            7 => MediaSessionActionType::SkipAd,
+            8 => MediaSessionActionType::Stop,
+            9 => MediaSessionActionType::SeekTo,
+            _ => panic!("Unknown MediaSessionActionType"),
+        }
+    }
+}
+
+/// A media session action that the user took with the controls of the platform, and its
+/// details.
+/// <https://w3c.github.io/mediasession/#dictdef-mediasessionactiondetails>
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct MediaSessionActionDetails {
+    pub action: MediaSessionActionType,
+    /// How far to move the playback time for `SeekBackward` and `SeekForward`, in seconds,
+    /// when it is not up to the page.
+    pub seek_offset: Option<f64>,
+    /// The playback time to move to for `SeekTo`, in seconds.
+    pub seek_time: Option<f64>,
+    /// Whether a `SeekTo` is one of a sequence, as while the user scrubs.
+    pub fast_seek: Option<bool>,
+}
+
+impl From<MediaSessionActionType> for MediaSessionActionDetails {
+    fn from(action: MediaSessionActionType) -> MediaSessionActionDetails {
+        MediaSessionActionDetails {
+            action,
+            seek_offset: None,
+            seek_time: None,
+            fast_seek: None,
+        }
+    }
 }
 
 /// Enum with variants that match the DOM PermissionName enum
@@ -320,6 +685,10 @@ pub enum PermissionName {
     BackgroundSync,
     Bluetooth,
     PersistentStorage,
+    Accelerometer,
+    Gyroscope,
+    Magnetometer,
+    IdleDetection,
 }
 
 /// Information required to display a permission prompt
@@ -335,3 +704,638 @@ pub enum PermissionRequest {
     Granted,
     Denied,
 }
+
+/// The state of the battery of the device.
+/// <https://w3c.github.io/battery/#the-batterymanager-interface>
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub struct BatteryStatus {
+    pub charging: bool,
+    /// The seconds until the battery is fully charged, or infinity if it is discharging.
+    pub charging_time: f64,
+    /// The seconds until the battery is empty, or infinity if it is charging.
+    pub discharging_time: f64,
+    /// The charge of the battery, between 0 and 1.
+    pub level: f64,
+}
+
+impl Default for BatteryStatus {
+    /// The state reported when the embedder doesn't know the state of the battery, which is
+    /// the one of a fully charged battery.
+    /// <https://w3c.github.io/battery/#internal-slots-0>
+    fn default() -> Self {
+        BatteryStatus {
+            charging: true,
+            charging_time: 0.,
+            discharging_time: f64::INFINITY,
+            level: 1.,
+        }
+    }
+}
+
+/// https://wicg.github.io/netinfo/#effectiveconnectiontype-enum
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum EffectiveConnectionType {
+    Slow2G,
+    TwoG,
+    ThreeG,
+    FourG,
+}
+
+/// The state of the network connection of the device.
+/// <https://wicg.github.io/netinfo/#networkinformation-interface>
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub struct NetworkInformation {
+    pub effective_type: EffectiveConnectionType,
+    /// The effective bandwidth, in megabits per second.
+    pub downlink: f64,
+    /// The effective round-trip time, in milliseconds.
+    pub rtt: u64,
+    /// Whether the user asked for a reduced data usage.
+    pub save_data: bool,
+}
+
+impl Default for NetworkInformation {
+    fn default() -> Self {
+        NetworkInformation {
+            effective_type: EffectiveConnectionType::FourG,
+            downlink: 10.,
+            rtt: 50,
+            save_data: false,
+        }
+    }
+}
+
+/// Supplies the state of the device that pages can query with `navigator.getBattery()` and
+/// `navigator.connection`. Embedders implement it with the data of the platform, or with fixed
+/// values for testing, and send `WindowEvent::DeviceStatusChanged` when the data changes.
+pub trait DeviceStatusProvider: Send {
+    fn battery_status(&self) -> BatteryStatus {
+        BatteryStatus::default()
+    }
+
+    fn network_information(&self) -> NetworkInformation {
+        NetworkInformation::default()
+    }
+}
+
+/// A position of the device.
+/// <https://w3c.github.io/geolocation-api/#coordinates_interface>
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub struct GeolocationPosition {
+    /// The latitude and longitude, in decimal degrees.
+    pub latitude: f64,
+    pub longitude: f64,
+    /// The accuracy of the latitude and longitude, in meters.
+    pub accuracy: f64,
+    /// The height above the WGS84 ellipsoid, in meters.
+    pub altitude: Option<f64>,
+    pub altitude_accuracy: Option<f64>,
+    /// The direction of travel, in degrees clockwise from the true north.
+    pub heading: Option<f64>,
+    /// The horizontal speed, in meters per second.
+    pub speed: Option<f64>,
+    /// When the position was acquired, in milliseconds since the Unix epoch.
+    pub timestamp: u64,
+}
+
+/// Supplies the positions that pages request with `navigator.geolocation`. Servo starts the
+/// provider while any page is waiting for a position, and stops it once none does. The
+/// embedder reports the positions it acquires with `WindowEvent::PositionChanged`.
+pub trait PositionProvider: Send {
+    /// Starts acquiring the position of the device, as precisely as possible if
+    /// `high_accuracy` is set. This is called again when the accuracy requested changes.
+    fn start(&mut self, high_accuracy: bool);
+
+    /// Stops acquiring the position of the device.
+    fn stop(&mut self);
+}
+
+/// The sensors of the device that pages can listen to.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum DeviceSensor {
+    /// The orientation relative to an arbitrary frame, for `deviceorientation` events.
+    Orientation,
+    /// The orientation relative to the Earth, for `deviceorientationabsolute` events.
+    AbsoluteOrientation,
+    /// The acceleration and the rotation rate, for `devicemotion` events.
+    Motion,
+}
+
+/// An orientation of the device, in degrees.
+/// <https://w3c.github.io/deviceorientation/#deviceorientation>
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub struct DeviceOrientation {
+    /// The rotation around the Z axis, between 0 and 360.
+    pub alpha: Option<f64>,
+    /// The rotation around the X axis, between -180 and 180.
+    pub beta: Option<f64>,
+    /// The rotation around the Y axis, between -90 and 90.
+    pub gamma: Option<f64>,
+    /// Whether the orientation is relative to the Earth rather than to an arbitrary frame.
+    pub absolute: bool,
+}
+
+/// An acceleration of the device along its axes, in meters per second squared.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub struct DeviceAcceleration {
+    pub x: Option<f64>,
+    pub y: Option<f64>,
+    pub z: Option<f64>,
+}
+
+/// A rate of rotation of the device around its axes, in degrees per second.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub struct DeviceRotationRate {
+    pub alpha: Option<f64>,
+    pub beta: Option<f64>,
+    pub gamma: Option<f64>,
+}
+
+/// A motion of the device.
+/// <https://w3c.github.io/deviceorientation/#devicemotion>
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub struct DeviceMotion {
+    /// The acceleration without the effect of gravity, if the device can tell it apart.
+    pub acceleration: Option<DeviceAcceleration>,
+    pub acceleration_including_gravity: Option<DeviceAcceleration>,
+    pub rotation_rate: Option<DeviceRotationRate>,
+    /// The milliseconds between two readings of the sensors.
+    pub interval: f64,
+}
+
+/// A reading of the sensors of the device.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub enum SensorReading {
+    Orientation(DeviceOrientation),
+    Motion(DeviceMotion),
+}
+
+impl SensorReading {
+    /// The sensor that the reading comes from.
+    pub fn sensor(&self) -> DeviceSensor {
+        match *self {
+            SensorReading::Orientation(DeviceOrientation { absolute: true, .. }) => {
+                DeviceSensor::AbsoluteOrientation
+            },
+            SensorReading::Orientation(_) => DeviceSensor::Orientation,
+            SensorReading::Motion(_) => DeviceSensor::Motion,
+        }
+    }
+}
+
+/// Supplies the readings of the orientation and motion sensors of the device that pages listen
+/// to. Servo starts each sensor while any page listens to it, and stops it once none does. The
+/// embedder reports the readings with `WindowEvent::SensorReading`.
+pub trait SensorProvider: Send {
+    /// Starts reading the given sensor.
+    fn start(&mut self, sensor: DeviceSensor);
+
+    /// Stops reading the given sensor.
+    fn stop(&mut self, sensor: DeviceSensor);
+}
+
+/// A voice that utterances can be spoken with.
+/// <https://wicg.github.io/speech-api/#speechsynthesisvoice>
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct SpeechVoice {
+    /// The identifier of the voice, which pages pass back to pick it.
+    pub voice_uri: String,
+    /// The name of the voice, to show to the user.
+    pub name: String,
+    /// The BCP 47 language tag of the language that the voice speaks.
+    pub lang: String,
+    /// Whether the voice is synthesized on the device rather than by a remote service.
+    pub local_service: bool,
+    /// Whether this is the voice that utterances are spoken with when they don't pick one.
+    pub default: bool,
+}
+
+/// Text to speak, and how to speak it.
+/// <https://wicg.github.io/speech-api/#speechsynthesisutterance>
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct SpeechUtterance {
+    pub text: String,
+    /// The BCP 47 language tag of the language of the text, or an empty string if the
+    /// language of the document is unknown.
+    pub lang: String,
+    /// The `voice_uri` of the voice that the page picked, if any.
+    pub voice_uri: Option<String>,
+    /// The volume, between 0 and 1.
+    pub volume: f32,
+    /// The speed, between 0.1 and 10 times the default one.
+    pub rate: f32,
+    /// The pitch, between 0 and 2, where 1 is the default one.
+    pub pitch: f32,
+}
+
+/// Where in the text of an utterance a boundary event was reached.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub enum SpeechBoundary {
+    Word,
+    Sentence,
+}
+
+/// Why an utterance could not be spoken to the end.
+/// <https://wicg.github.io/speech-api/#enumdef-speechsynthesiserrorcode>
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub enum SpeechError {
+    Interrupted,
+    AudioBusy,
+    AudioHardware,
+    Network,
+    SynthesisUnavailable,
+    SynthesisFailed,
+    LanguageUnavailable,
+    VoiceUnavailable,
+    TextTooLong,
+    InvalidArgument,
+    NotAllowed,
+}
+
+/// The progress of an utterance that is spoken.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub enum SpeechEvent {
+    /// The utterance began to be spoken.
+    Start,
+    /// A word or a sentence begins, at the given character index and length in the text.
+    Boundary(SpeechBoundary, u32, u32),
+    /// Speaking was paused.
+    Pause,
+    /// Speaking resumed.
+    Resume,
+    /// The utterance was spoken to the end.
+    End,
+    /// The utterance could not be spoken to the end.
+    Error(SpeechError),
+}
+
+/// Speaks the utterances that pages pass to `speechSynthesis.speak()`, with the voices of the
+/// platform. Servo asks for one utterance at a time, once the previous one of the page ended,
+/// but the utterance of another page may come while one is spoken, and interrupts it.
+pub trait SpeechSynthesisProvider: Send {
+    /// The voices that utterances can be spoken with.
+    fn voices(&mut self) -> Vec<SpeechVoice>;
+
+    /// Starts speaking an utterance, and reports its progress on `events` until it ends or
+    /// fails.
+    fn speak(&mut self, utterance: SpeechUtterance, events: IpcSender<SpeechEvent>);
+
+    /// Pauses speaking, in the middle of an utterance or before the next one.
+    fn pause(&mut self);
+
+    /// Resumes speaking after `pause`.
+    fn resume(&mut self);
+
+    /// Stops speaking the utterance being spoken, if any, without reporting anything more
+    /// about it.
+    fn cancel(&mut self);
+}
+
+/// What a context menu is shown for, from which embedders build their native menus.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct ContextMenu {
+    /// The URL of the link that was pressed, if any.
+    pub link_url: Option<ServoUrl>,
+    /// The URL of the image that was pressed, if any.
+    pub image_url: Option<ServoUrl>,
+    /// The text that is selected in the page, which may be empty.
+    pub selected_text: String,
+    /// Whether a text control or editable content was pressed, so that the menu can offer
+    /// to edit it.
+    pub editable: bool,
+    /// The misspelled word that was pressed, if any.
+    pub misspelled_word: Option<String>,
+    /// The corrections to suggest for the misspelled word, the most likely first.
+    pub spelling_suggestions: Vec<String>,
+}
+
+/// An action picked in a context menu that the page carries out. The other actions, such as
+/// opening the link or copying the selected text, are carried out by the embedder.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub enum ContextMenuAction {
+    /// Replace the misspelled word with one of the suggestions, or with any other word.
+    ReplaceMisspelling(String),
+    /// Add the misspelled word to the dictionary, so that it isn't flagged again.
+    AddToDictionary,
+}
+
+/// Checks the spelling of the words that users type into editable text. Words are checked
+/// for the BCP 47 language tag of the text they are in, which is empty when the language is
+/// unknown, and the checker is expected to use its default dictionary then.
+pub trait SpellCheckProvider: Send {
+    /// Tells, for each of the given words, whether it is misspelled.
+    fn check(&mut self, language: &str, words: Vec<String>) -> Vec<bool>;
+
+    /// The corrections to suggest for a misspelled word, the most likely first.
+    fn suggestions(&mut self, language: &str, word: &str) -> Vec<String>;
+
+    /// Adds a word to the personal dictionary of the user.
+    fn add_to_dictionary(&mut self, word: String);
+}
+
+/// Identifies a certificate that failed to verify, which the network stack keeps until the
+/// user decides whether to trust it.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub struct CertificateErrorId(pub usize);
+
+/// A certificate that failed to verify, with the details that the user needs to decide
+/// whether to trust it anyway.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct CertificateError {
+    pub id: CertificateErrorId,
+    /// The host that presented the certificate.
+    pub host: String,
+    /// Why the certificate failed to verify, such as that it is self-signed or expired.
+    pub reason: String,
+    /// The distinguished names of the subject and of the issuer of the certificate.
+    pub subject: String,
+    pub issuer: String,
+    /// The start and the end of the validity period of the certificate.
+    pub not_before: String,
+    pub not_after: String,
+    /// The SHA-256 fingerprint of the certificate, as colon-separated hex bytes.
+    pub fingerprint: String,
+}
+
+/// A request of a server for a client certificate, with the identities of the configured
+/// store that the user can pick from.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ClientCertificateRequest {
+    /// The host that asked for a certificate.
+    pub host: String,
+    pub identities: Vec<ClientIdentityInfo>,
+}
+
+/// The details of an identity that the user can authenticate with.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ClientIdentityInfo {
+    /// The name of the identity in its store.
+    pub name: String,
+    /// The distinguished names of the subject and of the issuer of the certificate.
+    pub subject: String,
+    pub issuer: String,
+    /// The end of the validity period of the certificate.
+    pub not_after: String,
+}
+
+/// The identity that the user chose to authenticate with.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub enum ClientIdentityChoice {
+    /// The identity at the given index of the identities of the request.
+    Stored(usize),
+    /// An identity from elsewhere, such as the keychain of the platform, as a PEM-encoded
+    /// certificate and private key.
+    Provided {
+        certificate: String,
+        private_key: String,
+    },
+}
+
+/// The data that a page asks to share with `navigator.share()`.
+/// <https://w3c.github.io/web-share/#sharedata-dictionary>
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ShareData {
+    pub title: Option<String>,
+    pub text: Option<String>,
+    pub url: Option<ServoUrl>,
+    pub files: Vec<SharedFile>,
+}
+
+/// A file that a page asks to share.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct SharedFile {
+    pub name: String,
+    /// The MIME type of the file, or an empty string if it is unknown.
+    pub mime_type: String,
+    pub bytes: Vec<u8>,
+}
+
+/// The outcome of showing the share sheet of the platform.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub enum ShareResult {
+    /// The user picked a target, which received the data.
+    Shared,
+    /// The user dismissed the share sheet without sharing.
+    Canceled,
+    /// The data could not be shared, for instance because the platform has no share sheet.
+    Failed,
+}
+
+/// A video that a page asks to show in a floating window.
+/// <https://w3c.github.io/picture-in-picture/#request-pip>
+#[derive(Debug, Deserialize, Serialize)]
+pub struct PictureInPictureRequest {
+    /// The natural size of the video.
+    pub video_size: DeviceIntSize,
+    /// The frames of the video, as the media pipeline presents them.
+    pub frames: IpcReceiver<PictureInPictureFrame>,
+    /// Where the embedder reports what happens to the floating window.
+    pub events: IpcSender<PictureInPictureEvent>,
+}
+
+/// A frame of a video that is shown in a floating window.
+#[derive(Deserialize, Serialize)]
+pub struct PictureInPictureFrame {
+    pub width: i32,
+    pub height: i32,
+    /// The pixels of the frame, in BGRA8.
+    pub data: IpcSharedMemory,
+}
+
+/// What happens to the floating window of a video.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub enum PictureInPictureEvent {
+    /// The floating window was opened with the given size.
+    Opened(DeviceIntSize),
+    /// The user resized the floating window.
+    Resized(DeviceIntSize),
+    /// The user closed the floating window, or it could not be opened.
+    Closed,
+}
+
+/// A request that the embedder is asked about before it is fetched.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct WebResourceRequest {
+    #[serde(
+        deserialize_with = "::hyper_serde::deserialize",
+        serialize_with = "::hyper_serde::serialize"
+    )]
+    pub method: Method,
+    #[serde(
+        deserialize_with = "::hyper_serde::deserialize",
+        serialize_with = "::hyper_serde::serialize"
+    )]
+    pub headers: HeaderMap,
+    pub url: ServoUrl,
+    /// Whether the request loads a document into a browsing context.
+    pub is_navigation: bool,
+    /// Whether the request follows a redirect.
+    pub is_redirect: bool,
+}
+
+/// The response that the embedder fetched or made up for a request.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct WebResourceResponse {
+    #[serde(
+        deserialize_with = "::hyper_serde::deserialize",
+        serialize_with = "::hyper_serde::serialize"
+    )]
+    pub status: StatusCode,
+    #[serde(
+        deserialize_with = "::hyper_serde::deserialize",
+        serialize_with = "::hyper_serde::serialize"
+    )]
+    pub headers: HeaderMap,
+}
+
+/// What the embedder decided to do with a request, and the body of its response if it
+/// responds to it.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub enum WebResourceResponseMsg {
+    /// Fetch the request as it is.
+    Continue,
+    /// Fetch the request with the method, the headers and the URL of the given request instead.
+    Rewrite(WebResourceRequest),
+    /// Fail the request with a network error.
+    Block,
+    /// Respond with the given response, whose body follows in `Body` messages.
+    Start(WebResourceResponse),
+    /// A chunk of the body of the response.
+    Body(Vec<u8>),
+    /// The whole body of the response was sent.
+    Done,
+}
+
+/// A handler that a site registered with `navigator.registerProtocolHandler`.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ProtocolHandlerRequest {
+    pub scheme: String,
+    /// The URL that the navigations go to instead, with a `%s` where the URL being navigated
+    /// to goes.
+    pub url: ServoUrl,
+    /// The handler of the scheme that the user allowed before.
+    pub current_url: Option<ServoUrl>,
+}
+
+/// Identifies a download in the messages about it.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct DownloadId(pub u64);
+
+/// What happened to a download.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub enum DownloadEvent {
+    /// The response from the URL started being saved to the file.
+    Started {
+        url: ServoUrl,
+        path: PathBuf,
+        total_bytes: Option<u64>,
+    },
+    /// More of the response was saved to the file.
+    Progress {
+        received_bytes: u64,
+        total_bytes: Option<u64>,
+    },
+    Paused,
+    Resumed,
+    /// The whole response was saved to the file.
+    Finished,
+    /// The download was stopped for good, and its file removed.
+    Cancelled,
+    /// The download stopped because of the given error, and can be resumed.
+    Failed(String),
+}
+
+/// What the embedder can do with a download.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub enum DownloadAction {
+    Pause,
+    Resume,
+    Cancel,
+}
+
+/// The response of a `ProtocolHandler` to a request for a URL of its scheme.
+#[derive(Clone, Debug)]
+pub struct ProtocolResponse {
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    pub body: Vec<u8>,
+}
+
+/// Loads the URLs of a custom scheme, such as `app:`, that the embedder registered. The
+/// documents loaded from them have an opaque origin, like those of other non-special schemes.
+pub trait ProtocolHandler: Send + Sync {
+    /// Responds to a request for a URL of the scheme, or fails it with a network error whose
+    /// message is the returned error. Called on the thread that fetches the request.
+    fn load(&self, request: &WebResourceRequest) -> Result<ProtocolResponse, String>;
+
+    /// Whether the pages of any origin may fetch the resources of the scheme and read them,
+    /// like `data:` URLs. Otherwise, they can only be navigated to and embedded without being
+    /// read, like cross-origin resources that were fetched without CORS.
+    fn is_fetchable(&self) -> bool {
+        false
+    }
+}
+
+/// The schemes that the fetch of the browser itself handles, which embedders can't take over.
+const BUILT_IN_SCHEMES: &[&str] = &[
+    "about",
+    "blob",
+    "chrome",
+    "data",
+    "file",
+    "ftp",
+    "http",
+    "https",
+    "javascript",
+    "servo",
+    "ws",
+    "wss",
+];
+
+/// The handlers of the custom schemes that the embedder registered, by scheme.
+#[derive(Clone, Default)]
+pub struct ProtocolRegistry {
+    handlers: HashMap<String, Arc<dyn ProtocolHandler>>,
+}
+
+impl ProtocolRegistry {
+    pub fn new() -> ProtocolRegistry {
+        Default::default()
+    }
+
+    /// Loads the URLs of the given scheme with the handler, unless the scheme is invalid, built
+    /// into the browser, or already has a handler.
+    pub fn register(
+        &mut self,
+        scheme: &str,
+        handler: Box<dyn ProtocolHandler>,
+    ) -> Result<(), String> {
+        let scheme = scheme.to_ascii_lowercase();
+        // https://url.spec.whatwg.org/#scheme-state
+        let is_valid = scheme.starts_with(|c: char| c.is_ascii_alphabetic()) &&
+            scheme
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '+' || c == '-' || c == '.');
+        if !is_valid {
+            return Err(format!("{:?} is not a valid scheme", scheme));
+        }
+        if BUILT_IN_SCHEMES.contains(&&*scheme) {
+            return Err(format!("The {} scheme is built into the browser", scheme));
+        }
+        if self.handlers.contains_key(&scheme) {
+            return Err(format!("The {} scheme already has a handler", scheme));
+        }
+        self.handlers.insert(scheme, Arc::from(handler));
+        Ok(())
+    }
+
+    pub fn get(&self, scheme: &str) -> Option<&dyn ProtocolHandler> {
+        self.handlers.get(scheme).map(|handler| &**handler)
+    }
+
+    /// Whether the pages of any origin may fetch the resources of the scheme.
+    pub fn is_fetchable(&self, scheme: &str) -> bool {
+        self.get(scheme)
+            .map_or(false, |handler| handler.is_fetchable())
+    }
+}
@@ -9,7 +9,8 @@ use crate::platform::font_context::FontContextHandle;
 pub use crate::platform::font_list::fallback_font_families;
 use crate::platform::font_template::FontTemplateData;
 use crate::text::glyph::{ByteIndex, GlyphData, GlyphId, GlyphStore};
-use crate::text::shaping::ShaperMethods;
+use crate::text::shaping::cache::ShapedTextKey;
+use crate::text::shaping::{self, ShaperMethods};
 use crate::text::Shaper;
 use app_units::Au;
 use euclid::default::{Point2D, Rect, Size2D};
@@ -143,7 +144,202 @@ pub struct Font {
     pub descriptor: FontDescriptor,
     pub actual_pt_size: Au,
     shaper: Option<Shaper>,
-    shape_cache: RefCell<HashMap<ShapeCacheEntry, Arc<GlyphStore>>>,
+    shape_cache: RefCell<HashMap<ShapedTextKey, Arc<GlyphStore>>>,
+    glyph_advance_cache: RefCell<HashMap<u32, FractionalPixel>>,
+    pub font_key: webrender_api::FontInstanceKey,
+}
+
+impl Font {
+    pub fn new(
+        handle: FontHandle,
+        descriptor: FontDescriptor,
+        actual_pt_size: Au,
+        font_key: webrender_api::FontInstanceKey,
+    ) -> Font {
+        let metrics = handle.metrics();
+
+        Font {
+            handle: handle,
+            shaper: None,
+            descriptor,
+            actual_pt_size,
+            metrics,
+            shape_cache: RefCell::new(HashMap::new()),
+            glyph_advance_cache: RefCell::new(HashMap::new()),
+            font_key,
+        }
+    }
+
+    /// A unique identifier for the font, allowing comparison.
+    pub fn identifier(&self) -> Atom {
+        self.handle.identifier()
+    }
+}
+
+bitflags! {
+    pub struct ShapingFlags: u8 {
+        #[doc = "Set if the text is entirely whitespace."]
+        const IS_WHITESPACE_SHAPING_FLAG = 0x01;
+        #[doc = "Set if we are to ignore ligatures."]
+        const IGNORE_LIGATURES_SHAPING_FLAG = 0x02;
+        #[doc = "Set if we are to disable kerning."]
+        const DISABLE_KERNING_SHAPING_FLAG = 0x04;
+        #[doc = "Text direction is right-to-left."]
+        const RTL_FLAG = 0x08;
+        #[doc = "Set if word-break is set to keep-all."]
+        const KEEP_ALL_FLAG = 0x10;
+    }
+}
+
+/// Various options that control text shaping.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct ShapingOptions {
+    /// Spacing to add between each letter. Corresponds to the CSS 2.1 `letter-spacing` property.
+    /// NB: You will probably want to set the `IGNORE_LIGATURES_SHAPING_FLAG` if this is non-null.
+    pub letter_spacing: Option<Au>,
+    /// Spacing to add between each word. Corresponds to the CSS 2.1 `word-spacing` property.
+    pub word_spacing: Au,
+    /// The Unicode script property of the characters in this run.
+    pub script: Script,
+    /// Various flags.
+    pub flags: ShapingFlags,
+}
+
+impl Font {
+    /// Shapes the given text, reusing the glyphs of a previous shaping of the same text with
+    /// the same font instance and options if this font or another one of the process did it.
+    pub fn shape_text(&mut self, text: &str, options: &ShapingOptions) -> Arc<GlyphStore> {
+        let lookup_key = ShapedTextKey {
+            font_key: self.font_key,
+            text: text.to_owned(),
+            options: *options,
+        };
+        if let Some(glyphs) = self.shape_cache.borrow().get(&lookup_key) {
+            return glyphs.clone();
+        }
+
+        let glyphs = match shaping::cache::get(&lookup_key) {
+            Some(glyphs) => glyphs,
+            None => {
+                let glyphs = Arc::new(self.shape_text_uncached(text, options));
+                shaping::cache::insert(lookup_key.clone(), glyphs.clone());
+                glyphs
+            },
+        };
+        self.shape_cache
+            .borrow_mut()
+            .insert(lookup_key, glyphs.clone());
+        glyphs
+    }
+
+    fn shape_text_uncached(&mut self, text: &str, options: &ShapingOptions) -> GlyphStore {
+        let start_time = time::precise_time_ns();
+        let mut glyphs = GlyphStore::new(
+            text.len(),
+            options
+                .flags
+                .contains(ShapingFlags::IS_WHITESPACE_SHAPING_FLAG),
+            options.flags.contains(ShapingFlags::RTL_FLAG),
+        );
+
+        if self.can_do_fast_shaping(text, options) {
+            debug!("shape_text: Using ASCII fast path.");
+            self.shape_text_fast(text, options, &mut glyphs);
+        } else {
+            debug!("shape_text: Using Harfbuzz.");
+            if self.shaper.is_none() {
+                self.shaper = Some(Shaper::new(self as *const Font));
+            }
+            self.shaper
+                .as_ref()
+                .unwrap()
+                .shape_text(text, options, &mut glyphs);
+        }
+
+        let end_time = time::precise_time_ns();
+        TEXT_SHAPING_PERFORMANCE_COUNTER
+            .fetch_add((end_time - start_time) as usize, Ordering::Relaxed);
+        glyphs
+    }
+
+    fn can_do_fast_shaping(&self) -> bool;
+    fn metrics(&self) -> FontMetrics;
+    fn table_for_tag(&self, _: FontTableTag) -> Option<FontTable>;
+
+    /// A unique identifier for the font, allowing comparison.
+    fn identifier(&self) -> Atom;
+}
+
+// Used to abstract over the shaper's choice of fixed int representation.
+pub type FractionalPixel = f64;
+
+pub type FontTableTag = u32;
+
+trait FontTableTagConversions {
+    fn tag_to_str(&self) -> String;
+}
+
+impl FontTableTagConversions for FontTableTag {
+    fn tag_to_str(&self) -> String {
+        let bytes = [
+            (self >> 24) as u8,
+            (self >> 16) as u8,
+            (self >> 8) as u8,
+            (self >> 0) as u8,
+        ];
+        str::from_utf8(&bytes).unwrap().to_owned()
+    }
+}
+
+pub trait FontTableMethods {
+    fn buffer(&self) -> &[u8];
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct FontMetrics {
+    pub underline_size: Au,
+    pub underline_offset: Au,
+    pub strikeout_size: Au,
+    pub strikeout_offset: Au,
+    pub leading: Au,
+    pub x_height: Au,
+    pub em_size: Au,
+    pub ascent: Au,
+    pub descent: Au,
+    pub max_advance: Au,
+    pub average_advance: Au,
+    pub line_gap: Au,
+}
+
+/// `FontDescriptor` describes the parameters of a `Font`. It represents rendering a given font
+/// template at a particular size, with a particular font-variant-caps applied, etc. This contrasts
+/// with `FontTemplateDescriptor` in that the latter represents only the parameters inherent in the
+/// font data (weight, stretch, etc.).
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct FontDescriptor {
+    pub template_descriptor: FontTemplateDescriptor,
+    pub variant: font_variant_caps::T,
+    pub pt_size: Au,
+}
+
+impl<'a> From<&'a FontStyleStruct> for FontDescriptor {
+    fn from(style: &'a FontStyleStruct) -> Self {
+        FontDescriptor {
+            template_descriptor: FontTemplateDescriptor::from(style),
+            variant: style.font_variant_caps,
+            pt_size: Au::from_f32_px(style.font_size.size().px()),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Font {
+    pub handle: FontHandle,
+    pub metrics: FontMetrics,
+    pub descriptor: FontDescriptor,
+    pub actual_pt_size: Au,
+    shaper: Option<Shaper>,
+    shape_cache: RefCell<HashMap<ShapedTextKey, Arc<GlyphStore>>>,
     glyph_advance_cache: RefCell<HashMap<u32, FractionalPixel>>,
     pub font_key: webrender_api::FontInstanceKey,
 }
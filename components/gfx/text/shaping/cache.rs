@@ -0,0 +1,102 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! A cache of shaping results shared by all the threads of the process.
+//!
+//! Each `Font` keeps its own cache of the text it shaped, but fonts are owned by the font
+//! context of a single thread and are dropped whenever the font caches are flushed. This cache
+//! lets the other layout threads, and the fonts created after a flush, reuse the glyphs of text
+//! which was already shaped with the same font instance.
+
+use crate::font::ShapingOptions;
+use crate::text::glyph::GlyphStore;
+use fnv::FnvHasher;
+use std::collections::HashMap;
+use std::hash::{BuildHasherDefault, Hash, Hasher};
+use std::sync::{Arc, Mutex};
+
+/// The number of independently locked parts of the cache, so that the threads of a parallel
+/// layout rarely wait on each other.
+const SHARD_COUNT: usize = 16;
+
+/// The maximum number of shaped runs kept by each shard.
+const MAX_ENTRIES_PER_SHARD: usize = 1024;
+
+/// The text run, font instance, direction and features a run was shaped with.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct ShapedTextKey {
+    pub font_key: webrender_api::FontInstanceKey,
+    pub text: String,
+    pub options: ShapingOptions,
+}
+
+struct ShapedTextEntry {
+    glyphs: Arc<GlyphStore>,
+    last_used: u64,
+}
+
+#[derive(Default)]
+struct Shard {
+    entries: HashMap<ShapedTextKey, ShapedTextEntry, BuildHasherDefault<FnvHasher>>,
+    clock: u64,
+}
+
+impl Shard {
+    fn get(&mut self, key: &ShapedTextKey) -> Option<Arc<GlyphStore>> {
+        self.clock += 1;
+        let clock = self.clock;
+        self.entries.get_mut(key).map(|entry| {
+            entry.last_used = clock;
+            entry.glyphs.clone()
+        })
+    }
+
+    fn insert(&mut self, key: ShapedTextKey, glyphs: Arc<GlyphStore>) {
+        if self.entries.len() >= MAX_ENTRIES_PER_SHARD && !self.entries.contains_key(&key) {
+            self.evict_least_recently_used_half();
+        }
+        self.clock += 1;
+        let last_used = self.clock;
+        self.entries
+            .insert(key, ShapedTextEntry { glyphs, last_used });
+    }
+
+    /// Evicting half of the entries at once keeps the cost of an insertion constant on average.
+    fn evict_least_recently_used_half(&mut self) {
+        let mut last_uses: Vec<u64> = self.entries.values().map(|entry| entry.last_used).collect();
+        last_uses.sort_unstable();
+        let threshold = last_uses[last_uses.len() / 2];
+        self.entries.retain(|_, entry| entry.last_used >= threshold);
+    }
+}
+
+lazy_static! {
+    static ref SHARDS: Vec<Mutex<Shard>> = (0..SHARD_COUNT)
+        .map(|_| Mutex::new(Shard::default()))
+        .collect();
+}
+
+fn shard(key: &ShapedTextKey) -> &'static Mutex<Shard> {
+    let mut hasher = FnvHasher::default();
+    key.hash(&mut hasher);
+    &SHARDS[hasher.finish() as usize % SHARD_COUNT]
+}
+
+/// Returns the glyphs of a run which was already shaped with the given font instance and
+/// options, if they are still cached.
+pub fn get(key: &ShapedTextKey) -> Option<Arc<GlyphStore>> {
+    shard(key).lock().unwrap().get(key)
+}
+
+/// Caches the glyphs of a shaped run, evicting the least recently used runs if needed.
+pub fn insert(key: ShapedTextKey, glyphs: Arc<GlyphStore>) {
+    shard(&key).lock().unwrap().insert(key, glyphs);
+}
+
+/// Drops every cached run, to free memory.
+pub fn clear() {
+    for shard in SHARDS.iter() {
+        shard.lock().unwrap().entries.clear();
+    }
+}
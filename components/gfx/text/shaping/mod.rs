@@ -12,6 +12,7 @@ use crate::text::glyph::GlyphStore;
 
 pub use self::harfbuzz::Shaper;
 
+pub mod cache;
 pub mod harfbuzz;
 
 pub trait ShaperMethods {
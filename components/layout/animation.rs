@@ -85,8 +85,10 @@ pub fn update_animation_state<E>(
                     },
                     Animation::Keyframes(_, _, _, ref mut state) => {
                         // This animation is still running, or we need to keep
-                        // iterating.
-                        now < state.started_at + state.duration || state.tick()
+                        // iterating. Scroll-driven animations never end.
+                        state.is_scroll_driven() ||
+                            now < state.started_at + state.duration ||
+                            state.tick()
                     },
                 };
 
@@ -598,6 +598,10 @@ pub struct BlockFlow {
     /// Additional floating flow members.
     pub float: Option<Box<FloatedBlockInfo>>,
 
+    /// The style of the `::backdrop` painted below this block, if it is in the top layer.
+    #[serde(skip_serializing)]
+    pub backdrop_style: Option<crate::ServoArc<ComputedValues>>,
+
     /// Various flags.
     flags: BlockFlowFlags,
 }
@@ -638,6 +642,7 @@ impl BlockFlow {
             ),
             fragment: fragment,
             float: float_kind.map(|kind| Box::new(FloatedBlockInfo::new(kind))),
+            backdrop_style: None,
             flags: BlockFlowFlags::empty(),
         }
     }
@@ -879,6 +884,7 @@ impl BlockFlow {
             base: self.base.clone_with_children(new_children),
             fragment: self.fragment.clone(),
             float: self.float.clone(),
+            backdrop_style: self.backdrop_style.clone(),
             ..*self
         }
     }
@@ -840,10 +840,12 @@ impl<'a, ConcreteThreadSafeLayoutNode: ThreadSafeLayoutNode>
 
         match text_content {
             TextContent::Text(string) => {
-                let info = Box::new(UnscannedTextFragmentInfo::new(
+                let mut info = Box::new(UnscannedTextFragmentInfo::new(
                     string.into(),
                     node.selection(),
                 ));
+                info.highlights = node.highlight_styles(self.style_context());
+                info.spelling_errors = node.spelling_errors();
                 let specific_fragment_info = SpecificFragmentInfo::UnscannedText(info);
                 fragments
                     .fragments
@@ -898,9 +900,11 @@ impl<'a, ConcreteThreadSafeLayoutNode: ThreadSafeLayoutNode>
         }
 
         let fragment = self.build_fragment_for_block(node);
-        let flow = FlowRef::new(Arc::new(BlockFlow::from_fragment_and_float_kind(
-            fragment, float_kind,
-        )));
+        let mut block_flow = BlockFlow::from_fragment_and_float_kind(fragment, float_kind);
+        if block_flow.fragment.style.in_top_layer() {
+            block_flow.backdrop_style = node.backdrop_style();
+        }
+        let flow = FlowRef::new(Arc::new(block_flow));
         self.build_flow_for_block_like(flow, node)
     }
 
@@ -22,7 +22,7 @@ use crate::display_list::items::{PopAllTextShadowsDisplayItem, PushTextShadowDis
 use crate::display_list::items::{StackingContext, StackingContextType, StickyFrameData};
 use crate::display_list::items::{TextOrientation, WebRenderImageInfo};
 use crate::display_list::ToLayout;
-use crate::flow::{BaseFlow, Flow, FlowFlags};
+use crate::flow::{BaseFlow, Flow, FlowFlags, GetBaseFlow};
 use crate::flow_ref::FlowRef;
 use crate::fragment::SpecificFragmentInfo;
 use crate::fragment::{CanvasFragmentSource, CoordinateSystem, Fragment, ScannedTextFragmentInfo};
@@ -33,7 +33,7 @@ use app_units::{Au, AU_PER_PX};
 use canvas_traits::canvas::{CanvasMsg, FromLayoutMsg};
 use embedder_traits::Cursor;
 use euclid::{
-    default::{Point2D, Rect, SideOffsets2D as UntypedSideOffsets2D, Size2D},
+    default::{Point2D, Rect, SideOffsets2D as UntypedSideOffsets2D, Size2D, Vector2D},
     rect, SideOffsets2D,
 };
 use fnv::FnvHashMap;
@@ -296,6 +296,32 @@ impl StackingContextCollectionState {
     }
 }
 
+/// What the display items of a flow were built from, besides the flow's own style and
+/// fragments, whose changes are tracked by the `REPAINT` restyle damage.
+#[derive(Clone, Copy, PartialEq)]
+struct FlowDisplayItemsKey {
+    position: LogicalRect<Au>,
+    stacking_relative_position: Vector2D<Au>,
+    clip: Rect<Au>,
+    stacking_context_id: StackingContextId,
+    clipping_and_scrolling: ClippingAndScrolling,
+    parent_scrolling: ClipScrollNodeIndex,
+    collected_clip_scroll_node_count: usize,
+}
+
+/// The display items built for a single flow, which the flow keeps so that they can be
+/// appended to the next display list again if the flow didn't change.
+pub struct FlowDisplayItems {
+    key: FlowDisplayItemsKey,
+    /// Whether the items only refer to the stacking context and clip scroll nodes of the flow,
+    /// which makes them reusable by the next display list.
+    cacheable: bool,
+    items: Vec<DisplayItem>,
+    late_clip_nodes: Vec<(StackingContextId, ClipScrollNode)>,
+    iframe_sizes: Vec<IFrameSize>,
+    indexable_text: IndexableText,
+}
+
 pub struct DisplayListBuildState<'a> {
     /// A LayoutContext reference important for creating WebRender images.
     pub layout_context: &'a LayoutContext<'a>,
@@ -330,6 +356,22 @@ pub struct DisplayListBuildState<'a> {
 
     /// Stores text runs to answer text queries used to place a cursor inside text.
     pub indexable_text: IndexableText,
+
+    /// The clip scroll nodes and stacking contexts collected before building the display
+    /// items, shared with the states building the display items of each flow.
+    collected: Arc<CollectedClipScrollInfo>,
+
+    /// The clip nodes added while building display items, along with the real stacking context
+    /// that defines them. They are numbered after the collected clip scroll nodes until the
+    /// display items of the flow are appended to the display list.
+    late_clip_nodes: Vec<(StackingContextId, ClipScrollNode)>,
+}
+
+/// The clip scroll nodes and stacking contexts found while collecting stacking contexts, which
+/// don't change while the display items of the flows are built.
+struct CollectedClipScrollInfo {
+    clip_scroll_nodes: Vec<ClipScrollNode>,
+    real_stacking_context_ids: FnvHashMap<StackingContextId, StackingContextId>,
 }
 
 impl<'a> DisplayListBuildState<'a> {
@@ -337,6 +379,15 @@ impl<'a> DisplayListBuildState<'a> {
         layout_context: &'a LayoutContext,
         state: StackingContextCollectionState,
     ) -> DisplayListBuildState<'a> {
+        let collected = CollectedClipScrollInfo {
+            clip_scroll_nodes: state.clip_scroll_nodes.clone(),
+            real_stacking_context_ids: state
+                .stacking_context_info
+                .iter()
+                .map(|(id, info)| (*id, info.real_stacking_context_id))
+                .collect(),
+        };
+
         DisplayListBuildState {
             layout_context: layout_context,
             root_stacking_context: state.root_stacking_context,
@@ -350,6 +401,164 @@ impl<'a> DisplayListBuildState<'a> {
             ),
             iframe_sizes: Vec::new(),
             indexable_text: IndexableText::default(),
+            collected: Arc::new(collected),
+            late_clip_nodes: Vec::new(),
+        }
+    }
+
+    /// Creates an empty state to build the display items of a single flow into, which only
+    /// shares the collected clip scroll nodes and stacking contexts with this one.
+    fn fork(
+        &self,
+        stacking_context_id: StackingContextId,
+        clipping_and_scrolling: ClippingAndScrolling,
+    ) -> DisplayListBuildState<'a> {
+        DisplayListBuildState {
+            layout_context: self.layout_context,
+            root_stacking_context: StackingContext::root(),
+            items: FnvHashMap::default(),
+            stacking_context_info: FnvHashMap::default(),
+            clip_scroll_nodes: Vec::new(),
+            processing_scrolling_overflow_element: false,
+            current_stacking_context_id: stacking_context_id,
+            current_clipping_and_scrolling: clipping_and_scrolling,
+            iframe_sizes: Vec::new(),
+            indexable_text: IndexableText::default(),
+            collected: self.collected.clone(),
+            late_clip_nodes: Vec::new(),
+        }
+    }
+
+    fn flow_display_items_key(&self, flow: &dyn Flow) -> FlowDisplayItemsKey {
+        let base = flow.base();
+        let clipping_and_scrolling = flow.clipping_and_scrolling();
+        FlowDisplayItemsKey {
+            position: base.position,
+            stacking_relative_position: base.stacking_relative_position,
+            clip: base.clip,
+            stacking_context_id: base.stacking_context_id,
+            clipping_and_scrolling,
+            parent_scrolling: self.parent_clip_scroll_node_index(clipping_and_scrolling.scrolling),
+            collected_clip_scroll_node_count: self.collected.clip_scroll_nodes.len(),
+        }
+    }
+
+    /// Builds the display items of the given flow, unless the ones it kept from the previous
+    /// display list can be appended again because neither the flow nor the clip scroll nodes
+    /// and stacking context it paints into changed since.
+    ///
+    /// This only mutates the given flow, so it can be called for several flows in parallel.
+    pub fn build_display_items_for_flow(&self, flow: &mut dyn Flow) {
+        let key = self.flow_display_items_key(flow);
+
+        let mut needs_repaint = flow
+            .base()
+            .restyle_damage
+            .contains(ServoRestyleDamage::REPAINT);
+        flow.mutate_fragments(&mut |fragment: &mut Fragment| {
+            needs_repaint |= fragment
+                .restyle_damage
+                .contains(ServoRestyleDamage::REPAINT);
+        });
+        let reusable = flow
+            .base()
+            .display_items
+            .as_ref()
+            .map_or(false, |display_items| display_items.key == key);
+        if reusable && !needs_repaint {
+            return;
+        }
+
+        let mut state = self.fork(key.stacking_context_id, key.clipping_and_scrolling);
+        flow.build_display_list(&mut state);
+        flow.mut_base()
+            .restyle_damage
+            .remove(ServoRestyleDamage::REPAINT);
+        flow.mut_base().display_items = Some(state.into_flow_display_items(key));
+    }
+
+    fn into_flow_display_items(self, key: FlowDisplayItemsKey) -> FlowDisplayItems {
+        let items: Vec<DisplayItem> = self
+            .items
+            .into_iter()
+            .flat_map(|(_, items)| items)
+            .collect();
+
+        // Items painting into other stacking contexts than the one of the flow, or into
+        // clip scroll nodes the flow doesn't know about, may refer to stacking contexts or
+        // clip scroll nodes that were numbered differently by the next collection.
+        let known_clip_scroll_node = |index: ClipScrollNodeIndex| {
+            index == key.clipping_and_scrolling.scrolling ||
+                Some(index) == key.clipping_and_scrolling.clipping ||
+                index == key.parent_scrolling ||
+                index.to_index() >= key.collected_clip_scroll_node_count
+        };
+        let cacheable = items.iter().all(|item| {
+            let clipping_and_scrolling = item.clipping_and_scrolling();
+            item.stacking_context_id() == key.stacking_context_id &&
+                known_clip_scroll_node(clipping_and_scrolling.scrolling) &&
+                clipping_and_scrolling
+                    .clipping
+                    .map_or(true, |index| known_clip_scroll_node(index))
+        });
+
+        FlowDisplayItems {
+            key,
+            cacheable,
+            items,
+            late_clip_nodes: self.late_clip_nodes,
+            iframe_sizes: self.iframe_sizes,
+            indexable_text: self.indexable_text,
+        }
+    }
+
+    /// Appends the display items built for the given flow by `build_display_items_for_flow`
+    /// to this display list, keeping them on the flow if they can be reused by the next one.
+    pub fn append_display_items_of_flow(&mut self, flow: &mut dyn Flow) {
+        let display_items = match flow.mut_base().display_items.take() {
+            Some(display_items) => display_items,
+            None => return,
+        };
+
+        // Renumber the clip nodes of the flow after the ones already in the display list.
+        let first_late_index = display_items.key.collected_clip_scroll_node_count;
+        let offset = self.clip_scroll_nodes.len() - first_late_index;
+        let renumber = |index: ClipScrollNodeIndex| {
+            if index.to_index() >= first_late_index {
+                ClipScrollNodeIndex::new(index.to_index() + offset)
+            } else {
+                index
+            }
+        };
+
+        for (real_stacking_context_id, node) in &display_items.late_clip_nodes {
+            let mut node = node.clone();
+            node.parent_index = renumber(node.parent_index);
+            self.clip_scroll_nodes.push(node);
+            let index = ClipScrollNodeIndex::new(self.clip_scroll_nodes.len() - 1);
+            self.stacking_context_info
+                .get_mut(real_stacking_context_id)
+                .unwrap()
+                .clip_scroll_nodes
+                .push(index);
+        }
+
+        for item in &display_items.items {
+            let mut item = item.clone();
+            let base = item.base_mut();
+            base.clipping_and_scrolling = ClippingAndScrolling {
+                scrolling: renumber(base.clipping_and_scrolling.scrolling),
+                clipping: base.clipping_and_scrolling.clipping.map(renumber),
+            };
+            self.add_display_item(item);
+        }
+
+        self.iframe_sizes
+            .extend(display_items.iframe_sizes.iter().cloned());
+        self.indexable_text.extend(&display_items.indexable_text);
+
+        if display_items.cacheable {
+            flow.mut_base().display_items = Some(display_items);
         }
     }
 
@@ -370,7 +579,11 @@ impl<'a> DisplayListBuildState<'a> {
             return index;
         }
 
-        self.clip_scroll_nodes[index.to_index()].parent_index
+        let collected_count = self.collected.clip_scroll_nodes.len();
+        match index.to_index().checked_sub(collected_count) {
+            Some(late_index) => self.late_clip_nodes[late_index].1.parent_index,
+            None => self.collected.clip_scroll_nodes[index.to_index()].parent_index,
+        }
     }
 
     fn is_background_or_border_of_clip_scroll_node(&self, section: DisplayListSection) -> bool {
@@ -437,16 +650,14 @@ impl<'a> DisplayListBuildState<'a> {
         // We want the scroll root to be defined before any possible item that could use it,
         // so we make sure that it is added to the beginning of the parent "real" (non-pseudo)
         // stacking context. This ensures that item reordering will not result in an item using
-        // the scroll root before it is defined.
-        self.clip_scroll_nodes.push(node);
-        let index = ClipScrollNodeIndex::new(self.clip_scroll_nodes.len() - 1);
+        // the scroll root before it is defined. The node is added to the display list along
+        // with the display items of the flow.
+        let index = ClipScrollNodeIndex::new(
+            self.collected.clip_scroll_nodes.len() + self.late_clip_nodes.len(),
+        );
         let real_stacking_context_id =
-            self.stacking_context_info[&self.current_stacking_context_id].real_stacking_context_id;
-        self.stacking_context_info
-            .get_mut(&real_stacking_context_id)
-            .unwrap()
-            .clip_scroll_nodes
-            .push(index);
+            self.collected.real_stacking_context_ids[&self.current_stacking_context_id];
+        self.late_clip_nodes.push((real_stacking_context_id, node));
 
         index
     }
@@ -571,6 +782,9 @@ impl<'a> DisplayListBuildState<'a> {
 /// The logical width of an insertion point: at the moment, a one-pixel-wide line.
 const INSERTION_POINT_LOGICAL_WIDTH: Au = Au(1 * AU_PER_PX);
 
+/// The block size of the squiggle under misspelled words.
+const SPELLING_ERROR_SQUIGGLE_SIZE: Au = Au(3 * AU_PER_PX);
+
 /// Get the border radius for the rectangle inside of a rounded border. This is useful
 /// for building the clip for the content inside the border.
 fn build_border_radius_for_inner_rect(
@@ -1444,6 +1658,24 @@ impl Fragment {
             _ => return,
         };
 
+        // Draw the background of the topmost custom highlight, under the selection.
+        if let Some(ref style) = scanned_text_fragment_info.highlight_style {
+            let background_color = style.resolve_color(style.get_background().background_color);
+            let base = state.create_base_display_item(
+                stacking_relative_border_box,
+                self.node,
+                get_cursor(&self.style, Cursor::Default),
+                display_list_section,
+            );
+            state.add_display_item(DisplayItem::Rectangle(CommonDisplayItem::new(
+                base,
+                webrender_api::RectangleDisplayItem {
+                    common: items::empty_common_item_properties(),
+                    color: background_color.to_layout(),
+                },
+            )));
+        }
+
         // Draw a highlighted background if the text is selected.
         //
         // TODO: Allow non-text fragments to be selected too.
@@ -1987,6 +2219,8 @@ impl Fragment {
         // Paint the text with the color as described in its styling.
         let text_color = if text_fragment.selected() {
             self.selected_style().get_inherited_text().color
+        } else if let Some(ref style) = text_fragment.highlight_style {
+            style.get_inherited_text().color
         } else {
             self.style().get_inherited_text().color
         };
@@ -2056,6 +2290,23 @@ impl Fragment {
                 &text_color,
                 &stacking_relative_box,
                 clip,
+                LineStyle::Solid,
+            );
+        }
+
+        // Spelling errors
+        if text_fragment.misspelled() {
+            let mut stacking_relative_box = logical_stacking_relative_content_box;
+            stacking_relative_box.start.b = logical_stacking_relative_content_box.start.b +
+                metrics.ascent -
+                metrics.underline_offset;
+            stacking_relative_box.size.block = SPELLING_ERROR_SQUIGGLE_SIZE;
+            self.build_display_list_for_text_decoration(
+                state,
+                &RGBA::new(255, 0, 0, 255),
+                &stacking_relative_box,
+                clip,
+                LineStyle::Wavy,
             );
         }
 
@@ -2068,6 +2319,7 @@ impl Fragment {
                 &text_color,
                 &stacking_relative_box,
                 clip,
+                LineStyle::Solid,
             );
         }
 
@@ -2123,6 +2375,7 @@ impl Fragment {
                 &text_color,
                 &stacking_relative_box,
                 clip,
+                LineStyle::Solid,
             );
         }
 
@@ -2134,13 +2387,15 @@ impl Fragment {
         }
     }
 
-    /// Creates the display item for a text decoration: underline, overline, or line-through.
+    /// Creates the display item for a text decoration: underline, overline, or line-through,
+    /// or for the squiggle under a misspelled word.
     fn build_display_list_for_text_decoration(
         &self,
         state: &mut DisplayListBuildState,
         color: &RGBA,
         stacking_relative_box: &LogicalRect<Au>,
         clip: Rect<Au>,
+        line_style: LineStyle,
     ) {
         // FIXME(pcwalton, #2795): Get the real container size.
         let container_size = Size2D::zero();
@@ -2164,7 +2419,7 @@ impl Fragment {
                 orientation: webrender_api::LineOrientation::Horizontal,
                 wavy_line_thickness,
                 color: color.to_layout(),
-                style: LineStyle::Solid,
+                style: line_style,
             },
         )));
     }
@@ -2764,6 +3019,20 @@ impl BlockFlow {
         let stacking_relative_border_box = self
             .base
             .stacking_relative_border_box_for_display_list(&self.fragment);
+
+        // A block in the top layer covers the viewport, and so does its backdrop, which is
+        // painted below everything else in the block's stacking context.
+        // https://fullscreen.spec.whatwg.org/#::backdrop-pseudo-element
+        if let Some(ref backdrop_style) = self.backdrop_style {
+            self.fragment
+                .build_display_list_for_background_if_applicable(
+                    state,
+                    backdrop_style,
+                    background_border_section,
+                    stacking_relative_border_box,
+                );
+        }
+
         // Add the box that starts the block context.
         self.fragment.build_display_list_no_damage(
             state,
@@ -2991,6 +3260,7 @@ fn convert_text_run_to_glyphs(
     return glyphs;
 }
 
+#[derive(Clone)]
 pub struct IndexableTextItem {
     /// The placement of the text item on the plane.
     pub origin: Point2D<Au>,
@@ -3013,6 +3283,13 @@ impl IndexableText {
         entries.push(item);
     }
 
+    fn extend(&mut self, other: &IndexableText) {
+        for (node, items) in &other.inner {
+            let entries = self.inner.entry(*node).or_insert(Vec::new());
+            entries.extend(items.iter().cloned());
+        }
+    }
+
     pub fn get(&self, node: OpaqueNode) -> Option<&[IndexableTextItem]> {
         self.inner.get(&node).map(|x| x.as_slice())
     }
@@ -3029,6 +3306,56 @@ impl IndexableText {
                 .range_index_of_advance(&item[0].range, offset.x),
         )
     }
+
+    /// Returns the text index within a node for a point in the coordinate space of its
+    /// items, measured on the line that the point falls on.
+    pub fn text_index_at_point(&self, node: OpaqueNode, point: Point2D<Au>) -> Option<usize> {
+        let items = self.inner.get(&node)?;
+        // Items are stored in line order, so the point is on the last one starting above it.
+        let item = items
+            .iter()
+            .rev()
+            .find(|item| item.origin.y <= point.y)
+            .unwrap_or(&items[0]);
+        let offset = point - item.baseline_origin;
+        let line_start = (item.range.begin() - items[0].range.begin()).to_usize();
+        Some(line_start + item.text_run.range_index_of_advance(&item.range, offset.x))
+    }
+
+    /// Returns the boxes of the text between two indices within a node, one for each of its
+    /// items that the text is on, in the coordinate space of the items.
+    pub fn text_boxes(&self, node: OpaqueNode, start: usize, end: usize) -> Vec<Rect<Au>> {
+        let items = match self.inner.get(&node) {
+            Some(items) => items,
+            None => return vec![],
+        };
+        let text_start = items[0].range.begin();
+        items
+            .iter()
+            .filter_map(|item| {
+                let item_start = (item.range.begin() - text_start).to_usize();
+                let item_end = item_start + item.range.length().to_usize();
+                if end < item_start || start > item_end {
+                    return None;
+                }
+                let before = start.max(item_start) - item_start;
+                let length = end.min(item_end) - item_start - before;
+                let before = Range::new(item.range.begin(), ByteIndex(before as isize));
+                let selected = Range::new(before.end(), ByteIndex(length as isize));
+                let metrics = &item.text_run.font_metrics;
+                Some(Rect::new(
+                    Point2D::new(
+                        item.baseline_origin.x + item.text_run.advance_for_range(&before),
+                        item.baseline_origin.y - metrics.ascent,
+                    ),
+                    Size2D::new(
+                        item.text_run.advance_for_range(&selected),
+                        metrics.ascent + metrics.descent,
+                    ),
+                ))
+            })
+            .collect()
+    }
 }
 
 trait ToF32Px {
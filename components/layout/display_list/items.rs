@@ -690,6 +690,28 @@ impl DisplayItem {
         }
     }
 
+    pub fn base_mut(&mut self) -> &mut BaseDisplayItem {
+        match *self {
+            DisplayItem::Rectangle(ref mut rect) => &mut rect.base,
+            DisplayItem::Text(ref mut text) => &mut text.base,
+            DisplayItem::Image(ref mut image_item) => &mut image_item.base,
+            DisplayItem::RepeatingImage(ref mut image_item) => &mut image_item.base,
+            DisplayItem::Border(ref mut border) => &mut border.base,
+            DisplayItem::Gradient(ref mut gradient) => &mut gradient.base,
+            DisplayItem::RadialGradient(ref mut gradient) => &mut gradient.base,
+            DisplayItem::Line(ref mut line) => &mut line.base,
+            DisplayItem::BoxShadow(ref mut box_shadow) => &mut box_shadow.base,
+            DisplayItem::PushTextShadow(ref mut push_text_shadow) => &mut push_text_shadow.base,
+            DisplayItem::PopAllTextShadows(ref mut pop_text_shadow) => &mut pop_text_shadow.base,
+            DisplayItem::Iframe(ref mut iframe) => &mut iframe.base,
+            DisplayItem::PushStackingContext(ref mut stacking_context) => {
+                &mut stacking_context.base
+            },
+            DisplayItem::PopStackingContext(ref mut item) => &mut item.base,
+            DisplayItem::DefineClipScrollNode(ref mut item) => &mut item.base,
+        }
+    }
+
     pub fn clipping_and_scrolling(&self) -> ClippingAndScrolling {
         self.base().clipping_and_scrolling
     }
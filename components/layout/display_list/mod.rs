@@ -4,6 +4,7 @@
 
 pub use self::builder::BorderPaintingMode;
 pub use self::builder::DisplayListBuildState;
+pub use self::builder::FlowDisplayItems;
 pub use self::builder::IndexableText;
 pub use self::builder::StackingContextCollectionFlags;
 pub use self::builder::StackingContextCollectionState;
@@ -28,7 +28,8 @@
 use crate::block::{BlockFlow, FormattingContextType};
 use crate::context::LayoutContext;
 use crate::display_list::items::ClippingAndScrolling;
-use crate::display_list::{DisplayListBuildState, StackingContextCollectionState};
+use crate::display_list::StackingContextCollectionState;
+use crate::display_list::{DisplayListBuildState, FlowDisplayItems};
 use crate::flex::FlexFlow;
 use crate::floats::{Floats, SpeculatedFloatPlacement};
 use crate::flow_list::{FlowList, FlowListIterator, MutFlowListIterator};
@@ -923,6 +924,10 @@ pub struct BaseFlow {
     /// The indices of this Flow's ClipScrollNode. This is used to place the node's
     /// display items into scrolling frames and clipping nodes.
     pub clipping_and_scrolling: Option<ClippingAndScrolling>,
+
+    /// The display items built for this flow by the last display list build, which are
+    /// appended to the next display list as they are if this flow doesn't need repainting.
+    pub display_items: Option<FlowDisplayItems>,
 }
 
 impl fmt::Debug for BaseFlow {
@@ -1092,6 +1097,7 @@ impl BaseFlow {
             thread_id: 0,
             stacking_context_id: StackingContextId::root(),
             clipping_and_scrolling: None,
+            display_items: None,
         }
     }
 
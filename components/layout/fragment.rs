@@ -522,6 +522,9 @@ pub struct ScannedTextFragmentInfo {
     pub range_end_including_stripped_whitespace: ByteIndex,
 
     pub flags: ScannedTextFlags,
+
+    /// The style of the topmost custom highlight that covers this fragment, if any.
+    pub highlight_style: Option<ServoArc<ComputedValues>>,
 }
 
 bitflags! {
@@ -537,6 +540,9 @@ bitflags! {
         ///
         /// This handles cases like Foo<span>bar</span>
         const SUPPRESS_LINE_BREAK_BEFORE = 0x04;
+
+        /// Is the text of this fragment a misspelled word?
+        const MISSPELLED = 0x08;
     }
 }
 
@@ -556,6 +562,7 @@ impl ScannedTextFragmentInfo {
             content_size: content_size,
             range_end_including_stripped_whitespace: range.end(),
             flags: flags,
+            highlight_style: None,
         }
     }
 
@@ -571,6 +578,10 @@ impl ScannedTextFragmentInfo {
     pub fn selected(&self) -> bool {
         self.flags.contains(ScannedTextFlags::SELECTED)
     }
+
+    pub fn misspelled(&self) -> bool {
+        self.flags.contains(ScannedTextFlags::MISSPELLED)
+    }
 }
 
 /// Describes how to split a fragment. This is used during line breaking as part of the return
@@ -620,6 +631,13 @@ pub struct UnscannedTextFragmentInfo {
 
     /// The selected text range.  An empty range represents the insertion point.
     pub selection: Option<Range<ByteIndex>>,
+
+    /// The text ranges of the custom highlights and their styles, from the highlight painted
+    /// lowest to the one painted highest.
+    pub highlights: Vec<(Range<ByteIndex>, ServoArc<ComputedValues>)>,
+
+    /// The text ranges of the misspelled words.
+    pub spelling_errors: Vec<Range<ByteIndex>>,
 }
 
 impl UnscannedTextFragmentInfo {
@@ -629,6 +647,8 @@ impl UnscannedTextFragmentInfo {
         UnscannedTextFragmentInfo {
             text: text,
             selection: selection,
+            highlights: vec![],
+            spelling_errors: vec![],
         }
     }
 }
@@ -811,13 +831,16 @@ impl Fragment {
             flags.set(ScannedTextFlags::SUPPRESS_LINE_BREAK_BEFORE, false);
         }
 
-        let info = Box::new(ScannedTextFragmentInfo::new(
+        let mut info = Box::new(ScannedTextFragmentInfo::new(
             text_run,
             split.range,
             size,
             insertion_point,
             flags,
         ));
+        if let SpecificFragmentInfo::ScannedText(ref old_info) = self.specific {
+            info.highlight_style = old_info.highlight_style.clone();
+        }
         self.transform(size, SpecificFragmentInfo::ScannedText(info))
     }
 
@@ -1857,8 +1880,11 @@ impl Fragment {
                     },
                     _ => (ScannedTextFlags::empty(), None),
                 };
-                let text_info =
+                let mut text_info =
                     ScannedTextFragmentInfo::new(text_run, range, size, insertion_point, flags);
+                if let SpecificFragmentInfo::ScannedText(ref info) = self.specific {
+                    text_info.highlight_style = info.highlight_style.clone();
+                }
                 (size, Some(text_info))
             },
             None => (LogicalSize::zero(self.style.writing_mode), None),
@@ -40,9 +40,15 @@ mod list_item;
 mod model;
 mod multicol;
 pub mod opaque_node;
+pub mod overscroll;
+pub mod paint_metrics;
 pub mod parallel;
 mod persistent_list;
+pub mod print;
 pub mod query;
+pub mod scroll_snap;
+pub mod scroll_timeline;
+pub mod scrollbars;
 pub mod sequential;
 mod table;
 mod table_caption;
@@ -0,0 +1,110 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Finds the `overscroll-behavior` of the scroll containers of a document, for the compositor
+//! to stop scrolls from chaining out of the scroll containers that ask for it, and for the
+//! embedder to know which overscroll effects the viewport allows.
+
+use crate::display_list::ToLayout;
+use crate::flow::Flow;
+use crate::fragment::{Fragment, FragmentBorderBoxIterator};
+use crate::sequential;
+use app_units::Au;
+use embedder_traits::OverscrollBehavior;
+use euclid::default::{Point2D, Rect, Size2D};
+use msg::constellation_msg::PipelineId;
+use script_traits::OverscrollContainer;
+use style::computed_values::overflow_x::T as Overflow;
+use style::values::computed::OverscrollBehavior as ComputedOverscrollBehavior;
+use webrender_api::ExternalScrollId;
+
+fn to_overscroll_behavior(behavior: ComputedOverscrollBehavior) -> OverscrollBehavior {
+    match behavior {
+        ComputedOverscrollBehavior::Auto => OverscrollBehavior::Auto,
+        ComputedOverscrollBehavior::Contain => OverscrollBehavior::Contain,
+        ComputedOverscrollBehavior::None => OverscrollBehavior::None,
+    }
+}
+
+struct OverscrollCollector {
+    pipeline_id: PipelineId,
+    viewport_size: Size2D<Au>,
+    /// The levels in the flow tree and the IDs of the scroll containers around the fragment
+    /// that is processed, innermost last.
+    open_containers: Vec<(i32, ExternalScrollId)>,
+    containers: Vec<OverscrollContainer>,
+}
+
+impl FragmentBorderBoxIterator for OverscrollCollector {
+    fn process(&mut self, fragment: &Fragment, level: i32, border_box: &Rect<Au>) {
+        while self
+            .open_containers
+            .last()
+            .map_or(false, |&(container_level, _)| container_level >= level)
+        {
+            self.open_containers.pop();
+        }
+
+        // The scroll container of the root element is the viewport.
+        let style = fragment.style();
+        let box_style = style.get_box();
+        let (scroll_id, scrollport) = if level == 0 {
+            (
+                self.pipeline_id.root_scroll_id(),
+                Rect::new(Point2D::zero(), self.viewport_size),
+            )
+        } else if box_style.overflow_x != Overflow::Visible ||
+            box_style.overflow_y != Overflow::Visible
+        {
+            let border_widths = style.logical_border_width().to_physical(style.writing_mode);
+            (
+                ExternalScrollId(fragment.unique_id(), self.pipeline_id.to_webrender()),
+                border_box.inner_rect(border_widths),
+            )
+        } else {
+            return;
+        };
+
+        self.containers.push(OverscrollContainer {
+            scroll_id,
+            parent_scroll_id: self.open_containers.last().map(|&(_, id)| id),
+            scrollport: scrollport.to_layout(),
+            behavior_x: to_overscroll_behavior(box_style.overscroll_behavior_x),
+            behavior_y: to_overscroll_behavior(box_style.overscroll_behavior_y),
+        });
+        self.open_containers.push((level, scroll_id));
+    }
+
+    fn should_process(&mut self, _: &Fragment) -> bool {
+        true
+    }
+}
+
+/// Finds the scroll containers in the flow tree of `root`, whose viewport is `viewport_size`,
+/// with the viewport first and every scroll container after the ones around it.
+///
+/// Only the viewport is returned when none of the other scroll containers have an overscroll
+/// behavior, as the compositor has nothing to do for them then.
+pub fn collect_overscroll_containers(
+    root: &mut dyn Flow,
+    pipeline_id: PipelineId,
+    viewport_size: Size2D<Au>,
+) -> Vec<OverscrollContainer> {
+    let mut collector = OverscrollCollector {
+        pipeline_id,
+        viewport_size,
+        open_containers: vec![],
+        containers: vec![],
+    };
+    sequential::iterate_through_flow_tree_fragment_border_boxes(root, &mut collector);
+    let mut containers = collector.containers;
+    let has_overscroll_behavior = containers.iter().skip(1).any(|container| {
+        container.behavior_x != OverscrollBehavior::Auto ||
+            container.behavior_y != OverscrollBehavior::Auto
+    });
+    if !has_overscroll_behavior {
+        containers.truncate(1);
+    }
+    containers
+}
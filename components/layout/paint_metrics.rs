@@ -0,0 +1,231 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Measures where the nodes of a display list are painted, to find the largest contentful paint
+//! candidate of a frame and the layout shift between two frames.
+//!
+//! <https://w3c.github.io/largest-contentful-paint/>
+//! <https://wicg.github.io/layout-instability/>
+
+use crate::display_list::items::{DisplayItem, DisplayList, OpaqueNode};
+use script_traits::{
+    LargestContentfulPaintCandidate, LayoutShift, LayoutShiftSource, UntrustedNodeAddress,
+};
+use std::collections::HashMap;
+use webrender_api::units::LayoutRect;
+
+/// The maximum number of nodes a layout shift is attributed to.
+///
+/// <https://wicg.github.io/layout-instability/#sec-layout-shift-attribution>
+const MAX_LAYOUT_SHIFT_SOURCES: usize = 5;
+
+/// Where a node was painted in a frame.
+struct PaintedNode {
+    /// The union of the bounds of the display items of the node, whose origin is the starting
+    /// point of the node.
+    bounds: LayoutRect,
+    /// The part of the visual representation of the node that is visible in the viewport.
+    visible_rect: Option<LayoutRect>,
+}
+
+/// The nodes painted by the display list of a frame.
+pub struct PaintedNodes {
+    /// The viewport of the frame, in the coordinates of the document.
+    viewport: LayoutRect,
+    nodes: HashMap<OpaqueNode, PaintedNode>,
+    /// The text or image with the largest visible area, and its area.
+    largest_contentful_node: Option<(OpaqueNode, f32)>,
+}
+
+impl PaintedNodes {
+    pub fn new(display_list: &DisplayList, viewport: LayoutRect) -> PaintedNodes {
+        let mut nodes: HashMap<OpaqueNode, PaintedNode> = HashMap::new();
+        let mut contentful_rects: HashMap<OpaqueNode, LayoutRect> = HashMap::new();
+        for item in &display_list.list {
+            let is_contentful = match *item {
+                DisplayItem::Text(..) |
+                DisplayItem::Image(..) |
+                DisplayItem::RepeatingImage(..) => true,
+                DisplayItem::Rectangle(..) |
+                DisplayItem::Border(..) |
+                DisplayItem::Gradient(..) |
+                DisplayItem::RadialGradient(..) |
+                DisplayItem::Line(..) |
+                DisplayItem::BoxShadow(..) => false,
+                _ => continue,
+            };
+            let node = item.base().metadata.node;
+            let bounds = item.bounds();
+            if node.0 == 0 || bounds.size.is_empty_or_negative() {
+                continue;
+            }
+            let visible_rect = bounds
+                .intersection(&item.base().clip_rect)
+                .and_then(|rect| rect.intersection(&viewport));
+
+            let painted_node = nodes.entry(node).or_insert(PaintedNode {
+                bounds,
+                visible_rect: None,
+            });
+            painted_node.bounds = painted_node.bounds.union(&bounds);
+            painted_node.visible_rect = union(painted_node.visible_rect, visible_rect);
+
+            if let (true, Some(visible_rect)) = (is_contentful, visible_rect) {
+                let contentful_rect = contentful_rects.entry(node).or_insert(visible_rect);
+                *contentful_rect = contentful_rect.union(&visible_rect);
+            }
+        }
+
+        let largest_contentful_node = contentful_rects
+            .into_iter()
+            .map(|(node, rect)| (node, rect.size.area()))
+            .max_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap());
+
+        PaintedNodes {
+            viewport,
+            nodes,
+            largest_contentful_node,
+        }
+    }
+
+    /// The text or image with the largest area painted in the viewport.
+    ///
+    /// <https://w3c.github.io/largest-contentful-paint/#sec-report-largest-contentful-paint>
+    pub fn largest_contentful_paint_candidate(&self) -> Option<LargestContentfulPaintCandidate> {
+        self.largest_contentful_node
+            .map(|(node, area)| LargestContentfulPaintCandidate {
+                node: UntrustedNodeAddress::from_id(node.0),
+                size: area.round() as u64,
+            })
+            .filter(|candidate| candidate.size > 0)
+    }
+
+    /// Computes the layout shift of the nodes that were also painted by the previous frame and
+    /// whose starting point moved since, or `None` if no node moved in the viewport.
+    ///
+    /// <https://wicg.github.io/layout-instability/#sec-layout-shift-value>
+    pub fn layout_shift_since(&self, previous: &PaintedNodes) -> Option<LayoutShift> {
+        let viewport_area = self.viewport.size.area();
+        let viewport_max_dimension = self.viewport.size.width.max(self.viewport.size.height);
+        if viewport_area <= 0. {
+            return None;
+        }
+
+        let mut impact_rects = vec![];
+        let mut move_distance: f32 = 0.;
+        let mut sources = vec![];
+        for (node, current) in &self.nodes {
+            let previous = match previous.nodes.get(node) {
+                Some(previous) => previous,
+                None => continue,
+            };
+            // Nodes which aren't visible in either frame don't shift anything in the viewport.
+            if previous.visible_rect.is_none() && current.visible_rect.is_none() {
+                continue;
+            }
+            // The bounds are in the coordinates of the document, so scrolling doesn't move
+            // the starting points.
+            let delta = current.bounds.origin - previous.bounds.origin;
+            if delta.x.abs() < 1. && delta.y.abs() < 1. {
+                continue;
+            }
+
+            move_distance = move_distance.max(delta.x.abs()).max(delta.y.abs());
+            impact_rects.extend(previous.visible_rect);
+            impact_rects.extend(current.visible_rect);
+
+            let impact_area = previous.visible_rect.map_or(0., |rect| rect.size.area()) +
+                current.visible_rect.map_or(0., |rect| rect.size.area());
+            sources.push((
+                impact_area,
+                *node,
+                previous.visible_rect,
+                current.visible_rect,
+            ));
+        }
+
+        if sources.is_empty() {
+            return None;
+        }
+
+        // https://wicg.github.io/layout-instability/#impact-fraction
+        let impact_fraction = (union_area(&impact_rects) / viewport_area).min(1.);
+        // https://wicg.github.io/layout-instability/#distance-fraction
+        let distance_fraction = (move_distance / viewport_max_dimension).min(1.);
+
+        sources.sort_by(|(a, ..), (b, ..)| b.partial_cmp(a).unwrap());
+        sources.truncate(MAX_LAYOUT_SHIFT_SOURCES);
+        let sources = sources
+            .into_iter()
+            .map(|(_, node, previous_rect, current_rect)| LayoutShiftSource {
+                node: UntrustedNodeAddress::from_id(node.0),
+                previous_rect: previous.to_viewport_coordinates(previous_rect),
+                current_rect: self.to_viewport_coordinates(current_rect),
+            })
+            .collect();
+
+        Some(LayoutShift {
+            value: (impact_fraction * distance_fraction) as f64,
+            sources,
+        })
+    }
+
+    /// Converts a visible rect of this frame from the coordinates of the document to the
+    /// coordinates of the viewport.
+    fn to_viewport_coordinates(&self, visible_rect: Option<LayoutRect>) -> LayoutRect {
+        visible_rect.map_or(LayoutRect::zero(), |rect| {
+            rect.translate(-self.viewport.origin.to_vector())
+        })
+    }
+}
+
+fn union(a: Option<LayoutRect>, b: Option<LayoutRect>) -> Option<LayoutRect> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a.union(&b)),
+        (a, None) => a,
+        (None, b) => b,
+    }
+}
+
+/// The area covered by the union of the given rectangles, computed by merging the vertical
+/// extents of the rectangles over each of the strips between their horizontal edges.
+fn union_area(rects: &[LayoutRect]) -> f32 {
+    let mut edges: Vec<f32> = rects
+        .iter()
+        .flat_map(|rect| vec![rect.min_x(), rect.max_x()])
+        .collect();
+    edges.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    edges.dedup();
+
+    let mut area = 0.;
+    for strip in edges.windows(2) {
+        let (left, right) = (strip[0], strip[1]);
+        let mut extents: Vec<(f32, f32)> = rects
+            .iter()
+            .filter(|rect| rect.min_x() <= left && rect.max_x() >= right)
+            .map(|rect| (rect.min_y(), rect.max_y()))
+            .collect();
+        extents.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+
+        let mut covered = 0.;
+        let mut current: Option<(f32, f32)> = None;
+        for (top, bottom) in extents {
+            current = match current {
+                Some((current_top, current_bottom)) if top <= current_bottom => {
+                    Some((current_top, current_bottom.max(bottom)))
+                },
+                Some((current_top, current_bottom)) => {
+                    covered += current_bottom - current_top;
+                    Some((top, bottom))
+                },
+                None => Some((top, bottom)),
+            };
+        }
+        if let Some((current_top, current_bottom)) = current {
+            covered += current_bottom - current_top;
+        }
+        area += covered * (right - left);
+    }
+    area
+}
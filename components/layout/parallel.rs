@@ -10,11 +10,13 @@
 
 use crate::block::BlockFlow;
 use crate::context::LayoutContext;
-use crate::flow::{Flow, GetBaseFlow};
+use crate::display_list::DisplayListBuildState;
+use crate::flow::{Flow, GetBaseFlow, ImmutableFlowUtils};
 use crate::flow_ref::FlowRef;
 use crate::traversal::{AssignBSizes, AssignISizes, BubbleISizes};
 use crate::traversal::{PostorderFlowTraversal, PreorderFlowTraversal};
 use profile_traits::time::{self, profile, TimerMetadata};
+use rayon::prelude::*;
 use servo_config::opts;
 use smallvec::SmallVec;
 use std::mem;
@@ -230,3 +232,40 @@ pub fn reflow(
         });
     });
 }
+
+/// Builds the display items of the flows in parallel, leaving the flows whose display items
+/// can be reused to the sequential `BuildDisplayList` traversal, which appends the items of all
+/// flows to the display list in tree order.
+///
+/// Tables build the display items of their row groups, rows and cells, so those are left to
+/// the sequential traversal as well.
+pub fn build_display_items(
+    root: &mut dyn Flow,
+    state: &DisplayListBuildState,
+    queue: &rayon::ThreadPool,
+) {
+    fn collect_flows(flow: &mut dyn Flow, flows: &mut Vec<UnsafeFlow>) {
+        let is_painted_by_table =
+            flow.is_table_rowgroup() || flow.is_table_row() || flow.is_table_cell();
+        if !is_painted_by_table {
+            flows.push(UnsafeFlow(flow));
+        }
+        for kid in flow.mut_base().child_iter_mut() {
+            collect_flows(kid, flows);
+        }
+    }
+
+    let mut flows = vec![];
+    collect_flows(root, &mut flows);
+
+    // Each flow is only mutated by the task building its own display items.
+    queue.install(|| {
+        flows
+            .par_iter()
+            .with_min_len(CHUNK_SIZE)
+            .for_each(|unsafe_flow| {
+                let flow: &mut dyn Flow = unsafe { mem::transmute(*unsafe_flow) };
+                state.build_display_items_for_flow(flow);
+            });
+    });
+}
@@ -0,0 +1,341 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Prints a document that was laid out for `@media print` to a PDF file.
+//!
+//! The document is laid out once, at the width of the page area, and cut into pages of the
+//! height of the page area; boxes are not fragmented between pages. Text is drawn with the
+//! standard Helvetica font of PDF and backgrounds as filled rectangles. Images and other
+//! replaced content are not printed yet.
+
+use crate::flow::Flow;
+use crate::fragment::{Fragment, FragmentBorderBoxIterator, SpecificFragmentInfo};
+use crate::sequential;
+use app_units::Au;
+use euclid::default::{Point2D, Rect, SideOffsets2D, Size2D, Vector2D};
+use std::fmt::Write;
+use style::computed_values::visibility::T as Visibility;
+use style::properties::PropertyDeclaration;
+use style::shared_lock::StylesheetGuards;
+use style::stylist::Stylist;
+use style::values::generics::length::LengthPercentageOrAuto;
+use style::values::specified::length::{LengthPercentage, NoCalcLength};
+use style::values::RGBA;
+
+/// The margin of pages that no `@page` rule sets a margin for, half an inch.
+const DEFAULT_PAGE_MARGIN_PX: i32 = 48;
+
+/// The number of PDF points in a CSS pixel.
+const POINTS_PER_PX: f32 = 0.75;
+
+/// The size of the pages a document is printed on, and the margins around their page area.
+#[derive(Clone, Copy, Debug)]
+pub struct PageSetup {
+    page_size: Size2D<Au>,
+    margins: SideOffsets2D<Au>,
+}
+
+impl PageSetup {
+    /// Sets up pages of the given size with the margins of the `@page` rules in `stylist`.
+    pub fn new(page_size: Size2D<Au>, stylist: &Stylist, guards: &StylesheetGuards) -> PageSetup {
+        let mut margins = SideOffsets2D::new_all_same(Au::from_px(DEFAULT_PAGE_MARGIN_PX));
+        for (data, origin) in stylist.iter_extra_data_origins() {
+            let guard = guards.for_origin(origin);
+            for rule in &data.pages {
+                let rule = rule.read_with(guard);
+                for declaration in rule.block.read_with(guard).declarations() {
+                    let (margin, value) = match *declaration {
+                        PropertyDeclaration::MarginTop(ref value) => (&mut margins.top, value),
+                        PropertyDeclaration::MarginRight(ref value) => (&mut margins.right, value),
+                        PropertyDeclaration::MarginBottom(ref value) => {
+                            (&mut margins.bottom, value)
+                        },
+                        PropertyDeclaration::MarginLeft(ref value) => (&mut margins.left, value),
+                        _ => continue,
+                    };
+                    // Percentages of page margins resolve against the width of the page.
+                    let length = match *value {
+                        LengthPercentageOrAuto::LengthPercentage(LengthPercentage::Length(
+                            NoCalcLength::Absolute(ref length),
+                        )) => Au::from_f32_px(length.to_px()),
+                        LengthPercentageOrAuto::LengthPercentage(LengthPercentage::Percentage(
+                            percentage,
+                        )) => page_size.width.scale_by(percentage.0),
+                        // FIXME: Resolve `auto` and relative lengths.
+                        _ => continue,
+                    };
+                    *margin = length;
+                }
+            }
+        }
+        PageSetup { page_size, margins }
+    }
+
+    /// The size of the area of a page that content is printed in.
+    pub fn page_area_size(&self) -> Size2D<Au> {
+        Size2D::new(
+            (self.page_size.width - self.margins.horizontal()).max(Au::from_px(1)),
+            (self.page_size.height - self.margins.vertical()).max(Au::from_px(1)),
+        )
+    }
+}
+
+/// Something that is drawn on a page.
+enum PrintItem {
+    /// A rectangle filled with a color.
+    Rectangle(Rect<Au>, RGBA),
+    /// A run of text, positioned by the start of its baseline.
+    Text {
+        baseline_origin: Point2D<Au>,
+        font_size: f32,
+        color: RGBA,
+        text: String,
+    },
+}
+
+impl PrintItem {
+    fn bounds(&self) -> Rect<Au> {
+        match *self {
+            PrintItem::Rectangle(rect, _) => rect,
+            PrintItem::Text {
+                baseline_origin,
+                font_size,
+                ..
+            } => Rect::new(
+                baseline_origin - Vector2D::new(Au(0), Au::from_f32_px(font_size)),
+                Size2D::new(Au(0), Au::from_f32_px(font_size)),
+            ),
+        }
+    }
+}
+
+struct PrintItemCollector {
+    items: Vec<PrintItem>,
+}
+
+impl FragmentBorderBoxIterator for PrintItemCollector {
+    fn process(&mut self, fragment: &Fragment, _: i32, border_box: &Rect<Au>) {
+        let style = fragment.style();
+        if style.get_inherited_box().visibility != Visibility::Visible {
+            return;
+        }
+
+        let background_color = style.resolve_color(style.get_background().background_color);
+        if background_color.alpha != 0 && !border_box.is_empty() {
+            self.items
+                .push(PrintItem::Rectangle(*border_box, background_color));
+        }
+
+        if let SpecificFragmentInfo::ScannedText(ref info) = fragment.specific {
+            let text = info.text().replace('\n', " ");
+            if text.trim().is_empty() {
+                return;
+            }
+            let content_box = fragment.stacking_relative_content_box(*border_box);
+            self.items.push(PrintItem::Text {
+                baseline_origin: Point2D::new(
+                    content_box.origin.x,
+                    content_box.origin.y + info.run.font_metrics.ascent,
+                ),
+                font_size: style.get_font().font_size.size().px(),
+                color: style.get_inherited_text().color,
+                text,
+            });
+        }
+    }
+
+    fn should_process(&mut self, _: &Fragment) -> bool {
+        true
+    }
+}
+
+/// Prints the document laid out in `root` on pages set up as `page_setup`, as a PDF file.
+pub fn print_document(root: &mut dyn Flow, page_setup: &PageSetup) -> Vec<u8> {
+    let mut collector = PrintItemCollector { items: vec![] };
+    sequential::iterate_through_flow_tree_fragment_border_boxes(root, &mut collector);
+
+    let page_area_height = page_setup.page_area_size().height;
+    let document_height = collector
+        .items
+        .iter()
+        .map(|item| item.bounds().max_y())
+        .max()
+        .unwrap_or(Au(0));
+    let page_count = ((document_height.0 + page_area_height.0 - 1) / page_area_height.0).max(1);
+    let mut pages = vec![String::new(); page_count as usize];
+
+    for item in &collector.items {
+        match *item {
+            PrintItem::Rectangle(rect, color) => {
+                // Backgrounds that cross a page break are split between the pages.
+                let first_page = (rect.origin.y.0 / page_area_height.0).max(0);
+                let last_page = ((rect.max_y().0 - 1) / page_area_height.0).min(page_count - 1);
+                for page in first_page..=last_page {
+                    let page_top = page_area_height * page;
+                    let page_area = Rect::new(
+                        Point2D::new(rect.origin.x, page_top),
+                        Size2D::new(rect.size.width, page_area_height),
+                    );
+                    if let Some(piece) = rect.intersection(&page_area) {
+                        let piece = piece.translate(Vector2D::new(Au(0), -page_top));
+                        write_rectangle(&mut pages[page as usize], page_setup, &piece, color);
+                    }
+                }
+            },
+            PrintItem::Text {
+                baseline_origin,
+                font_size,
+                color,
+                ref text,
+            } => {
+                let top = item.bounds().origin.y;
+                let page = (top.0 / page_area_height.0).max(0).min(page_count - 1);
+                let origin = baseline_origin - Vector2D::new(Au(0), page_area_height * page);
+                write_text(
+                    &mut pages[page as usize],
+                    page_setup,
+                    origin,
+                    font_size,
+                    color,
+                    text,
+                );
+            },
+        }
+    }
+
+    write_pdf(page_setup.page_size, &pages)
+}
+
+/// Converts a point on the page area to PDF points, whose origin is the bottom left corner
+/// of the page.
+fn to_pdf_point(page_setup: &PageSetup, point: Point2D<Au>) -> (f32, f32) {
+    let x = point.x + page_setup.margins.left;
+    let y = page_setup.page_size.height - page_setup.margins.top - point.y;
+    (x.to_f32_px() * POINTS_PER_PX, y.to_f32_px() * POINTS_PER_PX)
+}
+
+fn write_color(content: &mut String, color: RGBA) {
+    let _ = write!(
+        content,
+        "{:.3} {:.3} {:.3} rg ",
+        color.red_f32(),
+        color.green_f32(),
+        color.blue_f32()
+    );
+}
+
+fn write_rectangle(content: &mut String, page_setup: &PageSetup, rect: &Rect<Au>, color: RGBA) {
+    let (x, y) = to_pdf_point(page_setup, rect.bottom_left());
+    write_color(content, color);
+    let _ = writeln!(
+        content,
+        "{:.2} {:.2} {:.2} {:.2} re f",
+        x,
+        y,
+        rect.size.width.to_f32_px() * POINTS_PER_PX,
+        rect.size.height.to_f32_px() * POINTS_PER_PX
+    );
+}
+
+fn write_text(
+    content: &mut String,
+    page_setup: &PageSetup,
+    baseline_origin: Point2D<Au>,
+    font_size: f32,
+    color: RGBA,
+    text: &str,
+) {
+    let (x, y) = to_pdf_point(page_setup, baseline_origin);
+    content.push_str("BT ");
+    write_color(content, color);
+    let _ = write!(
+        content,
+        "/F1 {:.2} Tf {:.2} {:.2} Td (",
+        font_size * POINTS_PER_PX,
+        x,
+        y
+    );
+    // The standard fonts use WinAnsiEncoding, which agrees with Latin-1 for the characters
+    // that are written here.
+    for character in text.chars() {
+        match character {
+            '(' | ')' | '\\' => {
+                content.push('\\');
+                content.push(character);
+            },
+            ' '..='~' => content.push(character),
+            '\u{a0}'..='\u{ff}' => {
+                let _ = write!(content, "\\{:03o}", character as u32);
+            },
+            _ => content.push('?'),
+        }
+    }
+    content.push_str(") Tj ET\n");
+}
+
+/// Writes a PDF file with a page of `page_size` for each of the given content streams.
+fn write_pdf(page_size: Size2D<Au>, pages: &[String]) -> Vec<u8> {
+    let mut pdf = b"%PDF-1.4\n".to_vec();
+    let mut offsets = vec![];
+    let mut write_object = |pdf: &mut Vec<u8>, body: &str| {
+        offsets.push(pdf.len());
+        let number = offsets.len();
+        pdf.extend_from_slice(format!("{} 0 obj\n{}\nendobj\n", number, body).as_bytes());
+    };
+
+    // Objects 1 to 3 are the catalog, the page tree and the font; each page is followed by
+    // its content stream.
+    let kids: Vec<String> = (0..pages.len())
+        .map(|index| format!("{} 0 R", 4 + 2 * index))
+        .collect();
+    write_object(&mut pdf, "<< /Type /Catalog /Pages 2 0 R >>");
+    write_object(
+        &mut pdf,
+        &format!(
+            "<< /Type /Pages /Kids [{}] /Count {} >>",
+            kids.join(" "),
+            pages.len()
+        ),
+    );
+    write_object(
+        &mut pdf,
+        "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica /Encoding /WinAnsiEncoding >>",
+    );
+    let width = page_size.width.to_f32_px() * POINTS_PER_PX;
+    let height = page_size.height.to_f32_px() * POINTS_PER_PX;
+    for (index, content) in pages.iter().enumerate() {
+        write_object(
+            &mut pdf,
+            &format!(
+                "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {:.2} {:.2}] \
+                 /Resources << /Font << /F1 3 0 R >> >> /Contents {} 0 R >>",
+                width,
+                height,
+                5 + 2 * index
+            ),
+        );
+        write_object(
+            &mut pdf,
+            &format!(
+                "<< /Length {} >>\nstream\n{}endstream",
+                content.len(),
+                content
+            ),
+        );
+    }
+
+    let xref_offset = pdf.len();
+    let mut xref = format!("xref\n0 {}\n0000000000 65535 f \n", offsets.len() + 1);
+    for offset in &offsets {
+        let _ = write!(xref, "{:010} 00000 n \n", offset);
+    }
+    let _ = write!(
+        xref,
+        "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n",
+        offsets.len() + 1,
+        xref_offset
+    );
+    pdf.extend_from_slice(xref.as_bytes());
+    pdf
+}
@@ -94,6 +94,9 @@ pub struct LayoutThreadData {
 
     /// A queued response for the viewport dimensions for a given browsing context.
     pub inner_window_dimensions_response: Option<TypedSize2D<f32, CSSPixel>>,
+
+    /// A queued response for the PDF file the document was printed to.
+    pub print_response: Option<Vec<u8>>,
 }
 
 pub struct LayoutRPCImpl(pub Arc<Mutex<LayoutThreadData>>);
@@ -203,6 +206,12 @@ impl LayoutRPC for LayoutRPCImpl {
         let rw_data = rw_data.lock().unwrap();
         rw_data.inner_window_dimensions_response.clone()
     }
+
+    fn printed_document(&self) -> Option<Vec<u8>> {
+        let &LayoutRPCImpl(ref rw_data) = self;
+        let mut rw_data = rw_data.lock().unwrap();
+        rw_data.print_response.take()
+    }
 }
 
 struct UnioningFragmentBorderBoxIterator {
@@ -812,7 +821,9 @@ where
         Some(PseudoElement::After) => layout_el.get_after_pseudo(),
         Some(PseudoElement::DetailsSummary) |
         Some(PseudoElement::DetailsContent) |
-        Some(PseudoElement::Selection) => None,
+        Some(PseudoElement::Selection) |
+        Some(PseudoElement::Backdrop) |
+        Some(PseudoElement::Highlight(_)) => None,
         // FIXME(emilio): What about the other pseudos? Probably they shouldn't
         // just return the element's style!
         _ => Some(layout_el),
@@ -0,0 +1,220 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Finds the snap positions of the scroll containers that have a `scroll-snap-type`, for the
+//! compositor to snap those scroll containers to when a scroll ends.
+//!
+//! The snap areas of a scroll container are the fragments with a `scroll-snap-align` that are
+//! below it in the flow tree, up to the next scroll container. The block and inline axes are
+//! taken to be the vertical and horizontal axes, whatever the writing mode is.
+
+use crate::display_list::ToLayout;
+use crate::flow::Flow;
+use crate::fragment::{Fragment, FragmentBorderBoxIterator};
+use crate::sequential;
+use app_units::Au;
+use euclid::default::{Point2D, Rect, SideOffsets2D, Size2D};
+use msg::constellation_msg::PipelineId;
+use script_traits::ScrollSnapContainer;
+use style::computed_values::overflow_x::T as Overflow;
+use style::properties::ComputedValues;
+use style::values::computed::{NonNegativeLengthPercentageOrAuto, ScrollSnapAxis};
+use style::values::computed::{ScrollSnapStrictness, ScrollSnapType};
+use style::values::specified::box_::ScrollSnapAlignKeyword;
+use webrender_api::ExternalScrollId;
+
+/// A scroll container whose snap areas are being collected.
+struct OpenContainer {
+    /// The level in the flow tree of the fragment of the scroll container.
+    level: i32,
+    scroll_id: ExternalScrollId,
+    snap_type: ScrollSnapType,
+    /// The padding box of the scroll container when it is not scrolled.
+    scrollport: Rect<Au>,
+    /// The scrollport without the scroll padding of the scroll container.
+    snapport: Rect<Au>,
+    x_positions: Vec<Au>,
+    y_positions: Vec<Au>,
+}
+
+impl OpenContainer {
+    fn snaps_x(&self) -> bool {
+        match self.snap_type.axis {
+            ScrollSnapAxis::X | ScrollSnapAxis::Inline | ScrollSnapAxis::Both => true,
+            ScrollSnapAxis::Y | ScrollSnapAxis::Block => false,
+        }
+    }
+
+    fn snaps_y(&self) -> bool {
+        match self.snap_type.axis {
+            ScrollSnapAxis::Y | ScrollSnapAxis::Block | ScrollSnapAxis::Both => true,
+            ScrollSnapAxis::X | ScrollSnapAxis::Inline => false,
+        }
+    }
+
+    /// Adds the snap positions of the snap area of `style` with the given border box.
+    fn add_snap_area(&mut self, style: &ComputedValues, border_box: &Rect<Au>) {
+        let margin = style.get_margin();
+        let snap_area = border_box.outer_rect(SideOffsets2D::new(
+            Au::from(margin.scroll_margin_top),
+            Au::from(margin.scroll_margin_right),
+            Au::from(margin.scroll_margin_bottom),
+            Au::from(margin.scroll_margin_left),
+        ));
+        let align = style.get_box().scroll_snap_align;
+        if self.snaps_x() {
+            let position = snap_position(
+                align.inline,
+                (snap_area.origin.x, snap_area.max_x()),
+                (self.snapport.origin.x, self.snapport.max_x()),
+            );
+            self.x_positions.extend(position);
+        }
+        if self.snaps_y() {
+            let position = snap_position(
+                align.block,
+                (snap_area.origin.y, snap_area.max_y()),
+                (self.snapport.origin.y, self.snapport.max_y()),
+            );
+            self.y_positions.extend(position);
+        }
+    }
+
+    fn finish(mut self) -> Option<ScrollSnapContainer> {
+        if self.snap_type.strictness == ScrollSnapStrictness::None ||
+            (self.x_positions.is_empty() && self.y_positions.is_empty())
+        {
+            return None;
+        }
+        self.x_positions.sort();
+        self.x_positions.dedup();
+        self.y_positions.sort();
+        self.y_positions.dedup();
+        Some(ScrollSnapContainer {
+            scroll_id: self.scroll_id,
+            mandatory: self.snap_type.strictness == ScrollSnapStrictness::Mandatory,
+            scrollport_size: self.scrollport.size.to_layout(),
+            x_positions: self.x_positions.iter().map(|x| x.to_f32_px()).collect(),
+            y_positions: self.y_positions.iter().map(|y| y.to_f32_px()).collect(),
+        })
+    }
+}
+
+/// The used value of a `scroll-padding` side of a scroll container whose scrollport is `size`
+/// long along the axis of that side.
+fn used_scroll_padding(padding: &NonNegativeLengthPercentageOrAuto, size: Au) -> Au {
+    padding.to_used_value(size).unwrap_or(Au(0))
+}
+
+/// The scroll position along an axis that aligns `snap_area` with `snapport` as `align` asks
+/// for, where both are given as the start and end of a range along the axis.
+fn snap_position(
+    align: ScrollSnapAlignKeyword,
+    snap_area: (Au, Au),
+    snapport: (Au, Au),
+) -> Option<Au> {
+    let position = match align {
+        ScrollSnapAlignKeyword::None => return None,
+        ScrollSnapAlignKeyword::Start => snap_area.0 - snapport.0,
+        ScrollSnapAlignKeyword::End => snap_area.1 - snapport.1,
+        ScrollSnapAlignKeyword::Center => (snap_area.0 + snap_area.1 - snapport.0 - snapport.1) / 2,
+    };
+    Some(position.max(Au(0)))
+}
+
+struct ScrollSnapCollector {
+    pipeline_id: PipelineId,
+    viewport_size: Size2D<Au>,
+    /// The scroll containers around the fragment that is processed, innermost last.
+    open_containers: Vec<OpenContainer>,
+    containers: Vec<ScrollSnapContainer>,
+}
+
+impl ScrollSnapCollector {
+    /// Finishes the scroll containers that are not around fragments at `level` of the flow tree.
+    fn close_containers(&mut self, level: i32) {
+        while self
+            .open_containers
+            .last()
+            .map_or(false, |container| container.level >= level)
+        {
+            let container = self.open_containers.pop().unwrap();
+            self.containers.extend(container.finish());
+        }
+    }
+}
+
+impl FragmentBorderBoxIterator for ScrollSnapCollector {
+    fn process(&mut self, fragment: &Fragment, level: i32, border_box: &Rect<Au>) {
+        self.close_containers(level);
+
+        let style = fragment.style();
+        let align = style.get_box().scroll_snap_align;
+        if align.block != ScrollSnapAlignKeyword::None ||
+            align.inline != ScrollSnapAlignKeyword::None
+        {
+            if let Some(container) = self.open_containers.last_mut() {
+                container.add_snap_area(style, border_box);
+            }
+        }
+
+        // The scroll container of the root element is the viewport.
+        let box_style = style.get_box();
+        let (scroll_id, scrollport) = if level == 0 {
+            (
+                self.pipeline_id.root_scroll_id(),
+                Rect::new(Point2D::zero(), self.viewport_size),
+            )
+        } else if box_style.overflow_x != Overflow::Visible ||
+            box_style.overflow_y != Overflow::Visible
+        {
+            let border_widths = style.logical_border_width().to_physical(style.writing_mode);
+            (
+                ExternalScrollId(fragment.unique_id(), self.pipeline_id.to_webrender()),
+                border_box.inner_rect(border_widths),
+            )
+        } else {
+            return;
+        };
+
+        let padding = style.get_padding();
+        let snapport = scrollport.inner_rect(SideOffsets2D::new(
+            used_scroll_padding(&padding.scroll_padding_top, scrollport.size.height),
+            used_scroll_padding(&padding.scroll_padding_right, scrollport.size.width),
+            used_scroll_padding(&padding.scroll_padding_bottom, scrollport.size.height),
+            used_scroll_padding(&padding.scroll_padding_left, scrollport.size.width),
+        ));
+        self.open_containers.push(OpenContainer {
+            level,
+            scroll_id,
+            snap_type: box_style.scroll_snap_type,
+            scrollport,
+            snapport,
+            x_positions: vec![],
+            y_positions: vec![],
+        });
+    }
+
+    fn should_process(&mut self, _: &Fragment) -> bool {
+        true
+    }
+}
+
+/// Finds the snap positions of the scroll containers with a `scroll-snap-type` in the flow tree
+/// of `root`, whose viewport is `viewport_size`.
+pub fn collect_scroll_snap_containers(
+    root: &mut dyn Flow,
+    pipeline_id: PipelineId,
+    viewport_size: Size2D<Au>,
+) -> Vec<ScrollSnapContainer> {
+    let mut collector = ScrollSnapCollector {
+        pipeline_id,
+        viewport_size,
+        open_containers: vec![],
+        containers: vec![],
+    };
+    sequential::iterate_through_flow_tree_fragment_border_boxes(root, &mut collector);
+    collector.close_containers(0);
+    collector.containers
+}
@@ -0,0 +1,245 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Scroll-driven animations, whose progress follows the scroll position of a scroll container
+//! instead of time.
+//!
+//! Layout knows the scroll positions that the compositor scrolled to, so it updates the
+//! progress of the timelines of these animations each time animations are ticked, without
+//! waiting on script. The block and inline axes are taken to be the vertical and horizontal
+//! axes, whatever the writing mode is.
+
+use crate::display_list::items::{OpaqueNode, ScrollOffsetMap};
+use crate::flow::Flow;
+use crate::fragment::{Fragment, FragmentBorderBoxIterator};
+use crate::sequential;
+use app_units::Au;
+use euclid::default::{Point2D, Rect, Size2D};
+use fxhash::FxHashMap;
+use msg::constellation_msg::PipelineId;
+use style::animation::Animation;
+use style::computed_values::overflow_x::T as Overflow;
+use style::values::computed::{AnimationTimeline, ScrollAxis, Scroller};
+use webrender_api::ExternalScrollId;
+
+/// A scroll container that may drive scroll-driven animations.
+struct TimelineContainer {
+    scroll_id: ExternalScrollId,
+    /// The padding box of the scroll container when it is not scrolled.
+    scrollport: Rect<Au>,
+    /// The union of the scrollport and of the border boxes of the fragments in the scroll
+    /// container, which is what can be scrolled to.
+    scrollable_overflow: Rect<Au>,
+}
+
+/// An element with scroll-driven animations, and the scroll containers around it.
+struct TimelineSubject {
+    border_box: Rect<Au>,
+    /// The index of the nearest scroll container around the element.
+    nearest_container: Option<usize>,
+    /// The index of the scroll container of the element itself, if it is one.
+    own_container: Option<usize>,
+}
+
+struct ScrollTimelineCollector<'a> {
+    pipeline_id: PipelineId,
+    viewport_size: Size2D<Au>,
+    animations: &'a FxHashMap<OpaqueNode, Vec<Animation>>,
+    containers: Vec<TimelineContainer>,
+    /// The levels in the flow tree and the indices of the scroll containers around the
+    /// fragment that is processed, innermost last.
+    open_containers: Vec<(i32, usize)>,
+    subjects: FxHashMap<OpaqueNode, TimelineSubject>,
+}
+
+impl<'a> FragmentBorderBoxIterator for ScrollTimelineCollector<'a> {
+    fn process(&mut self, fragment: &Fragment, level: i32, border_box: &Rect<Au>) {
+        while self
+            .open_containers
+            .last()
+            .map_or(false, |&(container_level, _)| container_level >= level)
+        {
+            self.open_containers.pop();
+        }
+
+        let nearest_container = self.open_containers.last().map(|&(_, index)| index);
+        if let Some(index) = nearest_container {
+            let container = &mut self.containers[index];
+            container.scrollable_overflow = container.scrollable_overflow.union(border_box);
+        }
+
+        let is_subject = self
+            .animations
+            .get(&fragment.node)
+            .map_or(false, |animations| has_scroll_driven_animations(animations));
+        if is_subject && !self.subjects.contains_key(&fragment.node) {
+            self.subjects.insert(
+                fragment.node,
+                TimelineSubject {
+                    border_box: *border_box,
+                    nearest_container,
+                    own_container: None,
+                },
+            );
+        }
+
+        // The scroll container of the root element is the viewport.
+        let style = fragment.style();
+        let box_style = style.get_box();
+        let (scroll_id, scrollport) = if level == 0 {
+            (
+                self.pipeline_id.root_scroll_id(),
+                Rect::new(Point2D::zero(), self.viewport_size),
+            )
+        } else if box_style.overflow_x != Overflow::Visible ||
+            box_style.overflow_y != Overflow::Visible
+        {
+            let border_widths = style.logical_border_width().to_physical(style.writing_mode);
+            (
+                ExternalScrollId(fragment.unique_id(), self.pipeline_id.to_webrender()),
+                border_box.inner_rect(border_widths),
+            )
+        } else {
+            return;
+        };
+
+        let index = self.containers.len();
+        self.containers.push(TimelineContainer {
+            scroll_id,
+            scrollport,
+            scrollable_overflow: scrollport,
+        });
+        self.open_containers.push((level, index));
+        if let Some(subject) = self.subjects.get_mut(&fragment.node) {
+            if subject.own_container.is_none() {
+                subject.own_container = Some(index);
+            }
+        }
+    }
+
+    fn should_process(&mut self, _: &Fragment) -> bool {
+        true
+    }
+}
+
+fn has_scroll_driven_animations(animations: &[Animation]) -> bool {
+    animations.iter().any(|animation| match *animation {
+        Animation::Keyframes(_, _, _, ref state) => state.is_scroll_driven(),
+        Animation::Transition(..) => false,
+    })
+}
+
+/// The start and end of `rect` along `axis`.
+fn range_along_axis(rect: &Rect<Au>, axis: ScrollAxis) -> (Au, Au) {
+    match axis {
+        ScrollAxis::Block | ScrollAxis::Y => (rect.origin.y, rect.max_y()),
+        ScrollAxis::Inline | ScrollAxis::X => (rect.origin.x, rect.max_x()),
+    }
+}
+
+impl TimelineContainer {
+    /// The scroll position of this scroll container along `axis`.
+    fn scroll_position(&self, scroll_offsets: &ScrollOffsetMap, axis: ScrollAxis) -> f64 {
+        // Scroll offsets are the negated scroll positions.
+        let offset = match scroll_offsets.get(&self.scroll_id) {
+            Some(offset) => offset,
+            None => return 0.,
+        };
+        match axis {
+            ScrollAxis::Block | ScrollAxis::Y => -offset.y as f64,
+            ScrollAxis::Inline | ScrollAxis::X => -offset.x as f64,
+        }
+    }
+
+    /// The progress of a `scroll()` timeline of this scroll container, or `None` if this scroll
+    /// container can't scroll along `axis`.
+    fn scroll_progress(&self, scroll_offsets: &ScrollOffsetMap, axis: ScrollAxis) -> Option<f64> {
+        let (_, scrollport_end) = range_along_axis(&self.scrollport, axis);
+        let (_, overflow_end) = range_along_axis(&self.scrollable_overflow, axis);
+        let scroll_range = (overflow_end - scrollport_end).to_f64_px();
+        if scroll_range <= 0. {
+            return None;
+        }
+        let position = self.scroll_position(scroll_offsets, axis);
+        Some((position / scroll_range).max(0.).min(1.))
+    }
+
+    /// The progress of a `view()` timeline of an element with the given border box in this
+    /// scroll container, which goes from the element starting to enter the scrollport to the
+    /// element having completely left it.
+    fn view_progress(
+        &self,
+        scroll_offsets: &ScrollOffsetMap,
+        axis: ScrollAxis,
+        border_box: &Rect<Au>,
+    ) -> Option<f64> {
+        let (scrollport_start, scrollport_end) = range_along_axis(&self.scrollport, axis);
+        let (subject_start, subject_end) = range_along_axis(border_box, axis);
+        let start = (subject_start - scrollport_end).to_f64_px();
+        let end = (subject_end - scrollport_start).to_f64_px();
+        if end <= start {
+            return None;
+        }
+        let position = self.scroll_position(scroll_offsets, axis);
+        Some(((position - start) / (end - start)).max(0.).min(1.))
+    }
+}
+
+/// Updates the progress of the timelines of the scroll-driven animations in `animations`
+/// from the scroll positions in `scroll_offsets`, for the flow tree of `root` whose viewport
+/// is `viewport_size`.
+pub fn update_scroll_timelines(
+    root: &mut dyn Flow,
+    pipeline_id: PipelineId,
+    viewport_size: Size2D<Au>,
+    scroll_offsets: &ScrollOffsetMap,
+    animations: &mut FxHashMap<OpaqueNode, Vec<Animation>>,
+) {
+    let (subjects, containers) = {
+        let mut collector = ScrollTimelineCollector {
+            pipeline_id,
+            viewport_size,
+            animations: &*animations,
+            containers: vec![],
+            open_containers: vec![],
+            subjects: FxHashMap::default(),
+        };
+        sequential::iterate_through_flow_tree_fragment_border_boxes(root, &mut collector);
+        (collector.subjects, collector.containers)
+    };
+
+    for (node, node_animations) in animations.iter_mut() {
+        let subject = subjects.get(node);
+        for animation in node_animations.iter_mut() {
+            let state = match *animation {
+                Animation::Keyframes(_, _, _, ref mut state) if state.is_scroll_driven() => state,
+                _ => continue,
+            };
+            // Elements without a box have inactive timelines.
+            let subject = match subject {
+                Some(subject) => subject,
+                None => {
+                    state.timeline_progress = None;
+                    continue;
+                },
+            };
+            state.timeline_progress = match state.timeline {
+                AnimationTimeline::Scroll(axis, scroller) => {
+                    let container = match scroller {
+                        Scroller::Nearest => subject.nearest_container,
+                        Scroller::Root if containers.is_empty() => None,
+                        Scroller::Root => Some(0),
+                        Scroller::SelfElement => subject.own_container,
+                    };
+                    container
+                        .and_then(|index| containers[index].scroll_progress(scroll_offsets, axis))
+                },
+                AnimationTimeline::View(axis) => subject.nearest_container.and_then(|index| {
+                    containers[index].view_progress(scroll_offsets, axis, &subject.border_box)
+                }),
+                AnimationTimeline::Auto | AnimationTimeline::None => None,
+            };
+        }
+    }
+}
@@ -0,0 +1,148 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Finds the scroll containers of a document and the `scrollbar-width` and `scrollbar-color`
+//! of their scrollbars, for the compositor to paint overlay scrollbars over them while the user
+//! scrolls.
+//!
+//! The scrollable area of a scroll container is approximated by the border boxes of the
+//! fragments below it in the flow tree, up to the next scroll container.
+
+use crate::display_list::ToLayout;
+use crate::flow::Flow;
+use crate::fragment::{Fragment, FragmentBorderBoxIterator};
+use crate::sequential;
+use app_units::Au;
+use euclid::default::{Point2D, Rect, Size2D};
+use msg::constellation_msg::PipelineId;
+use script_traits::{ScrollbarContainer, ScrollbarWidth};
+use servo_config::pref;
+use style::computed_values::overflow_x::T as Overflow;
+use style::computed_values::scrollbar_width::T as ComputedScrollbarWidth;
+use style::values::computed::ui::ScrollbarColor;
+use webrender_api::units::LayoutSize;
+use webrender_api::ExternalScrollId;
+
+/// Whether the user can scroll a scroll container with the given overflow along an axis. The
+/// viewport can be scrolled unless the root element hides its overflow.
+fn is_user_scrollable(overflow: Overflow, is_viewport: bool) -> bool {
+    match overflow {
+        Overflow::Scroll | Overflow::Auto => true,
+        Overflow::Visible => is_viewport,
+        _ => false,
+    }
+}
+
+struct ScrollbarCollector {
+    pipeline_id: PipelineId,
+    viewport_size: Size2D<Au>,
+    /// The levels in the flow tree of the scroll containers around the fragment that is
+    /// processed, and their indices in `containers`, innermost last.
+    open_containers: Vec<(i32, usize)>,
+    containers: Vec<ScrollbarContainer>,
+    /// The top left and bottom right corners of the scrollable areas of `containers`, when
+    /// none of the scroll containers are scrolled.
+    scrollable_areas: Vec<(Point2D<Au>, Point2D<Au>)>,
+}
+
+impl FragmentBorderBoxIterator for ScrollbarCollector {
+    fn process(&mut self, fragment: &Fragment, level: i32, border_box: &Rect<Au>) {
+        while self
+            .open_containers
+            .last()
+            .map_or(false, |&(container_level, _)| container_level >= level)
+        {
+            self.open_containers.pop();
+        }
+
+        let parent = self.open_containers.last().map(|&(_, index)| index);
+        if let Some(index) = parent {
+            let extent = &mut self.scrollable_areas[index].1;
+            *extent = extent.max(border_box.bottom_right());
+        }
+
+        // The scroll container of the root element is the viewport, whose scrollbars take
+        // their style from the root element.
+        let style = fragment.style();
+        let box_style = style.get_box();
+        let is_viewport = level == 0;
+        let (scroll_id, scrollport) = if is_viewport {
+            (
+                self.pipeline_id.root_scroll_id(),
+                Rect::new(Point2D::zero(), self.viewport_size),
+            )
+        } else if box_style.overflow_x != Overflow::Visible ||
+            box_style.overflow_y != Overflow::Visible
+        {
+            let border_widths = style.logical_border_width().to_physical(style.writing_mode);
+            (
+                ExternalScrollId(fragment.unique_id(), self.pipeline_id.to_webrender()),
+                border_box.inner_rect(border_widths),
+            )
+        } else {
+            return;
+        };
+
+        let width = match style.get_ui().scrollbar_width {
+            ComputedScrollbarWidth::Auto => ScrollbarWidth::Auto,
+            ComputedScrollbarWidth::Thin => ScrollbarWidth::Thin,
+            ComputedScrollbarWidth::None => ScrollbarWidth::None,
+        };
+        let colors = match style.get_inherited_ui().scrollbar_color {
+            ScrollbarColor::Auto => None,
+            ScrollbarColor::Colors { thumb, track } => Some((
+                style.resolve_color(thumb).to_layout(),
+                style.resolve_color(track).to_layout(),
+            )),
+        };
+
+        self.containers.push(ScrollbarContainer {
+            scroll_id,
+            parent_scroll_id: parent.map(|index| self.containers[index].scroll_id),
+            scrollport: scrollport.to_layout(),
+            content_size: LayoutSize::zero(),
+            scrollable_x: is_user_scrollable(box_style.overflow_x, is_viewport),
+            scrollable_y: is_user_scrollable(box_style.overflow_y, is_viewport),
+            width,
+            colors,
+        });
+        let mut extent = scrollport.bottom_right();
+        if is_viewport {
+            extent = extent.max(border_box.bottom_right());
+        }
+        self.scrollable_areas.push((scrollport.origin, extent));
+        self.open_containers
+            .push((level, self.containers.len() - 1));
+    }
+
+    fn should_process(&mut self, _: &Fragment) -> bool {
+        true
+    }
+}
+
+/// Finds the scroll containers in the flow tree of `root`, whose viewport is `viewport_size`,
+/// with the viewport first and every scroll container after the ones around it.
+pub fn collect_scrollbar_containers(
+    root: &mut dyn Flow,
+    pipeline_id: PipelineId,
+    viewport_size: Size2D<Au>,
+) -> Vec<ScrollbarContainer> {
+    if !pref!(layout.scrollbars.enabled) {
+        return vec![];
+    }
+
+    let mut collector = ScrollbarCollector {
+        pipeline_id,
+        viewport_size,
+        open_containers: vec![],
+        containers: vec![],
+        scrollable_areas: vec![],
+    };
+    sequential::iterate_through_flow_tree_fragment_border_boxes(root, &mut collector);
+    let mut containers = collector.containers;
+    for (container, (origin, extent)) in containers.iter_mut().zip(collector.scrollable_areas) {
+        container.content_size = Size2D::new(extent.x - origin.x, extent.y - origin.y).to_layout();
+    }
+    containers
+}
@@ -12,6 +12,7 @@ use crate::flow::{Flow, FlowFlags, GetBaseFlow, ImmutableFlowUtils};
 use crate::fragment::{CoordinateSystem, FragmentBorderBoxIterator};
 use crate::generated_content::ResolveGeneratedContent;
 use crate::incremental::RelayoutMode;
+use crate::parallel;
 use crate::traversal::{AssignBSizes, AssignISizes, BubbleISizes, BuildDisplayList};
 use crate::traversal::{InorderFlowTraversal, PostorderFlowTraversal, PreorderFlowTraversal};
 use app_units::Au;
@@ -70,11 +71,14 @@ pub fn reflow(root: &mut dyn Flow, layout_context: &LayoutContext, relayout_mode
     doit(root, assign_inline_sizes, assign_block_sizes, relayout_mode);
 }
 
+/// Builds the display list of the given flow tree, building the display items of its flows in
+/// parallel on the given thread pool if there is one.
 pub fn build_display_list_for_subtree<'a>(
     flow_root: &mut dyn Flow,
     layout_context: &'a LayoutContext,
     background_color: webrender_api::ColorF,
     client_size: Size2D<Au>,
+    queue: Option<&rayon::ThreadPool>,
 ) -> DisplayListBuildState<'a> {
     let mut state = StackingContextCollectionState::new(layout_context.id);
     flow_root.collect_stacking_contexts(&mut state);
@@ -97,6 +101,10 @@ pub fn build_display_list_for_subtree<'a>(
         },
     )));
 
+    if let Some(queue) = queue {
+        parallel::build_display_items(flow_root, &state, queue);
+    }
+
     let mut build_display_list = BuildDisplayList { state: state };
     build_display_list.traverse(flow_root);
     build_display_list.state
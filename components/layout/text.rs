@@ -17,6 +17,7 @@ use gfx::text::util::{self, CompressionMode};
 use range::Range;
 use servo_atoms::Atom;
 use std::borrow::ToOwned;
+use std::cmp;
 use std::collections::LinkedList;
 use std::mem;
 use std::sync::Arc;
@@ -226,21 +227,27 @@ impl TextRunScanner {
                 let mut mapping = RunMapping::new(&run_info_list[..], fragment_index);
                 let text;
                 let selection;
+                let highlights;
+                let spelling_errors;
                 match in_fragment.specific {
                     SpecificFragmentInfo::UnscannedText(ref text_fragment_info) => {
                         text = &text_fragment_info.text;
                         selection = text_fragment_info.selection;
+                        highlights = &text_fragment_info.highlights;
+                        spelling_errors = &text_fragment_info.spelling_errors;
                     },
                     _ => panic!("Expected an unscanned text fragment!"),
                 };
-                insertion_point = match selection {
-                    Some(range) if range.is_empty() => {
+                // Only overwrite a pending insertion point when this fragment has its own, so that
+                // a caret in an earlier fragment of the clump survives until its run is flushed.
+                if let Some(range) = selection {
+                    if range.is_empty() {
                         // `range` is the range within the current fragment. To get the range
                         // within the text run, offset it by the length of the preceding fragments.
-                        Some(range.begin() + ByteIndex(run_info.text.len() as isize))
-                    },
-                    _ => None,
-                };
+                        insertion_point =
+                            Some(range.begin() + ByteIndex(run_info.text.len() as isize));
+                    }
+                }
 
                 let (mut start_position, mut end_position) = (0, 0);
                 for (byte_index, character) in text.char_indices() {
@@ -270,12 +277,21 @@ impl TextRunScanner {
                             Some(range) => range.contains(ByteIndex(byte_index as isize)),
                             None => false,
                         };
+                        let highlight = highlights.iter().rposition(|&(ref range, _)| {
+                            range.contains(ByteIndex(byte_index as isize))
+                        });
+                        let misspelled = spelling_errors
+                            .iter()
+                            .any(|range| range.contains(ByteIndex(byte_index as isize)));
 
                         // Now, if necessary, flush the mapping we were building up.
                         let flush_run = !run_info.has_font(&font) ||
                             run_info.bidi_level != bidi_level ||
                             !compatible_script;
-                        let new_mapping_needed = flush_run || mapping.selected != selected;
+                        let new_mapping_needed = flush_run ||
+                            mapping.selected != selected ||
+                            mapping.highlight != highlight ||
+                            mapping.misspelled != misspelled;
 
                         if new_mapping_needed {
                             // We ignore empty mappings at the very start of a fragment.
@@ -304,6 +320,8 @@ impl TextRunScanner {
                             run_info.bidi_level = bidi_level;
                             run_info.script = script;
                             mapping.selected = selected;
+                            mapping.highlight = highlight;
+                            mapping.misspelled = misspelled;
                         }
                     }
 
@@ -442,6 +460,10 @@ impl TextRunScanner {
                     flags.insert(ScannedTextFlags::SELECTED);
                 }
 
+                if mapping.misspelled {
+                    flags.insert(ScannedTextFlags::MISSPELLED);
+                }
+
                 let insertion_point =
                     if mapping.contains_insertion_point(scanned_run.insertion_point) {
                         scanned_run.insertion_point
@@ -457,6 +479,12 @@ impl TextRunScanner {
                     flags,
                 ));
 
+                if let (Some(index), &SpecificFragmentInfo::UnscannedText(ref info)) =
+                    (mapping.highlight, &old_fragment.specific)
+                {
+                    new_text_fragment_info.highlight_style = Some(info.highlights[index].1.clone());
+                }
+
                 let new_metrics = new_text_fragment_info.run.metrics_for_range(&byte_range);
                 let writing_mode = old_fragment.style.writing_mode;
                 let bounding_box_size = bounding_box_for_run_metrics(&new_metrics, writing_mode);
@@ -550,6 +578,8 @@ fn split_first_fragment_at_newline_if_necessary(fragments: &mut LinkedList<Fragm
         let first_fragment = fragments.front_mut().unwrap();
         let string_before;
         let selection_before;
+        let mut highlights_before = vec![];
+        let mut spelling_errors_before = vec![];
         {
             if !first_fragment.white_space().preserve_newlines() {
                 return;
@@ -591,13 +621,48 @@ fn split_first_fragment_at_newline_if_necessary(fragments: &mut LinkedList<Fragm
                     unscanned_text_fragment_info.selection = None;
                 },
             };
+            // Highlights and spelling errors keep the parts of their ranges on either side
+            // of the split.
+            let highlights = mem::replace(&mut unscanned_text_fragment_info.highlights, vec![]);
+            for (range, style) in highlights {
+                if range.begin() < offset {
+                    let end = cmp::min(range.end(), offset);
+                    highlights_before.push((
+                        Range::new(range.begin(), end - range.begin()),
+                        style.clone(),
+                    ));
+                }
+                if range.end() > offset {
+                    let begin = cmp::max(range.begin(), offset);
+                    unscanned_text_fragment_info
+                        .highlights
+                        .push((Range::new(begin - offset, range.end() - begin), style));
+                }
+            }
+            let spelling_errors =
+                mem::replace(&mut unscanned_text_fragment_info.spelling_errors, vec![]);
+            for range in spelling_errors {
+                if range.begin() < offset {
+                    let end = cmp::min(range.end(), offset);
+                    spelling_errors_before.push(Range::new(range.begin(), end - range.begin()));
+                }
+                if range.end() > offset {
+                    let begin = cmp::max(range.begin(), offset);
+                    unscanned_text_fragment_info
+                        .spelling_errors
+                        .push(Range::new(begin - offset, range.end() - begin));
+                }
+            }
         }
+        let mut info_before = Box::new(UnscannedTextFragmentInfo::new(
+            string_before.into_boxed_str(),
+            selection_before,
+        ));
+        info_before.highlights = highlights_before;
+        info_before.spelling_errors = spelling_errors_before;
         first_fragment.transform(
             first_fragment.border_box.size,
-            SpecificFragmentInfo::UnscannedText(Box::new(UnscannedTextFragmentInfo::new(
-                string_before.into_boxed_str(),
-                selection_before,
-            ))),
+            SpecificFragmentInfo::UnscannedText(info_before),
         )
     };
 
@@ -668,6 +733,10 @@ struct RunMapping {
     text_run_index: usize,
     /// Is the text in this fragment selected?
     selected: bool,
+    /// The index of the topmost custom highlight that covers the text in this fragment, if any.
+    highlight: Option<usize>,
+    /// Is the text in this fragment a misspelled word?
+    misspelled: bool,
 }
 
 impl RunMapping {
@@ -679,6 +748,8 @@ impl RunMapping {
             old_fragment_index: fragment_index,
             text_run_index: run_info_list.len(),
             selected: false,
+            highlight: None,
+            misspelled: false,
         }
     }
 
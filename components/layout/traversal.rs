@@ -258,6 +258,7 @@ impl<'a> PreorderFlowTraversal for AssignISizes<'a> {
     #[inline]
     fn process(&self, flow: &mut dyn Flow) {
         flow.assign_inline_sizes(self.layout_context);
+        flow.mut_base().display_items = None;
     }
 
     #[inline]
@@ -289,6 +290,7 @@ impl<'a> PostorderFlowTraversal for AssignBSizes<'a> {
         }
 
         flow.assign_block_size(self.layout_context);
+        flow.mut_base().display_items = None;
     }
 
     #[inline]
@@ -327,24 +329,16 @@ pub struct BuildDisplayList<'a> {
 }
 
 impl<'a> BuildDisplayList<'a> {
+    /// Appends the display items of the flows to the display list in tree order, building the
+    /// ones that weren't built in parallel beforehand and can't be reused from the previous
+    /// display list.
     #[inline]
     pub fn traverse(&mut self, flow: &mut dyn Flow) {
-        let parent_stacking_context_id = self.state.current_stacking_context_id;
-        self.state.current_stacking_context_id = flow.base().stacking_context_id;
-
-        let parent_clipping_and_scrolling = self.state.current_clipping_and_scrolling;
-        self.state.current_clipping_and_scrolling = flow.clipping_and_scrolling();
-
-        flow.build_display_list(&mut self.state);
-        flow.mut_base()
-            .restyle_damage
-            .remove(ServoRestyleDamage::REPAINT);
+        self.state.build_display_items_for_flow(flow);
+        self.state.append_display_items_of_flow(flow);
 
         for kid in flow.mut_base().child_iter_mut() {
             self.traverse(kid);
         }
-
-        self.state.current_stacking_context_id = parent_stacking_context_id;
-        self.state.current_clipping_and_scrolling = parent_clipping_and_scrolling;
     }
 }
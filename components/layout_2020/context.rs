@@ -3,6 +3,7 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use crate::display_list::WebRenderImageInfo;
+use euclid::Size2D;
 use fnv::FnvHashMap;
 use gfx::font_cache_thread::FontCacheThread;
 use gfx::font_context::FontContext;
@@ -11,11 +12,17 @@ use net_traits::image_cache::{CanRequestImages, ImageCache, ImageState};
 use net_traits::image_cache::{ImageOrMetadataAvailable, UsePlaceholder};
 use parking_lot::RwLock;
 use script_layout_interface::{PendingImage, PendingImageState};
+use script_traits::Painter;
+use servo_atoms::Atom;
 use servo_url::{ImmutableOrigin, ServoUrl};
 use std::cell::RefCell;
 use std::sync::{Arc, Mutex};
-use style::context::SharedStyleContext;
+use style::context::{RegisteredSpeculativePainter, SharedStyleContext};
 use style::dom::OpaqueNode;
+use style::properties::ComputedValues;
+use style::values::generics::image::PaintWorklet;
+use style_traits::ToCss;
+use webrender_api::units::LayoutSize;
 
 pub struct LayoutContext<'a> {
     pub id: PipelineId,
@@ -37,6 +44,9 @@ pub struct LayoutContext<'a> {
 
     pub webrender_image_cache:
         Arc<RwLock<FnvHashMap<(ServoUrl, UsePlaceholder), WebRenderImageInfo>>>,
+
+    /// Paint worklets
+    pub registered_painters: &'a dyn RegisteredPainters,
 }
 
 impl<'a> Drop for LayoutContext<'a> {
@@ -150,6 +160,65 @@ impl<'a> LayoutContext<'a> {
             None | Some(ImageOrMetadataAvailable::MetadataAvailable(_)) => None,
         }
     }
+
+    /// Draws the image of a paint worklet at the given size.
+    /// Returns None if the worklet is not registered.
+    /// If the worklet has missing image URLs, it passes them to the image cache for loading.
+    pub fn get_webrender_image_for_paint_worklet(
+        &self,
+        node: OpaqueNode,
+        style: &ComputedValues,
+        paint_worklet: &PaintWorklet,
+        size: LayoutSize,
+    ) -> Option<WebRenderImageInfo> {
+        let name = &paint_worklet.name;
+        let painter = match self.registered_painters.get(name) {
+            Some(painter) => painter,
+            None => {
+                debug!("Worklet {} called before registration.", name);
+                return None;
+            },
+        };
+
+        let arguments = paint_worklet
+            .arguments
+            .iter()
+            .map(|argument| argument.to_css_string())
+            .collect();
+        let properties = painter
+            .properties()
+            .iter()
+            .filter_map(|(name, id)| id.as_shorthand().err().map(|id| (name, id)))
+            .map(|(name, id)| (name.clone(), style.computed_value_to_string(id)))
+            .collect();
+        let draw_result = painter
+            .draw_a_paint_image(
+                Size2D::new(size.width, size.height),
+                self.style_context.device_pixel_ratio(),
+                properties,
+                arguments,
+            )
+            .ok()?;
+
+        for url in draw_result.missing_image_urls {
+            debug!("Requesting missing image URL {}.", url);
+            self.get_webrender_image_for_url(node, url, UsePlaceholder::No);
+        }
+        Some(WebRenderImageInfo {
+            width: draw_result.width,
+            height: draw_result.height,
+            key: draw_result.image_key,
+        })
+    }
+}
+
+/// A registered painter
+pub trait RegisteredPainter: RegisteredSpeculativePainter + Painter {}
+
+/// A set of registered painters
+pub trait RegisteredPainters: Sync {
+    /// Look up a painter
+    fn get(&self, name: &Atom) -> Option<&dyn RegisteredPainter>;
 }
 
 pub(crate) type LayoutFontContext = FontContext<FontCacheThread>;
@@ -395,28 +395,29 @@ impl<'a> BuilderForBoxFragment<'a> {
                         if let Some(layer) =
                             background::layout_layer(self, builder, index, intrinsic)
                         {
-                            let image_rendering =
-                                image_rendering(self.fragment.style.clone_image_rendering());
-                            if layer.repeat {
-                                builder.wr.push_repeating_image(
-                                    &layer.common,
-                                    layer.bounds,
-                                    layer.tile_size,
-                                    layer.tile_spacing,
-                                    image_rendering,
-                                    wr::AlphaType::PremultipliedAlpha,
-                                    key,
-                                    wr::ColorF::WHITE,
-                                )
-                            } else {
-                                builder.wr.push_image(
-                                    &layer.common,
-                                    layer.bounds,
-                                    image_rendering,
-                                    wr::AlphaType::PremultipliedAlpha,
-                                    key,
-                                    wr::ColorF::WHITE,
-                                )
+                            self.push_background_image(builder, &layer, key)
+                        }
+                    },
+                    Image::PaintWorklet(paint_worklet) => {
+                        // https://drafts.css-houdini.org/css-paint-api/#paint-notation
+                        // Paint images have no intrinsic dimensions, they are drawn at the
+                        // concrete size of the background tiles.
+                        let intrinsic = IntrinsicSizes {
+                            width: None,
+                            height: None,
+                            ratio: None,
+                        };
+                        if let Some(layer) =
+                            background::layout_layer(self, builder, index, intrinsic)
+                        {
+                            let image = builder.context.get_webrender_image_for_paint_worklet(
+                                self.fragment.tag,
+                                &self.fragment.style,
+                                paint_worklet,
+                                layer.tile_size,
+                            );
+                            if let Some(WebRenderImageInfo { key: Some(key), .. }) = image {
+                                self.push_background_image(builder, &layer, key)
                             }
                         }
                     },
@@ -427,6 +428,36 @@ impl<'a> BuilderForBoxFragment<'a> {
         }
     }
 
+    fn push_background_image(
+        &self,
+        builder: &mut DisplayListBuilder,
+        layer: &background::BackgroundLayer,
+        key: wr::ImageKey,
+    ) {
+        let image_rendering = image_rendering(self.fragment.style.clone_image_rendering());
+        if layer.repeat {
+            builder.wr.push_repeating_image(
+                &layer.common,
+                layer.bounds,
+                layer.tile_size,
+                layer.tile_spacing,
+                image_rendering,
+                wr::AlphaType::PremultipliedAlpha,
+                key,
+                wr::ColorF::WHITE,
+            )
+        } else {
+            builder.wr.push_image(
+                &layer.common,
+                layer.bounds,
+                image_rendering,
+                wr::AlphaType::PremultipliedAlpha,
+                key,
+                wr::ColorF::WHITE,
+            )
+        }
+    }
+
     fn build_border(&mut self, builder: &mut DisplayListBuilder) {
         let b = self.fragment.style.get_border();
         let widths = SideOffsets2D::new(
@@ -319,6 +319,8 @@ where
 
 pub struct BoxSlot<'dom> {
     slot: Option<ArcRefCell<Option<LayoutBox>>>,
+    /// The box previously in the slot, if its subtree wasn't damaged since it was constructed.
+    reusable: Option<LayoutBox>,
     marker: marker<&'dom ()>,
 }
 
@@ -326,12 +328,37 @@ impl BoxSlot<'_> {
     pub(crate) fn new(slot: ArcRefCell<Option<LayoutBox>>) -> Self {
         *slot.borrow_mut() = None;
         let slot = Some(slot);
-        Self { slot, marker }
+        let reusable = None;
+        Self {
+            slot,
+            reusable,
+            marker,
+        }
+    }
+
+    /// Like `new`, but keeps the box previously in the slot so that it can be reused.
+    pub(crate) fn new_reusing(slot: ArcRefCell<Option<LayoutBox>>) -> Self {
+        let reusable = slot.borrow_mut().take();
+        let slot = Some(slot);
+        Self {
+            slot,
+            reusable,
+            marker,
+        }
     }
 
     pub(crate) fn dummy() -> Self {
         let slot = None;
-        Self { slot, marker }
+        let reusable = None;
+        Self {
+            slot,
+            reusable,
+            marker,
+        }
+    }
+
+    pub(crate) fn take_reusable_box(&mut self) -> Option<LayoutBox> {
+        self.reusable.take()
     }
 
     pub(crate) fn set(mut self, box_: LayoutBox) {
@@ -447,7 +474,12 @@ where
     }
 
     fn element_box_slot(&self) -> BoxSlot<'dom> {
-        BoxSlot::new(self.layout_data_mut().self_box.clone())
+        let mut data = self.layout_data_mut();
+        if std::mem::replace(&mut data.subtree_damaged, false) {
+            BoxSlot::new(data.self_box.clone())
+        } else {
+            BoxSlot::new_reusing(data.self_box.clone())
+        }
     }
 
     fn pseudo_element_box_slot(&self, which: WhichPseudoElement) -> BoxSlot<'dom> {
@@ -10,6 +10,10 @@ use crate::flow::BlockLevelBox;
 pub struct LayoutDataForElement {
     pub(super) self_box: ArcRefCell<Option<LayoutBox>>,
     pub(super) pseudo_elements: Option<Box<PseudoElementBoxes>>,
+
+    /// Whether this element or one of its descendants was damaged by a restyle since the boxes
+    /// of the element were last constructed, in which case they can't be reused.
+    pub(super) subtree_damaged: bool,
 }
 
 #[derive(Default)]
@@ -669,7 +669,7 @@ where
     Node: NodeExt<'dom>,
 {
     fn finish(
-        self,
+        mut self,
         context: &LayoutContext,
         max_assign_in_flow_outer_content_sizes_to: Option<&mut ContentSizes>,
     ) -> (ArcRefCell<BlockLevelBox>, ContainsFloats) {
@@ -705,6 +705,19 @@ where
                     max_assign_in_flow_outer_content_sizes_to.is_some() &&
                         !style.inline_size_is_length(),
                 );
+                let reusable_box = self.box_slot.take_reusable_box().and_then(|box_| {
+                    reusable_independent_box(box_, content_sizes, propagated_text_decoration_line)
+                });
+                if let Some(block_level_box) = reusable_box {
+                    if let BlockLevelBox::Independent(contents) = &*block_level_box.borrow() {
+                        if let Some(to) = max_assign_in_flow_outer_content_sizes_to {
+                            to.max_assign(&contents.content_sizes.outer_inline(&contents.style))
+                        }
+                    }
+                    self.box_slot
+                        .set(LayoutBox::BlockLevel(block_level_box.clone()));
+                    return (block_level_box, ContainsFloats::No);
+                }
                 let contents = IndependentFormattingContext::construct(
                     context,
                     node,
@@ -754,6 +767,30 @@ where
     }
 }
 
+/// Returns the box previously constructed for an element establishing an independent
+/// formatting context, if it can be reused as is.
+fn reusable_independent_box(
+    box_: LayoutBox,
+    content_sizes: ContentSizesRequest,
+    propagated_text_decoration_line: TextDecorationLine,
+) -> Option<ArcRefCell<BlockLevelBox>> {
+    let block_level_box = match box_ {
+        LayoutBox::BlockLevel(block_level_box) => block_level_box,
+        LayoutBox::DisplayContents | LayoutBox::InlineLevel(_) => return None,
+    };
+    let can_be_reused = match &*block_level_box.borrow() {
+        BlockLevelBox::Independent(contents) => {
+            contents.can_be_reused(content_sizes, propagated_text_decoration_line)
+        },
+        _ => false,
+    };
+    if can_be_reused {
+        Some(block_level_box)
+    } else {
+        None
+    }
+}
+
 impl IntermediateBlockContainer {
     fn finish<'dom>(
         self,
@@ -11,11 +11,13 @@ use crate::replaced::ReplacedContent;
 use crate::sizing::{BoxContentSizes, ContentSizesRequest};
 use crate::style_ext::DisplayInside;
 use crate::ContainingBlock;
+use atomic_refcell::AtomicRefCell;
 use servo_arc::Arc;
 use std::convert::TryInto;
+use std::fmt;
 use style::dom::OpaqueNode;
 use style::properties::ComputedValues;
-use style::values::computed::Length;
+use style::values::computed::{Length, LengthOrAuto};
 use style::values::specified::text::TextDecorationLine;
 
 /// https://drafts.csswg.org/css-display/#independent-formatting-context
@@ -29,6 +31,60 @@ pub(crate) struct IndependentFormattingContext {
     pub content_sizes: BoxContentSizes,
 
     contents: IndependentFormattingContextContents,
+
+    /// The text decoration lines propagated to the contents during construction.
+    #[serde(skip_serializing)]
+    propagated_text_decoration_line: TextDecorationLine,
+
+    /// The last layout of the contents, reused while this box is reused by box tree
+    /// constructions and laid out in containing blocks of the same size.
+    #[serde(skip_serializing)]
+    layout_cache: LayoutCache,
+}
+
+#[derive(Default)]
+struct LayoutCache(AtomicRefCell<Option<CachedLayout>>);
+
+struct CachedLayout {
+    inline_size: Length,
+    block_size: LengthOrAuto,
+    fragments: Vec<Fragment>,
+    content_block_size: Length,
+}
+
+impl LayoutCache {
+    fn get(&self, containing_block: &ContainingBlock) -> Option<IndependentLayout> {
+        let cached = self.0.borrow();
+        let cached = cached.as_ref()?;
+        if cached.inline_size != containing_block.inline_size ||
+            cached.block_size != containing_block.block_size
+        {
+            return None;
+        }
+        Some(IndependentLayout {
+            fragments: cached.fragments.clone(),
+            content_block_size: cached.content_block_size,
+        })
+    }
+
+    fn set(&self, containing_block: &ContainingBlock, layout: &IndependentLayout) {
+        *self.0.borrow_mut() = Some(CachedLayout {
+            inline_size: containing_block.inline_size,
+            block_size: containing_block.block_size,
+            fragments: layout.fragments.clone(),
+            content_block_size: layout.content_block_size,
+        });
+    }
+
+    fn clear(&self) {
+        *self.0.borrow_mut() = None;
+    }
+}
+
+impl fmt::Debug for LayoutCache {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("LayoutCache")
+    }
 }
 
 pub(crate) struct IndependentLayout {
@@ -49,7 +105,7 @@ enum IndependentFormattingContextContents {
     // Other layout modes go here
 }
 
-pub(crate) struct NonReplacedIFC<'a>(NonReplacedIFCKind<'a>);
+pub(crate) struct NonReplacedIFC<'a>(NonReplacedIFCKind<'a>, &'a LayoutCache);
 
 enum NonReplacedIFCKind<'a> {
     Flow(&'a BlockFormattingContext),
@@ -81,6 +137,8 @@ impl IndependentFormattingContext {
                         style,
                         content_sizes,
                         contents: IndependentFormattingContextContents::Flow(bfc),
+                        propagated_text_decoration_line,
+                        layout_cache: LayoutCache::default(),
                     }
                 },
             },
@@ -91,6 +149,8 @@ impl IndependentFormattingContext {
                     style,
                     content_sizes,
                     contents: IndependentFormattingContextContents::Replaced(replaced),
+                    propagated_text_decoration_line,
+                    layout_cache: LayoutCache::default(),
                 }
             },
         }
@@ -102,9 +162,24 @@ impl IndependentFormattingContext {
         use self::NonReplacedIFCKind as Kind;
         match &self.contents {
             Contents::Replaced(r) => Ok(r),
-            Contents::Flow(f) => Err(NR(Kind::Flow(f))),
+            Contents::Flow(f) => Err(NR(Kind::Flow(f), &self.layout_cache)),
         }
     }
+
+    /// Whether this box, constructed for an element whose subtree wasn't damaged since, can
+    /// be reused instead of constructing a new one with the given parameters.
+    pub fn can_be_reused(
+        &self,
+        content_sizes: ContentSizesRequest,
+        propagated_text_decoration_line: TextDecorationLine,
+    ) -> bool {
+        let has_content_sizes = match self.content_sizes {
+            BoxContentSizes::NoneWereRequested => false,
+            BoxContentSizes::Inline(_) => true,
+        };
+        self.propagated_text_decoration_line == propagated_text_decoration_line &&
+            (has_content_sizes || !content_sizes.requests_inline())
+    }
 }
 
 impl NonReplacedIFC<'_> {
@@ -115,13 +190,28 @@ impl NonReplacedIFC<'_> {
         containing_block: &ContainingBlock,
         tree_rank: usize,
     ) -> IndependentLayout {
-        match &self.0 {
+        let layout_cache = self.1;
+        if let Some(layout) = layout_cache.get(containing_block) {
+            return layout;
+        }
+
+        let hoisted_boxes = positioning_context.hoisted_box_count();
+        let layout = match &self.0 {
             NonReplacedIFCKind::Flow(bfc) => bfc.layout(
                 layout_context,
                 positioning_context,
                 containing_block,
                 tree_rank,
             ),
+        };
+
+        // The layout can only be reused if it doesn't depend on absolutely positioned boxes
+        // laid out by an ancestor.
+        if positioning_context.hoisted_box_count() == hoisted_boxes {
+            layout_cache.set(containing_block, &layout);
+        } else {
+            layout_cache.clear();
         }
+        layout
     }
 }
@@ -23,7 +23,7 @@ use style::values::specified::text::TextDecorationLine;
 use style::Zero;
 use webrender_api::{FontInstanceKey, ImageKey};
 
-#[derive(Serialize)]
+#[derive(Clone, Serialize)]
 pub(crate) enum Fragment {
     Box(BoxFragment),
     Anonymous(AnonymousFragment),
@@ -32,10 +32,10 @@ pub(crate) enum Fragment {
     Image(ImageFragment),
 }
 
-#[derive(Serialize)]
+#[derive(Clone, Serialize)]
 pub(crate) struct AbsoluteOrFixedPositionedFragment(pub HoistedFragmentId);
 
-#[derive(Serialize)]
+#[derive(Clone, Serialize)]
 pub(crate) struct BoxFragment {
     pub tag: OpaqueNode,
     pub debug_id: DebugId,
@@ -63,7 +63,7 @@ pub(crate) struct BoxFragment {
     pub hoisted_fragment_id: Option<HoistedFragmentId>,
 }
 
-#[derive(Serialize)]
+#[derive(Clone, Serialize)]
 pub(crate) struct CollapsedBlockMargins {
     pub collapsed_through: bool,
     pub start: CollapsedMargin,
@@ -77,7 +77,7 @@ pub(crate) struct CollapsedMargin {
 }
 
 /// Can contain child fragments with relative coordinates, but does not contribute to painting itself.
-#[derive(Serialize)]
+#[derive(Clone, Serialize)]
 pub(crate) struct AnonymousFragment {
     pub debug_id: DebugId,
     pub rect: Rect<Length>,
@@ -111,7 +111,7 @@ impl From<&GfxFontMetrics> for FontMetrics {
     }
 }
 
-#[derive(Serialize)]
+#[derive(Clone, Serialize)]
 pub(crate) struct TextFragment {
     pub debug_id: DebugId,
     pub tag: OpaqueNode,
@@ -126,7 +126,7 @@ pub(crate) struct TextFragment {
     pub text_decoration_line: TextDecorationLine,
 }
 
-#[derive(Serialize)]
+#[derive(Clone, Serialize)]
 pub(crate) struct ImageFragment {
     pub debug_id: DebugId,
     #[serde(skip_serializing)]
@@ -171,6 +171,14 @@ impl PositioningContext {
         }
     }
 
+    /// The number of hoisted boxes collected so far.
+    pub(crate) fn hoisted_box_count(&self) -> usize {
+        self.for_nearest_positioned_ancestor
+            .as_ref()
+            .map_or(0, |v| v.len()) +
+            self.for_nearest_containing_block_for_all_descendants.len()
+    }
+
     pub(crate) fn collects_for_nearest_positioned_ancestor(&self) -> bool {
         self.for_nearest_positioned_ancestor.is_some()
     }
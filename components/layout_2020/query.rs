@@ -162,6 +162,11 @@ impl LayoutRPC for LayoutRPCImpl {
         let rw_data = rw_data.lock().unwrap();
         rw_data.inner_window_dimensions_response.clone()
     }
+
+    fn printed_document(&self) -> Option<Vec<u8>> {
+        // FIXME: Printing is not implemented by this layout yet.
+        None
+    }
 }
 
 pub fn process_content_box_request(
@@ -235,3 +240,7 @@ pub fn process_element_inner_text_query<N: LayoutNode>(_node: N) -> String {
 pub fn process_text_index_request(_node: OpaqueNode, _point: Point2D<Au>) -> TextIndexResponse {
     TextIndexResponse(None)
 }
+
+pub fn process_text_boxes_request(_node: OpaqueNode, _start: usize, _end: usize) -> Vec<Rect<Au>> {
+    vec![]
+}
@@ -8,6 +8,7 @@ use script_layout_interface::wrapper_traits::LayoutNode;
 use style::context::{SharedStyleContext, StyleContext};
 use style::data::ElementData;
 use style::dom::{NodeInfo, TElement, TNode};
+use style::selector_parser::RestyleDamage;
 use style::traversal::PerLevelTraversalData;
 use style::traversal::{recalc_style_at, DomTraversal};
 
@@ -56,6 +57,20 @@ where
 
     fn process_postorder(&self, _style_context: &mut StyleContext<E>, node: E::ConcreteNode) {
         if let Some(el) = node.as_element() {
+            // The dirty descendants bit is set on all the ancestors of the elements which
+            // changed, so together with the damage of the element it tells whether the boxes
+            // of its subtree need to be constructed again.
+            let damaged = el.has_dirty_descendants() ||
+                el.mutate_data().map_or(true, |mut data| {
+                    let damaged = !data.damage.is_empty();
+                    data.damage = RestyleDamage::empty();
+                    damaged
+                });
+            if damaged {
+                if let Some(data) = node.get_raw_data() {
+                    data.layout_data.borrow_mut().subtree_damaged = true;
+                }
+            }
             unsafe {
                 el.unset_dirty_descendants();
             }
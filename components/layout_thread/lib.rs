@@ -34,6 +34,7 @@ use fxhash::{FxHashMap, FxHashSet};
 use gfx::font;
 use gfx::font_cache_thread::FontCacheThread;
 use gfx::font_context;
+use gfx::text::shaping::cache as shaping_cache;
 use gfx_traits::{node_id_from_scroll_id, Epoch};
 use histogram::Histogram;
 use ipc_channel::ipc::{self, IpcReceiver, IpcSender};
@@ -50,7 +51,10 @@ use layout::flow::{Flow, GetBaseFlow, ImmutableFlowUtils, MutableOwnedFlowUtils}
 use layout::flow_ref::FlowRef;
 use layout::incremental::{RelayoutMode, SpecialRestyleDamage};
 use layout::layout_debug;
+use layout::overscroll;
+use layout::paint_metrics::PaintedNodes;
 use layout::parallel;
+use layout::print;
 use layout::query::{process_client_rect_query, process_element_inner_text_query};
 use layout::query::{
     process_content_box_request, process_content_boxes_request, LayoutRPCImpl, LayoutThreadData,
@@ -59,6 +63,9 @@ use layout::query::{process_node_scroll_area_request, process_node_scroll_id_req
 use layout::query::{
     process_offset_parent_query, process_resolved_style_request, process_style_query,
 };
+use layout::scroll_snap;
+use layout::scroll_timeline;
+use layout::scrollbars;
 use layout::sequential;
 use layout::traversal::{
     ComputeStackingRelativePositions, PreorderFlowTraversal, RecalcStyleAndConstructFlows,
@@ -85,8 +92,8 @@ use script_layout_interface::rpc::{LayoutRPC, OffsetParentResponse, StyleRespons
 use script_layout_interface::wrapper_traits::LayoutNode;
 use script_traits::{ConstellationControlMsg, LayoutControlMsg, LayoutMsg as ConstellationMsg};
 use script_traits::{DrawAPaintImageResult, IFrameSizeMsg, PaintWorkletError, WindowSizeType};
-use script_traits::{Painter, WebrenderIpcSender};
-use script_traits::{ScrollState, UntrustedNodeAddress, WindowSizeData};
+use script_traits::{OverscrollContainer, Painter, ScrollbarContainer, WebrenderIpcSender};
+use script_traits::{ScrollSnapContainer, ScrollState, UntrustedNodeAddress, WindowSizeData};
 use selectors::Element;
 use servo_arc::Arc as ServoArc;
 use servo_atoms::Atom;
@@ -125,6 +132,7 @@ use style::thread_state::{self, ThreadState};
 use style::timer::Timer;
 use style::traversal::DomTraversal;
 use style::traversal_flags::TraversalFlags;
+use style::values::specified::color::SystemColor;
 use style_traits::CSSPixel;
 use style_traits::DevicePixel;
 use style_traits::SpeculativePainter;
@@ -248,6 +256,19 @@ pub struct LayoutThread {
     /// The sizes of all iframes encountered during the last layout operation.
     last_iframe_sizes: RefCell<HashMap<BrowsingContextId, Size2D<f32, CSSPixel>>>,
 
+    /// The snap positions of the scroll containers found during the last layout operation.
+    last_scroll_snap_containers: RefCell<Vec<ScrollSnapContainer>>,
+
+    /// The overscroll behavior of the scroll containers found during the last layout operation.
+    last_overscroll_containers: RefCell<Vec<OverscrollContainer>>,
+
+    /// The scrollbars of the scroll containers found during the last layout operation.
+    last_scrollbar_containers: RefCell<Vec<ScrollbarContainer>>,
+
+    /// The nodes painted by the last display list sent to WebRender, to find the layout
+    /// shifts of the next one.
+    last_painted_nodes: RefCell<Option<PaintedNodes>>,
+
     /// Flag that indicates if LayoutThread is busy handling a request.
     busy: Arc<AtomicBool>,
 
@@ -603,6 +624,7 @@ impl LayoutThread {
                 nodes_from_point_response: vec![],
                 element_inner_text_response: String::new(),
                 inner_window_dimensions_response: None,
+                print_response: None,
             })),
             webrender_image_cache: Arc::new(RwLock::new(FnvHashMap::default())),
             timer: if pref!(layout.animations.test.enabled) {
@@ -613,6 +635,10 @@ impl LayoutThread {
             paint_time_metrics: paint_time_metrics,
             layout_query_waiting_time: Histogram::new(),
             last_iframe_sizes: Default::default(),
+            last_scroll_snap_containers: Default::default(),
+            last_overscroll_containers: Default::default(),
+            last_scrollbar_containers: Default::default(),
+            last_painted_nodes: Default::default(),
             busy,
             load_webfonts_synchronously,
             dump_display_list,
@@ -687,6 +713,7 @@ impl LayoutThread {
         let hang_annotation = match request {
             Msg::AddStylesheet(..) => LayoutHangAnnotation::AddStylesheet,
             Msg::RemoveStylesheet(..) => LayoutHangAnnotation::RemoveStylesheet,
+            Msg::StylesheetRuleChanged(..) => LayoutHangAnnotation::StylesheetRuleChanged,
             Msg::SetQuirksMode(..) => LayoutHangAnnotation::SetQuirksMode,
             Msg::Reflow(..) => LayoutHangAnnotation::Reflow,
             Msg::GetRPC(..) => LayoutHangAnnotation::GetRPC,
@@ -754,6 +781,11 @@ impl LayoutThread {
                 self.paint_time_metrics.maybe_set_metric(epoch, paint_time);
                 true
             },
+            Request::FromPipeline(LayoutControlMsg::MemoryPressure) => {
+                font_context::invalidate_font_caches();
+                shaping_cache::clear();
+                true
+            },
             Request::FromScript(msg) => self.handle_request_helper(msg, possibly_locked_rw_data),
             Request::FromFontCache => {
                 let _rw_data = possibly_locked_rw_data.lock();
@@ -798,6 +830,11 @@ impl LayoutThread {
                 self.stylist
                     .remove_stylesheet(DocumentStyleSheet(stylesheet.clone()), &guard);
             },
+            Msg::StylesheetRuleChanged(stylesheet, rule) => {
+                let guard = stylesheet.shared_lock.read();
+                self.stylist
+                    .rule_changed(&DocumentStyleSheet(stylesheet.clone()), &rule, &guard);
+            },
             Msg::SetQuirksMode(mode) => self.handle_set_quirks_mode(mode),
             Msg::GetRPC(response_chan) => {
                 response_chan
@@ -1132,12 +1169,23 @@ impl LayoutThread {
                     rw_data.display_list.is_none()
                 {
                     if reflow_goal.needs_display_list() {
-                        let background_color = get_root_flow_background_color(layout_root);
+                        let background_color = get_root_flow_background_color(
+                            layout_root,
+                            layout_context.style_context.stylist.device(),
+                        );
+                        let pool;
+                        let thread_pool = if self.parallel_flag {
+                            pool = STYLE_THREAD_POOL.pool();
+                            pool.as_ref()
+                        } else {
+                            None
+                        };
                         let mut build_state = sequential::build_display_list_for_subtree(
                             layout_root,
                             layout_context,
                             background_color,
                             data.page_clip_rect.size,
+                            thread_pool,
                         );
 
                         debug!("Done building display list.");
@@ -1198,6 +1246,58 @@ impl LayoutThread {
                             }
                         }
 
+                        // Only notify the constellation about snap positions that changed.
+                        let scroll_snap_containers = scroll_snap::collect_scroll_snap_containers(
+                            layout_root,
+                            self.id,
+                            self.viewport_size,
+                        );
+                        if *self.last_scroll_snap_containers.borrow() != scroll_snap_containers {
+                            *self.last_scroll_snap_containers.borrow_mut() =
+                                scroll_snap_containers.clone();
+                            let msg = ConstellationMsg::ScrollSnapContainers(
+                                self.id,
+                                scroll_snap_containers,
+                            );
+                            if let Err(e) = self.constellation_chan.send(msg) {
+                                warn!("Layout scroll snap to constellation failed ({}).", e);
+                            }
+                        }
+
+                        let overscroll_containers = overscroll::collect_overscroll_containers(
+                            layout_root,
+                            self.id,
+                            self.viewport_size,
+                        );
+                        if *self.last_overscroll_containers.borrow() != overscroll_containers {
+                            *self.last_overscroll_containers.borrow_mut() =
+                                overscroll_containers.clone();
+                            let msg = ConstellationMsg::OverscrollContainers(
+                                self.id,
+                                overscroll_containers,
+                            );
+                            if let Err(e) = self.constellation_chan.send(msg) {
+                                warn!("Layout overscroll to constellation failed ({}).", e);
+                            }
+                        }
+
+                        let scrollbar_containers = scrollbars::collect_scrollbar_containers(
+                            layout_root,
+                            self.id,
+                            self.viewport_size,
+                        );
+                        if *self.last_scrollbar_containers.borrow() != scrollbar_containers {
+                            *self.last_scrollbar_containers.borrow_mut() =
+                                scrollbar_containers.clone();
+                            let msg = ConstellationMsg::ScrollbarContainers(
+                                self.id,
+                                scrollbar_containers,
+                            );
+                            if let Err(e) = self.constellation_chan.send(msg) {
+                                warn!("Layout scrollbars to constellation failed ({}).", e);
+                            }
+                        }
+
                         rw_data.indexable_text = std::mem::replace(
                             &mut build_state.indexable_text,
                             IndexableText::default(),
@@ -1245,11 +1345,42 @@ impl LayoutThread {
 
                 let viewport_size = webrender_api::units::LayoutSize::from_untyped(viewport_size);
 
+                // Find the largest contentful paint candidate and the layout shift of this
+                // frame, in the part of the document that is scrolled into the viewport.
+                let scroll_origin = rw_data
+                    .scroll_offsets
+                    .get(&self.id.root_scroll_id())
+                    .map_or(webrender_api::units::LayoutPoint::zero(), |offset| {
+                        offset.to_point()
+                    });
+                let viewport = webrender_api::units::LayoutRect::new(scroll_origin, viewport_size);
+                let painted_nodes = PaintedNodes::new(display_list, viewport);
+                if let Some(ref last_painted_nodes) = *self.last_painted_nodes.borrow() {
+                    if let Some(layout_shift) = painted_nodes.layout_shift_since(last_painted_nodes)
+                    {
+                        let msg = ConstellationControlMsg::LayoutShift(
+                            self.id,
+                            time::precise_time_ns(),
+                            layout_shift,
+                        );
+                        if let Err(e) = self.script_chan.send(msg) {
+                            warn!("Sending layout shift to script thread failed ({}).", e);
+                        }
+                    }
+                }
+                let largest_contentful_paint_candidate =
+                    painted_nodes.largest_contentful_paint_candidate();
+                *self.last_painted_nodes.borrow_mut() = Some(painted_nodes);
+
                 // Observe notifications about rendered frames if needed right before
                 // sending the display list to WebRender in order to set time related
                 // Progressive Web Metrics.
-                self.paint_time_metrics
-                    .maybe_observe_paint_time(self, epoch, is_contentful.0);
+                self.paint_time_metrics.maybe_observe_paint_time(
+                    self,
+                    epoch,
+                    is_contentful.0,
+                    largest_contentful_paint_candidate,
+                );
 
                 self.webrender_api.send_display_list(
                     self.webrender_document,
@@ -1296,7 +1427,7 @@ impl LayoutThread {
                         &QueryMsg::ContentBoxQuery(_) => {
                             rw_data.content_box_response = None;
                         },
-                        &QueryMsg::ContentBoxesQuery(_) => {
+                        &QueryMsg::ContentBoxesQuery(_) | &QueryMsg::TextBoxesQuery(..) => {
                             rw_data.content_boxes_response = Vec::new();
                         },
                         &QueryMsg::NodesFromPointQuery(..) => {
@@ -1320,7 +1451,7 @@ impl LayoutThread {
                         &QueryMsg::StyleQuery(_) => {
                             rw_data.style_response = StyleResponse(None);
                         },
-                        &QueryMsg::TextIndexQuery(..) => {
+                        &QueryMsg::TextIndexQuery(..) | &QueryMsg::TextIndexAtPointQuery(..) => {
                             rw_data.text_index_response = TextIndexResponse(None);
                         },
                         &QueryMsg::ElementInnerTextQuery(_) => {
@@ -1329,6 +1460,9 @@ impl LayoutThread {
                         &QueryMsg::InnerWindowDimensionsQuery(_) => {
                             rw_data.inner_window_dimensions_response = None;
                         },
+                        &QueryMsg::PrintQuery(_) => {
+                            rw_data.print_response = None;
+                        },
                     },
                     ReflowGoal::Full | ReflowGoal::TickAnimations => {},
                 }
@@ -1343,7 +1477,15 @@ impl LayoutThread {
         );
         trace!("{:?}", ShowSubtree(element.as_node()));
 
-        let initial_viewport = data.window_size.initial_viewport;
+        // Printing lays the document out for print media, on pages of the requested size.
+        let print_page_size = match data.reflow_goal {
+            ReflowGoal::LayoutQuery(QueryMsg::PrintQuery(page_size), _) => Some(page_size),
+            _ => None,
+        };
+        let initial_viewport = match print_page_size {
+            Some(page_size) => Size2D::from_untyped(page_size),
+            None => data.window_size.initial_viewport,
+        };
         let device_pixel_ratio = data.window_size.device_pixel_ratio;
         let old_viewport_size = self.viewport_size;
         let current_screen_size = Size2D::new(
@@ -1366,8 +1508,26 @@ impl LayoutThread {
             ua_or_user: &ua_or_user_guard,
         };
 
+        // Geometry queries on a document that didn't change since the last reflow are answered
+        // from the flow tree of that reflow, without restyling it or laying it out again.
+        if data.layout_is_clean && data.reflow_goal.is_geometry_query() && self.is_laid_out() {
+            let map = SnapshotMap::new();
+            let mut layout_context = self.build_layout_context(guards, true, &map, origin);
+            self.respond_to_query_if_necessary(
+                &data.reflow_goal,
+                &mut *rw_data,
+                &mut layout_context,
+                data.result.borrow_mut().as_mut().unwrap(),
+            );
+            return;
+        }
+
         let had_used_viewport_units = self.stylist.device().used_viewport_units();
-        let device = Device::new(MediaType::screen(), initial_viewport, device_pixel_ratio);
+        let media_type = match print_page_size {
+            Some(_) => MediaType::print(),
+            None => MediaType::screen(),
+        };
+        let device = Device::new(media_type, initial_viewport, device_pixel_ratio);
         let sheet_origins_affected_by_device_change = self.stylist.set_device(device, &guards);
 
         self.stylist
@@ -1493,6 +1653,23 @@ impl LayoutThread {
 
         self.stylist.flush(&guards, Some(element), Some(&map));
 
+        // The margins of the pages are only known once the `@page` rules have been flushed, and
+        // the document is laid out at the width of their page area.
+        let page_setup = print_page_size.map(|page_size| {
+            let page_size = Size2D::new(
+                Au::from_f32_px(page_size.width),
+                Au::from_f32_px(page_size.height),
+            );
+            let page_setup = print::PageSetup::new(page_size, &self.stylist, &guards);
+            if self.viewport_size != page_setup.page_area_size() {
+                self.viewport_size = page_setup.page_area_size();
+                if let Some(mut flow) = self.try_get_layout_root(element.as_node()) {
+                    LayoutThread::reflow_all_nodes(FlowRef::deref_mut(&mut flow));
+                }
+            }
+            page_setup
+        });
+
         // Create a layout context for use throughout the following passes.
         let mut layout_context = self.build_layout_context(guards.clone(), true, &map, origin);
 
@@ -1581,6 +1758,12 @@ impl LayoutThread {
             );
         }
 
+        if let Some(ref page_setup) = page_setup {
+            rw_data.print_response = self.root_flow.borrow().clone().map(|mut root_flow| {
+                print::print_document(FlowRef::deref_mut(&mut root_flow), page_setup)
+            });
+        }
+
         self.first_reflow.set(false);
         self.respond_to_query_if_necessary(
             &data.reflow_goal,
@@ -1590,6 +1773,17 @@ impl LayoutThread {
         );
     }
 
+    /// Whether the flow tree was completely laid out for the screen by the last reflow.
+    fn is_laid_out(&self) -> bool {
+        if self.first_reflow.get() || self.stylist.device().media_type() != MediaType::screen() {
+            return false;
+        }
+        self.root_flow.borrow().as_ref().map_or(false, |root_flow| {
+            let damage = root_flow.base().restyle_damage;
+            (damage - ServoRestyleDamage::REPAINT).is_empty()
+        })
+    }
+
     fn respond_to_query_if_necessary(
         &self,
         reflow_goal: &ReflowGoal,
@@ -1630,6 +1824,15 @@ impl LayoutThread {
                     rw_data.text_index_response =
                         TextIndexResponse(rw_data.indexable_text.text_index(node, point_in_node));
                 },
+                &QueryMsg::TextIndexAtPointQuery(node, point) => {
+                    let point = Point2D::new(Au::from_f32_px(point.x), Au::from_f32_px(point.y));
+                    rw_data.text_index_response =
+                        TextIndexResponse(rw_data.indexable_text.text_index_at_point(node, point));
+                },
+                &QueryMsg::TextBoxesQuery(node, start, end) => {
+                    rw_data.content_boxes_response =
+                        rw_data.indexable_text.text_boxes(node, start, end);
+                },
                 &QueryMsg::ClientRectQuery(node) => {
                     rw_data.client_rect_response = process_client_rect_query(node, root_flow);
                 },
@@ -1690,6 +1893,8 @@ impl LayoutThread {
                         .get(&browsing_context_id)
                         .cloned();
                 },
+                // The document was printed during the reflow.
+                &QueryMsg::PrintQuery(_) => {},
             },
             ReflowGoal::Full | ReflowGoal::TickAnimations => {},
         }
@@ -1756,6 +1961,15 @@ impl LayoutThread {
             let snapshots = SnapshotMap::new();
             let mut layout_context = self.build_layout_context(guards, false, &snapshots, origin);
 
+            // Scroll-driven animations follow the latest scroll positions from the compositor.
+            scroll_timeline::update_scroll_timelines(
+                FlowRef::deref_mut(&mut root_flow),
+                self.id,
+                self.viewport_size,
+                &rw_data.scroll_offsets,
+                &mut *self.running_animations.write(),
+            );
+
             let invalid_nodes = {
                 // Perform an abbreviated style recalc that operates without access to the DOM.
                 let animations = self.running_animations.read();
@@ -1989,7 +2203,7 @@ impl ProfilerMetadataFactory for LayoutThread {
 // clearing the frame buffer to white. This ensures that setting a background
 // color on an iframe element, while the iframe content itself has a default
 // transparent background color is handled correctly.
-fn get_root_flow_background_color(flow: &mut dyn Flow) -> webrender_api::ColorF {
+fn get_root_flow_background_color(flow: &mut dyn Flow, device: &Device) -> webrender_api::ColorF {
     let transparent = webrender_api::ColorF {
         r: 0.0,
         g: 0.0,
@@ -2009,14 +2223,15 @@ fn get_root_flow_background_color(flow: &mut dyn Flow) -> webrender_api::ColorF
         return transparent;
     }
 
-    let kid_block_flow = kid.as_block();
-    let color = kid_block_flow.fragment.style.resolve_color(
-        kid_block_flow
-            .fragment
-            .style
-            .get_background()
-            .background_color,
-    );
+    let style = &kid.as_block().fragment.style;
+    let mut color = style.resolve_color(style.get_background().background_color);
+    // The frame buffer is cleared to white, so a root element which uses the dark color scheme
+    // needs an opaque canvas of its own.
+    // https://drafts.csswg.org/css-color-adjust/#color-scheme-effect
+    let color_scheme = style.get_inherited_ui().clone_color_scheme();
+    if color.alpha == 0 && device.uses_dark_color_scheme(&color_scheme) {
+        color = device.system_color(SystemColor::Canvas, &color_scheme);
+    }
     webrender_api::ColorF::new(
         color.red_f32(),
         color.green_f32(),
@@ -960,6 +960,7 @@ impl<'le> ::selectors::Element for ServoLayoutElement<'le> {
 
             NonTSPseudoClass::Active |
             NonTSPseudoClass::Focus |
+            NonTSPseudoClass::FocusVisible |
             NonTSPseudoClass::Fullscreen |
             NonTSPseudoClass::Hover |
             NonTSPseudoClass::Defined |
@@ -1201,6 +1202,35 @@ impl<'ln> ThreadSafeLayoutNode for ServoThreadSafeLayoutNode<'ln> {
         })
     }
 
+    fn highlights(&self) -> Vec<(Atom, Range<ByteIndex>)> {
+        let this = unsafe { self.get_jsmanaged() };
+
+        this.highlights()
+            .into_iter()
+            .map(|(name, range)| {
+                let range = Range::new(
+                    ByteIndex(range.start as isize),
+                    ByteIndex(range.len() as isize),
+                );
+                (name, range)
+            })
+            .collect()
+    }
+
+    fn spelling_errors(&self) -> Vec<Range<ByteIndex>> {
+        let this = unsafe { self.get_jsmanaged() };
+
+        this.spelling_errors()
+            .into_iter()
+            .map(|range| {
+                Range::new(
+                    ByteIndex(range.start as isize),
+                    ByteIndex(range.len() as isize),
+                )
+            })
+            .collect()
+    }
+
     fn image_url(&self) -> Option<ServoUrl> {
         let this = unsafe { self.get_jsmanaged() };
         this.image_url()
@@ -31,10 +31,11 @@ use fnv::FnvHashMap;
 use fxhash::FxHashMap;
 use gfx::font_cache_thread::FontCacheThread;
 use gfx::font_context;
+use gfx::text::shaping::cache as shaping_cache;
 use gfx_traits::{node_id_from_scroll_id, Epoch};
 use ipc_channel::ipc::{self, IpcReceiver, IpcSender};
 use ipc_channel::router::ROUTER;
-use layout::context::LayoutContext;
+use layout::context::{LayoutContext, RegisteredPainter, RegisteredPainters};
 use layout::display_list::{DisplayListBuilder, WebRenderImageInfo};
 use layout::layout_debug;
 use layout::query::{
@@ -44,7 +45,7 @@ use layout::query::{process_element_inner_text_query, process_node_geometry_requ
 use layout::query::{process_node_scroll_area_request, process_node_scroll_id_request};
 use layout::query::{
     process_offset_parent_query, process_resolved_style_request, process_style_query,
-    process_text_index_request,
+    process_text_boxes_request, process_text_index_request,
 };
 use layout::traversal::RecalcStyle;
 use layout::{BoxTreeRoot, FragmentTreeRoot};
@@ -622,6 +623,7 @@ impl LayoutThread {
             image_cache: self.image_cache.clone(),
             font_cache_thread: Mutex::new(self.font_cache_thread.clone()),
             webrender_image_cache: self.webrender_image_cache.clone(),
+            registered_painters: &self.registered_painters,
             pending_images: if script_initiated_layout {
                 Some(Mutex::new(Vec::new()))
             } else {
@@ -635,6 +637,7 @@ impl LayoutThread {
         let hang_annotation = match request {
             Msg::AddStylesheet(..) => LayoutHangAnnotation::AddStylesheet,
             Msg::RemoveStylesheet(..) => LayoutHangAnnotation::RemoveStylesheet,
+            Msg::StylesheetRuleChanged(..) => LayoutHangAnnotation::StylesheetRuleChanged,
             Msg::SetQuirksMode(..) => LayoutHangAnnotation::SetQuirksMode,
             Msg::Reflow(..) => LayoutHangAnnotation::Reflow,
             Msg::GetRPC(..) => LayoutHangAnnotation::GetRPC,
@@ -702,6 +705,11 @@ impl LayoutThread {
                 self.paint_time_metrics.maybe_set_metric(epoch, paint_time);
                 true
             },
+            Request::FromPipeline(LayoutControlMsg::MemoryPressure) => {
+                font_context::invalidate_font_caches();
+                shaping_cache::clear();
+                true
+            },
             Request::FromScript(msg) => self.handle_request_helper(msg, possibly_locked_rw_data),
             Request::FromFontCache => {
                 let _rw_data = possibly_locked_rw_data.lock();
@@ -746,6 +754,11 @@ impl LayoutThread {
                 self.stylist
                     .remove_stylesheet(DocumentStyleSheet(stylesheet.clone()), &guard);
             },
+            Msg::StylesheetRuleChanged(stylesheet, rule) => {
+                let guard = stylesheet.shared_lock.read();
+                self.stylist
+                    .rule_changed(&DocumentStyleSheet(stylesheet.clone()), &rule, &guard);
+            },
             Msg::SetQuirksMode(mode) => self.handle_set_quirks_mode(mode),
             Msg::GetRPC(response_chan) => {
                 response_chan
@@ -801,7 +814,23 @@ impl LayoutThread {
             Msg::SetFinalUrl(final_url) => {
                 self.url = final_url;
             },
-            Msg::RegisterPaint(_name, _properties, _painter) => {},
+            Msg::RegisterPaint(name, mut properties, painter) => {
+                debug!("Registering the painter");
+                let properties = properties
+                    .drain(..)
+                    .filter_map(|name| {
+                        let id = PropertyId::parse_enabled_for_all_content(&*name).ok()?;
+                        Some((name.clone(), id))
+                    })
+                    .filter(|&(_, ref id)| !id.is_shorthand())
+                    .collect();
+                let registered_painter = RegisteredPainterImpl {
+                    name: name.clone(),
+                    properties,
+                    painter,
+                };
+                self.registered_painters.0.insert(name, registered_painter);
+            },
             Msg::PrepareToExit(response_chan) => {
                 self.prepare_to_exit(response_chan);
                 return false;
@@ -966,7 +995,7 @@ impl LayoutThread {
                         &QueryMsg::ContentBoxQuery(_) => {
                             rw_data.content_box_response = None;
                         },
-                        &QueryMsg::ContentBoxesQuery(_) => {
+                        &QueryMsg::ContentBoxesQuery(_) | &QueryMsg::TextBoxesQuery(..) => {
                             rw_data.content_boxes_response = Vec::new();
                         },
                         &QueryMsg::NodesFromPointQuery(..) => {
@@ -990,7 +1019,7 @@ impl LayoutThread {
                         &QueryMsg::StyleQuery(_) => {
                             rw_data.style_response = StyleResponse(None);
                         },
-                        &QueryMsg::TextIndexQuery(..) => {
+                        &QueryMsg::TextIndexQuery(..) | &QueryMsg::TextIndexAtPointQuery(..) => {
                             rw_data.text_index_response = TextIndexResponse(None);
                         },
                         &QueryMsg::ElementInnerTextQuery(_) => {
@@ -999,6 +1028,7 @@ impl LayoutThread {
                         &QueryMsg::InnerWindowDimensionsQuery(_) => {
                             rw_data.inner_window_dimensions_response = None;
                         },
+                        &QueryMsg::PrintQuery(_) => {},
                     },
                     ReflowGoal::Full | ReflowGoal::TickAnimations => {},
                 }
@@ -1254,6 +1284,13 @@ impl LayoutThread {
                     );
                     rw_data.text_index_response = process_text_index_request(node, point_in_node);
                 },
+                &QueryMsg::TextIndexAtPointQuery(node, point) => {
+                    let point = Point2D::new(Au::from_f32_px(point.x), Au::from_f32_px(point.y));
+                    rw_data.text_index_response = process_text_index_request(node, point);
+                },
+                &QueryMsg::TextBoxesQuery(node, start, end) => {
+                    rw_data.content_boxes_response = process_text_boxes_request(node, start, end);
+                },
                 &QueryMsg::ClientRectQuery(node) => {
                     rw_data.client_rect_response = process_node_geometry_request(
                         node,
@@ -1313,6 +1350,7 @@ impl LayoutThread {
                     //            builder in order to support query iframe sizing.
                     rw_data.inner_window_dimensions_response = None;
                 },
+                &QueryMsg::PrintQuery(_) => {},
             },
             ReflowGoal::Full | ReflowGoal::TickAnimations => {},
         }
@@ -1432,8 +1470,12 @@ impl LayoutThread {
         // Observe notifications about rendered frames if needed right before
         // sending the display list to WebRender in order to set time related
         // Progressive Web Metrics.
-        self.paint_time_metrics
-            .maybe_observe_paint_time(self, epoch, display_list.is_contentful);
+        self.paint_time_metrics.maybe_observe_paint_time(
+            self,
+            epoch,
+            display_list.is_contentful,
+            None,
+        );
 
         let viewport_size = webrender_api::units::LayoutSize::from_untyped(Size2D::new(
             self.viewport_size.width.to_f32_px(),
@@ -1601,6 +1643,8 @@ impl Painter for RegisteredPainterImpl {
     }
 }
 
+impl RegisteredPainter for RegisteredPainterImpl {}
+
 struct RegisteredPaintersImpl(FnvHashMap<Atom, RegisteredPainterImpl>);
 
 impl RegisteredSpeculativePainters for RegisteredPaintersImpl {
@@ -1610,3 +1654,11 @@ impl RegisteredSpeculativePainters for RegisteredPaintersImpl {
             .map(|painter| painter as &dyn RegisteredSpeculativePainter)
     }
 }
+
+impl RegisteredPainters for RegisteredPaintersImpl {
+    fn get(&self, name: &Atom) -> Option<&dyn RegisteredPainter> {
+        self.0
+            .get(&name)
+            .map(|painter| painter as &dyn RegisteredPainter)
+    }
+}
@@ -12,7 +12,8 @@ use ipc_channel::ipc::IpcSender;
 use msg::constellation_msg::PipelineId;
 use profile_traits::time::TimerMetadata;
 use profile_traits::time::{send_profile_data, ProfilerCategory, ProfilerChan};
-use script_traits::{ConstellationControlMsg, LayoutMsg, ProgressiveWebMetricType};
+use script_traits::ProgressiveWebMetricType;
+use script_traits::{ConstellationControlMsg, LargestContentfulPaintCandidate, LayoutMsg};
 use servo_config::opts;
 use servo_url::ServoUrl;
 use std::cell::{Cell, RefCell};
@@ -249,12 +250,24 @@ impl ProgressiveWebMetric for InteractiveMetrics {
     }
 }
 
+/// A frame whose paint time is waited for.
+struct PendingPaintMetric {
+    profiler_metadata: Option<TimerMetadata>,
+    is_contentful: bool,
+    /// The largest contentful paint candidate of the frame, if it is larger than the ones of
+    /// the previous frames.
+    largest_contentful_paint_candidate: Option<LargestContentfulPaintCandidate>,
+}
+
 // https://w3c.github.io/paint-timing/
+// https://w3c.github.io/largest-contentful-paint/
 pub struct PaintTimeMetrics {
-    pending_metrics: RefCell<HashMap<Epoch, (Option<TimerMetadata>, bool)>>,
+    pending_metrics: RefCell<HashMap<Epoch, PendingPaintMetric>>,
     navigation_start: Option<u64>,
     first_paint: Cell<Option<u64>>,
     first_contentful_paint: Cell<Option<u64>>,
+    /// The size of the largest contentful paint candidate seen so far.
+    largest_contentful_paint_size: Cell<u64>,
     pipeline_id: PipelineId,
     time_profiler_chan: ProfilerChan,
     constellation_chan: IpcSender<LayoutMsg>,
@@ -275,6 +288,7 @@ impl PaintTimeMetrics {
             navigation_start: None,
             first_paint: Cell::new(None),
             first_contentful_paint: Cell::new(None),
+            largest_contentful_paint_size: Cell::new(0),
             pipeline_id,
             time_profiler_chan,
             constellation_chan,
@@ -307,20 +321,34 @@ impl PaintTimeMetrics {
         profiler_metadata_factory: &T,
         epoch: Epoch,
         display_list_is_contentful: bool,
+        largest_contentful_paint_candidate: Option<LargestContentfulPaintCandidate>,
     ) where
         T: ProfilerMetadataFactory,
     {
-        if self.first_paint.get().is_some() && self.first_contentful_paint.get().is_some() {
-            // If we already set all paint metrics, we just bail out.
+        // Only candidates larger than the previous ones are new largest contentful paints.
+        let largest_contentful_paint_candidate = largest_contentful_paint_candidate
+            .filter(|candidate| candidate.size > self.largest_contentful_paint_size.get());
+
+        if self.first_paint.get().is_some() &&
+            self.first_contentful_paint.get().is_some() &&
+            largest_contentful_paint_candidate.is_none()
+        {
+            // If we already set all paint metrics and there is no new largest contentful
+            // paint, we just bail out.
             return;
         }
 
+        if let Some(ref candidate) = largest_contentful_paint_candidate {
+            self.largest_contentful_paint_size.set(candidate.size);
+        }
+
         self.pending_metrics.borrow_mut().insert(
             epoch,
-            (
-                profiler_metadata_factory.new_metadata(),
-                display_list_is_contentful,
-            ),
+            PendingPaintMetric {
+                profiler_metadata: profiler_metadata_factory.new_metadata(),
+                is_contentful: display_list_is_contentful,
+                largest_contentful_paint_candidate,
+            },
         );
 
         // Send the pending metric information to the compositor thread.
@@ -333,16 +361,39 @@ impl PaintTimeMetrics {
     }
 
     pub fn maybe_set_metric(&self, epoch: Epoch, paint_time: u64) {
-        if self.first_paint.get().is_some() && self.first_contentful_paint.get().is_some() ||
-            self.navigation_start.is_none()
-        {
-            // If we already set all paint metrics or we have not set navigation start yet,
-            // we just bail out.
-            return;
-        }
+        let navigation_start = match self.navigation_start {
+            Some(navigation_start) => navigation_start,
+            // If we have not set navigation start yet, we just bail out.
+            None => return,
+        };
+
+        // A frame presents the display lists of its epoch and of all the earlier epochs
+        // whose frames were skipped, so every pending metric up to the painted epoch is
+        // resolved with the time the frame was presented.
+        let presented = {
+            let mut pending_metrics = self.pending_metrics.borrow_mut();
+            let mut epochs: Vec<Epoch> = pending_metrics
+                .keys()
+                .filter(|pending_epoch| **pending_epoch <= epoch)
+                .cloned()
+                .collect();
+            epochs.sort();
+            epochs
+                .into_iter()
+                .filter_map(|epoch| pending_metrics.remove(&epoch))
+                .collect::<Vec<_>>()
+        };
+        let (profiler_metadata, is_contentful) = match presented.last() {
+            Some(pending_metric) => (
+                pending_metric.profiler_metadata.clone(),
+                presented
+                    .iter()
+                    .any(|pending_metric| pending_metric.is_contentful),
+            ),
+            None => return,
+        };
 
-        if let Some(pending_metric) = self.pending_metrics.borrow_mut().remove(&epoch) {
-            let profiler_metadata = pending_metric.0;
+        if self.first_paint.get().is_none() {
             set_metric(
                 self,
                 profiler_metadata.clone(),
@@ -352,17 +403,31 @@ impl PaintTimeMetrics {
                 Some(paint_time),
                 &self.url,
             );
+        }
+
+        if is_contentful && self.first_contentful_paint.get().is_none() {
+            set_metric(
+                self,
+                profiler_metadata,
+                ProgressiveWebMetricType::FirstContentfulPaint,
+                ProfilerCategory::TimeToFirstContentfulPaint,
+                &self.first_contentful_paint,
+                Some(paint_time),
+                &self.url,
+            );
+        }
 
-            if pending_metric.1 {
-                set_metric(
-                    self,
-                    profiler_metadata,
-                    ProgressiveWebMetricType::FirstContentfulPaint,
-                    ProfilerCategory::TimeToFirstContentfulPaint,
-                    &self.first_contentful_paint,
-                    Some(paint_time),
-                    &self.url,
-                );
+        // The candidates are pending in increasing size, so the last one is the largest.
+        if let Some(candidate) = presented
+            .into_iter()
+            .filter_map(|pending_metric| pending_metric.largest_contentful_paint_candidate)
+            .last()
+        {
+            let time = paint_time - navigation_start;
+            let msg =
+                ConstellationControlMsg::LargestContentfulPaint(self.pipeline_id, time, candidate);
+            if let Err(e) = self.script_chan.send(msg) {
+                warn!("Sending largest contentful paint failed ({}).", e);
             }
         }
     }
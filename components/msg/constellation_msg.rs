@@ -184,6 +184,13 @@ impl PipelineNamespace {
             index: BlobIndex(self.next_index()),
         }
     }
+
+    fn next_lock_request_id(&mut self) -> LockRequestId {
+        LockRequestId {
+            namespace_id: self.id,
+            index: LockRequestIndex(self.next_index()),
+        }
+    }
 }
 
 thread_local!(pub static PIPELINE_NAMESPACE: Cell<Option<PipelineNamespace>> = Cell::new(None));
@@ -454,6 +461,39 @@ impl fmt::Display for BlobId {
     }
 }
 
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub struct LockRequestIndex(pub NonZeroU32);
+malloc_size_of_is_0!(LockRequestIndex);
+
+/// The id of a request for a lock of the Web Locks API, which also identifies the lock that
+/// is granted for it.
+#[derive(
+    Clone, Copy, Debug, Deserialize, Eq, Hash, MallocSizeOf, Ord, PartialEq, PartialOrd, Serialize,
+)]
+pub struct LockRequestId {
+    pub namespace_id: PipelineNamespaceId,
+    pub index: LockRequestIndex,
+}
+
+impl LockRequestId {
+    pub fn new() -> LockRequestId {
+        PIPELINE_NAMESPACE.with(|tls| {
+            let mut namespace = tls.get().expect("No namespace set for this thread!");
+            let next_lock_request_id = namespace.next_lock_request_id();
+            tls.set(Some(namespace));
+            next_lock_request_id
+        })
+    }
+}
+
+impl fmt::Display for LockRequestId {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        let PipelineNamespaceId(namespace_id) = self.namespace_id;
+        let LockRequestIndex(index) = self.index;
+        write!(fmt, "(LockRequestId{},{})", namespace_id, index.get())
+    }
+}
+
 #[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
 pub struct HistoryStateIndex(pub NonZeroU32);
 malloc_size_of_is_0!(HistoryStateIndex);
@@ -522,11 +562,48 @@ pub enum InputMethodType {
     Week,
 }
 
+/// The conditions that the network of a top-level browsing context is made to behave like, to
+/// test how its pages load over slow or missing connections.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct NetworkConditions {
+    /// Whether every request fails as if there were no connection.
+    pub offline: bool,
+    /// The time that is added before each response arrives.
+    pub latency: Duration,
+    /// The most bytes per second that response bodies are received at, if they are limited.
+    pub download_throughput: Option<u64>,
+    /// The most bytes per second that request bodies are sent at, if they are limited.
+    pub upload_throughput: Option<u64>,
+}
+
+impl NetworkConditions {
+    /// The time that receiving the given number of bytes takes.
+    pub fn download_time(&self, bytes: usize) -> Duration {
+        transfer_time(bytes, self.download_throughput)
+    }
+
+    /// The time that sending the given number of bytes takes.
+    pub fn upload_time(&self, bytes: usize) -> Duration {
+        transfer_time(bytes, self.upload_throughput)
+    }
+}
+
+fn transfer_time(bytes: usize, throughput: Option<u64>) -> Duration {
+    match throughput {
+        // A throughput of 0 does not limit the transfers, like in other browsers.
+        Some(throughput) if throughput > 0 => {
+            Duration::from_micros(bytes as u64 * 1_000_000 / throughput)
+        },
+        _ => Duration::from_secs(0),
+    }
+}
+
 #[derive(Clone, Copy, Debug, Deserialize, Serialize)]
 /// The equivalent of script_layout_interface::message::Msg
 pub enum LayoutHangAnnotation {
     AddStylesheet,
     RemoveStylesheet,
+    StylesheetRuleChanged,
     SetQuirksMode,
     Reflow,
     GetRPC,
@@ -0,0 +1,246 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Authenticates the user to the servers that ask for a client certificate during the TLS
+//! handshake, with an identity that the user picks from the configured store, or that the
+//! embedder provides from elsewhere, such as the keychain of the platform.
+
+use crate::connector::distinguished_name;
+use embedder_traits::{ClientCertificateRequest, ClientIdentityChoice, ClientIdentityInfo};
+use embedder_traits::{EmbedderMsg, EmbedderProxy};
+use foreign_types::{ForeignType, ForeignTypeRef};
+use ipc_channel::ipc;
+use openssl::ex_data::Index;
+use openssl::pkey::{PKey, Private};
+use openssl::ssl::{ConnectConfiguration, Ssl, SslConnectorBuilder, SslRef};
+use openssl::x509::X509;
+use openssl_sys as ffi;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::os::raw::c_int;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+lazy_static! {
+    /// The index of the `ConnectionIdentity` of a connection in the extra data of its `Ssl`.
+    static ref CONNECTION_INDEX: Index<Ssl, ConnectionIdentity> =
+        Ssl::new_ex_index().expect("Failed to create an index for the extra data of Ssl");
+}
+
+/// A certificate with its private key, to authenticate the user with.
+pub struct ClientIdentity {
+    name: String,
+    certificate: X509,
+    private_key: PKey<Private>,
+}
+
+impl ClientIdentity {
+    /// Reads an identity from PEM. Both may be the same file, in which case its first
+    /// certificate must be the one of the user.
+    fn from_pem(name: String, certificate: &[u8], private_key: &[u8]) -> Option<ClientIdentity> {
+        let certificate = X509::from_pem(certificate).ok()?;
+        let private_key = PKey::private_key_from_pem(private_key).ok()?;
+        Some(ClientIdentity {
+            name,
+            certificate,
+            private_key,
+        })
+    }
+
+    fn info(&self) -> ClientIdentityInfo {
+        ClientIdentityInfo {
+            name: self.name.clone(),
+            subject: distinguished_name(self.certificate.subject_name()),
+            issuer: distinguished_name(self.certificate.issuer_name()),
+            not_after: self.certificate.not_after().to_string(),
+        }
+    }
+}
+
+/// Reads the identities of a directory, one for each `.pem` file that holds both a certificate
+/// and its private key, sorted by the name of the file.
+fn read_identity_store(directory: &Path) -> Vec<Arc<ClientIdentity>> {
+    let entries = match fs::read_dir(directory) {
+        Ok(entries) => entries,
+        Err(error) => {
+            warn!(
+                "Couldn't read the client identities of {:?}: {}",
+                directory, error
+            );
+            return vec![];
+        },
+    };
+    let mut paths: Vec<_> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.extension()
+                .map_or(false, |extension| extension == "pem")
+        })
+        .collect();
+    paths.sort();
+    paths
+        .into_iter()
+        .filter_map(|path| {
+            let name = path.file_stem()?.to_string_lossy().into_owned();
+            let pem = fs::read(&path).ok()?;
+            let identity = ClientIdentity::from_pem(name, &pem, &pem);
+            if identity.is_none() {
+                warn!(
+                    "Ignoring the client identity {:?} that has no certificate or key.",
+                    path
+                );
+            }
+            identity.map(Arc::new)
+        })
+        .collect()
+}
+
+/// What a connection needs to answer a request for a client certificate.
+struct ConnectionIdentity {
+    host: String,
+    client_certificates: ClientCertificates,
+}
+
+/// The identities that the user can authenticate with, and the one that they chose for each
+/// host that asked for a client certificate.
+#[derive(Clone, Default)]
+pub struct ClientCertificates {
+    /// The identities of the configured store.
+    identities: Arc<Vec<Arc<ClientIdentity>>>,
+    /// The identity that the user chose for each host, or `None` if they chose to send none.
+    chosen: Arc<Mutex<HashMap<String, Option<Arc<ClientIdentity>>>>>,
+    /// The hosts that asked for a client certificate before the user chose an identity for them.
+    requested: Arc<Mutex<HashSet<String>>>,
+    /// Where to ask the user which identity to use, without which none is sent.
+    embedder_proxy: Option<Arc<Mutex<EmbedderProxy>>>,
+}
+
+impl ClientCertificates {
+    pub fn new() -> ClientCertificates {
+        Default::default()
+    }
+
+    /// Reads the identities of the store that the `network.tls.client_identities` preference
+    /// names, and asks the embedder which one to use when a host asks for a certificate.
+    pub fn with_identity_store(embedder_proxy: EmbedderProxy) -> ClientCertificates {
+        let directory = pref!(network.tls.client_identities);
+        let identities = if directory.is_empty() {
+            vec![]
+        } else {
+            read_identity_store(Path::new(&directory))
+        };
+        ClientCertificates {
+            identities: Arc::new(identities),
+            embedder_proxy: Some(Arc::new(Mutex::new(embedder_proxy))),
+            ..Default::default()
+        }
+    }
+
+    /// Lets the TLS connector answer the requests of servers for a client certificate.
+    #[allow(unsafe_code)]
+    pub fn install(tls_config: &mut SslConnectorBuilder) {
+        unsafe {
+            ffi::SSL_CTX_set_client_cert_cb(tls_config.as_ptr(), Some(client_certificate_callback));
+        }
+    }
+
+    /// Lets a connection to the host answer a request for a client certificate.
+    pub fn configure(&self, ssl: &mut ConnectConfiguration, host: String) {
+        let connection = ConnectionIdentity {
+            host,
+            client_certificates: self.clone(),
+        };
+        ssl.set_ex_data(*CONNECTION_INDEX, connection);
+    }
+
+    /// The identity to answer a request of the host for a client certificate with. Hosts
+    /// that the user did not choose one for yet are remembered, to ask the user once the
+    /// handshake failed without one.
+    fn identity_for(&self, host: &str) -> Option<Arc<ClientIdentity>> {
+        if let Some(identity) = self.chosen.lock().unwrap().get(host) {
+            return identity.clone();
+        }
+        self.requested.lock().unwrap().insert(host.to_owned());
+        None
+    }
+
+    /// Asks the user which identity to authenticate to the host with, if the host asked for
+    /// a client certificate before they chose one. Returns whether they chose an identity, in
+    /// which case the connection is worth trying again.
+    /// <https://fetch.spec.whatwg.org/#concept-tls-client-certificate>
+    pub fn ask_for_identity(&self, host: &str) -> bool {
+        if !self.requested.lock().unwrap().remove(host) {
+            return false;
+        }
+        let embedder_proxy = match self.embedder_proxy {
+            Some(ref embedder_proxy) => embedder_proxy,
+            None => return false,
+        };
+        let (sender, receiver) = ipc::channel().expect("Failed to create IPC channel!");
+        let request = ClientCertificateRequest {
+            host: host.to_owned(),
+            identities: self
+                .identities
+                .iter()
+                .map(|identity| identity.info())
+                .collect(),
+        };
+        embedder_proxy
+            .lock()
+            .unwrap()
+            .send((None, EmbedderMsg::SelectClientCertificate(request, sender)));
+        let identity = match receiver.recv() {
+            Ok(Some(ClientIdentityChoice::Stored(index))) => self.identities.get(index).cloned(),
+            Ok(Some(ClientIdentityChoice::Provided {
+                certificate,
+                private_key,
+            })) => ClientIdentity::from_pem(
+                host.to_owned(),
+                certificate.as_bytes(),
+                private_key.as_bytes(),
+            )
+            .map(Arc::new),
+            Ok(None) => None,
+            Err(error) => {
+                warn!(
+                    "Failed to receive a client identity from embedder ({:?}).",
+                    error
+                );
+                None
+            },
+        };
+        let chosen = identity.is_some();
+        self.chosen
+            .lock()
+            .unwrap()
+            .insert(host.to_owned(), identity);
+        chosen
+    }
+}
+
+/// Answers the request of a server for a client certificate with the identity that the user
+/// chose for its host, if any. OpenSSL takes the references that it is handed.
+#[allow(unsafe_code)]
+unsafe extern "C" fn client_certificate_callback(
+    ssl: *mut ffi::SSL,
+    x509: *mut *mut ffi::X509,
+    pkey: *mut *mut ffi::EVP_PKEY,
+) -> c_int {
+    let ssl = SslRef::from_ptr(ssl);
+    let connection = match ssl.ex_data(*CONNECTION_INDEX) {
+        Some(connection) => connection,
+        None => return 0,
+    };
+    match connection
+        .client_certificates
+        .identity_for(&connection.host)
+    {
+        Some(identity) => {
+            *x509 = identity.certificate.clone().into_ptr();
+            *pkey = identity.private_key.clone().into_ptr();
+            1
+        },
+        None => 0,
+    }
+}
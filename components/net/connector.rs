@@ -2,14 +2,30 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use crate::client_certificates::ClientCertificates;
+use crate::doh::DnsResolver;
+use crate::happy_eyeballs::{connect_to_first, sort_addresses};
 use crate::hosts::replace_host;
-use hyper::client::connect::{Connect, Destination};
-use hyper::client::HttpConnector as HyperHttpConnector;
+use crate::http_loader::HANDLE;
+use crate::proxy::{connect_through_http_proxy, connect_through_socks5_proxy, Proxy, ProxyConfig};
+use embedder_traits::{CertificateError, CertificateErrorId};
+use futures::future;
+use hyper::client::connect::dns::{Name, Resolve};
+use hyper::client::connect::{Connect, Connected, Destination};
 use hyper::rt::Future;
 use hyper::{Body, Client};
 use hyper_openssl::HttpsConnector;
-use openssl::ssl::{SslConnector, SslConnectorBuilder, SslMethod, SslOptions};
-use openssl::x509;
+use msg::constellation_msg::PipelineId;
+use openssl::hash::MessageDigest;
+use openssl::ssl::{SslConnector, SslConnectorBuilder, SslMethod, SslOptions, SslVerifyMode};
+use openssl::x509::{self, X509NameRef, X509StoreContextRef};
+use servo_url::ServoUrl;
+use std::collections::{BTreeMap, HashMap};
+use std::io::{self, ErrorKind};
+use std::net::{IpAddr, SocketAddr};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::net::TcpStream;
 use tokio::prelude::future::Executor;
 
 pub const BUF_SIZE: usize = 32768;
@@ -30,30 +46,123 @@ const SIGNATURE_ALGORITHMS: &'static str = concat!(
     "RSA+SHA512:RSA+SHA384:RSA+SHA256"
 );
 
+/// Resolves the host of an HTTP(S) URL ahead of a fetch from it, with the resolver that the
+/// fetch connects with. Over HTTPS, the addresses are cached until the fetch needs them; with
+/// the resolver of the system, this only saves time if the system caches them. No connection
+/// is opened.
+pub fn resolve_host_ahead(resolver: &DnsResolver, url: &ServoUrl) {
+    if !matches!(url.scheme(), "http" | "https") {
+        return;
+    }
+    let host = match url.host_str() {
+        Some(host) => replace_host(host),
+        None => return,
+    };
+    let host = host.trim_matches(|c| c == '[' || c == ']');
+    if host.parse::<IpAddr>().is_ok() {
+        return;
+    }
+    let name = match host.parse::<Name>() {
+        Ok(name) => name,
+        Err(_) => return,
+    };
+    let host = host.to_owned();
+    let resolved = resolver.resolve(name).map(|_| ()).map_err(move |error| {
+        debug!("Failed to resolve {} ahead of a fetch: {}", host, error);
+    });
+    HANDLE.lock().unwrap().spawn(resolved);
+}
+
 pub struct HttpConnector {
-    inner: HyperHttpConnector,
+    resolver: DnsResolver,
 }
 
 impl HttpConnector {
-    fn new() -> HttpConnector {
-        let mut inner = HyperHttpConnector::new(4);
-        inner.enforce_http(false);
-        inner.set_happy_eyeballs_timeout(None);
-        HttpConnector { inner }
+    fn new(resolver: DnsResolver) -> HttpConnector {
+        HttpConnector { resolver }
+    }
+
+    /// Connects to the first address of the host that accepts a connection.
+    fn connect_to_host(
+        &self,
+        host: &str,
+        port: u16,
+    ) -> Box<dyn Future<Item = TcpStream, Error = io::Error> + Send> {
+        // Perform host replacement when making the actual TCP connection.
+        let host = replace_host(host);
+        let host = host.trim_matches(|c| c == '[' || c == ']');
+        if let Ok(address) = host.parse::<IpAddr>() {
+            return Box::new(connect_to_first(vec![SocketAddr::new(address, port)]));
+        }
+        let name = match host.parse::<Name>() {
+            Ok(name) => name,
+            Err(error) => {
+                return Box::new(future::err(io::Error::new(ErrorKind::InvalidInput, error)))
+            },
+        };
+        Box::new(self.resolver.resolve(name).and_then(move |addresses| {
+            let addresses = sort_addresses(addresses.collect())
+                .into_iter()
+                .map(|address| SocketAddr::new(address, port))
+                .collect();
+            connect_to_first(addresses)
+        }))
     }
 }
 
 impl Connect for HttpConnector {
-    type Transport = <HyperHttpConnector as Connect>::Transport;
-    type Error = <HyperHttpConnector as Connect>::Error;
-    type Future = <HyperHttpConnector as Connect>::Future;
+    type Transport = TcpStream;
+    type Error = io::Error;
+    type Future = Box<dyn Future<Item = (TcpStream, Connected), Error = io::Error> + Send>;
 
     fn connect(&self, dest: Destination) -> Self::Future {
-        // Perform host replacement when making the actual TCP connection.
-        let mut new_dest = dest.clone();
-        let addr = replace_host(dest.host());
-        new_dest.set_host(&*addr).unwrap();
-        self.inner.connect(new_dest)
+        let scheme = dest.scheme().to_owned();
+        let host = dest.host().to_owned();
+        let default_port = if scheme == "https" { 443 } else { 80 };
+        let port = dest.port().unwrap_or(default_port);
+        let proxy = match ProxyConfig::from_prefs().proxy_for(&scheme, &host, port) {
+            Ok(proxy) => proxy,
+            Err(error) => {
+                warn!("Refusing to connect to {}: {}", host, error);
+                return Box::new(future::err(io::Error::new(ErrorKind::Other, error)));
+            },
+        };
+
+        let stream = match proxy {
+            Proxy::Direct => self.connect_to_host(&host, port),
+            Proxy::Http {
+                host: ref proxy_host,
+                port: proxy_port,
+                ..
+            } |
+            Proxy::Socks5 {
+                host: ref proxy_host,
+                port: proxy_port,
+            } => self.connect_to_host(proxy_host, proxy_port),
+        };
+
+        Box::new(stream.and_then(move |stream| {
+            let connected = Connected::new();
+            match proxy {
+                // Hyper sends plain HTTP requests to the proxy with their absolute URL, but
+                // has no way to add the credentials for the proxy to them, so they are
+                // tunneled to authenticated proxies.
+                Proxy::Http {
+                    credentials: None, ..
+                } if scheme == "http" => {
+                    Box::new(future::ok((stream, connected.proxy(true)))) as Self::Future
+                },
+                Proxy::Http { credentials, .. } => Box::new(
+                    connect_through_http_proxy(stream, &host, port, credentials.as_deref())
+                        .map(|stream| (stream, connected)),
+                ),
+                Proxy::Socks5 { .. } => Box::new(
+                    connect_through_socks5_proxy(stream, &host, port)
+                        .map(|stream| (stream, connected)),
+                ),
+                Proxy::Direct => Box::new(future::ok((stream, connected))),
+            }
+        }))
     }
 }
 
@@ -107,11 +216,216 @@ pub fn create_tls_config(certs: &str, alpn: &[u8]) -> TlsConfig {
     cfg
 }
 
-pub fn create_http_client<E>(tls_config: TlsConfig, executor: E) -> Client<Connector, Body>
+/// The most certificate errors that wait for the decision of the user at once. The oldest one
+/// is forgotten when there are more.
+const MAX_PENDING_CERTIFICATE_ERRORS: usize = 64;
+
+/// A certificate that failed to verify for the request of a pipeline, which the user may
+/// choose to trust.
+struct PendingCertificateError {
+    pipeline_id: Option<PipelineId>,
+    error: CertificateError,
+    host: String,
+    /// The DER encoding of the certificate.
+    certificate: Vec<u8>,
+}
+
+/// The requests that wait for a connection, with their host and the certificate error of the
+/// connection to it that failed to verify, if one did.
+type CertificateRequests = HashMap<usize, (String, Option<(CertificateError, Vec<u8>)>)>;
+
+/// The certificates that the user chose to trust for a host although they failed to verify,
+/// and the errors of the certificates that failed to verify, which are reported to the user
+/// from here, so that only the user decides which certificate is trusted for which host.
+#[derive(Clone, Default)]
+pub struct CertificateExceptions {
+    /// The DER encodings of the trusted certificates, by host.
+    trusted: Arc<Mutex<HashMap<String, Vec<Vec<u8>>>>>,
+    requests: Arc<Mutex<CertificateRequests>>,
+    pending: Arc<Mutex<BTreeMap<CertificateErrorId, PendingCertificateError>>>,
+    next_id: Arc<AtomicUsize>,
+}
+
+impl CertificateExceptions {
+    pub fn new() -> CertificateExceptions {
+        Default::default()
+    }
+
+    /// Trusts the certificate for the connections to the host from now on.
+    pub fn add(&self, host: String, certificate: Vec<u8>) {
+        let mut trusted = self.trusted.lock().unwrap();
+        let certificates = trusted.entry(host).or_insert(vec![]);
+        if !certificates.contains(&certificate) {
+            certificates.push(certificate);
+        }
+    }
+
+    /// Starts recording the certificate errors of the connections to the host for a request,
+    /// until the returned watch is dropped.
+    pub fn watch(&self, host: &str) -> CertificateErrorWatch {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        self.requests
+            .lock()
+            .unwrap()
+            .insert(id, (host.to_owned(), None));
+        CertificateErrorWatch {
+            exceptions: self.clone(),
+            id,
+        }
+    }
+
+    /// Answers the certificate error of a request of the pipeline, trusting its certificate
+    /// for its host if the user chose to. Answers for errors of other pipelines are ignored.
+    pub fn answer(&self, id: CertificateErrorId, pipeline_id: PipelineId, trusted: bool) {
+        let mut pending = self.pending.lock().unwrap();
+        match pending.get(&id) {
+            Some(error) if error.pipeline_id == Some(pipeline_id) => {},
+            _ => return warn!("Answer for an unknown certificate error {:?}", id),
+        }
+        let error = pending.remove(&id).unwrap();
+        if trusted {
+            self.add(error.host, error.certificate);
+        }
+    }
+
+    /// Returns the certificate error of a request of the pipeline that waits for the decision
+    /// of the user, to show it to them.
+    pub fn pending_error(
+        &self,
+        id: CertificateErrorId,
+        pipeline_id: PipelineId,
+    ) -> Option<CertificateError> {
+        match self.pending.lock().unwrap().get(&id) {
+            Some(error) if error.pipeline_id == Some(pipeline_id) => Some(error.error.clone()),
+            _ => None,
+        }
+    }
+
+    /// Accepts the certificate of a connection to the host that failed to verify if the user
+    /// trusts it, and records why it failed otherwise.
+    fn verify(&self, host: &str, verified: bool, context: &X509StoreContextRef) -> bool {
+        if verified {
+            return true;
+        }
+        let certificate = match context
+            .chain()
+            .and_then(|chain| chain.get(0))
+            .or_else(|| context.current_cert())
+        {
+            Some(certificate) => certificate,
+            None => return false,
+        };
+        let der = match certificate.to_der() {
+            Ok(der) => der,
+            Err(_) => return false,
+        };
+        let trusted = self
+            .trusted
+            .lock()
+            .unwrap()
+            .get(host)
+            .map_or(false, |certificates| certificates.contains(&der));
+        if trusted {
+            return true;
+        }
+        let fingerprint = certificate
+            .digest(MessageDigest::sha256())
+            .map(|digest| {
+                let bytes: Vec<String> =
+                    digest.iter().map(|byte| format!("{:02X}", byte)).collect();
+                bytes.join(":")
+            })
+            .unwrap_or_default();
+        let error = CertificateError {
+            host: host.to_owned(),
+            reason: context.error().error_string().to_owned(),
+            subject: distinguished_name(certificate.subject_name()),
+            issuer: distinguished_name(certificate.issuer_name()),
+            not_before: certificate.not_before().to_string(),
+            not_after: certificate.not_after().to_string(),
+            fingerprint,
+            id: CertificateErrorId(self.next_id.fetch_add(1, Ordering::Relaxed)),
+        };
+        let mut requests = self.requests.lock().unwrap();
+        for request in requests.values_mut().filter(|request| request.0 == host) {
+            request.1 = Some((error.clone(), der.clone()));
+        }
+        false
+    }
+}
+
+/// Records the certificate errors of the connections for a request.
+pub struct CertificateErrorWatch {
+    exceptions: CertificateExceptions,
+    id: usize,
+}
+
+impl CertificateErrorWatch {
+    /// Returns why the certificate of the connection for the request failed to verify, if it
+    /// did, and keeps the certificate until the user decides whether to trust it.
+    pub fn take_error(&self, pipeline_id: Option<PipelineId>) -> Option<CertificateError> {
+        let (host, error) = self.exceptions.requests.lock().unwrap().remove(&self.id)?;
+        let (error, certificate) = error?;
+        let mut pending = self.exceptions.pending.lock().unwrap();
+        if pending.len() >= MAX_PENDING_CERTIFICATE_ERRORS {
+            let oldest = *pending.keys().next().unwrap();
+            pending.remove(&oldest);
+        }
+        pending.insert(
+            error.id,
+            PendingCertificateError {
+                pipeline_id,
+                error: error.clone(),
+                host,
+                certificate,
+            },
+        );
+        Some(error)
+    }
+}
+
+impl Drop for CertificateErrorWatch {
+    fn drop(&mut self) {
+        self.exceptions.requests.lock().unwrap().remove(&self.id);
+    }
+}
+
+pub fn distinguished_name(name: &X509NameRef) -> String {
+    let entries: Vec<String> = name
+        .entries()
+        .map(|entry| {
+            let key = entry.object().nid().short_name().unwrap_or("?");
+            match entry.data().as_utf8() {
+                Ok(value) => format!("{}={}", key, value),
+                Err(_) => format!("{}=?", key),
+            }
+        })
+        .collect();
+    entries.join(", ")
+}
+
+pub fn create_http_client<E>(
+    mut tls_config: TlsConfig,
+    certificate_exceptions: CertificateExceptions,
+    client_certificates: ClientCertificates,
+    resolver: DnsResolver,
+    executor: E,
+) -> Client<Connector, Body>
 where
     E: Executor<Box<dyn Future<Error = (), Item = ()> + Send + 'static>> + Sync + Send + 'static,
 {
-    let connector = HttpsConnector::with_connector(HttpConnector::new(), tls_config).unwrap();
+    ClientCertificates::install(&mut tls_config);
+    let mut connector =
+        HttpsConnector::with_connector(HttpConnector::new(resolver), tls_config).unwrap();
+    connector.set_callback(move |ssl, destination| {
+        let host = destination.host().to_owned();
+        client_certificates.configure(ssl, host.clone());
+        let certificate_exceptions = certificate_exceptions.clone();
+        ssl.set_verify_callback(SslVerifyMode::PEER, move |verified, context| {
+            certificate_exceptions.verify(&host, verified, context)
+        });
+        Ok(())
+    });
 
     Client::builder()
         .http1_title_case_headers(true)
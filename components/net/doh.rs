@@ -0,0 +1,307 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Resolves host names with DNS queries over HTTPS to the provider that the `network.doh`
+//! preferences name, instead of with the resolver of the system, which is used when DNS over
+//! HTTPS is disabled, and optionally when it fails.
+//! <https://tools.ietf.org/html/rfc8484>
+
+use crate::connector::{create_tls_config, ALPN_H1};
+use embedder_traits::resources::{self, Resource};
+use futures::future::{self, Future};
+use futures::Stream;
+use http::header::{ACCEPT, CONTENT_TYPE};
+use hyper::client::connect::dns::{GaiResolver, Name, Resolve};
+use hyper::client::HttpConnector as HyperHttpConnector;
+use hyper::{Body, Client, Request};
+use hyper_openssl::HttpsConnector;
+use std::collections::HashMap;
+use std::io::{self, ErrorKind};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use std::vec;
+
+/// The most host names whose addresses are cached.
+pub const MAX_CACHED_NAMES: usize = 1000;
+
+const DNS_MESSAGE: &str = "application/dns-message";
+const RECORD_TYPE_A: u16 = 1;
+const RECORD_TYPE_AAAA: u16 = 28;
+const RECORD_CLASS_IN: u16 = 1;
+
+/// Builds the wire format of a recursive query for the records of the given type of a name.
+/// <https://tools.ietf.org/html/rfc1035#section-4.1>
+pub fn encode_query(name: &str, record_type: u16) -> Result<Vec<u8>, io::Error> {
+    // The ID is 0, so that caches can share the responses.
+    let mut query = vec![0, 0, 0x01, 0x00, 0, 1, 0, 0, 0, 0, 0, 0];
+    for label in name.trim_end_matches('.').split('.') {
+        if label.is_empty() || label.len() > 63 {
+            return Err(io::Error::new(
+                ErrorKind::InvalidInput,
+                format!("invalid host name {:?}", name),
+            ));
+        }
+        query.push(label.len() as u8);
+        query.extend_from_slice(label.as_bytes());
+    }
+    query.push(0);
+    query.extend_from_slice(&record_type.to_be_bytes());
+    query.extend_from_slice(&RECORD_CLASS_IN.to_be_bytes());
+    Ok(query)
+}
+
+/// Returns the addresses that a response answers with, and the number of seconds that each
+/// one may be cached for.
+/// <https://tools.ietf.org/html/rfc1035#section-4.1>
+pub fn decode_response(response: &[u8]) -> Result<Vec<(IpAddr, u32)>, io::Error> {
+    let invalid = || io::Error::new(ErrorKind::InvalidData, "invalid DNS response");
+    let read_u16 = |position: usize| -> Result<u16, io::Error> {
+        match response.get(position..position + 2) {
+            Some(bytes) => Ok(u16::from(bytes[0]) << 8 | u16::from(bytes[1])),
+            None => Err(invalid()),
+        }
+    };
+    // Returns the position after the name at the given position.
+    let skip_name = |mut position: usize| -> Result<usize, io::Error> {
+        loop {
+            match response.get(position) {
+                Some(0) => return Ok(position + 1),
+                Some(length) if length & 0xC0 == 0xC0 => return Ok(position + 2),
+                Some(length) => position += 1 + *length as usize,
+                None => return Err(invalid()),
+            }
+        }
+    };
+
+    let flags = read_u16(2)?;
+    match flags & 0x000F {
+        0 => {},
+        // The name does not exist.
+        3 => return Ok(vec![]),
+        code => {
+            return Err(io::Error::new(
+                ErrorKind::Other,
+                format!("the DNS query failed with the response code {}", code),
+            ));
+        },
+    }
+    let questions = read_u16(4)?;
+    let answers = read_u16(6)?;
+    let mut position = 12;
+    for _ in 0..questions {
+        position = skip_name(position)? + 4;
+    }
+    let mut addresses = vec![];
+    for _ in 0..answers {
+        position = skip_name(position)?;
+        let record_type = read_u16(position)?;
+        let ttl = u32::from(read_u16(position + 4)?) << 16 | u32::from(read_u16(position + 6)?);
+        let length = read_u16(position + 8)? as usize;
+        position += 10;
+        let data = response
+            .get(position..position + length)
+            .ok_or_else(invalid)?;
+        position += length;
+        // Other records, such as the aliases that lead to the addresses, are skipped.
+        match (record_type, length) {
+            (RECORD_TYPE_A, 4) => {
+                let address = Ipv4Addr::new(data[0], data[1], data[2], data[3]);
+                addresses.push((IpAddr::V4(address), ttl));
+            },
+            (RECORD_TYPE_AAAA, 16) => {
+                let mut octets = [0; 16];
+                octets.copy_from_slice(data);
+                addresses.push((IpAddr::V6(Ipv6Addr::from(octets)), ttl));
+            },
+            _ => {},
+        }
+    }
+    Ok(addresses)
+}
+
+/// The addresses of the host names that were resolved over HTTPS, until their records expire.
+#[derive(Clone, Default)]
+pub struct DohCache {
+    entries: Arc<Mutex<HashMap<String, (Vec<IpAddr>, Instant)>>>,
+}
+
+impl DohCache {
+    pub fn new() -> DohCache {
+        Default::default()
+    }
+
+    pub fn get(&self, name: &str) -> Option<Vec<IpAddr>> {
+        let mut entries = self.entries.lock().unwrap();
+        match entries.get(name) {
+            Some(&(ref addresses, expiry)) if expiry > Instant::now() => Some(addresses.clone()),
+            Some(_) => {
+                entries.remove(name);
+                None
+            },
+            None => None,
+        }
+    }
+
+    /// Caches the addresses of a name until the first of their records expires.
+    pub fn insert(&self, name: &str, addresses: &[(IpAddr, u32)]) {
+        let ttl = match addresses.iter().map(|address| address.1).min() {
+            Some(ttl) if ttl > 0 => ttl,
+            _ => return,
+        };
+        let now = Instant::now();
+        let mut entries = self.entries.lock().unwrap();
+        if entries.len() >= MAX_CACHED_NAMES {
+            entries.retain(|_, entry| entry.1 > now);
+            if entries.len() >= MAX_CACHED_NAMES {
+                entries.clear();
+            }
+        }
+        let addresses = addresses.iter().map(|address| address.0).collect();
+        let expiry = now + Duration::from_secs(ttl.into());
+        entries.insert(name.to_owned(), (addresses, expiry));
+    }
+}
+
+/// Resolves host names over HTTPS if it is enabled, and with the resolver of the system
+/// otherwise.
+#[derive(Clone)]
+pub struct DnsResolver {
+    system: GaiResolver,
+    client: Client<HttpsConnector<HyperHttpConnector>, Body>,
+    cache: DohCache,
+}
+
+impl DnsResolver {
+    pub fn new() -> DnsResolver {
+        // The host of the provider itself is resolved by the system.
+        let tls_config =
+            create_tls_config(&resources::read_string(Resource::SSLCertificates), ALPN_H1);
+        let mut http_connector = HyperHttpConnector::new(1);
+        http_connector.enforce_http(false);
+        let connector = HttpsConnector::with_connector(http_connector, tls_config)
+            .expect("could not create the connector of the DNS over HTTPS client");
+        DnsResolver {
+            system: GaiResolver::new(4),
+            client: Client::builder().build(connector),
+            cache: DohCache::new(),
+        }
+    }
+
+    fn resolve_with_system(
+        &self,
+        name: Name,
+    ) -> Box<dyn Future<Item = vec::IntoIter<IpAddr>, Error = io::Error> + Send> {
+        Box::new(
+            self.system
+                .resolve(name)
+                .map(|addresses| addresses.collect::<Vec<_>>().into_iter()),
+        )
+    }
+
+    /// Queries the provider for the records of the given type of a name.
+    fn query(
+        &self,
+        provider: &str,
+        name: &str,
+        record_type: u16,
+    ) -> Box<dyn Future<Item = Vec<(IpAddr, u32)>, Error = io::Error> + Send> {
+        let query = match encode_query(name, record_type) {
+            Ok(query) => query,
+            Err(error) => return Box::new(future::err(error)),
+        };
+        let separator = if provider.contains('?') { '&' } else { '?' };
+        let uri = format!(
+            "{}{}dns={}",
+            provider,
+            separator,
+            base64::encode_config(&query, base64::URL_SAFE_NO_PAD)
+        );
+        let request = match Request::get(uri)
+            .header(ACCEPT, DNS_MESSAGE)
+            .body(Body::empty())
+        {
+            Ok(request) => request,
+            Err(error) => {
+                return Box::new(future::err(io::Error::new(ErrorKind::InvalidInput, error)))
+            },
+        };
+        let to_io_error = |error: hyper::Error| io::Error::new(ErrorKind::Other, error);
+        Box::new(
+            self.client
+                .request(request)
+                .map_err(to_io_error)
+                .and_then(move |response| {
+                    let is_dns_message = response
+                        .headers()
+                        .get(CONTENT_TYPE)
+                        .map_or(false, |content_type| content_type == DNS_MESSAGE);
+                    if !response.status().is_success() || !is_dns_message {
+                        return future::Either::A(future::err(io::Error::new(
+                            ErrorKind::Other,
+                            format!("the DNS provider responded with {}", response.status()),
+                        )));
+                    }
+                    future::Either::B(response.into_body().concat2().map_err(to_io_error))
+                })
+                .and_then(|body| decode_response(&body)),
+        )
+    }
+}
+
+impl Resolve for DnsResolver {
+    type Addrs = vec::IntoIter<IpAddr>;
+    type Future = Box<dyn Future<Item = Self::Addrs, Error = io::Error> + Send>;
+
+    fn resolve(&self, name: Name) -> Self::Future {
+        if !pref!(network.doh.enabled) {
+            return self.resolve_with_system(name);
+        }
+        let host = name.as_str().to_ascii_lowercase();
+        if let Some(addresses) = self.cache.get(&host) {
+            return Box::new(future::ok(addresses.into_iter()));
+        }
+
+        let provider = pref!(network.doh.url);
+        // The host resolves if either query succeeds, since many hosts have no IPv6 address,
+        // and some providers fail the queries for them.
+        let ipv4 = self
+            .query(&provider, &host, RECORD_TYPE_A)
+            .then(|result| Ok::<_, io::Error>(result));
+        let ipv6 = self
+            .query(&provider, &host, RECORD_TYPE_AAAA)
+            .then(|result| Ok::<_, io::Error>(result));
+        let cache = self.cache.clone();
+        let resolved = ipv4.join(ipv6).and_then(move |results| {
+            let addresses: Vec<_> = match results {
+                (Err(error), Err(_)) => return Err(error),
+                (ipv4, ipv6) => ipv4
+                    .into_iter()
+                    .chain(ipv6)
+                    .flat_map(|addresses| addresses)
+                    .collect(),
+            };
+            if addresses.is_empty() {
+                return Err(io::Error::new(
+                    ErrorKind::NotFound,
+                    format!("the DNS provider knows no address of {}", host),
+                ));
+            }
+            cache.insert(&host, &addresses);
+            Ok(addresses
+                .into_iter()
+                .map(|address| address.0)
+                .collect::<Vec<_>>()
+                .into_iter())
+        });
+        if !pref!(network.doh.fallback) {
+            return Box::new(resolved);
+        }
+        let resolver = self.clone();
+        Box::new(resolved.or_else(move |error| {
+            debug!("Falling back to the system resolver: {}", error);
+            resolver.resolve_with_system(name)
+        }))
+    }
+}
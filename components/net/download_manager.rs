@@ -0,0 +1,558 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Saves the responses that are not to be rendered, such as attachments, to the files that the
+//! embedder chooses, and lets the embedder follow the downloads, and pause, resume or cancel
+//! them. A download is resumed with a range request for the part of the response that is
+//! missing, which is only appended to the file if the response did not change meanwhile.
+//! Until the embedder chose where to save a response, it is received into a temporary file, so
+//! that no thread waits for the answer.
+
+use crate::http_loader::HttpState;
+use crate::resource_thread::CoreResourceManager;
+use embedder_traits::{DownloadAction, DownloadEvent, DownloadId, EmbedderMsg, EmbedderProxy};
+use headers::{ContentLength, ContentRange, HeaderMapExt};
+use http::header::{self, HeaderValue};
+use http::StatusCode;
+use ipc_channel::ipc::{self, IpcSender};
+use ipc_channel::router::ROUTER;
+use msg::constellation_msg::TopLevelBrowsingContextId;
+use net_traits::request::{Request, RequestBuilder};
+use net_traits::response::Response;
+use net_traits::FetchTaskTarget;
+use percent_encoding::percent_decode;
+use servo_config::basedir;
+use servo_url::ServoUrl;
+use std::collections::HashMap;
+use std::env;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, ErrorKind, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// How often the embedder is told about the progress of a download at most.
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+
+static NEXT_DOWNLOAD_ID: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Clone)]
+pub struct DownloadManager {
+    downloads: Arc<Mutex<HashMap<DownloadId, Download>>>,
+    embedder_proxy: EmbedderProxy,
+}
+
+struct Download {
+    /// The request to make again, for the missing part of the response, to resume.
+    request: RequestBuilder,
+    http_state: Arc<HttpState>,
+    browsing_context_id: Option<TopLevelBrowsingContextId>,
+    /// The file that the response is saved to, once it started.
+    path: Option<PathBuf>,
+    /// The file that the response is received into: the one of the download, or a temporary
+    /// one until the embedder chose where to save the response.
+    file: Option<File>,
+    /// Whether the embedder was asked where to save the response and did not answer yet.
+    choosing_path: bool,
+    /// Whether the whole response was received before the embedder chose where to save it,
+    /// which finishes the download once it did.
+    received_all: bool,
+    received_bytes: u64,
+    total_bytes: Option<u64>,
+    /// The `ETag` or `Last-Modified` of the response, which the missing part of the response
+    /// must still have.
+    validator: Option<HeaderValue>,
+    /// Cancels the fetch of the response, unless the download is paused or failed.
+    cancel_chan: Option<IpcSender<()>>,
+    /// Counts the fetches of the response, so that what a cancelled one still receives is
+    /// ignored.
+    fetch_count: u32,
+}
+
+impl DownloadManager {
+    pub fn new(embedder_proxy: EmbedderProxy) -> DownloadManager {
+        DownloadManager {
+            downloads: Arc::new(Mutex::new(HashMap::new())),
+            embedder_proxy,
+        }
+    }
+
+    pub fn start(
+        &self,
+        request: RequestBuilder,
+        browsing_context_id: Option<TopLevelBrowsingContextId>,
+        http_state: &Arc<HttpState>,
+        resource_manager: &CoreResourceManager,
+    ) {
+        let id = DownloadId(NEXT_DOWNLOAD_ID.fetch_add(1, Ordering::SeqCst));
+        let download = Download {
+            request,
+            http_state: http_state.clone(),
+            browsing_context_id,
+            path: None,
+            file: None,
+            choosing_path: false,
+            received_all: false,
+            received_bytes: 0,
+            total_bytes: None,
+            validator: None,
+            cancel_chan: None,
+            fetch_count: 0,
+        };
+        self.downloads.lock().unwrap().insert(id, download);
+        self.fetch(id, resource_manager);
+    }
+
+    pub fn handle_action(
+        &self,
+        id: DownloadId,
+        action: DownloadAction,
+        resource_manager: &CoreResourceManager,
+    ) {
+        let mut downloads = self.downloads.lock().unwrap();
+        match action {
+            DownloadAction::Pause => {
+                let download = match downloads.get_mut(&id) {
+                    Some(download) => download,
+                    None => return,
+                };
+                if let Some(cancel_chan) = download.cancel_chan.take() {
+                    let _ = cancel_chan.send(());
+                    self.notify(id, download, DownloadEvent::Paused);
+                }
+            },
+            DownloadAction::Resume => {
+                match downloads.get(&id) {
+                    Some(download) if download.cancel_chan.is_none() => {
+                        self.notify(id, download, DownloadEvent::Resumed)
+                    },
+                    _ => return,
+                }
+                drop(downloads);
+                self.fetch(id, resource_manager);
+            },
+            DownloadAction::Cancel => {
+                let download = match downloads.remove(&id) {
+                    Some(download) => download,
+                    None => return,
+                };
+                if let Some(ref cancel_chan) = download.cancel_chan {
+                    let _ = cancel_chan.send(());
+                }
+                if let Some(ref path) = download.path {
+                    let _ = fs::remove_file(path);
+                }
+                self.notify(id, &download, DownloadEvent::Cancelled);
+            },
+        }
+    }
+
+    /// Fetches the part of the response that is missing from the file.
+    fn fetch(&self, id: DownloadId, resource_manager: &CoreResourceManager) {
+        let mut downloads = self.downloads.lock().unwrap();
+        let download = match downloads.get_mut(&id) {
+            Some(download) => download,
+            None => return,
+        };
+        let mut request = download.request.clone();
+        if download.received_bytes > 0 {
+            let range = format!("bytes={}-", download.received_bytes);
+            request
+                .headers
+                .insert(header::RANGE, HeaderValue::from_str(&range).unwrap());
+            if let Some(ref validator) = download.validator {
+                request.headers.insert(header::IF_RANGE, validator.clone());
+            }
+        }
+
+        let (cancel_sender, cancel_receiver) = ipc::channel().unwrap();
+        download.cancel_chan = Some(cancel_sender);
+        download.fetch_count += 1;
+        let target = DownloadTarget {
+            manager: self.clone(),
+            id,
+            fetch_count: download.fetch_count,
+            last_progress: Instant::now(),
+        };
+        let http_state = download.http_state.clone();
+        drop(downloads);
+        resource_manager.fetch(request, None, target, &http_state, Some(cancel_receiver));
+    }
+
+    /// Asks the embedder where to save a response, if it wants it downloaded at all, and
+    /// carries on with the download when it answers.
+    fn choose_path(
+        &self,
+        id: DownloadId,
+        browsing_context_id: Option<TopLevelBrowsingContextId>,
+        url: ServoUrl,
+        suggested_path: PathBuf,
+    ) {
+        let (sender, receiver) = ipc::channel().unwrap();
+        let manager = self.clone();
+        let started_url = url.clone();
+        ROUTER.add_route(
+            receiver.to_opaque(),
+            Box::new(move |message| {
+                let path = message.to().unwrap_or(None);
+                manager.path_chosen(id, started_url.clone(), path);
+            }),
+        );
+        let msg = EmbedderMsg::AllowDownload(id, url, suggested_path, sender);
+        self.embedder_proxy.send((browsing_context_id, msg));
+    }
+
+    /// Moves what was received of the response from the temporary file to a new file at the
+    /// path that the embedder chose, or stops the download if it did not want it.
+    fn path_chosen(&self, id: DownloadId, url: ServoUrl, path: Option<PathBuf>) {
+        let mut downloads = self.downloads.lock().unwrap();
+        let download = match downloads.get_mut(&id) {
+            Some(download) if download.choosing_path => download,
+            _ => return,
+        };
+        download.choosing_path = false;
+        let path = match path {
+            Some(path) => path,
+            None => {
+                if let Some(ref cancel_chan) = download.cancel_chan {
+                    let _ = cancel_chan.send(());
+                }
+                self.notify(id, download, DownloadEvent::Cancelled);
+                downloads.remove(&id);
+                return;
+            },
+        };
+        let received = download.file.take();
+        let saved = create_new_file(&path).and_then(|(path, mut file)| {
+            if let Some(mut received) = received {
+                received.seek(SeekFrom::Start(0))?;
+                io::copy(&mut received, &mut file)?;
+            }
+            Ok((path, file))
+        });
+        let (path, file) = match saved {
+            Ok(saved) => saved,
+            Err(error) => return self.fail(id, download, error.to_string()),
+        };
+        download.path = Some(path.clone());
+        download.file = Some(file);
+        let event = DownloadEvent::Started {
+            url,
+            path,
+            total_bytes: download.total_bytes,
+        };
+        self.notify(id, download, event);
+        if download.received_all {
+            self.finish(id, &mut downloads);
+        }
+    }
+
+    /// Stops the download, which can then be resumed.
+    fn fail(&self, id: DownloadId, download: &mut Download, reason: String) {
+        warn!("Download of {} failed ({}).", download.request.url, reason);
+        download.file = None;
+        download.received_all = false;
+        if download.path.is_none() {
+            // What was received went away with the temporary file.
+            download.received_bytes = 0;
+            download.validator = None;
+        }
+        if let Some(cancel_chan) = download.cancel_chan.take() {
+            let _ = cancel_chan.send(());
+        }
+        self.notify(id, download, DownloadEvent::Failed(reason));
+    }
+
+    /// Completes the download, whose whole response was saved.
+    fn finish(&self, id: DownloadId, downloads: &mut HashMap<DownloadId, Download>) {
+        let download = match downloads.get_mut(&id) {
+            Some(download) => download,
+            None => return,
+        };
+        let synced = match download.file.take() {
+            Some(file) => file.sync_all(),
+            None => Ok(()),
+        };
+        if let Err(error) = synced {
+            return self.fail(id, download, error.to_string());
+        }
+        let event = DownloadEvent::Progress {
+            received_bytes: download.received_bytes,
+            total_bytes: download.total_bytes,
+        };
+        self.notify(id, download, event);
+        self.notify(id, download, DownloadEvent::Finished);
+        downloads.remove(&id);
+    }
+
+    fn notify(&self, id: DownloadId, download: &Download, event: DownloadEvent) {
+        self.embedder_proxy.send((
+            download.browsing_context_id,
+            EmbedderMsg::Download(id, event),
+        ));
+    }
+}
+
+/// Receives a response, or the part of it that is missing, into the file of a download.
+struct DownloadTarget {
+    manager: DownloadManager,
+    id: DownloadId,
+    fetch_count: u32,
+    last_progress: Instant,
+}
+
+impl DownloadTarget {
+    /// The download, unless it was paused, failed or cancelled since the response started
+    /// being received.
+    fn current_download<'a>(
+        &self,
+        downloads: &'a mut HashMap<DownloadId, Download>,
+    ) -> Option<&'a mut Download> {
+        downloads.get_mut(&self.id).filter(|download| {
+            download.fetch_count == self.fetch_count && download.cancel_chan.is_some()
+        })
+    }
+
+    /// Checks that the response, or the missing part of it, can be saved.
+    fn check_response(&self, download: &mut Download, response: &Response) -> Result<(), String> {
+        if let Some(error) = response.get_network_error() {
+            return Err(format!("{:?}", error));
+        }
+        let status = response.status.as_ref().map(|&(status, _)| status);
+        match status {
+            // The missing part of the response.
+            Some(StatusCode::PARTIAL_CONTENT) if download.received_bytes > 0 => {
+                let range = response.headers.typed_get::<ContentRange>();
+                match range.as_ref().and_then(|range| range.bytes_range()) {
+                    Some((start, _)) if start == download.received_bytes => {},
+                    _ => return Err("Invalid range".into()),
+                }
+                download.total_bytes = range
+                    .and_then(|range| range.bytes_len())
+                    .or(download.total_bytes);
+            },
+            // The whole response, which may have changed since the download was paused.
+            Some(StatusCode::OK) => {
+                download.received_bytes = 0;
+                download.total_bytes = response
+                    .headers
+                    .typed_get::<ContentLength>()
+                    .map(|ContentLength(length)| length);
+            },
+            Some(status) => return Err(format!("HTTP error {}", status.as_u16())),
+            None => return Err("No response".into()),
+        }
+        download.validator = response
+            .headers
+            .get(header::ETAG)
+            .or_else(|| response.headers.get(header::LAST_MODIFIED))
+            .cloned();
+        Ok(())
+    }
+
+    /// Opens the file to receive the response into: the one of the download when it is
+    /// resumed, or else a temporary one until the embedder chose where to save the response.
+    /// Returns whether the embedder needs to be asked.
+    fn open_file(&self, download: &mut Download) -> io::Result<bool> {
+        if let Some(ref path) = download.path {
+            let file = OpenOptions::new()
+                .write(true)
+                .append(download.received_bytes > 0)
+                .truncate(download.received_bytes == 0)
+                .open(path)?;
+            download.file = Some(file);
+            return Ok(false);
+        }
+        match download.file {
+            Some(ref mut file) if download.received_bytes > 0 => {
+                file.seek(SeekFrom::End(0))?;
+            },
+            _ => download.file = Some(tempfile::tempfile()?),
+        }
+        let ask = !download.choosing_path;
+        download.choosing_path = true;
+        Ok(ask)
+    }
+}
+
+impl FetchTaskTarget for DownloadTarget {
+    fn process_request_body(&mut self, _: &Request) {}
+
+    fn process_request_eof(&mut self, _: &Request) {}
+
+    fn process_response(&mut self, response: &Response) {
+        // The download is not subject to the response tainting of the navigation it replaced.
+        let response = response.actual_response();
+        let downloads = self.manager.downloads.clone();
+        let mut downloads = downloads.lock().unwrap();
+        let download = match self.current_download(&mut downloads) {
+            Some(download) => download,
+            None => return,
+        };
+        if let Err(reason) = self.check_response(download, response) {
+            return self.manager.fail(self.id, download, reason);
+        }
+        let ask = match self.open_file(download) {
+            Ok(ask) => ask,
+            Err(error) => return self.manager.fail(self.id, download, error.to_string()),
+        };
+        if ask {
+            // The embedder chooses where to save the response, and may not want it at all.
+            let url = response.url().unwrap_or(&download.request.url).clone();
+            let suggested_path = downloads_dir().join(file_name(response, &url));
+            let browsing_context_id = download.browsing_context_id;
+            drop(downloads);
+            self.manager
+                .choose_path(self.id, browsing_context_id, url, suggested_path);
+        }
+    }
+
+    fn process_response_chunk(&mut self, chunk: Vec<u8>) {
+        let downloads = self.manager.downloads.clone();
+        let mut downloads = downloads.lock().unwrap();
+        let download = match self.current_download(&mut downloads) {
+            Some(download) => download,
+            None => return,
+        };
+        let written = match download.file {
+            Some(ref mut file) => file.write_all(&chunk),
+            None => return,
+        };
+        if let Err(error) = written {
+            return self.manager.fail(self.id, download, error.to_string());
+        }
+        download.received_bytes += chunk.len() as u64;
+        // The embedder only follows the progress once the download started.
+        if download.path.is_some() && self.last_progress.elapsed() >= PROGRESS_INTERVAL {
+            self.last_progress = Instant::now();
+            let event = DownloadEvent::Progress {
+                received_bytes: download.received_bytes,
+                total_bytes: download.total_bytes,
+            };
+            self.manager.notify(self.id, download, event);
+        }
+    }
+
+    fn process_response_eof(&mut self, response: &Response) {
+        let downloads = self.manager.downloads.clone();
+        let mut downloads = downloads.lock().unwrap();
+        let download = match self.current_download(&mut downloads) {
+            Some(download) => download,
+            None => return,
+        };
+        if download.file.is_none() {
+            return;
+        }
+        let incomplete = download
+            .total_bytes
+            .map_or(false, |total_bytes| download.received_bytes < total_bytes);
+        if response.is_network_error() || incomplete {
+            return self
+                .manager
+                .fail(self.id, download, "Incomplete response".into());
+        }
+        if download.choosing_path {
+            download.received_all = true;
+            return;
+        }
+        self.manager.finish(self.id, &mut downloads);
+    }
+}
+
+/// The directory to save the downloads to, from the `network.downloads.directory` preference,
+/// or the one of the platform.
+fn downloads_dir() -> PathBuf {
+    let directory = pref!(network.downloads.directory);
+    if !directory.is_empty() {
+        return PathBuf::from(directory);
+    }
+    basedir::default_downloads_dir().unwrap_or_else(env::temp_dir)
+}
+
+/// The name to save a response as, from its `Content-Disposition` header, or the last segment
+/// of its URL, without the parts that could make it a path.
+fn file_name(response: &Response, url: &ServoUrl) -> String {
+    let name = response
+        .headers
+        .get(header::CONTENT_DISPOSITION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(content_disposition_file_name)
+        .or_else(|| {
+            let segment = url.path_segments()?.last()?;
+            let segment = percent_decode(segment.as_bytes()).decode_utf8_lossy();
+            Some(segment.into_owned())
+        })
+        .unwrap_or_default();
+    let name = name
+        .rsplit(|c| c == '/' || c == '\\')
+        .next()
+        .unwrap_or("")
+        .trim_start_matches('.')
+        .replace(|c: char| c.is_control(), "");
+    if name.trim().is_empty() {
+        return "download".to_owned();
+    }
+    name
+}
+
+/// The file name that a `Content-Disposition` header suggests, preferring the `filename*`
+/// parameter, which can be in any script.
+/// <https://tools.ietf.org/html/rfc6266#section-4.3>
+pub fn content_disposition_file_name(value: &str) -> Option<String> {
+    let mut file_name = None;
+    for parameter in value.split(';').skip(1) {
+        let mut parts = parameter.splitn(2, '=');
+        let name = parts.next()?.trim().to_ascii_lowercase();
+        let value = parts.next()?.trim();
+        match &*name {
+            "filename*" => {
+                // https://tools.ietf.org/html/rfc5987#section-3.2
+                let mut parts = value.splitn(3, '\'');
+                let charset = parts.next()?;
+                let encoded = parts.nth(1)?;
+                if charset.eq_ignore_ascii_case("utf-8") {
+                    let decoded = percent_decode(encoded.as_bytes()).decode_utf8().ok()?;
+                    return Some(decoded.into_owned());
+                }
+            },
+            "filename" => file_name = Some(value.trim_matches('"').to_owned()),
+            _ => {},
+        }
+    }
+    file_name
+}
+
+/// The most numbers that are tried to add to the name of a file that exists already.
+const MAX_FILE_NUMBER: u32 = 1000;
+
+/// Creates a file that did not exist yet at the path, or at the path with a number added to
+/// the name of the file if it is taken, without following symbolic links.
+fn create_new_file(path: &Path) -> io::Result<(PathBuf, File)> {
+    let directory = path.parent().unwrap_or_else(|| Path::new(""));
+    let stem = path
+        .file_stem()
+        .map_or("download".into(), |stem| stem.to_string_lossy());
+    let extension = path
+        .extension()
+        .map(|extension| format!(".{}", extension.to_string_lossy()))
+        .unwrap_or_default();
+    let mut candidate = path.to_owned();
+    for number in 1..=MAX_FILE_NUMBER {
+        match OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&candidate)
+        {
+            Ok(file) => return Ok((candidate, file)),
+            Err(ref error) if error.kind() == ErrorKind::AlreadyExists => {},
+            Err(error) => return Err(error),
+        }
+        candidate = directory.join(format!("{} ({}){}", stem, number, extension));
+    }
+    Err(io::Error::new(
+        ErrorKind::AlreadyExists,
+        "every name for the file is taken",
+    ))
+}
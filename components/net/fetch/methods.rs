@@ -5,8 +5,11 @@
 use crate::data_loader::decode;
 use crate::fetch::cors_cache::CorsCache;
 use crate::filemanager_thread::{FileManager, FILE_CHUNK_SIZE};
-use crate::http_loader::{determine_request_referrer, http_fetch, HttpState};
+use crate::http_loader::{cors_check, determine_request_referrer, http_fetch, HttpState};
 use crate::http_loader::{set_default_accept, set_default_accept_language};
+use crate::internal_pages;
+use crate::request_scheduler::ConnectionSlot;
+use crate::resource_thread::CoreResourceThreadPool;
 use crate::subresource_integrity::is_response_integrity_valid;
 use content_security_policy as csp;
 use crossbeam_channel::{unbounded, Receiver, Sender};
@@ -26,6 +29,7 @@ use net_traits::request::{CredentialsMode, Destination, Referrer, Request, Reque
 use net_traits::response::{Response, ResponseBody, ResponseType};
 use net_traits::{FetchTaskTarget, NetworkError, ReferrerPolicy, ResourceFetchTiming};
 use net_traits::{ResourceAttribute, ResourceTimeValue};
+use profile_traits::mem::ProfilerChan as MemProfilerChan;
 use servo_arc::Arc as ServoArc;
 use servo_url::ServoUrl;
 use std::borrow::Cow;
@@ -35,7 +39,7 @@ use std::mem;
 use std::ops::Bound;
 use std::str;
 use std::sync::atomic::Ordering;
-use std::sync::{Arc, Mutex};
+use std::sync::{Arc, Mutex, Weak};
 
 lazy_static! {
     static ref X_CONTENT_TYPE_OPTIONS: HeaderName =
@@ -59,6 +63,11 @@ pub struct FetchContext {
     pub file_token: FileTokenCheck,
     pub cancellation_listener: Arc<Mutex<CancellationListener>>,
     pub timing: ServoArc<Mutex<ResourceFetchTiming>>,
+    pub mem_profiler_chan: Option<MemProfilerChan>,
+    pub thread_pool: Weak<CoreResourceThreadPool>,
+    /// The connection to the host of the request that the fetch waited for before it
+    /// started, until its network fetch takes it.
+    pub connection_slot: Mutex<Option<ConnectionSlot>>,
 }
 
 pub struct CancellationListener {
@@ -145,8 +154,27 @@ pub fn fetch_with_cors_cache(
         // TODO: handle client hints headers.
     }
 
+    // A preload of the same resource may already have fetched the response.
+    if !request.preload {
+        if let Some(mut response) = context.state.preload_cache.take(request) {
+            target.process_response(&response);
+            wait_for_response(&mut response, target, &mut None);
+            target.process_response_eof(&response);
+            return;
+        }
+    }
+    let preload_key = if request.preload {
+        context.state.preload_cache.start(request)
+    } else {
+        None
+    };
+
     // Step 8.
-    main_fetch(request, cache, false, false, target, &mut None, &context);
+    let response = main_fetch(request, cache, false, false, target, &mut None, &context);
+
+    if let Some(preload_key) = preload_key {
+        context.state.preload_cache.finish(preload_key, response);
+    }
 }
 
 /// https://www.w3.org/TR/CSP/#should-block-request
@@ -260,6 +288,14 @@ pub fn main_fetch(
     // Step 9.
     // TODO: handle FTP URLs.
 
+    // Navigations to the URLs of a scheme that a site registered a handler for go to the handler.
+    if request.destination == Destination::Document {
+        let protocol_handlers = context.state.protocol_handlers.read().unwrap();
+        if let Some(handler_url) = protocol_handlers.handler_url(&request.current_url()) {
+            *request.current_url_mut() = handler_url;
+        }
+    }
+
     // Step 10.
     context
         .state
@@ -271,6 +307,15 @@ pub fn main_fetch(
     // Step 11.
     // Not applicable: see fetch_async.
 
+    // The embedder may rewrite or block the request, or respond to it itself.
+    if response.is_none() {
+        response = context
+            .state
+            .request_interceptor
+            .intercept_request(request, done_chan, context)
+            .map(|response| taint_intercepted_response(request, response, cors_flag, done_chan));
+    }
+
     // Step 12.
     let mut response = response.unwrap_or_else(|| {
         let current_url = request.current_url();
@@ -280,7 +325,10 @@ pub fn main_fetch(
             false
         };
 
-        if (same_origin && !cors_flag) || current_url.scheme() == "data" {
+        if (same_origin && !cors_flag) ||
+            current_url.scheme() == "data" ||
+            context.state.protocols.is_fetchable(current_url.scheme())
+        {
             // Substep 1.
             request.response_tainting = ResponseTainting::Basic;
 
@@ -505,6 +553,45 @@ pub fn main_fetch(
     response
 }
 
+/// Sets the response tainting of a request that the embedder responded to, like step 12 of
+/// main fetch does for the responses that are fetched, so that its response is filtered and
+/// only reaches other origins when they may read it.
+fn taint_intercepted_response(
+    request: &mut Request,
+    response: Response,
+    cors_flag: bool,
+    done_chan: &mut DoneChannel,
+) -> Response {
+    if response.is_network_error() {
+        return response;
+    }
+    let same_origin = match request.origin {
+        Origin::Origin(ref origin) => *origin == request.current_url().origin(),
+        Origin::Client => false,
+    };
+    if same_origin && !cors_flag {
+        request.response_tainting = ResponseTainting::Basic;
+        return response;
+    }
+    let error = match request.mode {
+        RequestMode::SameOrigin => "Cross-origin response",
+        RequestMode::NoCors => {
+            request.response_tainting = ResponseTainting::Opaque;
+            return response;
+        },
+        _ => {
+            request.response_tainting = ResponseTainting::CorsTainting;
+            if cors_check(request, &response).is_ok() {
+                return response;
+            }
+            "CORS check failed"
+        },
+    };
+    // The body that the embedder still sends is not for the network error.
+    *done_chan = None;
+    Response::network_error(NetworkError::Internal(error.into()))
+}
+
 fn wait_for_response(response: &mut Response, target: Target, done_chan: &mut DoneChannel) {
     if let Some(ref ch) = *done_chan {
         loop {
@@ -781,12 +868,41 @@ fn scheme_fetch(
             response
         },
 
+        "servo" => internal_pages::fetch(request, context),
+
         "ftp" => {
             debug!("ftp is not implemented");
             Response::network_error(NetworkError::Internal("Unexpected scheme".into()))
         },
 
-        _ => Response::network_error(NetworkError::Internal("Unexpected scheme".into())),
+        scheme => match context.state.protocols.get(scheme) {
+            Some(handler) => protocol_fetch(request, handler),
+            None => Response::network_error(NetworkError::Internal("Unexpected scheme".into())),
+        },
+    }
+}
+
+/// Fetches a URL of a scheme that the embedder registered a handler for.
+fn protocol_fetch(request: &Request, handler: &dyn ProtocolHandler) -> Response {
+    let url = request.current_url();
+    let web_request = WebResourceRequest {
+        method: request.method.clone(),
+        headers: request.headers.clone(),
+        url: url.clone(),
+        is_navigation: request.destination == Destination::Document,
+        is_redirect: request.redirect_count > 0,
+    };
+    match handler.load(&web_request) {
+        Ok(protocol_response) => {
+            let mut response = Response::new(url, ResourceFetchTiming::new(request.timing_type()));
+            let reason = protocol_response.status.canonical_reason().unwrap_or("");
+            response.status = Some((protocol_response.status, reason.into()));
+            response.raw_status = Some((protocol_response.status.as_u16(), reason.into()));
+            response.headers = protocol_response.headers;
+            *response.body.lock().unwrap() = ResponseBody::Done(protocol_response.body);
+            response
+        },
+        Err(message) => Response::network_error(NetworkError::Internal(message)),
     }
 }
 
@@ -0,0 +1,318 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! The entries that the handles of the File System Access API represent: those of the origin
+//! private file systems, which are directories of their own on disk, and the files that the
+//! user picked.
+//! <https://wicg.github.io/file-system-access/>
+
+use net_traits::filemanager_thread::{FileManagerResult, FileManagerThreadError};
+use net_traits::filemanager_thread::{FileSystemAccessError, FileSystemEntryKind};
+use net_traits::filemanager_thread::{FileSystemLocator, SyncAccessOperation, SyncAccessReply};
+use percent_encoding::{utf8_percent_encode, NON_ALPHANUMERIC};
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tempfile::TempDir;
+use uuid::Uuid;
+
+pub struct FileSystemStore {
+    /// The directory that holds the origin private file systems, one directory per origin,
+    /// which only the current user can access. There are no origin private file systems
+    /// without one.
+    root: Option<PathBuf>,
+    /// The directory that is removed along with the store, when the origin private file
+    /// systems only last for the session.
+    _session_dir: Option<TempDir>,
+    /// The paths of the files that the user picked, by the ids given to them.
+    picked_files: Mutex<HashMap<Uuid, PathBuf>>,
+    /// The files opened by sync access handles, which no one else can open or remove until
+    /// they are closed, by the ids given to the handles.
+    sync_access_files: Mutex<HashMap<Uuid, (PathBuf, Arc<Mutex<File>>)>>,
+}
+
+impl FileSystemStore {
+    /// A store that keeps the origin private file systems in the given directory, or in a
+    /// directory of its own for the session if there is none or it can't be made private.
+    pub fn new(root: Option<PathBuf>) -> FileSystemStore {
+        let root = root.and_then(|root| match create_private_dir(&root) {
+            Ok(()) => Some(root),
+            Err(e) => {
+                warn!("Failed to use {:?} for the file systems ({}).", root, e);
+                None
+            },
+        });
+        let session_dir = match root {
+            Some(_) => None,
+            None => match tempfile::Builder::new()
+                .prefix("servo-file-systems-")
+                .tempdir()
+            {
+                Ok(dir) => Some(dir),
+                Err(e) => {
+                    warn!("Origin private file systems are unavailable ({}).", e);
+                    None
+                },
+            },
+        };
+        FileSystemStore {
+            root: root.or_else(|| session_dir.as_ref().map(|dir| dir.path().to_path_buf())),
+            _session_dir: session_dir,
+            picked_files: Mutex::new(HashMap::new()),
+            sync_access_files: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn add_picked_file(&self, path: PathBuf) -> Uuid {
+        let id = Uuid::new_v4();
+        self.picked_files.lock().unwrap().insert(id, path);
+        id
+    }
+
+    /// Forgets a file that the user picked, once the handle to it is gone.
+    pub fn forget_picked_file(&self, id: &Uuid) {
+        self.picked_files.lock().unwrap().remove(id);
+    }
+
+    /// The path on disk of the entry that a handle represents.
+    pub fn path(&self, locator: &FileSystemLocator) -> FileManagerResult<PathBuf> {
+        match *locator {
+            FileSystemLocator::Private(ref origin, ref names) => {
+                let root = match self.root {
+                    Some(ref root) => root,
+                    None => return Err(access_error(FileSystemAccessError::Unavailable)),
+                };
+                let origin_root =
+                    root.join(utf8_percent_encode(origin, NON_ALPHANUMERIC).to_string());
+                fs::create_dir_all(&origin_root).map_err(io_error)?;
+                let mut path = origin_root.clone();
+                for name in names {
+                    // Each name must be a single entry of the directory before it, so that the
+                    // path can't leave the file system of the origin.
+                    let mut components = Path::new(name).components();
+                    let is_entry = match (components.next(), components.next()) {
+                        (Some(Component::Normal(_)), None) => true,
+                        _ => false,
+                    };
+                    if !is_valid_name(name) || !is_entry {
+                        return Err(access_error(FileSystemAccessError::InvalidName));
+                    }
+                    path.push(name);
+                }
+                if !path.starts_with(&origin_root) {
+                    return Err(access_error(FileSystemAccessError::InvalidName));
+                }
+                Ok(path)
+            },
+            FileSystemLocator::Picked(ref id) => match self.picked_files.lock().unwrap().get(id) {
+                Some(path) => Ok(path.clone()),
+                None => Err(access_error(FileSystemAccessError::NotFound)),
+            },
+        }
+    }
+
+    /// <https://wicg.github.io/file-system-access/#dom-filesystemdirectoryhandle-getfilehandle>
+    /// <https://wicg.github.io/file-system-access/#dom-filesystemdirectoryhandle-getdirectoryhandle>
+    pub fn get_entry(
+        &self,
+        locator: &FileSystemLocator,
+        kind: FileSystemEntryKind,
+        create: bool,
+    ) -> FileManagerResult<()> {
+        let path = self.path(locator)?;
+        match fs::metadata(&path) {
+            Ok(metadata) => {
+                if metadata.is_dir() == (kind == FileSystemEntryKind::Directory) {
+                    Ok(())
+                } else {
+                    Err(access_error(FileSystemAccessError::TypeMismatch))
+                }
+            },
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound && create => {
+                let parent_is_dir = path.parent().map_or(false, Path::is_dir);
+                if !parent_is_dir {
+                    return Err(access_error(FileSystemAccessError::NotFound));
+                }
+                match kind {
+                    FileSystemEntryKind::File => OpenOptions::new()
+                        .write(true)
+                        .create_new(true)
+                        .open(&path)
+                        .map(|_| ()),
+                    FileSystemEntryKind::Directory => fs::create_dir(&path),
+                }
+                .map_err(io_error)
+            },
+            Err(e) => Err(io_error(e)),
+        }
+    }
+
+    /// <https://wicg.github.io/file-system-access/#dom-filesystemdirectoryhandle-removeentry>
+    pub fn remove_entry(
+        &self,
+        locator: &FileSystemLocator,
+        recursive: bool,
+    ) -> FileManagerResult<()> {
+        match *locator {
+            FileSystemLocator::Private(_, ref names) if !names.is_empty() => {},
+            _ => return Err(access_error(FileSystemAccessError::InvalidModification)),
+        }
+        let path = self.path(locator)?;
+        let in_use = self
+            .sync_access_files
+            .lock()
+            .unwrap()
+            .values()
+            .any(|&(ref open_path, _)| open_path.starts_with(&path));
+        if in_use {
+            return Err(access_error(FileSystemAccessError::NoModificationAllowed));
+        }
+
+        let metadata = fs::metadata(&path).map_err(io_error)?;
+        if !metadata.is_dir() {
+            return fs::remove_file(&path).map_err(io_error);
+        }
+        if recursive {
+            return fs::remove_dir_all(&path).map_err(io_error);
+        }
+        let is_empty = fs::read_dir(&path).map_err(io_error)?.next().is_none();
+        if !is_empty {
+            return Err(access_error(FileSystemAccessError::InvalidModification));
+        }
+        fs::remove_dir(&path).map_err(io_error)
+    }
+
+    /// <https://wicg.github.io/file-system-access/#dom-filesystemfilehandle-createsyncaccesshandle>
+    pub fn open_sync_access(&self, locator: &FileSystemLocator) -> FileManagerResult<Uuid> {
+        if let FileSystemLocator::Picked(_) = *locator {
+            return Err(access_error(FileSystemAccessError::NoModificationAllowed));
+        }
+        let path = self.path(locator)?;
+        if !fs::metadata(&path).map_err(io_error)?.is_file() {
+            return Err(access_error(FileSystemAccessError::TypeMismatch));
+        }
+
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(&path)
+            .map_err(io_error)?;
+        let mut files = self.sync_access_files.lock().unwrap();
+        if files.values().any(|&(ref open_path, _)| *open_path == path) {
+            return Err(access_error(FileSystemAccessError::NoModificationAllowed));
+        }
+        let id = Uuid::new_v4();
+        files.insert(id, (path, Arc::new(Mutex::new(file))));
+        Ok(id)
+    }
+
+    /// <https://wicg.github.io/file-system-access/#filesystemsyncaccesshandle>
+    pub fn sync_access(
+        &self,
+        id: &Uuid,
+        operation: SyncAccessOperation,
+    ) -> FileManagerResult<SyncAccessReply> {
+        if let SyncAccessOperation::Close = operation {
+            self.sync_access_files.lock().unwrap().remove(id);
+            return Ok(SyncAccessReply::Done);
+        }
+
+        // Only the file itself stays locked while it is operated on.
+        let file = match self.sync_access_files.lock().unwrap().get(id) {
+            Some(&(_, ref file)) => file.clone(),
+            None => return Err(access_error(FileSystemAccessError::NotFound)),
+        };
+        let mut file = file.lock().unwrap();
+        let file = &mut *file;
+
+        match operation {
+            SyncAccessOperation::Read(offset, length) => {
+                let mut bytes = vec![];
+                file.seek(SeekFrom::Start(offset)).map_err(io_error)?;
+                file.take(length)
+                    .read_to_end(&mut bytes)
+                    .map_err(io_error)?;
+                Ok(SyncAccessReply::Read(bytes))
+            },
+            SyncAccessOperation::Write(offset, bytes) => {
+                file.seek(SeekFrom::Start(offset)).map_err(io_error)?;
+                file.write_all(&bytes).map_err(io_error)?;
+                Ok(SyncAccessReply::Written(bytes.len() as u64))
+            },
+            SyncAccessOperation::Truncate(size) => {
+                file.set_len(size).map_err(io_error)?;
+                Ok(SyncAccessReply::Done)
+            },
+            SyncAccessOperation::GetSize => {
+                let metadata = file.metadata().map_err(io_error)?;
+                Ok(SyncAccessReply::Size(metadata.len()))
+            },
+            SyncAccessOperation::Flush => {
+                file.sync_data().map_err(io_error)?;
+                Ok(SyncAccessReply::Done)
+            },
+            SyncAccessOperation::Close => unreachable!(),
+        }
+    }
+}
+
+/// Creates a directory that only the current user can access, or makes it so if it exists.
+fn create_private_dir(path: &Path) -> io::Result<()> {
+    let mut builder = fs::DirBuilder::new();
+    builder.recursive(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::{DirBuilderExt, PermissionsExt};
+        builder.mode(0o700);
+        builder.create(path)?;
+        fs::set_permissions(path, fs::Permissions::from_mode(0o700))
+    }
+    #[cfg(not(unix))]
+    builder.create(path)
+}
+
+/// <https://wicg.github.io/file-system-access/#valid-file-name>
+///
+/// Names that the platform would not take as the name of a single entry are not valid either:
+/// a `:` makes a drive or a stream on Windows, which has more characters and names of its own
+/// that it does not allow.
+pub fn is_valid_name(name: &str) -> bool {
+    !name.is_empty() &&
+        name != "." &&
+        name != ".." &&
+        !name.contains(|c| c == '/' || c == '\\' || c == '\0' || c == ':') &&
+        is_valid_platform_name(name)
+}
+
+#[cfg(windows)]
+fn is_valid_platform_name(name: &str) -> bool {
+    const RESERVED_NAMES: &[&str] = &[
+        "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8",
+        "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+    ];
+    let stem = name.split('.').next().unwrap_or(name).trim_end();
+    !name.contains(|c: char| c < ' ' || "<>\"|?*".contains(c)) &&
+        !name.ends_with(|c| c == '.' || c == ' ') &&
+        !RESERVED_NAMES
+            .iter()
+            .any(|reserved| stem.eq_ignore_ascii_case(reserved))
+}
+
+#[cfg(not(windows))]
+fn is_valid_platform_name(_: &str) -> bool {
+    true
+}
+
+fn access_error(error: FileSystemAccessError) -> FileManagerThreadError {
+    FileManagerThreadError::FileSystemAccessError(error)
+}
+
+fn io_error(error: io::Error) -> FileManagerThreadError {
+    match error.kind() {
+        io::ErrorKind::NotFound => access_error(FileSystemAccessError::NotFound),
+        _ => FileManagerThreadError::FileSystemError(error.to_string()),
+    }
+}
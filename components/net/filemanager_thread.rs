@@ -3,6 +3,7 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use crate::fetch::methods::{CancellationListener, Data, RangeRequestBounds};
+use crate::file_system::FileSystemStore;
 use crate::resource_thread::CoreResourceThreadPool;
 use crossbeam_channel::Sender;
 use embedder_traits::{EmbedderMsg, EmbedderProxy, FilterPattern};
@@ -10,24 +11,25 @@ use headers::{ContentLength, ContentType, HeaderMap, HeaderMapExt};
 use http::header::{self, HeaderValue};
 use ipc_channel::ipc::{self, IpcSender};
 use mime::{self, Mime};
-use net_traits::blob_url_store::{BlobBuf, BlobURLStoreError};
+use net_traits::blob_url_store::{BlobBuf, BlobURLStoreError, MAX_MEMORY_BLOB_SIZE};
 use net_traits::filemanager_thread::{
     FileManagerResult, FileManagerThreadMsg, FileOrigin, FileTokenCheck,
 };
 use net_traits::filemanager_thread::{
-    FileManagerThreadError, ReadFileProgress, RelativePos, SelectedFile,
+    FileManagerThreadError, FileSystemEntryKind, ReadFileProgress, RelativePos, SelectedFile,
 };
 use net_traits::http_percent_encode;
 use net_traits::response::{Response, ResponseBody};
 use servo_arc::Arc as ServoArc;
 use std::collections::{HashMap, HashSet};
 use std::fs::File;
-use std::io::{BufRead, BufReader, Read, Seek, SeekFrom};
+use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom, Write};
 use std::mem;
 use std::ops::Index;
 use std::path::{Path, PathBuf};
 use std::sync::atomic::{self, AtomicBool, AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex, RwLock, Weak};
+use tempfile::{NamedTempFile, TempDir, TempPath};
 use url::Url;
 use uuid::Uuid;
 
@@ -61,6 +63,14 @@ struct FileMetaData {
     size: u64,
 }
 
+/// A memory-based blob too large to be kept in memory, written to a temporary file that is
+/// removed along with the last reference to it.
+struct SpilledBlob {
+    path: TempPath,
+    type_string: String,
+    size: u64,
+}
+
 /// File backend implementation
 #[derive(Clone)]
 enum FileImpl {
@@ -68,6 +78,8 @@ enum FileImpl {
     MetaDataOnly(FileMetaData),
     /// In-memory Blob buffer object
     Memory(BlobBuf),
+    /// Memory-based Blob moved to a temporary file
+    Spilled(Arc<SpilledBlob>),
     /// A reference to parent entry in `FileManagerStore`,
     /// representing a sliced version of the parent entry data
     Sliced(Uuid, RelativePos),
@@ -77,6 +89,7 @@ enum FileImpl {
 pub struct FileManager {
     embedder_proxy: EmbedderProxy,
     store: Arc<FileManagerStore>,
+    file_systems: Arc<FileSystemStore>,
     thread_pool: Weak<CoreResourceThreadPool>,
 }
 
@@ -84,10 +97,29 @@ impl FileManager {
     pub fn new(
         embedder_proxy: EmbedderProxy,
         pool_handle: Weak<CoreResourceThreadPool>,
+    ) -> FileManager {
+        FileManager::with_file_systems(embedder_proxy, pool_handle, FileSystemStore::new(None))
+    }
+
+    /// A file manager that keeps the origin private file systems in the given directory.
+    pub fn with_file_system_root(
+        embedder_proxy: EmbedderProxy,
+        pool_handle: Weak<CoreResourceThreadPool>,
+        file_system_root: PathBuf,
+    ) -> FileManager {
+        let file_systems = FileSystemStore::new(Some(file_system_root));
+        FileManager::with_file_systems(embedder_proxy, pool_handle, file_systems)
+    }
+
+    fn with_file_systems(
+        embedder_proxy: EmbedderProxy,
+        pool_handle: Weak<CoreResourceThreadPool>,
+        file_systems: FileSystemStore,
     ) -> FileManager {
         FileManager {
             embedder_proxy: embedder_proxy,
             store: Arc::new(FileManagerStore::new()),
+            file_systems: Arc::new(file_systems),
             thread_pool: pool_handle,
         }
     }
@@ -147,7 +179,35 @@ impl FileManager {
     }
 
     pub fn promote_memory(&self, id: Uuid, blob_buf: BlobBuf, set_valid: bool, origin: FileOrigin) {
+        let is_large = blob_buf.size > MAX_MEMORY_BLOB_SIZE;
         self.store.promote_memory(id, blob_buf, set_valid, origin);
+        if is_large {
+            self.spill_to_disk(id);
+        }
+    }
+
+    /// Moves the bytes of a large memory-based blob to a temporary file in the background, so
+    /// that they are read from disk when needed instead of being kept in memory. Until then,
+    /// the blob is read from memory as usual.
+    fn spill_to_disk(&self, id: Uuid) {
+        let store = self.store.clone();
+        self.thread_pool
+            .upgrade()
+            .and_then(|pool| {
+                pool.spawn(move || store.spill_to_disk(&id));
+                Some(())
+            })
+            .unwrap_or_else(|| {
+                warn!("FileManager tried to spill a blob after CoreResourceManager has exited.");
+            });
+    }
+
+    /// The temporary file that a memory-based blob was moved to, if it was.
+    pub fn spilled_blob_path(&self, id: &Uuid) -> Option<PathBuf> {
+        match self.store.entries.read().unwrap().get(id)?.file_impl {
+            FileImpl::Spilled(ref spilled) => Some(spilled.path.to_path_buf()),
+            _ => None,
+        }
     }
 
     /// Message handler
@@ -205,9 +265,85 @@ impl FileManager {
             FileManagerThreadMsg::ActivateBlobURL(id, sender, origin) => {
                 let _ = sender.send(self.store.set_blob_url_validity(true, &id, &origin));
             },
+            FileManagerThreadMsg::PickFiles(filter, multiple, sender, opt_test_paths) => {
+                let store = self.store.clone();
+                let embedder = self.embedder_proxy.clone();
+                self.spawn_file_system_task(move |file_systems| {
+                    let paths = if pref!(dom.testing.html_input_element.select_files.enabled) {
+                        opt_test_paths
+                    } else {
+                        store.query_files_from_embedder(filter, multiple, embedder)
+                    };
+                    let paths = match paths {
+                        Some(paths) => paths,
+                        None => {
+                            let _ = sender.send(Err(FileManagerThreadError::UserCancelled));
+                            return;
+                        },
+                    };
+                    let files = paths
+                        .into_iter()
+                        .map(PathBuf::from)
+                        .filter_map(|path| {
+                            let name = path.file_name()?.to_str()?.to_owned();
+                            Some((file_systems.add_picked_file(path), name))
+                        })
+                        .collect();
+                    let _ = sender.send(Ok(files));
+                });
+            },
+            FileManagerThreadMsg::ForgetPickedFile(id) => {
+                self.file_systems.forget_picked_file(&id);
+            },
+            FileManagerThreadMsg::GetFileSystemEntry(locator, kind, create, sender) => {
+                self.spawn_file_system_task(move |file_systems| {
+                    let _ = sender.send(file_systems.get_entry(&locator, kind, create));
+                });
+            },
+            FileManagerThreadMsg::RemoveFileSystemEntry(locator, recursive, sender) => {
+                self.spawn_file_system_task(move |file_systems| {
+                    let _ = sender.send(file_systems.remove_entry(&locator, recursive));
+                });
+            },
+            FileManagerThreadMsg::GetFileSystemFile(locator, origin, sender) => {
+                let store = self.store.clone();
+                self.spawn_file_system_task(move |file_systems| {
+                    let result = file_systems
+                        .get_entry(&locator, FileSystemEntryKind::File, false)
+                        .and_then(|_| file_systems.path(&locator))
+                        .and_then(|path| store.create_entry(&path, &origin));
+                    let _ = sender.send(result);
+                });
+            },
+            FileManagerThreadMsg::OpenSyncAccessHandle(locator, sender) => {
+                self.spawn_file_system_task(move |file_systems| {
+                    let _ = sender.send(file_systems.open_sync_access(&locator));
+                });
+            },
+            FileManagerThreadMsg::SyncAccess(id, operation, sender) => {
+                self.spawn_file_system_task(move |file_systems| {
+                    let _ = sender.send(file_systems.sync_access(&id, operation));
+                });
+            },
         }
     }
 
+    fn spawn_file_system_task<F>(&self, task: F)
+    where
+        F: FnOnce(&FileSystemStore) + Send + 'static,
+    {
+        let file_systems = self.file_systems.clone();
+        self.thread_pool
+            .upgrade()
+            .and_then(|pool| {
+                pool.spawn(move || task(&file_systems));
+                Some(())
+            })
+            .unwrap_or_else(|| {
+                warn!("FileManager tried to access a file system after CoreResourceManager has exited.");
+            });
+    }
+
     pub fn fetch_file_in_chunks(
         &self,
         done_sender: Sender<Data>,
@@ -367,6 +503,40 @@ impl FileManager {
 
                 Ok(())
             },
+            FileImpl::Spilled(spilled) => {
+                let range = match range.get_final(Some(spilled.size)) {
+                    Ok(range) => range,
+                    Err(_) => {
+                        return Err(BlobURLStoreError::InvalidRange);
+                    },
+                };
+
+                let file = File::open(&spilled.path)
+                    .map_err(|e| BlobURLStoreError::External(e.to_string()))?;
+                let mut reader = BufReader::with_capacity(FILE_CHUNK_SIZE, file);
+                if reader.seek(SeekFrom::Start(range.start as u64)).is_err() {
+                    return Err(BlobURLStoreError::External(
+                        "Unexpected method for blob".into(),
+                    ));
+                }
+
+                set_headers(
+                    &mut response.headers,
+                    range.to_abs_range(spilled.size as usize).len() as u64,
+                    spilled.type_string.parse().unwrap_or(mime::TEXT_PLAIN),
+                    /* filename */ None,
+                );
+
+                self.fetch_file_in_chunks(
+                    done_sender.clone(),
+                    reader,
+                    response.body.clone(),
+                    cancellation_listener,
+                    range,
+                );
+
+                Ok(())
+            },
             FileImpl::Sliced(parent_id, inner_rel_pos) => {
                 // Next time we don't need to check validity since
                 // we have already done that for requesting URL if necessary.
@@ -391,12 +561,16 @@ impl FileManager {
 /// Access to the content is encapsulated as methods of this struct.
 struct FileManagerStore {
     entries: RwLock<HashMap<Uuid, FileStoreEntry>>,
+    /// The private directory that spilled blobs are written to, created when the first blob
+    /// is spilled and removed along with the store.
+    spill_dir: Mutex<Option<TempDir>>,
 }
 
 impl FileManagerStore {
     fn new() -> Self {
         FileManagerStore {
             entries: RwLock::new(HashMap::new()),
+            spill_dir: Mutex::new(None),
         }
     }
 
@@ -744,12 +918,34 @@ impl FileManagerStore {
                         None => "".to_string(),
                     };
 
-                    read_file_in_chunks(sender, &mut file, range.len(), opt_filename, type_string);
+                    read_file_in_chunks(
+                        sender,
+                        &mut file.take(range.len() as u64),
+                        range.len(),
+                        opt_filename,
+                        type_string,
+                    );
                     Ok(())
                 } else {
                     Err(BlobURLStoreError::InvalidEntry)
                 }
             },
+            FileImpl::Spilled(spilled) => {
+                let range = rel_pos.to_abs_range(spilled.size as usize);
+                let mut file = File::open(&spilled.path)
+                    .map_err(|e| BlobURLStoreError::External(e.to_string()))?;
+                file.seek(SeekFrom::Start(range.start as u64))
+                    .map_err(|e| BlobURLStoreError::External(e.to_string()))?;
+
+                read_file_in_chunks(
+                    sender,
+                    &mut file.take(range.len() as u64),
+                    range.len(),
+                    None,
+                    spilled.type_string.clone(),
+                );
+                Ok(())
+            },
             FileImpl::Sliced(parent_id, inner_rel_pos) => {
                 // Next time we don't need to check validity since
                 // we have already done that for requesting URL if necessary
@@ -846,6 +1042,52 @@ impl FileManagerStore {
         }
     }
 
+    /// Writes the bytes of a memory-based blob to a temporary file, and reads them from there
+    /// from now on.
+    fn spill_to_disk(&self, id: &Uuid) {
+        let blob_buf = match self.entries.read().unwrap().get(id) {
+            Some(entry) => match entry.file_impl {
+                FileImpl::Memory(ref buf) => buf.clone(),
+                _ => return,
+            },
+            None => return,
+        };
+
+        let written = self.spill_dir().and_then(|dir| {
+            let mut file = NamedTempFile::new_in(dir)?;
+            file.write_all(&blob_buf.bytes)?;
+            Ok(file.into_temp_path())
+        });
+        let spilled = match written {
+            Ok(path) => Arc::new(SpilledBlob {
+                path: path,
+                type_string: blob_buf.type_string,
+                size: blob_buf.size,
+            }),
+            Err(e) => {
+                warn!("Failed to spill a blob to disk ({}).", e);
+                return;
+            },
+        };
+
+        // The blob might have been released in the meantime, in which case the file is
+        // removed along with `spilled`.
+        if let Some(entry) = self.entries.write().unwrap().get_mut(id) {
+            if let FileImpl::Memory(_) = entry.file_impl {
+                entry.file_impl = FileImpl::Spilled(spilled);
+            }
+        }
+    }
+
+    /// The directory that spilled blobs are written to, which only the current user can access.
+    fn spill_dir(&self) -> io::Result<PathBuf> {
+        let mut spill_dir = self.spill_dir.lock().unwrap();
+        if spill_dir.is_none() {
+            *spill_dir = Some(tempfile::Builder::new().prefix("servo-blobs-").tempdir()?);
+        }
+        Ok(spill_dir.as_ref().unwrap().path().to_path_buf())
+    }
+
     fn set_blob_url_validity(
         &self,
         validity: bool,
@@ -898,7 +1140,7 @@ impl FileManagerStore {
 
 fn read_file_in_chunks(
     sender: &IpcSender<FileManagerResult<ReadFileProgress>>,
-    file: &mut File,
+    file: &mut impl Read,
     size: usize,
     opt_filename: Option<String>,
     type_string: String,
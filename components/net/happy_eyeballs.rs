@@ -0,0 +1,102 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Races the connections to the addresses of a host, so that a host whose IPv6 addresses are
+//! unreachable loads as fast as over IPv4, instead of once the IPv6 connections timed out.
+//! <https://tools.ietf.org/html/rfc8305>
+
+use futures::{Async, Future, Poll};
+use std::collections::VecDeque;
+use std::io::{self, ErrorKind};
+use std::net::{IpAddr, SocketAddr};
+use std::time::{Duration, Instant};
+use tokio::net::tcp::ConnectFuture;
+use tokio::net::TcpStream;
+use tokio::timer::Delay;
+
+/// How long a connection attempt is waited for before the next one starts alongside it.
+/// <https://tools.ietf.org/html/rfc8305#section-5>
+pub const CONNECTION_ATTEMPT_DELAY: Duration = Duration::from_millis(250);
+
+/// Orders the addresses of a host for the connection attempts, alternating between the address
+/// families, starting with IPv6, and otherwise keeping the order of the resolver.
+/// <https://tools.ietf.org/html/rfc8305#section-4>
+pub fn sort_addresses(addresses: Vec<IpAddr>) -> Vec<IpAddr> {
+    let (mut ipv6, mut ipv4): (VecDeque<_>, VecDeque<_>) =
+        addresses.into_iter().partition(|address| address.is_ipv6());
+    let mut sorted = Vec::with_capacity(ipv6.len() + ipv4.len());
+    loop {
+        match (ipv6.pop_front(), ipv4.pop_front()) {
+            (None, None) => return sorted,
+            (first, second) => sorted.extend(first.into_iter().chain(second)),
+        }
+    }
+}
+
+/// Connects to the first of the addresses that accepts a connection. The attempt to connect to
+/// an address starts when the attempt to connect to the previous one failed, or after the
+/// connection attempt delay if it did not complete by then, whichever comes first.
+pub fn connect_to_first(addresses: Vec<SocketAddr>) -> RacingConnect {
+    RacingConnect {
+        remaining: addresses.into(),
+        attempts: vec![],
+        delay: None,
+        last_error: None,
+    }
+}
+
+pub struct RacingConnect {
+    /// The addresses that no attempt was started for yet.
+    remaining: VecDeque<SocketAddr>,
+    /// The attempts that neither succeeded nor failed yet.
+    attempts: Vec<ConnectFuture>,
+    /// The delay after which the next attempt starts.
+    delay: Option<Delay>,
+    last_error: Option<io::Error>,
+}
+
+impl Future for RacingConnect {
+    type Item = TcpStream;
+    type Error = io::Error;
+
+    fn poll(&mut self) -> Poll<TcpStream, io::Error> {
+        loop {
+            let mut failed = false;
+            let mut index = 0;
+            while index < self.attempts.len() {
+                match self.attempts[index].poll() {
+                    Ok(Async::Ready(stream)) => return Ok(Async::Ready(stream)),
+                    Ok(Async::NotReady) => index += 1,
+                    Err(error) => {
+                        self.attempts.swap_remove(index);
+                        self.last_error = Some(error);
+                        failed = true;
+                    },
+                }
+            }
+
+            if self.remaining.is_empty() {
+                if !self.attempts.is_empty() {
+                    return Ok(Async::NotReady);
+                }
+                return Err(self.last_error.take().unwrap_or_else(|| {
+                    io::Error::new(ErrorKind::NotFound, "the host has no address")
+                }));
+            }
+
+            // A timer that is shut down can't delay the next attempt.
+            let delay_elapsed = match self.delay {
+                Some(ref mut delay) => !matches!(delay.poll(), Ok(Async::NotReady)),
+                None => true,
+            };
+            if !failed && !delay_elapsed && !self.attempts.is_empty() {
+                return Ok(Async::NotReady);
+            }
+            if let Some(address) = self.remaining.pop_front() {
+                self.attempts.push(TcpStream::connect(&address));
+                self.delay = Some(Delay::new(Instant::now() + CONNECTION_ATTEMPT_DELAY));
+            }
+        }
+    }
+}
@@ -41,13 +41,19 @@ impl HstsEntry {
     pub fn is_expired(&self) -> bool {
         match (self.max_age, self.timestamp) {
             (Some(max_age), Some(timestamp)) => {
-                (time::get_time().sec as u64) - timestamp >= max_age
+                (time::get_time().sec as u64).saturating_sub(timestamp) >= max_age
             },
 
             _ => false,
         }
     }
 
+    /// Entries of the preload list have no max-age, unlike the ones that were learned from the
+    /// `Strict-Transport-Security` headers of responses.
+    pub fn is_preloaded(&self) -> bool {
+        self.max_age.is_none()
+    }
+
     fn matches_domain(&self, host: &str) -> bool {
         !self.is_expired() && self.host == host
     }
@@ -94,6 +100,36 @@ impl HstsList {
         HstsList::from_preload(&list).expect("Servo HSTS preload file is invalid")
     }
 
+    /// Returns the entries that were learned from responses and have not expired yet, which
+    /// are the ones that are persisted across sessions.
+    pub fn learned_entries(&self) -> HstsList {
+        let mut entries_map = HashMap::new();
+        for (base_domain, entries) in &self.entries_map {
+            let learned: Vec<HstsEntry> = entries
+                .iter()
+                .filter(|e| !e.is_preloaded() && !e.is_expired())
+                .cloned()
+                .collect();
+            if !learned.is_empty() {
+                entries_map.insert(base_domain.clone(), learned);
+            }
+        }
+        HstsList { entries_map }
+    }
+
+    /// Adds the learned entries of a persisted list that have not expired yet. Lists that were
+    /// persisted with the preload list are accepted, but their preloaded entries are ignored,
+    /// so that the ones that shipped since are used.
+    pub fn merge_learned_entries(&mut self, persisted: HstsList) {
+        for (_, entries) in persisted.entries_map {
+            for entry in entries {
+                if !entry.is_preloaded() && !entry.is_expired() {
+                    self.push(entry);
+                }
+            }
+        }
+    }
+
     pub fn is_host_secure(&self, host: &str) -> bool {
         let base_domain = reg_suffix(host);
         self.entries_map.get(base_domain).map_or(false, |entries| {
@@ -129,13 +165,25 @@ impl HstsList {
             .entries_map
             .entry(base_domain.to_owned())
             .or_insert(vec![]);
+        // A max-age of zero tells to forget the host.
+        // https://tools.ietf.org/html/rfc6797#section-6.1.1
+        let forget = entry.max_age == Some(0);
         if !have_domain && !have_subdomain {
-            entries.push(entry);
+            if !forget {
+                entries.push(entry);
+            }
         } else if !have_subdomain {
-            for e in entries {
-                if e.matches_domain(&entry.host) {
+            // Headers do not override the preload list, which outlives their max-age.
+            let overrides = |e: &HstsEntry| {
+                e.matches_domain(&entry.host) && (entry.is_preloaded() || !e.is_preloaded())
+            };
+            if forget {
+                entries.retain(|e| !overrides(e));
+            } else {
+                for e in entries.iter_mut().filter(|e| overrides(&**e)) {
                     e.include_subdomains = entry.include_subdomains;
                     e.max_age = entry.max_age;
+                    e.timestamp = entry.timestamp;
                 }
             }
         }
@@ -590,6 +590,11 @@ impl HttpCache {
         }
     }
 
+    /// Drop every cached response, to free memory.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
     /// Constructing Responses from Caches.
     /// <https://tools.ietf.org/html/rfc7234#section-4>
     pub fn construct_response(
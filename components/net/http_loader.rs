@@ -2,20 +2,29 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
-use crate::connector::{create_http_client, Connector, TlsConfig};
+use crate::client_certificates::ClientCertificates;
+use crate::connector::{create_http_client, CertificateExceptions, Connector, TlsConfig};
 use crate::cookie;
 use crate::cookie_storage::CookieStorage;
 use crate::decoder::Decoder;
+use crate::doh::DnsResolver;
 use crate::fetch::cors_cache::CorsCache;
 use crate::fetch::methods::{main_fetch, Data, DoneChannel, FetchContext, Target};
 use crate::hsts::HstsList;
 use crate::http_cache::{CacheKey, HttpCache};
+use crate::link_headers::process_link_headers;
+use crate::preload_cache::PreloadCache;
+use crate::protocol_handlers::ProtocolHandlerList;
+use crate::request_interceptor::RequestInterceptor;
+use crate::request_scheduler::RequestScheduler;
 use crate::resource_thread::AuthCache;
 use crossbeam_channel::{unbounded, Sender};
 use devtools_traits::{
     ChromeToDevtoolsControlMsg, DevtoolsControlMsg, HttpRequest as DevtoolsHttpRequest,
 };
 use devtools_traits::{HttpResponse as DevtoolsHttpResponse, NetworkEvent};
+use embedder_traits::ProtocolRegistry;
+use futures::sync::oneshot;
 use headers::authorization::Basic;
 use headers::{AccessControlAllowCredentials, AccessControlAllowHeaders, HeaderMapExt};
 use headers::{
@@ -26,10 +35,10 @@ use headers::{AccessControlAllowOrigin, AccessControlMaxAge};
 use headers::{CacheControl, ContentEncoding, ContentLength};
 use headers::{IfModifiedSince, LastModified, Origin as HyperOrigin, Pragma, Referer, UserAgent};
 use http::header::{self, HeaderName, HeaderValue};
-use http::{HeaderMap, Request as HyperRequest};
+use http::{HeaderMap, Request as HyperRequest, Version};
 use hyper::{Body, Client, Method, Response as HyperResponse, StatusCode};
 use hyper_serde::Serde;
-use msg::constellation_msg::{HistoryStateId, PipelineId};
+use msg::constellation_msg::{HistoryStateId, NetworkConditions, PipelineId};
 use net_traits::quality::{quality_to_value, Quality, QualityItem};
 use net_traits::request::Origin::Origin as SpecificOrigin;
 use net_traits::request::{is_cors_safelisted_method, is_cors_safelisted_request_header};
@@ -49,15 +58,21 @@ use std::mem;
 use std::ops::Deref;
 use std::str::FromStr;
 use std::sync::{Condvar, Mutex, RwLock};
-use std::time::{Duration, SystemTime};
+use std::time::{Duration, Instant, SystemTime};
 use time::{self, Tm};
 use tokio::prelude::{future, Future, Stream};
 use tokio::runtime::Runtime;
+use tokio::timer::Delay;
 
 lazy_static! {
     pub static ref HANDLE: Mutex<Runtime> = { Mutex::new(Runtime::new().unwrap()) };
 }
 
+/// The most bytes that the bodies of the keepalive requests of a client can add up to while
+/// they are in flight.
+/// <https://fetch.spec.whatwg.org/#http-network-or-cache-fetch>
+const KEEPALIVE_QUOTA: u64 = 64 * 1024;
+
 /// The various states an entry of the HttpCache can be in.
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum HttpCacheEntryState {
@@ -79,19 +94,98 @@ pub struct HttpState {
     pub http_cache_state: Mutex<HashMap<CacheKey, Arc<(Mutex<HttpCacheEntryState>, Condvar)>>>,
     pub auth_cache: RwLock<AuthCache>,
     pub history_states: RwLock<HashMap<HistoryStateId, Vec<u8>>>,
+    /// The bytes of the bodies of the keepalive requests in flight, by client.
+    pub keepalive_bytes: Mutex<HashMap<PipelineId, u64>>,
+    /// The conditions that the network is made to behave like for the requests of a client.
+    pub network_conditions: RwLock<HashMap<PipelineId, NetworkConditions>>,
+    pub request_scheduler: RequestScheduler,
+    pub preload_cache: PreloadCache,
+    pub certificate_exceptions: CertificateExceptions,
+    pub client_certificates: ClientCertificates,
+    pub request_interceptor: RequestInterceptor,
+    /// The handlers of the custom schemes that the embedder registered.
+    pub protocols: ProtocolRegistry,
+    pub protocol_handlers: RwLock<ProtocolHandlerList>,
+    /// The resolver that the client connects with, which the hosts of the connections that
+    /// are hinted at are resolved with ahead of time too.
+    pub resolver: DnsResolver,
     pub client: Client<Connector, Body>,
 }
 
 impl HttpState {
     pub fn new(tls_config: TlsConfig) -> HttpState {
+        let certificate_exceptions = CertificateExceptions::new();
+        let client_certificates = ClientCertificates::new();
+        let resolver = DnsResolver::new();
         HttpState {
             hsts_list: RwLock::new(HstsList::new()),
             cookie_jar: RwLock::new(CookieStorage::new(150)),
             auth_cache: RwLock::new(AuthCache::new()),
             history_states: RwLock::new(HashMap::new()),
+            keepalive_bytes: Mutex::new(HashMap::new()),
+            network_conditions: RwLock::new(HashMap::new()),
+            request_scheduler: RequestScheduler::new(),
+            preload_cache: PreloadCache::new(),
             http_cache: RwLock::new(HttpCache::new()),
             http_cache_state: Mutex::new(HashMap::new()),
-            client: create_http_client(tls_config, HANDLE.lock().unwrap().executor()),
+            certificate_exceptions: certificate_exceptions.clone(),
+            client_certificates: client_certificates.clone(),
+            request_interceptor: RequestInterceptor::new(),
+            protocols: ProtocolRegistry::new(),
+            protocol_handlers: RwLock::new(ProtocolHandlerList::new()),
+            resolver: resolver.clone(),
+            client: create_http_client(
+                tls_config,
+                certificate_exceptions,
+                client_certificates,
+                resolver,
+                HANDLE.lock().unwrap().executor(),
+            ),
+        }
+    }
+
+    fn network_conditions_for(&self, request: &Request) -> Option<NetworkConditions> {
+        let pipeline_id = request.pipeline_id?;
+        self.network_conditions
+            .read()
+            .unwrap()
+            .get(&pipeline_id)
+            .cloned()
+    }
+}
+
+/// The bytes that a keepalive request counts against the quota of its client, until it is
+/// dropped once the request was sent.
+struct KeepaliveReservation<'a> {
+    state: &'a HttpState,
+    pipeline_id: PipelineId,
+    bytes: u64,
+}
+
+impl<'a> KeepaliveReservation<'a> {
+    fn new(state: &'a HttpState, pipeline_id: PipelineId, bytes: u64) -> Option<Self> {
+        let mut keepalive_bytes = state.keepalive_bytes.lock().unwrap();
+        let in_flight = keepalive_bytes.entry(pipeline_id).or_insert(0);
+        if in_flight.saturating_add(bytes) > KEEPALIVE_QUOTA {
+            return None;
+        }
+        *in_flight += bytes;
+        Some(KeepaliveReservation {
+            state,
+            pipeline_id,
+            bytes,
+        })
+    }
+}
+
+impl<'a> Drop for KeepaliveReservation<'a> {
+    fn drop(&mut self) {
+        let mut keepalive_bytes = self.state.keepalive_bytes.lock().unwrap();
+        if let Some(in_flight) = keepalive_bytes.get_mut(&self.pipeline_id) {
+            *in_flight -= self.bytes;
+            if *in_flight == 0 {
+                keepalive_bytes.remove(&self.pipeline_id);
+            }
         }
     }
 }
@@ -100,6 +194,25 @@ fn precise_time_ms() -> u64 {
     time::precise_time_ns() / (1000 * 1000)
 }
 
+/// Approximates the size of the header section of a response, as the client doesn't expose
+/// the bytes it received.
+fn header_section_size(headers: &HeaderMap) -> u64 {
+    headers
+        .iter()
+        .map(|(name, value)| (name.as_str().len() + value.len() + 4) as u64)
+        .sum()
+}
+
+/// <https://www.iana.org/assignments/tls-extensiontype-values/tls-extensiontype-values.xhtml#alpn-protocol-ids>
+fn alpn_protocol_id(version: Version) -> &'static str {
+    match version {
+        Version::HTTP_09 => "http/0.9",
+        Version::HTTP_10 => "http/1.0",
+        Version::HTTP_2 => "h2",
+        _ => "http/1.1",
+    }
+}
+
 // Step 3 of https://fetch.spec.whatwg.org/#concept-fetch.
 pub fn set_default_accept(destination: Destination, headers: &mut HeaderMap) {
     if headers.contains_key(header::ACCEPT) {
@@ -400,9 +513,17 @@ fn obtain_response(
         .unwrap()
         .set_attribute(ResourceAttribute::DomainLookupStart);
 
+    // The host name is resolved by the connector of the client when it opens a connection,
+    // so the lookup can't be timed on its own and ends when the connection starts.
+    context
+        .timing
+        .lock()
+        .unwrap()
+        .set_attribute(ResourceAttribute::DomainLookupEnd);
+
     // TODO(#21261) connect_start: set if a persistent connection is *not* used and the last non-redirected
     // fetch passes the timing allow check
-    let connect_start = precise_time_ms();
+    let connect_start = time::precise_time_ns();
     context
         .timing
         .lock()
@@ -439,7 +560,7 @@ fn obtain_response(
     };
     *request.headers_mut() = headers.clone();
 
-    let connect_end = precise_time_ms();
+    let connect_end = time::precise_time_ns();
     context
         .timing
         .lock()
@@ -451,6 +572,14 @@ fn obtain_response(
     let closure_url = url.clone();
     let method = method.clone();
     let send_start = precise_time_ms();
+    let timing = context.timing.clone();
+    let error_url = url.clone();
+    // The connections for the request record why their certificate failed to verify, if it
+    // did, for as long as the request waits for its response.
+    let certificate_error_watch = context
+        .state
+        .certificate_exceptions
+        .watch(url.host_str().unwrap_or(""));
 
     Box::new(
         client
@@ -458,7 +587,15 @@ fn obtain_response(
             .and_then(move |res| {
                 let send_end = precise_time_ms();
 
-                // TODO(#21271) response_start: immediately after receiving first byte of response
+                // The response headers have been received by now, which is as close to the
+                // first byte of the response as the client lets us get.
+                {
+                    let mut timing = timing.lock().unwrap();
+                    timing.set_attribute(ResourceAttribute::ResponseStart);
+                    timing.set_attribute(ResourceAttribute::NextHopProtocol(
+                        alpn_protocol_id(res.version()).to_owned(),
+                    ));
+                }
 
                 let msg = if let Some(request_id) = request_id {
                     if let Some(pipeline_id) = pipeline_id {
@@ -470,7 +607,7 @@ fn obtain_response(
                             Some(request_body.clone()),
                             pipeline_id,
                             time::now(),
-                            connect_end - connect_start,
+                            (connect_end - connect_start) / (1000 * 1000),
                             send_end - send_start,
                             is_xhr,
                         ))
@@ -488,7 +625,12 @@ fn obtain_response(
                 };
                 Ok((Decoder::detect(res), msg))
             })
-            .map_err(move |e| NetworkError::from_hyper_error(&e)),
+            .map_err(
+                move |e| match certificate_error_watch.take_error(pipeline_id) {
+                    Some(error) => NetworkError::SslValidation(error_url, error),
+                    None => NetworkError::from_hyper_error(&e),
+                },
+            ),
     )
 }
 
@@ -908,12 +1050,21 @@ fn http_network_or_cache_fetch(
     };
 
     // Step 5.7
+    let mut keepalive_reservation = None;
     if let Some(content_length_value) = content_length_value {
         http_request
             .headers
             .typed_insert(ContentLength(content_length_value));
-        if http_request.keep_alive {
-            // Step 5.8 TODO: needs request's client object
+        // Step 5.8
+        // The pipeline of the request stands in for its client.
+        if let (true, Some(pipeline_id)) = (http_request.keep_alive, http_request.pipeline_id) {
+            keepalive_reservation =
+                KeepaliveReservation::new(&context.state, pipeline_id, content_length_value);
+            if keepalive_reservation.is_none() {
+                return Response::network_error(NetworkError::Internal(
+                    "Keepalive request exceeds the quota".into(),
+                ));
+            }
         }
     }
 
@@ -1352,6 +1503,23 @@ impl Drop for ResponseEndTimer {
     }
 }
 
+/// A future that completes after the given time, which the timer of the runtime keeps, to make
+/// the network behave like a slow one.
+fn network_delay(duration: Duration) -> Box<dyn Future<Item = (), Error = NetworkError>> {
+    if duration == Duration::from_secs(0) {
+        return Box::new(future::ok(()));
+    }
+    let (sender, receiver) = oneshot::channel();
+    HANDLE
+        .lock()
+        .unwrap()
+        .spawn(Delay::new(Instant::now() + duration).then(move |_| {
+            let _ = sender.send(());
+            Ok(())
+        }));
+    Box::new(receiver.map_err(|_| NetworkError::Internal("Network delay failed".into())))
+}
+
 /// [HTTP network fetch](https://fetch.spec.whatwg.org/#http-network-fetch)
 fn http_network_fetch(
     request: &Request,
@@ -1392,25 +1560,63 @@ fn http_network_fetch(
     // do not. Once we support other kinds of fetches we'll need to be more fine grained here
     // since things like image fetches are classified differently by devtools
     let is_xhr = request.destination == Destination::None;
-    let response_future = obtain_response(
-        &context.state.client,
-        &url,
-        &request.method,
-        &request.headers,
-        &request.body,
-        &request.method,
-        &request.pipeline_id,
-        request.redirect_count + 1,
-        request_id.as_ref().map(Deref::deref),
-        is_xhr,
-        context,
-    );
-
+    // The connection is used until the whole response was received. The fetch waited for a
+    // connection to the host that it started with before it started; the hosts that it is
+    // redirected to only count its request if they have a connection to spare, since it
+    // can't wait for one here without holding up the thread pool.
+    let connection_slot = context
+        .connection_slot
+        .lock()
+        .unwrap()
+        .take()
+        .filter(|slot| *slot.host() == url.origin())
+        .or_else(|| context.state.request_scheduler.try_acquire(request));
     let pipeline_id = request.pipeline_id;
+
+    // The network may be made to behave like a slow or missing one, to test how pages load.
+    // A slow one delays the request by its latency and the time that the body takes to upload.
+    let network_conditions = context.state.network_conditions_for(request);
+    let request_delay = match network_conditions {
+        Some(conditions) => {
+            if conditions.offline {
+                return Response::network_error(NetworkError::Internal(
+                    "Network is offline".into(),
+                ));
+            }
+            let upload_size = request.body.as_ref().map_or(0, |body| body.len());
+            conditions.latency + conditions.upload_time(upload_size)
+        },
+        None => Duration::from_secs(0),
+    };
     // This will only get the headers, the body is read later
-    let (res, msg) = match response_future.wait() {
-        Ok(wrapped_response) => wrapped_response,
-        Err(error) => return Response::network_error(error),
+    let (res, msg) = loop {
+        let response_future = network_delay(request_delay).and_then(|()| {
+            obtain_response(
+                &context.state.client,
+                &url,
+                &request.method,
+                &request.headers,
+                &request.body,
+                &request.method,
+                &request.pipeline_id,
+                request.redirect_count + 1,
+                request_id.as_ref().map(Deref::deref),
+                is_xhr,
+                context,
+            )
+        });
+        match response_future.wait() {
+            Ok(wrapped_response) => break wrapped_response,
+            // The server may have refused the handshake because it asked for a client
+            // certificate, in which case the connection is tried again once the user chose
+            // an identity for the host.
+            Err(error) => {
+                let host = url.host_str().unwrap_or("");
+                if !context.state.client_certificates.ask_for_identity(host) {
+                    return Response::network_error(error);
+                }
+            },
+        }
     };
 
     if log_enabled!(log::Level::Info) {
@@ -1451,6 +1657,17 @@ fn http_network_fetch(
     let timing = context.timing.lock().unwrap().clone();
     let mut response = Response::new(url.clone(), timing);
 
+    // The body is decoded as it is received, so only the Content-Length header tells the
+    // size of an encoded body.
+    let header_section_size = header_section_size(res.headers());
+    let encoded_body_size = if res.headers().contains_key(header::CONTENT_ENCODING) {
+        res.headers()
+            .typed_get::<ContentLength>()
+            .map(|length| length.0)
+    } else {
+        None
+    };
+
     response.status = Some((
         res.status(),
         res.status().canonical_reason().unwrap_or("").into(),
@@ -1464,6 +1681,13 @@ fn http_network_fetch(
     response.referrer = request.referrer.to_url().cloned();
     response.referrer_policy = request.referrer_policy.clone();
 
+    // Hyper skips the interim responses before the final one, so the hints of a 103 (Early
+    // Hints) response are followed when the final response repeats them, which it does to
+    // support clients that do not understand them, before its body is received.
+    if res.status().is_success() {
+        process_link_headers(request, &response.headers, context);
+    }
+
     let res_body = response.body.clone();
 
     // We're about to spawn a future to be waited on here
@@ -1519,14 +1743,30 @@ fn http_network_fetch(
                 if cancellation_listener.lock().unwrap().cancelled() {
                     *res_body.lock().unwrap() = ResponseBody::Done(vec![]);
                     let _ = done_sender.send(Data::Cancelled);
-                    return future::failed(());
+                    return future::Either::A(future::failed(()));
                 }
-                if let ResponseBody::Receiving(ref mut body) = *res_body.lock().unwrap() {
-                    let bytes = chunk.into_bytes();
-                    body.extend_from_slice(&*bytes);
-                    let _ = done_sender.send(Data::Payload(bytes.to_vec()));
+                let bytes = chunk.into_bytes();
+                // A limited throughput delays each chunk by the time it takes to receive it.
+                let download_time = network_conditions
+                    .map_or(Duration::from_secs(0), |conditions| {
+                        conditions.download_time(bytes.len())
+                    });
+                let done_sender = done_sender.clone();
+                let receive = move |()| -> Result<_, ()> {
+                    if let ResponseBody::Receiving(ref mut body) = *res_body.lock().unwrap() {
+                        body.extend_from_slice(&*bytes);
+                        let _ = done_sender.send(Data::Payload(bytes.to_vec()));
+                    }
+                    Ok(res_body)
+                };
+                if download_time == Duration::from_secs(0) {
+                    return future::Either::A(future::result(receive(())));
                 }
-                future::ok(res_body)
+                future::Either::B(
+                    Delay::new(Instant::now() + download_time)
+                        .map_err(|_| ())
+                        .and_then(receive),
+                )
             })
             .and_then(move |res_body| {
                 debug!("successfully finished response for {:?}", url1);
@@ -1535,11 +1775,16 @@ fn http_network_fetch(
                     ResponseBody::Receiving(ref mut body) => mem::replace(body, vec![]),
                     _ => vec![],
                 };
+                let decoded_body_size = completed_body.len() as u64;
+                let encoded_body_size = encoded_body_size.unwrap_or(decoded_body_size);
                 *body = ResponseBody::Done(completed_body);
-                timing_ptr2
-                    .lock()
-                    .unwrap()
-                    .set_attribute(ResourceAttribute::ResponseEnd);
+                let mut timing = timing_ptr2.lock().unwrap();
+                timing.set_attribute(ResourceAttribute::ResponseEnd);
+                timing.set_attribute(ResourceAttribute::TransferSize(
+                    header_section_size + encoded_body_size,
+                ));
+                timing.set_attribute(ResourceAttribute::EncodedBodySize(encoded_body_size));
+                timing.set_attribute(ResourceAttribute::DecodedBodySize(decoded_body_size));
                 let _ = done_sender2.send(Data::Done);
                 future::ok(())
             })
@@ -1556,6 +1801,10 @@ fn http_network_fetch(
                     .unwrap()
                     .set_attribute(ResourceAttribute::ResponseEnd);
                 let _ = done_sender3.send(Data::Done);
+            })
+            .then(move |result| {
+                drop(connection_slot);
+                result
             }),
     );
 
@@ -1797,7 +2046,7 @@ fn cors_preflight_fetch(
 }
 
 /// [CORS check](https://fetch.spec.whatwg.org#concept-cors-check)
-fn cors_check(request: &Request, response: &Response) -> Result<(), ()> {
+pub fn cors_check(request: &Request, response: &Response) -> Result<(), ()> {
     // Step 1
     let origin = response.headers.typed_get::<AccessControlAllowOrigin>();
 
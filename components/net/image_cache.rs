@@ -4,7 +4,7 @@
 
 use embedder_traits::resources::{self, Resource};
 use immeta::load_from_buf;
-use net_traits::image::base::{load_from_memory, Image, ImageMetadata};
+use net_traits::image::base::{downscale_to_fit, load_from_memory, Image, ImageMetadata};
 use net_traits::image_cache::{CanRequestImages, CorsStatus, ImageCache, ImageResponder};
 use net_traits::image_cache::{ImageOrMetadataAvailable, ImageResponse, ImageState};
 use net_traits::image_cache::{PendingImageId, UsePlaceholder};
@@ -13,6 +13,7 @@ use net_traits::{
     FetchMetadata, FetchResponseMsg, FilteredMetadata, NetworkError, WebrenderIpcSender,
 };
 use pixels::PixelFormat;
+use servo_config::pref;
 use servo_url::{ImmutableOrigin, ServoUrl};
 use std::collections::hash_map::Entry::{Occupied, Vacant};
 use std::collections::HashMap;
@@ -21,7 +22,7 @@ use std::mem;
 use std::sync::{Arc, Mutex};
 use std::thread;
 use webrender_api::units::DeviceIntSize;
-use webrender_api::ImageDescriptorFlags;
+use webrender_api::{DirtyRect, ImageDescriptorFlags};
 
 ///
 /// TODO(gw): Remaining work on image cache:
@@ -50,11 +51,20 @@ fn get_placeholder_image(
     data: &[u8],
 ) -> io::Result<Arc<Image>> {
     let mut image = load_from_memory(&data, CorsStatus::Unsafe).unwrap();
-    set_webrender_image_key(webrender_api, &mut image);
+    set_webrender_image_key(webrender_api, &mut image, None);
     Ok(Arc::new(image))
 }
 
-fn set_webrender_image_key(webrender_api: &WebrenderIpcSender, image: &mut Image) {
+/// Sends the pixels of an image to WebRender, reusing the given key if the image was already
+/// sent before its decoded bitmap was evicted from the cache.
+///
+/// Images larger than the `network.image_cache.max_dimension` pref are scaled down before
+/// being sent, since WebRender draws them into the bounds of their display items anyway.
+fn set_webrender_image_key(
+    webrender_api: &WebrenderIpcSender,
+    image: &mut Image,
+    evicted_key: Option<webrender_api::ImageKey>,
+) {
     if image.id.is_some() {
         return;
     }
@@ -76,19 +86,40 @@ fn set_webrender_image_key(webrender_api: &WebrenderIpcSender, image: &mut Image
             panic!("Not support by webrender yet");
         },
     };
+    let (width, height) = match downscale_to_fit(
+        &bytes,
+        image.width,
+        image.height,
+        pref!(network.image_cache.max_dimension).max(0) as u32,
+    ) {
+        Some((width, height, downscaled)) => {
+            bytes = downscaled;
+            (width, height)
+        },
+        None => (image.width, image.height),
+    };
     let mut flags = ImageDescriptorFlags::ALLOW_MIPMAPS;
     flags.set(ImageDescriptorFlags::IS_OPAQUE, is_opaque);
     let descriptor = webrender_api::ImageDescriptor {
-        size: DeviceIntSize::new(image.width as i32, image.height as i32),
+        size: DeviceIntSize::new(width as i32, height as i32),
         stride: None,
         format: webrender_api::ImageFormat::BGRA8,
         offset: 0,
         flags,
     };
     let data = webrender_api::ImageData::new(bytes);
-    let image_key = webrender_api.generate_image_key();
     let mut txn = webrender_api::Transaction::new();
-    txn.add_image(image_key, descriptor, data, None);
+    let image_key = match evicted_key {
+        Some(image_key) => {
+            txn.update_image(image_key, descriptor, data, &DirtyRect::All);
+            image_key
+        },
+        None => {
+            let image_key = webrender_api.generate_image_key();
+            txn.add_image(image_key, descriptor, data, None);
+            image_key
+        },
+    };
     webrender_api.update_resources(txn.resource_updates);
     image.id = Some(image_key);
 }
@@ -193,13 +224,26 @@ enum CacheResult<'a> {
 struct CompletedLoad {
     image_response: ImageResponse,
     id: PendingImageId,
+    // The value of the clock of the cache when the image was last looked up.
+    last_used: u64,
 }
 
 impl CompletedLoad {
-    fn new(image_response: ImageResponse, id: PendingImageId) -> CompletedLoad {
+    fn new(image_response: ImageResponse, id: PendingImageId, last_used: u64) -> CompletedLoad {
         CompletedLoad {
             image_response: image_response,
             id: id,
+            last_used,
+        }
+    }
+
+    /// The size of the decoded bitmap owned by this load, if it can be evicted.
+    fn decoded_size(&self) -> usize {
+        match self.image_response {
+            ImageResponse::Loaded(ref image, _) => image.bytes.len(),
+            ImageResponse::PlaceholderLoaded(..) |
+            ImageResponse::MetadataLoaded(_) |
+            ImageResponse::None => 0,
         }
     }
 }
@@ -342,6 +386,17 @@ struct ImageCacheStore {
 
     // Webrender API instance.
     webrender_api: WebrenderIpcSender,
+
+    // The total size of the decoded bitmaps of the completed loads.
+    decoded_bytes: usize,
+
+    // A counter incremented on every lookup, used to find the least recently used images.
+    clock: u64,
+
+    // The WebRender keys of the images whose decoded bitmaps were evicted. Layout keeps the
+    // keys of the images it painted, so they are updated instead of replaced when the images
+    // are decoded again.
+    evicted_webrender_keys: HashMap<ImageKey, webrender_api::ImageKey>,
 }
 
 impl ImageCacheStore {
@@ -352,9 +407,15 @@ impl ImageCacheStore {
             None => return,
         };
 
+        let image_key = (
+            pending_load.url.clone(),
+            pending_load.load_origin.clone(),
+            pending_load.cors_setting,
+        );
         match load_result {
             LoadResult::Loaded(ref mut image) => {
-                set_webrender_image_key(&self.webrender_api, image)
+                let evicted_key = self.evicted_webrender_keys.remove(&image_key);
+                set_webrender_image_key(&self.webrender_api, image, evicted_key)
             },
             LoadResult::PlaceholderLoaded(..) | LoadResult::None => {},
         }
@@ -368,34 +429,73 @@ impl ImageCacheStore {
             LoadResult::None => ImageResponse::None,
         };
 
-        let completed_load = CompletedLoad::new(image_response.clone(), key);
-        self.completed_loads.insert(
-            (
-                pending_load.url.into(),
-                pending_load.load_origin,
-                pending_load.cors_setting,
-            ),
-            completed_load,
-        );
+        self.clock += 1;
+        let completed_load = CompletedLoad::new(image_response.clone(), key, self.clock);
+        self.decoded_bytes += completed_load.decoded_size();
+        if let Some(replaced) = self.completed_loads.insert(image_key, completed_load) {
+            self.decoded_bytes -= replaced.decoded_size();
+        }
 
         for listener in pending_load.listeners {
             listener.respond(image_response.clone());
         }
+
+        self.evict_over_budget(pref!(network.image_cache.max_bytes).max(0) as usize);
+    }
+
+    /// Drops the decoded bitmaps of the least recently used images until the cache fits in the
+    /// given budget. Images still referenced outside of the cache are kept, since dropping
+    /// them wouldn't free their memory. Evicted images are fetched and decoded again the next
+    /// time they are requested.
+    fn evict_over_budget(&mut self, budget: usize) {
+        if self.decoded_bytes <= budget {
+            return;
+        }
+
+        let mut candidates: Vec<(u64, ImageKey)> = self
+            .completed_loads
+            .iter()
+            .filter(|(_, load)| match load.image_response {
+                ImageResponse::Loaded(ref image, _) => Arc::strong_count(image) == 1,
+                _ => false,
+            })
+            .map(|(key, load)| (load.last_used, key.clone()))
+            .collect();
+        candidates.sort_unstable_by_key(|&(last_used, _)| last_used);
+
+        for (_, key) in candidates {
+            if self.decoded_bytes <= budget {
+                break;
+            }
+            let load = match self.completed_loads.remove(&key) {
+                Some(load) => load,
+                None => continue,
+            };
+            self.decoded_bytes -= load.decoded_size();
+            if let ImageResponse::Loaded(ref image, _) = load.image_response {
+                if let Some(webrender_key) = image.id {
+                    self.evicted_webrender_keys.insert(key, webrender_key);
+                }
+            }
+        }
     }
 
     /// Return a completed image if it exists, or None if there is no complete load
     /// or the complete load is not fully decoded or is unavailable.
     fn get_completed_image_if_available(
-        &self,
+        &mut self,
         url: ServoUrl,
         origin: ImmutableOrigin,
         cors_setting: Option<CorsSettings>,
         placeholder: UsePlaceholder,
     ) -> Option<Result<ImageOrMetadataAvailable, ImageState>> {
+        self.clock += 1;
+        let clock = self.clock;
         self.completed_loads
-            .get(&(url, origin, cors_setting))
-            .map(
-                |completed_load| match (&completed_load.image_response, placeholder) {
+            .get_mut(&(url, origin, cors_setting))
+            .map(|completed_load| {
+                completed_load.last_used = clock;
+                match (&completed_load.image_response, placeholder) {
                     (&ImageResponse::Loaded(ref image, ref url), _) |
                     (
                         &ImageResponse::PlaceholderLoaded(ref image, ref url),
@@ -407,8 +507,8 @@ impl ImageCacheStore {
                     (&ImageResponse::PlaceholderLoaded(_, _), UsePlaceholder::No) |
                     (&ImageResponse::None, _) |
                     (&ImageResponse::MetadataLoaded(_), _) => Err(ImageState::LoadError),
-                },
-            )
+                }
+            })
     }
 
     /// Handle a message from one of the decoder worker threads or from a sync
@@ -439,6 +539,9 @@ impl ImageCache for ImageCacheImpl {
                 placeholder_image: get_placeholder_image(&webrender_api, &rippy_data).ok(),
                 placeholder_url: ServoUrl::parse("chrome://resources/rippy.png").unwrap(),
                 webrender_api: webrender_api,
+                decoded_bytes: 0,
+                clock: 0,
+                evicted_webrender_keys: HashMap::new(),
             })),
         }
     }
@@ -612,4 +715,9 @@ impl ImageCache for ImageCacheImpl {
             },
         }
     }
+
+    /// Drop the decoded bitmaps of all the images which aren't used outside of the cache.
+    fn purge(&self) {
+        self.store.lock().unwrap().evict_over_budget(0);
+    }
 }
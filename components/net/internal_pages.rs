@@ -0,0 +1,132 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! The pages of the `servo:` scheme, which expose the internal state of the browser.
+//!
+//! * `servo:memory` shows the memory reports of every pipeline, broken down by category. The
+//!   `servo:memory?json` variant returns the same trees of reports as JSON, for automation.
+
+use crate::fetch::methods::FetchContext;
+use headers::{ContentType, HeaderMapExt};
+use hyper::StatusCode;
+use ipc_channel::ipc;
+use mime::{self, Mime};
+use net_traits::request::{Destination, Request};
+use net_traits::response::{Response, ResponseBody};
+use net_traits::{NetworkError, ResourceFetchTiming};
+use profile_traits::mem::{ProfilerMsg, ReportsTreeNode};
+use std::fmt::Write;
+
+pub fn fetch(request: &Request, context: &FetchContext) -> Response {
+    let url = request.current_url();
+
+    // Only navigations may load these pages. The documents loaded from them have an opaque
+    // origin, so the pages that navigate to them or embed them can't read them.
+    if request.destination != Destination::Document {
+        return Response::network_error(NetworkError::Internal(
+            "servo: URLs can only be navigated to".into(),
+        ));
+    }
+
+    let (mime, body): (Mime, Vec<u8>) = match url.path() {
+        "memory" => {
+            let trees = match collect_memory_reports(context) {
+                Some(trees) => trees,
+                None => {
+                    return Response::network_error(NetworkError::Internal(
+                        "The memory profiler is unavailable".into(),
+                    ));
+                },
+            };
+            if url.query() == Some("json") {
+                let json = serde_json::to_vec(&trees).unwrap_or_default();
+                (mime::APPLICATION_JSON, json)
+            } else {
+                (mime::TEXT_HTML_UTF_8, memory_page(&trees).into_bytes())
+            }
+        },
+        _ => {
+            return Response::network_error(NetworkError::Internal("Unknown servo: page".into()));
+        },
+    };
+
+    let mut response = Response::new(url, ResourceFetchTiming::new(request.timing_type()));
+    response.headers.typed_insert(ContentType::from(mime));
+    *response.body.lock().unwrap() = ResponseBody::Done(body);
+    response.status = Some((StatusCode::OK, "OK".to_string()));
+    response.raw_status = Some((StatusCode::OK.as_u16(), b"OK".to_vec()));
+    response
+}
+
+fn collect_memory_reports(context: &FetchContext) -> Option<Vec<ReportsTreeNode>> {
+    let mem_profiler_chan = context.mem_profiler_chan.as_ref()?;
+    let (sender, receiver) = ipc::channel().ok()?;
+    mem_profiler_chan.send(ProfilerMsg::Report(sender));
+    receiver.recv().ok()
+}
+
+/// Renders the trees of memory reports as nested lists. The reports of the pipelines are the
+/// `url(...)` children of the `explicit` tree, and their children are the categories of memory
+/// the pipelines use, so the first two levels of every tree are expanded.
+fn memory_page(trees: &[ReportsTreeNode]) -> String {
+    let mut html = String::from(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>Memory reports</title>\n\
+         <style>body { font-family: monospace; } ul { list-style: none; }</style>\n\
+         </head>\n<body>\n<h1>Memory reports</h1>\n\
+         <p><a href=\"servo:memory\">Update</a> <a href=\"servo:memory?json\">JSON</a></p>\n",
+    );
+    for tree in trees {
+        html.push_str("<ul>\n");
+        write_report_node(&mut html, tree, 0);
+        html.push_str("</ul>\n");
+    }
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+fn write_report_node(html: &mut String, node: &ReportsTreeNode, depth: usize) {
+    let mebi = 1024f64 * 1024f64;
+    let count = if node.count > 1 {
+        format!(" [{}]", node.count)
+    } else {
+        String::new()
+    };
+    let label = format!(
+        "{:8.2} MiB -- {}{}",
+        node.size as f64 / mebi,
+        escape_html(&node.path_seg),
+        count
+    );
+
+    if node.children.is_empty() {
+        let _ = writeln!(html, "<li>{}</li>", label);
+        return;
+    }
+
+    let open = if depth < 2 { " open" } else { "" };
+    let _ = writeln!(
+        html,
+        "<li><details{}><summary>{}</summary><ul>",
+        open, label
+    );
+    for child in &node.children {
+        write_report_node(html, child, depth + 1);
+    }
+    html.push_str("</ul></details></li>\n");
+}
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
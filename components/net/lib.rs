@@ -17,21 +17,35 @@ extern crate serde;
 #[macro_use]
 extern crate servo_config;
 
+pub mod client_certificates;
 pub mod connector;
 pub mod cookie;
 pub mod cookie_storage;
 mod data_loader;
 mod decoder;
+pub mod doh;
+pub mod download_manager;
+pub mod file_system;
 pub mod filemanager_thread;
+pub mod happy_eyeballs;
 mod hosts;
 pub mod hsts;
 pub mod http_cache;
 pub mod http_loader;
 pub mod image_cache;
+mod internal_pages;
+mod link_headers;
 pub mod mime_classifier;
+pub mod pac;
+pub mod preload_cache;
+pub mod protocol_handlers;
+pub mod proxy;
+pub mod request_interceptor;
+pub mod request_scheduler;
 pub mod resource_thread;
 mod storage_thread;
 pub mod subresource_integrity;
+pub mod webauthn;
 mod websocket_loader;
 /// An implementation of the [Fetch specification](https://fetch.spec.whatwg.org/)
 pub mod fetch {
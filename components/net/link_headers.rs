@@ -0,0 +1,244 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Starts the preloads and DNS prefetches that the `Link` headers of a document response hint
+//! at, before the document that needs them is parsed.
+//!
+//! These are the hints that `103 Early Hints` responses carry, but hyper drops interim
+//! responses, so only the headers of the final response are processed.
+//! <https://html.spec.whatwg.org/multipage/#early-hints>
+
+use crate::connector::resolve_host_ahead;
+use crate::fetch::methods::{fetch, CancellationListener, FetchContext};
+use crate::request_scheduler::spawn_fetch;
+use http::header::{self, HeaderMap};
+use net_traits::filemanager_thread::FileTokenCheck;
+use net_traits::request::{CredentialsMode, Destination, Referrer, Request, RequestBuilder};
+use net_traits::request::{RequestMode, RequestPriority};
+use net_traits::{DiscardFetch, ResourceFetchTiming, ResourceTimingType};
+use servo_arc::Arc as ServoArc;
+use servo_url::{Host, ServoUrl};
+use std::sync::{Arc, Mutex};
+
+/// The most links of the headers of a response that are followed.
+const MAX_FOLLOWED_LINKS: usize = 16;
+
+/// A link of a `Link` header, with the names of its parameters in lower case.
+struct Link {
+    url: ServoUrl,
+    params: Vec<(String, String)>,
+}
+
+impl Link {
+    fn param(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|param| param.0 == name)
+            .map(|param| &*param.1)
+    }
+
+    fn has_rel(&self, link_type: &str) -> bool {
+        self.param("rel").map_or(false, |rel| {
+            rel.split_whitespace()
+                .any(|rel| rel.eq_ignore_ascii_case(link_type))
+        })
+    }
+}
+
+/// Parses the links of the value of a `Link` header, resolving their URLs against the URL of
+/// the response. Links whose URL does not parse are skipped.
+/// <https://httpwg.org/specs/rfc8288.html#header>
+fn parse_link_header(value: &str, base_url: &ServoUrl) -> Vec<Link> {
+    let mut links = vec![];
+    let mut rest = value;
+    loop {
+        rest = rest.trim_start_matches(|c: char| c == ',' || c.is_whitespace());
+        if !rest.starts_with('<') {
+            break;
+        }
+        let url_end = match rest.find('>') {
+            Some(url_end) => url_end,
+            None => break,
+        };
+        let url = base_url.join(rest[1..url_end].trim());
+        rest = &rest[url_end + 1..];
+
+        let mut params: Vec<(String, String)> = vec![];
+        loop {
+            rest = rest.trim_start();
+            if !rest.starts_with(';') {
+                break;
+            }
+            rest = rest[1..].trim_start();
+            let name_end = rest
+                .find(|c: char| c == '=' || c == ';' || c == ',')
+                .unwrap_or(rest.len());
+            let name = rest[..name_end].trim().to_ascii_lowercase();
+            rest = &rest[name_end..];
+            let mut value = String::new();
+            if rest.starts_with('=') {
+                rest = rest[1..].trim_start();
+                if rest.starts_with('"') {
+                    let mut value_end = rest.len();
+                    let mut escaped = false;
+                    for (index, c) in rest.char_indices().skip(1) {
+                        if escaped {
+                            value.push(c);
+                            escaped = false;
+                        } else if c == '\\' {
+                            escaped = true;
+                        } else if c == '"' {
+                            value_end = index + 1;
+                            break;
+                        } else {
+                            value.push(c);
+                        }
+                    }
+                    rest = &rest[value_end..];
+                } else {
+                    let value_end = rest
+                        .find(|c: char| c == ';' || c == ',')
+                        .unwrap_or(rest.len());
+                    value = rest[..value_end].trim().to_owned();
+                    rest = &rest[value_end..];
+                }
+            }
+            // Only the first occurrence of a parameter counts.
+            if !params.iter().any(|param| param.0 == name) {
+                params.push((name, value));
+            }
+        }
+
+        if let Ok(url) = url {
+            links.push(Link { url, params });
+        }
+        match rest.find(',') {
+            Some(link_end) => rest = &rest[link_end..],
+            None => break,
+        }
+    }
+    links
+}
+
+/// Whether a document at the URL would be a secure context, which are the only ones whose
+/// hints are followed.
+fn is_potentially_trustworthy(url: &ServoUrl) -> bool {
+    if url.is_secure_scheme() {
+        return true;
+    }
+    match url.host() {
+        Some(Host::Domain(domain)) => domain == "localhost" || domain.ends_with(".localhost"),
+        Some(Host::Ipv4(address)) => address.is_loopback(),
+        Some(Host::Ipv6(address)) => address.is_loopback(),
+        None => false,
+    }
+}
+
+/// The destination of a preload with the given `as` parameter, for the destinations whose
+/// requests match the ones that the document makes for them.
+fn preload_destination(value: &str) -> Option<Destination> {
+    match &*value.to_ascii_lowercase() {
+        "fetch" => Some(Destination::None),
+        "font" => Some(Destination::Font),
+        "image" => Some(Destination::Image),
+        "script" => Some(Destination::Script),
+        "style" => Some(Destination::Style),
+        "track" => Some(Destination::Track),
+        _ => None,
+    }
+}
+
+/// Follows the `rel=preload`, `rel=preconnect` and `rel=dns-prefetch` links of the headers of
+/// a response to the request for a document, up to `MAX_FOLLOWED_LINKS` of them. The links of
+/// `preconnect` only get their host resolved.
+/// <https://html.spec.whatwg.org/multipage/#process-early-hint-headers>
+pub fn process_link_headers(request: &Request, headers: &HeaderMap, context: &FetchContext) {
+    let url = request.current_url();
+    if request.destination != Destination::Document || !is_potentially_trustworthy(&url) {
+        return;
+    }
+    let links = headers
+        .get_all(header::LINK)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| parse_link_header(value, &url))
+        .filter(|link| {
+            link.has_rel("preconnect") || link.has_rel("dns-prefetch") || link.has_rel("preload")
+        })
+        .take(MAX_FOLLOWED_LINKS);
+    for link in links {
+        if link.has_rel("preconnect") || link.has_rel("dns-prefetch") {
+            resolve_host_ahead(&context.state.resolver, &link.url);
+        }
+        if link.has_rel("preload") {
+            preload(request, &url, &link, context);
+        }
+    }
+}
+
+/// Fetches the resource of a `rel=preload` link into the preload cache, from which the fetch of
+/// the document for the same resource takes it.
+/// <https://html.spec.whatwg.org/multipage/#create-a-preload-request>
+fn preload(request: &Request, document_url: &ServoUrl, link: &Link, context: &FetchContext) {
+    let destination = match link.param("as").and_then(preload_destination) {
+        Some(destination) => destination,
+        None => return,
+    };
+    if !matches!(link.url.scheme(), "http" | "https") {
+        return;
+    }
+    let (mode, credentials_mode) = match link.param("crossorigin") {
+        None => (RequestMode::NoCors, CredentialsMode::Include),
+        Some(value) if value.eq_ignore_ascii_case("use-credentials") => {
+            (RequestMode::CorsMode, CredentialsMode::Include)
+        },
+        Some(_) => (
+            RequestMode::CorsMode,
+            CredentialsMode::CredentialsSameOrigin,
+        ),
+    };
+    let priority = match link.param("fetchpriority") {
+        Some(value) if value.eq_ignore_ascii_case("high") => RequestPriority::High,
+        Some(value) if value.eq_ignore_ascii_case("low") => RequestPriority::Low,
+        _ => RequestPriority::Auto,
+    };
+    let preload_request = RequestBuilder::new(link.url.clone())
+        .destination(destination)
+        .mode(mode)
+        .credentials_mode(credentials_mode)
+        .integrity_metadata(link.param("integrity").unwrap_or("").to_owned())
+        .origin(document_url.origin())
+        .pipeline_id(request.pipeline_id)
+        .referrer(Some(Referrer::ReferrerUrl(document_url.clone())))
+        .referrer_policy(request.referrer_policy)
+        .priority(priority)
+        .preload(true)
+        .build();
+
+    let context = FetchContext {
+        state: context.state.clone(),
+        user_agent: context.user_agent.clone(),
+        devtools_chan: context.devtools_chan.clone(),
+        filemanager: context.filemanager.clone(),
+        file_token: FileTokenCheck::NotRequired,
+        cancellation_listener: Arc::new(Mutex::new(CancellationListener::new(None))),
+        timing: ServoArc::new(Mutex::new(ResourceFetchTiming::new(
+            ResourceTimingType::Resource,
+        ))),
+        mem_profiler_chan: context.mem_profiler_chan.clone(),
+        thread_pool: context.thread_pool.clone(),
+        connection_slot: Mutex::new(None),
+    };
+    let scheduler = context.state.request_scheduler.clone();
+    let thread_pool = context.thread_pool.clone();
+    spawn_fetch(
+        &scheduler,
+        &thread_pool,
+        preload_request,
+        move |mut preload_request, connection_slot| {
+            *context.connection_slot.lock().unwrap() = connection_slot;
+            fetch(&mut preload_request, &mut DiscardFetch, &context)
+        },
+    );
+}
@@ -0,0 +1,554 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Evaluates proxy auto-config (PAC) scripts, which choose the proxy for each URL with a
+//! `FindProxyForURL(url, host)` function. Only the subset of JavaScript that these scripts
+//! commonly use is understood: `if`/`else`, `return` and `var` statements, string and number
+//! literals, `+`, the comparison and logical operators, and calls to the PAC helper functions.
+//! Anything else is an error rather than a guess, so that a script that is not understood
+//! fails instead of choosing another proxy than it would in a browser.
+//! <https://developer.mozilla.org/en-US/docs/Web/HTTP/Proxy_servers_and_tunneling/Proxy_Auto-Configuration_PAC_file>
+
+use std::collections::HashMap;
+use std::mem;
+use std::net::{IpAddr, Ipv4Addr, ToSocketAddrs, UdpSocket};
+
+const PUNCTUATORS: &[&str] = &[
+    "===", "!==", "==", "!=", "&&", "||", "(", ")", "{", "}", ",", ";", "!", "=", "+",
+];
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Identifier(String),
+    String(String),
+    Number(f64),
+    Punctuator(&'static str),
+}
+
+fn tokenize(source: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = vec![];
+    let mut rest = source;
+    loop {
+        rest = rest.trim_start();
+        if rest.starts_with("//") {
+            rest = rest.find('\n').map_or("", |end| &rest[end..]);
+            continue;
+        }
+        if rest.starts_with("/*") {
+            rest = match rest.find("*/") {
+                Some(end) => &rest[end + 2..],
+                None => return Err("unterminated comment".to_owned()),
+            };
+            continue;
+        }
+        let c = match rest.chars().next() {
+            Some(c) => c,
+            None => return Ok(tokens),
+        };
+        if c == '"' || c == '\'' {
+            let mut value = String::new();
+            let mut chars = rest.char_indices().skip(1);
+            let end = loop {
+                match chars.next() {
+                    Some((_, '\\')) => match chars.next() {
+                        Some((_, 'n')) => value.push('\n'),
+                        Some((_, 'r')) => value.push('\r'),
+                        Some((_, 't')) => value.push('\t'),
+                        Some((_, escaped)) if "\\\"'".contains(escaped) => value.push(escaped),
+                        Some((_, escaped)) => {
+                            return Err(format!("unsupported escape sequence \\{}", escaped))
+                        },
+                        None => return Err("unterminated string".to_owned()),
+                    },
+                    Some((index, quote)) if quote == c => break index + 1,
+                    Some((_, other)) => value.push(other),
+                    None => return Err("unterminated string".to_owned()),
+                }
+            };
+            tokens.push(Token::String(value));
+            rest = &rest[end..];
+        } else if c.is_ascii_digit() {
+            let end = rest
+                .find(|c: char| !c.is_ascii_digit() && c != '.')
+                .unwrap_or(rest.len());
+            let number = rest[..end]
+                .parse()
+                .map_err(|_| format!("invalid number {:?}", &rest[..end]))?;
+            tokens.push(Token::Number(number));
+            rest = &rest[end..];
+        } else if c.is_alphabetic() || c == '_' || c == '$' {
+            let end = rest
+                .find(|c: char| !c.is_alphanumeric() && c != '_' && c != '$')
+                .unwrap_or(rest.len());
+            tokens.push(Token::Identifier(rest[..end].to_owned()));
+            rest = &rest[end..];
+        } else {
+            let punctuator = PUNCTUATORS
+                .iter()
+                .find(|punctuator| rest.starts_with(**punctuator))
+                .ok_or_else(|| format!("unsupported character {:?}", c))?;
+            tokens.push(Token::Punctuator(*punctuator));
+            rest = &rest[punctuator.len()..];
+        }
+    }
+}
+
+#[derive(Debug)]
+enum Expression {
+    Literal(Value),
+    Variable(String),
+    Call(String, Vec<Expression>),
+    Not(Box<Expression>),
+    Binary(&'static str, Box<Expression>, Box<Expression>),
+}
+
+#[derive(Debug)]
+enum Statement {
+    If(Expression, Box<Statement>, Option<Box<Statement>>),
+    Block(Vec<Statement>),
+    Return(Expression),
+    Var(String, Expression),
+    Empty,
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Result<Token, String> {
+        let token = self
+            .tokens
+            .get(self.position)
+            .cloned()
+            .ok_or_else(|| "unexpected end of script".to_owned())?;
+        self.position += 1;
+        Ok(token)
+    }
+
+    fn eat(&mut self, punctuator: &'static str) -> bool {
+        if self.peek() == Some(&Token::Punctuator(punctuator)) {
+            self.position += 1;
+            return true;
+        }
+        false
+    }
+
+    fn expect(&mut self, punctuator: &'static str) -> Result<(), String> {
+        if self.eat(punctuator) {
+            return Ok(());
+        }
+        Err(format!(
+            "expected {:?}, found {:?}",
+            punctuator,
+            self.peek()
+        ))
+    }
+
+    fn identifier(&mut self) -> Result<String, String> {
+        match self.next()? {
+            Token::Identifier(name) => Ok(name),
+            token => Err(format!("expected a name, found {:?}", token)),
+        }
+    }
+
+    fn statement(&mut self) -> Result<Statement, String> {
+        if self.eat("{") {
+            return Ok(Statement::Block(self.block()?));
+        }
+        if self.eat(";") {
+            return Ok(Statement::Empty);
+        }
+        match self.identifier()?.as_str() {
+            "if" => {
+                self.expect("(")?;
+                let condition = self.expression()?;
+                self.expect(")")?;
+                let consequent = Box::new(self.statement()?);
+                let alternative = match self.peek() {
+                    Some(Token::Identifier(name)) if name == "else" => {
+                        self.position += 1;
+                        Some(Box::new(self.statement()?))
+                    },
+                    _ => None,
+                };
+                Ok(Statement::If(condition, consequent, alternative))
+            },
+            "return" => {
+                let value = self.expression()?;
+                self.eat(";");
+                Ok(Statement::Return(value))
+            },
+            "var" | "let" | "const" => {
+                let name = self.identifier()?;
+                self.expect("=")?;
+                let value = self.expression()?;
+                self.eat(";");
+                Ok(Statement::Var(name, value))
+            },
+            name => Err(format!("unsupported statement starting with {:?}", name)),
+        }
+    }
+
+    /// Parses the statements up to the closing brace of a block.
+    fn block(&mut self) -> Result<Vec<Statement>, String> {
+        let mut statements = vec![];
+        while !self.eat("}") {
+            statements.push(self.statement()?);
+        }
+        Ok(statements)
+    }
+
+    fn expression(&mut self) -> Result<Expression, String> {
+        self.binary(0)
+    }
+
+    /// Parses the binary operators from the given level of precedence on.
+    fn binary(&mut self, level: usize) -> Result<Expression, String> {
+        const LEVELS: &[&[&str]] = &[&["||"], &["&&"], &["==", "!=", "===", "!=="], &["+"]];
+        let operators = match LEVELS.get(level) {
+            Some(operators) => *operators,
+            None => return self.unary(),
+        };
+        let mut left = self.binary(level + 1)?;
+        loop {
+            let operator = match self.peek() {
+                Some(Token::Punctuator(punctuator)) if operators.contains(punctuator) => {
+                    *punctuator
+                },
+                _ => return Ok(left),
+            };
+            self.position += 1;
+            let right = self.binary(level + 1)?;
+            left = Expression::Binary(operator, Box::new(left), Box::new(right));
+        }
+    }
+
+    fn unary(&mut self) -> Result<Expression, String> {
+        if self.eat("!") {
+            return Ok(Expression::Not(Box::new(self.unary()?)));
+        }
+        match self.next()? {
+            Token::String(value) => Ok(Expression::Literal(Value::String(value))),
+            Token::Number(value) => Ok(Expression::Literal(Value::Number(value))),
+            Token::Punctuator("(") => {
+                let expression = self.expression()?;
+                self.expect(")")?;
+                Ok(expression)
+            },
+            Token::Identifier(name) => match name.as_str() {
+                "true" => Ok(Expression::Literal(Value::Boolean(true))),
+                "false" => Ok(Expression::Literal(Value::Boolean(false))),
+                "null" | "undefined" => Ok(Expression::Literal(Value::Undefined)),
+                _ if self.eat("(") => {
+                    let mut arguments = vec![];
+                    while !self.eat(")") {
+                        if !arguments.is_empty() {
+                            self.expect(",")?;
+                        }
+                        arguments.push(self.expression()?);
+                    }
+                    Ok(Expression::Call(name, arguments))
+                },
+                _ => Ok(Expression::Variable(name)),
+            },
+            token => Err(format!("unexpected {:?}", token)),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Value {
+    String(String),
+    Number(f64),
+    Boolean(bool),
+    Undefined,
+}
+
+impl Value {
+    fn is_truthy(&self) -> bool {
+        match *self {
+            Value::String(ref value) => !value.is_empty(),
+            Value::Number(value) => value != 0. && !value.is_nan(),
+            Value::Boolean(value) => value,
+            Value::Undefined => false,
+        }
+    }
+
+    fn stringify(&self) -> String {
+        match *self {
+            Value::String(ref value) => value.clone(),
+            Value::Number(value) => value.to_string(),
+            Value::Boolean(value) => value.to_string(),
+            Value::Undefined => "undefined".to_owned(),
+        }
+    }
+}
+
+enum Completion {
+    Normal,
+    Return(Value),
+}
+
+/// A parsed PAC script.
+#[derive(Debug)]
+pub struct PacScript {
+    /// The names of the parameters of `FindProxyForURL`.
+    parameters: (String, String),
+    body: Vec<Statement>,
+}
+
+impl PacScript {
+    /// Parses the `FindProxyForURL` function of a script. The rest of the script is ignored,
+    /// so the function fails when it uses the variables or the functions that the rest of the
+    /// script defines.
+    pub fn parse(source: &str) -> Result<PacScript, String> {
+        let tokens = tokenize(source)?;
+        let mut definitions = tokens.windows(2).enumerate().filter(|&(_, window)| {
+            window[0] == Token::Identifier("function".to_owned()) &&
+                window[1] == Token::Identifier("FindProxyForURL".to_owned())
+        });
+        let start = match (definitions.next(), definitions.next()) {
+            (Some((start, _)), None) => start,
+            (None, _) => return Err("the script has no FindProxyForURL function".to_owned()),
+            (Some(_), Some(_)) => {
+                return Err("the script defines FindProxyForURL more than once".to_owned())
+            },
+        };
+        let mut parser = Parser {
+            tokens,
+            position: start + 2,
+        };
+        parser.expect("(")?;
+        let url = parser.identifier()?;
+        parser.expect(",")?;
+        let host = parser.identifier()?;
+        parser.expect(")")?;
+        parser.expect("{")?;
+        Ok(PacScript {
+            parameters: (url, host),
+            body: parser.block()?,
+        })
+    }
+
+    /// Returns the proxies that the script lists for the URL, such as `PROXY proxy:8080; DIRECT`.
+    pub fn find_proxy_for_url(&self, url: &str, host: &str) -> Result<String, String> {
+        let mut variables = HashMap::new();
+        variables.insert(self.parameters.0.clone(), Value::String(url.to_owned()));
+        variables.insert(self.parameters.1.clone(), Value::String(host.to_owned()));
+        match execute_all(&self.body, &mut variables)? {
+            Completion::Return(value) => Ok(value.stringify()),
+            Completion::Normal => Err("FindProxyForURL returned nothing".to_owned()),
+        }
+    }
+}
+
+fn execute_all(
+    statements: &[Statement],
+    variables: &mut HashMap<String, Value>,
+) -> Result<Completion, String> {
+    for statement in statements {
+        if let Completion::Return(value) = execute(statement, variables)? {
+            return Ok(Completion::Return(value));
+        }
+    }
+    Ok(Completion::Normal)
+}
+
+fn execute(
+    statement: &Statement,
+    variables: &mut HashMap<String, Value>,
+) -> Result<Completion, String> {
+    match *statement {
+        Statement::If(ref condition, ref consequent, ref alternative) => {
+            if evaluate(condition, variables)?.is_truthy() {
+                execute(consequent, variables)
+            } else if let Some(ref alternative) = *alternative {
+                execute(alternative, variables)
+            } else {
+                Ok(Completion::Normal)
+            }
+        },
+        Statement::Block(ref statements) => execute_all(statements, variables),
+        Statement::Return(ref value) => Ok(Completion::Return(evaluate(value, variables)?)),
+        Statement::Var(ref name, ref value) => {
+            let value = evaluate(value, variables)?;
+            variables.insert(name.clone(), value);
+            Ok(Completion::Normal)
+        },
+        Statement::Empty => Ok(Completion::Normal),
+    }
+}
+
+fn evaluate(expression: &Expression, variables: &HashMap<String, Value>) -> Result<Value, String> {
+    match *expression {
+        Expression::Literal(ref value) => Ok(value.clone()),
+        Expression::Variable(ref name) => variables
+            .get(name)
+            .cloned()
+            .ok_or_else(|| format!("{} is not defined", name)),
+        Expression::Not(ref operand) => {
+            Ok(Value::Boolean(!evaluate(operand, variables)?.is_truthy()))
+        },
+        Expression::Binary(operator, ref left, ref right) => {
+            let left = evaluate(left, variables)?;
+            // The logical operators only evaluate their right operand when it decides the result.
+            match operator {
+                "||" if left.is_truthy() => return Ok(left),
+                "&&" if !left.is_truthy() => return Ok(left),
+                "||" | "&&" => return evaluate(right, variables),
+                _ => {},
+            }
+            let right = evaluate(right, variables)?;
+            Ok(match (operator, left, right) {
+                ("+", Value::Number(left), Value::Number(right)) => Value::Number(left + right),
+                ("+", left, right) => Value::String(left.stringify() + &right.stringify()),
+                ("===", left, right) => Value::Boolean(left == right),
+                ("!==", left, right) => Value::Boolean(left != right),
+                // The loose comparisons of values of different types convert them first.
+                (_, ref left, ref right) if mem::discriminant(left) != mem::discriminant(right) => {
+                    return Err(format!(
+                        "unsupported comparison of {:?} and {:?}",
+                        left, right
+                    ))
+                },
+                ("==", left, right) => Value::Boolean(left == right),
+                (_, left, right) => Value::Boolean(left != right),
+            })
+        },
+        Expression::Call(ref name, ref arguments) => {
+            let arguments = arguments
+                .iter()
+                .map(|argument| evaluate(argument, variables).map(|value| value.stringify()))
+                .collect::<Result<Vec<_>, _>>()?;
+            call(name, &arguments)
+        },
+    }
+}
+
+/// Calls a PAC helper function.
+fn call(name: &str, arguments: &[String]) -> Result<Value, String> {
+    let arity = match name {
+        "isPlainHostName" | "dnsDomainLevels" | "isResolvable" | "dnsResolve" | "alert" => 1,
+        "dnsDomainIs" | "localHostOrDomainIs" | "shExpMatch" => 2,
+        "isInNet" => 3,
+        "myIpAddress" => 0,
+        _ => return Err(format!("the function {} is not supported", name)),
+    };
+    if arguments.len() != arity {
+        return Err(format!(
+            "{} takes {} arguments, not {}",
+            name,
+            arity,
+            arguments.len()
+        ));
+    }
+    let argument = |index: usize| &*arguments[index];
+    Ok(match name {
+        "isPlainHostName" => Value::Boolean(!argument(0).contains('.')),
+        "dnsDomainIs" => Value::Boolean(
+            argument(0)
+                .to_ascii_lowercase()
+                .ends_with(&argument(1).to_ascii_lowercase()),
+        ),
+        "localHostOrDomainIs" => {
+            let (host, domain) = (argument(0), argument(1));
+            Value::Boolean(
+                host.eq_ignore_ascii_case(domain) ||
+                    (!host.contains('.') &&
+                        domain
+                            .to_ascii_lowercase()
+                            .starts_with(&format!("{}.", host.to_ascii_lowercase()))),
+            )
+        },
+        "dnsDomainLevels" => Value::Number(argument(0).matches('.').count() as f64),
+        "shExpMatch" => Value::Boolean(shell_expression_match(argument(0), argument(1))),
+        "isResolvable" => Value::Boolean(resolve(argument(0)).is_some()),
+        "dnsResolve" => resolve(argument(0)).map_or(Value::Undefined, |address| {
+            Value::String(address.to_string())
+        }),
+        "myIpAddress" => Value::String(my_ip_address().to_string()),
+        "isInNet" => {
+            let address = match resolve(argument(0)) {
+                Some(address) => u32::from(address),
+                None => return Ok(Value::Boolean(false)),
+            };
+            let pattern = argument(1).parse::<Ipv4Addr>().map(u32::from);
+            let mask = argument(2).parse::<Ipv4Addr>().map(u32::from);
+            match (pattern, mask) {
+                (Ok(pattern), Ok(mask)) => Value::Boolean(address & mask == pattern & mask),
+                _ => Value::Boolean(false),
+            }
+        },
+        _ => Value::Undefined,
+    })
+}
+
+/// Matches a string against a shell expression, in which `*` matches any number of
+/// characters and `?` matches one.
+pub fn shell_expression_match(string: &str, expression: &str) -> bool {
+    let string: Vec<char> = string.chars().collect();
+    let expression: Vec<char> = expression.chars().collect();
+    // The position in the expression of the last `*`, and the position in the string that it
+    // was tried to match up to.
+    let (mut s, mut e) = (0, 0);
+    let mut backtrack = None;
+    while s < string.len() {
+        match expression.get(e) {
+            Some(&'*') => {
+                backtrack = Some((e, s));
+                e += 1;
+            },
+            Some(&c) if c == '?' || c == string[s] => {
+                s += 1;
+                e += 1;
+            },
+            _ => match backtrack {
+                Some((star, matched)) => {
+                    backtrack = Some((star, matched + 1));
+                    e = star + 1;
+                    s = matched + 1;
+                },
+                None => return false,
+            },
+        }
+    }
+    expression[e..].iter().all(|c| *c == '*')
+}
+
+/// The first IPv4 address of the host, which may be an address itself.
+fn resolve(host: &str) -> Option<Ipv4Addr> {
+    if let Ok(address) = host.parse() {
+        return Some(address);
+    }
+    (host, 0)
+        .to_socket_addrs()
+        .ok()?
+        .filter_map(|address| match address.ip() {
+            IpAddr::V4(address) => Some(address),
+            IpAddr::V6(_) => None,
+        })
+        .next()
+}
+
+/// The address of the interface that connects to the internet, found by connecting a UDP
+/// socket, which sends no packet.
+fn my_ip_address() -> Ipv4Addr {
+    UdpSocket::bind("0.0.0.0:0")
+        .and_then(|socket| {
+            socket.connect("192.0.2.1:80")?;
+            socket.local_addr()
+        })
+        .ok()
+        .and_then(|address| match address.ip() {
+            IpAddr::V4(address) if !address.is_unspecified() => Some(address),
+            _ => None,
+        })
+        .unwrap_or(Ipv4Addr::LOCALHOST)
+}
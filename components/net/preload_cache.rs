@@ -0,0 +1,108 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Keeps the responses to `<link rel=preload>` fetches until the fetch of the document that
+//! needs the same resource uses them, instead of fetching the resource again.
+//! <https://html.spec.whatwg.org/multipage/#preload-cache>
+
+use msg::constellation_msg::PipelineId;
+use net_traits::request::{CredentialsMode, Destination, Request, RequestMode};
+use net_traits::response::{Response, ResponseBody};
+use servo_url::ServoUrl;
+use std::sync::atomic::Ordering;
+use std::sync::{Arc, Condvar, Mutex};
+
+/// The most responses that are kept, after which the oldest one is dropped to make room.
+pub const MAX_PRELOADED_RESPONSES: usize = 100;
+
+/// <https://html.spec.whatwg.org/multipage/#preload-key>
+#[derive(Clone, PartialEq)]
+pub struct PreloadKey {
+    pipeline_id: Option<PipelineId>,
+    url: ServoUrl,
+    destination: Destination,
+    mode: RequestMode,
+    credentials_mode: CredentialsMode,
+    integrity_metadata: String,
+}
+
+impl PreloadKey {
+    fn new(request: &Request) -> PreloadKey {
+        PreloadKey {
+            pipeline_id: request.pipeline_id,
+            url: request.url(),
+            destination: request.destination,
+            mode: request.mode.clone(),
+            credentials_mode: request.credentials_mode,
+            integrity_metadata: request.integrity_metadata.clone(),
+        }
+    }
+}
+
+/// The preloads of every document, in the order they were started. The response of a
+/// preload is `None` while it is being fetched.
+#[derive(Clone, Default)]
+pub struct PreloadCache {
+    state: Arc<(Mutex<Vec<(PreloadKey, Option<Response>)>>, Condvar)>,
+}
+
+impl PreloadCache {
+    pub fn new() -> PreloadCache {
+        Default::default()
+    }
+
+    /// Records that the preload request is being fetched, and returns the key to store its
+    /// response with, unless the same resource is already being preloaded.
+    pub fn start(&self, request: &Request) -> Option<PreloadKey> {
+        let key = PreloadKey::new(request);
+        let mut entries = self.state.0.lock().unwrap();
+        if entries.iter().any(|entry| entry.0 == key) {
+            return None;
+        }
+        if entries.len() >= MAX_PRELOADED_RESPONSES {
+            if let Some(index) = entries.iter().position(|entry| entry.1.is_some()) {
+                entries.remove(index);
+            }
+        }
+        entries.push((key.clone(), None));
+        Some(key)
+    }
+
+    /// Stores the response of a preload once it was fully received. Failed preloads are
+    /// forgotten, so that the fetch that needs the resource tries again.
+    pub fn finish(&self, key: PreloadKey, response: Response) {
+        let (ref entries, ref condvar) = *self.state;
+        let mut entries = entries.lock().unwrap();
+        let index = match entries.iter().position(|entry| entry.0 == key) {
+            Some(index) => index,
+            None => return,
+        };
+        let complete = match *response.actual_response().body.lock().unwrap() {
+            ResponseBody::Done(_) => true,
+            _ => false,
+        };
+        if response.is_network_error() || response.aborted.load(Ordering::Acquire) || !complete {
+            entries.remove(index);
+        } else {
+            entries[index].1 = Some(response);
+        }
+        condvar.notify_all();
+    }
+
+    /// Takes the preloaded response to the resource that the request fetches, waiting for the
+    /// preload to finish if it is still being fetched.
+    /// <https://html.spec.whatwg.org/multipage/#consume-a-preloaded-resource>
+    pub fn take(&self, request: &Request) -> Option<Response> {
+        let key = PreloadKey::new(request);
+        let (ref entries, ref condvar) = *self.state;
+        let mut entries = entries.lock().unwrap();
+        loop {
+            let index = entries.iter().position(|entry| entry.0 == key)?;
+            if entries[index].1.is_some() {
+                return entries.remove(index).1;
+            }
+            entries = condvar.wait(entries).unwrap();
+        }
+    }
+}
@@ -0,0 +1,134 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! The protocol handlers that sites registered with `navigator.registerProtocolHandler` and the
+//! user allowed, which the navigations to the URLs of their schemes are sent to instead.
+//! <https://html.spec.whatwg.org/multipage/#custom-handlers>
+
+use crate::http_loader::HttpState;
+use embedder_traits::{EmbedderMsg, EmbedderProxy, ProtocolHandlerRequest};
+use ipc_channel::ipc;
+use ipc_channel::router::ROUTER;
+use msg::constellation_msg::TopLevelBrowsingContextId;
+use percent_encoding::{utf8_percent_encode, AsciiSet, CONTROLS};
+use servo_url::ServoUrl;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// <https://url.spec.whatwg.org/#component-percent-encode-set>
+const COMPONENT: &AsciiSet = &CONTROLS
+    .add(b' ')
+    .add(b'"')
+    .add(b'#')
+    .add(b'$')
+    .add(b'%')
+    .add(b'&')
+    .add(b'+')
+    .add(b',')
+    .add(b'/')
+    .add(b':')
+    .add(b';')
+    .add(b'<')
+    .add(b'=')
+    .add(b'>')
+    .add(b'?')
+    .add(b'@')
+    .add(b'[')
+    .add(b'\\')
+    .add(b']')
+    .add(b'^')
+    .add(b'`')
+    .add(b'{')
+    .add(b'|')
+    .add(b'}');
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct ProtocolHandlerList {
+    /// The URLs of the handlers of each scheme that the user allowed, with a `%s` where the URL
+    /// being navigated to goes, the most recently allowed last.
+    allowed: HashMap<String, Vec<ServoUrl>>,
+}
+
+impl ProtocolHandlerList {
+    pub fn new() -> ProtocolHandlerList {
+        Default::default()
+    }
+
+    /// Registers a handler that the user allowed, which the navigations go to from now on.
+    pub fn register(&mut self, scheme: String, url: ServoUrl) {
+        let handlers = self.allowed.entry(scheme).or_insert_with(Vec::new);
+        handlers.retain(|handler| *handler != url);
+        handlers.push(url);
+    }
+
+    pub fn unregister(&mut self, scheme: &str, url: &ServoUrl) {
+        if let Some(handlers) = self.allowed.get_mut(scheme) {
+            handlers.retain(|handler| handler != url);
+            if handlers.is_empty() {
+                self.allowed.remove(scheme);
+            }
+        }
+    }
+
+    pub fn is_registered(&self, scheme: &str, url: &ServoUrl) -> bool {
+        self.allowed
+            .get(scheme)
+            .map_or(false, |handlers| handlers.contains(url))
+    }
+
+    /// The handler that the navigations to the URLs of the scheme go to, which is the one that
+    /// the user allowed last.
+    pub fn current_url(&self, scheme: &str) -> Option<&ServoUrl> {
+        self.allowed.get(scheme)?.last()
+    }
+
+    /// The URL that a navigation to the given URL goes to instead, if the user allowed a
+    /// handler for its scheme.
+    /// <https://html.spec.whatwg.org/multipage/#protocol-handler-invocation>
+    pub fn handler_url(&self, url: &ServoUrl) -> Option<ServoUrl> {
+        let handler = self.current_url(url.scheme())?;
+        let escaped_url = utf8_percent_encode(url.as_str(), COMPONENT).to_string();
+        ServoUrl::parse(&handler.as_str().replacen("%s", &escaped_url, 1)).ok()
+    }
+}
+
+/// Asks the user through the embedder whether a handler that a site registered may handle the
+/// navigations to the URLs of the scheme in place of the current one, and registers it once
+/// they allowed it. A handler that the user allowed before is left as it is.
+pub fn ask_to_register(
+    http_state: &Arc<HttpState>,
+    embedder_proxy: &EmbedderProxy,
+    top_level_browsing_context_id: TopLevelBrowsingContextId,
+    scheme: String,
+    url: ServoUrl,
+) {
+    let current_url = {
+        let protocol_handlers = http_state.protocol_handlers.read().unwrap();
+        if protocol_handlers.is_registered(&scheme, &url) {
+            return;
+        }
+        protocol_handlers.current_url(&scheme).cloned()
+    };
+    let request = ProtocolHandlerRequest {
+        scheme: scheme.clone(),
+        url: url.clone(),
+        current_url,
+    };
+    let (sender, receiver) = ipc::channel().expect("Failed to create IPC channel!");
+    let http_state = http_state.clone();
+    ROUTER.add_route(
+        receiver.to_opaque(),
+        Box::new(move |message| {
+            if message.to().unwrap_or(false) {
+                http_state
+                    .protocol_handlers
+                    .write()
+                    .unwrap()
+                    .register(scheme.clone(), url.clone());
+            }
+        }),
+    );
+    let msg = EmbedderMsg::RegisterProtocolHandler(request, sender);
+    embedder_proxy.send((Some(top_level_browsing_context_id), msg));
+}
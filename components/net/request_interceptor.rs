@@ -0,0 +1,175 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Lets the embedder observe the requests before they are fetched, and let them through,
+//! rewrite them, block them, or respond to them itself, for instance to serve custom schemes,
+//! block ads, or override resources with local files.
+
+use crate::fetch::methods::{Data, DoneChannel, FetchContext};
+use crossbeam_channel::{unbounded, Sender};
+use embedder_traits::{EmbedderMsg, EmbedderProxy};
+use embedder_traits::{WebResourceRequest, WebResourceResponseMsg};
+use futures::sync::oneshot;
+use futures::Future;
+use ipc_channel::ipc;
+use ipc_channel::router::ROUTER;
+use net_traits::request::{Destination, Request};
+use net_traits::response::{Response, ResponseBody};
+use net_traits::NetworkError;
+use std::mem;
+use std::sync::{Arc, Mutex};
+
+#[derive(Clone, Default)]
+pub struct RequestInterceptor {
+    embedder_proxy: Option<Arc<Mutex<EmbedderProxy>>>,
+}
+
+impl RequestInterceptor {
+    /// An interceptor that lets every request through without asking anyone.
+    pub fn new() -> RequestInterceptor {
+        Default::default()
+    }
+
+    pub fn with_embedder(embedder_proxy: EmbedderProxy) -> RequestInterceptor {
+        RequestInterceptor {
+            embedder_proxy: Some(Arc::new(Mutex::new(embedder_proxy))),
+        }
+    }
+
+    /// Asks the embedder what to do with a request. Returns the response of the embedder,
+    /// whose body is received in the background like the body of a network response, or a
+    /// network error if it blocked the request, and otherwise lets the request be fetched,
+    /// after applying the changes that the embedder made to it.
+    pub fn intercept_request(
+        &self,
+        request: &mut Request,
+        done_chan: &mut DoneChannel,
+        context: &FetchContext,
+    ) -> Option<Response> {
+        let embedder_proxy = match self.embedder_proxy {
+            Some(ref embedder_proxy) if pref!(network.request_interception.enabled) => {
+                embedder_proxy
+            },
+            _ => return None,
+        };
+        // The requests that never leave the browser are not the embedder's business.
+        let url = request.current_url();
+        if matches!(url.scheme(), "about" | "blob" | "data") {
+            return None;
+        }
+
+        let (sender, receiver) = ipc::channel().expect("Failed to create IPC channel!");
+        let (answer_sender, answer_receiver) = oneshot::channel();
+        let (done_sender, done_receiver) = unbounded();
+        let body = Arc::new(Mutex::new(ResponseBody::Receiving(vec![])));
+        let mut body_receiver = BodyReceiver {
+            answer_sender: Some(answer_sender),
+            done_sender,
+            body: body.clone(),
+        };
+        ROUTER.add_route(
+            receiver.to_opaque(),
+            Box::new(move |message| body_receiver.receive(message.to().ok())),
+        );
+
+        let web_request = WebResourceRequest {
+            method: request.method.clone(),
+            headers: request.headers.clone(),
+            url: url.clone(),
+            is_navigation: request.destination == Destination::Document,
+            is_redirect: request.redirect_count > 0,
+        };
+        embedder_proxy
+            .lock()
+            .unwrap()
+            .send((None, EmbedderMsg::WebResourceRequested(web_request, sender)));
+
+        // The fetch carries on once the embedder answered, like it does once the headers of a
+        // network response are received.
+        match answer_receiver.wait() {
+            Ok(WebResourceResponseMsg::Continue) => None,
+            Ok(WebResourceResponseMsg::Rewrite(rewritten)) => {
+                request.method = rewritten.method;
+                request.headers = rewritten.headers;
+                *request.current_url_mut() = rewritten.url;
+                None
+            },
+            Ok(WebResourceResponseMsg::Block) => Some(Response::network_error(
+                NetworkError::Internal("Request blocked by the embedder".into()),
+            )),
+            Ok(WebResourceResponseMsg::Start(web_response)) => {
+                let timing = context.timing.lock().unwrap().clone();
+                let mut response = Response::new(url, timing);
+                let reason = web_response.status.canonical_reason().unwrap_or("");
+                response.status = Some((web_response.status, reason.into()));
+                response.raw_status = Some((web_response.status.as_u16(), reason.into()));
+                response.headers = web_response.headers;
+                response.body = body;
+                *done_chan = Some((done_sender, done_receiver));
+                Some(response)
+            },
+            Ok(msg) => {
+                warn!("Unexpected {:?} instead of an intercepted response.", msg);
+                None
+            },
+            Err(_) => {
+                warn!("Failed to receive an intercepted response from embedder.");
+                None
+            },
+        }
+    }
+}
+
+/// Passes the answer of the embedder to the fetch that waits for it, and then receives the body
+/// of the response of the embedder, if it responds to the request itself.
+struct BodyReceiver {
+    answer_sender: Option<oneshot::Sender<WebResourceResponseMsg>>,
+    done_sender: Sender<Data>,
+    body: Arc<Mutex<ResponseBody>>,
+}
+
+impl BodyReceiver {
+    fn receive(&mut self, msg: Option<WebResourceResponseMsg>) {
+        if let Some(answer_sender) = self.answer_sender.take() {
+            if let Some(msg) = msg {
+                let _ = answer_sender.send(msg);
+            }
+            return;
+        }
+        match msg {
+            Some(WebResourceResponseMsg::Body(chunk)) => {
+                if let ResponseBody::Receiving(ref mut body) = *self.body.lock().unwrap() {
+                    body.extend_from_slice(&chunk);
+                    let _ = self.done_sender.send(Data::Payload(chunk));
+                }
+            },
+            Some(WebResourceResponseMsg::Done) => self.finish(Data::Done),
+            msg => {
+                warn!(
+                    "Unexpected {:?} in the body of an intercepted response.",
+                    msg
+                );
+                self.finish(Data::Cancelled);
+            },
+        }
+    }
+
+    /// Completes the body with what was received of it, unless it is already complete.
+    fn finish(&self, data: Data) {
+        let mut body = self.body.lock().unwrap();
+        let received = match *body {
+            ResponseBody::Receiving(ref mut received) => mem::replace(received, vec![]),
+            _ => return,
+        };
+        *body = ResponseBody::Done(received);
+        let _ = self.done_sender.send(data);
+    }
+}
+
+impl Drop for BodyReceiver {
+    /// The embedder hung up before it sent the whole body.
+    fn drop(&mut self) {
+        self.finish(Data::Cancelled);
+    }
+}
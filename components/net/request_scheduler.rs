@@ -0,0 +1,209 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Orders the use of connections by network fetches, so that requests with a higher
+//! priority are sent first when several of them wait for a connection to the same host.
+//!
+//! A fetch waits for a connection before it starts rather than on the thread that runs it, so
+//! that the fetches that wait, however many they are, never hold up the thread pool of the
+//! resource threads.
+
+use crate::resource_thread::CoreResourceThreadPool;
+use net_traits::request::{Destination, Initiator, Request, RequestPriority};
+use servo_url::ImmutableOrigin;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, Weak};
+
+/// The most requests to a host that are sent or receive their response at the same time,
+/// which is the number of HTTP/1.1 connections to a host that browsers open.
+pub const MAX_CONNECTIONS_PER_HOST: usize = 6;
+
+/// A request that waits for a connection, and what to do with it once it has one.
+struct Waiting {
+    rank: u8,
+    ticket: u64,
+    request: Request,
+    start: Box<dyn FnOnce(Request, ConnectionSlot) + Send>,
+}
+
+#[derive(Default)]
+struct HostState {
+    /// The number of requests that use a connection to the host.
+    active: usize,
+    /// The requests that wait for a connection to the host.
+    waiting: Vec<Waiting>,
+}
+
+#[derive(Default)]
+struct SchedulerState {
+    next_ticket: u64,
+    hosts: HashMap<ImmutableOrigin, HostState>,
+}
+
+/// Hands out the connections to each host to the waiting request that ranks first, by its
+/// priority and then by the order in which it asked for one.
+#[derive(Clone, Default)]
+pub struct RequestScheduler {
+    state: Arc<Mutex<SchedulerState>>,
+}
+
+impl RequestScheduler {
+    pub fn new() -> RequestScheduler {
+        Default::default()
+    }
+
+    /// Starts the request with a slot for a connection to the host of its current URL, right
+    /// away if one is free, and otherwise on the thread that releases the slot it gets.
+    pub fn schedule<F>(&self, request: Request, start: F)
+    where
+        F: FnOnce(Request, ConnectionSlot) + Send + 'static,
+    {
+        let host = request.current_url().origin();
+        let mut state = self.state.lock().unwrap();
+        let ticket = state.next_ticket;
+        state.next_ticket += 1;
+        let host_state = state.hosts.entry(host.clone()).or_default();
+        if host_state.active < MAX_CONNECTIONS_PER_HOST && host_state.waiting.is_empty() {
+            host_state.active += 1;
+            drop(state);
+            return start(
+                request,
+                ConnectionSlot {
+                    scheduler: self.clone(),
+                    host,
+                },
+            );
+        }
+        host_state.waiting.push(Waiting {
+            rank: rank(&request),
+            ticket,
+            request,
+            start: Box::new(start),
+        });
+    }
+
+    /// Returns a slot if the request can use a connection to its host without waiting.
+    pub fn try_acquire(&self, request: &Request) -> Option<ConnectionSlot> {
+        let host = request.current_url().origin();
+        let mut state = self.state.lock().unwrap();
+        let host_state = state.hosts.entry(host.clone()).or_default();
+        if host_state.active >= MAX_CONNECTIONS_PER_HOST || !host_state.waiting.is_empty() {
+            return None;
+        }
+        host_state.active += 1;
+        Some(ConnectionSlot {
+            scheduler: self.clone(),
+            host,
+        })
+    }
+
+    /// Passes the slot of a request that no longer uses its connection on to the waiting
+    /// request that ranks first, if any.
+    fn release(&self, host: &ImmutableOrigin) {
+        let mut state = self.state.lock().unwrap();
+        let next = {
+            let host_state = match state.hosts.get_mut(host) {
+                Some(host_state) => host_state,
+                None => return,
+            };
+            let first = host_state
+                .waiting
+                .iter()
+                .enumerate()
+                .min_by_key(|&(_, waiting)| (waiting.rank, waiting.ticket))
+                .map(|(index, _)| index);
+            match first {
+                Some(index) => Some(host_state.waiting.remove(index)),
+                None => {
+                    host_state.active -= 1;
+                    None
+                },
+            }
+        };
+        let next = match next {
+            Some(next) => next,
+            None => {
+                if state.hosts[host].active == 0 {
+                    state.hosts.remove(host);
+                }
+                return;
+            },
+        };
+        drop(state);
+        (next.start)(
+            next.request,
+            ConnectionSlot {
+                scheduler: self.clone(),
+                host: host.clone(),
+            },
+        );
+    }
+}
+
+/// The permission of a request to use a connection to a host, which is passed on to the
+/// next waiting request when it is dropped.
+pub struct ConnectionSlot {
+    scheduler: RequestScheduler,
+    host: ImmutableOrigin,
+}
+
+impl ConnectionSlot {
+    pub fn host(&self) -> &ImmutableOrigin {
+        &self.host
+    }
+}
+
+impl Drop for ConnectionSlot {
+    fn drop(&mut self) {
+        self.scheduler.release(&self.host);
+    }
+}
+
+/// Runs the fetch of the request on the thread pool once it may use a connection to the host
+/// of its URL, with the slot that it holds until its network fetch takes it. Only the fetches
+/// of HTTP(S) URLs wait for a connection.
+pub fn spawn_fetch<F>(
+    scheduler: &RequestScheduler,
+    thread_pool: &Weak<CoreResourceThreadPool>,
+    request: Request,
+    fetch: F,
+) where
+    F: FnOnce(Request, Option<ConnectionSlot>) + Send + 'static,
+{
+    let thread_pool = thread_pool.clone();
+    if !matches!(request.current_url().scheme(), "http" | "https") {
+        return spawn(&thread_pool, move || fetch(request, None));
+    }
+    scheduler.schedule(request, move |request, slot| {
+        spawn(&thread_pool, move || fetch(request, Some(slot)))
+    });
+}
+
+/// Runs the work on the thread pool of the resource threads, unless they are shutting down.
+fn spawn<F: FnOnce() + Send + 'static>(thread_pool: &Weak<CoreResourceThreadPool>, work: F) {
+    if let Some(thread_pool) = thread_pool.upgrade() {
+        thread_pool.spawn(work);
+    }
+}
+
+/// The rank of a request, where requests that rank lower are sent first. Documents, and the
+/// style sheets and fonts that block rendering, rank before scripts and fetches, which rank
+/// before images and media. The priority of a request moves it up or down by one. Prefetches
+/// are for later navigations, and are only sent when no other request waits.
+/// <https://fetch.spec.whatwg.org/#request-internal-priority>
+fn rank(request: &Request) -> u8 {
+    if request.initiator == Initiator::Prefetch {
+        return 5;
+    }
+    let rank = match request.destination {
+        Destination::Document | Destination::Style | Destination::Font => 1,
+        Destination::Script | Destination::None => 2,
+        _ => 3,
+    };
+    match request.priority {
+        RequestPriority::High => rank - 1,
+        RequestPriority::Low => rank + 1,
+        RequestPriority::Auto => rank,
+    }
+}
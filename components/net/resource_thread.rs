@@ -4,27 +4,36 @@
 
 //! A thread that takes a URL and streams back the binary data.
 
-use crate::connector::{create_http_client, create_tls_config, ALPN_H2_H1};
+use crate::client_certificates::ClientCertificates;
+use crate::connector::{create_http_client, create_tls_config, resolve_host_ahead};
+use crate::connector::{CertificateExceptions, ALPN_H2_H1};
 use crate::cookie;
 use crate::cookie_storage::CookieStorage;
+use crate::doh::DnsResolver;
+use crate::download_manager::DownloadManager;
 use crate::fetch::cors_cache::CorsCache;
 use crate::fetch::methods::{fetch, CancellationListener, FetchContext};
 use crate::filemanager_thread::FileManager;
 use crate::hsts::HstsList;
 use crate::http_cache::HttpCache;
 use crate::http_loader::{http_redirect_fetch, HttpState, HANDLE};
+use crate::preload_cache::PreloadCache;
+use crate::protocol_handlers::{self, ProtocolHandlerList};
+use crate::request_interceptor::RequestInterceptor;
+use crate::request_scheduler::{spawn_fetch, ConnectionSlot, RequestScheduler};
 use crate::storage_thread::StorageThreadFactory;
+use crate::webauthn::{self, PlatformAuthenticator};
 use crate::websocket_loader;
 use crossbeam_channel::Sender;
 use devtools_traits::DevtoolsControlMsg;
 use embedder_traits::resources::{self, Resource};
-use embedder_traits::EmbedderProxy;
+use embedder_traits::{EmbedderMsg, EmbedderProxy, ProtocolRegistry};
 use hyper_serde::Serde;
 use ipc_channel::ipc::{self, IpcReceiver, IpcReceiverSet, IpcSender};
 use malloc_size_of::{MallocSizeOf, MallocSizeOfOps};
 use net_traits::blob_url_store::parse_blob_url;
 use net_traits::filemanager_thread::FileTokenCheck;
-use net_traits::request::{Destination, RequestBuilder};
+use net_traits::request::{Destination, Request, RequestBuilder};
 use net_traits::response::{Response, ResponseInit};
 use net_traits::storage_thread::StorageThreadMsg;
 use net_traits::DiscardFetch;
@@ -59,6 +68,7 @@ pub fn new_resource_threads(
     embedder_proxy: EmbedderProxy,
     config_dir: Option<PathBuf>,
     certificate_path: Option<String>,
+    protocols: ProtocolRegistry,
 ) -> (ResourceThreads, ResourceThreads) {
     let (public_core, private_core) = new_core_resource_thread(
         user_agent,
@@ -68,6 +78,7 @@ pub fn new_resource_threads(
         embedder_proxy,
         config_dir.clone(),
         certificate_path,
+        protocols,
     );
     let storage: IpcSender<StorageThreadMsg> = StorageThreadFactory::new(config_dir);
     (
@@ -85,6 +96,7 @@ pub fn new_core_resource_thread(
     embedder_proxy: EmbedderProxy,
     config_dir: Option<PathBuf>,
     certificate_path: Option<String>,
+    protocols: ProtocolRegistry,
 ) -> (CoreResourceThread, CoreResourceThread) {
     let (public_setup_chan, public_setup_port) = ipc::channel().unwrap();
     let (private_setup_chan, private_setup_port) = ipc::channel().unwrap();
@@ -97,14 +109,20 @@ pub fn new_core_resource_thread(
                 user_agent,
                 devtools_chan,
                 time_profiler_chan,
-                embedder_proxy,
+                mem_profiler_chan.clone(),
+                embedder_proxy.clone(),
                 certificate_path.clone(),
+                config_dir.as_ref().map(|dir| dir.join("file_systems")),
             );
 
             let mut channel_manager = ResourceChannelManager {
                 resource_manager,
+                authenticator: Arc::new(Mutex::new(PlatformAuthenticator::new(config_dir.clone()))),
                 config_dir,
                 certificate_path,
+                downloads: DownloadManager::new(embedder_proxy.clone()),
+                embedder_proxy,
+                protocols,
             };
 
             mem_profiler_chan.run_with_memory_reporting(
@@ -120,22 +138,32 @@ pub fn new_core_resource_thread(
 
 struct ResourceChannelManager {
     resource_manager: CoreResourceManager,
+    authenticator: Arc<Mutex<PlatformAuthenticator>>,
     config_dir: Option<PathBuf>,
     certificate_path: Option<String>,
+    downloads: DownloadManager,
+    embedder_proxy: EmbedderProxy,
+    protocols: ProtocolRegistry,
 }
 
 fn create_http_states(
     config_dir: Option<&Path>,
     certificate_path: Option<String>,
+    embedder_proxy: EmbedderProxy,
+    protocols: ProtocolRegistry,
 ) -> (Arc<HttpState>, Arc<HttpState>) {
     let mut hsts_list = HstsList::from_servo_preload();
     let mut auth_cache = AuthCache::new();
     let http_cache = HttpCache::new();
     let mut cookie_jar = CookieStorage::new(150);
+    let mut protocol_handlers = ProtocolHandlerList::new();
     if let Some(config_dir) = config_dir {
         read_json_from_file(&mut auth_cache, config_dir, "auth_cache.json");
-        read_json_from_file(&mut hsts_list, config_dir, "hsts_list.json");
+        let mut learned_hsts_list = HstsList::new();
+        read_json_from_file(&mut learned_hsts_list, config_dir, "hsts_list.json");
+        hsts_list.merge_learned_entries(learned_hsts_list);
         read_json_from_file(&mut cookie_jar, config_dir, "cookie_jar.json");
+        read_json_from_file(&mut protocol_handlers, config_dir, "protocol_handlers.json");
     }
 
     let certs = match certificate_path {
@@ -143,28 +171,61 @@ fn create_http_states(
         None => resources::read_string(Resource::SSLCertificates),
     };
 
+    // The addresses that are resolved over HTTPS are cached for all the resource threads.
+    let resolver = DnsResolver::new();
+
+    let certificate_exceptions = CertificateExceptions::new();
+    let client_certificates = ClientCertificates::with_identity_store(embedder_proxy.clone());
     let http_state = HttpState {
         hsts_list: RwLock::new(hsts_list),
         cookie_jar: RwLock::new(cookie_jar),
         auth_cache: RwLock::new(auth_cache),
         history_states: RwLock::new(HashMap::new()),
+        keepalive_bytes: Mutex::new(HashMap::new()),
+        network_conditions: RwLock::new(HashMap::new()),
+        request_scheduler: RequestScheduler::new(),
+        preload_cache: PreloadCache::new(),
         http_cache: RwLock::new(http_cache),
         http_cache_state: Mutex::new(HashMap::new()),
+        certificate_exceptions: certificate_exceptions.clone(),
+        client_certificates: client_certificates.clone(),
+        request_interceptor: RequestInterceptor::with_embedder(embedder_proxy.clone()),
+        protocols: protocols.clone(),
+        protocol_handlers: RwLock::new(protocol_handlers),
+        resolver: resolver.clone(),
         client: create_http_client(
             create_tls_config(&certs, ALPN_H2_H1),
+            certificate_exceptions,
+            client_certificates,
+            resolver.clone(),
             HANDLE.lock().unwrap().executor(),
         ),
     };
 
+    let certificate_exceptions = CertificateExceptions::new();
+    let client_certificates = ClientCertificates::with_identity_store(embedder_proxy.clone());
     let private_http_state = HttpState {
         hsts_list: RwLock::new(HstsList::from_servo_preload()),
         cookie_jar: RwLock::new(CookieStorage::new(150)),
         auth_cache: RwLock::new(AuthCache::new()),
         history_states: RwLock::new(HashMap::new()),
+        keepalive_bytes: Mutex::new(HashMap::new()),
+        network_conditions: RwLock::new(HashMap::new()),
+        request_scheduler: RequestScheduler::new(),
+        preload_cache: PreloadCache::new(),
         http_cache: RwLock::new(HttpCache::new()),
         http_cache_state: Mutex::new(HashMap::new()),
+        certificate_exceptions: certificate_exceptions.clone(),
+        client_certificates: client_certificates.clone(),
+        request_interceptor: RequestInterceptor::with_embedder(embedder_proxy),
+        protocols,
+        protocol_handlers: RwLock::new(ProtocolHandlerList::new()),
+        resolver: resolver.clone(),
         client: create_http_client(
             create_tls_config(&certs, ALPN_H2_H1),
+            certificate_exceptions,
+            client_certificates,
+            resolver,
             HANDLE.lock().unwrap().executor(),
         ),
     };
@@ -183,6 +244,8 @@ impl ResourceChannelManager {
         let (public_http_state, private_http_state) = create_http_states(
             self.config_dir.as_ref().map(Deref::deref),
             self.certificate_path.clone(),
+            self.embedder_proxy.clone(),
+            self.protocols.clone(),
         );
 
         let mut rx_set = IpcReceiverSet::new().unwrap();
@@ -268,6 +331,7 @@ impl ResourceChannelManager {
                         .fetch(req_init, None, DiscardFetch, http_state, None)
                 },
             },
+            CoreResourceMsg::PrefetchDns(url) => resolve_host_ahead(&http_state.resolver, &url),
             CoreResourceMsg::DeleteCookies(request) => {
                 http_state
                     .cookie_jar
@@ -327,10 +391,80 @@ impl ResourceChannelManager {
                     history_states.remove(&history_state);
                 }
             },
+            CoreResourceMsg::ClearHttpCache => {
+                http_state.http_cache.write().unwrap().clear();
+            },
+            CoreResourceMsg::ReportCertificateError(
+                id,
+                pipeline_id,
+                top_level_browsing_context_id,
+            ) => {
+                let certificate_exceptions = &http_state.certificate_exceptions;
+                match certificate_exceptions.pending_error(id, pipeline_id) {
+                    Some(error) => self.embedder_proxy.send((
+                        Some(top_level_browsing_context_id),
+                        EmbedderMsg::CertificateError(error),
+                    )),
+                    None => warn!("Unknown certificate error {:?} reported", id),
+                }
+            },
+            CoreResourceMsg::AnswerCertificateError(id, pipeline_id, trusted) => {
+                http_state
+                    .certificate_exceptions
+                    .answer(id, pipeline_id, trusted);
+            },
+            CoreResourceMsg::SetNetworkConditions(pipeline_ids, conditions) => {
+                let mut network_conditions = http_state.network_conditions.write().unwrap();
+                for pipeline_id in pipeline_ids {
+                    match conditions {
+                        Some(conditions) => network_conditions.insert(pipeline_id, conditions),
+                        None => network_conditions.remove(&pipeline_id),
+                    };
+                }
+            },
+            CoreResourceMsg::RegisterProtocolHandler(
+                scheme,
+                url,
+                top_level_browsing_context_id,
+            ) => protocol_handlers::ask_to_register(
+                http_state,
+                &self.embedder_proxy,
+                top_level_browsing_context_id,
+                scheme,
+                url,
+            ),
+            CoreResourceMsg::UnregisterProtocolHandler(scheme, url) => http_state
+                .protocol_handlers
+                .write()
+                .unwrap()
+                .unregister(&scheme, &url),
+            CoreResourceMsg::Download(request, browsing_context_id) => self.downloads.start(
+                request,
+                browsing_context_id,
+                http_state,
+                &self.resource_manager,
+            ),
+            CoreResourceMsg::DownloadAction(id, action) => {
+                self.downloads
+                    .handle_action(id, action, &self.resource_manager)
+            },
             CoreResourceMsg::Synchronize(sender) => {
                 let _ = sender.send(());
             },
             CoreResourceMsg::ToFileManager(msg) => self.resource_manager.filemanager.handle(msg),
+            CoreResourceMsg::ToAuthenticator(msg, top_level_browsing_context_id) => {
+                // The user is asked first, which must not hold up the other messages.
+                let authenticator = self.authenticator.clone();
+                let embedder_proxy = self.embedder_proxy.clone();
+                self.resource_manager.thread_pool.spawn(move || {
+                    webauthn::handle(
+                        &authenticator,
+                        msg,
+                        &embedder_proxy,
+                        top_level_browsing_context_id,
+                    )
+                });
+            },
             CoreResourceMsg::Exit(sender) => {
                 if let Some(ref config_dir) = self.config_dir {
                     match http_state.auth_cache.read() {
@@ -344,9 +478,21 @@ impl ResourceChannelManager {
                         Err(_) => warn!("Error writing cookie jar to disk"),
                     }
                     match http_state.hsts_list.read() {
-                        Ok(hsts) => write_json_to_file(&*hsts, config_dir, "hsts_list.json"),
+                        Ok(hsts) => write_json_to_file(
+                            &hsts.learned_entries(),
+                            config_dir,
+                            "hsts_list.json",
+                        ),
                         Err(_) => warn!("Error writing hsts list to disk"),
                     }
+                    match http_state.protocol_handlers.read() {
+                        Ok(protocol_handlers) => write_json_to_file(
+                            &*protocol_handlers,
+                            config_dir,
+                            "protocol_handlers.json",
+                        ),
+                        Err(_) => warn!("Error writing protocol handlers to disk"),
+                    }
                 }
                 self.resource_manager.exit();
                 let _ = sender.send(());
@@ -435,6 +581,7 @@ pub struct CoreResourceManager {
     filemanager: FileManager,
     thread_pool: Arc<CoreResourceThreadPool>,
     certificate_path: Option<String>,
+    mem_profiler_chan: MemProfilerChan,
 }
 
 /// The state of the thread-pool used by CoreResource.
@@ -567,18 +714,29 @@ impl CoreResourceManager {
         user_agent: Cow<'static, str>,
         devtools_channel: Option<Sender<DevtoolsControlMsg>>,
         _profiler_chan: ProfilerChan,
+        mem_profiler_chan: MemProfilerChan,
         embedder_proxy: EmbedderProxy,
         certificate_path: Option<String>,
+        file_system_root: Option<PathBuf>,
     ) -> CoreResourceManager {
         let pool = CoreResourceThreadPool::new(16);
         let pool_handle = Arc::new(pool);
+        let filemanager = match file_system_root {
+            Some(root) => FileManager::with_file_system_root(
+                embedder_proxy,
+                Arc::downgrade(&pool_handle),
+                root,
+            ),
+            None => FileManager::new(embedder_proxy, Arc::downgrade(&pool_handle)),
+        };
         CoreResourceManager {
             user_agent: user_agent,
             devtools_chan: devtools_channel,
             swmanager_chan: None,
-            filemanager: FileManager::new(embedder_proxy, Arc::downgrade(&pool_handle)),
+            filemanager,
             thread_pool: pool_handle,
             certificate_path,
+            mem_profiler_chan,
         }
     }
 
@@ -605,7 +763,7 @@ impl CoreResourceManager {
         }
     }
 
-    fn fetch<Target: 'static + FetchTaskTarget + Send>(
+    pub(crate) fn fetch<Target: 'static + FetchTaskTarget + Send>(
         &self,
         request_builder: RequestBuilder,
         res_init_: Option<ResponseInit>,
@@ -617,13 +775,14 @@ impl CoreResourceManager {
         let ua = self.user_agent.clone();
         let dc = self.devtools_chan.clone();
         let filemanager = self.filemanager.clone();
+        let mem_profiler_chan = self.mem_profiler_chan.clone();
 
         let timing_type = match request_builder.destination {
             Destination::Document => ResourceTimingType::Navigation,
             _ => ResourceTimingType::Resource,
         };
 
-        let mut request = request_builder.build();
+        let request = request_builder.build();
         let url = request.current_url();
 
         // In the case of a valid blob URL, acquiring a token granting access to a file,
@@ -647,7 +806,10 @@ impl CoreResourceManager {
             _ => (FileTokenCheck::NotRequired, None),
         };
 
-        self.thread_pool.spawn(move || {
+        let thread_pool = Arc::downgrade(&self.thread_pool);
+        let scheduler = http_state.request_scheduler.clone();
+        let pool = thread_pool.clone();
+        let start = move |mut request: Request, connection_slot: Option<ConnectionSlot>| {
             // XXXManishearth: Check origin against pipeline id (also ensure that the mode is allowed)
             // todo load context / mimesniff in fetch
             // todo referrer policy?
@@ -660,6 +822,9 @@ impl CoreResourceManager {
                 file_token,
                 cancellation_listener: Arc::new(Mutex::new(CancellationListener::new(cancel_chan))),
                 timing: ServoArc::new(Mutex::new(ResourceFetchTiming::new(request.timing_type()))),
+                mem_profiler_chan: Some(mem_profiler_chan),
+                thread_pool,
+                connection_slot: Mutex::new(connection_slot),
             };
 
             match res_init_ {
@@ -684,7 +849,8 @@ impl CoreResourceManager {
                     .filemanager
                     .invalidate_token(&context.file_token, id);
             }
-        });
+        };
+        spawn_fetch(&scheduler, &pool, request, start);
     }
 
     fn websocket_connect(
@@ -0,0 +1,65 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use net::doh::{decode_response, encode_query, DohCache};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+#[test]
+fn test_doh_encodes_a_recursive_query_for_a_name() {
+    let query = encode_query("www.example.com.", 28).unwrap();
+    let mut expected = vec![0, 0, 1, 0, 0, 1, 0, 0, 0, 0, 0, 0];
+    expected.extend_from_slice(b"\x03www\x07example\x03com\x00");
+    expected.extend_from_slice(&[0, 28, 0, 1]);
+    assert_eq!(query, expected);
+
+    assert!(encode_query("www..example.com", 1).is_err());
+}
+
+#[test]
+fn test_doh_decodes_the_addresses_of_a_response() {
+    let mut response = vec![0, 0, 0x81, 0x80, 0, 1, 0, 3, 0, 0, 0, 0];
+    // The question.
+    response.extend_from_slice(b"\x03www\x07example\x03com\x00");
+    response.extend_from_slice(&[0, 1, 0, 1]);
+    // An alias of the name, which points at the question.
+    response.extend_from_slice(&[0xC0, 12, 0, 5, 0, 1, 0, 0, 1, 0, 0, 2, 0xC0, 16]);
+    // The addresses of the alias.
+    response.extend_from_slice(&[0xC0, 16, 0, 1, 0, 1, 0, 0, 0, 60, 0, 4, 93, 184, 216, 34]);
+    response.extend_from_slice(&[0xC0, 16, 0, 28, 0, 1, 0, 0, 0, 30, 0, 16]);
+    response.extend_from_slice(&Ipv6Addr::LOCALHOST.octets());
+
+    assert_eq!(
+        decode_response(&response).unwrap(),
+        vec![
+            (IpAddr::V4(Ipv4Addr::new(93, 184, 216, 34)), 60),
+            (IpAddr::V6(Ipv6Addr::LOCALHOST), 30),
+        ]
+    );
+
+    // A truncated response is invalid.
+    assert!(decode_response(&response[..response.len() - 1]).is_err());
+}
+
+#[test]
+fn test_doh_reports_failed_queries() {
+    // A name that does not exist has no addresses.
+    let no_name = [0, 0, 0x81, 0x83, 0, 0, 0, 0, 0, 0, 0, 0];
+    assert!(decode_response(&no_name).unwrap().is_empty());
+
+    let server_failure = [0, 0, 0x81, 0x82, 0, 0, 0, 0, 0, 0, 0, 0];
+    assert!(decode_response(&server_failure).is_err());
+}
+
+#[test]
+fn test_doh_cache_keeps_the_addresses_until_they_expire() {
+    let cache = DohCache::new();
+    let address = IpAddr::V4(Ipv4Addr::new(93, 184, 216, 34));
+    cache.insert("example.com", &[(address, 60)]);
+    assert_eq!(cache.get("example.com"), Some(vec![address]));
+    assert_eq!(cache.get("example.org"), None);
+
+    // Records that may not be cached are not.
+    cache.insert("example.org", &[(address, 60), (address, 0)]);
+    assert_eq!(cache.get("example.org"), None);
+}
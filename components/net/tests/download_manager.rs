@@ -0,0 +1,191 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::{create_embedder_proxy_and_receiver, make_server};
+use embedder_traits::{DownloadEvent, EmbedderMsg, ProtocolRegistry};
+use http::header::{HeaderValue, CONTENT_DISPOSITION};
+use hyper::{Body, Request as HyperRequest, Response as HyperResponse};
+use ipc_channel::ipc;
+use net::download_manager::content_disposition_file_name;
+use net::resource_thread::new_core_resource_thread;
+use net_traits::request::RequestBuilder;
+use net_traits::CoreResourceMsg;
+use profile_traits::mem::ProfilerChan as MemProfilerChan;
+use profile_traits::time::ProfilerChan;
+use servo_config::set_pref;
+use std::time::Duration;
+use std::{env, fs, process, thread};
+
+#[test]
+fn test_content_disposition_file_name() {
+    assert_eq!(
+        content_disposition_file_name("attachment; filename=\"report.txt\""),
+        Some("report.txt".to_owned())
+    );
+    assert_eq!(
+        content_disposition_file_name(
+            "attachment; filename=\"euro.txt\"; filename*=UTF-8''%E2%82%AC.txt"
+        ),
+        Some("€.txt".to_owned())
+    );
+    assert_eq!(content_disposition_file_name("attachment"), None);
+}
+
+#[test]
+fn test_download_saves_the_response_to_a_file() {
+    let handler = |_: HyperRequest<Body>, response: &mut HyperResponse<Body>| {
+        response.headers_mut().insert(
+            CONTENT_DISPOSITION,
+            HeaderValue::from_static("attachment; filename=\"report.txt\""),
+        );
+        *response.body_mut() = b"the report".to_vec().into();
+    };
+    let (server, url) = make_server(handler);
+    let directory = env::temp_dir().join(format!("servo-downloads-{}", process::id()));
+    fs::create_dir_all(&directory).unwrap();
+    set_pref!(
+        network.downloads.directory,
+        directory.to_str().unwrap().to_owned()
+    );
+
+    let (tx, _rx) = ipc::channel().unwrap();
+    let (mtx, _mrx) = ipc::channel().unwrap();
+    let (embedder_proxy, embedder_receiver) = create_embedder_proxy_and_receiver();
+    let (resource_thread, _private_resource_thread) = new_core_resource_thread(
+        "".into(),
+        None,
+        ProfilerChan(tx),
+        MemProfilerChan(mtx),
+        embedder_proxy,
+        None,
+        None,
+        ProtocolRegistry::new(),
+    );
+    resource_thread
+        .send(CoreResourceMsg::Download(RequestBuilder::new(url), None))
+        .unwrap();
+
+    let mut path = None;
+    loop {
+        match embedder_receiver.recv().unwrap().1 {
+            EmbedderMsg::AllowDownload(_, _, suggested_path, sender) => {
+                assert_eq!(suggested_path, directory.join("report.txt"));
+                sender.send(Some(suggested_path)).unwrap();
+            },
+            EmbedderMsg::Download(_, DownloadEvent::Started { path: started, .. }) => {
+                path = Some(started)
+            },
+            EmbedderMsg::Download(_, DownloadEvent::Finished) => break,
+            EmbedderMsg::Download(_, DownloadEvent::Failed(reason)) => panic!("{}", reason),
+            _ => {},
+        }
+    }
+    let path = path.unwrap();
+    assert_eq!(path, directory.join("report.txt"));
+    assert_eq!(fs::read(&path).unwrap(), b"the report");
+
+    let _ = fs::remove_dir_all(&directory);
+    let _ = server.close();
+}
+
+#[test]
+fn test_download_does_not_overwrite_an_existing_file() {
+    let handler = |_: HyperRequest<Body>, response: &mut HyperResponse<Body>| {
+        *response.body_mut() = b"the new report".to_vec().into();
+    };
+    let (server, url) = make_server(handler);
+    let directory = env::temp_dir().join(format!("servo-downloads-existing-{}", process::id()));
+    fs::create_dir_all(&directory).unwrap();
+    let existing_path = directory.join("report.txt");
+    fs::write(&existing_path, b"the report").unwrap();
+
+    let (tx, _rx) = ipc::channel().unwrap();
+    let (mtx, _mrx) = ipc::channel().unwrap();
+    let (embedder_proxy, embedder_receiver) = create_embedder_proxy_and_receiver();
+    let (resource_thread, _private_resource_thread) = new_core_resource_thread(
+        "".into(),
+        None,
+        ProfilerChan(tx),
+        MemProfilerChan(mtx),
+        embedder_proxy,
+        None,
+        None,
+        ProtocolRegistry::new(),
+    );
+    resource_thread
+        .send(CoreResourceMsg::Download(RequestBuilder::new(url), None))
+        .unwrap();
+
+    let mut path = None;
+    loop {
+        match embedder_receiver.recv().unwrap().1 {
+            EmbedderMsg::AllowDownload(_, _, _, sender) => {
+                sender.send(Some(existing_path.clone())).unwrap();
+            },
+            EmbedderMsg::Download(_, DownloadEvent::Started { path: started, .. }) => {
+                path = Some(started)
+            },
+            EmbedderMsg::Download(_, DownloadEvent::Finished) => break,
+            EmbedderMsg::Download(_, DownloadEvent::Failed(reason)) => panic!("{}", reason),
+            _ => {},
+        }
+    }
+    let path = path.unwrap();
+    assert_eq!(path, directory.join("report (1).txt"));
+    assert_eq!(fs::read(&path).unwrap(), b"the new report");
+    assert_eq!(fs::read(&existing_path).unwrap(), b"the report");
+
+    let _ = fs::remove_dir_all(&directory);
+    let _ = server.close();
+}
+
+#[test]
+fn test_download_is_received_while_the_embedder_chooses_where_to_save_it() {
+    let handler = |_: HyperRequest<Body>, response: &mut HyperResponse<Body>| {
+        *response.body_mut() = b"the late report".to_vec().into();
+    };
+    let (server, url) = make_server(handler);
+    let directory = env::temp_dir().join(format!("servo-downloads-late-{}", process::id()));
+    fs::create_dir_all(&directory).unwrap();
+
+    let (tx, _rx) = ipc::channel().unwrap();
+    let (mtx, _mrx) = ipc::channel().unwrap();
+    let (embedder_proxy, embedder_receiver) = create_embedder_proxy_and_receiver();
+    let (resource_thread, _private_resource_thread) = new_core_resource_thread(
+        "".into(),
+        None,
+        ProfilerChan(tx),
+        MemProfilerChan(mtx),
+        embedder_proxy,
+        None,
+        None,
+        ProtocolRegistry::new(),
+    );
+    resource_thread
+        .send(CoreResourceMsg::Download(RequestBuilder::new(url), None))
+        .unwrap();
+
+    let chosen_path = directory.join("late.txt");
+    let mut path = None;
+    loop {
+        match embedder_receiver.recv().unwrap().1 {
+            EmbedderMsg::AllowDownload(_, _, _, sender) => {
+                // The whole response is received before the user answers.
+                thread::sleep(Duration::from_millis(200));
+                sender.send(Some(chosen_path.clone())).unwrap();
+            },
+            EmbedderMsg::Download(_, DownloadEvent::Started { path: started, .. }) => {
+                path = Some(started)
+            },
+            EmbedderMsg::Download(_, DownloadEvent::Finished) => break,
+            EmbedderMsg::Download(_, DownloadEvent::Failed(reason)) => panic!("{}", reason),
+            _ => {},
+        }
+    }
+    assert_eq!(path, Some(chosen_path.clone()));
+    assert_eq!(fs::read(&chosen_path).unwrap(), b"the late report");
+
+    let _ = fs::remove_dir_all(&directory);
+    let _ = server.close();
+}
@@ -6,12 +6,14 @@ use crate::fetch_with_context;
 use crate::fetch_with_cors_cache;
 use crate::http_loader::{expect_devtools_http_request, expect_devtools_http_response};
 use crate::{
-    create_embedder_proxy, fetch, make_server, make_ssl_server, new_fetch_context,
-    DEFAULT_USER_AGENT,
+    create_embedder_proxy, create_embedder_proxy_and_receiver, fetch, make_server, make_ssl_server,
+    new_fetch_context, DEFAULT_USER_AGENT,
 };
 use crossbeam_channel::{unbounded, Sender};
 use devtools_traits::HttpRequest as DevtoolsHttpRequest;
 use devtools_traits::HttpResponse as DevtoolsHttpResponse;
+use embedder_traits::{EmbedderMsg, WebResourceResponse, WebResourceResponseMsg};
+use embedder_traits::{ProtocolHandler, ProtocolRegistry, ProtocolResponse, WebResourceRequest};
 use headers::StrictTransportSecurity;
 use headers::{AccessControlAllowCredentials, AccessControlAllowHeaders, AccessControlAllowOrigin};
 use headers::{AccessControlAllowMethods, AccessControlMaxAge, HeaderMapExt};
@@ -27,11 +29,13 @@ use net::fetch::cors_cache::CorsCache;
 use net::fetch::methods::{self, CancellationListener, FetchContext};
 use net::filemanager_thread::FileManager;
 use net::hsts::HstsEntry;
+use net::request_interceptor::RequestInterceptor;
 use net::resource_thread::CoreResourceThreadPool;
 use net::test::HttpState;
 use net_traits::filemanager_thread::FileTokenCheck;
 use net_traits::request::{
-    Destination, Origin, RedirectMode, Referrer, Request, RequestBuilder, RequestMode,
+    CredentialsMode, Destination, Origin, RedirectMode, Referrer, Request, RequestBuilder,
+    RequestMode,
 };
 use net_traits::response::{CacheState, Response, ResponseBody, ResponseType};
 use net_traits::{
@@ -39,12 +43,14 @@ use net_traits::{
     ResourceTimingType,
 };
 use servo_arc::Arc as ServoArc;
+use servo_config::set_pref;
 use servo_url::{ImmutableOrigin, ServoUrl};
 use std::fs;
 use std::iter::FromIterator;
 use std::path::Path;
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex, Weak};
+use std::thread;
 use std::time::{Duration, SystemTime};
 use uuid::Uuid;
 
@@ -109,6 +115,31 @@ fn test_fetch_response_body_matches_const_message() {
     };
 }
 
+#[test]
+fn test_fetch_response_timing_has_sizes_and_protocol() {
+    static MESSAGE: &'static [u8] = b"Hello World!";
+    let handler = move |_: HyperRequest<Body>, response: &mut HyperResponse<Body>| {
+        *response.body_mut() = MESSAGE.to_vec().into();
+    };
+    let (server, url) = make_server(handler);
+
+    let origin = Origin::Origin(url.origin());
+    let mut request = Request::new(url, Some(origin), None);
+    request.referrer = Referrer::NoReferrer;
+    let fetch_response = fetch(&mut request, None);
+    let _ = server.close();
+
+    assert!(!fetch_response.is_network_error());
+    let timing = fetch_response.get_resource_timing();
+    let timing = timing.lock().unwrap();
+    assert_eq!(timing.decoded_body_size, MESSAGE.len() as u64);
+    assert_eq!(timing.encoded_body_size, MESSAGE.len() as u64);
+    assert!(timing.transfer_size > timing.encoded_body_size);
+    assert_eq!(timing.next_hop_protocol.as_ref().unwrap(), "http/1.1");
+    assert!(timing.response_start >= timing.request_start);
+    assert!(timing.response_end >= timing.response_start);
+}
+
 #[test]
 fn test_fetch_aboutblank() {
     let url = ServoUrl::parse("about:blank").unwrap();
@@ -689,6 +720,9 @@ fn test_fetch_with_hsts() {
         timing: ServoArc::new(Mutex::new(ResourceFetchTiming::new(
             ResourceTimingType::Navigation,
         ))),
+        mem_profiler_chan: None,
+        thread_pool: Weak::new(),
+        connection_slot: Mutex::new(None),
     };
 
     {
@@ -742,6 +776,9 @@ fn test_load_adds_host_to_hsts_list_when_url_is_https() {
         timing: ServoArc::new(Mutex::new(ResourceFetchTiming::new(
             ResourceTimingType::Navigation,
         ))),
+        mem_profiler_chan: None,
+        thread_pool: Weak::new(),
+        connection_slot: Mutex::new(None),
     };
 
     let mut request = RequestBuilder::new(url.clone())
@@ -771,6 +808,51 @@ fn test_load_adds_host_to_hsts_list_when_url_is_https() {
         .is_host_secure(url.host_str().unwrap()));
 }
 
+#[test]
+fn test_fetch_reports_certificate_error_until_certificate_is_trusted() {
+    let handler = move |_: HyperRequest<Body>, response: &mut HyperResponse<Body>| {
+        *response.body_mut() = b"Yay!".to_vec().into();
+    };
+    let cert_path = Path::new("../../resources/self_signed_certificate_for_testing.crt")
+        .canonicalize()
+        .unwrap();
+    let key_path = Path::new("../../resources/privatekey_for_testing.key")
+        .canonicalize()
+        .unwrap();
+    let (server, mut url) = make_ssl_server(handler, cert_path, key_path);
+    url.as_mut_url().set_scheme("https").unwrap();
+
+    // The context only trusts the usual certificate authorities.
+    let mut context = new_fetch_context(None, None, None);
+
+    let mut request = RequestBuilder::new(url.clone())
+        .destination(Destination::Document)
+        .origin(url.clone().origin())
+        .pipeline_id(Some(TEST_PIPELINE_ID))
+        .build();
+    let response = fetch_with_context(&mut request, &mut context);
+    let error = match response.get_network_error() {
+        Some(&NetworkError::SslValidation(_, ref error)) => error.clone(),
+        error => panic!("Unexpected network error {:?}", error),
+    };
+    assert_eq!(error.host, "localhost");
+
+    context
+        .state
+        .certificate_exceptions
+        .answer(error.id, TEST_PIPELINE_ID, true);
+    let mut request = RequestBuilder::new(url.clone())
+        .destination(Destination::Document)
+        .origin(url.clone().origin())
+        .pipeline_id(Some(TEST_PIPELINE_ID))
+        .build();
+    let response = fetch_with_context(&mut request, &mut context);
+
+    let _ = server.close();
+
+    assert!(!response.is_network_error());
+}
+
 #[test]
 fn test_fetch_with_sri_network_error() {
     static MESSAGE: &'static [u8] = b"alert('Hello, Network Error');";
@@ -819,6 +901,97 @@ fn test_fetch_with_sri_sucess() {
     assert_eq!(response_is_done(&response), true);
 }
 
+#[test]
+fn test_fetch_uses_preloaded_response() {
+    static MESSAGE: &'static [u8] = b"preloaded";
+    let counter = Arc::new(AtomicUsize::new(0));
+    let handler_counter = counter.clone();
+    let handler = move |_: HyperRequest<Body>, response: &mut HyperResponse<Body>| {
+        handler_counter.fetch_add(1, Ordering::SeqCst);
+        response
+            .headers_mut()
+            .typed_insert(CacheControl::new().with_no_store());
+        *response.body_mut() = MESSAGE.to_vec().into();
+    };
+    let (server, url) = make_server(handler);
+
+    let origin = Origin::Origin(url.origin());
+    let mut request = Request::new(url, Some(origin), Some(TEST_PIPELINE_ID));
+    request.referrer = Referrer::NoReferrer;
+    request.destination = Destination::Script;
+    let mut preload_request = request.clone();
+    preload_request.preload = true;
+
+    let mut context = new_fetch_context(None, None, None);
+    let preload_response = fetch_with_context(&mut preload_request, &mut context);
+    let response = fetch_with_context(&mut request, &mut context);
+    let response_again = fetch_with_context(&mut request.clone(), &mut context);
+    let _ = server.close();
+
+    assert!(!preload_response.is_network_error());
+    match *response.body.lock().unwrap() {
+        ResponseBody::Done(ref body) => assert_eq!(&**body, MESSAGE),
+        _ => panic!(),
+    };
+    assert!(!response_again.is_network_error());
+    // The preloaded response is used once, after which the resource is fetched again.
+    assert_eq!(counter.load(Ordering::SeqCst), 2);
+}
+
+#[test]
+fn test_fetch_preloads_link_header_of_document() {
+    static MESSAGE: &'static [u8] = b"preloaded by a hint";
+    let counter = Arc::new(AtomicUsize::new(0));
+    let handler_counter = counter.clone();
+    let (hit_sender, hit_receiver) = unbounded();
+    let handler = move |_: HyperRequest<Body>, response: &mut HyperResponse<Body>| {
+        handler_counter.fetch_add(1, Ordering::SeqCst);
+        let _ = hit_sender.send(());
+        response
+            .headers_mut()
+            .typed_insert(CacheControl::new().with_no_store());
+        *response.body_mut() = MESSAGE.to_vec().into();
+    };
+    let (resource_server, resource_url) = make_server(handler);
+
+    let link = format!("<{}>; rel=preload; as=script", resource_url);
+    let handler = move |_: HyperRequest<Body>, response: &mut HyperResponse<Body>| {
+        response
+            .headers_mut()
+            .insert(header::LINK, HeaderValue::from_str(&link).unwrap());
+        *response.body_mut() = b"<script src=resource></script>".to_vec().into();
+    };
+    let (document_server, document_url) = make_server(handler);
+
+    let origin = Origin::Origin(document_url.origin());
+    let mut document_request = Request::new(
+        document_url.clone(),
+        Some(origin.clone()),
+        Some(TEST_PIPELINE_ID),
+    );
+    document_request.referrer = Referrer::NoReferrer;
+    document_request.destination = Destination::Document;
+    let mut context = new_fetch_context(None, None, None);
+    let document_response = fetch_with_context(&mut document_request, &mut context);
+    assert!(!document_response.is_network_error());
+
+    // The preload is underway once the server receives its request.
+    hit_receiver.recv_timeout(Duration::from_secs(5)).unwrap();
+    let mut request = Request::new(resource_url, Some(origin), Some(TEST_PIPELINE_ID));
+    request.referrer = Referrer::ReferrerUrl(document_url);
+    request.destination = Destination::Script;
+    request.credentials_mode = CredentialsMode::Include;
+    let response = fetch_with_context(&mut request, &mut context);
+    let _ = resource_server.close();
+    let _ = document_server.close();
+
+    match *response.body.lock().unwrap() {
+        ResponseBody::Done(ref body) => assert_eq!(&**body, MESSAGE),
+        _ => panic!(),
+    };
+    assert_eq!(counter.load(Ordering::SeqCst), 1);
+}
+
 /// `fetch` should return a network error if there is a header `X-Content-Type-Options: nosniff`
 #[test]
 fn test_fetch_blocked_nosniff() {
@@ -1197,3 +1370,177 @@ fn test_fetch_with_devtools() {
     assert_eq!(devhttprequest, httprequest);
     assert_eq!(devhttpresponse, httpresponse);
 }
+
+fn fetch_body(url: ServoUrl, context: &mut FetchContext) -> Option<Vec<u8>> {
+    let origin = Origin::Origin(url.origin());
+    let mut request = Request::new(url, Some(origin), None);
+    request.referrer = Referrer::NoReferrer;
+    let response = fetch_with_context(&mut request, context);
+    if response.is_network_error() {
+        return None;
+    }
+    let body = response.body.lock().unwrap();
+    match *body {
+        ResponseBody::Done(ref body) => Some(body.clone()),
+        _ => panic!(),
+    }
+}
+
+#[test]
+fn test_fetch_lets_the_embedder_intercept_requests() {
+    static MESSAGE: &'static [u8] = b"from the network";
+    let handler = move |_: HyperRequest<Body>, response: &mut HyperResponse<Body>| {
+        *response.body_mut() = MESSAGE.to_vec().into();
+    };
+    let (server, url) = make_server(handler);
+
+    set_pref!(network.request_interception.enabled, true);
+    let (embedder_proxy, embedder_receiver) = create_embedder_proxy_and_receiver();
+    let mut context = new_fetch_context(None, None, None);
+    Arc::get_mut(&mut context.state)
+        .unwrap()
+        .request_interceptor = RequestInterceptor::with_embedder(embedder_proxy);
+
+    let network_url = url.clone();
+    thread::spawn(move || {
+        while let Ok((_, msg)) = embedder_receiver.recv() {
+            let (request, sender) = match msg {
+                EmbedderMsg::WebResourceRequested(request, sender) => (request, sender),
+                _ => continue,
+            };
+            match request.url.path() {
+                "/blocked" => sender.send(WebResourceResponseMsg::Block).unwrap(),
+                "/fulfilled" => {
+                    let response = WebResourceResponse {
+                        status: StatusCode::OK,
+                        headers: HeaderMap::new(),
+                    };
+                    sender
+                        .send(WebResourceResponseMsg::Start(response))
+                        .unwrap();
+                    let chunks: [&[u8]; 2] = [b"from the ", b"embedder"];
+                    for chunk in &chunks {
+                        let msg = WebResourceResponseMsg::Body(chunk.to_vec());
+                        sender.send(msg).unwrap();
+                    }
+                    sender.send(WebResourceResponseMsg::Done).unwrap();
+                },
+                "/shared" => {
+                    let mut headers = HeaderMap::new();
+                    headers.typed_insert(AccessControlAllowOrigin::ANY);
+                    let response = WebResourceResponse {
+                        status: StatusCode::OK,
+                        headers,
+                    };
+                    sender
+                        .send(WebResourceResponseMsg::Start(response))
+                        .unwrap();
+                    sender.send(WebResourceResponseMsg::Done).unwrap();
+                },
+                "/rewritten" => {
+                    let mut rewritten = request.clone();
+                    rewritten.url = network_url.clone();
+                    sender
+                        .send(WebResourceResponseMsg::Rewrite(rewritten))
+                        .unwrap();
+                },
+                _ => sender.send(WebResourceResponseMsg::Continue).unwrap(),
+            }
+        }
+    });
+
+    let body = fetch_body(url.clone(), &mut context);
+    assert_eq!(body.as_ref().map(|body| &**body), Some(MESSAGE));
+    let body = fetch_body(url.join("/rewritten").unwrap(), &mut context);
+    assert_eq!(body.as_ref().map(|body| &**body), Some(MESSAGE));
+    let body = fetch_body(url.join("/fulfilled").unwrap(), &mut context);
+    assert_eq!(
+        body.as_ref().map(|body| &**body),
+        Some(&b"from the embedder"[..])
+    );
+    assert_eq!(
+        fetch_body(url.join("/blocked").unwrap(), &mut context),
+        None
+    );
+
+    // The responses of the embedder are filtered like the ones from the network.
+    let other_origin = ServoUrl::parse("https://example.com").unwrap().origin();
+    let mut fetch = |path: &str, mode: RequestMode| {
+        let url = url.join(path).unwrap();
+        let mut request = Request::new(url, Some(Origin::Origin(other_origin.clone())), None);
+        request.mode = mode;
+        request.referrer = Referrer::NoReferrer;
+        fetch_with_context(&mut request, &mut context)
+    };
+    assert!(fetch("/fulfilled", RequestMode::CorsMode).is_network_error());
+    let response = fetch("/fulfilled", RequestMode::NoCors);
+    assert_eq!(response.response_type, ResponseType::Opaque);
+    assert_eq!(*response.body.lock().unwrap(), ResponseBody::Empty);
+    let response = fetch("/shared", RequestMode::CorsMode);
+    assert_eq!(response.response_type, ResponseType::Cors);
+
+    let _ = server.close();
+}
+
+struct PathProtocolHandler {
+    fetchable: bool,
+}
+
+impl ProtocolHandler for PathProtocolHandler {
+    fn load(&self, request: &WebResourceRequest) -> Result<ProtocolResponse, String> {
+        if request.url.path() == "/missing" {
+            return Err("Not found".into());
+        }
+        Ok(ProtocolResponse {
+            status: StatusCode::OK,
+            headers: HeaderMap::new(),
+            body: request.url.path().as_bytes().to_vec(),
+        })
+    }
+
+    fn is_fetchable(&self) -> bool {
+        self.fetchable
+    }
+}
+
+#[test]
+fn test_fetch_custom_scheme() {
+    let mut registry = ProtocolRegistry::new();
+    let handler = |fetchable| Box::new(PathProtocolHandler { fetchable });
+    registry.register("app", handler(false)).unwrap();
+    registry.register("shared", handler(true)).unwrap();
+    assert!(registry.register("App", handler(true)).is_err());
+    assert!(registry.register("https", handler(true)).is_err());
+    assert!(registry.register("not a scheme", handler(true)).is_err());
+
+    let mut context = new_fetch_context(None, None, None);
+    Arc::get_mut(&mut context.state).unwrap().protocols = registry;
+
+    let origin = Origin::Origin(ServoUrl::parse("https://example.com").unwrap().origin());
+    let mut fetch = |url: &str, mode: RequestMode| {
+        let mut request = Request::new(ServoUrl::parse(url).unwrap(), Some(origin.clone()), None);
+        request.mode = mode;
+        request.referrer = Referrer::NoReferrer;
+        fetch_with_context(&mut request, &mut context)
+    };
+
+    // Like file URLs, the URLs of the scheme have opaque origins, so their responses are
+    // opaque-filtered.
+    let response = fetch("app://resources/index.html", RequestMode::NoCors);
+    assert_eq!(response.response_type, ResponseType::Opaque);
+    let actual_response = response.actual_response();
+    assert_eq!(
+        *actual_response.body.lock().unwrap(),
+        ResponseBody::Done(b"/index.html".to_vec())
+    );
+    assert!(fetch("app://resources/missing", RequestMode::NoCors).is_network_error());
+
+    // Only the schemes whose handler allows it can be read by other origins.
+    assert!(fetch("app://resources/index.html", RequestMode::CorsMode).is_network_error());
+    let response = fetch("shared://resources/index.html", RequestMode::CorsMode);
+    assert_eq!(response.response_type, ResponseType::Basic);
+    assert_eq!(
+        *response.body.lock().unwrap(),
+        ResponseBody::Done(b"/index.html".to_vec())
+    );
+}
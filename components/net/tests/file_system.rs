@@ -0,0 +1,170 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use net::file_system::{is_valid_name, FileSystemStore};
+use net_traits::filemanager_thread::{FileManagerThreadError, FileSystemAccessError};
+use net_traits::filemanager_thread::{FileSystemEntryKind, FileSystemLocator};
+use net_traits::filemanager_thread::{SyncAccessOperation, SyncAccessReply};
+use std::env;
+use uuid::Uuid;
+
+fn store() -> FileSystemStore {
+    FileSystemStore::new(None)
+}
+
+fn locator(names: &[&str]) -> FileSystemLocator {
+    FileSystemLocator::Private(
+        "https://example.com".to_owned(),
+        names.iter().map(|name| name.to_string()).collect(),
+    )
+}
+
+fn access_error(error: FileSystemAccessError) -> FileManagerThreadError {
+    FileManagerThreadError::FileSystemAccessError(error)
+}
+
+#[test]
+fn test_valid_names() {
+    assert!(is_valid_name("file.txt"));
+    assert!(!is_valid_name(""));
+    assert!(!is_valid_name(".."));
+    assert!(!is_valid_name("a/b"));
+    assert!(!is_valid_name("a\\b"));
+    assert!(!is_valid_name("C:file"));
+    assert!(!is_valid_name("file:stream"));
+}
+
+#[cfg(windows)]
+#[test]
+fn test_valid_windows_names() {
+    assert!(!is_valid_name("a<b"));
+    assert!(!is_valid_name("a?"));
+    assert!(!is_valid_name("file."));
+    assert!(!is_valid_name("NUL"));
+    assert!(!is_valid_name("com1.txt"));
+    assert!(is_valid_name("console.txt"));
+}
+
+#[test]
+fn test_get_and_remove_entries() {
+    let store = store();
+    let dir = locator(&["dir"]);
+    let file = locator(&["dir", "file"]);
+
+    assert_eq!(
+        store.get_entry(&dir, FileSystemEntryKind::Directory, false),
+        Err(access_error(FileSystemAccessError::NotFound))
+    );
+    assert_eq!(
+        store.get_entry(&file, FileSystemEntryKind::File, true),
+        Err(access_error(FileSystemAccessError::NotFound))
+    );
+    assert_eq!(
+        store.get_entry(&dir, FileSystemEntryKind::Directory, true),
+        Ok(())
+    );
+    assert_eq!(
+        store.get_entry(&file, FileSystemEntryKind::File, true),
+        Ok(())
+    );
+    assert_eq!(
+        store.get_entry(&file, FileSystemEntryKind::Directory, false),
+        Err(access_error(FileSystemAccessError::TypeMismatch))
+    );
+
+    assert_eq!(
+        store.remove_entry(&dir, false),
+        Err(access_error(FileSystemAccessError::InvalidModification))
+    );
+    assert_eq!(store.remove_entry(&dir, true), Ok(()));
+    assert_eq!(
+        store.get_entry(&file, FileSystemEntryKind::File, false),
+        Err(access_error(FileSystemAccessError::NotFound))
+    );
+}
+
+#[test]
+fn test_sync_access() {
+    let store = store();
+    let file = locator(&["file"]);
+    assert_eq!(
+        store.get_entry(&file, FileSystemEntryKind::File, true),
+        Ok(())
+    );
+
+    let id = store.open_sync_access(&file).unwrap();
+    assert_eq!(
+        store.open_sync_access(&file),
+        Err(access_error(FileSystemAccessError::NoModificationAllowed))
+    );
+    assert_eq!(
+        store.remove_entry(&file, false),
+        Err(access_error(FileSystemAccessError::NoModificationAllowed))
+    );
+
+    assert_eq!(
+        store.sync_access(&id, SyncAccessOperation::Write(2, b"servo".to_vec())),
+        Ok(SyncAccessReply::Written(5))
+    );
+    assert_eq!(
+        store.sync_access(&id, SyncAccessOperation::GetSize),
+        Ok(SyncAccessReply::Size(7))
+    );
+    assert_eq!(
+        store.sync_access(&id, SyncAccessOperation::Truncate(4)),
+        Ok(SyncAccessReply::Done)
+    );
+    assert_eq!(
+        store.sync_access(&id, SyncAccessOperation::Read(0, 10)),
+        Ok(SyncAccessReply::Read(b"\0\0se".to_vec()))
+    );
+    assert_eq!(
+        store.sync_access(&id, SyncAccessOperation::Close),
+        Ok(SyncAccessReply::Done)
+    );
+
+    assert_eq!(
+        store.sync_access(&id, SyncAccessOperation::GetSize),
+        Err(access_error(FileSystemAccessError::NotFound))
+    );
+    assert_eq!(store.remove_entry(&file, false), Ok(()));
+}
+
+#[test]
+fn test_picked_files_are_forgotten() {
+    let store = store();
+    let path = env::temp_dir().join("picked.txt");
+    let id = store.add_picked_file(path.clone());
+    assert_eq!(store.path(&FileSystemLocator::Picked(id)), Ok(path));
+
+    store.forget_picked_file(&id);
+    assert_eq!(
+        store.path(&FileSystemLocator::Picked(id)),
+        Err(access_error(FileSystemAccessError::NotFound))
+    );
+}
+
+#[test]
+fn test_names_stay_in_the_file_system_of_the_origin() {
+    let store = store();
+    for name in &["C:file", "..", "a/../../b", "/etc"] {
+        assert_eq!(
+            store.path(&locator(&[*name])).err(),
+            Some(access_error(FileSystemAccessError::InvalidName))
+        );
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn test_file_systems_are_private() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let root = env::temp_dir().join(format!("servo-file-systems-{}", Uuid::new_v4()));
+    let store = FileSystemStore::new(Some(root.clone()));
+    assert!(store.path(&locator(&[])).is_ok());
+    let mode = root.metadata().unwrap().permissions().mode();
+    assert_eq!(mode & 0o777, 0o700);
+    let _ = std::fs::remove_dir_all(&root);
+}
@@ -7,7 +7,7 @@ use embedder_traits::FilterPattern;
 use ipc_channel::ipc;
 use net::filemanager_thread::FileManager;
 use net::resource_thread::CoreResourceThreadPool;
-use net_traits::blob_url_store::BlobURLStoreError;
+use net_traits::blob_url_store::{BlobBuf, BlobURLStoreError, MAX_MEMORY_BLOB_SIZE};
 use net_traits::filemanager_thread::{
     FileManagerThreadError, FileManagerThreadMsg, ReadFileProgress,
 };
@@ -16,6 +16,9 @@ use std::fs::File;
 use std::io::Read;
 use std::path::PathBuf;
 use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+use uuid::Uuid;
 
 #[test]
 fn test_filemanager() {
@@ -132,3 +135,87 @@ fn test_filemanager() {
         }
     }
 }
+
+#[test]
+fn test_filemanager_spills_large_blobs_to_disk() {
+    let pool = CoreResourceThreadPool::new(1);
+    let pool_handle = Arc::new(pool);
+    let filemanager = FileManager::new(create_embedder_proxy(), Arc::downgrade(&pool_handle));
+    let origin = "http://test.com".to_string();
+
+    let bytes: Vec<u8> = (0..MAX_MEMORY_BLOB_SIZE + 1).map(|i| i as u8).collect();
+    let blob_buf = BlobBuf {
+        filename: None,
+        type_string: "application/octet-stream".to_string(),
+        size: bytes.len() as u64,
+        bytes: bytes.clone(),
+    };
+    let id = Uuid::new_v4();
+    filemanager.handle(FileManagerThreadMsg::PromoteMemory(
+        id,
+        blob_buf,
+        false,
+        origin.clone(),
+    ));
+
+    // The blob is written to a temporary file in the background, which only the current user
+    // can access.
+    let start = Instant::now();
+    let path = loop {
+        if let Some(path) = filemanager.spilled_blob_path(&id) {
+            break path;
+        }
+        assert!(
+            start.elapsed() < Duration::from_secs(10),
+            "The blob wasn't spilled"
+        );
+        thread::sleep(Duration::from_millis(10));
+    };
+    assert!(path.exists());
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = path.metadata().unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+        let mode = path
+            .parent()
+            .unwrap()
+            .metadata()
+            .unwrap()
+            .permissions()
+            .mode();
+        assert_eq!(mode & 0o777, 0o700);
+    }
+
+    // Reading it, whether or not it is still being written, gives back the same bytes.
+    let (tx, rx) = ipc::channel().unwrap();
+    filemanager.handle(FileManagerThreadMsg::ReadFile(tx, id, origin.clone()));
+    let mut read_bytes = vec![];
+    loop {
+        match rx.recv().unwrap().unwrap() {
+            ReadFileProgress::Meta(mut blob_buf) => read_bytes.append(&mut blob_buf.bytes),
+            ReadFileProgress::Partial(mut bytes_in) => read_bytes.append(&mut bytes_in),
+            ReadFileProgress::EOF => break,
+        }
+    }
+    assert!(read_bytes == bytes, "Read content differs");
+
+    // Releasing the blob removes its file, once it is done being written.
+    let (tx, rx) = ipc::channel().unwrap();
+    filemanager.handle(FileManagerThreadMsg::DecRef(id, origin, tx));
+    assert!(rx.recv().unwrap().is_ok());
+    let start = Instant::now();
+    while path.exists() {
+        assert!(
+            start.elapsed() < Duration::from_secs(10),
+            "The file of the blob wasn't removed"
+        );
+        thread::sleep(Duration::from_millis(10));
+    }
+
+    // The directory of spilled blobs goes away along with the file manager.
+    let spill_dir = path.parent().unwrap().to_path_buf();
+    drop(filemanager);
+    drop(pool_handle);
+    assert!(!spill_dir.exists());
+}
@@ -0,0 +1,43 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use net::happy_eyeballs::{connect_to_first, sort_addresses};
+use std::net::{IpAddr, TcpListener};
+use tokio::runtime::Runtime;
+
+fn parse_addresses(addresses: &[&str]) -> Vec<IpAddr> {
+    addresses
+        .iter()
+        .map(|address| address.parse().unwrap())
+        .collect()
+}
+
+#[test]
+fn test_happy_eyeballs_alternates_between_address_families() {
+    let addresses = parse_addresses(&["192.0.2.1", "192.0.2.2", "2001:db8::1", "192.0.2.3"]);
+    let expected = parse_addresses(&["2001:db8::1", "192.0.2.1", "192.0.2.2", "192.0.2.3"]);
+    assert_eq!(sort_addresses(addresses), expected);
+}
+
+#[test]
+fn test_happy_eyeballs_connects_to_the_next_address_when_one_fails() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    // A port that was just free refuses the connection.
+    let refusing_address = TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap();
+
+    let mut runtime = Runtime::new().unwrap();
+    let stream = runtime
+        .block_on(connect_to_first(vec![refusing_address, address]))
+        .unwrap();
+    assert_eq!(stream.peer_addr().unwrap(), address);
+
+    assert!(runtime
+        .block_on(connect_to_first(vec![refusing_address]))
+        .is_err());
+    assert!(runtime.block_on(connect_to_first(vec![])).is_err());
+}
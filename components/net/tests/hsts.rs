@@ -382,3 +382,136 @@ fn test_preload_hsts_domains_well_formed() {
     let hsts_list = HstsList::from_servo_preload();
     assert!(!hsts_list.entries_map.is_empty());
 }
+
+#[test]
+fn test_push_entry_with_0_max_age_removes_entry_from_list() {
+    let mut list = HstsList::new();
+    list.push(
+        HstsEntry::new(
+            "mozilla.org".to_owned(),
+            IncludeSubdomains::Included,
+            Some(500000),
+        )
+        .unwrap(),
+    );
+
+    list.push(
+        HstsEntry::new(
+            "mozilla.org".to_owned(),
+            IncludeSubdomains::Included,
+            Some(0),
+        )
+        .unwrap(),
+    );
+
+    assert!(list.entries_map.get("mozilla.org").unwrap().is_empty());
+}
+
+#[test]
+fn test_push_learned_entry_does_not_override_preloaded_entry() {
+    let mut list = HstsList::new();
+    list.push(HstsEntry::new("mozilla.org".to_owned(), IncludeSubdomains::Included, None).unwrap());
+
+    list.push(
+        HstsEntry::new(
+            "mozilla.org".to_owned(),
+            IncludeSubdomains::Included,
+            Some(0),
+        )
+        .unwrap(),
+    );
+    list.push(
+        HstsEntry::new(
+            "mozilla.org".to_owned(),
+            IncludeSubdomains::NotIncluded,
+            Some(500000),
+        )
+        .unwrap(),
+    );
+
+    assert!(list.is_host_secure("servo.mozilla.org"));
+    assert!(list.entries_map.get("mozilla.org").unwrap()[0].is_preloaded());
+}
+
+#[test]
+fn test_push_learned_entry_renews_its_timestamp() {
+    let mut entries_map = HashMap::new();
+    entries_map.insert(
+        "mozilla.org".to_owned(),
+        vec![HstsEntry {
+            host: "mozilla.org".to_owned(),
+            include_subdomains: false,
+            max_age: Some(200),
+            timestamp: Some(time::get_time().sec as u64 - 180u64),
+        }],
+    );
+    let mut list = HstsList {
+        entries_map: entries_map,
+    };
+
+    list.push(
+        HstsEntry::new(
+            "mozilla.org".to_owned(),
+            IncludeSubdomains::NotIncluded,
+            Some(150),
+        )
+        .unwrap(),
+    );
+
+    assert!(list.is_host_secure("mozilla.org"));
+}
+
+#[test]
+fn test_hsts_list_learned_entries_skips_preloaded_and_expired_entries() {
+    let mut entries_map = HashMap::new();
+    entries_map.insert(
+        "mozilla.org".to_owned(),
+        vec![
+            HstsEntry::new("mozilla.org".to_owned(), IncludeSubdomains::Included, None).unwrap(),
+            HstsEntry {
+                host: "servo.mozilla.org".to_owned(),
+                include_subdomains: false,
+                max_age: Some(10),
+                timestamp: Some(time::get_time().sec as u64 - 20u64),
+            },
+        ],
+    );
+    entries_map.insert(
+        "servo.org".to_owned(),
+        vec![HstsEntry::new(
+            "servo.org".to_owned(),
+            IncludeSubdomains::NotIncluded,
+            Some(500000),
+        )
+        .unwrap()],
+    );
+    let list = HstsList {
+        entries_map: entries_map,
+    };
+
+    let learned = list.learned_entries();
+
+    assert!(!learned.entries_map.contains_key("mozilla.org"));
+    assert_eq!(learned.entries_map.get("servo.org").unwrap().len(), 1);
+}
+
+#[test]
+fn test_hsts_list_merge_learned_entries_ignores_persisted_preloaded_entries() {
+    let mut persisted = HstsList::new();
+    persisted
+        .push(HstsEntry::new("mozilla.org".to_owned(), IncludeSubdomains::Included, None).unwrap());
+    persisted.push(
+        HstsEntry::new(
+            "servo.org".to_owned(),
+            IncludeSubdomains::NotIncluded,
+            Some(500000),
+        )
+        .unwrap(),
+    );
+    let mut list = HstsList::new();
+
+    list.merge_learned_entries(persisted);
+
+    assert!(!list.is_host_secure("mozilla.org"));
+    assert!(list.is_host_secure("servo.org"));
+}
@@ -24,7 +24,7 @@ use http::uri::Authority;
 use http::{Method, StatusCode};
 use hyper::body::Body;
 use hyper::{Request as HyperRequest, Response as HyperResponse};
-use msg::constellation_msg::TEST_PIPELINE_ID;
+use msg::constellation_msg::{NetworkConditions, TEST_PIPELINE_ID};
 use net::cookie::Cookie;
 use net::cookie_storage::CookieStorage;
 use net::http_loader::determine_request_referrer;
@@ -39,7 +39,7 @@ use std::io::Write;
 use std::str;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex, RwLock};
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 fn mock_origin() -> ImmutableOrigin {
     ServoUrl::parse("http://servo.org").unwrap().origin()
@@ -1461,3 +1461,81 @@ fn test_determine_request_referrer_longer_than_4k() {
 
     assert_eq!(referer.unwrap().as_str(), "http://example.com/");
 }
+
+#[test]
+fn test_network_conditions_make_the_requests_of_a_client_fail_offline() {
+    let handler = move |_: HyperRequest<Body>, response: &mut HyperResponse<Body>| {
+        *response.body_mut() = b"Yay!".to_vec().into();
+    };
+    let (server, url) = make_server(handler);
+
+    let mut context = new_fetch_context(None, None, None);
+    let conditions = NetworkConditions {
+        offline: true,
+        ..NetworkConditions::default()
+    };
+    context
+        .state
+        .network_conditions
+        .write()
+        .unwrap()
+        .insert(TEST_PIPELINE_ID, conditions);
+
+    let mut request = RequestBuilder::new(url.clone())
+        .method(Method::GET)
+        .body(None)
+        .destination(Destination::Document)
+        .origin(url.clone().origin())
+        .pipeline_id(Some(TEST_PIPELINE_ID))
+        .build();
+    let response = fetch_with_context(&mut request, &mut context);
+    assert!(response.is_network_error());
+
+    // The requests of other clients still reach the network.
+    let mut request = RequestBuilder::new(url.clone())
+        .method(Method::GET)
+        .body(None)
+        .destination(Destination::Document)
+        .origin(url.clone().origin())
+        .build();
+    let response = fetch_with_context(&mut request, &mut context);
+    let _ = server.close();
+    assert!(!response.is_network_error());
+}
+
+#[test]
+fn test_network_conditions_delay_the_responses_of_a_client() {
+    let handler = move |_: HyperRequest<Body>, response: &mut HyperResponse<Body>| {
+        *response.body_mut() = vec![b'a'; 100].into();
+    };
+    let (server, url) = make_server(handler);
+
+    let mut context = new_fetch_context(None, None, None);
+    let conditions = NetworkConditions {
+        latency: Duration::from_millis(200),
+        download_throughput: Some(1000),
+        ..NetworkConditions::default()
+    };
+    context
+        .state
+        .network_conditions
+        .write()
+        .unwrap()
+        .insert(TEST_PIPELINE_ID, conditions);
+
+    let mut request = RequestBuilder::new(url.clone())
+        .method(Method::GET)
+        .body(None)
+        .destination(Destination::Document)
+        .origin(url.clone().origin())
+        .pipeline_id(Some(TEST_PIPELINE_ID))
+        .build();
+    let start = Instant::now();
+    let response = fetch_with_context(&mut request, &mut context);
+    let elapsed = start.elapsed();
+    let _ = server.close();
+
+    // The latency delays the response, and the body of 100 bytes takes 100ms to receive.
+    assert!(!response.is_network_error());
+    assert!(elapsed >= Duration::from_millis(300), "{:?}", elapsed);
+}
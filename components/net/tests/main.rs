@@ -10,25 +10,34 @@ extern crate lazy_static;
 mod cookie;
 mod cookie_http_state;
 mod data_loader;
+mod doh;
+mod download_manager;
 mod fetch;
 mod file_loader;
+mod file_system;
 mod filemanager_thread;
+mod happy_eyeballs;
 mod hsts;
 mod http_cache;
 mod http_loader;
 mod mime_classifier;
+mod protocol_handlers;
+mod proxy;
+mod request_scheduler;
 mod resource_thread;
 mod subresource_integrity;
+mod webauthn;
 
-use crossbeam_channel::{unbounded, Sender};
+use crossbeam_channel::{unbounded, Receiver, Sender};
 use devtools_traits::DevtoolsControlMsg;
 use embedder_traits::resources::{self, Resource};
-use embedder_traits::{EmbedderProxy, EventLoopWaker};
+use embedder_traits::{EmbedderMsg, EmbedderProxy, EventLoopWaker};
 use futures::{Future, Stream};
 use hyper::server::conn::Http;
 use hyper::server::Server as HyperServer;
 use hyper::service::service_fn_ok;
 use hyper::{Body, Request as HyperRequest, Response as HyperResponse};
+use msg::constellation_msg::TopLevelBrowsingContextId;
 use net::connector::{create_tls_config, ALPN_H2_H1};
 use net::fetch::cors_cache::CorsCache;
 use net::fetch::methods::{self, CancellationListener, FetchContext};
@@ -61,7 +70,14 @@ struct FetchResponseCollector {
 }
 
 fn create_embedder_proxy() -> EmbedderProxy {
-    let (sender, _) = unbounded();
+    create_embedder_proxy_and_receiver().0
+}
+
+fn create_embedder_proxy_and_receiver() -> (
+    EmbedderProxy,
+    Receiver<(Option<TopLevelBrowsingContextId>, EmbedderMsg)>,
+) {
+    let (sender, receiver) = unbounded();
     let event_loop_waker = || {
         struct DummyEventLoopWaker {}
         impl DummyEventLoopWaker {
@@ -79,10 +95,11 @@ fn create_embedder_proxy() -> EmbedderProxy {
         Box::new(DummyEventLoopWaker::new())
     };
 
-    EmbedderProxy {
+    let embedder_proxy = EmbedderProxy {
         sender: sender,
         event_loop_waker: event_loop_waker(),
-    }
+    };
+    (embedder_proxy, receiver)
 }
 
 fn new_fetch_context(
@@ -98,12 +115,15 @@ fn new_fetch_context(
         state: Arc::new(HttpState::new(tls_config)),
         user_agent: DEFAULT_USER_AGENT.into(),
         devtools_chan: dc,
-        filemanager: FileManager::new(sender, pool_handle.unwrap_or_else(|| Weak::new())),
+        filemanager: FileManager::new(sender, pool_handle.clone().unwrap_or_else(|| Weak::new())),
         file_token: FileTokenCheck::NotRequired,
         cancellation_listener: Arc::new(Mutex::new(CancellationListener::new(None))),
         timing: ServoArc::new(Mutex::new(ResourceFetchTiming::new(
             ResourceTimingType::Navigation,
         ))),
+        mem_profiler_chan: None,
+        thread_pool: pool_handle.unwrap_or_else(|| Weak::new()),
+        connection_slot: Mutex::new(None),
     }
 }
 impl FetchTaskTarget for FetchResponseCollector {
@@ -0,0 +1,95 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::{create_embedder_proxy_and_receiver, new_fetch_context};
+use embedder_traits::EmbedderMsg;
+use msg::constellation_msg::{TopLevelBrowsingContextId, TEST_BROWSING_CONTEXT_ID};
+use net::protocol_handlers::{self, ProtocolHandlerList};
+use servo_url::ServoUrl;
+use std::thread;
+use std::time::Duration;
+
+fn url(url: &str) -> ServoUrl {
+    ServoUrl::parse(url).unwrap()
+}
+
+#[test]
+fn test_protocol_handler_url_escapes_the_url() {
+    let mut list = ProtocolHandlerList::new();
+    list.register("web+chat".into(), url("https://example.com/chat?to=%s"));
+    assert_eq!(
+        list.handler_url(&url("web+chat:alice@example.org?m=hi&x=1")),
+        Some(url(
+            "https://example.com/chat?to=web%2Bchat%3Aalice%40example.org%3Fm%3Dhi%26x%3D1"
+        ))
+    );
+    assert_eq!(list.handler_url(&url("mailto:alice@example.org")), None);
+}
+
+#[test]
+fn test_protocol_handler_registered_last_is_used() {
+    let mut list = ProtocolHandlerList::new();
+    list.register("mailto".into(), url("https://one.example/?%s"));
+    list.register("mailto".into(), url("https://two.example/?%s"));
+    let mailto = url("mailto:alice@example.org");
+    assert_eq!(
+        list.handler_url(&mailto).unwrap().host_str(),
+        Some("two.example")
+    );
+
+    list.unregister("mailto", &url("https://two.example/?%s"));
+    assert_eq!(
+        list.handler_url(&mailto).unwrap().host_str(),
+        Some("one.example")
+    );
+    list.unregister("mailto", &url("https://one.example/?%s"));
+    assert_eq!(list.handler_url(&mailto), None);
+}
+
+#[test]
+fn test_protocol_handler_is_only_used_once_the_user_allowed_it() {
+    let http_state = new_fetch_context(None, None, None).state;
+    let (embedder_proxy, embedder_receiver) = create_embedder_proxy_and_receiver();
+    let browser = TopLevelBrowsingContextId(TEST_BROWSING_CONTEXT_ID);
+    let ask = |handler: &str, allowed: bool| {
+        protocol_handlers::ask_to_register(
+            &http_state,
+            &embedder_proxy,
+            browser,
+            "mailto".into(),
+            url(handler),
+        );
+        match embedder_receiver.recv().unwrap() {
+            (Some(id), EmbedderMsg::RegisterProtocolHandler(request, sender)) => {
+                assert_eq!(id, browser);
+                sender.send(allowed).unwrap();
+                request.current_url
+            },
+            (_, msg) => panic!("Unexpected {:?}", msg),
+        }
+    };
+    let current_host = || {
+        let protocol_handlers = http_state.protocol_handlers.read().unwrap();
+        let current_url = protocol_handlers.current_url("mailto")?;
+        current_url.host_str().map(str::to_owned)
+    };
+    let wait_for_current_host = |host: &str| {
+        while current_host().as_deref() != Some(host) {
+            thread::sleep(Duration::from_millis(10));
+        }
+    };
+
+    assert_eq!(ask("https://one.example/?%s", false), None);
+    assert_eq!(ask("https://one.example/?%s", true), None);
+    wait_for_current_host("one.example");
+
+    // Another site does not take the place of the handler that the user allowed unless they
+    // allow it too.
+    let current_url = ask("https://two.example/?%s", false);
+    assert_eq!(current_url, Some(url("https://one.example/?%s")));
+    assert_eq!(ask("https://three.example/?%s", true), current_url);
+    wait_for_current_host("three.example");
+    let protocol_handlers = http_state.protocol_handlers.read().unwrap();
+    assert!(!protocol_handlers.is_registered("mailto", &url("https://two.example/?%s")));
+}
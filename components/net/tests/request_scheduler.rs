@@ -0,0 +1,64 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use net::request_scheduler::{RequestScheduler, MAX_CONNECTIONS_PER_HOST};
+use net_traits::request::{Request, RequestPriority};
+use servo_url::ServoUrl;
+use std::sync::mpsc::channel;
+
+fn new_request(url: &str, priority: RequestPriority) -> Request {
+    let mut request = Request::new(ServoUrl::parse(url).unwrap(), None, None);
+    request.priority = priority;
+    request
+}
+
+#[test]
+fn test_request_scheduler_limits_the_connections_to_a_host() {
+    let scheduler = RequestScheduler::new();
+    let request = new_request("http://example.com/", RequestPriority::Auto);
+    let mut slots: Vec<_> = (0..MAX_CONNECTIONS_PER_HOST)
+        .map(|_| scheduler.try_acquire(&request).unwrap())
+        .collect();
+    assert!(scheduler.try_acquire(&request).is_none());
+
+    let other_host = new_request("http://example.org/", RequestPriority::Auto);
+    assert!(scheduler.try_acquire(&other_host).is_some());
+
+    slots.pop();
+    assert!(scheduler.try_acquire(&request).is_some());
+}
+
+#[test]
+fn test_request_scheduler_hands_released_connections_to_waiting_requests() {
+    let scheduler = RequestScheduler::new();
+    let request = new_request("http://example.com/", RequestPriority::Auto);
+    let mut slots: Vec<_> = (0..MAX_CONNECTIONS_PER_HOST)
+        .map(|_| scheduler.try_acquire(&request).unwrap())
+        .collect();
+
+    // The requests that wait are started in the order of their priority, once a connection is
+    // released, without holding up the thread that scheduled them.
+    let (sender, receiver) = channel();
+    for (url, priority) in &[
+        ("http://example.com/low.png", RequestPriority::Low),
+        ("http://example.com/high.png", RequestPriority::High),
+    ] {
+        let sender = sender.clone();
+        scheduler.schedule(new_request(url, *priority), move |request, slot| {
+            sender.send((request.current_url(), slot)).unwrap();
+        });
+    }
+    assert!(receiver.try_recv().is_err());
+    assert!(scheduler.try_acquire(&request).is_none());
+
+    slots.pop();
+    let (url, slot) = receiver.try_recv().unwrap();
+    assert_eq!(url.as_str(), "http://example.com/high.png");
+    assert!(receiver.try_recv().is_err());
+
+    drop(slot);
+    let (url, _slot) = receiver.try_recv().unwrap();
+    assert_eq!(url.as_str(), "http://example.com/low.png");
+    assert!(scheduler.try_acquire(&request).is_none());
+}
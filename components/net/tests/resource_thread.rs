@@ -3,6 +3,7 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use crate::create_embedder_proxy;
+use embedder_traits::ProtocolRegistry;
 use ipc_channel::ipc;
 use net::resource_thread::new_core_resource_thread;
 use net::test::parse_hostsfile;
@@ -28,6 +29,7 @@ fn test_exit() {
         create_embedder_proxy(),
         None,
         None,
+        ProtocolRegistry::new(),
     );
     resource_thread.send(CoreResourceMsg::Exit(sender)).unwrap();
     receiver.recv().unwrap();
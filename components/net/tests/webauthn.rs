@@ -0,0 +1,120 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use net::webauthn::PlatformAuthenticator;
+use net_traits::webauthn::COSE_ALGORITHM_ES256;
+use net_traits::webauthn::{AuthenticatorError, GetAssertionRequest, MakeCredentialRequest};
+use openssl::hash::MessageDigest;
+use openssl::pkey::PKey;
+use openssl::sign::Verifier;
+use std::env;
+use uuid::Uuid;
+
+fn make_credential_request(user_id: &[u8]) -> MakeCredentialRequest {
+    MakeCredentialRequest {
+        client_data_hash: vec![1; 32],
+        rp_id: "example.com".to_owned(),
+        user_id: user_id.to_vec(),
+        user_name: "alice".to_owned(),
+        algorithms: vec![-257, COSE_ALGORITHM_ES256],
+        exclude_list: vec![],
+    }
+}
+
+fn get_assertion_request(allow_list: Vec<Vec<u8>>) -> GetAssertionRequest {
+    GetAssertionRequest {
+        client_data_hash: vec![2; 32],
+        rp_id: "example.com".to_owned(),
+        allow_list,
+    }
+}
+
+#[test]
+fn test_webauthn_assertion_is_signed_with_the_created_credential() {
+    let mut authenticator = PlatformAuthenticator::new(None);
+    let created = authenticator
+        .make_credential(make_credential_request(b"alice"))
+        .unwrap();
+    assert_eq!(created.algorithm, COSE_ALGORITHM_ES256);
+    // The flags tell that the user was present and that the credential data follows.
+    assert_eq!(created.authenticator_data[32], 0x41);
+
+    let assertion = authenticator
+        .get_assertion(&get_assertion_request(vec![]))
+        .unwrap();
+    assert_eq!(assertion.credential_id, created.credential_id);
+    assert_eq!(assertion.user_handle, b"alice".to_vec());
+    assert_eq!(&assertion.authenticator_data[33..37], &[0, 0, 0, 1]);
+
+    let public_key = PKey::public_key_from_der(&created.public_key).unwrap();
+    let mut verifier = Verifier::new(MessageDigest::sha256(), &public_key).unwrap();
+    verifier.update(&assertion.authenticator_data).unwrap();
+    verifier.update(&[2; 32]).unwrap();
+    assert!(verifier.verify(&assertion.signature).unwrap());
+}
+
+#[test]
+fn test_webauthn_credentials_are_excluded_and_allowed() {
+    let mut authenticator = PlatformAuthenticator::new(None);
+    let created = authenticator
+        .make_credential(make_credential_request(b"alice"))
+        .unwrap();
+
+    let mut request = make_credential_request(b"alice");
+    request.exclude_list = vec![created.credential_id.clone()];
+    assert_eq!(
+        authenticator.make_credential(request),
+        Err(AuthenticatorError::CredentialExcluded)
+    );
+    assert_eq!(
+        authenticator.get_assertion(&get_assertion_request(vec![vec![0; 32]])),
+        Err(AuthenticatorError::NoCredentials)
+    );
+    assert!(authenticator
+        .get_assertion(&get_assertion_request(vec![created.credential_id]))
+        .is_ok());
+}
+
+#[test]
+fn test_webauthn_unsupported_algorithms() {
+    let mut authenticator = PlatformAuthenticator::new(None);
+    let mut request = make_credential_request(b"alice");
+    request.algorithms = vec![-257];
+    assert_eq!(
+        authenticator.make_credential(request),
+        Err(AuthenticatorError::UnsupportedAlgorithm)
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn test_webauthn_credentials_are_private_and_encrypted() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let config_dir = env::temp_dir().join(format!("servo-webauthn-{}", Uuid::new_v4()));
+    std::fs::create_dir(&config_dir).unwrap();
+    let mut authenticator = PlatformAuthenticator::new(Some(config_dir.clone()));
+    authenticator
+        .make_credential(make_credential_request(b"alice"))
+        .unwrap();
+    for file in &["webauthn_credentials", "webauthn_key"] {
+        let mode = config_dir
+            .join(file)
+            .metadata()
+            .unwrap()
+            .permissions()
+            .mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+    // The credentials are encrypted.
+    let data = std::fs::read(config_dir.join("webauthn_credentials")).unwrap();
+    assert!(!data.windows(5).any(|window| window == b"alice"));
+
+    // The credentials are there again for the next session.
+    let mut authenticator = PlatformAuthenticator::new(Some(config_dir.clone()));
+    assert!(authenticator
+        .get_assertion(&get_assertion_request(vec![]))
+        .is_ok());
+    let _ = std::fs::remove_dir_all(&config_dir);
+}
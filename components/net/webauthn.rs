@@ -0,0 +1,459 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! A platform authenticator built into the browser, which creates the public key credentials
+//! of the Web Authentication API and signs with them. The credentials are discoverable ones
+//! that stay in the config directory, encrypted with a key of the profile, in files that only
+//! the user can read. The constellation
+//! already checked that the relying party of each operation is one that the page may use, and
+//! the user is asked to agree to it here, where the page has no say in what they are shown.
+//! <https://w3c.github.io/webauthn/#sctn-authenticator-model>
+
+use embedder_traits::{EmbedderMsg, EmbedderProxy, PromptDefinition, PromptOrigin, PromptResult};
+use ipc_channel::ipc;
+use msg::constellation_msg::TopLevelBrowsingContextId;
+use net_traits::webauthn::{AuthenticatorError, AuthenticatorMsg, COSE_ALGORITHM_ES256};
+use net_traits::webauthn::{GetAssertionRequest, GetAssertionResponse};
+use net_traits::webauthn::{MakeCredentialRequest, MakeCredentialResponse};
+use openssl::bn::{BigNum, BigNumContext};
+use openssl::ec::{EcGroup, EcKey};
+use openssl::error::ErrorStack;
+use openssl::hash::{hash, MessageDigest};
+use openssl::nid::Nid;
+use openssl::pkey::PKey;
+use openssl::rand::rand_bytes;
+use openssl::sign::Signer;
+use openssl::symm::{decrypt_aead, encrypt_aead, Cipher};
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tempfile::NamedTempFile;
+
+const CREDENTIALS_FILE: &str = "webauthn_credentials";
+/// The file of the AES-256 key that the credentials are encrypted with.
+const KEY_FILE: &str = "webauthn_key";
+const KEY_LENGTH: usize = 32;
+/// The lengths of the nonce and of the authentication tag of AES-GCM, which come before the
+/// encrypted credentials in their file.
+const NONCE_LENGTH: usize = 12;
+const TAG_LENGTH: usize = 16;
+
+/// The flag of the authenticator data telling that the user was present.
+const FLAG_USER_PRESENT: u8 = 0x01;
+/// The flag of the authenticator data telling that the attested credential data follows.
+const FLAG_ATTESTED_CREDENTIAL_DATA: u8 = 0x40;
+
+/// The values of CBOR that the authenticator writes.
+/// <https://tools.ietf.org/html/rfc7049>
+enum Cbor {
+    Integer(i64),
+    Bytes(Vec<u8>),
+    Text(&'static str),
+    /// The entries of a map, which must already be in the canonical order of CTAP2.
+    Map(Vec<(Cbor, Cbor)>),
+}
+
+impl Cbor {
+    fn encode(&self, output: &mut Vec<u8>) {
+        match *self {
+            Cbor::Integer(value) if value >= 0 => write_cbor_head(0, value as u64, output),
+            Cbor::Integer(value) => write_cbor_head(1, (-1 - value) as u64, output),
+            Cbor::Bytes(ref bytes) => {
+                write_cbor_head(2, bytes.len() as u64, output);
+                output.extend_from_slice(bytes);
+            },
+            Cbor::Text(text) => {
+                write_cbor_head(3, text.len() as u64, output);
+                output.extend_from_slice(text.as_bytes());
+            },
+            Cbor::Map(ref entries) => {
+                write_cbor_head(5, entries.len() as u64, output);
+                for &(ref key, ref value) in entries {
+                    key.encode(output);
+                    value.encode(output);
+                }
+            },
+        }
+    }
+
+    fn to_vec(&self) -> Vec<u8> {
+        let mut output = vec![];
+        self.encode(&mut output);
+        output
+    }
+}
+
+fn write_cbor_head(major_type: u8, value: u64, output: &mut Vec<u8>) {
+    let major_type = major_type << 5;
+    if value < 24 {
+        output.push(major_type | value as u8);
+    } else if value <= 0xff {
+        output.push(major_type | 24);
+        output.push(value as u8);
+    } else if value <= 0xffff {
+        output.push(major_type | 25);
+        output.extend_from_slice(&(value as u16).to_be_bytes());
+    } else if value <= 0xffff_ffff {
+        output.push(major_type | 26);
+        output.extend_from_slice(&(value as u32).to_be_bytes());
+    } else {
+        output.push(major_type | 27);
+        output.extend_from_slice(&value.to_be_bytes());
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+struct StoredCredential {
+    id: Vec<u8>,
+    rp_id: String,
+    user_id: Vec<u8>,
+    user_name: String,
+    /// The P-256 private key in DER.
+    private_key: Vec<u8>,
+    /// The number of assertions that were made with the credential.
+    sign_count: u32,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+struct CredentialList {
+    /// The credentials, the most recently created last.
+    credentials: Vec<StoredCredential>,
+}
+
+pub struct PlatformAuthenticator {
+    credentials: CredentialList,
+    /// Where the credentials are saved as soon as they change, if anywhere, since losing one
+    /// locks the user out of their account.
+    store: Option<CredentialStore>,
+}
+
+/// The file of the credentials, and the key that they are encrypted with.
+struct CredentialStore {
+    path: PathBuf,
+    key: Vec<u8>,
+}
+
+impl PlatformAuthenticator {
+    /// An authenticator with the credentials saved in the config directory, if any. If they
+    /// can't be read, new credentials only last for the session rather than replace them.
+    pub fn new(config_dir: Option<PathBuf>) -> PlatformAuthenticator {
+        let (credentials, store) = match config_dir {
+            Some(config_dir) => match CredentialStore::open(&config_dir) {
+                Ok((store, credentials)) => (credentials, Some(store)),
+                Err(e) => {
+                    warn!("Failed to read the credentials in {:?} ({})", config_dir, e);
+                    (CredentialList::default(), None)
+                },
+            },
+            None => (CredentialList::default(), None),
+        };
+        PlatformAuthenticator { credentials, store }
+    }
+
+    /// <https://fidoalliance.org/specs/fido-v2.0-ps-20190130/fido-client-to-authenticator-protocol-v2.0-ps-20190130.html#authenticatorMakeCredential>
+    pub fn make_credential(
+        &mut self,
+        request: MakeCredentialRequest,
+    ) -> Result<MakeCredentialResponse, AuthenticatorError> {
+        if !request.algorithms.contains(&COSE_ALGORITHM_ES256) {
+            return Err(AuthenticatorError::UnsupportedAlgorithm);
+        }
+        let excluded = self.credentials.credentials.iter().any(|credential| {
+            credential.rp_id == request.rp_id && request.exclude_list.contains(&credential.id)
+        });
+        if excluded {
+            return Err(AuthenticatorError::CredentialExcluded);
+        }
+
+        let (credential, response) =
+            new_credential(request).map_err(|_| AuthenticatorError::Failed)?;
+
+        // A new discoverable credential replaces the one of the same account.
+        self.credentials.credentials.retain(|existing| {
+            existing.rp_id != credential.rp_id || existing.user_id != credential.user_id
+        });
+        self.credentials.credentials.push(credential);
+        self.save();
+        Ok(response)
+    }
+
+    /// Without a way to ask the user which account they want to sign in with, the most recently
+    /// created of the credentials that are allowed is used.
+    /// <https://fidoalliance.org/specs/fido-v2.0-ps-20190130/fido-client-to-authenticator-protocol-v2.0-ps-20190130.html#authenticatorGetAssertion>
+    pub fn get_assertion(
+        &mut self,
+        request: &GetAssertionRequest,
+    ) -> Result<GetAssertionResponse, AuthenticatorError> {
+        let credential = self
+            .credentials
+            .credentials
+            .iter_mut()
+            .rev()
+            .find(|credential| {
+                credential.rp_id == request.rp_id &&
+                    (request.allow_list.is_empty() ||
+                        request.allow_list.contains(&credential.id))
+            })
+            .ok_or(AuthenticatorError::NoCredentials)?;
+
+        credential.sign_count = credential.sign_count.wrapping_add(1);
+        let response = assert(credential, &request.client_data_hash)
+            .map_err(|_| AuthenticatorError::Failed)?;
+        self.save();
+        Ok(response)
+    }
+
+    fn save(&self) {
+        if let Some(ref store) = self.store {
+            if let Err(e) = store.save(&self.credentials) {
+                warn!("Failed to save the credentials to {:?} ({})", store.path, e);
+            }
+        }
+    }
+}
+
+impl CredentialStore {
+    /// Opens the credentials of the config directory, with the key of the profile, which is
+    /// made along with the directory's first credentials.
+    fn open(config_dir: &Path) -> io::Result<(CredentialStore, CredentialList)> {
+        let key = read_or_create_key(&config_dir.join(KEY_FILE))?;
+        let path = config_dir.join(CREDENTIALS_FILE);
+        let credentials = match fs::read(&path) {
+            Ok(data) => {
+                let json = decrypt(&key, &data)?;
+                serde_json::from_slice(&json)?
+            },
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => CredentialList::default(),
+            Err(e) => return Err(e),
+        };
+        Ok((CredentialStore { path, key }, credentials))
+    }
+
+    /// Replaces the file of the credentials at once, so that a crash can't leave half of it.
+    fn save(&self, credentials: &CredentialList) -> io::Result<()> {
+        let data = encrypt(&self.key, &serde_json::to_vec(credentials)?)?;
+        let file = private_file(&self.path, &data)?;
+        file.persist(&self.path).map_err(|e| e.error)?;
+        Ok(())
+    }
+}
+
+fn read_or_create_key(path: &Path) -> io::Result<Vec<u8>> {
+    match fs::read(path) {
+        Ok(key) if key.len() == KEY_LENGTH => return Ok(key),
+        Ok(_) => return Err(io::Error::new(io::ErrorKind::InvalidData, "Malformed key")),
+        Err(ref e) if e.kind() == io::ErrorKind::NotFound => {},
+        Err(e) => return Err(e),
+    }
+    let mut key = vec![0; KEY_LENGTH];
+    rand_bytes(&mut key).map_err(openssl_error)?;
+    // A key that was made in the meantime is never replaced, since it may already encrypt
+    // credentials.
+    match private_file(path, &key)?.persist_noclobber(path) {
+        Ok(_) => Ok(key),
+        Err(ref e) if e.error.kind() == io::ErrorKind::AlreadyExists => fs::read(path),
+        Err(e) => Err(e.error),
+    }
+}
+
+/// A temporary file next to `path` with the given contents, which only the user can read
+/// until it replaces `path`.
+fn private_file(path: &Path, contents: &[u8]) -> io::Result<NamedTempFile> {
+    let dir = path.parent().unwrap_or_else(|| Path::new("."));
+    let mut file = NamedTempFile::new_in(dir)?;
+    file.write_all(contents)?;
+    file.as_file().sync_all()?;
+    Ok(file)
+}
+
+fn encrypt(key: &[u8], plaintext: &[u8]) -> io::Result<Vec<u8>> {
+    let mut nonce = vec![0; NONCE_LENGTH];
+    rand_bytes(&mut nonce).map_err(openssl_error)?;
+    let mut tag = vec![0; TAG_LENGTH];
+    let ciphertext = encrypt_aead(
+        Cipher::aes_256_gcm(),
+        key,
+        Some(&nonce),
+        &[],
+        plaintext,
+        &mut tag,
+    )
+    .map_err(openssl_error)?;
+    let mut data = nonce;
+    data.extend(tag);
+    data.extend(ciphertext);
+    Ok(data)
+}
+
+fn decrypt(key: &[u8], data: &[u8]) -> io::Result<Vec<u8>> {
+    if data.len() < NONCE_LENGTH + TAG_LENGTH {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "Truncated credentials",
+        ));
+    }
+    let (nonce, rest) = data.split_at(NONCE_LENGTH);
+    let (tag, ciphertext) = rest.split_at(TAG_LENGTH);
+    decrypt_aead(
+        Cipher::aes_256_gcm(),
+        key,
+        Some(nonce),
+        &[],
+        ciphertext,
+        tag,
+    )
+    .map_err(openssl_error)
+}
+
+fn openssl_error(error: ErrorStack) -> io::Error {
+    io::Error::new(io::ErrorKind::Other, error)
+}
+
+/// Makes the operation once the user agreed to it, without holding the authenticator while
+/// waiting for them.
+pub fn handle(
+    authenticator: &Mutex<PlatformAuthenticator>,
+    msg: AuthenticatorMsg,
+    embedder_proxy: &EmbedderProxy,
+    top_level_browsing_context_id: TopLevelBrowsingContextId,
+) {
+    match msg {
+        AuthenticatorMsg::MakeCredential(request, sender) => {
+            let message = format!("Create a passkey on {}?", request.rp_id);
+            let result = if ask_user(embedder_proxy, top_level_browsing_context_id, message) {
+                authenticator.lock().unwrap().make_credential(request)
+            } else {
+                Err(AuthenticatorError::NotAllowed)
+            };
+            let _ = sender.send(result);
+        },
+        AuthenticatorMsg::GetAssertion(request, sender) => {
+            let message = format!("Sign in to {} with a passkey?", request.rp_id);
+            let result = if ask_user(embedder_proxy, top_level_browsing_context_id, message) {
+                authenticator.lock().unwrap().get_assertion(&request)
+            } else {
+                Err(AuthenticatorError::NotAllowed)
+            };
+            let _ = sender.send(result);
+        },
+    }
+}
+
+/// Asks the user whether they agree to the operation, which the authenticator must know they
+/// are present for. The message only names the relying party, which the constellation checked,
+/// so that the page can't make the user agree to something else.
+/// <https://w3c.github.io/webauthn/#test-of-user-presence>
+fn ask_user(
+    embedder_proxy: &EmbedderProxy,
+    top_level_browsing_context_id: TopLevelBrowsingContextId,
+    message: String,
+) -> bool {
+    let (sender, receiver) = match ipc::channel() {
+        Ok(channel) => channel,
+        Err(_) => return false,
+    };
+    let prompt = PromptDefinition::YesNo(message, sender);
+    embedder_proxy.send((
+        Some(top_level_browsing_context_id),
+        EmbedderMsg::Prompt(prompt, PromptOrigin::Trusted),
+    ));
+    receiver.recv() == Ok(PromptResult::Primary)
+}
+
+/// The authenticator data up to the attested credential data.
+/// <https://w3c.github.io/webauthn/#sctn-authenticator-data>
+fn authenticator_data(rp_id: &str, flags: u8, sign_count: u32) -> Result<Vec<u8>, ErrorStack> {
+    let mut data = hash(MessageDigest::sha256(), rp_id.as_bytes())?.to_vec();
+    data.push(flags);
+    data.extend_from_slice(&sign_count.to_be_bytes());
+    Ok(data)
+}
+
+/// A coordinate of a P-256 point, left-padded to 32 bytes.
+fn coordinate(value: &BigNum) -> Vec<u8> {
+    let bytes = value.to_vec();
+    let mut padded = vec![0; 32usize.saturating_sub(bytes.len())];
+    padded.extend(bytes);
+    padded
+}
+
+fn new_credential(
+    request: MakeCredentialRequest,
+) -> Result<(StoredCredential, MakeCredentialResponse), ErrorStack> {
+    let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1)?;
+    let private_key = EcKey::generate(&group)?;
+    let mut context = BigNumContext::new()?;
+    let mut x = BigNum::new()?;
+    let mut y = BigNum::new()?;
+    private_key
+        .public_key()
+        .affine_coordinates_gfp(&group, &mut x, &mut y, &mut context)?;
+    let public_key = EcKey::from_public_key(&group, private_key.public_key())?;
+
+    let mut id = vec![0; 32];
+    rand_bytes(&mut id)?;
+
+    // <https://w3c.github.io/webauthn/#sctn-encoded-credPubKey-examples>
+    let cose_key = Cbor::Map(vec![
+        (Cbor::Integer(1), Cbor::Integer(2)),
+        (Cbor::Integer(3), Cbor::Integer(COSE_ALGORITHM_ES256 as i64)),
+        (Cbor::Integer(-1), Cbor::Integer(1)),
+        (Cbor::Integer(-2), Cbor::Bytes(coordinate(&x))),
+        (Cbor::Integer(-3), Cbor::Bytes(coordinate(&y))),
+    ]);
+
+    // <https://w3c.github.io/webauthn/#sctn-attested-credential-data>
+    let mut data = authenticator_data(
+        &request.rp_id,
+        FLAG_USER_PRESENT | FLAG_ATTESTED_CREDENTIAL_DATA,
+        0,
+    )?;
+    // The AAGUID is zero, as with self attestation.
+    data.extend_from_slice(&[0; 16]);
+    data.extend_from_slice(&(id.len() as u16).to_be_bytes());
+    data.extend_from_slice(&id);
+    cose_key.encode(&mut data);
+
+    // <https://w3c.github.io/webauthn/#sctn-none-attestation>
+    let attestation_object = Cbor::Map(vec![
+        (Cbor::Text("fmt"), Cbor::Text("none")),
+        (Cbor::Text("attStmt"), Cbor::Map(vec![])),
+        (Cbor::Text("authData"), Cbor::Bytes(data.clone())),
+    ]);
+
+    let response = MakeCredentialResponse {
+        credential_id: id.clone(),
+        authenticator_data: data,
+        attestation_object: attestation_object.to_vec(),
+        public_key: PKey::from_ec_key(public_key)?.public_key_to_der()?,
+        algorithm: COSE_ALGORITHM_ES256,
+    };
+    let credential = StoredCredential {
+        id,
+        rp_id: request.rp_id,
+        user_id: request.user_id,
+        user_name: request.user_name,
+        private_key: private_key.private_key_to_der()?,
+        sign_count: 0,
+    };
+    Ok((credential, response))
+}
+
+fn assert(
+    credential: &StoredCredential,
+    client_data_hash: &[u8],
+) -> Result<GetAssertionResponse, ErrorStack> {
+    let data = authenticator_data(&credential.rp_id, FLAG_USER_PRESENT, credential.sign_count)?;
+    let private_key = PKey::from_ec_key(EcKey::private_key_from_der(&credential.private_key)?)?;
+    let mut signer = Signer::new(MessageDigest::sha256(), &private_key)?;
+    signer.update(&data)?;
+    signer.update(client_data_hash)?;
+    Ok(GetAssertionResponse {
+        credential_id: credential.id.clone(),
+        authenticator_data: data,
+        signature: signer.sign_to_vec()?,
+        user_handle: credential.user_id.clone(),
+    })
+}
@@ -23,6 +23,10 @@ pub enum BlobURLStoreError {
     External(String),
 }
 
+/// The size above which the file manager keeps the bytes of a memory-based blob in a
+/// temporary file, and reads them from it when needed, rather than in memory.
+pub const MAX_MEMORY_BLOB_SIZE: u64 = 4 * 1024 * 1024;
+
 /// Standalone blob buffer object
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct BlobBuf {
@@ -117,6 +117,46 @@ pub struct SelectedFile {
     pub type_string: String,
 }
 
+/// <https://wicg.github.io/file-system-access/#enumdef-filesystemhandlekind>
+#[derive(Clone, Copy, Debug, Deserialize, MallocSizeOf, PartialEq, Serialize)]
+pub enum FileSystemEntryKind {
+    File,
+    Directory,
+}
+
+/// The entry of a file system that a handle represents.
+#[derive(Clone, Debug, Deserialize, MallocSizeOf, PartialEq, Serialize)]
+pub enum FileSystemLocator {
+    /// An entry of the origin private file system of an origin, by the names of the
+    /// directories leading to it followed by its own, or the root directory if there are none.
+    Private(FileOrigin, Vec<String>),
+    /// A file that the user picked, by the id that the file manager gave it.
+    Picked(Uuid),
+}
+
+/// An operation on a file opened with a sync access handle.
+#[derive(Debug, Deserialize, Serialize)]
+pub enum SyncAccessOperation {
+    /// Read up to the given number of bytes at an offset
+    Read(u64, u64),
+    /// Write bytes at an offset
+    Write(u64, Vec<u8>),
+    /// Resize the file
+    Truncate(u64),
+    GetSize,
+    Flush,
+    /// Close the file, letting it be opened again
+    Close,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+pub enum SyncAccessReply {
+    Read(Vec<u8>),
+    Written(u64),
+    Size(u64),
+    Done,
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 pub enum FileManagerThreadMsg {
     /// Select a single file. Last field is pre-selected file path for testing
@@ -162,6 +202,47 @@ pub enum FileManagerThreadMsg {
 
     /// Revoke Blob URL and send back the acknowledgement
     RevokeBlobURL(Uuid, FileOrigin, IpcSender<Result<(), BlobURLStoreError>>),
+
+    /// Let the user pick one or several files to get handles to, and send back the ids and
+    /// names of the files. Last field is pre-selected file paths for testing
+    PickFiles(
+        Vec<FilterPattern>,
+        bool,
+        IpcSender<FileManagerResult<Vec<(Uuid, String)>>>,
+        Option<Vec<String>>,
+    ),
+
+    /// Check that there is an entry of the given kind, creating it if the flag is set
+    GetFileSystemEntry(
+        FileSystemLocator,
+        FileSystemEntryKind,
+        bool,
+        IpcSender<FileManagerResult<()>>,
+    ),
+
+    /// Remove an entry, along with its contents if the flag is set
+    RemoveFileSystemEntry(FileSystemLocator, bool, IpcSender<FileManagerResult<()>>),
+
+    /// Add an entry for a file of a file system, and send back the associated FileID
+    GetFileSystemFile(
+        FileSystemLocator,
+        FileOrigin,
+        IpcSender<FileManagerResult<SelectedFile>>,
+    ),
+
+    /// Forget a file that the user picked, whose handle went away
+    ForgetPickedFile(Uuid),
+
+    /// Open a file of an origin private file system for a sync access handle, and send back
+    /// the id to use to access it
+    OpenSyncAccessHandle(FileSystemLocator, IpcSender<FileManagerResult<Uuid>>),
+
+    /// Operate on a file opened for a sync access handle
+    SyncAccess(
+        Uuid,
+        SyncAccessOperation,
+        IpcSender<FileManagerResult<SyncAccessReply>>,
+    ),
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -173,7 +254,7 @@ pub enum ReadFileProgress {
 
 pub type FileManagerResult<T> = Result<T, FileManagerThreadError>;
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
 pub enum FileManagerThreadError {
     /// The selection action is invalid due to exceptional reason
     InvalidSelection,
@@ -183,4 +264,23 @@ pub enum FileManagerThreadError {
     FileSystemError(String),
     /// Blob URL Store error
     BlobURLStoreError(BlobURLStoreError),
+    /// Errors returned from File System Access API request
+    FileSystemAccessError(FileSystemAccessError),
+}
+
+/// <https://wicg.github.io/file-system-access/#errors>
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+pub enum FileSystemAccessError {
+    /// There is no entry by this name
+    NotFound,
+    /// The entry is not of the expected kind
+    TypeMismatch,
+    /// The name is not a valid name for an entry
+    InvalidName,
+    /// A directory that is not empty was removed without its contents
+    InvalidModification,
+    /// The file is in use by a sync access handle
+    NoModificationAllowed,
+    /// There is nowhere to keep the origin private file systems
+    Unavailable,
 }
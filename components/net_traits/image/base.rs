@@ -4,7 +4,8 @@
 
 use crate::image_cache::CorsStatus;
 use ipc_channel::ipc::IpcSharedMemory;
-use piston_image::{DynamicImage, ImageFormat};
+use piston_image::imageops::{self, FilterType};
+use piston_image::{DynamicImage, ImageFormat, RgbaImage};
 use pixels::PixelFormat;
 use std::fmt;
 
@@ -74,6 +75,27 @@ pub fn load_from_memory(buffer: &[u8], cors_status: CorsStatus) -> Option<Image>
     }
 }
 
+/// Scales down the data of an image with four bytes per pixel so that neither of its
+/// dimensions exceeds `max_dimension`, keeping its aspect ratio. Every channel is filtered
+/// the same way, so the order of the channels doesn't matter. Returns the new size and data,
+/// or `None` if the image already fits.
+pub fn downscale_to_fit(
+    bytes: &[u8],
+    width: u32,
+    height: u32,
+    max_dimension: u32,
+) -> Option<(u32, u32, Vec<u8>)> {
+    if max_dimension == 0 || (width <= max_dimension && height <= max_dimension) {
+        return None;
+    }
+    let scale = max_dimension as f64 / width.max(height) as f64;
+    let new_width = ((width as f64 * scale).round() as u32).max(1);
+    let new_height = ((height as f64 * scale).round() as u32).max(1);
+    let image = RgbaImage::from_raw(width, height, bytes.to_vec())?;
+    let resized = imageops::resize(&image, new_width, new_height, FilterType::Triangle);
+    Some((new_width, new_height, resized.into_raw()))
+}
+
 // https://developer.mozilla.org/en-US/docs/Web/HTML/Element/img
 pub fn detect_image_format(buffer: &[u8]) -> Result<ImageFormat, &str> {
     if is_gif(buffer) {
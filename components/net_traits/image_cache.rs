@@ -124,6 +124,10 @@ pub trait ImageCache: Sync + Send {
 
     /// Inform the image cache about a response for a pending request.
     fn notify_pending_response(&self, id: PendingImageId, action: FetchResponseMsg);
+
+    /// Drop the decoded bitmaps of all the images which aren't used outside of the cache,
+    /// in response to memory pressure.
+    fn purge(&self);
 }
 
 /// Whether this response passed any CORS checks, and is thus safe to read from
@@ -19,7 +19,9 @@ use crate::filemanager_thread::FileManagerThreadMsg;
 use crate::request::{Request, RequestBuilder};
 use crate::response::{HttpsState, Response, ResponseInit};
 use crate::storage_thread::StorageThreadMsg;
+use crate::webauthn::AuthenticatorMsg;
 use cookie::Cookie;
+use embedder_traits::{CertificateError, CertificateErrorId, DownloadAction, DownloadId};
 use headers::{ContentType, HeaderMapExt, ReferrerPolicy as ReferrerPolicyHeader};
 use http::{Error as HttpError, HeaderMap};
 use hyper::Error as HyperError;
@@ -29,7 +31,8 @@ use ipc_channel::ipc::{self, IpcReceiver, IpcSender};
 use ipc_channel::router::ROUTER;
 use ipc_channel::Error as IpcError;
 use mime::Mime;
-use msg::constellation_msg::HistoryStateId;
+use msg::constellation_msg::{HistoryStateId, NetworkConditions, PipelineId};
+use msg::constellation_msg::TopLevelBrowsingContextId;
 use servo_url::ServoUrl;
 use time::precise_time_ns;
 use webrender_api::ImageKey;
@@ -42,6 +45,7 @@ pub mod quality;
 pub mod request;
 pub mod response;
 pub mod storage_thread;
+pub mod webauthn;
 
 /// Image handling.
 ///
@@ -402,6 +406,9 @@ pub enum CoreResourceMsg {
         IpcSender<FetchResponseMsg>,
         /* cancel_chan */ Option<IpcReceiver<()>>,
     ),
+    /// Resolve the host of a URL ahead of a fetch from it, for `preconnect` and
+    /// `dns-prefetch` links. No connection is opened.
+    PrefetchDns(ServoUrl),
     /// Store a cookie for a given originating URL
     SetCookieForUrl(ServoUrl, Serde<Cookie<'static>>, CookieSource),
     /// Store a set of cookies for a given originating URL
@@ -421,12 +428,37 @@ pub enum CoreResourceMsg {
     SetHistoryState(HistoryStateId, Vec<u8>),
     /// Removes history states for the given ids
     RemoveHistoryStates(Vec<HistoryStateId>),
+    /// Drop every response stored in the HTTP cache
+    ClearHttpCache,
+    /// Ask the embedder whether to trust the certificate that failed to verify for a request
+    /// of the pipeline, on behalf of the top-level browsing context
+    ReportCertificateError(CertificateErrorId, PipelineId, TopLevelBrowsingContextId),
+    /// Trust the certificate that failed to verify for a request of the pipeline for its host
+    /// if the user chose to, and forget about it otherwise
+    AnswerCertificateError(CertificateErrorId, PipelineId, bool),
+    /// Make the network behave like under the given conditions for the requests of the given
+    /// pipelines, or like it really does when there are none
+    SetNetworkConditions(Vec<PipelineId>, Option<NetworkConditions>),
+    /// Send the navigations to the URLs of the scheme to the given handler URL, which contains
+    /// a `%s` where the URL goes, once the user of the given browser allowed it
+    RegisterProtocolHandler(String, ServoUrl, TopLevelBrowsingContextId),
+    /// Stop sending the navigations to the URLs of the scheme to the given handler URL
+    UnregisterProtocolHandler(String, ServoUrl),
+    /// Save the response to the request to a file instead of rendering it, letting the embedder
+    /// of the given browser follow the download
+    Download(RequestBuilder, Option<TopLevelBrowsingContextId>),
+    /// Pause, resume or cancel a download
+    DownloadAction(DownloadId, DownloadAction),
     /// Synchronization message solely for knowing the state of the ResourceChannelManager loop
     Synchronize(IpcSender<()>),
     /// Send the network sender in constellation to CoreResourceThread
     NetworkMediator(IpcSender<CustomResponseMediator>),
     /// Message forwarded to file manager's handler
     ToFileManager(FileManagerThreadMsg),
+    /// Message forwarded to the authenticator of the public key credentials by the
+    /// constellation, once it checked that the relying party is one that the page may use.
+    /// The user is asked on behalf of the top-level browsing context
+    ToAuthenticator(AuthenticatorMsg, TopLevelBrowsingContextId),
     /// Break the load handler loop, send a reply when done cleaning up local resources
     /// and exit
     Exit(IpcSender<()>),
@@ -461,6 +493,7 @@ pub struct ResourceCorsData {
 #[derive(Clone, Debug, Deserialize, MallocSizeOf, Serialize)]
 pub struct ResourceFetchTiming {
     pub domain_lookup_start: u64,
+    pub domain_lookup_end: u64,
     pub timing_check_passed: bool,
     pub timing_type: ResourceTimingType,
     /// Number of redirects until final resource (currently limited to 20)
@@ -475,6 +508,14 @@ pub struct ResourceFetchTiming {
     pub connect_start: u64,
     pub connect_end: u64,
     pub start_time: u64,
+    /// Size of the response header fields and of the encoded body, in octets.
+    pub transfer_size: u64,
+    /// Size of the body before removing any content coding, in octets.
+    pub encoded_body_size: u64,
+    /// Size of the body after removing any content coding, in octets.
+    pub decoded_body_size: u64,
+    /// The ALPN protocol ID of the protocol used to fetch the resource.
+    pub next_hop_protocol: Option<String>,
 }
 
 pub enum RedirectStartValue {
@@ -500,6 +541,7 @@ pub enum ResourceTimeValue {
 pub enum ResourceAttribute {
     RedirectCount(u16),
     DomainLookupStart,
+    DomainLookupEnd,
     RequestStart,
     ResponseStart,
     RedirectStart(RedirectStartValue),
@@ -510,6 +552,10 @@ pub enum ResourceAttribute {
     SecureConnectionStart,
     ResponseEnd,
     StartTime(ResourceTimeValue),
+    TransferSize(u64),
+    EncodedBodySize(u64),
+    DecodedBodySize(u64),
+    NextHopProtocol(String),
 }
 
 #[derive(Clone, Copy, Debug, Deserialize, MallocSizeOf, PartialEq, Serialize)]
@@ -526,6 +572,7 @@ impl ResourceFetchTiming {
             timing_type: timing_type,
             timing_check_passed: true,
             domain_lookup_start: 0,
+            domain_lookup_end: 0,
             redirect_count: 0,
             secure_connection_start: 0,
             request_start: 0,
@@ -537,6 +584,10 @@ impl ResourceFetchTiming {
             connect_end: 0,
             response_end: 0,
             start_time: 0,
+            transfer_size: 0,
+            encoded_body_size: 0,
+            decoded_body_size: 0,
+            next_hop_protocol: None,
         }
     }
 
@@ -554,6 +605,7 @@ impl ResourceFetchTiming {
         }
         match attribute {
             ResourceAttribute::DomainLookupStart => self.domain_lookup_start = precise_time_ns(),
+            ResourceAttribute::DomainLookupEnd => self.domain_lookup_end = precise_time_ns(),
             ResourceAttribute::RedirectCount(count) => self.redirect_count = count,
             ResourceAttribute::RequestStart => self.request_start = precise_time_ns(),
             ResourceAttribute::ResponseStart => self.response_start = precise_time_ns(),
@@ -581,6 +633,10 @@ impl ResourceFetchTiming {
                     if self.redirect_start == 0 || !self.timing_check_passed => {},
                 _ => self.start_time = self.get_time_value(val),
             },
+            ResourceAttribute::TransferSize(size) => self.transfer_size = size,
+            ResourceAttribute::EncodedBodySize(size) => self.encoded_body_size = size,
+            ResourceAttribute::DecodedBodySize(size) => self.decoded_body_size = size,
+            ResourceAttribute::NextHopProtocol(protocol) => self.next_hop_protocol = Some(protocol),
         }
     }
 
@@ -596,12 +652,18 @@ impl ResourceFetchTiming {
     pub fn mark_timing_check_failed(&mut self) {
         self.timing_check_passed = false;
         self.domain_lookup_start = 0;
+        self.domain_lookup_end = 0;
         self.redirect_count = 0;
         self.request_start = 0;
         self.response_start = 0;
         self.redirect_start = 0;
         self.connect_start = 0;
         self.connect_end = 0;
+        self.secure_connection_start = 0;
+        self.transfer_size = 0;
+        self.encoded_body_size = 0;
+        self.decoded_body_size = 0;
+        self.next_hop_protocol = None;
     }
 }
 
@@ -693,7 +755,10 @@ pub enum NetworkError {
     Internal(String),
     LoadCancelled,
     /// SSL validation error that has to be handled in the HTML parser
-    SslValidation(ServoUrl, String),
+    SslValidation(
+        ServoUrl,
+        #[ignore_malloc_size_of = "Defined in embedder_traits"] CertificateError,
+    ),
 }
 
 impl NetworkError {
@@ -18,6 +18,7 @@ pub enum Initiator {
     Download,
     ImageSet,
     Manifest,
+    Prefetch,
     XSLT,
 }
 
@@ -84,6 +85,38 @@ pub enum RedirectMode {
     Manual,
 }
 
+/// A request [priority](https://fetch.spec.whatwg.org/#request-priority)
+#[derive(Clone, Copy, Debug, Deserialize, Eq, MallocSizeOf, PartialEq, Serialize)]
+pub enum RequestPriority {
+    High,
+    Low,
+    Auto,
+}
+
+impl RequestPriority {
+    /// Parses the value of a `fetchpriority` attribute, which is `auto` if it is missing or
+    /// invalid.
+    /// <https://html.spec.whatwg.org/multipage/#fetch-priority-attribute>
+    pub fn from_attribute_value(value: &str) -> RequestPriority {
+        if value.eq_ignore_ascii_case("high") {
+            RequestPriority::High
+        } else if value.eq_ignore_ascii_case("low") {
+            RequestPriority::Low
+        } else {
+            RequestPriority::Auto
+        }
+    }
+
+    /// The keyword of the priority, as reflected by the `fetchPriority` IDL attributes.
+    pub fn as_str(&self) -> &'static str {
+        match *self {
+            RequestPriority::High => "high",
+            RequestPriority::Low => "low",
+            RequestPriority::Auto => "auto",
+        }
+    }
+}
+
 /// [Response tainting](https://fetch.spec.whatwg.org/#concept-request-response-tainting)
 #[derive(Clone, Copy, MallocSizeOf, PartialEq)]
 pub enum ResponseTainting {
@@ -131,6 +164,8 @@ pub struct RequestBuilder {
     pub headers: HeaderMap,
     pub unsafe_request: bool,
     pub body: Option<Vec<u8>>,
+    pub keep_alive: bool,
+    pub preload: bool,
     pub service_workers_mode: ServiceWorkersMode,
     // TODO: client object
     pub destination: Destination,
@@ -156,6 +191,7 @@ pub struct RequestBuilder {
     pub url_list: Vec<ServoUrl>,
     pub parser_metadata: ParserMetadata,
     pub initiator: Initiator,
+    pub priority: RequestPriority,
 }
 
 impl RequestBuilder {
@@ -166,6 +202,8 @@ impl RequestBuilder {
             headers: HeaderMap::new(),
             unsafe_request: false,
             body: None,
+            keep_alive: false,
+            preload: false,
             service_workers_mode: ServiceWorkersMode::All,
             destination: Destination::None,
             synchronous: false,
@@ -183,6 +221,7 @@ impl RequestBuilder {
             url_list: vec![],
             parser_metadata: ParserMetadata::Default,
             initiator: Initiator::None,
+            priority: RequestPriority::Auto,
             csp_list: None,
         }
     }
@@ -192,6 +231,11 @@ impl RequestBuilder {
         self
     }
 
+    pub fn priority(mut self, priority: RequestPriority) -> RequestBuilder {
+        self.priority = priority;
+        self
+    }
+
     pub fn method(mut self, method: Method) -> RequestBuilder {
         self.method = method;
         self
@@ -212,6 +256,16 @@ impl RequestBuilder {
         self
     }
 
+    pub fn keep_alive(mut self, keep_alive: bool) -> RequestBuilder {
+        self.keep_alive = keep_alive;
+        self
+    }
+
+    pub fn preload(mut self, preload: bool) -> RequestBuilder {
+        self.preload = preload;
+        self
+    }
+
     pub fn destination(mut self, destination: Destination) -> RequestBuilder {
         self.destination = destination;
         self
@@ -288,8 +342,11 @@ impl RequestBuilder {
         request.headers = self.headers;
         request.unsafe_request = self.unsafe_request;
         request.body = self.body;
+        request.keep_alive = self.keep_alive;
+        request.preload = self.preload;
         request.service_workers_mode = self.service_workers_mode;
         request.destination = self.destination;
+        request.priority = self.priority;
         request.synchronous = self.synchronous;
         request.mode = self.mode;
         request.use_cors_preflight = self.use_cors_preflight;
@@ -335,13 +392,18 @@ pub struct Request {
     // TODO: target browsing context
     /// <https://fetch.spec.whatwg.org/#request-keepalive-flag>
     pub keep_alive: bool,
+    /// Whether the response is stored in the preload cache of the document, for the fetch
+    /// that uses it.
+    /// <https://html.spec.whatwg.org/multipage/#preload-cache>
+    pub preload: bool,
     /// <https://fetch.spec.whatwg.org/#request-service-workers-mode>
     pub service_workers_mode: ServiceWorkersMode,
     /// <https://fetch.spec.whatwg.org/#concept-request-initiator>
     pub initiator: Initiator,
     /// <https://fetch.spec.whatwg.org/#concept-request-destination>
     pub destination: Destination,
-    // TODO: priority object
+    /// <https://fetch.spec.whatwg.org/#request-priority>
+    pub priority: RequestPriority,
     /// <https://fetch.spec.whatwg.org/#concept-request-origin>
     pub origin: Origin,
     /// <https://fetch.spec.whatwg.org/#concept-request-referrer>
@@ -393,9 +455,11 @@ impl Request {
             body: None,
             window: Window::Client,
             keep_alive: false,
+            preload: false,
             service_workers_mode: ServiceWorkersMode::All,
             initiator: Initiator::None,
             destination: Destination::None,
+            priority: RequestPriority::Auto,
             origin: origin.unwrap_or(Origin::Client),
             referrer: Referrer::Client,
             referrer_policy: None,
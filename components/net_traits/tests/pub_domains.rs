@@ -2,7 +2,9 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use net_traits::pub_domains::get_registrable_domain_suffix_of_or_is_equal_to;
 use net_traits::pub_domains::{is_pub_domain, is_reg_domain, pub_suffix, reg_suffix};
+use servo_url::Host;
 
 // These tests may need to be updated if the PSL changes.
 
@@ -120,3 +122,17 @@ fn test_weirdness() {
     );
     assert!(!is_pub_domain(pub_suffix("city.yokohama.jp")));
 }
+
+#[test]
+fn test_registrable_domain_suffix_of_or_is_equal_to() {
+    let host = || Host::Domain("login.example.com".to_owned());
+    assert_eq!(
+        get_registrable_domain_suffix_of_or_is_equal_to("example.com", host()),
+        Some(Host::Domain("example.com".to_owned()))
+    );
+    assert!(get_registrable_domain_suffix_of_or_is_equal_to("login.example.com", host()).is_some());
+    assert!(get_registrable_domain_suffix_of_or_is_equal_to("com", host()).is_none());
+    assert!(get_registrable_domain_suffix_of_or_is_equal_to("ample.com", host()).is_none());
+    assert!(get_registrable_domain_suffix_of_or_is_equal_to("other.com", host()).is_none());
+    assert!(get_registrable_domain_suffix_of_or_is_equal_to("", host()).is_none());
+}
@@ -0,0 +1,98 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! The messages to the authenticator that creates the public key credentials of the Web
+//! Authentication API, and signs with them, on behalf of the pages. They carry the parameters
+//! of the commands of the Client to Authenticator Protocol.
+//! <https://fidoalliance.org/specs/fido-v2.0-ps-20190130/fido-client-to-authenticator-protocol-v2.0-ps-20190130.html#authenticator-api>
+
+use ipc_channel::ipc::IpcSender;
+
+/// The COSE identifier of ECDSA with P-256 and SHA-256, the algorithm of the credentials.
+/// <https://www.iana.org/assignments/cose/cose.xhtml#algorithms>
+pub const COSE_ALGORITHM_ES256: i32 = -7;
+
+/// <https://fidoalliance.org/specs/fido-v2.0-ps-20190130/fido-client-to-authenticator-protocol-v2.0-ps-20190130.html#authenticatorMakeCredential>
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct MakeCredentialRequest {
+    /// The SHA-256 hash of the client data, which the attestation covers.
+    pub client_data_hash: Vec<u8>,
+    pub rp_id: String,
+    /// The user handle of the account at the relying party.
+    pub user_id: Vec<u8>,
+    pub user_name: String,
+    /// The COSE identifiers of the algorithms that the relying party accepts, the preferred
+    /// first.
+    pub algorithms: Vec<i32>,
+    /// The ids of the credentials that the account already has, which must not be created
+    /// again on the same authenticator.
+    pub exclude_list: Vec<Vec<u8>>,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct MakeCredentialResponse {
+    pub credential_id: Vec<u8>,
+    pub authenticator_data: Vec<u8>,
+    /// The attestation object in CBOR, whose statement is of the "none" format.
+    pub attestation_object: Vec<u8>,
+    /// The public key of the credential as a DER SubjectPublicKeyInfo.
+    pub public_key: Vec<u8>,
+    pub algorithm: i32,
+}
+
+/// <https://fidoalliance.org/specs/fido-v2.0-ps-20190130/fido-client-to-authenticator-protocol-v2.0-ps-20190130.html#authenticatorGetAssertion>
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct GetAssertionRequest {
+    /// The SHA-256 hash of the client data, which the signature covers.
+    pub client_data_hash: Vec<u8>,
+    pub rp_id: String,
+    /// The ids of the credentials that may be used, or none for any credential of the relying
+    /// party.
+    pub allow_list: Vec<Vec<u8>>,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct GetAssertionResponse {
+    pub credential_id: Vec<u8>,
+    pub authenticator_data: Vec<u8>,
+    /// The DER-encoded ECDSA signature of the authenticator data and the client data hash.
+    pub signature: Vec<u8>,
+    pub user_handle: Vec<u8>,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub enum AuthenticatorError {
+    /// None of the algorithms that the relying party accepts is supported.
+    UnsupportedAlgorithm,
+    /// One of the credentials of the exclude list is on the authenticator.
+    CredentialExcluded,
+    /// There is no credential for the relying party that is allowed.
+    NoCredentials,
+    /// The key of a credential could not be generated or used.
+    Failed,
+    /// The user did not agree to the operation.
+    NotAllowed,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub enum AuthenticatorMsg {
+    MakeCredential(
+        MakeCredentialRequest,
+        IpcSender<Result<MakeCredentialResponse, AuthenticatorError>>,
+    ),
+    GetAssertion(
+        GetAssertionRequest,
+        IpcSender<Result<GetAssertionResponse, AuthenticatorError>>,
+    ),
+}
+
+impl AuthenticatorMsg {
+    /// The relying party that the operation is for.
+    pub fn rp_id(&self) -> &str {
+        match *self {
+            AuthenticatorMsg::MakeCredential(ref request, _) => &request.rp_id,
+            AuthenticatorMsg::GetAssertion(ref request, _) => &request.rp_id,
+        }
+    }
+}
@@ -7,8 +7,8 @@
 use crate::time::duration_from_seconds;
 use ipc_channel::ipc::{self, IpcReceiver};
 use ipc_channel::router::ROUTER;
-use profile_traits::mem::ReportsChan;
 use profile_traits::mem::{ProfilerChan, ProfilerMsg, ReportKind, Reporter, ReporterRequest};
+use profile_traits::mem::{ReportsChan, ReportsTreeNode};
 use std::borrow::ToOwned;
 use std::cmp::Ordering;
 use std::collections::HashMap;
@@ -121,6 +121,12 @@ impl Profiler {
                 true
             },
 
+            ProfilerMsg::Report(sender) => {
+                let mut forest = self.collect_reports();
+                let _ = sender.send(forest.to_nodes());
+                true
+            },
+
             ProfilerMsg::Exit => false,
         }
     }
@@ -130,6 +136,14 @@ impl Profiler {
         println!("Begin memory reports {}", elapsed.as_secs());
         println!("|");
 
+        self.collect_reports().print();
+
+        println!("|");
+        println!("End memory reports");
+        println!("");
+    }
+
+    fn collect_reports(&self) -> ReportsForest {
         // Collect reports from memory reporters.
         //
         // This serializes the report-gathering. It might be worth creating a new scoped thread for
@@ -207,11 +221,7 @@ impl Profiler {
             );
         }
 
-        forest.print();
-
-        println!("|");
-        println!("End memory reports");
-        println!("");
+        forest
     }
 }
 
@@ -320,6 +330,15 @@ impl ReportsTree {
             child.print(depth + 1);
         }
     }
+
+    fn to_node(&self) -> ReportsTreeNode {
+        ReportsTreeNode {
+            path_seg: self.path_seg.clone(),
+            size: self.size,
+            count: self.count,
+            children: self.children.iter().map(ReportsTree::to_node).collect(),
+        }
+    }
 }
 
 /// A collection of ReportsTrees. It represents the data from multiple memory reports in a form
@@ -350,6 +369,24 @@ impl ReportsForest {
     }
 
     fn print(&mut self) {
+        // Print the forest.
+        for tree in &self.sorted_trees() {
+            tree.print(0);
+            // Print a blank line after non-degenerate trees.
+            if !tree.children.is_empty() {
+                println!("|");
+            }
+        }
+    }
+
+    fn to_nodes(&mut self) -> Vec<ReportsTreeNode> {
+        self.sorted_trees()
+            .into_iter()
+            .map(ReportsTree::to_node)
+            .collect()
+    }
+
+    fn sorted_trees(&mut self) -> Vec<&ReportsTree> {
         // Fill in sizes of interior nodes, and recursively sort the sub-trees.
         for (_, tree) in &mut self.trees {
             tree.compute_interior_node_sizes_and_sort();
@@ -371,15 +408,7 @@ impl ReportsForest {
                 a.path_seg.cmp(&b.path_seg)
             }
         });
-
-        // Print the forest.
-        for tree in &v {
-            tree.print(0);
-            // Print a blank line after non-degenerate trees.
-            if !tree.children.is_empty() {
-                println!("|");
-            }
-        }
+        v
     }
 }
 
@@ -178,6 +178,24 @@ impl Reporter {
     }
 }
 
+/// A node of the trees built from the reports of all the memory reporters, which gathers the
+/// reports whose paths start with the path leading to this node.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ReportsTreeNode {
+    /// The segment of the report paths that maps to this node, e.g. `explicit`,
+    /// `url(https://servo.org/)` or `layout-thread`.
+    pub path_seg: String,
+
+    /// The sum of the sizes of the reports gathered by this node, in bytes.
+    pub size: usize,
+
+    /// For leaf nodes, the number of reports that mapped to this node. Zero for interior nodes.
+    pub count: u32,
+
+    /// The child nodes, sorted by decreasing size.
+    pub children: Vec<ReportsTreeNode>,
+}
+
 /// An easy way to build a path for a report.
 #[macro_export]
 macro_rules! path {
@@ -203,6 +221,10 @@ pub enum ProfilerMsg {
     /// Triggers printing of the memory profiling metrics.
     Print,
 
+    /// Collects the reports of all the memory reporters and sends back the trees they form,
+    /// e.g. to display them in `servo:memory`.
+    Report(IpcSender<Vec<ReportsTreeNode>>),
+
     /// Tells the memory profiler to shut down.
     Exit,
 }
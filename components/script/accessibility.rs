@@ -0,0 +1,667 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! The accessibility tree of a document, which embedders expose to the accessibility API of
+//! their platform.
+//!
+//! Roles, names and states come from ARIA attributes and from the native semantics of HTML
+//! elements. Elements without semantics are left out of the tree, and their children become
+//! children of their closest ancestor in the tree. Same-origin iframes are included.
+
+use crate::dom::bindings::codegen::Bindings::DocumentBinding::DocumentMethods;
+use crate::dom::bindings::codegen::Bindings::HTMLInputElementBinding::HTMLInputElementMethods;
+use crate::dom::bindings::codegen::Bindings::HTMLLabelElementBinding::HTMLLabelElementMethods;
+use crate::dom::bindings::codegen::Bindings::HTMLOptionElementBinding::HTMLOptionElementMethods;
+use crate::dom::bindings::codegen::Bindings::HTMLTextAreaElementBinding::HTMLTextAreaElementMethods;
+use crate::dom::bindings::codegen::Bindings::NodeBinding::NodeMethods;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::characterdata::CharacterData;
+use crate::dom::document::Document;
+use crate::dom::element::Element;
+use crate::dom::htmliframeelement::HTMLIFrameElement;
+use crate::dom::htmlinputelement::{HTMLInputElement, InputType};
+use crate::dom::htmllabelelement::HTMLLabelElement;
+use crate::dom::htmloptionelement::HTMLOptionElement;
+use crate::dom::htmltextareaelement::HTMLTextAreaElement;
+use crate::dom::node::{Node, ShadowIncluding};
+use crate::dom::text::Text;
+use crate::script_thread::ScriptThread;
+use app_units::Au;
+use embedder_traits::{AccessibilityNode, AccessibilityRole, AccessibilityStates};
+use euclid::default::Vector2D;
+use euclid::{Point2D, Rect, Size2D};
+use html5ever::LocalName;
+use servo_atoms::Atom;
+use style_traits::CSSPixel;
+use webrender_api::units::DeviceIntRect;
+
+/// Elements that are never rendered, along with their content.
+static HIDDEN_TAGS: &[&str] = &[
+    "datalist", "head", "noscript", "script", "style", "template", "title",
+];
+
+/// A document whose nodes are added to the tree.
+struct Frame<'a> {
+    top_document: &'a Document,
+    document: &'a Document,
+    /// The position of the viewport of `document` in the viewport of `top_document`.
+    offset: Vector2D<Au>,
+}
+
+/// Builds the accessibility tree of `document` and of its same-origin iframes.
+pub fn accessibility_tree(document: &Document) -> AccessibilityNode {
+    let frame = Frame {
+        top_document: document,
+        document,
+        offset: Vector2D::zero(),
+    };
+    document_node(&frame)
+}
+
+fn document_node(frame: &Frame) -> AccessibilityNode {
+    let mut node = new_node(AccessibilityRole::Document, frame.document.Title().into());
+    if let Some(root) = frame.document.GetDocumentElement() {
+        add_children(frame, root.upcast(), &mut node.children);
+    }
+    node
+}
+
+fn new_node(role: AccessibilityRole, name: String) -> AccessibilityNode {
+    AccessibilityNode {
+        role,
+        name,
+        description: String::new(),
+        value: None,
+        level: None,
+        states: AccessibilityStates::default(),
+        bounds: None,
+        children: vec![],
+    }
+}
+
+fn add_children(frame: &Frame, node: &Node, children: &mut Vec<AccessibilityNode>) {
+    for child in node.children() {
+        add_node(frame, &child, children);
+    }
+}
+
+/// Appends the accessibility node of `node` to `nodes`, or the nodes of its children if it
+/// has no semantics.
+fn add_node(frame: &Frame, node: &Node, nodes: &mut Vec<AccessibilityNode>) {
+    if let Some(text) = node.downcast::<Text>() {
+        if node
+            .GetParentElement()
+            .map_or(false, |parent| parent.is_inert())
+        {
+            return;
+        }
+        let name = collapse_whitespace(&text.upcast::<CharacterData>().data());
+        if name.is_empty() {
+            return;
+        }
+        let mut text_node = new_node(AccessibilityRole::StaticText, name);
+        text_node.bounds = bounds(frame, node);
+        return nodes.push(text_node);
+    }
+    let element = match node.downcast::<Element>() {
+        Some(element) => element,
+        None => return,
+    };
+    if is_hidden(element) {
+        return;
+    }
+    // Inert elements are left out, but a modal dialog inside them is not.
+    if element.is_inert() {
+        return add_children(frame, node, nodes);
+    }
+    // Elements without boxes are not rendered, except for the options of select elements,
+    // which are drawn by the select element itself.
+    let bounds = bounds(frame, node);
+    if bounds.is_none() && element.downcast::<HTMLOptionElement>().is_none() {
+        return;
+    }
+
+    let role = match role(element) {
+        Some(role) => role,
+        None => return add_children(frame, node, nodes),
+    };
+    let mut accessibility_node = new_node(role, accessible_name(frame.document, element, role));
+    accessibility_node.description = accessible_description(frame.document, element);
+    accessibility_node.value = value(element);
+    accessibility_node.level = level(element, role);
+    accessibility_node.states = states(frame.document, element);
+    accessibility_node.bounds = bounds;
+
+    match element.downcast::<HTMLIFrameElement>() {
+        Some(iframe) => {
+            if let Some(document) = iframe.pipeline_id().and_then(ScriptThread::find_document) {
+                // Cross-origin documents are left out, as they may be in another script thread.
+                if frame
+                    .top_document
+                    .origin()
+                    .same_origin_domain(document.origin())
+                {
+                    let viewport = frame.document.window().current_viewport();
+                    let origin = node.bounding_content_box_or_zero().origin;
+                    let content_frame = Frame {
+                        top_document: frame.top_document,
+                        document: &document,
+                        offset: frame.offset + (origin - viewport.origin),
+                    };
+                    accessibility_node
+                        .children
+                        .push(document_node(&content_frame));
+                }
+            }
+        },
+        None => add_children(frame, node, &mut accessibility_node.children),
+    }
+    nodes.push(accessibility_node);
+}
+
+/// Whether `element` and its content are hidden from assistive technology.
+fn is_hidden(element: &Element) -> bool {
+    HIDDEN_TAGS.contains(&&**element.local_name()) ||
+        element.has_attribute(&local_name!("hidden")) ||
+        attribute_is_true(element, &LocalName::from("aria-hidden")) ||
+        element
+            .downcast::<HTMLInputElement>()
+            .map_or(false, |input| input.input_type() == InputType::Hidden)
+}
+
+fn attribute_is_true(element: &Element, name: &LocalName) -> bool {
+    element
+        .get_string_attribute(name)
+        .eq_ignore_ascii_case("true")
+}
+
+/// The box of `node` in device pixels, relative to the viewport of the top-level document.
+fn bounds(frame: &Frame, node: &Node) -> Option<DeviceIntRect> {
+    let rect = node.bounding_content_box()?;
+    let viewport = frame.document.window().current_viewport();
+    let origin = rect.origin - viewport.origin.to_vector() + frame.offset;
+    let rect = Rect::<f32, CSSPixel>::new(
+        Point2D::new(origin.x.to_f32_px(), origin.y.to_f32_px()),
+        Size2D::new(rect.size.width.to_f32_px(), rect.size.height.to_f32_px()),
+    );
+    Some((rect * frame.top_document.window().device_pixel_ratio()).to_i32())
+}
+
+/// The role of `element`, or `None` if it has no semantics of its own.
+fn role(element: &Element) -> Option<AccessibilityRole> {
+    // https://w3c.github.io/aria/#document-handling_author-errors_roles
+    let explicit_roles = element.get_string_attribute(&local_name!("role"));
+    for explicit_role in explicit_roles.split_whitespace() {
+        let explicit_role = explicit_role.to_ascii_lowercase();
+        if explicit_role == "none" || explicit_role == "presentation" {
+            // Focusable elements keep their role, so that users can tell what they are.
+            if element.is_focusable_area() {
+                break;
+            }
+            return None;
+        }
+        if let Some(role) = aria_role(&explicit_role) {
+            return Some(role);
+        }
+    }
+    if *element.namespace() != ns!(html) {
+        return match *element.local_name() {
+            local_name!("svg") => Some(AccessibilityRole::Image),
+            _ => None,
+        };
+    }
+    native_role(element)
+}
+
+fn aria_role(role: &str) -> Option<AccessibilityRole> {
+    Some(match role {
+        "alert" => AccessibilityRole::Alert,
+        "article" => AccessibilityRole::Article,
+        "banner" => AccessibilityRole::Banner,
+        "button" => AccessibilityRole::Button,
+        "cell" | "gridcell" => AccessibilityRole::Cell,
+        "checkbox" | "menuitemcheckbox" => AccessibilityRole::CheckBox,
+        "columnheader" => AccessibilityRole::ColumnHeader,
+        "combobox" => AccessibilityRole::ComboBox,
+        "complementary" => AccessibilityRole::Complementary,
+        "contentinfo" => AccessibilityRole::ContentInfo,
+        "dialog" | "alertdialog" => AccessibilityRole::Dialog,
+        "document" => AccessibilityRole::Document,
+        "figure" => AccessibilityRole::Figure,
+        "form" => AccessibilityRole::Form,
+        "group" => AccessibilityRole::Group,
+        "heading" => AccessibilityRole::Heading,
+        "img" => AccessibilityRole::Image,
+        "link" => AccessibilityRole::Link,
+        "list" => AccessibilityRole::List,
+        "listbox" => AccessibilityRole::ListBox,
+        "listitem" => AccessibilityRole::ListItem,
+        "main" => AccessibilityRole::Main,
+        "menu" => AccessibilityRole::Menu,
+        "menubar" => AccessibilityRole::MenuBar,
+        "menuitem" => AccessibilityRole::MenuItem,
+        "meter" => AccessibilityRole::Meter,
+        "navigation" => AccessibilityRole::Navigation,
+        "option" => AccessibilityRole::Option,
+        "paragraph" => AccessibilityRole::Paragraph,
+        "progressbar" => AccessibilityRole::ProgressBar,
+        "radio" | "menuitemradio" => AccessibilityRole::RadioButton,
+        "region" => AccessibilityRole::Region,
+        "row" => AccessibilityRole::Row,
+        "rowheader" => AccessibilityRole::RowHeader,
+        "searchbox" => AccessibilityRole::SearchBox,
+        "separator" => AccessibilityRole::Separator,
+        "slider" => AccessibilityRole::Slider,
+        "spinbutton" => AccessibilityRole::SpinButton,
+        "status" => AccessibilityRole::Status,
+        "switch" => AccessibilityRole::Switch,
+        "tab" => AccessibilityRole::Tab,
+        "tablist" => AccessibilityRole::TabList,
+        "tabpanel" => AccessibilityRole::TabPanel,
+        "table" | "grid" | "treegrid" => AccessibilityRole::Table,
+        "textbox" => AccessibilityRole::TextBox,
+        "toolbar" => AccessibilityRole::Toolbar,
+        "tooltip" => AccessibilityRole::Tooltip,
+        "tree" => AccessibilityRole::Tree,
+        "treeitem" => AccessibilityRole::TreeItem,
+        _ => return None,
+    })
+}
+
+/// The role of HTML elements without a `role` attribute.
+///
+/// https://w3c.github.io/html-aam/#html-element-role-mappings
+fn native_role(element: &Element) -> Option<AccessibilityRole> {
+    Some(match *element.local_name() {
+        local_name!("a") | local_name!("area")
+            if element.has_attribute(&local_name!("href")) =>
+        {
+            AccessibilityRole::Link
+        },
+        local_name!("article") => AccessibilityRole::Article,
+        local_name!("aside") => AccessibilityRole::Complementary,
+        local_name!("button") | local_name!("summary") => AccessibilityRole::Button,
+        local_name!("details") | local_name!("fieldset") | local_name!("optgroup") => {
+            AccessibilityRole::Group
+        },
+        local_name!("dialog") => AccessibilityRole::Dialog,
+        local_name!("figure") => AccessibilityRole::Figure,
+        local_name!("footer") if !is_in_sectioning_content(element) => {
+            AccessibilityRole::ContentInfo
+        },
+        local_name!("form") => AccessibilityRole::Form,
+        local_name!("h1") |
+        local_name!("h2") |
+        local_name!("h3") |
+        local_name!("h4") |
+        local_name!("h5") |
+        local_name!("h6") => AccessibilityRole::Heading,
+        local_name!("header") if !is_in_sectioning_content(element) => AccessibilityRole::Banner,
+        local_name!("hr") => AccessibilityRole::Separator,
+        local_name!("iframe") => AccessibilityRole::Iframe,
+        // Images with an empty alternative text are decorative.
+        local_name!("img")
+            if element
+                .get_attribute(&ns!(), &local_name!("alt"))
+                .map_or(true, |alt| !alt.value().is_empty()) =>
+        {
+            AccessibilityRole::Image
+        },
+        local_name!("input") => match element.downcast::<HTMLInputElement>()?.input_type() {
+            InputType::Button | InputType::Image | InputType::Reset | InputType::Submit => {
+                AccessibilityRole::Button
+            },
+            InputType::Checkbox => AccessibilityRole::CheckBox,
+            InputType::Number => AccessibilityRole::SpinButton,
+            InputType::Radio => AccessibilityRole::RadioButton,
+            InputType::Range => AccessibilityRole::Slider,
+            InputType::Search => AccessibilityRole::SearchBox,
+            InputType::Hidden => return None,
+            _ => AccessibilityRole::TextBox,
+        },
+        local_name!("li") => AccessibilityRole::ListItem,
+        local_name!("main") => AccessibilityRole::Main,
+        local_name!("menu") | local_name!("ol") | local_name!("ul") => AccessibilityRole::List,
+        local_name!("meter") => AccessibilityRole::Meter,
+        local_name!("nav") => AccessibilityRole::Navigation,
+        local_name!("option") => AccessibilityRole::Option,
+        local_name!("output") => AccessibilityRole::Status,
+        local_name!("p") => AccessibilityRole::Paragraph,
+        local_name!("progress") => AccessibilityRole::ProgressBar,
+        // Sections are only landmarks if they have a name.
+        local_name!("section")
+            if element.has_attribute(&LocalName::from("aria-label")) ||
+                element.has_attribute(&LocalName::from("aria-labelledby")) =>
+        {
+            AccessibilityRole::Region
+        },
+        local_name!("select")
+            if element.has_attribute(&local_name!("multiple")) ||
+                element.get_uint_attribute(&local_name!("size"), 0) > 1 =>
+        {
+            AccessibilityRole::ListBox
+        },
+        local_name!("select") => AccessibilityRole::ComboBox,
+        local_name!("table") => AccessibilityRole::Table,
+        local_name!("td") => AccessibilityRole::Cell,
+        local_name!("textarea") => AccessibilityRole::TextBox,
+        local_name!("th") => AccessibilityRole::ColumnHeader,
+        local_name!("tr") => AccessibilityRole::Row,
+        _ => return None,
+    })
+}
+
+/// Whether `element` is inside an article, aside, main, nav or section element, in which
+/// case headers and footers are not landmarks of the whole page.
+fn is_in_sectioning_content(element: &Element) -> bool {
+    element
+        .upcast::<Node>()
+        .ancestors()
+        .filter_map(DomRoot::downcast::<Element>)
+        .any(|ancestor| match *ancestor.local_name() {
+            local_name!("article") |
+            local_name!("aside") |
+            local_name!("main") |
+            local_name!("nav") |
+            local_name!("section") => true,
+            _ => false,
+        })
+}
+
+/// Whether elements of `role` are named by their content.
+///
+/// https://w3c.github.io/aria/#namefromcontent
+fn is_named_from_content(role: AccessibilityRole) -> bool {
+    match role {
+        AccessibilityRole::Button |
+        AccessibilityRole::Cell |
+        AccessibilityRole::CheckBox |
+        AccessibilityRole::ColumnHeader |
+        AccessibilityRole::Heading |
+        AccessibilityRole::Link |
+        AccessibilityRole::MenuItem |
+        AccessibilityRole::Option |
+        AccessibilityRole::RadioButton |
+        AccessibilityRole::Row |
+        AccessibilityRole::RowHeader |
+        AccessibilityRole::Switch |
+        AccessibilityRole::Tab |
+        AccessibilityRole::Tooltip |
+        AccessibilityRole::TreeItem => true,
+        _ => false,
+    }
+}
+
+/// A simplified version of the accessible name computation.
+///
+/// https://w3c.github.io/accname/#mapping_additional_nd_te
+fn accessible_name(document: &Document, element: &Element, role: AccessibilityRole) -> String {
+    let labelled_by = element.get_string_attribute(&LocalName::from("aria-labelledby"));
+    let name = referenced_text(document, &labelled_by);
+    if !name.is_empty() {
+        return name;
+    }
+    let name = collapse_whitespace(&element.get_string_attribute(&LocalName::from("aria-label")));
+    if !name.is_empty() {
+        return name;
+    }
+
+    let name = native_name(element);
+    if !name.is_empty() {
+        return name;
+    }
+    if is_named_from_content(role) {
+        let mut text = String::new();
+        collect_text(element.upcast(), &mut text);
+        let name = collapse_whitespace(&text);
+        if !name.is_empty() {
+            return name;
+        }
+    }
+    let name = collapse_whitespace(&element.get_string_attribute(&local_name!("title")));
+    if !name.is_empty() {
+        return name;
+    }
+    collapse_whitespace(&element.get_string_attribute(&local_name!("placeholder")))
+}
+
+/// The accessible name of `element`, which is empty if it has no semantics of its own.
+pub fn name(document: &Document, element: &Element) -> String {
+    role(element).map_or(String::new(), |role| {
+        accessible_name(document, element, role)
+    })
+}
+
+/// The name that the markup of `element` gives it, such as the labels of a form control.
+fn native_name(element: &Element) -> String {
+    let attribute = |name: &LocalName| collapse_whitespace(&element.get_string_attribute(name));
+    match *element.local_name() {
+        local_name!("img") | local_name!("area") => attribute(&local_name!("alt")),
+        local_name!("input") => {
+            let input = match element.downcast::<HTMLInputElement>() {
+                Some(input) => input,
+                None => return String::new(),
+            };
+            match input.input_type() {
+                InputType::Image => attribute(&local_name!("alt")),
+                InputType::Button => attribute(&local_name!("value")),
+                InputType::Reset | InputType::Submit => {
+                    let value = attribute(&local_name!("value"));
+                    if !value.is_empty() {
+                        value
+                    } else if input.input_type() == InputType::Reset {
+                        "Reset".to_owned()
+                    } else {
+                        "Submit".to_owned()
+                    }
+                },
+                _ => label_text(element),
+            }
+        },
+        local_name!("button") |
+        local_name!("meter") |
+        local_name!("output") |
+        local_name!("progress") |
+        local_name!("select") |
+        local_name!("textarea") => label_text(element),
+        local_name!("fieldset") => child_text(element, &local_name!("legend")),
+        local_name!("figure") => child_text(element, &local_name!("figcaption")),
+        local_name!("table") => child_text(element, &local_name!("caption")),
+        _ => String::new(),
+    }
+}
+
+/// The text of the labels of the form control `element`.
+fn label_text(element: &Element) -> String {
+    let root = element.root_element();
+    let labels = root
+        .upcast::<Node>()
+        .traverse_preorder(ShadowIncluding::No)
+        .filter_map(DomRoot::downcast::<HTMLLabelElement>)
+        .filter(|label| {
+            label
+                .GetControl()
+                .map_or(false, |control| control.upcast::<Element>() == element)
+        });
+    let mut text = String::new();
+    for label in labels {
+        collect_text(label.upcast(), &mut text);
+        text.push(' ');
+    }
+    collapse_whitespace(&text)
+}
+
+/// The text of the first child of `element` called `name`.
+fn child_text(element: &Element, name: &LocalName) -> String {
+    let child = element
+        .upcast::<Node>()
+        .children()
+        .filter_map(DomRoot::downcast::<Element>)
+        .find(|child| child.local_name() == name);
+    let mut text = String::new();
+    if let Some(child) = child {
+        collect_text(child.upcast(), &mut text);
+    }
+    collapse_whitespace(&text)
+}
+
+/// The text of the elements whose identifiers are listed in `ids`.
+fn referenced_text(document: &Document, ids: &str) -> String {
+    let mut text = String::new();
+    for id in ids.split_whitespace() {
+        if let Some(element) = document.get_element_by_id(&Atom::from(id)) {
+            collect_text(element.upcast(), &mut text);
+            text.push(' ');
+        }
+    }
+    collapse_whitespace(&text)
+}
+
+fn accessible_description(document: &Document, element: &Element) -> String {
+    let described_by = element.get_string_attribute(&LocalName::from("aria-describedby"));
+    referenced_text(document, &described_by)
+}
+
+/// Appends the text of `node` that is not hidden to `text`, using the alternative text of
+/// images.
+fn collect_text(node: &Node, text: &mut String) {
+    if let Some(data) = node.downcast::<Text>() {
+        text.push_str(&data.upcast::<CharacterData>().data());
+        return;
+    }
+    if let Some(element) = node.downcast::<Element>() {
+        if is_hidden(element) {
+            return;
+        }
+        if element.local_name() == &local_name!("img") {
+            text.push_str(&element.get_string_attribute(&local_name!("alt")));
+            return;
+        }
+    }
+    for child in node.children() {
+        collect_text(&child, text);
+    }
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// The current value of a form control or of an element with an ARIA range role.
+fn value(element: &Element) -> Option<String> {
+    let value_text = element.get_string_attribute(&LocalName::from("aria-valuetext"));
+    if !value_text.is_empty() {
+        return Some(value_text.into());
+    }
+    let value_now = element.get_string_attribute(&LocalName::from("aria-valuenow"));
+    if !value_now.is_empty() {
+        return Some(value_now.into());
+    }
+
+    if let Some(input) = element.downcast::<HTMLInputElement>() {
+        return match input.input_type() {
+            // The value of a password field must not be exposed.
+            InputType::Password |
+            InputType::Button |
+            InputType::Checkbox |
+            InputType::Image |
+            InputType::Radio |
+            InputType::Reset |
+            InputType::Submit => None,
+            _ => Some(input.Value().into()),
+        };
+    }
+    if let Some(textarea) = element.downcast::<HTMLTextAreaElement>() {
+        return Some(textarea.Value().into());
+    }
+    match *element.local_name() {
+        local_name!("select") => {
+            let selected: Vec<String> = element
+                .upcast::<Node>()
+                .traverse_preorder(ShadowIncluding::No)
+                .filter_map(DomRoot::downcast::<HTMLOptionElement>)
+                .filter(|option| option.Selected())
+                .map(|option| collapse_whitespace(&option.Text()))
+                .collect();
+            Some(selected.join(", "))
+        },
+        local_name!("meter") | local_name!("progress") => {
+            let value = element.get_string_attribute(&local_name!("value"));
+            Some(value.into())
+        },
+        _ => None,
+    }
+}
+
+/// The level of a heading or of an item of a tree.
+fn level(element: &Element, role: AccessibilityRole) -> Option<u32> {
+    let aria_level = element.get_string_attribute(&LocalName::from("aria-level"));
+    if let Ok(level) = aria_level.trim().parse::<u32>() {
+        if level > 0 {
+            return Some(level);
+        }
+    }
+    if role != AccessibilityRole::Heading {
+        return None;
+    }
+    Some(match *element.local_name() {
+        local_name!("h1") => 1,
+        local_name!("h3") => 3,
+        local_name!("h4") => 4,
+        local_name!("h5") => 5,
+        local_name!("h6") => 6,
+        // Headings that are not heading elements default to level 2.
+        _ => 2,
+    })
+}
+
+fn states(document: &Document, element: &Element) -> AccessibilityStates {
+    let aria_state = |name: &LocalName| {
+        let value = element.get_string_attribute(name);
+        if value.eq_ignore_ascii_case("true") || value.eq_ignore_ascii_case("mixed") {
+            Some(true)
+        } else if value.eq_ignore_ascii_case("false") {
+            Some(false)
+        } else {
+            None
+        }
+    };
+
+    let input = element.downcast::<HTMLInputElement>();
+    let checked = match input {
+        Some(input)
+            if input.input_type() == InputType::Checkbox ||
+                input.input_type() == InputType::Radio =>
+        {
+            Some(input.Checked())
+        },
+        _ => aria_state(&LocalName::from("aria-checked")),
+    };
+    let expanded = match *element.local_name() {
+        local_name!("details") => Some(element.has_attribute(&local_name!("open"))),
+        _ => aria_state(&LocalName::from("aria-expanded")),
+    };
+    let selected = match element.downcast::<HTMLOptionElement>() {
+        Some(option) => option.Selected(),
+        None => attribute_is_true(element, &LocalName::from("aria-selected")),
+    };
+    let is_text_control = input.is_some() || element.is::<HTMLTextAreaElement>();
+
+    AccessibilityStates {
+        focusable: element.is_focusable_area(),
+        focused: document
+            .get_focused_element()
+            .map_or(false, |focused| &*focused == element),
+        disabled: element.disabled_state() ||
+            attribute_is_true(element, &LocalName::from("aria-disabled")),
+        readonly: (is_text_control && element.has_attribute(&local_name!("readonly"))) ||
+            attribute_is_true(element, &LocalName::from("aria-readonly")),
+        required: element.has_attribute(&local_name!("required")) ||
+            attribute_is_true(element, &LocalName::from("aria-required")),
+        selected,
+        checked,
+        expanded,
+    }
+}
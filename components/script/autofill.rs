@@ -0,0 +1,318 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! The form controls of a document that embedders can fill in for the user, such as with
+//! saved addresses or payment cards.
+//!
+//! Controls are described by their `autocomplete` attribute when they have one, and otherwise
+//! by a field name guessed from their type, name, identifier and label. They are filled in as
+//! if the user had typed the values, so that the `input` and `change` events that frameworks
+//! listen to are fired.
+
+use crate::accessibility;
+use crate::dom::bindings::codegen::Bindings::HTMLInputElementBinding::HTMLInputElementMethods;
+use crate::dom::bindings::codegen::Bindings::HTMLOptionElementBinding::HTMLOptionElementMethods;
+use crate::dom::bindings::codegen::Bindings::HTMLSelectElementBinding::HTMLSelectElementMethods;
+use crate::dom::bindings::codegen::Bindings::HTMLTextAreaElementBinding::HTMLTextAreaElementMethods;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::document::Document;
+use crate::dom::element::Element;
+use crate::dom::eventtarget::EventTarget;
+use crate::dom::htmlformelement::{FormControl, HTMLFormElement};
+use crate::dom::htmlinputelement::{HTMLInputElement, InputType};
+use crate::dom::htmlselectelement::HTMLSelectElement;
+use crate::dom::htmltextareaelement::HTMLTextAreaElement;
+use crate::dom::node::{Node, ShadowIncluding};
+use embedder_traits::FormField;
+use html5ever::LocalName;
+
+/// The autofill field names.
+/// <https://html.spec.whatwg.org/multipage/#autofill-field-name>
+static FIELD_NAMES: &[&str] = &[
+    "name",
+    "honorific-prefix",
+    "given-name",
+    "additional-name",
+    "family-name",
+    "honorific-suffix",
+    "nickname",
+    "username",
+    "new-password",
+    "current-password",
+    "one-time-code",
+    "organization-title",
+    "organization",
+    "street-address",
+    "address-line1",
+    "address-line2",
+    "address-line3",
+    "address-level4",
+    "address-level3",
+    "address-level2",
+    "address-level1",
+    "country",
+    "country-name",
+    "postal-code",
+    "cc-name",
+    "cc-given-name",
+    "cc-additional-name",
+    "cc-family-name",
+    "cc-number",
+    "cc-exp",
+    "cc-exp-month",
+    "cc-exp-year",
+    "cc-csc",
+    "cc-type",
+    "transaction-currency",
+    "transaction-amount",
+    "language",
+    "bday",
+    "bday-day",
+    "bday-month",
+    "bday-year",
+    "sex",
+    "url",
+    "photo",
+    "tel",
+    "tel-country-code",
+    "tel-national",
+    "tel-area-code",
+    "tel-local",
+    "tel-extension",
+    "email",
+    "impp",
+];
+
+/// The words in the names, identifiers and labels of controls without an `autocomplete`
+/// attribute that give away their field name. The first word that is found wins, so words
+/// that contain others come before them.
+static FIELD_NAME_HINTS: &[(&str, &str)] = &[
+    ("email", "email"),
+    ("mail", "email"),
+    ("username", "username"),
+    ("userid", "username"),
+    ("login", "username"),
+    ("firstname", "given-name"),
+    ("givenname", "given-name"),
+    ("forename", "given-name"),
+    ("middlename", "additional-name"),
+    ("lastname", "family-name"),
+    ("familyname", "family-name"),
+    ("surname", "family-name"),
+    ("cardholder", "cc-name"),
+    ("nameoncard", "cc-name"),
+    ("cardnumber", "cc-number"),
+    ("ccnumber", "cc-number"),
+    ("cvc", "cc-csc"),
+    ("cvv", "cc-csc"),
+    ("securitycode", "cc-csc"),
+    ("expir", "cc-exp"),
+    ("company", "organization"),
+    ("organization", "organization"),
+    ("organisation", "organization"),
+    ("address2", "address-line2"),
+    ("addressline2", "address-line2"),
+    ("address", "street-address"),
+    ("street", "street-address"),
+    ("postcode", "postal-code"),
+    ("postal", "postal-code"),
+    ("zip", "postal-code"),
+    ("city", "address-level2"),
+    ("town", "address-level2"),
+    ("province", "address-level1"),
+    ("state", "address-level1"),
+    ("region", "address-level1"),
+    ("country", "country-name"),
+    ("phone", "tel"),
+    ("mobile", "tel"),
+    ("birthday", "bday"),
+    ("birthdate", "bday"),
+    ("website", "url"),
+    ("name", "name"),
+];
+
+/// The form controls of `document` that can be filled in, in tree order.
+pub fn form_fields(document: &Document) -> Vec<FormField> {
+    let root = document.upcast::<Node>();
+    let forms: Vec<_> = root
+        .traverse_preorder(ShadowIncluding::No)
+        .filter_map(DomRoot::downcast::<HTMLFormElement>)
+        .collect();
+    root.traverse_preorder(ShadowIncluding::No)
+        .filter_map(DomRoot::downcast::<Element>)
+        .filter(|element| is_fillable(element))
+        .map(|element| form_field(document, &element, &forms))
+        .collect()
+}
+
+/// Whether `element` is a form control that the user can type a value into or pick one for.
+fn is_fillable(element: &Element) -> bool {
+    if element.disabled_state() || element.has_attribute(&local_name!("readonly")) {
+        return false;
+    }
+    if let Some(input) = element.downcast::<HTMLInputElement>() {
+        return match input.input_type() {
+            InputType::Date |
+            InputType::Email |
+            InputType::Month |
+            InputType::Number |
+            InputType::Password |
+            InputType::Search |
+            InputType::Tel |
+            InputType::Text |
+            InputType::Url => true,
+            _ => false,
+        };
+    }
+    element.is::<HTMLTextAreaElement>() || element.is::<HTMLSelectElement>()
+}
+
+fn form_field(
+    document: &Document,
+    element: &Element,
+    forms: &[DomRoot<HTMLFormElement>],
+) -> FormField {
+    let mut options = vec![];
+    let (type_, form, value, max_length) =
+        if let Some(input) = element.downcast::<HTMLInputElement>() {
+            // Passwords are left for the password manager.
+            let value = match input.input_type() {
+                InputType::Password => DOMString::new(),
+                _ => input.Value(),
+            };
+            (input.Type(), input.form_owner(), value, input.MaxLength())
+        } else if let Some(area) = element.downcast::<HTMLTextAreaElement>() {
+            (
+                area.Type(),
+                area.form_owner(),
+                area.Value(),
+                area.MaxLength(),
+            )
+        } else {
+            let select = element.downcast::<HTMLSelectElement>().unwrap();
+            options = select
+                .list_of_options()
+                .map(|option| option.Value().into())
+                .collect();
+            (select.Type(), select.form_owner(), select.Value(), -1)
+        };
+    let autocomplete: Vec<String> = element
+        .get_string_attribute(&LocalName::from("autocomplete"))
+        .split_ascii_whitespace()
+        .map(|token| token.to_ascii_lowercase())
+        .collect();
+    let label = accessibility::name(document, element);
+    let field_name = field_name(element, &autocomplete, &label);
+    FormField {
+        id: element.upcast::<Node>().unique_id(),
+        form: form.and_then(|form| forms.iter().position(|candidate| *candidate == form)),
+        type_: type_.into(),
+        name: element.get_string_attribute(&local_name!("name")).into(),
+        label,
+        autocomplete,
+        field_name: field_name.map(str::to_owned),
+        value: value.into(),
+        options,
+        max_length: if max_length < 0 {
+            None
+        } else {
+            Some(max_length as u32)
+        },
+    }
+}
+
+/// The autofill field name of `element`, from the last of its `autocomplete` tokens, which
+/// may be preceded by a section, an address type and a contact type, or else guessed.
+fn field_name(element: &Element, autocomplete: &[String], label: &str) -> Option<&'static str> {
+    match autocomplete.last().map(|token| &**token) {
+        Some("off") => return None,
+        Some(token) => {
+            if let Some(&name) = FIELD_NAMES.iter().find(|&&name| name == token) {
+                return Some(name);
+            }
+        },
+        None => {},
+    }
+
+    let input_type = element
+        .downcast::<HTMLInputElement>()
+        .map(|input| input.input_type());
+    let hints: Vec<String> = [
+        String::from(element.get_string_attribute(&local_name!("name"))),
+        String::from(element.get_string_attribute(&local_name!("id"))),
+        label.to_owned(),
+    ]
+    .iter()
+    .map(|hint| {
+        hint.chars()
+            .filter(char::is_ascii_alphanumeric)
+            .map(|c| c.to_ascii_lowercase())
+            .collect()
+    })
+    .collect();
+    let has_hint = |word: &str| hints.iter().any(|hint| hint.contains(word));
+    match input_type {
+        Some(InputType::Email) => return Some("email"),
+        Some(InputType::Tel) => return Some("tel"),
+        Some(InputType::Url) => return Some("url"),
+        Some(InputType::Password) => {
+            return Some(if has_hint("new") || has_hint("confirm") {
+                "new-password"
+            } else {
+                "current-password"
+            });
+        },
+        _ => {},
+    }
+    FIELD_NAME_HINTS
+        .iter()
+        .find(|&&(word, _)| has_hint(word))
+        .map(|&(_, name)| name)
+}
+
+/// Fills in the form controls of `document` with the given values, by the identifiers that
+/// `form_fields` reported. Controls that are gone or can no longer be filled in are skipped.
+pub fn fill_form_fields(document: &Document, values: Vec<(String, String)>) {
+    for (id, value) in values {
+        let element = document
+            .upcast::<Node>()
+            .traverse_preorder(ShadowIncluding::No)
+            .find(|node| node.unique_id() == id)
+            .and_then(DomRoot::downcast::<Element>);
+        if let Some(element) = element {
+            fill(&element, DOMString::from(value));
+        }
+    }
+}
+
+/// Fills in the form control `element` with `value`, firing the `input` and `change` events
+/// that typing it would, and returns whether it was filled in. Select elements are only
+/// filled in if they have an option whose value or label is `value`.
+pub fn fill(element: &Element, value: DOMString) -> bool {
+    if !is_fillable(element) {
+        return false;
+    }
+    if let Some(input) = element.downcast::<HTMLInputElement>() {
+        if input.SetValue(value).is_err() {
+            return false;
+        }
+    } else if let Some(area) = element.downcast::<HTMLTextAreaElement>() {
+        area.SetValue(value);
+    } else if let Some(select) = element.downcast::<HTMLSelectElement>() {
+        let option = select.list_of_options().find(|option| {
+            option.Value().eq_ignore_ascii_case(&value) ||
+                option.Label().trim().eq_ignore_ascii_case(value.trim())
+        });
+        match option {
+            Some(option) => option.SetSelected(true),
+            None => return false,
+        }
+    }
+    let target = element.upcast::<EventTarget>();
+    target.fire_bubbling_event(atom!("input"));
+    target.fire_bubbling_event(atom!("change"));
+    true
+}
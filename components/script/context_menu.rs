@@ -0,0 +1,87 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! The context menus that the embedder shows when the user presses the secondary button in a
+//! page, unless the page cancels the `contextmenu` event to show its own.
+
+use crate::dom::bindings::codegen::Bindings::DocumentBinding::DocumentMethods;
+use crate::dom::bindings::codegen::Bindings::HTMLImageElementBinding::HTMLImageElementMethods;
+use crate::dom::bindings::codegen::Bindings::SelectionBinding::SelectionMethods;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::element::Element;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::htmlanchorelement::HTMLAnchorElement;
+use crate::dom::htmlareaelement::HTMLAreaElement;
+use crate::dom::htmlimageelement::HTMLImageElement;
+use crate::dom::htmlinputelement::HTMLInputElement;
+use crate::dom::htmltextareaelement::HTMLTextAreaElement;
+use crate::dom::node::{document_from_node, window_from_node, Node, ShadowIncluding};
+use crate::spellcheck;
+use crate::task_source::{TaskSource, TaskSourceName};
+use embedder_traits::{ContextMenu, EmbedderMsg};
+use euclid::default::Point2D;
+use ipc_channel::ipc;
+use ipc_channel::router::ROUTER;
+use servo_url::ServoUrl;
+
+/// Asks the embedder to show a context menu for `target`, the node that the user pressed with
+/// the secondary button at `point_in_target`, and carries out the action picked in it, if any.
+pub fn show(target: &Node, point_in_target: Option<Point2D<f32>>) {
+    let document = document_from_node(target);
+    let window = window_from_node(target);
+
+    let link_url = target
+        .inclusive_ancestors(ShadowIncluding::No)
+        .filter_map(DomRoot::downcast::<Element>)
+        .find(|element| {
+            (element.is::<HTMLAnchorElement>() || element.is::<HTMLAreaElement>()) &&
+                element.has_attribute(&local_name!("href"))
+        })
+        .and_then(|link| ServoUrl::parse(&link.get_url_attribute(&local_name!("href"))).ok());
+    let image_url = target
+        .downcast::<HTMLImageElement>()
+        .and_then(|image| ServoUrl::parse(&image.CurrentSrc()).ok());
+    let selected_text = document
+        .GetSelection()
+        .map_or(String::new(), |selection| selection.Stringifier().into());
+    let editable = target.editing_host().is_some() ||
+        ((target.is::<HTMLInputElement>() || target.is::<HTMLTextAreaElement>()) &&
+            target.downcast::<Element>().unwrap().read_write_state());
+    let misspelling = point_in_target.and_then(|point| spellcheck::misspelling_at(target, point));
+    let menu = ContextMenu {
+        link_url,
+        image_url,
+        selected_text,
+        editable,
+        misspelled_word: misspelling
+            .as_ref()
+            .map(|misspelling| misspelling.word.clone()),
+        spelling_suggestions: misspelling
+            .as_ref()
+            .map_or(vec![], |misspelling| misspelling.suggestions.clone()),
+    };
+
+    let (sender, receiver) = ipc::channel().unwrap();
+    let global = window.upcast::<GlobalScope>();
+    let task_source = window.task_manager().user_interaction_task_source();
+    let canceller = global.task_canceller(TaskSourceName::UserInteraction);
+    ROUTER.add_route(
+        receiver.to_opaque(),
+        Box::new(move |message| {
+            // Only the actions on a misspelled word are carried out by the page for now.
+            let (action, misspelling) = match (message.to(), misspelling.clone()) {
+                (Ok(Some(action)), Some(misspelling)) => (action, misspelling),
+                _ => return,
+            };
+            let _ = task_source.queue_with_canceller(
+                task!(apply_context_menu_action: move || {
+                    misspelling.apply(action);
+                }),
+                &canceller,
+            );
+        }),
+    );
+    window.send_to_embedder(EmbedderMsg::ShowContextMenu(menu, sender));
+}
@@ -2,30 +2,67 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use crate::dom::bindings::codegen::Bindings::CSSConditionRuleBinding::CSSConditionRuleMethods;
+use crate::dom::bindings::codegen::Bindings::CSSGroupingRuleBinding::CSSGroupingRuleMethods;
+use crate::dom::bindings::codegen::Bindings::CSSMediaRuleBinding::CSSMediaRuleMethods;
+use crate::dom::bindings::codegen::Bindings::CSSRuleBinding::CSSRuleMethods;
+use crate::dom::bindings::codegen::Bindings::CSSRuleListBinding::CSSRuleListMethods;
 use crate::dom::bindings::codegen::Bindings::CSSStyleDeclarationBinding::CSSStyleDeclarationMethods;
+use crate::dom::bindings::codegen::Bindings::CSSStyleRuleBinding::CSSStyleRuleMethods;
+use crate::dom::bindings::codegen::Bindings::CSSStyleSheetBinding::CSSStyleSheetMethods;
 use crate::dom::bindings::codegen::Bindings::DOMRectBinding::DOMRectMethods;
 use crate::dom::bindings::codegen::Bindings::DocumentBinding::DocumentMethods;
 use crate::dom::bindings::codegen::Bindings::ElementBinding::ElementMethods;
+use crate::dom::bindings::codegen::Bindings::HTMLElementBinding::HTMLElementMethods;
+use crate::dom::bindings::codegen::Bindings::MediaListBinding::MediaListMethods;
+use crate::dom::bindings::codegen::Bindings::MediaQueryListBinding::MediaQueryListMethods;
+use crate::dom::bindings::codegen::Bindings::NodeBinding::NodeMethods;
+use crate::dom::bindings::codegen::Bindings::StyleSheetBinding::StyleSheetMethods;
+use crate::dom::bindings::codegen::Bindings::StyleSheetListBinding::StyleSheetListMethods;
 use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
 use crate::dom::bindings::conversions::{jsstring_to_str, ConversionResult, FromJSValConvertible};
 use crate::dom::bindings::inheritance::Castable;
-use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::refcounted::Trusted;
+use crate::dom::bindings::root::{Dom, DomRoot};
 use crate::dom::bindings::str::DOMString;
-use crate::dom::document::AnimationFrameCallback;
+use crate::dom::css::CSS;
+use crate::dom::cssconditionrule::CSSConditionRule;
+use crate::dom::cssgroupingrule::CSSGroupingRule;
+use crate::dom::cssmediarule::CSSMediaRule;
+use crate::dom::cssrulelist::CSSRuleList;
+use crate::dom::cssstyledeclaration::CSSStyleDeclaration;
+use crate::dom::cssstylerule::CSSStyleRule;
+use crate::dom::cssstylesheet::CSSStyleSheet;
+use crate::dom::csssupportsrule::CSSSupportsRule;
+use crate::dom::document::{AnimationFrameCallback, Document};
 use crate::dom::element::Element;
 use crate::dom::globalscope::GlobalScope;
-use crate::dom::node::{window_from_node, Node, ShadowIncluding};
+use crate::dom::htmlelement::HTMLElement;
+use crate::dom::mutationobserver::Mutation;
+use crate::dom::node::{document_from_node, window_from_node, Node, ShadowIncluding};
+use crate::dom::stylesheet::StyleSheet;
 use crate::dom::window::Window;
 use crate::realms::enter_realm;
 use crate::script_thread::Documents;
-use devtools_traits::{AutoMargins, ComputedNodeLayout, TimelineMarkerType};
-use devtools_traits::{EvaluateJSReply, Modification, NodeInfo, TimelineMarker};
+use crate::task_source::TaskSource;
+use cssparser::ToCss;
+use devtools_traits::ScriptToDevtoolsControlMsg;
+use devtools_traits::{AppliedRuleInfo, ComputedProperty, CssModification, CssRuleId};
+use devtools_traits::{AutoMargins, ComputedNodeLayout, DomMutation, TimelineMarkerType};
+use devtools_traits::{EvaluateJSReply, Modification, NodeInfo, StyleSheetInfo, TimelineMarker};
+use html5ever::{LocalName, Namespace};
 use ipc_channel::ipc::IpcSender;
 use js::jsval::UndefinedValue;
 use js::rust::wrappers::ObjectClassName;
-use msg::constellation_msg::PipelineId;
+use msg::constellation_msg::{NetworkConditions, PipelineId};
+use script_traits::ScriptMsg;
+use selectors::parser::SelectorList;
+use std::cmp;
+use std::collections::HashSet;
 use std::ffi::CStr;
 use std::str;
+use style::dom_apis;
+use style::selector_parser::SelectorParser;
 use uuid::Uuid;
 
 #[allow(unsafe_code)]
@@ -213,10 +250,525 @@ pub fn handle_modify_attribute(
     }
 }
 
+pub fn handle_set_node_value(
+    documents: &Documents,
+    pipeline: PipelineId,
+    node_id: String,
+    value: String,
+) {
+    match find_node_by_unique_id(documents, pipeline, &*node_id) {
+        None => warn!(
+            "node id {} for pipeline id {} is not found",
+            &node_id, &pipeline
+        ),
+        Some(node) => node.SetNodeValue(Some(DOMString::from(value))),
+    }
+}
+
+pub fn handle_remove_node(
+    documents: &Documents,
+    pipeline: PipelineId,
+    node_id: String,
+    reply: IpcSender<Option<NodeInfo>>,
+) {
+    let node = match find_node_by_unique_id(documents, pipeline, &*node_id) {
+        None => return reply.send(None).unwrap(),
+        Some(found_node) => found_node,
+    };
+
+    let next_sibling = node.GetNextSibling();
+    node.remove_self();
+    reply
+        .send(next_sibling.map(|sibling| sibling.summarize()))
+        .unwrap();
+}
+
+pub fn handle_get_outer_html(
+    documents: &Documents,
+    pipeline: PipelineId,
+    node_id: String,
+    reply: IpcSender<Option<String>>,
+) {
+    let html = find_node_by_unique_id(documents, pipeline, &*node_id)
+        .and_then(DomRoot::downcast::<Element>)
+        .and_then(|element| element.GetOuterHTML().ok())
+        .map(String::from);
+    reply.send(html).unwrap();
+}
+
+pub fn handle_set_outer_html(
+    documents: &Documents,
+    pipeline: PipelineId,
+    node_id: String,
+    html: String,
+) {
+    let element = match find_node_by_unique_id(documents, pipeline, &*node_id)
+        .and_then(DomRoot::downcast::<Element>)
+    {
+        None => {
+            return warn!(
+                "element id {} for pipeline id {} is not found",
+                &node_id, &pipeline
+            );
+        },
+        Some(found_element) => found_element,
+    };
+
+    if element.SetOuterHTML(DOMString::from(html)).is_err() {
+        warn!("couldn't set the outer HTML of element id {}", &node_id);
+    }
+}
+
+pub fn handle_get_stylesheets(
+    documents: &Documents,
+    pipeline: PipelineId,
+    reply: IpcSender<Vec<StyleSheetInfo>>,
+) {
+    let document = match documents.find_document(pipeline) {
+        None => return reply.send(vec![]).unwrap(),
+        Some(found_document) => found_document,
+    };
+
+    let sheets = document.StyleSheets();
+    let infos = (0..sheets.Length())
+        .filter_map(|index| sheets.Item(index).map(|sheet| (index, sheet)))
+        .map(|(index, sheet)| StyleSheetInfo {
+            index: index as usize,
+            href: sheet.GetHref().map(String::from),
+            nodeHref: document.url().into_string(),
+            title: sheet.GetTitle().map(String::from).unwrap_or_default(),
+            disabled: sheet.Disabled(),
+            ruleCount: sheet
+                .downcast::<CSSStyleSheet>()
+                .and_then(|sheet| sheet.GetCssRules().ok())
+                .map_or(0, |rules| rules.Length() as usize),
+        })
+        .collect();
+    reply.send(infos).unwrap();
+}
+
+fn find_stylesheet(
+    documents: &Documents,
+    pipeline: PipelineId,
+    index: usize,
+) -> Option<DomRoot<CSSStyleSheet>> {
+    documents
+        .find_document(pipeline)?
+        .StyleSheets()
+        .Item(index as u32)
+        .and_then(DomRoot::downcast::<CSSStyleSheet>)
+}
+
+pub fn handle_get_stylesheet_text(
+    documents: &Documents,
+    pipeline: PipelineId,
+    index: usize,
+    reply: IpcSender<Option<String>>,
+) {
+    let text = find_stylesheet(documents, pipeline, index)
+        .and_then(|sheet| sheet.GetCssRules().ok())
+        .map(|rules| {
+            (0..rules.Length())
+                .filter_map(|index| rules.Item(index))
+                .map(|rule| String::from(rule.CssText()))
+                .collect::<Vec<_>>()
+                .join("\n")
+        });
+    reply.send(text).unwrap();
+}
+
+pub fn handle_set_stylesheet_text(
+    documents: &Documents,
+    pipeline: PipelineId,
+    index: usize,
+    text: String,
+) {
+    match find_stylesheet(documents, pipeline, index) {
+        None => warn!(
+            "style sheet {} for pipeline id {} is not found",
+            index, &pipeline
+        ),
+        Some(sheet) => sheet.replace_rules(&text),
+    }
+}
+
+pub fn handle_toggle_stylesheet_disabled(
+    documents: &Documents,
+    pipeline: PipelineId,
+    index: usize,
+    reply: IpcSender<Option<bool>>,
+) {
+    let disabled = find_stylesheet(documents, pipeline, index).map(|sheet| {
+        let sheet = sheet.upcast::<StyleSheet>();
+        sheet.SetDisabled(!sheet.Disabled());
+        sheet.Disabled()
+    });
+    reply.send(disabled).unwrap();
+}
+
+/// The selectors of `selector_text`, and the ones that match `element` with the specificity
+/// of the most specific of them, if any does.
+fn match_selectors(
+    element: &Element,
+    selector_text: &str,
+) -> Option<(Vec<String>, Vec<String>, u32)> {
+    let selector_list = SelectorParser::parse_author_origin_no_namespace(selector_text).ok()?;
+    let quirks_mode = document_from_node(element).quirks_mode();
+    let element = DomRoot::from_ref(element);
+    let mut selectors = vec![];
+    let mut matched_selectors = vec![];
+    let mut specificity = None;
+    for selector in selector_list.0.iter() {
+        let text = selector.to_css_string();
+        let single = SelectorList::from_vec(vec![selector.clone()]);
+        if dom_apis::element_matches(&element, &single, quirks_mode) {
+            matched_selectors.push(text.clone());
+            specificity = cmp::max(specificity, Some(selector.specificity()));
+        }
+        selectors.push(text);
+    }
+    Some((selectors, matched_selectors, specificity?))
+}
+
+/// Adds the style rules among `rules`, and in the grouping rules among them whose conditions
+/// hold, that apply to `element`, in the order they appear and with their specificity.
+fn collect_applied_rules(
+    element: &Element,
+    rules: &CSSRuleList,
+    sheet: usize,
+    path: &mut Vec<usize>,
+    applied: &mut Vec<(u32, AppliedRuleInfo, DomRoot<CSSStyleDeclaration>)>,
+) {
+    let window = window_from_node(element);
+    for index in 0..rules.Length() {
+        let rule = match rules.Item(index) {
+            Some(rule) => rule,
+            None => continue,
+        };
+        path.push(index as usize);
+        if let Some(style_rule) = rule.downcast::<CSSStyleRule>() {
+            if let Some((selectors, matched_selectors, specificity)) =
+                match_selectors(element, &style_rule.SelectorText())
+            {
+                let style = style_rule.Style();
+                let info = AppliedRuleInfo {
+                    id: CssRuleId::StyleRule(sheet, path.clone()),
+                    selectors,
+                    matchedSelectors: matched_selectors,
+                    cssText: String::from(style.CssText()),
+                };
+                applied.push((specificity, info, style));
+            }
+        } else if rule.is::<CSSMediaRule>() || rule.is::<CSSSupportsRule>() {
+            let holds = match rule.downcast::<CSSMediaRule>() {
+                Some(media_rule) => window.MatchMedia(media_rule.Media().MediaText()).Matches(),
+                None => CSS::Supports_(
+                    &window,
+                    rule.downcast::<CSSConditionRule>().unwrap().ConditionText(),
+                ),
+            };
+            if holds {
+                let grouping_rule = rule.downcast::<CSSGroupingRule>().unwrap();
+                collect_applied_rules(element, &grouping_rule.CssRules(), sheet, path, applied);
+            }
+        }
+        path.pop();
+    }
+}
+
+/// The rules that apply to `element`, with their declarations, the ones that win first: its
+/// `style` attribute, then the style rules by decreasing specificity, the last ones first for
+/// the same specificity. Important declarations and the rules of imported style sheets are
+/// not taken into account.
+fn applied_rules(
+    element: &Element,
+    node_id: &str,
+) -> Vec<(AppliedRuleInfo, DomRoot<CSSStyleDeclaration>)> {
+    let mut applied = vec![];
+    let sheets = document_from_node(element).StyleSheets();
+    for index in 0..sheets.Length() {
+        let rules = sheets
+            .Item(index)
+            .filter(|sheet| !sheet.Disabled())
+            .and_then(DomRoot::downcast::<CSSStyleSheet>)
+            .and_then(|sheet| sheet.GetCssRules().ok());
+        if let Some(rules) = rules {
+            collect_applied_rules(element, &rules, index as usize, &mut vec![], &mut applied);
+        }
+    }
+    applied.reverse();
+    applied.sort_by(|a, b| b.0.cmp(&a.0));
+    let mut rules: Vec<_> = applied
+        .into_iter()
+        .map(|(_, info, style)| (info, style))
+        .collect();
+
+    if let Some(html_element) = element.downcast::<HTMLElement>() {
+        if element.has_attribute(&local_name!("style")) {
+            let style = html_element.Style();
+            let info = AppliedRuleInfo {
+                id: CssRuleId::ElementStyle(node_id.to_owned()),
+                selectors: vec![],
+                matchedSelectors: vec![],
+                cssText: String::from(style.CssText()),
+            };
+            rules.insert(0, (info, style));
+        }
+    }
+    rules
+}
+
+pub fn handle_get_applied_rules(
+    documents: &Documents,
+    pipeline: PipelineId,
+    node_id: String,
+    reply: IpcSender<Option<Vec<AppliedRuleInfo>>>,
+) {
+    let element = match find_node_by_unique_id(documents, pipeline, &*node_id)
+        .and_then(DomRoot::downcast::<Element>)
+    {
+        None => return reply.send(None).unwrap(),
+        Some(found_element) => found_element,
+    };
+
+    let rules = applied_rules(&element, &node_id)
+        .into_iter()
+        .map(|(info, _)| info)
+        .collect();
+    reply.send(Some(rules)).unwrap();
+}
+
+pub fn handle_get_computed_style(
+    documents: &Documents,
+    pipeline: PipelineId,
+    node_id: String,
+    reply: IpcSender<Option<Vec<ComputedProperty>>>,
+) {
+    let element = match find_node_by_unique_id(documents, pipeline, &*node_id)
+        .and_then(DomRoot::downcast::<Element>)
+    {
+        None => return reply.send(None).unwrap(),
+        Some(found_element) => found_element,
+    };
+
+    let declared: HashSet<DOMString> = applied_rules(&element, &node_id)
+        .iter()
+        .flat_map(|(_, style)| style.property_names())
+        .collect();
+    let computed_style = window_from_node(&*element).GetComputedStyle(&element, None);
+    let properties = computed_style
+        .property_names()
+        .into_iter()
+        .map(|name| ComputedProperty {
+            value: String::from(computed_style.GetPropertyValue(name.clone())),
+            matched: declared.contains(&name),
+            name: String::from(name),
+        })
+        .collect();
+    reply.send(Some(properties)).unwrap();
+}
+
+/// The declarations of the rule identified by `rule`.
+fn find_rule_style(
+    documents: &Documents,
+    pipeline: PipelineId,
+    rule: &CssRuleId,
+) -> Option<DomRoot<CSSStyleDeclaration>> {
+    match *rule {
+        CssRuleId::ElementStyle(ref node_id) => {
+            let node = find_node_by_unique_id(documents, pipeline, node_id)?;
+            node.downcast::<HTMLElement>()
+                .map(|element| element.Style())
+        },
+        CssRuleId::StyleRule(sheet, ref path) => {
+            let mut rules = find_stylesheet(documents, pipeline, sheet)?
+                .GetCssRules()
+                .ok()?;
+            let (&index, grouping_rules) = path.split_last()?;
+            for &grouping_index in grouping_rules {
+                let grouping_rule = rules.Item(grouping_index as u32)?;
+                rules = grouping_rule.downcast::<CSSGroupingRule>()?.CssRules();
+            }
+            let rule = rules.Item(index as u32)?;
+            rule.downcast::<CSSStyleRule>().map(|rule| rule.Style())
+        },
+    }
+}
+
+pub fn handle_modify_rule(
+    documents: &Documents,
+    pipeline: PipelineId,
+    rule: CssRuleId,
+    modifications: Vec<CssModification>,
+    reply: IpcSender<Option<String>>,
+) {
+    let style = match find_rule_style(documents, pipeline, &rule) {
+        None => return reply.send(None).unwrap(),
+        Some(found_style) => found_style,
+    };
+
+    for modification in modifications {
+        let name = DOMString::from(modification.name);
+        match modification.value {
+            Some(value) => {
+                let _ = style.SetProperty(
+                    name,
+                    DOMString::from(value),
+                    DOMString::from(modification.priority),
+                );
+            },
+            None => {
+                let _ = style.RemoveProperty(name);
+            },
+        }
+    }
+    reply.send(Some(String::from(style.CssText()))).unwrap();
+}
+
+pub fn handle_set_rule_text(
+    documents: &Documents,
+    pipeline: PipelineId,
+    rule: CssRuleId,
+    text: String,
+    reply: IpcSender<Option<String>>,
+) {
+    let style = match find_rule_style(documents, pipeline, &rule) {
+        None => return reply.send(None).unwrap(),
+        Some(found_style) => found_style,
+    };
+
+    let _ = style.SetCssText(DOMString::from(text));
+    reply.send(Some(String::from(style.CssText()))).unwrap();
+}
+
 pub fn handle_wants_live_notifications(global: &GlobalScope, send_notifications: bool) {
     global.set_devtools_wants_updates(send_notifications);
 }
 
+pub fn handle_wants_dom_mutations(document: &Document, send_mutations: bool) {
+    document.set_devtools_wants_mutations(send_mutations);
+}
+
+/// A change to a node that devtools is told about once the task that made it is over, since
+/// mutation records are queued before attributes and character data change.
+#[derive(JSTraceable, MallocSizeOf)]
+#[unrooted_must_root_lint::must_root]
+pub struct PendingDomMutation {
+    target: Dom<Node>,
+    kind: PendingDomMutationKind,
+}
+
+#[derive(JSTraceable, MallocSizeOf)]
+enum PendingDomMutationKind {
+    Attribute(LocalName, Namespace),
+    CharacterData,
+    ChildList {
+        added: Vec<String>,
+        removed: Vec<String>,
+    },
+}
+
+impl PendingDomMutation {
+    fn to_dom_mutation(&self) -> DomMutation {
+        let target = self.target.unique_id();
+        match self.kind {
+            PendingDomMutationKind::Attribute(ref name, ref namespace) => DomMutation::Attribute {
+                target,
+                name: String::from(&**name),
+                namespace: String::from(&**namespace),
+                value: self
+                    .target
+                    .downcast::<Element>()
+                    .and_then(|element| element.get_attribute(namespace, name))
+                    .map(|attr| String::from(&**attr.value())),
+            },
+            PendingDomMutationKind::CharacterData => DomMutation::CharacterData {
+                target,
+                value: self
+                    .target
+                    .GetNodeValue()
+                    .map(String::from)
+                    .unwrap_or_default(),
+            },
+            PendingDomMutationKind::ChildList {
+                ref added,
+                ref removed,
+            } => DomMutation::ChildList {
+                target,
+                added: added.clone(),
+                removed: removed.clone(),
+                numChildren: self.target.children_count() as usize,
+            },
+        }
+    }
+}
+
+/// Notes a change to `target` for devtools, if it asked for the changes to its document.
+#[allow(unrooted_must_root)]
+pub fn note_dom_mutation(target: &Node, mutation: &Mutation) {
+    let document = target.owner_doc();
+    if !document.devtools_wants_mutations() {
+        return;
+    }
+    let unique_ids = |nodes: Option<&[&Node]>| -> Vec<String> {
+        nodes
+            .unwrap_or(&[])
+            .iter()
+            .map(|node| node.unique_id())
+            .collect()
+    };
+    let kind = match *mutation {
+        Mutation::Attribute {
+            ref name,
+            ref namespace,
+            ..
+        } => PendingDomMutationKind::Attribute(name.clone(), namespace.clone()),
+        Mutation::CharacterData { .. } => PendingDomMutationKind::CharacterData,
+        Mutation::ChildList { added, removed, .. } => PendingDomMutationKind::ChildList {
+            added: unique_ids(added),
+            removed: unique_ids(removed),
+        },
+    };
+
+    let mut pending = document.devtools_mutations().borrow_mut();
+    if pending.is_empty() {
+        let trusted_document = Trusted::new(&*document);
+        let _ = document
+            .window()
+            .task_manager()
+            .dom_manipulation_task_source()
+            .queue(
+                task!(send_dom_mutations_to_devtools: move || {
+                    send_dom_mutations(&trusted_document.root());
+                }),
+                document.window().upcast(),
+            );
+    }
+    pending.push(PendingDomMutation {
+        target: Dom::from_ref(target),
+        kind,
+    });
+}
+
+/// Tells devtools about the changes to `document` since it was last told.
+#[allow(unrooted_must_root)]
+fn send_dom_mutations(document: &Document) {
+    let mutations: Vec<_> = document
+        .devtools_mutations()
+        .borrow_mut()
+        .drain(..)
+        .map(|pending| pending.to_dom_mutation())
+        .collect();
+    if mutations.is_empty() || !document.devtools_wants_mutations() {
+        return;
+    }
+    let global = document.window().upcast::<GlobalScope>();
+    if let Some(chan) = global.devtools_chan() {
+        let msg = ScriptToDevtoolsControlMsg::DomMutations(global.pipeline_id(), mutations);
+        chan.send(msg).unwrap();
+    }
+}
+
 pub fn handle_set_timeline_markers(
     documents: &Documents,
     pipeline: PipelineId,
@@ -250,3 +802,17 @@ pub fn handle_reload(documents: &Documents, id: PipelineId) {
         win.Location().reload_without_origin_check();
     }
 }
+
+pub fn handle_set_network_conditions(
+    documents: &Documents,
+    id: PipelineId,
+    conditions: Option<NetworkConditions>,
+) {
+    if let Some(win) = documents.find_window(id) {
+        let msg = ScriptMsg::SetNetworkConditions(conditions);
+        let _ = win
+            .upcast::<GlobalScope>()
+            .script_to_constellation_chan()
+            .send(msg);
+    }
+}
@@ -0,0 +1,48 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::abortsignal::AbortSignal;
+use crate::dom::bindings::codegen::Bindings::AbortControllerBinding::AbortControllerMethods;
+use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::globalscope::GlobalScope;
+use dom_struct::dom_struct;
+
+/// <https://dom.spec.whatwg.org/#interface-abortcontroller>
+#[dom_struct]
+pub struct AbortController {
+    reflector_: Reflector,
+    signal: Dom<AbortSignal>,
+}
+
+impl AbortController {
+    pub fn new_inherited(signal: &AbortSignal) -> AbortController {
+        AbortController {
+            reflector_: Reflector::new(),
+            signal: Dom::from_ref(signal),
+        }
+    }
+
+    pub fn new(global: &GlobalScope, signal: &AbortSignal) -> DomRoot<AbortController> {
+        reflect_dom_object(Box::new(AbortController::new_inherited(signal)), global)
+    }
+
+    // https://dom.spec.whatwg.org/#dom-abortcontroller-abortcontroller
+    #[allow(non_snake_case)]
+    pub fn Constructor(global: &GlobalScope) -> DomRoot<AbortController> {
+        AbortController::new(global, &AbortSignal::new(global))
+    }
+}
+
+impl AbortControllerMethods for AbortController {
+    // https://dom.spec.whatwg.org/#dom-abortcontroller-signal
+    fn Signal(&self) -> DomRoot<AbortSignal> {
+        DomRoot::from_ref(&*self.signal)
+    }
+
+    // https://dom.spec.whatwg.org/#dom-abortcontroller-abort
+    fn Abort(&self) {
+        self.signal.signal_abort();
+    }
+}
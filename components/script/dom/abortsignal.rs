@@ -0,0 +1,103 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::AbortSignalBinding::AbortSignalMethods;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::reflector::reflect_dom_object;
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::eventtarget::EventTarget;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::idledetector::IdleDetector;
+use crate::dom::lockmanager::LockManager;
+use crate::dom::scheduler::Scheduler;
+use dom_struct::dom_struct;
+use msg::constellation_msg::LockRequestId;
+use std::cell::Cell;
+
+/// <https://dom.spec.whatwg.org/#abortsignal-abort-algorithms>
+#[derive(JSTraceable, MallocSizeOf)]
+#[unrooted_must_root_lint::must_root]
+pub enum AbortAlgorithm {
+    /// Removes a task posted with `scheduler.postTask()` from its scheduler, and rejects the
+    /// promise of the task.
+    SchedulerTask(Dom<Scheduler>, u64),
+    /// Drops a request made with `navigator.locks.request()` that wasn't granted yet, and
+    /// rejects its promise.
+    LockRequest(Dom<LockManager>, LockRequestId),
+    /// Stops an `IdleDetector` from observing the idle state of the user.
+    IdleDetector(Dom<IdleDetector>),
+}
+
+impl AbortAlgorithm {
+    fn run(&self) {
+        match *self {
+            AbortAlgorithm::SchedulerTask(ref scheduler, id) => scheduler.abort_task(id),
+            AbortAlgorithm::LockRequest(ref manager, id) => manager.abort_request(id),
+            AbortAlgorithm::IdleDetector(ref detector) => detector.abort(),
+        }
+    }
+}
+
+/// <https://dom.spec.whatwg.org/#interface-AbortSignal>
+#[dom_struct]
+pub struct AbortSignal {
+    eventtarget: EventTarget,
+    /// <https://dom.spec.whatwg.org/#abortsignal-aborted-flag>
+    aborted: Cell<bool>,
+    abort_algorithms: DomRefCell<Vec<AbortAlgorithm>>,
+}
+
+impl AbortSignal {
+    pub fn new_inherited() -> AbortSignal {
+        AbortSignal {
+            eventtarget: EventTarget::new_inherited(),
+            aborted: Cell::new(false),
+            abort_algorithms: DomRefCell::new(vec![]),
+        }
+    }
+
+    pub fn new(global: &GlobalScope) -> DomRoot<AbortSignal> {
+        reflect_dom_object(Box::new(AbortSignal::new_inherited()), global)
+    }
+
+    /// <https://dom.spec.whatwg.org/#abortsignal-add>
+    #[allow(unrooted_must_root)]
+    pub fn add_algorithm(&self, algorithm: AbortAlgorithm) {
+        // Step 1.
+        if self.aborted.get() {
+            return;
+        }
+        // Step 2.
+        self.abort_algorithms.borrow_mut().push(algorithm);
+    }
+
+    /// <https://dom.spec.whatwg.org/#abortsignal-signal-abort>
+    pub fn signal_abort(&self) {
+        // Step 1.
+        if self.aborted.get() {
+            return;
+        }
+        // Step 2.
+        self.aborted.set(true);
+        // Step 3. The abort algorithms don't run script, so they can't add new ones.
+        for algorithm in self.abort_algorithms.borrow().iter() {
+            algorithm.run();
+        }
+        // Step 4.
+        self.abort_algorithms.borrow_mut().clear();
+        // Step 5.
+        self.upcast::<EventTarget>().fire_event(atom!("abort"));
+    }
+}
+
+impl AbortSignalMethods for AbortSignal {
+    // https://dom.spec.whatwg.org/#dom-abortsignal-aborted
+    fn Aborted(&self) -> bool {
+        self.aborted.get()
+    }
+
+    // https://dom.spec.whatwg.org/#dom-abortsignal-onabort
+    event_handler!(abort, GetOnabort, SetOnabort);
+}
@@ -0,0 +1,35 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::AnimationTimelineBinding::AnimationTimelineMethods;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::num::Finite;
+use crate::dom::bindings::reflector::Reflector;
+use crate::dom::scrolltimeline::ScrollTimeline;
+use dom_struct::dom_struct;
+
+/// <https://drafts.csswg.org/web-animations-1/#the-animationtimeline-interface>
+///
+/// Servo has no document timeline yet, so all timelines are scroll-driven.
+#[dom_struct]
+pub struct AnimationTimeline {
+    reflector_: Reflector,
+}
+
+impl AnimationTimeline {
+    pub fn new_inherited() -> AnimationTimeline {
+        AnimationTimeline {
+            reflector_: Reflector::new(),
+        }
+    }
+}
+
+impl AnimationTimelineMethods for AnimationTimeline {
+    // https://drafts.csswg.org/web-animations-1/#dom-animationtimeline-currenttime
+    fn GetCurrentTime(&self) -> Option<Finite<f64>> {
+        self.downcast::<ScrollTimeline>()
+            .and_then(|timeline| timeline.current_time())
+            .map(Finite::wrap)
+    }
+}
@@ -3,6 +3,7 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use crate::dom::baseaudiocontext::{BaseAudioContext, BaseAudioContextOptions};
+use crate::dom::bindings::cell::DomRefCell;
 use crate::dom::bindings::codegen::Bindings::AudioContextBinding::{
     AudioContextLatencyCategory, AudioContextMethods,
 };
@@ -18,7 +19,9 @@ use crate::dom::bindings::num::Finite;
 use crate::dom::bindings::refcounted::{Trusted, TrustedPromise};
 use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
 use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
 use crate::dom::htmlmediaelement::HTMLMediaElement;
+use crate::dom::mediadevices::is_audio_output_device;
 use crate::dom::mediaelementaudiosourcenode::MediaElementAudioSourceNode;
 use crate::dom::promise::Promise;
 use crate::dom::window::Window;
@@ -37,6 +40,8 @@ pub struct AudioContext {
     base_latency: f64,
     /// https://webaudio.github.io/web-audio-api/#dom-audiocontext-outputlatency
     output_latency: f64,
+    /// https://webaudio.github.io/web-audio-api/#dom-audiocontext-sink-id-slot
+    sink_id: DomRefCell<DOMString>,
 }
 
 impl AudioContext {
@@ -69,6 +74,7 @@ impl AudioContext {
             latency_hint,
             base_latency: 0.,   // TODO
             output_latency: 0., // TODO
+            sink_id: DomRefCell::new(DOMString::new()),
         }
     }
 
@@ -87,7 +93,40 @@ impl AudioContext {
         window: &Window,
         options: &AudioContextOptions,
     ) -> Fallible<DomRoot<AudioContext>> {
-        Ok(AudioContext::new(window, options))
+        // Step 4.
+        if let Some(ref sink_id) = options.sinkId {
+            if !is_audio_output_device(sink_id) {
+                return Err(Error::NotFound);
+            }
+        }
+
+        let context = AudioContext::new(window, options);
+        if let Some(ref sink_id) = options.sinkId {
+            context.set_output_device(sink_id)?;
+        }
+        Ok(context)
+    }
+
+    /// Plays the audio of the context on the given device, or on the default one when the
+    /// identifier is empty.
+    fn set_output_device(&self, sink_id: &DOMString) -> Fallible<()> {
+        let device = if sink_id.is_empty() {
+            None
+        } else {
+            Some(String::from(sink_id.clone()))
+        };
+        let result = self
+            .context
+            .audio_context_impl()
+            .lock()
+            .unwrap()
+            .set_output_device(device);
+        if let Err(e) = result {
+            warn!("Could not set the audio output device {:?}", e);
+            return Err(Error::Abort);
+        }
+        *self.sink_id.borrow_mut() = sink_id.clone();
+        Ok(())
     }
 
     fn resume(&self) {
@@ -254,6 +293,50 @@ impl AudioContextMethods for AudioContext {
         let window = global.as_window();
         MediaElementAudioSourceNode::new(window, self, media_element)
     }
+
+    // https://webaudio.github.io/web-audio-api/#dom-audiocontext-sinkid
+    fn SinkId(&self) -> DOMString {
+        self.sink_id.borrow().clone()
+    }
+
+    // https://webaudio.github.io/web-audio-api/#dom-audiocontext-setsinkid
+    fn SetSinkId(&self, sink_id: DOMString, comp: InRealm) -> Rc<Promise> {
+        let promise = Promise::new_in_current_realm(&self.global(), comp);
+
+        // Step 3.
+        if self.context.control_thread_state() == ProcessingState::Closed {
+            promise.reject_error(Error::InvalidState);
+            return promise;
+        }
+
+        // Step 4.
+        if !is_audio_output_device(&sink_id) {
+            promise.reject_error(Error::NotFound);
+            return promise;
+        }
+
+        // Step 5.
+        if *self.sink_id.borrow() == sink_id {
+            promise.resolve_native(&());
+            return promise;
+        }
+
+        // Step 8.
+        if let Err(error) = self.set_output_device(&sink_id) {
+            promise.reject_error(error);
+            return promise;
+        }
+        let window = DomRoot::downcast::<Window>(self.global()).unwrap();
+        window
+            .task_manager()
+            .dom_manipulation_task_source()
+            .queue_simple_event(self.upcast(), atom!("sinkchange"), &window);
+        promise.resolve_native(&());
+        promise
+    }
+
+    // https://webaudio.github.io/web-audio-api/#dom-audiocontext-onsinkchange
+    event_handler!(sinkchange, GetOnsinkchange, SetOnsinkchange);
 }
 
 impl From<AudioContextLatencyCategory> for LatencyCategory {
@@ -0,0 +1,82 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::authenticatorresponse::{set_array_buffer, AuthenticatorResponse};
+use crate::dom::bindings::codegen::Bindings::AuthenticatorAssertionResponseBinding::AuthenticatorAssertionResponseMethods;
+use crate::dom::bindings::reflector::reflect_dom_object;
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::globalscope::GlobalScope;
+use crate::script_runtime::JSContext;
+use dom_struct::dom_struct;
+use js::jsapi::{Heap, JSObject};
+use net_traits::webauthn::GetAssertionResponse;
+use std::ptr::NonNull;
+
+/// <https://w3c.github.io/webauthn/#iface-authenticatorassertionresponse>
+#[dom_struct]
+pub struct AuthenticatorAssertionResponse {
+    response: AuthenticatorResponse,
+    #[ignore_malloc_size_of = "mozjs"]
+    authenticator_data: Heap<*mut JSObject>,
+    #[ignore_malloc_size_of = "mozjs"]
+    signature: Heap<*mut JSObject>,
+    /// Null when the credential has no user handle.
+    #[ignore_malloc_size_of = "mozjs"]
+    user_handle: Heap<*mut JSObject>,
+}
+
+impl AuthenticatorAssertionResponse {
+    fn new_inherited() -> AuthenticatorAssertionResponse {
+        AuthenticatorAssertionResponse {
+            response: AuthenticatorResponse::new_inherited(),
+            authenticator_data: Heap::default(),
+            signature: Heap::default(),
+            user_handle: Heap::default(),
+        }
+    }
+
+    pub fn new(
+        global: &GlobalScope,
+        client_data_json: &[u8],
+        response: &GetAssertionResponse,
+    ) -> DomRoot<AuthenticatorAssertionResponse> {
+        let assertion_response = reflect_dom_object(
+            Box::new(AuthenticatorAssertionResponse::new_inherited()),
+            global,
+        );
+        let cx = global.get_cx();
+        assertion_response
+            .response
+            .set_client_data_json(cx, client_data_json);
+        set_array_buffer(
+            cx,
+            &assertion_response.authenticator_data,
+            &response.authenticator_data,
+        );
+        set_array_buffer(cx, &assertion_response.signature, &response.signature);
+        if !response.user_handle.is_empty() {
+            set_array_buffer(cx, &assertion_response.user_handle, &response.user_handle);
+        }
+        assertion_response
+    }
+}
+
+impl AuthenticatorAssertionResponseMethods for AuthenticatorAssertionResponse {
+    #[allow(unsafe_code)]
+    // https://w3c.github.io/webauthn/#dom-authenticatorassertionresponse-authenticatordata
+    fn AuthenticatorData(&self, _cx: JSContext) -> NonNull<JSObject> {
+        unsafe { NonNull::new_unchecked(self.authenticator_data.get()) }
+    }
+
+    #[allow(unsafe_code)]
+    // https://w3c.github.io/webauthn/#dom-authenticatorassertionresponse-signature
+    fn Signature(&self, _cx: JSContext) -> NonNull<JSObject> {
+        unsafe { NonNull::new_unchecked(self.signature.get()) }
+    }
+
+    // https://w3c.github.io/webauthn/#dom-authenticatorassertionresponse-userhandle
+    fn GetUserHandle(&self, _cx: JSContext) -> Option<NonNull<JSObject>> {
+        NonNull::new(self.user_handle.get())
+    }
+}
@@ -0,0 +1,88 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::authenticatorresponse::AuthenticatorResponse;
+use crate::dom::authenticatorresponse::{new_array_buffer, set_array_buffer};
+use crate::dom::bindings::codegen::Bindings::AuthenticatorAttestationResponseBinding::AuthenticatorAttestationResponseMethods;
+use crate::dom::bindings::reflector::reflect_dom_object;
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::globalscope::GlobalScope;
+use crate::script_runtime::JSContext;
+use dom_struct::dom_struct;
+use js::jsapi::{Heap, JSObject};
+use net_traits::webauthn::MakeCredentialResponse;
+use std::ptr::NonNull;
+
+/// <https://w3c.github.io/webauthn/#iface-authenticatorattestationresponse>
+#[dom_struct]
+pub struct AuthenticatorAttestationResponse {
+    response: AuthenticatorResponse,
+    #[ignore_malloc_size_of = "mozjs"]
+    attestation_object: Heap<*mut JSObject>,
+    authenticator_data: Vec<u8>,
+    public_key: Vec<u8>,
+    public_key_algorithm: i32,
+}
+
+impl AuthenticatorAttestationResponse {
+    fn new_inherited(response: &MakeCredentialResponse) -> AuthenticatorAttestationResponse {
+        AuthenticatorAttestationResponse {
+            response: AuthenticatorResponse::new_inherited(),
+            attestation_object: Heap::default(),
+            authenticator_data: response.authenticator_data.clone(),
+            public_key: response.public_key.clone(),
+            public_key_algorithm: response.algorithm,
+        }
+    }
+
+    pub fn new(
+        global: &GlobalScope,
+        client_data_json: &[u8],
+        response: &MakeCredentialResponse,
+    ) -> DomRoot<AuthenticatorAttestationResponse> {
+        let attestation_response = reflect_dom_object(
+            Box::new(AuthenticatorAttestationResponse::new_inherited(response)),
+            global,
+        );
+        let cx = global.get_cx();
+        attestation_response
+            .response
+            .set_client_data_json(cx, client_data_json);
+        set_array_buffer(
+            cx,
+            &attestation_response.attestation_object,
+            &response.attestation_object,
+        );
+        attestation_response
+    }
+}
+
+impl AuthenticatorAttestationResponseMethods for AuthenticatorAttestationResponse {
+    #[allow(unsafe_code)]
+    // https://w3c.github.io/webauthn/#dom-authenticatorattestationresponse-attestationobject
+    fn AttestationObject(&self, _cx: JSContext) -> NonNull<JSObject> {
+        unsafe { NonNull::new_unchecked(self.attestation_object.get()) }
+    }
+
+    // https://w3c.github.io/webauthn/#dom-authenticatorattestationresponse-gettransports
+    fn GetTransports(&self) -> Vec<DOMString> {
+        vec![DOMString::from("internal")]
+    }
+
+    // https://w3c.github.io/webauthn/#dom-authenticatorattestationresponse-getauthenticatordata
+    fn GetAuthenticatorData(&self, cx: JSContext) -> NonNull<JSObject> {
+        new_array_buffer(cx, &self.authenticator_data)
+    }
+
+    // https://w3c.github.io/webauthn/#dom-authenticatorattestationresponse-getpublickey
+    fn GetPublicKey(&self, cx: JSContext) -> Option<NonNull<JSObject>> {
+        Some(new_array_buffer(cx, &self.public_key))
+    }
+
+    // https://w3c.github.io/webauthn/#dom-authenticatorattestationresponse-getpublickeyalgorithm
+    fn GetPublicKeyAlgorithm(&self) -> i32 {
+        self.public_key_algorithm
+    }
+}
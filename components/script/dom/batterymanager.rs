@@ -0,0 +1,95 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::BatteryManagerBinding::BatteryManagerMethods;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::num::Finite;
+use crate::dom::bindings::reflector::reflect_dom_object;
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::eventtarget::EventTarget;
+use crate::dom::globalscope::GlobalScope;
+use dom_struct::dom_struct;
+use embedder_traits::BatteryStatus;
+use std::cell::Cell;
+
+/// The state of the battery of the device, as reported by the embedder.
+#[dom_struct]
+pub struct BatteryManager {
+    eventtarget: EventTarget,
+    status: Cell<BatteryStatus>,
+}
+
+impl BatteryManager {
+    fn new_inherited(status: BatteryStatus) -> BatteryManager {
+        BatteryManager {
+            eventtarget: EventTarget::new_inherited(),
+            status: Cell::new(status),
+        }
+    }
+
+    pub fn new(global: &GlobalScope, status: BatteryStatus) -> DomRoot<BatteryManager> {
+        reflect_dom_object(Box::new(BatteryManager::new_inherited(status)), global)
+    }
+
+    /// Stores the new state of the battery and fires an event for each attribute that changed.
+    /// <https://w3c.github.io/battery/#updating-the-battery-status>
+    pub fn update(&self, status: BatteryStatus) {
+        let old_status = self.status.replace(status);
+        let target = self.upcast::<EventTarget>();
+        if old_status.charging != status.charging {
+            target.fire_event(atom!("chargingchange"));
+        }
+        if old_status.charging_time != status.charging_time {
+            target.fire_event(atom!("chargingtimechange"));
+        }
+        if old_status.discharging_time != status.discharging_time {
+            target.fire_event(atom!("dischargingtimechange"));
+        }
+        if old_status.level != status.level {
+            target.fire_event(atom!("levelchange"));
+        }
+    }
+}
+
+impl BatteryManagerMethods for BatteryManager {
+    // https://w3c.github.io/battery/#dom-batterymanager-charging
+    fn Charging(&self) -> bool {
+        self.status.get().charging
+    }
+
+    // https://w3c.github.io/battery/#dom-batterymanager-chargingtime
+    fn ChargingTime(&self) -> f64 {
+        self.status.get().charging_time
+    }
+
+    // https://w3c.github.io/battery/#dom-batterymanager-dischargingtime
+    fn DischargingTime(&self) -> f64 {
+        self.status.get().discharging_time
+    }
+
+    // https://w3c.github.io/battery/#dom-batterymanager-level
+    fn Level(&self) -> Finite<f64> {
+        Finite::wrap(self.status.get().level.max(0.).min(1.))
+    }
+
+    // https://w3c.github.io/battery/#dom-batterymanager-onchargingchange
+    event_handler!(chargingchange, GetOnchargingchange, SetOnchargingchange);
+
+    // https://w3c.github.io/battery/#dom-batterymanager-onchargingtimechange
+    event_handler!(
+        chargingtimechange,
+        GetOnchargingtimechange,
+        SetOnchargingtimechange
+    );
+
+    // https://w3c.github.io/battery/#dom-batterymanager-ondischargingtimechange
+    event_handler!(
+        dischargingtimechange,
+        GetOndischargingtimechange,
+        SetOndischargingtimechange
+    );
+
+    // https://w3c.github.io/battery/#dom-batterymanager-onlevelchange
+    event_handler!(levelchange, GetOnlevelchange, SetOnlevelchange);
+}
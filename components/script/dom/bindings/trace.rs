@@ -43,6 +43,8 @@ use crate::dom::htmlmediaelement::{HTMLMediaElementFetchContext, MediaFrameRende
 use crate::dom::identityhub::Identities;
 use crate::script_runtime::StreamConsumer;
 use crate::task::TaskBox;
+use crate::xpath::Expr;
+use crate::xslt::Stylesheet;
 use app_units::Au;
 use canvas_traits::canvas::{
     CanvasGradientStop, CanvasId, LinearGradientStyle, RadialGradientStyle,
@@ -62,7 +64,9 @@ use content_security_policy::CspList;
 use crossbeam_channel::{Receiver, Sender};
 use cssparser::RGBA;
 use devtools_traits::{CSSError, TimelineMarkerType, WorkerId};
-use embedder_traits::{EventLoopWaker, MediaMetadata};
+use embedder_traits::{BatteryStatus, DeviceAcceleration, DeviceOrientation};
+use embedder_traits::{DeviceRotationRate, DeviceSensor, EventLoopWaker, GeolocationPosition};
+use embedder_traits::{MediaImage, MediaMetadata, NetworkInformation, SpeechVoice};
 use encoding_rs::{Decoder, Encoding};
 use euclid::default::{Point2D, Rect, Rotation3D, Transform2D};
 use euclid::Length as EuclidLength;
@@ -83,10 +87,10 @@ use media::WindowGLContext;
 use metrics::{InteractiveMetrics, InteractiveWindow};
 use mime::Mime;
 use msg::constellation_msg::{
-    BlobId, BroadcastChannelRouterId, BrowsingContextId, HistoryStateId, MessagePortId,
-    MessagePortRouterId, PipelineId, TopLevelBrowsingContextId,
+    BlobId, BroadcastChannelRouterId, BrowsingContextId, HistoryStateId, LockRequestId,
+    MessagePortId, MessagePortRouterId, PipelineId, TopLevelBrowsingContextId,
 };
-use net_traits::filemanager_thread::RelativePos;
+use net_traits::filemanager_thread::{FileSystemEntryKind, FileSystemLocator, RelativePos};
 use net_traits::image::base::{Image, ImageMetadata};
 use net_traits::image_cache::{ImageCache, PendingImageId};
 use net_traits::request::{Referrer, Request, RequestBuilder};
@@ -100,7 +104,7 @@ use script_layout_interface::rpc::LayoutRPC;
 use script_layout_interface::OpaqueStyleAndLayoutData;
 use script_traits::serializable::BlobImpl;
 use script_traits::transferable::MessagePortImpl;
-use script_traits::{DocumentActivity, DrawAPaintImageResult};
+use script_traits::{DocumentActivity, DrawAPaintImageResult, FrameTiming};
 use script_traits::{MediaSessionActionType, ScriptToConstellationChan, TimerEventId, TimerSource};
 use script_traits::{UntrustedNodeAddress, WebrenderIpcSender, WindowSizeData, WindowSizeType};
 use selectors::matching::ElementSelectorFlags;
@@ -118,7 +122,7 @@ use servo_media::player::video::VideoFrame;
 use servo_media::player::Player;
 use servo_media::streams::registry::MediaStreamId;
 use servo_media::streams::MediaStreamType;
-use servo_media::webrtc::WebRtcController;
+use servo_media::webrtc::{RtcStats, WebRtcController};
 use servo_url::{ImmutableOrigin, MutableOrigin, ServoUrl};
 use smallvec::SmallVec;
 use std::borrow::Cow;
@@ -178,6 +182,7 @@ unsafe_no_jsmanaged_fields!(MessagePortRouterId);
 unsafe_no_jsmanaged_fields!(BroadcastChannelRouterId);
 
 unsafe_no_jsmanaged_fields!(BlobId);
+unsafe_no_jsmanaged_fields!(LockRequestId);
 unsafe_no_jsmanaged_fields!(BlobImpl);
 
 unsafe_no_jsmanaged_fields!(CSSError);
@@ -460,6 +465,7 @@ unsafe_no_jsmanaged_fields!(PropertyDeclarationBlock);
 // These three are interdependent, if you plan to put jsmanaged data
 // in one of these make sure it is propagated properly to containing structs
 unsafe_no_jsmanaged_fields!(DocumentActivity, WindowSizeData, WindowSizeType);
+unsafe_no_jsmanaged_fields!(FrameTiming);
 unsafe_no_jsmanaged_fields!(
     BrowsingContextId,
     HistoryStateId,
@@ -508,6 +514,7 @@ unsafe_no_jsmanaged_fields!(StatusCode);
 unsafe_no_jsmanaged_fields!(SystemTime);
 unsafe_no_jsmanaged_fields!(Instant);
 unsafe_no_jsmanaged_fields!(RelativePos);
+unsafe_no_jsmanaged_fields!(FileSystemEntryKind, FileSystemLocator);
 unsafe_no_jsmanaged_fields!(OpaqueStyleAndLayoutData);
 unsafe_no_jsmanaged_fields!(PathBuf);
 unsafe_no_jsmanaged_fields!(DrawAPaintImageResult);
@@ -566,7 +573,7 @@ unsafe_no_jsmanaged_fields!(Arc<Mutex<AudioContext>>);
 unsafe_no_jsmanaged_fields!(NodeId);
 unsafe_no_jsmanaged_fields!(AnalysisEngine, DistanceModel, PanningModel, ParamType);
 unsafe_no_jsmanaged_fields!(Arc<Mutex<dyn Player>>);
-unsafe_no_jsmanaged_fields!(WebRtcController);
+unsafe_no_jsmanaged_fields!(RtcStats, WebRtcController);
 unsafe_no_jsmanaged_fields!(MediaStreamId, MediaStreamType);
 unsafe_no_jsmanaged_fields!(Mutex<MediaFrameRenderer>);
 unsafe_no_jsmanaged_fields!(ResourceFetchTiming);
@@ -581,9 +588,16 @@ unsafe_no_jsmanaged_fields!(VideoFrame);
 unsafe_no_jsmanaged_fields!(WebGLContextId);
 unsafe_no_jsmanaged_fields!(Arc<Mutex<dyn AudioRenderer>>);
 unsafe_no_jsmanaged_fields!(MediaSessionActionType);
-unsafe_no_jsmanaged_fields!(MediaMetadata);
+unsafe_no_jsmanaged_fields!(MediaImage, MediaMetadata);
+unsafe_no_jsmanaged_fields!(SpeechVoice);
+unsafe_no_jsmanaged_fields!(BatteryStatus, NetworkInformation);
+unsafe_no_jsmanaged_fields!(GeolocationPosition);
+unsafe_no_jsmanaged_fields!(DeviceAcceleration, DeviceOrientation, DeviceRotationRate);
+unsafe_no_jsmanaged_fields!(DeviceSensor);
 unsafe_no_jsmanaged_fields!(WebrenderIpcSender);
 unsafe_no_jsmanaged_fields!(StreamConsumer);
+unsafe_no_jsmanaged_fields!(Expr);
+unsafe_no_jsmanaged_fields!(Stylesheet);
 
 unsafe impl<'a> JSTraceable for &'a str {
     #[inline]
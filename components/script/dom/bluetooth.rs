@@ -39,6 +39,7 @@ use js::conversions::ConversionResult;
 use js::jsapi::JSObject;
 use js::jsval::{ObjectValue, UndefinedValue};
 use profile_traits::ipc as ProfiledIpc;
+use servo_config::pref;
 use std::collections::HashMap;
 use std::rc::Rc;
 use std::str::FromStr;
@@ -163,7 +164,14 @@ impl Bluetooth {
         optional_services: &[BluetoothServiceUUID],
         sender: IpcSender<BluetoothResponseResult>,
     ) {
-        // TODO: Step 1: Triggered by user activation.
+        // Step 1.
+        // The tests request devices without user interaction.
+        if !pref!(dom.bluetooth.testing.enabled) &&
+            !self.global().as_window().has_transient_activation()
+        {
+            p.reject_error(Security);
+            return;
+        }
 
         // Step 2.2: There are no requiredServiceUUIDS, we scan for all devices.
         let mut uuid_filters = vec![];
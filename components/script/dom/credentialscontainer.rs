@@ -0,0 +1,308 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::authenticatorassertionresponse::AuthenticatorAssertionResponse;
+use crate::dom::authenticatorattestationresponse::AuthenticatorAttestationResponse;
+use crate::dom::bindings::codegen::Bindings::CredentialsContainerBinding::CredentialsContainerMethods;
+use crate::dom::bindings::codegen::Bindings::CredentialsContainerBinding::{
+    CredentialCreationOptions, CredentialRequestOptions,
+};
+use crate::dom::bindings::codegen::Bindings::PublicKeyCredentialBinding::PublicKeyCredentialDescriptor;
+use crate::dom::bindings::codegen::UnionTypes::ArrayBufferViewOrArrayBuffer;
+use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::trace::RootedTraceableBox;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::promise::Promise;
+use crate::dom::publickeycredential::PublicKeyCredential;
+use crate::dom::window::Window;
+use crate::realms::InRealm;
+use dom_struct::dom_struct;
+use net_traits::pub_domains::get_registrable_domain_suffix_of_or_is_equal_to;
+use net_traits::webauthn::{AuthenticatorError, AuthenticatorMsg, COSE_ALGORITHM_ES256};
+use net_traits::webauthn::{GetAssertionRequest, MakeCredentialRequest};
+use openssl::hash::{hash, MessageDigest};
+use profile_traits::ipc;
+use script_traits::ScriptMsg;
+use std::rc::Rc;
+
+/// The COSE identifier of RSASSA-PKCS1-v1_5 with SHA-256, which relying parties accept by
+/// default along with ES256.
+/// <https://w3c.github.io/webauthn/#dom-publickeycredentialcreationoptions-pubkeycredparams>
+const COSE_ALGORITHM_RS256: i32 = -257;
+
+/// <https://w3c.github.io/webappsec-credential-management/#credentialscontainer>
+///
+/// Only public key credentials are supported, whose operations are made before the methods
+/// return, with a platform authenticator that lives in the resource thread. The constellation
+/// only forwards the operations for the relying parties that the page may use, and the
+/// authenticator asks the user to agree to them.
+#[dom_struct]
+pub struct CredentialsContainer {
+    reflector_: Reflector,
+}
+
+impl CredentialsContainer {
+    fn new_inherited() -> CredentialsContainer {
+        CredentialsContainer {
+            reflector_: Reflector::new(),
+        }
+    }
+
+    pub fn new(global: &GlobalScope) -> DomRoot<CredentialsContainer> {
+        reflect_dom_object(Box::new(CredentialsContainer::new_inherited()), global)
+    }
+
+    /// <https://w3c.github.io/webauthn/#sctn-createCredential>
+    fn create_public_key_credential(
+        &self,
+        options: &CredentialCreationOptions,
+    ) -> Fallible<DomRoot<PublicKeyCredential>> {
+        let global = self.global();
+        let window = global.as_window();
+        // Step 1.
+        let options = options.publicKey.as_ref().ok_or(Error::NotSupported)?;
+
+        // Step 2.
+        if !is_same_origin_with_ancestors(window) {
+            return Err(Error::NotAllowed);
+        }
+
+        // Steps 6-8.
+        let rp_id = relying_party_id(&global, options.rp.id.as_ref().map(|id| &**id))?;
+
+        // Step 5.
+        let user_id = buffer_source_to_vec(&options.user.id);
+        if user_id.is_empty() || user_id.len() > 64 {
+            return Err(Error::Type(
+                "The user handle must be between 1 and 64 bytes long".to_owned(),
+            ));
+        }
+
+        // Steps 9-10.
+        let algorithms = if options.pubKeyCredParams.is_empty() {
+            vec![COSE_ALGORITHM_ES256, COSE_ALGORITHM_RS256]
+        } else {
+            options
+                .pubKeyCredParams
+                .iter()
+                .filter(|parameters| &*parameters.type_ == "public-key")
+                .map(|parameters| parameters.alg)
+                .collect()
+        };
+        if algorithms.is_empty() {
+            return Err(Error::NotSupported);
+        }
+
+        // Step 20. There is no roaming authenticator, and the platform one can't verify the
+        // user.
+        let selection = &options.authenticatorSelection;
+        if selection.authenticatorAttachment.as_ref().map(|a| &**a) == Some("cross-platform") ||
+            &*selection.userVerification == "required"
+        {
+            return Err(Error::NotAllowed);
+        }
+
+        // Steps 13-14.
+        let client_data_json = client_data_json(
+            "webauthn.create",
+            &buffer_source_to_vec(&options.challenge),
+            &global,
+        );
+        let client_data_hash = hash(MessageDigest::sha256(), client_data_json.as_bytes())
+            .map_err(|_| Error::Operation)?
+            .to_vec();
+
+        let request = MakeCredentialRequest {
+            client_data_hash,
+            rp_id,
+            user_id,
+            user_name: options.user.parent.name.to_string(),
+            algorithms,
+            exclude_list: descriptor_ids(&options.excludeCredentials),
+        };
+        let (sender, receiver) = ipc::channel(global.time_profiler_chan().clone()).unwrap();
+        // Step 20. The user needs to be present, which the authenticator tests by asking them.
+        let _ = global
+            .script_to_constellation_chan()
+            .send(ScriptMsg::ToAuthenticator(
+                AuthenticatorMsg::MakeCredential(request, sender),
+            ));
+        let response = receiver
+            .recv()
+            .map_err(|_| Error::NotAllowed)?
+            .map_err(authenticator_error)?;
+
+        // Step 21.
+        let attestation_response =
+            AuthenticatorAttestationResponse::new(&global, client_data_json.as_bytes(), &response);
+        Ok(PublicKeyCredential::new(
+            &global,
+            &response.credential_id,
+            attestation_response.upcast(),
+        ))
+    }
+
+    /// <https://w3c.github.io/webauthn/#sctn-getAssertion>
+    fn get_public_key_credential(
+        &self,
+        options: &CredentialRequestOptions,
+    ) -> Fallible<DomRoot<PublicKeyCredential>> {
+        let global = self.global();
+        let window = global.as_window();
+        // Step 1.
+        let options = options.publicKey.as_ref().ok_or(Error::NotSupported)?;
+
+        // Without permissions policies, credentials can't be used from other origins.
+        if !is_same_origin_with_ancestors(window) {
+            return Err(Error::NotAllowed);
+        }
+
+        // Steps 6-7.
+        let rp_id = relying_party_id(&global, options.rpId.as_ref().map(|id| &*id.0))?;
+
+        // The platform authenticator can't verify the user.
+        if &*options.userVerification == "required" {
+            return Err(Error::NotAllowed);
+        }
+
+        // Steps 11-12.
+        let client_data_json = client_data_json(
+            "webauthn.get",
+            &buffer_source_to_vec(&options.challenge),
+            &global,
+        );
+        let client_data_hash = hash(MessageDigest::sha256(), client_data_json.as_bytes())
+            .map_err(|_| Error::Operation)?
+            .to_vec();
+
+        let request = GetAssertionRequest {
+            client_data_hash,
+            rp_id,
+            allow_list: descriptor_ids(&options.allowCredentials),
+        };
+        let (sender, receiver) = ipc::channel(global.time_profiler_chan().clone()).unwrap();
+        // Step 18. The user needs to be present, which the authenticator tests by asking them.
+        let _ = global
+            .script_to_constellation_chan()
+            .send(ScriptMsg::ToAuthenticator(AuthenticatorMsg::GetAssertion(
+                request, sender,
+            )));
+        let response = receiver
+            .recv()
+            .map_err(|_| Error::NotAllowed)?
+            .map_err(authenticator_error)?;
+
+        // Step 20.
+        let assertion_response =
+            AuthenticatorAssertionResponse::new(&global, client_data_json.as_bytes(), &response);
+        Ok(PublicKeyCredential::new(
+            &global,
+            &response.credential_id,
+            assertion_response.upcast(),
+        ))
+    }
+}
+
+impl CredentialsContainerMethods for CredentialsContainer {
+    // https://w3c.github.io/webappsec-credential-management/#dom-credentialscontainer-get
+    fn Get(
+        &self,
+        options: RootedTraceableBox<CredentialRequestOptions>,
+        comp: InRealm,
+    ) -> Rc<Promise> {
+        let promise = Promise::new_in_current_realm(&self.global(), comp);
+        match self.get_public_key_credential(&options) {
+            Ok(credential) => promise.resolve_native(&credential),
+            Err(error) => promise.reject_error(error),
+        }
+        promise
+    }
+
+    // https://w3c.github.io/webappsec-credential-management/#dom-credentialscontainer-create
+    fn Create(
+        &self,
+        options: RootedTraceableBox<CredentialCreationOptions>,
+        comp: InRealm,
+    ) -> Rc<Promise> {
+        let promise = Promise::new_in_current_realm(&self.global(), comp);
+        match self.create_public_key_credential(&options) {
+            Ok(credential) => promise.resolve_native(&credential),
+            Err(error) => promise.reject_error(error),
+        }
+        promise
+    }
+}
+
+fn buffer_source_to_vec(buffer: &ArrayBufferViewOrArrayBuffer) -> Vec<u8> {
+    match *buffer {
+        ArrayBufferViewOrArrayBuffer::ArrayBufferView(ref view) => view.to_vec(),
+        ArrayBufferViewOrArrayBuffer::ArrayBuffer(ref buffer) => buffer.to_vec(),
+    }
+}
+
+fn descriptor_ids(
+    descriptors: &[RootedTraceableBox<PublicKeyCredentialDescriptor>],
+) -> Vec<Vec<u8>> {
+    descriptors
+        .iter()
+        .filter(|descriptor| &*descriptor.type_ == "public-key")
+        .map(|descriptor| buffer_source_to_vec(&descriptor.id))
+        .collect()
+}
+
+/// <https://w3c.github.io/webauthn/#same-origin-with-its-ancestors>
+fn is_same_origin_with_ancestors(window: &Window) -> bool {
+    let origin = window.upcast::<GlobalScope>().origin();
+    let window_proxy = window.window_proxy();
+    let mut ancestor = window_proxy.parent();
+    while let Some(proxy) = ancestor {
+        match proxy.document() {
+            Some(ref document) if document.origin().same_origin(origin) => {
+                ancestor = proxy.parent()
+            },
+            _ => return false,
+        }
+    }
+    true
+}
+
+/// The RP ID that the options give, which must be the effective domain of the page or one of
+/// its registrable suffixes, or the effective domain itself.
+/// <https://w3c.github.io/webauthn/#rp-id>
+fn relying_party_id(global: &GlobalScope, rp_id: Option<&str>) -> Fallible<String> {
+    let effective_domain = global.origin().effective_domain().ok_or(Error::Security)?;
+    match rp_id {
+        Some(rp_id) => get_registrable_domain_suffix_of_or_is_equal_to(rp_id, effective_domain)
+            .map(|host| host.to_string())
+            .ok_or(Error::Security),
+        None => Ok(effective_domain.to_string()),
+    }
+}
+
+/// The client data is never cross-origin, since the operations are only allowed in documents
+/// that are same origin with their ancestors.
+/// <https://w3c.github.io/webauthn/#clientdatajson-serialization>
+fn client_data_json(client_data_type: &str, challenge: &[u8], global: &GlobalScope) -> String {
+    let challenge = base64::encode_config(challenge, base64::URL_SAFE_NO_PAD);
+    let origin = global.origin().immutable().ascii_serialization();
+    format!(
+        "{{\"type\":{},\"challenge\":{},\"origin\":{},\"crossOrigin\":false}}",
+        serde_json::to_string(client_data_type).unwrap(),
+        serde_json::to_string(&challenge).unwrap(),
+        serde_json::to_string(&origin).unwrap(),
+    )
+}
+
+fn authenticator_error(error: AuthenticatorError) -> Error {
+    match error {
+        AuthenticatorError::UnsupportedAlgorithm => Error::NotSupported,
+        AuthenticatorError::CredentialExcluded => Error::InvalidState,
+        AuthenticatorError::NoCredentials |
+        AuthenticatorError::Failed |
+        AuthenticatorError::NotAllowed => Error::NotAllowed,
+    }
+}
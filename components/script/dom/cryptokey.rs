@@ -0,0 +1,253 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::CryptoKeyBinding::RsaHashedKeyAlgorithm;
+use crate::dom::bindings::codegen::Bindings::CryptoKeyBinding::{AesKeyAlgorithm, KeyAlgorithm};
+use crate::dom::bindings::codegen::Bindings::CryptoKeyBinding::{CryptoKeyMethods, KeyType};
+use crate::dom::bindings::codegen::Bindings::CryptoKeyBinding::{EcKeyAlgorithm, KeyUsage};
+use crate::dom::bindings::codegen::Bindings::CryptoKeyBinding::{
+    HmacKeyAlgorithm, RsaKeyAlgorithm,
+};
+use crate::dom::bindings::conversions::ToJSValConvertible;
+use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::bindings::utils::set_dictionary_property;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::subtlecrypto::{AlgorithmName, HashAlgorithm, NamedCurve};
+use crate::script_runtime::JSContext;
+use dom_struct::dom_struct;
+use js::jsapi::{Heap, JSObject};
+use js::jsval::{ObjectValue, UndefinedValue};
+use js::rust::MutableHandleValue;
+use js::typedarray::{CreateWith, Uint8Array};
+use openssl::pkey::{PKey, Private, Public};
+use std::ptr::{self, NonNull};
+
+/// The key material of a key.
+/// <https://w3c.github.io/webcrypto/#dfn-CryptoKey-slot-handle>
+#[derive(Clone)]
+pub enum Handle {
+    Secret(Vec<u8>),
+    Private(PKey<Private>),
+    Public(PKey<Public>),
+}
+
+unsafe_no_jsmanaged_fields!(Handle);
+
+/// The algorithm that a key can be used with, and the parameters that were fixed when the key
+/// was generated or imported.
+/// <https://w3c.github.io/webcrypto/#dfn-CryptoKey-slot-algorithm>
+#[derive(Clone, Debug, JSTraceable, MallocSizeOf, PartialEq)]
+pub enum CryptoKeyAlgorithm {
+    /// RSA-PSS and RSA-OAEP.
+    RsaHashed {
+        name: AlgorithmName,
+        modulus_length: u32,
+        public_exponent: Vec<u8>,
+        hash: HashAlgorithm,
+    },
+    /// ECDSA and ECDH.
+    Ec {
+        name: AlgorithmName,
+        curve: NamedCurve,
+    },
+    /// AES-GCM.
+    Aes {
+        name: AlgorithmName,
+        length: u16,
+    },
+    Hmac {
+        hash: HashAlgorithm,
+        length: u32,
+    },
+    /// HKDF and PBKDF2.
+    Kdf(AlgorithmName),
+}
+
+impl CryptoKeyAlgorithm {
+    pub fn name(&self) -> AlgorithmName {
+        match *self {
+            CryptoKeyAlgorithm::RsaHashed { name, .. } |
+            CryptoKeyAlgorithm::Ec { name, .. } |
+            CryptoKeyAlgorithm::Aes { name, .. } |
+            CryptoKeyAlgorithm::Kdf(name) => name,
+            CryptoKeyAlgorithm::Hmac { .. } => AlgorithmName::Hmac,
+        }
+    }
+
+    /// <https://w3c.github.io/webcrypto/#dom-cryptokey-algorithm>
+    #[allow(unsafe_code)]
+    unsafe fn to_jsval(&self, cx: JSContext, rval: MutableHandleValue) {
+        let key_algorithm = |name: &str| KeyAlgorithm {
+            name: DOMString::from(name),
+        };
+        let parent = key_algorithm(self.name().as_str());
+        match *self {
+            CryptoKeyAlgorithm::RsaHashed {
+                modulus_length,
+                ref public_exponent,
+                hash,
+                ..
+            } => {
+                let algorithm = RsaHashedKeyAlgorithm {
+                    parent: RsaKeyAlgorithm {
+                        parent,
+                        modulusLength: modulus_length,
+                    },
+                    hash: key_algorithm(hash.as_str()),
+                };
+                rooted!(in(*cx) let mut value = UndefinedValue());
+                algorithm.to_jsval(*cx, value.handle_mut());
+
+                rooted!(in(*cx) let object = value.to_object());
+                rooted!(in(*cx) let mut array = ptr::null_mut::<JSObject>());
+                let _ =
+                    Uint8Array::create(*cx, CreateWith::Slice(public_exponent), array.handle_mut());
+                rooted!(in(*cx) let exponent = ObjectValue(array.get()));
+                let _ = set_dictionary_property(
+                    *cx,
+                    object.handle(),
+                    "publicExponent",
+                    exponent.handle(),
+                );
+                rval.set(value.get());
+            },
+            CryptoKeyAlgorithm::Ec { curve, .. } => {
+                let algorithm = EcKeyAlgorithm {
+                    parent,
+                    namedCurve: DOMString::from(curve.as_str()),
+                };
+                algorithm.to_jsval(*cx, rval);
+            },
+            CryptoKeyAlgorithm::Aes { length, .. } => {
+                AesKeyAlgorithm { parent, length }.to_jsval(*cx, rval);
+            },
+            CryptoKeyAlgorithm::Hmac { hash, length } => {
+                let algorithm = HmacKeyAlgorithm {
+                    parent,
+                    hash: key_algorithm(hash.as_str()),
+                    length,
+                };
+                algorithm.to_jsval(*cx, rval);
+            },
+            CryptoKeyAlgorithm::Kdf(_) => parent.to_jsval(*cx, rval),
+        }
+    }
+}
+
+/// <https://w3c.github.io/webcrypto/#cryptokey-interface>
+#[dom_struct]
+pub struct CryptoKey {
+    reflector_: Reflector,
+    key_type: KeyType,
+    extractable: bool,
+    algorithm: CryptoKeyAlgorithm,
+    usages: Vec<KeyUsage>,
+    #[ignore_malloc_size_of = "Defined in openssl"]
+    handle: Handle,
+    /// The objects that the algorithm and usages attributes return.
+    #[ignore_malloc_size_of = "mozjs"]
+    algorithm_object: Heap<*mut JSObject>,
+    #[ignore_malloc_size_of = "mozjs"]
+    usages_object: Heap<*mut JSObject>,
+}
+
+impl CryptoKey {
+    fn new_inherited(
+        key_type: KeyType,
+        extractable: bool,
+        algorithm: CryptoKeyAlgorithm,
+        usages: Vec<KeyUsage>,
+        handle: Handle,
+    ) -> CryptoKey {
+        CryptoKey {
+            reflector_: Reflector::new(),
+            key_type,
+            extractable,
+            algorithm,
+            usages,
+            handle,
+            algorithm_object: Heap::default(),
+            usages_object: Heap::default(),
+        }
+    }
+
+    #[allow(unsafe_code)]
+    pub fn new(
+        global: &GlobalScope,
+        key_type: KeyType,
+        extractable: bool,
+        algorithm: CryptoKeyAlgorithm,
+        usages: Vec<KeyUsage>,
+        handle: Handle,
+    ) -> DomRoot<CryptoKey> {
+        let key = reflect_dom_object(
+            Box::new(CryptoKey::new_inherited(
+                key_type,
+                extractable,
+                algorithm,
+                usages,
+                handle,
+            )),
+            global,
+        );
+
+        let cx = global.get_cx();
+        rooted!(in(*cx) let mut algorithm = UndefinedValue());
+        rooted!(in(*cx) let mut usages = UndefinedValue());
+        unsafe {
+            key.algorithm.to_jsval(cx, algorithm.handle_mut());
+            key.usages.to_jsval(*cx, usages.handle_mut());
+        }
+        key.algorithm_object.set(algorithm.to_object());
+        key.usages_object.set(usages.to_object());
+
+        key
+    }
+
+    pub fn key_type(&self) -> KeyType {
+        self.key_type
+    }
+
+    pub fn extractable(&self) -> bool {
+        self.extractable
+    }
+
+    pub fn algorithm(&self) -> &CryptoKeyAlgorithm {
+        &self.algorithm
+    }
+
+    pub fn usages(&self) -> &[KeyUsage] {
+        &self.usages
+    }
+
+    pub fn handle(&self) -> &Handle {
+        &self.handle
+    }
+}
+
+impl CryptoKeyMethods for CryptoKey {
+    // https://w3c.github.io/webcrypto/#dom-cryptokey-type
+    fn Type(&self) -> KeyType {
+        self.key_type
+    }
+
+    // https://w3c.github.io/webcrypto/#dom-cryptokey-extractable
+    fn Extractable(&self) -> bool {
+        self.extractable
+    }
+
+    #[allow(unsafe_code)]
+    // https://w3c.github.io/webcrypto/#dom-cryptokey-algorithm
+    fn Algorithm(&self, _cx: JSContext) -> NonNull<JSObject> {
+        unsafe { NonNull::new_unchecked(self.algorithm_object.get()) }
+    }
+
+    #[allow(unsafe_code)]
+    // https://w3c.github.io/webcrypto/#dom-cryptokey-usages
+    fn Usages(&self, _cx: JSContext) -> NonNull<JSObject> {
+        unsafe { NonNull::new_unchecked(self.usages_object.get()) }
+    }
+}
@@ -4,9 +4,13 @@
 
 use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowBinding::WindowMethods;
 use crate::dom::bindings::error::Fallible;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::num::Finite;
 use crate::dom::bindings::reflector::Reflector;
 use crate::dom::bindings::root::DomRoot;
 use crate::dom::bindings::str::DOMString;
+use crate::dom::cssunitvalue::CSSUnitValue;
+use crate::dom::highlightregistry::HighlightRegistry;
 use crate::dom::window::Window;
 use crate::dom::worklet::Worklet;
 use cssparser::{serialize_identifier, Parser, ParserInput};
@@ -75,4 +79,130 @@ impl CSS {
     pub fn PaintWorklet(win: &Window) -> DomRoot<Worklet> {
         win.paint_worklet()
     }
+
+    /// <https://drafts.csswg.org/css-highlight-api-1/#dom-css-highlights>
+    pub fn Highlights(win: &Window) -> DomRoot<HighlightRegistry> {
+        win.Document().highlight_registry()
+    }
+
+    /// <https://drafts.css-houdini.org/css-typed-om-1/#numeric-factory>
+    fn unit_value(win: &Window, value: Finite<f64>, unit: &str) -> DomRoot<CSSUnitValue> {
+        CSSUnitValue::new(win.upcast(), *value, unit)
+    }
+
+    pub fn Number(win: &Window, value: Finite<f64>) -> DomRoot<CSSUnitValue> {
+        CSS::unit_value(win, value, "number")
+    }
+
+    pub fn Percent(win: &Window, value: Finite<f64>) -> DomRoot<CSSUnitValue> {
+        CSS::unit_value(win, value, "percent")
+    }
+
+    pub fn Em(win: &Window, value: Finite<f64>) -> DomRoot<CSSUnitValue> {
+        CSS::unit_value(win, value, "em")
+    }
+
+    pub fn Ex(win: &Window, value: Finite<f64>) -> DomRoot<CSSUnitValue> {
+        CSS::unit_value(win, value, "ex")
+    }
+
+    pub fn Ch(win: &Window, value: Finite<f64>) -> DomRoot<CSSUnitValue> {
+        CSS::unit_value(win, value, "ch")
+    }
+
+    pub fn Rem(win: &Window, value: Finite<f64>) -> DomRoot<CSSUnitValue> {
+        CSS::unit_value(win, value, "rem")
+    }
+
+    pub fn Vw(win: &Window, value: Finite<f64>) -> DomRoot<CSSUnitValue> {
+        CSS::unit_value(win, value, "vw")
+    }
+
+    pub fn Vh(win: &Window, value: Finite<f64>) -> DomRoot<CSSUnitValue> {
+        CSS::unit_value(win, value, "vh")
+    }
+
+    pub fn Vmin(win: &Window, value: Finite<f64>) -> DomRoot<CSSUnitValue> {
+        CSS::unit_value(win, value, "vmin")
+    }
+
+    pub fn Vmax(win: &Window, value: Finite<f64>) -> DomRoot<CSSUnitValue> {
+        CSS::unit_value(win, value, "vmax")
+    }
+
+    pub fn Cm(win: &Window, value: Finite<f64>) -> DomRoot<CSSUnitValue> {
+        CSS::unit_value(win, value, "cm")
+    }
+
+    pub fn Mm(win: &Window, value: Finite<f64>) -> DomRoot<CSSUnitValue> {
+        CSS::unit_value(win, value, "mm")
+    }
+
+    pub fn Q(win: &Window, value: Finite<f64>) -> DomRoot<CSSUnitValue> {
+        CSS::unit_value(win, value, "q")
+    }
+
+    pub fn In(win: &Window, value: Finite<f64>) -> DomRoot<CSSUnitValue> {
+        CSS::unit_value(win, value, "in")
+    }
+
+    pub fn Pt(win: &Window, value: Finite<f64>) -> DomRoot<CSSUnitValue> {
+        CSS::unit_value(win, value, "pt")
+    }
+
+    pub fn Pc(win: &Window, value: Finite<f64>) -> DomRoot<CSSUnitValue> {
+        CSS::unit_value(win, value, "pc")
+    }
+
+    pub fn Px(win: &Window, value: Finite<f64>) -> DomRoot<CSSUnitValue> {
+        CSS::unit_value(win, value, "px")
+    }
+
+    pub fn Deg(win: &Window, value: Finite<f64>) -> DomRoot<CSSUnitValue> {
+        CSS::unit_value(win, value, "deg")
+    }
+
+    pub fn Grad(win: &Window, value: Finite<f64>) -> DomRoot<CSSUnitValue> {
+        CSS::unit_value(win, value, "grad")
+    }
+
+    pub fn Rad(win: &Window, value: Finite<f64>) -> DomRoot<CSSUnitValue> {
+        CSS::unit_value(win, value, "rad")
+    }
+
+    pub fn Turn(win: &Window, value: Finite<f64>) -> DomRoot<CSSUnitValue> {
+        CSS::unit_value(win, value, "turn")
+    }
+
+    pub fn S(win: &Window, value: Finite<f64>) -> DomRoot<CSSUnitValue> {
+        CSS::unit_value(win, value, "s")
+    }
+
+    pub fn Ms(win: &Window, value: Finite<f64>) -> DomRoot<CSSUnitValue> {
+        CSS::unit_value(win, value, "ms")
+    }
+
+    pub fn Hz(win: &Window, value: Finite<f64>) -> DomRoot<CSSUnitValue> {
+        CSS::unit_value(win, value, "hz")
+    }
+
+    pub fn KHz(win: &Window, value: Finite<f64>) -> DomRoot<CSSUnitValue> {
+        CSS::unit_value(win, value, "khz")
+    }
+
+    pub fn Dpi(win: &Window, value: Finite<f64>) -> DomRoot<CSSUnitValue> {
+        CSS::unit_value(win, value, "dpi")
+    }
+
+    pub fn Dpcm(win: &Window, value: Finite<f64>) -> DomRoot<CSSUnitValue> {
+        CSS::unit_value(win, value, "dpcm")
+    }
+
+    pub fn Dppx(win: &Window, value: Finite<f64>) -> DomRoot<CSSUnitValue> {
+        CSS::unit_value(win, value, "dppx")
+    }
+
+    pub fn Fr(win: &Window, value: Finite<f64>) -> DomRoot<CSSUnitValue> {
+        CSS::unit_value(win, value, "fr")
+    }
 }
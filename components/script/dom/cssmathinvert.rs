@@ -0,0 +1,49 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::CSSMathInvertBinding::CSSMathInvertMethods;
+use crate::dom::bindings::codegen::UnionTypes::DoubleOrCSSNumericValue as CSSNumberish;
+use crate::dom::bindings::reflector::reflect_dom_object;
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::cssmathvalue::CSSMathValue;
+use crate::dom::cssnumericvalue::{rectify_numberish, CSSNumericValue};
+use crate::dom::globalscope::GlobalScope;
+use dom_struct::dom_struct;
+
+/// <https://drafts.css-houdini.org/css-typed-om-1/#cssmathinvert>
+#[dom_struct]
+pub struct CSSMathInvert {
+    math_value: CSSMathValue,
+    value: Dom<CSSNumericValue>,
+}
+
+impl CSSMathInvert {
+    fn new_inherited(value: &CSSNumericValue) -> CSSMathInvert {
+        CSSMathInvert {
+            math_value: CSSMathValue::new_inherited(value.numeric_type().invert()),
+            value: Dom::from_ref(value),
+        }
+    }
+
+    pub fn new(global: &GlobalScope, value: &CSSNumericValue) -> DomRoot<CSSMathInvert> {
+        reflect_dom_object(Box::new(CSSMathInvert::new_inherited(value)), global)
+    }
+
+    /// <https://drafts.css-houdini.org/css-typed-om-1/#dom-cssmathinvert-cssmathinvert>
+    #[allow(non_snake_case)]
+    pub fn Constructor(global: &GlobalScope, arg: CSSNumberish) -> DomRoot<CSSMathInvert> {
+        CSSMathInvert::new(global, &rectify_numberish(global, &arg))
+    }
+
+    pub fn operand(&self) -> DomRoot<CSSNumericValue> {
+        DomRoot::from_ref(&*self.value)
+    }
+}
+
+impl CSSMathInvertMethods for CSSMathInvert {
+    /// <https://drafts.css-houdini.org/css-typed-om-1/#dom-cssmathinvert-value>
+    fn Value(&self) -> DomRoot<CSSNumericValue> {
+        self.operand()
+    }
+}
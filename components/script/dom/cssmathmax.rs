@@ -0,0 +1,85 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::CSSMathMaxBinding::CSSMathMaxMethods;
+use crate::dom::bindings::codegen::UnionTypes::DoubleOrCSSNumericValue as CSSNumberish;
+use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::reflector::reflect_dom_object;
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::cssmathvalue::CSSMathValue;
+use crate::dom::cssnumericarray::CSSNumericArray;
+use crate::dom::cssnumericvalue::NumericType;
+use crate::dom::cssnumericvalue::{rectify_numberish, CSSNumericValue, MathValueWithValues};
+use crate::dom::globalscope::GlobalScope;
+use dom_struct::dom_struct;
+
+/// <https://drafts.css-houdini.org/css-typed-om-1/#cssmathmax>
+#[dom_struct]
+pub struct CSSMathMax {
+    math_value: CSSMathValue,
+    values: Dom<CSSNumericArray>,
+}
+
+impl CSSMathMax {
+    fn new_inherited(numeric_type: NumericType, values: &CSSNumericArray) -> CSSMathMax {
+        CSSMathMax {
+            math_value: CSSMathValue::new_inherited(numeric_type),
+            values: Dom::from_ref(values),
+        }
+    }
+
+    /// <https://drafts.css-houdini.org/css-typed-om-1/#dom-cssmathmax-cssmathmax>
+    pub fn new(
+        global: &GlobalScope,
+        values: Vec<DomRoot<CSSNumericValue>>,
+    ) -> Fallible<DomRoot<CSSMathMax>> {
+        // Step 2.
+        if values.is_empty() {
+            return Err(Error::Syntax);
+        }
+
+        // Step 3.
+        let mut numeric_type = values[0].numeric_type();
+        for value in &values[1..] {
+            numeric_type = match numeric_type.add(value.numeric_type()) {
+                Some(numeric_type) => numeric_type,
+                None => return Err(Error::Type("Incompatible types".to_owned())),
+            };
+        }
+
+        // Step 4.
+        let values = CSSNumericArray::new(global, &values);
+        Ok(reflect_dom_object(
+            Box::new(CSSMathMax::new_inherited(numeric_type, &values)),
+            global,
+        ))
+    }
+
+    /// <https://drafts.css-houdini.org/css-typed-om-1/#dom-cssmathmax-cssmathmax>
+    #[allow(non_snake_case)]
+    pub fn Constructor(
+        global: &GlobalScope,
+        args: Vec<CSSNumberish>,
+    ) -> Fallible<DomRoot<CSSMathMax>> {
+        // Step 1.
+        let values = args
+            .iter()
+            .map(|arg| rectify_numberish(global, arg))
+            .collect();
+        CSSMathMax::new(global, values)
+    }
+}
+
+impl MathValueWithValues for CSSMathMax {
+    fn math_values(&self) -> Vec<DomRoot<CSSNumericValue>> {
+        self.values.values()
+    }
+}
+
+impl CSSMathMaxMethods for CSSMathMax {
+    /// <https://drafts.css-houdini.org/css-typed-om-1/#dom-cssmathmax-values>
+    fn Values(&self) -> DomRoot<CSSNumericArray> {
+        DomRoot::from_ref(&*self.values)
+    }
+}
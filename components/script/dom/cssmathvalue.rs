@@ -0,0 +1,177 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::CSSMathValueBinding::{
+    CSSMathOperator, CSSMathValueMethods,
+};
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::cssmathinvert::CSSMathInvert;
+use crate::dom::cssmathmax::CSSMathMax;
+use crate::dom::cssmathmin::CSSMathMin;
+use crate::dom::cssmathnegate::CSSMathNegate;
+use crate::dom::cssmathproduct::CSSMathProduct;
+use crate::dom::cssmathsum::CSSMathSum;
+use crate::dom::cssnumericvalue::{CSSNumericValue, MathValueWithValues, NumericType};
+use dom_struct::dom_struct;
+
+/// <https://drafts.css-houdini.org/css-typed-om-1/#cssmathvalue>
+#[dom_struct]
+pub struct CSSMathValue {
+    numeric_value: CSSNumericValue,
+}
+
+impl CSSMathValue {
+    pub fn new_inherited(numeric_type: NumericType) -> CSSMathValue {
+        CSSMathValue {
+            numeric_value: CSSNumericValue::new_inherited(numeric_type),
+        }
+    }
+
+    /// <https://drafts.css-houdini.org/css-typed-om-1/#serialize-a-cssmathvalue>
+    pub fn serialize(&self, nested: bool, paren_less: bool) -> String {
+        // Step 2.
+        let (name, values) = if let Some(min) = self.downcast::<CSSMathMin>() {
+            ("min(", min.math_values())
+        } else if let Some(max) = self.downcast::<CSSMathMax>() {
+            ("max(", max.math_values())
+        } else {
+            ("", vec![])
+        };
+        if !name.is_empty() {
+            let values: Vec<_> = values
+                .iter()
+                .map(|value| value.serialize(true, true))
+                .collect();
+            return format!("{}{})", name, values.join(", "));
+        }
+
+        let (open, close) = match (nested, paren_less) {
+            (_, true) => ("", ""),
+            (true, false) => ("(", ")"),
+            (false, false) => ("calc(", ")"),
+        };
+
+        // Steps 3 and 4.
+        if let Some(sum) = self.downcast::<CSSMathSum>() {
+            let values = serialize_operands(sum.math_values(), true);
+            return format!("{}{}{}", open, values, close);
+        }
+        if let Some(product) = self.downcast::<CSSMathProduct>() {
+            let values = serialize_operands(product.math_values(), false);
+            return format!("{}{}{}", open, values, close);
+        }
+
+        // Step 5.
+        if let Some(negate) = self.downcast::<CSSMathNegate>() {
+            return format!(
+                "{}-{}{}",
+                open,
+                negate.operand().serialize(true, false),
+                close
+            );
+        }
+
+        // Step 6.
+        let invert = self.downcast::<CSSMathInvert>().unwrap();
+        format!(
+            "{}1 / {}{}",
+            open,
+            invert.operand().serialize(true, false),
+            close
+        )
+    }
+
+    /// Whether two math values have the same operator and equal operands.
+    pub fn equals(&self, other: &CSSMathValue) -> bool {
+        if self.Operator() != other.Operator() {
+            return false;
+        }
+        if let (Some(first), Some(second)) = (
+            self.downcast::<CSSMathNegate>(),
+            other.downcast::<CSSMathNegate>(),
+        ) {
+            return first.operand().equals(&second.operand());
+        }
+        if let (Some(first), Some(second)) = (
+            self.downcast::<CSSMathInvert>(),
+            other.downcast::<CSSMathInvert>(),
+        ) {
+            return first.operand().equals(&second.operand());
+        }
+        let (first, second) = (self.math_values(), other.math_values());
+        first.len() == second.len() &&
+            first
+                .iter()
+                .zip(second.iter())
+                .all(|(first, second)| first.equals(second))
+    }
+}
+
+/// Serializes the operands of a sum or a product, writing the negated terms of a sum as
+/// subtractions and the inverted factors of a product as divisions.
+fn serialize_operands(values: Vec<DomRoot<CSSNumericValue>>, sum: bool) -> String {
+    let (separator, inverse_separator) = if sum { (" + ", " - ") } else { (" * ", " / ") };
+    let mut result = String::new();
+    for (index, value) in values.iter().enumerate() {
+        let inverse = if sum {
+            value
+                .downcast::<CSSMathNegate>()
+                .map(|negate| negate.operand())
+        } else {
+            value
+                .downcast::<CSSMathInvert>()
+                .map(|invert| invert.operand())
+        };
+        match inverse {
+            Some(ref inverse) if index > 0 => {
+                result.push_str(inverse_separator);
+                result.push_str(&inverse.serialize(true, false));
+            },
+            _ => {
+                if index > 0 {
+                    result.push_str(separator);
+                }
+                result.push_str(&value.serialize(true, false));
+            },
+        }
+    }
+    result
+}
+
+impl MathValueWithValues for CSSMathValue {
+    /// The operands of the sums, products, minimums and maximums.
+    fn math_values(&self) -> Vec<DomRoot<CSSNumericValue>> {
+        if let Some(sum) = self.downcast::<CSSMathSum>() {
+            sum.math_values()
+        } else if let Some(product) = self.downcast::<CSSMathProduct>() {
+            product.math_values()
+        } else if let Some(min) = self.downcast::<CSSMathMin>() {
+            min.math_values()
+        } else if let Some(max) = self.downcast::<CSSMathMax>() {
+            max.math_values()
+        } else {
+            vec![]
+        }
+    }
+}
+
+impl CSSMathValueMethods for CSSMathValue {
+    /// <https://drafts.css-houdini.org/css-typed-om-1/#dom-cssmathvalue-operator>
+    fn Operator(&self) -> CSSMathOperator {
+        if self.is::<CSSMathSum>() {
+            CSSMathOperator::Sum
+        } else if self.is::<CSSMathProduct>() {
+            CSSMathOperator::Product
+        } else if self.is::<CSSMathNegate>() {
+            CSSMathOperator::Negate
+        } else if self.is::<CSSMathInvert>() {
+            CSSMathOperator::Invert
+        } else if self.is::<CSSMathMin>() {
+            CSSMathOperator::Min
+        } else {
+            CSSMathOperator::Max
+        }
+    }
+}
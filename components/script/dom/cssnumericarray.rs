@@ -0,0 +1,55 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::CSSNumericArrayBinding::CSSNumericArrayMethods;
+use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::cssnumericvalue::CSSNumericValue;
+use crate::dom::globalscope::GlobalScope;
+use dom_struct::dom_struct;
+
+/// <https://drafts.css-houdini.org/css-typed-om-1/#cssnumericarray>
+#[dom_struct]
+pub struct CSSNumericArray {
+    reflector_: Reflector,
+    values: Vec<Dom<CSSNumericValue>>,
+}
+
+impl CSSNumericArray {
+    #[allow(unrooted_must_root)]
+    fn new_inherited(values: &[DomRoot<CSSNumericValue>]) -> CSSNumericArray {
+        CSSNumericArray {
+            reflector_: Reflector::new(),
+            values: values.iter().map(|value| Dom::from_ref(&**value)).collect(),
+        }
+    }
+
+    pub fn new(
+        global: &GlobalScope,
+        values: &[DomRoot<CSSNumericValue>],
+    ) -> DomRoot<CSSNumericArray> {
+        reflect_dom_object(Box::new(CSSNumericArray::new_inherited(values)), global)
+    }
+
+    pub fn values(&self) -> Vec<DomRoot<CSSNumericValue>> {
+        self.values
+            .iter()
+            .map(|value| DomRoot::from_ref(&**value))
+            .collect()
+    }
+}
+
+impl CSSNumericArrayMethods for CSSNumericArray {
+    /// <https://drafts.css-houdini.org/css-typed-om-1/#dom-cssnumericarray-length>
+    fn Length(&self) -> u32 {
+        self.values.len() as u32
+    }
+
+    /// <https://drafts.css-houdini.org/css-typed-om-1/#cssnumericarray>
+    fn IndexedGetter(&self, index: u32) -> Option<DomRoot<CSSNumericValue>> {
+        self.values
+            .get(index as usize)
+            .map(|value| DomRoot::from_ref(&**value))
+    }
+}
@@ -0,0 +1,658 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::CSSNumericValueBinding::{
+    CSSNumericBaseType, CSSNumericType, CSSNumericValueMethods,
+};
+use crate::dom::bindings::codegen::UnionTypes::DoubleOrCSSNumericValue as CSSNumberish;
+use crate::dom::bindings::conversions::DerivedFrom;
+use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::reflector::DomObject;
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::USVString;
+use crate::dom::cssmathinvert::CSSMathInvert;
+use crate::dom::cssmathmax::CSSMathMax;
+use crate::dom::cssmathmin::CSSMathMin;
+use crate::dom::cssmathnegate::CSSMathNegate;
+use crate::dom::cssmathproduct::CSSMathProduct;
+use crate::dom::cssmathsum::CSSMathSum;
+use crate::dom::cssmathvalue::CSSMathValue;
+use crate::dom::cssstylevalue::CSSStyleValue;
+use crate::dom::cssunitvalue::CSSUnitValue;
+use crate::dom::globalscope::GlobalScope;
+use cssparser::{ParseError, Parser, ParserInput, Token};
+use dom_struct::dom_struct;
+use std::f64::consts::PI;
+
+/// The base types of the numeric values, in the order of `NumericType::exponents`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum BaseType {
+    Length,
+    Angle,
+    Time,
+    Frequency,
+    Resolution,
+    Flex,
+    Percent,
+}
+
+const BASE_TYPES: [BaseType; 7] = [
+    BaseType::Length,
+    BaseType::Angle,
+    BaseType::Time,
+    BaseType::Frequency,
+    BaseType::Resolution,
+    BaseType::Flex,
+    BaseType::Percent,
+];
+
+/// Returns the base type of a unit, `None` for numbers, the canonical unit of the base type,
+/// and the factor which converts the unit to the canonical one, if they are compatible.
+/// <https://drafts.css-houdini.org/css-typed-om-1/#create-a-type>
+pub fn unit_info(unit: &str) -> Option<(Option<BaseType>, &'static str, Option<f64>)> {
+    let length = Some(BaseType::Length);
+    let info = match unit {
+        "number" => (None, "number", Some(1.)),
+        "percent" => (Some(BaseType::Percent), "percent", Some(1.)),
+        "px" => (length, "px", Some(1.)),
+        "cm" => (length, "px", Some(96. / 2.54)),
+        "mm" => (length, "px", Some(96. / 25.4)),
+        "q" => (length, "px", Some(96. / 101.6)),
+        "in" => (length, "px", Some(96.)),
+        "pt" => (length, "px", Some(96. / 72.)),
+        "pc" => (length, "px", Some(16.)),
+        "em" | "ex" | "ch" | "ic" | "rem" | "lh" | "rlh" | "vw" | "vh" | "vi" | "vb" | "vmin" |
+        "vmax" => (length, "px", None),
+        "deg" => (Some(BaseType::Angle), "deg", Some(1.)),
+        "grad" => (Some(BaseType::Angle), "deg", Some(0.9)),
+        "rad" => (Some(BaseType::Angle), "deg", Some(180. / PI)),
+        "turn" => (Some(BaseType::Angle), "deg", Some(360.)),
+        "s" => (Some(BaseType::Time), "s", Some(1.)),
+        "ms" => (Some(BaseType::Time), "s", Some(0.001)),
+        "hz" => (Some(BaseType::Frequency), "hz", Some(1.)),
+        "khz" => (Some(BaseType::Frequency), "hz", Some(1000.)),
+        "dppx" | "x" => (Some(BaseType::Resolution), "dppx", Some(1.)),
+        "dpi" => (Some(BaseType::Resolution), "dppx", Some(1. / 96.)),
+        "dpcm" => (Some(BaseType::Resolution), "dppx", Some(2.54 / 96.)),
+        "fr" => (Some(BaseType::Flex), "fr", Some(1.)),
+        _ => return None,
+    };
+    Some(info)
+}
+
+/// The type of a numeric value, which is the exponent of each of its base types.
+/// <https://drafts.css-houdini.org/css-typed-om-1/#cssnumericvalue-type>
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct NumericType {
+    exponents: [i32; 7],
+    percent_hint: Option<BaseType>,
+}
+
+unsafe_no_jsmanaged_fields!(NumericType);
+
+impl NumericType {
+    /// <https://drafts.css-houdini.org/css-typed-om-1/#create-a-type>
+    pub fn for_unit(unit: &str) -> Option<NumericType> {
+        let (base_type, _, _) = unit_info(unit)?;
+        let mut exponents = [0; 7];
+        if let Some(base_type) = base_type {
+            exponents[base_type as usize] = 1;
+        }
+        Some(NumericType {
+            exponents,
+            percent_hint: None,
+        })
+    }
+
+    /// <https://drafts.css-houdini.org/css-typed-om-1/#apply-the-percent-hint>
+    fn apply_percent_hint(&mut self, hint: BaseType) {
+        let percent = BaseType::Percent as usize;
+        self.exponents[hint as usize] += self.exponents[percent];
+        self.exponents[percent] = 0;
+        self.percent_hint = Some(hint);
+    }
+
+    fn unify_percent_hints(mut self, mut other: NumericType) -> Option<(Self, Self)> {
+        match (self.percent_hint, other.percent_hint) {
+            (Some(hint), Some(other_hint)) if hint != other_hint => return None,
+            (Some(hint), None) => other.apply_percent_hint(hint),
+            (None, Some(hint)) => self.apply_percent_hint(hint),
+            _ => {},
+        }
+        Some((self, other))
+    }
+
+    /// <https://drafts.css-houdini.org/css-typed-om-1/#cssnumericvalue-add-two-types>
+    pub fn add(self, other: NumericType) -> Option<NumericType> {
+        let (first, second) = self.unify_percent_hints(other)?;
+        if first.exponents == second.exponents {
+            return Some(first);
+        }
+
+        // The percent base type is the last one.
+        let percent = BaseType::Percent as usize;
+        let has_percent = first.exponents[percent] != 0 || second.exponents[percent] != 0;
+        let has_other = |numeric_type: &NumericType| {
+            numeric_type.exponents[..percent]
+                .iter()
+                .any(|exponent| *exponent != 0)
+        };
+        if !has_percent || !(has_other(&first) || has_other(&second)) {
+            return None;
+        }
+        BASE_TYPES[..percent].iter().find_map(|hint| {
+            let (mut first, mut second) = (first, second);
+            first.apply_percent_hint(*hint);
+            second.apply_percent_hint(*hint);
+            if first.exponents == second.exponents {
+                Some(first)
+            } else {
+                None
+            }
+        })
+    }
+
+    /// <https://drafts.css-houdini.org/css-typed-om-1/#cssnumericvalue-multiply-two-types>
+    pub fn multiply(self, other: NumericType) -> Option<NumericType> {
+        let (mut first, second) = self.unify_percent_hints(other)?;
+        for (exponent, other_exponent) in first.exponents.iter_mut().zip(second.exponents.iter()) {
+            *exponent += *other_exponent;
+        }
+        Some(first)
+    }
+
+    /// <https://drafts.css-houdini.org/css-typed-om-1/#cssmathinvert-type>
+    pub fn invert(mut self) -> NumericType {
+        for exponent in self.exponents.iter_mut() {
+            *exponent = -*exponent;
+        }
+        self
+    }
+}
+
+/// <https://drafts.css-houdini.org/css-typed-om-1/#cssnumericvalue>
+#[dom_struct]
+pub struct CSSNumericValue {
+    style_value: CSSStyleValue,
+    #[ignore_malloc_size_of = "Has no heap data"]
+    numeric_type: NumericType,
+}
+
+impl CSSNumericValue {
+    pub fn new_inherited(numeric_type: NumericType) -> CSSNumericValue {
+        CSSNumericValue {
+            style_value: CSSStyleValue::new_inherited(String::new()),
+            numeric_type,
+        }
+    }
+
+    pub fn numeric_type(&self) -> NumericType {
+        self.numeric_type
+    }
+
+    /// <https://drafts.css-houdini.org/css-typed-om-1/#serialize-a-cssnumericvalue>
+    pub fn serialize(&self, nested: bool, paren_less: bool) -> String {
+        match self.downcast::<CSSUnitValue>() {
+            Some(unit_value) => unit_value.serialize(),
+            None => self
+                .downcast::<CSSMathValue>()
+                .unwrap()
+                .serialize(nested, paren_less),
+        }
+    }
+
+    /// Whether two numeric values are the same value.
+    /// <https://drafts.css-houdini.org/css-typed-om-1/#equal-numeric-value>
+    pub fn equals(&self, other: &CSSNumericValue) -> bool {
+        if let (Some(first), Some(second)) = (
+            self.downcast::<CSSUnitValue>(),
+            other.downcast::<CSSUnitValue>(),
+        ) {
+            return first.value() == second.value() && first.unit() == second.unit();
+        }
+        match (
+            self.downcast::<CSSMathValue>(),
+            other.downcast::<CSSMathValue>(),
+        ) {
+            (Some(first), Some(second)) => first.equals(second),
+            _ => false,
+        }
+    }
+
+    /// Parses a numeric literal or a math function, for `CSSNumericValue.parse` and the
+    /// reification of declared and computed values.
+    /// <https://drafts.css-houdini.org/css-typed-om-1/#dom-cssnumericvalue-parse>
+    pub fn parse(global: &GlobalScope, css_text: &str) -> Option<DomRoot<CSSNumericValue>> {
+        let mut input = ParserInput::new(css_text);
+        let mut parser = Parser::new(&mut input);
+        parser
+            .parse_entirely(|input| parse_value(global, input))
+            .ok()
+    }
+
+    /// Returns the values to combine with this one, as the first operands of an operation.
+    fn operands<T>(&self, values: Vec<CSSNumberish>) -> Vec<DomRoot<CSSNumericValue>>
+    where
+        T: DerivedFrom<CSSNumericValue> + MathValueWithValues,
+    {
+        let global = self.global();
+        let mut operands = match self.downcast::<T>() {
+            Some(math_value) => math_value.math_values(),
+            None => vec![DomRoot::from_ref(self)],
+        };
+        operands.extend(values.iter().map(|value| rectify_numberish(&global, value)));
+        operands
+    }
+
+    /// <https://drafts.css-houdini.org/css-typed-om-1/#create-a-sum-value>
+    fn sum_value(&self) -> Option<SumValue> {
+        if let Some(unit_value) = self.downcast::<CSSUnitValue>() {
+            let unit = unit_value.unit();
+            let (_, canonical_unit, factor) = unit_info(&unit)?;
+            return Some(match factor {
+                Some(factor) => vec![(unit_value.value() * factor, canonical_unit.to_owned())],
+                None => vec![(unit_value.value(), unit)],
+            });
+        }
+        let math_value = self.downcast::<CSSMathValue>()?;
+        if let Some(sum) = math_value.downcast::<CSSMathSum>() {
+            let mut result: SumValue = vec![];
+            for value in sum.math_values() {
+                for (value, unit) in value.sum_value()? {
+                    match result.iter_mut().find(|item| item.1 == unit) {
+                        Some(item) => item.0 += value,
+                        None => result.push((value, unit)),
+                    }
+                }
+            }
+            return Some(result);
+        }
+        if let Some(product) = math_value.downcast::<CSSMathProduct>() {
+            let mut result: SumValue = vec![(1., "number".to_owned())];
+            for value in product.math_values() {
+                let sum = value.sum_value()?;
+                let mut product = vec![];
+                for (value, unit) in &result {
+                    for (other_value, other_unit) in &sum {
+                        let unit = match (&**unit, &**other_unit) {
+                            ("number", _) => other_unit.clone(),
+                            (_, "number") => unit.clone(),
+                            // Sums of units with exponents other than one aren't supported.
+                            _ => return None,
+                        };
+                        product.push((value * other_value, unit));
+                    }
+                }
+                result = product;
+            }
+            return Some(result);
+        }
+        if let Some(negate) = math_value.downcast::<CSSMathNegate>() {
+            let sum = negate.operand().sum_value()?;
+            return Some(
+                sum.into_iter()
+                    .map(|(value, unit)| (-value, unit))
+                    .collect(),
+            );
+        }
+        if let Some(invert) = math_value.downcast::<CSSMathInvert>() {
+            let sum = invert.operand().sum_value()?;
+            return match &*sum {
+                [(value, unit)] if unit == "number" && *value != 0. => {
+                    Some(vec![(1. / value, unit.clone())])
+                },
+                _ => None,
+            };
+        }
+        None
+    }
+}
+
+/// The terms of a sum of values, with their units.
+type SumValue = Vec<(f64, String)>;
+
+/// The math values which combine a list of values, and which absorb the values of the
+/// operations of the same kind.
+pub trait MathValueWithValues {
+    fn math_values(&self) -> Vec<DomRoot<CSSNumericValue>>;
+}
+
+/// <https://drafts.css-houdini.org/css-typed-om-1/#rectify-a-numberish-value>
+pub fn rectify_numberish(global: &GlobalScope, value: &CSSNumberish) -> DomRoot<CSSNumericValue> {
+    match *value {
+        CSSNumberish::Double(value) => DomRoot::upcast(CSSUnitValue::new(global, *value, "number")),
+        CSSNumberish::CSSNumericValue(ref value) => value.clone(),
+    }
+}
+
+/// Returns the unit shared by all the values if they are all unit values.
+fn common_unit(values: &[DomRoot<CSSNumericValue>]) -> Option<String> {
+    let mut unit = None;
+    for value in values {
+        let value_unit = value.downcast::<CSSUnitValue>()?.unit();
+        match unit {
+            Some(ref unit) if *unit != value_unit => return None,
+            _ => unit = Some(value_unit),
+        }
+    }
+    unit
+}
+
+fn unit_values(values: &[DomRoot<CSSNumericValue>]) -> Option<Vec<(f64, String)>> {
+    values
+        .iter()
+        .map(|value| {
+            let unit_value = value.downcast::<CSSUnitValue>()?;
+            Some((unit_value.value(), unit_value.unit()))
+        })
+        .collect()
+}
+
+/// <https://drafts.css-houdini.org/css-typed-om-1/#dom-cssnumericvalue-add>
+fn sum(
+    global: &GlobalScope,
+    values: Vec<DomRoot<CSSNumericValue>>,
+) -> Fallible<DomRoot<CSSNumericValue>> {
+    if let Some(unit) = common_unit(&values) {
+        let total = unit_values(&values)
+            .unwrap()
+            .iter()
+            .map(|(value, _)| value)
+            .sum();
+        return Ok(DomRoot::upcast(CSSUnitValue::new(global, total, &unit)));
+    }
+    Ok(DomRoot::upcast(CSSMathSum::new(global, values)?))
+}
+
+/// <https://drafts.css-houdini.org/css-typed-om-1/#dom-cssnumericvalue-mul>
+fn product(
+    global: &GlobalScope,
+    values: Vec<DomRoot<CSSNumericValue>>,
+) -> Fallible<DomRoot<CSSNumericValue>> {
+    if let Some(unit_values) = unit_values(&values) {
+        let mut units = unit_values.iter().filter(|(_, unit)| unit != "number");
+        let unit = units.next().map(|(_, unit)| unit.clone());
+        if units.next().is_none() {
+            let total = unit_values.iter().map(|(value, _)| value).product();
+            let unit = unit.unwrap_or_else(|| "number".to_owned());
+            return Ok(DomRoot::upcast(CSSUnitValue::new(global, total, &unit)));
+        }
+    }
+    Ok(DomRoot::upcast(CSSMathProduct::new(global, values)?))
+}
+
+/// <https://drafts.css-houdini.org/css-typed-om-1/#cssnumericvalue-negate>
+fn negate(global: &GlobalScope, value: &CSSNumericValue) -> DomRoot<CSSNumericValue> {
+    if let Some(negate) = value.downcast::<CSSMathNegate>() {
+        return negate.operand();
+    }
+    match value.downcast::<CSSUnitValue>() {
+        Some(unit_value) => DomRoot::upcast(CSSUnitValue::new(
+            global,
+            -unit_value.value(),
+            &unit_value.unit(),
+        )),
+        None => DomRoot::upcast(CSSMathNegate::new(global, value)),
+    }
+}
+
+/// <https://drafts.css-houdini.org/css-typed-om-1/#cssnumericvalue-invert>
+fn invert(global: &GlobalScope, value: &CSSNumericValue) -> Fallible<DomRoot<CSSNumericValue>> {
+    if let Some(invert) = value.downcast::<CSSMathInvert>() {
+        return Ok(invert.operand());
+    }
+    match value.downcast::<CSSUnitValue>() {
+        Some(unit_value) if &*unit_value.unit() == "number" => {
+            if unit_value.value() == 0. {
+                return Err(Error::Range("Can't divide by zero".to_owned()));
+            }
+            Ok(DomRoot::upcast(CSSUnitValue::new(
+                global,
+                1. / unit_value.value(),
+                "number",
+            )))
+        },
+        _ => Ok(DomRoot::upcast(CSSMathInvert::new(global, value))),
+    }
+}
+
+/// <https://drafts.css-houdini.org/css-typed-om-1/#dom-cssnumericvalue-min>
+fn extremum(
+    global: &GlobalScope,
+    values: Vec<DomRoot<CSSNumericValue>>,
+    max: bool,
+) -> Fallible<DomRoot<CSSNumericValue>> {
+    if let Some(unit) = common_unit(&values) {
+        let numbers = unit_values(&values)
+            .unwrap()
+            .into_iter()
+            .map(|(value, _)| value);
+        let extremum = if max {
+            numbers.fold(f64::NEG_INFINITY, f64::max)
+        } else {
+            numbers.fold(f64::INFINITY, f64::min)
+        };
+        return Ok(DomRoot::upcast(CSSUnitValue::new(global, extremum, &unit)));
+    }
+    if max {
+        Ok(DomRoot::upcast(CSSMathMax::new(global, values)?))
+    } else {
+        Ok(DomRoot::upcast(CSSMathMin::new(global, values)?))
+    }
+}
+
+/// Parses a math expression of the `calc()`, `min()` and `max()` functions.
+fn parse_sum<'i, 't>(
+    global: &GlobalScope,
+    input: &mut Parser<'i, 't>,
+) -> Result<DomRoot<CSSNumericValue>, ParseError<'i, ()>> {
+    let mut values = vec![parse_product(global, input)?];
+    loop {
+        let subtract = match input.try_parse(|input| -> Result<_, ParseError<'i, ()>> {
+            let location = input.current_source_location();
+            match *input.next()? {
+                Token::Delim('+') => Ok(false),
+                Token::Delim('-') => Ok(true),
+                _ => Err(location.new_custom_error(())),
+            }
+        }) {
+            Ok(subtract) => subtract,
+            Err(_) => break,
+        };
+        let value = parse_product(global, input)?;
+        values.push(if subtract {
+            negate(global, &value)
+        } else {
+            value
+        });
+    }
+    if values.len() == 1 {
+        return Ok(values.pop().unwrap());
+    }
+    CSSMathSum::new(global, values)
+        .map(DomRoot::upcast)
+        .map_err(|_| input.new_custom_error(()))
+}
+
+fn parse_product<'i, 't>(
+    global: &GlobalScope,
+    input: &mut Parser<'i, 't>,
+) -> Result<DomRoot<CSSNumericValue>, ParseError<'i, ()>> {
+    let mut values = vec![parse_value(global, input)?];
+    loop {
+        let divide = match input.try_parse(|input| -> Result<_, ParseError<'i, ()>> {
+            let location = input.current_source_location();
+            match *input.next()? {
+                Token::Delim('*') => Ok(false),
+                Token::Delim('/') => Ok(true),
+                _ => Err(location.new_custom_error(())),
+            }
+        }) {
+            Ok(divide) => divide,
+            Err(_) => break,
+        };
+        let value = parse_value(global, input)?;
+        values.push(if divide {
+            invert(global, &value).map_err(|_| input.new_custom_error(()))?
+        } else {
+            value
+        });
+    }
+    if values.len() == 1 {
+        return Ok(values.pop().unwrap());
+    }
+    CSSMathProduct::new(global, values)
+        .map(DomRoot::upcast)
+        .map_err(|_| input.new_custom_error(()))
+}
+
+fn parse_value<'i, 't>(
+    global: &GlobalScope,
+    input: &mut Parser<'i, 't>,
+) -> Result<DomRoot<CSSNumericValue>, ParseError<'i, ()>> {
+    let location = input.current_source_location();
+    let (value, unit) = match input.next()?.clone() {
+        Token::Number { value, .. } => (value as f64, "number".to_owned()),
+        Token::Percentage { unit_value, .. } => (unit_value as f64 * 100., "percent".to_owned()),
+        Token::Dimension { value, unit, .. } => {
+            let unit = unit.to_ascii_lowercase();
+            if unit_info(&unit).is_none() {
+                return Err(location.new_custom_error(()));
+            }
+            (value as f64, unit)
+        },
+        Token::ParenthesisBlock => {
+            return input.parse_nested_block(|input| parse_sum(global, input));
+        },
+        Token::Function(ref name) if name.eq_ignore_ascii_case("calc") => {
+            return input.parse_nested_block(|input| parse_sum(global, input));
+        },
+        Token::Function(ref name)
+            if name.eq_ignore_ascii_case("min") || name.eq_ignore_ascii_case("max") =>
+        {
+            let max = name.eq_ignore_ascii_case("max");
+            let values = input.parse_nested_block(|input| {
+                input.parse_comma_separated(|input| parse_sum(global, input))
+            })?;
+            return extremum(global, values, max).map_err(|_| location.new_custom_error(()));
+        },
+        _ => return Err(location.new_custom_error(())),
+    };
+    Ok(DomRoot::upcast(CSSUnitValue::new(global, value, &unit)))
+}
+
+impl CSSNumericValueMethods for CSSNumericValue {
+    /// <https://drafts.css-houdini.org/css-typed-om-1/#dom-cssnumericvalue-add>
+    fn Add(&self, values: Vec<CSSNumberish>) -> Fallible<DomRoot<CSSNumericValue>> {
+        sum(&self.global(), self.operands::<CSSMathSum>(values))
+    }
+
+    /// <https://drafts.css-houdini.org/css-typed-om-1/#dom-cssnumericvalue-sub>
+    fn Sub(&self, values: Vec<CSSNumberish>) -> Fallible<DomRoot<CSSNumericValue>> {
+        let global = self.global();
+        let values = values
+            .iter()
+            .map(|value| {
+                let value = negate(&global, &rectify_numberish(&global, value));
+                CSSNumberish::CSSNumericValue(value)
+            })
+            .collect();
+        self.Add(values)
+    }
+
+    /// <https://drafts.css-houdini.org/css-typed-om-1/#dom-cssnumericvalue-mul>
+    fn Mul(&self, values: Vec<CSSNumberish>) -> Fallible<DomRoot<CSSNumericValue>> {
+        product(&self.global(), self.operands::<CSSMathProduct>(values))
+    }
+
+    /// <https://drafts.css-houdini.org/css-typed-om-1/#dom-cssnumericvalue-div>
+    fn Div(&self, values: Vec<CSSNumberish>) -> Fallible<DomRoot<CSSNumericValue>> {
+        let global = self.global();
+        let values = values
+            .iter()
+            .map(|value| {
+                let value = invert(&global, &rectify_numberish(&global, value))?;
+                Ok(CSSNumberish::CSSNumericValue(value))
+            })
+            .collect::<Fallible<_>>()?;
+        self.Mul(values)
+    }
+
+    /// <https://drafts.css-houdini.org/css-typed-om-1/#dom-cssnumericvalue-min>
+    fn Min(&self, values: Vec<CSSNumberish>) -> Fallible<DomRoot<CSSNumericValue>> {
+        extremum(&self.global(), self.operands::<CSSMathMin>(values), false)
+    }
+
+    /// <https://drafts.css-houdini.org/css-typed-om-1/#dom-cssnumericvalue-max>
+    fn Max(&self, values: Vec<CSSNumberish>) -> Fallible<DomRoot<CSSNumericValue>> {
+        extremum(&self.global(), self.operands::<CSSMathMax>(values), true)
+    }
+
+    /// <https://drafts.css-houdini.org/css-typed-om-1/#dom-cssnumericvalue-equals>
+    fn Equals(&self, values: Vec<CSSNumberish>) -> bool {
+        let global = self.global();
+        values
+            .iter()
+            .all(|value| self.equals(&rectify_numberish(&global, value)))
+    }
+
+    /// <https://drafts.css-houdini.org/css-typed-om-1/#dom-cssnumericvalue-to>
+    fn To(&self, unit: USVString) -> Fallible<DomRoot<CSSUnitValue>> {
+        let unit = unit.to_ascii_lowercase();
+        let (_, canonical_unit, factor) = match unit_info(&unit) {
+            Some(info) => info,
+            None => return Err(Error::Type(format!("Unknown unit {}", unit))),
+        };
+        let sum = match self.sum_value() {
+            Some(sum) => sum,
+            None => return Err(Error::Type("The value can't be simplified".to_owned())),
+        };
+        let value = match (&*sum, factor) {
+            ([(value, sum_unit)], _) if *sum_unit == unit => *value,
+            ([(value, sum_unit)], Some(factor)) if sum_unit == canonical_unit => value / factor,
+            _ => {
+                return Err(Error::Type(format!(
+                    "The value can't be converted to {}",
+                    unit
+                )))
+            },
+        };
+        Ok(CSSUnitValue::new(&self.global(), value, &unit))
+    }
+
+    /// <https://drafts.css-houdini.org/css-typed-om-1/#dom-cssnumericvalue-type>
+    fn Type(&self) -> CSSNumericType {
+        let exponent = |base_type: BaseType| match self.numeric_type.exponents[base_type as usize] {
+            0 => None,
+            exponent => Some(exponent),
+        };
+        CSSNumericType {
+            length: exponent(BaseType::Length),
+            angle: exponent(BaseType::Angle),
+            time: exponent(BaseType::Time),
+            frequency: exponent(BaseType::Frequency),
+            resolution: exponent(BaseType::Resolution),
+            flex: exponent(BaseType::Flex),
+            percent: exponent(BaseType::Percent),
+            percentHint: self.numeric_type.percent_hint.map(|hint| match hint {
+                BaseType::Length => CSSNumericBaseType::Length,
+                BaseType::Angle => CSSNumericBaseType::Angle,
+                BaseType::Time => CSSNumericBaseType::Time,
+                BaseType::Frequency => CSSNumericBaseType::Frequency,
+                BaseType::Resolution => CSSNumericBaseType::Resolution,
+                BaseType::Flex => CSSNumericBaseType::Flex,
+                BaseType::Percent => CSSNumericBaseType::Percent,
+            }),
+        }
+    }
+}
+
+#[allow(non_snake_case)]
+impl CSSNumericValue {
+    /// <https://drafts.css-houdini.org/css-typed-om-1/#dom-cssnumericvalue-parse>
+    pub fn Parse(global: &GlobalScope, css_text: USVString) -> Fallible<DomRoot<CSSNumericValue>> {
+        CSSNumericValue::parse(global, &css_text).ok_or(Error::Syntax)
+    }
+}
@@ -17,6 +17,7 @@ use crate::stylesheet_loader::StylesheetLoader;
 use dom_struct::dom_struct;
 use servo_arc::Arc;
 use style::shared_lock::Locked;
+use style::stylesheets::StylesheetLoader as StyleStylesheetLoader;
 use style::stylesheets::{CssRules, CssRulesHelpers, KeyframesRule, RulesMutateError};
 
 #[allow(unsafe_code)]
@@ -102,12 +103,11 @@ impl CSSRuleList {
         let index = idx as usize;
 
         let parent_stylesheet = self.parent_stylesheet.style_stylesheet();
-        let owner = self
+        // Constructed stylesheets have no owner, and can't load `@import` rules.
+        let loader = self
             .parent_stylesheet
             .get_owner()
-            .downcast::<HTMLElement>()
-            .unwrap();
-        let loader = StylesheetLoader::for_element(owner);
+            .map(|owner| StylesheetLoader::for_element(owner.downcast::<HTMLElement>().unwrap()));
         let new_rule = css_rules.with_raw_offset_arc(|arc| {
             arc.insert_rule(
                 &parent_stylesheet.shared_lock,
@@ -115,11 +115,14 @@ impl CSSRuleList {
                 &parent_stylesheet.contents,
                 index,
                 nested,
-                Some(&loader),
+                loader
+                    .as_ref()
+                    .map(|loader| loader as &dyn StyleStylesheetLoader),
             )
         })?;
 
         let parent_stylesheet = &*self.parent_stylesheet;
+        parent_stylesheet.rule_changed(&new_rule);
         let dom_rule = CSSRule::new_specific(&window, parent_stylesheet, new_rule);
         self.dom_rules
             .borrow_mut()
@@ -134,10 +137,19 @@ impl CSSRuleList {
 
         match self.rules {
             RulesSource::Rules(ref css_rules) => {
-                css_rules.write_with(&mut guard).remove_rule(index)?;
+                let removed_rule = {
+                    let css_rules = css_rules.write_with(&mut guard);
+                    let rule = css_rules.0.get(index).cloned();
+                    css_rules.remove_rule(index)?;
+                    rule
+                };
+                drop(guard);
                 let mut dom_rules = self.dom_rules.borrow_mut();
                 dom_rules[index].get().map(|r| r.detach());
                 dom_rules.remove(index);
+                if let Some(rule) = removed_rule {
+                    self.parent_stylesheet.rule_changed(&rule);
+                }
                 Ok(())
             },
             RulesSource::Keyframes(ref kf) => {
@@ -158,6 +170,18 @@ impl CSSRuleList {
         }
     }
 
+    /// Detaches the DOM rules after all the rules of the list were replaced, and makes room
+    /// for the DOM rules of the new ones.
+    pub fn reset(&self) {
+        self.deparent_all();
+        let guard = self.parent_stylesheet.shared_lock().read();
+        let len = match self.rules {
+            RulesSource::Rules(ref rules) => rules.read_with(&guard).0.len(),
+            RulesSource::Keyframes(ref rules) => rules.read_with(&guard).keyframes.len(),
+        };
+        *self.dom_rules.borrow_mut() = (0..len).map(|_| MutNullableDom::new(None)).collect();
+    }
+
     pub fn item(&self, idx: u32) -> Option<DomRoot<CSSRule>> {
         self.dom_rules.borrow().get(idx as usize).map(|rule| {
             rule.or_init(|| {
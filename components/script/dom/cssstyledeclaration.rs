@@ -10,8 +10,9 @@ use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
 use crate::dom::bindings::root::{Dom, DomRoot};
 use crate::dom::bindings::str::DOMString;
 use crate::dom::cssrule::CSSRule;
+use crate::dom::cssstylerule::CSSStyleRule;
 use crate::dom::element::Element;
-use crate::dom::node::{document_from_node, stylesheets_owner_from_node, window_from_node, Node};
+use crate::dom::node::{document_from_node, window_from_node, Node};
 use crate::dom::window::Window;
 use dom_struct::dom_struct;
 use servo_arc::Arc;
@@ -25,6 +26,7 @@ use style::properties::{
 };
 use style::selector_parser::PseudoElement;
 use style::shared_lock::Locked;
+use style::stylesheets::CssRule as StyleCssRule;
 use style_traits::ParsingMode;
 
 // http://dev.w3.org/csswg/cssom/#the-cssstyledeclaration-interface
@@ -111,10 +113,14 @@ impl CSSStyleOwner {
                     f(&mut *pdb.write_with(&mut guard), &mut changed)
                 };
                 if changed {
-                    // If this is changed, see also
-                    // CSSStyleRule::SetSelectorText, which does the same thing.
-                    stylesheets_owner_from_node(rule.parent_stylesheet().owner().upcast::<Node>())
-                        .invalidate_stylesheets();
+                    // Only the elements matching the selectors of a style rule are affected
+                    // by its declarations.
+                    match rule.downcast::<CSSStyleRule>() {
+                        Some(style_rule) => rule
+                            .parent_stylesheet()
+                            .rule_changed(&StyleCssRule::Style(style_rule.style_rule().clone())),
+                        None => rule.parent_stylesheet().invalidate(),
+                    }
                 }
                 result
             },
@@ -252,6 +258,24 @@ impl CSSStyleDeclaration {
         }
     }
 
+    /// The names of the properties of the declaration block, or of every longhand for the
+    /// computed values.
+    pub fn property_names(&self) -> Vec<DOMString> {
+        if self.readonly {
+            return ShorthandId::All
+                .longhands()
+                .filter(|id| PropertyId::Longhand(*id).enabled_for_all_content())
+                .map(|id| DOMString::from(id.name()))
+                .collect();
+        }
+        self.owner.with_block(|pdb| {
+            pdb.declarations()
+                .iter()
+                .map(|declaration| DOMString::from(&*declaration.id().name()))
+                .collect()
+        })
+    }
+
     fn get_property_value(&self, id: PropertyId) -> DOMString {
         if self.readonly {
             // Readonly style declarations are used for getComputedStyle.
@@ -10,7 +10,6 @@ use crate::dom::bindings::str::DOMString;
 use crate::dom::cssrule::{CSSRule, SpecificCSSRule};
 use crate::dom::cssstyledeclaration::{CSSModificationAccess, CSSStyleDeclaration, CSSStyleOwner};
 use crate::dom::cssstylesheet::CSSStyleSheet;
-use crate::dom::node::{stylesheets_owner_from_node, Node};
 use crate::dom::window::Window;
 use cssparser::ToCss;
 use cssparser::{Parser as CssParser, ParserInput as CssParserInput};
@@ -53,6 +52,10 @@ impl CSSStyleRule {
             window,
         )
     }
+
+    pub fn style_rule(&self) -> &Arc<Locked<StyleRule>> {
+        &self.stylerule
+    }
 }
 
 impl SpecificCSSRule for CSSStyleRule {
@@ -117,8 +120,10 @@ impl CSSStyleRuleMethods for CSSStyleRule {
             let mut guard = self.cssrule.shared_lock().write();
             let stylerule = self.stylerule.write_with(&mut guard);
             mem::swap(&mut stylerule.selectors, &mut s);
-            stylesheets_owner_from_node(self.cssrule.parent_stylesheet().owner().upcast::<Node>())
-                .invalidate_stylesheets();
+            drop(guard);
+            // The elements matching the old selectors are affected too, so this can't be
+            // narrowed down to the rule.
+            self.cssrule.parent_stylesheet().invalidate();
         }
     }
 }
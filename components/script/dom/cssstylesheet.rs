@@ -2,47 +2,75 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::CSSStyleSheetBinding::CSSStyleSheetInit;
 use crate::dom::bindings::codegen::Bindings::CSSStyleSheetBinding::CSSStyleSheetMethods;
+use crate::dom::bindings::codegen::Bindings::MediaListBinding::MediaListMethods;
+use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
+use crate::dom::bindings::codegen::UnionTypes::MediaListOrString;
 use crate::dom::bindings::error::{Error, ErrorResult, Fallible};
 use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::refcounted::{Trusted, TrustedPromise};
 use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
 use crate::dom::bindings::root::{Dom, DomRoot, MutNullableDom};
-use crate::dom::bindings::str::DOMString;
+use crate::dom::bindings::str::{DOMString, USVString};
 use crate::dom::cssrulelist::{CSSRuleList, RulesSource};
+use crate::dom::document::Document;
 use crate::dom::element::Element;
 use crate::dom::node::{stylesheets_owner_from_node, Node};
+use crate::dom::promise::Promise;
 use crate::dom::stylesheet::StyleSheet;
+use crate::dom::stylesheetlist::StyleSheetListOwner;
 use crate::dom::window::Window;
+use crate::realms::InRealm;
+use crate::task_source::TaskSource;
+use cssparser::{Parser as CssParser, ParserInput};
 use dom_struct::dom_struct;
 use servo_arc::Arc;
 use std::cell::Cell;
+use std::rc::Rc;
+use style::media_queries::MediaList as StyleMediaList;
+use style::parser::ParserContext;
 use style::shared_lock::SharedRwLock;
-use style::stylesheets::Stylesheet as StyleStyleSheet;
+use style::stylesheets::{CssRule, CssRuleType, Origin, Stylesheet as StyleStyleSheet};
+use style_traits::ParsingMode;
 
 #[dom_struct]
 pub struct CSSStyleSheet {
     stylesheet: StyleSheet,
-    owner: Dom<Element>,
+    /// The element whose stylesheet this is, or `None` for a constructed stylesheet.
+    owner: Option<Dom<Element>>,
     rulelist: MutNullableDom<CSSRuleList>,
     #[ignore_malloc_size_of = "Arc"]
     style_stylesheet: Arc<StyleStyleSheet>,
     origin_clean: Cell<bool>,
+    /// <https://drafts.csswg.org/cssom/#concept-css-style-sheet-constructor-document>
+    constructor_document: Option<Dom<Document>>,
+    /// <https://drafts.csswg.org/cssom/#concept-css-style-sheet-disallow-modification-flag>
+    disallow_modification: Cell<bool>,
+    /// The documents and shadow roots whose `adoptedStyleSheets` contain this constructed
+    /// stylesheet, once per occurrence.
+    adopters: DomRefCell<Vec<StyleSheetListOwner>>,
 }
 
 impl CSSStyleSheet {
     fn new_inherited(
-        owner: &Element,
+        owner: Option<&Element>,
         type_: DOMString,
         href: Option<DOMString>,
         title: Option<DOMString>,
         stylesheet: Arc<StyleStyleSheet>,
+        constructor_document: Option<&Document>,
     ) -> CSSStyleSheet {
         CSSStyleSheet {
             stylesheet: StyleSheet::new_inherited(type_, href, title),
-            owner: Dom::from_ref(owner),
+            owner: owner.map(Dom::from_ref),
             rulelist: MutNullableDom::new(None),
             style_stylesheet: stylesheet,
             origin_clean: Cell::new(true),
+            constructor_document: constructor_document.map(Dom::from_ref),
+            disallow_modification: Cell::new(false),
+            adopters: DomRefCell::new(Vec::new()),
         }
     }
 
@@ -57,14 +85,80 @@ impl CSSStyleSheet {
     ) -> DomRoot<CSSStyleSheet> {
         reflect_dom_object(
             Box::new(CSSStyleSheet::new_inherited(
-                owner, type_, href, title, stylesheet,
+                Some(owner),
+                type_,
+                href,
+                title,
+                stylesheet,
+                None,
             )),
             window,
         )
     }
 
-    pub fn owner(&self) -> DomRoot<Element> {
-        DomRoot::from_ref(&*self.owner)
+    // https://drafts.csswg.org/cssom/#dom-cssstylesheet-cssstylesheet
+    #[allow(non_snake_case)]
+    pub fn Constructor(
+        window: &Window,
+        options: &CSSStyleSheetInit,
+    ) -> Fallible<DomRoot<CSSStyleSheet>> {
+        let document = window.Document();
+
+        // Step 2.
+        let url = match options.baseURL {
+            Some(ref base_url) => document
+                .base_url()
+                .join(base_url)
+                .map_err(|_| Error::NotAllowed)?,
+            None => document.base_url(),
+        };
+
+        // Step 3.
+        let media = match options.media {
+            MediaListOrString::MediaList(ref media) => String::from(media.MediaText()),
+            MediaListOrString::String(ref media) => String::from(media.clone()),
+        };
+        let css_error_reporter = window.css_error_reporter();
+        let context = ParserContext::new_for_cssom(
+            &url,
+            Some(CssRuleType::Media),
+            ParsingMode::DEFAULT,
+            document.quirks_mode(),
+            css_error_reporter,
+            None,
+        );
+        let shared_lock = document.style_shared_lock().clone();
+        let mut input = ParserInput::new(&media);
+        let media = StyleMediaList::parse(&context, &mut CssParser::new(&mut input));
+        let media = Arc::new(shared_lock.wrap(media));
+
+        // Step 1.
+        let stylesheet = StyleStyleSheet::from_str(
+            "",
+            url.clone(),
+            Origin::Author,
+            media,
+            shared_lock,
+            None,
+            css_error_reporter,
+            document.quirks_mode(),
+            0,
+        );
+
+        // Step 4.
+        stylesheet.set_disabled(options.disabled);
+
+        Ok(reflect_dom_object(
+            Box::new(CSSStyleSheet::new_inherited(
+                None,
+                "text/css".into(),
+                Some(DOMString::from(url.into_string())),
+                None,
+                Arc::new(stylesheet),
+                Some(&document),
+            )),
+            window,
+        ))
     }
 
     fn rulelist(&self) -> DomRoot<CSSRuleList> {
@@ -78,13 +172,14 @@ impl CSSStyleSheet {
         self.style_stylesheet.disabled()
     }
 
-    pub fn get_owner(&self) -> &Element {
-        &*self.owner
+    /// The element whose stylesheet this is, or `None` for a constructed stylesheet.
+    pub fn get_owner(&self) -> Option<&Element> {
+        self.owner.as_deref()
     }
 
     pub fn set_disabled(&self, disabled: bool) {
         if self.style_stylesheet.set_disabled(disabled) {
-            stylesheets_owner_from_node(self.owner().upcast::<Node>()).invalidate_stylesheets();
+            self.invalidate();
         }
     }
 
@@ -92,13 +187,90 @@ impl CSSStyleSheet {
         &self.style_stylesheet.shared_lock
     }
 
-    pub fn style_stylesheet(&self) -> &StyleStyleSheet {
+    pub fn style_stylesheet(&self) -> &Arc<StyleStyleSheet> {
         &self.style_stylesheet
     }
 
     pub fn set_origin_clean(&self, origin_clean: bool) {
         self.origin_clean.set(origin_clean);
     }
+
+    /// <https://drafts.csswg.org/cssom/#concept-css-style-sheet-constructed-flag>
+    pub fn is_constructed(&self) -> bool {
+        self.constructor_document.is_some()
+    }
+
+    /// Whether this is a stylesheet constructed in `document`, which it can adopt.
+    pub fn is_constructed_in(&self, document: &Document) -> bool {
+        self.constructor_document
+            .as_ref()
+            .map_or(false, |constructor_document| {
+                *constructor_document == document
+            })
+    }
+
+    /// Notes that a document or shadow root adopted this constructed stylesheet.
+    pub fn add_adopter(&self, adopter: StyleSheetListOwner) {
+        debug_assert!(self.is_constructed());
+        self.adopters.borrow_mut().push(adopter);
+    }
+
+    /// Notes that a document or shadow root no longer adopts this constructed stylesheet.
+    pub fn remove_adopter(&self, adopter: &StyleSheetListOwner) {
+        let mut adopters = self.adopters.borrow_mut();
+        if let Some(index) = adopters.iter().position(|owner| owner == adopter) {
+            adopters.remove(index);
+        }
+    }
+
+    /// Runs `f` for the document or shadow root of the owner element, or for the documents
+    /// and shadow roots which adopted this constructed stylesheet.
+    #[allow(unrooted_must_root)]
+    fn for_each_stylesheets_owner<F: FnMut(&StyleSheetListOwner)>(&self, mut f: F) {
+        match self.owner {
+            Some(ref owner) => f(&stylesheets_owner_from_node(owner.upcast::<Node>())),
+            None => {
+                for adopter in self.adopters.borrow().iter() {
+                    f(adopter);
+                }
+            },
+        }
+    }
+
+    /// Restyles everything this stylesheet applies to, after a change which can't be narrowed
+    /// down to a rule.
+    pub fn invalidate(&self) {
+        self.for_each_stylesheets_owner(|owner| owner.invalidate_stylesheets());
+    }
+
+    /// Notes that `rule` was inserted into or removed from this stylesheet, or that its
+    /// declarations changed, so that only the elements it may apply to are restyled.
+    pub fn rule_changed(&self, rule: &CssRule) {
+        self.for_each_stylesheets_owner(|owner| {
+            owner.stylesheet_rule_changed(&self.style_stylesheet, rule)
+        });
+    }
+
+    /// <https://drafts.csswg.org/cssom/#synchronously-replace-the-rules-of-a-cssstylesheet>
+    pub fn replace_rules(&self, text: &str) {
+        let window = self.global();
+        let window = window.as_window();
+        let url = self.style_stylesheet.contents.url_data.read().clone();
+
+        // Without a stylesheet loader, the parser drops the `@import` rules.
+        StyleStyleSheet::update_from_str(
+            &self.style_stylesheet,
+            text,
+            url,
+            None,
+            window.css_error_reporter(),
+            0,
+        );
+        if let Some(rulelist) = self.rulelist.get() {
+            rulelist.reset();
+        }
+        self.invalidate();
+    }
 }
 
 impl CSSStyleSheetMethods for CSSStyleSheet {
@@ -115,6 +287,11 @@ impl CSSStyleSheetMethods for CSSStyleSheet {
         if !self.origin_clean.get() {
             return Err(Error::Security);
         }
+        if self.disallow_modification.get() {
+            return Err(Error::NotAllowed);
+        }
+        // A constructed stylesheet has no loader, so inserting an `@import` rule into it
+        // throws a SyntaxError.
         self.rulelist()
             .insert_rule(&rule, index, /* nested */ false)
     }
@@ -124,6 +301,53 @@ impl CSSStyleSheetMethods for CSSStyleSheet {
         if !self.origin_clean.get() {
             return Err(Error::Security);
         }
+        if self.disallow_modification.get() {
+            return Err(Error::NotAllowed);
+        }
         self.rulelist().remove_rule(index)
     }
+
+    // https://drafts.csswg.org/cssom/#dom-cssstylesheet-replace
+    fn Replace(&self, text: USVString, comp: InRealm) -> Rc<Promise> {
+        // Step 1.
+        let promise = Promise::new_in_current_realm(&self.global(), comp);
+
+        // Steps 2-3.
+        if !self.is_constructed() || self.disallow_modification.get() {
+            promise.reject_error(Error::NotAllowed);
+            return promise;
+        }
+
+        // Step 4.
+        self.disallow_modification.set(true);
+
+        // Step 5.
+        let this = Trusted::new(self);
+        let trusted_promise = TrustedPromise::new(promise.clone());
+        let window = self.global();
+        let window = window.as_window();
+        let _ = window.task_manager().dom_manipulation_task_source().queue(
+            task!(replace_stylesheet_rules: move || {
+                let this = this.root();
+                this.replace_rules(&text);
+                this.disallow_modification.set(false);
+                trusted_promise.root().resolve_native(&this);
+            }),
+            window.upcast(),
+        );
+
+        promise
+    }
+
+    // https://drafts.csswg.org/cssom/#dom-cssstylesheet-replacesync
+    fn ReplaceSync(&self, text: USVString) -> ErrorResult {
+        // Step 1.
+        if !self.is_constructed() || self.disallow_modification.get() {
+            return Err(Error::NotAllowed);
+        }
+
+        // Steps 2-4.
+        self.replace_rules(&text);
+        Ok(())
+    }
 }
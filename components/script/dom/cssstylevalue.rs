@@ -3,15 +3,24 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use crate::dom::bindings::codegen::Bindings::CSSStyleValueBinding::CSSStyleValueMethods;
+use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::reflector::reflect_dom_object;
 use crate::dom::bindings::reflector::Reflector;
 use crate::dom::bindings::root::DomRoot;
-use crate::dom::bindings::str::DOMString;
+use crate::dom::bindings::str::{DOMString, USVString};
+use crate::dom::css::CSS;
+use crate::dom::csskeywordvalue::CSSKeywordValue;
+use crate::dom::cssnumericvalue::CSSNumericValue;
 use crate::dom::globalscope::GlobalScope;
-use cssparser::Parser;
-use cssparser::ParserInput;
+use crate::script_runtime::JSContext;
+use cssparser::{ParseError, Parser, ParserInput};
 use dom_struct::dom_struct;
+use js::rust::HandleObject;
+use servo_config::pref;
 use servo_url::ServoUrl;
+use style::custom_properties;
+use style::properties::PropertyId;
 
 #[dom_struct]
 pub struct CSSStyleValue {
@@ -20,7 +29,7 @@ pub struct CSSStyleValue {
 }
 
 impl CSSStyleValue {
-    fn new_inherited(value: String) -> CSSStyleValue {
+    pub fn new_inherited(value: String) -> CSSStyleValue {
         CSSStyleValue {
             reflector: Reflector::new(),
             value: value,
@@ -30,11 +39,76 @@ impl CSSStyleValue {
     pub fn new(global: &GlobalScope, value: String) -> DomRoot<CSSStyleValue> {
         reflect_dom_object(Box::new(CSSStyleValue::new_inherited(value)), global)
     }
+
+    /// The style values are exposed to the paint worklets, which predate the rest of the typed
+    /// OM, whenever worklets are enabled.
+    pub fn typed_om_enabled(_: JSContext, _: HandleObject) -> bool {
+        pref!(dom.typed_om.enabled) || pref!(dom.worklet.enabled)
+    }
+
+    /// Turns the serialization of a declared or computed value into a style value, which is a
+    /// numeric value or a keyword if possible.
+    /// <https://drafts.css-houdini.org/css-typed-om-1/#reify-as-a-cssstylevalue>
+    pub fn reify(global: &GlobalScope, value: String) -> DomRoot<CSSStyleValue> {
+        if !pref!(dom.typed_om.enabled) {
+            return CSSStyleValue::new(global, value);
+        }
+        if let Some(numeric_value) = CSSNumericValue::parse(global, &value) {
+            return DomRoot::upcast(numeric_value);
+        }
+        let keyword = {
+            let mut input = ParserInput::new(&value);
+            let mut parser = Parser::new(&mut input);
+            parser
+                .parse_entirely(|input| -> Result<_, ParseError<()>> {
+                    Ok(input.expect_ident()?.to_string())
+                })
+                .ok()
+        };
+        match keyword {
+            Some(keyword) => DomRoot::upcast(CSSKeywordValue::new(global, keyword)),
+            None => CSSStyleValue::new(global, value),
+        }
+    }
+
+    /// <https://drafts.css-houdini.org/css-typed-om-1/#dom-cssstylevalue-parse>
+    #[allow(non_snake_case)]
+    pub fn Parse(
+        global: &GlobalScope,
+        property: USVString,
+        css_text: USVString,
+    ) -> Fallible<DomRoot<CSSStyleValue>> {
+        // Step 1.
+        if !is_valid_property(&property) {
+            return Err(Error::Type(format!("Unknown property {}", &*property)));
+        }
+
+        // Steps 2 to 4.
+        let window = global.as_window();
+        if !CSS::Supports(window, property.0.into(), css_text.0.clone().into()) {
+            return Err(Error::Type(format!("Invalid value for {}", &*property)));
+        }
+
+        // Step 5.
+        Ok(CSSStyleValue::reify(global, css_text.0.trim().to_owned()))
+    }
+}
+
+/// Whether a property name is a custom property or a property supported by the style system.
+pub fn is_valid_property(property: &str) -> bool {
+    custom_properties::parse_name(property).is_ok() ||
+        PropertyId::parse_enabled_for_all_content(property).is_ok()
 }
 
 impl CSSStyleValueMethods for CSSStyleValue {
     /// <https://drafts.css-houdini.org/css-typed-om-1/#CSSStyleValue-stringification-behavior>
     fn Stringifier(&self) -> DOMString {
+        if let Some(numeric_value) = self.downcast::<CSSNumericValue>() {
+            return DOMString::from(numeric_value.serialize(false, false));
+        }
+        if let Some(keyword_value) = self.downcast::<CSSKeywordValue>() {
+            return DOMString::from(keyword_value.serialize());
+        }
         DOMString::from(&*self.value)
     }
 }
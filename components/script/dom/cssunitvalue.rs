@@ -0,0 +1,106 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::CSSUnitValueBinding::CSSUnitValueMethods;
+use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::num::Finite;
+use crate::dom::bindings::reflector::reflect_dom_object;
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::USVString;
+use crate::dom::cssnumericvalue::{CSSNumericValue, NumericType};
+use crate::dom::globalscope::GlobalScope;
+use dom_struct::dom_struct;
+use std::cell::Cell;
+
+/// <https://drafts.css-houdini.org/css-typed-om-1/#cssunitvalue>
+#[dom_struct]
+pub struct CSSUnitValue {
+    numeric_value: CSSNumericValue,
+    value: Cell<f64>,
+    unit: String,
+}
+
+impl CSSUnitValue {
+    fn new_inherited(numeric_type: NumericType, value: f64, unit: String) -> CSSUnitValue {
+        CSSUnitValue {
+            numeric_value: CSSNumericValue::new_inherited(numeric_type),
+            value: Cell::new(value),
+            unit,
+        }
+    }
+
+    /// Creates a unit value with a lowercase unit, which must be valid. The results of the
+    /// arithmetic which overflow are clamped, since the value of a unit value is finite.
+    pub fn new(global: &GlobalScope, value: f64, unit: &str) -> DomRoot<CSSUnitValue> {
+        let numeric_type = NumericType::for_unit(unit).expect("Invalid unit");
+        let value = if value.is_nan() {
+            0.
+        } else {
+            value.max(f64::MIN).min(f64::MAX)
+        };
+        reflect_dom_object(
+            Box::new(CSSUnitValue::new_inherited(
+                numeric_type,
+                value,
+                unit.to_owned(),
+            )),
+            global,
+        )
+    }
+
+    /// <https://drafts.css-houdini.org/css-typed-om-1/#dom-cssunitvalue-cssunitvalue>
+    #[allow(non_snake_case)]
+    pub fn Constructor(
+        global: &GlobalScope,
+        value: Finite<f64>,
+        unit: USVString,
+    ) -> Fallible<DomRoot<CSSUnitValue>> {
+        // Step 1.
+        let unit = match &*unit.to_ascii_lowercase() {
+            "%" => "percent".to_owned(),
+            unit => unit.to_owned(),
+        };
+        if NumericType::for_unit(&unit).is_none() {
+            return Err(Error::Type(format!("Unknown unit {}", unit)));
+        }
+
+        // Steps 2 and 3.
+        Ok(CSSUnitValue::new(global, *value, &unit))
+    }
+
+    pub fn value(&self) -> f64 {
+        self.value.get()
+    }
+
+    pub fn unit(&self) -> String {
+        self.unit.clone()
+    }
+
+    /// <https://drafts.css-houdini.org/css-typed-om-1/#serialize-a-cssunitvalue>
+    pub fn serialize(&self) -> String {
+        let unit = match &*self.unit {
+            "number" => "",
+            "percent" => "%",
+            unit => unit,
+        };
+        format!("{}{}", self.value.get(), unit)
+    }
+}
+
+impl CSSUnitValueMethods for CSSUnitValue {
+    /// <https://drafts.css-houdini.org/css-typed-om-1/#dom-cssunitvalue-value>
+    fn Value(&self) -> Finite<f64> {
+        Finite::wrap(self.value.get())
+    }
+
+    /// <https://drafts.css-houdini.org/css-typed-om-1/#dom-cssunitvalue-value>
+    fn SetValue(&self, value: Finite<f64>) {
+        self.value.set(*value);
+    }
+
+    /// <https://drafts.css-houdini.org/css-typed-om-1/#dom-cssunitvalue-unit>
+    fn Unit(&self) -> USVString {
+        USVString(self.unit.clone())
+    }
+}
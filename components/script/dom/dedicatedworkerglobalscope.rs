@@ -410,6 +410,7 @@ impl DedicatedWorkerGlobalScope {
                             while !scope.is_closing() {
                                 run_worker_event_loop(&*global, Some(&worker));
                             }
+                            scope.release_locks();
                         },
                         reporter_name,
                         parent_sender,
@@ -0,0 +1,156 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::DeviceMotionEventBinding::DeviceMotionEventInit;
+use crate::dom::bindings::codegen::Bindings::DeviceMotionEventBinding::DeviceMotionEventMethods;
+use crate::dom::bindings::codegen::Bindings::DeviceMotionEventBinding::{
+    DeviceMotionEventAccelerationInit, DeviceMotionEventRotationRateInit,
+};
+use crate::dom::bindings::codegen::Bindings::EventBinding::EventMethods;
+use crate::dom::bindings::error::Fallible;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::num::Finite;
+use crate::dom::bindings::reflector::reflect_dom_object;
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::devicemotioneventacceleration::DeviceMotionEventAcceleration;
+use crate::dom::devicemotioneventrotationrate::DeviceMotionEventRotationRate;
+use crate::dom::deviceorientationevent::request_sensor_permissions;
+use crate::dom::event::Event;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::promise::Promise;
+use crate::dom::window::Window;
+use dom_struct::dom_struct;
+use embedder_traits::{DeviceAcceleration, DeviceMotion, DeviceRotationRate, DeviceSensor};
+use servo_atoms::Atom;
+use std::rc::Rc;
+
+// https://w3c.github.io/deviceorientation/#devicemotion
+#[dom_struct]
+pub struct DeviceMotionEvent {
+    event: Event,
+    acceleration: Option<Dom<DeviceMotionEventAcceleration>>,
+    acceleration_including_gravity: Option<Dom<DeviceMotionEventAcceleration>>,
+    rotation_rate: Option<Dom<DeviceMotionEventRotationRate>>,
+    interval: f64,
+}
+
+impl DeviceMotionEvent {
+    fn new_inherited(
+        acceleration: Option<&DeviceMotionEventAcceleration>,
+        acceleration_including_gravity: Option<&DeviceMotionEventAcceleration>,
+        rotation_rate: Option<&DeviceMotionEventRotationRate>,
+        interval: f64,
+    ) -> DeviceMotionEvent {
+        DeviceMotionEvent {
+            event: Event::new_inherited(),
+            acceleration: acceleration.map(Dom::from_ref),
+            acceleration_including_gravity: acceleration_including_gravity.map(Dom::from_ref),
+            rotation_rate: rotation_rate.map(Dom::from_ref),
+            interval,
+        }
+    }
+
+    pub fn new(
+        global: &GlobalScope,
+        type_: Atom,
+        bubbles: bool,
+        cancelable: bool,
+        motion: DeviceMotion,
+    ) -> DomRoot<DeviceMotionEvent> {
+        let acceleration = motion
+            .acceleration
+            .map(|acceleration| DeviceMotionEventAcceleration::new(global, acceleration));
+        let acceleration_including_gravity = motion
+            .acceleration_including_gravity
+            .map(|acceleration| DeviceMotionEventAcceleration::new(global, acceleration));
+        let rotation_rate = motion
+            .rotation_rate
+            .map(|rotation_rate| DeviceMotionEventRotationRate::new(global, rotation_rate));
+        let ev = reflect_dom_object(
+            Box::new(DeviceMotionEvent::new_inherited(
+                acceleration.as_deref(),
+                acceleration_including_gravity.as_deref(),
+                rotation_rate.as_deref(),
+                motion.interval,
+            )),
+            global,
+        );
+        ev.upcast::<Event>().init_event(type_, bubbles, cancelable);
+        ev
+    }
+
+    #[allow(non_snake_case)]
+    pub fn Constructor(
+        window: &Window,
+        type_: DOMString,
+        init: &DeviceMotionEventInit,
+    ) -> Fallible<DomRoot<DeviceMotionEvent>> {
+        let acceleration = |init: &DeviceMotionEventAccelerationInit| DeviceAcceleration {
+            x: init.x.map(|x| *x),
+            y: init.y.map(|y| *y),
+            z: init.z.map(|z| *z),
+        };
+        let rotation_rate = |init: &DeviceMotionEventRotationRateInit| DeviceRotationRate {
+            alpha: init.alpha.map(|alpha| *alpha),
+            beta: init.beta.map(|beta| *beta),
+            gamma: init.gamma.map(|gamma| *gamma),
+        };
+        let motion = DeviceMotion {
+            acceleration: init.acceleration.as_ref().map(acceleration),
+            acceleration_including_gravity: init
+                .accelerationIncludingGravity
+                .as_ref()
+                .map(acceleration),
+            rotation_rate: init.rotationRate.as_ref().map(rotation_rate),
+            interval: *init.interval,
+        };
+        Ok(DeviceMotionEvent::new(
+            window.upcast(),
+            Atom::from(type_),
+            init.parent.bubbles,
+            init.parent.cancelable,
+            motion,
+        ))
+    }
+
+    // https://w3c.github.io/deviceorientation/#dom-devicemotionevent-requestpermission
+    #[allow(non_snake_case)]
+    pub fn RequestPermission(window: &Window) -> Rc<Promise> {
+        request_sensor_permissions(window, DeviceSensor::Motion)
+    }
+}
+
+impl DeviceMotionEventMethods for DeviceMotionEvent {
+    // https://w3c.github.io/deviceorientation/#dom-devicemotionevent-acceleration
+    fn GetAcceleration(&self) -> Option<DomRoot<DeviceMotionEventAcceleration>> {
+        self.acceleration
+            .as_ref()
+            .map(|acceleration| DomRoot::from_ref(&**acceleration))
+    }
+
+    // https://w3c.github.io/deviceorientation/#dom-devicemotionevent-accelerationincludinggravity
+    fn GetAccelerationIncludingGravity(&self) -> Option<DomRoot<DeviceMotionEventAcceleration>> {
+        self.acceleration_including_gravity
+            .as_ref()
+            .map(|acceleration| DomRoot::from_ref(&**acceleration))
+    }
+
+    // https://w3c.github.io/deviceorientation/#dom-devicemotionevent-rotationrate
+    fn GetRotationRate(&self) -> Option<DomRoot<DeviceMotionEventRotationRate>> {
+        self.rotation_rate
+            .as_ref()
+            .map(|rotation_rate| DomRoot::from_ref(&**rotation_rate))
+    }
+
+    // https://w3c.github.io/deviceorientation/#dom-devicemotionevent-interval
+    fn Interval(&self) -> Finite<f64> {
+        Finite::wrap(self.interval)
+    }
+
+    // https://dom.spec.whatwg.org/#dom-event-istrusted
+    fn IsTrusted(&self) -> bool {
+        self.upcast::<Event>().IsTrusted()
+    }
+}
@@ -0,0 +1,54 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::DeviceMotionEventAccelerationBinding::DeviceMotionEventAccelerationMethods;
+use crate::dom::bindings::num::Finite;
+use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::globalscope::GlobalScope;
+use dom_struct::dom_struct;
+use embedder_traits::DeviceAcceleration;
+
+#[dom_struct]
+pub struct DeviceMotionEventAcceleration {
+    reflector_: Reflector,
+    #[ignore_malloc_size_of = "defined in embedder_traits"]
+    acceleration: DeviceAcceleration,
+}
+
+impl DeviceMotionEventAcceleration {
+    fn new_inherited(acceleration: DeviceAcceleration) -> DeviceMotionEventAcceleration {
+        DeviceMotionEventAcceleration {
+            reflector_: Reflector::new(),
+            acceleration,
+        }
+    }
+
+    pub fn new(
+        global: &GlobalScope,
+        acceleration: DeviceAcceleration,
+    ) -> DomRoot<DeviceMotionEventAcceleration> {
+        reflect_dom_object(
+            Box::new(DeviceMotionEventAcceleration::new_inherited(acceleration)),
+            global,
+        )
+    }
+}
+
+impl DeviceMotionEventAccelerationMethods for DeviceMotionEventAcceleration {
+    // https://w3c.github.io/deviceorientation/#dom-devicemotioneventacceleration-x
+    fn GetX(&self) -> Option<Finite<f64>> {
+        self.acceleration.x.and_then(Finite::new)
+    }
+
+    // https://w3c.github.io/deviceorientation/#dom-devicemotioneventacceleration-y
+    fn GetY(&self) -> Option<Finite<f64>> {
+        self.acceleration.y.and_then(Finite::new)
+    }
+
+    // https://w3c.github.io/deviceorientation/#dom-devicemotioneventacceleration-z
+    fn GetZ(&self) -> Option<Finite<f64>> {
+        self.acceleration.z.and_then(Finite::new)
+    }
+}
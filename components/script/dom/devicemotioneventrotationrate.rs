@@ -0,0 +1,54 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::DeviceMotionEventRotationRateBinding::DeviceMotionEventRotationRateMethods;
+use crate::dom::bindings::num::Finite;
+use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::globalscope::GlobalScope;
+use dom_struct::dom_struct;
+use embedder_traits::DeviceRotationRate;
+
+#[dom_struct]
+pub struct DeviceMotionEventRotationRate {
+    reflector_: Reflector,
+    #[ignore_malloc_size_of = "defined in embedder_traits"]
+    rotation_rate: DeviceRotationRate,
+}
+
+impl DeviceMotionEventRotationRate {
+    fn new_inherited(rotation_rate: DeviceRotationRate) -> DeviceMotionEventRotationRate {
+        DeviceMotionEventRotationRate {
+            reflector_: Reflector::new(),
+            rotation_rate,
+        }
+    }
+
+    pub fn new(
+        global: &GlobalScope,
+        rotation_rate: DeviceRotationRate,
+    ) -> DomRoot<DeviceMotionEventRotationRate> {
+        reflect_dom_object(
+            Box::new(DeviceMotionEventRotationRate::new_inherited(rotation_rate)),
+            global,
+        )
+    }
+}
+
+impl DeviceMotionEventRotationRateMethods for DeviceMotionEventRotationRate {
+    // https://w3c.github.io/deviceorientation/#dom-devicemotioneventrotationrate-alpha
+    fn GetAlpha(&self) -> Option<Finite<f64>> {
+        self.rotation_rate.alpha.and_then(Finite::new)
+    }
+
+    // https://w3c.github.io/deviceorientation/#dom-devicemotioneventrotationrate-beta
+    fn GetBeta(&self) -> Option<Finite<f64>> {
+        self.rotation_rate.beta.and_then(Finite::new)
+    }
+
+    // https://w3c.github.io/deviceorientation/#dom-devicemotioneventrotationrate-gamma
+    fn GetGamma(&self) -> Option<Finite<f64>> {
+        self.rotation_rate.gamma.and_then(Finite::new)
+    }
+}
@@ -0,0 +1,146 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::DeviceOrientationEventBinding::DeviceOrientationEventInit;
+use crate::dom::bindings::codegen::Bindings::DeviceOrientationEventBinding::DeviceOrientationEventMethods;
+use crate::dom::bindings::codegen::Bindings::EventBinding::EventMethods;
+use crate::dom::bindings::codegen::Bindings::PermissionStatusBinding::{
+    PermissionName, PermissionState,
+};
+use crate::dom::bindings::error::Fallible;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::num::Finite;
+use crate::dom::bindings::reflector::reflect_dom_object;
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::event::Event;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::permissions::request_permission_to_use;
+use crate::dom::promise::Promise;
+use crate::dom::window::Window;
+use dom_struct::dom_struct;
+use embedder_traits::{DeviceOrientation, DeviceSensor};
+use servo_atoms::Atom;
+use std::rc::Rc;
+
+// https://w3c.github.io/deviceorientation/#deviceorientation
+#[dom_struct]
+pub struct DeviceOrientationEvent {
+    event: Event,
+    #[ignore_malloc_size_of = "defined in embedder_traits"]
+    orientation: DeviceOrientation,
+}
+
+impl DeviceOrientationEvent {
+    fn new_inherited(orientation: DeviceOrientation) -> DeviceOrientationEvent {
+        DeviceOrientationEvent {
+            event: Event::new_inherited(),
+            orientation,
+        }
+    }
+
+    pub fn new(
+        global: &GlobalScope,
+        type_: Atom,
+        bubbles: bool,
+        cancelable: bool,
+        orientation: DeviceOrientation,
+    ) -> DomRoot<DeviceOrientationEvent> {
+        let ev = reflect_dom_object(
+            Box::new(DeviceOrientationEvent::new_inherited(orientation)),
+            global,
+        );
+        ev.upcast::<Event>().init_event(type_, bubbles, cancelable);
+        ev
+    }
+
+    #[allow(non_snake_case)]
+    pub fn Constructor(
+        window: &Window,
+        type_: DOMString,
+        init: &DeviceOrientationEventInit,
+    ) -> Fallible<DomRoot<DeviceOrientationEvent>> {
+        let orientation = DeviceOrientation {
+            alpha: init.alpha.map(|alpha| *alpha),
+            beta: init.beta.map(|beta| *beta),
+            gamma: init.gamma.map(|gamma| *gamma),
+            absolute: init.absolute,
+        };
+        Ok(DeviceOrientationEvent::new(
+            window.upcast(),
+            Atom::from(type_),
+            init.parent.bubbles,
+            init.parent.cancelable,
+            orientation,
+        ))
+    }
+
+    // https://w3c.github.io/deviceorientation/#dom-deviceorientationevent-requestpermission
+    #[allow(non_snake_case)]
+    pub fn RequestPermission(window: &Window, absolute: bool) -> Rc<Promise> {
+        let sensor = if absolute {
+            DeviceSensor::AbsoluteOrientation
+        } else {
+            DeviceSensor::Orientation
+        };
+        request_sensor_permissions(window, sensor)
+    }
+}
+
+/// The permissions that the readings of a sensor of the device require.
+pub fn sensor_permissions(sensor: DeviceSensor) -> &'static [PermissionName] {
+    match sensor {
+        DeviceSensor::Orientation | DeviceSensor::Motion => {
+            &[PermissionName::Accelerometer, PermissionName::Gyroscope]
+        },
+        DeviceSensor::AbsoluteOrientation => &[
+            PermissionName::Accelerometer,
+            PermissionName::Gyroscope,
+            PermissionName::Magnetometer,
+        ],
+    }
+}
+
+/// Asks the user for the permissions that the readings of a sensor of the device require, and
+/// returns a promise resolved with whether they were all granted.
+pub fn request_sensor_permissions(window: &Window, sensor: DeviceSensor) -> Rc<Promise> {
+    let global = window.upcast::<GlobalScope>();
+    let promise = Promise::new(global);
+    let granted = sensor_permissions(sensor)
+        .iter()
+        .all(|name| request_permission_to_use(*name, global) == PermissionState::Granted);
+    if granted {
+        promise.resolve_native(&PermissionState::Granted);
+    } else {
+        promise.resolve_native(&PermissionState::Denied);
+    }
+    promise
+}
+
+impl DeviceOrientationEventMethods for DeviceOrientationEvent {
+    // https://w3c.github.io/deviceorientation/#dom-deviceorientationevent-alpha
+    fn GetAlpha(&self) -> Option<Finite<f64>> {
+        self.orientation.alpha.and_then(Finite::new)
+    }
+
+    // https://w3c.github.io/deviceorientation/#dom-deviceorientationevent-beta
+    fn GetBeta(&self) -> Option<Finite<f64>> {
+        self.orientation.beta.and_then(Finite::new)
+    }
+
+    // https://w3c.github.io/deviceorientation/#dom-deviceorientationevent-gamma
+    fn GetGamma(&self) -> Option<Finite<f64>> {
+        self.orientation.gamma.and_then(Finite::new)
+    }
+
+    // https://w3c.github.io/deviceorientation/#dom-deviceorientationevent-absolute
+    fn Absolute(&self) -> bool {
+        self.orientation.absolute
+    }
+
+    // https://dom.spec.whatwg.org/#dom-event-istrusted
+    fn IsTrusted(&self) -> bool {
+        self.upcast::<Event>().IsTrusted()
+    }
+}
@@ -2,6 +2,8 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use crate::context_menu;
+use crate::devtools::PendingDomMutation;
 use crate::document_loader::{DocumentLoader, LoadType};
 use crate::dom::attr::Attr;
 use crate::dom::beforeunloadevent::BeforeUnloadEvent;
@@ -9,18 +11,19 @@ use crate::dom::bindings::callback::ExceptionHandling;
 use crate::dom::bindings::cell::{ref_filter_map, DomRefCell, Ref, RefMut};
 use crate::dom::bindings::codegen::Bindings::BeforeUnloadEventBinding::BeforeUnloadEventBinding::BeforeUnloadEventMethods;
 use crate::dom::bindings::codegen::Bindings::DocumentBinding::{
-    DocumentMethods, DocumentReadyState,
+    DocumentMethods, DocumentReadyState, DocumentVisibilityState,
 };
 use crate::dom::bindings::codegen::Bindings::EventBinding::EventBinding::EventMethods;
 use crate::dom::bindings::codegen::Bindings::HTMLIFrameElementBinding::HTMLIFrameElementBinding::HTMLIFrameElementMethods;
 use crate::dom::bindings::codegen::Bindings::NodeBinding::NodeMethods;
 use crate::dom::bindings::codegen::Bindings::NodeFilterBinding::NodeFilter;
-use crate::dom::bindings::codegen::Bindings::PerformanceBinding::PerformanceMethods;
 use crate::dom::bindings::codegen::Bindings::ShadowRootBinding::ShadowRootMethods;
 use crate::dom::bindings::codegen::Bindings::TouchBinding::TouchMethods;
 use crate::dom::bindings::codegen::Bindings::WindowBinding::{
     FrameRequestCallback, ScrollBehavior, WindowMethods,
 };
+use crate::dom::bindings::codegen::Bindings::XPathEvaluatorBinding::XPathNSResolver;
+use crate::dom::bindings::codegen::Bindings::XPathExpressionBinding::XPathExpressionMethods;
 use crate::dom::bindings::codegen::UnionTypes::{NodeOrString, StringOrElementCreationOptions};
 use crate::dom::bindings::error::{Error, ErrorResult, Fallible};
 use crate::dom::bindings::inheritance::{Castable, ElementTypeId, HTMLElementTypeId, NodeTypeId};
@@ -33,6 +36,7 @@ use crate::dom::bindings::xmlname::XMLName::InvalidXMLName;
 use crate::dom::bindings::xmlname::{
     namespace_from_domstring, validate_and_extract, xml_name_type,
 };
+use crate::dom::caretposition::CaretPosition;
 use crate::dom::cdatasection::CDATASection;
 use crate::dom::comment::Comment;
 use crate::dom::compositionevent::CompositionEvent;
@@ -52,11 +56,13 @@ use crate::dom::eventtarget::EventTarget;
 use crate::dom::focusevent::FocusEvent;
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::hashchangeevent::HashChangeEvent;
+use crate::dom::highlightregistry::{HighlightRegistry, HighlightedText};
 use crate::dom::htmlanchorelement::HTMLAnchorElement;
 use crate::dom::htmlareaelement::HTMLAreaElement;
 use crate::dom::htmlbaseelement::HTMLBaseElement;
 use crate::dom::htmlbodyelement::HTMLBodyElement;
 use crate::dom::htmlcollection::{CollectionFilter, HTMLCollection};
+use crate::dom::htmldialogelement::HTMLDialogElement;
 use crate::dom::htmlelement::HTMLElement;
 use crate::dom::htmlembedelement::HTMLEmbedElement;
 use crate::dom::htmlformelement::{FormControl, FormControlElementHelpers, HTMLFormElement};
@@ -66,6 +72,7 @@ use crate::dom::htmliframeelement::HTMLIFrameElement;
 use crate::dom::htmlimageelement::HTMLImageElement;
 use crate::dom::htmlscriptelement::{HTMLScriptElement, ScriptResult};
 use crate::dom::htmltitleelement::HTMLTitleElement;
+use crate::dom::htmlvideoelement::HTMLVideoElement;
 use crate::dom::keyboardevent::KeyboardEvent;
 use crate::dom::location::Location;
 use crate::dom::messageevent::MessageEvent;
@@ -75,6 +82,7 @@ use crate::dom::node::{LayoutNodeHelpers, Node, NodeDamage, NodeFlags, ShadowInc
 use crate::dom::nodeiterator::NodeIterator;
 use crate::dom::nodelist::NodeList;
 use crate::dom::pagetransitionevent::PageTransitionEvent;
+use crate::dom::performance::reduce_timing_resolution;
 use crate::dom::processinginstruction::ProcessingInstruction;
 use crate::dom::promise::Promise;
 use crate::dom::range::Range;
@@ -90,19 +98,25 @@ use crate::dom::touchlist::TouchList;
 use crate::dom::treewalker::TreeWalker;
 use crate::dom::uievent::UIEvent;
 use crate::dom::virtualmethods::vtable_for;
+use crate::dom::wakelocksentinel::WakeLockSentinel;
 use crate::dom::webglrenderingcontext::WebGLRenderingContext;
 use crate::dom::wheelevent::WheelEvent;
 use crate::dom::window::{ReflowReason, Window};
 use crate::dom::windowproxy::WindowProxy;
+use crate::dom::xpathexpression::XPathExpression;
+use crate::dom::xpathresult::XPathResult;
+use crate::editing::{self, Composition, EditingHistory};
 use crate::fetch::FetchCanceller;
+use crate::find_in_page::{FindMatch, FindState};
 use crate::realms::{AlreadyInRealm, InRealm};
 use crate::script_runtime::JSContext;
 use crate::script_runtime::{CommonScriptMsg, ScriptThreadEventCategory};
 use crate::script_thread::{MainThreadScriptMsg, ScriptThread};
+use crate::speculation_rules::SpeculationRuleSet;
+use crate::spellcheck::{self, SpellingError};
 use crate::stylesheet_set::StylesheetSetRef;
 use crate::task::TaskBox;
 use crate::task_source::{TaskSource, TaskSourceName};
-use crate::timers::OneshotTimerCallback;
 use canvas_traits::webgl::{self, SwapChainId, WebGLContextId, WebGLMsg};
 use content_security_policy::{self as csp, CspList};
 use cookie::Cookie;
@@ -110,34 +124,36 @@ use devtools_traits::ScriptToDevtoolsControlMsg;
 use dom_struct::dom_struct;
 use embedder_traits::EmbedderMsg;
 use encoding_rs::{Encoding, UTF_8};
-use euclid::default::Point2D;
+use euclid::default::{Point2D, Vector2D};
+use euclid::{Rect, Size2D};
 use html5ever::{LocalName, Namespace, QualName};
 use hyper_serde::Serde;
 use ipc_channel::ipc::{self, IpcSender};
 use js::jsapi::{JSObject, JSRuntime};
-use keyboard_types::{Code, Key, KeyState};
+use js::jsval::JSVal;
+use js::rust::HandleValue;
+use keyboard_types::{Code, Key, KeyState, Modifiers};
 use metrics::{
     InteractiveFlag, InteractiveMetrics, InteractiveWindow, ProfilerMetadataFactory,
     ProgressiveWebMetric,
 };
 use mime::{self, Mime};
 use msg::constellation_msg::BrowsingContextId;
-use net_traits::pub_domains::is_pub_domain;
-use net_traits::request::RequestBuilder;
+use net_traits::pub_domains::get_registrable_domain_suffix_of_or_is_equal_to;
+use net_traits::request::{CredentialsMode, Destination, Initiator, Referrer, RequestBuilder};
 use net_traits::response::HttpsState;
 use net_traits::CookieSource::NonHTTP;
-use net_traits::CoreResourceMsg::{GetCookiesForUrl, SetCookiesForUrl};
-use net_traits::{FetchResponseMsg, IpcSend, ReferrerPolicy};
+use net_traits::CoreResourceMsg::{Fetch, GetCookiesForUrl, SetCookiesForUrl};
+use net_traits::{FetchChannels, FetchResponseMsg, IpcSend, ReferrerPolicy};
 use num_traits::ToPrimitive;
 use percent_encoding::percent_decode;
 use profile_traits::ipc as profile_ipc;
 use profile_traits::time::{TimerMetadata, TimerMetadataFrameType, TimerMetadataReflowType};
 use ref_slice::ref_slice;
 use script_layout_interface::message::{Msg, ReflowGoal};
-use script_traits::{AnimationState, DocumentActivity, MouseButton, MouseEventType};
-use script_traits::{
-    MsDuration, ScriptMsg, TouchEventType, TouchId, UntrustedNodeAddress, WheelDelta,
-};
+use script_traits::{AnimationState, DocumentActivity, FrameTiming, MouseButton, MouseEventType};
+use script_traits::{LoadData, LoadOrigin};
+use script_traits::{ScriptMsg, TouchEventType, TouchId, UntrustedNodeAddress, WheelDelta};
 use servo_arc::Arc;
 use servo_atoms::Atom;
 use servo_config::pref;
@@ -161,18 +177,14 @@ use style::selector_parser::{RestyleDamage, Snapshot};
 use style::shared_lock::SharedRwLock as StyleSharedRwLock;
 use style::str::{split_html_space_chars, str_join};
 use style::stylesheet_set::DocumentStylesheetSet;
-use style::stylesheets::{Origin, OriginSet, Stylesheet};
+use style::stylesheets::{CssRule, Origin, OriginSet, Stylesheet};
+use style_traits::CSSPixel;
 use url::Host;
 use uuid::Uuid;
 
-/// The number of times we are allowed to see spurious `requestAnimationFrame()` calls before
-/// falling back to fake ones.
-///
-/// A spurious `requestAnimationFrame()` call is defined as one that does not change the DOM.
-const SPURIOUS_ANIMATION_FRAME_THRESHOLD: u8 = 5;
-
-/// The amount of time between fake `requestAnimationFrame()`s.
-const FAKE_REQUEST_ANIMATION_FRAME_DELAY: u64 = 16;
+/// The time between two animation frames that is assumed until the first one told the video
+/// refresh rate, in milliseconds.
+const ANIMATION_FRAME_INTERVAL: f64 = 1000. / 60.;
 
 pub enum TouchEventResult {
     Processed(bool),
@@ -260,6 +272,9 @@ pub struct Document {
     /// List of stylesheets associated with nodes in this document. |None| if the list needs to be refreshed.
     stylesheets: DomRefCell<DocumentStylesheetSet<StyleSheetInDocument>>,
     stylesheet_list: MutNullableDom<StyleSheetList>,
+    /// Whether rules were inserted into or removed from the stylesheets since the last reflow.
+    /// Layout was told which ones, so that it only restyles the elements they may apply to.
+    stylesheet_rules_changed: Cell<bool>,
     ready_state: Cell<DocumentReadyState>,
     /// Whether the DOMContentLoaded event has already been dispatched.
     domcontentloaded_dispatched: Cell<bool>,
@@ -267,6 +282,10 @@ pub struct Document {
     possibly_focused: MutNullableDom<Element>,
     /// The element that currently has the document focus context.
     focused: MutNullableDom<Element>,
+    /// Whether the user last interacted with the document through a pointing device rather
+    /// than the keyboard, which keeps `:focus-visible` from matching newly focused elements.
+    /// <https://drafts.csswg.org/selectors-4/#the-focus-visible-pseudo>
+    pointer_modality: Cell<bool>,
     /// The script element that is currently executing.
     current_script: MutNullableDom<HTMLScriptElement>,
     /// <https://html.spec.whatwg.org/multipage/#pending-parsing-blocking-script>
@@ -288,11 +307,22 @@ pub struct Document {
     /// <https://html.spec.whatwg.org/multipage/#list-of-animation-frame-callbacks>
     /// List of animation frame callbacks
     animation_frame_list: DomRefCell<Vec<(u32, Option<AnimationFrameCallback>)>>,
+    /// The video elements that presented a new frame since the last animation frame, and have
+    /// video frame request callbacks to run for it.
+    /// <https://wicg.github.io/video-rvfc/#video-rvfc-procedures>
+    pending_video_frame_callbacks: DomRefCell<Vec<Dom<HTMLVideoElement>>>,
     /// Whether we're in the process of running animation callbacks.
     ///
     /// Tracking this is not necessary for correctness. Instead, it is an optimization to avoid
     /// sending needless `ChangeRunningAnimationsState` messages to the compositor.
     running_animation_callbacks: Cell<bool>,
+    /// The video refresh that the animation frame callbacks were last run for, to estimate
+    /// when the next rendering opportunity is.
+    #[ignore_malloc_size_of = "defined in script_traits"]
+    last_animation_frame: Cell<Option<FrameTiming>>,
+    /// Whether the animation frame callbacks were not run for the last video refresh, because
+    /// it had passed by the time they could be.
+    skipped_animation_frame: Cell<bool>,
     /// Tracks all outstanding loads related to this document.
     loader: DomRefCell<DocumentLoader>,
     /// The current active HTML parser, to allow resuming after interruptions.
@@ -312,7 +342,7 @@ pub struct Document {
     needs_paint: Cell<bool>,
     /// <http://w3c.github.io/touch-events/#dfn-active-touch-point>
     active_touch_points: DomRefCell<Vec<Dom<Touch>>>,
-    /// Navigation Timing properties:
+    /// Navigation Timing properties, as `time::precise_time_ns()` values:
     /// <https://w3c.github.io/navigation-timing/#sec-PerformanceNavigationTiming>
     dom_loading: Cell<u64>,
     dom_interactive: Cell<u64>,
@@ -326,6 +356,11 @@ pub struct Document {
     unload_event_end: Cell<u64>,
     /// <https://html.spec.whatwg.org/multipage/#concept-document-https-state>
     https_state: Cell<HttpsState>,
+    /// Whether the document was served with the headers which give it the cross-origin
+    /// isolated capability.
+    ///
+    /// <https://html.spec.whatwg.org/multipage/#concept-settings-object-cross-origin-isolated-capability>
+    cross_origin_isolated: Cell<bool>,
     /// The document's origin.
     origin: MutableOrigin,
     ///  https://w3c.github.io/webappsec-referrer-policy/#referrer-policy-states
@@ -341,10 +376,6 @@ pub struct Document {
     ignore_destructive_writes_counter: Cell<u32>,
     /// <https://html.spec.whatwg.org/multipage/#ignore-opens-during-unload-counter>
     ignore_opens_during_unload_counter: Cell<u32>,
-    /// The number of spurious `requestAnimationFrame()` requests we've received.
-    ///
-    /// A rAF request is considered spurious if nothing was actually reflowed.
-    spurious_animation_frames: Cell<u8>,
 
     /// Track the total number of elements in this DOM's tree.
     /// This is sent to the layout thread every time a reflow is done;
@@ -354,6 +385,22 @@ pub struct Document {
     dom_count: Cell<u32>,
     /// Entry node for fullscreen.
     fullscreen_element: MutNullableDom<Element>,
+    /// The element which locked the pointer, and gets the mouse events while it is locked.
+    /// <https://w3c.github.io/pointerlock/#dfn-pointer-lock-target>
+    pointer_lock_element: MutNullableDom<Element>,
+    /// The video of this document that is shown in a floating window.
+    /// <https://w3c.github.io/picture-in-picture/#pictureinpictureelement>
+    picture_in_picture_element: MutNullableDom<HTMLVideoElement>,
+    /// The position of the mouse in the last `mousemove` event, which the movement of the next
+    /// one is relative to.
+    last_mouse_move_point: Cell<Option<Point2D<f32>>>,
+    /// The screen wake locks acquired by this document which were not released yet.
+    /// <https://w3c.github.io/screen-wake-lock/#dfn-activelocks>
+    wake_locks: DomRefCell<Vec<Dom<WakeLockSentinel>>>,
+    /// The modal dialogs in the top layer, the topmost last. The topmost one blocks the rest
+    /// of the document.
+    /// <https://fullscreen.spec.whatwg.org/#top-layer>
+    modal_dialogs: DomRefCell<Vec<Dom<HTMLDialogElement>>>,
     /// Map from ID to set of form control elements that have that ID as
     /// their 'form' content attribute. Used to reset form controls
     /// whenever any element with the same ID as the form attribute
@@ -383,6 +430,16 @@ pub struct Document {
     /// List of tasks to execute as soon as last script/layout blocker is removed.
     #[ignore_malloc_size_of = "Measuring trait objects is hard"]
     delayed_tasks: DomRefCell<Vec<Box<dyn TaskBox>>>,
+    /// <https://wicg.github.io/nav-speculation/prerendering.html#document-prerendering>
+    prerendering: Cell<bool>,
+    /// <https://wicg.github.io/nav-speculation/prerendering.html#document-post-prerendering-activation-steps-list>
+    #[ignore_malloc_size_of = "Measuring trait objects is hard"]
+    post_prerendering_activation_tasks: DomRefCell<Vec<Box<dyn TaskBox>>>,
+    /// The rule sets of the speculation rules scripts of the document, in tree order.
+    /// <https://wicg.github.io/nav-speculation/speculation-rules.html#document-sr-sets>
+    speculation_rule_sets: DomRefCell<Vec<(Dom<HTMLScriptElement>, SpeculationRuleSet)>>,
+    /// The URLs that the speculation rules of the document prefetched.
+    prefetched_urls: DomRefCell<Vec<ServoUrl>>,
     /// https://html.spec.whatwg.org/multipage/#completely-loaded
     completely_loaded: Cell<bool>,
     /// Set of shadow roots connected to the document tree.
@@ -402,6 +459,35 @@ pub struct Document {
     csp_list: DomRefCell<Option<CspList>>,
     /// https://w3c.github.io/slection-api/#dfn-selection
     selection: MutNullableDom<Selection>,
+    /// The text node in which layout draws the caret of the focused editing host.
+    editing_caret: MutNullableDom<Text>,
+    /// The UTF-8 offset of the editing caret into `editing_caret`.
+    editing_caret_offset: Cell<usize>,
+    /// The undo and redo stacks of the document's editing hosts.
+    editing_history: DomRefCell<EditingHistory>,
+    /// The text that an IME is composing in the focused editing host.
+    editing_composition: DomRefCell<Option<Composition>>,
+    /// The find-in-page search started by the embedder, for top-level documents.
+    find_state: DomRefCell<Option<FindState>>,
+    /// The find-in-page matches in this document that layout highlights.
+    find_highlights: DomRefCell<Vec<FindMatch>>,
+    /// https://drafts.csswg.org/css-highlight-api-1/#highlight-registry
+    highlight_registry: MutNullableDom<HighlightRegistry>,
+    /// Whether the custom highlights or their ranges changed since `highlighted_text` was
+    /// last updated.
+    highlights_changed: Cell<bool>,
+    /// The text in this document that custom highlights cover, which layout paints.
+    highlighted_text: DomRefCell<Vec<HighlightedText>>,
+    /// The misspelled words of the editable text of this document, which layout underlines.
+    spelling_errors: DomRefCell<Vec<SpellingError>>,
+    /// The text controls and editing hosts whose spelling is checked at the next reflow.
+    spellcheck_requests: DomRefCell<Vec<Dom<Element>>>,
+    /// Whether this document got a password field, which the password manager was told about.
+    has_password_field: Cell<bool>,
+    /// Whether devtools asked to be told about the changes to the DOM tree of this document.
+    devtools_wants_mutations: Cell<bool>,
+    /// The changes to the DOM tree of this document that devtools wasn't told about yet.
+    devtools_mutations: DomRefCell<Vec<PendingDomMutation>>,
 }
 
 #[derive(JSTraceable, MallocSizeOf)]
@@ -500,6 +586,14 @@ impl Document {
         self.https_state.set(https_state);
     }
 
+    pub fn is_cross_origin_isolated(&self) -> bool {
+        self.cross_origin_isolated.get()
+    }
+
+    pub fn set_cross_origin_isolated(&self, cross_origin_isolated: bool) {
+        self.cross_origin_isolated.set(cross_origin_isolated);
+    }
+
     pub fn is_fully_active(&self) -> bool {
         self.activity.get() == DocumentActivity::FullyActive
     }
@@ -523,6 +617,8 @@ impl Document {
             ClientContextId::build(pipeline_id.namespace_id.0, pipeline_id.index.0.get());
 
         if activity != DocumentActivity::FullyActive {
+            // https://w3c.github.io/screen-wake-lock/#handling-document-loss-of-full-activity
+            self.release_wake_locks();
             self.window().suspend();
             media.suspend(&client_context_id);
             return;
@@ -535,6 +631,9 @@ impl Document {
         self.window().resume();
         media.resume(&client_context_id);
 
+        // The viewport may have been resized while the document was inactive.
+        self.window().evaluate_media_queries_and_report_changes();
+
         if self.ready_state.get() != DocumentReadyState::Complete {
             return;
         }
@@ -615,6 +714,9 @@ impl Document {
         // not the document element. Needs some layout changes to make
         // that workable.
         self.stylesheets.borrow().has_changed() ||
+            self.highlights_changed.get() ||
+            !self.spellcheck_requests.borrow().is_empty() ||
+            self.stylesheet_rules_changed.get() ||
             self.GetDocumentElement().map_or(false, |root| {
                 root.upcast::<Node>().has_dirty_descendants() ||
                     !self.pending_restyles.borrow().is_empty() ||
@@ -622,6 +724,18 @@ impl Document {
             })
     }
 
+    /// Whether the document changed in a way that may affect its layout since the last reflow,
+    /// as opposed to only needing to be painted again.
+    pub fn needs_layout(&self) -> bool {
+        self.stylesheets.borrow().has_changed() ||
+            self.stylesheet_rules_changed.get() ||
+            self.shadow_roots_styles_changed() ||
+            self.GetDocumentElement().map_or(false, |root| {
+                root.upcast::<Node>().has_dirty_descendants() ||
+                    !self.pending_restyles.borrow().is_empty()
+            })
+    }
+
     /// Returns the first `base` element in the DOM that has an `href` attribute.
     pub fn base_element(&self) -> Option<DomRoot<HTMLBaseElement>> {
         self.base_element.get()
@@ -864,12 +978,12 @@ impl Document {
     pub fn set_ready_state(&self, state: DocumentReadyState) {
         match state {
             DocumentReadyState::Loading => {
-                update_with_current_time_ms(&self.dom_loading);
+                update_with_current_time_ns(&self.dom_loading);
             },
             DocumentReadyState::Complete => {
-                update_with_current_time_ms(&self.dom_complete);
+                update_with_current_time_ns(&self.dom_complete);
             },
-            DocumentReadyState::Interactive => update_with_current_time_ms(&self.dom_interactive),
+            DocumentReadyState::Interactive => update_with_current_time_ns(&self.dom_interactive),
         };
 
         self.ready_state.set(state);
@@ -897,7 +1011,10 @@ impl Document {
 
     /// Request that the given element receive focus once the current transaction is complete.
     pub fn request_focus(&self, elem: &Element) {
-        if elem.is_focusable_area() {
+        // https://html.spec.whatwg.org/multipage/#focusing-steps
+        if let Some(delegate) = elem.focus_delegate() {
+            self.possibly_focused.set(Some(&delegate))
+        } else if elem.is_focusable_area() {
             self.possibly_focused.set(Some(elem))
         }
     }
@@ -908,11 +1025,15 @@ impl Document {
         if self.focused == self.possibly_focused.get().as_deref() {
             return;
         }
-        if let Some(ref elem) = self.focused.get() {
+        let old_focused = self.focused.get();
+        let new_focused = self.possibly_focused.get();
+        if let Some(ref elem) = old_focused {
             let node = elem.upcast::<Node>();
             elem.set_focus_state(false);
-            // FIXME: pass appropriate relatedTarget
-            self.fire_focus_event(FocusEventType::Blur, node, None);
+            elem.set_focus_visible_state(false);
+            let related_target = new_focused.as_ref().map(|elem| elem.upcast());
+            self.fire_focus_event(FocusEventType::Blur, node, related_target);
+            self.fire_focus_event(FocusEventType::FocusOut, node, related_target);
 
             // Notify the embedder to hide the input method.
             if elem.input_method_type().is_some() {
@@ -920,13 +1041,24 @@ impl Document {
             }
         }
 
-        self.focused.set(self.possibly_focused.get().as_deref());
+        self.focused.set(new_focused.as_deref());
+
+        // The word that was being typed in the element that lost the focus is checked now.
+        for elem in old_focused.iter().chain(new_focused.iter()) {
+            self.request_spellcheck(elem);
+        }
 
         if let Some(ref elem) = self.focused.get() {
             elem.set_focus_state(true);
+            // Elements that take keyboard input always indicate focus, others only when the
+            // user is not using a pointing device.
+            elem.set_focus_visible_state(
+                !self.pointer_modality.get() || elem.input_method_type().is_some(),
+            );
             let node = elem.upcast::<Node>();
-            // FIXME: pass appropriate relatedTarget
-            self.fire_focus_event(FocusEventType::Focus, node, None);
+            let related_target = old_focused.as_ref().map(|elem| elem.upcast());
+            self.fire_focus_event(FocusEventType::Focus, node, related_target);
+            self.fire_focus_event(FocusEventType::FocusIn, node, related_target);
             // Update the focus state for all elements in the focus chain.
             // https://html.spec.whatwg.org/multipage/#focus-chain
             if focus_type == FocusType::Element {
@@ -936,10 +1068,203 @@ impl Document {
             // Notify the embedder to display an input method.
             if let Some(kind) = elem.input_method_type() {
                 self.send_to_embedder(EmbedderMsg::ShowIME(kind));
+                self.report_ime_caret_rect();
+            }
+        }
+
+        self.update_editing_caret();
+    }
+
+    /// Moves the focus to the next (or previous) element in the sequential focus navigation
+    /// order. Moving past the last (or first) element leaves the document without a focused
+    /// element, so that the next navigation starts over.
+    /// <https://html.spec.whatwg.org/multipage/#sequential-focus-navigation>
+    pub fn sequential_focus_navigation(&self, direction: SequentialFocusDirection) {
+        let mut order = vec![];
+        sequential_focus_navigation_order(self.upcast(), &mut order);
+        let current = self
+            .focused
+            .get()
+            .and_then(|focused| order.iter().position(|element| *element == focused));
+        let next = match (direction, current) {
+            (SequentialFocusDirection::Forward, Some(index)) => order.get(index + 1),
+            (SequentialFocusDirection::Forward, None) => order.first(),
+            (SequentialFocusDirection::Backward, Some(index)) => {
+                index.checked_sub(1).and_then(|index| order.get(index))
+            },
+            (SequentialFocusDirection::Backward, None) => order.last(),
+        };
+
+        self.begin_focus_transaction();
+        if let Some(next) = next {
+            self.request_focus(next);
+        }
+        self.commit_focus_transaction(FocusType::Element);
+
+        if let Some(next) = next {
+            let rect = next.upcast::<Node>().bounding_content_box_or_zero();
+            let viewport = self.window.current_viewport();
+            if !viewport.contains_rect(&rect) {
+                self.window.scroll(
+                    viewport.origin.x.to_f64_px(),
+                    rect.origin.y.to_f64_px(),
+                    ScrollBehavior::Auto,
+                );
             }
         }
     }
 
+    /// Moves the caret drawn by layout to the document's selection, if that is collapsed
+    /// inside the focused editing host.
+    pub fn update_editing_caret(&self) {
+        let (text, offset) = match editing::focused_caret(self) {
+            Some((text, offset)) => (Some(text), offset),
+            None => (None, 0),
+        };
+        if self.editing_caret.get() == text && self.editing_caret_offset.get() == offset {
+            return;
+        }
+        if let Some(old) = self.editing_caret.get() {
+            old.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
+        }
+        self.editing_caret.set(text.as_deref());
+        self.editing_caret_offset.set(offset);
+        if let Some(text) = text {
+            text.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
+        }
+        self.report_ime_caret_rect();
+    }
+
+    pub fn editing_history(&self) -> &DomRefCell<EditingHistory> {
+        &self.editing_history
+    }
+
+    pub fn editing_composition(&self) -> &DomRefCell<Option<Composition>> {
+        &self.editing_composition
+    }
+
+    pub fn find_state(&self) -> &DomRefCell<Option<FindState>> {
+        &self.find_state
+    }
+
+    pub fn find_highlights(&self) -> &DomRefCell<Vec<FindMatch>> {
+        &self.find_highlights
+    }
+
+    pub fn highlight_registry(&self) -> DomRoot<HighlightRegistry> {
+        self.highlight_registry
+            .or_init(|| HighlightRegistry::new(self))
+    }
+
+    /// Notes that the custom highlights changed, so that they are painted again.
+    pub fn highlights_changed(&self) {
+        self.highlights_changed.set(true);
+    }
+
+    /// Notes that a range in this document changed, which may be one of a custom highlight.
+    pub fn note_range_change(&self) {
+        let has_highlights = self
+            .highlight_registry
+            .get()
+            .map_or(false, |registry| !registry.is_empty());
+        if has_highlights {
+            self.highlights_changed();
+        }
+    }
+
+    pub fn spelling_errors(&self) -> &DomRefCell<Vec<SpellingError>> {
+        &self.spelling_errors
+    }
+
+    /// Asks for the spelling of the text of `element` to be checked at the next reflow, if it
+    /// is a text control or an editing host.
+    pub fn request_spellcheck(&self, element: &Element) {
+        if spellcheck::checked_element(element.upcast()).as_deref() != Some(element) {
+            return;
+        }
+        let mut requests = self.spellcheck_requests.borrow_mut();
+        if !requests.iter().any(|requested| &**requested == element) {
+            requests.push(Dom::from_ref(element));
+        }
+    }
+
+    /// Notes that this document got a password field, and returns whether it had one already.
+    pub fn note_password_field(&self) -> bool {
+        self.has_password_field.replace(true)
+    }
+
+    pub fn devtools_wants_mutations(&self) -> bool {
+        self.devtools_wants_mutations.get()
+    }
+
+    pub fn set_devtools_wants_mutations(&self, value: bool) {
+        self.devtools_wants_mutations.set(value);
+        if !value {
+            self.devtools_mutations.borrow_mut().clear();
+        }
+    }
+
+    pub fn devtools_mutations(&self) -> &DomRefCell<Vec<PendingDomMutation>> {
+        &self.devtools_mutations
+    }
+
+    /// Checks the spelling of the text controls and editing hosts that asked for it.
+    pub fn update_spelling_errors(&self) {
+        let requests: Vec<_> = self
+            .spellcheck_requests
+            .borrow_mut()
+            .drain(..)
+            .map(|element| DomRoot::from_ref(&*element))
+            .collect();
+        for element in requests {
+            spellcheck::check(&element, &self.spelling_errors);
+        }
+    }
+
+    /// Updates the text that layout paints for the custom highlights, if they changed, and
+    /// dirties the text nodes whose highlights are different.
+    #[allow(unrooted_must_root)]
+    pub fn update_highlighted_text(&self) {
+        if !self.highlights_changed.replace(false) {
+            return;
+        }
+        let highlighted_text = self
+            .highlight_registry
+            .get()
+            .map_or(vec![], |registry| registry.highlighted_text());
+        let mut current = self.highlighted_text.borrow_mut();
+        if *current == highlighted_text {
+            return;
+        }
+        for highlighted in current.iter().chain(highlighted_text.iter()) {
+            highlighted
+                .text()
+                .upcast::<Node>()
+                .dirty(NodeDamage::OtherNodeDamage);
+        }
+        *current = highlighted_text;
+    }
+
+    /// Tells the embedder where the caret of the focused editable element is, so that
+    /// the IME can show its candidate window next to it.
+    pub fn report_ime_caret_rect(&self) {
+        let focused = match self.get_focused_element() {
+            Some(focused) if focused.input_method_type().is_some() => focused,
+            _ => return,
+        };
+        let node = match self.editing_caret.get() {
+            Some(text) => DomRoot::upcast::<Node>(text),
+            None => DomRoot::upcast::<Node>(focused),
+        };
+        let rect = node.bounding_content_box_or_zero();
+        let rect = Rect::<f32, CSSPixel>::new(
+            euclid::Point2D::new(rect.origin.x.to_f32_px(), rect.origin.y.to_f32_px()),
+            Size2D::new(rect.size.width.to_f32_px(), rect.size.height.to_f32_px()),
+        );
+        let rect = rect * self.window.device_pixel_ratio();
+        self.send_to_embedder(EmbedderMsg::IMECaretRect(rect.to_i32()));
+    }
+
     /// Handles any updates when the document's title has changed.
     pub fn title_changed(&self) {
         if self.browsing_context().is_some() {
@@ -972,7 +1297,7 @@ impl Document {
     pub fn handle_mouse_event(
         &self,
         js_runtime: *mut JSRuntime,
-        _button: MouseButton,
+        button: MouseButton,
         client_point: Point2D<f32>,
         mouse_event_type: MouseEventType,
         node_address: Option<UntrustedNodeAddress>,
@@ -986,15 +1311,22 @@ impl Document {
         };
         debug!("{}: at {:?}", mouse_event_type_string, client_point);
 
-        let el = node_address.and_then(|address| {
-            let node = unsafe { node::from_untrusted_node_address(js_runtime, address) };
+        // While the pointer is locked, the mouse events target the element which locked it,
+        // wherever the hidden cursor is.
+        let hit_node = match self.pointer_lock_element.get() {
+            Some(element) => Some(DomRoot::from_ref(element.upcast::<Node>())),
+            None => node_address
+                .map(|address| unsafe { node::from_untrusted_node_address(js_runtime, address) }),
+        };
+        let el = hit_node.as_ref().and_then(|node| {
             node.inclusive_ancestors(ShadowIncluding::No)
                 .filter_map(DomRoot::downcast::<Element>)
                 .next()
         });
+        // Inert elements can't be clicked.
         let el = match el {
-            Some(el) => el,
-            None => return,
+            Some(el) if !el.is_inert() => el,
+            _ => return,
         };
 
         let node = el.upcast::<Node>();
@@ -1006,6 +1338,7 @@ impl Document {
             }
 
             self.begin_focus_transaction();
+            self.pointer_modality.set(true);
         }
 
         // https://w3c.github.io/uievents/#event-type-click
@@ -1043,14 +1376,34 @@ impl Document {
                 el.set_click_in_progress(true);
                 event.fire(node.upcast());
                 el.set_click_in_progress(false);
+
+                // Clicking into editable content focuses its editing host and places the caret.
+                if !event.DefaultPrevented() {
+                    if let Some(ref hit_node) = hit_node {
+                        if let Some(host) = hit_node.editing_host() {
+                            editing::handle_click(&host, hit_node, point_in_node);
+                        }
+                    }
+                }
             },
             MouseEventType::MouseDown => {
+                // https://html.spec.whatwg.org/multipage/#activation-triggering-input-event
+                self.window.notify_activation();
                 if let Some(a) = activatable {
                     a.enter_formal_activation_state();
                 }
 
                 let target = node.upcast();
                 event.fire(target);
+
+                if let MouseButton::Right = button {
+                    self.fire_contextmenu_event(
+                        client_point,
+                        hit_node.as_deref().unwrap_or(node),
+                        point_in_node,
+                        pressed_mouse_buttons,
+                    );
+                }
             },
             MouseEventType::MouseUp => {
                 if let Some(a) = activatable {
@@ -1071,6 +1424,46 @@ impl Document {
             .reflow(ReflowGoal::Full, ReflowReason::MouseEvent);
     }
 
+    /// Fires a `contextmenu` event at `target`, which the user pressed with the secondary button,
+    /// and unless it is canceled, asks the embedder to show its context menu.
+    /// <https://w3c.github.io/uievents/#event-type-contextmenu>
+    fn fire_contextmenu_event(
+        &self,
+        client_point: Point2D<f32>,
+        target: &Node,
+        point_in_target: Option<Point2D<f32>>,
+        pressed_mouse_buttons: u16,
+    ) {
+        let client_x = client_point.x as i32;
+        let client_y = client_point.y as i32;
+        let event = MouseEvent::new(
+            &self.window,
+            DOMString::from("contextmenu"),
+            EventBubbles::Bubbles,
+            EventCancelable::Cancelable,
+            Some(&self.window),
+            0,
+            client_x,
+            client_y,
+            client_x,
+            client_y,
+            false,
+            false,
+            false,
+            false,
+            2i16,
+            pressed_mouse_buttons,
+            None,
+            point_in_target,
+        );
+        let event = event.upcast::<Event>();
+        event.set_trusted(true);
+        event.fire(target.upcast());
+        if !event.DefaultPrevented() {
+            context_menu::show(target, point_in_target);
+        }
+    }
+
     fn maybe_fire_dblclick(
         &self,
         click_pos: Point2D<f32>,
@@ -1138,10 +1531,34 @@ impl Document {
         event_name: FireMouseEventType,
         pressed_mouse_buttons: u16,
     ) {
+        let mouse_event = self.new_mouse_event(client_point, event_name, pressed_mouse_buttons);
+        mouse_event.upcast::<Event>().fire(target);
+    }
+
+    /// Fires a `mousemove` event, which moved the mouse by `movement` since the previous one.
+    fn fire_mouse_move_event(
+        &self,
+        client_point: Point2D<f32>,
+        movement: Vector2D<i32>,
+        target: &EventTarget,
+        pressed_mouse_buttons: u16,
+    ) {
+        let mouse_event =
+            self.new_mouse_event(client_point, FireMouseEventType::Move, pressed_mouse_buttons);
+        mouse_event.set_movement(movement.x, movement.y);
+        mouse_event.upcast::<Event>().fire(target);
+    }
+
+    fn new_mouse_event(
+        &self,
+        client_point: Point2D<f32>,
+        event_name: FireMouseEventType,
+        pressed_mouse_buttons: u16,
+    ) -> DomRoot<MouseEvent> {
         let client_x = client_point.x.to_i32().unwrap_or(0);
         let client_y = client_point.y.to_i32().unwrap_or(0);
 
-        let mouse_event = MouseEvent::new(
+        MouseEvent::new(
             &self.window,
             DOMString::from(event_name.as_str()),
             EventBubbles::Bubbles,
@@ -1160,9 +1577,7 @@ impl Document {
             pressed_mouse_buttons,
             None,
             None,
-        );
-        let event = mouse_event.upcast::<Event>();
-        event.fire(target);
+        )
     }
 
     #[allow(unsafe_code)]
@@ -1184,11 +1599,35 @@ impl Document {
             Some(client_point) => client_point,
         };
 
+        // https://w3c.github.io/pointerlock/#dfn-movementx
+        let movement = self
+            .last_mouse_move_point
+            .replace(Some(client_point))
+            .map_or(Vector2D::zero(), |last_point| {
+                Vector2D::new(
+                    client_point.x as i32 - last_point.x as i32,
+                    client_point.y as i32 - last_point.y as i32,
+                )
+            });
+
+        // While the pointer is locked, the element which locked it gets the mouse events, and
+        // the hover state doesn't change.
+        if let Some(target) = self.pointer_lock_element.get() {
+            self.fire_mouse_move_event(
+                client_point,
+                movement,
+                target.upcast(),
+                pressed_mouse_buttons,
+            );
+            return;
+        }
+
         let maybe_new_target = node_address.and_then(|address| {
             let node = unsafe { node::from_untrusted_node_address(js_runtime, address) };
             node.inclusive_ancestors(ShadowIncluding::No)
                 .filter_map(DomRoot::downcast::<Element>)
                 .next()
+                .filter(|element| !element.is_inert())
         });
 
         // Send mousemove event to topmost target, unless it's an iframe, in which case the
@@ -1198,10 +1637,10 @@ impl Document {
             None => return,
         };
 
-        self.fire_mouse_event(
+        self.fire_mouse_move_event(
             client_point,
+            movement,
             new_target.upcast(),
-            FireMouseEventType::Move,
             pressed_mouse_buttons,
         );
 
@@ -1277,6 +1716,31 @@ impl Document {
             .reflow(ReflowGoal::Full, ReflowReason::MouseEvent);
     }
 
+    /// Fires a `mousemove` event at the element which locked the pointer, when the hidden
+    /// cursor was moved by `movement`. The position of the event is the one the cursor had
+    /// when the pointer was locked.
+    pub fn handle_pointer_lock_move_event(
+        &self,
+        movement: Vector2D<f32>,
+        pressed_mouse_buttons: u16,
+    ) {
+        let target = match self.pointer_lock_element.get() {
+            Some(target) => target,
+            None => return,
+        };
+        let client_point = self.last_mouse_move_point.get().unwrap_or_else(Point2D::zero);
+        let movement = Vector2D::new(movement.x.round() as i32, movement.y.round() as i32);
+        self.fire_mouse_move_event(
+            client_point,
+            movement,
+            target.upcast(),
+            pressed_mouse_buttons,
+        );
+
+        self.window
+            .reflow(ReflowGoal::Full, ReflowReason::MouseEvent);
+    }
+
     #[allow(unsafe_code)]
     pub fn handle_wheel_event(
         &self,
@@ -1386,6 +1850,10 @@ impl Document {
                 }
             },
             TouchEventType::Up | TouchEventType::Cancel => {
+                // https://html.spec.whatwg.org/multipage/#activation-triggering-input-event
+                if let TouchEventType::Up = event_type {
+                    window.notify_activation();
+                }
                 // Remove an existing touch point
                 let mut active_touch_points = self.active_touch_points.borrow_mut();
                 match active_touch_points
@@ -1436,6 +1904,11 @@ impl Document {
 
     /// The entry point for all key processing for web content
     pub fn dispatch_key_event(&self, keyboard_event: ::keyboard_types::KeyboardEvent) {
+        self.pointer_modality.set(false);
+        // https://html.spec.whatwg.org/multipage/#activation-triggering-input-event
+        if keyboard_event.state == KeyState::Down && keyboard_event.key != Key::Escape {
+            self.window.notify_activation();
+        }
         let focused = self.get_focused_element();
         let body = self.GetBody();
 
@@ -1493,6 +1966,23 @@ impl Document {
             cancel_state = ev.get_cancel_state();
         }
 
+        // https://html.spec.whatwg.org/multipage/#sequential-focus-navigation
+        if cancel_state == EventDefault::Allowed &&
+            keyboard_event.key == Key::Tab &&
+            keyboard_event.state == KeyState::Down &&
+            !keyboard_event
+                .modifiers
+                .intersects(Modifiers::ALT | Modifiers::CONTROL | Modifiers::META)
+        {
+            let direction = if keyboard_event.modifiers.contains(Modifiers::SHIFT) {
+                SequentialFocusDirection::Backward
+            } else {
+                SequentialFocusDirection::Forward
+            };
+            self.sequential_focus_navigation(direction);
+            return;
+        }
+
         if cancel_state == EventDefault::Allowed {
             let msg = EmbedderMsg::Keyboard(keyboard_event.clone());
             self.send_to_embedder(msg);
@@ -1510,6 +2000,13 @@ impl Document {
                         .fire_synthetic_mouse_event_not_trusted(DOMString::from("click"));
                 }
             }
+
+            // The context menu key shows the context menu of the focused element.
+            if keyboard_event.key == Key::ContextMenu && keyboard_event.state == KeyState::Down {
+                if let Some(node) = target.downcast::<Node>() {
+                    self.fire_contextmenu_event(Point2D::zero(), node, None, 0);
+                }
+            }
         }
 
         self.window.reflow(ReflowGoal::Full, ReflowReason::KeyEvent);
@@ -1635,18 +2132,7 @@ impl Document {
             .borrow_mut()
             .push((ident, Some(callback)));
 
-        // If we are running 'fake' animation frames, we unconditionally
-        // set up a one-shot timer for script to execute the rAF callbacks.
-        if self.is_faking_animation_frames() && self.window().visible() {
-            warn!("Scheduling fake animation frame. Animation frames tick too fast.");
-            let callback = FakeRequestAnimationFrameCallback {
-                document: Trusted::new(self),
-            };
-            self.global().schedule_callback(
-                OneshotTimerCallback::FakeRequestAnimationFrame(callback),
-                MsDuration::new(FAKE_REQUEST_ANIMATION_FRAME_DELAY),
-            );
-        } else if !self.running_animation_callbacks.get() {
+        if !self.running_animation_callbacks.get() {
             // No need to send a `ChangeRunningAnimationsState` if we're running animation callbacks:
             // we're guaranteed to already be in the "animation callbacks present" state.
             //
@@ -1661,6 +2147,30 @@ impl Document {
         ident
     }
 
+    /// Has the video frame request callbacks of a video that presented a new frame run in the
+    /// next animation frame.
+    pub fn schedule_video_frame_callbacks(&self, video: &HTMLVideoElement) {
+        let mut videos = self.pending_video_frame_callbacks.borrow_mut();
+        if videos.iter().any(|pending| &**pending == video) {
+            return;
+        }
+        videos.push(Dom::from_ref(video));
+
+        if !self.running_animation_callbacks.get() {
+            let event =
+                ScriptMsg::ChangeRunningAnimationsState(AnimationState::AnimationCallbacksPresent);
+            self.window().send_to_constellation(event);
+        }
+    }
+
+    /// Runs the page visibility change steps after the window of this document was hidden or
+    /// shown, and fires `visibilitychange`.
+    /// <https://html.spec.whatwg.org/multipage/#update-the-visibility-state>
+    pub fn visibility_state_changed(&self) {
+        self.upcast::<EventTarget>()
+            .fire_bubbling_event(atom!("visibilitychange"));
+    }
+
     /// <https://html.spec.whatwg.org/multipage/#dom-window-cancelanimationframe>
     pub fn cancel_animation_frame(&self, ident: u32) {
         let mut list = self.animation_frame_list.borrow_mut();
@@ -1670,7 +2180,18 @@ impl Document {
     }
 
     /// <https://html.spec.whatwg.org/multipage/#run-the-animation-frame-callbacks>
-    pub fn run_the_animation_frame_callbacks(&self) {
+    ///
+    /// The callbacks are not run for a video refresh which the next one already followed by
+    /// the time they could be, because the previous frames took longer than their budget,
+    /// unless they were not run for the previous video refresh either.
+    pub fn run_the_animation_frame_callbacks(&self, timing: FrameTiming) {
+        let elapsed = time::precise_time_ns().saturating_sub(timing.vsync_time);
+        if elapsed > 2 * timing.frame_interval && !self.skipped_animation_frame.get() {
+            self.skipped_animation_frame.set(true);
+            return;
+        }
+        self.skipped_animation_frame.set(false);
+
         rooted_vec!(let mut animation_frame_list);
         mem::swap(
             &mut *animation_frame_list,
@@ -1678,72 +2199,54 @@ impl Document {
         );
 
         self.running_animation_callbacks.set(true);
-        let was_faking_animation_frames = self.is_faking_animation_frames();
-        let timing = self.global().performance().Now();
+        self.last_animation_frame.set(Some(timing));
+        // The documents ticked for the same video refresh all get its time, relative to their
+        // own time origin.
+        let now =
+            reduce_timing_resolution(self.global().performance().relative_time(timing.vsync_time));
+
+        // https://wicg.github.io/video-rvfc/#video-rvfc-procedures
+        let videos: Vec<DomRoot<HTMLVideoElement>> = self
+            .pending_video_frame_callbacks
+            .borrow_mut()
+            .drain(..)
+            .map(|video| DomRoot::from_ref(&*video))
+            .collect();
+        for video in videos {
+            video.run_video_frame_callbacks(*now, timing);
+        }
 
         for (_, callback) in animation_frame_list.drain(..) {
             if let Some(callback) = callback {
-                callback.call(self, *timing);
+                callback.call(self, *now);
             }
         }
 
         self.running_animation_callbacks.set(false);
 
-        let spurious = !self
-            .window
+        self.window
             .reflow(ReflowGoal::Full, ReflowReason::RequestAnimationFrame);
 
-        if spurious && !was_faking_animation_frames {
-            // If the rAF callbacks did not mutate the DOM, then the
-            // reflow call above means that layout will not be invoked,
-            // and therefore no new frame will be sent to the compositor.
-            // If this happens, the compositor will not tick the animation
-            // and the next rAF will never be called! When this happens
-            // for several frames, then the spurious rAF detection below
-            // will kick in and use a timer to tick the callbacks. However,
-            // for the interim frames where we are deciding whether this rAF
-            // is considered spurious, we need to ensure that the layout
-            // and compositor *do* tick the animation.
-            self.window
-                .force_reflow(ReflowGoal::Full, ReflowReason::RequestAnimationFrame);
-        }
-
         // Only send the animation change state message after running any callbacks.
         // This means that if the animation callback adds a new callback for
         // the next frame (which is the common case), we won't send a NoAnimationCallbacksPresent
         // message quickly followed by an AnimationCallbacksPresent message.
-        //
-        // If this frame was spurious and we've seen too many spurious frames in a row, tell the
-        // constellation to stop giving us video refresh callbacks, to save energy. (A spurious
-        // animation frame is one in which the callback did not mutate the DOM—that is, an
-        // animation frame that wasn't actually used for animation.)
-        let is_empty = self.animation_frame_list.borrow().is_empty();
-        if is_empty || (!was_faking_animation_frames && self.is_faking_animation_frames()) {
-            if is_empty {
-                // If the current animation frame list in the DOM instance is empty,
-                // we can reuse the original `Vec<T>` that we put on the stack to
-                // avoid allocating a new one next time an animation callback
-                // is queued.
-                mem::swap(
-                    &mut *self.animation_frame_list.borrow_mut(),
-                    &mut *animation_frame_list,
-                );
-            }
+        if self.animation_frame_list.borrow().is_empty() &&
+            self.pending_video_frame_callbacks.borrow().is_empty()
+        {
+            // If the current animation frame list in the DOM instance is empty,
+            // we can reuse the original `Vec<T>` that we put on the stack to
+            // avoid allocating a new one next time an animation callback
+            // is queued.
+            mem::swap(
+                &mut *self.animation_frame_list.borrow_mut(),
+                &mut *animation_frame_list,
+            );
             let event = ScriptMsg::ChangeRunningAnimationsState(
                 AnimationState::NoAnimationCallbacksPresent,
             );
             self.window().send_to_constellation(event);
         }
-
-        // Update the counter of spurious animation frames.
-        if spurious {
-            if self.spurious_animation_frames.get() < SPURIOUS_ANIMATION_FRAME_THRESHOLD {
-                self.spurious_animation_frames
-                    .set(self.spurious_animation_frames.get() + 1)
-            }
-        } else {
-            self.spurious_animation_frames.set(0)
-        }
     }
 
     pub fn fetch_async(
@@ -1757,6 +2260,17 @@ impl Document {
         loader.fetch_async(load, request, fetch_target);
     }
 
+    /// Initiates a fetch for the document that does not delay its load event.
+    pub fn fetch_async_background(
+        &self,
+        mut request: RequestBuilder,
+        fetch_target: IpcSender<FetchResponseMsg>,
+    ) {
+        request.csp_list = self.get_csp_list().map(|x| x.clone());
+        self.loader_mut()
+            .fetch_async_background(request, fetch_target);
+    }
+
     // https://html.spec.whatwg.org/multipage/#the-end
     // https://html.spec.whatwg.org/multipage/#delay-the-load-event
     pub fn finish_load(&self, load: LoadType) {
@@ -1805,7 +2319,7 @@ impl Document {
 
         // Servo measures when the top-level content (not iframes) is loaded.
         if (self.top_level_dom_complete.get() == 0) && loader.is_only_blocked_by_iframes() {
-            update_with_current_time_ms(&self.top_level_dom_complete);
+            update_with_current_time_ns(&self.top_level_dom_complete);
         }
 
         if loader.is_blocked() || loader.events_inhibited() {
@@ -1891,6 +2405,9 @@ impl Document {
             let _ = self.window.dispatch_event_with_target_override(&event);
             // TODO Step 6, document visibility steps.
         }
+        // A document which is unloaded can't keep the pointer locked or the screen on.
+        self.exit_pointer_lock();
+        self.release_wake_locks();
         // Step 7
         if !self.fired_unload.get() {
             let event = Event::new(
@@ -1994,7 +2511,7 @@ impl Document {
                     event.set_trusted(true);
 
                     // http://w3c.github.io/navigation-timing/#widl-PerformanceNavigationTiming-loadEventStart
-                    update_with_current_time_ms(&document.load_event_start);
+                    update_with_current_time_ns(&document.load_event_start);
 
                     debug!("About to dispatch load for {:?}", document.url());
                     // FIXME(nox): Why are errors silenced here?
@@ -2003,7 +2520,10 @@ impl Document {
                     );
 
                     // http://w3c.github.io/navigation-timing/#widl-PerformanceNavigationTiming-loadEventEnd
-                    update_with_current_time_ms(&document.load_event_end);
+                    update_with_current_time_ns(&document.load_event_end);
+                    window
+                        .Performance()
+                        .end_navigation_timing(document.load_event_end.get());
 
                     window.reflow(ReflowGoal::Full, ReflowReason::DocumentLoaded);
 
@@ -2215,7 +2735,7 @@ impl Document {
             "Complete before DOMContentLoaded?"
         );
 
-        update_with_current_time_ms(&self.dom_content_loaded_event_start);
+        update_with_current_time_ns(&self.dom_content_loaded_event_start);
 
         // Step 4.1.
         let window = self.window();
@@ -2227,7 +2747,7 @@ impl Document {
                 task!(fire_dom_content_loaded_event: move || {
                 let document = document.root();
                 document.upcast::<EventTarget>().fire_bubbling_event(atom!("DOMContentLoaded"));
-                update_with_current_time_ms(&document.dom_content_loaded_event_end);
+                update_with_current_time_ns(&document.dom_content_loaded_event_end);
                 }),
                 window.upcast(),
             )
@@ -2410,6 +2930,8 @@ impl Document {
         let (event_name, does_bubble) = match focus_event_type {
             FocusEventType::Focus => (DOMString::from("focus"), EventBubbles::DoesNotBubble),
             FocusEventType::Blur => (DOMString::from("blur"), EventBubbles::DoesNotBubble),
+            FocusEventType::FocusIn => (DOMString::from("focusin"), EventBubbles::Bubbles),
+            FocusEventType::FocusOut => (DOMString::from("focusout"), EventBubbles::Bubbles),
         };
         let event = FocusEvent::new(
             &self.window,
@@ -2637,6 +3159,19 @@ pub trait LayoutDocumentHelpers {
     unsafe fn shadow_roots(&self) -> Vec<LayoutDom<ShadowRoot>>;
     unsafe fn shadow_roots_styles_changed(&self) -> bool;
     unsafe fn flush_shadow_roots_stylesheets(&self);
+    unsafe fn editing_caret_for_layout(&self, text: LayoutDom<Text>) -> Option<usize>;
+    unsafe fn find_highlight_for_layout(
+        &self,
+        text: LayoutDom<Text>,
+    ) -> Option<std::ops::Range<usize>>;
+    unsafe fn custom_highlights_for_layout(
+        &self,
+        text: LayoutDom<Text>,
+    ) -> Vec<(Atom, std::ops::Range<usize>)>;
+    unsafe fn spelling_errors_for_layout(
+        &self,
+        node: LayoutDom<Node>,
+    ) -> Vec<std::ops::Range<usize>>;
 }
 
 #[allow(unsafe_code)]
@@ -2701,57 +3236,58 @@ impl LayoutDocumentHelpers for LayoutDom<Document> {
     unsafe fn flush_shadow_roots_stylesheets(&self) {
         (*self.unsafe_get()).flush_shadow_roots_stylesheets()
     }
-}
-
-// https://html.spec.whatwg.org/multipage/#is-a-registrable-domain-suffix-of-or-is-equal-to
-// The spec says to return a bool, we actually return an Option<Host> containing
-// the parsed host in the successful case, to avoid having to re-parse the host.
-fn get_registrable_domain_suffix_of_or_is_equal_to(
-    host_suffix_string: &str,
-    original_host: Host,
-) -> Option<Host> {
-    // Step 1
-    if host_suffix_string.is_empty() {
-        return None;
-    }
-
-    // Step 2-3.
-    let host = match Host::parse(host_suffix_string) {
-        Ok(host) => host,
-        Err(_) => return None,
-    };
-
-    // Step 4.
-    if host != original_host {
-        // Step 4.1
-        let host = match host {
-            Host::Domain(ref host) => host,
-            _ => return None,
-        };
-        let original_host = match original_host {
-            Host::Domain(ref original_host) => original_host,
-            _ => return None,
-        };
-
-        // Step 4.2
-        let index = original_host.len().checked_sub(host.len())?;
-        let (prefix, suffix) = original_host.split_at(index);
 
-        if !prefix.ends_with(".") {
-            return None;
-        }
-        if suffix != host {
-            return None;
+    #[inline]
+    #[allow(unrooted_must_root)]
+    unsafe fn editing_caret_for_layout(&self, text: LayoutDom<Text>) -> Option<usize> {
+        let document = &*self.unsafe_get();
+        match document.editing_caret.get_inner_as_layout() {
+            Some(caret) if caret == text => Some(document.editing_caret_offset.get()),
+            _ => None,
         }
+    }
 
-        // Step 4.3
-        if is_pub_domain(host) {
-            return None;
-        }
+    #[inline]
+    #[allow(unrooted_must_root)]
+    unsafe fn find_highlight_for_layout(
+        &self,
+        text: LayoutDom<Text>,
+    ) -> Option<std::ops::Range<usize>> {
+        (*self.unsafe_get())
+            .find_highlights
+            .borrow_for_layout()
+            .iter()
+            .filter_map(|highlight| highlight.range_for_layout(text))
+            .next()
     }
 
-    // Step 5
-    Some(host)
+    #[inline]
+    #[allow(unrooted_must_root)]
+    unsafe fn custom_highlights_for_layout(
+        &self,
+        text: LayoutDom<Text>,
+    ) -> Vec<(Atom, std::ops::Range<usize>)> {
+        (*self.unsafe_get())
+            .highlighted_text
+            .borrow_for_layout()
+            .iter()
+            .filter_map(|highlighted| highlighted.range_for_layout(text))
+            .collect()
+    }
+
+    #[inline]
+    #[allow(unrooted_must_root)]
+    unsafe fn spelling_errors_for_layout(
+        &self,
+        node: LayoutDom<Node>,
+    ) -> Vec<std::ops::Range<usize>> {
+        (*self.unsafe_get())
+            .spelling_errors
+            .borrow_for_layout()
+            .iter()
+            .filter_map(|error| error.range_for_layout(node))
+            .collect()
+    }
 }
 
 /// <https://url.spec.whatwg.org/#network-scheme>
@@ -2854,10 +3390,12 @@ impl Document {
             },
             stylesheets: DomRefCell::new(DocumentStylesheetSet::new()),
             stylesheet_list: MutNullableDom::new(None),
+            stylesheet_rules_changed: Cell::new(false),
             ready_state: Cell::new(ready_state),
             domcontentloaded_dispatched: Cell::new(domcontentloaded_dispatched),
             possibly_focused: Default::default(),
             focused: Default::default(),
+            pointer_modality: Cell::new(false),
             current_script: Default::default(),
             pending_parsing_blocking_script: Default::default(),
             script_blocking_stylesheets_count: Cell::new(0u32),
@@ -2867,7 +3405,10 @@ impl Document {
             scripting_enabled: has_browsing_context,
             animation_frame_ident: Cell::new(0),
             animation_frame_list: DomRefCell::new(vec![]),
+            pending_video_frame_callbacks: DomRefCell::new(vec![]),
             running_animation_callbacks: Cell::new(false),
+            last_animation_frame: Cell::new(None),
+            skipped_animation_frame: Cell::new(false),
             loader: DomRefCell::new(doc_loader),
             current_parser: Default::default(),
             reflow_timeout: Cell::new(None),
@@ -2887,6 +3428,7 @@ impl Document {
             unload_event_start: Cell::new(Default::default()),
             unload_event_end: Cell::new(Default::default()),
             https_state: Cell::new(HttpsState::None),
+            cross_origin_isolated: Cell::new(false),
             origin: origin,
             referrer: referrer,
             referrer_policy: Cell::new(referrer_policy),
@@ -2894,9 +3436,13 @@ impl Document {
             last_click_info: DomRefCell::new(None),
             ignore_destructive_writes_counter: Default::default(),
             ignore_opens_during_unload_counter: Default::default(),
-            spurious_animation_frames: Cell::new(0),
             dom_count: Cell::new(1),
             fullscreen_element: MutNullableDom::new(None),
+            pointer_lock_element: MutNullableDom::new(None),
+            picture_in_picture_element: MutNullableDom::new(None),
+            last_mouse_move_point: Cell::new(None),
+            wake_locks: Default::default(),
+            modal_dialogs: Default::default(),
             form_id_listener_map: Default::default(),
             interactive_time: DomRefCell::new(interactive_time),
             tti_window: DomRefCell::new(InteractiveWindow::new()),
@@ -2911,12 +3457,30 @@ impl Document {
             completely_loaded: Cell::new(false),
             script_and_layout_blockers: Cell::new(0),
             delayed_tasks: Default::default(),
+            prerendering: Cell::new(false),
+            post_prerendering_activation_tasks: Default::default(),
+            speculation_rule_sets: Default::default(),
+            prefetched_urls: Default::default(),
             shadow_roots: DomRefCell::new(HashSet::new()),
             shadow_roots_styles_changed: Cell::new(false),
             media_controls: DomRefCell::new(HashMap::new()),
             dirty_webgl_contexts: DomRefCell::new(HashMap::new()),
             csp_list: DomRefCell::new(None),
             selection: MutNullableDom::new(None),
+            editing_caret: MutNullableDom::new(None),
+            editing_caret_offset: Cell::new(0),
+            editing_history: Default::default(),
+            editing_composition: DomRefCell::new(None),
+            find_state: DomRefCell::new(None),
+            find_highlights: DomRefCell::new(vec![]),
+            highlight_registry: MutNullableDom::new(None),
+            highlights_changed: Cell::new(false),
+            highlighted_text: DomRefCell::new(vec![]),
+            spelling_errors: DomRefCell::new(vec![]),
+            spellcheck_requests: DomRefCell::new(vec![]),
+            has_password_field: Cell::new(false),
+            devtools_wants_mutations: Cell::new(false),
+            devtools_mutations: DomRefCell::new(vec![]),
         }
     }
 
@@ -2979,6 +3543,140 @@ impl Document {
         self.delayed_tasks.borrow_mut().push(Box::new(task));
     }
 
+    /// Whether the document is loaded for a navigation that did not happen yet, during which
+    /// the APIs that need the attention of the user are held back.
+    pub fn is_prerendering(&self) -> bool {
+        self.prerendering.get()
+    }
+
+    pub fn set_prerendering(&self, prerendering: bool) {
+        self.prerendering.set(prerendering);
+    }
+
+    /// Runs the task once the document is activated, or at once if it is not prerendered.
+    pub fn add_post_prerendering_activation_task<T: 'static + TaskBox>(&self, task: T) {
+        if self.is_prerendering() {
+            self.post_prerendering_activation_tasks
+                .borrow_mut()
+                .push(Box::new(task));
+        } else {
+            Box::new(task).run_box();
+        }
+    }
+
+    /// Runs the activation steps of a prerendered document once the browsing context
+    /// navigated to it.
+    /// <https://wicg.github.io/nav-speculation/prerendering.html#prerendering-traversable-activate>
+    pub fn activate_prerendered_document(&self) {
+        if !self.prerendering.replace(false) {
+            return;
+        }
+        self.upcast::<EventTarget>()
+            .fire_event(atom!("prerenderingchange"));
+        let tasks = mem::replace(
+            &mut *self.post_prerendering_activation_tasks.borrow_mut(),
+            vec![],
+        );
+        for task in tasks {
+            task.run_box();
+        }
+        // The embedder did not hear about the document while it was prerendered.
+        self.send_title_to_embedder();
+        self.consider_speculative_loads();
+    }
+
+    /// <https://wicg.github.io/nav-speculation/speculation-rules.html#register-speculation-rules>
+    pub fn register_speculation_rules(
+        &self,
+        script: &HTMLScriptElement,
+        rules: SpeculationRuleSet,
+    ) {
+        {
+            let mut rule_sets = self.speculation_rule_sets.borrow_mut();
+            rule_sets.retain(|&(ref other, _)| &**other != script);
+            // Keep the rule sets in tree order, so that the earlier scripts prerender first.
+            let index = rule_sets
+                .iter()
+                .position(|&(ref other, _)| {
+                    script.upcast::<Node>().is_before(other.upcast::<Node>())
+                })
+                .unwrap_or(rule_sets.len());
+            rule_sets.insert(index, (Dom::from_ref(script), rules));
+        }
+        self.consider_speculative_loads();
+    }
+
+    /// <https://wicg.github.io/nav-speculation/speculation-rules.html#unregister-speculation-rules>
+    pub fn unregister_speculation_rules(&self, script: &HTMLScriptElement) {
+        {
+            let mut rule_sets = self.speculation_rule_sets.borrow_mut();
+            let count = rule_sets.len();
+            rule_sets.retain(|&(ref other, _)| &**other != script);
+            if rule_sets.len() == count {
+                return;
+            }
+        }
+        self.consider_speculative_loads();
+    }
+
+    /// Prefetches the URLs that the speculation rules of the document newly list, and asks the
+    /// constellation to prerender the ones that they list now. Only the current document of a
+    /// top-level browsing context prerenders, and only documents of its own origin.
+    /// <https://wicg.github.io/nav-speculation/speculation-rules.html#consider-speculative-loads>
+    fn consider_speculative_loads(&self) {
+        if self.is_prerendering() || !self.window.is_top_level() || !self.is_fully_active() {
+            return;
+        }
+        let mut prefetch: Vec<ServoUrl> = vec![];
+        let mut prerender: Vec<ServoUrl> = vec![];
+        for &(_, ref rules) in self.speculation_rule_sets.borrow().iter() {
+            prefetch.extend(rules.prefetch.iter().cloned());
+            let same_origin = rules
+                .prerender
+                .iter()
+                .filter(|url| &url.origin() == self.origin().immutable());
+            prerender.extend(same_origin.cloned());
+        }
+
+        let origin = self.origin().immutable().clone();
+        let pipeline_id = self.window.pipeline_id();
+        let referrer = Referrer::ReferrerUrl(self.url());
+        for url in prefetch {
+            if prerender.contains(&url) || self.prefetched_urls.borrow().contains(&url) {
+                continue;
+            }
+            self.prefetched_urls.borrow_mut().push(url.clone());
+            let mut request = RequestBuilder::new(url)
+                .destination(Destination::Document)
+                .credentials_mode(CredentialsMode::CredentialsSameOrigin)
+                .initiator(Initiator::Prefetch)
+                .origin(origin.clone())
+                .pipeline_id(Some(pipeline_id))
+                .referrer(Some(referrer.clone()))
+                .referrer_policy(self.get_referrer_policy());
+            request.csp_list = self.get_csp_list().map(|x| x.clone());
+            let _ = self
+                .window
+                .upcast::<GlobalScope>()
+                .resource_threads()
+                .send(Fetch(request, FetchChannels::Prefetch));
+        }
+
+        let loads = prerender
+            .into_iter()
+            .map(|url| {
+                LoadData::new(
+                    LoadOrigin::Script(origin.clone()),
+                    url,
+                    Some(pipeline_id),
+                    Some(referrer.clone()),
+                    self.get_referrer_policy(),
+                )
+            })
+            .collect();
+        self.window.send_to_constellation(ScriptMsg::Prerender(loads));
+    }
+
     /// Assert that the DOM is in a state that will allow running content JS or
     /// performing a layout operation.
     pub fn ensure_safe_to_run_script_or_layout(&self) {
@@ -3132,6 +3830,7 @@ impl Document {
         let mut stylesheets = self.stylesheets.borrow_mut();
         let have_changed = stylesheets.has_changed();
         stylesheets.flush_without_invalidation();
+        self.stylesheet_rules_changed.set(false);
         have_changed
     }
 
@@ -3292,10 +3991,25 @@ impl Document {
             .set(self.ignore_opens_during_unload_counter.get() - 1);
     }
 
-    /// Whether we've seen so many spurious animation frames (i.e. animation frames that didn't
-    /// mutate the DOM) that we've decided to fall back to fake ones.
-    fn is_faking_animation_frames(&self) -> bool {
-        self.spurious_animation_frames.get() >= SPURIOUS_ANIMATION_FRAME_THRESHOLD
+    /// An estimate of the next rendering opportunity of this document after `now`, both in
+    /// milliseconds of `time::precise_time_ns()`, or `None` if there is no animation frame
+    /// callback that needs one.
+    ///
+    /// Animation frames are taken to keep following the video refresh that they were last run
+    /// for.
+    pub fn next_rendering_opportunity(&self, now: f64) -> Option<f64> {
+        if self.animation_frame_list.borrow().is_empty() {
+            return None;
+        }
+        let (last_frame, frame_interval) = match self.last_animation_frame.get() {
+            Some(timing) => (
+                timing.vsync_time as f64 / 1_000_000.,
+                timing.frame_interval as f64 / 1_000_000.,
+            ),
+            None => (now, ANIMATION_FRAME_INTERVAL),
+        };
+        let frames_since_last = ((now - last_frame) / frame_interval).floor().max(0.);
+        Some(last_frame + (frames_since_last + 1.) * frame_interval)
     }
 
     // https://fullscreen.spec.whatwg.org/#dom-element-requestfullscreen
@@ -3326,18 +4040,25 @@ impl Document {
         if !pending.fullscreen_element_ready_check() {
             error = true;
         }
+        // A prerendered document is not shown, so neither can its elements be.
+        if self.is_prerendering() {
+            error = true;
+        }
 
         if pref!(dom.fullscreen.test) {
             // For reftests we just take over the current window,
             // and don't try to really enter fullscreen.
             info!("Tests don't really enter fullscreen.");
-        } else {
-            // TODO fullscreen is supported
-            // TODO This algorithm is allowed to request fullscreen.
-            warn!("Fullscreen not supported yet");
+        } else if !self.window.has_transient_activation() {
+            error = true;
+        }
+
+        // Step 5
+        if !error {
+            self.window.consume_user_activation();
         }
 
-        // Step 5 Parallel start
+        // Parallel start
 
         let window = self.window();
         // Step 6
@@ -3410,6 +4131,114 @@ impl Document {
         self.fullscreen_element.set(element);
     }
 
+    pub fn picture_in_picture_element(&self) -> Option<DomRoot<HTMLVideoElement>> {
+        self.picture_in_picture_element.get()
+    }
+
+    pub fn set_picture_in_picture_element(&self, element: Option<&HTMLVideoElement>) {
+        self.picture_in_picture_element.set(element);
+    }
+
+    /// <https://w3c.github.io/pointerlock/#dom-element-requestpointerlock>
+    pub fn request_pointer_lock(&self, element: &Element) {
+        // TODO: Honour the allow-pointer-lock sandboxing flag.
+        let task_source = self.window.task_manager().dom_manipulation_task_source();
+        // Moving the lock within a document which already holds it needs no engagement gesture.
+        let has_engagement_gesture =
+            self.pointer_lock_element.get().is_some() || self.window.has_transient_activation();
+        if !element.upcast::<Node>().is_connected() ||
+            !self.is_fully_active() ||
+            !has_engagement_gesture
+        {
+            task_source.queue_simple_event(self.upcast(), atom!("pointerlockerror"), &self.window);
+            return;
+        }
+
+        // The element which already locked the pointer hands the lock over to this one.
+        if self.pointer_lock_element.get().is_none() {
+            self.window
+                .send_to_constellation(ScriptMsg::SetPointerLock(true));
+        }
+        self.pointer_lock_element.set(Some(element));
+        task_source.queue_simple_event(self.upcast(), atom!("pointerlockchange"), &self.window);
+    }
+
+    /// <https://w3c.github.io/pointerlock/#dom-document-exitpointerlock>
+    pub fn exit_pointer_lock(&self) {
+        if self.pointer_lock_element.get().is_none() {
+            return;
+        }
+        self.pointer_lock_element.set(None);
+        self.window
+            .send_to_constellation(ScriptMsg::SetPointerLock(false));
+        self.window
+            .task_manager()
+            .dom_manipulation_task_source()
+            .queue_simple_event(self.upcast(), atom!("pointerlockchange"), &self.window);
+    }
+
+    /// Adds a screen wake lock to the active locks of this document. The constellation asks
+    /// the embedder to keep the screen on while any document holds a lock.
+    pub fn acquire_wake_lock(&self, sentinel: &WakeLockSentinel) {
+        let mut wake_locks = self.wake_locks.borrow_mut();
+        if wake_locks.is_empty() {
+            self.window
+                .send_to_constellation(ScriptMsg::SetScreenWakeLock(true));
+        }
+        wake_locks.push(Dom::from_ref(sentinel));
+    }
+
+    pub fn remove_wake_lock(&self, sentinel: &WakeLockSentinel) {
+        let mut wake_locks = self.wake_locks.borrow_mut();
+        let had_wake_locks = !wake_locks.is_empty();
+        wake_locks.retain(|wake_lock| &**wake_lock != sentinel);
+        if had_wake_locks && wake_locks.is_empty() {
+            self.window
+                .send_to_constellation(ScriptMsg::SetScreenWakeLock(false));
+        }
+    }
+
+    /// Releases the screen wake locks of this document, when it is hidden or stops being fully
+    /// active.
+    /// <https://w3c.github.io/screen-wake-lock/#handling-document-loss-of-visibility>
+    pub fn release_wake_locks(&self) {
+        let wake_locks: Vec<_> = self
+            .wake_locks
+            .borrow()
+            .iter()
+            .map(|wake_lock| DomRoot::from_ref(&**wake_lock))
+            .collect();
+        for wake_lock in wake_locks {
+            wake_lock.release_wake_lock();
+        }
+    }
+
+    /// Adds `dialog` to the top layer, unless it is there already.
+    pub fn add_modal_dialog(&self, dialog: &HTMLDialogElement) {
+        let mut modal_dialogs = self.modal_dialogs.borrow_mut();
+        if !modal_dialogs
+            .iter()
+            .any(|modal_dialog| *modal_dialog == dialog)
+        {
+            modal_dialogs.push(Dom::from_ref(dialog));
+        }
+    }
+
+    pub fn remove_modal_dialog(&self, dialog: &HTMLDialogElement) {
+        self.modal_dialogs
+            .borrow_mut()
+            .retain(|modal_dialog| *modal_dialog != dialog);
+    }
+
+    /// The modal dialog that blocks this document, if any.
+    /// <https://html.spec.whatwg.org/multipage/#blocked-by-a-modal-dialog>
+    pub fn blocking_modal_dialog(&self) -> Option<DomRoot<HTMLDialogElement>> {
+        self.modal_dialogs
+            .borrow()
+            .last()
+            .map(|dialog| DomRoot::from_ref(&**dialog))
+    }
+
     pub fn get_allow_fullscreen(&self) -> bool {
         // https://html.spec.whatwg.org/multipage/#allowed-to-use
         match self.browsing_context() {
@@ -3469,8 +4298,13 @@ impl Document {
         self.shadow_roots_styles_changed.set(false);
     }
 
+    /// The number of stylesheets of the tree, which doesn't include the adopted stylesheets.
     pub fn stylesheet_count(&self) -> usize {
-        self.stylesheets.borrow().len()
+        self.stylesheets
+            .borrow()
+            .iter()
+            .filter(|(sheet, _origin)| sheet.owner.is_some())
+            .count()
     }
 
     pub fn stylesheet_at(&self, index: usize) -> Option<DomRoot<CSSStyleSheet>> {
@@ -3478,7 +4312,8 @@ impl Document {
 
         stylesheets
             .get(Origin::Author, index)
-            .and_then(|s| s.owner.upcast::<Node>().get_cssom_stylesheet())
+            .and_then(|s| s.owner.as_ref())
+            .and_then(|owner| owner.upcast::<Node>().get_cssom_stylesheet())
     }
 
     /// Add a stylesheet owned by `owner` to the list of document sheets, in the
@@ -3486,13 +4321,13 @@ impl Document {
     #[allow(unrooted_must_root)] // Owner needs to be rooted already necessarily.
     pub fn add_stylesheet(&self, owner: &Element, sheet: Arc<Stylesheet>) {
         let stylesheets = &mut *self.stylesheets.borrow_mut();
+        // The adopted stylesheets come after all the stylesheets of the tree.
         let insertion_point = stylesheets
             .iter()
             .map(|(sheet, _origin)| sheet)
-            .find(|sheet_in_doc| {
-                owner
-                    .upcast::<Node>()
-                    .is_before(sheet_in_doc.owner.upcast())
+            .find(|sheet_in_doc| match sheet_in_doc.owner {
+                Some(ref other_owner) => owner.upcast::<Node>().is_before(other_owner.upcast()),
+                None => true,
             })
             .cloned();
 
@@ -3505,7 +4340,7 @@ impl Document {
             .unwrap();
 
         DocumentOrShadowRoot::add_stylesheet(
-            owner,
+            Some(owner),
             StylesheetSetRef::Document(stylesheets),
             sheet,
             insertion_point,
@@ -3522,12 +4357,68 @@ impl Document {
             .unwrap();
 
         DocumentOrShadowRoot::remove_stylesheet(
-            owner,
+            Some(owner),
             s,
             StylesheetSetRef::Document(&mut *self.stylesheets.borrow_mut()),
         )
     }
 
+    /// Replaces the adopted stylesheets of the document after `adoptedStyleSheets` was set.
+    /// They are appended after the stylesheets of the tree.
+    fn replace_adopted_stylesheets(&self, previous_sheets: &[DomRoot<CSSStyleSheet>]) {
+        let owner = StyleSheetListOwner::Document(Dom::from_ref(self));
+        let sheets = self.document_or_shadow_root.adopted_stylesheets();
+        for sheet in previous_sheets {
+            sheet.remove_adopter(&owner);
+        }
+        for sheet in &sheets {
+            sheet.add_adopter(owner.clone());
+        }
+
+        let layout_chan = self.window.layout_chan();
+        for sheet in DocumentOrShadowRoot::unique_stylesheets(previous_sheets) {
+            layout_chan
+                .send(Msg::RemoveStylesheet(sheet.clone()))
+                .unwrap();
+            DocumentOrShadowRoot::remove_stylesheet(
+                None,
+                &sheet,
+                StylesheetSetRef::Document(&mut *self.stylesheets.borrow_mut()),
+            );
+        }
+
+        for sheet in DocumentOrShadowRoot::unique_stylesheets(&sheets) {
+            layout_chan
+                .send(Msg::AddStylesheet(sheet.clone(), None))
+                .unwrap();
+            DocumentOrShadowRoot::add_stylesheet(
+                None,
+                StylesheetSetRef::Document(&mut *self.stylesheets.borrow_mut()),
+                sheet,
+                None,
+                self.style_shared_lock(),
+            );
+        }
+    }
+
+    /// Tells layout that `rule` was inserted into or removed from `sheet`, or that its
+    /// declarations changed, if `sheet` applies to the document.
+    pub fn stylesheet_rule_changed(&self, sheet: &Arc<Stylesheet>, rule: &CssRule) {
+        let applies = self
+            .stylesheets
+            .borrow()
+            .iter()
+            .any(|(sheet_in_doc, _origin)| Arc::ptr_eq(&sheet_in_doc.sheet, sheet));
+        if !applies {
+            return;
+        }
+        self.window
+            .layout_chan()
+            .send(Msg::StylesheetRuleChanged(sheet.clone(), rule.clone()))
+            .unwrap();
+        self.stylesheet_rules_changed.set(true);
+    }
+
     // https://html.spec.whatwg.org/multipage/#dom-tree-accessors:determine-the-value-of-a-named-property
     // Support method for steps 1-3:
     // Count if there are 0, 1, or >1 elements that match the name.
@@ -3641,6 +4532,20 @@ impl DocumentMethods for Document {
         })
     }
 
+    // https://drafts.csswg.org/cssom/#dom-documentorshadowroot-adoptedstylesheets
+    fn AdoptedStyleSheets(&self, cx: JSContext) -> JSVal {
+        self.document_or_shadow_root.get_adopted_stylesheets(cx)
+    }
+
+    // https://drafts.csswg.org/cssom/#dom-documentorshadowroot-adoptedstylesheets
+    fn SetAdoptedStyleSheets(&self, cx: JSContext, value: HandleValue) -> ErrorResult {
+        let previous_sheets = self
+            .document_or_shadow_root
+            .set_adopted_stylesheets(cx, value, self)?;
+        self.replace_adopted_stylesheets(&previous_sheets);
+        Ok(())
+    }
+
     // https://dom.spec.whatwg.org/#dom-document-implementation
     fn Implementation(&self) -> DomRoot<DOMImplementation> {
         self.implementation.or_init(|| DOMImplementation::new(self))
@@ -3660,6 +4565,20 @@ impl DocumentMethods for Document {
         )
     }
 
+    // https://html.spec.whatwg.org/multipage/#dom-document-hidden
+    fn Hidden(&self) -> bool {
+        self.VisibilityState() == DocumentVisibilityState::Hidden
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-document-visibilitystate
+    fn VisibilityState(&self) -> DocumentVisibilityState {
+        if self.window.visible() {
+            DocumentVisibilityState::Visible
+        } else {
+            DocumentVisibilityState::Hidden
+        }
+    }
+
     // https://html.spec.whatwg.org/multipage/#dom-document-hasfocus
     fn HasFocus(&self) -> bool {
         // Step 1-2.
@@ -3670,6 +4589,36 @@ impl DocumentMethods for Document {
         false
     }
 
+    // https://w3c.github.io/editing/docs/execCommand/#execcommand()
+    fn ExecCommand(&self, command_id: DOMString, _show_ui: bool, value: DOMString) -> bool {
+        editing::exec_command(self, &command_id, value)
+    }
+
+    // https://w3c.github.io/editing/docs/execCommand/#querycommandenabled()
+    fn QueryCommandEnabled(&self, command_id: DOMString) -> bool {
+        editing::query_command_enabled(self, &command_id)
+    }
+
+    // https://w3c.github.io/editing/docs/execCommand/#querycommandindeterm()
+    fn QueryCommandIndeterm(&self, command_id: DOMString) -> bool {
+        editing::query_command_indeterm(self, &command_id)
+    }
+
+    // https://w3c.github.io/editing/docs/execCommand/#querycommandstate()
+    fn QueryCommandState(&self, command_id: DOMString) -> bool {
+        editing::query_command_state(self, &command_id)
+    }
+
+    // https://w3c.github.io/editing/docs/execCommand/#querycommandsupported()
+    fn QueryCommandSupported(&self, command_id: DOMString) -> bool {
+        editing::query_command_supported(&command_id)
+    }
+
+    // https://w3c.github.io/editing/docs/execCommand/#querycommandvalue()
+    fn QueryCommandValue(&self, command_id: DOMString) -> DOMString {
+        editing::query_command_value(self, &command_id)
+    }
+
     // https://html.spec.whatwg.org/multipage/#dom-document-domain
     fn Domain(&self) -> DOMString {
         // Step 1.
@@ -4101,6 +5050,36 @@ impl DocumentMethods for Document {
         TreeWalker::new(self, root, what_to_show, filter)
     }
 
+    // https://dom.spec.whatwg.org/#dom-xpathevaluatorbase-createexpression
+    fn CreateExpression(
+        &self,
+        expression: DOMString,
+        resolver: Option<Rc<XPathNSResolver>>,
+    ) -> Fallible<DomRoot<XPathExpression>> {
+        XPathExpression::create(&self.window, expression, resolver)
+    }
+
+    // https://dom.spec.whatwg.org/#dom-xpathevaluatorbase-creatensresolver
+    fn CreateNSResolver(&self, node_resolver: &Node) -> DomRoot<Node> {
+        DomRoot::from_ref(node_resolver)
+    }
+
+    // https://dom.spec.whatwg.org/#dom-xpathevaluatorbase-evaluate
+    fn Evaluate(
+        &self,
+        expression: DOMString,
+        context_node: &Node,
+        resolver: Option<Rc<XPathNSResolver>>,
+        type_: u16,
+        result: Option<&XPathResult>,
+    ) -> Fallible<DomRoot<XPathResult>> {
+        XPathExpression::create(&self.window, expression, resolver)?.Evaluate(
+            context_node,
+            type_,
+            result,
+        )
+    }
+
     // https://html.spec.whatwg.org/multipage/#document.title
     fn Title(&self) -> DOMString {
         let title = self.GetDocumentElement().and_then(|root| {
@@ -4540,9 +5519,17 @@ impl DocumentMethods for Document {
         SetOnreadystatechange
     );
 
+    // https://html.spec.whatwg.org/multipage/#handler-onvisibilitychange
+    event_handler!(
+        visibilitychange,
+        GetOnvisibilitychange,
+        SetOnvisibilitychange
+    );
+
     // https://drafts.csswg.org/cssom-view/#dom-document-elementfrompoint
     fn ElementFromPoint(&self, x: Finite<f64>, y: Finite<f64>) -> Option<DomRoot<Element>> {
         self.document_or_shadow_root.element_from_point(
+            self.upcast(),
             x,
             y,
             self.GetDocumentElement(),
@@ -4553,6 +5540,7 @@ impl DocumentMethods for Document {
     // https://drafts.csswg.org/cssom-view/#dom-document-elementsfrompoint
     fn ElementsFromPoint(&self, x: Finite<f64>, y: Finite<f64>) -> Vec<DomRoot<Element>> {
         self.document_or_shadow_root.elements_from_point(
+            self.upcast(),
             x,
             y,
             self.GetDocumentElement(),
@@ -4560,6 +5548,20 @@ impl DocumentMethods for Document {
         )
     }
 
+    // https://drafts.csswg.org/cssom-view/#dom-document-caretpositionfrompoint
+    fn CaretPositionFromPoint(
+        &self,
+        x: Finite<f64>,
+        y: Finite<f64>,
+    ) -> Option<DomRoot<CaretPosition>> {
+        self.document_or_shadow_root.caret_position_from_point(
+            self.upcast(),
+            x,
+            y,
+            self.has_browsing_context,
+        )
+    }
+
     // https://html.spec.whatwg.org/multipage/#dom-document-open
     fn Open(
         &self,
@@ -4803,6 +5805,57 @@ impl DocumentMethods for Document {
         self.exit_fullscreen()
     }
 
+    // https://w3c.github.io/pointerlock/#dom-documentorshadowroot-pointerlockelement
+    fn GetPointerLockElement(&self) -> Option<DomRoot<Element>> {
+        // TODO ShadowRoot
+        self.pointer_lock_element.get()
+    }
+
+    // https://w3c.github.io/pointerlock/#dom-document-exitpointerlock
+    fn ExitPointerLock(&self) {
+        self.exit_pointer_lock()
+    }
+
+    // https://w3c.github.io/picture-in-picture/#dom-document-pictureinpictureenabled
+    fn PictureInPictureEnabled(&self) -> bool {
+        true
+    }
+
+    // https://w3c.github.io/picture-in-picture/#dom-document-exitpictureinpicture
+    fn ExitPictureInPicture(&self) -> Rc<Promise> {
+        let promise = Promise::new(&self.global());
+        match self.picture_in_picture_element.get() {
+            // Step 1.
+            None => promise.reject_error(Error::InvalidState),
+            // Steps 2-3.
+            Some(video) => {
+                video.exit_picture_in_picture();
+                promise.resolve_native(&());
+            },
+        }
+        promise
+    }
+
+    // https://w3c.github.io/picture-in-picture/#dom-documentorshadowroot-pictureinpictureelement
+    fn GetPictureInPictureElement(&self) -> Option<DomRoot<Element>> {
+        self.document_or_shadow_root
+            .get_picture_in_picture_element(self.upcast())
+    }
+
+    // https://w3c.github.io/pointerlock/#dom-document-onpointerlockchange
+    event_handler!(
+        pointerlockchange,
+        GetOnpointerlockchange,
+        SetOnpointerlockchange
+    );
+
+    // https://w3c.github.io/pointerlock/#dom-document-onpointerlockerror
+    event_handler!(
+        pointerlockerror,
+        GetOnpointerlockerror,
+        SetOnpointerlockerror
+    );
+
     // check-tidy: no specs after this line
     // Servo only API to get an instance of the controls of a specific
     // media element matching the given id.
@@ -4821,13 +5874,23 @@ impl DocumentMethods for Document {
             None
         }
     }
+
+    // https://wicg.github.io/nav-speculation/prerendering.html#dom-document-prerendering
+    fn Prerendering(&self) -> bool {
+        self.is_prerendering()
+    }
+
+    // https://wicg.github.io/nav-speculation/prerendering.html#dom-document-onprerenderingchange
+    event_handler!(
+        prerenderingchange,
+        GetOnprerenderingchange,
+        SetOnprerenderingchange
+    );
 }
 
-fn update_with_current_time_ms(marker: &Cell<u64>) {
+fn update_with_current_time_ns(marker: &Cell<u64>) {
     if marker.get() == 0 {
-        let time = time::get_time();
-        let current_time_ms = time.sec * 1000 + time.nsec as i64 / 1000000;
-        marker.set(current_time_ms as u64);
+        marker.set(time::precise_time_ns());
     }
 }
 
@@ -4856,28 +5919,57 @@ pub enum FocusType {
 
 /// Focus events
 pub enum FocusEventType {
-    Focus, // Element gained focus. Doesn't bubble.
-    Blur,  // Element lost focus. Doesn't bubble.
+    Focus,    // Element gained focus. Doesn't bubble.
+    Blur,     // Element lost focus. Doesn't bubble.
+    FocusIn,  // Element gained focus. Bubbles.
+    FocusOut, // Element lost focus. Bubbles.
 }
 
-/// A fake `requestAnimationFrame()` callback—"fake" because it is not triggered by the video
-/// refresh but rather a simple timer.
-///
-/// If the page is observed to be using `requestAnimationFrame()` for non-animation purposes (i.e.
-/// without mutating the DOM), then we fall back to simple timeouts to save energy over video
-/// refresh.
-#[derive(JSTraceable, MallocSizeOf)]
-pub struct FakeRequestAnimationFrameCallback {
-    /// The document.
-    #[ignore_malloc_size_of = "non-owning"]
-    document: Trusted<Document>,
+/// The direction in which sequential focus navigation moves the focus.
+#[derive(Clone, Copy, PartialEq)]
+pub enum SequentialFocusDirection {
+    Forward,  // Tab
+    Backward, // Shift+Tab
 }
 
-impl FakeRequestAnimationFrameCallback {
-    pub fn invoke(self) {
-        let document = self.document.root();
-        document.run_the_animation_frame_callbacks();
+/// Appends the elements of the focus navigation scope of `scope` to `order`, in the sequential
+/// focus navigation order: positive tabindex values first, in ascending order, then the rest
+/// in tree order. The contents of a shadow tree follow its host.
+/// <https://html.spec.whatwg.org/multipage/#flattened-tabindex-ordered-focus-navigation-scope>
+fn sequential_focus_navigation_order(scope: &Node, order: &mut Vec<DomRoot<Element>>) {
+    let mut positive = vec![];
+    let mut rest = vec![];
+    for node in scope.traverse_preorder(ShadowIncluding::No).skip(1) {
+        let element = match node.downcast::<Element>() {
+            Some(element) => element,
+            None => continue,
+        };
+        let tab_index = element.tab_index();
+        let mut entry = vec![];
+        // Hosts that delegate focus are skipped in favour of their shadow tree, and elements
+        // with a negative tabindex and elements that are not rendered are skipped altogether.
+        let delegates_focus = element
+            .shadow_root()
+            .map_or(false, |shadow_root| shadow_root.delegates_focus());
+        if !delegates_focus &&
+            element.is_focusable_area() &&
+            tab_index.map_or(true, |tab_index| tab_index >= 0) &&
+            node.bounding_content_box().is_some()
+        {
+            entry.push(DomRoot::from_ref(element));
+        }
+        if let Some(shadow_root) = element.shadow_root() {
+            sequential_focus_navigation_order(shadow_root.upcast(), &mut entry);
+        }
+        match tab_index {
+            Some(tab_index) if tab_index > 0 => positive.push((tab_index, entry)),
+            _ => rest.push(entry),
+        }
     }
+    // The sort is stable, so elements with the same tabindex stay in tree order.
+    positive.sort_by_key(|&(tab_index, _)| tab_index);
+    order.extend(positive.into_iter().flat_map(|(_, entry)| entry));
+    order.extend(rest.into_iter().flatten());
 }
 
 #[derive(JSTraceable, MallocSizeOf)]
@@ -3,19 +3,33 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::NodeBinding::GetRootNodeOptions;
 use crate::dom::bindings::codegen::Bindings::NodeBinding::NodeBinding::NodeMethods;
+use crate::dom::bindings::error::{Error, Fallible};
 use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::num::Finite;
 use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::utils::to_frozen_array;
+use crate::dom::caretposition::CaretPosition;
+use crate::dom::characterdata::CharacterData;
+use crate::dom::cssstylesheet::CSSStyleSheet;
+use crate::dom::document::Document;
 use crate::dom::element::Element;
 use crate::dom::htmlelement::HTMLElement;
 use crate::dom::htmlmetaelement::HTMLMetaElement;
 use crate::dom::node::{self, Node, VecPreOrderInsertionHelper};
+use crate::dom::text::Text;
 use crate::dom::window::Window;
+use crate::editing;
+use crate::script_runtime::JSContext;
 use crate::stylesheet_set::StylesheetSetRef;
 use euclid::default::Point2D;
-use js::jsapi::JS_GetRuntime;
+use js::conversions::{ConversionResult, FromJSValConvertible};
+use js::jsapi::{Heap, JS_GetRuntime};
+use js::jsval::JSVal;
+use js::rust::HandleValue;
 use script_layout_interface::message::{NodesFromPointQueryType, QueryMsg};
+use script_layout_interface::rpc::TextIndexResponse;
 use script_traits::UntrustedNodeAddress;
 use servo_arc::Arc;
 use servo_atoms::Atom;
@@ -32,7 +46,9 @@ use style::stylesheets::{CssRule, Origin, Stylesheet};
 pub struct StyleSheetInDocument {
     #[ignore_malloc_size_of = "Arc"]
     pub sheet: Arc<Stylesheet>,
-    pub owner: Dom<Element>,
+    /// The element whose stylesheet this is, or `None` for the constructed stylesheets adopted
+    /// by the document or shadow root, which come after the stylesheets of the tree.
+    pub owner: Option<Dom<Element>>,
 }
 
 impl fmt::Debug for StyleSheetInDocument {
@@ -80,12 +96,19 @@ impl ::style::stylesheets::StylesheetInDocument for StyleSheetInDocument {
 #[derive(JSTraceable, MallocSizeOf)]
 pub struct DocumentOrShadowRoot {
     window: Dom<Window>,
+    /// <https://drafts.csswg.org/cssom/#dom-documentorshadowroot-adoptedstylesheets>
+    adopted_stylesheets: DomRefCell<Vec<Dom<CSSStyleSheet>>>,
+    /// The frozen array returned by `adoptedStyleSheets` until it is set again.
+    #[ignore_malloc_size_of = "mozjs"]
+    frozen_adopted_stylesheets: DomRefCell<Option<Heap<JSVal>>>,
 }
 
 impl DocumentOrShadowRoot {
     pub fn new(window: &Window) -> Self {
         Self {
             window: Dom::from_ref(window),
+            adopted_stylesheets: DomRefCell::new(Vec::new()),
+            frozen_adopted_stylesheets: DomRefCell::new(None),
         }
     }
 
@@ -104,10 +127,46 @@ impl DocumentOrShadowRoot {
         self.window.layout().nodes_from_point_response()
     }
 
+    /// The elements hit at `point`, topmost first, with text standing for its parent
+    /// element and each element retargeted against `context`. Inert elements are not hit.
     #[allow(unsafe_code)]
+    fn hit_elements(
+        &self,
+        context: &Node,
+        point: &Point2D<f32>,
+        query_type: NodesFromPointQueryType,
+    ) -> Vec<DomRoot<Element>> {
+        let js_runtime = unsafe { JS_GetRuntime(*self.window.get_cx()) };
+        let mut elements: Vec<DomRoot<Element>> = vec![];
+        for address in self.nodes_from_point(point, query_type) {
+            let node = unsafe { node::from_untrusted_node_address(js_runtime, address) };
+            let node = if node.is::<Element>() {
+                node
+            } else {
+                match node.GetParentNode() {
+                    Some(parent) => parent,
+                    None => continue,
+                }
+            };
+            if node
+                .downcast::<Element>()
+                .map_or(false, |element| element.is_inert())
+            {
+                continue;
+            }
+            if let Some(element) = DomRoot::downcast::<Element>(node.retarget(context)) {
+                if !elements.contains(&element) {
+                    elements.push(element);
+                }
+            }
+        }
+        elements
+    }
+
     // https://drafts.csswg.org/cssom-view/#dom-document-elementfrompoint
     pub fn element_from_point(
         &self,
+        context: &Node,
         x: Finite<f64>,
         y: Finite<f64>,
         document_element: Option<DomRoot<Element>>,
@@ -126,28 +185,22 @@ impl DocumentOrShadowRoot {
             return None;
         }
 
-        match self
-            .nodes_from_point(point, NodesFromPointQueryType::Topmost)
-            .first()
-        {
-            Some(address) => {
-                let js_runtime = unsafe { JS_GetRuntime(*self.window.get_cx()) };
-                let node = unsafe { node::from_untrusted_node_address(js_runtime, *address) };
-                let parent_node = node.GetParentNode().unwrap();
-                let element_ref = node
-                    .downcast::<Element>()
-                    .unwrap_or_else(|| parent_node.downcast::<Element>().unwrap());
-
-                Some(DomRoot::from_ref(element_ref))
-            },
-            None => document_element,
-        }
+        // The topmost node may be inert, in which case the element below it is hit.
+        self.hit_elements(context, point, NodesFromPointQueryType::Topmost)
+            .into_iter()
+            .next()
+            .or_else(|| {
+                self.hit_elements(context, point, NodesFromPointQueryType::All)
+                    .into_iter()
+                    .next()
+            })
+            .or(document_element)
     }
 
-    #[allow(unsafe_code)]
     // https://drafts.csswg.org/cssom-view/#dom-document-elementsfrompoint
     pub fn elements_from_point(
         &self,
+        context: &Node,
         x: Finite<f64>,
         y: Finite<f64>,
         document_element: Option<DomRoot<Element>>,
@@ -167,19 +220,8 @@ impl DocumentOrShadowRoot {
             return vec![];
         }
 
-        let js_runtime = unsafe { JS_GetRuntime(*self.window.get_cx()) };
-
         // Step 1 and Step 3
-        let nodes = self.nodes_from_point(point, NodesFromPointQueryType::All);
-        let mut elements: Vec<DomRoot<Element>> = nodes
-            .iter()
-            .flat_map(|&untrusted_node_address| {
-                let node = unsafe {
-                    node::from_untrusted_node_address(js_runtime, untrusted_node_address)
-                };
-                DomRoot::downcast::<Element>(node)
-            })
-            .collect();
+        let mut elements = self.hit_elements(context, point, NodesFromPointQueryType::All);
 
         // Step 4
         if let Some(root_element) = document_element {
@@ -192,6 +234,66 @@ impl DocumentOrShadowRoot {
         elements
     }
 
+    #[allow(unsafe_code)]
+    // https://drafts.csswg.org/cssom-view/#dom-document-caretpositionfrompoint
+    pub fn caret_position_from_point(
+        &self,
+        context: &Node,
+        x: Finite<f64>,
+        y: Finite<f64>,
+        has_browsing_context: bool,
+    ) -> Option<DomRoot<CaretPosition>> {
+        let x = *x as f32;
+        let y = *y as f32;
+        let point = &Point2D::new(x, y);
+        let viewport = self.window.window_size().initial_viewport;
+
+        if !has_browsing_context {
+            return None;
+        }
+
+        if x < 0.0 || y < 0.0 || x > viewport.width || y > viewport.height {
+            return None;
+        }
+
+        // Step 1
+        let address = *self
+            .nodes_from_point(point, NodesFromPointQueryType::Topmost)
+            .first()?;
+        let js_runtime = unsafe { JS_GetRuntime(*self.window.get_cx()) };
+        let node = unsafe { node::from_untrusted_node_address(js_runtime, address) };
+
+        // Step 2. Text resolves to the character boundary nearest to the point; layout
+        // measures text in document coordinates.
+        let offset = match node.downcast::<Text>() {
+            Some(text) => {
+                let scroll_origin = self.window.current_viewport().origin;
+                let document_point = Point2D::new(
+                    x + scroll_origin.x.to_f32_px(),
+                    y + scroll_origin.y.to_f32_px(),
+                );
+                let TextIndexResponse(index) =
+                    self.window.text_index_at_point_query(&node, document_point);
+                let data = text.upcast::<CharacterData>().data();
+                index.map_or(0, |index| editing::utf8_to_utf16_offset(&data, index))
+            },
+            None => 0,
+        };
+
+        // A position inside a shadow tree hidden from `context` becomes the one before
+        // the shadow host.
+        let retargeted = node.retarget(context);
+        if retargeted != node {
+            let parent = retargeted.GetParentNode()?;
+            return Some(CaretPosition::new(
+                &self.window,
+                &parent,
+                retargeted.index(),
+            ));
+        }
+        Some(CaretPosition::new(&self.window, &node, offset))
+    }
+
     // https://html.spec.whatwg.org/multipage/#dom-document-activeelement
     pub fn get_active_element(
         &self,
@@ -211,10 +313,28 @@ impl DocumentOrShadowRoot {
         }
     }
 
-    /// Remove a stylesheet owned by `owner` from the list of document sheets.
+    /// The video in a floating window, retargeted against `context`, if it is in the same tree.
+    /// <https://w3c.github.io/picture-in-picture/#dom-documentorshadowroot-pictureinpictureelement>
+    pub fn get_picture_in_picture_element(&self, context: &Node) -> Option<DomRoot<Element>> {
+        let video = context.owner_doc().picture_in_picture_element()?;
+
+        // Step 2.
+        let candidate = video.upcast::<Node>().retarget(context);
+
+        // Step 3.
+        if &*candidate.GetRootNode(&GetRootNodeOptions::empty()) == context {
+            return DomRoot::downcast(candidate);
+        }
+
+        // Step 4.
+        None
+    }
+
+    /// Remove a stylesheet owned by `owner`, or an adopted stylesheet, from the list of document
+    /// sheets.
     #[allow(unrooted_must_root)] // Owner needs to be rooted already necessarily.
     pub fn remove_stylesheet(
-        owner: &Element,
+        owner: Option<&Element>,
         s: &Arc<Stylesheet>,
         mut stylesheets: StylesheetSetRef<StyleSheetInDocument>,
     ) {
@@ -225,17 +345,17 @@ impl DocumentOrShadowRoot {
             None,
             StyleSheetInDocument {
                 sheet: s.clone(),
-                owner: Dom::from_ref(owner),
+                owner: owner.map(Dom::from_ref),
             },
             &guard,
         );
     }
 
-    /// Add a stylesheet owned by `owner` to the list of document sheets, in the
-    /// correct tree position.
+    /// Add a stylesheet owned by `owner`, or an adopted stylesheet, to the list of document
+    /// sheets, in the correct tree position.
     #[allow(unrooted_must_root)] // Owner needs to be rooted already necessarily.
     pub fn add_stylesheet(
-        owner: &Element,
+        owner: Option<&Element>,
         mut stylesheets: StylesheetSetRef<StyleSheetInDocument>,
         sheet: Arc<Stylesheet>,
         insertion_point: Option<StyleSheetInDocument>,
@@ -245,13 +365,14 @@ impl DocumentOrShadowRoot {
         // that own stylesheets, but StylesheetOwner is more about loading
         // them...
         debug_assert!(
-            owner.as_stylesheet_owner().is_some() || owner.is::<HTMLMetaElement>(),
+            owner.map_or(true, |owner| owner.as_stylesheet_owner().is_some() ||
+                owner.is::<HTMLMetaElement>()),
             "Wat"
         );
 
         let sheet = StyleSheetInDocument {
             sheet,
-            owner: Dom::from_ref(owner),
+            owner: owner.map(Dom::from_ref),
         };
 
         let guard = style_shared_lock.read();
@@ -266,6 +387,75 @@ impl DocumentOrShadowRoot {
         }
     }
 
+    pub fn adopted_stylesheets(&self) -> Vec<DomRoot<CSSStyleSheet>> {
+        self.adopted_stylesheets
+            .borrow()
+            .iter()
+            .map(|sheet| DomRoot::from_ref(&**sheet))
+            .collect()
+    }
+
+    /// The stylesheets of a list of adopted stylesheets which apply, in order. A stylesheet
+    /// which was adopted several times only applies once, at its first position.
+    pub fn unique_stylesheets(sheets: &[DomRoot<CSSStyleSheet>]) -> Vec<Arc<Stylesheet>> {
+        let mut unique_sheets: Vec<Arc<Stylesheet>> = vec![];
+        for sheet in sheets {
+            let sheet = sheet.style_stylesheet();
+            if !unique_sheets.iter().any(|other| Arc::ptr_eq(other, sheet)) {
+                unique_sheets.push(sheet.clone());
+            }
+        }
+        unique_sheets
+    }
+
+    /// <https://drafts.csswg.org/cssom/#dom-documentorshadowroot-adoptedstylesheets>
+    pub fn get_adopted_stylesheets(&self, cx: JSContext) -> JSVal {
+        if let Some(sheets) = &*self.frozen_adopted_stylesheets.borrow() {
+            return sheets.get();
+        }
+
+        let sheets = self.adopted_stylesheets();
+        let frozen_sheets = to_frozen_array(sheets.as_slice(), cx);
+
+        // Safety: need to create the Heap value in its final memory location before setting it.
+        *self.frozen_adopted_stylesheets.borrow_mut() = Some(Heap::default());
+        self.frozen_adopted_stylesheets
+            .borrow()
+            .as_ref()
+            .unwrap()
+            .set(frozen_sheets);
+
+        frozen_sheets
+    }
+
+    /// Sets the adopted stylesheets from the value given to `adoptedStyleSheets`, and returns
+    /// the previous ones. Only the stylesheets constructed in `document` can be adopted.
+    /// <https://drafts.csswg.org/cssom/#dom-documentorshadowroot-adoptedstylesheets>
+    #[allow(unsafe_code)]
+    pub fn set_adopted_stylesheets(
+        &self,
+        cx: JSContext,
+        value: HandleValue,
+        document: &Document,
+    ) -> Fallible<Vec<DomRoot<CSSStyleSheet>>> {
+        let conversion = unsafe { Vec::<DomRoot<CSSStyleSheet>>::from_jsval(*cx, value, ()) };
+        let sheets = match conversion {
+            Ok(ConversionResult::Success(sheets)) => sheets,
+            Ok(ConversionResult::Failure(error)) => return Err(Error::Type(error.into())),
+            Err(()) => return Err(Error::JSFailed),
+        };
+
+        if !sheets.iter().all(|sheet| sheet.is_constructed_in(document)) {
+            return Err(Error::NotAllowed);
+        }
+
+        let previous_sheets = self.adopted_stylesheets();
+        *self.adopted_stylesheets.borrow_mut() =
+            sheets.iter().map(|sheet| Dom::from_ref(&**sheet)).collect();
+        *self.frozen_adopted_stylesheets.borrow_mut() = None;
+        Ok(previous_sheets)
+    }
+
     /// Remove any existing association between the provided id/name and any elements in this document.
     pub fn unregister_named_element(
         &self,
@@ -38,6 +38,8 @@ pub enum DOMErrorName {
     DataCloneError = DOMExceptionConstants::DATA_CLONE_ERR,
     NotReadableError,
     OperationError,
+    NotAllowedError,
+    DataError,
 }
 
 impl DOMErrorName {
@@ -67,6 +69,8 @@ impl DOMErrorName {
             "DataCloneError" => Some(DOMErrorName::DataCloneError),
             "NotReadableError" => Some(DOMErrorName::NotReadableError),
             "OperationError" => Some(DOMErrorName::OperationError),
+            "NotAllowedError" => Some(DOMErrorName::NotAllowedError),
+            "DataError" => Some(DOMErrorName::DataError),
             _ => None,
         }
     }
@@ -114,6 +118,10 @@ impl DOMException {
             DOMErrorName::OperationError => {
                 "The operation failed for an operation-specific reason."
             },
+            DOMErrorName::NotAllowedError => {
+                "The operation is not allowed in the current context or state."
+            },
+            DOMErrorName::DataError => "The provided data is inadequate.",
         };
 
         (
@@ -7,21 +7,32 @@ use crate::dom::bindings::codegen::Bindings::DOMParserBinding;
 use crate::dom::bindings::codegen::Bindings::DOMParserBinding::DOMParserMethods;
 use crate::dom::bindings::codegen::Bindings::DOMParserBinding::SupportedType::Application_xhtml_xml;
 use crate::dom::bindings::codegen::Bindings::DOMParserBinding::SupportedType::Application_xml;
+use crate::dom::bindings::codegen::Bindings::DOMParserBinding::SupportedType::Image_svg_xml;
 use crate::dom::bindings::codegen::Bindings::DOMParserBinding::SupportedType::Text_html;
 use crate::dom::bindings::codegen::Bindings::DOMParserBinding::SupportedType::Text_xml;
+use crate::dom::bindings::codegen::Bindings::DocumentBinding::DocumentMethods;
 use crate::dom::bindings::codegen::Bindings::DocumentBinding::DocumentReadyState;
+use crate::dom::bindings::codegen::Bindings::NodeBinding::NodeMethods;
 use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
 use crate::dom::bindings::error::Fallible;
+use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
 use crate::dom::bindings::root::{Dom, DomRoot};
 use crate::dom::bindings::str::DOMString;
 use crate::dom::document::DocumentSource;
 use crate::dom::document::{Document, HasBrowsingContext, IsHTMLDocument};
+use crate::dom::element::{CustomElementCreationMode, Element, ElementCreator};
+use crate::dom::node::Node;
 use crate::dom::servoparser::ServoParser;
 use crate::dom::window::Window;
 use dom_struct::dom_struct;
+use html5ever::{LocalName, Namespace, QualName};
 use script_traits::DocumentActivity;
 
+/// The namespace of the element that replaces the content of XML documents that are not
+/// well-formed.
+const PARSER_ERROR_NAMESPACE: &str = "http://www.mozilla.org/newlayout/xml/parsererror.xml";
+
 #[dom_struct]
 pub struct DOMParser {
     reflector_: Reflector,
@@ -46,6 +57,27 @@ impl DOMParser {
     }
 }
 
+/// Replaces the content of a document that is not well-formed with a `parsererror` element
+/// that describes the error.
+/// <https://w3c.github.io/DOM-Parsing/#dom-domparser-parsefromstring>
+fn replace_with_parser_error(document: &Document, error: &str) {
+    let name = QualName::new(
+        None,
+        Namespace::from(PARSER_ERROR_NAMESPACE),
+        LocalName::from("parsererror"),
+    );
+    let root = Element::create(
+        name,
+        None,
+        document,
+        ElementCreator::ScriptCreated,
+        CustomElementCreationMode::Synchronous,
+    );
+    root.upcast::<Node>()
+        .SetTextContent(Some(DOMString::from(error)));
+    Node::replace_all(Some(root.upcast()), document.upcast());
+}
+
 impl DOMParserMethods for DOMParser {
     // https://w3c.github.io/DOM-Parsing/#the-domparser-interface
     fn ParseFromString(
@@ -81,7 +113,7 @@ impl DOMParserMethods for DOMParser {
                 document.set_ready_state(DocumentReadyState::Complete);
                 Ok(document)
             },
-            Text_xml | Application_xml | Application_xhtml_xml => {
+            Text_xml | Application_xml | Application_xhtml_xml | Image_svg_xml => {
                 let document = Document::new(
                     &self.window,
                     HasBrowsingContext::No,
@@ -97,7 +129,13 @@ impl DOMParserMethods for DOMParser {
                     None,
                     Default::default(),
                 );
-                ServoParser::parse_xml_document(&document, s, url);
+                let mut result = ServoParser::parse_complete_xml_document(&document, s, url);
+                if result.is_ok() && document.GetDocumentElement().is_none() {
+                    result = Err("no root element found".to_owned());
+                }
+                if let Err(error) = result {
+                    replace_with_parser_error(&document, &error);
+                }
                 document.set_ready_state(DocumentReadyState::Complete);
                 Ok(document)
             },
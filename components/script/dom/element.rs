@@ -9,14 +9,16 @@ use crate::dom::attr::{Attr, AttrHelpersForLayout};
 use crate::dom::bindings::cell::{ref_filter_map, DomRefCell, Ref, RefMut};
 use crate::dom::bindings::codegen::Bindings::AttrBinding::AttrMethods;
 use crate::dom::bindings::codegen::Bindings::DocumentBinding::DocumentMethods;
-use crate::dom::bindings::codegen::Bindings::ElementBinding::ElementMethods;
+use crate::dom::bindings::codegen::Bindings::ElementBinding::{
+    ElementMethods, ScrollLogicalPosition, ShadowRootInit,
+};
 use crate::dom::bindings::codegen::Bindings::FunctionBinding::Function;
 use crate::dom::bindings::codegen::Bindings::HTMLTemplateElementBinding::HTMLTemplateElementMethods;
 use crate::dom::bindings::codegen::Bindings::NodeBinding::NodeMethods;
 use crate::dom::bindings::codegen::Bindings::ShadowRootBinding::ShadowRootBinding::ShadowRootMethods;
 use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
 use crate::dom::bindings::codegen::Bindings::WindowBinding::{ScrollBehavior, ScrollToOptions};
-use crate::dom::bindings::codegen::UnionTypes::NodeOrString;
+use crate::dom::bindings::codegen::UnionTypes::{BooleanOrScrollIntoViewOptions, NodeOrString};
 use crate::dom::bindings::conversions::DerivedFrom;
 use crate::dom::bindings::error::{Error, ErrorResult, Fallible};
 use crate::dom::bindings::inheritance::{Castable, ElementTypeId, HTMLElementTypeId, NodeTypeId};
@@ -76,13 +78,16 @@ use crate::dom::promise::Promise;
 use crate::dom::raredata::ElementRareData;
 use crate::dom::servoparser::ServoParser;
 use crate::dom::shadowroot::{IsUserAgentWidget, ShadowRoot};
+use crate::dom::stylepropertymapreadonly::StylePropertyMapReadOnly;
 use crate::dom::text::Text;
 use crate::dom::validation::Validatable;
 use crate::dom::virtualmethods::{vtable_for, VirtualMethods};
 use crate::dom::window::ReflowReason;
+use crate::dom::xmlserializer::{serialize_xml, serialize_xml_children};
 use crate::script_thread::ScriptThread;
 use crate::stylesheet_loader::StylesheetOwner;
 use crate::task::TaskOnce;
+use app_units::Au;
 use devtools_traits::AttrInfo;
 use dom_struct::dom_struct;
 use html5ever::serialize;
@@ -93,7 +98,7 @@ use html5ever::{LocalName, Namespace, Prefix, QualName};
 use js::jsapi::Heap;
 use js::jsval::JSVal;
 use msg::constellation_msg::InputMethodType;
-use net_traits::request::CorsSettings;
+use net_traits::request::{CorsSettings, RequestPriority};
 use net_traits::ReferrerPolicy;
 use script_layout_interface::message::ReflowGoal;
 use selectors::attr::{AttrSelectorOperation, CaseSensitivity, NamespaceConstraint};
@@ -110,7 +115,7 @@ use std::mem;
 use std::rc::Rc;
 use std::str::FromStr;
 use style::applicable_declarations::ApplicableDeclarationBlock;
-use style::attr::{AttrValue, LengthOrPercentageOrAuto};
+use style::attr::{parse_integer, AttrValue, LengthOrPercentageOrAuto};
 use style::context::QuirksMode;
 use style::dom_apis;
 use style::element_state::ElementState;
@@ -131,11 +136,6 @@ use style::thread_state;
 use style::values::generics::NonNegative;
 use style::values::{computed, specified, CSSFloat};
 use style::CaseSensitivityExt;
-use xml5ever::serialize as xmlSerialize;
-use xml5ever::serialize::SerializeOpts as XmlSerializeOpts;
-use xml5ever::serialize::TraversalScope as XmlTraversalScope;
-use xml5ever::serialize::TraversalScope::ChildrenOnly as XmlChildrenOnly;
-use xml5ever::serialize::TraversalScope::IncludeNode as XmlIncludeNode;
 
 // TODO: Update focus state when the top-level browsing context gains or loses system focus,
 // and when the element enters or leaves a browsing context container.
@@ -409,7 +409,7 @@ impl Element {
     }
 
     // https://drafts.csswg.org/cssom-view/#scrolling-box
-    fn has_scrolling_box(&self) -> bool {
+    pub fn has_scrolling_box(&self) -> bool {
         // TODO: scrolling mechanism, such as scrollbar (We don't have scrollbar yet)
         //       self.has_scrolling_mechanism()
         self.has_any_hidden_overflow()
@@ -459,7 +459,11 @@ impl Element {
     /// https://dom.spec.whatwg.org/#dom-element-attachshadow
     /// XXX This is not exposed to web content yet. It is meant to be used
     ///     for UA widgets only.
-    pub fn attach_shadow(&self, is_ua_widget: IsUserAgentWidget) -> Fallible<DomRoot<ShadowRoot>> {
+    pub fn attach_shadow(
+        &self,
+        is_ua_widget: IsUserAgentWidget,
+        delegates_focus: bool,
+    ) -> Fallible<DomRoot<ShadowRoot>> {
         // Step 1.
         if self.namespace != ns!(html) {
             return Err(Error::NotSupported);
@@ -496,7 +500,7 @@ impl Element {
         }
 
         // Steps 4, 5 and 6.
-        let shadow_root = ShadowRoot::new(self, &*self.node.owner_doc());
+        let shadow_root = ShadowRoot::new(self, &*self.node.owner_doc(), delegates_focus);
         self.ensure_rare_data().shadow_root = Some(Dom::from_ref(&*shadow_root));
         shadow_root
             .upcast::<Node>()
@@ -1244,22 +1248,6 @@ impl Element {
         }
     }
 
-    #[allow(non_snake_case)]
-    pub fn xmlSerialize(&self, traversal_scope: XmlTraversalScope) -> Fallible<DOMString> {
-        let mut writer = vec![];
-        match xmlSerialize::serialize(
-            &mut writer,
-            &self.upcast::<Node>(),
-            XmlSerializeOpts {
-                traversal_scope: traversal_scope,
-                ..Default::default()
-            },
-        ) {
-            Ok(()) => Ok(DOMString::from(String::from_utf8(writer).unwrap())),
-            Err(_) => panic!("Cannot serialize element"),
-        }
-    }
-
     pub fn root_element(&self) -> DomRoot<Element> {
         if self.node.is_in_doc() {
             self.upcast::<Node>()
@@ -1311,6 +1299,11 @@ impl Element {
             input.input_type().as_ime_type()
         } else if self.is::<HTMLTextAreaElement>() {
             Some(InputMethodType::Text)
+        } else if self
+            .downcast::<HTMLElement>()
+            .map_or(false, |element| element.is_editing_host())
+        {
+            Some(InputMethodType::Text)
         } else {
             // Other focusable elements that are not input fields.
             None
@@ -1318,7 +1311,7 @@ impl Element {
     }
 
     pub fn is_focusable_area(&self) -> bool {
-        if self.is_actually_disabled() {
+        if self.is_actually_disabled() || self.is_inert() {
             return false;
         }
         // TODO: Check whether the element is being rendered (i.e. not hidden).
@@ -1344,6 +1337,52 @@ impl Element {
         }
     }
 
+    /// Whether this element is inert, because it is in a subtree with the inert attribute or
+    /// outside the modal dialog that blocks its document. Inert elements can't be focused or
+    /// clicked and are hidden from hit testing and assistive technology.
+    /// <https://html.spec.whatwg.org/multipage/#inert>
+    pub fn is_inert(&self) -> bool {
+        let node = self.upcast::<Node>();
+        if let Some(dialog) = node.owner_doc().blocking_modal_dialog() {
+            let is_in_dialog = node
+                .inclusive_ancestors(ShadowIncluding::Yes)
+                .any(|ancestor| &*ancestor == dialog.upcast::<Node>());
+            if !is_in_dialog {
+                return true;
+            }
+        }
+        let inert = LocalName::from("inert");
+        node.inclusive_ancestors(ShadowIncluding::Yes)
+            .filter_map(DomRoot::downcast::<Element>)
+            .any(|element| element.has_attribute(&inert))
+    }
+
+    /// The first focusable area in the shadow tree of this element, if its shadow root
+    /// delegates focus.
+    /// <https://html.spec.whatwg.org/multipage/#focus-delegate>
+    pub fn focus_delegate(&self) -> Option<DomRoot<Element>> {
+        let shadow_root = self.shadow_root()?;
+        if !shadow_root.delegates_focus() {
+            return None;
+        }
+        shadow_root
+            .upcast::<Node>()
+            .traverse_preorder(ShadowIncluding::No)
+            .filter_map(DomRoot::downcast::<Element>)
+            .find_map(|element| match element.focus_delegate() {
+                Some(delegate) => Some(delegate),
+                None if element.is_focusable_area() => Some(element),
+                None => None,
+            })
+    }
+
+    /// The value of the tabindex attribute, if it is a valid integer.
+    /// <https://html.spec.whatwg.org/multipage/#attr-tabindex>
+    pub fn tab_index(&self) -> Option<i32> {
+        self.get_attribute(&ns!(), &local_name!("tabindex"))
+            .and_then(|attr| parse_integer(attr.value().chars()).ok())
+    }
+
     pub fn is_actually_disabled(&self) -> bool {
         let node = self.upcast::<Node>();
         match node.type_id() {
@@ -1656,6 +1695,20 @@ impl Element {
         self.set_attribute(local_name, AttrValue::String(value.into()));
     }
 
+    pub fn get_nullable_string_attribute(&self, local_name: &LocalName) -> Option<DOMString> {
+        self.get_attribute(&ns!(), local_name)
+            .map(|attr| attr.Value())
+    }
+
+    pub fn set_nullable_string_attribute(&self, local_name: &LocalName, value: Option<DOMString>) {
+        match value {
+            Some(value) => self.set_string_attribute(local_name, value),
+            None => {
+                self.remove_attribute(&ns!(), local_name);
+            },
+        }
+    }
+
     pub fn get_tokenlist_attribute(&self, local_name: &LocalName) -> Vec<Atom> {
         self.get_attribute(&ns!(), local_name)
             .map(|attr| attr.value().as_tokens().to_vec())
@@ -1800,6 +1853,129 @@ impl Element {
         win.scroll_node(node, x, y, behavior);
     }
 
+    /// <https://drafts.csswg.org/cssom-view/#scroll-an-element-into-view>
+    ///
+    /// FIXME: `block` and `inline` are taken to be the vertical and horizontal axes, whatever
+    /// the writing mode is.
+    fn scroll_into_view(
+        &self,
+        behavior: ScrollBehavior,
+        block: ScrollLogicalPosition,
+        inline: ScrollLogicalPosition,
+    ) {
+        let style = match self.style() {
+            Some(style) => style,
+            None => return,
+        };
+
+        // The border box of the element grown by its scroll margin, as ranges along the
+        // horizontal and vertical axes. The box is positioned as if none of the scrolling boxes
+        // were scrolled, and moved by the scroll offsets of the boxes it is scrolled into view of.
+        let rect = self.upcast::<Node>().bounding_content_box_or_zero();
+        let margin = style.get_margin();
+        let mut target_x = (
+            rect.origin.x.to_f64_px() - margin.scroll_margin_left.px() as f64,
+            rect.max_x().to_f64_px() + margin.scroll_margin_right.px() as f64,
+        );
+        let mut target_y = (
+            rect.origin.y.to_f64_px() - margin.scroll_margin_top.px() as f64,
+            rect.max_y().to_f64_px() + margin.scroll_margin_bottom.px() as f64,
+        );
+
+        // Scroll the scrolling boxes around the element, from the innermost to the outermost.
+        let root_element = self.root_element();
+        for ancestor in self
+            .upcast::<Node>()
+            .inclusive_ancestors(ShadowIncluding::Yes)
+            .skip(1)
+            .filter_map(DomRoot::downcast::<Element>)
+        {
+            if ancestor == root_element || !ancestor.has_scrolling_box() {
+                continue;
+            }
+            let ancestor_style = match ancestor.style() {
+                Some(style) => style,
+                None => continue,
+            };
+            let node = ancestor.upcast::<Node>();
+            let border_box = node.bounding_content_box_or_zero();
+            let padding_box = node.client_rect();
+            let origin_x = border_box.origin.x.to_f64_px() + padding_box.origin.x as f64;
+            let origin_y = border_box.origin.y.to_f64_px() + padding_box.origin.y as f64;
+            let width = padding_box.size.width as f64;
+            let height = padding_box.size.height as f64;
+
+            let scroll_x = ancestor.ScrollLeft();
+            let scroll_y = ancestor.ScrollTop();
+            let padding = ancestor_style.get_padding();
+            let scrollport_x = (
+                scroll_x + used_scroll_padding(&padding.scroll_padding_left, width),
+                scroll_x + width - used_scroll_padding(&padding.scroll_padding_right, width),
+            );
+            let scrollport_y = (
+                scroll_y + used_scroll_padding(&padding.scroll_padding_top, height),
+                scroll_y + height - used_scroll_padding(&padding.scroll_padding_bottom, height),
+            );
+
+            let scroll_area = node.scroll_area();
+            let max_scroll_x = (scroll_area.size.width as f64 - width).max(0.0);
+            let max_scroll_y = (scroll_area.size.height as f64 - height).max(0.0);
+            let new_scroll_x = aligned_scroll_position(
+                inline,
+                (target_x.0 - origin_x, target_x.1 - origin_x),
+                scrollport_x,
+                scroll_x,
+            )
+            .min(max_scroll_x)
+            .max(0.0);
+            let new_scroll_y = aligned_scroll_position(
+                block,
+                (target_y.0 - origin_y, target_y.1 - origin_y),
+                scrollport_y,
+                scroll_y,
+            )
+            .min(max_scroll_y)
+            .max(0.0);
+            if new_scroll_x != scroll_x || new_scroll_y != scroll_y {
+                ancestor.scroll(new_scroll_x, new_scroll_y, behavior);
+            }
+
+            target_x = (target_x.0 - new_scroll_x, target_x.1 - new_scroll_x);
+            target_y = (target_y.0 - new_scroll_y, target_y.1 - new_scroll_y);
+        }
+
+        // Then scroll the viewport, taking the scroll padding from the root element.
+        let window = window_from_node(self);
+        let viewport = window.current_viewport();
+        let scroll_x = viewport.origin.x.to_f64_px();
+        let scroll_y = viewport.origin.y.to_f64_px();
+        let width = viewport.size.width.to_f64_px();
+        let height = viewport.size.height.to_f64_px();
+        let (scrollport_x, scrollport_y) = match root_element.style() {
+            Some(root_style) => {
+                let padding = root_style.get_padding();
+                (
+                    (
+                        scroll_x + used_scroll_padding(&padding.scroll_padding_left, width),
+                        scroll_x + width -
+                            used_scroll_padding(&padding.scroll_padding_right, width),
+                    ),
+                    (
+                        scroll_y + used_scroll_padding(&padding.scroll_padding_top, height),
+                        scroll_y + height -
+                            used_scroll_padding(&padding.scroll_padding_bottom, height),
+                    ),
+                )
+            },
+            None => ((scroll_x, scroll_x + width), (scroll_y, scroll_y + height)),
+        };
+        window.scroll(
+            aligned_scroll_position(inline, target_x, scrollport_x, scroll_x),
+            aligned_scroll_position(block, target_y, scrollport_y, scroll_y),
+            behavior,
+        );
+    }
+
     // https://w3c.github.io/DOM-Parsing/#parsing
     pub fn parse_fragment(&self, markup: DOMString) -> Fallible<DomRoot<DocumentFragment>> {
         // Steps 1-2.
@@ -2193,6 +2369,33 @@ impl ElementMethods for Element {
         )
     }
 
+    // https://drafts.csswg.org/cssom-view/#dom-element-scrollintoview
+    fn ScrollIntoView(&self, arg: BooleanOrScrollIntoViewOptions) {
+        // Steps 1-3
+        let (behavior, block, inline) = match arg {
+            BooleanOrScrollIntoViewOptions::ScrollIntoViewOptions(options) => {
+                (options.parent.behavior, options.block, options.inline)
+            },
+            BooleanOrScrollIntoViewOptions::Boolean(align_to_top) => (
+                ScrollBehavior::Auto,
+                if align_to_top {
+                    ScrollLogicalPosition::Start
+                } else {
+                    ScrollLogicalPosition::End
+                },
+                ScrollLogicalPosition::Nearest,
+            ),
+        };
+
+        // Step 4
+        if !self.has_css_layout_box() {
+            return;
+        }
+
+        // Step 5
+        self.scroll_into_view(behavior, block, inline);
+    }
+
     // https://drafts.csswg.org/cssom-view/#dom-element-scroll
     fn Scroll(&self, options: &ScrollToOptions) {
         // Step 1
@@ -2466,7 +2669,7 @@ impl ElementMethods for Element {
         if document_from_node(self).is_html_document() {
             return self.serialize(ChildrenOnly(Some(qname)));
         } else {
-            return self.xmlSerialize(XmlChildrenOnly(Some(qname)));
+            return serialize_xml_children(self, true);
         }
     }
 
@@ -2490,7 +2693,7 @@ impl ElementMethods for Element {
         if document_from_node(self).is_html_document() {
             return self.serialize(IncludeNode);
         } else {
-            return self.xmlSerialize(XmlIncludeNode);
+            return serialize_xml(self.upcast(), true);
         }
     }
 
@@ -2727,12 +2930,265 @@ impl ElementMethods for Element {
         doc.enter_fullscreen(self)
     }
 
+    // https://w3c.github.io/pointerlock/#dom-element-requestpointerlock
+    fn RequestPointerLock(&self) {
+        let doc = document_from_node(self);
+        doc.request_pointer_lock(self);
+    }
+
+    // https://drafts.css-houdini.org/css-typed-om-1/#dom-element-computedstylemap
+    fn ComputedStyleMap(&self) -> DomRoot<StylePropertyMapReadOnly> {
+        let window = window_from_node(self);
+        let declaration = window.GetComputedStyle(self, None);
+        StylePropertyMapReadOnly::from_declaration(window.upcast(), &declaration)
+    }
+
     // XXX Hidden under dom.shadowdom.enabled pref. Only exposed to be able
     //     to test partial Shadow DOM support for UA widgets.
     // https://dom.spec.whatwg.org/#dom-element-attachshadow
-    fn AttachShadow(&self) -> Fallible<DomRoot<ShadowRoot>> {
-        self.attach_shadow(IsUserAgentWidget::No)
+    fn AttachShadow(&self, init: &ShadowRootInit) -> Fallible<DomRoot<ShadowRoot>> {
+        self.attach_shadow(IsUserAgentWidget::No, init.delegatesFocus)
     }
+
+    // https://w3c.github.io/aria/#dom-ariamixin-role
+    make_nullable_getter!(GetRole, "role");
+
+    // https://w3c.github.io/aria/#dom-ariamixin-role
+    make_nullable_setter!(SetRole, "role");
+
+    // https://w3c.github.io/aria/#dom-ariamixin-ariaatomic
+    make_nullable_getter!(GetAriaAtomic, "aria-atomic");
+
+    // https://w3c.github.io/aria/#dom-ariamixin-ariaatomic
+    make_nullable_setter!(SetAriaAtomic, "aria-atomic");
+
+    // https://w3c.github.io/aria/#dom-ariamixin-ariaautocomplete
+    make_nullable_getter!(GetAriaAutoComplete, "aria-autocomplete");
+
+    // https://w3c.github.io/aria/#dom-ariamixin-ariaautocomplete
+    make_nullable_setter!(SetAriaAutoComplete, "aria-autocomplete");
+
+    // https://w3c.github.io/aria/#dom-ariamixin-ariabusy
+    make_nullable_getter!(GetAriaBusy, "aria-busy");
+
+    // https://w3c.github.io/aria/#dom-ariamixin-ariabusy
+    make_nullable_setter!(SetAriaBusy, "aria-busy");
+
+    // https://w3c.github.io/aria/#dom-ariamixin-ariachecked
+    make_nullable_getter!(GetAriaChecked, "aria-checked");
+
+    // https://w3c.github.io/aria/#dom-ariamixin-ariachecked
+    make_nullable_setter!(SetAriaChecked, "aria-checked");
+
+    // https://w3c.github.io/aria/#dom-ariamixin-ariacolcount
+    make_nullable_getter!(GetAriaColCount, "aria-colcount");
+
+    // https://w3c.github.io/aria/#dom-ariamixin-ariacolcount
+    make_nullable_setter!(SetAriaColCount, "aria-colcount");
+
+    // https://w3c.github.io/aria/#dom-ariamixin-ariacolindex
+    make_nullable_getter!(GetAriaColIndex, "aria-colindex");
+
+    // https://w3c.github.io/aria/#dom-ariamixin-ariacolindex
+    make_nullable_setter!(SetAriaColIndex, "aria-colindex");
+
+    // https://w3c.github.io/aria/#dom-ariamixin-ariacolspan
+    make_nullable_getter!(GetAriaColSpan, "aria-colspan");
+
+    // https://w3c.github.io/aria/#dom-ariamixin-ariacolspan
+    make_nullable_setter!(SetAriaColSpan, "aria-colspan");
+
+    // https://w3c.github.io/aria/#dom-ariamixin-ariacurrent
+    make_nullable_getter!(GetAriaCurrent, "aria-current");
+
+    // https://w3c.github.io/aria/#dom-ariamixin-ariacurrent
+    make_nullable_setter!(SetAriaCurrent, "aria-current");
+
+    // https://w3c.github.io/aria/#dom-ariamixin-ariadescription
+    make_nullable_getter!(GetAriaDescription, "aria-description");
+
+    // https://w3c.github.io/aria/#dom-ariamixin-ariadescription
+    make_nullable_setter!(SetAriaDescription, "aria-description");
+
+    // https://w3c.github.io/aria/#dom-ariamixin-ariadisabled
+    make_nullable_getter!(GetAriaDisabled, "aria-disabled");
+
+    // https://w3c.github.io/aria/#dom-ariamixin-ariadisabled
+    make_nullable_setter!(SetAriaDisabled, "aria-disabled");
+
+    // https://w3c.github.io/aria/#dom-ariamixin-ariaexpanded
+    make_nullable_getter!(GetAriaExpanded, "aria-expanded");
+
+    // https://w3c.github.io/aria/#dom-ariamixin-ariaexpanded
+    make_nullable_setter!(SetAriaExpanded, "aria-expanded");
+
+    // https://w3c.github.io/aria/#dom-ariamixin-ariahaspopup
+    make_nullable_getter!(GetAriaHasPopup, "aria-haspopup");
+
+    // https://w3c.github.io/aria/#dom-ariamixin-ariahaspopup
+    make_nullable_setter!(SetAriaHasPopup, "aria-haspopup");
+
+    // https://w3c.github.io/aria/#dom-ariamixin-ariahidden
+    make_nullable_getter!(GetAriaHidden, "aria-hidden");
+
+    // https://w3c.github.io/aria/#dom-ariamixin-ariahidden
+    make_nullable_setter!(SetAriaHidden, "aria-hidden");
+
+    // https://w3c.github.io/aria/#dom-ariamixin-ariainvalid
+    make_nullable_getter!(GetAriaInvalid, "aria-invalid");
+
+    // https://w3c.github.io/aria/#dom-ariamixin-ariainvalid
+    make_nullable_setter!(SetAriaInvalid, "aria-invalid");
+
+    // https://w3c.github.io/aria/#dom-ariamixin-ariakeyshortcuts
+    make_nullable_getter!(GetAriaKeyShortcuts, "aria-keyshortcuts");
+
+    // https://w3c.github.io/aria/#dom-ariamixin-ariakeyshortcuts
+    make_nullable_setter!(SetAriaKeyShortcuts, "aria-keyshortcuts");
+
+    // https://w3c.github.io/aria/#dom-ariamixin-arialabel
+    make_nullable_getter!(GetAriaLabel, "aria-label");
+
+    // https://w3c.github.io/aria/#dom-ariamixin-arialabel
+    make_nullable_setter!(SetAriaLabel, "aria-label");
+
+    // https://w3c.github.io/aria/#dom-ariamixin-arialevel
+    make_nullable_getter!(GetAriaLevel, "aria-level");
+
+    // https://w3c.github.io/aria/#dom-ariamixin-arialevel
+    make_nullable_setter!(SetAriaLevel, "aria-level");
+
+    // https://w3c.github.io/aria/#dom-ariamixin-arialive
+    make_nullable_getter!(GetAriaLive, "aria-live");
+
+    // https://w3c.github.io/aria/#dom-ariamixin-arialive
+    make_nullable_setter!(SetAriaLive, "aria-live");
+
+    // https://w3c.github.io/aria/#dom-ariamixin-ariamodal
+    make_nullable_getter!(GetAriaModal, "aria-modal");
+
+    // https://w3c.github.io/aria/#dom-ariamixin-ariamodal
+    make_nullable_setter!(SetAriaModal, "aria-modal");
+
+    // https://w3c.github.io/aria/#dom-ariamixin-ariamultiline
+    make_nullable_getter!(GetAriaMultiLine, "aria-multiline");
+
+    // https://w3c.github.io/aria/#dom-ariamixin-ariamultiline
+    make_nullable_setter!(SetAriaMultiLine, "aria-multiline");
+
+    // https://w3c.github.io/aria/#dom-ariamixin-ariamultiselectable
+    make_nullable_getter!(GetAriaMultiSelectable, "aria-multiselectable");
+
+    // https://w3c.github.io/aria/#dom-ariamixin-ariamultiselectable
+    make_nullable_setter!(SetAriaMultiSelectable, "aria-multiselectable");
+
+    // https://w3c.github.io/aria/#dom-ariamixin-ariaorientation
+    make_nullable_getter!(GetAriaOrientation, "aria-orientation");
+
+    // https://w3c.github.io/aria/#dom-ariamixin-ariaorientation
+    make_nullable_setter!(SetAriaOrientation, "aria-orientation");
+
+    // https://w3c.github.io/aria/#dom-ariamixin-ariaplaceholder
+    make_nullable_getter!(GetAriaPlaceholder, "aria-placeholder");
+
+    // https://w3c.github.io/aria/#dom-ariamixin-ariaplaceholder
+    make_nullable_setter!(SetAriaPlaceholder, "aria-placeholder");
+
+    // https://w3c.github.io/aria/#dom-ariamixin-ariaposinset
+    make_nullable_getter!(GetAriaPosInSet, "aria-posinset");
+
+    // https://w3c.github.io/aria/#dom-ariamixin-ariaposinset
+    make_nullable_setter!(SetAriaPosInSet, "aria-posinset");
+
+    // https://w3c.github.io/aria/#dom-ariamixin-ariapressed
+    make_nullable_getter!(GetAriaPressed, "aria-pressed");
+
+    // https://w3c.github.io/aria/#dom-ariamixin-ariapressed
+    make_nullable_setter!(SetAriaPressed, "aria-pressed");
+
+    // https://w3c.github.io/aria/#dom-ariamixin-ariareadonly
+    make_nullable_getter!(GetAriaReadOnly, "aria-readonly");
+
+    // https://w3c.github.io/aria/#dom-ariamixin-ariareadonly
+    make_nullable_setter!(SetAriaReadOnly, "aria-readonly");
+
+    // https://w3c.github.io/aria/#dom-ariamixin-ariarelevant
+    make_nullable_getter!(GetAriaRelevant, "aria-relevant");
+
+    // https://w3c.github.io/aria/#dom-ariamixin-ariarelevant
+    make_nullable_setter!(SetAriaRelevant, "aria-relevant");
+
+    // https://w3c.github.io/aria/#dom-ariamixin-ariarequired
+    make_nullable_getter!(GetAriaRequired, "aria-required");
+
+    // https://w3c.github.io/aria/#dom-ariamixin-ariarequired
+    make_nullable_setter!(SetAriaRequired, "aria-required");
+
+    // https://w3c.github.io/aria/#dom-ariamixin-ariaroledescription
+    make_nullable_getter!(GetAriaRoleDescription, "aria-roledescription");
+
+    // https://w3c.github.io/aria/#dom-ariamixin-ariaroledescription
+    make_nullable_setter!(SetAriaRoleDescription, "aria-roledescription");
+
+    // https://w3c.github.io/aria/#dom-ariamixin-ariarowcount
+    make_nullable_getter!(GetAriaRowCount, "aria-rowcount");
+
+    // https://w3c.github.io/aria/#dom-ariamixin-ariarowcount
+    make_nullable_setter!(SetAriaRowCount, "aria-rowcount");
+
+    // https://w3c.github.io/aria/#dom-ariamixin-ariarowindex
+    make_nullable_getter!(GetAriaRowIndex, "aria-rowindex");
+
+    // https://w3c.github.io/aria/#dom-ariamixin-ariarowindex
+    make_nullable_setter!(SetAriaRowIndex, "aria-rowindex");
+
+    // https://w3c.github.io/aria/#dom-ariamixin-ariarowspan
+    make_nullable_getter!(GetAriaRowSpan, "aria-rowspan");
+
+    // https://w3c.github.io/aria/#dom-ariamixin-ariarowspan
+    make_nullable_setter!(SetAriaRowSpan, "aria-rowspan");
+
+    // https://w3c.github.io/aria/#dom-ariamixin-ariaselected
+    make_nullable_getter!(GetAriaSelected, "aria-selected");
+
+    // https://w3c.github.io/aria/#dom-ariamixin-ariaselected
+    make_nullable_setter!(SetAriaSelected, "aria-selected");
+
+    // https://w3c.github.io/aria/#dom-ariamixin-ariasetsize
+    make_nullable_getter!(GetAriaSetSize, "aria-setsize");
+
+    // https://w3c.github.io/aria/#dom-ariamixin-ariasetsize
+    make_nullable_setter!(SetAriaSetSize, "aria-setsize");
+
+    // https://w3c.github.io/aria/#dom-ariamixin-ariasort
+    make_nullable_getter!(GetAriaSort, "aria-sort");
+
+    // https://w3c.github.io/aria/#dom-ariamixin-ariasort
+    make_nullable_setter!(SetAriaSort, "aria-sort");
+
+    // https://w3c.github.io/aria/#dom-ariamixin-ariavaluemax
+    make_nullable_getter!(GetAriaValueMax, "aria-valuemax");
+
+    // https://w3c.github.io/aria/#dom-ariamixin-ariavaluemax
+    make_nullable_setter!(SetAriaValueMax, "aria-valuemax");
+
+    // https://w3c.github.io/aria/#dom-ariamixin-ariavaluemin
+    make_nullable_getter!(GetAriaValueMin, "aria-valuemin");
+
+    // https://w3c.github.io/aria/#dom-ariamixin-ariavaluemin
+    make_nullable_setter!(SetAriaValueMin, "aria-valuemin");
+
+    // https://w3c.github.io/aria/#dom-ariamixin-ariavaluenow
+    make_nullable_getter!(GetAriaValueNow, "aria-valuenow");
+
+    // https://w3c.github.io/aria/#dom-ariamixin-ariavaluenow
+    make_nullable_setter!(SetAriaValueNow, "aria-valuenow");
+
+    // https://w3c.github.io/aria/#dom-ariamixin-ariavaluetext
+    make_nullable_getter!(GetAriaValueText, "aria-valuetext");
+
+    // https://w3c.github.io/aria/#dom-ariamixin-ariavaluetext
+    make_nullable_setter!(SetAriaValueText, "aria-valuetext");
 }
 
 impl VirtualMethods for Element {
@@ -2967,6 +3423,9 @@ impl VirtualMethods for Element {
         if fullscreen.as_deref() == Some(self) {
             doc.exit_fullscreen();
         }
+        if doc.GetPointerLockElement().as_deref() == Some(self) {
+            doc.exit_pointer_lock();
+        }
         if let Some(ref value) = *self.id_attribute.borrow() {
             doc.unregister_element_id(self, value.clone());
         }
@@ -3144,6 +3603,7 @@ impl<'a> SelectorsElement for DomRoot<Element> {
 
             NonTSPseudoClass::Active |
             NonTSPseudoClass::Focus |
+            NonTSPseudoClass::FocusVisible |
             NonTSPseudoClass::Fullscreen |
             NonTSPseudoClass::Hover |
             NonTSPseudoClass::Defined |
@@ -3374,6 +3834,11 @@ impl Element {
         self.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
     }
 
+    pub fn set_focus_visible_state(&self, value: bool) {
+        self.set_state(ElementState::IN_FOCUSRING_STATE, value);
+        self.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
+    }
+
     pub fn hover_state(&self) -> bool {
         self.state.get().contains(ElementState::IN_HOVER_STATE)
     }
@@ -3591,15 +4056,20 @@ impl TaskOnce for ElementPerformFullscreenEnter {
 
         // Step 7.1
         if self.error || !element.fullscreen_element_ready_check() {
-            document
-                .upcast::<EventTarget>()
-                .fire_event(atom!("fullscreenerror"));
+            fire_fullscreen_event(&element, &document, atom!("fullscreenerror"));
             promise.reject_error(Error::Type(String::from("fullscreen is not connected")));
             return;
         }
 
         // TODO Step 7.2-4
         // Step 7.5
+        // The element which was fullscreen before leaves the top layer, so that only one
+        // element is fullscreen at a time.
+        if let Some(previous) = document.GetFullscreenElement() {
+            if previous != element {
+                previous.set_fullscreen_state(false);
+            }
+        }
         element.set_fullscreen_state(true);
         document.set_fullscreen_element(Some(&element));
         document
@@ -3607,9 +4077,7 @@ impl TaskOnce for ElementPerformFullscreenEnter {
             .reflow(ReflowGoal::Full, ReflowReason::ElementStateChanged);
 
         // Step 7.6
-        document
-            .upcast::<EventTarget>()
-            .fire_event(atom!("fullscreenchange"));
+        fire_fullscreen_event(&element, &document, atom!("fullscreenchange"));
 
         // Step 7.7
         promise.resolve_native(&());
@@ -3641,23 +4109,33 @@ impl TaskOnce for ElementPerformFullscreenExit {
         // TODO Step 9.1-5
         // Step 9.6
         element.set_fullscreen_state(false);
+        if document.GetFullscreenElement().as_deref() == Some(&*element) {
+            document.set_fullscreen_element(None);
+        }
 
         document
             .window()
             .reflow(ReflowGoal::Full, ReflowReason::ElementStateChanged);
 
-        document.set_fullscreen_element(None);
-
         // Step 9.8
-        document
-            .upcast::<EventTarget>()
-            .fire_event(atom!("fullscreenchange"));
+        fire_fullscreen_event(&element, &document, atom!("fullscreenchange"));
 
         // Step 9.10
         self.promise.root().resolve_native(&());
     }
 }
 
+/// Fires a fullscreen event at `element`, which bubbles up to its document, or at `document`
+/// if the element was removed from it in the meantime.
+/// <https://fullscreen.spec.whatwg.org/#run-the-fullscreen-steps>
+fn fire_fullscreen_event(element: &Element, document: &Document, name: Atom) {
+    if element.upcast::<Node>().is_connected() {
+        element.upcast::<EventTarget>().fire_bubbling_event(name);
+    } else {
+        document.upcast::<EventTarget>().fire_event(name);
+    }
+}
+
 pub fn reflect_cross_origin_attribute(element: &Element) -> Option<DOMString> {
     let attr = element.get_attribute(&ns!(), &local_name!("crossorigin"));
 
@@ -3687,6 +4165,19 @@ pub(crate) fn referrer_policy_for_element(element: &Element) -> Option<ReferrerP
         .or_else(|| document_from_node(element).get_referrer_policy())
 }
 
+pub(crate) fn fetch_priority_for_element(element: &Element) -> RequestPriority {
+    let value = element.get_string_attribute(&LocalName::from("fetchpriority"));
+    RequestPriority::from_attribute_value(&value)
+}
+
+pub fn reflect_fetch_priority_attribute(element: &Element) -> DOMString {
+    DOMString::from(fetch_priority_for_element(element).as_str())
+}
+
+pub fn set_fetch_priority_attribute(element: &Element, value: DOMString) {
+    element.set_string_attribute(&LocalName::from("fetchpriority"), value);
+}
+
 pub(crate) fn cors_setting_for_element(element: &Element) -> Option<CorsSettings> {
     reflect_cross_origin_attribute(element).map_or(None, |attr| match &*attr {
         "anonymous" => Some(CorsSettings::Anonymous),
@@ -3694,3 +4185,43 @@ pub(crate) fn cors_setting_for_element(element: &Element) -> Option<CorsSettings
         _ => unreachable!(),
     })
 }
+
+/// The used value of a `scroll-padding` side of a scroll container whose scrollport is `size`
+/// long along the axis of that side.
+fn used_scroll_padding(padding: &computed::NonNegativeLengthPercentageOrAuto, size: f64) -> f64 {
+    padding
+        .to_used_value(Au::from_f64_px(size))
+        .map_or(0.0, |padding| padding.to_f64_px())
+}
+
+/// The scroll position along an axis that brings `target` into view of `scrollport` at
+/// `position`, where `scrollport` is the scrollport at `scroll_position` without its scroll
+/// padding, and both are given as the start and end of a range along the axis.
+///
+/// <https://drafts.csswg.org/cssom-view/#scroll-an-element-into-view>
+fn aligned_scroll_position(
+    position: ScrollLogicalPosition,
+    target: (f64, f64),
+    scrollport: (f64, f64),
+    scroll_position: f64,
+) -> f64 {
+    let align_start = scroll_position + target.0 - scrollport.0;
+    let align_end = scroll_position + target.1 - scrollport.1;
+    match position {
+        ScrollLogicalPosition::Start => align_start,
+        ScrollLogicalPosition::End => align_end,
+        ScrollLogicalPosition::Center => (align_start + align_end) / 2.0,
+        ScrollLogicalPosition::Nearest => {
+            let target_is_smaller = target.1 - target.0 <= scrollport.1 - scrollport.0;
+            if (target.0 < scrollport.0 && target.1 > scrollport.1) ||
+                (target.0 >= scrollport.0 && target.1 <= scrollport.1)
+            {
+                scroll_position
+            } else if (target.0 < scrollport.0) == target_is_smaller {
+                align_start
+            } else {
+                align_end
+            }
+        },
+    }
+}
@@ -23,6 +23,7 @@ use crate::dom::htmlinputelement::InputActivationState;
 use crate::dom::mouseevent::MouseEvent;
 use crate::dom::node::{Node, ShadowIncluding};
 use crate::dom::performance::reduce_timing_resolution;
+use crate::dom::performanceeventtiming::TIMED_EVENT_TYPES;
 use crate::dom::virtualmethods::vtable_for;
 use crate::dom::window::Window;
 use crate::task::TaskOnce;
@@ -172,6 +173,13 @@ impl Event {
         // Step 1.
         self.dispatching.set(true);
 
+        // https://w3c.github.io/event-timing/#sec-init-event-timing
+        let processing_start = if self.should_report_timing(target) {
+            Some(time::precise_time_ns())
+        } else {
+            None
+        };
+
         // Step 2.
         let target_override_document; // upcasted EventTarget's lifetime depends on this
         let target_override = if legacy_target_override {
@@ -344,9 +352,29 @@ impl Event {
             }
         }
 
+        // https://w3c.github.io/event-timing/#sec-fin-event-timing
+        if let Some(processing_start) = processing_start {
+            target.global().performance().queue_event_timing(
+                DOMString::from(&*self.type_()),
+                self.cancelable.get(),
+                target.downcast::<Node>(),
+                self.precise_time_ns,
+                processing_start,
+                time::precise_time_ns(),
+            );
+        }
+
         return self.status();
     }
 
+    /// Whether the timing of the dispatch of this event to the given target is reported
+    /// to the performance timeline.
+    fn should_report_timing(&self, target: &EventTarget) -> bool {
+        self.trusted.get() &&
+            TIMED_EVENT_TYPES.contains(&&*self.type_()) &&
+            target.global().is::<Window>()
+    }
+
     pub fn status(&self) -> EventStatus {
         if self.DefaultPrevented() {
             EventStatus::Canceled
@@ -45,6 +45,12 @@ use utf8;
 
 const DEFAULT_RECONNECTION_TIME: u64 = 5000;
 
+/// The longest that reconnections are delayed by the backoff after failed reconnections,
+/// unless the server asked for a longer reconnection time.
+const MAX_RECONNECTION_BACKOFF: u64 = 60000;
+
+const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+
 #[derive(Clone, Copy, Debug, JSTraceable, MallocSizeOf, PartialEq)]
 struct GenerationId(u32);
 
@@ -63,6 +69,8 @@ pub struct EventSource {
     request: DomRefCell<Option<RequestBuilder>>,
     last_event_id: DomRefCell<DOMString>,
     reconnection_time: Cell<u64>,
+    /// The reconnections that failed in a row since the connection was last open.
+    failed_reconnections: Cell<u32>,
     generation_id: Cell<GenerationId>,
 
     ready_state: Cell<ReadyState>,
@@ -79,6 +87,8 @@ enum ParserState {
 
 struct EventSourceContext {
     incomplete_utf8: Option<utf8::Incomplete>,
+    /// The first bytes of the stream, while it is not known yet whether they are a BOM.
+    stream_start: Option<Vec<u8>>,
 
     event_source: Trusted<EventSource>,
     gen_id: GenerationId,
@@ -111,6 +121,7 @@ impl EventSourceContext {
                 let event_source = event_source.root();
                 if event_source.ready_state.get() != ReadyState::Closed {
                     event_source.ready_state.set(ReadyState::Open);
+                    event_source.failed_reconnections.set(0);
                     event_source.upcast::<EventTarget>().fire_event(atom!("open"));
                 }
             }),
@@ -154,11 +165,10 @@ impl EventSourceContext {
                 // Step 1.3.
                 event_source.upcast::<EventTarget>().fire_event(atom!("error"));
 
-                // Step 2.
-                let duration = Length::new(event_source.reconnection_time.get());
-
-                // Step 3.
-                // TODO: Optionally wait some more.
+                // Steps 2-3.
+                let duration = Length::new(event_source.reconnection_delay());
+                let failed_reconnections = event_source.failed_reconnections.get();
+                event_source.failed_reconnections.set(failed_reconnections.saturating_add(1));
 
                 // Steps 4-5.
                 let callback = OneshotTimerCallback::EventSourceTimeout(
@@ -182,10 +192,16 @@ impl EventSourceContext {
                 self.data.push_str(&self.value);
                 self.data.push('\n');
             },
-            "id" => mem::swap(&mut self.last_event_id, &mut self.value),
+            "id" => {
+                if !self.value.contains('\0') {
+                    mem::swap(&mut self.last_event_id, &mut self.value);
+                }
+            },
             "retry" => {
-                if let Ok(time) = u64::from_str(&self.value) {
-                    self.event_source.root().reconnection_time.set(time);
+                if !self.value.is_empty() && self.value.bytes().all(|b| b.is_ascii_digit()) {
+                    if let Ok(time) = u64::from_str(&self.value) {
+                        self.event_source.root().reconnection_time.set(time);
+                    }
                 }
             },
             _ => (),
@@ -259,6 +275,61 @@ impl EventSourceContext {
         );
     }
 
+    /// Decodes a chunk of the stream as UTF-8 and parses it.
+    fn decode_and_parse(&mut self, chunk: &[u8]) {
+        let mut input = chunk;
+        if let Some(mut incomplete) = self.incomplete_utf8.take() {
+            match incomplete.try_complete(input) {
+                None => return,
+                Some((result, remaining_input)) => {
+                    self.parse(result.unwrap_or("\u{FFFD}").chars());
+                    input = remaining_input;
+                },
+            }
+        }
+
+        while !input.is_empty() {
+            match utf8::decode(&input) {
+                Ok(s) => {
+                    self.parse(s.chars());
+                    return;
+                },
+                Err(utf8::DecodeError::Invalid {
+                    valid_prefix,
+                    remaining_input,
+                    ..
+                }) => {
+                    self.parse(valid_prefix.chars());
+                    self.parse("\u{FFFD}".chars());
+                    input = remaining_input;
+                },
+                Err(utf8::DecodeError::Incomplete {
+                    valid_prefix,
+                    incomplete_suffix,
+                }) => {
+                    self.parse(valid_prefix.chars());
+                    self.incomplete_utf8 = Some(incomplete_suffix);
+                    return;
+                },
+            }
+        }
+    }
+
+    /// Discards the state of the stream that ended, so that the stream of the next
+    /// connection is parsed from its start.
+    /// <https://html.spec.whatwg.org/multipage/#event-stream-interpretation>
+    fn reset_stream(&mut self) {
+        // Pending data is discarded, the incomplete event is not dispatched.
+        self.incomplete_utf8 = None;
+        self.parser_state = ParserState::Eol;
+        self.field.clear();
+        self.value.clear();
+        self.event_type.clear();
+        self.data.clear();
+        self.last_event_id = String::from(self.event_source.root().last_event_id.borrow().clone());
+        self.stream_start = Some(vec![]);
+    }
+
     // https://html.spec.whatwg.org/multipage/#event-stream-interpretation
     fn parse(&mut self, stream: Chars) {
         let mut stream = stream.peekable();
@@ -353,6 +424,9 @@ impl FetchResponseListener for EventSourceContext {
                 if (mime.type_(), mime.subtype()) != (mime::TEXT, mime::EVENT_STREAM) {
                     return self.fail_the_connection();
                 }
+                if meta.status.map_or(true, |(code, _)| code != 200) {
+                    return self.fail_the_connection();
+                }
                 self.origin = meta.final_url.origin().ascii_serialization();
                 self.announce_the_connection();
             },
@@ -369,48 +443,26 @@ impl FetchResponseListener for EventSourceContext {
     }
 
     fn process_response_chunk(&mut self, chunk: Vec<u8>) {
-        let mut input = &*chunk;
-        if let Some(mut incomplete) = self.incomplete_utf8.take() {
-            match incomplete.try_complete(input) {
-                None => return,
-                Some((result, remaining_input)) => {
-                    self.parse(result.unwrap_or("\u{FFFD}").chars());
-                    input = remaining_input;
-                },
-            }
-        }
-
-        while !input.is_empty() {
-            match utf8::decode(&input) {
-                Ok(s) => {
-                    self.parse(s.chars());
-                    return;
-                },
-                Err(utf8::DecodeError::Invalid {
-                    valid_prefix,
-                    remaining_input,
-                    ..
-                }) => {
-                    self.parse(valid_prefix.chars());
-                    self.parse("\u{FFFD}".chars());
-                    input = remaining_input;
-                },
-                Err(utf8::DecodeError::Incomplete {
-                    valid_prefix,
-                    incomplete_suffix,
-                }) => {
-                    self.parse(valid_prefix.chars());
-                    self.incomplete_utf8 = Some(incomplete_suffix);
-                    return;
-                },
-            }
+        // The UTF-8 decode algorithm strips a leading BOM, which may be split across chunks.
+        let mut stream_start = match self.stream_start.take() {
+            Some(stream_start) => stream_start,
+            None => return self.decode_and_parse(&chunk),
+        };
+        stream_start.extend_from_slice(&chunk);
+        if stream_start.len() < UTF8_BOM.len() && UTF8_BOM.starts_with(&stream_start) {
+            self.stream_start = Some(stream_start);
+            return;
         }
+        let input = if stream_start.starts_with(UTF8_BOM) {
+            &stream_start[UTF8_BOM.len()..]
+        } else {
+            &stream_start[..]
+        };
+        self.decode_and_parse(input);
     }
 
     fn process_response_eof(&mut self, _response: Result<ResourceFetchTiming, NetworkError>) {
-        if let Some(_) = self.incomplete_utf8.take() {
-            self.parse("\u{FFFD}".chars());
-        }
+        self.reset_stream();
         self.reestablish_the_connection();
     }
 
@@ -451,6 +503,7 @@ impl EventSource {
             request: DomRefCell::new(None),
             last_event_id: DomRefCell::new(DOMString::from("")),
             reconnection_time: Cell::new(DEFAULT_RECONNECTION_TIME),
+            failed_reconnections: Cell::new(0),
             generation_id: Cell::new(GenerationId(0)),
 
             ready_state: Cell::new(ReadyState::Connecting),
@@ -489,6 +542,21 @@ impl EventSource {
         );
     }
 
+    /// The time to wait before reconnecting, which backs off exponentially while
+    /// reconnections keep failing.
+    /// <https://html.spec.whatwg.org/multipage/#reestablish-the-connection>
+    fn reconnection_delay(&self) -> u64 {
+        let reconnection_time = self.reconnection_time.get();
+        let backoff = 1u64
+            .checked_shl(self.failed_reconnections.get().min(16))
+            .unwrap_or(u64::max_value());
+        reconnection_time.max(
+            reconnection_time
+                .saturating_mul(backoff)
+                .min(MAX_RECONNECTION_BACKOFF),
+        )
+    }
+
     pub fn request(&self) -> RequestBuilder {
         self.request.borrow().clone().unwrap()
     }
@@ -550,6 +618,7 @@ impl EventSource {
         let (action_sender, action_receiver) = ipc::channel().unwrap();
         let context = EventSourceContext {
             incomplete_utf8: None,
+            stream_start: Some(vec![]),
 
             event_source: Trusted::new(&ev),
             gen_id: ev.generation_id.get(),
@@ -652,20 +721,27 @@ impl EventSourceTimeoutCallback {
         // Step 5.2
         let mut request = event_source.request();
         // Step 5.3
-        if !event_source.last_event_id.borrow().is_empty() {
+        let last_event_id = event_source.last_event_id.borrow().clone();
+        if !last_event_id.is_empty() {
+            // The value is sent UTF-8 encoded. It can't contain line breaks, but other control
+            // characters are not allowed in header values.
             //TODO(eijebong): Change this once typed header support custom values
-            request.headers.insert(
-                HeaderName::from_static("last-event-id"),
-                HeaderValue::from_str(&String::from(event_source.last_event_id.borrow().clone()))
-                    .unwrap(),
-            );
+            match HeaderValue::from_bytes(last_event_id.as_bytes()) {
+                Ok(value) => {
+                    request
+                        .headers
+                        .insert(HeaderName::from_static("last-event-id"), value);
+                },
+                Err(_) => warn!("Can't send Last-Event-ID {:?}", last_event_id),
+            }
         }
         // Step 5.4
+        let cancel_receiver = event_source.canceller.borrow_mut().initialize();
         global
             .core_resource_thread()
             .send(CoreResourceMsg::Fetch(
                 request,
-                FetchChannels::ResponseMsg(self.action_sender, None),
+                FetchChannels::ResponseMsg(self.action_sender, Some(cancel_receiver)),
             ))
             .unwrap();
     }
@@ -29,6 +29,7 @@ use crate::dom::errorevent::ErrorEvent;
 use crate::dom::event::{Event, EventBubbles, EventCancelable, EventStatus};
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::htmlformelement::FormControlElementHelpers;
+use crate::dom::mediaquerylist::MediaQueryList;
 use crate::dom::node::document_from_node;
 use crate::dom::virtualmethods::VirtualMethods;
 use crate::dom::window::Window;
@@ -380,8 +381,21 @@ impl EventTarget {
         *self.handlers.borrow_mut() = Default::default();
     }
 
+    /// Lets the targets which must be kept alive, or watch a source of events, while they have
+    /// listeners know that one of their listeners was added.
+    fn listener_added(&self, ty: &Atom) {
+        if let Some(media_query_list) = self.downcast::<MediaQueryList>() {
+            media_query_list.listener_added(ty);
+        } else if let Some(window) = self.downcast::<Window>() {
+            window.listener_added(ty);
+        }
+    }
+
     /// <https://html.spec.whatwg.org/multipage/#event-handler-attributes:event-handlers-11>
     fn set_inline_event_listener(&self, ty: Atom, listener: Option<InlineEventListener>) {
+        if listener.is_some() {
+            self.listener_added(&ty);
+        }
         let mut handlers = self.handlers.borrow_mut();
         let entries = match handlers.entry(ty) {
             Occupied(entry) => entry.into_mut(),
@@ -695,8 +709,10 @@ impl EventTarget {
             Some(l) => l,
             None => return,
         };
+        let ty = Atom::from(ty);
+        self.listener_added(&ty);
         let mut handlers = self.handlers.borrow_mut();
-        let entry = match handlers.entry(Atom::from(ty)) {
+        let entry = match handlers.entry(ty) {
             Occupied(entry) => entry.into_mut(),
             Vacant(entry) => entry.insert(EventListeners(vec![])),
         };
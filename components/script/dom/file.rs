@@ -6,13 +6,11 @@ use crate::dom::bindings::codegen::Bindings::FileBinding;
 use crate::dom::bindings::codegen::Bindings::FileBinding::FileMethods;
 use crate::dom::bindings::codegen::UnionTypes::ArrayBufferOrArrayBufferViewOrBlobOrString;
 use crate::dom::bindings::error::{Error, Fallible};
-use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::reflector::reflect_dom_object;
 use crate::dom::bindings::root::DomRoot;
 use crate::dom::bindings::str::DOMString;
 use crate::dom::blob::{blob_parts_to_bytes, normalize_type_string, Blob};
 use crate::dom::globalscope::GlobalScope;
-use crate::dom::window::Window;
 use dom_struct::dom_struct;
 use net_traits::filemanager_thread::SelectedFile;
 use script_traits::serializable::BlobImpl;
@@ -57,7 +55,7 @@ impl File {
     }
 
     // Construct from selected file message from file manager thread
-    pub fn new_from_selected(window: &Window, selected: SelectedFile) -> DomRoot<File> {
+    pub fn new_from_selected(global: &GlobalScope, selected: SelectedFile) -> DomRoot<File> {
         let name = DOMString::from(
             selected
                 .filename
@@ -66,7 +64,7 @@ impl File {
         );
 
         File::new(
-            window.upcast(),
+            global,
             BlobImpl::new_from_file(
                 selected.id,
                 selected.filename,
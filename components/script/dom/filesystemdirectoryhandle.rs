@@ -0,0 +1,150 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::FileSystemDirectoryHandleBinding::FileSystemDirectoryHandleMethods;
+use crate::dom::bindings::codegen::Bindings::FileSystemDirectoryHandleBinding::{
+    FileSystemGetDirectoryOptions, FileSystemGetFileOptions, FileSystemRemoveOptions,
+};
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::USVString;
+use crate::dom::filesystemfilehandle::FileSystemFileHandle;
+use crate::dom::filesystemhandle::{file_manager_request, FileSystemHandle};
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::promise::Promise;
+use crate::realms::InRealm;
+use dom_struct::dom_struct;
+use net_traits::filemanager_thread::{
+    FileManagerThreadMsg, FileSystemEntryKind, FileSystemLocator,
+};
+use std::rc::Rc;
+
+/// A directory of the origin private file system of an origin.
+/// <https://wicg.github.io/file-system-access/#filesystemdirectoryhandle>
+#[dom_struct]
+pub struct FileSystemDirectoryHandle {
+    handle: FileSystemHandle,
+}
+
+impl FileSystemDirectoryHandle {
+    fn new_inherited(locator: FileSystemLocator, name: String) -> FileSystemDirectoryHandle {
+        FileSystemDirectoryHandle {
+            handle: FileSystemHandle::new_inherited(locator, FileSystemEntryKind::Directory, name),
+        }
+    }
+
+    pub fn new(
+        global: &GlobalScope,
+        locator: FileSystemLocator,
+        name: String,
+    ) -> DomRoot<FileSystemDirectoryHandle> {
+        reflect_dom_object(
+            Box::new(FileSystemDirectoryHandle::new_inherited(locator, name)),
+            global,
+        )
+    }
+
+    /// The entry by the given name in this directory, whose validity the file manager checks.
+    fn child_locator(&self, name: &str) -> FileSystemLocator {
+        match *self.upcast::<FileSystemHandle>().locator() {
+            FileSystemLocator::Private(ref origin, ref names) => {
+                let mut names = names.clone();
+                names.push(name.to_owned());
+                FileSystemLocator::Private(origin.clone(), names)
+            },
+            FileSystemLocator::Picked(_) => unreachable!("Picked entries are files"),
+        }
+    }
+
+    /// The steps shared by getFileHandle and getDirectoryHandle.
+    fn get_entry(
+        &self,
+        name: USVString,
+        kind: FileSystemEntryKind,
+        create: bool,
+        comp: InRealm,
+    ) -> Rc<Promise> {
+        let global = self.global();
+        let promise = Promise::new_in_current_realm(&global, comp);
+        let locator = self.child_locator(&name);
+        let result = file_manager_request(&global, |sender| {
+            FileManagerThreadMsg::GetFileSystemEntry(locator.clone(), kind, create, sender)
+        });
+        match (result, kind) {
+            (Ok(()), FileSystemEntryKind::File) => {
+                promise.resolve_native(&FileSystemFileHandle::new(&global, locator, name.0))
+            },
+            (Ok(()), FileSystemEntryKind::Directory) => {
+                promise.resolve_native(&FileSystemDirectoryHandle::new(&global, locator, name.0))
+            },
+            (Err(error), _) => promise.reject_error(error),
+        }
+        promise
+    }
+}
+
+impl FileSystemDirectoryHandleMethods for FileSystemDirectoryHandle {
+    // https://wicg.github.io/file-system-access/#dom-filesystemdirectoryhandle-getfilehandle
+    fn GetFileHandle(
+        &self,
+        name: USVString,
+        options: &FileSystemGetFileOptions,
+        comp: InRealm,
+    ) -> Rc<Promise> {
+        self.get_entry(name, FileSystemEntryKind::File, options.create, comp)
+    }
+
+    // https://wicg.github.io/file-system-access/#dom-filesystemdirectoryhandle-getdirectoryhandle
+    fn GetDirectoryHandle(
+        &self,
+        name: USVString,
+        options: &FileSystemGetDirectoryOptions,
+        comp: InRealm,
+    ) -> Rc<Promise> {
+        self.get_entry(name, FileSystemEntryKind::Directory, options.create, comp)
+    }
+
+    // https://wicg.github.io/file-system-access/#dom-filesystemdirectoryhandle-removeentry
+    fn RemoveEntry(
+        &self,
+        name: USVString,
+        options: &FileSystemRemoveOptions,
+        comp: InRealm,
+    ) -> Rc<Promise> {
+        let global = self.global();
+        let promise = Promise::new_in_current_realm(&global, comp);
+        let locator = self.child_locator(&name);
+        let result = file_manager_request(&global, |sender| {
+            FileManagerThreadMsg::RemoveFileSystemEntry(locator, options.recursive, sender)
+        });
+        match result {
+            Ok(()) => promise.resolve_native(&()),
+            Err(error) => promise.reject_error(error),
+        }
+        promise
+    }
+
+    // https://wicg.github.io/file-system-access/#dom-filesystemdirectoryhandle-resolve
+    fn Resolve(&self, possible_descendant: &FileSystemHandle, comp: InRealm) -> Rc<Promise> {
+        let promise = Promise::new_in_current_realm(&self.global(), comp);
+        let path = match (
+            self.upcast::<FileSystemHandle>().locator(),
+            possible_descendant.locator(),
+        ) {
+            (
+                FileSystemLocator::Private(ref origin, ref names),
+                FileSystemLocator::Private(ref descendant_origin, ref descendant_names),
+            ) if origin == descendant_origin && descendant_names.starts_with(names) => Some(
+                descendant_names[names.len()..]
+                    .iter()
+                    .map(|name| USVString(name.clone()))
+                    .collect::<Vec<_>>(),
+            ),
+            _ => None,
+        };
+        promise.resolve_native(&path);
+        promise
+    }
+}
@@ -0,0 +1,134 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::FileSystemFileHandleBinding::FileSystemFileHandleMethods;
+use crate::dom::bindings::codegen::Bindings::FileSystemHandleBinding::OpenFilePickerOptions;
+use crate::dom::bindings::codegen::UnionTypes::USVStringOrUSVStringSequence;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::file::File;
+use crate::dom::filesystemhandle::{file_manager_request, FileSystemHandle};
+use crate::dom::filesystemsyncaccesshandle::FileSystemSyncAccessHandle;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::promise::Promise;
+use crate::realms::InRealm;
+use dom_struct::dom_struct;
+use embedder_traits::FilterPattern;
+use net_traits::blob_url_store::get_blob_origin;
+use net_traits::filemanager_thread::{
+    FileManagerThreadMsg, FileSystemEntryKind, FileSystemLocator,
+};
+use net_traits::{CoreResourceMsg, IpcSend, ResourceThreads};
+use std::rc::Rc;
+
+/// <https://wicg.github.io/file-system-access/#filesystemfilehandle>
+#[dom_struct]
+pub struct FileSystemFileHandle {
+    handle: FileSystemHandle,
+    /// Used to let the file manager forget a picked file once the handle is collected.
+    resource_threads: ResourceThreads,
+}
+
+impl FileSystemFileHandle {
+    fn new_inherited(
+        locator: FileSystemLocator,
+        name: String,
+        resource_threads: ResourceThreads,
+    ) -> FileSystemFileHandle {
+        FileSystemFileHandle {
+            handle: FileSystemHandle::new_inherited(locator, FileSystemEntryKind::File, name),
+            resource_threads,
+        }
+    }
+
+    pub fn new(
+        global: &GlobalScope,
+        locator: FileSystemLocator,
+        name: String,
+    ) -> DomRoot<FileSystemFileHandle> {
+        reflect_dom_object(
+            Box::new(FileSystemFileHandle::new_inherited(
+                locator,
+                name,
+                global.resource_threads().clone(),
+            )),
+            global,
+        )
+    }
+}
+
+impl Drop for FileSystemFileHandle {
+    fn drop(&mut self) {
+        if let FileSystemLocator::Picked(id) = *self.handle.locator() {
+            let msg = FileManagerThreadMsg::ForgetPickedFile(id);
+            let _ = self
+                .resource_threads
+                .send(CoreResourceMsg::ToFileManager(msg));
+        }
+    }
+}
+
+impl FileSystemFileHandleMethods for FileSystemFileHandle {
+    // https://wicg.github.io/file-system-access/#dom-filesystemfilehandle-getfile
+    fn GetFile(&self, comp: InRealm) -> Rc<Promise> {
+        let global = self.global();
+        let promise = Promise::new_in_current_realm(&global, comp);
+        let locator = self.upcast::<FileSystemHandle>().locator().clone();
+        let origin = get_blob_origin(&global.get_url());
+        let result = file_manager_request(&global, |sender| {
+            FileManagerThreadMsg::GetFileSystemFile(locator, origin, sender)
+        });
+        match result {
+            Ok(selected) => promise.resolve_native(&File::new_from_selected(&global, selected)),
+            Err(error) => promise.reject_error(error),
+        }
+        promise
+    }
+
+    // https://wicg.github.io/file-system-access/#dom-filesystemfilehandle-createsyncaccesshandle
+    fn CreateSyncAccessHandle(&self, comp: InRealm) -> Rc<Promise> {
+        let global = self.global();
+        let promise = Promise::new_in_current_realm(&global, comp);
+        let locator = self.upcast::<FileSystemHandle>().locator().clone();
+        let result = file_manager_request(&global, |sender| {
+            FileManagerThreadMsg::OpenSyncAccessHandle(locator, sender)
+        });
+        match result {
+            Ok(id) => promise.resolve_native(&FileSystemSyncAccessHandle::new(&global, id)),
+            Err(error) => promise.reject_error(error),
+        }
+        promise
+    }
+}
+
+/// The file extensions that the picker shows, from those of the accepted types, or those
+/// of their MIME types if they have none.
+/// <https://wicg.github.io/file-system-access/#process-accept-types>
+pub fn file_picker_filter(options: &OpenFilePickerOptions) -> Vec<FilterPattern> {
+    let mut filter = vec![];
+    let accepts = options
+        .types
+        .iter()
+        .flatten()
+        .filter_map(|type_| type_.accept.as_ref());
+    for (mime_type, extensions) in accepts.flat_map(|accept| accept.iter()) {
+        let extensions = match *extensions {
+            USVStringOrUSVStringSequence::USVString(ref extension) => vec![extension.0.clone()],
+            USVStringOrUSVStringSequence::USVStringSequence(ref extensions) => extensions
+                .iter()
+                .map(|extension| extension.0.clone())
+                .collect(),
+        };
+        if extensions.is_empty() {
+            if let Some(extensions) = mime_guess::get_mime_extensions_str(&mime_type.0) {
+                filter.extend(extensions.iter().map(|ext| FilterPattern(ext.to_string())));
+            }
+        }
+        for extension in extensions {
+            filter.push(FilterPattern(extension.trim_start_matches('.').to_owned()));
+        }
+    }
+    filter
+}
@@ -0,0 +1,111 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::FileSystemHandleBinding::FileSystemHandleKind;
+use crate::dom::bindings::codegen::Bindings::FileSystemHandleBinding::FileSystemHandleMethods;
+use crate::dom::bindings::error::Error;
+use crate::dom::bindings::reflector::{DomObject, Reflector};
+use crate::dom::bindings::str::USVString;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::promise::Promise;
+use crate::realms::InRealm;
+use dom_struct::dom_struct;
+use ipc_channel::ipc::IpcSender;
+use net_traits::filemanager_thread::{FileManagerResult, FileManagerThreadError};
+use net_traits::filemanager_thread::{FileManagerThreadMsg, FileSystemAccessError};
+use net_traits::filemanager_thread::{FileSystemEntryKind, FileSystemLocator};
+use net_traits::{CoreResourceMsg, IpcSend};
+use profile_traits::ipc;
+use serde::{Deserialize, Serialize};
+use std::rc::Rc;
+
+/// A file or directory of the origin private file system of an origin, or a file that the
+/// user picked, whose contents live in the file manager.
+/// <https://wicg.github.io/file-system-access/#filesystemhandle>
+#[dom_struct]
+pub struct FileSystemHandle {
+    reflector_: Reflector,
+    locator: FileSystemLocator,
+    kind: FileSystemEntryKind,
+    name: String,
+}
+
+impl FileSystemHandle {
+    pub fn new_inherited(
+        locator: FileSystemLocator,
+        kind: FileSystemEntryKind,
+        name: String,
+    ) -> FileSystemHandle {
+        FileSystemHandle {
+            reflector_: Reflector::new(),
+            locator,
+            kind,
+            name,
+        }
+    }
+
+    pub fn locator(&self) -> &FileSystemLocator {
+        &self.locator
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl FileSystemHandleMethods for FileSystemHandle {
+    // https://wicg.github.io/file-system-access/#dom-filesystemhandle-kind
+    fn Kind(&self) -> FileSystemHandleKind {
+        match self.kind {
+            FileSystemEntryKind::File => FileSystemHandleKind::File,
+            FileSystemEntryKind::Directory => FileSystemHandleKind::Directory,
+        }
+    }
+
+    // https://wicg.github.io/file-system-access/#dom-filesystemhandle-name
+    fn Name(&self) -> USVString {
+        USVString(self.name.clone())
+    }
+
+    // https://wicg.github.io/file-system-access/#dom-filesystemhandle-issameentry
+    fn IsSameEntry(&self, other: &FileSystemHandle, comp: InRealm) -> Rc<Promise> {
+        let promise = Promise::new_in_current_realm(&self.global(), comp);
+        promise.resolve_native(&(self.kind == other.kind && self.locator == other.locator));
+        promise
+    }
+}
+
+/// Sends a message to the file manager, and waits for its reply.
+pub fn file_manager_request<T, F>(global: &GlobalScope, msg: F) -> Result<T, Error>
+where
+    T: for<'de> Deserialize<'de> + Serialize,
+    F: FnOnce(IpcSender<FileManagerResult<T>>) -> FileManagerThreadMsg,
+{
+    let (sender, receiver) = ipc::channel(global.time_profiler_chan().clone()).unwrap();
+    let _ = global
+        .resource_threads()
+        .send(CoreResourceMsg::ToFileManager(msg(sender)));
+    match receiver.recv() {
+        Ok(result) => result.map_err(file_system_error),
+        Err(_) => Err(Error::Operation),
+    }
+}
+
+/// <https://wicg.github.io/file-system-access/#errors>
+fn file_system_error(error: FileManagerThreadError) -> Error {
+    match error {
+        FileManagerThreadError::FileSystemAccessError(error) => match error {
+            FileSystemAccessError::NotFound => Error::NotFound,
+            FileSystemAccessError::TypeMismatch => Error::TypeMismatch,
+            FileSystemAccessError::InvalidName => Error::Type("Invalid entry name".to_owned()),
+            FileSystemAccessError::InvalidModification => Error::InvalidModification,
+            FileSystemAccessError::NoModificationAllowed => Error::NoModificationAllowed,
+            FileSystemAccessError::Unavailable => Error::Security,
+        },
+        FileManagerThreadError::UserCancelled => Error::Abort,
+        FileManagerThreadError::InvalidSelection |
+        FileManagerThreadError::FileSystemError(_) |
+        FileManagerThreadError::BlobURLStoreError(_) => Error::Operation,
+    }
+}
@@ -0,0 +1,156 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::FileSystemSyncAccessHandleBinding::FileSystemReadWriteOptions;
+use crate::dom::bindings::codegen::Bindings::FileSystemSyncAccessHandleBinding::FileSystemSyncAccessHandleMethods;
+use crate::dom::bindings::codegen::UnionTypes::ArrayBufferViewOrArrayBuffer;
+use crate::dom::bindings::error::{Error, ErrorResult, Fallible};
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::filesystemhandle::file_manager_request;
+use crate::dom::globalscope::GlobalScope;
+use dom_struct::dom_struct;
+use ipc_channel::ipc;
+use net_traits::filemanager_thread::FileManagerThreadMsg;
+use net_traits::filemanager_thread::{SyncAccessOperation, SyncAccessReply};
+use net_traits::{CoreResourceMsg, IpcSend, ResourceThreads};
+use std::cell::Cell;
+use uuid::Uuid;
+
+/// A file of an origin private file system that a dedicated worker opened for reading and
+/// writing synchronously, which no one else can open or remove until it is closed.
+/// <https://wicg.github.io/file-system-access/#filesystemsyncaccesshandle>
+#[dom_struct]
+pub struct FileSystemSyncAccessHandle {
+    reflector_: Reflector,
+    /// The id that the file manager gave the open file.
+    id: Uuid,
+    /// <https://wicg.github.io/file-system-access/#filesystemsyncaccesshandle-state>
+    closed: Cell<bool>,
+    /// <https://wicg.github.io/file-system-access/#filesystemsyncaccesshandle-file-position-cursor>
+    cursor: Cell<u64>,
+    /// Used to close the file if the handle is collected while still open.
+    resource_threads: ResourceThreads,
+}
+
+impl FileSystemSyncAccessHandle {
+    fn new_inherited(id: Uuid, resource_threads: ResourceThreads) -> FileSystemSyncAccessHandle {
+        FileSystemSyncAccessHandle {
+            reflector_: Reflector::new(),
+            id,
+            closed: Cell::new(false),
+            cursor: Cell::new(0),
+            resource_threads,
+        }
+    }
+
+    pub fn new(global: &GlobalScope, id: Uuid) -> DomRoot<FileSystemSyncAccessHandle> {
+        reflect_dom_object(
+            Box::new(FileSystemSyncAccessHandle::new_inherited(
+                id,
+                global.resource_threads().clone(),
+            )),
+            global,
+        )
+    }
+
+    fn operate(&self, operation: SyncAccessOperation) -> Fallible<SyncAccessReply> {
+        if self.closed.get() {
+            return Err(Error::InvalidState);
+        }
+        file_manager_request(&self.global(), |sender| {
+            FileManagerThreadMsg::SyncAccess(self.id, operation, sender)
+        })
+    }
+}
+
+impl Drop for FileSystemSyncAccessHandle {
+    fn drop(&mut self) {
+        if !self.closed.get() {
+            let (sender, _) = ipc::channel().unwrap();
+            let msg = FileManagerThreadMsg::SyncAccess(self.id, SyncAccessOperation::Close, sender);
+            let _ = self
+                .resource_threads
+                .send(CoreResourceMsg::ToFileManager(msg));
+        }
+    }
+}
+
+impl FileSystemSyncAccessHandleMethods for FileSystemSyncAccessHandle {
+    // https://wicg.github.io/file-system-access/#dom-filesystemsyncaccesshandle-read
+    #[allow(unsafe_code)]
+    fn Read(
+        &self,
+        mut buffer: ArrayBufferViewOrArrayBuffer,
+        options: &FileSystemReadWriteOptions,
+    ) -> Fallible<u64> {
+        // Safe because no script runs until the end of the method.
+        let data = unsafe {
+            match buffer {
+                ArrayBufferViewOrArrayBuffer::ArrayBufferView(ref mut view) => view.as_mut_slice(),
+                ArrayBufferViewOrArrayBuffer::ArrayBuffer(ref mut buffer) => buffer.as_mut_slice(),
+            }
+        };
+        let offset = options.at.unwrap_or(self.cursor.get());
+        match self.operate(SyncAccessOperation::Read(offset, data.len() as u64))? {
+            SyncAccessReply::Read(bytes) => {
+                data[..bytes.len()].copy_from_slice(&bytes);
+                self.cursor.set(offset + bytes.len() as u64);
+                Ok(bytes.len() as u64)
+            },
+            _ => Err(Error::Operation),
+        }
+    }
+
+    // https://wicg.github.io/file-system-access/#dom-filesystemsyncaccesshandle-write
+    fn Write(
+        &self,
+        buffer: ArrayBufferViewOrArrayBuffer,
+        options: &FileSystemReadWriteOptions,
+    ) -> Fallible<u64> {
+        let bytes = match buffer {
+            ArrayBufferViewOrArrayBuffer::ArrayBufferView(view) => view.to_vec(),
+            ArrayBufferViewOrArrayBuffer::ArrayBuffer(buffer) => buffer.to_vec(),
+        };
+        let offset = options.at.unwrap_or(self.cursor.get());
+        match self.operate(SyncAccessOperation::Write(offset, bytes))? {
+            SyncAccessReply::Written(length) => {
+                self.cursor.set(offset + length);
+                Ok(length)
+            },
+            _ => Err(Error::Operation),
+        }
+    }
+
+    // https://wicg.github.io/file-system-access/#dom-filesystemsyncaccesshandle-truncate
+    fn Truncate(&self, new_size: u64) -> ErrorResult {
+        self.operate(SyncAccessOperation::Truncate(new_size))?;
+        if self.cursor.get() > new_size {
+            self.cursor.set(new_size);
+        }
+        Ok(())
+    }
+
+    // https://wicg.github.io/file-system-access/#dom-filesystemsyncaccesshandle-getsize
+    fn GetSize(&self) -> Fallible<u64> {
+        match self.operate(SyncAccessOperation::GetSize)? {
+            SyncAccessReply::Size(size) => Ok(size),
+            _ => Err(Error::Operation),
+        }
+    }
+
+    // https://wicg.github.io/file-system-access/#dom-filesystemsyncaccesshandle-flush
+    fn Flush(&self) -> ErrorResult {
+        self.operate(SyncAccessOperation::Flush).map(|_| ())
+    }
+
+    // https://wicg.github.io/file-system-access/#dom-filesystemsyncaccesshandle-close
+    fn Close(&self) {
+        if self.closed.get() {
+            return;
+        }
+        let _ = self.operate(SyncAccessOperation::Close);
+        self.closed.set(true);
+    }
+}
@@ -0,0 +1,372 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::callback::ExceptionHandling;
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::GeolocationBinding::GeolocationMethods;
+use crate::dom::bindings::codegen::Bindings::GeolocationBinding::{
+    PositionCallback, PositionErrorCallback, PositionOptions,
+};
+use crate::dom::bindings::codegen::Bindings::GeolocationPositionErrorBinding::GeolocationPositionErrorConstants;
+use crate::dom::bindings::codegen::Bindings::PermissionStatusBinding::{
+    PermissionName, PermissionState,
+};
+use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
+use crate::dom::bindings::refcounted::Trusted;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::geolocationposition::GeolocationPosition;
+use crate::dom::geolocationpositionerror::GeolocationPositionError;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::permissions::request_permission_to_use;
+use crate::task_source::TaskSource;
+use crate::timers::{OneshotTimerCallback, OneshotTimerHandle};
+use dom_struct::dom_struct;
+use embedder_traits::GeolocationPosition as DevicePosition;
+use script_traits::{MsDuration, ScriptMsg};
+use std::cell::Cell;
+use std::rc::Rc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// The error code and message reported to the error callback of a request.
+type PositionError = (u16, String);
+
+/// A call of `getCurrentPosition()` or `watchPosition()`, which waits for positions until it
+/// got one, or until it is cleared for watches.
+#[derive(JSTraceable, MallocSizeOf)]
+struct PositionRequest {
+    id: i32,
+    is_watch: bool,
+    #[ignore_malloc_size_of = "Rc"]
+    success_callback: Rc<PositionCallback>,
+    #[ignore_malloc_size_of = "Rc"]
+    error_callback: Option<Rc<PositionErrorCallback>>,
+    high_accuracy: bool,
+    timeout: u32,
+    /// Whether the request waits for the embedder to acquire a position, once the permission
+    /// was granted and the cached position was too old.
+    acquiring: bool,
+    timer: Option<OneshotTimerHandle>,
+}
+
+#[dom_struct]
+pub struct Geolocation {
+    reflector_: Reflector,
+    requests: DomRefCell<Vec<PositionRequest>>,
+    next_request_id: Cell<i32>,
+    /// <https://w3c.github.io/geolocation-api/#dfn-cachedposition>
+    #[ignore_malloc_size_of = "defined in embedder_traits"]
+    cached_position: Cell<Option<DevicePosition>>,
+    /// The accuracy the constellation was asked to acquire positions with, if it was.
+    watched_accuracy: Cell<Option<bool>>,
+}
+
+impl Geolocation {
+    fn new_inherited() -> Geolocation {
+        Geolocation {
+            reflector_: Reflector::new(),
+            requests: DomRefCell::new(vec![]),
+            next_request_id: Cell::new(0),
+            cached_position: Cell::new(None),
+            watched_accuracy: Cell::new(None),
+        }
+    }
+
+    pub fn new(global: &GlobalScope) -> DomRoot<Geolocation> {
+        reflect_dom_object(Box::new(Geolocation::new_inherited()), global)
+    }
+
+    /// <https://w3c.github.io/geolocation-api/#dfn-request-a-position>
+    fn request_position(
+        &self,
+        success_callback: Rc<PositionCallback>,
+        error_callback: Option<Rc<PositionErrorCallback>>,
+        options: &PositionOptions,
+        is_watch: bool,
+    ) -> i32 {
+        let id = self.next_request_id.get() + 1;
+        self.next_request_id.set(id);
+        self.requests.borrow_mut().push(PositionRequest {
+            id,
+            is_watch,
+            success_callback,
+            error_callback,
+            high_accuracy: options.enableHighAccuracy,
+            timeout: options.timeout,
+            acquiring: false,
+            timer: None,
+        });
+
+        // Prerendered documents only ask for positions once they are activated.
+        // https://wicg.github.io/nav-speculation/prerendering.html#implicitly-restricted
+        let document = self.global().as_window().Document();
+        if document.is_prerendering() {
+            let this = Trusted::new(self);
+            let maximum_age = options.maximumAge;
+            document.add_post_prerendering_activation_task(task!(geolocation_activated: move || {
+                this.root().start_request(id, maximum_age, is_watch);
+            }));
+            return id;
+        }
+        self.start_request(id, options.maximumAge, is_watch);
+        id
+    }
+
+    /// Checks that a request may acquire positions, and answers it with the cached position
+    /// or starts acquiring one.
+    fn start_request(&self, id: i32, maximum_age: u32, is_watch: bool) {
+        // The request may have been cleared while its document was prerendered.
+        if !self
+            .requests
+            .borrow()
+            .iter()
+            .any(|request| request.id == id)
+        {
+            return;
+        }
+
+        let global = self.global();
+        if !global.as_window().Document().is_fully_active() {
+            self.queue_result(
+                id,
+                Err((
+                    GeolocationPositionErrorConstants::POSITION_UNAVAILABLE,
+                    "The document is not fully active".to_owned(),
+                )),
+            );
+            return;
+        }
+
+        if request_permission_to_use(PermissionName::Geolocation, &global) !=
+            PermissionState::Granted
+        {
+            self.queue_result(
+                id,
+                Err((
+                    GeolocationPositionErrorConstants::PERMISSION_DENIED,
+                    "User denied Geolocation".to_owned(),
+                )),
+            );
+            return;
+        }
+
+        if let Some(position) = self.cached_position.get() {
+            if now_ms().saturating_sub(position.timestamp) <= maximum_age as u64 {
+                self.queue_result(id, Ok(position));
+                if !is_watch {
+                    return;
+                }
+            }
+        }
+
+        {
+            let mut requests = self.requests.borrow_mut();
+            if let Some(request) = requests.iter_mut().find(|request| request.id == id) {
+                request.acquiring = true;
+            }
+        }
+        self.start_timeout(id);
+        self.update_watch();
+    }
+
+    /// Fails the request with a `TIMEOUT` error if no position is acquired within its timeout.
+    fn start_timeout(&self, id: i32) {
+        let mut requests = self.requests.borrow_mut();
+        let request = match requests.iter_mut().find(|request| request.id == id) {
+            Some(request) => request,
+            None => return,
+        };
+        if request.timeout == u32::max_value() {
+            return;
+        }
+        let callback = GeolocationTimeoutCallback {
+            geolocation: Trusted::new(self),
+            id,
+        };
+        request.timer = Some(self.global().schedule_callback(
+            OneshotTimerCallback::GeolocationTimeout(callback),
+            MsDuration::new(request.timeout as u64),
+        ));
+    }
+
+    fn queue_result(&self, id: i32, result: Result<DevicePosition, PositionError>) {
+        let this = Trusted::new(self);
+        let global = self.global();
+        let _ = global.dom_manipulation_task_source().queue(
+            task!(geolocation_result: move || {
+                this.root().call_back(id, result);
+            }),
+            &global,
+        );
+    }
+
+    /// Calls the success or error callback of a request. One-shot requests, and watches whose
+    /// permission was denied, are removed afterwards.
+    fn call_back(&self, id: i32, result: Result<DevicePosition, PositionError>) {
+        let global = self.global();
+        let (success_callback, error_callback) = {
+            let mut requests = self.requests.borrow_mut();
+            let index = match requests.iter().position(|request| request.id == id) {
+                Some(index) => index,
+                None => return,
+            };
+            let request = &mut requests[index];
+            if let Some(timer) = request.timer.take() {
+                global.unschedule_callback(timer);
+            }
+            let callbacks = (
+                request.success_callback.clone(),
+                request.error_callback.clone(),
+            );
+            let permission_denied = match result {
+                Err((code, _)) => code == GeolocationPositionErrorConstants::PERMISSION_DENIED,
+                Ok(_) => false,
+            };
+            if !request.is_watch || permission_denied {
+                requests.remove(index);
+            }
+            callbacks
+        };
+
+        match result {
+            Ok(position) => {
+                // The timeout of a watch applies to each of the positions it waits for.
+                self.start_timeout(id);
+                let position = GeolocationPosition::new(&global, position);
+                let _ = success_callback.Call__(&position, ExceptionHandling::Report);
+            },
+            Err((code, message)) => {
+                if let Some(error_callback) = error_callback {
+                    let error = GeolocationPositionError::new(&global, code, message.into());
+                    let _ = error_callback.Call__(&error, ExceptionHandling::Report);
+                }
+            },
+        }
+        self.update_watch();
+    }
+
+    fn timed_out(&self, id: i32) {
+        if let Some(request) = self
+            .requests
+            .borrow_mut()
+            .iter_mut()
+            .find(|request| request.id == id)
+        {
+            request.timer = None;
+        }
+        self.call_back(
+            id,
+            Err((
+                GeolocationPositionErrorConstants::TIMEOUT,
+                "Timeout expired".to_owned(),
+            )),
+        );
+    }
+
+    /// Asks the constellation to send positions while requests wait for them, as precisely as
+    /// the most demanding request asked for.
+    fn update_watch(&self) {
+        let accuracy = {
+            let requests = self.requests.borrow();
+            let mut acquiring = requests.iter().filter(|request| request.acquiring);
+            match acquiring.next() {
+                Some(first) => {
+                    Some(first.high_accuracy || acquiring.any(|request| request.high_accuracy))
+                },
+                None => None,
+            }
+        };
+        if self.watched_accuracy.replace(accuracy) != accuracy {
+            let _ = self
+                .global()
+                .script_to_constellation_chan()
+                .send(ScriptMsg::WatchPosition(accuracy));
+        }
+    }
+
+    /// Reports a position acquired by the embedder, or its failure to acquire one, to the
+    /// requests waiting for positions.
+    /// <https://w3c.github.io/geolocation-api/#dfn-acquire-a-position>
+    pub fn position_changed(&self, result: Result<DevicePosition, String>) {
+        if let Ok(position) = result {
+            self.cached_position.set(Some(position));
+        }
+        let ids: Vec<i32> = self
+            .requests
+            .borrow()
+            .iter()
+            .filter(|request| request.acquiring)
+            .map(|request| request.id)
+            .collect();
+        for id in ids {
+            let result = result.clone().map_err(|message| {
+                (
+                    GeolocationPositionErrorConstants::POSITION_UNAVAILABLE,
+                    message,
+                )
+            });
+            self.call_back(id, result);
+        }
+    }
+}
+
+/// The milliseconds since the Unix epoch, which the timestamps of positions are relative to.
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_millis() as u64)
+}
+
+impl GeolocationMethods for Geolocation {
+    // https://w3c.github.io/geolocation-api/#getcurrentposition-method
+    fn GetCurrentPosition(
+        &self,
+        success_callback: Rc<PositionCallback>,
+        error_callback: Option<Rc<PositionErrorCallback>>,
+        options: &PositionOptions,
+    ) {
+        self.request_position(success_callback, error_callback, options, false);
+    }
+
+    // https://w3c.github.io/geolocation-api/#watchposition-method
+    fn WatchPosition(
+        &self,
+        success_callback: Rc<PositionCallback>,
+        error_callback: Option<Rc<PositionErrorCallback>>,
+        options: &PositionOptions,
+    ) -> i32 {
+        self.request_position(success_callback, error_callback, options, true)
+    }
+
+    // https://w3c.github.io/geolocation-api/#clearwatch-method
+    fn ClearWatch(&self, watch_id: i32) {
+        let timer = {
+            let mut requests = self.requests.borrow_mut();
+            let index = requests
+                .iter()
+                .position(|request| request.is_watch && request.id == watch_id);
+            match index {
+                Some(index) => requests.remove(index).timer,
+                None => return,
+            }
+        };
+        if let Some(timer) = timer {
+            self.global().unschedule_callback(timer);
+        }
+        self.update_watch();
+    }
+}
+
+#[derive(JSTraceable, MallocSizeOf)]
+pub struct GeolocationTimeoutCallback {
+    #[ignore_malloc_size_of = "non-owning"]
+    geolocation: Trusted<Geolocation>,
+    id: i32,
+}
+
+impl GeolocationTimeoutCallback {
+    pub fn invoke(self) {
+        self.geolocation.root().timed_out(self.id);
+    }
+}
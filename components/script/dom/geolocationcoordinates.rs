@@ -0,0 +1,71 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::GeolocationCoordinatesBinding::GeolocationCoordinatesMethods;
+use crate::dom::bindings::num::Finite;
+use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::globalscope::GlobalScope;
+use dom_struct::dom_struct;
+use embedder_traits::GeolocationPosition as DevicePosition;
+
+#[dom_struct]
+pub struct GeolocationCoordinates {
+    reflector_: Reflector,
+    #[ignore_malloc_size_of = "defined in embedder_traits"]
+    position: DevicePosition,
+}
+
+impl GeolocationCoordinates {
+    fn new_inherited(position: DevicePosition) -> GeolocationCoordinates {
+        GeolocationCoordinates {
+            reflector_: Reflector::new(),
+            position,
+        }
+    }
+
+    pub fn new(global: &GlobalScope, position: DevicePosition) -> DomRoot<GeolocationCoordinates> {
+        reflect_dom_object(
+            Box::new(GeolocationCoordinates::new_inherited(position)),
+            global,
+        )
+    }
+}
+
+impl GeolocationCoordinatesMethods for GeolocationCoordinates {
+    // https://w3c.github.io/geolocation-api/#accuracy-attribute
+    fn Accuracy(&self) -> Finite<f64> {
+        Finite::wrap(self.position.accuracy)
+    }
+
+    // https://w3c.github.io/geolocation-api/#latitude-attribute
+    fn Latitude(&self) -> Finite<f64> {
+        Finite::wrap(self.position.latitude)
+    }
+
+    // https://w3c.github.io/geolocation-api/#longitude-attribute
+    fn Longitude(&self) -> Finite<f64> {
+        Finite::wrap(self.position.longitude)
+    }
+
+    // https://w3c.github.io/geolocation-api/#altitude-attribute
+    fn GetAltitude(&self) -> Option<Finite<f64>> {
+        self.position.altitude.and_then(Finite::new)
+    }
+
+    // https://w3c.github.io/geolocation-api/#altitudeaccuracy-attribute
+    fn GetAltitudeAccuracy(&self) -> Option<Finite<f64>> {
+        self.position.altitude_accuracy.and_then(Finite::new)
+    }
+
+    // https://w3c.github.io/geolocation-api/#heading-attribute
+    fn GetHeading(&self) -> Option<Finite<f64>> {
+        self.position.heading.and_then(Finite::new)
+    }
+
+    // https://w3c.github.io/geolocation-api/#speed-attribute
+    fn GetSpeed(&self) -> Option<Finite<f64>> {
+        self.position.speed.and_then(Finite::new)
+    }
+}
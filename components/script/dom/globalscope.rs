@@ -51,6 +51,7 @@ use crate::task_source::file_reading::FileReadingTaskSource;
 use crate::task_source::networking::NetworkingTaskSource;
 use crate::task_source::performance_timeline::PerformanceTimelineTaskSource;
 use crate::task_source::port_message::PortMessageQueue;
+use crate::task_source::posted_task::PostedTaskSource;
 use crate::task_source::remote_event::RemoteEventTaskSource;
 use crate::task_source::timer::TimerTaskSource;
 use crate::task_source::websocket::WebsocketTaskSource;
@@ -77,7 +78,7 @@ use js::{JSCLASS_IS_DOMJSCLASS, JSCLASS_IS_GLOBAL};
 use msg::constellation_msg::{
     BlobId, BroadcastChannelRouterId, MessagePortId, MessagePortRouterId, PipelineId,
 };
-use net_traits::blob_url_store::{get_blob_origin, BlobBuf};
+use net_traits::blob_url_store::{get_blob_origin, BlobBuf, MAX_MEMORY_BLOB_SIZE};
 use net_traits::filemanager_thread::{
     FileManagerResult, FileManagerThreadMsg, ReadFileProgress, RelativePos,
 };
@@ -1281,7 +1282,17 @@ impl GlobalScope {
         BlobImpl::new_from_bytes(bytes, type_string)
     }
 
-    fn track_blob_info(&self, blob_info: BlobInfo, blob_id: BlobId) {
+    fn track_blob_info(&self, mut blob_info: BlobInfo, blob_id: BlobId) {
+        // Large memory-based blobs are handed over to the file manager right away, which keeps
+        // them on disk, instead of being held in memory for as long as they are alive.
+        let is_large = match blob_info.blob_impl.blob_data() {
+            BlobData::Memory(ref bytes) => bytes.len() as u64 > MAX_MEMORY_BLOB_SIZE,
+            _ => false,
+        };
+        if is_large {
+            self.promote(&mut blob_info, /* set_valid is */ false);
+        }
+
         let mut blob_state = self.blob_state.borrow_mut();
 
         match &mut *blob_state {
@@ -1355,6 +1366,10 @@ impl GlobalScope {
         if let BlobState::Managed(blobs_map) = &mut *blob_state {
             blobs_map.drain().for_each(|(_id, blob_info)| {
                 if let BlobData::File(ref f) = blob_info.blob_impl.blob_data() {
+                    // The blob URLs created by this global don't outlive it.
+                    if blob_info.has_url {
+                        self.revoke_file_url(f.get_id());
+                    }
                     self.decrement_file_ref(f.get_id());
                 }
             });
@@ -1372,6 +1387,16 @@ impl GlobalScope {
         let _ = rx.recv();
     }
 
+    fn revoke_file_url(&self, id: Uuid) {
+        let origin = get_blob_origin(&self.get_url());
+
+        let (tx, rx) = profile_ipc::channel(self.time_profiler_chan().clone()).unwrap();
+
+        let msg = FileManagerThreadMsg::RevokeBlobURL(id, origin, tx);
+        self.send_to_file_manager(msg);
+        let _ = rx.recv();
+    }
+
     /// Get a slice to the inner data of a Blob,
     /// In the case of a File-backed blob, this might incur synchronous read and caching.
     pub fn get_blob_bytes(&self, blob_id: &BlobId) -> Result<Vec<u8>, ()> {
@@ -1418,8 +1443,8 @@ impl GlobalScope {
                         },
                     };
 
-                    // Cache
-                    if is_new_buffer {
+                    // Cache, unless the blob is too large to be kept in memory
+                    if is_new_buffer && f.get_size() <= MAX_MEMORY_BLOB_SIZE {
                         f.cache_bytes(buffer.clone());
                     }
 
@@ -1606,23 +1631,28 @@ impl GlobalScope {
 
         let origin = get_blob_origin(&global_url);
 
+        // Large blobs are read back from the file manager when needed, rather than also being
+        // kept here.
+        let size = bytes.len() as u64;
+        let cache = if size <= MAX_MEMORY_BLOB_SIZE {
+            Some(bytes.clone())
+        } else {
+            None
+        };
+
         let blob_buf = BlobBuf {
             filename: None,
             type_string: blob_info.blob_impl.type_string(),
-            size: bytes.len() as u64,
-            bytes: bytes.to_vec(),
+            size,
+            bytes,
         };
 
         let id = Uuid::new_v4();
         let msg = FileManagerThreadMsg::PromoteMemory(id, blob_buf, set_valid, origin.clone());
         self.send_to_file_manager(msg);
 
-        *blob_info.blob_impl.blob_data_mut() = BlobData::File(FileBlob::new(
-            id.clone(),
-            None,
-            Some(bytes.to_vec()),
-            bytes.len() as u64,
-        ));
+        *blob_info.blob_impl.blob_data_mut() =
+            BlobData::File(FileBlob::new(id.clone(), None, cache, size));
 
         id
     }
@@ -2100,6 +2130,18 @@ impl GlobalScope {
         unreachable!();
     }
 
+    /// `TaskSource` to send messages to the posted task task source of
+    /// this global scope.
+    pub fn posted_task_source(&self) -> PostedTaskSource {
+        if let Some(window) = self.downcast::<Window>() {
+            return window.task_manager().posted_task_source();
+        }
+        if let Some(worker) = self.downcast::<WorkerGlobalScope>() {
+            return worker.posted_task_source();
+        }
+        unreachable!();
+    }
+
     /// `TaskSource` to send messages to the remote-event task source of
     /// this global scope.
     pub fn remote_event_task_source(&self) -> RemoteEventTaskSource {
@@ -2202,6 +2244,10 @@ impl GlobalScope {
         self.timers.unschedule_callback(handle);
     }
 
+    pub fn time_until_next_timer(&self) -> Option<MsDuration> {
+        self.timers.time_until_next_timer()
+    }
+
     /// <https://html.spec.whatwg.org/multipage/#timer-initialisation-steps>
     pub fn set_timeout_or_interval(
         &self,
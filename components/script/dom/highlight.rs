@@ -0,0 +1,126 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::HighlightBinding::{HighlightMethods, HighlightType};
+use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
+use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::range::Range;
+use crate::dom::window::Window;
+use dom_struct::dom_struct;
+use std::cell::Cell;
+
+/// <https://drafts.csswg.org/css-highlight-api-1/#highlight>
+#[dom_struct]
+pub struct Highlight {
+    reflector_: Reflector,
+    window: Dom<Window>,
+    /// The ranges of the highlight, in the order they were added.
+    ranges: DomRefCell<Vec<Dom<Range>>>,
+    priority: Cell<i32>,
+    type_: Cell<HighlightType>,
+}
+
+impl Highlight {
+    fn new_inherited(window: &Window) -> Highlight {
+        Highlight {
+            reflector_: Reflector::new(),
+            window: Dom::from_ref(window),
+            ranges: DomRefCell::new(vec![]),
+            priority: Cell::new(0),
+            type_: Cell::new(HighlightType::Highlight),
+        }
+    }
+
+    pub fn new(window: &Window) -> DomRoot<Highlight> {
+        reflect_dom_object(Box::new(Highlight::new_inherited(window)), window)
+    }
+
+    // https://drafts.csswg.org/css-highlight-api-1/#dom-highlight-highlight
+    #[allow(non_snake_case)]
+    pub fn Constructor(window: &Window, initial_ranges: Vec<DomRoot<Range>>) -> DomRoot<Highlight> {
+        let highlight = Highlight::new(window);
+        for range in initial_ranges {
+            highlight.Add(&range);
+        }
+        highlight
+    }
+
+    pub fn ranges(&self) -> Vec<DomRoot<Range>> {
+        self.ranges
+            .borrow()
+            .iter()
+            .map(|range| DomRoot::from_ref(&**range))
+            .collect()
+    }
+
+    /// Lets the document repaint the text that the highlight covers.
+    fn changed(&self) {
+        self.window.Document().highlights_changed();
+    }
+}
+
+impl HighlightMethods for Highlight {
+    // https://drafts.csswg.org/css-highlight-api-1/#highlight
+    fn Size(&self) -> u32 {
+        self.ranges.borrow().len() as u32
+    }
+
+    // https://drafts.csswg.org/css-highlight-api-1/#highlight
+    fn Has(&self, range: &Range) -> bool {
+        self.ranges.borrow().iter().any(|r| &**r == range)
+    }
+
+    // https://drafts.csswg.org/css-highlight-api-1/#highlight
+    fn Add(&self, range: &Range) -> DomRoot<Highlight> {
+        if !self.Has(range) {
+            self.ranges.borrow_mut().push(Dom::from_ref(range));
+            self.changed();
+        }
+        DomRoot::from_ref(self)
+    }
+
+    // https://drafts.csswg.org/css-highlight-api-1/#highlight
+    fn Delete(&self, range: &Range) -> bool {
+        let index = self.ranges.borrow().iter().position(|r| &**r == range);
+        match index {
+            Some(index) => {
+                self.ranges.borrow_mut().remove(index);
+                self.changed();
+                true
+            },
+            None => false,
+        }
+    }
+
+    // https://drafts.csswg.org/css-highlight-api-1/#highlight
+    fn Clear(&self) {
+        if !self.ranges.borrow().is_empty() {
+            self.ranges.borrow_mut().clear();
+            self.changed();
+        }
+    }
+
+    // https://drafts.csswg.org/css-highlight-api-1/#dom-highlight-priority
+    fn Priority(&self) -> i32 {
+        self.priority.get()
+    }
+
+    // https://drafts.csswg.org/css-highlight-api-1/#dom-highlight-priority
+    fn SetPriority(&self, priority: i32) {
+        self.priority.set(priority);
+        self.changed();
+    }
+
+    // https://drafts.csswg.org/css-highlight-api-1/#dom-highlight-type
+    fn Type(&self) -> HighlightType {
+        self.type_.get()
+    }
+
+    // https://drafts.csswg.org/css-highlight-api-1/#dom-highlight-type
+    fn SetType(&self, type_: HighlightType) {
+        self.type_.set(type_);
+    }
+}
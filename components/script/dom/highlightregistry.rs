@@ -0,0 +1,195 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::HighlightBinding::HighlightMethods;
+use crate::dom::bindings::codegen::Bindings::HighlightRegistryBinding::HighlightRegistryMethods;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::iterable::Iterable;
+use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
+use crate::dom::bindings::root::{Dom, DomRoot, LayoutDom};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::characterdata::{CharacterData, LayoutCharacterDataHelpers};
+use crate::dom::document::Document;
+use crate::dom::highlight::Highlight;
+use crate::dom::text::Text;
+use crate::editing;
+use dom_struct::dom_struct;
+use servo_atoms::Atom;
+use std::ops::Range;
+
+/// The part of a text node that a custom highlight covers, as a UTF-8 byte range.
+#[derive(JSTraceable, MallocSizeOf, PartialEq)]
+#[unrooted_must_root_lint::must_root]
+pub struct HighlightedText {
+    text: Dom<Text>,
+    name: Atom,
+    start: usize,
+    end: usize,
+}
+
+impl HighlightedText {
+    pub fn text(&self) -> &Text {
+        &self.text
+    }
+
+    /// The name of the highlight and the range of `text` that it covers, if this is in it
+    /// and still fits its data.
+    #[allow(unsafe_code)]
+    pub unsafe fn range_for_layout(&self, text: LayoutDom<Text>) -> Option<(Atom, Range<usize>)> {
+        if self.text.to_layout() != text {
+            return None;
+        }
+        let data = text.upcast::<CharacterData>().data_for_layout();
+        if !data.is_char_boundary(self.start) || !data.is_char_boundary(self.end) {
+            return None;
+        }
+        Some((self.name.clone(), self.start..self.end))
+    }
+}
+
+/// <https://drafts.csswg.org/css-highlight-api-1/#highlight-registry>
+#[dom_struct]
+pub struct HighlightRegistry {
+    reflector_: Reflector,
+    document: Dom<Document>,
+    /// The registered highlights by name, in the order they were registered.
+    highlights: DomRefCell<Vec<(DOMString, Dom<Highlight>)>>,
+}
+
+impl HighlightRegistry {
+    fn new_inherited(document: &Document) -> HighlightRegistry {
+        HighlightRegistry {
+            reflector_: Reflector::new(),
+            document: Dom::from_ref(document),
+            highlights: DomRefCell::new(vec![]),
+        }
+    }
+
+    pub fn new(document: &Document) -> DomRoot<HighlightRegistry> {
+        reflect_dom_object(
+            Box::new(HighlightRegistry::new_inherited(document)),
+            document.window(),
+        )
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.highlights.borrow().is_empty()
+    }
+
+    /// The text of the document that the registered highlights cover, in painting order:
+    /// highlights with a higher priority are painted over the others, and so are the ones
+    /// registered later among highlights with the same priority.
+    /// <https://drafts.csswg.org/css-highlight-api-1/#priorities>
+    #[allow(unrooted_must_root)]
+    pub fn highlighted_text(&self) -> Vec<HighlightedText> {
+        let highlights = self.highlights.borrow();
+        let mut order: Vec<usize> = (0..highlights.len()).collect();
+        order.sort_by_key(|&index| highlights[index].1.Priority());
+
+        let mut highlighted_text = vec![];
+        for index in order {
+            let (ref name, ref highlight) = highlights[index];
+            let name = Atom::from(&**name);
+            for range in highlight.ranges() {
+                for (text, start, end) in range.selected_text() {
+                    let in_document = text.upcast::<CharacterData>().owner_doc() == *self.document;
+                    if start >= end || !in_document {
+                        continue;
+                    }
+                    // Layout indexes text by bytes.
+                    let (start, end) = {
+                        let data = text.upcast::<CharacterData>().data();
+                        (
+                            editing::utf16_to_utf8_offset(&data, start),
+                            editing::utf16_to_utf8_offset(&data, end),
+                        )
+                    };
+                    highlighted_text.push(HighlightedText {
+                        text: Dom::from_ref(&*text),
+                        name: name.clone(),
+                        start,
+                        end,
+                    });
+                }
+            }
+        }
+        highlighted_text
+    }
+
+    fn position(&self, name: &DOMString) -> Option<usize> {
+        self.highlights
+            .borrow()
+            .iter()
+            .position(|&(ref registered, _)| registered == name)
+    }
+}
+
+impl HighlightRegistryMethods for HighlightRegistry {
+    // https://drafts.csswg.org/css-highlight-api-1/#highlight-registry
+    fn Size(&self) -> u32 {
+        self.highlights.borrow().len() as u32
+    }
+
+    // https://drafts.csswg.org/css-highlight-api-1/#highlight-registry
+    fn Has(&self, name: DOMString) -> bool {
+        self.position(&name).is_some()
+    }
+
+    // https://drafts.csswg.org/css-highlight-api-1/#highlight-registry
+    fn Get(&self, name: DOMString) -> Option<DomRoot<Highlight>> {
+        let index = self.position(&name)?;
+        Some(DomRoot::from_ref(&*self.highlights.borrow()[index].1))
+    }
+
+    // https://drafts.csswg.org/css-highlight-api-1/#highlight-registry
+    fn Set(&self, name: DOMString, highlight: &Highlight) -> DomRoot<HighlightRegistry> {
+        match self.position(&name) {
+            Some(index) => self.highlights.borrow_mut()[index].1 = Dom::from_ref(highlight),
+            None => self
+                .highlights
+                .borrow_mut()
+                .push((name, Dom::from_ref(highlight))),
+        }
+        self.document.highlights_changed();
+        DomRoot::from_ref(self)
+    }
+
+    // https://drafts.csswg.org/css-highlight-api-1/#highlight-registry
+    fn Delete(&self, name: DOMString) -> bool {
+        match self.position(&name) {
+            Some(index) => {
+                self.highlights.borrow_mut().remove(index);
+                self.document.highlights_changed();
+                true
+            },
+            None => false,
+        }
+    }
+
+    // https://drafts.csswg.org/css-highlight-api-1/#highlight-registry
+    fn Clear(&self) {
+        if !self.is_empty() {
+            self.highlights.borrow_mut().clear();
+            self.document.highlights_changed();
+        }
+    }
+}
+
+impl Iterable for HighlightRegistry {
+    type Key = DOMString;
+    type Value = DomRoot<Highlight>;
+
+    fn get_iterable_length(&self) -> u32 {
+        self.highlights.borrow().len() as u32
+    }
+
+    fn get_value_at_index(&self, index: u32) -> DomRoot<Highlight> {
+        DomRoot::from_ref(&*self.highlights.borrow()[index as usize].1)
+    }
+
+    fn get_key_at_index(&self, index: u32) -> DOMString {
+        self.highlights.borrow()[index as usize].0.clone()
+    }
+}
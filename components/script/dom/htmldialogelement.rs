@@ -4,14 +4,18 @@
 
 use crate::dom::bindings::cell::DomRefCell;
 use crate::dom::bindings::codegen::Bindings::HTMLDialogElementBinding::HTMLDialogElementMethods;
+use crate::dom::bindings::error::{Error, ErrorResult};
 use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::root::DomRoot;
 use crate::dom::bindings::str::DOMString;
-use crate::dom::document::Document;
+use crate::dom::document::{Document, FocusType};
 use crate::dom::element::Element;
 use crate::dom::eventtarget::EventTarget;
 use crate::dom::htmlelement::HTMLElement;
-use crate::dom::node::{window_from_node, Node};
+use crate::dom::node::{
+    document_from_node, window_from_node, Node, ShadowIncluding, UnbindContext,
+};
+use crate::dom::virtualmethods::VirtualMethods;
 use dom_struct::dom_struct;
 use html5ever::{LocalName, Prefix};
 
@@ -46,6 +50,39 @@ impl HTMLDialogElement {
             document,
         )
     }
+
+    /// <https://html.spec.whatwg.org/multipage/#dialog-focusing-steps>
+    fn run_focusing_steps(&self) {
+        let focusable_descendants: Vec<_> = self
+            .upcast::<Node>()
+            .traverse_preorder(ShadowIncluding::No)
+            .skip(1)
+            .filter_map(DomRoot::downcast::<Element>)
+            .filter(|element| element.is_focusable_area())
+            .collect();
+
+        // Steps 1 and 2.
+        let control = focusable_descendants
+            .iter()
+            .find(|element| element.has_attribute(&local_name!("autofocus")))
+            .or_else(|| focusable_descendants.first())
+            .cloned();
+
+        // Step 3.
+        let control = match control {
+            Some(control) => control,
+            None if self.upcast::<Element>().is_focusable_area() => {
+                DomRoot::from_ref(self.upcast::<Element>())
+            },
+            None => return,
+        };
+
+        // Step 4.
+        let document = document_from_node(self);
+        document.begin_focus_transaction();
+        document.request_focus(&control);
+        document.commit_focus_transaction(FocusType::Element);
+    }
 }
 
 impl HTMLDialogElementMethods for HTMLDialogElement {
@@ -66,6 +103,33 @@ impl HTMLDialogElementMethods for HTMLDialogElement {
         *self.return_value.borrow_mut() = return_value;
     }
 
+    // https://html.spec.whatwg.org/multipage/#dom-dialog-showmodal
+    fn ShowModal(&self) -> ErrorResult {
+        let element = self.upcast::<Element>();
+
+        // Step 1
+        if element.has_attribute(&local_name!("open")) {
+            return Err(Error::InvalidState);
+        }
+
+        // Step 2
+        if !element.is_connected() {
+            return Err(Error::InvalidState);
+        }
+
+        // Step 3
+        element.set_bool_attribute(&local_name!("open"), true);
+
+        // Steps 4, 5 and 6
+        // FIXME: The top layer is not rendered above the rest of the document yet.
+        document_from_node(self).add_modal_dialog(self);
+
+        // Step 7
+        self.run_focusing_steps();
+
+        Ok(())
+    }
+
     // https://html.spec.whatwg.org/multipage/#dom-dialog-close
     fn Close(&self, return_value: Option<DOMString>) {
         let element = self.upcast::<Element>();
@@ -85,7 +149,8 @@ impl HTMLDialogElementMethods for HTMLDialogElement {
             *self.return_value.borrow_mut() = new_value;
         }
 
-        // TODO: Step 4 implement pending dialog stack removal
+        // Step 4
+        document_from_node(self).remove_modal_dialog(self);
 
         // Step 5
         win.task_manager()
@@ -93,3 +158,18 @@ impl HTMLDialogElementMethods for HTMLDialogElement {
             .queue_simple_event(target, atom!("close"), &win);
     }
 }
+
+impl VirtualMethods for HTMLDialogElement {
+    fn super_type(&self) -> Option<&dyn VirtualMethods> {
+        Some(self.upcast::<HTMLElement>() as &dyn VirtualMethods)
+    }
+
+    // https://html.spec.whatwg.org/multipage/#the-dialog-element:html-element-removing-steps
+    fn unbind_from_tree(&self, context: &UnbindContext) {
+        if let Some(s) = self.super_type() {
+            s.unbind_from_tree(context);
+        }
+
+        document_from_node(self).remove_modal_dialog(self);
+    }
+}
@@ -3,6 +3,7 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use crate::dom::attr::Attr;
+use crate::dom::bindings::codegen::Bindings::EventBinding::EventMethods;
 use crate::dom::bindings::codegen::Bindings::EventHandlerBinding::EventHandlerNonNull;
 use crate::dom::bindings::codegen::Bindings::EventHandlerBinding::OnErrorEventHandlerNonNull;
 use crate::dom::bindings::codegen::Bindings::HTMLElementBinding::HTMLElementMethods;
@@ -14,11 +15,13 @@ use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::inheritance::{ElementTypeId, HTMLElementTypeId, NodeTypeId};
 use crate::dom::bindings::root::{Dom, DomRoot, MutNullableDom};
 use crate::dom::bindings::str::DOMString;
+use crate::dom::compositionevent::CompositionEvent;
 use crate::dom::cssstyledeclaration::{CSSModificationAccess, CSSStyleDeclaration, CSSStyleOwner};
 use crate::dom::document::{Document, FocusType};
 use crate::dom::documentfragment::DocumentFragment;
 use crate::dom::domstringmap::DOMStringMap;
 use crate::dom::element::{AttributeMutation, Element};
+use crate::dom::event::Event;
 use crate::dom::eventtarget::EventTarget;
 use crate::dom::htmlbodyelement::HTMLBodyElement;
 use crate::dom::htmlbrelement::HTMLBRElement;
@@ -27,10 +30,14 @@ use crate::dom::htmlhtmlelement::HTMLHtmlElement;
 use crate::dom::htmlinputelement::{HTMLInputElement, InputType};
 use crate::dom::htmllabelelement::HTMLLabelElement;
 use crate::dom::htmltextareaelement::HTMLTextAreaElement;
+use crate::dom::keyboardevent::KeyboardEvent;
 use crate::dom::node::{document_from_node, window_from_node};
 use crate::dom::node::{BindContext, Node, NodeFlags, ShadowIncluding};
+use crate::dom::stylepropertymap::StylePropertyMap;
 use crate::dom::text::Text;
 use crate::dom::virtualmethods::VirtualMethods;
+use crate::editing;
+use crate::spellcheck;
 use dom_struct::dom_struct;
 use html5ever::{LocalName, Prefix};
 use script_layout_interface::message::QueryMsg;
@@ -44,6 +51,7 @@ use style::element_state::*;
 pub struct HTMLElement {
     element: Element,
     style_decl: MutNullableDom<CSSStyleDeclaration>,
+    attribute_style_map: MutNullableDom<StylePropertyMap>,
     dataset: MutNullableDom<DOMStringMap>,
 }
 
@@ -71,6 +79,7 @@ impl HTMLElement {
                 document,
             ),
             style_decl: Default::default(),
+            attribute_style_map: Default::default(),
             dataset: Default::default(),
         }
     }
@@ -122,17 +131,16 @@ impl HTMLElement {
                     }
                 },
                 _ => {
-                    if let Some(attr) = element.get_attribute(&ns!(), &local_name!("draggable")) {
-                        let value = attr.value();
-                        let is_true = match *value {
+                    let is_draggable = element
+                        .get_attribute(&ns!(), &local_name!("draggable"))
+                        .map_or(false, |attr| match *attr.value() {
                             AttrValue::String(ref string) => string == "true",
                             _ => false,
-                        };
-                        node.set_flag(NodeFlags::SEQUENTIALLY_FOCUSABLE, is_true);
-                    } else {
-                        node.set_flag(NodeFlags::SEQUENTIALLY_FOCUSABLE, false);
-                    }
-                    //TODO set SEQUENTIALLY_FOCUSABLE flag if editing host
+                        });
+                    node.set_flag(
+                        NodeFlags::SEQUENTIALLY_FOCUSABLE,
+                        is_draggable || self.is_editing_host(),
+                    );
                     //TODO set SEQUENTIALLY_FOCUSABLE flag if "sorting interface th elements"
                 },
             }
@@ -154,6 +162,12 @@ impl HTMLElementMethods for HTMLElement {
         })
     }
 
+    // https://drafts.css-houdini.org/css-typed-om-1/#dom-elementcssinlinestyle-attributestylemap
+    fn AttributeStyleMap(&self) -> DomRoot<StylePropertyMap> {
+        self.attribute_style_map
+            .or_init(|| StylePropertyMap::new(&window_from_node(self), &self.Style()))
+    }
+
     // https://html.spec.whatwg.org/multipage/#attr-title
     make_getter!(Title, "title");
     // https://html.spec.whatwg.org/multipage/#attr-title
@@ -169,6 +183,18 @@ impl HTMLElementMethods for HTMLElement {
     // https://html.spec.whatwg.org/multipage/#dom-hidden
     make_bool_setter!(SetHidden, "hidden");
 
+    // https://html.spec.whatwg.org/multipage/#dom-inert
+    fn Inert(&self) -> bool {
+        self.upcast::<Element>()
+            .has_attribute(&LocalName::from("inert"))
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-inert
+    fn SetInert(&self, value: bool) {
+        self.upcast::<Element>()
+            .set_bool_attribute(&LocalName::from("inert"), value)
+    }
+
     // https://html.spec.whatwg.org/multipage/#the-dir-attribute
     make_getter!(Dir, "dir");
     // https://html.spec.whatwg.org/multipage/#the-dir-attribute
@@ -552,6 +578,35 @@ impl HTMLElementMethods for HTMLElement {
         Node::replace_all(Some(fragment.upcast()), self.upcast::<Node>());
     }
 
+    // https://html.spec.whatwg.org/multipage/#dom-contenteditable
+    fn ContentEditable(&self) -> DOMString {
+        DOMString::from(match self.content_editable_state() {
+            ContentEditableState::True => "true",
+            ContentEditableState::False => "false",
+            ContentEditableState::Inherit => "inherit",
+        })
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-contenteditable
+    fn SetContentEditable(&self, value: DOMString) -> ErrorResult {
+        let element = self.upcast::<Element>();
+        let value = value.to_ascii_lowercase();
+        match &*value {
+            "true" | "false" => element
+                .set_string_attribute(&local_name!("contenteditable"), DOMString::from(&*value)),
+            "inherit" => {
+                element.remove_attribute(&ns!(), &local_name!("contenteditable"));
+            },
+            _ => return Err(Error::Syntax),
+        }
+        Ok(())
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-iscontenteditable
+    fn IsContentEditable(&self) -> bool {
+        self.upcast::<Node>().editing_host().is_some()
+    }
+
     // https://html.spec.whatwg.org/multipage/#dom-translate
     fn Translate(&self) -> bool {
         self.upcast::<Element>().is_translate_enabled()
@@ -568,6 +623,22 @@ impl HTMLElementMethods for HTMLElement {
             },
         );
     }
+
+    // https://html.spec.whatwg.org/multipage/#dom-spellcheck
+    fn Spellcheck(&self) -> bool {
+        spellcheck::is_enabled(self.upcast())
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-spellcheck
+    fn SetSpellcheck(&self, value: bool) {
+        self.upcast::<Element>().set_string_attribute(
+            &local_name!("spellcheck"),
+            match value {
+                true => DOMString::from("true"),
+                false => DOMString::from("false"),
+            },
+        );
+    }
 }
 
 fn append_text_node_to_fragment(document: &Document, fragment: &DocumentFragment, text: String) {
@@ -641,7 +712,40 @@ fn to_camel_case(name: &str) -> Option<DOMString> {
     Some(DOMString::from(result))
 }
 
+/// The state of the `contenteditable` attribute.
+/// https://html.spec.whatwg.org/multipage/#attr-contenteditable
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ContentEditableState {
+    True,
+    False,
+    Inherit,
+}
+
 impl HTMLElement {
+    pub fn content_editable_state(&self) -> ContentEditableState {
+        let element = self.upcast::<Element>();
+        match element.get_attribute(&ns!(), &local_name!("contenteditable")) {
+            Some(attr) => {
+                let value = attr.value();
+                if value.is_empty() || value.eq_ignore_ascii_case("true") {
+                    ContentEditableState::True
+                } else if value.eq_ignore_ascii_case("false") {
+                    ContentEditableState::False
+                } else {
+                    ContentEditableState::Inherit
+                }
+            },
+            None => ContentEditableState::Inherit,
+        }
+    }
+
+    // https://html.spec.whatwg.org/multipage/#editing-host
+    pub fn is_editing_host(&self) -> bool {
+        self.upcast::<Node>()
+            .editing_host()
+            .map_or(false, |host| &*host == self.upcast::<Element>())
+    }
+
     pub fn set_custom_attr(&self, name: DOMString, value: DOMString) -> ErrorResult {
         if name
             .chars()
@@ -823,6 +927,20 @@ impl VirtualMethods for HTMLElement {
     fn attribute_mutated(&self, attr: &Attr, mutation: AttributeMutation) {
         self.super_type().unwrap().attribute_mutated(attr, mutation);
         match (attr.local_name(), mutation) {
+            (&local_name!("contenteditable"), _) => {
+                // This element and the editable elements below it may have gained or lost
+                // an editing host.
+                for node in self.upcast::<Node>().traverse_preorder(ShadowIncluding::No) {
+                    if let Some(element) = node.downcast::<HTMLElement>() {
+                        element.update_sequentially_focusable_status();
+                    }
+                }
+            },
+            (&local_name!("spellcheck"), _) => {
+                if let Some(element) = spellcheck::checked_element(self.upcast()) {
+                    document_from_node(self).request_spellcheck(&element);
+                }
+            },
             (name, AttributeMutation::Set(_)) if name.starts_with("on") => {
                 let evtarget = self.upcast::<EventTarget>();
                 let source_line = 1; //TODO(#9604) get current JS execution line
@@ -845,6 +963,28 @@ impl VirtualMethods for HTMLElement {
         self.update_sequentially_focusable_status();
     }
 
+    fn handle_event(&self, event: &Event) {
+        if let Some(s) = self.super_type() {
+            s.handle_event(event);
+        }
+
+        if event.DefaultPrevented() || !self.is_editing_host() {
+            return;
+        }
+        if let Some(keyevent) = event.downcast::<KeyboardEvent>() {
+            if event.type_() == atom!("keydown") {
+                if editing::handle_keydown(self.upcast(), keyevent) {
+                    event.mark_as_handled();
+                }
+            } else if event.type_() == atom!("keypress") {
+                // The editor has already acted on the keydown.
+                event.mark_as_handled();
+            }
+        } else if let Some(compositionevent) = event.downcast::<CompositionEvent>() {
+            editing::handle_composition(self.upcast(), compositionevent);
+        }
+    }
+
     fn parse_plain_attribute(&self, name: &LocalName, value: DOMString) -> AttrValue {
         match name {
             &local_name!("itemprop") => AttrValue::from_serialized_tokenlist(value.into()),
@@ -51,6 +51,7 @@ use crate::dom::submitevent::SubmitEvent;
 use crate::dom::validitystate::ValidationFlags;
 use crate::dom::virtualmethods::VirtualMethods;
 use crate::dom::window::Window;
+use crate::password_manager;
 use crate::task_source::TaskSource;
 use dom_struct::dom_struct;
 use encoding_rs::{Encoding, UTF_8};
@@ -654,6 +655,7 @@ impl HTMLFormElement {
             Ok(url) => url,
             Err(_) => return,
         };
+        password_manager::form_submitted(self, &action_components);
         // Step 15-17
         let scheme = action_components.scheme().to_owned();
         let enctype = submitter.enctype();
@@ -20,6 +20,9 @@ use crate::dom::bindings::root::{DomRoot, LayoutDom, MutNullableDom};
 use crate::dom::bindings::str::{DOMString, USVString};
 use crate::dom::document::Document;
 use crate::dom::element::{cors_setting_for_element, referrer_policy_for_element};
+use crate::dom::element::{
+    fetch_priority_for_element, reflect_fetch_priority_attribute, set_fetch_priority_attribute,
+};
 use crate::dom::element::{reflect_cross_origin_attribute, set_cross_origin_attribute};
 use crate::dom::element::{
     AttributeMutation, CustomElementCreationMode, Element, ElementCreator, RawLayoutElementHelpers,
@@ -61,7 +64,7 @@ use net_traits::image::base::{Image, ImageMetadata};
 use net_traits::image_cache::UsePlaceholder;
 use net_traits::image_cache::{CanRequestImages, CorsStatus, ImageCache, ImageOrMetadataAvailable};
 use net_traits::image_cache::{ImageResponder, ImageResponse, ImageState, PendingImageId};
-use net_traits::request::{CorsSettings, Destination, Initiator, RequestBuilder};
+use net_traits::request::{CorsSettings, Destination, Initiator, RequestBuilder, RequestPriority};
 use net_traits::{FetchMetadata, FetchResponseListener, FetchResponseMsg, NetworkError};
 use net_traits::{ReferrerPolicy, ResourceFetchTiming, ResourceTimingType};
 use num_traits::ToPrimitive;
@@ -298,13 +301,15 @@ pub(crate) fn image_fetch_request(
     pipeline_id: PipelineId,
     cors_setting: Option<CorsSettings>,
     referrer_policy: Option<ReferrerPolicy>,
+    priority: RequestPriority,
     from_picture_or_srcset: FromPictureOrSrcSet,
 ) -> RequestBuilder {
     let mut request =
         create_a_potential_cors_request(img_url, Destination::Image, cors_setting, None)
             .origin(origin)
             .pipeline_id(Some(pipeline_id))
-            .referrer_policy(referrer_policy);
+            .referrer_policy(referrer_policy)
+            .priority(priority);
     if from_picture_or_srcset == FromPictureOrSrcSet::Yes {
         request = request.initiator(Initiator::ImageSet);
     }
@@ -385,6 +390,7 @@ impl HTMLImageElement {
             document.global().pipeline_id(),
             cors_setting_for_element(self.upcast()),
             referrer_policy_for_element(self.upcast()),
+            fetch_priority_for_element(self.upcast()),
             if Self::uses_srcset_or_picture(self.upcast()) {
                 FromPictureOrSrcSet::Yes
             } else {
@@ -1488,6 +1494,16 @@ impl HTMLImageElementMethods for HTMLImageElement {
         set_cross_origin_attribute(self.upcast::<Element>(), value);
     }
 
+    // https://html.spec.whatwg.org/multipage/#dom-img-fetchpriority
+    fn FetchPriority(&self) -> DOMString {
+        reflect_fetch_priority_attribute(self.upcast::<Element>())
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-img-fetchpriority
+    fn SetFetchPriority(&self, value: DOMString) {
+        set_fetch_priority_attribute(self.upcast::<Element>(), value);
+    }
+
     // https://html.spec.whatwg.org/multipage/#dom-img-usemap
     make_getter!(UseMap, "usemap");
     // https://html.spec.whatwg.org/multipage/#dom-img-usemap
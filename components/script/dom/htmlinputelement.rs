@@ -40,10 +40,11 @@ use crate::dom::node::{
     BindContext, CloneChildrenFlag, Node, NodeDamage, ShadowIncluding, UnbindContext,
 };
 use crate::dom::nodelist::NodeList;
-use crate::dom::textcontrol::{TextControlElement, TextControlSelection};
+use crate::dom::textcontrol::{self, TextControlElement, TextControlSelection};
 use crate::dom::validation::Validatable;
 use crate::dom::validitystate::ValidationFlags;
 use crate::dom::virtualmethods::VirtualMethods;
+use crate::password_manager;
 use crate::script_runtime::JSContext as SafeJSContext;
 use crate::textinput::KeyReaction::{
     DispatchInput, Nothing, RedrawSelection, TriggerDefaultAction,
@@ -1561,7 +1562,7 @@ impl HTMLInputElement {
             match recv.recv().expect("IpcSender side error") {
                 Ok(selected_files) => {
                     for selected in selected_files {
-                        files.push(File::new_from_selected(&window, selected));
+                        files.push(File::new_from_selected(window.upcast(), selected));
                     }
                 },
                 Err(err) => error = Some(err),
@@ -1587,7 +1588,7 @@ impl HTMLInputElement {
 
             match recv.recv().expect("IpcSender side error") {
                 Ok(selected) => {
-                    files.push(File::new_from_selected(&window, selected));
+                    files.push(File::new_from_selected(window.upcast(), selected));
                 },
                 Err(err) => error = Some(err),
             };
@@ -1763,6 +1764,14 @@ impl HTMLInputElement {
         TextControlSelection::new(&self, &self.textinput)
     }
 
+    /// The selected text of the control, unless it is a password field.
+    pub fn selected_text(&self) -> Option<DOMString> {
+        if self.input_type() == InputType::Password {
+            return None;
+        }
+        self.selection().selected_text()
+    }
+
     // https://html.spec.whatwg.org/multipage/#implicit-submission
     #[allow(unsafe_code)]
     fn implicit_submission(&self) {
@@ -2033,6 +2042,10 @@ impl VirtualMethods for HTMLInputElement {
                             self.filelist.set(Some(&filelist));
                         }
 
+                        if new_type == InputType::Password && self.upcast::<Node>().is_connected() {
+                            password_manager::password_field_added(self);
+                        }
+
                         let new_value_mode = self.value_mode();
 
                         match (&old_value_mode, old_idl_value.is_empty(), new_value_mode) {
@@ -2188,6 +2201,9 @@ impl VirtualMethods for HTMLInputElement {
         }
         self.upcast::<Element>()
             .check_ancestors_disabled_state_for_form_control();
+        if context.tree_connected && self.input_type() == InputType::Password {
+            password_manager::password_field_added(self);
+        }
     }
 
     fn unbind_from_tree(&self, context: &UnbindContext) {
@@ -2248,9 +2264,13 @@ impl VirtualMethods for HTMLInputElement {
             self.input_type().is_textual_or_password()
         {
             if let Some(keyevent) = event.downcast::<KeyboardEvent>() {
+                let old_selection = self.textinput.borrow().selection_state();
                 // This can't be inlined, as holding on to textinput.borrow_mut()
                 // during self.implicit_submission will cause a panic.
                 let action = self.textinput.borrow_mut().handle_keydown(keyevent);
+                if self.textinput.borrow().selection_state() != old_selection {
+                    textcontrol::queue_selectionchange(self);
+                }
                 match action {
                     TriggerDefaultAction => {
                         self.implicit_submission();
@@ -2259,6 +2279,7 @@ impl VirtualMethods for HTMLInputElement {
                         self.value_dirty.set(true);
                         self.update_placeholder_shown_state();
                         self.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
+                        document_from_node(self).request_spellcheck(self.upcast());
                         event.mark_as_handled();
                     },
                     RedrawSelection => {
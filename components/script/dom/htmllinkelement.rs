@@ -7,6 +7,8 @@ use crate::dom::bindings::cell::DomRefCell;
 use crate::dom::bindings::codegen::Bindings::DOMTokenListBinding::DOMTokenListBinding::DOMTokenListMethods;
 use crate::dom::bindings::codegen::Bindings::HTMLLinkElementBinding::HTMLLinkElementMethods;
 use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::refcounted::Trusted;
+use crate::dom::bindings::reflector::DomObject;
 use crate::dom::bindings::root::{DomRoot, MutNullableDom};
 use crate::dom::bindings::str::{DOMString, USVString};
 use crate::dom::cssstylesheet::CSSStyleSheet;
@@ -15,24 +17,41 @@ use crate::dom::domtokenlist::DOMTokenList;
 use crate::dom::element::{
     cors_setting_for_element, reflect_cross_origin_attribute, set_cross_origin_attribute,
 };
+use crate::dom::element::{
+    fetch_priority_for_element, reflect_fetch_priority_attribute, set_fetch_priority_attribute,
+};
 use crate::dom::element::{AttributeMutation, Element, ElementCreator};
+use crate::dom::eventtarget::EventTarget;
+use crate::dom::globalscope::GlobalScope;
 use crate::dom::htmlelement::HTMLElement;
+use crate::dom::htmlscriptelement::SCRIPT_JS_MIMES;
 use crate::dom::node::{
     document_from_node, stylesheets_owner_from_node, window_from_node, BindContext, Node,
     UnbindContext,
 };
+use crate::dom::performanceresourcetiming::InitiatorType;
 use crate::dom::stylesheet::StyleSheet as DOMStyleSheet;
 use crate::dom::virtualmethods::VirtualMethods;
+use crate::fetch::create_a_potential_cors_request;
+use crate::network_listener::{self, NetworkListener, PreInvoke, ResourceTimingListener};
 use crate::stylesheet_loader::{StylesheetContextSource, StylesheetLoader, StylesheetOwner};
 use cssparser::{Parser as CssParser, ParserInput};
 use dom_struct::dom_struct;
 use embedder_traits::EmbedderMsg;
 use html5ever::{LocalName, Prefix};
-use net_traits::ReferrerPolicy;
+use ipc_channel::ipc;
+use ipc_channel::router::ROUTER;
+use mime::Mime;
+use net_traits::request::{Destination, Initiator, Referrer, RequestBuilder};
+use net_traits::{CoreResourceMsg, FetchMetadata, FetchResponseListener, NetworkError};
+use net_traits::{ReferrerPolicy, ResourceFetchTiming, ResourceTimingType};
 use servo_arc::Arc;
+use servo_url::ServoUrl;
 use std::borrow::ToOwned;
 use std::cell::Cell;
 use std::default::Default;
+use std::sync::Arc as StdArc;
+use std::sync::Mutex;
 use style::attr::AttrValue;
 use style::media_queries::MediaList;
 use style::parser::ParserContext as CssParserContext;
@@ -178,6 +197,62 @@ fn is_favicon(value: &Option<String>) -> bool {
     }
 }
 
+fn has_link_type(value: &Option<String>, link_type: &str) -> bool {
+    match *value {
+        Some(ref value) => value
+            .split(HTML_SPACE_CHARACTERS)
+            .any(|s| s.eq_ignore_ascii_case(link_type)),
+        None => false,
+    }
+}
+
+/// Translates the value of the `as` attribute to the destination of the request, if it is
+/// one that links can fetch.
+/// <https://fetch.spec.whatwg.org/#concept-potential-destination-translate>
+fn translate_potential_destination(value: &str) -> Option<Destination> {
+    let destination = match &*value.to_ascii_lowercase() {
+        "fetch" => Destination::None,
+        "audio" => Destination::Audio,
+        "document" => Destination::Document,
+        "embed" => Destination::Embed,
+        "font" => Destination::Font,
+        "image" => Destination::Image,
+        "manifest" => Destination::Manifest,
+        "object" => Destination::Object,
+        "report" => Destination::Report,
+        "script" => Destination::Script,
+        "sharedworker" => Destination::SharedWorker,
+        "style" => Destination::Style,
+        "track" => Destination::Track,
+        "video" => Destination::Video,
+        "worker" => Destination::Worker,
+        "xslt" => Destination::Xslt,
+        _ => return None,
+    };
+    Some(destination)
+}
+
+/// Whether a resource of the MIME type in the `type` attribute of a preload link can be used
+/// for the destination, so that preloads of unsupported formats are skipped.
+/// <https://html.spec.whatwg.org/multipage/#link-type-preload>
+fn is_supported_mime_type(value: &str, destination: Destination) -> bool {
+    let mime: Mime = match value.trim().parse() {
+        Ok(mime) => mime,
+        Err(_) => return false,
+    };
+    let type_ = mime.type_().as_str();
+    match destination {
+        Destination::Script => SCRIPT_JS_MIMES.contains(&mime.essence_str()),
+        Destination::Style => mime.essence_str() == "text/css",
+        Destination::Image => type_ == "image",
+        Destination::Font => type_ == "font" || type_ == "application",
+        Destination::Audio => type_ == "audio",
+        Destination::Video => type_ == "video",
+        Destination::Track => mime.essence_str() == "text/vtt",
+        _ => true,
+    }
+}
+
 impl VirtualMethods for HTMLLinkElement {
     fn super_type(&self) -> Option<&dyn VirtualMethods> {
         Some(self.upcast::<HTMLElement>() as &dyn VirtualMethods)
@@ -198,6 +273,7 @@ impl VirtualMethods for HTMLLinkElement {
                     let sizes = get_attr(self.upcast(), &local_name!("sizes"));
                     self.handle_favicon_url(rel.as_ref().unwrap(), &attr.value(), &sizes);
                 }
+                self.handle_resource_hints(&rel, &attr.value());
             },
             &local_name!("sizes") => {
                 if is_favicon(&rel) {
@@ -210,6 +286,13 @@ impl VirtualMethods for HTMLLinkElement {
                     }
                 }
             },
+            name if &**name == "as" => {
+                if has_link_type(&rel, "preload") {
+                    if let Some(ref href) = get_attr(self.upcast(), &local_name!("href")) {
+                        self.handle_preload_url(href);
+                    }
+                }
+            },
             _ => {},
         }
     }
@@ -245,6 +328,9 @@ impl VirtualMethods for HTMLLinkElement {
                 },
                 _ => {},
             }
+            if let Some(ref href) = href {
+                self.handle_resource_hints(&rel, href);
+            }
         }
     }
 
@@ -331,6 +417,128 @@ impl HTMLLinkElement {
         );
     }
 
+    /// Fetches the resources that `preload` and `prefetch` links hint at, and resolves the
+    /// origins of `preconnect` and `dns-prefetch` links.
+    fn handle_resource_hints(&self, rel: &Option<String>, href: &str) {
+        if has_link_type(rel, "preload") {
+            self.handle_preload_url(href);
+        }
+        if has_link_type(rel, "prefetch") {
+            self.handle_prefetch_url(href);
+        }
+        if has_link_type(rel, "preconnect") || has_link_type(rel, "dns-prefetch") {
+            self.handle_dns_prefetch_url(href);
+        }
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#link-type-preload>
+    fn handle_preload_url(&self, href: &str) {
+        let document = document_from_node(self);
+        if document.browsing_context().is_none() || href.is_empty() {
+            return;
+        }
+        let url = match document.base_url().join(href) {
+            Ok(url) => url,
+            Err(e) => {
+                debug!("Parsing url {} failed: {}", href, e);
+                return;
+            },
+        };
+
+        let element = self.upcast::<Element>();
+        let destination = match get_attr(element, &LocalName::from("as")) {
+            Some(ref value) => translate_potential_destination(value),
+            None => None,
+        };
+        let destination = match destination {
+            Some(Destination::Document) | None => return,
+            Some(destination) => destination,
+        };
+        if let Some(ref type_) = get_attr(element, &local_name!("type")) {
+            if !is_supported_mime_type(type_, destination) {
+                return;
+            }
+        }
+
+        let cors_setting = cors_setting_for_element(element);
+        let integrity_metadata = get_attr(element, &local_name!("integrity")).unwrap_or_default();
+        let request = create_a_potential_cors_request(url, destination, cors_setting, None)
+            .integrity_metadata(integrity_metadata)
+            .preload(true);
+        self.fetch_resource_hint(request);
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#link-type-prefetch>
+    fn handle_prefetch_url(&self, href: &str) {
+        let document = document_from_node(self);
+        if document.browsing_context().is_none() || href.is_empty() {
+            return;
+        }
+        let url = match document.base_url().join(href) {
+            Ok(url) => url,
+            Err(e) => {
+                debug!("Parsing url {} failed: {}", href, e);
+                return;
+            },
+        };
+
+        let cors_setting = cors_setting_for_element(self.upcast());
+        let request = create_a_potential_cors_request(url, Destination::None, cors_setting, None)
+            .initiator(Initiator::Prefetch);
+        self.fetch_resource_hint(request);
+    }
+
+    /// Fetches the resource that a link hints at for the document, which fires `load` or
+    /// `error` at the link once it was fetched.
+    fn fetch_resource_hint(&self, request: RequestBuilder) {
+        let document = document_from_node(self);
+        let url = request.url.clone();
+        let request = request
+            .origin(document.origin().immutable().clone())
+            .pipeline_id(Some(self.global().pipeline_id()))
+            .referrer(Some(Referrer::ReferrerUrl(document.url())))
+            .referrer_policy(self.referrer_policy().or(document.get_referrer_policy()))
+            .priority(fetch_priority_for_element(self.upcast()));
+
+        let context = StdArc::new(Mutex::new(ResourceHintContext {
+            link: Trusted::new(self),
+            url,
+            status: Ok(()),
+            resource_timing: ResourceFetchTiming::new(ResourceTimingType::Resource),
+        }));
+        let (action_sender, action_receiver) = ipc::channel().unwrap();
+        let (task_source, canceller) = document
+            .window()
+            .task_manager()
+            .networking_task_source_with_canceller();
+        let listener = NetworkListener {
+            context,
+            task_source,
+            canceller: Some(canceller),
+        };
+        ROUTER.add_route(
+            action_receiver.to_opaque(),
+            Box::new(move |message| {
+                listener.notify_fetch(message.to().unwrap());
+            }),
+        );
+        document.fetch_async_background(request, action_sender);
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#link-type-dns-prefetch>
+    fn handle_dns_prefetch_url(&self, href: &str) {
+        let document = document_from_node(self);
+        match document.base_url().join(href) {
+            Ok(url) => {
+                let _ = self
+                    .global()
+                    .core_resource_thread()
+                    .send(CoreResourceMsg::PrefetchDns(url));
+            },
+            Err(e) => debug!("Parsing url {} failed: {}", href, e),
+        }
+    }
+
     fn handle_favicon_url(&self, _rel: &str, href: &str, _sizes: &Option<String>) {
         let document = document_from_node(self);
         match document.base_url().join(href) {
@@ -460,8 +668,100 @@ impl HTMLLinkElementMethods for HTMLLinkElement {
         set_cross_origin_attribute(self.upcast::<Element>(), value);
     }
 
+    // https://html.spec.whatwg.org/multipage/#dom-link-fetchpriority
+    fn FetchPriority(&self) -> DOMString {
+        reflect_fetch_priority_attribute(self.upcast::<Element>())
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-link-fetchpriority
+    fn SetFetchPriority(&self, value: DOMString) {
+        set_fetch_priority_attribute(self.upcast::<Element>(), value);
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-link-as
+    fn As(&self) -> DOMString {
+        let value = get_attr(self.upcast(), &LocalName::from("as")).unwrap_or_default();
+        match translate_potential_destination(&value) {
+            Some(_) => DOMString::from(value.to_ascii_lowercase()),
+            None => DOMString::new(),
+        }
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-link-as
+    fn SetAs(&self, value: DOMString) {
+        self.upcast::<Element>()
+            .set_string_attribute(&LocalName::from("as"), value);
+    }
+
     // https://drafts.csswg.org/cssom/#dom-linkstyle-sheet
     fn GetSheet(&self) -> Option<DomRoot<DOMStyleSheet>> {
         self.get_cssom_stylesheet().map(DomRoot::upcast)
     }
 }
+
+/// The fetch of the resource that a `preload` or `prefetch` link hints at.
+struct ResourceHintContext {
+    link: Trusted<HTMLLinkElement>,
+    url: ServoUrl,
+    status: Result<(), NetworkError>,
+    resource_timing: ResourceFetchTiming,
+}
+
+impl PreInvoke for ResourceHintContext {}
+
+impl FetchResponseListener for ResourceHintContext {
+    fn process_request_body(&mut self) {}
+
+    fn process_request_eof(&mut self) {}
+
+    fn process_response(&mut self, metadata: Result<FetchMetadata, NetworkError>) {
+        let metadata = metadata.map(|metadata| match metadata {
+            FetchMetadata::Unfiltered(m) => m,
+            FetchMetadata::Filtered { unsafe_, .. } => unsafe_,
+        });
+        self.status = match metadata {
+            Ok(ref metadata) => match metadata.status {
+                Some((200..=299, _)) => Ok(()),
+                _ => Err(NetworkError::Internal("HTTP error code".to_owned())),
+            },
+            Err(error) => Err(error),
+        };
+    }
+
+    fn process_response_chunk(&mut self, _: Vec<u8>) {}
+
+    /// <https://html.spec.whatwg.org/multipage/#process-the-linked-resource>
+    fn process_response_eof(&mut self, response: Result<ResourceFetchTiming, NetworkError>) {
+        let link = self.link.root();
+        if self.status.is_ok() && response.is_ok() {
+            link.upcast::<EventTarget>().fire_event(atom!("load"));
+        } else {
+            link.upcast::<EventTarget>().fire_event(atom!("error"));
+        }
+    }
+
+    fn resource_timing_mut(&mut self) -> &mut ResourceFetchTiming {
+        &mut self.resource_timing
+    }
+
+    fn resource_timing(&self) -> &ResourceFetchTiming {
+        &self.resource_timing
+    }
+
+    fn submit_resource_timing(&mut self) {
+        network_listener::submit_timing(self)
+    }
+}
+
+impl ResourceTimingListener for ResourceHintContext {
+    fn resource_timing_information(&self) -> (InitiatorType, ServoUrl) {
+        (
+            InitiatorType::LocalName("link".to_owned()),
+            self.url.clone(),
+        )
+    }
+
+    fn resource_timing_global(&self) -> DomRoot<GlobalScope> {
+        self.link.root().global()
+    }
+}
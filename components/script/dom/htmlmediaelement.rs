@@ -44,6 +44,7 @@ use crate::dom::htmlscriptelement::HTMLScriptElement;
 use crate::dom::htmlsourceelement::HTMLSourceElement;
 use crate::dom::htmlstyleelement::HTMLStyleElement;
 use crate::dom::htmlvideoelement::HTMLVideoElement;
+use crate::dom::mediadevices::is_audio_output_device;
 use crate::dom::mediaerror::MediaError;
 use crate::dom::mediafragmentparser::MediaFragmentParser;
 use crate::dom::mediastream::MediaStream;
@@ -67,12 +68,14 @@ use crate::script_thread::ScriptThread;
 use crate::task_source::TaskSource;
 use dom_struct::dom_struct;
 use embedder_traits::resources::{self, Resource as EmbedderResource};
-use embedder_traits::{MediaPositionState, MediaSessionEvent, MediaSessionPlaybackState};
+use embedder_traits::{
+    MediaPositionState, MediaSessionEvent, MediaSessionPlaybackState, PictureInPictureFrame,
+};
 use euclid::default::Size2D;
 use headers::{ContentLength, ContentRange, HeaderMapExt};
 use html5ever::{LocalName, Prefix};
 use http::header::{self, HeaderMap, HeaderValue};
-use ipc_channel::ipc;
+use ipc_channel::ipc::{self, IpcSender, IpcSharedMemory};
 use ipc_channel::router::ROUTER;
 use media::{glplayer_channel, GLPlayerMsg, GLPlayerMsgForward, WindowGLContext};
 use net_traits::image::base::Image;
@@ -154,6 +157,10 @@ pub struct MediaFrameRenderer {
     old_frame: Option<ImageKey>,
     very_old_frame: Option<ImageKey>,
     current_frame_holder: Option<FrameHolder>,
+    /// When the current frame was handed to WebRender, in `time::precise_time_ns()`.
+    current_frame_presented: Option<u64>,
+    /// Where the frames are also sent while the video is shown in a floating window.
+    picture_in_picture: Option<IpcSender<PictureInPictureFrame>>,
 }
 
 impl MediaFrameRenderer {
@@ -165,6 +172,29 @@ impl MediaFrameRenderer {
             old_frame: None,
             very_old_frame: None,
             current_frame_holder: None,
+            current_frame_presented: None,
+            picture_in_picture: None,
+        }
+    }
+
+    /// Sends a frame to the floating window of the video, if it is shown in one.
+    fn send_picture_in_picture_frame(&mut self, frame: &VideoFrame) {
+        // The pixels of the frames that live in GL textures aren't available here.
+        if frame.is_gl_texture() {
+            return;
+        }
+        let sender = match self.picture_in_picture {
+            Some(ref sender) => sender,
+            None => return,
+        };
+        let frame = PictureInPictureFrame {
+            width: frame.get_width(),
+            height: frame.get_height(),
+            data: IpcSharedMemory::from_bytes(&frame.get_data()),
+        };
+        if sender.send(frame).is_err() {
+            // The embedder closed the floating window.
+            self.picture_in_picture = None;
         }
     }
 
@@ -178,6 +208,8 @@ impl MediaFrameRenderer {
 impl VideoFrameRenderer for MediaFrameRenderer {
     fn render(&mut self, frame: VideoFrame) {
         let mut txn = Transaction::new();
+        self.current_frame_presented = Some(time::precise_time_ns());
+        self.send_picture_in_picture_frame(&frame);
 
         if let Some(old_image_key) = mem::replace(&mut self.very_old_frame, self.old_frame.take()) {
             txn.delete_image(old_image_key);
@@ -347,6 +379,8 @@ pub struct HTMLMediaElement {
     seeking: Cell<bool>,
     /// https://html.spec.whatwg.org/multipage/#dom-media-muted
     muted: Cell<bool>,
+    /// https://w3c.github.io/mediacapture-output/#dom-htmlmediaelement-sinkid
+    sink_id: DomRefCell<DOMString>,
     /// URL of the media resource, if any.
     resource_url: DomRefCell<Option<ServoUrl>>,
     /// URL of the media resource, if the resource is set through the src_object attribute and it
@@ -413,6 +447,7 @@ impl HTMLMediaElement {
             defaultPlaybackRate: Cell::new(1.0),
             playbackRate: Cell::new(1.0),
             muted: Cell::new(false),
+            sink_id: DomRefCell::new(DOMString::new()),
             // FIXME(nox): Why is this initialised to true?
             autoplaying: Cell::new(true),
             delaying_the_load_event_flag: Default::default(),
@@ -1355,6 +1390,14 @@ impl HTMLMediaElement {
             Box::new(window.get_player_context()),
         );
 
+        // The audio plays on the device that the page chose before the resource loaded.
+        if !self.sink_id.borrow().is_empty() {
+            let sink_id = String::from(self.sink_id.borrow().clone());
+            if let Err(e) = player.lock().unwrap().set_output_device(Some(sink_id)) {
+                warn!("Could not set the audio output device {:?}", e);
+            }
+        }
+
         *self.player.borrow_mut() = Some(player);
 
         let trusted_node = Trusted::new(self);
@@ -1541,6 +1584,9 @@ impl HTMLMediaElement {
             },
             PlayerEvent::VideoFrameUpdated => {
                 self.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
+                if let Some(video) = self.downcast::<HTMLVideoElement>() {
+                    video.frame_presented();
+                }
             },
             PlayerEvent::MetadataUpdated(ref metadata) => {
                 // https://html.spec.whatwg.org/multipage/#media-data-processing-steps-list
@@ -1852,7 +1898,7 @@ impl HTMLMediaElement {
             // if we are already showing the controls.
             return;
         }
-        let shadow_root = element.attach_shadow(IsUserAgentWidget::Yes).unwrap();
+        let shadow_root = element.attach_shadow(IsUserAgentWidget::Yes, false).unwrap();
         let document = document_from_node(self);
         let script = HTMLScriptElement::new(
             local_name!("script"),
@@ -1914,6 +1960,25 @@ impl HTMLMediaElement {
         }
     }
 
+    /// When the current frame of the video was presented, in `time::precise_time_ns()`.
+    pub fn current_frame_presentation_time(&self) -> Option<u64> {
+        self.video_renderer.lock().unwrap().current_frame_presented
+    }
+
+    /// Sends the frames of the video to its floating window from now on, starting with the
+    /// current one, or stops doing so, which has the embedder close the window.
+    pub fn set_picture_in_picture_frames(&self, frames: Option<IpcSender<PictureInPictureFrame>>) {
+        let mut renderer = self.video_renderer.lock().unwrap();
+        renderer.picture_in_picture = frames;
+        let current_frame = renderer
+            .current_frame_holder
+            .as_ref()
+            .map(|holder| holder.get_frame());
+        if let Some(frame) = current_frame {
+            renderer.send_picture_in_picture_frame(&frame);
+        }
+    }
+
     /// By default the audio is rendered through the audio sink automatically
     /// selected by the servo-media Player instance. However, in some cases, like
     /// the WebAudio MediaElementAudioSourceNode, we need to set a custom audio
@@ -2399,6 +2464,48 @@ impl HTMLMediaElementMethods for HTMLMediaElement {
 
         Ok(())
     }
+
+    // https://w3c.github.io/mediacapture-output/#dom-htmlmediaelement-sinkid
+    fn SinkId(&self) -> DOMString {
+        self.sink_id.borrow().clone()
+    }
+
+    // https://w3c.github.io/mediacapture-output/#dom-htmlmediaelement-setsinkid
+    fn SetSinkId(&self, sink_id: DOMString, comp: InRealm) -> Rc<Promise> {
+        // Step 1.
+        let promise = Promise::new_in_current_realm(&self.global(), comp);
+
+        // Step 5.
+        if *self.sink_id.borrow() == sink_id {
+            promise.resolve_native(&());
+            return promise;
+        }
+
+        // Step 8.1.
+        if !is_audio_output_device(&sink_id) {
+            promise.reject_error(Error::NotFound);
+            return promise;
+        }
+
+        // Step 8.3.
+        if let Some(ref player) = *self.player.borrow() {
+            let device = if sink_id.is_empty() {
+                None
+            } else {
+                Some(String::from(sink_id.clone()))
+            };
+            if let Err(e) = player.lock().unwrap().set_output_device(device) {
+                warn!("Could not set the audio output device {:?}", e);
+                promise.reject_error(Error::Abort);
+                return promise;
+            }
+        }
+
+        // Step 8.4.
+        *self.sink_id.borrow_mut() = sink_id;
+        promise.resolve_native(&());
+        promise
+    }
 }
 
 impl VirtualMethods for HTMLMediaElement {
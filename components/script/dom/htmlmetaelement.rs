@@ -19,6 +19,7 @@ use crate::dom::node::{
     UnbindContext,
 };
 use crate::dom::virtualmethods::VirtualMethods;
+use cssparser::{Parser as CssParser, ParserInput};
 use dom_struct::dom_struct;
 use html5ever::{LocalName, Prefix};
 use parking_lot::RwLock;
@@ -26,8 +27,12 @@ use servo_arc::Arc;
 use servo_config::pref;
 use std::sync::atomic::AtomicBool;
 use style::media_queries::MediaList;
+use style::parser::{Parse, ParserContext};
 use style::str::HTML_SPACE_CHARACTERS;
-use style::stylesheets::{CssRule, CssRules, Origin, Stylesheet, StylesheetContents, ViewportRule};
+use style::stylesheets::{CssRule, CssRuleType, CssRules, Origin, Stylesheet};
+use style::stylesheets::{StylesheetContents, ViewportRule};
+use style::values::specified::ColorScheme;
+use style_traits::{ParsingMode, ToCss};
 
 #[dom_struct]
 pub struct HTMLMetaElement {
@@ -94,6 +99,10 @@ impl HTMLMetaElement {
             if name == "referrer" {
                 self.apply_referrer();
             }
+
+            if name == "color-scheme" {
+                self.apply_color_scheme();
+            }
         }
     }
 
@@ -132,6 +141,73 @@ impl HTMLMetaElement {
         }
     }
 
+    /// Applies the color schemes of the content attribute to the root element, with a style
+    /// sheet of its own which the style sheets of the page can override.
+    /// <https://html.spec.whatwg.org/multipage/#meta-color-scheme>
+    #[allow(unrooted_must_root)]
+    fn apply_color_scheme(&self) {
+        let element = self.upcast::<Element>();
+        let content = match element.get_attribute(&ns!(), &local_name!("content")) {
+            Some(content) => content,
+            None => return,
+        };
+        let document = document_from_node(self);
+        let url = window_from_node(self).get_url();
+        let context = ParserContext::new_for_cssom(
+            &url,
+            Some(CssRuleType::Style),
+            ParsingMode::DEFAULT,
+            document.quirks_mode(),
+            None,
+            None,
+        );
+        let content = content.value();
+        let mut input = ParserInput::new(&**content);
+        let color_scheme = match CssParser::new(&mut input)
+            .parse_entirely(|input| ColorScheme::parse(&context, input))
+        {
+            Ok(color_scheme) => color_scheme,
+            Err(_) => return,
+        };
+
+        let css = format!("html {{ color-scheme: {} }}", color_scheme.to_css_string());
+        let shared_lock = document.style_shared_lock();
+        let sheet = Arc::new(Stylesheet::from_str(
+            &css,
+            url,
+            Origin::Author,
+            Arc::new(shared_lock.wrap(MediaList::empty())),
+            shared_lock.clone(),
+            None,
+            None,
+            document.quirks_mode(),
+            0,
+        ));
+        *self.stylesheet.borrow_mut() = Some(sheet.clone());
+        stylesheets_owner_from_node(self).add_stylesheet(self.upcast(), sheet);
+    }
+
+    /// Replaces the style sheet of a `viewport` or `color-scheme` meta element whose name or
+    /// content changed.
+    fn process_stylesheet_attributes(&self) {
+        if let Some(sheet) = self.stylesheet.borrow_mut().take() {
+            stylesheets_owner_from_node(self).remove_stylesheet(self.upcast(), &sheet);
+        }
+        let element = self.upcast::<Element>();
+        if let Some(ref name) = element.get_name() {
+            let name = name.to_ascii_lowercase();
+            let name = name.trim_matches(HTML_SPACE_CHARACTERS);
+
+            if name == "viewport" {
+                self.apply_viewport();
+            }
+
+            if name == "color-scheme" {
+                self.apply_color_scheme();
+            }
+        }
+    }
+
     fn process_referrer_attribute(&self) {
         let element = self.upcast::<Element>();
         if let Some(ref name) = element.get_name() {
@@ -189,6 +265,14 @@ impl VirtualMethods for HTMLMetaElement {
         }
 
         self.process_referrer_attribute();
+
+        let is_stylesheet_attribute = match *attr.local_name() {
+            local_name!("name") | local_name!("content") => true,
+            _ => false,
+        };
+        if is_stylesheet_attribute && self.upcast::<Node>().is_connected() {
+            self.process_stylesheet_attributes();
+        }
     }
 
     fn unbind_from_tree(&self, context: &UnbindContext) {
@@ -17,18 +17,22 @@ use crate::dom::document::Document;
 use crate::dom::element::{
     cors_setting_for_element, reflect_cross_origin_attribute, set_cross_origin_attribute,
 };
+use crate::dom::element::{
+    fetch_priority_for_element, reflect_fetch_priority_attribute, set_fetch_priority_attribute,
+};
 use crate::dom::element::{AttributeMutation, Element, ElementCreator};
 use crate::dom::event::{Event, EventBubbles, EventCancelable, EventStatus};
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::htmlelement::HTMLElement;
 use crate::dom::node::{document_from_node, window_from_node};
-use crate::dom::node::{BindContext, ChildrenMutation, CloneChildrenFlag, Node};
+use crate::dom::node::{BindContext, ChildrenMutation, CloneChildrenFlag, Node, UnbindContext};
 use crate::dom::performanceresourcetiming::InitiatorType;
 use crate::dom::virtualmethods::VirtualMethods;
 use crate::fetch::create_a_potential_cors_request;
 use crate::network_listener::{self, NetworkListener, PreInvoke, ResourceTimingListener};
 use crate::script_module::fetch_inline_module_script;
 use crate::script_module::{fetch_external_module_script, ModuleOwner};
+use crate::speculation_rules::parse_speculation_rule_set;
 use content_security_policy as csp;
 use dom_struct::dom_struct;
 use encoding_rs::Encoding;
@@ -37,6 +41,7 @@ use ipc_channel::ipc;
 use ipc_channel::router::ROUTER;
 use js::jsval::UndefinedValue;
 use msg::constellation_msg::PipelineId;
+use net_traits::request::RequestPriority;
 use net_traits::request::{CorsSettings, CredentialsMode, Destination, Referrer, RequestBuilder};
 use net_traits::ReferrerPolicy;
 use net_traits::{FetchMetadata, FetchResponseListener, Metadata, NetworkError};
@@ -323,6 +328,7 @@ pub(crate) fn script_fetch_request(
     referrer: Referrer,
     referrer_policy: Option<ReferrerPolicy>,
     integrity_metadata: String,
+    priority: RequestPriority,
 ) -> RequestBuilder {
     create_a_potential_cors_request(url, Destination::Script, cors_setting, None)
         .origin(origin)
@@ -330,6 +336,7 @@ pub(crate) fn script_fetch_request(
         .referrer(Some(referrer))
         .referrer_policy(referrer_policy)
         .integrity_metadata(integrity_metadata)
+        .priority(priority)
 }
 
 /// <https://html.spec.whatwg.org/multipage/#fetch-a-classic-script>
@@ -352,6 +359,7 @@ fn fetch_a_classic_script(
         Referrer::ReferrerUrl(doc.url()),
         doc.get_referrer_policy(),
         integrity_metadata,
+        fetch_priority_for_element(script.upcast()),
     );
 
     // TODO: Step 3, Add custom steps to perform fetch
@@ -418,6 +426,12 @@ impl HTMLScriptElement {
             return;
         }
 
+        // Speculation rules are data for the user agent, rather than a script that runs.
+        if self.is_speculation_rules() {
+            self.prepare_speculation_rules(was_parser_inserted, &text);
+            return;
+        }
+
         let script_type = if let Some(ty) = self.get_script_type() {
             ty
         } else {
@@ -939,6 +953,53 @@ impl HTMLScriptElement {
         script_type
     }
 
+    fn is_speculation_rules(&self) -> bool {
+        self.upcast::<Element>()
+            .get_attribute(&ns!(), &local_name!("type"))
+            .map_or(false, |ty| {
+                ty.value()
+                    .trim_matches(HTML_SPACE_CHARACTERS)
+                    .eq_ignore_ascii_case("speculationrules")
+            })
+    }
+
+    /// Registers the speculation rules that the script lists with its document.
+    /// <https://wicg.github.io/nav-speculation/speculation-rules.html#speculation-rules-script>
+    fn prepare_speculation_rules(&self, was_parser_inserted: bool, text: &str) {
+        self.already_started.set(true);
+
+        let doc = document_from_node(self);
+        if was_parser_inserted && &*self.parser_document != &*doc {
+            return;
+        }
+        if !doc.is_scripting_enabled() {
+            return;
+        }
+
+        // Speculation rules can only be inline.
+        let element = self.upcast::<Element>();
+        if element.has_attribute(&local_name!("src")) {
+            self.queue_error_event();
+            return;
+        }
+        if doc.should_elements_inline_type_behavior_be_blocked(
+            &element,
+            csp::InlineCheckType::Script,
+            text,
+        ) == csp::CheckResult::Blocked
+        {
+            return;
+        }
+
+        match parse_speculation_rule_set(text, &doc.base_url()) {
+            Ok(rules) => doc.register_speculation_rules(self, rules),
+            Err(error) => {
+                warn!("Parsing speculation rules failed: {}", error);
+                self.queue_error_event();
+            },
+        }
+    }
+
     pub fn set_parser_inserted(&self, parser_inserted: bool) {
         self.parser_inserted.set(parser_inserted);
     }
@@ -1008,6 +1069,17 @@ impl VirtualMethods for HTMLScriptElement {
         }
     }
 
+    fn unbind_from_tree(&self, context: &UnbindContext) {
+        if let Some(ref s) = self.super_type() {
+            s.unbind_from_tree(context);
+        }
+
+        // https://wicg.github.io/nav-speculation/speculation-rules.html#unregister-speculation-rules
+        if context.tree_connected {
+            document_from_node(self).unregister_speculation_rules(self);
+        }
+    }
+
     fn cloning_steps(
         &self,
         copy: &Node,
@@ -1093,6 +1165,16 @@ impl HTMLScriptElementMethods for HTMLScriptElement {
         set_cross_origin_attribute(self.upcast::<Element>(), value);
     }
 
+    // https://html.spec.whatwg.org/multipage/#dom-script-fetchpriority
+    fn FetchPriority(&self) -> DOMString {
+        reflect_fetch_priority_attribute(self.upcast::<Element>())
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-script-fetchpriority
+    fn SetFetchPriority(&self, value: DOMString) {
+        set_fetch_priority_attribute(self.upcast::<Element>(), value);
+    }
+
     // https://html.spec.whatwg.org/multipage/#dom-script-text
     fn Text(&self) -> DOMString {
         self.upcast::<Node>().child_text_content()
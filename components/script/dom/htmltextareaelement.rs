@@ -28,7 +28,7 @@ use crate::dom::node::{
     BindContext, ChildrenMutation, CloneChildrenFlag, Node, NodeDamage, UnbindContext,
 };
 use crate::dom::nodelist::NodeList;
-use crate::dom::textcontrol::{TextControlElement, TextControlSelection};
+use crate::dom::textcontrol::{self, TextControlElement, TextControlSelection};
 use crate::dom::validation::Validatable;
 use crate::dom::virtualmethods::VirtualMethods;
 use crate::textinput::{
@@ -401,6 +401,11 @@ impl HTMLTextAreaElement {
     fn selection(&self) -> TextControlSelection<Self> {
         TextControlSelection::new(&self, &self.textinput)
     }
+
+    /// The selected text of the control.
+    pub fn selected_text(&self) -> Option<DOMString> {
+        self.selection().selected_text()
+    }
 }
 
 impl VirtualMethods for HTMLTextAreaElement {
@@ -562,15 +567,20 @@ impl VirtualMethods for HTMLTextAreaElement {
             document_from_node(self).request_focus(self.upcast());
         } else if event.type_() == atom!("keydown") && !event.DefaultPrevented() {
             if let Some(kevent) = event.downcast::<KeyboardEvent>() {
+                let old_selection = self.textinput.borrow().selection_state();
                 // This can't be inlined, as holding on to textinput.borrow_mut()
                 // during self.implicit_submission will cause a panic.
                 let action = self.textinput.borrow_mut().handle_keydown(kevent);
+                if self.textinput.borrow().selection_state() != old_selection {
+                    textcontrol::queue_selectionchange(self);
+                }
                 match action {
                     KeyReaction::TriggerDefaultAction => (),
                     KeyReaction::DispatchInput => {
                         self.value_dirty.set(true);
                         self.update_placeholder_shown_state();
                         self.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
+                        document_from_node(self).request_spellcheck(self.upcast());
                         event.mark_as_handled();
                     },
                     KeyReaction::RedrawSelection => {
@@ -4,27 +4,42 @@
 
 use crate::document_loader::{LoadBlocker, LoadType};
 use crate::dom::attr::Attr;
+use crate::dom::bindings::callback::ExceptionHandling;
 use crate::dom::bindings::cell::DomRefCell;
-use crate::dom::bindings::codegen::Bindings::HTMLVideoElementBinding::HTMLVideoElementMethods;
+use crate::dom::bindings::codegen::Bindings::HTMLMediaElementBinding::HTMLMediaElementMethods;
+use crate::dom::bindings::codegen::Bindings::HTMLVideoElementBinding::{
+    HTMLVideoElementMethods, VideoFrameCallbackMetadata, VideoFrameRequestCallback,
+};
+use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
+use crate::dom::bindings::error::{Error, ErrorResult};
 use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::num::Finite;
 use crate::dom::bindings::refcounted::Trusted;
 use crate::dom::bindings::reflector::DomObject;
-use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::root::{DomRoot, MutNullableDom};
 use crate::dom::bindings::str::DOMString;
 use crate::dom::document::Document;
 use crate::dom::element::{AttributeMutation, Element};
+use crate::dom::event::Event;
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::htmlmediaelement::{HTMLMediaElement, ReadyState};
 use crate::dom::node::{document_from_node, window_from_node, Node};
+use crate::dom::performance::reduce_timing_resolution;
 use crate::dom::performanceresourcetiming::InitiatorType;
+use crate::dom::pictureinpictureevent::PictureInPictureEvent;
+use crate::dom::pictureinpicturewindow::PictureInPictureWindow;
+use crate::dom::promise::Promise;
 use crate::dom::virtualmethods::VirtualMethods;
 use crate::fetch::FetchCanceller;
 use crate::image_listener::{add_cache_listener_for_element, ImageCacheListener};
 use crate::network_listener::{self, NetworkListener, PreInvoke, ResourceTimingListener};
+use crate::task_source::TaskSource;
 use dom_struct::dom_struct;
+use embedder_traits::PictureInPictureEvent as EmbedderPictureInPictureEvent;
+use embedder_traits::{EmbedderMsg, PictureInPictureFrame, PictureInPictureRequest};
 use euclid::default::Size2D;
 use html5ever::{LocalName, Prefix};
-use ipc_channel::ipc;
+use ipc_channel::ipc::{self, IpcSender};
 use ipc_channel::router::ROUTER;
 use net_traits::image_cache::UsePlaceholder;
 use net_traits::image_cache::{CanRequestImages, ImageCache, ImageOrMetadataAvailable};
@@ -34,10 +49,14 @@ use net_traits::{
     CoreResourceMsg, FetchChannels, FetchMetadata, FetchResponseListener, FetchResponseMsg,
 };
 use net_traits::{NetworkError, ResourceFetchTiming, ResourceTimingType};
+use script_traits::FrameTiming;
 use servo_media::player::video::VideoFrame;
 use servo_url::ServoUrl;
 use std::cell::Cell;
+use std::mem;
+use std::rc::Rc;
 use std::sync::{Arc, Mutex};
+use webrender_api::units::DeviceIntSize;
 
 const DEFAULT_WIDTH: u32 = 300;
 const DEFAULT_HEIGHT: u32 = 150;
@@ -59,6 +78,27 @@ pub struct HTMLVideoElement {
     /// A copy of the last frame
     #[ignore_malloc_size_of = "VideoFrame"]
     last_frame: DomRefCell<Option<VideoFrame>>,
+    /// <https://wicg.github.io/video-rvfc/#list-of-video-frame-request-callbacks>
+    #[ignore_malloc_size_of = "Rc is hard"]
+    video_frame_callbacks: DomRefCell<Vec<(u32, Option<Rc<VideoFrameRequestCallback>>)>>,
+    /// <https://wicg.github.io/video-rvfc/#video-frame-request-callback-identifier>
+    video_frame_callback_ident: Cell<u32>,
+    /// How many frames the media pipeline presented since the video was created.
+    presented_frames: Cell<u32>,
+    /// The playback position when the media pipeline presented the current frame.
+    presented_media_time: Cell<f64>,
+    /// The floating window that the video is shown in.
+    picture_in_picture_window: MutNullableDom<PictureInPictureWindow>,
+    /// The promises of `requestPictureInPicture()` that wait for the embedder to open the
+    /// floating window.
+    #[ignore_malloc_size_of = "Rc is hard"]
+    pending_picture_in_picture: DomRefCell<Vec<Rc<Promise>>>,
+    /// Where the frames go once the embedder opened the floating window.
+    #[ignore_malloc_size_of = "channels are hard"]
+    picture_in_picture_frames: DomRefCell<Option<IpcSender<PictureInPictureFrame>>>,
+    /// Identifies the floating window that was last asked for, so that the events of the
+    /// previous ones are ignored.
+    picture_in_picture_request: Cell<u32>,
 }
 
 impl HTMLVideoElement {
@@ -75,6 +115,14 @@ impl HTMLVideoElement {
             poster_frame_canceller: DomRefCell::new(Default::default()),
             load_blocker: Default::default(),
             last_frame: Default::default(),
+            video_frame_callbacks: DomRefCell::new(vec![]),
+            video_frame_callback_ident: Cell::new(0),
+            presented_frames: Cell::new(0),
+            presented_media_time: Cell::new(0.),
+            picture_in_picture_window: Default::default(),
+            pending_picture_in_picture: DomRefCell::new(vec![]),
+            picture_in_picture_frames: DomRefCell::new(None),
+            picture_in_picture_request: Cell::new(0),
         }
     }
 
@@ -108,6 +156,246 @@ impl HTMLVideoElement {
         self.video_height.set(height);
     }
 
+    /// Records that the media pipeline presented a new frame, and has the video frame request
+    /// callbacks run for it in the next animation frame of the document.
+    pub fn frame_presented(&self) {
+        self.presented_frames
+            .set(self.presented_frames.get().wrapping_add(1));
+        self.presented_media_time
+            .set(*self.htmlmediaelement.CurrentTime());
+        if !self.video_frame_callbacks.borrow().is_empty() {
+            document_from_node(self).schedule_video_frame_callbacks(self);
+        }
+    }
+
+    /// Runs the video frame request callbacks for the frame that was last presented, as part
+    /// of the animation frame of the given video refresh.
+    /// <https://wicg.github.io/video-rvfc/#video-rvfc-procedures>
+    pub fn run_video_frame_callbacks(&self, now: f64, timing: FrameTiming) {
+        let mut callbacks = mem::replace(&mut *self.video_frame_callbacks.borrow_mut(), vec![]);
+        if callbacks.is_empty() {
+            return;
+        }
+
+        let performance = window_from_node(self).Performance();
+        let relative_time = |precise_time_ns| {
+            Finite::wrap(*reduce_timing_resolution(
+                performance.relative_time(precise_time_ns),
+            ))
+        };
+        let presentation_time = self
+            .htmlmediaelement
+            .current_frame_presentation_time()
+            .unwrap_or(timing.vsync_time);
+        let metadata = VideoFrameCallbackMetadata {
+            presentationTime: relative_time(presentation_time),
+            // The frame is shown by the video refresh that follows this animation frame.
+            expectedDisplayTime: relative_time(timing.vsync_time + timing.frame_interval),
+            width: self.VideoWidth(),
+            height: self.VideoHeight(),
+            mediaTime: Finite::wrap(self.presented_media_time.get()),
+            presentedFrames: self.presented_frames.get(),
+            processingDuration: None,
+            captureTime: None,
+            receiveTime: None,
+            rtpTimestamp: None,
+        };
+
+        for (_, callback) in callbacks.drain(..) {
+            if let Some(callback) = callback {
+                let _ = callback.Call__(Finite::wrap(now), &metadata, ExceptionHandling::Report);
+            }
+        }
+    }
+
+    /// <https://w3c.github.io/picture-in-picture/#request-pip>
+    fn request_picture_in_picture(&self, promise: &Rc<Promise>) -> ErrorResult {
+        let window = window_from_node(self);
+        let document = document_from_node(self);
+
+        // Step 1 is covered by the `dom.picture_in_picture.enabled` preference, and there is
+        // no permissions policy for step 2.
+
+        // Step 3.
+        if self.htmlmediaelement.get_ready_state() == ReadyState::HaveNothing {
+            return Err(Error::InvalidState);
+        }
+
+        // Step 4.
+        if self.htmlmediaelement.VideoTracks().len() == 0 {
+            return Err(Error::InvalidState);
+        }
+
+        // Step 5.
+        if self.DisablePictureInPicture() {
+            return Err(Error::InvalidState);
+        }
+
+        // Step 6.
+        let current = document.picture_in_picture_element();
+        if current.is_none() {
+            if !window.has_transient_activation() {
+                return Err(Error::NotAllowed);
+            }
+            window.consume_user_activation();
+        }
+
+        // Step 7.
+        if current.map_or(false, |current| &*current == self) {
+            if let Some(picture_in_picture_window) = self.picture_in_picture_window.get() {
+                promise.resolve_native(&picture_in_picture_window);
+                return Ok(());
+            }
+        }
+
+        // Step 8. The promise is settled once the embedder opened the floating window.
+        let mut pending = self.pending_picture_in_picture.borrow_mut();
+        pending.push(promise.clone());
+        if pending.len() > 1 {
+            return Ok(());
+        }
+        drop(pending);
+
+        let request_id = self.picture_in_picture_request.get() + 1;
+        self.picture_in_picture_request.set(request_id);
+        let (frame_sender, frame_receiver) = ipc::channel().unwrap();
+        *self.picture_in_picture_frames.borrow_mut() = Some(frame_sender);
+
+        let (event_sender, event_receiver) = ipc::channel().unwrap();
+        let this = Trusted::new(self);
+        let (task_source, canceller) = window
+            .task_manager()
+            .media_element_task_source_with_canceller();
+        ROUTER.add_route(
+            event_receiver.to_opaque(),
+            Box::new(move |message| {
+                let this = this.clone();
+                let event = message
+                    .to()
+                    .unwrap_or(EmbedderPictureInPictureEvent::Closed);
+                let _ = task_source.queue_with_canceller(
+                    task!(picture_in_picture_event: move || {
+                        this.root().handle_picture_in_picture_event(request_id, event);
+                    }),
+                    &canceller,
+                );
+            }),
+        );
+
+        let video_size = DeviceIntSize::new(
+            self.video_width.get() as i32,
+            self.video_height.get() as i32,
+        );
+        window.send_to_embedder(EmbedderMsg::EnterPictureInPicture(
+            PictureInPictureRequest {
+                video_size,
+                frames: frame_receiver,
+                events: event_sender,
+            },
+        ));
+        Ok(())
+    }
+
+    fn handle_picture_in_picture_event(
+        &self,
+        request_id: u32,
+        event: EmbedderPictureInPictureEvent,
+    ) {
+        // The events of the floating windows that were since closed are stale.
+        if request_id != self.picture_in_picture_request.get() {
+            return;
+        }
+        match event {
+            EmbedderPictureInPictureEvent::Opened(size) => self.picture_in_picture_opened(size),
+            EmbedderPictureInPictureEvent::Resized(size) => {
+                if let Some(picture_in_picture_window) = self.picture_in_picture_window.get() {
+                    picture_in_picture_window.resized(size);
+                }
+            },
+            EmbedderPictureInPictureEvent::Closed => {
+                self.picture_in_picture_frames.borrow_mut().take();
+                let pending =
+                    mem::replace(&mut *self.pending_picture_in_picture.borrow_mut(), vec![]);
+                for promise in pending {
+                    promise.reject_error(Error::NotAllowed);
+                }
+                self.exit_picture_in_picture();
+            },
+        }
+    }
+
+    /// Shows the video in the floating window that the embedder opened for it.
+    /// <https://w3c.github.io/picture-in-picture/#request-pip>
+    fn picture_in_picture_opened(&self, size: DeviceIntSize) {
+        let window = window_from_node(self);
+        let document = document_from_node(self);
+
+        // Step 8. A single video of the document is shown in a floating window.
+        if let Some(previous) = document.picture_in_picture_element() {
+            if &*previous != self {
+                previous.exit_picture_in_picture();
+            }
+        }
+        let picture_in_picture_window = PictureInPictureWindow::new(window.upcast(), size);
+        self.picture_in_picture_window
+            .set(Some(&picture_in_picture_window));
+        let frames = self.picture_in_picture_frames.borrow_mut().take();
+        self.htmlmediaelement.set_picture_in_picture_frames(frames);
+
+        // Step 9.
+        document.set_picture_in_picture_element(Some(self));
+
+        // Step 10.
+        let event = PictureInPictureEvent::new(
+            window.upcast(),
+            atom!("enterpictureinpicture"),
+            true,
+            false,
+            &picture_in_picture_window,
+        );
+        event.upcast::<Event>().fire(self.upcast());
+
+        // Step 11.
+        let pending = mem::replace(&mut *self.pending_picture_in_picture.borrow_mut(), vec![]);
+        for promise in pending {
+            promise.resolve_native(&picture_in_picture_window);
+        }
+    }
+
+    /// <https://w3c.github.io/picture-in-picture/#exit-picture-in-picture-algorithm>
+    pub fn exit_picture_in_picture(&self) {
+        let picture_in_picture_window = match self.picture_in_picture_window.get() {
+            Some(picture_in_picture_window) => picture_in_picture_window,
+            None => return,
+        };
+        self.picture_in_picture_window.set(None);
+        self.picture_in_picture_request
+            .set(self.picture_in_picture_request.get() + 1);
+
+        // Step 2. Hanging up on the frames has the embedder close the floating window.
+        self.htmlmediaelement.set_picture_in_picture_frames(None);
+        picture_in_picture_window.close();
+
+        // Step 3.
+        let document = document_from_node(self);
+        if document
+            .picture_in_picture_element()
+            .map_or(false, |element| &*element == self)
+        {
+            document.set_picture_in_picture_element(None);
+        }
+
+        // Step 4.
+        let event = PictureInPictureEvent::new(
+            window_from_node(self).upcast(),
+            atom!("leavepictureinpicture"),
+            true,
+            false,
+            &picture_in_picture_window,
+        );
+        event.upcast::<Event>().fire(self.upcast());
+    }
+
     pub fn allow_load_event(&self) {
         LoadBlocker::terminate(&mut *self.load_blocker.borrow_mut());
     }
@@ -266,6 +554,62 @@ impl HTMLVideoElementMethods for HTMLVideoElement {
     // For testing purposes only. This is not an event from
     // https://html.spec.whatwg.org/multipage/#dom-video-poster
     event_handler!(postershown, GetOnpostershown, SetOnpostershown);
+
+    // https://wicg.github.io/video-rvfc/#dom-htmlvideoelement-requestvideoframecallback
+    fn RequestVideoFrameCallback(&self, callback: Rc<VideoFrameRequestCallback>) -> u32 {
+        let ident = self.video_frame_callback_ident.get() + 1;
+        self.video_frame_callback_ident.set(ident);
+        self.video_frame_callbacks
+            .borrow_mut()
+            .push((ident, Some(callback)));
+        ident
+    }
+
+    // https://w3c.github.io/picture-in-picture/#dom-htmlvideoelement-requestpictureinpicture
+    fn RequestPictureInPicture(&self) -> Rc<Promise> {
+        let promise = Promise::new(&self.global());
+        if let Err(error) = self.request_picture_in_picture(&promise) {
+            promise.reject_error(error);
+        }
+        promise
+    }
+
+    // https://w3c.github.io/picture-in-picture/#dom-htmlvideoelement-onenterpictureinpicture
+    event_handler!(
+        enterpictureinpicture,
+        GetOnenterpictureinpicture,
+        SetOnenterpictureinpicture
+    );
+
+    // https://w3c.github.io/picture-in-picture/#dom-htmlvideoelement-onleavepictureinpicture
+    event_handler!(
+        leavepictureinpicture,
+        GetOnleavepictureinpicture,
+        SetOnleavepictureinpicture
+    );
+
+    // https://w3c.github.io/picture-in-picture/#dom-htmlvideoelement-disablepictureinpicture
+    fn DisablePictureInPicture(&self) -> bool {
+        self.upcast::<Element>()
+            .has_attribute(&LocalName::from("disablepictureinpicture"))
+    }
+
+    // https://w3c.github.io/picture-in-picture/#dom-htmlvideoelement-disablepictureinpicture
+    fn SetDisablePictureInPicture(&self, value: bool) {
+        self.upcast::<Element>()
+            .set_bool_attribute(&LocalName::from("disablepictureinpicture"), value);
+        if value {
+            self.exit_picture_in_picture();
+        }
+    }
+
+    // https://wicg.github.io/video-rvfc/#dom-htmlvideoelement-cancelvideoframecallback
+    fn CancelVideoFrameCallback(&self, handle: u32) {
+        let mut list = self.video_frame_callbacks.borrow_mut();
+        if let Some(pair) = list.iter_mut().find(|pair| pair.0 == handle) {
+            pair.1 = None;
+        }
+    }
 }
 
 impl VirtualMethods for HTMLVideoElement {
@@ -0,0 +1,230 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::abortsignal::AbortAlgorithm;
+use crate::dom::bindings::codegen::Bindings::AbortSignalBinding::AbortSignalMethods;
+use crate::dom::bindings::codegen::Bindings::IdleDetectorBinding::{
+    IdleDetectorMethods, IdleOptions, ScreenIdleState, UserIdleState,
+};
+use crate::dom::bindings::codegen::Bindings::PermissionStatusBinding::{
+    PermissionName, PermissionState,
+};
+use crate::dom::bindings::error::Error;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::refcounted::Trusted;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::eventtarget::EventTarget;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::permissions::{get_descriptor_permission_state, request_permission_to_use};
+use crate::dom::promise::Promise;
+use crate::dom::window::Window;
+use crate::timers::{OneshotTimerCallback, OneshotTimerHandle};
+use dom_struct::dom_struct;
+use profile_traits::ipc;
+use script_traits::{MsDuration, ScriptMsg};
+use std::cell::Cell;
+use std::rc::Rc;
+use std::time::Duration;
+
+/// The shortest threshold that a page may observe the idle state of the user with.
+/// <https://wicg.github.io/idle-detection/#dom-idledetector-start>
+const MIN_THRESHOLD: u64 = 60_000;
+
+/// How often the idle state is checked again while the user is idle, to notice that they
+/// became active.
+const IDLE_POLL_INTERVAL: u64 = 1_000;
+
+/// <https://wicg.github.io/idle-detection/#dom-idledetector-state-slot>
+#[derive(Clone, Copy, JSTraceable, MallocSizeOf, PartialEq)]
+enum IdleDetectorState {
+    Stopped,
+    Started,
+}
+
+/// <https://wicg.github.io/idle-detection/#api-idledetector>
+#[dom_struct]
+pub struct IdleDetector {
+    eventtarget: EventTarget,
+    state: Cell<IdleDetectorState>,
+    /// <https://wicg.github.io/idle-detection/#dom-idledetector-threshold-slot>
+    threshold: Cell<u64>,
+    /// <https://wicg.github.io/idle-detection/#dom-idledetector-userstate-slot>
+    user_state: Cell<Option<UserIdleState>>,
+    /// <https://wicg.github.io/idle-detection/#dom-idledetector-screenstate-slot>
+    screen_state: Cell<Option<ScreenIdleState>>,
+    /// The timer which checks the idle state again.
+    poll_timer: Cell<Option<OneshotTimerHandle>>,
+}
+
+impl IdleDetector {
+    fn new_inherited() -> IdleDetector {
+        IdleDetector {
+            eventtarget: EventTarget::new_inherited(),
+            state: Cell::new(IdleDetectorState::Stopped),
+            threshold: Cell::new(MIN_THRESHOLD),
+            user_state: Cell::new(None),
+            screen_state: Cell::new(None),
+            poll_timer: Cell::new(None),
+        }
+    }
+
+    pub fn new(global: &GlobalScope) -> DomRoot<IdleDetector> {
+        reflect_dom_object(Box::new(IdleDetector::new_inherited()), global)
+    }
+
+    // https://wicg.github.io/idle-detection/#dom-idledetector-constructor
+    #[allow(non_snake_case)]
+    pub fn Constructor(window: &Window) -> DomRoot<IdleDetector> {
+        IdleDetector::new(window.upcast())
+    }
+
+    // https://wicg.github.io/idle-detection/#dom-idledetector-requestpermission
+    #[allow(non_snake_case)]
+    pub fn RequestPermission(window: &Window) -> Rc<Promise> {
+        let global = window.upcast::<GlobalScope>();
+        let promise = Promise::new(global);
+
+        // Step 3.
+        if !window.has_transient_activation() {
+            promise.reject_error(Error::NotAllowed);
+            return promise;
+        }
+
+        // Step 4.
+        let state = request_permission_to_use(PermissionName::Idle_detection, global);
+        promise.resolve_native(&state);
+        promise
+    }
+
+    /// Checks the idle state of the user, fires `change` if it changed, and schedules the
+    /// next check for when it can next change.
+    fn poll(&self) {
+        self.poll_timer.set(None);
+        if self.state.get() != IdleDetectorState::Started {
+            return;
+        }
+
+        let global = self.global();
+        let (sender, receiver) = ipc::channel(global.time_profiler_chan().clone()).unwrap();
+        global.send_to_constellation(ScriptMsg::GetUserIdleTime(sender));
+        let idle_time = receiver.recv().unwrap_or_default();
+
+        let threshold = Duration::from_millis(self.threshold.get());
+        let (user_state, next_poll) = if idle_time >= threshold {
+            (UserIdleState::Idle, IDLE_POLL_INTERVAL)
+        } else {
+            let remaining = threshold - idle_time;
+            (UserIdleState::Active, remaining.as_millis() as u64)
+        };
+        // The embedder doesn't tell whether the screen is locked.
+        let screen_state = ScreenIdleState::Unlocked;
+
+        let changed = self.user_state.get().map_or(false, |old| old != user_state) ||
+            self
+                .screen_state
+                .get()
+                .map_or(false, |old| old != screen_state);
+        self.user_state.set(Some(user_state));
+        self.screen_state.set(Some(screen_state));
+
+        let callback = IdleDetectorPollCallback {
+            detector: Trusted::new(self),
+        };
+        self.poll_timer.set(Some(global.schedule_callback(
+            OneshotTimerCallback::IdleDetectorPoll(callback),
+            MsDuration::new(next_poll),
+        )));
+
+        if changed {
+            self.upcast::<EventTarget>().fire_event(atom!("change"));
+        }
+    }
+
+    /// Stops observing the idle state of the user, as requested by the signal passed to
+    /// `start()`.
+    pub fn abort(&self) {
+        self.state.set(IdleDetectorState::Stopped);
+        if let Some(timer) = self.poll_timer.take() {
+            self.global().unschedule_callback(timer);
+        }
+    }
+}
+
+impl IdleDetectorMethods for IdleDetector {
+    // https://wicg.github.io/idle-detection/#dom-idledetector-userstate
+    fn GetUserState(&self) -> Option<UserIdleState> {
+        self.user_state.get()
+    }
+
+    // https://wicg.github.io/idle-detection/#dom-idledetector-screenstate
+    fn GetScreenState(&self) -> Option<ScreenIdleState> {
+        self.screen_state.get()
+    }
+
+    // https://wicg.github.io/idle-detection/#dom-idledetector-onchange
+    event_handler!(change, GetOnchange, SetOnchange);
+
+    // https://wicg.github.io/idle-detection/#dom-idledetector-start
+    fn Start(&self, options: &IdleOptions) -> Rc<Promise> {
+        let global = self.global();
+        let promise = Promise::new(&global);
+
+        // Step 3.
+        if self.state.get() != IdleDetectorState::Stopped {
+            promise.reject_error(Error::InvalidState);
+            return promise;
+        }
+
+        // Step 5.
+        if let Some(ref signal) = options.signal {
+            if signal.Aborted() {
+                promise.reject_error(Error::Abort);
+                return promise;
+            }
+        }
+
+        // Step 6.
+        let threshold = options.threshold.unwrap_or(MIN_THRESHOLD);
+        if threshold < MIN_THRESHOLD {
+            promise.reject_error(Error::Type(
+                "The threshold must be at least 60 seconds".to_owned(),
+            ));
+            return promise;
+        }
+
+        // Step 8.
+        let permission =
+            get_descriptor_permission_state(PermissionName::Idle_detection, Some(&global));
+        if permission != PermissionState::Granted {
+            promise.reject_error(Error::NotAllowed);
+            return promise;
+        }
+
+        // Steps 4 and 7.
+        self.state.set(IdleDetectorState::Started);
+        self.threshold.set(threshold);
+        if let Some(ref signal) = options.signal {
+            signal.add_algorithm(AbortAlgorithm::IdleDetector(Dom::from_ref(self)));
+        }
+
+        // Step 9.
+        self.poll();
+        promise.resolve_native(&());
+        promise
+    }
+}
+
+/// A timer that checks the idle state of the user again.
+#[derive(JSTraceable, MallocSizeOf)]
+pub struct IdleDetectorPollCallback {
+    #[ignore_malloc_size_of = "non-owning"]
+    detector: Trusted<IdleDetector>,
+}
+
+impl IdleDetectorPollCallback {
+    pub fn invoke(self) {
+        self.detector.root().poll();
+    }
+}
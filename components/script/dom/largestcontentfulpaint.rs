@@ -0,0 +1,110 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::ElementBinding::ElementMethods;
+use crate::dom::bindings::codegen::Bindings::LargestContentfulPaintBinding::LargestContentfulPaintMethods;
+use crate::dom::bindings::codegen::Bindings::PerformanceBinding::DOMHighResTimeStamp;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::reflector::reflect_dom_object;
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::element::Element;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::node::Node;
+use crate::dom::performance::reduce_timing_resolution;
+use crate::dom::performanceentry::PerformanceEntry;
+use dom_struct::dom_struct;
+
+/// <https://w3c.github.io/largest-contentful-paint/#sec-largest-contentful-paint-interface>
+#[dom_struct]
+pub struct LargestContentfulPaint {
+    entry: PerformanceEntry,
+    render_time: f64,
+    load_time: f64,
+    size: u32,
+    id: DOMString,
+    url: DOMString,
+    element: Dom<Element>,
+}
+
+impl LargestContentfulPaint {
+    fn new_inherited(
+        render_time: f64,
+        load_time: f64,
+        size: u32,
+        url: DOMString,
+        element: &Element,
+    ) -> LargestContentfulPaint {
+        // https://w3c.github.io/largest-contentful-paint/#sec-add-lcp-entry
+        let start_time = if render_time != 0. {
+            render_time
+        } else {
+            load_time
+        };
+        LargestContentfulPaint {
+            entry: PerformanceEntry::new_inherited(
+                DOMString::new(),
+                DOMString::from("largest-contentful-paint"),
+                start_time,
+                0.,
+            ),
+            render_time,
+            load_time,
+            size,
+            id: element.Id(),
+            url,
+            element: Dom::from_ref(element),
+        }
+    }
+
+    #[allow(unrooted_must_root)]
+    pub fn new(
+        global: &GlobalScope,
+        render_time: f64,
+        load_time: f64,
+        size: u32,
+        url: DOMString,
+        element: &Element,
+    ) -> DomRoot<LargestContentfulPaint> {
+        let entry =
+            LargestContentfulPaint::new_inherited(render_time, load_time, size, url, element);
+        reflect_dom_object(Box::new(entry), global)
+    }
+}
+
+impl LargestContentfulPaintMethods for LargestContentfulPaint {
+    // https://w3c.github.io/largest-contentful-paint/#dom-largestcontentfulpaint-rendertime
+    fn RenderTime(&self) -> DOMHighResTimeStamp {
+        reduce_timing_resolution(self.render_time)
+    }
+
+    // https://w3c.github.io/largest-contentful-paint/#dom-largestcontentfulpaint-loadtime
+    fn LoadTime(&self) -> DOMHighResTimeStamp {
+        reduce_timing_resolution(self.load_time)
+    }
+
+    // https://w3c.github.io/largest-contentful-paint/#dom-largestcontentfulpaint-size
+    fn Size(&self) -> u32 {
+        self.size
+    }
+
+    // https://w3c.github.io/largest-contentful-paint/#dom-largestcontentfulpaint-id
+    fn Id(&self) -> DOMString {
+        self.id.clone()
+    }
+
+    // https://w3c.github.io/largest-contentful-paint/#dom-largestcontentfulpaint-url
+    fn Url(&self) -> DOMString {
+        self.url.clone()
+    }
+
+    // https://w3c.github.io/largest-contentful-paint/#dom-largestcontentfulpaint-element
+    fn GetElement(&self) -> Option<DomRoot<Element>> {
+        // Elements which were removed from the document are not exposed.
+        if !self.element.upcast::<Node>().is_connected() {
+            return None;
+        }
+        Some(DomRoot::from_ref(&*self.element))
+    }
+}
@@ -0,0 +1,119 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::LayoutShiftBinding::LayoutShiftMethods;
+use crate::dom::bindings::codegen::Bindings::PerformanceBinding::DOMHighResTimeStamp;
+use crate::dom::bindings::num::Finite;
+use crate::dom::bindings::reflector::reflect_dom_object;
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::bindings::utils::to_frozen_array;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::layoutshiftattribution::LayoutShiftAttribution;
+use crate::dom::performance::reduce_timing_resolution;
+use crate::dom::performanceentry::PerformanceEntry;
+use crate::script_runtime::JSContext;
+use dom_struct::dom_struct;
+use js::jsapi::Heap;
+use js::jsval::JSVal;
+
+/// <https://wicg.github.io/layout-instability/#sec-layout-shift>
+#[dom_struct]
+pub struct LayoutShift {
+    entry: PerformanceEntry,
+    value: f64,
+    had_recent_input: bool,
+    last_input_time: f64,
+    sources: Vec<Dom<LayoutShiftAttribution>>,
+    #[ignore_malloc_size_of = "mozjs"]
+    frozen_sources: DomRefCell<Option<Heap<JSVal>>>,
+}
+
+impl LayoutShift {
+    fn new_inherited(
+        start_time: f64,
+        value: f64,
+        had_recent_input: bool,
+        last_input_time: f64,
+        sources: &[DomRoot<LayoutShiftAttribution>],
+    ) -> LayoutShift {
+        LayoutShift {
+            entry: PerformanceEntry::new_inherited(
+                DOMString::new(),
+                DOMString::from("layout-shift"),
+                start_time,
+                0.,
+            ),
+            value,
+            had_recent_input,
+            last_input_time,
+            sources: sources
+                .iter()
+                .map(|source| Dom::from_ref(&**source))
+                .collect(),
+            frozen_sources: DomRefCell::new(None),
+        }
+    }
+
+    #[allow(unrooted_must_root)]
+    pub fn new(
+        global: &GlobalScope,
+        start_time: f64,
+        value: f64,
+        had_recent_input: bool,
+        last_input_time: f64,
+        sources: &[DomRoot<LayoutShiftAttribution>],
+    ) -> DomRoot<LayoutShift> {
+        let entry = LayoutShift::new_inherited(
+            start_time,
+            value,
+            had_recent_input,
+            last_input_time,
+            sources,
+        );
+        reflect_dom_object(Box::new(entry), global)
+    }
+}
+
+impl LayoutShiftMethods for LayoutShift {
+    // https://wicg.github.io/layout-instability/#dom-layoutshift-value
+    fn Value(&self) -> Finite<f64> {
+        Finite::wrap(self.value)
+    }
+
+    // https://wicg.github.io/layout-instability/#dom-layoutshift-hadrecentinput
+    fn HadRecentInput(&self) -> bool {
+        self.had_recent_input
+    }
+
+    // https://wicg.github.io/layout-instability/#dom-layoutshift-lastinputtime
+    fn LastInputTime(&self) -> DOMHighResTimeStamp {
+        reduce_timing_resolution(self.last_input_time)
+    }
+
+    // https://wicg.github.io/layout-instability/#dom-layoutshift-sources
+    fn Sources(&self, cx: JSContext) -> JSVal {
+        if let Some(sources) = &*self.frozen_sources.borrow() {
+            return sources.get();
+        }
+
+        let sources: Vec<DomRoot<LayoutShiftAttribution>> = self
+            .sources
+            .iter()
+            .map(|source| DomRoot::from_ref(&**source))
+            .collect();
+        let frozen_sources = to_frozen_array(sources.as_slice(), cx);
+
+        // Safety: need to create the Heap value in its final memory location before setting it.
+        *self.frozen_sources.borrow_mut() = Some(Heap::default());
+        self.frozen_sources
+            .borrow()
+            .as_ref()
+            .unwrap()
+            .set(frozen_sources);
+
+        frozen_sources
+    }
+}
@@ -0,0 +1,72 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::LayoutShiftAttributionBinding::LayoutShiftAttributionMethods;
+use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::domrectreadonly::DOMRectReadOnly;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::node::Node;
+use dom_struct::dom_struct;
+
+/// <https://wicg.github.io/layout-instability/#sec-layout-shift-attribution>
+#[dom_struct]
+pub struct LayoutShiftAttribution {
+    reflector_: Reflector,
+    node: Dom<Node>,
+    previous_rect: Dom<DOMRectReadOnly>,
+    current_rect: Dom<DOMRectReadOnly>,
+}
+
+impl LayoutShiftAttribution {
+    fn new_inherited(
+        node: &Node,
+        previous_rect: &DOMRectReadOnly,
+        current_rect: &DOMRectReadOnly,
+    ) -> LayoutShiftAttribution {
+        LayoutShiftAttribution {
+            reflector_: Reflector::new(),
+            node: Dom::from_ref(node),
+            previous_rect: Dom::from_ref(previous_rect),
+            current_rect: Dom::from_ref(current_rect),
+        }
+    }
+
+    pub fn new(
+        global: &GlobalScope,
+        node: &Node,
+        previous_rect: &DOMRectReadOnly,
+        current_rect: &DOMRectReadOnly,
+    ) -> DomRoot<LayoutShiftAttribution> {
+        reflect_dom_object(
+            Box::new(LayoutShiftAttribution::new_inherited(
+                node,
+                previous_rect,
+                current_rect,
+            )),
+            global,
+        )
+    }
+}
+
+impl LayoutShiftAttributionMethods for LayoutShiftAttribution {
+    // https://wicg.github.io/layout-instability/#dom-layoutshiftattribution-node
+    fn GetNode(&self) -> Option<DomRoot<Node>> {
+        // Nodes which were removed from the document are not exposed.
+        if !self.node.is_connected() {
+            return None;
+        }
+        Some(DomRoot::from_ref(&*self.node))
+    }
+
+    // https://wicg.github.io/layout-instability/#dom-layoutshiftattribution-previousrect
+    fn PreviousRect(&self) -> DomRoot<DOMRectReadOnly> {
+        DomRoot::from_ref(&*self.previous_rect)
+    }
+
+    // https://wicg.github.io/layout-instability/#dom-layoutshiftattribution-currentrect
+    fn CurrentRect(&self) -> DomRoot<DOMRectReadOnly> {
+        DomRoot::from_ref(&*self.current_rect)
+    }
+}
@@ -0,0 +1,384 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::abortsignal::AbortAlgorithm;
+use crate::dom::bindings::callback::ExceptionHandling;
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::AbortSignalBinding::AbortSignalMethods;
+use crate::dom::bindings::codegen::Bindings::LockManagerBinding::{
+    LockGrantedCallback, LockInfo, LockManagerMethods, LockManagerSnapshot, LockMode, LockOptions,
+};
+use crate::dom::bindings::error::Error;
+use crate::dom::bindings::refcounted::Trusted;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::lock::Lock;
+use crate::dom::promise::Promise;
+use crate::dom::promisenativehandler::{Callback, PromiseNativeHandler};
+use crate::realms::enter_realm;
+use crate::task_source::{TaskSource, TaskSourceName};
+use dom_struct::dom_struct;
+use ipc_channel::ipc;
+use ipc_channel::router::ROUTER;
+use js::jsapi::{JSContext, JS_ClearPendingException};
+use js::jsval::UndefinedValue;
+use js::rust::wrappers::JS_GetPendingException;
+use js::rust::HandleValue;
+use msg::constellation_msg::LockRequestId;
+use profile_traits::ipc as profiled_ipc;
+use script_traits::{self, LockEvent, LockRequest, ScriptMsg};
+use std::rc::Rc;
+use uuid::Uuid;
+
+/// A request for a lock that wasn't granted yet.
+#[derive(JSTraceable, MallocSizeOf)]
+#[unrooted_must_root_lint::must_root]
+struct PendingLockRequest {
+    id: LockRequestId,
+    name: DOMString,
+    mode: LockMode,
+    #[ignore_malloc_size_of = "Rc is hard"]
+    callback: Rc<LockGrantedCallback>,
+    /// The promise that `request()` returned.
+    #[ignore_malloc_size_of = "Rc is hard"]
+    promise: Rc<Promise>,
+}
+
+/// A lock that is held until the promise that its callback returned settles.
+#[derive(JSTraceable, MallocSizeOf)]
+#[unrooted_must_root_lint::must_root]
+struct HeldLock {
+    id: LockRequestId,
+    /// <https://w3c.github.io/web-locks/#lock-concept-released-promise>
+    #[ignore_malloc_size_of = "Rc is hard"]
+    released_promise: Rc<Promise>,
+}
+
+/// <https://w3c.github.io/web-locks/#lockmanager>
+///
+/// The lock manager of the origin lives in the constellation, this object keeps the requests
+/// of its global until they are granted and the locks until they are released.
+#[dom_struct]
+pub struct LockManager {
+    reflector_: Reflector,
+    /// The id of the environment, which `query()` reports for the requests made here.
+    client_id: String,
+    pending: DomRefCell<Vec<PendingLockRequest>>,
+    held: DomRefCell<Vec<HeldLock>>,
+}
+
+impl LockManager {
+    fn new_inherited() -> LockManager {
+        LockManager {
+            reflector_: Reflector::new(),
+            client_id: Uuid::new_v4().to_string(),
+            pending: DomRefCell::new(vec![]),
+            held: DomRefCell::new(vec![]),
+        }
+    }
+
+    pub fn new(global: &GlobalScope) -> DomRoot<LockManager> {
+        reflect_dom_object(Box::new(LockManager::new_inherited()), global)
+    }
+
+    /// <https://w3c.github.io/web-locks/#dom-lockmanager-request>
+    #[allow(unrooted_must_root)]
+    fn request(
+        &self,
+        name: DOMString,
+        options: &LockOptions,
+        callback: Rc<LockGrantedCallback>,
+    ) -> Rc<Promise> {
+        let global = self.global();
+        let promise = Promise::new(&global);
+
+        // Step 4.
+        if !global.origin().immutable().is_tuple() {
+            promise.reject_error(Error::Security);
+            return promise;
+        }
+
+        // Steps 5-8.
+        if name.starts_with('-') ||
+            (options.steal && options.ifAvailable) ||
+            (options.steal && options.mode != LockMode::Exclusive) ||
+            (options.signal.is_some() && (options.steal || options.ifAvailable))
+        {
+            promise.reject_error(Error::NotSupported);
+            return promise;
+        }
+
+        // Step 9.
+        if let Some(ref signal) = options.signal {
+            if signal.Aborted() {
+                promise.reject_error(Error::Abort);
+                return promise;
+            }
+        }
+
+        // Step 10.
+        let id = LockRequestId::new();
+        self.pending.borrow_mut().push(PendingLockRequest {
+            id,
+            name: name.clone(),
+            mode: options.mode,
+            callback,
+            promise: promise.clone(),
+        });
+
+        let (sender, receiver) = ipc::channel().unwrap();
+        let task_source = global.dom_manipulation_task_source();
+        let canceller = global.task_canceller(TaskSourceName::DOMManipulation);
+        let manager = Trusted::new(self);
+        ROUTER.add_route(
+            receiver.to_opaque(),
+            Box::new(move |message| {
+                let manager = manager.clone();
+                let event = message.to().unwrap();
+                let _ = task_source.queue_with_canceller(
+                    task!(handle_lock_event: move || {
+                        manager.root().handle_event(id, event);
+                    }),
+                    &canceller,
+                );
+            }),
+        );
+        let mode = match options.mode {
+            LockMode::Shared => script_traits::LockMode::Shared,
+            LockMode::Exclusive => script_traits::LockMode::Exclusive,
+        };
+        let _ = global
+            .script_to_constellation_chan()
+            .send(ScriptMsg::RequestLock(LockRequest {
+                id,
+                origin: global.origin().immutable().clone(),
+                name: name.into(),
+                mode,
+                client_id: self.client_id.clone(),
+                if_available: options.ifAvailable,
+                steal: options.steal,
+                sender,
+            }));
+
+        // Step 11.
+        if let Some(ref signal) = options.signal {
+            signal.add_algorithm(AbortAlgorithm::LockRequest(Dom::from_ref(self), id));
+        }
+
+        // Step 12.
+        promise
+    }
+
+    #[allow(unrooted_must_root)]
+    fn take_pending(&self, id: LockRequestId) -> Option<PendingLockRequest> {
+        let mut pending = self.pending.borrow_mut();
+        let index = pending.iter().position(|request| request.id == id)?;
+        Some(pending.remove(index))
+    }
+
+    #[allow(unrooted_must_root)]
+    fn take_held(&self, id: LockRequestId) -> Option<HeldLock> {
+        let mut held = self.held.borrow_mut();
+        let index = held.iter().position(|lock| lock.id == id)?;
+        Some(held.remove(index))
+    }
+
+    fn send_release(&self, id: LockRequestId) {
+        let global = self.global();
+        let _ = global
+            .script_to_constellation_chan()
+            .send(ScriptMsg::ReleaseLock(
+                global.origin().immutable().clone(),
+                id,
+            ));
+    }
+
+    #[allow(unrooted_must_root)]
+    fn handle_event(&self, id: LockRequestId, event: LockEvent) {
+        match event {
+            // https://w3c.github.io/web-locks/#process-the-lock-request-queue
+            LockEvent::Granted => {
+                // A request that was aborted meanwhile was already dropped by the lock manager.
+                let request = match self.take_pending(id) {
+                    Some(request) => request,
+                    None => return,
+                };
+                let global = self.global();
+                let lock = Lock::new(&global, request.name, request.mode);
+                self.held.borrow_mut().push(HeldLock {
+                    id,
+                    released_promise: request.promise,
+                });
+                let waiting_promise = Promise::new(&global);
+                let handler = PromiseNativeHandler::new(
+                    &global,
+                    Some(Box::new(LockReleaser {
+                        manager: Trusted::new(self),
+                        id,
+                        fulfilled: true,
+                    })),
+                    Some(Box::new(LockReleaser {
+                        manager: Trusted::new(self),
+                        id,
+                        fulfilled: false,
+                    })),
+                );
+                waiting_promise.append_native_handler(&handler);
+                self.invoke_callback(&request.callback, Some(&lock), &waiting_promise);
+            },
+            // https://w3c.github.io/web-locks/#request-a-lock, step 3.
+            LockEvent::NotAvailable => {
+                if let Some(request) = self.take_pending(id) {
+                    self.invoke_callback(&request.callback, None, &request.promise);
+                }
+            },
+            // https://w3c.github.io/web-locks/#request-a-lock, step 1.
+            LockEvent::Stolen => {
+                if let Some(lock) = self.take_held(id) {
+                    lock.released_promise.reject_error(Error::Abort);
+                }
+            },
+        }
+    }
+
+    /// Resolves the promise with the result of the callback, or rejects it with the exception
+    /// that the callback threw.
+    #[allow(unsafe_code)]
+    fn invoke_callback(
+        &self,
+        callback: &LockGrantedCallback,
+        lock: Option<&Lock>,
+        promise: &Promise,
+    ) {
+        let cx = self.global().get_cx();
+        let _ac = enter_realm(self);
+        match callback.Call__(lock, ExceptionHandling::Rethrow) {
+            Ok(value) => {
+                rooted!(in(*cx) let value = value);
+                promise.resolve(cx, value.handle());
+            },
+            Err(_) => {
+                rooted!(in(*cx) let mut exception = UndefinedValue());
+                unsafe {
+                    if JS_GetPendingException(*cx, exception.handle_mut()) {
+                        JS_ClearPendingException(*cx);
+                    }
+                }
+                promise.reject(cx, exception.handle());
+            },
+        }
+    }
+
+    /// Releases the lock once the promise that its callback returned settles, and settles the
+    /// released promise the same way, unless the lock was stolen.
+    /// <https://w3c.github.io/web-locks/#release-the-lock>
+    #[allow(unrooted_must_root)]
+    fn release(&self, id: LockRequestId, value: HandleValue, fulfilled: bool) {
+        let lock = match self.take_held(id) {
+            Some(lock) => lock,
+            None => return,
+        };
+        self.send_release(id);
+        let cx = self.global().get_cx();
+        if fulfilled {
+            lock.released_promise.resolve(cx, value);
+        } else {
+            lock.released_promise.reject(cx, value);
+        }
+    }
+
+    /// <https://w3c.github.io/web-locks/#abort-the-request>
+    #[allow(unrooted_must_root)]
+    pub fn abort_request(&self, id: LockRequestId) {
+        if let Some(request) = self.take_pending(id) {
+            self.send_release(id);
+            request.promise.reject_error(Error::Abort);
+        }
+    }
+
+    /// Releases the held locks and drops the pending requests, for a global that is closing.
+    pub fn release_all(&self) {
+        let ids: Vec<_> = self
+            .pending
+            .borrow()
+            .iter()
+            .map(|request| request.id)
+            .chain(self.held.borrow().iter().map(|lock| lock.id))
+            .collect();
+        for id in ids {
+            self.send_release(id);
+        }
+    }
+}
+
+impl LockManagerMethods for LockManager {
+    // https://w3c.github.io/web-locks/#dom-lockmanager-request
+    fn Request(&self, name: DOMString, callback: Rc<LockGrantedCallback>) -> Rc<Promise> {
+        self.request(name, &LockOptions::empty(), callback)
+    }
+
+    // https://w3c.github.io/web-locks/#dom-lockmanager-request-name-options-callback
+    fn Request_(
+        &self,
+        name: DOMString,
+        options: &LockOptions,
+        callback: Rc<LockGrantedCallback>,
+    ) -> Rc<Promise> {
+        self.request(name, options, callback)
+    }
+
+    // https://w3c.github.io/web-locks/#dom-lockmanager-query
+    fn Query(&self) -> Rc<Promise> {
+        let global = self.global();
+        let promise = Promise::new(&global);
+
+        // Step 3.
+        if !global.origin().immutable().is_tuple() {
+            promise.reject_error(Error::Security);
+            return promise;
+        }
+
+        // Steps 5-6.
+        let (sender, receiver) =
+            profiled_ipc::channel(global.time_profiler_chan().clone()).unwrap();
+        let _ = global
+            .script_to_constellation_chan()
+            .send(ScriptMsg::QueryLocks(
+                global.origin().immutable().clone(),
+                sender,
+            ));
+        let (held, pending) = receiver.recv().unwrap_or_default();
+        let info = |info: script_traits::LockInfo| LockInfo {
+            name: Some(DOMString::from(info.name)),
+            mode: Some(match info.mode {
+                script_traits::LockMode::Shared => LockMode::Shared,
+                script_traits::LockMode::Exclusive => LockMode::Exclusive,
+            }),
+            clientId: Some(DOMString::from(info.client_id)),
+        };
+        let snapshot = LockManagerSnapshot {
+            held: Some(held.into_iter().map(info).collect()),
+            pending: Some(pending.into_iter().map(info).collect()),
+        };
+        promise.resolve_native(&snapshot);
+        promise
+    }
+}
+
+/// Releases a lock once the promise that its callback returned settles.
+#[derive(JSTraceable, MallocSizeOf)]
+struct LockReleaser {
+    #[ignore_malloc_size_of = "non-owning"]
+    manager: Trusted<LockManager>,
+    id: LockRequestId,
+    fulfilled: bool,
+}
+
+impl Callback for LockReleaser {
+    fn callback(&self, _cx: *mut JSContext, value: HandleValue) {
+        self.manager.root().release(self.id, value, self.fulfilled);
+    }
+}
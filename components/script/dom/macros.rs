@@ -187,6 +187,34 @@ macro_rules! make_setter(
     );
 );
 
+// Not all of the attributes that are reflected as nullable strings, such as the ARIA
+// attributes, have static atoms, so their names are interned at runtime.
+#[macro_export]
+macro_rules! make_nullable_getter(
+    ( $attr:ident, $htmlname:tt ) => (
+        fn $attr(&self) -> Option<DOMString> {
+            use crate::dom::bindings::inheritance::Castable;
+            use crate::dom::element::Element;
+            use html5ever::LocalName;
+            let element = self.upcast::<Element>();
+            element.get_nullable_string_attribute(&LocalName::from($htmlname))
+        }
+    );
+);
+
+#[macro_export]
+macro_rules! make_nullable_setter(
+    ( $attr:ident, $htmlname:tt ) => (
+        fn $attr(&self, value: Option<DOMString>) {
+            use crate::dom::bindings::inheritance::Castable;
+            use crate::dom::element::Element;
+            use html5ever::LocalName;
+            let element = self.upcast::<Element>();
+            element.set_nullable_string_attribute(&LocalName::from($htmlname), value)
+        }
+    );
+);
+
 #[macro_export]
 macro_rules! make_bool_setter(
     ( $attr:ident, $htmlname:tt ) => (
@@ -442,6 +470,7 @@ macro_rules! global_event_handlers(
     );
     (NoOnload) => (
         event_handler!(abort, GetOnabort, SetOnabort);
+        event_handler!(beforeinput, GetOnbeforeinput, SetOnbeforeinput);
         event_handler!(cancel, GetOncancel, SetOncancel);
         event_handler!(canplay, GetOncanplay, SetOncanplay);
         event_handler!(canplaythrough, GetOncanplaythrough, SetOncanplaythrough);
@@ -598,44 +627,6 @@ macro_rules! rooted_vec {
     };
 }
 
-/// DOM struct implementation for simple interfaces inheriting from PerformanceEntry.
-macro_rules! impl_performance_entry_struct(
-    ($binding:ident, $struct:ident, $type:expr) => (
-        use crate::dom::bindings::reflector::reflect_dom_object;
-        use crate::dom::bindings::root::DomRoot;
-        use crate::dom::bindings::str::DOMString;
-        use crate::dom::globalscope::GlobalScope;
-        use crate::dom::performanceentry::PerformanceEntry;
-        use dom_struct::dom_struct;
-
-        #[dom_struct]
-        pub struct $struct {
-            entry: PerformanceEntry,
-        }
-
-        impl $struct {
-            fn new_inherited(name: DOMString, start_time: f64, duration: f64)
-                -> $struct {
-                $struct {
-                    entry: PerformanceEntry::new_inherited(name,
-                                                           DOMString::from($type),
-                                                           start_time,
-                                                           duration)
-                }
-            }
-
-            #[allow(unrooted_must_root)]
-            pub fn new(global: &GlobalScope,
-                       name: DOMString,
-                       start_time: f64,
-                       duration: f64) -> DomRoot<$struct> {
-                let entry = $struct::new_inherited(name, start_time, duration);
-                reflect_dom_object(Box::new(entry), global)
-            }
-        }
-    );
-);
-
 macro_rules! handle_potential_webgl_error {
     ($context:expr, $call:expr, $return_on_error:expr) => {
         match $call {
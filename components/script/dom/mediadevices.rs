@@ -12,12 +12,15 @@ use crate::dom::bindings::reflector::DomObject;
 use crate::dom::bindings::root::DomRoot;
 use crate::dom::eventtarget::EventTarget;
 use crate::dom::globalscope::GlobalScope;
+use crate::dom::mediadeviceinfo::MediaDeviceInfo;
 use crate::dom::mediastream::MediaStream;
 use crate::dom::mediastreamtrack::MediaStreamTrack;
 use crate::dom::promise::Promise;
 use crate::realms::InRealm;
 use dom_struct::dom_struct;
 use servo_media::streams::capture::{Constrain, ConstrainRange, MediaTrackConstraintSet};
+use servo_media::streams::device_monitor::MediaDeviceInfo as ServoMediaDeviceInfo;
+use servo_media::streams::device_monitor::MediaDeviceKind as ServoMediaDeviceKind;
 use servo_media::streams::MediaStreamType;
 use servo_media::ServoMedia;
 use std::rc::Rc;
@@ -39,6 +42,28 @@ impl MediaDevices {
     }
 }
 
+/// The media input and output devices of the platform.
+fn enumerate_devices() -> Vec<ServoMediaDeviceInfo> {
+    ServoMedia::get()
+        .unwrap()
+        .get_device_monitor()
+        .enumerate_devices()
+        .unwrap_or_else(|_| {
+            warn!("Could not enumerate the media devices");
+            vec![]
+        })
+}
+
+/// Whether the given sink identifier names an audio output device of the platform, or the
+/// default one, as an empty string does.
+/// <https://w3c.github.io/mediacapture-output/#dom-htmlmediaelement-setsinkid>
+pub fn is_audio_output_device(sink_id: &str) -> bool {
+    sink_id.is_empty() ||
+        enumerate_devices().iter().any(|device| {
+            device.kind == ServoMediaDeviceKind::AudioOutput && device.device_id == sink_id
+        })
+}
+
 impl MediaDevicesMethods for MediaDevices {
     /// https://w3c.github.io/mediacapture-main/#dom-mediadevices-getusermedia
     #[allow(unsafe_code)]
@@ -62,6 +87,25 @@ impl MediaDevicesMethods for MediaDevices {
         p.resolve_native(&stream);
         p
     }
+
+    /// https://w3c.github.io/mediacapture-main/#dom-mediadevices-enumeratedevices
+    fn EnumerateDevices(&self, comp: InRealm) -> Rc<Promise> {
+        let p = Promise::new_in_current_realm(&self.global(), comp);
+        let devices: Vec<_> = enumerate_devices()
+            .into_iter()
+            .map(|device| {
+                MediaDeviceInfo::new(
+                    &self.global(),
+                    &device.device_id,
+                    device.kind.into(),
+                    &device.label,
+                    "",
+                )
+            })
+            .collect();
+        p.resolve_native(&devices);
+        p
+    }
 }
 
 fn convert_constraints(js: &BooleanOrMediaTrackConstraints) -> Option<MediaTrackConstraintSet> {
@@ -3,15 +3,24 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::MediaMetadataBinding::MediaImage;
 use crate::dom::bindings::codegen::Bindings::MediaMetadataBinding::MediaMetadataInit;
 use crate::dom::bindings::codegen::Bindings::MediaMetadataBinding::MediaMetadataMethods;
-use crate::dom::bindings::error::Fallible;
-use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
+use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
+use crate::dom::bindings::error::{Error, ErrorResult, Fallible};
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
 use crate::dom::bindings::root::{DomRoot, MutNullableDom};
-use crate::dom::bindings::str::DOMString;
+use crate::dom::bindings::str::{DOMString, USVString};
+use crate::dom::bindings::utils::to_frozen_array;
 use crate::dom::mediasession::MediaSession;
 use crate::dom::window::Window;
+use crate::script_runtime::JSContext;
 use dom_struct::dom_struct;
+use embedder_traits::MediaImage as EmbedderMediaImage;
+use js::conversions::{ConversionResult, FromJSValConvertible};
+use js::jsapi::Heap;
+use js::jsval::JSVal;
+use js::rust::HandleValue;
 
 #[dom_struct]
 pub struct MediaMetadata {
@@ -20,21 +29,36 @@ pub struct MediaMetadata {
     title: DomRefCell<DOMString>,
     artist: DomRefCell<DOMString>,
     album: DomRefCell<DOMString>,
+    /// https://w3c.github.io/mediasession/#dom-mediametadata-artwork-images-slot
+    #[ignore_malloc_size_of = "defined in embedder_traits"]
+    artwork: DomRefCell<Vec<EmbedderMediaImage>>,
+    /// The frozen array returned by `artwork` until it is set again.
+    #[ignore_malloc_size_of = "mozjs"]
+    frozen_artwork: DomRefCell<Option<Heap<JSVal>>>,
 }
 
 impl MediaMetadata {
-    fn new_inherited(init: &MediaMetadataInit) -> MediaMetadata {
+    fn new_inherited(init: &MediaMetadataInit, artwork: Vec<EmbedderMediaImage>) -> MediaMetadata {
         MediaMetadata {
             reflector_: Reflector::new(),
             session: Default::default(),
             title: DomRefCell::new(init.title.clone()),
             artist: DomRefCell::new(init.artist.clone()),
             album: DomRefCell::new(init.album.clone()),
+            artwork: DomRefCell::new(artwork),
+            frozen_artwork: DomRefCell::new(None),
         }
     }
 
-    pub fn new(global: &Window, init: &MediaMetadataInit) -> DomRoot<MediaMetadata> {
-        reflect_dom_object(Box::new(MediaMetadata::new_inherited(init)), global)
+    pub fn new(
+        global: &Window,
+        init: &MediaMetadataInit,
+        artwork: Vec<EmbedderMediaImage>,
+    ) -> DomRoot<MediaMetadata> {
+        reflect_dom_object(
+            Box::new(MediaMetadata::new_inherited(init, artwork)),
+            global,
+        )
     }
 
     /// https://w3c.github.io/mediasession/#dom-mediametadata-mediametadata
@@ -43,18 +67,42 @@ impl MediaMetadata {
         window: &Window,
         init: &MediaMetadataInit,
     ) -> Fallible<DomRoot<MediaMetadata>> {
-        Ok(MediaMetadata::new(window, init))
+        let artwork = convert_artwork(window, &init.artwork)?;
+        Ok(MediaMetadata::new(window, init, artwork))
     }
 
+    /// https://w3c.github.io/mediasession/#queue-a-task-to-update-mediasession-metadata
     fn queue_update_metadata_algorithm(&self) {
-        if self.session.get().is_none() {
-            return;
+        if let Some(session) = self.session.get() {
+            session.update_metadata(self);
         }
     }
 
     pub fn set_session(&self, session: &MediaSession) {
         self.session.set(Some(&session));
     }
+
+    pub fn artwork(&self) -> Vec<EmbedderMediaImage> {
+        self.artwork.borrow().clone()
+    }
+}
+
+/// https://w3c.github.io/mediasession/#convert-artwork-algorithm
+fn convert_artwork(window: &Window, images: &[MediaImage]) -> Fallible<Vec<EmbedderMediaImage>> {
+    let base_url = window.Document().base_url();
+    images
+        .iter()
+        .map(|image| {
+            let src = base_url
+                .join(&image.src.0)
+                .map_err(|_| Error::Type(format!("Invalid artwork URL {}", image.src.0)))?;
+            Ok(EmbedderMediaImage {
+                src,
+                sizes: image.sizes.to_string(),
+                type_: image.type_.to_string(),
+            })
+        })
+        .collect()
 }
 
 impl MediaMetadataMethods for MediaMetadata {
@@ -90,4 +138,50 @@ impl MediaMetadataMethods for MediaMetadata {
         *self.album.borrow_mut() = value;
         self.queue_update_metadata_algorithm();
     }
+
+    /// https://w3c.github.io/mediasession/#dom-mediametadata-artwork
+    fn Artwork(&self, cx: JSContext) -> JSVal {
+        if let Some(artwork) = &*self.frozen_artwork.borrow() {
+            return artwork.get();
+        }
+
+        let images: Vec<MediaImage> = self
+            .artwork
+            .borrow()
+            .iter()
+            .map(|image| MediaImage {
+                src: USVString(image.src.to_string()),
+                sizes: DOMString::from(image.sizes.clone()),
+                type_: DOMString::from(image.type_.clone()),
+            })
+            .collect();
+        let frozen_artwork = to_frozen_array(images.as_slice(), cx);
+
+        // Safety: need to create the Heap value in its final memory location before setting it.
+        *self.frozen_artwork.borrow_mut() = Some(Heap::default());
+        self.frozen_artwork
+            .borrow()
+            .as_ref()
+            .unwrap()
+            .set(frozen_artwork);
+
+        frozen_artwork
+    }
+
+    /// https://w3c.github.io/mediasession/#dom-mediametadata-artwork
+    #[allow(unsafe_code)]
+    fn SetArtwork(&self, cx: JSContext, value: HandleValue) -> ErrorResult {
+        let conversion = unsafe { Vec::<MediaImage>::from_jsval(*cx, value, ()) };
+        let images = match conversion {
+            Ok(ConversionResult::Success(images)) => images,
+            Ok(ConversionResult::Failure(error)) => return Err(Error::Type(error.into())),
+            Err(()) => return Err(Error::JSFailed),
+        };
+
+        let artwork = convert_artwork(self.global().as_window(), &images)?;
+        *self.artwork.borrow_mut() = artwork;
+        *self.frozen_artwork.borrow_mut() = None;
+        self.queue_update_metadata_algorithm();
+        Ok(())
+    }
 }
@@ -13,6 +13,7 @@ use crate::dom::bindings::str::DOMString;
 use crate::dom::document::Document;
 use crate::dom::eventtarget::EventTarget;
 use dom_struct::dom_struct;
+use servo_atoms::Atom;
 use std::cell::Cell;
 use std::rc::Rc;
 use style::media_queries::MediaList;
@@ -28,16 +29,18 @@ pub struct MediaQueryList {
     eventtarget: EventTarget,
     document: Dom<Document>,
     media_query_list: MediaList,
-    last_match_state: Cell<Option<bool>>,
+    /// <https://drafts.csswg.org/cssom-view/#mediaquerylist-matches-state>
+    last_match_state: Cell<bool>,
 }
 
 impl MediaQueryList {
     fn new_inherited(document: &Document, media_query_list: MediaList) -> MediaQueryList {
+        let matches = media_query_list.evaluate(&document.device(), document.quirks_mode());
         MediaQueryList {
             eventtarget: EventTarget::new_inherited(),
             document: Dom::from_ref(document),
             media_query_list: media_query_list,
-            last_match_state: Cell::new(None),
+            last_match_state: Cell::new(matches),
         }
     }
 
@@ -53,17 +56,13 @@ impl MediaQueryList {
     pub fn evaluate_changes(&self) -> MediaQueryListMatchState {
         let matches = self.evaluate();
 
-        let result = if let Some(old_matches) = self.last_match_state.get() {
-            if old_matches == matches {
-                MediaQueryListMatchState::Same(matches)
-            } else {
-                MediaQueryListMatchState::Changed(matches)
-            }
+        let result = if self.last_match_state.get() == matches {
+            MediaQueryListMatchState::Same(matches)
         } else {
             MediaQueryListMatchState::Changed(matches)
         };
 
-        self.last_match_state.set(Some(matches));
+        self.last_match_state.set(matches);
         result
     }
 
@@ -71,6 +70,14 @@ impl MediaQueryList {
         self.media_query_list
             .evaluate(&self.document.device(), self.document.quirks_mode())
     }
+
+    /// The window only keeps weak references to the lists it evaluates, so a list with `change`
+    /// listeners asks it to keep the list alive, or script dropping the list would silence them.
+    pub fn listener_added(&self, ty: &Atom) {
+        if *ty == atom!("change") {
+            self.document.window().observe_media_query_list(self);
+        }
+    }
 }
 
 impl MediaQueryListMethods for MediaQueryList {
@@ -81,10 +88,7 @@ impl MediaQueryListMethods for MediaQueryList {
 
     // https://drafts.csswg.org/cssom-view/#dom-mediaquerylist-matches
     fn Matches(&self) -> bool {
-        match self.last_match_state.get() {
-            None => self.evaluate(),
-            Some(state) => state,
-        }
+        self.last_match_state.get()
     }
 
     // https://drafts.csswg.org/cssom-view/#dom-mediaquerylist-addlistener
@@ -5,10 +5,10 @@
 use crate::dom::bindings::callback::ExceptionHandling;
 use crate::dom::bindings::cell::DomRefCell;
 use crate::dom::bindings::codegen::Bindings::HTMLMediaElementBinding::HTMLMediaElementMethods;
-use crate::dom::bindings::codegen::Bindings::MediaMetadataBinding::MediaMetadataInit;
 use crate::dom::bindings::codegen::Bindings::MediaMetadataBinding::MediaMetadataMethods;
 use crate::dom::bindings::codegen::Bindings::MediaSessionBinding::MediaPositionState;
 use crate::dom::bindings::codegen::Bindings::MediaSessionBinding::MediaSessionAction;
+use crate::dom::bindings::codegen::Bindings::MediaSessionBinding::MediaSessionActionDetails as DOMMediaSessionActionDetails;
 use crate::dom::bindings::codegen::Bindings::MediaSessionBinding::MediaSessionActionHandler;
 use crate::dom::bindings::codegen::Bindings::MediaSessionBinding::MediaSessionMethods;
 use crate::dom::bindings::codegen::Bindings::MediaSessionBinding::MediaSessionPlaybackState;
@@ -16,7 +16,6 @@ use crate::dom::bindings::error::{Error, Fallible};
 use crate::dom::bindings::num::Finite;
 use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
 use crate::dom::bindings::root::{DomRoot, MutNullableDom};
-use crate::dom::bindings::str::DOMString;
 use crate::dom::htmlmediaelement::HTMLMediaElement;
 use crate::dom::mediametadata::MediaMetadata;
 use crate::dom::window::Window;
@@ -24,15 +23,22 @@ use crate::realms::{AlreadyInRealm, InRealm};
 use dom_struct::dom_struct;
 use embedder_traits::MediaMetadata as EmbedderMediaMetadata;
 use embedder_traits::MediaSessionEvent;
-use script_traits::MediaSessionActionType;
+use embedder_traits::MediaSessionPlaybackState as EmbedderMediaSessionPlaybackState;
 use script_traits::ScriptMsg;
+use script_traits::{MediaSessionActionDetails, MediaSessionActionType};
 use std::collections::HashMap;
 use std::rc::Rc;
 
+/// How far the default `seekbackward` and `seekforward` actions move the playback time when
+/// the controls of the platform don't tell, in seconds.
+const DEFAULT_SEEK_OFFSET: f64 = 10.0;
+
 #[dom_struct]
 pub struct MediaSession {
     reflector_: Reflector,
     /// https://w3c.github.io/mediasession/#dom-mediasession-metadata
+    metadata_object: MutNullableDom<MediaMetadata>,
+    /// The metadata that was last sent to the embedder.
     #[ignore_malloc_size_of = "defined in embedder_traits"]
     metadata: DomRefCell<Option<EmbedderMediaMetadata>>,
     /// https://w3c.github.io/mediasession/#dom-mediasession-playbackstate
@@ -50,6 +56,7 @@ impl MediaSession {
     fn new_inherited() -> MediaSession {
         let media_session = MediaSession {
             reflector_: Reflector::new(),
+            metadata_object: Default::default(),
             metadata: DomRefCell::new(None),
             playback_state: DomRefCell::new(MediaSessionPlaybackState::None),
             action_handlers: DomRefCell::new(HashMap::new()),
@@ -66,11 +73,22 @@ impl MediaSession {
         self.media_instance.set(Some(media_instance));
     }
 
-    pub fn handle_action(&self, action: MediaSessionActionType) {
-        debug!("Handle media session action {:?}", action);
+    /// https://w3c.github.io/mediasession/#handle-media-session-action
+    pub fn handle_action(&self, details: MediaSessionActionDetails) {
+        debug!("Handle media session action {:?}", details);
 
-        if let Some(handler) = self.action_handlers.borrow().get(&action) {
-            if handler.Call__(ExceptionHandling::Report).is_err() {
+        let handler = self.action_handlers.borrow().get(&details.action).cloned();
+        if let Some(handler) = handler {
+            let dom_details = DOMMediaSessionActionDetails {
+                action: details.action.into(),
+                seekOffset: details.seek_offset.and_then(Finite::new),
+                seekTime: details.seek_time.and_then(Finite::new),
+                fastSeek: details.fast_seek,
+            };
+            if handler
+                .Call__(&dom_details, ExceptionHandling::Report)
+                .is_err()
+            {
                 warn!("Error calling MediaSessionActionHandler callback");
             }
             return;
@@ -78,7 +96,7 @@ impl MediaSession {
 
         // Default action.
         if let Some(media) = self.media_instance.get() {
-            match action {
+            match details.action {
                 MediaSessionActionType::Play => {
                     let in_realm_proof = AlreadyInRealm::assert(&self.global());
                     media.Play(InRealm::Already(&in_realm_proof));
@@ -86,24 +104,70 @@ impl MediaSession {
                 MediaSessionActionType::Pause => {
                     media.Pause();
                 },
-                MediaSessionActionType::SeekBackward => {},
-                MediaSessionActionType::SeekForward => {},
+                MediaSessionActionType::SeekBackward => {
+                    let offset = details.seek_offset.unwrap_or(DEFAULT_SEEK_OFFSET);
+                    let time = (*media.CurrentTime() - offset).max(0.0);
+                    if let Some(time) = Finite::new(time) {
+                        media.SetCurrentTime(time);
+                    }
+                },
+                MediaSessionActionType::SeekForward => {
+                    let offset = details.seek_offset.unwrap_or(DEFAULT_SEEK_OFFSET);
+                    if let Some(time) = Finite::new(*media.CurrentTime() + offset) {
+                        media.SetCurrentTime(time);
+                    }
+                },
                 MediaSessionActionType::PreviousTrack => {},
                 MediaSessionActionType::NextTrack => {},
                 MediaSessionActionType::SkipAd => {},
-                MediaSessionActionType::Stop => {},
-                MediaSessionActionType::SeekTo => {},
+                MediaSessionActionType::Stop => {
+                    media.Pause();
+                    media.SetCurrentTime(Finite::wrap(0.0));
+                },
+                MediaSessionActionType::SeekTo => {
+                    if let Some(time) = details.seek_time.and_then(Finite::new) {
+                        if details.fast_seek == Some(true) {
+                            media.FastSeek(time);
+                        } else {
+                            media.SetCurrentTime(time);
+                        }
+                    }
+                },
             }
         }
     }
 
     pub fn send_event(&self, event: MediaSessionEvent) {
+        let event = match event {
+            MediaSessionEvent::PlaybackStateChange(state) => {
+                MediaSessionEvent::PlaybackStateChange(self.actual_playback_state(state))
+            },
+            event => event,
+        };
+
         let global = self.global();
         let window = global.as_window();
         let pipeline_id = window.pipeline_id();
         window.send_to_constellation(ScriptMsg::MediaSessionEvent(pipeline_id, event));
     }
 
+    /// The playback state that the page declared wins over the one of the media, unless the
+    /// media went away.
+    /// https://w3c.github.io/mediasession/#actual-playback-state
+    fn actual_playback_state(
+        &self,
+        media_state: EmbedderMediaSessionPlaybackState,
+    ) -> EmbedderMediaSessionPlaybackState {
+        if let EmbedderMediaSessionPlaybackState::None_ = media_state {
+            return media_state;
+        }
+        match *self.playback_state.borrow() {
+            MediaSessionPlaybackState::None => media_state,
+            MediaSessionPlaybackState::Paused => EmbedderMediaSessionPlaybackState::Paused,
+            MediaSessionPlaybackState::Playing => EmbedderMediaSessionPlaybackState::Playing,
+        }
+    }
+
     pub fn update_title(&self, title: String) {
         let mut metadata = self.metadata.borrow_mut();
         if let Some(ref mut metadata) = *metadata {
@@ -120,50 +184,57 @@ impl MediaSession {
             metadata.as_ref().unwrap().clone(),
         ));
     }
+
+    /// Sends the metadata of the session to the embedder again, after it changed.
+    /// https://w3c.github.io/mediasession/#update-metadata-algorithm
+    pub fn update_metadata(&self, metadata: &MediaMetadata) {
+        // Metadata that was replaced since no longer describes the session.
+        if self.metadata_object.get().as_deref() != Some(metadata) {
+            return;
+        }
+
+        let global = self.global();
+        let window = global.as_window();
+        let title = if metadata.Title().is_empty() {
+            window.get_url().into_string()
+        } else {
+            metadata.Title().into()
+        };
+        let metadata = EmbedderMediaMetadata {
+            title,
+            artist: metadata.Artist().into(),
+            album: metadata.Album().into(),
+            artwork: metadata.artwork(),
+        };
+
+        *self.metadata.borrow_mut() = Some(metadata.clone());
+        self.send_event(MediaSessionEvent::SetMetadata(metadata));
+    }
 }
 
 impl MediaSessionMethods for MediaSession {
     /// https://w3c.github.io/mediasession/#dom-mediasession-metadata
     fn GetMetadata(&self) -> Option<DomRoot<MediaMetadata>> {
-        if let Some(ref metadata) = *self.metadata.borrow() {
-            let mut init = MediaMetadataInit::empty();
-            init.title = DOMString::from_string(metadata.title.clone());
-            init.artist = DOMString::from_string(metadata.artist.clone());
-            init.album = DOMString::from_string(metadata.album.clone());
-            let global = self.global();
-            Some(MediaMetadata::new(&global.as_window(), &init))
-        } else {
-            None
-        }
+        self.metadata_object.get()
     }
 
     /// https://w3c.github.io/mediasession/#dom-mediasession-metadata
     fn SetMetadata(&self, metadata: Option<&MediaMetadata>) {
-        if let Some(ref metadata) = metadata {
-            metadata.set_session(self);
-        }
+        self.metadata_object.set(metadata);
 
-        let global = self.global();
-        let window = global.as_window();
-        let _metadata = match metadata {
-            Some(m) => {
-                let title = if m.Title().is_empty() {
-                    window.get_url().into_string()
-                } else {
-                    m.Title().into()
-                };
-                EmbedderMediaMetadata {
-                    title,
-                    artist: m.Artist().into(),
-                    album: m.Album().into(),
-                }
+        match metadata {
+            Some(metadata) => {
+                metadata.set_session(self);
+                self.update_metadata(metadata);
             },
-            None => EmbedderMediaMetadata::new(window.get_url().into_string()),
-        };
-
-        *self.metadata.borrow_mut() = Some(_metadata.clone());
-
-        self.send_event(MediaSessionEvent::SetMetadata(_metadata));
+            None => {
+                let global = self.global();
+                let window = global.as_window();
+                let metadata = EmbedderMediaMetadata::new(window.get_url().into_string());
+                *self.metadata.borrow_mut() = Some(metadata.clone());
+                self.send_event(MediaSessionEvent::SetMetadata(metadata));
+            },
+        }
     }
 
     /// https://w3c.github.io/mediasession/#dom-mediasession-playbackstate
@@ -174,6 +245,18 @@ impl MediaSessionMethods for MediaSession {
     /// https://w3c.github.io/mediasession/#dom-mediasession-playbackstate
     fn SetPlaybackState(&self, state: MediaSessionPlaybackState) {
         *self.playback_state.borrow_mut() = state;
+
+        // Without a declared state, the controls follow the media again.
+        let state = match state {
+            MediaSessionPlaybackState::None => match self.media_instance.get() {
+                Some(media) if media.Paused() => EmbedderMediaSessionPlaybackState::Paused,
+                Some(_) => EmbedderMediaSessionPlaybackState::Playing,
+                None => EmbedderMediaSessionPlaybackState::None_,
+            },
+            MediaSessionPlaybackState::Paused => EmbedderMediaSessionPlaybackState::Paused,
+            MediaSessionPlaybackState::Playing => EmbedderMediaSessionPlaybackState::Playing,
+        };
+        self.send_event(MediaSessionEvent::PlaybackStateChange(state));
     }
 
     /// https://w3c.github.io/mediasession/#update-action-handler-algorithm
@@ -189,6 +272,9 @@ impl MediaSessionMethods for MediaSession {
                 .insert(action.into(), handler.clone()),
             None => self.action_handlers.borrow_mut().remove(&action.into()),
         };
+
+        let actions = self.action_handlers.borrow().keys().cloned().collect();
+        self.send_event(MediaSessionEvent::SetActionHandlers(actions));
     }
 
     /// https://w3c.github.io/mediasession/#dom-mediasession-setpositionstate
@@ -263,3 +349,19 @@ impl From<MediaSessionAction> for MediaSessionActionType {
         }
     }
 }
+
+impl From<MediaSessionActionType> for MediaSessionAction {
+    fn from(action: MediaSessionActionType) -> MediaSessionAction {
+        match action {
+            MediaSessionActionType::Play => MediaSessionAction::Play,
+            MediaSessionActionType::Pause => MediaSessionAction::Pause,
+            MediaSessionActionType::SeekBackward => MediaSessionAction::Seekbackward,
+            MediaSessionActionType::SeekForward => MediaSessionAction::Seekforward,
+            MediaSessionActionType::PreviousTrack => MediaSessionAction::Previoustrack,
+            MediaSessionActionType::NextTrack => MediaSessionAction::Nexttrack,
+            MediaSessionActionType::SkipAd => MediaSessionAction::Skipad,
+            MediaSessionActionType::Stop => MediaSessionAction::Stop,
+            MediaSessionActionType::SeekTo => MediaSessionAction::Seekto,
+        }
+    }
+}
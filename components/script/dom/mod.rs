@@ -209,10 +209,13 @@ pub mod types {
     include!(concat!(env!("OUT_DIR"), "/InterfaceTypes.rs"));
 }
 
+pub mod abortcontroller;
+pub mod abortsignal;
 pub mod abstractworker;
 pub mod abstractworkerglobalscope;
 pub mod activation;
 pub mod analysernode;
+pub mod animationtimeline;
 pub mod attr;
 pub mod audiobuffer;
 pub mod audiobuffersourcenode;
@@ -224,7 +227,11 @@ pub mod audioparam;
 pub mod audioscheduledsourcenode;
 pub mod audiotrack;
 pub mod audiotracklist;
+pub mod authenticatorassertionresponse;
+pub mod authenticatorattestationresponse;
+pub mod authenticatorresponse;
 pub mod baseaudiocontext;
+pub mod batterymanager;
 pub mod beforeunloadevent;
 pub mod bindings;
 pub mod biquadfilternode;
@@ -243,6 +250,7 @@ pub mod broadcastchannel;
 pub mod canvasgradient;
 pub mod canvaspattern;
 pub mod canvasrenderingcontext2d;
+pub mod caretposition;
 pub mod cdatasection;
 pub mod channelmergernode;
 pub mod channelsplitternode;
@@ -254,7 +262,10 @@ pub mod compositionevent;
 pub mod console;
 pub mod constantsourcenode;
 mod create;
+pub mod credential;
+pub mod credentialscontainer;
 pub mod crypto;
+pub mod cryptokey;
 pub mod css;
 pub mod cssconditionrule;
 pub mod cssfontfacerule;
@@ -262,8 +273,18 @@ pub mod cssgroupingrule;
 pub mod cssimportrule;
 pub mod csskeyframerule;
 pub mod csskeyframesrule;
+pub mod csskeywordvalue;
+pub mod cssmathinvert;
+pub mod cssmathmax;
+pub mod cssmathmin;
+pub mod cssmathnegate;
+pub mod cssmathproduct;
+pub mod cssmathsum;
+pub mod cssmathvalue;
 pub mod cssmediarule;
 pub mod cssnamespacerule;
+pub mod cssnumericarray;
+pub mod cssnumericvalue;
 pub mod cssrule;
 pub mod cssrulelist;
 pub mod cssstyledeclaration;
@@ -271,10 +292,15 @@ pub mod cssstylerule;
 pub mod cssstylesheet;
 pub mod cssstylevalue;
 pub mod csssupportsrule;
+pub mod cssunitvalue;
 pub mod cssviewportrule;
 pub mod customelementregistry;
 pub mod customevent;
 pub mod dedicatedworkerglobalscope;
+pub mod devicemotionevent;
+pub mod devicemotioneventacceleration;
+pub mod devicemotioneventrotationrate;
+pub mod deviceorientationevent;
 pub mod dissimilaroriginlocation;
 pub mod dissimilaroriginwindow;
 pub mod document;
@@ -307,6 +333,10 @@ pub mod file;
 pub mod filelist;
 pub mod filereader;
 pub mod filereadersync;
+pub mod filesystemdirectoryhandle;
+pub mod filesystemfilehandle;
+pub mod filesystemhandle;
+pub mod filesystemsyncaccesshandle;
 pub mod focusevent;
 pub mod formdata;
 pub mod formdataevent;
@@ -316,6 +346,10 @@ pub mod gamepadbutton;
 pub mod gamepadbuttonlist;
 pub mod gamepadevent;
 pub mod gamepadlist;
+pub mod geolocation;
+pub mod geolocationcoordinates;
+pub mod geolocationposition;
+pub mod geolocationpositionerror;
 pub mod globalscope;
 pub mod gpu;
 pub mod gpuadapter;
@@ -334,6 +368,8 @@ pub mod gpushadermodule;
 pub mod gpushaderstage;
 pub mod hashchangeevent;
 pub mod headers;
+pub mod highlight;
+pub mod highlightregistry;
 pub mod history;
 pub mod htmlanchorelement;
 pub mod htmlareaelement;
@@ -408,10 +444,18 @@ pub mod htmlulistelement;
 pub mod htmlunknownelement;
 pub mod htmlvideoelement;
 pub mod identityhub;
+pub mod idledeadline;
+pub mod idledetector;
 pub mod imagedata;
 pub mod inputevent;
 pub mod keyboardevent;
+pub mod largestcontentfulpaint;
+pub mod layoutshift;
+pub mod layoutshiftattribution;
 pub mod location;
+pub mod lock;
+pub mod lockmanager;
+pub mod mediadeviceinfo;
 pub mod mediadevices;
 pub mod mediaelementaudiosourcenode;
 pub mod mediaerror;
@@ -435,6 +479,7 @@ pub mod namednodemap;
 pub mod navigationpreloadmanager;
 pub mod navigator;
 pub mod navigatorinfo;
+pub mod networkinformation;
 pub mod node;
 pub mod nodeiterator;
 pub mod nodelist;
@@ -450,6 +495,8 @@ pub mod paintworkletglobalscope;
 pub mod pannernode;
 pub mod performance;
 pub mod performanceentry;
+pub mod performanceeventtiming;
+pub mod performancelongtasktiming;
 pub mod performancemark;
 pub mod performancemeasure;
 pub mod performancenavigation;
@@ -460,6 +507,8 @@ pub mod performancepainttiming;
 pub mod performanceresourcetiming;
 pub mod permissions;
 pub mod permissionstatus;
+pub mod pictureinpictureevent;
+pub mod pictureinpicturewindow;
 pub mod plugin;
 pub mod pluginarray;
 pub mod popstateevent;
@@ -468,17 +517,23 @@ pub mod progressevent;
 pub mod promise;
 pub mod promisenativehandler;
 pub mod promiserejectionevent;
+pub mod publickeycredential;
 pub mod radionodelist;
 pub mod range;
 pub mod raredata;
 pub mod request;
 pub mod response;
+pub mod rtcdatachannel;
+pub mod rtcdatachannelevent;
 pub mod rtcicecandidate;
 pub mod rtcpeerconnection;
 pub mod rtcpeerconnectioniceevent;
 pub mod rtcsessiondescription;
+pub mod rtcstatsreport;
 pub mod rtctrackevent;
+pub mod scheduler;
 pub mod screen;
+pub mod scrolltimeline;
 pub mod selection;
 pub mod serviceworker;
 pub mod serviceworkercontainer;
@@ -486,16 +541,28 @@ pub mod serviceworkerglobalscope;
 pub mod serviceworkerregistration;
 pub mod servoparser;
 pub mod shadowroot;
+pub mod speechsynthesis;
+pub mod speechsynthesiserrorevent;
+pub mod speechsynthesisevent;
+pub mod speechsynthesisutterance;
+pub mod speechsynthesisvoice;
 pub mod stereopannernode;
 pub mod storage;
 pub mod storageevent;
+pub mod storagemanager;
+pub mod stylepropertymap;
 pub mod stylepropertymapreadonly;
 pub mod stylesheet;
 pub mod stylesheetlist;
 pub mod submitevent;
+pub mod subtlecrypto;
 pub mod svgelement;
 pub mod svggraphicselement;
 pub mod svgsvgelement;
+pub mod taskattributiontiming;
+pub mod taskcontroller;
+pub mod taskprioritychangeevent;
+pub mod tasksignal;
 pub mod testbinding;
 pub mod testbindingiterable;
 pub mod testbindingpairiterable;
@@ -523,6 +590,7 @@ pub mod uievent;
 pub mod url;
 pub mod urlhelper;
 pub mod urlsearchparams;
+pub mod useractivation;
 pub mod userscripts;
 pub mod validation;
 pub mod validitystate;
@@ -530,7 +598,9 @@ pub mod values;
 pub mod vertexarrayobject;
 pub mod videotrack;
 pub mod videotracklist;
+pub mod viewtimeline;
 pub mod virtualmethods;
+pub mod visualviewport;
 pub mod vrdisplay;
 pub mod vrdisplaycapabilities;
 pub mod vrdisplayevent;
@@ -541,6 +611,8 @@ pub mod vrpose;
 pub mod vrstageparameters;
 pub mod vttcue;
 pub mod vttregion;
+pub mod wakelock;
+pub mod wakelocksentinel;
 pub mod webgl_extensions;
 pub use self::webgl_extensions::ext::*;
 pub mod webgl2renderingcontext;
@@ -578,6 +650,10 @@ pub mod xmlhttprequest;
 pub mod xmlhttprequesteventtarget;
 pub mod xmlhttprequestupload;
 pub mod xmlserializer;
+pub mod xpathevaluator;
+pub mod xpathexpression;
+pub mod xpathresult;
+pub mod xsltprocessor;
 pub mod xrframe;
 pub mod xrinputsource;
 pub mod xrinputsourcearray;
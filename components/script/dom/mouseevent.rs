@@ -35,6 +35,8 @@ pub struct MouseEvent {
     y: Cell<i32>,
     offset_x: Cell<i32>,
     offset_y: Cell<i32>,
+    movement_x: Cell<i32>,
+    movement_y: Cell<i32>,
     ctrl_key: Cell<bool>,
     shift_key: Cell<bool>,
     alt_key: Cell<bool>,
@@ -59,6 +61,8 @@ impl MouseEvent {
             y: Cell::new(0),
             offset_x: Cell::new(0),
             offset_y: Cell::new(0),
+            movement_x: Cell::new(0),
+            movement_y: Cell::new(0),
             ctrl_key: Cell::new(false),
             shift_key: Cell::new(false),
             alt_key: Cell::new(false),
@@ -148,12 +152,19 @@ impl MouseEvent {
             init.relatedTarget.as_deref(),
             None,
         );
+        event.set_movement(init.movementX, init.movementY);
         Ok(event)
     }
 
     pub fn point_in_target(&self) -> Option<Point2D<f32>> {
         self.point_in_target.get()
     }
+
+    /// Sets the change in position of the pointer since the previous `mousemove` event.
+    pub fn set_movement(&self, movement_x: i32, movement_y: i32) {
+        self.movement_x.set(movement_x);
+        self.movement_y.set(movement_y);
+    }
 }
 
 impl MouseEventMethods for MouseEvent {
@@ -249,6 +260,16 @@ impl MouseEventMethods for MouseEvent {
         }
     }
 
+    // https://w3c.github.io/pointerlock/#dom-mouseevent-movementx
+    fn MovementX(&self) -> i32 {
+        self.movement_x.get()
+    }
+
+    // https://w3c.github.io/pointerlock/#dom-mouseevent-movementy
+    fn MovementY(&self) -> i32 {
+        self.movement_y.get()
+    }
+
     // https://w3c.github.io/uievents/#widl-MouseEvent-ctrlKey
     fn CtrlKey(&self) -> bool {
         self.ctrl_key.get()
@@ -2,27 +2,97 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use crate::dom::batterymanager::BatteryManager;
+use crate::dom::bindings::cell::DomRefCell;
 use crate::dom::bindings::codegen::Bindings::NavigatorBinding::NavigatorMethods;
-use crate::dom::bindings::error::Error;
+use crate::dom::bindings::codegen::Bindings::WebShareBinding::ShareData;
+use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
+use crate::dom::bindings::codegen::Bindings::XMLHttpRequestBinding::BodyInit;
+use crate::dom::bindings::error::{Error, ErrorResult, Fallible};
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::refcounted::Trusted;
 use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
 use crate::dom::bindings::root::{DomRoot, MutNullableDom};
-use crate::dom::bindings::str::DOMString;
+use crate::dom::bindings::str::{DOMString, USVString};
+use crate::dom::blob::Blob;
 use crate::dom::bluetooth::Bluetooth;
+use crate::dom::credentialscontainer::CredentialsContainer;
+use crate::dom::document::Document;
 use crate::dom::gamepadlist::GamepadList;
+use crate::dom::geolocation::Geolocation;
+use crate::dom::globalscope::GlobalScope;
 use crate::dom::gpu::GPU;
+use crate::dom::lockmanager::LockManager;
 use crate::dom::mediadevices::MediaDevices;
 use crate::dom::mediasession::MediaSession;
 use crate::dom::mimetypearray::MimeTypeArray;
 use crate::dom::navigatorinfo;
+use crate::dom::networkinformation::NetworkInformation;
+use crate::dom::performanceresourcetiming::InitiatorType;
 use crate::dom::permissions::Permissions;
 use crate::dom::pluginarray::PluginArray;
 use crate::dom::promise::Promise;
 use crate::dom::serviceworkercontainer::ServiceWorkerContainer;
+use crate::dom::storagemanager::StorageManager;
+use crate::dom::useractivation::UserActivation;
+use crate::dom::wakelock::WakeLock;
 use crate::dom::window::Window;
+use crate::dom::xmlhttprequest::Extractable;
 use crate::dom::xrsystem::XRSystem;
+use crate::network_listener::{self, NetworkListener, PreInvoke, ResourceTimingListener};
 use crate::realms::InRealm;
+use crate::task_source::{TaskSource, TaskSourceName};
 use dom_struct::dom_struct;
+use embedder_traits::{BatteryStatus, EmbedderMsg, GeolocationPosition};
+use embedder_traits::{NetworkInformation as ConnectionState, ShareData as EmbedderShareData};
+use embedder_traits::{ShareResult, SharedFile};
+use http::header::{HeaderMap, HeaderValue, CONTENT_TYPE};
+use hyper::Method;
+use ipc_channel::router::ROUTER;
+use net_traits::request::{is_cors_safelisted_request_header, CredentialsMode};
+use net_traits::request::{Destination, RequestBuilder, RequestMode};
+use net_traits::{CoreResourceMsg, FetchChannels, FetchMetadata, FetchResponseListener};
+use net_traits::{NetworkError, ResourceFetchTiming, ResourceTimingType};
+use profile_traits::ipc;
+use script_traits::ScriptMsg;
+use servo_url::ServoUrl;
+use std::cell::Cell;
 use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+
+/// The most bytes that the bodies of the beacons of a page can add up to while they are in
+/// flight, which is the quota of keepalive requests.
+/// <https://w3c.github.io/beacon/#sec-processing-model>
+const BEACON_QUOTA: u64 = 64 * 1024;
+
+/// The schemes other than the `web+` ones that sites may register protocol handlers for.
+/// <https://html.spec.whatwg.org/multipage/#safelisted-scheme>
+const SAFELISTED_SCHEMES: &[&str] = &[
+    "bitcoin",
+    "ftp",
+    "ftps",
+    "geo",
+    "im",
+    "irc",
+    "ircs",
+    "magnet",
+    "mailto",
+    "matrix",
+    "mms",
+    "news",
+    "nntp",
+    "openpgp4fpr",
+    "sftp",
+    "sip",
+    "sms",
+    "smsto",
+    "ssh",
+    "tel",
+    "urn",
+    "webcal",
+    "wtai",
+    "xmpp",
+];
 
 #[dom_struct]
 pub struct Navigator {
@@ -37,6 +107,22 @@ pub struct Navigator {
     permissions: MutNullableDom<Permissions>,
     mediasession: MutNullableDom<MediaSession>,
     gpu: MutNullableDom<GPU>,
+    wake_lock: MutNullableDom<WakeLock>,
+    /// <https://w3c.github.io/battery/#dfn-battery-promise>
+    #[ignore_malloc_size_of = "Rc"]
+    battery_promise: DomRefCell<Option<Rc<Promise>>>,
+    battery_manager: MutNullableDom<BatteryManager>,
+    connection: MutNullableDom<NetworkInformation>,
+    geolocation: MutNullableDom<Geolocation>,
+    /// <https://w3c.github.io/web-share/#dfn-sharepromise>
+    #[ignore_malloc_size_of = "Rc"]
+    share_promise: DomRefCell<Option<Rc<Promise>>>,
+    /// The bytes of the bodies of the beacons that were not sent yet.
+    beacon_bytes: Cell<u64>,
+    storage: MutNullableDom<StorageManager>,
+    credentials: MutNullableDom<CredentialsContainer>,
+    locks: MutNullableDom<LockManager>,
+    user_activation: MutNullableDom<UserActivation>,
 }
 
 impl Navigator {
@@ -53,12 +139,131 @@ impl Navigator {
             permissions: Default::default(),
             mediasession: Default::default(),
             gpu: Default::default(),
+            wake_lock: Default::default(),
+            battery_promise: Default::default(),
+            battery_manager: Default::default(),
+            connection: Default::default(),
+            geolocation: Default::default(),
+            share_promise: Default::default(),
+            beacon_bytes: Cell::new(0),
+            storage: Default::default(),
+            credentials: Default::default(),
+            locks: Default::default(),
+            user_activation: Default::default(),
         }
     }
 
     pub fn new(window: &Window) -> DomRoot<Navigator> {
         reflect_dom_object(Box::new(Navigator::new_inherited()), window)
     }
+
+    /// Updates the battery and connection objects that the page obtained, which fire events
+    /// for the attributes that changed.
+    pub fn device_status_changed(
+        &self,
+        battery_status: BatteryStatus,
+        network_information: ConnectionState,
+    ) {
+        if let Some(battery_manager) = self.battery_manager.get() {
+            battery_manager.update(battery_status);
+        }
+        if let Some(connection) = self.connection.get() {
+            connection.update(network_information);
+        }
+    }
+
+    /// Reports a position acquired by the embedder to the page, if it asked for positions.
+    pub fn position_changed(&self, position: Result<GeolocationPosition, String>) {
+        if let Some(geolocation) = self.geolocation.get() {
+            geolocation.position_changed(position);
+        }
+    }
+
+    /// Settles the promise of `share()` with the outcome of showing the share sheet.
+    fn share_completed(&self, result: ShareResult) {
+        let promise = match self.share_promise.borrow_mut().take() {
+            Some(promise) => promise,
+            None => return,
+        };
+        match result {
+            ShareResult::Shared => promise.resolve_native(&()),
+            ShareResult::Canceled => promise.reject_error(Error::Abort),
+            ShareResult::Failed => promise.reject_error(Error::Data),
+        }
+    }
+
+    /// Releases the bytes of the body of a beacon from the quota, once it was sent.
+    fn beacon_sent(&self, bytes: u64) {
+        self.beacon_bytes
+            .set(self.beacon_bytes.get().saturating_sub(bytes));
+    }
+}
+
+/// Checks that there is something to share, and returns the URL to share, parsed relative to
+/// the base URL of the document, if any.
+/// <https://w3c.github.io/web-share/#dfn-validate-share-data>
+fn validate_share_data(data: &ShareData, document: &Document) -> Result<Option<ServoUrl>, ()> {
+    // Steps 1-3.
+    let title_text_or_url = data.title.is_some() || data.text.is_some() || data.url.is_some();
+    let has_files = data.files.as_ref().map_or(false, |files| !files.is_empty());
+    if !title_text_or_url && !has_files {
+        return Err(());
+    }
+
+    // Step 4.
+    let url = match data.url {
+        Some(ref url) => match document.base_url().join(&url.0) {
+            Ok(url) => url,
+            Err(_) => return Err(()),
+        },
+        None => return Ok(None),
+    };
+    match url.scheme() {
+        "about" | "blob" | "data" | "file" | "ws" | "wss" => Err(()),
+        _ => Ok(Some(url)),
+    }
+}
+
+/// Checks that the document may register or unregister a handler for the scheme, and returns
+/// the scheme in lowercase and the URL of the handler, parsed relative to the base URL of the
+/// document.
+/// <https://html.spec.whatwg.org/multipage/#normalize-protocol-handler-parameters>
+fn normalize_protocol_handler_parameters(
+    scheme: DOMString,
+    url: USVString,
+    document: &Document,
+) -> Fallible<(String, ServoUrl)> {
+    // Step 1.
+    let scheme = scheme.to_ascii_lowercase();
+
+    // Step 2.
+    let is_web_scheme = scheme.starts_with("web+") &&
+        scheme.len() > "web+".len() &&
+        scheme["web+".len()..]
+            .bytes()
+            .all(|byte| byte.is_ascii_lowercase());
+    if !is_web_scheme && !SAFELISTED_SCHEMES.contains(&&*scheme) {
+        return Err(Error::Security);
+    }
+
+    // Step 3.
+    if !url.0.contains("%s") {
+        return Err(Error::Syntax);
+    }
+
+    // Step 4.
+    let url = document
+        .base_url()
+        .join(&url.0)
+        .map_err(|_| Error::Syntax)?;
+
+    // Step 5.
+    if !matches!(url.scheme(), "http" | "https") || !url.origin().same_origin(document.origin()) {
+        return Err(Error::Security);
+    }
+
+    // Step 6.
+    Ok((scheme, url))
 }
 
 impl NavigatorMethods for Navigator {
@@ -149,6 +354,33 @@ impl NavigatorMethods for Navigator {
         true
     }
 
+    // https://html.spec.whatwg.org/multipage/#dom-navigator-registerprotocolhandler
+    fn RegisterProtocolHandler(&self, scheme: DOMString, url: USVString) -> ErrorResult {
+        let global = self.global();
+        let document = global.as_window().Document();
+        let (scheme, url) = normalize_protocol_handler_parameters(scheme, url, &document)?;
+        // The user is asked whether to use the handler.
+        let top_level_browsing_context_id = global
+            .as_window()
+            .window_proxy()
+            .top_level_browsing_context_id();
+        let msg =
+            CoreResourceMsg::RegisterProtocolHandler(scheme, url, top_level_browsing_context_id);
+        let _ = global.core_resource_thread().send(msg);
+        Ok(())
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-navigator-unregisterprotocolhandler
+    fn UnregisterProtocolHandler(&self, scheme: DOMString, url: USVString) -> ErrorResult {
+        let global = self.global();
+        let document = global.as_window().Document();
+        let (scheme, url) = normalize_protocol_handler_parameters(scheme, url, &document)?;
+        let _ = global
+            .core_resource_thread()
+            .send(CoreResourceMsg::UnregisterProtocolHandler(scheme, url));
+        Ok(())
+    }
+
     // https://www.w3.org/TR/gamepad/#navigator-interface-extension
     fn GetGamepads(&self) -> DomRoot<GamepadList> {
         let root = self
@@ -209,4 +441,274 @@ impl NavigatorMethods for Navigator {
     fn Gpu(&self) -> DomRoot<GPU> {
         self.gpu.or_init(|| GPU::new(&self.global()))
     }
+
+    // https://w3c.github.io/screen-wake-lock/#dom-navigator-wakelock
+    fn WakeLock(&self) -> DomRoot<WakeLock> {
+        self.wake_lock.or_init(|| WakeLock::new(&self.global()))
+    }
+
+    // https://w3c.github.io/battery/#dom-navigator-getbattery
+    fn GetBattery(&self, comp: InRealm) -> Rc<Promise> {
+        // Step 1
+        if let Some(ref promise) = *self.battery_promise.borrow() {
+            return promise.clone();
+        }
+
+        // Steps 2-3
+        let global = self.global();
+        let promise = Promise::new_in_current_realm(&global, comp);
+        *self.battery_promise.borrow_mut() = Some(promise.clone());
+
+        // Steps 4-5
+        let (sender, receiver) = ipc::channel(global.time_profiler_chan().clone()).unwrap();
+        let _ = global
+            .script_to_constellation_chan()
+            .send(ScriptMsg::GetBatteryStatus(sender));
+        let status = receiver.recv().unwrap_or_default();
+        let battery_manager = BatteryManager::new(&global, status);
+        self.battery_manager.set(Some(&battery_manager));
+        promise.resolve_native(&battery_manager);
+        promise
+    }
+
+    // https://w3c.github.io/geolocation-api/#navigator_interface
+    fn Geolocation(&self) -> DomRoot<Geolocation> {
+        self.geolocation
+            .or_init(|| Geolocation::new(&self.global()))
+    }
+
+    // https://w3c.github.io/web-share/#share-method
+    fn Share(&self, data: &ShareData, comp: InRealm) -> Rc<Promise> {
+        let global = self.global();
+        let window = global.as_window();
+        let promise = Promise::new_in_current_realm(&global, comp);
+
+        // Steps 2-4.
+        if !window.Document().is_fully_active() || self.share_promise.borrow().is_some() {
+            promise.reject_error(Error::InvalidState);
+            return promise;
+        }
+
+        // Steps 5-6.
+        if !window.has_transient_activation() {
+            promise.reject_error(Error::NotAllowed);
+            return promise;
+        }
+        window.consume_user_activation();
+
+        // Steps 7-8.
+        let url = match validate_share_data(data, &window.Document()) {
+            Ok(url) => url,
+            Err(()) => {
+                promise.reject_error(Error::Type("Invalid share data".to_owned()));
+                return promise;
+            },
+        };
+        let mut files = vec![];
+        for file in data.files.iter().flatten() {
+            let blob = file.upcast::<Blob>();
+            let bytes = match blob.get_bytes() {
+                Ok(bytes) => bytes,
+                Err(()) => {
+                    promise.reject_error(Error::Data);
+                    return promise;
+                },
+            };
+            files.push(SharedFile {
+                name: file.name().to_string(),
+                mime_type: blob.type_string(),
+                bytes,
+            });
+        }
+        let share_data = EmbedderShareData {
+            title: data.title.as_ref().map(|title| title.0.clone()),
+            text: data.text.as_ref().map(|text| text.0.clone()),
+            url,
+            files,
+        };
+
+        // Steps 9-10.
+        *self.share_promise.borrow_mut() = Some(promise.clone());
+        let (sender, receiver) = ipc::channel(global.time_profiler_chan().clone()).unwrap();
+        let this = Trusted::new(self);
+        let (task_source, canceller) = window
+            .task_manager()
+            .dom_manipulation_task_source_with_canceller();
+        ROUTER.add_route(
+            receiver.to_opaque(),
+            Box::new(move |message| {
+                let this = this.clone();
+                let result = message.to().unwrap_or(ShareResult::Failed);
+                let _ = task_source.queue_with_canceller(
+                    task!(share_completed: move || {
+                        this.root().share_completed(result);
+                    }),
+                    &canceller,
+                );
+            }),
+        );
+        window.send_to_embedder(EmbedderMsg::Share(share_data, sender));
+        promise
+    }
+
+    // https://w3c.github.io/web-share/#canshare-method
+    fn CanShare(&self, data: &ShareData) -> bool {
+        let document = self.global().as_window().Document();
+        document.is_fully_active() && validate_share_data(data, &document).is_ok()
+    }
+
+    // https://w3c.github.io/beacon/#sec-sendBeacon-method
+    fn SendBeacon(&self, url: USVString, data: Option<BodyInit>) -> Fallible<bool> {
+        let global = self.global();
+        let document = global.as_window().Document();
+
+        // Steps 1-2.
+        let url = match document.base_url().join(&url.0) {
+            Ok(url) => url,
+            Err(_) => return Err(Error::Type("Invalid beacon URL".to_owned())),
+        };
+        if url.scheme() != "http" && url.scheme() != "https" {
+            return Err(Error::Type(
+                "Beacons can only be sent over HTTP(S)".to_owned(),
+            ));
+        }
+
+        // Steps 3-6.
+        let mut headers = HeaderMap::new();
+        let mut mode = RequestMode::NoCors;
+        let body = data.map(|data| {
+            let (bytes, content_type) = data.extract();
+            if let Some(content_type) = content_type {
+                if !is_cors_safelisted_request_header(&"content-type", &content_type.as_bytes()) {
+                    mode = RequestMode::CorsMode;
+                }
+                if let Ok(value) = HeaderValue::from_str(&content_type) {
+                    headers.insert(CONTENT_TYPE, value);
+                }
+            }
+            bytes
+        });
+        let bytes = body.as_ref().map_or(0, |body| body.len() as u64);
+        if self.beacon_bytes.get() + bytes > BEACON_QUOTA {
+            return Ok(false);
+        }
+        self.beacon_bytes.set(self.beacon_bytes.get() + bytes);
+
+        // Step 7.
+        let request = RequestBuilder::new(url.clone())
+            .method(Method::POST)
+            .headers(headers)
+            .body(body)
+            .keep_alive(true)
+            .destination(Destination::None)
+            .mode(mode)
+            .credentials_mode(CredentialsMode::Include)
+            .origin(global.origin().immutable().clone())
+            .referrer_policy(document.get_referrer_policy())
+            .pipeline_id(Some(global.pipeline_id()));
+        let context = BeaconContext {
+            navigator: Trusted::new(self),
+            url,
+            bytes,
+            resource_timing: ResourceFetchTiming::new(ResourceTimingType::Resource),
+        };
+        let listener = NetworkListener {
+            context: Arc::new(Mutex::new(context)),
+            task_source: global.networking_task_source(),
+            canceller: Some(global.task_canceller(TaskSourceName::Networking)),
+        };
+        let (action_sender, action_receiver) = ipc_channel::ipc::channel().unwrap();
+        ROUTER.add_route(
+            action_receiver.to_opaque(),
+            Box::new(move |message| {
+                listener.notify_fetch(message.to().unwrap());
+            }),
+        );
+        let _ = global.core_resource_thread().send(CoreResourceMsg::Fetch(
+            request,
+            FetchChannels::ResponseMsg(action_sender, None),
+        ));
+        Ok(true)
+    }
+
+    // https://wicg.github.io/netinfo/#connection-attribute
+    fn Connection(&self) -> DomRoot<NetworkInformation> {
+        self.connection.or_init(|| {
+            let global = self.global();
+            let (sender, receiver) = ipc::channel(global.time_profiler_chan().clone()).unwrap();
+            let _ = global
+                .script_to_constellation_chan()
+                .send(ScriptMsg::GetNetworkInformation(sender));
+            NetworkInformation::new(&global, receiver.recv().unwrap_or_default())
+        })
+    }
+
+    // https://storage.spec.whatwg.org/#dom-navigatorstorage-storage
+    fn Storage(&self) -> DomRoot<StorageManager> {
+        self.storage.or_init(|| StorageManager::new(&self.global()))
+    }
+
+    // https://w3c.github.io/webappsec-credential-management/#dom-navigator-credentials
+    fn Credentials(&self) -> DomRoot<CredentialsContainer> {
+        self.credentials
+            .or_init(|| CredentialsContainer::new(&self.global()))
+    }
+
+    // https://w3c.github.io/web-locks/#dom-navigatorlocks-locks
+    fn Locks(&self) -> DomRoot<LockManager> {
+        self.locks.or_init(|| LockManager::new(&self.global()))
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-navigator-useractivation
+    fn UserActivation(&self) -> DomRoot<UserActivation> {
+        self.user_activation
+            .or_init(|| UserActivation::new(self.global().as_window()))
+    }
+}
+
+/// The fetch of a beacon, whose response is ignored.
+struct BeaconContext {
+    navigator: Trusted<Navigator>,
+    url: ServoUrl,
+    /// The bytes of the body, which count against the quota until the beacon was sent.
+    bytes: u64,
+    resource_timing: ResourceFetchTiming,
+}
+
+impl PreInvoke for BeaconContext {}
+
+impl FetchResponseListener for BeaconContext {
+    fn process_request_body(&mut self) {}
+
+    fn process_request_eof(&mut self) {}
+
+    fn process_response(&mut self, _: Result<FetchMetadata, NetworkError>) {}
+
+    fn process_response_chunk(&mut self, _: Vec<u8>) {}
+
+    fn process_response_eof(&mut self, _: Result<ResourceFetchTiming, NetworkError>) {
+        self.navigator.root().beacon_sent(self.bytes);
+    }
+
+    fn resource_timing_mut(&mut self) -> &mut ResourceFetchTiming {
+        &mut self.resource_timing
+    }
+
+    fn resource_timing(&self) -> &ResourceFetchTiming {
+        &self.resource_timing
+    }
+
+    fn submit_resource_timing(&mut self) {
+        network_listener::submit_timing(self)
+    }
+}
+
+impl ResourceTimingListener for BeaconContext {
+    fn resource_timing_information(&self) -> (InitiatorType, ServoUrl) {
+        (InitiatorType::Beacon, self.url.clone())
+    }
+
+    fn resource_timing_global(&self) -> DomRoot<GlobalScope> {
+        self.navigator.root().global()
+    }
 }
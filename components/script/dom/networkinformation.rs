@@ -0,0 +1,93 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::NetworkInformationBinding::NetworkInformationMethods;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::reflector::reflect_dom_object;
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::eventtarget::EventTarget;
+use crate::dom::globalscope::GlobalScope;
+use dom_struct::dom_struct;
+use embedder_traits::{EffectiveConnectionType, NetworkInformation as ConnectionState};
+use std::cell::Cell;
+
+/// The state of the network connection of the device, as reported by the embedder.
+#[dom_struct]
+pub struct NetworkInformation {
+    eventtarget: EventTarget,
+    state: Cell<ConnectionState>,
+}
+
+impl NetworkInformation {
+    fn new_inherited(state: ConnectionState) -> NetworkInformation {
+        NetworkInformation {
+            eventtarget: EventTarget::new_inherited(),
+            state: Cell::new(state),
+        }
+    }
+
+    pub fn new(global: &GlobalScope, state: ConnectionState) -> DomRoot<NetworkInformation> {
+        reflect_dom_object(Box::new(NetworkInformation::new_inherited(state)), global)
+    }
+
+    /// Stores the new state of the connection, and fires `change` if any of the attributes
+    /// changed.
+    /// <https://wicg.github.io/netinfo/#handling-changes-to-the-underlying-connection>
+    pub fn update(&self, state: ConnectionState) {
+        let old_state = self.state.replace(state);
+        if old_state.effective_type != state.effective_type ||
+            round_downlink(old_state.downlink) != round_downlink(state.downlink) ||
+            round_rtt(old_state.rtt) != round_rtt(state.rtt) ||
+            old_state.save_data != state.save_data
+        {
+            self.upcast::<EventTarget>().fire_event(atom!("change"));
+        }
+    }
+}
+
+/// The bandwidth is rounded to multiples of 25 kilobits per second and capped, to limit
+/// fingerprinting.
+/// <https://wicg.github.io/netinfo/#dfn-downlink>
+fn round_downlink(downlink: f64) -> f64 {
+    ((downlink.max(0.).min(10.) * 40.).round()) / 40.
+}
+
+/// The round-trip time is rounded to multiples of 25 milliseconds and capped, to limit
+/// fingerprinting.
+/// <https://wicg.github.io/netinfo/#dfn-rtt>
+fn round_rtt(rtt: u64) -> u64 {
+    ((rtt.min(3000) + 12) / 25) * 25
+}
+
+impl NetworkInformationMethods for NetworkInformation {
+    // https://wicg.github.io/netinfo/#effectivetype-attribute
+    fn EffectiveType(&self) -> DOMString {
+        let effective_type = match self.state.get().effective_type {
+            EffectiveConnectionType::Slow2G => "slow-2g",
+            EffectiveConnectionType::TwoG => "2g",
+            EffectiveConnectionType::ThreeG => "3g",
+            EffectiveConnectionType::FourG => "4g",
+        };
+        DOMString::from(effective_type)
+    }
+
+    // https://wicg.github.io/netinfo/#downlink-attribute
+    fn Downlink(&self) -> f64 {
+        round_downlink(self.state.get().downlink)
+    }
+
+    // https://wicg.github.io/netinfo/#rtt-attribute
+    fn Rtt(&self) -> u64 {
+        round_rtt(self.state.get().rtt)
+    }
+
+    // https://wicg.github.io/netinfo/#savedata-attribute
+    fn SaveData(&self) -> bool {
+        self.state.get().save_data
+    }
+
+    // https://wicg.github.io/netinfo/#onchange-attribute
+    event_handler!(change, GetOnchange, SetOnchange);
+}
@@ -33,6 +33,7 @@ use crate::dom::bindings::xmlname::namespace_from_domstring;
 use crate::dom::characterdata::{CharacterData, LayoutCharacterDataHelpers};
 use crate::dom::cssstylesheet::CSSStyleSheet;
 use crate::dom::customelementregistry::{try_upgrade_element, CallbackReaction};
+use crate::dom::document::LayoutDocumentHelpers;
 use crate::dom::document::{Document, DocumentSource, HasBrowsingContext, IsHTMLDocument};
 use crate::dom::documentfragment::DocumentFragment;
 use crate::dom::documenttype::DocumentType;
@@ -42,7 +43,7 @@ use crate::dom::eventtarget::EventTarget;
 use crate::dom::htmlbodyelement::HTMLBodyElement;
 use crate::dom::htmlcanvaselement::{HTMLCanvasElement, LayoutHTMLCanvasElementHelpers};
 use crate::dom::htmlcollection::HTMLCollection;
-use crate::dom::htmlelement::HTMLElement;
+use crate::dom::htmlelement::{ContentEditableState, HTMLElement};
 use crate::dom::htmliframeelement::{HTMLIFrameElement, HTMLIFrameElementLayoutMethods};
 use crate::dom::htmlimageelement::{HTMLImageElement, LayoutHTMLImageElementHelpers};
 use crate::dom::htmlinputelement::{HTMLInputElement, LayoutHTMLInputElementHelpers};
@@ -732,6 +733,24 @@ impl Node {
         }
     }
 
+    /// Returns the editing host this node is editable within, if any.
+    /// https://html.spec.whatwg.org/multipage/#editing-host
+    pub fn editing_host(&self) -> Option<DomRoot<Element>> {
+        let mut host = None;
+        for ancestor in self.inclusive_ancestors(ShadowIncluding::No) {
+            if let Some(element) = ancestor.downcast::<HTMLElement>() {
+                match element.content_editable_state() {
+                    ContentEditableState::True => {
+                        host = Some(DomRoot::from_ref(element.upcast::<Element>()))
+                    },
+                    ContentEditableState::False => break,
+                    ContentEditableState::Inherit => {},
+                }
+            }
+        }
+        host
+    }
+
     pub fn is_inclusive_ancestor_of(&self, parent: &Node) -> bool {
         self == parent || self.is_ancestor_of(parent)
     }
@@ -1328,6 +1347,8 @@ pub trait LayoutNodeHelpers {
 
     fn text_content(&self) -> String;
     fn selection(&self) -> Option<Range<usize>>;
+    fn highlights(&self) -> Vec<(Atom, Range<usize>)>;
+    fn spelling_errors(&self) -> Vec<Range<usize>>;
     fn image_url(&self) -> Option<ServoUrl>;
     fn image_density(&self) -> Option<f64>;
     fn image_data(&self) -> Option<(Option<StdArc<Image>>, Option<ImageMetadata>)>;
@@ -1483,9 +1504,35 @@ impl LayoutNodeHelpers for LayoutDom<Node> {
             return unsafe { input.selection_for_layout() };
         }
 
+        if let Some(text) = self.downcast::<Text>() {
+            let document = unsafe { self.owner_doc_for_layout() };
+            return unsafe { document.editing_caret_for_layout(text) }
+                .map(|offset| offset..offset)
+                .or_else(|| unsafe { document.find_highlight_for_layout(text) });
+        }
+
         None
     }
 
+    #[allow(unsafe_code)]
+    fn highlights(&self) -> Vec<(Atom, Range<usize>)> {
+        match self.downcast::<Text>() {
+            Some(text) => unsafe {
+                self.owner_doc_for_layout()
+                    .custom_highlights_for_layout(text)
+            },
+            None => vec![],
+        }
+    }
+
+    #[allow(unsafe_code)]
+    fn spelling_errors(&self) -> Vec<Range<usize>> {
+        unsafe {
+            self.owner_doc_for_layout()
+                .spelling_errors_for_layout(*self)
+        }
+    }
+
     #[allow(unsafe_code)]
     fn image_url(&self) -> Option<ServoUrl> {
         unsafe {
@@ -3,32 +3,66 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::ElementBinding::ElementMethods;
+use crate::dom::bindings::codegen::Bindings::HTMLImageElementBinding::HTMLImageElementMethods;
+use crate::dom::bindings::codegen::Bindings::NodeBinding::NodeMethods;
 use crate::dom::bindings::codegen::Bindings::PerformanceBinding::PerformanceEntryList as DOMPerformanceEntryList;
 use crate::dom::bindings::codegen::Bindings::PerformanceBinding::{
-    DOMHighResTimeStamp, PerformanceMethods,
+    DOMHighResTimeStamp, MemoryAttribution, MemoryAttributionContainer, MemoryBreakdownEntry,
+    MemoryMeasurement, PerformanceMarkOptions, PerformanceMeasureOptions, PerformanceMethods,
 };
-use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::codegen::Bindings::PerformanceNavigationTimingBinding::PerformanceNavigationTimingMethods;
+use crate::dom::bindings::codegen::Bindings::PerformanceResourceTimingBinding::PerformanceResourceTimingMethods;
+use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
+use crate::dom::bindings::codegen::UnionTypes::{
+    StringOrDouble, StringOrPerformanceMeasureOptions,
+};
+use crate::dom::bindings::error::{Error, ErrorResult, Fallible};
 use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::num::Finite;
+use crate::dom::bindings::refcounted::{Trusted, TrustedPromise};
 use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
 use crate::dom::bindings::root::DomRoot;
-use crate::dom::bindings::str::DOMString;
+use crate::dom::bindings::str::{DOMString, USVString};
+use crate::dom::bindings::structuredclone;
+use crate::dom::bindings::trace::RootedTraceableBox;
+use crate::dom::domrectreadonly::DOMRectReadOnly;
+use crate::dom::element::Element;
 use crate::dom::eventtarget::EventTarget;
 use crate::dom::globalscope::GlobalScope;
+use crate::dom::htmlimageelement::HTMLImageElement;
+use crate::dom::largestcontentfulpaint::LargestContentfulPaint;
+use crate::dom::layoutshift::LayoutShift;
+use crate::dom::layoutshiftattribution::LayoutShiftAttribution;
+use crate::dom::node::Node;
 use crate::dom::performanceentry::PerformanceEntry;
+use crate::dom::performanceeventtiming::PerformanceEventTiming;
+use crate::dom::performancelongtasktiming::PerformanceLongTaskTiming;
 use crate::dom::performancemark::PerformanceMark;
 use crate::dom::performancemeasure::PerformanceMeasure;
 use crate::dom::performancenavigation::PerformanceNavigation;
 use crate::dom::performancenavigationtiming::PerformanceNavigationTiming;
 use crate::dom::performanceobserver::PerformanceObserver as DOMPerformanceObserver;
+use crate::dom::performanceobserver::DEFAULT_EVENT_TIMING_DURATION_THRESHOLD;
+use crate::dom::performanceresourcetiming::PerformanceResourceTiming;
+use crate::dom::promise::Promise;
 use crate::dom::window::Window;
+use crate::realms::{AlreadyInRealm, InRealm};
+use crate::script_thread::ScriptThread;
+use crate::task_source::TaskSource;
 use dom_struct::dom_struct;
+use js::jsval::{NullValue, UndefinedValue};
+use js::rust::{HandleValue, MutableHandleValue};
 use metrics::ToMs;
 use std::cell::Cell;
 use std::cmp::Ordering;
 use std::collections::VecDeque;
+use std::rc::Rc;
+use webrender_api::units::LayoutRect;
 
-const INVALID_ENTRY_NAMES: &'static [&'static str] = &[
+/// The names of the attributes of `PerformanceTiming`, which can't be used as mark names in
+/// windows.
+pub const INVALID_ENTRY_NAMES: &'static [&'static str] = &[
     "navigationStart",
     "unloadEventStart",
     "unloadEventEnd",
@@ -52,6 +86,28 @@ const INVALID_ENTRY_NAMES: &'static [&'static str] = &[
     "loadEventEnd",
 ];
 
+/// Entry types which are only exposed to performance observers, and not through
+/// `getEntries()` and friends.
+///
+/// <https://w3c.github.io/timing-entrytypes-registry/#registry>
+const OBSERVER_ONLY_ENTRY_TYPES: &'static [&'static str] = &[
+    "event",
+    "largest-contentful-paint",
+    "layout-shift",
+    "longtask",
+];
+
+/// The time after an input during which layout shifts are considered caused by the input.
+///
+/// <https://wicg.github.io/layout-instability/#recent-input-exclusion>
+const RECENT_INPUT_EXCLUSION_MS: f64 = 500.;
+
+/// Event types whose first occurrence is reported as the "first-input" entry.
+///
+/// <https://w3c.github.io/event-timing/#sec-init-event-timing>
+const FIRST_INPUT_EVENT_TYPES: &'static [&'static str] =
+    &["click", "keydown", "mousedown", "pointerdown"];
+
 /// Implementation of a list of PerformanceEntry items shared by the
 /// Performance and PerformanceObserverEntryList interfaces implementations.
 #[derive(JSTraceable, MallocSizeOf)]
@@ -104,18 +160,14 @@ impl PerformanceEntryList {
 
     fn get_last_entry_start_time_with_name_and_type(
         &self,
-        name: DOMString,
+        name: &DOMString,
         entry_type: DOMString,
-    ) -> f64 {
-        match self
-            .entries
+    ) -> Option<f64> {
+        self.entries
             .iter()
             .rev()
             .find(|e| *e.entry_type() == *entry_type && *e.name() == *name)
-        {
-            Some(entry) => entry.start_time(),
-            None => 0.,
-        }
+            .map(|entry| entry.start_time())
     }
 }
 
@@ -148,6 +200,12 @@ pub struct Performance {
     resource_timing_buffer_current_size: Cell<usize>,
     resource_timing_buffer_pending_full_event: Cell<bool>,
     resource_timing_secondary_entries: DomRefCell<VecDeque<DomRoot<PerformanceEntry>>>,
+    /// <https://w3c.github.io/event-timing/#has-dispatched-input-event>
+    has_dispatched_input_event: Cell<bool>,
+    /// The time of the most recent input, or 0 if there has been none.
+    ///
+    /// <https://wicg.github.io/layout-instability/#dom-layoutshift-lastinputtime>
+    last_input_time: Cell<f64>,
 }
 
 impl Performance {
@@ -162,6 +220,8 @@ impl Performance {
             resource_timing_buffer_current_size: Cell::new(0),
             resource_timing_buffer_pending_full_event: Cell::new(false),
             resource_timing_secondary_entries: DomRefCell::new(VecDeque::new()),
+            has_dispatched_input_event: Cell::new(false),
+            last_input_time: Cell::new(0.),
         }
     }
 
@@ -210,8 +270,11 @@ impl Performance {
     ) {
         if buffered {
             let buffer = self.buffer.borrow();
-            let mut new_entries =
-                buffer.get_entries_by_name_and_type(None, Some(entry_type.clone()));
+            let mut new_entries: Vec<_> = buffer
+                .get_entries_by_name_and_type(None, Some(entry_type.clone()))
+                .into_iter()
+                .filter(|entry| observer.should_observe(entry))
+                .collect();
             if new_entries.len() > 0 {
                 let mut obs_entries = observer.entries();
                 obs_entries.append(&mut new_entries);
@@ -271,23 +334,25 @@ impl Performance {
         // Add the performance entry to the list of performance entries that have not
         // been notified to each performance observer owner, filtering the ones it's
         // interested in.
-        for o in self
-            .observers
-            .borrow()
-            .iter()
-            .filter(|o| o.entry_types.contains(entry.entry_type()))
-        {
+        for o in self.observers.borrow().iter().filter(|o| {
+            o.entry_types.contains(entry.entry_type()) && o.observer.should_observe(entry)
+        }) {
             o.observer.queue_entry(entry);
         }
 
         // Step 4.
-        //add the new entry to the buffer.
-        self.buffer
-            .borrow_mut()
-            .entries
-            .push(DomRoot::from_ref(entry));
-
-        let entry_last_index = self.buffer.borrow_mut().entries.len() - 1;
+        // Add the new entry to the buffer. Short event timing entries are only
+        // reported to the observers which asked for them.
+        // https://w3c.github.io/event-timing/#should-add-performanceeventtiming
+        let entry_last_index = if entry.entry_type() == "event" &&
+            entry.duration() < DEFAULT_EVENT_TIMING_DURATION_THRESHOLD
+        {
+            None
+        } else {
+            let mut buffer = self.buffer.borrow_mut();
+            buffer.entries.push(DomRoot::from_ref(entry));
+            Some(buffer.entries.len() - 1)
+        };
 
         // Step 5.
         // If there is already a queued notification task, we just bail out.
@@ -301,7 +366,7 @@ impl Performance {
         let task_source = self.global().performance_timeline_task_source();
         task_source.queue_notification(&self.global());
 
-        Some(entry_last_index)
+        entry_last_index
     }
 
     /// Observers notifications task.
@@ -330,10 +395,260 @@ impl Performance {
         }
     }
 
-    fn now(&self) -> f64 {
+    pub fn now(&self) -> f64 {
         (time::precise_time_ns() - self.navigation_start_precise).to_ms()
     }
 
+    /// Converts a `time::precise_time_ns()` timestamp to a time relative to the time origin.
+    pub fn relative_time(&self, precise_time_ns: u64) -> f64 {
+        precise_time_ns
+            .saturating_sub(self.navigation_start_precise)
+            .to_ms()
+    }
+
+    /// Reports a task of the event loop which took longer than `metrics::MAX_TASK_NS`.
+    ///
+    /// <https://w3c.github.io/longtasks/#report-long-tasks>
+    pub fn queue_long_task(&self, start_ns: u64, end_ns: u64) {
+        let start_time = self.relative_time(start_ns);
+        let duration = (end_ns - start_ns).to_ms();
+        let entry = PerformanceLongTaskTiming::new(&self.global(), start_time, duration);
+        self.queue_entry(entry.upcast::<PerformanceEntry>());
+    }
+
+    /// Reports the timing of a trusted input event once it has been dispatched.
+    ///
+    /// <https://w3c.github.io/event-timing/#sec-fin-event-timing>
+    pub fn queue_event_timing(
+        &self,
+        event_type: DOMString,
+        cancelable: bool,
+        target: Option<&Node>,
+        start_ns: u64,
+        processing_start_ns: u64,
+        processing_end_ns: u64,
+    ) {
+        let global = self.global();
+        let start_time = self.relative_time(start_ns);
+        let processing_start = self.relative_time(processing_start_ns);
+        let processing_end = self.relative_time(processing_end_ns);
+
+        if FIRST_INPUT_EVENT_TYPES.contains(&&*event_type) {
+            self.last_input_time.set(start_time);
+        }
+
+        // https://w3c.github.io/event-timing/#sec-init-event-timing
+        if !self.has_dispatched_input_event.get() && FIRST_INPUT_EVENT_TYPES.contains(&&*event_type)
+        {
+            self.has_dispatched_input_event.set(true);
+            let first_input = PerformanceEventTiming::new(
+                &global,
+                event_type.clone(),
+                DOMString::from("first-input"),
+                start_time,
+                processing_start,
+                processing_end,
+                cancelable,
+                target,
+            );
+            self.queue_entry(first_input.upcast::<PerformanceEntry>());
+        }
+
+        let entry = PerformanceEventTiming::new(
+            &global,
+            event_type,
+            DOMString::from("event"),
+            start_time,
+            processing_start,
+            processing_end,
+            cancelable,
+            target,
+        );
+        self.queue_entry(entry.upcast::<PerformanceEntry>());
+    }
+
+    /// Reports a new largest contentful paint candidate painted at the given time. Candidates
+    /// are no longer reported once the user interacted with the page.
+    ///
+    /// <https://w3c.github.io/largest-contentful-paint/#sec-report-largest-contentful-paint>
+    pub fn queue_largest_contentful_paint(&self, render_time: f64, node: &Node, size: u64) {
+        if self.has_dispatched_input_event.get() {
+            return;
+        }
+        // Text is attributed to the element which contains it.
+        let element = match node.downcast::<Element>() {
+            Some(element) => DomRoot::from_ref(element),
+            None => match node.GetParentElement() {
+                Some(element) => element,
+                None => return,
+            },
+        };
+        let url = element
+            .downcast::<HTMLImageElement>()
+            .map_or(DOMString::new(), |image| image.CurrentSrc().into());
+        let size = size.min(u32::MAX as u64) as u32;
+        let entry =
+            LargestContentfulPaint::new(&self.global(), render_time, 0., size, url, &element);
+        self.queue_entry(entry.upcast::<PerformanceEntry>());
+    }
+
+    /// Reports a layout shift measured in a frame painted at the given time, along with the
+    /// nodes which moved and their visible rects in the previous and current frames.
+    ///
+    /// <https://wicg.github.io/layout-instability/#sec-report-layout-shift>
+    pub fn queue_layout_shift(
+        &self,
+        time_ns: u64,
+        value: f64,
+        sources: &[(DomRoot<Node>, LayoutRect, LayoutRect)],
+    ) {
+        let global = self.global();
+        let start_time = self.relative_time(time_ns);
+        let last_input_time = self.last_input_time.get();
+        let had_recent_input =
+            last_input_time > 0. && start_time - last_input_time <= RECENT_INPUT_EXCLUSION_MS;
+
+        let to_dom_rect = |rect: &LayoutRect| {
+            DOMRectReadOnly::new(
+                &global,
+                rect.origin.x as f64,
+                rect.origin.y as f64,
+                rect.size.width as f64,
+                rect.size.height as f64,
+            )
+        };
+        let sources: Vec<_> = sources
+            .iter()
+            .map(|(node, previous_rect, current_rect)| {
+                LayoutShiftAttribution::new(
+                    &global,
+                    node,
+                    &to_dom_rect(previous_rect),
+                    &to_dom_rect(current_rect),
+                )
+            })
+            .collect();
+        let entry = LayoutShift::new(
+            &global,
+            start_time,
+            value,
+            had_recent_input,
+            last_input_time,
+            &sources,
+        );
+        self.queue_entry(entry.upcast::<PerformanceEntry>());
+    }
+
+    /// Measures the memory used by the agent cluster of this window. The JavaScript runtime
+    /// of a script thread is shared by all the windows it runs, so its memory is attributed to
+    /// all the windows with the same origin as this one.
+    ///
+    /// <https://wicg.github.io/performance-measure-memory/#measure-memory-algorithm>
+    fn measure_memory(&self) -> MemoryMeasurement {
+        let global = self.global();
+        let (bytes, documents) = ScriptThread::agent_cluster_memory(global.origin());
+        let attribution = documents
+            .iter()
+            .map(|document| {
+                let container = document
+                    .window()
+                    .window_proxy()
+                    .frame_element()
+                    .map(|frame| MemoryAttributionContainer {
+                        id: Some(frame.Id()),
+                        src: Some(USVString(String::from(
+                            frame.get_string_attribute(&local_name!("src")),
+                        ))),
+                    });
+                MemoryAttribution {
+                    url: Some(USVString(document.url().into_string())),
+                    container,
+                    scope: Some(DOMString::from("Window")),
+                }
+            })
+            .collect();
+        let breakdown = MemoryBreakdownEntry {
+            bytes: Some(bytes as u64),
+            attribution: Some(attribution),
+            types: Some(vec![DOMString::from("JavaScript")]),
+        };
+        MemoryMeasurement {
+            bytes: Some(bytes as u64),
+            breakdown: Some(vec![breakdown]),
+        }
+    }
+
+    /// <https://w3c.github.io/user-timing/#convert-a-mark-to-a-timestamp>
+    fn convert_mark_to_timestamp(&self, mark: &StringOrDouble) -> Fallible<f64> {
+        match *mark {
+            StringOrDouble::String(ref name) => {
+                // Step 1.
+                if self.global().is::<Window>() && INVALID_ENTRY_NAMES.contains(&name.as_ref()) {
+                    if &**name == "navigationStart" {
+                        return Ok(0.);
+                    }
+                    let time = self.navigation_timing_attribute(&name);
+                    if time == 0. {
+                        return Err(Error::InvalidAccess);
+                    }
+                    return Ok(time);
+                }
+                // Step 2.
+                self.buffer
+                    .borrow()
+                    .get_last_entry_start_time_with_name_and_type(name, DOMString::from("mark"))
+                    .ok_or(Error::Syntax)
+            },
+            StringOrDouble::Double(time) => {
+                if *time < 0. {
+                    return Err(Error::Type("Timestamps must not be negative".to_owned()));
+                }
+                Ok(*time)
+            },
+        }
+    }
+
+    /// The value of the given attribute of `PerformanceTiming` relative to the navigation start,
+    /// or 0 if it isn't known yet.
+    fn navigation_timing_attribute(&self, name: &str) -> f64 {
+        let entries = self
+            .buffer
+            .borrow()
+            .get_entries_by_name_and_type(None, Some(DOMString::from("navigation")));
+        let timing = match entries
+            .first()
+            .and_then(|entry| entry.downcast::<PerformanceNavigationTiming>())
+        {
+            Some(timing) => timing,
+            None => return 0.,
+        };
+        let resource_timing = timing.upcast::<PerformanceResourceTiming>();
+        let time = match name {
+            "unloadEventStart" => timing.UnloadEventStart(),
+            "unloadEventEnd" => timing.UnloadEventEnd(),
+            "redirectStart" => resource_timing.RedirectStart(),
+            "redirectEnd" => resource_timing.RedirectEnd(),
+            "fetchStart" => resource_timing.FetchStart(),
+            "domainLookupStart" => resource_timing.DomainLookupStart(),
+            "domainLookupEnd" => resource_timing.DomainLookupEnd(),
+            "connectStart" => resource_timing.ConnectStart(),
+            "connectEnd" => resource_timing.ConnectEnd(),
+            "secureConnectionStart" => resource_timing.SecureConnectionStart(),
+            "requestStart" => resource_timing.RequestStart(),
+            "responseStart" => resource_timing.ResponseStart(),
+            "responseEnd" => resource_timing.ResponseEnd(),
+            "domInteractive" => timing.DomInteractive(),
+            "domContentLoadedEventStart" => timing.DomContentLoadedEventStart(),
+            "domContentLoadedEventEnd" => timing.DomContentLoadedEventEnd(),
+            "domComplete" => timing.DomComplete(),
+            "loadEventStart" => timing.LoadEventStart(),
+            "loadEventEnd" => timing.LoadEventEnd(),
+            // TODO: domLoading isn't tracked.
+            _ => return 0.,
+        };
+        *time
+    }
+
     fn can_add_resource_timing_entry(&self) -> bool {
         self.resource_timing_buffer_current_size.get() <=
             self.resource_timing_buffer_size_limit.get()
@@ -395,6 +710,19 @@ impl Performance {
         false
     }
 
+    /// Ends the navigation timing entry with the given `time::precise_time_ns()` value of
+    /// the end of the load event of the document.
+    ///
+    /// <https://w3c.github.io/navigation-timing/#dom-performancenavigationtiming-duration>
+    pub fn end_navigation_timing(&self, load_event_end: u64) {
+        let duration = self.relative_time(load_event_end);
+        for entry in self.buffer.borrow().entries.iter() {
+            if entry.entry_type() == "navigation" {
+                entry.set_duration(duration);
+            }
+        }
+    }
+
     pub fn update_entry(&self, index: usize, entry: &PerformanceEntry) {
         if let Some(e) = self.buffer.borrow_mut().entries.get_mut(index) {
             *e = DomRoot::from_ref(entry);
@@ -437,6 +765,9 @@ impl PerformanceMethods for Performance {
         self.buffer
             .borrow()
             .get_entries_by_name_and_type(None, None)
+            .into_iter()
+            .filter(|entry| !OBSERVER_ONLY_ENTRY_TYPES.contains(&&**entry.entry_type()))
+            .collect()
     }
 
     // https://www.w3.org/TR/performance-timeline-2/#dom-performance-getentriesbytype
@@ -444,6 +775,9 @@ impl PerformanceMethods for Performance {
         self.buffer
             .borrow()
             .get_entries_by_name_and_type(None, Some(entry_type))
+            .into_iter()
+            .filter(|entry| !OBSERVER_ONLY_ENTRY_TYPES.contains(&&**entry.entry_type()))
+            .collect()
     }
 
     // https://www.w3.org/TR/performance-timeline-2/#dom-performance-getentriesbyname
@@ -455,23 +789,25 @@ impl PerformanceMethods for Performance {
         self.buffer
             .borrow()
             .get_entries_by_name_and_type(Some(name), entry_type)
+            .into_iter()
+            .filter(|entry| !OBSERVER_ONLY_ENTRY_TYPES.contains(&&**entry.entry_type()))
+            .collect()
     }
 
     // https://w3c.github.io/user-timing/#dom-performance-mark
-    fn Mark(&self, mark_name: DOMString) -> Fallible<()> {
-        let global = self.global();
+    fn Mark(
+        &self,
+        mark_name: DOMString,
+        mark_options: RootedTraceableBox<PerformanceMarkOptions>,
+    ) -> Fallible<DomRoot<PerformanceMark>> {
         // Step 1.
-        if global.is::<Window>() && INVALID_ENTRY_NAMES.contains(&mark_name.as_ref()) {
-            return Err(Error::Syntax);
-        }
+        let entry = PerformanceMark::Constructor(&self.global(), mark_name, mark_options)?;
 
-        // Steps 2 to 6.
-        let entry = PerformanceMark::new(&global, mark_name, self.now(), 0.);
-        // Steps 7 and 8.
+        // Step 2.
         self.queue_entry(&entry.upcast::<PerformanceEntry>());
 
-        // Step 9.
-        Ok(())
+        // Step 3.
+        Ok(entry)
     }
 
     // https://w3c.github.io/user-timing/#dom-performance-clearmarks
@@ -485,40 +821,90 @@ impl PerformanceMethods for Performance {
     fn Measure(
         &self,
         measure_name: DOMString,
-        start_mark: Option<DOMString>,
+        start_or_measure_options: StringOrPerformanceMeasureOptions,
         end_mark: Option<DOMString>,
-    ) -> Fallible<()> {
-        // Steps 1 and 2.
-        let end_time = match end_mark {
-            Some(name) => self
-                .buffer
-                .borrow()
-                .get_last_entry_start_time_with_name_and_type(DOMString::from("mark"), name),
-            None => self.now(),
+    ) -> Fallible<DomRoot<PerformanceMeasure>> {
+        let global = self.global();
+        let measure_options = match start_or_measure_options {
+            StringOrPerformanceMeasureOptions::PerformanceMeasureOptions(ref options)
+                if !is_empty_measure_options(options) =>
+            {
+                Some(options)
+            },
+            _ => None,
+        };
+
+        // Step 1.
+        if let Some(options) = measure_options {
+            if end_mark.is_some() {
+                return Err(Error::Type(
+                    "endMark can't be given along with measure options".to_owned(),
+                ));
+            }
+            if options.start.is_none() && options.end.is_none() {
+                return Err(Error::Type(
+                    "Measure options must have a start or an end".to_owned(),
+                ));
+            }
+            if options.start.is_some() && options.duration.is_some() && options.end.is_some() {
+                return Err(Error::Type(
+                    "Measure options can't have a start, a duration and an end".to_owned(),
+                ));
+            }
+        }
+
+        // Step 2.
+        let end_time = match (end_mark, measure_options) {
+            (Some(end_mark), _) => {
+                self.convert_mark_to_timestamp(&StringOrDouble::String(end_mark))?
+            },
+            (None, Some(options)) => match (&options.end, &options.start, options.duration) {
+                (Some(end), _, _) => self.convert_mark_to_timestamp(end)?,
+                (None, Some(start), Some(duration)) => {
+                    self.convert_mark_to_timestamp(start)? + *duration
+                },
+                _ => self.now(),
+            },
+            (None, None) => self.now(),
         };
 
         // Step 3.
-        let start_time = match start_mark {
-            Some(name) => self
-                .buffer
-                .borrow()
-                .get_last_entry_start_time_with_name_and_type(DOMString::from("mark"), name),
-            None => 0.,
+        let start_time = match measure_options {
+            Some(options) => match (&options.start, options.duration, &options.end) {
+                (Some(start), _, _) => self.convert_mark_to_timestamp(start)?,
+                (None, Some(duration), Some(_)) => end_time - *duration,
+                _ => 0.,
+            },
+            None => match start_or_measure_options {
+                StringOrPerformanceMeasureOptions::String(ref start_mark) => {
+                    self.convert_mark_to_timestamp(&StringOrDouble::String(start_mark.clone()))?
+                },
+                _ => 0.,
+            },
         };
 
+        // Step 9.
+        rooted!(in(*global.get_cx()) let mut detail = UndefinedValue());
+        if let Some(options) = measure_options {
+            clone_user_timing_detail(&global, options.detail.handle(), detail.handle_mut())?;
+        } else {
+            detail.set(NullValue());
+        }
+
         // Steps 4 to 8.
         let entry = PerformanceMeasure::new(
-            &self.global(),
+            &global,
             measure_name,
             start_time,
             end_time - start_time,
+            detail.handle(),
         );
 
-        // Step 9 and 10.
+        // Step 10.
         self.queue_entry(&entry.upcast::<PerformanceEntry>());
 
         // Step 11.
-        Ok(())
+        Ok(entry)
     }
 
     // https://w3c.github.io/user-timing/#dom-performance-clearmeasures
@@ -547,6 +933,60 @@ impl PerformanceMethods for Performance {
         GetOnresourcetimingbufferfull,
         SetOnresourcetimingbufferfull
     );
+
+    // https://wicg.github.io/performance-measure-memory/#dom-performance-measureuseragentspecificmemory
+    fn MeasureUserAgentSpecificMemory(&self) -> Rc<Promise> {
+        let global = self.global();
+        let in_realm_proof = AlreadyInRealm::assert(&global);
+        let promise = Promise::new_in_current_realm(&global, InRealm::Already(&in_realm_proof));
+
+        let window = global
+            .downcast::<Window>()
+            .expect("measureUserAgentSpecificMemory is only exposed to windows");
+        if !window.Document().is_cross_origin_isolated() {
+            promise.reject_error(Error::Security);
+            return promise;
+        }
+
+        // The memory is measured in a task, once the current one has released its
+        // temporary allocations.
+        let this = Trusted::new(self);
+        let trusted_promise = TrustedPromise::new(promise.clone());
+        let _ = global.performance_timeline_task_source().queue(
+            task!(measure_memory: move || {
+                let measurement = this.root().measure_memory();
+                trusted_promise.root().resolve_native(&measurement);
+            }),
+            &global,
+        );
+        promise
+    }
+}
+
+/// Whether none of the members of the given measure options are present.
+fn is_empty_measure_options(options: &PerformanceMeasureOptions) -> bool {
+    options.detail.get().is_undefined() &&
+        options.start.is_none() &&
+        options.duration.is_none() &&
+        options.end.is_none()
+}
+
+/// Sets `rval` to a structured clone of the detail of a user timing entry, created in the
+/// current realm, or to null if no detail was given.
+///
+/// <https://w3c.github.io/user-timing/#the-performancemark-constructor>
+pub fn clone_user_timing_detail(
+    global: &GlobalScope,
+    detail: HandleValue,
+    mut rval: MutableHandleValue,
+) -> ErrorResult {
+    if detail.get().is_null_or_undefined() {
+        rval.set(NullValue());
+        return Ok(());
+    }
+    let data = structuredclone::write(global.get_cx(), detail, None)?;
+    structuredclone::read(global, data, rval).map_err(|_| Error::DataClone)?;
+    Ok(())
 }
 
 // https://www.w3.org/TR/hr-time-2/#clock-resolution
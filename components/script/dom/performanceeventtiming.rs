@@ -0,0 +1,144 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::PerformanceBinding::DOMHighResTimeStamp;
+use crate::dom::bindings::codegen::Bindings::PerformanceEventTimingBinding::PerformanceEventTimingMethods;
+use crate::dom::bindings::reflector::reflect_dom_object;
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::node::Node;
+use crate::dom::performance::reduce_timing_resolution;
+use crate::dom::performanceentry::PerformanceEntry;
+use dom_struct::dom_struct;
+
+/// The types of the trusted events whose timing is reported.
+///
+/// <https://w3c.github.io/event-timing/#sec-events-exposed>
+pub const TIMED_EVENT_TYPES: &'static [&'static str] = &[
+    "auxclick",
+    "beforeinput",
+    "click",
+    "compositionend",
+    "compositionstart",
+    "compositionupdate",
+    "contextmenu",
+    "dblclick",
+    "dragend",
+    "dragenter",
+    "dragleave",
+    "dragover",
+    "dragstart",
+    "drop",
+    "gotpointercapture",
+    "input",
+    "keydown",
+    "keypress",
+    "keyup",
+    "lostpointercapture",
+    "mousedown",
+    "mouseenter",
+    "mouseleave",
+    "mouseout",
+    "mouseover",
+    "mouseup",
+    "pointercancel",
+    "pointerdown",
+    "pointerenter",
+    "pointerleave",
+    "pointerout",
+    "pointerover",
+    "pointerup",
+    "touchcancel",
+    "touchend",
+    "touchstart",
+];
+
+/// The granularity the duration of event timing entries is rounded to, in milliseconds.
+///
+/// <https://w3c.github.io/event-timing/#set-event-timing-entry-duration>
+const EVENT_TIMING_DURATION_GRANULARITY: f64 = 8.;
+
+/// <https://w3c.github.io/event-timing/#sec-performance-event-timing>
+#[dom_struct]
+pub struct PerformanceEventTiming {
+    entry: PerformanceEntry,
+    processing_start: f64,
+    processing_end: f64,
+    cancelable: bool,
+    target: Option<Dom<Node>>,
+}
+
+impl PerformanceEventTiming {
+    fn new_inherited(
+        name: DOMString,
+        entry_type: DOMString,
+        start_time: f64,
+        processing_start: f64,
+        processing_end: f64,
+        cancelable: bool,
+        target: Option<&Node>,
+    ) -> PerformanceEventTiming {
+        // There is no notion of the next paint after the event handlers ran, so the duration
+        // spans from the event's creation to the end of its dispatch.
+        let duration = ((processing_end - start_time) / EVENT_TIMING_DURATION_GRANULARITY).round() *
+            EVENT_TIMING_DURATION_GRANULARITY;
+        PerformanceEventTiming {
+            entry: PerformanceEntry::new_inherited(name, entry_type, start_time, duration),
+            processing_start,
+            processing_end,
+            cancelable,
+            target: target.map(Dom::from_ref),
+        }
+    }
+
+    #[allow(unrooted_must_root)]
+    pub fn new(
+        global: &GlobalScope,
+        name: DOMString,
+        entry_type: DOMString,
+        start_time: f64,
+        processing_start: f64,
+        processing_end: f64,
+        cancelable: bool,
+        target: Option<&Node>,
+    ) -> DomRoot<PerformanceEventTiming> {
+        let entry = PerformanceEventTiming::new_inherited(
+            name,
+            entry_type,
+            start_time,
+            processing_start,
+            processing_end,
+            cancelable,
+            target,
+        );
+        reflect_dom_object(Box::new(entry), global)
+    }
+}
+
+impl PerformanceEventTimingMethods for PerformanceEventTiming {
+    // https://w3c.github.io/event-timing/#dom-performanceeventtiming-processingstart
+    fn ProcessingStart(&self) -> DOMHighResTimeStamp {
+        reduce_timing_resolution(self.processing_start)
+    }
+
+    // https://w3c.github.io/event-timing/#dom-performanceeventtiming-processingend
+    fn ProcessingEnd(&self) -> DOMHighResTimeStamp {
+        reduce_timing_resolution(self.processing_end)
+    }
+
+    // https://w3c.github.io/event-timing/#dom-performanceeventtiming-cancelable
+    fn Cancelable(&self) -> bool {
+        self.cancelable
+    }
+
+    // https://w3c.github.io/event-timing/#dom-performanceeventtiming-target
+    fn GetTarget(&self) -> Option<DomRoot<Node>> {
+        // Nodes which were removed from the document are not exposed.
+        self.target
+            .as_ref()
+            .filter(|target| target.is_connected())
+            .map(|target| DomRoot::from_ref(&**target))
+    }
+}
@@ -0,0 +1,84 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::PerformanceLongTaskTimingBinding::PerformanceLongTaskTimingMethods;
+use crate::dom::bindings::reflector::reflect_dom_object;
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::bindings::utils::to_frozen_array;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::performanceentry::PerformanceEntry;
+use crate::dom::taskattributiontiming::TaskAttributionTiming;
+use crate::script_runtime::JSContext;
+use dom_struct::dom_struct;
+use js::jsapi::Heap;
+use js::jsval::JSVal;
+
+/// <https://w3c.github.io/longtasks/#sec-PerformanceLongTaskTiming>
+#[dom_struct]
+pub struct PerformanceLongTaskTiming {
+    entry: PerformanceEntry,
+    attribution: Vec<Dom<TaskAttributionTiming>>,
+    #[ignore_malloc_size_of = "mozjs"]
+    frozen_attribution: DomRefCell<Option<Heap<JSVal>>>,
+}
+
+impl PerformanceLongTaskTiming {
+    fn new_inherited(
+        start_time: f64,
+        duration: f64,
+        attribution: &TaskAttributionTiming,
+    ) -> PerformanceLongTaskTiming {
+        PerformanceLongTaskTiming {
+            // The name is the culprit of the long task, which is always the window's own
+            // browsing context until tasks can be attributed to other frames.
+            entry: PerformanceEntry::new_inherited(
+                DOMString::from("self"),
+                DOMString::from("longtask"),
+                start_time,
+                duration,
+            ),
+            attribution: vec![Dom::from_ref(attribution)],
+            frozen_attribution: DomRefCell::new(None),
+        }
+    }
+
+    #[allow(unrooted_must_root)]
+    pub fn new(
+        global: &GlobalScope,
+        start_time: f64,
+        duration: f64,
+    ) -> DomRoot<PerformanceLongTaskTiming> {
+        let attribution = TaskAttributionTiming::new_for_window(global);
+        let entry = PerformanceLongTaskTiming::new_inherited(start_time, duration, &attribution);
+        reflect_dom_object(Box::new(entry), global)
+    }
+}
+
+impl PerformanceLongTaskTimingMethods for PerformanceLongTaskTiming {
+    // https://w3c.github.io/longtasks/#dom-performancelongtasktiming-attribution
+    fn Attribution(&self, cx: JSContext) -> JSVal {
+        if let Some(attribution) = &*self.frozen_attribution.borrow() {
+            return attribution.get();
+        }
+
+        let attribution: Vec<DomRoot<TaskAttributionTiming>> = self
+            .attribution
+            .iter()
+            .map(|attribution| DomRoot::from_ref(&**attribution))
+            .collect();
+        let frozen_attribution = to_frozen_array(attribution.as_slice(), cx);
+
+        // Safety: need to create the Heap value in its final memory location before setting it.
+        *self.frozen_attribution.borrow_mut() = Some(Heap::default());
+        self.frozen_attribution
+            .borrow()
+            .as_ref()
+            .unwrap()
+            .set(frozen_attribution);
+
+        frozen_attribution
+    }
+}
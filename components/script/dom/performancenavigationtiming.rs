@@ -6,12 +6,14 @@ use crate::dom::bindings::codegen::Bindings::PerformanceBinding::DOMHighResTimeS
 use crate::dom::bindings::codegen::Bindings::PerformanceNavigationTimingBinding::NavigationType;
 use crate::dom::bindings::codegen::Bindings::PerformanceNavigationTimingBinding::PerformanceNavigationTimingMethods;
 use crate::dom::bindings::num::Finite;
-use crate::dom::bindings::reflector::reflect_dom_object;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
 use crate::dom::bindings::root::{Dom, DomRoot};
 use crate::dom::document::Document;
 use crate::dom::globalscope::GlobalScope;
+use crate::dom::performance::reduce_timing_resolution;
 use crate::dom::performanceresourcetiming::{InitiatorType, PerformanceResourceTiming};
 use dom_struct::dom_struct;
+use net_traits::ResourceFetchTiming;
 
 #[dom_struct]
 // https://w3c.github.io/navigation-timing/#dom-performancenavigationtiming
@@ -20,89 +22,95 @@ use dom_struct::dom_struct;
 pub struct PerformanceNavigationTiming {
     // https://w3c.github.io/navigation-timing/#PerformanceResourceTiming
     performanceresourcetiming: PerformanceResourceTiming,
-    navigation_start: u64,
-    navigation_start_precise: u64,
     document: Dom<Document>,
     nav_type: NavigationType,
 }
 
 impl PerformanceNavigationTiming {
     fn new_inherited(
-        nav_start: u64,
-        nav_start_precise: u64,
+        global: &GlobalScope,
         document: &Document,
+        resource_timing: &ResourceFetchTiming,
     ) -> PerformanceNavigationTiming {
         PerformanceNavigationTiming {
-            performanceresourcetiming: PerformanceResourceTiming::new_inherited(
+            performanceresourcetiming: PerformanceResourceTiming::from_resource_timing(
+                &global.performance(),
                 document.url(),
                 InitiatorType::Navigation,
-                None,
-                nav_start_precise as f64,
+                resource_timing,
             ),
-            navigation_start: nav_start,
-            navigation_start_precise: nav_start_precise,
             document: Dom::from_ref(document),
             nav_type: NavigationType::Navigate,
         }
     }
 
+    /// Creates the navigation timing entry of `document`, whose fetch timing is taken from
+    /// `resource_timing` and whose document lifecycle timing is read from the document.
     pub fn new(
         global: &GlobalScope,
-        nav_start: u64,
-        nav_start_precise: u64,
         document: &Document,
+        resource_timing: &ResourceFetchTiming,
     ) -> DomRoot<PerformanceNavigationTiming> {
         reflect_dom_object(
             Box::new(PerformanceNavigationTiming::new_inherited(
-                nav_start,
-                nav_start_precise,
+                global,
                 document,
+                resource_timing,
             )),
             global,
         )
     }
+
+    /// Converts a document lifecycle timestamp to a time relative to the time origin.
+    /// Timestamps of steps that haven't happened yet are zero.
+    fn relative_time(&self, precise_time_ns: u64) -> DOMHighResTimeStamp {
+        if precise_time_ns == 0 {
+            return Finite::wrap(0.);
+        }
+        reduce_timing_resolution(self.global().performance().relative_time(precise_time_ns))
+    }
 }
 
 // https://w3c.github.io/navigation-timing/
 impl PerformanceNavigationTimingMethods for PerformanceNavigationTiming {
     // https://w3c.github.io/navigation-timing/#dom-performancenavigationtiming-unloadeventstart
     fn UnloadEventStart(&self) -> DOMHighResTimeStamp {
-        Finite::wrap(self.document.get_unload_event_start() as f64)
+        self.relative_time(self.document.get_unload_event_start())
     }
 
     // https://w3c.github.io/navigation-timing/#dom-performancenavigationtiming-unloadeventend
     fn UnloadEventEnd(&self) -> DOMHighResTimeStamp {
-        Finite::wrap(self.document.get_unload_event_end() as f64)
+        self.relative_time(self.document.get_unload_event_end())
     }
 
     // https://w3c.github.io/navigation-timing/#dom-performancenavigationtiming-dominteractive
     fn DomInteractive(&self) -> DOMHighResTimeStamp {
-        Finite::wrap(self.document.get_dom_interactive() as f64)
+        self.relative_time(self.document.get_dom_interactive())
     }
 
     // https://w3c.github.io/navigation-timing/#dom-performancenavigationtiming-domcontentloadedeventstart
     fn DomContentLoadedEventStart(&self) -> DOMHighResTimeStamp {
-        Finite::wrap(self.document.get_dom_content_loaded_event_start() as f64)
+        self.relative_time(self.document.get_dom_content_loaded_event_start())
     }
 
     // https://w3c.github.io/navigation-timing/#dom-performancenavigationtiming-domcontentloadedeventstart
     fn DomContentLoadedEventEnd(&self) -> DOMHighResTimeStamp {
-        Finite::wrap(self.document.get_dom_content_loaded_event_end() as f64)
+        self.relative_time(self.document.get_dom_content_loaded_event_end())
     }
 
     // https://w3c.github.io/navigation-timing/#dom-performancenavigationtiming-domcomplete
     fn DomComplete(&self) -> DOMHighResTimeStamp {
-        Finite::wrap(self.document.get_dom_complete() as f64)
+        self.relative_time(self.document.get_dom_complete())
     }
 
     // https://w3c.github.io/navigation-timing/#dom-performancenavigationtiming-loadeventstart
     fn LoadEventStart(&self) -> DOMHighResTimeStamp {
-        Finite::wrap(self.document.get_load_event_start() as f64)
+        self.relative_time(self.document.get_load_event_start())
     }
 
     // https://w3c.github.io/navigation-timing/#dom-performancenavigationtiming-loadeventend
     fn LoadEventEnd(&self) -> DOMHighResTimeStamp {
-        Finite::wrap(self.document.get_load_event_end() as f64)
+        self.relative_time(self.document.get_load_event_end())
     }
 
     // https://w3c.github.io/navigation-timing/#dom-performancenavigationtiming-type
@@ -118,6 +126,6 @@ impl PerformanceNavigationTimingMethods for PerformanceNavigationTiming {
     // check-tidy: no specs after this line
     // Servo-only timing for when top-level content (not iframes) is complete
     fn TopLevelDomComplete(&self) -> DOMHighResTimeStamp {
-        Finite::wrap(self.document.get_top_level_dom_complete() as f64)
+        self.relative_time(self.document.get_top_level_dom_complete())
     }
 }
@@ -25,15 +25,28 @@ use std::rc::Rc;
 
 /// List of allowed performance entry types, in alphabetical order.
 pub const VALID_ENTRY_TYPES: &'static [&'static str] = &[
+    "event",       // Event Timing API
+    "first-input", // Event Timing API
     // "frame", //TODO Frame Timing API
-    "mark",       // User Timing API
-    "measure",    // User Timing API
-    "navigation", // Navigation Timing API
-    "paint",      // Paint Timing API
-    "resource",   // Resource Timing API
-                  // "server", XXX Server Timing API
+    "largest-contentful-paint", // Largest Contentful Paint API
+    "layout-shift",             // Layout Instability API
+    "longtask",                 // Long Tasks API
+    "mark",                     // User Timing API
+    "measure",                  // User Timing API
+    "navigation",               // Navigation Timing API
+    "paint",                    // Paint Timing API
+    "resource",                 // Resource Timing API
+                                // "server", XXX Server Timing API
 ];
 
+/// The duration threshold of event timing entries, used when none is given to `observe()`.
+///
+/// <https://w3c.github.io/event-timing/#dom-performanceobserverinit-durationthreshold>
+pub const DEFAULT_EVENT_TIMING_DURATION_THRESHOLD: f64 = 104.;
+
+/// The lowest duration threshold of event timing entries an observer can ask for.
+const MIN_EVENT_TIMING_DURATION_THRESHOLD: f64 = 16.;
+
 #[derive(Clone, Copy, JSTraceable, MallocSizeOf, PartialEq)]
 enum ObserverType {
     Undefined,
@@ -48,6 +61,8 @@ pub struct PerformanceObserver {
     callback: Rc<PerformanceObserverCallback>,
     entries: DomRefCell<DOMPerformanceEntryList>,
     observer_type: Cell<ObserverType>,
+    /// <https://w3c.github.io/event-timing/#dom-performanceobserverinit-durationthreshold>
+    duration_threshold: Cell<f64>,
 }
 
 impl PerformanceObserver {
@@ -60,6 +75,7 @@ impl PerformanceObserver {
             callback,
             entries,
             observer_type: Cell::new(ObserverType::Undefined),
+            duration_threshold: Cell::new(DEFAULT_EVENT_TIMING_DURATION_THRESHOLD),
         }
     }
 
@@ -81,6 +97,12 @@ impl PerformanceObserver {
         Ok(PerformanceObserver::new(global, callback, Vec::new()))
     }
 
+    /// Whether this observer wants to be notified of the given entry, on top of observing its
+    /// type. Event timing entries are only observed above the duration threshold.
+    pub fn should_observe(&self, entry: &PerformanceEntry) -> bool {
+        entry.entry_type() != "event" || entry.duration() >= self.duration_threshold.get()
+    }
+
     /// Buffer a new performance entry.
     pub fn queue_entry(&self, entry: &PerformanceEntry) {
         self.entries.borrow_mut().push(DomRoot::from_ref(entry));
@@ -201,6 +223,14 @@ impl PerformanceObserverMethods for PerformanceObserver {
                 return Ok(());
             }
 
+            // https://w3c.github.io/event-timing/#sec-modifications-perf-timeline
+            if entry_type == "event" {
+                if let Some(threshold) = options.durationThreshold {
+                    self.duration_threshold
+                        .set((*threshold).max(MIN_EVENT_TIMING_DURATION_THRESHOLD));
+                }
+            }
+
             // Steps 7.3-7.5
             // This may pre-fill buffered entries, and
             // existing types are appended to.
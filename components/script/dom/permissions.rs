@@ -323,6 +323,28 @@ pub fn get_descriptor_permission_state(
     state
 }
 
+/// Asks the user to grant a permission, unless they already granted or denied it.
+/// <https://w3c.github.io/permissions/#request-permission-to-use>
+pub fn request_permission_to_use(
+    permission_name: PermissionName,
+    globalscope: &GlobalScope,
+) -> PermissionState {
+    // Step 1-2.
+    let state = get_descriptor_permission_state(permission_name, Some(globalscope));
+    if state != PermissionState::Prompt {
+        return state;
+    }
+
+    // Step 3-4.
+    let prompt = PermissionPrompt::Request(embedder_traits::PermissionName::from(permission_name));
+    let state = prompt_user_from_embedder(prompt, globalscope);
+    globalscope
+        .permission_state_invocation_results()
+        .borrow_mut()
+        .insert(permission_name.to_string(), state);
+    state
+}
+
 // https://w3c.github.io/permissions/#allowed-in-non-secure-contexts
 fn allowed_in_nonsecure_contexts(permission_name: &PermissionName) -> bool {
     match *permission_name {
@@ -348,6 +370,14 @@ fn allowed_in_nonsecure_contexts(permission_name: &PermissionName) -> bool {
         PermissionName::Bluetooth => false,
         // https://storage.spec.whatwg.org/#dom-permissionname-persistent-storage
         PermissionName::Persistent_storage => false,
+        // https://w3c.github.io/accelerometer/#accelerometer-sensor-type
+        PermissionName::Accelerometer => false,
+        // https://w3c.github.io/gyroscope/#gyroscope-sensor-type
+        PermissionName::Gyroscope => false,
+        // https://w3c.github.io/magnetometer/#magnetometer-sensor-type
+        PermissionName::Magnetometer => false,
+        // https://wicg.github.io/idle-detection/#api-permissions
+        PermissionName::Idle_detection => false,
     }
 }
 
@@ -384,6 +414,10 @@ impl From<PermissionName> for embedder_traits::PermissionName {
             PermissionName::Persistent_storage => {
                 embedder_traits::PermissionName::PersistentStorage
             },
+            PermissionName::Accelerometer => embedder_traits::PermissionName::Accelerometer,
+            PermissionName::Gyroscope => embedder_traits::PermissionName::Gyroscope,
+            PermissionName::Magnetometer => embedder_traits::PermissionName::Magnetometer,
+            PermissionName::Idle_detection => embedder_traits::PermissionName::IdleDetection,
         }
     }
 }
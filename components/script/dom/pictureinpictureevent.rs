@@ -0,0 +1,80 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::EventBinding::EventBinding::EventMethods;
+use crate::dom::bindings::codegen::Bindings::PictureInPictureEventBinding::{
+    self, PictureInPictureEventMethods,
+};
+use crate::dom::bindings::error::Fallible;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::event::Event;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::pictureinpicturewindow::PictureInPictureWindow;
+use crate::dom::window::Window;
+use dom_struct::dom_struct;
+use servo_atoms::Atom;
+
+/// <https://w3c.github.io/picture-in-picture/#interface-picture-in-picture-event>
+#[dom_struct]
+pub struct PictureInPictureEvent {
+    event: Event,
+    picture_in_picture_window: Dom<PictureInPictureWindow>,
+}
+
+impl PictureInPictureEvent {
+    #[allow(unrooted_must_root)]
+    fn new_inherited(window: &PictureInPictureWindow) -> PictureInPictureEvent {
+        PictureInPictureEvent {
+            event: Event::new_inherited(),
+            picture_in_picture_window: Dom::from_ref(window),
+        }
+    }
+
+    pub fn new(
+        global: &GlobalScope,
+        type_: Atom,
+        bubbles: bool,
+        cancelable: bool,
+        window: &PictureInPictureWindow,
+    ) -> DomRoot<PictureInPictureEvent> {
+        let event = reflect_dom_object(
+            Box::new(PictureInPictureEvent::new_inherited(window)),
+            global,
+        );
+        event
+            .upcast::<Event>()
+            .init_event(type_, bubbles, cancelable);
+        event
+    }
+
+    #[allow(non_snake_case)]
+    pub fn Constructor(
+        window: &Window,
+        type_: DOMString,
+        init: &PictureInPictureEventBinding::PictureInPictureEventInit,
+    ) -> Fallible<DomRoot<PictureInPictureEvent>> {
+        Ok(PictureInPictureEvent::new(
+            &window.global(),
+            Atom::from(type_),
+            init.parent.bubbles,
+            init.parent.cancelable,
+            &init.pictureInPictureWindow,
+        ))
+    }
+}
+
+impl PictureInPictureEventMethods for PictureInPictureEvent {
+    // https://w3c.github.io/picture-in-picture/#dom-pictureinpictureevent-pictureinpicturewindow
+    fn PictureInPictureWindow(&self) -> DomRoot<PictureInPictureWindow> {
+        DomRoot::from_ref(&*self.picture_in_picture_window)
+    }
+
+    // https://dom.spec.whatwg.org/#dom-event-istrusted
+    fn IsTrusted(&self) -> bool {
+        self.event.IsTrusted()
+    }
+}
@@ -0,0 +1,76 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::PictureInPictureWindowBinding::PictureInPictureWindowMethods;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::reflector::reflect_dom_object;
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::eventtarget::EventTarget;
+use crate::dom::globalscope::GlobalScope;
+use dom_struct::dom_struct;
+use std::cell::Cell;
+use webrender_api::units::DeviceIntSize;
+
+/// The floating window that the embedder shows a video in.
+/// <https://w3c.github.io/picture-in-picture/#interface-picture-in-picture-window>
+#[dom_struct]
+pub struct PictureInPictureWindow {
+    eventtarget: EventTarget,
+    /// The size of the floating window, which is zero once it is closed.
+    width: Cell<i32>,
+    height: Cell<i32>,
+}
+
+impl PictureInPictureWindow {
+    fn new_inherited(size: DeviceIntSize) -> PictureInPictureWindow {
+        PictureInPictureWindow {
+            eventtarget: EventTarget::new_inherited(),
+            width: Cell::new(size.width),
+            height: Cell::new(size.height),
+        }
+    }
+
+    pub fn new(global: &GlobalScope, size: DeviceIntSize) -> DomRoot<PictureInPictureWindow> {
+        reflect_dom_object(
+            Box::new(PictureInPictureWindow::new_inherited(size)),
+            global,
+        )
+    }
+
+    /// Records that the user resized the floating window, and fires `resize`.
+    pub fn resized(&self, size: DeviceIntSize) {
+        if self.is_closed() || (self.width.get() == size.width && self.height.get() == size.height)
+        {
+            return;
+        }
+        self.width.set(size.width);
+        self.height.set(size.height);
+        self.upcast::<EventTarget>().fire_event(atom!("resize"));
+    }
+
+    /// <https://w3c.github.io/picture-in-picture/#exit-picture-in-picture-algorithm>
+    pub fn close(&self) {
+        self.width.set(0);
+        self.height.set(0);
+    }
+
+    fn is_closed(&self) -> bool {
+        self.width.get() == 0 && self.height.get() == 0
+    }
+}
+
+impl PictureInPictureWindowMethods for PictureInPictureWindow {
+    // https://w3c.github.io/picture-in-picture/#dom-pictureinpicturewindow-width
+    fn Width(&self) -> i32 {
+        self.width.get()
+    }
+
+    // https://w3c.github.io/picture-in-picture/#dom-pictureinpicturewindow-height
+    fn Height(&self) -> i32 {
+        self.height.get()
+    }
+
+    // https://w3c.github.io/picture-in-picture/#dom-pictureinpicturewindow-onresize
+    event_handler!(resize, GetOnresize, SetOnresize);
+}
@@ -0,0 +1,85 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::authenticatorresponse::{set_array_buffer, AuthenticatorResponse};
+use crate::dom::bindings::codegen::Bindings::PublicKeyCredentialBinding::AuthenticationExtensionsClientOutputs;
+use crate::dom::bindings::codegen::Bindings::PublicKeyCredentialBinding::PublicKeyCredentialMethods;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::reflector::reflect_dom_object;
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::str::{DOMString, USVString};
+use crate::dom::credential::Credential;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::promise::Promise;
+use crate::dom::window::Window;
+use crate::script_runtime::JSContext;
+use dom_struct::dom_struct;
+use js::jsapi::{Heap, JSObject};
+use std::ptr::NonNull;
+use std::rc::Rc;
+
+/// <https://w3c.github.io/webauthn/#iface-pkcredential>
+#[dom_struct]
+pub struct PublicKeyCredential {
+    credential: Credential,
+    #[ignore_malloc_size_of = "mozjs"]
+    raw_id: Heap<*mut JSObject>,
+    response: Dom<AuthenticatorResponse>,
+}
+
+impl PublicKeyCredential {
+    fn new_inherited(raw_id: &[u8], response: &AuthenticatorResponse) -> PublicKeyCredential {
+        let id = base64::encode_config(raw_id, base64::URL_SAFE_NO_PAD);
+        PublicKeyCredential {
+            credential: Credential::new_inherited(USVString(id), DOMString::from("public-key")),
+            raw_id: Heap::default(),
+            response: Dom::from_ref(response),
+        }
+    }
+
+    pub fn new(
+        global: &GlobalScope,
+        raw_id: &[u8],
+        response: &AuthenticatorResponse,
+    ) -> DomRoot<PublicKeyCredential> {
+        let credential = reflect_dom_object(
+            Box::new(PublicKeyCredential::new_inherited(raw_id, response)),
+            global,
+        );
+        set_array_buffer(global.get_cx(), &credential.raw_id, raw_id);
+        credential
+    }
+
+    // https://w3c.github.io/webauthn/#dom-publickeycredential-isuserverifyingplatformauthenticatoravailable
+    #[allow(non_snake_case)]
+    pub fn IsUserVerifyingPlatformAuthenticatorAvailable(window: &Window) -> Rc<Promise> {
+        // The platform authenticator only tests for the presence of the user, by asking them.
+        let promise = Promise::new(window.upcast());
+        promise.resolve_native(&false);
+        promise
+    }
+}
+
+impl PublicKeyCredentialMethods for PublicKeyCredential {
+    #[allow(unsafe_code)]
+    // https://w3c.github.io/webauthn/#dom-publickeycredential-rawid
+    fn RawId(&self, _cx: JSContext) -> NonNull<JSObject> {
+        unsafe { NonNull::new_unchecked(self.raw_id.get()) }
+    }
+
+    // https://w3c.github.io/webauthn/#dom-publickeycredential-response
+    fn Response(&self) -> DomRoot<AuthenticatorResponse> {
+        DomRoot::from_ref(&*self.response)
+    }
+
+    // https://w3c.github.io/webauthn/#dom-publickeycredential-authenticatorattachment
+    fn GetAuthenticatorAttachment(&self) -> Option<DOMString> {
+        Some(DOMString::from("platform"))
+    }
+
+    // https://w3c.github.io/webauthn/#dom-publickeycredential-getclientextensionresults
+    fn GetClientExtensionResults(&self) -> AuthenticationExtensionsClientOutputs {
+        AuthenticationExtensionsClientOutputs::empty()
+    }
+}
@@ -22,13 +22,17 @@ use crate::dom::bindings::weakref::{WeakRef, WeakRefVec};
 use crate::dom::characterdata::CharacterData;
 use crate::dom::document::Document;
 use crate::dom::documentfragment::DocumentFragment;
+use crate::dom::domrect::DOMRect;
 use crate::dom::element::Element;
 use crate::dom::htmlscriptelement::HTMLScriptElement;
-use crate::dom::node::{Node, ShadowIncluding, UnbindContext};
+use crate::dom::node::{window_from_node, Node, ShadowIncluding, UnbindContext};
 use crate::dom::selection::Selection;
 use crate::dom::text::Text;
 use crate::dom::window::Window;
+use crate::editing;
+use app_units::Au;
 use dom_struct::dom_struct;
+use euclid::default::Rect;
 use js::jsapi::JSTracer;
 use malloc_size_of::{MallocSizeOf, MallocSizeOfOps};
 use std::cell::{Cell, UnsafeCell};
@@ -265,6 +269,87 @@ impl Range {
             .borrow()
             .iter()
             .for_each(|s| s.queue_selectionchange_task());
+        self.StartContainer().owner_doc().note_range_change();
+    }
+
+    /// The boxes of the contents of the range, in the coordinate space of `getClientRects()`.
+    /// <https://drafts.csswg.org/cssom-view/#dom-range-getclientrects>
+    fn client_rects(&self) -> Vec<Rect<Au>> {
+        let start_node = self.StartContainer();
+
+        // Step 1.
+        if !start_node.is_connected() {
+            return vec![];
+        }
+
+        // Step 2.
+        let ancestor = self.CommonAncestorContainer();
+        let mut rects: Vec<_> = start_node
+            .following_nodes(&ancestor)
+            .filter(|node| {
+                node.is::<Element>() &&
+                    self.contains(node) &&
+                    !node
+                        .GetParentNode()
+                        .map_or(false, |parent| self.contains(&parent))
+            })
+            .flat_map(|element| element.content_boxes())
+            .collect();
+
+        // Step 3.
+        let window = window_from_node(&*start_node);
+        for (text, start, end) in self.selected_text() {
+            // Layout indexes text by bytes.
+            let (start, end) = {
+                let data = text.upcast::<CharacterData>().data();
+                (
+                    editing::utf16_to_utf8_offset(&data, start),
+                    editing::utf16_to_utf8_offset(&data, end),
+                )
+            };
+            rects.extend(window.text_boxes_query(text.upcast(), start, end));
+        }
+        rects
+    }
+
+    /// The text nodes that the range selects or partially selects in tree order, with the
+    /// UTF-16 offsets of the part of their data that it selects.
+    pub fn selected_text(&self) -> Vec<(DomRoot<Text>, u32, u32)> {
+        let start_node = self.StartContainer();
+        let end_node = self.EndContainer();
+        let mut selected = vec![];
+
+        if let Some(text) = start_node.downcast::<Text>() {
+            if start_node == end_node {
+                return vec![(
+                    DomRoot::from_ref(text),
+                    self.StartOffset(),
+                    self.EndOffset(),
+                )];
+            }
+            selected.push((
+                DomRoot::from_ref(text),
+                self.StartOffset(),
+                start_node.len(),
+            ));
+        }
+
+        let ancestor = self.CommonAncestorContainer();
+        selected.extend(
+            start_node
+                .following_nodes(&ancestor)
+                .filter(|node| self.contains(node))
+                .filter_map(DomRoot::downcast::<Text>)
+                .map(|text| {
+                    let length = text.upcast::<Node>().len();
+                    (text, 0, length)
+                }),
+        );
+
+        if let Some(text) = end_node.downcast::<Text>() {
+            selected.push((DomRoot::from_ref(text), 0, self.EndOffset()));
+        }
+        selected
     }
 }
 
@@ -1051,6 +1136,46 @@ impl RangeMethods for Range {
         // Step 5.
         Ok(fragment_node)
     }
+
+    // https://drafts.csswg.org/cssom-view/#dom-range-getclientrects
+    fn GetClientRects(&self) -> Vec<DomRoot<DOMRect>> {
+        let window = window_from_node(&*self.StartContainer());
+        self.client_rects()
+            .iter()
+            .map(|rect| {
+                DOMRect::new(
+                    window.upcast(),
+                    rect.origin.x.to_f64_px(),
+                    rect.origin.y.to_f64_px(),
+                    rect.size.width.to_f64_px(),
+                    rect.size.height.to_f64_px(),
+                )
+            })
+            .collect()
+    }
+
+    // https://drafts.csswg.org/cssom-view/#dom-range-getboundingclientrect
+    fn GetBoundingClientRect(&self) -> DomRoot<DOMRect> {
+        let window = window_from_node(&*self.StartContainer());
+        let rects = self.client_rects();
+
+        // Boxes without an area don't count, unless all of them are like that.
+        let rect = rects
+            .iter()
+            .filter(|rect| !rect.is_empty())
+            .fold(None, |bounds: Option<Rect<Au>>, rect| {
+                Some(bounds.map_or(*rect, |bounds| bounds.union(rect)))
+            })
+            .or_else(|| rects.first().cloned())
+            .unwrap_or_else(Rect::zero);
+        DOMRect::new(
+            window.upcast(),
+            rect.origin.x.to_f64_px(),
+            rect.origin.y.to_f64_px(),
+            rect.size.width.to_f64_px(),
+            rect.size.height.to_f64_px(),
+        )
+    }
 }
 
 #[derive(DenyPublicFields, JSTraceable, MallocSizeOf)]
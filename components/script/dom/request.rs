@@ -13,6 +13,7 @@ use crate::dom::bindings::codegen::Bindings::RequestBinding::RequestInfo;
 use crate::dom::bindings::codegen::Bindings::RequestBinding::RequestInit;
 use crate::dom::bindings::codegen::Bindings::RequestBinding::RequestMethods;
 use crate::dom::bindings::codegen::Bindings::RequestBinding::RequestMode;
+use crate::dom::bindings::codegen::Bindings::RequestBinding::RequestPriority;
 use crate::dom::bindings::codegen::Bindings::RequestBinding::RequestRedirect;
 use crate::dom::bindings::error::{Error, Fallible};
 use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
@@ -34,6 +35,7 @@ use net_traits::request::RedirectMode as NetTraitsRequestRedirect;
 use net_traits::request::Referrer as NetTraitsRequestReferrer;
 use net_traits::request::Request as NetTraitsRequest;
 use net_traits::request::RequestMode as NetTraitsRequestMode;
+use net_traits::request::RequestPriority as NetTraitsRequestPriority;
 use net_traits::request::{Origin, Window};
 use net_traits::ReferrerPolicy as MsgReferrerPolicy;
 use servo_url::ServoUrl;
@@ -159,6 +161,8 @@ impl Request {
         request.cache_mode = temporary_request.cache_mode;
         request.redirect_mode = temporary_request.redirect_mode;
         request.integrity_metadata = temporary_request.integrity_metadata;
+        request.keep_alive = temporary_request.keep_alive;
+        request.priority = temporary_request.priority;
 
         // Step 14
         if init.body.is_some() ||
@@ -166,8 +170,10 @@ impl Request {
             init.credentials.is_some() ||
             init.integrity.is_some() ||
             init.headers.is_some() ||
+            init.keepalive.is_some() ||
             init.method.is_some() ||
             init.mode.is_some() ||
+            init.priority.is_some() ||
             init.redirect.is_some() ||
             init.referrer.is_some() ||
             init.referrerPolicy.is_some() ||
@@ -277,7 +283,15 @@ impl Request {
             request.integrity_metadata = integrity;
         }
 
-        // Step 26 TODO: "If init["keepalive"] exists..."
+        // Step 26
+        if let Some(keep_alive) = init.keepalive {
+            request.keep_alive = keep_alive;
+        }
+
+        // "If init["priority"] exists, ... set request's priority to init["priority"]."
+        if let Some(priority) = init.priority {
+            request.priority = priority.into();
+        }
 
         // Step 27.1
         if let Some(init_method) = init.method.as_ref() {
@@ -637,6 +651,11 @@ impl RequestMethods for Request {
         DOMString::from_string(r.integrity_metadata.clone())
     }
 
+    // https://fetch.spec.whatwg.org/#dom-request-keepalive
+    fn Keepalive(&self) -> bool {
+        self.request.borrow().keep_alive
+    }
+
     // https://fetch.spec.whatwg.org/#dom-body-bodyused
     fn BodyUsed(&self) -> bool {
         self.body_used.get()
@@ -734,6 +753,16 @@ impl Into<RequestCache> for NetTraitsRequestCache {
     }
 }
 
+impl Into<NetTraitsRequestPriority> for RequestPriority {
+    fn into(self) -> NetTraitsRequestPriority {
+        match self {
+            RequestPriority::High => NetTraitsRequestPriority::High,
+            RequestPriority::Low => NetTraitsRequestPriority::Low,
+            RequestPriority::Auto => NetTraitsRequestPriority::Auto,
+        }
+    }
+}
+
 impl Into<NetTraitsRequestCredentials> for RequestCredentials {
     fn into(self) -> NetTraitsRequestCredentials {
         match self {
@@ -19,12 +19,13 @@ use crate::dom::headers::{is_obs_text, is_vchar};
 use crate::dom::headers::{Guard, Headers};
 use crate::dom::promise::Promise;
 use crate::dom::xmlhttprequest::Extractable;
-use crate::script_runtime::StreamConsumer;
+use crate::script_runtime::{StreamConsumer, StreamConsumerError};
 use dom_struct::dom_struct;
 use http::header::HeaderMap as HyperHeaders;
 use hyper::StatusCode;
 use hyper_serde::Serde;
 use net_traits::response::ResponseBody as NetTraitsResponseBody;
+use net_traits::NetworkError;
 use servo_url::ServoUrl;
 use std::cell::Cell;
 use std::mem;
@@ -442,24 +443,71 @@ impl Response {
         }
     }
 
-    pub fn set_stream_consumer(&self, sc: Option<StreamConsumer>) {
-        *self.stream_consumer.borrow_mut() = sc;
+    /// Hands the body of this response to the consumer of a WebAssembly compilation, which
+    /// disturbs the body. The bytes received so far are consumed right away, and the ones
+    /// received later as they arrive.
+    pub fn set_stream_consumer(&self, stream_consumer: StreamConsumer) {
+        self.body_used.set(true);
+        match *self.body.borrow() {
+            NetTraitsResponseBody::Receiving(ref bytes) => {
+                if !bytes.is_empty() && !stream_consumer.consume_chunk(bytes) {
+                    return;
+                }
+            },
+            NetTraitsResponseBody::Done(ref bytes) => {
+                if stream_consumer.consume_chunk(bytes) {
+                    stream_consumer.stream_end();
+                }
+                return;
+            },
+            NetTraitsResponseBody::Empty => {
+                stream_consumer.stream_end();
+                return;
+            },
+        }
+        *self.stream_consumer.borrow_mut() = Some(stream_consumer);
     }
 
-    pub fn stream_chunk(&self, stream: &[u8]) {
-        if let Some(stream_consumer) = self.stream_consumer.borrow_mut().as_ref() {
-            stream_consumer.consume_chunk(stream);
+    /// Marks the body as being received from the network, until `finish` is called.
+    pub fn start_receiving_body(&self) {
+        let mut body = self.body.borrow_mut();
+        if *body == NetTraitsResponseBody::Empty {
+            *body = NetTraitsResponseBody::Receiving(vec![]);
+        }
+    }
+
+    pub fn stream_chunk(&self, chunk: &[u8]) {
+        if let NetTraitsResponseBody::Receiving(ref mut bytes) = *self.body.borrow_mut() {
+            bytes.extend_from_slice(chunk);
+        }
+        let mut stream_consumer = self.stream_consumer.borrow_mut();
+        let consumed = match *stream_consumer {
+            Some(ref consumer) => consumer.consume_chunk(chunk),
+            None => return,
+        };
+        // The compilation failed, and doesn't need the rest of the body anymore.
+        if !consumed {
+            *stream_consumer = None;
         }
     }
 
     #[allow(unrooted_must_root)]
-    pub fn finish(&self, body: Vec<u8>) {
-        *self.body.borrow_mut() = NetTraitsResponseBody::Done(body);
+    pub fn finish(&self, result: Result<(), NetworkError>) {
+        {
+            let mut body = self.body.borrow_mut();
+            if let NetTraitsResponseBody::Receiving(ref mut bytes) = *body {
+                let bytes = mem::replace(bytes, vec![]);
+                *body = NetTraitsResponseBody::Done(bytes);
+            }
+        }
         if let Some((p, body_type)) = self.body_promise.borrow_mut().take() {
             consume_body_with_promise(self, body_type, &p);
         }
         if let Some(stream_consumer) = self.stream_consumer.borrow_mut().take() {
-            stream_consumer.stream_end();
+            match result {
+                Ok(()) => stream_consumer.stream_end(),
+                Err(_) => stream_consumer.stream_error(StreamConsumerError::Network as usize),
+            }
         }
     }
 }
@@ -0,0 +1,406 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::BlobBinding::BlobMethods;
+use crate::dom::bindings::codegen::Bindings::RTCDataChannelBinding::RTCDataChannelMethods;
+use crate::dom::bindings::codegen::Bindings::RTCDataChannelBinding::{
+    RTCDataChannelInit, RTCDataChannelState,
+};
+use crate::dom::bindings::codegen::Bindings::WebSocketBinding::BinaryType;
+use crate::dom::bindings::conversions::ToJSValConvertible;
+use crate::dom::bindings::error::{Error, ErrorResult};
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::refcounted::Trusted;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::str::USVString;
+use crate::dom::blob::Blob;
+use crate::dom::event::{Event, EventBubbles, EventCancelable};
+use crate::dom::eventtarget::EventTarget;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::messageevent::MessageEvent;
+use crate::dom::rtcpeerconnection::RTCPeerConnection;
+use crate::task_source::TaskSource;
+use dom_struct::dom_struct;
+use js::jsapi::{JSAutoRealm, JSObject};
+use js::jsval::UndefinedValue;
+use js::rust::CustomAutoRooterGuard;
+use js::typedarray::{ArrayBuffer, ArrayBufferView, CreateWith};
+use script_traits::serializable::BlobImpl;
+use servo_atoms::Atom;
+use servo_media::webrtc::{
+    DataChannelId, DataChannelInit, DataChannelMessage, DataChannelState, WebRtcError,
+};
+use std::cell::Cell;
+use std::ptr;
+
+#[dom_struct]
+pub struct RTCDataChannel {
+    eventtarget: EventTarget,
+    servo_media_id: DataChannelId,
+    peer_connection: Dom<RTCPeerConnection>,
+    label: USVString,
+    ordered: bool,
+    max_packet_life_time: Option<u16>,
+    max_retransmits: Option<u16>,
+    protocol: USVString,
+    negotiated: bool,
+    id: Option<u16>,
+    ready_state: Cell<RTCDataChannelState>,
+    /// The bytes of the messages that were sent, but not yet handed to the SCTP transport.
+    buffered_amount: Cell<u32>,
+    buffered_amount_low_threshold: Cell<u32>,
+    binary_type: Cell<BinaryType>,
+}
+
+impl RTCDataChannel {
+    #[allow(unrooted_must_root)]
+    fn new_inherited(
+        peer_connection: &RTCPeerConnection,
+        label: USVString,
+        options: &RTCDataChannelInit,
+        servo_media_id: Option<DataChannelId>,
+    ) -> RTCDataChannel {
+        // Channels announced by the remote peer are already known to the backend, the others
+        // are created now.
+        let servo_media_id = servo_media_id.unwrap_or_else(|| {
+            let init = DataChannelInit {
+                label: label.0.clone(),
+                ordered: options.ordered,
+                max_packet_life_time: options.maxPacketLifeTime,
+                max_retransmits: options.maxRetransmits,
+                protocol: options.protocol.0.clone(),
+                negotiated: options.negotiated,
+                id: options.id,
+            };
+            peer_connection
+                .get_webrtc_controller()
+                .borrow()
+                .as_ref()
+                .unwrap()
+                .create_data_channel(&init)
+                .expect("Expected data channel id")
+        });
+
+        RTCDataChannel {
+            eventtarget: EventTarget::new_inherited(),
+            servo_media_id,
+            peer_connection: Dom::from_ref(peer_connection),
+            label,
+            ordered: options.ordered,
+            max_packet_life_time: options.maxPacketLifeTime,
+            max_retransmits: options.maxRetransmits,
+            protocol: options.protocol.clone(),
+            negotiated: options.negotiated,
+            id: options.id,
+            ready_state: Cell::new(RTCDataChannelState::Connecting),
+            buffered_amount: Cell::new(0),
+            buffered_amount_low_threshold: Cell::new(0),
+            binary_type: Cell::new(BinaryType::Blob),
+        }
+    }
+
+    pub fn new(
+        global: &GlobalScope,
+        peer_connection: &RTCPeerConnection,
+        label: USVString,
+        options: &RTCDataChannelInit,
+        servo_media_id: Option<DataChannelId>,
+    ) -> DomRoot<RTCDataChannel> {
+        let channel = reflect_dom_object(
+            Box::new(RTCDataChannel::new_inherited(
+                peer_connection,
+                label,
+                options,
+                servo_media_id,
+            )),
+            global,
+        );
+        peer_connection.register_data_channel(channel.servo_media_id, &channel);
+        channel
+    }
+
+    fn fire_simple_event(&self, name: Atom) {
+        let event = Event::new(
+            &self.global(),
+            name,
+            EventBubbles::DoesNotBubble,
+            EventCancelable::NotCancelable,
+        );
+        event.upcast::<Event>().fire(self.upcast());
+    }
+
+    /// <https://w3c.github.io/webrtc-pc/#announcing-a-data-channel-as-open>
+    pub fn on_open(&self) {
+        if self.ready_state.get() != RTCDataChannelState::Connecting {
+            return;
+        }
+        self.ready_state.set(RTCDataChannelState::Open);
+        self.fire_simple_event(atom!("open"));
+    }
+
+    /// <https://w3c.github.io/webrtc-pc/#announcing-a-data-channel-as-closed>
+    pub fn on_close(&self) {
+        if self.ready_state.get() == RTCDataChannelState::Closed {
+            return;
+        }
+        self.ready_state.set(RTCDataChannelState::Closed);
+        self.peer_connection
+            .unregister_data_channel(&self.servo_media_id);
+        self.fire_simple_event(atom!("close"));
+    }
+
+    /// <https://w3c.github.io/webrtc-pc/#transport-independent-data-channel-closure>
+    pub fn on_error(&self, error: WebRtcError) {
+        warn!("Data channel error {:?}", error);
+        self.fire_simple_event(atom!("error"));
+    }
+
+    pub fn on_state_change(&self, state: DataChannelState) {
+        match state {
+            DataChannelState::Open => self.on_open(),
+            DataChannelState::Closed => self.on_close(),
+            // https://w3c.github.io/webrtc-pc/#data-transport-closing-procedure
+            DataChannelState::Closing => {
+                if self.ready_state.get() == RTCDataChannelState::Closing ||
+                    self.ready_state.get() == RTCDataChannelState::Closed
+                {
+                    return;
+                }
+                self.ready_state.set(RTCDataChannelState::Closing);
+                self.fire_simple_event(atom!("closing"));
+            },
+            _ => {},
+        }
+    }
+
+    /// <https://w3c.github.io/webrtc-pc/#receiving-messages-on-a-data-channel>
+    #[allow(unsafe_code)]
+    pub fn on_message(&self, message: DataChannelMessage) {
+        if self.ready_state.get() != RTCDataChannelState::Open {
+            return;
+        }
+
+        let global = self.global();
+        // global.get_cx() returns a valid `JSContext` pointer, so this is safe.
+        unsafe {
+            let cx = global.get_cx();
+            let _ac = JSAutoRealm::new(*cx, self.reflector().get_jsobject().get());
+            rooted!(in(*cx) let mut message_value = UndefinedValue());
+            match message {
+                DataChannelMessage::Text(text) => {
+                    text.to_jsval(*cx, message_value.handle_mut());
+                },
+                DataChannelMessage::Binary(data) => match self.binary_type.get() {
+                    BinaryType::Blob => {
+                        let blob =
+                            Blob::new(&global, BlobImpl::new_from_bytes(data, "".to_owned()));
+                        blob.to_jsval(*cx, message_value.handle_mut());
+                    },
+                    BinaryType::Arraybuffer => {
+                        rooted!(in(*cx) let mut array_buffer = ptr::null_mut::<JSObject>());
+                        assert!(ArrayBuffer::create(
+                            *cx,
+                            CreateWith::Slice(&data),
+                            array_buffer.handle_mut()
+                        )
+                        .is_ok());
+
+                        (*array_buffer).to_jsval(*cx, message_value.handle_mut());
+                    },
+                },
+            }
+            MessageEvent::dispatch_jsval(
+                self.upcast(),
+                &global,
+                message_value.handle(),
+                Some(&global.origin().immutable().ascii_serialization()),
+                None,
+                vec![],
+            );
+        }
+    }
+
+    /// <https://w3c.github.io/webrtc-pc/#dom-rtcdatachannel-send>
+    fn send(&self, message: DataChannelMessage, byte_length: usize) -> ErrorResult {
+        if self.ready_state.get() != RTCDataChannelState::Open {
+            return Err(Error::InvalidState);
+        }
+
+        let byte_length = byte_length as u32;
+        self.buffered_amount
+            .set(self.buffered_amount.get().saturating_add(byte_length));
+        self.peer_connection
+            .get_webrtc_controller()
+            .borrow()
+            .as_ref()
+            .unwrap()
+            .send_data_channel_message(&self.servo_media_id, message);
+
+        // The backend hands the message to the SCTP transport right away, which is as far as
+        // the buffer can be observed from here.
+        let this = Trusted::new(self);
+        let (task_source, canceller) = self
+            .global()
+            .as_window()
+            .task_manager()
+            .networking_task_source_with_canceller();
+        let _ = task_source.queue_with_canceller(
+            task!(data_channel_message_sent: move || {
+                this.root().message_sent(byte_length);
+            }),
+            &canceller,
+        );
+        Ok(())
+    }
+
+    /// Removes a message from the buffered amount, firing `bufferedamountlow` when the amount
+    /// falls to the threshold.
+    /// <https://w3c.github.io/webrtc-pc/#dom-rtcdatachannel-bufferedamount>
+    fn message_sent(&self, byte_length: u32) {
+        let old_amount = self.buffered_amount.get();
+        let new_amount = old_amount.saturating_sub(byte_length);
+        self.buffered_amount.set(new_amount);
+        let threshold = self.buffered_amount_low_threshold.get();
+        if old_amount > threshold && new_amount <= threshold {
+            self.fire_simple_event(atom!("bufferedamountlow"));
+        }
+    }
+
+    /// Marks the channel as closed without firing events, when its peer connection is closed.
+    pub fn close_silently(&self) {
+        self.ready_state.set(RTCDataChannelState::Closed);
+    }
+}
+
+impl RTCDataChannelMethods for RTCDataChannel {
+    // https://w3c.github.io/webrtc-pc/#dom-rtcdatachannel-onopen
+    event_handler!(open, GetOnopen, SetOnopen);
+
+    // https://w3c.github.io/webrtc-pc/#dom-rtcdatachannel-onbufferedamountlow
+    event_handler!(
+        bufferedamountlow,
+        GetOnbufferedamountlow,
+        SetOnbufferedamountlow
+    );
+
+    // https://w3c.github.io/webrtc-pc/#dom-rtcdatachannel-onerror
+    event_handler!(error, GetOnerror, SetOnerror);
+
+    // https://w3c.github.io/webrtc-pc/#dom-rtcdatachannel-onclosing
+    event_handler!(closing, GetOnclosing, SetOnclosing);
+
+    // https://w3c.github.io/webrtc-pc/#dom-rtcdatachannel-onclose
+    event_handler!(close, GetOnclose, SetOnclose);
+
+    // https://w3c.github.io/webrtc-pc/#dom-rtcdatachannel-onmessage
+    event_handler!(message, GetOnmessage, SetOnmessage);
+
+    // https://w3c.github.io/webrtc-pc/#dom-datachannel-label
+    fn Label(&self) -> USVString {
+        self.label.clone()
+    }
+
+    // https://w3c.github.io/webrtc-pc/#dom-datachannel-ordered
+    fn Ordered(&self) -> bool {
+        self.ordered
+    }
+
+    // https://w3c.github.io/webrtc-pc/#dom-datachannel-maxpacketlifetime
+    fn GetMaxPacketLifeTime(&self) -> Option<u16> {
+        self.max_packet_life_time
+    }
+
+    // https://w3c.github.io/webrtc-pc/#dom-datachannel-maxretransmits
+    fn GetMaxRetransmits(&self) -> Option<u16> {
+        self.max_retransmits
+    }
+
+    // https://w3c.github.io/webrtc-pc/#dom-datachannel-protocol
+    fn Protocol(&self) -> USVString {
+        self.protocol.clone()
+    }
+
+    // https://w3c.github.io/webrtc-pc/#dom-datachannel-negotiated
+    fn Negotiated(&self) -> bool {
+        self.negotiated
+    }
+
+    // https://w3c.github.io/webrtc-pc/#dom-rtcdatachannel-id
+    fn GetId(&self) -> Option<u16> {
+        self.id
+    }
+
+    // https://w3c.github.io/webrtc-pc/#dom-datachannel-readystate
+    fn ReadyState(&self) -> RTCDataChannelState {
+        self.ready_state.get()
+    }
+
+    // https://w3c.github.io/webrtc-pc/#dom-datachannel-bufferedamount
+    fn BufferedAmount(&self) -> u32 {
+        self.buffered_amount.get()
+    }
+
+    // https://w3c.github.io/webrtc-pc/#dom-datachannel-bufferedamountlowthreshold
+    fn BufferedAmountLowThreshold(&self) -> u32 {
+        self.buffered_amount_low_threshold.get()
+    }
+
+    // https://w3c.github.io/webrtc-pc/#dom-datachannel-bufferedamountlowthreshold
+    fn SetBufferedAmountLowThreshold(&self, value: u32) {
+        self.buffered_amount_low_threshold.set(value);
+    }
+
+    // https://w3c.github.io/webrtc-pc/#dom-rtcdatachannel-close
+    fn Close(&self) {
+        match self.ready_state.get() {
+            RTCDataChannelState::Closing | RTCDataChannelState::Closed => return,
+            _ => {},
+        }
+        self.ready_state.set(RTCDataChannelState::Closing);
+        self.peer_connection
+            .get_webrtc_controller()
+            .borrow()
+            .as_ref()
+            .unwrap()
+            .close_data_channel(&self.servo_media_id);
+    }
+
+    // https://w3c.github.io/webrtc-pc/#dom-datachannel-binarytype
+    fn BinaryType(&self) -> BinaryType {
+        self.binary_type.get()
+    }
+
+    // https://w3c.github.io/webrtc-pc/#dom-datachannel-binarytype
+    fn SetBinaryType(&self, value: BinaryType) {
+        self.binary_type.set(value);
+    }
+
+    // https://w3c.github.io/webrtc-pc/#dom-rtcdatachannel-send
+    fn Send(&self, data: USVString) -> ErrorResult {
+        let byte_length = data.0.len();
+        self.send(DataChannelMessage::Text(data.0), byte_length)
+    }
+
+    // https://w3c.github.io/webrtc-pc/#dom-rtcdatachannel-send!overload-1
+    fn Send_(&self, data: &Blob) -> ErrorResult {
+        let byte_length = data.Size() as usize;
+        let bytes = data.get_bytes().unwrap_or(vec![]);
+        self.send(DataChannelMessage::Binary(bytes), byte_length)
+    }
+
+    // https://w3c.github.io/webrtc-pc/#dom-rtcdatachannel-send!overload-2
+    fn Send__(&self, data: CustomAutoRooterGuard<ArrayBuffer>) -> ErrorResult {
+        let bytes = data.to_vec();
+        let byte_length = bytes.len();
+        self.send(DataChannelMessage::Binary(bytes), byte_length)
+    }
+
+    // https://w3c.github.io/webrtc-pc/#dom-rtcdatachannel-send!overload-3
+    fn Send___(&self, data: CustomAutoRooterGuard<ArrayBufferView>) -> ErrorResult {
+        let bytes = data.to_vec();
+        let byte_length = bytes.len();
+        self.send(DataChannelMessage::Binary(bytes), byte_length)
+    }
+}
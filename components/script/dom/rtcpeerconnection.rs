@@ -3,6 +3,7 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::RTCDataChannelBinding::RTCDataChannelInit;
 use crate::dom::bindings::codegen::Bindings::RTCIceCandidateBinding::RTCIceCandidateInit;
 use crate::dom::bindings::codegen::Bindings::RTCPeerConnectionBinding::RTCPeerConnectionMethods;
 use crate::dom::bindings::codegen::Bindings::RTCPeerConnectionBinding::{
@@ -19,16 +20,20 @@ use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::refcounted::{Trusted, TrustedPromise};
 use crate::dom::bindings::reflector::reflect_dom_object;
 use crate::dom::bindings::reflector::DomObject;
-use crate::dom::bindings::root::{DomRoot, MutNullableDom};
+use crate::dom::bindings::root::{Dom, DomRoot, MutNullableDom};
+use crate::dom::bindings::str::USVString;
 use crate::dom::event::{Event, EventBubbles, EventCancelable};
 use crate::dom::eventtarget::EventTarget;
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::mediastream::MediaStream;
 use crate::dom::mediastreamtrack::MediaStreamTrack;
 use crate::dom::promise::Promise;
+use crate::dom::rtcdatachannel::RTCDataChannel;
+use crate::dom::rtcdatachannelevent::RTCDataChannelEvent;
 use crate::dom::rtcicecandidate::RTCIceCandidate;
 use crate::dom::rtcpeerconnectioniceevent::RTCPeerConnectionIceEvent;
 use crate::dom::rtcsessiondescription::RTCSessionDescription;
+use crate::dom::rtcstatsreport::RTCStatsReport;
 use crate::dom::rtctrackevent::RTCTrackEvent;
 use crate::dom::window::Window;
 use crate::realms::InRealm;
@@ -40,12 +45,14 @@ use dom_struct::dom_struct;
 use servo_media::streams::registry::MediaStreamId;
 use servo_media::streams::MediaStreamType;
 use servo_media::webrtc::{
-    BundlePolicy, GatheringState, IceCandidate, IceConnectionState, SdpType, SessionDescription,
-    SignalingState, WebRtcController, WebRtcSignaller,
+    BundlePolicy, DataChannelEvent, DataChannelId, GatheringState, IceCandidate,
+    IceConnectionState, RtcStats, RtcStatsType, SdpType, SessionDescription, SignalingState,
+    WebRtcController, WebRtcSignaller,
 };
 use servo_media::ServoMedia;
 
 use std::cell::Cell;
+use std::collections::HashMap;
 use std::rc::Rc;
 
 #[dom_struct]
@@ -66,6 +73,7 @@ pub struct RTCPeerConnection {
     gathering_state: Cell<RTCIceGatheringState>,
     ice_connection_state: Cell<RTCIceConnectionState>,
     signaling_state: Cell<RTCSignalingState>,
+    data_channels: DomRefCell<HashMap<DataChannelId, Dom<RTCDataChannel>>>,
 }
 
 struct RTCSignaller {
@@ -142,6 +150,22 @@ impl WebRtcSignaller for RTCSignaller {
         );
     }
 
+    fn on_data_channel_event(
+        &self,
+        channel: DataChannelId,
+        event: DataChannelEvent,
+        _: &WebRtcController,
+    ) {
+        let this = self.trusted.clone();
+        let _ = self.task_source.queue_with_canceller(
+            task!(on_data_channel_event: move || {
+                let this = this.root();
+                this.on_data_channel_event(channel, event);
+            }),
+            &self.canceller,
+        );
+    }
+
     fn close(&self) {
         // do nothing
     }
@@ -161,6 +185,7 @@ impl RTCPeerConnection {
             gathering_state: Cell::new(RTCIceGatheringState::New),
             ice_connection_state: Cell::new(RTCIceConnectionState::New),
             signaling_state: Cell::new(RTCSignalingState::Stable),
+            data_channels: DomRefCell::new(HashMap::new()),
         }
     }
 
@@ -213,6 +238,25 @@ impl RTCPeerConnection {
         })
     }
 
+    pub fn get_webrtc_controller(&self) -> &DomRefCell<Option<WebRtcController>> {
+        &self.controller
+    }
+
+    pub fn register_data_channel(&self, id: DataChannelId, channel: &RTCDataChannel) {
+        if self
+            .data_channels
+            .borrow_mut()
+            .insert(id, Dom::from_ref(channel))
+            .is_some()
+        {
+            warn!("Data channel {} already registered", id);
+        }
+    }
+
+    pub fn unregister_data_channel(&self, id: &DataChannelId) {
+        self.data_channels.borrow_mut().remove(id);
+    }
+
     fn on_ice_candidate(&self, candidate: IceCandidate) {
         if self.closed.get() {
             return;
@@ -256,6 +300,53 @@ impl RTCPeerConnection {
         event.upcast::<Event>().fire(self.upcast());
     }
 
+    /// https://w3c.github.io/webrtc-pc/#announcing-a-data-channel-to-the-remote-peer
+    fn on_data_channel_event(&self, channel_id: DataChannelId, event: DataChannelEvent) {
+        if self.closed.get() {
+            return;
+        }
+
+        if let DataChannelEvent::NewChannel = event {
+            // The label and the options of channels that the remote peer opened are not exposed
+            // by the backend yet.
+            let channel = RTCDataChannel::new(
+                &self.global(),
+                self,
+                USVString::from("".to_owned()),
+                &RTCDataChannelInit::empty(),
+                Some(channel_id),
+            );
+            let event = RTCDataChannelEvent::new(
+                &self.global(),
+                atom!("datachannel"),
+                false,
+                false,
+                &channel,
+            );
+            event.upcast::<Event>().fire(self.upcast());
+            return;
+        }
+
+        let channel = match self.data_channels.borrow().get(&channel_id) {
+            Some(channel) => DomRoot::from_ref(&**channel),
+            None => {
+                warn!(
+                    "Got an event for an unregistered data channel {}",
+                    channel_id
+                );
+                return;
+            },
+        };
+        match event {
+            DataChannelEvent::Open => channel.on_open(),
+            DataChannelEvent::Close => channel.on_close(),
+            DataChannelEvent::Error(error) => channel.on_error(error),
+            DataChannelEvent::OnMessage(message) => channel.on_message(message),
+            DataChannelEvent::StateChange(state) => channel.on_state_change(state),
+            DataChannelEvent::NewChannel => unreachable!(),
+        }
+    }
+
     /// https://www.w3.org/TR/webrtc/#update-ice-gathering-state
     fn update_gathering_state(&self, state: GatheringState) {
         // step 1
@@ -448,6 +539,9 @@ impl RTCPeerConnectionMethods for RTCPeerConnection {
         SetOnsignalingstatechange
     );
 
+    // https://w3c.github.io/webrtc-pc/#dom-rtcpeerconnection-ondatachannel
+    event_handler!(datachannel, GetOndatachannel, SetOndatachannel);
+
     /// https://w3c.github.io/webrtc-pc/#dom-rtcpeerconnection-addicecandidate
     fn AddIceCandidate(&self, candidate: &RTCIceCandidateInit, comp: InRealm) -> Rc<Promise> {
         let p = Promise::new_in_current_realm(&self.global(), comp);
@@ -583,6 +677,86 @@ impl RTCPeerConnectionMethods for RTCPeerConnection {
         p
     }
 
+    /// https://w3c.github.io/webrtc-pc/#dom-peerconnection-createdatachannel
+    fn CreateDataChannel(
+        &self,
+        label: USVString,
+        init: &RTCDataChannelInit,
+    ) -> Fallible<DomRoot<RTCDataChannel>> {
+        // Step 3
+        if self.closed.get() {
+            return Err(Error::InvalidState);
+        }
+
+        // Steps 6-8
+        if label.0.len() > 65535 || init.protocol.0.len() > 65535 {
+            return Err(Error::Type("label or protocol is too long".to_owned()));
+        }
+
+        // Step 11
+        if init.negotiated && init.id.is_none() {
+            return Err(Error::Type("negotiated channels need an id".to_owned()));
+        }
+
+        // Step 13
+        if init.maxPacketLifeTime.is_some() && init.maxRetransmits.is_some() {
+            return Err(Error::Type(
+                "maxPacketLifeTime and maxRetransmits are exclusive".to_owned(),
+            ));
+        }
+
+        // Step 17
+        if init.id == Some(65535) {
+            return Err(Error::Type("id must be below 65535".to_owned()));
+        }
+
+        Ok(RTCDataChannel::new(&self.global(), self, label, init, None))
+    }
+
+    /// https://w3c.github.io/webrtc-pc/#dom-rtcpeerconnection-getstats
+    fn GetStats(&self, selector: Option<&MediaStreamTrack>, comp: InRealm) -> Rc<Promise> {
+        let p = Promise::new_in_current_realm(&self.global(), comp);
+        if self.closed.get() {
+            p.reject_error(Error::InvalidState);
+            return p;
+        }
+
+        // The stats of the RTP streams of other tracks are left out of the report of a track,
+        // along with the transports of those streams, which are not tracked by the backend.
+        let selector = selector.map(|track| track.id());
+        let trusted_promise = TrustedPromise::new(p.clone());
+        let (task_source, canceller) = self
+            .global()
+            .as_window()
+            .task_manager()
+            .networking_task_source_with_canceller();
+        self.controller.borrow().as_ref().unwrap().get_stats(
+            (move |stats: Vec<RtcStats>| {
+                let stats: Vec<RtcStats> = match selector {
+                    Some(selector) => stats
+                        .into_iter()
+                        .filter(|stats| match stats.type_ {
+                            RtcStatsType::InboundRtp { track, .. } |
+                            RtcStatsType::OutboundRtp { track, .. } => track == Some(selector),
+                            RtcStatsType::CandidatePair { .. } => false,
+                        })
+                        .collect(),
+                    None => stats,
+                };
+                let _ = task_source.queue_with_canceller(
+                    task!(stats_received: move || {
+                        let promise = trusted_promise.root();
+                        let report = RTCStatsReport::new(&promise.global(), stats);
+                        promise.resolve_native(&report);
+                    }),
+                    &canceller,
+                );
+            })
+            .into(),
+        );
+        p
+    }
+
     // https://w3c.github.io/webrtc-pc/#legacy-interface-extensions
     fn AddStream(&self, stream: &MediaStream) {
         for track in &*stream.get_tracks() {
@@ -624,8 +798,14 @@ impl RTCPeerConnectionMethods for RTCPeerConnection {
         // Step 5 handled by backend
         self.controller.borrow_mut().as_ref().unwrap().quit();
 
-        // Step 6-10
-        // (no current support for data channels, transports, etc)
+        // Step 6-9
+        // (no current support for transceivers, transports, etc)
+
+        // Step 10
+        for channel in self.data_channels.borrow().values() {
+            channel.close_silently();
+        }
+        self.data_channels.borrow_mut().clear();
 
         // Step 11
         self.ice_connection_state.set(RTCIceConnectionState::Closed);
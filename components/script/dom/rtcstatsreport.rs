@@ -0,0 +1,179 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::RTCStatsReportBinding::RTCStatsReportMethods;
+use crate::dom::bindings::codegen::Bindings::RTCStatsReportBinding::{
+    RTCIceCandidatePairStats, RTCInboundRtpStreamStats, RTCOutboundRtpStreamStats, RTCStatsType,
+};
+use crate::dom::bindings::conversions::ToJSValConvertible;
+use crate::dom::bindings::iterable::Iterable;
+use crate::dom::bindings::num::Finite;
+use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::globalscope::GlobalScope;
+use crate::script_runtime::JSContext;
+use dom_struct::dom_struct;
+use js::jsapi::JSContext as RawJSContext;
+use js::jsval::{JSVal, UndefinedValue};
+use js::rust::MutableHandleValue;
+use servo_media::streams::MediaStreamType;
+use servo_media::webrtc::{RtcStats, RtcStatsType};
+
+/// A stats object of a report, which is exposed to script as the dictionary of its type.
+#[derive(Clone, JSTraceable, MallocSizeOf)]
+pub struct RTCStatsEntry(#[ignore_malloc_size_of = "defined in servo-media"] RtcStats);
+
+/// Backends may not know every value, which are reported as 0 then.
+fn finite(value: f64) -> Finite<f64> {
+    Finite::new(value).unwrap_or(Finite::wrap(0.))
+}
+
+fn kind(kind: MediaStreamType) -> DOMString {
+    match kind {
+        MediaStreamType::Video => "video".into(),
+        MediaStreamType::Audio => "audio".into(),
+    }
+}
+
+impl ToJSValConvertible for RTCStatsEntry {
+    #[allow(unsafe_code)]
+    unsafe fn to_jsval(&self, cx: *mut RawJSContext, rval: MutableHandleValue) {
+        let stats = &self.0;
+        let timestamp = finite(stats.timestamp);
+        let id = DOMString::from(stats.id.clone());
+        match stats.type_ {
+            RtcStatsType::InboundRtp {
+                ssrc,
+                kind: media_kind,
+                ref transport_id,
+                track,
+                packets_received,
+                packets_lost,
+                jitter,
+                bytes_received,
+            } => RTCInboundRtpStreamStats {
+                timestamp,
+                type_: RTCStatsType::Inbound_rtp,
+                id,
+                ssrc,
+                kind: kind(media_kind),
+                transportId: transport_id.clone().map(DOMString::from),
+                packetsReceived: Some(packets_received),
+                packetsLost: Some(packets_lost),
+                jitter: Some(finite(jitter)),
+                trackIdentifier: track.map(|track| DOMString::from(track.id().to_string())),
+                bytesReceived: Some(bytes_received),
+            }
+            .to_jsval(cx, rval),
+            RtcStatsType::OutboundRtp {
+                ssrc,
+                kind: media_kind,
+                ref transport_id,
+                packets_sent,
+                bytes_sent,
+                ..
+            } => RTCOutboundRtpStreamStats {
+                timestamp,
+                type_: RTCStatsType::Outbound_rtp,
+                id,
+                ssrc,
+                kind: kind(media_kind),
+                transportId: transport_id.clone().map(DOMString::from),
+                packetsSent: Some(packets_sent),
+                bytesSent: Some(bytes_sent),
+            }
+            .to_jsval(cx, rval),
+            RtcStatsType::CandidatePair {
+                ref transport_id,
+                ref local_candidate_id,
+                ref remote_candidate_id,
+                nominated,
+                bytes_sent,
+                bytes_received,
+                current_round_trip_time,
+                available_outgoing_bitrate,
+            } => RTCIceCandidatePairStats {
+                timestamp,
+                type_: RTCStatsType::Candidate_pair,
+                id,
+                transportId: transport_id.clone().into(),
+                localCandidateId: local_candidate_id.clone().into(),
+                remoteCandidateId: remote_candidate_id.clone().into(),
+                nominated: Some(nominated),
+                bytesSent: Some(bytes_sent),
+                bytesReceived: Some(bytes_received),
+                currentRoundTripTime: current_round_trip_time.map(finite),
+                availableOutgoingBitrate: available_outgoing_bitrate.map(finite),
+            }
+            .to_jsval(cx, rval),
+        }
+    }
+}
+
+/// The stats of a peer connection at the time `getStats()` was called, keyed by their ids.
+#[dom_struct]
+pub struct RTCStatsReport {
+    reflector_: Reflector,
+    stats: Vec<RTCStatsEntry>,
+}
+
+impl RTCStatsReport {
+    fn new_inherited(stats: Vec<RtcStats>) -> RTCStatsReport {
+        RTCStatsReport {
+            reflector_: Reflector::new(),
+            stats: stats.into_iter().map(RTCStatsEntry).collect(),
+        }
+    }
+
+    pub fn new(global: &GlobalScope, stats: Vec<RtcStats>) -> DomRoot<RTCStatsReport> {
+        reflect_dom_object(Box::new(RTCStatsReport::new_inherited(stats)), global)
+    }
+
+    fn find(&self, key: &str) -> Option<&RTCStatsEntry> {
+        self.stats.iter().find(|entry| entry.0.id == key)
+    }
+}
+
+impl RTCStatsReportMethods for RTCStatsReport {
+    // https://heycam.github.io/webidl/#dfn-map-size
+    fn Size(&self) -> u32 {
+        self.stats.len() as u32
+    }
+
+    // https://heycam.github.io/webidl/#es-map-get-has
+    #[allow(unsafe_code)]
+    fn Get(&self, cx: JSContext, key: DOMString) -> JSVal {
+        match self.find(&key) {
+            Some(entry) => unsafe {
+                rooted!(in(*cx) let mut value = UndefinedValue());
+                entry.to_jsval(*cx, value.handle_mut());
+                value.get()
+            },
+            None => UndefinedValue(),
+        }
+    }
+
+    // https://heycam.github.io/webidl/#es-map-get-has
+    fn Has(&self, key: DOMString) -> bool {
+        self.find(&key).is_some()
+    }
+}
+
+impl Iterable for RTCStatsReport {
+    type Key = DOMString;
+    type Value = RTCStatsEntry;
+
+    fn get_iterable_length(&self) -> u32 {
+        self.stats.len() as u32
+    }
+
+    fn get_value_at_index(&self, n: u32) -> RTCStatsEntry {
+        self.stats[n as usize].clone()
+    }
+
+    fn get_key_at_index(&self, n: u32) -> DOMString {
+        DOMString::from(self.stats[n as usize].0.id.clone())
+    }
+}
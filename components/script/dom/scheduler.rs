@@ -0,0 +1,248 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::abortsignal::{AbortAlgorithm, AbortSignal};
+use crate::dom::bindings::callback::ExceptionHandling;
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::AbortSignalBinding::AbortSignalMethods;
+use crate::dom::bindings::codegen::Bindings::SchedulerBinding::{
+    SchedulerMethods, SchedulerPostTaskCallback, SchedulerPostTaskOptions, TaskPriority,
+};
+use crate::dom::bindings::error::Error;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::refcounted::Trusted;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::promise::Promise;
+use crate::dom::tasksignal::TaskSignal;
+use crate::realms::enter_realm;
+use crate::timers::OneshotTimerCallback;
+use dom_struct::dom_struct;
+use js::jsapi::JS_ClearPendingException;
+use js::jsval::UndefinedValue;
+use js::rust::wrappers::JS_GetPendingException;
+use script_traits::MsDuration;
+use std::cell::Cell;
+use std::rc::Rc;
+
+/// A task posted with `scheduler.postTask()` that hasn't run yet.
+///
+/// <https://wicg.github.io/scheduling-apis/#scheduler-task>
+#[derive(JSTraceable, MallocSizeOf)]
+#[unrooted_must_root_lint::must_root]
+struct SchedulerTask {
+    id: u64,
+    #[ignore_malloc_size_of = "Rc is hard"]
+    callback: Rc<SchedulerPostTaskCallback>,
+    #[ignore_malloc_size_of = "Rc is hard"]
+    promise: Rc<Promise>,
+    /// The priority given in the options of `postTask()`, which takes precedence over the
+    /// priority of the signal.
+    priority: Option<TaskPriority>,
+    signal: Option<Dom<AbortSignal>>,
+    /// Whether the delay of the task has elapsed.
+    runnable: bool,
+}
+
+impl SchedulerTask {
+    /// <https://wicg.github.io/scheduling-apis/#scheduler-task-queue-effective-priority>
+    fn effective_priority(&self) -> TaskPriority {
+        if let Some(priority) = self.priority {
+            return priority;
+        }
+        self.signal
+            .as_ref()
+            .and_then(|signal| signal.downcast::<TaskSignal>())
+            .map_or(TaskPriority::User_visible, |signal| signal.priority())
+    }
+}
+
+fn priority_rank(priority: TaskPriority) -> u8 {
+    match priority {
+        TaskPriority::User_blocking => 2,
+        TaskPriority::User_visible => 1,
+        TaskPriority::Background => 0,
+    }
+}
+
+/// <https://wicg.github.io/scheduling-apis/#sec-scheduler>
+#[dom_struct]
+pub struct Scheduler {
+    reflector_: Reflector,
+    tasks: DomRefCell<Vec<SchedulerTask>>,
+    next_task_id: Cell<u64>,
+}
+
+impl Scheduler {
+    fn new_inherited() -> Scheduler {
+        Scheduler {
+            reflector_: Reflector::new(),
+            tasks: DomRefCell::new(vec![]),
+            next_task_id: Cell::new(0),
+        }
+    }
+
+    pub fn new(global: &GlobalScope) -> DomRoot<Scheduler> {
+        reflect_dom_object(Box::new(Scheduler::new_inherited()), global)
+    }
+
+    /// Queues a task on the posted task task source which runs the most important runnable
+    /// task of this scheduler. The event loop task only decides when a task may run, which
+    /// task runs is decided once it does, so that priority changes are honoured.
+    fn queue_runner(&self, priority: TaskPriority) {
+        let global = self.global();
+        let scheduler = Trusted::new(self);
+        let _ = global.posted_task_source().queue_with_priority(
+            task!(run_scheduler_task: move || {
+                scheduler.root().run_next_task();
+            }),
+            priority,
+            &global,
+        );
+    }
+
+    /// <https://wicg.github.io/scheduling-apis/#scheduler-run-a-scheduler-task>
+    #[allow(unsafe_code)]
+    fn run_next_task(&self) {
+        let task = {
+            let mut tasks = self.tasks.borrow_mut();
+            let next = tasks
+                .iter()
+                .enumerate()
+                .filter(|(_, task)| task.runnable)
+                .max_by(|(a_index, a), (b_index, b)| {
+                    priority_rank(a.effective_priority())
+                        .cmp(&priority_rank(b.effective_priority()))
+                        .then(b_index.cmp(a_index))
+                })
+                .map(|(index, _)| index);
+            match next {
+                Some(index) => tasks.remove(index),
+                None => return,
+            }
+        };
+
+        let global = self.global();
+        let cx = global.get_cx();
+        let _ac = enter_realm(self);
+        match task.callback.Call__(ExceptionHandling::Rethrow) {
+            Ok(value) => {
+                rooted!(in(*cx) let value = value);
+                task.promise.resolve(cx, value.handle());
+            },
+            Err(_) => {
+                rooted!(in(*cx) let mut exception = UndefinedValue());
+                unsafe {
+                    if JS_GetPendingException(*cx, exception.handle_mut()) {
+                        JS_ClearPendingException(*cx);
+                    }
+                }
+                task.promise.reject(cx, exception.handle());
+            },
+        }
+    }
+
+    /// Removes the task with the given id, rejecting its promise with an "AbortError".
+    pub fn abort_task(&self, id: u64) {
+        let task = {
+            let mut tasks = self.tasks.borrow_mut();
+            match tasks.iter().position(|task| task.id == id) {
+                Some(index) => tasks.remove(index),
+                None => return,
+            }
+        };
+        task.promise.reject_error(Error::Abort);
+    }
+
+    /// Called once the delay of the task with the given id has elapsed.
+    fn make_runnable(&self, id: u64) {
+        let priority = {
+            let mut tasks = self.tasks.borrow_mut();
+            match tasks.iter_mut().find(|task| task.id == id) {
+                Some(task) => {
+                    task.runnable = true;
+                    task.effective_priority()
+                },
+                None => return,
+            }
+        };
+        self.queue_runner(priority);
+    }
+}
+
+impl SchedulerMethods for Scheduler {
+    // https://wicg.github.io/scheduling-apis/#dom-scheduler-posttask
+    #[allow(unrooted_must_root)]
+    fn PostTask(
+        &self,
+        callback: Rc<SchedulerPostTaskCallback>,
+        options: &SchedulerPostTaskOptions,
+    ) -> Rc<Promise> {
+        let global = self.global();
+
+        // Step 1.
+        let promise = Promise::new(&global);
+
+        // Step 2.
+        if let Some(ref signal) = options.signal {
+            if signal.Aborted() {
+                promise.reject_error(Error::Abort);
+                return promise;
+            }
+        }
+
+        // Steps 3-6.
+        let id = self.next_task_id.get();
+        self.next_task_id.set(id + 1);
+        let task = SchedulerTask {
+            id,
+            callback,
+            promise: promise.clone(),
+            priority: options.priority,
+            signal: options
+                .signal
+                .as_ref()
+                .map(|signal| Dom::from_ref(&**signal)),
+            runnable: options.delay == 0,
+        };
+        let priority = task.effective_priority();
+        self.tasks.borrow_mut().push(task);
+
+        // Step 7.
+        if let Some(ref signal) = options.signal {
+            signal.add_algorithm(AbortAlgorithm::SchedulerTask(Dom::from_ref(self), id));
+        }
+
+        // Steps 8-9.
+        if options.delay > 0 {
+            let callback = SchedulerTaskDelayCallback {
+                scheduler: Trusted::new(self),
+                id,
+            };
+            global.schedule_callback(
+                OneshotTimerCallback::SchedulerTaskDelay(callback),
+                MsDuration::new(options.delay),
+            );
+        } else {
+            self.queue_runner(priority);
+        }
+
+        // Step 10.
+        promise
+    }
+}
+
+#[derive(JSTraceable, MallocSizeOf)]
+pub struct SchedulerTaskDelayCallback {
+    #[ignore_malloc_size_of = "non-owning"]
+    scheduler: Trusted<Scheduler>,
+    id: u64,
+}
+
+impl SchedulerTaskDelayCallback {
+    pub fn invoke(self) {
+        self.scheduler.root().make_runnable(self.id);
+    }
+}
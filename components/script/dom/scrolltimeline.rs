@@ -0,0 +1,121 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::animationtimeline::AnimationTimeline;
+use crate::dom::bindings::codegen::Bindings::DocumentBinding::DocumentMethods;
+use crate::dom::bindings::codegen::Bindings::ElementBinding::ElementMethods;
+use crate::dom::bindings::codegen::Bindings::ScrollTimelineBinding::{
+    ScrollAxis, ScrollTimelineMethods, ScrollTimelineOptions,
+};
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::reflector::reflect_dom_object;
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::element::Element;
+use crate::dom::node::{document_from_node, window_from_node};
+use crate::dom::viewtimeline::ViewTimeline;
+use crate::dom::window::Window;
+use dom_struct::dom_struct;
+
+/// <https://drafts.csswg.org/scroll-animations-1/#scrolltimeline-interface>
+#[dom_struct]
+pub struct ScrollTimeline {
+    timeline: AnimationTimeline,
+    /// The scroll container of the timeline, which is only stored for scroll progress
+    /// timelines, as view progress timelines find it from their subject.
+    source: Option<Dom<Element>>,
+    axis: ScrollAxis,
+}
+
+impl ScrollTimeline {
+    pub fn new_inherited(source: Option<&Element>, axis: ScrollAxis) -> ScrollTimeline {
+        ScrollTimeline {
+            timeline: AnimationTimeline::new_inherited(),
+            source: source.map(Dom::from_ref),
+            axis,
+        }
+    }
+
+    pub fn new(window: &Window, source: Option<&Element>, axis: ScrollAxis) -> DomRoot<Self> {
+        reflect_dom_object(
+            Box::new(ScrollTimeline::new_inherited(source, axis)),
+            window,
+        )
+    }
+
+    // https://drafts.csswg.org/scroll-animations-1/#dom-scrolltimeline-scrolltimeline
+    #[allow(non_snake_case)]
+    pub fn Constructor(window: &Window, options: &ScrollTimelineOptions) -> DomRoot<Self> {
+        // A source that is left out is the root element of the document, which scrolls the
+        // viewport, while a source that is null leaves the timeline inactive.
+        let source = match options.source {
+            Some(ref source) => source.clone(),
+            None => window.Document().GetDocumentElement(),
+        };
+        ScrollTimeline::new(window, source.as_deref(), options.axis)
+    }
+
+    pub fn axis(&self) -> ScrollAxis {
+        self.axis
+    }
+
+    /// The progress of this timeline as a percentage, or `None` if it is inactive.
+    pub fn current_time(&self) -> Option<f64> {
+        if let Some(view_timeline) = self.downcast::<ViewTimeline>() {
+            return view_timeline.current_time();
+        }
+
+        // https://drafts.csswg.org/scroll-animations-1/#scroll-timeline-progress
+        //
+        // FIXME: The block and inline axes are taken to be the vertical and horizontal axes,
+        // whatever the writing mode is.
+        let source = self.source.as_ref()?;
+        let root = document_from_node(&**source).GetDocumentElement();
+        let is_root = root.as_deref() == Some(&**source);
+        let (position, scroll_size, client_size) = match self.axis {
+            ScrollAxis::Block | ScrollAxis::Y => (
+                source.ScrollTop(),
+                source.ScrollHeight() as f64,
+                source.ClientHeight() as f64,
+            ),
+            ScrollAxis::Inline | ScrollAxis::X => (
+                source.ScrollLeft(),
+                source.ScrollWidth() as f64,
+                source.ClientWidth() as f64,
+            ),
+        };
+        // The scrollport of the root element is the viewport.
+        let client_size = if is_root {
+            let viewport = window_from_node(&**source).current_viewport();
+            match self.axis {
+                ScrollAxis::Block | ScrollAxis::Y => viewport.size.height.to_f64_px(),
+                ScrollAxis::Inline | ScrollAxis::X => viewport.size.width.to_f64_px(),
+            }
+        } else {
+            client_size
+        };
+        let range = scroll_size - client_size;
+        if range <= 0. {
+            return None;
+        }
+        Some((position / range).max(0.).min(1.) * 100.)
+    }
+}
+
+impl ScrollTimelineMethods for ScrollTimeline {
+    // https://drafts.csswg.org/scroll-animations-1/#dom-scrolltimeline-source
+    fn GetSource(&self) -> Option<DomRoot<Element>> {
+        match self.downcast::<ViewTimeline>() {
+            Some(view_timeline) => view_timeline.source(),
+            None => self
+                .source
+                .as_ref()
+                .map(|source| DomRoot::from_ref(&**source)),
+        }
+    }
+
+    // https://drafts.csswg.org/scroll-animations-1/#dom-scrolltimeline-axis
+    fn Axis(&self) -> ScrollAxis {
+        self.axis
+    }
+}
@@ -2,6 +2,7 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use crate::dom::bindings::codegen::Bindings::DocumentBinding::DocumentMethods;
 use crate::dom::bindings::codegen::Bindings::NodeBinding::{GetRootNodeOptions, NodeMethods};
 use crate::dom::bindings::codegen::Bindings::RangeBinding::RangeMethods;
 use crate::dom::bindings::codegen::Bindings::SelectionBinding::SelectionMethods;
@@ -13,8 +14,11 @@ use crate::dom::bindings::root::{Dom, DomRoot, MutNullableDom};
 use crate::dom::bindings::str::DOMString;
 use crate::dom::document::Document;
 use crate::dom::eventtarget::EventTarget;
+use crate::dom::htmlinputelement::HTMLInputElement;
+use crate::dom::htmltextareaelement::HTMLTextAreaElement;
 use crate::dom::node::{window_from_node, Node};
 use crate::dom::range::Range;
+use crate::editing::{self, Granularity};
 use crate::task_source::TaskSource;
 use dom_struct::dom_struct;
 use std::cell::Cell;
@@ -93,6 +97,7 @@ impl Selection {
                 task!(selectionchange_task_steps: move || {
                     let this = this.root();
                     this.task_queued.set(false);
+                    this.document.update_editing_caret();
                     this.document.upcast::<EventTarget>().fire_event(atom!("selectionchange"));
                 }),
                 window.upcast(),
@@ -186,6 +191,18 @@ impl SelectionMethods for Selection {
         }
     }
 
+    // https://w3c.github.io/selection-api/#dom-selection-direction
+    fn Direction(&self) -> DOMString {
+        match self.range.get() {
+            Some(ref range) if !range.Collapsed() => match self.direction.get() {
+                Direction::Forwards => DOMString::from("forward"),
+                Direction::Backwards => DOMString::from("backward"),
+                Direction::Directionless => DOMString::from("none"),
+            },
+            _ => DOMString::from("none"),
+        }
+    }
+
     // https://w3c.github.io/selection-api/#dom-selection-getrangeat
     fn GetRangeAt(&self, index: u32) -> Fallible<DomRoot<Range>> {
         if index != 0 {
@@ -438,6 +455,53 @@ impl SelectionMethods for Selection {
         Ok(())
     }
 
+    // https://w3c.github.io/selection-api/#dom-selection-modify
+    fn Modify(&self, alter: DOMString, direction: DOMString, granularity: DOMString) {
+        // Step 1
+        let extend = match &*alter.to_ascii_lowercase() {
+            "move" => false,
+            "extend" => true,
+            _ => return,
+        };
+
+        // Step 2. Without bidi information, left and right follow the inline direction
+        // of left-to-right text.
+        let direction = match &*direction.to_ascii_lowercase() {
+            "forward" | "right" => editing::Direction::Forward,
+            "backward" | "left" => editing::Direction::Backward,
+            _ => return,
+        };
+
+        // Step 3
+        let granularity = match Granularity::from_name(&granularity) {
+            Some(granularity) => granularity,
+            None => return,
+        };
+
+        // Step 4
+        let (node, offset) = match self.GetFocusNode() {
+            Some(node) => (node, self.FocusOffset()),
+            None => return,
+        };
+
+        // Step 5
+        let root = match node.editing_host() {
+            Some(host) => DomRoot::upcast::<Node>(host),
+            None => match self.document.GetDocumentElement() {
+                Some(root) => DomRoot::upcast::<Node>(root),
+                None => return,
+            },
+        };
+        let position = editing::modified_position(&root, &node, offset, direction, granularity);
+        if let Some((node, offset)) = position {
+            let _ = if extend {
+                self.Extend(&node, offset)
+            } else {
+                self.Collapse(Some(&node), offset)
+            };
+        }
+    }
+
     // https://w3c.github.io/selection-api/#dom-selection-deletecontents
     fn DeleteFromDocument(&self) -> ErrorResult {
         if let Some(range) = self.range.get() {
@@ -505,6 +569,20 @@ impl SelectionMethods for Selection {
         // "See W3C bug 10583." for this method.
         // Stringifying the range seems at least approximately right
         // and passes the non-style-dependent case in the WPT tests.
+        // While a text control is focused, what its user selected is reported instead,
+        // as other engines do.
+        if let Some(focused) = self.document.get_focused_element() {
+            let text = if let Some(input) = focused.downcast::<HTMLInputElement>() {
+                input.selected_text()
+            } else if let Some(textarea) = focused.downcast::<HTMLTextAreaElement>() {
+                textarea.selected_text()
+            } else {
+                None
+            };
+            if let Some(text) = text {
+                return text;
+            }
+        }
         if let Some(range) = self.range.get() {
             range.Stringifier()
         } else {
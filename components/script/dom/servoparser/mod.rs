@@ -52,7 +52,7 @@ use net_traits::{ResourceFetchTiming, ResourceTimingType};
 use profile_traits::time::{
     profile, ProfilerCategory, TimerMetadata, TimerMetadataFrameType, TimerMetadataReflowType,
 };
-use script_traits::DocumentActivity;
+use script_traits::{DocumentActivity, ScriptMsg};
 use servo_config::pref;
 use servo_url::ServoUrl;
 use std::borrow::Cow;
@@ -103,12 +103,11 @@ pub struct ServoParser {
     aborted: Cell<bool>,
     /// <https://html.spec.whatwg.org/multipage/#script-created-parser>
     script_created_parser: bool,
-    /// We do a quick-and-dirty parse of the input looking for resources to prefetch.
+    /// We do a quick-and-dirty parse of the input on a helper thread, looking for resources
+    /// to prefetch.
     // TODO: if we had speculative parsing, we could do this when speculatively
     // building the DOM. https://github.com/servo/servo/pull/19203
     prefetch_tokenizer: DomRefCell<prefetch::Tokenizer>,
-    #[ignore_malloc_size_of = "Defined in html5ever"]
-    prefetch_input: DomRefCell<BufferQueue>,
 }
 
 #[derive(PartialEq)]
@@ -255,6 +254,27 @@ impl ServoParser {
         parser.parse_string_chunk(String::from(input));
     }
 
+    /// Parses an XML document that is given whole, and returns the first error that makes it
+    /// not well-formed, if any.
+    pub fn parse_complete_xml_document(
+        document: &Document,
+        input: DOMString,
+        url: ServoUrl,
+    ) -> Result<(), String> {
+        let parser = ServoParser::new(
+            document,
+            Tokenizer::Xml(self::xml::Tokenizer::new(document, url)),
+            LastChunkState::Received,
+            ParserKind::Normal,
+        );
+        parser.parse_string_chunk(String::from(input));
+        let parse_error = parser.tokenizer.borrow().parse_error();
+        match parse_error {
+            Some(error) => Err(error),
+            None => Ok(()),
+        }
+    }
+
     pub fn script_nesting_level(&self) -> usize {
         self.script_nesting_level.get()
     }
@@ -412,7 +432,6 @@ impl ServoParser {
             aborted: Default::default(),
             script_created_parser: kind == ParserKind::ScriptCreated,
             prefetch_tokenizer: DomRefCell::new(prefetch::Tokenizer::new(document)),
-            prefetch_input: DomRefCell::new(BufferQueue::new()),
         }
     }
 
@@ -446,16 +465,13 @@ impl ServoParser {
         // We're conservative, and only prefetch for documents
         // with browsing contexts.
         if self.document.browsing_context().is_some() {
-            // Push the chunk into the prefetch input stream,
-            // which is tokenized eagerly, to scan for resources
-            // to prefetch. If the user script uses `document.write()`
-            // to overwrite the network input, this prefetching may
-            // have been wasted, but in most cases it won't.
-            let mut prefetch_input = self.prefetch_input.borrow_mut();
-            prefetch_input.push_back(chunk.clone());
-            self.prefetch_tokenizer
-                .borrow_mut()
-                .feed(&mut *prefetch_input);
+            // Send the chunk to the prefetch tokenizer, which
+            // tokenizes it eagerly on its own thread, to scan for
+            // resources to prefetch. If the user script uses
+            // `document.write()` to overwrite the network input,
+            // this prefetching may have been wasted, but in most
+            // cases it won't.
+            self.prefetch_tokenizer.borrow_mut().feed(chunk.clone());
         }
         // Push the chunk into the network input stream,
         // which is tokenized lazily.
@@ -662,6 +678,15 @@ impl Tokenizer {
         }
     }
 
+    /// The first error that the XML parser reported, if any. Errors don't matter to the HTML
+    /// parser, which recovers from them as specified.
+    fn parse_error(&self) -> Option<String> {
+        match *self {
+            Tokenizer::Xml(ref tokenizer) => tokenizer.parse_error(),
+            Tokenizer::Html(_) | Tokenizer::AsyncHtml(_) => None,
+        }
+    }
+
     fn url(&self) -> &ServoUrl {
         match *self {
             Tokenizer::Html(ref tokenizer) => tokenizer.url(),
@@ -718,6 +743,23 @@ impl ParserContext {
     }
 }
 
+/// Escapes a detail of a certificate for the page that shows its error. Most of the details
+/// are chosen by the server, so they must not be able to add markup or other placeholders.
+fn escape_certificate_detail(detail: &str) -> String {
+    let mut escaped = String::with_capacity(detail.len());
+    for character in detail.chars() {
+        match character {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '$' => escaped.push_str("&#36;"),
+            _ => escaped.push(character),
+        }
+    }
+    escaped
+}
+
 impl FetchResponseListener for ParserContext {
     fn process_request_body(&mut self) {}
 
@@ -731,8 +773,8 @@ impl FetchResponseListener for ParserContext {
                 FetchMetadata::Unfiltered(m) => m,
                 FetchMetadata::Filtered { unsafe_, .. } => unsafe_,
             }),
-            Err(NetworkError::SslValidation(url, reason)) => {
-                ssl_error = Some(reason);
+            Err(NetworkError::SslValidation(url, error)) => {
+                ssl_error = Some(error);
                 let mut meta = Metadata::default(url);
                 let mime: Option<Mime> = "text/html".parse().ok();
                 meta.set_content_type(mime.as_ref());
@@ -815,12 +857,23 @@ impl FetchResponseListener for ParserContext {
             },
             Some(ref mime) if mime.type_() == mime::TEXT && mime.subtype() == mime::HTML => {
                 // Handle text/html
-                if let Some(reason) = ssl_error {
+                if let Some(error) = ssl_error {
                     self.is_synthesized_document = true;
-                    let page = resources::read_string(Resource::BadCertHTML);
-                    let page = page.replace("${reason}", &reason);
+                    let page = resources::read_string(Resource::BadCertHTML)
+                        .replace("${host}", &escape_certificate_detail(&error.host))
+                        .replace("${reason}", &escape_certificate_detail(&error.reason))
+                        .replace("${subject}", &escape_certificate_detail(&error.subject))
+                        .replace("${issuer}", &escape_certificate_detail(&error.issuer))
+                        .replace("${not_before}", &escape_certificate_detail(&error.not_before))
+                        .replace("${not_after}", &escape_certificate_detail(&error.not_after))
+                        .replace("${fingerprint}", &error.fingerprint);
                     parser.push_string_input_chunk(page);
                     parser.parse_sync();
+                    // The embedder asks the user whether to trust the certificate anyway.
+                    parser
+                        .document
+                        .window()
+                        .send_to_constellation(ScriptMsg::CertificateError(error.id));
                 }
                 if let Some(reason) = network_error {
                     self.is_synthesized_document = true;
@@ -883,8 +936,7 @@ impl FetchResponseListener for ParserContext {
         }
 
         match status {
-            // are we throwing this away or can we use it?
-            Ok(_) => (),
+            Ok(resource_timing) => self.resource_timing = resource_timing,
             // TODO(Savago): we should send a notification to callers #5463.
             Err(err) => debug!("Failed to load page URL {}, error: {:?}", self.url, err),
         }
@@ -901,8 +953,11 @@ impl FetchResponseListener for ParserContext {
         //TODO only update if this is the current document resource
         if let Some(pushed_index) = self.pushed_entry_index {
             let document = &parser.document;
-            let performance_entry =
-                PerformanceNavigationTiming::new(&document.global(), 0, 0, &document);
+            let performance_entry = PerformanceNavigationTiming::new(
+                &document.global(),
+                &document,
+                &self.resource_timing,
+            );
             document
                 .global()
                 .performance()
@@ -930,9 +985,8 @@ impl FetchResponseListener for ParserContext {
 
         let document = &parser.document;
 
-        //TODO nav_start and nav_start_precise
         let performance_entry =
-            PerformanceNavigationTiming::new(&document.global(), 0, 0, &document);
+            PerformanceNavigationTiming::new(&document.global(), &document, &self.resource_timing);
         self.pushed_entry_index = document
             .global()
             .performance()
@@ -994,6 +1048,8 @@ pub struct Sink {
     current_line: u64,
     script: MutNullableDom<HTMLScriptElement>,
     parsing_algorithm: ParsingAlgorithm,
+    /// The first error that the parser reported, which makes an XML document not well-formed.
+    parse_error: Option<String>,
 }
 
 impl Sink {
@@ -1125,6 +1181,9 @@ impl TreeSink for Sink {
 
     fn parse_error(&mut self, msg: Cow<'static, str>) {
         debug!("Parse error: {}", msg);
+        if self.parse_error.is_none() {
+            self.parse_error = Some(msg.into_owned());
+        }
     }
 
     fn set_quirks_mode(&mut self, mode: QuirksMode) {
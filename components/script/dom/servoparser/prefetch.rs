@@ -3,12 +3,14 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use crate::dom::bindings::reflector::DomObject;
-use crate::dom::bindings::trace::JSTraceable;
 use crate::dom::document::{determine_policy_for_token, Document};
 use crate::dom::htmlimageelement::{image_fetch_request, FromPictureOrSrcSet};
 use crate::dom::htmlscriptelement::script_fetch_request;
 use crate::stylesheet_loader::stylesheet_fetch_request;
+use crossbeam_channel::{unbounded, Receiver, Sender};
 use html5ever::buffer_queue::BufferQueue;
+use html5ever::tendril::fmt::UTF8;
+use html5ever::tendril::{SendTendril, StrTendril};
 use html5ever::tokenizer::states::RawKind;
 use html5ever::tokenizer::Tag;
 use html5ever::tokenizer::TagKind;
@@ -19,10 +21,10 @@ use html5ever::tokenizer::Tokenizer as HtmlTokenizer;
 use html5ever::tokenizer::TokenizerResult;
 use html5ever::Attribute;
 use html5ever::LocalName;
-use js::jsapi::JSTracer;
 use msg::constellation_msg::PipelineId;
 use net_traits::request::CorsSettings;
 use net_traits::request::Referrer;
+use net_traits::request::RequestPriority;
 use net_traits::CoreResourceMsg;
 use net_traits::FetchChannels;
 use net_traits::IpcSend;
@@ -30,19 +32,26 @@ use net_traits::ReferrerPolicy;
 use net_traits::ResourceThreads;
 use servo_url::ImmutableOrigin;
 use servo_url::ServoUrl;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
 
+/// A tokenizer which scans the network input of the parser for resources to prefetch.
+///
+/// The input is tokenized on a helper thread as soon as it arrives, so it runs ahead of the
+/// tree builder, which is blocked whenever it waits for a script. The helper thread is shared
+/// by all the parsers of the script thread.
 #[derive(JSTraceable, MallocSizeOf)]
-#[unrooted_must_root_lint::must_root]
 pub struct Tokenizer {
+    /// The id of this tokenizer on the prefetch thread.
+    id: usize,
+    /// The sink of the tokenizer, until it is sent to the prefetch thread with the first chunk
+    /// of input.
     #[ignore_malloc_size_of = "Defined in html5ever"]
-    inner: HtmlTokenizer<PrefetchSink>,
-}
-
-#[allow(unsafe_code)]
-unsafe impl JSTraceable for HtmlTokenizer<PrefetchSink> {
-    unsafe fn trace(&self, trc: *mut JSTracer) {
-        self.sink.trace(trc)
-    }
+    sink: Option<PrefetchSink>,
+    #[ignore_malloc_size_of = "Defined in std"]
+    sender: Option<Sender<PrefetchMsg>>,
 }
 
 impl Tokenizer {
@@ -60,13 +69,90 @@ impl Tokenizer {
             // block the main parser.
             prefetching: false,
         };
-        let options = Default::default();
-        let inner = HtmlTokenizer::new(sink, options);
-        Tokenizer { inner }
+        Tokenizer {
+            id: NEXT_TOKENIZER_ID.fetch_add(1, Ordering::Relaxed),
+            sink: Some(sink),
+            sender: None,
+        }
+    }
+
+    pub fn feed(&mut self, chunk: StrTendril) {
+        if let Some(sink) = self.sink.take() {
+            // If there is no prefetch thread, the input is simply not scanned.
+            self.sender = prefetch_thread();
+            if let Some(ref sender) = self.sender {
+                let _ = sender.send(PrefetchMsg::Start(self.id, sink));
+            }
+        }
+        if let Some(ref sender) = self.sender {
+            let _ = sender.send(PrefetchMsg::Chunk(self.id, SendTendril::from(chunk)));
+        }
     }
+}
 
-    pub fn feed(&mut self, input: &mut BufferQueue) {
-        while let TokenizerResult::Script(PrefetchHandle) = self.inner.feed(input) {}
+impl Drop for Tokenizer {
+    fn drop(&mut self) {
+        if let Some(ref sender) = self.sender {
+            let _ = sender.send(PrefetchMsg::Stop(self.id));
+        }
+    }
+}
+
+static NEXT_TOKENIZER_ID: AtomicUsize = AtomicUsize::new(0);
+
+thread_local!(
+    static PREFETCH_THREAD: RefCell<Option<Option<Sender<PrefetchMsg>>>> = RefCell::new(None)
+);
+
+/// Returns the prefetch thread of the script thread, spawning it on first use. Returns `None`
+/// if the thread could not be spawned.
+fn prefetch_thread() -> Option<Sender<PrefetchMsg>> {
+    PREFETCH_THREAD.with(|prefetch_thread| {
+        prefetch_thread
+            .borrow_mut()
+            .get_or_insert_with(|| {
+                let (sender, receiver) = unbounded();
+                match thread::Builder::new()
+                    .name(String::from("HTML Prefetch"))
+                    .spawn(move || run(receiver))
+                {
+                    Ok(_) => Some(sender),
+                    Err(error) => {
+                        warn!("Failed to spawn the HTML prefetch thread: {}", error);
+                        None
+                    },
+                }
+            })
+            .clone()
+    })
+}
+
+/// A message to the prefetch thread about the input of one of the parsers.
+enum PrefetchMsg {
+    Start(usize, PrefetchSink),
+    Chunk(usize, SendTendril<UTF8>),
+    Stop(usize),
+}
+
+/// Tokenizes the chunks of input of every parser until the script thread goes away.
+fn run(receiver: Receiver<PrefetchMsg>) {
+    let mut tokenizers = HashMap::new();
+    while let Ok(msg) = receiver.recv() {
+        match msg {
+            PrefetchMsg::Start(id, sink) => {
+                let tokenizer = HtmlTokenizer::new(sink, Default::default());
+                tokenizers.insert(id, (tokenizer, BufferQueue::new()));
+            },
+            PrefetchMsg::Chunk(id, chunk) => {
+                if let Some(&mut (ref mut tokenizer, ref mut input)) = tokenizers.get_mut(&id) {
+                    input.push_back(StrTendril::from(chunk));
+                    while let TokenizerResult::Script(PrefetchHandle) = tokenizer.feed(input) {}
+                }
+            },
+            PrefetchMsg::Stop(id) => {
+                tokenizers.remove(&id);
+            },
+        }
     }
 }
 
@@ -113,6 +199,7 @@ impl TokenSink for PrefetchSink {
                         self.referrer.clone(),
                         self.referrer_policy,
                         integrity_metadata,
+                        self.get_fetch_priority(tag),
                     );
                     let _ = self
                         .resource_threads
@@ -129,6 +216,7 @@ impl TokenSink for PrefetchSink {
                         self.pipeline_id,
                         self.get_cors_settings(tag, local_name!("crossorigin")),
                         self.get_referrer_policy(tag, local_name!("referrerpolicy")),
+                        self.get_fetch_priority(tag),
                         FromPictureOrSrcSet::No,
                     );
                     let _ = self
@@ -158,6 +246,7 @@ impl TokenSink for PrefetchSink {
                                 self.referrer.clone(),
                                 referrer_policy,
                                 integrity_metadata,
+                                self.get_fetch_priority(tag),
                             );
                             let _ = self
                                 .resource_threads
@@ -206,6 +295,11 @@ impl PrefetchSink {
             .or(self.referrer_policy)
     }
 
+    fn get_fetch_priority(&self, tag: &Tag) -> RequestPriority {
+        let value = self.get_attr(tag, LocalName::from("fetchpriority"));
+        RequestPriority::from_attribute_value(value.map_or("", |attr| &*attr.value))
+    }
+
     fn get_cors_settings(&self, tag: &Tag, name: LocalName) -> Option<CorsSettings> {
         let crossorigin = self.get_attr(tag, name)?;
         if crossorigin.value.eq_ignore_ascii_case("anonymous") {
@@ -31,6 +31,7 @@ impl Tokenizer {
             current_line: 1,
             script: Default::default(),
             parsing_algorithm: ParsingAlgorithm::Normal,
+            parse_error: None,
         };
 
         let tb = XmlTreeBuilder::new(sink, Default::default());
@@ -54,6 +55,10 @@ impl Tokenizer {
     pub fn url(&self) -> &ServoUrl {
         &self.inner.sink.sink.base_url
     }
+
+    pub fn parse_error(&self) -> Option<String> {
+        self.inner.sink.sink.parse_error.clone()
+    }
 }
 
 #[allow(unsafe_code)]
@@ -5,10 +5,12 @@
 use crate::dom::bindings::cell::DomRefCell;
 use crate::dom::bindings::codegen::Bindings::ShadowRootBinding::ShadowRootBinding::ShadowRootMethods;
 use crate::dom::bindings::codegen::Bindings::ShadowRootBinding::ShadowRootMode;
+use crate::dom::bindings::error::ErrorResult;
 use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::num::Finite;
 use crate::dom::bindings::reflector::reflect_dom_object;
 use crate::dom::bindings::root::{Dom, DomRoot, LayoutDom, MutNullableDom};
+use crate::dom::caretposition::CaretPosition;
 use crate::dom::cssstylesheet::CSSStyleSheet;
 use crate::dom::document::Document;
 use crate::dom::documentfragment::DocumentFragment;
@@ -17,8 +19,11 @@ use crate::dom::element::Element;
 use crate::dom::node::{Node, NodeDamage, NodeFlags, ShadowIncluding, UnbindContext};
 use crate::dom::stylesheetlist::{StyleSheetList, StyleSheetListOwner};
 use crate::dom::window::Window;
+use crate::script_runtime::JSContext;
 use crate::stylesheet_set::StylesheetSetRef;
 use dom_struct::dom_struct;
+use js::jsval::JSVal;
+use js::rust::HandleValue;
 use selectors::context::QuirksMode;
 use servo_arc::Arc;
 use servo_atoms::Atom;
@@ -46,11 +51,13 @@ pub struct ShadowRoot {
     author_styles: DomRefCell<AuthorStyles<StyleSheetInDocument>>,
     stylesheet_list: MutNullableDom<StyleSheetList>,
     window: Dom<Window>,
+    /// <https://dom.spec.whatwg.org/#shadowroot-delegates-focus>
+    delegates_focus: bool,
 }
 
 impl ShadowRoot {
     #[allow(unrooted_must_root)]
-    fn new_inherited(host: &Element, document: &Document) -> ShadowRoot {
+    fn new_inherited(host: &Element, document: &Document, delegates_focus: bool) -> ShadowRoot {
         let document_fragment = DocumentFragment::new_inherited(document);
         let node = document_fragment.upcast::<Node>();
         node.set_flag(NodeFlags::IS_IN_SHADOW_TREE, true);
@@ -66,12 +73,13 @@ impl ShadowRoot {
             author_styles: DomRefCell::new(AuthorStyles::new()),
             stylesheet_list: MutNullableDom::new(None),
             window: Dom::from_ref(document.window()),
+            delegates_focus,
         }
     }
 
-    pub fn new(host: &Element, document: &Document) -> DomRoot<ShadowRoot> {
+    pub fn new(host: &Element, document: &Document, delegates_focus: bool) -> DomRoot<ShadowRoot> {
         reflect_dom_object(
-            Box::new(ShadowRoot::new_inherited(host, document)),
+            Box::new(ShadowRoot::new_inherited(host, document, delegates_focus)),
             document.window(),
         )
     }
@@ -84,13 +92,23 @@ impl ShadowRoot {
         self.host.set(None);
     }
 
+    pub fn delegates_focus(&self) -> bool {
+        self.delegates_focus
+    }
+
     pub fn get_focused_element(&self) -> Option<DomRoot<Element>> {
         //XXX get retargeted focused element
         None
     }
 
+    /// The number of stylesheets of the tree, which doesn't include the adopted stylesheets.
     pub fn stylesheet_count(&self) -> usize {
-        self.author_styles.borrow().stylesheets.len()
+        self.author_styles
+            .borrow()
+            .stylesheets
+            .iter()
+            .filter(|sheet| sheet.owner.is_some())
+            .count()
     }
 
     pub fn stylesheet_at(&self, index: usize) -> Option<DomRoot<CSSStyleSheet>> {
@@ -98,7 +116,8 @@ impl ShadowRoot {
 
         stylesheets
             .get(index)
-            .and_then(|s| s.owner.upcast::<Node>().get_cssom_stylesheet())
+            .and_then(|s| s.owner.as_ref())
+            .and_then(|owner| owner.upcast::<Node>().get_cssom_stylesheet())
     }
 
     /// Add a stylesheet owned by `owner` to the list of shadow root sheets, in the
@@ -106,16 +125,16 @@ impl ShadowRoot {
     #[allow(unrooted_must_root)] // Owner needs to be rooted already necessarily.
     pub fn add_stylesheet(&self, owner: &Element, sheet: Arc<Stylesheet>) {
         let stylesheets = &mut self.author_styles.borrow_mut().stylesheets;
+        // The adopted stylesheets come after all the stylesheets of the tree.
         let insertion_point = stylesheets
             .iter()
-            .find(|sheet_in_shadow| {
-                owner
-                    .upcast::<Node>()
-                    .is_before(sheet_in_shadow.owner.upcast())
+            .find(|sheet_in_shadow| match sheet_in_shadow.owner {
+                Some(ref other_owner) => owner.upcast::<Node>().is_before(other_owner.upcast()),
+                None => true,
             })
             .cloned();
         DocumentOrShadowRoot::add_stylesheet(
-            owner,
+            Some(owner),
             StylesheetSetRef::Author(stylesheets),
             sheet,
             insertion_point,
@@ -127,12 +146,46 @@ impl ShadowRoot {
     #[allow(unrooted_must_root)] // Owner needs to be rooted already necessarily.
     pub fn remove_stylesheet(&self, owner: &Element, s: &Arc<Stylesheet>) {
         DocumentOrShadowRoot::remove_stylesheet(
-            owner,
+            Some(owner),
             s,
             StylesheetSetRef::Author(&mut self.author_styles.borrow_mut().stylesheets),
         )
     }
 
+    /// Replaces the adopted stylesheets of the shadow root after `adoptedStyleSheets` was set.
+    /// They are appended after the stylesheets of the tree.
+    fn replace_adopted_stylesheets(&self, previous_sheets: &[DomRoot<CSSStyleSheet>]) {
+        let owner = StyleSheetListOwner::ShadowRoot(Dom::from_ref(self));
+        let sheets = self.document_or_shadow_root.adopted_stylesheets();
+        for sheet in previous_sheets {
+            sheet.remove_adopter(&owner);
+        }
+        for sheet in &sheets {
+            sheet.add_adopter(owner.clone());
+        }
+
+        {
+            let stylesheets = &mut self.author_styles.borrow_mut().stylesheets;
+            for sheet in DocumentOrShadowRoot::unique_stylesheets(previous_sheets) {
+                DocumentOrShadowRoot::remove_stylesheet(
+                    None,
+                    &sheet,
+                    StylesheetSetRef::Author(&mut *stylesheets),
+                );
+            }
+            for sheet in DocumentOrShadowRoot::unique_stylesheets(&sheets) {
+                DocumentOrShadowRoot::add_stylesheet(
+                    None,
+                    StylesheetSetRef::Author(&mut *stylesheets),
+                    sheet,
+                    None,
+                    self.document.style_shared_lock(),
+                );
+            }
+        }
+        self.invalidate_stylesheets();
+    }
+
     pub fn invalidate_stylesheets(&self) {
         self.document.invalidate_shadow_roots_stylesheets();
         self.author_styles.borrow_mut().stylesheets.force_dirty();
@@ -175,45 +228,54 @@ impl ShadowRootMethods for ShadowRoot {
             .get_active_element(self.get_focused_element(), None, None)
     }
 
+    // https://w3c.github.io/picture-in-picture/#dom-documentorshadowroot-pictureinpictureelement
+    fn GetPictureInPictureElement(&self) -> Option<DomRoot<Element>> {
+        // Step 1.
+        if !self.Host().upcast::<Node>().is_connected() {
+            return None;
+        }
+        self.document_or_shadow_root
+            .get_picture_in_picture_element(self.upcast())
+    }
+
     // https://drafts.csswg.org/cssom-view/#dom-document-elementfrompoint
     fn ElementFromPoint(&self, x: Finite<f64>, y: Finite<f64>) -> Option<DomRoot<Element>> {
         // Return the result of running the retargeting algorithm with context object
         // and the original result as input.
-        match self.document_or_shadow_root.element_from_point(
+        self.document_or_shadow_root.element_from_point(
+            self.upcast(),
             x,
             y,
             None,
             self.document.has_browsing_context(),
-        ) {
-            Some(e) => {
-                let retargeted_node = self.upcast::<Node>().retarget(e.upcast::<Node>());
-                retargeted_node
-                    .downcast::<Element>()
-                    .map(|n| DomRoot::from_ref(n))
-            },
-            None => None,
-        }
+        )
     }
 
     // https://drafts.csswg.org/cssom-view/#dom-document-elementsfrompoint
     fn ElementsFromPoint(&self, x: Finite<f64>, y: Finite<f64>) -> Vec<DomRoot<Element>> {
         // Return the result of running the retargeting algorithm with context object
         // and the original result as input
-        let mut elements = Vec::new();
-        for e in self
-            .document_or_shadow_root
-            .elements_from_point(x, y, None, self.document.has_browsing_context())
-            .iter()
-        {
-            let retargeted_node = self.upcast::<Node>().retarget(e.upcast::<Node>());
-            if let Some(element) = retargeted_node
-                .downcast::<Element>()
-                .map(|n| DomRoot::from_ref(n))
-            {
-                elements.push(element);
-            }
-        }
-        elements
+        self.document_or_shadow_root.elements_from_point(
+            self.upcast(),
+            x,
+            y,
+            None,
+            self.document.has_browsing_context(),
+        )
+    }
+
+    // https://drafts.csswg.org/cssom-view/#dom-document-caretpositionfrompoint
+    fn CaretPositionFromPoint(
+        &self,
+        x: Finite<f64>,
+        y: Finite<f64>,
+    ) -> Option<DomRoot<CaretPosition>> {
+        self.document_or_shadow_root.caret_position_from_point(
+            self.upcast(),
+            x,
+            y,
+            self.document.has_browsing_context(),
+        )
     }
 
     /// https://dom.spec.whatwg.org/#dom-shadowroot-mode
@@ -236,6 +298,20 @@ impl ShadowRootMethods for ShadowRoot {
             )
         })
     }
+
+    // https://drafts.csswg.org/cssom/#dom-documentorshadowroot-adoptedstylesheets
+    fn AdoptedStyleSheets(&self, cx: JSContext) -> JSVal {
+        self.document_or_shadow_root.get_adopted_stylesheets(cx)
+    }
+
+    // https://drafts.csswg.org/cssom/#dom-documentorshadowroot-adoptedstylesheets
+    fn SetAdoptedStyleSheets(&self, cx: JSContext, value: HandleValue) -> ErrorResult {
+        let previous_sheets =
+            self.document_or_shadow_root
+                .set_adopted_stylesheets(cx, value, &self.document)?;
+        self.replace_adopted_stylesheets(&previous_sheets);
+        Ok(())
+    }
 }
 
 #[allow(unsafe_code)]
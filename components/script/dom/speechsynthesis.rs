@@ -0,0 +1,320 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::DocumentBinding::DocumentMethods;
+use crate::dom::bindings::codegen::Bindings::SpeechSynthesisBinding::SpeechSynthesisMethods;
+use crate::dom::bindings::codegen::Bindings::SpeechSynthesisErrorEventBinding::SpeechSynthesisErrorCode;
+use crate::dom::bindings::codegen::Bindings::SpeechSynthesisUtteranceBinding::SpeechSynthesisUtteranceMethods;
+use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::num::Finite;
+use crate::dom::bindings::refcounted::Trusted;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::event::Event;
+use crate::dom::eventtarget::EventTarget;
+use crate::dom::speechsynthesiserrorevent::SpeechSynthesisErrorEvent;
+use crate::dom::speechsynthesisevent::SpeechSynthesisEvent;
+use crate::dom::speechsynthesisutterance::SpeechSynthesisUtterance;
+use crate::dom::speechsynthesisvoice::SpeechSynthesisVoice;
+use crate::dom::window::Window;
+use crate::task_source::{TaskSource, TaskSourceName};
+use dom_struct::dom_struct;
+use embedder_traits::{SpeechBoundary, SpeechEvent};
+use ipc_channel::ipc;
+use ipc_channel::router::ROUTER;
+use profile_traits::ipc as profiled_ipc;
+use script_traits::{ScriptMsg, SpeechAction};
+use servo_atoms::Atom;
+use std::cell::Cell;
+use std::collections::VecDeque;
+
+/// <https://wicg.github.io/speech-api/#tts-section>
+#[dom_struct]
+pub struct SpeechSynthesis {
+    eventtarget: EventTarget,
+    /// The utterances to speak, the first of which is the one being spoken while `speaking`
+    /// is set.
+    queue: DomRefCell<VecDeque<Dom<SpeechSynthesisUtterance>>>,
+    /// https://wicg.github.io/speech-api/#dom-speechsynthesis-speaking
+    speaking: Cell<bool>,
+    /// https://wicg.github.io/speech-api/#dom-speechsynthesis-paused
+    paused: Cell<bool>,
+    /// Counts the utterances passed to the speech synthesizer, so that the events of the ones
+    /// that were cancelled since are ignored.
+    utterance_id: Cell<u64>,
+    /// When the utterance being spoken began to be spoken, in `time::precise_time_ns()`.
+    start_time: Cell<Option<u64>>,
+    /// The voices of the speech synthesizer, once the page asked for them.
+    voices: DomRefCell<Option<Vec<Dom<SpeechSynthesisVoice>>>>,
+}
+
+impl SpeechSynthesis {
+    #[allow(unrooted_must_root)]
+    fn new_inherited() -> SpeechSynthesis {
+        SpeechSynthesis {
+            eventtarget: EventTarget::new_inherited(),
+            queue: DomRefCell::new(VecDeque::new()),
+            speaking: Cell::new(false),
+            paused: Cell::new(false),
+            utterance_id: Cell::new(0),
+            start_time: Cell::new(None),
+            voices: DomRefCell::new(None),
+        }
+    }
+
+    pub fn new(window: &Window) -> DomRoot<SpeechSynthesis> {
+        reflect_dom_object(Box::new(SpeechSynthesis::new_inherited()), window)
+    }
+
+    /// Passes the utterance at the front of the queue to the speech synthesizer, unless one
+    /// is being spoken or speaking is paused.
+    fn speak_next(&self) {
+        if self.speaking.get() || self.paused.get() {
+            return;
+        }
+        let utterance = match self.queue.borrow().front() {
+            Some(utterance) => DomRoot::from_ref(&**utterance),
+            None => return,
+        };
+
+        let id = self.utterance_id.get() + 1;
+        self.utterance_id.set(id);
+        self.speaking.set(true);
+        self.start_time.set(None);
+
+        let global = self.global();
+        let (sender, receiver) = ipc::channel().unwrap();
+        let task_source = global.dom_manipulation_task_source();
+        let canceller = global.task_canceller(TaskSourceName::DOMManipulation);
+        let synthesis = Trusted::new(self);
+        ROUTER.add_route(
+            receiver.to_opaque(),
+            Box::new(move |message| {
+                let synthesis = synthesis.clone();
+                let event = message.to().unwrap();
+                let _ = task_source.queue_with_canceller(
+                    task!(handle_speech_event: move || {
+                        synthesis.root().handle_event(id, event);
+                    }),
+                    &canceller,
+                );
+            }),
+        );
+
+        let lang = global
+            .as_window()
+            .Document()
+            .GetDocumentElement()
+            .map_or(String::new(), |element| element.get_lang());
+        let _ = global.script_to_constellation_chan().send(ScriptMsg::Speak(
+            utterance.to_speech_utterance(&lang),
+            sender,
+        ));
+    }
+
+    /// Fires the event that the speech synthesizer reported for the utterance being spoken.
+    fn handle_event(&self, id: u64, event: SpeechEvent) {
+        if id != self.utterance_id.get() || !self.speaking.get() {
+            return;
+        }
+        let utterance = match self.queue.borrow().front() {
+            Some(utterance) => DomRoot::from_ref(&**utterance),
+            None => return,
+        };
+
+        match event {
+            SpeechEvent::Start => {
+                self.start_time.set(Some(time::precise_time_ns()));
+                self.fire_event(&utterance, atom!("start"), 0, 0, "");
+            },
+            SpeechEvent::Boundary(boundary, char_index, char_length) => {
+                let name = match boundary {
+                    SpeechBoundary::Word => "word",
+                    SpeechBoundary::Sentence => "sentence",
+                };
+                self.fire_event(&utterance, atom!("boundary"), char_index, char_length, name);
+            },
+            SpeechEvent::Pause => self.fire_event(&utterance, atom!("pause"), 0, 0, ""),
+            SpeechEvent::Resume => self.fire_event(&utterance, atom!("resume"), 0, 0, ""),
+            SpeechEvent::End => {
+                self.finish_utterance();
+                let char_index = utterance.Text().chars().count() as u32;
+                self.fire_event(&utterance, atom!("end"), char_index, 0, "");
+                self.speak_next();
+            },
+            SpeechEvent::Error(error) => {
+                self.finish_utterance();
+                self.fire_error_event(&utterance, error.into());
+                self.speak_next();
+            },
+        }
+    }
+
+    /// Removes the utterance that was spoken from the queue.
+    fn finish_utterance(&self) {
+        self.queue.borrow_mut().pop_front();
+        self.speaking.set(false);
+    }
+
+    /// The seconds since the utterance being spoken began to be spoken.
+    fn elapsed_time(&self) -> Finite<f32> {
+        let elapsed = self.start_time.get().map_or(0., |start_time| {
+            (time::precise_time_ns() - start_time) as f32 / 1_000_000_000.
+        });
+        Finite::wrap(elapsed)
+    }
+
+    fn fire_event(
+        &self,
+        utterance: &SpeechSynthesisUtterance,
+        type_: Atom,
+        char_index: u32,
+        char_length: u32,
+        name: &str,
+    ) {
+        let event = SpeechSynthesisEvent::new(
+            self.global().as_window(),
+            type_,
+            false,
+            false,
+            utterance,
+            char_index,
+            char_length,
+            self.elapsed_time(),
+            DOMString::from(name),
+        );
+        event.upcast::<Event>().fire(utterance.upcast());
+    }
+
+    fn fire_error_event(
+        &self,
+        utterance: &SpeechSynthesisUtterance,
+        error: SpeechSynthesisErrorCode,
+    ) {
+        let event = SpeechSynthesisErrorEvent::new(
+            self.global().as_window(),
+            atom!("error"),
+            false,
+            false,
+            utterance,
+            0,
+            0,
+            self.elapsed_time(),
+            DOMString::new(),
+            error,
+        );
+        event.upcast::<Event>().fire(utterance.upcast());
+    }
+}
+
+impl SpeechSynthesisMethods for SpeechSynthesis {
+    // https://wicg.github.io/speech-api/#dom-speechsynthesis-pending
+    fn Pending(&self) -> bool {
+        let spoken = if self.speaking.get() { 1 } else { 0 };
+        self.queue.borrow().len() > spoken
+    }
+
+    // https://wicg.github.io/speech-api/#dom-speechsynthesis-speaking
+    fn Speaking(&self) -> bool {
+        self.speaking.get()
+    }
+
+    // https://wicg.github.io/speech-api/#dom-speechsynthesis-paused
+    fn Paused(&self) -> bool {
+        self.paused.get()
+    }
+
+    // https://wicg.github.io/speech-api/#dom-speechsynthesis-onvoiceschanged
+    event_handler!(voiceschanged, GetOnvoiceschanged, SetOnvoiceschanged);
+
+    // https://wicg.github.io/speech-api/#dom-speechsynthesis-speak
+    fn Speak(&self, utterance: &SpeechSynthesisUtterance) {
+        self.queue.borrow_mut().push_back(Dom::from_ref(utterance));
+        self.speak_next();
+    }
+
+    // https://wicg.github.io/speech-api/#dom-speechsynthesis-cancel
+    fn Cancel(&self) {
+        let utterances: Vec<_> = self
+            .queue
+            .borrow_mut()
+            .drain(..)
+            .map(|utterance| DomRoot::from_ref(&*utterance))
+            .collect();
+        let was_speaking = self.speaking.replace(false);
+        if was_speaking {
+            let _ = self
+                .global()
+                .script_to_constellation_chan()
+                .send(ScriptMsg::SpeechAction(SpeechAction::Cancel));
+        }
+
+        // The utterance being spoken is interrupted, and the ones after it are canceled.
+        for (index, utterance) in utterances.iter().enumerate() {
+            let error = if index == 0 && was_speaking {
+                SpeechSynthesisErrorCode::Interrupted
+            } else {
+                SpeechSynthesisErrorCode::Canceled
+            };
+            self.fire_error_event(utterance, error);
+        }
+    }
+
+    // https://wicg.github.io/speech-api/#dom-speechsynthesis-pause
+    fn Pause(&self) {
+        if self.paused.replace(true) {
+            return;
+        }
+        if self.speaking.get() {
+            let _ = self
+                .global()
+                .script_to_constellation_chan()
+                .send(ScriptMsg::SpeechAction(SpeechAction::Pause));
+        }
+    }
+
+    // https://wicg.github.io/speech-api/#dom-speechsynthesis-resume
+    fn Resume(&self) {
+        if !self.paused.replace(false) {
+            return;
+        }
+        if self.speaking.get() {
+            let _ = self
+                .global()
+                .script_to_constellation_chan()
+                .send(ScriptMsg::SpeechAction(SpeechAction::Resume));
+        } else {
+            self.speak_next();
+        }
+    }
+
+    // https://wicg.github.io/speech-api/#dom-speechsynthesis-getvoices
+    fn GetVoices(&self) -> Vec<DomRoot<SpeechSynthesisVoice>> {
+        if let Some(ref voices) = *self.voices.borrow() {
+            return voices
+                .iter()
+                .map(|voice| DomRoot::from_ref(&**voice))
+                .collect();
+        }
+
+        let global = self.global();
+        let (sender, receiver) =
+            profiled_ipc::channel(global.time_profiler_chan().clone()).unwrap();
+        let _ = global
+            .script_to_constellation_chan()
+            .send(ScriptMsg::GetSpeechVoices(sender));
+        let voices: Vec<_> = receiver
+            .recv()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|voice| SpeechSynthesisVoice::new(global.as_window(), voice))
+            .collect();
+        *self.voices.borrow_mut() =
+            Some(voices.iter().map(|voice| Dom::from_ref(&**voice)).collect());
+        voices
+    }
+}
@@ -0,0 +1,130 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::SpeechSynthesisErrorEventBinding::{
+    SpeechSynthesisErrorCode, SpeechSynthesisErrorEventInit, SpeechSynthesisErrorEventMethods,
+};
+use crate::dom::bindings::codegen::Bindings::SpeechSynthesisEventBinding::SpeechSynthesisEventMethods;
+use crate::dom::bindings::error::Fallible;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::num::Finite;
+use crate::dom::bindings::reflector::reflect_dom_object;
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::event::Event;
+use crate::dom::speechsynthesisevent::SpeechSynthesisEvent;
+use crate::dom::speechsynthesisutterance::SpeechSynthesisUtterance;
+use crate::dom::window::Window;
+use dom_struct::dom_struct;
+use embedder_traits::SpeechError;
+use servo_atoms::Atom;
+
+/// <https://wicg.github.io/speech-api/#speechsynthesiserrorevent>
+#[dom_struct]
+pub struct SpeechSynthesisErrorEvent {
+    event: SpeechSynthesisEvent,
+    error: SpeechSynthesisErrorCode,
+}
+
+impl SpeechSynthesisErrorEvent {
+    #[allow(unrooted_must_root)]
+    fn new_inherited(
+        utterance: &SpeechSynthesisUtterance,
+        char_index: u32,
+        char_length: u32,
+        elapsed_time: Finite<f32>,
+        name: DOMString,
+        error: SpeechSynthesisErrorCode,
+    ) -> SpeechSynthesisErrorEvent {
+        SpeechSynthesisErrorEvent {
+            event: SpeechSynthesisEvent::new_inherited(
+                utterance,
+                char_index,
+                char_length,
+                elapsed_time,
+                name,
+            ),
+            error,
+        }
+    }
+
+    pub fn new(
+        window: &Window,
+        type_: Atom,
+        bubbles: bool,
+        cancelable: bool,
+        utterance: &SpeechSynthesisUtterance,
+        char_index: u32,
+        char_length: u32,
+        elapsed_time: Finite<f32>,
+        name: DOMString,
+        error: SpeechSynthesisErrorCode,
+    ) -> DomRoot<SpeechSynthesisErrorEvent> {
+        let event = reflect_dom_object(
+            Box::new(SpeechSynthesisErrorEvent::new_inherited(
+                utterance,
+                char_index,
+                char_length,
+                elapsed_time,
+                name,
+                error,
+            )),
+            window,
+        );
+        event
+            .upcast::<Event>()
+            .init_event(type_, bubbles, cancelable);
+        event
+    }
+
+    #[allow(non_snake_case)]
+    pub fn Constructor(
+        window: &Window,
+        type_: DOMString,
+        init: &SpeechSynthesisErrorEventInit,
+    ) -> Fallible<DomRoot<SpeechSynthesisErrorEvent>> {
+        Ok(SpeechSynthesisErrorEvent::new(
+            window,
+            Atom::from(type_),
+            init.parent.parent.bubbles,
+            init.parent.parent.cancelable,
+            &init.parent.utterance,
+            init.parent.charIndex,
+            init.parent.charLength,
+            init.parent.elapsedTime,
+            init.parent.name.clone(),
+            init.error,
+        ))
+    }
+}
+
+impl SpeechSynthesisErrorEventMethods for SpeechSynthesisErrorEvent {
+    // https://wicg.github.io/speech-api/#dom-speechsynthesiserrorevent-error
+    fn Error(&self) -> SpeechSynthesisErrorCode {
+        self.error
+    }
+
+    // https://dom.spec.whatwg.org/#dom-event-istrusted
+    fn IsTrusted(&self) -> bool {
+        self.event.IsTrusted()
+    }
+}
+
+impl From<SpeechError> for SpeechSynthesisErrorCode {
+    fn from(error: SpeechError) -> SpeechSynthesisErrorCode {
+        match error {
+            SpeechError::Interrupted => SpeechSynthesisErrorCode::Interrupted,
+            SpeechError::AudioBusy => SpeechSynthesisErrorCode::Audio_busy,
+            SpeechError::AudioHardware => SpeechSynthesisErrorCode::Audio_hardware,
+            SpeechError::Network => SpeechSynthesisErrorCode::Network,
+            SpeechError::SynthesisUnavailable => SpeechSynthesisErrorCode::Synthesis_unavailable,
+            SpeechError::SynthesisFailed => SpeechSynthesisErrorCode::Synthesis_failed,
+            SpeechError::LanguageUnavailable => SpeechSynthesisErrorCode::Language_unavailable,
+            SpeechError::VoiceUnavailable => SpeechSynthesisErrorCode::Voice_unavailable,
+            SpeechError::TextTooLong => SpeechSynthesisErrorCode::Text_too_long,
+            SpeechError::InvalidArgument => SpeechSynthesisErrorCode::Invalid_argument,
+            SpeechError::NotAllowed => SpeechSynthesisErrorCode::Not_allowed,
+        }
+    }
+}
@@ -0,0 +1,129 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::EventBinding::EventBinding::EventMethods;
+use crate::dom::bindings::codegen::Bindings::SpeechSynthesisEventBinding::{
+    SpeechSynthesisEventInit, SpeechSynthesisEventMethods,
+};
+use crate::dom::bindings::error::Fallible;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::num::Finite;
+use crate::dom::bindings::reflector::reflect_dom_object;
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::event::Event;
+use crate::dom::speechsynthesisutterance::SpeechSynthesisUtterance;
+use crate::dom::window::Window;
+use dom_struct::dom_struct;
+use servo_atoms::Atom;
+
+/// <https://wicg.github.io/speech-api/#speechsynthesisevent>
+#[dom_struct]
+pub struct SpeechSynthesisEvent {
+    event: Event,
+    utterance: Dom<SpeechSynthesisUtterance>,
+    char_index: u32,
+    char_length: u32,
+    /// The seconds since the utterance began to be spoken.
+    elapsed_time: Finite<f32>,
+    name: DOMString,
+}
+
+impl SpeechSynthesisEvent {
+    #[allow(unrooted_must_root)]
+    pub fn new_inherited(
+        utterance: &SpeechSynthesisUtterance,
+        char_index: u32,
+        char_length: u32,
+        elapsed_time: Finite<f32>,
+        name: DOMString,
+    ) -> SpeechSynthesisEvent {
+        SpeechSynthesisEvent {
+            event: Event::new_inherited(),
+            utterance: Dom::from_ref(utterance),
+            char_index,
+            char_length,
+            elapsed_time,
+            name,
+        }
+    }
+
+    pub fn new(
+        window: &Window,
+        type_: Atom,
+        bubbles: bool,
+        cancelable: bool,
+        utterance: &SpeechSynthesisUtterance,
+        char_index: u32,
+        char_length: u32,
+        elapsed_time: Finite<f32>,
+        name: DOMString,
+    ) -> DomRoot<SpeechSynthesisEvent> {
+        let event = reflect_dom_object(
+            Box::new(SpeechSynthesisEvent::new_inherited(
+                utterance,
+                char_index,
+                char_length,
+                elapsed_time,
+                name,
+            )),
+            window,
+        );
+        event
+            .upcast::<Event>()
+            .init_event(type_, bubbles, cancelable);
+        event
+    }
+
+    #[allow(non_snake_case)]
+    pub fn Constructor(
+        window: &Window,
+        type_: DOMString,
+        init: &SpeechSynthesisEventInit,
+    ) -> Fallible<DomRoot<SpeechSynthesisEvent>> {
+        Ok(SpeechSynthesisEvent::new(
+            window,
+            Atom::from(type_),
+            init.parent.bubbles,
+            init.parent.cancelable,
+            &init.utterance,
+            init.charIndex,
+            init.charLength,
+            init.elapsedTime,
+            init.name.clone(),
+        ))
+    }
+}
+
+impl SpeechSynthesisEventMethods for SpeechSynthesisEvent {
+    // https://wicg.github.io/speech-api/#dom-speechsynthesisevent-utterance
+    fn Utterance(&self) -> DomRoot<SpeechSynthesisUtterance> {
+        DomRoot::from_ref(&*self.utterance)
+    }
+
+    // https://wicg.github.io/speech-api/#dom-speechsynthesisevent-charindex
+    fn CharIndex(&self) -> u32 {
+        self.char_index
+    }
+
+    // https://wicg.github.io/speech-api/#dom-speechsynthesisevent-charlength
+    fn CharLength(&self) -> u32 {
+        self.char_length
+    }
+
+    // https://wicg.github.io/speech-api/#dom-speechsynthesisevent-elapsedtime
+    fn ElapsedTime(&self) -> Finite<f32> {
+        self.elapsed_time
+    }
+
+    // https://wicg.github.io/speech-api/#dom-speechsynthesisevent-name
+    fn Name(&self) -> DOMString {
+        self.name.clone()
+    }
+
+    // https://dom.spec.whatwg.org/#dom-event-istrusted
+    fn IsTrusted(&self) -> bool {
+        self.event.IsTrusted()
+    }
+}
@@ -0,0 +1,64 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::SpeechSynthesisVoiceBinding::SpeechSynthesisVoiceMethods;
+use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::window::Window;
+use dom_struct::dom_struct;
+use embedder_traits::SpeechVoice;
+
+/// <https://wicg.github.io/speech-api/#speechsynthesisvoice>
+#[dom_struct]
+pub struct SpeechSynthesisVoice {
+    reflector_: Reflector,
+    #[ignore_malloc_size_of = "defined in embedder_traits"]
+    voice: SpeechVoice,
+}
+
+impl SpeechSynthesisVoice {
+    fn new_inherited(voice: SpeechVoice) -> SpeechSynthesisVoice {
+        SpeechSynthesisVoice {
+            reflector_: Reflector::new(),
+            voice,
+        }
+    }
+
+    pub fn new(window: &Window, voice: SpeechVoice) -> DomRoot<SpeechSynthesisVoice> {
+        reflect_dom_object(Box::new(SpeechSynthesisVoice::new_inherited(voice)), window)
+    }
+
+    /// The identifier that the speech synthesizer knows the voice by.
+    pub fn voice_uri(&self) -> &str {
+        &self.voice.voice_uri
+    }
+}
+
+impl SpeechSynthesisVoiceMethods for SpeechSynthesisVoice {
+    // https://wicg.github.io/speech-api/#dom-speechsynthesisvoice-voiceuri
+    fn VoiceURI(&self) -> DOMString {
+        DOMString::from(self.voice.voice_uri.clone())
+    }
+
+    // https://wicg.github.io/speech-api/#dom-speechsynthesisvoice-name
+    fn Name(&self) -> DOMString {
+        DOMString::from(self.voice.name.clone())
+    }
+
+    // https://wicg.github.io/speech-api/#dom-speechsynthesisvoice-lang
+    fn Lang(&self) -> DOMString {
+        DOMString::from(self.voice.lang.clone())
+    }
+
+    // https://wicg.github.io/speech-api/#dom-speechsynthesisvoice-localservice
+    fn LocalService(&self) -> bool {
+        self.voice.local_service
+    }
+
+    // https://wicg.github.io/speech-api/#dom-speechsynthesisvoice-default
+    fn Default(&self) -> bool {
+        self.voice.default
+    }
+}
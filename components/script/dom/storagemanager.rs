@@ -0,0 +1,70 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::StorageManagerBinding::StorageManagerMethods;
+use crate::dom::bindings::error::Error;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::filesystemdirectoryhandle::FileSystemDirectoryHandle;
+use crate::dom::filesystemhandle::file_manager_request;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::promise::Promise;
+use crate::realms::InRealm;
+use dom_struct::dom_struct;
+use net_traits::filemanager_thread::{
+    FileManagerThreadMsg, FileSystemEntryKind, FileSystemLocator,
+};
+use std::rc::Rc;
+
+/// <https://storage.spec.whatwg.org/#storagemanager>
+#[dom_struct]
+pub struct StorageManager {
+    reflector_: Reflector,
+}
+
+impl StorageManager {
+    fn new_inherited() -> StorageManager {
+        StorageManager {
+            reflector_: Reflector::new(),
+        }
+    }
+
+    pub fn new(global: &GlobalScope) -> DomRoot<StorageManager> {
+        reflect_dom_object(Box::new(StorageManager::new_inherited()), global)
+    }
+}
+
+impl StorageManagerMethods for StorageManager {
+    // https://wicg.github.io/file-system-access/#dom-storagemanager-getdirectory
+    fn GetDirectory(&self, comp: InRealm) -> Rc<Promise> {
+        let global = self.global();
+        let promise = Promise::new_in_current_realm(&global, comp);
+
+        // Opaque origins have no storage of their own.
+        let origin = global.origin().immutable().clone();
+        if !origin.is_tuple() {
+            promise.reject_error(Error::Security);
+            return promise;
+        }
+
+        let locator = FileSystemLocator::Private(origin.ascii_serialization(), vec![]);
+        let result = file_manager_request(&global, |sender| {
+            FileManagerThreadMsg::GetFileSystemEntry(
+                locator.clone(),
+                FileSystemEntryKind::Directory,
+                true,
+                sender,
+            )
+        });
+        match result {
+            Ok(()) => promise.resolve_native(&FileSystemDirectoryHandle::new(
+                &global,
+                locator,
+                String::new(),
+            )),
+            Err(error) => promise.reject_error(error),
+        }
+        promise
+    }
+}
@@ -0,0 +1,129 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::CSSStyleDeclarationBinding::CSSStyleDeclarationMethods;
+use crate::dom::bindings::codegen::Bindings::CSSStyleValueBinding::CSSStyleValueMethods;
+use crate::dom::bindings::codegen::Bindings::StylePropertyMapBinding::StylePropertyMapMethods;
+use crate::dom::bindings::codegen::UnionTypes::CSSStyleValueOrUSVString;
+use crate::dom::bindings::error::{Error, ErrorResult};
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::str::{DOMString, USVString};
+use crate::dom::css::CSS;
+use crate::dom::cssstyledeclaration::CSSStyleDeclaration;
+use crate::dom::cssstylevalue::is_valid_property;
+use crate::dom::stylepropertymapreadonly::{StylePropertyMapReadOnly, StylePropertyMapSource};
+use crate::dom::window::Window;
+use dom_struct::dom_struct;
+
+/// The map of the declarations of a style attribute, which writes through the declaration
+/// block of `element.style`.
+/// <https://drafts.css-houdini.org/css-typed-om-1/#declared-stylepropertymap-objects>
+#[dom_struct]
+pub struct StylePropertyMap {
+    map: StylePropertyMapReadOnly,
+}
+
+impl StylePropertyMap {
+    #[allow(unrooted_must_root)]
+    fn new_inherited(declaration: &CSSStyleDeclaration) -> StylePropertyMap {
+        StylePropertyMap {
+            map: StylePropertyMapReadOnly::new_inherited(StylePropertyMapSource::Declarations(
+                Dom::from_ref(declaration),
+            )),
+        }
+    }
+
+    pub fn new(window: &Window, declaration: &CSSStyleDeclaration) -> DomRoot<StylePropertyMap> {
+        reflect_dom_object(
+            Box::new(StylePropertyMap::new_inherited(declaration)),
+            window,
+        )
+    }
+
+    fn declaration(&self) -> DomRoot<CSSStyleDeclaration> {
+        self.map
+            .declaration()
+            .expect("No declaration for a style property map")
+    }
+
+    /// Checks that the values form a valid value of the property, and returns their
+    /// serialization.
+    fn serialize_values(
+        &self,
+        property: &str,
+        values: Vec<CSSStyleValueOrUSVString>,
+    ) -> Result<String, Error> {
+        if !is_valid_property(property) {
+            return Err(Error::Type(format!("Unknown property {}", property)));
+        }
+        let values: Vec<String> = values
+            .into_iter()
+            .map(|value| match value {
+                CSSStyleValueOrUSVString::CSSStyleValue(value) => value.Stringifier().into(),
+                CSSStyleValueOrUSVString::USVString(value) => value.0,
+            })
+            .collect();
+        if values.is_empty() {
+            return Err(Error::Type("No values to set".to_owned()));
+        }
+        let value = values.join(", ");
+        let supported = CSS::Supports(
+            self.global().as_window(),
+            DOMString::from(property),
+            DOMString::from(value.clone()),
+        );
+        if !supported {
+            return Err(Error::Type(format!("Invalid value for {}", property)));
+        }
+        Ok(value)
+    }
+}
+
+impl StylePropertyMapMethods for StylePropertyMap {
+    /// <https://drafts.css-houdini.org/css-typed-om-1/#dom-stylepropertymap-set>
+    fn Set(&self, property: USVString, values: Vec<CSSStyleValueOrUSVString>) -> ErrorResult {
+        let value = self.serialize_values(&property, values)?;
+        self.declaration().SetProperty(
+            DOMString::from(property.0),
+            DOMString::from(value),
+            DOMString::new(),
+        )
+    }
+
+    /// Appends to the list of values of a comma-separated property.
+    /// <https://drafts.css-houdini.org/css-typed-om-1/#dom-stylepropertymap-append>
+    fn Append(&self, property: USVString, values: Vec<CSSStyleValueOrUSVString>) -> ErrorResult {
+        let declaration = self.declaration();
+        let current = declaration.GetPropertyValue(DOMString::from(&*property.0));
+        let mut values = values;
+        if !current.is_empty() {
+            values.insert(
+                0,
+                CSSStyleValueOrUSVString::USVString(USVString(current.into())),
+            );
+        }
+        let value = self.serialize_values(&property, values)?;
+        declaration.SetProperty(
+            DOMString::from(property.0),
+            DOMString::from(value),
+            DOMString::new(),
+        )
+    }
+
+    /// <https://drafts.css-houdini.org/css-typed-om-1/#dom-stylepropertymap-delete>
+    fn Delete(&self, property: USVString) -> ErrorResult {
+        if !is_valid_property(&property) {
+            return Err(Error::Type(format!("Unknown property {}", &*property)));
+        }
+        self.declaration()
+            .RemoveProperty(DOMString::from(property.0))
+            .map(|_| ())
+    }
+
+    /// <https://drafts.css-houdini.org/css-typed-om-1/#dom-stylepropertymap-clear>
+    fn Clear(&self) -> ErrorResult {
+        self.declaration().SetCssText(DOMString::new())
+    }
+}
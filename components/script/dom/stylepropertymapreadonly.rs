@@ -2,12 +2,14 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use crate::dom::bindings::codegen::Bindings::CSSStyleDeclarationBinding::CSSStyleDeclarationMethods;
 use crate::dom::bindings::codegen::Bindings::StylePropertyMapReadOnlyBinding::StylePropertyMapReadOnlyMethods;
 use crate::dom::bindings::reflector::reflect_dom_object;
-use crate::dom::bindings::reflector::Reflector;
+use crate::dom::bindings::reflector::{DomObject, Reflector};
 use crate::dom::bindings::root::{Dom, DomRoot};
 use crate::dom::bindings::str::DOMString;
-use crate::dom::cssstylevalue::CSSStyleValue;
+use crate::dom::cssstyledeclaration::CSSStyleDeclaration;
+use crate::dom::cssstylevalue::{is_valid_property, CSSStyleValue};
 use crate::dom::globalscope::GlobalScope;
 use dom_struct::dom_struct;
 use servo_atoms::Atom;
@@ -16,20 +18,29 @@ use std::collections::HashMap;
 use std::iter::Iterator;
 use style::custom_properties;
 
+/// Where the values of a style property map come from.
+#[derive(JSTraceable, MallocSizeOf)]
+#[unrooted_must_root_lint::must_root]
+pub enum StylePropertyMapSource {
+    /// A fixed set of values, like the properties a paint worklet asked for.
+    Entries(HashMap<Atom, Dom<CSSStyleValue>>),
+    /// The declarations of a style attribute, or the computed values of an element, which
+    /// are read each time they are requested.
+    Declarations(Dom<CSSStyleDeclaration>),
+}
+
 #[dom_struct]
 pub struct StylePropertyMapReadOnly {
     reflector: Reflector,
-    entries: HashMap<Atom, Dom<CSSStyleValue>>,
+    source: StylePropertyMapSource,
 }
 
 impl StylePropertyMapReadOnly {
-    fn new_inherited<Entries>(entries: Entries) -> StylePropertyMapReadOnly
-    where
-        Entries: IntoIterator<Item = (Atom, Dom<CSSStyleValue>)>,
-    {
+    #[allow(unrooted_must_root)]
+    pub fn new_inherited(source: StylePropertyMapSource) -> StylePropertyMapReadOnly {
         StylePropertyMapReadOnly {
             reflector: Reflector::new(),
-            entries: entries.into_iter().collect(),
+            source,
         }
     }
 
@@ -47,40 +58,97 @@ impl StylePropertyMapReadOnly {
         keys.reserve(lo);
         values.reserve(lo);
         for (key, value) in iter {
-            let value = CSSStyleValue::new(global, value);
+            let value = CSSStyleValue::reify(global, value);
             keys.push(key);
             values.push(Dom::from_ref(&*value));
         }
         let iter = keys.drain(..).zip(values.iter().cloned());
+        let source = StylePropertyMapSource::Entries(iter.collect());
         reflect_dom_object(
-            Box::new(StylePropertyMapReadOnly::new_inherited(iter)),
+            Box::new(StylePropertyMapReadOnly::new_inherited(source)),
             global,
         )
     }
+
+    /// Creates the map of the computed values of an element, from the declaration returned
+    /// by `getComputedStyle`.
+    /// <https://drafts.css-houdini.org/css-typed-om-1/#computed-stylepropertymapreadonly-objects>
+    pub fn from_declaration(
+        global: &GlobalScope,
+        declaration: &CSSStyleDeclaration,
+    ) -> DomRoot<StylePropertyMapReadOnly> {
+        let source = StylePropertyMapSource::Declarations(Dom::from_ref(declaration));
+        reflect_dom_object(
+            Box::new(StylePropertyMapReadOnly::new_inherited(source)),
+            global,
+        )
+    }
+
+    /// The declaration the values are read from, for the maps which have one.
+    pub fn declaration(&self) -> Option<DomRoot<CSSStyleDeclaration>> {
+        match self.source {
+            StylePropertyMapSource::Entries(_) => None,
+            StylePropertyMapSource::Declarations(ref declaration) => {
+                Some(DomRoot::from_ref(&**declaration))
+            },
+        }
+    }
+
+    fn value(&self, property: &str) -> Option<DomRoot<CSSStyleValue>> {
+        match self.source {
+            // TODO: avoid constructing an Atom
+            StylePropertyMapSource::Entries(ref entries) => entries
+                .get(&Atom::from(property))
+                .map(|value| DomRoot::from_ref(&**value)),
+            StylePropertyMapSource::Declarations(ref declaration) => {
+                if !is_valid_property(property) {
+                    return None;
+                }
+                let value = declaration.GetPropertyValue(DOMString::from(property));
+                if value.is_empty() {
+                    return None;
+                }
+                let value = String::from(value);
+                // Custom properties are kept as written.
+                if custom_properties::parse_name(property).is_ok() {
+                    return Some(CSSStyleValue::new(&self.global(), value));
+                }
+                Some(CSSStyleValue::reify(&self.global(), value))
+            },
+        }
+    }
 }
 
 impl StylePropertyMapReadOnlyMethods for StylePropertyMapReadOnly {
     /// <https://drafts.css-houdini.org/css-typed-om-1/#dom-stylepropertymapreadonly-get>
     fn Get(&self, property: DOMString) -> Option<DomRoot<CSSStyleValue>> {
-        // TODO: avoid constructing an Atom
-        self.entries
-            .get(&Atom::from(property))
-            .map(|value| DomRoot::from_ref(&**value))
+        self.value(&property)
+    }
+
+    /// <https://drafts.css-houdini.org/css-typed-om-1/#dom-stylepropertymapreadonly-getall>
+    fn GetAll(&self, property: DOMString) -> Vec<DomRoot<CSSStyleValue>> {
+        self.value(&property).into_iter().collect()
     }
 
     /// <https://drafts.css-houdini.org/css-typed-om-1/#dom-stylepropertymapreadonly-has>
     fn Has(&self, property: DOMString) -> bool {
-        // TODO: avoid constructing an Atom
-        self.entries.contains_key(&Atom::from(property))
+        match self.source {
+            // TODO: avoid constructing an Atom
+            StylePropertyMapSource::Entries(ref entries) => {
+                entries.contains_key(&Atom::from(property))
+            },
+            StylePropertyMapSource::Declarations(_) => self.value(&property).is_some(),
+        }
     }
 
     /// <https://drafts.css-houdini.org/css-typed-om-1/#dom-stylepropertymapreadonly-getproperties>
     fn GetProperties(&self) -> Vec<DOMString> {
-        let mut result: Vec<DOMString> = self
-            .entries
-            .keys()
-            .map(|key| DOMString::from(&**key))
-            .collect();
+        let mut result: Vec<DOMString> = match self.source {
+            StylePropertyMapSource::Entries(ref entries) => {
+                entries.keys().map(|key| DOMString::from(&**key)).collect()
+            },
+            StylePropertyMapSource::Declarations(ref declaration) => declaration.property_names(),
+        };
         // https://drafts.css-houdini.org/css-typed-om-1/#dom-stylepropertymap-getproperties
         // requires this sort order
         result.sort_by(|key1, key2| {
@@ -100,4 +168,14 @@ impl StylePropertyMapReadOnlyMethods for StylePropertyMapReadOnly {
         });
         result
     }
+
+    /// <https://drafts.css-houdini.org/css-typed-om-1/#dom-stylepropertymapreadonly-size>
+    fn Size(&self) -> u32 {
+        match self.source {
+            StylePropertyMapSource::Entries(ref entries) => entries.len() as u32,
+            StylePropertyMapSource::Declarations(ref declaration) => {
+                declaration.property_names().len() as u32
+            },
+        }
+    }
 }
@@ -13,10 +13,10 @@ use crate::dom::stylesheet::StyleSheet;
 use crate::dom::window::Window;
 use dom_struct::dom_struct;
 use servo_arc::Arc;
-use style::stylesheets::Stylesheet;
+use style::stylesheets::{CssRule, Stylesheet};
 
 #[unrooted_must_root_lint::must_root]
-#[derive(JSTraceable, MallocSizeOf)]
+#[derive(Clone, JSTraceable, MallocSizeOf, PartialEq)]
 pub enum StyleSheetListOwner {
     Document(Dom<Document>),
     ShadowRoot(Dom<ShadowRoot>),
@@ -63,6 +63,17 @@ impl StyleSheetListOwner {
             },
         }
     }
+
+    pub fn stylesheet_rule_changed(&self, sheet: &Arc<Stylesheet>, rule: &CssRule) {
+        match *self {
+            StyleSheetListOwner::Document(ref doc) => doc.stylesheet_rule_changed(sheet, rule),
+            // The stylesheets of a shadow tree only apply to the tree, which is restyled as a
+            // whole.
+            StyleSheetListOwner::ShadowRoot(ref shadow_root) => {
+                shadow_root.invalidate_stylesheets()
+            },
+        }
+    }
 }
 
 #[dom_struct]
@@ -0,0 +1,2072 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::CryptoKeyBinding::{CryptoKeyPair, KeyType, KeyUsage};
+use crate::dom::bindings::codegen::Bindings::SubtleCryptoBinding::SubtleCryptoMethods;
+use crate::dom::bindings::codegen::Bindings::SubtleCryptoBinding::{
+    AesDerivedKeyParams, AesGcmParams,
+};
+use crate::dom::bindings::codegen::Bindings::SubtleCryptoBinding::{AesKeyGenParams, Algorithm};
+use crate::dom::bindings::codegen::Bindings::SubtleCryptoBinding::{
+    EcKeyGenParams, EcKeyImportParams,
+};
+use crate::dom::bindings::codegen::Bindings::SubtleCryptoBinding::{
+    EcdhKeyDeriveParams, EcdsaParams,
+};
+use crate::dom::bindings::codegen::Bindings::SubtleCryptoBinding::{HkdfParams, HmacImportParams};
+use crate::dom::bindings::codegen::Bindings::SubtleCryptoBinding::{HmacKeyGenParams, JsonWebKey};
+use crate::dom::bindings::codegen::Bindings::SubtleCryptoBinding::{KeyFormat, Pbkdf2Params};
+use crate::dom::bindings::codegen::Bindings::SubtleCryptoBinding::{
+    RsaHashedImportParams, RsaPssParams,
+};
+use crate::dom::bindings::codegen::Bindings::SubtleCryptoBinding::{
+    RsaHashedKeyGenParams, RsaOaepParams,
+};
+use crate::dom::bindings::codegen::UnionTypes::ArrayBufferViewOrArrayBufferOrJsonWebKey;
+use crate::dom::bindings::codegen::UnionTypes::{ArrayBufferViewOrArrayBuffer, ObjectOrString};
+use crate::dom::bindings::conversions::ToJSValConvertible;
+use crate::dom::bindings::conversions::{ConversionResult, FromJSValConvertible};
+use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::refcounted::TrustedPromise;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::bindings::trace::RootedTraceableBox;
+use crate::dom::cryptokey::{CryptoKey, CryptoKeyAlgorithm, Handle};
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::promise::Promise;
+use crate::realms::InRealm;
+use crate::script_runtime::JSContext;
+use crate::task_source::{TaskSource, TaskSourceName};
+use crossbeam_channel::{unbounded, SendError, Sender};
+use dom_struct::dom_struct;
+use js::jsapi::JSObject;
+use js::jsval::{ObjectValue, UndefinedValue};
+use js::rust::HandleValue;
+use js::typedarray::{ArrayBuffer, CreateWith};
+use openssl::bn::{BigNum, BigNumContext};
+use openssl::derive::Deriver;
+use openssl::ec::{EcGroup, EcKey, EcKeyRef, EcPoint, PointConversionForm};
+use openssl::ecdsa::EcdsaSig;
+use openssl::encrypt::{Decrypter, Encrypter};
+use openssl::error::ErrorStack;
+use openssl::hash::{self, MessageDigest};
+use openssl::memcmp;
+use openssl::nid::Nid;
+use openssl::pkcs5;
+use openssl::pkey::{HasPublic, PKey, Private, Public};
+use openssl::rand;
+use openssl::rsa::{Padding, Rsa, RsaRef};
+use openssl::sign::{RsaPssSaltlen, Signer, Verifier};
+use openssl::symm::{self, Cipher};
+use std::cell::RefCell;
+use std::ptr;
+use std::rc::Rc;
+use std::thread;
+
+/// The algorithms that are recognized, whether for keys or for digests.
+/// <https://w3c.github.io/webcrypto/#algorithms>
+#[derive(Clone, Copy, Debug, JSTraceable, MallocSizeOf, PartialEq)]
+pub enum AlgorithmName {
+    RsaPss,
+    RsaOaep,
+    Ecdsa,
+    Ecdh,
+    AesGcm,
+    Hmac,
+    Hkdf,
+    Pbkdf2,
+    Sha1,
+    Sha256,
+    Sha384,
+    Sha512,
+}
+
+const ALGORITHM_NAMES: [AlgorithmName; 12] = [
+    AlgorithmName::RsaPss,
+    AlgorithmName::RsaOaep,
+    AlgorithmName::Ecdsa,
+    AlgorithmName::Ecdh,
+    AlgorithmName::AesGcm,
+    AlgorithmName::Hmac,
+    AlgorithmName::Hkdf,
+    AlgorithmName::Pbkdf2,
+    AlgorithmName::Sha1,
+    AlgorithmName::Sha256,
+    AlgorithmName::Sha384,
+    AlgorithmName::Sha512,
+];
+
+impl AlgorithmName {
+    /// Algorithm names are matched case-insensitively.
+    fn from_name(name: &str) -> Option<AlgorithmName> {
+        ALGORITHM_NAMES
+            .iter()
+            .find(|algorithm| algorithm.as_str().eq_ignore_ascii_case(name))
+            .cloned()
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match *self {
+            AlgorithmName::RsaPss => "RSA-PSS",
+            AlgorithmName::RsaOaep => "RSA-OAEP",
+            AlgorithmName::Ecdsa => "ECDSA",
+            AlgorithmName::Ecdh => "ECDH",
+            AlgorithmName::AesGcm => "AES-GCM",
+            AlgorithmName::Hmac => "HMAC",
+            AlgorithmName::Hkdf => "HKDF",
+            AlgorithmName::Pbkdf2 => "PBKDF2",
+            AlgorithmName::Sha1 => "SHA-1",
+            AlgorithmName::Sha256 => "SHA-256",
+            AlgorithmName::Sha384 => "SHA-384",
+            AlgorithmName::Sha512 => "SHA-512",
+        }
+    }
+
+    fn hash(&self) -> Option<HashAlgorithm> {
+        match *self {
+            AlgorithmName::Sha1 => Some(HashAlgorithm::Sha1),
+            AlgorithmName::Sha256 => Some(HashAlgorithm::Sha256),
+            AlgorithmName::Sha384 => Some(HashAlgorithm::Sha384),
+            AlgorithmName::Sha512 => Some(HashAlgorithm::Sha512),
+            _ => None,
+        }
+    }
+}
+
+/// <https://w3c.github.io/webcrypto/#sha>
+#[derive(Clone, Copy, Debug, JSTraceable, MallocSizeOf, PartialEq)]
+pub enum HashAlgorithm {
+    Sha1,
+    Sha256,
+    Sha384,
+    Sha512,
+}
+
+impl HashAlgorithm {
+    pub fn as_str(&self) -> &'static str {
+        match *self {
+            HashAlgorithm::Sha1 => "SHA-1",
+            HashAlgorithm::Sha256 => "SHA-256",
+            HashAlgorithm::Sha384 => "SHA-384",
+            HashAlgorithm::Sha512 => "SHA-512",
+        }
+    }
+
+    fn message_digest(&self) -> MessageDigest {
+        match *self {
+            HashAlgorithm::Sha1 => MessageDigest::sha1(),
+            HashAlgorithm::Sha256 => MessageDigest::sha256(),
+            HashAlgorithm::Sha384 => MessageDigest::sha384(),
+            HashAlgorithm::Sha512 => MessageDigest::sha512(),
+        }
+    }
+
+    /// The block size of the hash function in bits, which is the length of the HMAC keys that
+    /// are generated without one.
+    /// <https://w3c.github.io/webcrypto/#hmac-operations>
+    fn block_size(&self) -> u32 {
+        match *self {
+            HashAlgorithm::Sha1 | HashAlgorithm::Sha256 => 512,
+            HashAlgorithm::Sha384 | HashAlgorithm::Sha512 => 1024,
+        }
+    }
+
+    /// The suffix of the names of the algorithms using this hash function in JSON Web Keys.
+    fn jwk_suffix(&self) -> &'static str {
+        match *self {
+            HashAlgorithm::Sha1 => "1",
+            HashAlgorithm::Sha256 => "256",
+            HashAlgorithm::Sha384 => "384",
+            HashAlgorithm::Sha512 => "512",
+        }
+    }
+}
+
+/// <https://w3c.github.io/webcrypto/#dfn-NamedCurve>
+#[derive(Clone, Copy, Debug, JSTraceable, MallocSizeOf, PartialEq)]
+pub enum NamedCurve {
+    P256,
+    P384,
+    P521,
+}
+
+impl NamedCurve {
+    /// Curve names, unlike algorithm names, are case-sensitive.
+    fn from_name(name: &str) -> Option<NamedCurve> {
+        match name {
+            "P-256" => Some(NamedCurve::P256),
+            "P-384" => Some(NamedCurve::P384),
+            "P-521" => Some(NamedCurve::P521),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match *self {
+            NamedCurve::P256 => "P-256",
+            NamedCurve::P384 => "P-384",
+            NamedCurve::P521 => "P-521",
+        }
+    }
+
+    fn nid(&self) -> Nid {
+        match *self {
+            NamedCurve::P256 => Nid::X9_62_PRIME256V1,
+            NamedCurve::P384 => Nid::SECP384R1,
+            NamedCurve::P521 => Nid::SECP521R1,
+        }
+    }
+
+    /// The length in bytes of the coordinates and private keys, and of each half of signatures.
+    fn coordinate_size(&self) -> usize {
+        match *self {
+            NamedCurve::P256 => 32,
+            NamedCurve::P384 => 48,
+            NamedCurve::P521 => 66,
+        }
+    }
+
+    /// The name of the ECDSA algorithm using this curve in JSON Web Keys.
+    fn jwk_ecdsa_alg(&self) -> &'static str {
+        match *self {
+            NamedCurve::P256 => "ES256",
+            NamedCurve::P384 => "ES384",
+            NamedCurve::P521 => "ES512",
+        }
+    }
+}
+
+/// The operations that decide which dictionary the parameters of an algorithm are read from.
+/// <https://w3c.github.io/webcrypto/#dfn-supportedAlgorithms>
+#[derive(Clone, Copy, PartialEq)]
+enum Operation {
+    Encrypt,
+    Decrypt,
+    Sign,
+    Verify,
+    Digest,
+    GenerateKey,
+    DeriveBits,
+    ImportKey,
+    GetKeyLength,
+}
+
+/// An algorithm and the parameters that were given for an operation.
+/// <https://w3c.github.io/webcrypto/#algorithm-normalization-normalize-an-algorithm>
+enum NormalizedAlgorithm {
+    /// An algorithm that takes no parameters for the operation.
+    Plain(AlgorithmName),
+    Hash(HashAlgorithm),
+    RsaHashedKeyGen {
+        name: AlgorithmName,
+        modulus_length: u32,
+        public_exponent: Vec<u8>,
+        hash: HashAlgorithm,
+    },
+    RsaHashedImport {
+        name: AlgorithmName,
+        hash: HashAlgorithm,
+    },
+    RsaPss {
+        salt_length: u32,
+    },
+    RsaOaep {
+        label: Vec<u8>,
+    },
+    Ecdsa {
+        hash: HashAlgorithm,
+    },
+    /// The parameters to generate or import ECDSA and ECDH keys.
+    EcKey {
+        name: AlgorithmName,
+        curve: NamedCurve,
+    },
+    EcdhDerive {
+        public: Key,
+    },
+    /// The parameters to generate or derive AES keys.
+    AesLength {
+        name: AlgorithmName,
+        length: u16,
+    },
+    AesGcm {
+        iv: Vec<u8>,
+        additional_data: Vec<u8>,
+        tag_length: u8,
+    },
+    /// The parameters to generate, import or derive HMAC keys.
+    Hmac {
+        hash: HashAlgorithm,
+        length: Option<u32>,
+    },
+    Hkdf {
+        hash: HashAlgorithm,
+        salt: Vec<u8>,
+        info: Vec<u8>,
+    },
+    Pbkdf2 {
+        hash: HashAlgorithm,
+        salt: Vec<u8>,
+        iterations: u32,
+    },
+}
+
+impl NormalizedAlgorithm {
+    fn name(&self) -> AlgorithmName {
+        match *self {
+            NormalizedAlgorithm::Plain(name) |
+            NormalizedAlgorithm::RsaHashedKeyGen { name, .. } |
+            NormalizedAlgorithm::RsaHashedImport { name, .. } |
+            NormalizedAlgorithm::EcKey { name, .. } |
+            NormalizedAlgorithm::AesLength { name, .. } => name,
+            NormalizedAlgorithm::Hash(hash) => match hash {
+                HashAlgorithm::Sha1 => AlgorithmName::Sha1,
+                HashAlgorithm::Sha256 => AlgorithmName::Sha256,
+                HashAlgorithm::Sha384 => AlgorithmName::Sha384,
+                HashAlgorithm::Sha512 => AlgorithmName::Sha512,
+            },
+            NormalizedAlgorithm::RsaPss { .. } => AlgorithmName::RsaPss,
+            NormalizedAlgorithm::RsaOaep { .. } => AlgorithmName::RsaOaep,
+            NormalizedAlgorithm::Ecdsa { .. } => AlgorithmName::Ecdsa,
+            NormalizedAlgorithm::EcdhDerive { .. } => AlgorithmName::Ecdh,
+            NormalizedAlgorithm::AesGcm { .. } => AlgorithmName::AesGcm,
+            NormalizedAlgorithm::Hmac { .. } => AlgorithmName::Hmac,
+            NormalizedAlgorithm::Hkdf { .. } => AlgorithmName::Hkdf,
+            NormalizedAlgorithm::Pbkdf2 { .. } => AlgorithmName::Pbkdf2,
+        }
+    }
+}
+
+/// The key data that importKey was given, which depends on the format.
+enum KeyData {
+    Bytes(Vec<u8>),
+    Jwk(Jwk),
+}
+
+enum ExportedKey {
+    Bytes(Vec<u8>),
+    Jwk(Jwk),
+}
+
+/// The members of a JsonWebKey dictionary that are used, as strings that can be moved to the
+/// crypto thread and back.
+#[derive(Default)]
+struct Jwk {
+    kty: Option<String>,
+    use_: Option<String>,
+    key_ops: Option<Vec<String>>,
+    alg: Option<String>,
+    ext: Option<bool>,
+    crv: Option<String>,
+    x: Option<String>,
+    y: Option<String>,
+    d: Option<String>,
+    n: Option<String>,
+    e: Option<String>,
+    p: Option<String>,
+    q: Option<String>,
+    dp: Option<String>,
+    dq: Option<String>,
+    qi: Option<String>,
+    /// Whether there is an `oth` member, which is not supported.
+    oth: bool,
+    k: Option<String>,
+}
+
+impl Jwk {
+    fn from_dictionary(jwk: &JsonWebKey) -> Jwk {
+        let string = |value: &Option<DOMString>| value.as_ref().map(|value| value.to_string());
+        Jwk {
+            kty: string(&jwk.kty),
+            use_: string(&jwk.use_),
+            key_ops: jwk
+                .key_ops
+                .as_ref()
+                .map(|ops| ops.iter().map(|op| op.to_string()).collect()),
+            alg: string(&jwk.alg),
+            ext: jwk.ext,
+            crv: string(&jwk.crv),
+            x: string(&jwk.x),
+            y: string(&jwk.y),
+            d: string(&jwk.d),
+            n: string(&jwk.n),
+            e: string(&jwk.e),
+            p: string(&jwk.p),
+            q: string(&jwk.q),
+            dp: string(&jwk.dp),
+            dq: string(&jwk.dq),
+            qi: string(&jwk.qi),
+            oth: jwk.oth.is_some(),
+            k: string(&jwk.k),
+        }
+    }
+
+    fn into_dictionary(self) -> JsonWebKey {
+        let string = |value: Option<String>| value.map(DOMString::from);
+        let mut jwk = JsonWebKey::empty();
+        jwk.kty = string(self.kty);
+        jwk.use_ = string(self.use_);
+        jwk.key_ops = self
+            .key_ops
+            .map(|ops| ops.into_iter().map(DOMString::from).collect());
+        jwk.alg = string(self.alg);
+        jwk.ext = self.ext;
+        jwk.crv = string(self.crv);
+        jwk.x = string(self.x);
+        jwk.y = string(self.y);
+        jwk.d = string(self.d);
+        jwk.n = string(self.n);
+        jwk.e = string(self.e);
+        jwk.p = string(self.p);
+        jwk.q = string(self.q);
+        jwk.dp = string(self.dp);
+        jwk.dq = string(self.dq);
+        jwk.qi = string(self.qi);
+        jwk.k = string(self.k);
+        jwk
+    }
+}
+
+enum GeneratedKey {
+    Key(Key),
+    Pair(Key, Key),
+}
+
+/// The parts of a CryptoKey that the operations use or create, which can be moved to the
+/// crypto thread and back.
+struct Key {
+    key_type: KeyType,
+    extractable: bool,
+    algorithm: CryptoKeyAlgorithm,
+    usages: Vec<KeyUsage>,
+    handle: Handle,
+}
+
+impl Key {
+    fn new(
+        key_type: KeyType,
+        extractable: bool,
+        algorithm: CryptoKeyAlgorithm,
+        usages: Vec<KeyUsage>,
+        handle: Handle,
+    ) -> Key {
+        Key {
+            key_type,
+            extractable,
+            algorithm,
+            usages,
+            handle,
+        }
+    }
+
+    fn of(key: &CryptoKey) -> Key {
+        Key::new(
+            key.key_type(),
+            key.extractable(),
+            key.algorithm().clone(),
+            key.usages().to_vec(),
+            key.handle().clone(),
+        )
+    }
+
+    fn into_crypto_key(self, global: &GlobalScope) -> DomRoot<CryptoKey> {
+        CryptoKey::new(
+            global,
+            self.key_type,
+            self.extractable,
+            self.algorithm,
+            self.usages,
+            self.handle,
+        )
+    }
+
+    fn key_type(&self) -> KeyType {
+        self.key_type
+    }
+
+    fn extractable(&self) -> bool {
+        self.extractable
+    }
+
+    fn algorithm(&self) -> &CryptoKeyAlgorithm {
+        &self.algorithm
+    }
+
+    fn usages(&self) -> &[KeyUsage] {
+        &self.usages
+    }
+
+    fn handle(&self) -> &Handle {
+        &self.handle
+    }
+}
+
+type CryptoJob = Box<dyn FnOnce() + Send>;
+
+thread_local!(
+    static CRYPTO_THREAD: RefCell<Option<Option<Sender<CryptoJob>>>> = RefCell::new(None)
+);
+
+/// Returns the crypto thread of the script thread, spawning it on first use. Returns `None`
+/// if the thread could not be spawned.
+fn crypto_thread() -> Option<Sender<CryptoJob>> {
+    CRYPTO_THREAD.with(|crypto_thread| {
+        crypto_thread
+            .borrow_mut()
+            .get_or_insert_with(|| {
+                let (sender, receiver) = unbounded::<CryptoJob>();
+                match thread::Builder::new()
+                    .name(String::from("WebCrypto"))
+                    .spawn(move || {
+                        for job in receiver {
+                            job();
+                        }
+                    }) {
+                    Ok(_) => Some(sender),
+                    Err(error) => {
+                        warn!("Failed to spawn the WebCrypto thread: {}", error);
+                        None
+                    },
+                }
+            })
+            .clone()
+    })
+}
+
+/// <https://w3c.github.io/webcrypto/#subtlecrypto-interface>
+///
+/// The operations are performed in parallel on the crypto thread of the script thread, and
+/// the promises that the methods return are settled in tasks once they are done.
+#[dom_struct]
+pub struct SubtleCrypto {
+    reflector_: Reflector,
+}
+
+impl SubtleCrypto {
+    fn new_inherited() -> SubtleCrypto {
+        SubtleCrypto {
+            reflector_: Reflector::new(),
+        }
+    }
+
+    pub fn new(global: &GlobalScope) -> DomRoot<SubtleCrypto> {
+        reflect_dom_object(Box::new(SubtleCrypto::new_inherited()), global)
+    }
+
+    /// Performs an operation on the crypto thread, and settles the promise with its result in
+    /// a task once it is done. The operation is performed right away if there is no crypto
+    /// thread.
+    fn perform<T, F, S>(&self, promise: &Rc<Promise>, operation: F, settle: S)
+    where
+        T: Send + 'static,
+        F: FnOnce() -> Fallible<T> + Send + 'static,
+        S: FnOnce(&GlobalScope, &Promise, T) + Send + 'static,
+    {
+        let global = self.global();
+        let task_source = global.dom_manipulation_task_source();
+        let canceller = global.task_canceller(TaskSourceName::DOMManipulation);
+        let trusted_promise = TrustedPromise::new(promise.clone());
+        let job = move || {
+            let result = operation();
+            let _ = task_source.queue_with_canceller(
+                task!(settle_crypto_promise: move || {
+                    let promise = trusted_promise.root();
+                    match result {
+                        Ok(value) => settle(&promise.global(), &promise, value),
+                        Err(error) => promise.reject_error(error),
+                    }
+                }),
+                &canceller,
+            );
+        };
+        match crypto_thread() {
+            Some(sender) => {
+                if let Err(SendError(job)) = sender.send(Box::new(job)) {
+                    job();
+                }
+            },
+            None => job(),
+        }
+    }
+}
+
+impl SubtleCryptoMethods for SubtleCrypto {
+    // https://w3c.github.io/webcrypto/#SubtleCrypto-method-encrypt
+    fn Encrypt(
+        &self,
+        cx: JSContext,
+        algorithm: ObjectOrString,
+        key: &CryptoKey,
+        data: ArrayBufferViewOrArrayBuffer,
+        comp: InRealm,
+    ) -> Rc<Promise> {
+        let promise = Promise::new_in_current_realm(&self.global(), comp);
+        let normalized = normalize_algorithm(cx, &algorithm, Operation::Encrypt);
+        let (key, data) = (Key::of(key), buffer_source_to_vec(&data));
+        self.perform(
+            &promise,
+            move || {
+                let normalized = normalized?;
+                check_key(&key, normalized.name(), KeyUsage::Encrypt)?;
+                encrypt(&normalized, &key, &data)
+            },
+            resolve_with_bytes,
+        );
+        promise
+    }
+
+    // https://w3c.github.io/webcrypto/#SubtleCrypto-method-decrypt
+    fn Decrypt(
+        &self,
+        cx: JSContext,
+        algorithm: ObjectOrString,
+        key: &CryptoKey,
+        data: ArrayBufferViewOrArrayBuffer,
+        comp: InRealm,
+    ) -> Rc<Promise> {
+        let promise = Promise::new_in_current_realm(&self.global(), comp);
+        let normalized = normalize_algorithm(cx, &algorithm, Operation::Decrypt);
+        let (key, data) = (Key::of(key), buffer_source_to_vec(&data));
+        self.perform(
+            &promise,
+            move || {
+                let normalized = normalized?;
+                check_key(&key, normalized.name(), KeyUsage::Decrypt)?;
+                decrypt(&normalized, &key, &data)
+            },
+            resolve_with_bytes,
+        );
+        promise
+    }
+
+    // https://w3c.github.io/webcrypto/#SubtleCrypto-method-sign
+    fn Sign(
+        &self,
+        cx: JSContext,
+        algorithm: ObjectOrString,
+        key: &CryptoKey,
+        data: ArrayBufferViewOrArrayBuffer,
+        comp: InRealm,
+    ) -> Rc<Promise> {
+        let promise = Promise::new_in_current_realm(&self.global(), comp);
+        let normalized = normalize_algorithm(cx, &algorithm, Operation::Sign);
+        let (key, data) = (Key::of(key), buffer_source_to_vec(&data));
+        self.perform(
+            &promise,
+            move || {
+                let normalized = normalized?;
+                check_key(&key, normalized.name(), KeyUsage::Sign)?;
+                sign(&normalized, &key, &data)
+            },
+            resolve_with_bytes,
+        );
+        promise
+    }
+
+    // https://w3c.github.io/webcrypto/#SubtleCrypto-method-verify
+    fn Verify(
+        &self,
+        cx: JSContext,
+        algorithm: ObjectOrString,
+        key: &CryptoKey,
+        signature: ArrayBufferViewOrArrayBuffer,
+        data: ArrayBufferViewOrArrayBuffer,
+        comp: InRealm,
+    ) -> Rc<Promise> {
+        let promise = Promise::new_in_current_realm(&self.global(), comp);
+        let normalized = normalize_algorithm(cx, &algorithm, Operation::Verify);
+        let key = Key::of(key);
+        let signature = buffer_source_to_vec(&signature);
+        let data = buffer_source_to_vec(&data);
+        self.perform(
+            &promise,
+            move || {
+                let normalized = normalized?;
+                check_key(&key, normalized.name(), KeyUsage::Verify)?;
+                verify(&normalized, &key, &signature, &data)
+            },
+            |_, promise, valid| promise.resolve_native(&valid),
+        );
+        promise
+    }
+
+    // https://w3c.github.io/webcrypto/#SubtleCrypto-method-digest
+    fn Digest(
+        &self,
+        cx: JSContext,
+        algorithm: ObjectOrString,
+        data: ArrayBufferViewOrArrayBuffer,
+        comp: InRealm,
+    ) -> Rc<Promise> {
+        let promise = Promise::new_in_current_realm(&self.global(), comp);
+        let hash = normalize_hash(cx, &algorithm);
+        let data = buffer_source_to_vec(&data);
+        self.perform(
+            &promise,
+            move || {
+                hash::hash(hash?.message_digest(), &data)
+                    .map(|digest| digest.to_vec())
+                    .map_err(operation_error)
+            },
+            resolve_with_bytes,
+        );
+        promise
+    }
+
+    // https://w3c.github.io/webcrypto/#SubtleCrypto-method-generateKey
+    fn GenerateKey(
+        &self,
+        cx: JSContext,
+        algorithm: ObjectOrString,
+        extractable: bool,
+        key_usages: Vec<KeyUsage>,
+        comp: InRealm,
+    ) -> Rc<Promise> {
+        let promise = Promise::new_in_current_realm(&self.global(), comp);
+        let normalized = normalize_algorithm(cx, &algorithm, Operation::GenerateKey);
+        self.perform(
+            &promise,
+            move || generate_key(&normalized?, extractable, &key_usages),
+            |global, promise, generated| match generated {
+                GeneratedKey::Key(key) => promise.resolve_native(&key.into_crypto_key(global)),
+                GeneratedKey::Pair(public_key, private_key) => {
+                    promise.resolve_native(&CryptoKeyPair {
+                        publicKey: Some(public_key.into_crypto_key(global)),
+                        privateKey: Some(private_key.into_crypto_key(global)),
+                    })
+                },
+            },
+        );
+        promise
+    }
+
+    // https://w3c.github.io/webcrypto/#SubtleCrypto-method-deriveKey
+    fn DeriveKey(
+        &self,
+        cx: JSContext,
+        algorithm: ObjectOrString,
+        base_key: &CryptoKey,
+        derived_key_type: ObjectOrString,
+        extractable: bool,
+        key_usages: Vec<KeyUsage>,
+        comp: InRealm,
+    ) -> Rc<Promise> {
+        let promise = Promise::new_in_current_realm(&self.global(), comp);
+        let normalized = normalize_algorithm(cx, &algorithm, Operation::DeriveBits);
+        let import_algorithm = normalize_algorithm(cx, &derived_key_type, Operation::ImportKey);
+        let length_algorithm = normalize_algorithm(cx, &derived_key_type, Operation::GetKeyLength);
+        let base_key = Key::of(base_key);
+        self.perform(
+            &promise,
+            move || {
+                derive_key(
+                    &normalized?,
+                    &import_algorithm?,
+                    &length_algorithm?,
+                    &base_key,
+                    extractable,
+                    &key_usages,
+                )
+            },
+            |global, promise, key| promise.resolve_native(&key.into_crypto_key(global)),
+        );
+        promise
+    }
+
+    // https://w3c.github.io/webcrypto/#SubtleCrypto-method-deriveBits
+    fn DeriveBits(
+        &self,
+        cx: JSContext,
+        algorithm: ObjectOrString,
+        base_key: &CryptoKey,
+        length: u32,
+        comp: InRealm,
+    ) -> Rc<Promise> {
+        let promise = Promise::new_in_current_realm(&self.global(), comp);
+        let normalized = normalize_algorithm(cx, &algorithm, Operation::DeriveBits);
+        let base_key = Key::of(base_key);
+        self.perform(
+            &promise,
+            move || {
+                let normalized = normalized?;
+                check_key(&base_key, normalized.name(), KeyUsage::DeriveBits)?;
+                derive_bits(&normalized, &base_key, length)
+            },
+            resolve_with_bytes,
+        );
+        promise
+    }
+
+    // https://w3c.github.io/webcrypto/#SubtleCrypto-method-importKey
+    fn ImportKey(
+        &self,
+        cx: JSContext,
+        format: KeyFormat,
+        key_data: ArrayBufferViewOrArrayBufferOrJsonWebKey,
+        algorithm: ObjectOrString,
+        extractable: bool,
+        key_usages: Vec<KeyUsage>,
+        comp: InRealm,
+    ) -> Rc<Promise> {
+        let promise = Promise::new_in_current_realm(&self.global(), comp);
+        let key_data = match (format, key_data) {
+            (KeyFormat::Jwk, ArrayBufferViewOrArrayBufferOrJsonWebKey::JsonWebKey(jwk)) => {
+                Ok(KeyData::Jwk(Jwk::from_dictionary(&jwk)))
+            },
+            (KeyFormat::Jwk, _) | (_, ArrayBufferViewOrArrayBufferOrJsonWebKey::JsonWebKey(_)) => {
+                Err(Error::Type(
+                    "The key data does not match the format".to_owned(),
+                ))
+            },
+            (_, ArrayBufferViewOrArrayBufferOrJsonWebKey::ArrayBufferView(view)) => {
+                Ok(KeyData::Bytes(view.to_vec()))
+            },
+            (_, ArrayBufferViewOrArrayBufferOrJsonWebKey::ArrayBuffer(buffer)) => {
+                Ok(KeyData::Bytes(buffer.to_vec()))
+            },
+        };
+        let normalized = key_data.and_then(|key_data| {
+            normalize_algorithm(cx, &algorithm, Operation::ImportKey)
+                .map(|normalized| (key_data, normalized))
+        });
+        self.perform(
+            &promise,
+            move || {
+                let (key_data, normalized) = normalized?;
+                import_key(format, key_data, &normalized, extractable, &key_usages)
+            },
+            |global, promise, key| promise.resolve_native(&key.into_crypto_key(global)),
+        );
+        promise
+    }
+
+    // https://w3c.github.io/webcrypto/#SubtleCrypto-method-exportKey
+    fn ExportKey(&self, format: KeyFormat, key: &CryptoKey, comp: InRealm) -> Rc<Promise> {
+        let promise = Promise::new_in_current_realm(&self.global(), comp);
+        let key = Key::of(key);
+        self.perform(
+            &promise,
+            move || export_key(format, &key),
+            |global, promise, exported| match exported {
+                ExportedKey::Bytes(bytes) => resolve_with_bytes(global, promise, bytes),
+                ExportedKey::Jwk(jwk) => promise.resolve_native(&jwk.into_dictionary()),
+            },
+        );
+        promise
+    }
+}
+
+#[allow(unsafe_code)]
+fn resolve_with_bytes(global: &GlobalScope, promise: &Promise, bytes: Vec<u8>) {
+    let cx = global.get_cx();
+    rooted!(in(*cx) let mut array_buffer = ptr::null_mut::<JSObject>());
+    let created =
+        unsafe { ArrayBuffer::create(*cx, CreateWith::Slice(&bytes), array_buffer.handle_mut()) };
+    if created.is_err() {
+        return promise.reject_error(Error::JSFailed);
+    }
+    rooted!(in(*cx) let value = ObjectValue(array_buffer.get()));
+    promise.resolve(cx, value.handle());
+}
+
+fn buffer_source_to_vec(buffer: &ArrayBufferViewOrArrayBuffer) -> Vec<u8> {
+    match *buffer {
+        ArrayBufferViewOrArrayBuffer::ArrayBufferView(ref view) => view.to_vec(),
+        ArrayBufferViewOrArrayBuffer::ArrayBuffer(ref buffer) => buffer.to_vec(),
+    }
+}
+
+#[allow(unsafe_code)]
+fn dictionary_from_jsval<T>(cx: JSContext, value: HandleValue) -> Fallible<T>
+where
+    T: FromJSValConvertible<Config = ()>,
+{
+    match unsafe { T::from_jsval(*cx, value, ()) } {
+        Ok(ConversionResult::Success(dictionary)) => Ok(dictionary),
+        Ok(ConversionResult::Failure(error)) => Err(Error::Type(error.into_owned())),
+        Err(()) => Err(Error::JSFailed),
+    }
+}
+
+/// <https://w3c.github.io/webcrypto/#algorithm-normalization-normalize-an-algorithm>
+#[allow(unsafe_code)]
+fn normalize_algorithm(
+    cx: JSContext,
+    algorithm: &ObjectOrString,
+    operation: Operation,
+) -> Fallible<NormalizedAlgorithm> {
+    // A string is the same as an object with only a name.
+    rooted!(in(*cx) let mut value = UndefinedValue());
+    match *algorithm {
+        ObjectOrString::Object(ref object) => value.set(ObjectValue(object.get())),
+        ObjectOrString::String(ref name) => unsafe {
+            let algorithm = Algorithm { name: name.clone() };
+            algorithm.to_jsval(*cx, value.handle_mut());
+        },
+    }
+    let value = value.handle();
+
+    let algorithm: Algorithm = dictionary_from_jsval(cx, value)?;
+    let name = AlgorithmName::from_name(&algorithm.name).ok_or(Error::NotSupported)?;
+    let normalized = match (operation, name) {
+        (Operation::Digest, _) => match name.hash() {
+            Some(hash) => NormalizedAlgorithm::Hash(hash),
+            None => return Err(Error::NotSupported),
+        },
+        (Operation::Encrypt, AlgorithmName::RsaOaep) |
+        (Operation::Decrypt, AlgorithmName::RsaOaep) => {
+            let params: RootedTraceableBox<RsaOaepParams> = dictionary_from_jsval(cx, value)?;
+            NormalizedAlgorithm::RsaOaep {
+                label: params.label.as_ref().map_or(vec![], buffer_source_to_vec),
+            }
+        },
+        (Operation::Encrypt, AlgorithmName::AesGcm) |
+        (Operation::Decrypt, AlgorithmName::AesGcm) => {
+            let params: RootedTraceableBox<AesGcmParams> = dictionary_from_jsval(cx, value)?;
+            NormalizedAlgorithm::AesGcm {
+                iv: buffer_source_to_vec(&params.iv),
+                additional_data: params
+                    .additionalData
+                    .as_ref()
+                    .map_or(vec![], buffer_source_to_vec),
+                tag_length: params.tagLength.unwrap_or(128),
+            }
+        },
+        (Operation::Sign, AlgorithmName::RsaPss) | (Operation::Verify, AlgorithmName::RsaPss) => {
+            let params: RsaPssParams = dictionary_from_jsval(cx, value)?;
+            NormalizedAlgorithm::RsaPss {
+                salt_length: params.saltLength,
+            }
+        },
+        (Operation::Sign, AlgorithmName::Ecdsa) | (Operation::Verify, AlgorithmName::Ecdsa) => {
+            let params: RootedTraceableBox<EcdsaParams> = dictionary_from_jsval(cx, value)?;
+            NormalizedAlgorithm::Ecdsa {
+                hash: normalize_hash(cx, &params.hash)?,
+            }
+        },
+        (Operation::Sign, AlgorithmName::Hmac) |
+        (Operation::Verify, AlgorithmName::Hmac) |
+        (Operation::ImportKey, AlgorithmName::AesGcm) |
+        (Operation::ImportKey, AlgorithmName::Hkdf) |
+        (Operation::ImportKey, AlgorithmName::Pbkdf2) |
+        (Operation::GetKeyLength, AlgorithmName::Hkdf) |
+        (Operation::GetKeyLength, AlgorithmName::Pbkdf2) => NormalizedAlgorithm::Plain(name),
+        (Operation::GenerateKey, AlgorithmName::RsaPss) |
+        (Operation::GenerateKey, AlgorithmName::RsaOaep) => {
+            let params: RootedTraceableBox<RsaHashedKeyGenParams> =
+                dictionary_from_jsval(cx, value)?;
+            NormalizedAlgorithm::RsaHashedKeyGen {
+                name,
+                modulus_length: params.modulusLength,
+                public_exponent: buffer_source_to_vec(&params.publicExponent),
+                hash: normalize_hash(cx, &params.hash)?,
+            }
+        },
+        (Operation::ImportKey, AlgorithmName::RsaPss) |
+        (Operation::ImportKey, AlgorithmName::RsaOaep) => {
+            let params: RootedTraceableBox<RsaHashedImportParams> =
+                dictionary_from_jsval(cx, value)?;
+            NormalizedAlgorithm::RsaHashedImport {
+                name,
+                hash: normalize_hash(cx, &params.hash)?,
+            }
+        },
+        (Operation::GenerateKey, AlgorithmName::Ecdsa) |
+        (Operation::GenerateKey, AlgorithmName::Ecdh) => {
+            let params: EcKeyGenParams = dictionary_from_jsval(cx, value)?;
+            NormalizedAlgorithm::EcKey {
+                name,
+                curve: NamedCurve::from_name(&params.namedCurve).ok_or(Error::NotSupported)?,
+            }
+        },
+        (Operation::ImportKey, AlgorithmName::Ecdsa) |
+        (Operation::ImportKey, AlgorithmName::Ecdh) => {
+            let params: EcKeyImportParams = dictionary_from_jsval(cx, value)?;
+            NormalizedAlgorithm::EcKey {
+                name,
+                curve: NamedCurve::from_name(&params.namedCurve).ok_or(Error::NotSupported)?,
+            }
+        },
+        (Operation::DeriveBits, AlgorithmName::Ecdh) => {
+            let params: EcdhKeyDeriveParams = dictionary_from_jsval(cx, value)?;
+            NormalizedAlgorithm::EcdhDerive {
+                public: Key::of(&params.public),
+            }
+        },
+        (Operation::GenerateKey, AlgorithmName::AesGcm) => {
+            let params: AesKeyGenParams = dictionary_from_jsval(cx, value)?;
+            NormalizedAlgorithm::AesLength {
+                name,
+                length: params.length,
+            }
+        },
+        (Operation::GetKeyLength, AlgorithmName::AesGcm) => {
+            let params: AesDerivedKeyParams = dictionary_from_jsval(cx, value)?;
+            NormalizedAlgorithm::AesLength {
+                name,
+                length: params.length,
+            }
+        },
+        (Operation::GenerateKey, AlgorithmName::Hmac) => {
+            let params: RootedTraceableBox<HmacKeyGenParams> = dictionary_from_jsval(cx, value)?;
+            NormalizedAlgorithm::Hmac {
+                hash: normalize_hash(cx, &params.hash)?,
+                length: params.length,
+            }
+        },
+        (Operation::ImportKey, AlgorithmName::Hmac) |
+        (Operation::GetKeyLength, AlgorithmName::Hmac) => {
+            let params: RootedTraceableBox<HmacImportParams> = dictionary_from_jsval(cx, value)?;
+            NormalizedAlgorithm::Hmac {
+                hash: normalize_hash(cx, &params.hash)?,
+                length: params.length,
+            }
+        },
+        (Operation::DeriveBits, AlgorithmName::Hkdf) => {
+            let params: RootedTraceableBox<HkdfParams> = dictionary_from_jsval(cx, value)?;
+            NormalizedAlgorithm::Hkdf {
+                hash: normalize_hash(cx, &params.hash)?,
+                salt: buffer_source_to_vec(&params.salt),
+                info: buffer_source_to_vec(&params.info),
+            }
+        },
+        (Operation::DeriveBits, AlgorithmName::Pbkdf2) => {
+            let params: RootedTraceableBox<Pbkdf2Params> = dictionary_from_jsval(cx, value)?;
+            NormalizedAlgorithm::Pbkdf2 {
+                hash: normalize_hash(cx, &params.hash)?,
+                salt: buffer_source_to_vec(&params.salt),
+                iterations: params.iterations,
+            }
+        },
+        _ => return Err(Error::NotSupported),
+    };
+    Ok(normalized)
+}
+
+fn normalize_hash(cx: JSContext, hash: &ObjectOrString) -> Fallible<HashAlgorithm> {
+    match normalize_algorithm(cx, hash, Operation::Digest)? {
+        NormalizedAlgorithm::Hash(hash) => Ok(hash),
+        _ => Err(Error::NotSupported),
+    }
+}
+
+fn operation_error(_: ErrorStack) -> Error {
+    Error::Operation
+}
+
+fn data_error(_: ErrorStack) -> Error {
+    Error::Data
+}
+
+/// Checks that the key can be used for an operation of the algorithm.
+fn check_key(key: &Key, name: AlgorithmName, usage: KeyUsage) -> Fallible<()> {
+    if key.algorithm().name() != name || !key.usages().contains(&usage) {
+        return Err(Error::InvalidAccess);
+    }
+    Ok(())
+}
+
+/// Fails with a SyntaxError if a key can't be created for one of the usages.
+fn check_usages(usages: &[KeyUsage], allowed: &[KeyUsage]) -> Fallible<()> {
+    if usages.iter().any(|usage| !allowed.contains(usage)) {
+        return Err(Error::Syntax);
+    }
+    Ok(())
+}
+
+/// The usages that are among the allowed ones, without duplicates, in the order of the
+/// KeyUsage enum.
+fn usages_within(usages: &[KeyUsage], allowed: &[KeyUsage]) -> Vec<KeyUsage> {
+    let mut usages: Vec<KeyUsage> = usages
+        .iter()
+        .filter(|usage| allowed.contains(usage))
+        .cloned()
+        .collect();
+    usages.sort_by_key(|usage| *usage as usize);
+    usages.dedup();
+    usages
+}
+
+fn secret(key: &Key) -> Fallible<&[u8]> {
+    match *key.handle() {
+        Handle::Secret(ref bytes) => Ok(bytes),
+        _ => Err(Error::InvalidAccess),
+    }
+}
+
+fn private_key(key: &Key) -> Fallible<&PKey<Private>> {
+    match *key.handle() {
+        Handle::Private(ref pkey) => Ok(pkey),
+        _ => Err(Error::InvalidAccess),
+    }
+}
+
+fn public_key(key: &Key) -> Fallible<&PKey<Public>> {
+    match *key.handle() {
+        Handle::Public(ref pkey) => Ok(pkey),
+        _ => Err(Error::InvalidAccess),
+    }
+}
+
+fn key_hash(key: &Key) -> Fallible<HashAlgorithm> {
+    match *key.algorithm() {
+        CryptoKeyAlgorithm::RsaHashed { hash, .. } | CryptoKeyAlgorithm::Hmac { hash, .. } => {
+            Ok(hash)
+        },
+        _ => Err(Error::InvalidAccess),
+    }
+}
+
+fn key_curve(key: &Key) -> Fallible<NamedCurve> {
+    match *key.algorithm() {
+        CryptoKeyAlgorithm::Ec { curve, .. } => Ok(curve),
+        _ => Err(Error::InvalidAccess),
+    }
+}
+
+fn left_pad(bytes: Vec<u8>, size: usize) -> Vec<u8> {
+    let mut padded = vec![0; size.saturating_sub(bytes.len())];
+    padded.extend(bytes);
+    padded
+}
+
+fn random_bytes(length: usize) -> Fallible<Vec<u8>> {
+    let mut bytes = vec![0; length];
+    rand::rand_bytes(&mut bytes).map_err(operation_error)?;
+    Ok(bytes)
+}
+
+fn hmac(hash: HashAlgorithm, key: &[u8], data: &[u8]) -> Result<Vec<u8>, ErrorStack> {
+    let pkey = PKey::hmac(key)?;
+    let mut signer = Signer::new(hash.message_digest(), &pkey)?;
+    signer.update(data)?;
+    signer.sign_to_vec()
+}
+
+fn aes_gcm_cipher(key: &[u8]) -> Fallible<Cipher> {
+    match key.len() {
+        16 => Ok(Cipher::aes_128_gcm()),
+        24 => Ok(Cipher::aes_192_gcm()),
+        32 => Ok(Cipher::aes_256_gcm()),
+        _ => Err(Error::Operation),
+    }
+}
+
+/// <https://w3c.github.io/webcrypto/#aes-gcm-operations>
+fn check_tag_length(tag_length: u8) -> Fallible<usize> {
+    match tag_length {
+        32 | 64 | 96 | 104 | 112 | 120 | 128 => Ok(tag_length as usize / 8),
+        _ => Err(Error::Operation),
+    }
+}
+
+fn encrypt(normalized: &NormalizedAlgorithm, key: &Key, data: &[u8]) -> Fallible<Vec<u8>> {
+    match *normalized {
+        // https://w3c.github.io/webcrypto/#rsa-oaep-operations
+        NormalizedAlgorithm::RsaOaep { ref label } => {
+            let pkey = public_key(key)?;
+            let hash = key_hash(key)?;
+            let mut encrypter = Encrypter::new(pkey).map_err(operation_error)?;
+            encrypter
+                .set_rsa_padding(Padding::PKCS1_OAEP)
+                .and_then(|_| encrypter.set_rsa_oaep_md(hash.message_digest()))
+                .and_then(|_| encrypter.set_rsa_mgf1_md(hash.message_digest()))
+                .and_then(|_| encrypter.set_rsa_oaep_label(label))
+                .map_err(operation_error)?;
+            let mut ciphertext = vec![0; encrypter.encrypt_len(data).map_err(operation_error)?];
+            let length = encrypter
+                .encrypt(data, &mut ciphertext)
+                .map_err(operation_error)?;
+            ciphertext.truncate(length);
+            Ok(ciphertext)
+        },
+        // https://w3c.github.io/webcrypto/#aes-gcm-operations
+        NormalizedAlgorithm::AesGcm {
+            ref iv,
+            ref additional_data,
+            tag_length,
+        } => {
+            let secret = secret(key)?;
+            let mut tag = vec![0; check_tag_length(tag_length)?];
+            let mut ciphertext = symm::encrypt_aead(
+                aes_gcm_cipher(secret)?,
+                secret,
+                Some(iv),
+                additional_data,
+                data,
+                &mut tag,
+            )
+            .map_err(operation_error)?;
+            ciphertext.extend_from_slice(&tag);
+            Ok(ciphertext)
+        },
+        _ => Err(Error::NotSupported),
+    }
+}
+
+fn decrypt(normalized: &NormalizedAlgorithm, key: &Key, data: &[u8]) -> Fallible<Vec<u8>> {
+    match *normalized {
+        // https://w3c.github.io/webcrypto/#rsa-oaep-operations
+        NormalizedAlgorithm::RsaOaep { ref label } => {
+            let pkey = private_key(key)?;
+            let hash = key_hash(key)?;
+            let mut decrypter = Decrypter::new(pkey).map_err(operation_error)?;
+            decrypter
+                .set_rsa_padding(Padding::PKCS1_OAEP)
+                .and_then(|_| decrypter.set_rsa_oaep_md(hash.message_digest()))
+                .and_then(|_| decrypter.set_rsa_mgf1_md(hash.message_digest()))
+                .and_then(|_| decrypter.set_rsa_oaep_label(label))
+                .map_err(operation_error)?;
+            let mut plaintext = vec![0; decrypter.decrypt_len(data).map_err(operation_error)?];
+            let length = decrypter
+                .decrypt(data, &mut plaintext)
+                .map_err(operation_error)?;
+            plaintext.truncate(length);
+            Ok(plaintext)
+        },
+        // https://w3c.github.io/webcrypto/#aes-gcm-operations
+        NormalizedAlgorithm::AesGcm {
+            ref iv,
+            ref additional_data,
+            tag_length,
+        } => {
+            let secret = secret(key)?;
+            let tag_length = check_tag_length(tag_length)?;
+            if data.len() < tag_length {
+                return Err(Error::Operation);
+            }
+            let (ciphertext, tag) = data.split_at(data.len() - tag_length);
+            symm::decrypt_aead(
+                aes_gcm_cipher(secret)?,
+                secret,
+                Some(iv),
+                additional_data,
+                ciphertext,
+                tag,
+            )
+            .map_err(operation_error)
+        },
+        _ => Err(Error::NotSupported),
+    }
+}
+
+fn sign(normalized: &NormalizedAlgorithm, key: &Key, data: &[u8]) -> Fallible<Vec<u8>> {
+    match *normalized {
+        // https://w3c.github.io/webcrypto/#rsa-pss-operations
+        NormalizedAlgorithm::RsaPss { salt_length } => {
+            let pkey = private_key(key)?;
+            let hash = key_hash(key)?;
+            let mut signer = Signer::new(hash.message_digest(), pkey).map_err(operation_error)?;
+            signer
+                .set_rsa_padding(Padding::PKCS1_PSS)
+                .and_then(|_| signer.set_rsa_pss_saltlen(RsaPssSaltlen::custom(salt_length as i32)))
+                .and_then(|_| signer.set_rsa_mgf1_md(hash.message_digest()))
+                .and_then(|_| signer.update(data))
+                .and_then(|_| signer.sign_to_vec())
+                .map_err(operation_error)
+        },
+        // https://w3c.github.io/webcrypto/#ecdsa-operations
+        NormalizedAlgorithm::Ecdsa { hash } => {
+            let pkey = private_key(key)?;
+            let size = key_curve(key)?.coordinate_size();
+            let digest = hash::hash(hash.message_digest(), data).map_err(operation_error)?;
+            let ec_key = pkey.ec_key().map_err(operation_error)?;
+            let signature = EcdsaSig::sign(&digest, &ec_key).map_err(operation_error)?;
+            // The signature is r and s next to each other, rather than in DER.
+            let mut result = left_pad(signature.r().to_vec(), size);
+            result.extend(left_pad(signature.s().to_vec(), size));
+            Ok(result)
+        },
+        // https://w3c.github.io/webcrypto/#hmac-operations
+        NormalizedAlgorithm::Plain(AlgorithmName::Hmac) => {
+            hmac(key_hash(key)?, secret(key)?, data).map_err(operation_error)
+        },
+        _ => Err(Error::NotSupported),
+    }
+}
+
+fn verify(
+    normalized: &NormalizedAlgorithm,
+    key: &Key,
+    signature: &[u8],
+    data: &[u8],
+) -> Fallible<bool> {
+    match *normalized {
+        // https://w3c.github.io/webcrypto/#rsa-pss-operations
+        NormalizedAlgorithm::RsaPss { salt_length } => {
+            let pkey = public_key(key)?;
+            let hash = key_hash(key)?;
+            let mut verifier =
+                Verifier::new(hash.message_digest(), pkey).map_err(operation_error)?;
+            verifier
+                .set_rsa_padding(Padding::PKCS1_PSS)
+                .and_then(|_| {
+                    verifier.set_rsa_pss_saltlen(RsaPssSaltlen::custom(salt_length as i32))
+                })
+                .and_then(|_| verifier.set_rsa_mgf1_md(hash.message_digest()))
+                .and_then(|_| verifier.update(data))
+                .map_err(operation_error)?;
+            // Signatures that OpenSSL fails to parse are not valid either.
+            Ok(verifier.verify(signature).unwrap_or(false))
+        },
+        // https://w3c.github.io/webcrypto/#ecdsa-operations
+        NormalizedAlgorithm::Ecdsa { hash } => {
+            let pkey = public_key(key)?;
+            let size = key_curve(key)?.coordinate_size();
+            if signature.len() != 2 * size {
+                return Ok(false);
+            }
+            let digest = hash::hash(hash.message_digest(), data).map_err(operation_error)?;
+            let ec_key = pkey.ec_key().map_err(operation_error)?;
+            let (r, s) = signature.split_at(size);
+            let signature = BigNum::from_slice(r)
+                .and_then(|r| BigNum::from_slice(s).map(|s| (r, s)))
+                .and_then(|(r, s)| EcdsaSig::from_private_components(r, s))
+                .map_err(operation_error)?;
+            Ok(signature.verify(&digest, &ec_key).unwrap_or(false))
+        },
+        // https://w3c.github.io/webcrypto/#hmac-operations
+        NormalizedAlgorithm::Plain(AlgorithmName::Hmac) => {
+            let mac = hmac(key_hash(key)?, secret(key)?, data).map_err(operation_error)?;
+            Ok(mac.len() == signature.len() && memcmp::eq(&mac, signature))
+        },
+        _ => Err(Error::NotSupported),
+    }
+}
+
+/// Keeps the first bits of a secret.
+fn truncate_bits(mut secret: Vec<u8>, length: u32) -> Fallible<Vec<u8>> {
+    let length = length as usize;
+    if length > secret.len() * 8 {
+        return Err(Error::Operation);
+    }
+    secret.truncate((length + 7) / 8);
+    if length % 8 != 0 {
+        if let Some(last) = secret.last_mut() {
+            *last &= 0xff << (8 - length % 8);
+        }
+    }
+    Ok(secret)
+}
+
+/// <https://tools.ietf.org/html/rfc5869>
+fn hkdf(
+    hash: HashAlgorithm,
+    key: &[u8],
+    salt: &[u8],
+    info: &[u8],
+    length: usize,
+) -> Result<Vec<u8>, ErrorStack> {
+    let hash_length = hash.message_digest().size();
+    let salt = if salt.is_empty() {
+        vec![0; hash_length]
+    } else {
+        salt.to_vec()
+    };
+    let pseudorandom_key = hmac(hash, &salt, key)?;
+
+    let mut output = Vec::with_capacity(length);
+    let mut block = vec![];
+    let mut counter = 1u8;
+    while output.len() < length {
+        let mut input = block;
+        input.extend_from_slice(info);
+        input.push(counter);
+        block = hmac(hash, &pseudorandom_key, &input)?;
+        output.extend_from_slice(&block);
+        counter = counter.wrapping_add(1);
+    }
+    output.truncate(length);
+    Ok(output)
+}
+
+fn derive_bits(normalized: &NormalizedAlgorithm, key: &Key, length: u32) -> Fallible<Vec<u8>> {
+    match *normalized {
+        // https://w3c.github.io/webcrypto/#ecdh-operations
+        NormalizedAlgorithm::EcdhDerive { ref public } => {
+            let private = private_key(key)?;
+            if public.key_type() != KeyType::Public ||
+                public.algorithm().name() != AlgorithmName::Ecdh ||
+                key_curve(public)? != key_curve(key)?
+            {
+                return Err(Error::InvalidAccess);
+            }
+            let mut deriver = Deriver::new(private).map_err(operation_error)?;
+            deriver
+                .set_peer(public_key(public)?)
+                .map_err(operation_error)?;
+            let secret = deriver.derive_to_vec().map_err(operation_error)?;
+            truncate_bits(secret, length)
+        },
+        // https://w3c.github.io/webcrypto/#hkdf-operations
+        NormalizedAlgorithm::Hkdf {
+            hash,
+            ref salt,
+            ref info,
+        } => {
+            let length = length as usize;
+            if length % 8 != 0 || length / 8 > 255 * hash.message_digest().size() {
+                return Err(Error::Operation);
+            }
+            hkdf(hash, secret(key)?, salt, info, length / 8).map_err(operation_error)
+        },
+        // https://w3c.github.io/webcrypto/#pbkdf2-operations
+        NormalizedAlgorithm::Pbkdf2 {
+            hash,
+            ref salt,
+            iterations,
+        } => {
+            if length == 0 || length % 8 != 0 || iterations == 0 {
+                return Err(Error::Operation);
+            }
+            let mut output = vec![0; length as usize / 8];
+            pkcs5::pbkdf2_hmac(
+                secret(key)?,
+                salt,
+                iterations as usize,
+                hash.message_digest(),
+                &mut output,
+            )
+            .map_err(operation_error)?;
+            Ok(output)
+        },
+        _ => Err(Error::NotSupported),
+    }
+}
+
+/// <https://w3c.github.io/webcrypto/#dfn-get-key-length>
+fn key_length(normalized: &NormalizedAlgorithm) -> Fallible<u32> {
+    match *normalized {
+        NormalizedAlgorithm::AesLength { length, .. } => match length {
+            128 | 192 | 256 => Ok(length as u32),
+            _ => Err(Error::Operation),
+        },
+        NormalizedAlgorithm::Hmac { hash, length } => match length {
+            Some(0) => Err(Error::Type("The length of a key can't be zero".to_owned())),
+            Some(length) => Ok(length),
+            None => Ok(hash.block_size()),
+        },
+        // Keys for HKDF and PBKDF2 have no length of their own.
+        _ => Err(Error::NotSupported),
+    }
+}
+
+/// <https://w3c.github.io/webcrypto/#SubtleCrypto-method-deriveKey>
+fn derive_key(
+    normalized: &NormalizedAlgorithm,
+    import_algorithm: &NormalizedAlgorithm,
+    length_algorithm: &NormalizedAlgorithm,
+    base_key: &Key,
+    extractable: bool,
+    usages: &[KeyUsage],
+) -> Fallible<Key> {
+    check_key(base_key, normalized.name(), KeyUsage::DeriveKey)?;
+    let length = key_length(length_algorithm)?;
+    let secret = derive_bits(normalized, base_key, length)?;
+    import_key(
+        KeyFormat::Raw,
+        KeyData::Bytes(secret),
+        import_algorithm,
+        extractable,
+        usages,
+    )
+}
+
+fn generate_key(
+    normalized: &NormalizedAlgorithm,
+    extractable: bool,
+    usages: &[KeyUsage],
+) -> Fallible<GeneratedKey> {
+    let generated = match *normalized {
+        // https://w3c.github.io/webcrypto/#rsa-pss-operations
+        // https://w3c.github.io/webcrypto/#rsa-oaep-operations
+        NormalizedAlgorithm::RsaHashedKeyGen {
+            name,
+            modulus_length,
+            ref public_exponent,
+            hash,
+        } => {
+            let (private_usages, public_usages) = rsa_usages(name);
+            check_usages(usages, &[private_usages, public_usages].concat())?;
+            let exponent = BigNum::from_slice(public_exponent).map_err(operation_error)?;
+            let rsa = Rsa::generate_with_e(modulus_length, &exponent).map_err(operation_error)?;
+            let public = rsa
+                .n()
+                .to_owned()
+                .and_then(|n| rsa.e().to_owned().map(|e| (n, e)))
+                .and_then(|(n, e)| Rsa::from_public_components(n, e))
+                .and_then(PKey::from_rsa)
+                .map_err(operation_error)?;
+            let private = PKey::from_rsa(rsa).map_err(operation_error)?;
+
+            let algorithm = CryptoKeyAlgorithm::RsaHashed {
+                name,
+                modulus_length,
+                public_exponent: public_exponent.clone(),
+                hash,
+            };
+            key_pair(
+                algorithm,
+                extractable,
+                usages,
+                (private_usages, private),
+                (public_usages, public),
+            )
+        },
+        // https://w3c.github.io/webcrypto/#ecdsa-operations
+        // https://w3c.github.io/webcrypto/#ecdh-operations
+        NormalizedAlgorithm::EcKey { name, curve } => {
+            let (private_usages, public_usages) = ec_usages(name);
+            check_usages(usages, &[private_usages, public_usages].concat())?;
+            let group = EcGroup::from_curve_name(curve.nid()).map_err(operation_error)?;
+            let private = EcKey::generate(&group).map_err(operation_error)?;
+            let public = EcKey::from_public_key(&group, private.public_key())
+                .and_then(PKey::from_ec_key)
+                .map_err(operation_error)?;
+            let private = PKey::from_ec_key(private).map_err(operation_error)?;
+
+            let algorithm = CryptoKeyAlgorithm::Ec { name, curve };
+            key_pair(
+                algorithm,
+                extractable,
+                usages,
+                (private_usages, private),
+                (public_usages, public),
+            )
+        },
+        // https://w3c.github.io/webcrypto/#aes-gcm-operations
+        NormalizedAlgorithm::AesLength { name, length } => {
+            check_usages(usages, AES_USAGES)?;
+            if length != 128 && length != 192 && length != 256 {
+                return Err(Error::Operation);
+            }
+            let algorithm = CryptoKeyAlgorithm::Aes { name, length };
+            let handle = Handle::Secret(random_bytes(length as usize / 8)?);
+            GeneratedKey::Key(secret_key(
+                algorithm,
+                extractable,
+                usages,
+                AES_USAGES,
+                handle,
+            ))
+        },
+        // https://w3c.github.io/webcrypto/#hmac-operations
+        NormalizedAlgorithm::Hmac { hash, length } => {
+            check_usages(usages, HMAC_USAGES)?;
+            let length = length.unwrap_or(hash.block_size());
+            if length == 0 {
+                return Err(Error::Operation);
+            }
+            let algorithm = CryptoKeyAlgorithm::Hmac { hash, length };
+            let handle = Handle::Secret(random_bytes((length as usize + 7) / 8)?);
+            GeneratedKey::Key(secret_key(
+                algorithm,
+                extractable,
+                usages,
+                HMAC_USAGES,
+                handle,
+            ))
+        },
+        _ => return Err(Error::NotSupported),
+    };
+
+    let has_usages = match generated {
+        GeneratedKey::Key(ref key) | GeneratedKey::Pair(_, ref key) => !key.usages().is_empty(),
+    };
+    if !has_usages {
+        return Err(Error::Syntax);
+    }
+    Ok(generated)
+}
+
+const AES_USAGES: &[KeyUsage] = &[
+    KeyUsage::Encrypt,
+    KeyUsage::Decrypt,
+    KeyUsage::WrapKey,
+    KeyUsage::UnwrapKey,
+];
+
+const HMAC_USAGES: &[KeyUsage] = &[KeyUsage::Sign, KeyUsage::Verify];
+
+const KDF_USAGES: &[KeyUsage] = &[KeyUsage::DeriveKey, KeyUsage::DeriveBits];
+
+/// The usages of the private and public keys of an RSA algorithm.
+fn rsa_usages(name: AlgorithmName) -> (&'static [KeyUsage], &'static [KeyUsage]) {
+    match name {
+        AlgorithmName::RsaPss => (&[KeyUsage::Sign], &[KeyUsage::Verify]),
+        _ => (
+            &[KeyUsage::Decrypt, KeyUsage::UnwrapKey],
+            &[KeyUsage::Encrypt, KeyUsage::WrapKey],
+        ),
+    }
+}
+
+/// The usages of the private and public keys of an elliptic curve algorithm. ECDH public keys
+/// are only ever given as parameters, and have no usages.
+fn ec_usages(name: AlgorithmName) -> (&'static [KeyUsage], &'static [KeyUsage]) {
+    match name {
+        AlgorithmName::Ecdsa => (&[KeyUsage::Sign], &[KeyUsage::Verify]),
+        _ => (KDF_USAGES, &[]),
+    }
+}
+
+fn secret_key(
+    algorithm: CryptoKeyAlgorithm,
+    extractable: bool,
+    usages: &[KeyUsage],
+    allowed: &[KeyUsage],
+    handle: Handle,
+) -> Key {
+    Key::new(
+        KeyType::Secret,
+        extractable,
+        algorithm,
+        usages_within(usages, allowed),
+        handle,
+    )
+}
+
+/// Public keys are always extractable.
+fn key_pair(
+    algorithm: CryptoKeyAlgorithm,
+    extractable: bool,
+    usages: &[KeyUsage],
+    (private_usages, private): (&[KeyUsage], PKey<Private>),
+    (public_usages, public): (&[KeyUsage], PKey<Public>),
+) -> GeneratedKey {
+    let public = Key::new(
+        KeyType::Public,
+        true,
+        algorithm.clone(),
+        usages_within(usages, public_usages),
+        Handle::Public(public),
+    );
+    let private = Key::new(
+        KeyType::Private,
+        extractable,
+        algorithm,
+        usages_within(usages, private_usages),
+        Handle::Private(private),
+    );
+    GeneratedKey::Pair(public, private)
+}
+
+fn base64url_decode(value: &Option<String>) -> Fallible<Vec<u8>> {
+    let value = value.as_ref().ok_or(Error::Data)?;
+    base64::decode_config(&**value, base64::URL_SAFE_NO_PAD).map_err(|_| Error::Data)
+}
+
+fn base64url_encode(bytes: &[u8]) -> Option<String> {
+    Some(base64::encode_config(bytes, base64::URL_SAFE_NO_PAD))
+}
+
+fn jwk_big_num(value: &Option<String>) -> Fallible<BigNum> {
+    BigNum::from_slice(&base64url_decode(value)?).map_err(data_error)
+}
+
+/// The checks that all the JSON Web Keys go through, whatever their algorithm.
+/// <https://w3c.github.io/webcrypto/#concept-parse-a-jwk>
+fn check_jwk(
+    jwk: &Jwk,
+    kty: &str,
+    key_use: &str,
+    usages: &[KeyUsage],
+    extractable: bool,
+) -> Fallible<()> {
+    if jwk.kty.as_ref().map(|value| &**value) != Some(kty) {
+        return Err(Error::Data);
+    }
+    if let Some(ref value) = jwk.use_ {
+        if !usages.is_empty() && &**value != key_use {
+            return Err(Error::Data);
+        }
+    }
+    if let Some(ref key_ops) = jwk.key_ops {
+        let allowed = usages
+            .iter()
+            .all(|usage| key_ops.iter().any(|op| &**op == usage.as_str()));
+        if !allowed {
+            return Err(Error::Data);
+        }
+    }
+    if jwk.ext == Some(false) && extractable {
+        return Err(Error::Data);
+    }
+    Ok(())
+}
+
+fn check_jwk_alg(jwk: &Jwk, expected: &str) -> Fallible<()> {
+    match jwk.alg {
+        Some(ref alg) if &**alg != expected => Err(Error::Data),
+        _ => Ok(()),
+    }
+}
+
+fn rsa_jwk_alg(name: AlgorithmName, hash: HashAlgorithm) -> String {
+    match (name, hash) {
+        (AlgorithmName::RsaPss, _) => format!("PS{}", hash.jwk_suffix()),
+        (_, HashAlgorithm::Sha1) => "RSA-OAEP".to_owned(),
+        _ => format!("RSA-OAEP-{}", hash.jwk_suffix()),
+    }
+}
+
+/// The specification allows the spki and pkcs8 formats, which are not supported.
+fn import_key(
+    format: KeyFormat,
+    key_data: KeyData,
+    normalized: &NormalizedAlgorithm,
+    extractable: bool,
+    usages: &[KeyUsage],
+) -> Fallible<Key> {
+    if format == KeyFormat::Spki || format == KeyFormat::Pkcs8 {
+        return Err(Error::NotSupported);
+    }
+
+    let key = match (normalized, key_data) {
+        (&NormalizedAlgorithm::RsaHashedImport { name, hash }, KeyData::Jwk(jwk)) => {
+            import_rsa_jwk(jwk, name, hash, extractable, usages)?
+        },
+        (&NormalizedAlgorithm::EcKey { name, curve }, key_data) => {
+            import_ec(key_data, name, curve, extractable, usages)?
+        },
+        (&NormalizedAlgorithm::Plain(AlgorithmName::AesGcm), key_data) => {
+            check_usages(usages, AES_USAGES)?;
+            let bytes = match key_data {
+                KeyData::Bytes(bytes) => bytes,
+                KeyData::Jwk(jwk) => {
+                    check_jwk(&jwk, "oct", "enc", usages, extractable)?;
+                    let bytes = base64url_decode(&jwk.k)?;
+                    check_jwk_alg(&jwk, &format!("A{}GCM", bytes.len() * 8))?;
+                    bytes
+                },
+            };
+            if bytes.len() != 16 && bytes.len() != 24 && bytes.len() != 32 {
+                return Err(Error::Data);
+            }
+            let algorithm = CryptoKeyAlgorithm::Aes {
+                name: AlgorithmName::AesGcm,
+                length: bytes.len() as u16 * 8,
+            };
+            secret_key(
+                algorithm,
+                extractable,
+                usages,
+                AES_USAGES,
+                Handle::Secret(bytes),
+            )
+        },
+        (&NormalizedAlgorithm::Hmac { hash, length }, key_data) => {
+            check_usages(usages, HMAC_USAGES)?;
+            let bytes = match key_data {
+                KeyData::Bytes(bytes) => bytes,
+                KeyData::Jwk(jwk) => {
+                    check_jwk(&jwk, "oct", "sig", usages, extractable)?;
+                    check_jwk_alg(&jwk, &format!("HS{}", hash.jwk_suffix()))?;
+                    base64url_decode(&jwk.k)?
+                },
+            };
+            let data_length = bytes.len() as u32 * 8;
+            if data_length == 0 {
+                return Err(Error::Data);
+            }
+            // The length can only leave out some bits of the last byte.
+            let length = match length {
+                Some(length) if length > data_length || length + 8 <= data_length => {
+                    return Err(Error::Data);
+                },
+                Some(length) => length,
+                None => data_length,
+            };
+            let algorithm = CryptoKeyAlgorithm::Hmac { hash, length };
+            secret_key(
+                algorithm,
+                extractable,
+                usages,
+                HMAC_USAGES,
+                Handle::Secret(bytes),
+            )
+        },
+        // https://w3c.github.io/webcrypto/#hkdf-operations
+        // https://w3c.github.io/webcrypto/#pbkdf2-operations
+        (&NormalizedAlgorithm::Plain(name), KeyData::Bytes(bytes)) => {
+            check_usages(usages, KDF_USAGES)?;
+            if extractable {
+                return Err(Error::Syntax);
+            }
+            let algorithm = CryptoKeyAlgorithm::Kdf(name);
+            secret_key(
+                algorithm,
+                extractable,
+                usages,
+                KDF_USAGES,
+                Handle::Secret(bytes),
+            )
+        },
+        _ => return Err(Error::NotSupported),
+    };
+
+    if key.key_type() != KeyType::Public && key.usages().is_empty() {
+        return Err(Error::Syntax);
+    }
+    Ok(key)
+}
+
+/// <https://w3c.github.io/webcrypto/#rsa-pss-operations>
+/// <https://w3c.github.io/webcrypto/#rsa-oaep-operations>
+fn import_rsa_jwk(
+    jwk: Jwk,
+    name: AlgorithmName,
+    hash: HashAlgorithm,
+    extractable: bool,
+    usages: &[KeyUsage],
+) -> Fallible<Key> {
+    let (private_usages, public_usages) = rsa_usages(name);
+    let is_private = jwk.d.is_some();
+    check_usages(
+        usages,
+        if is_private {
+            private_usages
+        } else {
+            public_usages
+        },
+    )?;
+    let key_use = if name == AlgorithmName::RsaPss {
+        "sig"
+    } else {
+        "enc"
+    };
+    check_jwk(&jwk, "RSA", key_use, usages, extractable)?;
+    check_jwk_alg(&jwk, &rsa_jwk_alg(name, hash))?;
+    if jwk.oth {
+        return Err(Error::NotSupported);
+    }
+
+    let n = jwk_big_num(&jwk.n)?;
+    let e = jwk_big_num(&jwk.e)?;
+    let algorithm = CryptoKeyAlgorithm::RsaHashed {
+        name,
+        modulus_length: n.num_bits() as u32,
+        public_exponent: e.to_vec(),
+        hash,
+    };
+    if !is_private {
+        let rsa = Rsa::from_public_components(n, e).map_err(data_error)?;
+        let pkey = PKey::from_rsa(rsa).map_err(data_error)?;
+        return Ok(Key::new(
+            KeyType::Public,
+            extractable,
+            algorithm,
+            usages_within(usages, public_usages),
+            Handle::Public(pkey),
+        ));
+    }
+
+    let rsa = Rsa::from_private_components(
+        n,
+        e,
+        jwk_big_num(&jwk.d)?,
+        jwk_big_num(&jwk.p)?,
+        jwk_big_num(&jwk.q)?,
+        jwk_big_num(&jwk.dp)?,
+        jwk_big_num(&jwk.dq)?,
+        jwk_big_num(&jwk.qi)?,
+    )
+    .map_err(data_error)?;
+    let pkey = PKey::from_rsa(rsa).map_err(data_error)?;
+    Ok(Key::new(
+        KeyType::Private,
+        extractable,
+        algorithm,
+        usages_within(usages, private_usages),
+        Handle::Private(pkey),
+    ))
+}
+
+/// <https://w3c.github.io/webcrypto/#ecdsa-operations>
+/// <https://w3c.github.io/webcrypto/#ecdh-operations>
+fn import_ec(
+    key_data: KeyData,
+    name: AlgorithmName,
+    curve: NamedCurve,
+    extractable: bool,
+    usages: &[KeyUsage],
+) -> Fallible<Key> {
+    let (private_usages, public_usages) = ec_usages(name);
+    let algorithm = CryptoKeyAlgorithm::Ec { name, curve };
+    let group = EcGroup::from_curve_name(curve.nid()).map_err(operation_error)?;
+    let public_key = |public: EcKey<Public>| -> Fallible<Key> {
+        check_usages(usages, public_usages)?;
+        let pkey = PKey::from_ec_key(public).map_err(data_error)?;
+        Ok(Key::new(
+            KeyType::Public,
+            extractable,
+            algorithm.clone(),
+            usages_within(usages, public_usages),
+            Handle::Public(pkey),
+        ))
+    };
+
+    let jwk = match key_data {
+        // Raw keys are uncompressed public points.
+        KeyData::Bytes(bytes) => {
+            let mut context = BigNumContext::new().map_err(operation_error)?;
+            let public = EcPoint::from_bytes(&group, &bytes, &mut context)
+                .and_then(|point| EcKey::from_public_key(&group, &point))
+                .map_err(data_error)?;
+            return public_key(public);
+        },
+        KeyData::Jwk(jwk) => jwk,
+    };
+
+    let key_use = if name == AlgorithmName::Ecdsa {
+        "sig"
+    } else {
+        "enc"
+    };
+    check_jwk(&jwk, "EC", key_use, usages, extractable)?;
+    if jwk.crv.as_ref().map(|crv| &**crv) != Some(curve.as_str()) {
+        return Err(Error::Data);
+    }
+    if name == AlgorithmName::Ecdsa {
+        check_jwk_alg(&jwk, curve.jwk_ecdsa_alg())?;
+    }
+    let x = jwk_big_num(&jwk.x)?;
+    let y = jwk_big_num(&jwk.y)?;
+    let public = EcKey::from_public_key_affine_coordinates(&group, &x, &y).map_err(data_error)?;
+    if jwk.d.is_none() {
+        return public_key(public);
+    }
+
+    check_usages(usages, private_usages)?;
+    let d = jwk_big_num(&jwk.d)?;
+    let private = EcKey::from_private_components(&group, &d, public.public_key())
+        .and_then(|private| private.check_key().map(|_| private))
+        .and_then(PKey::from_ec_key)
+        .map_err(data_error)?;
+    Ok(Key::new(
+        KeyType::Private,
+        extractable,
+        algorithm,
+        usages_within(usages, private_usages),
+        Handle::Private(private),
+    ))
+}
+
+/// <https://w3c.github.io/webcrypto/#SubtleCrypto-method-exportKey>
+fn export_key(format: KeyFormat, key: &Key) -> Fallible<ExportedKey> {
+    if !key.extractable() {
+        return Err(Error::InvalidAccess);
+    }
+
+    match format {
+        KeyFormat::Raw => match (key.algorithm(), key.handle()) {
+            (&CryptoKeyAlgorithm::Aes { .. }, &Handle::Secret(ref bytes)) |
+            (&CryptoKeyAlgorithm::Hmac { .. }, &Handle::Secret(ref bytes)) => {
+                Ok(ExportedKey::Bytes(bytes.clone()))
+            },
+            (&CryptoKeyAlgorithm::Ec { .. }, &Handle::Public(ref pkey)) => {
+                let ec_key = pkey.ec_key().map_err(operation_error)?;
+                let mut context = BigNumContext::new().map_err(operation_error)?;
+                let point = ec_key
+                    .public_key()
+                    .to_bytes(
+                        ec_key.group(),
+                        PointConversionForm::UNCOMPRESSED,
+                        &mut context,
+                    )
+                    .map_err(operation_error)?;
+                Ok(ExportedKey::Bytes(point))
+            },
+            (&CryptoKeyAlgorithm::Ec { .. }, _) => Err(Error::InvalidAccess),
+            _ => Err(Error::NotSupported),
+        },
+        KeyFormat::Jwk => {
+            let mut jwk = Jwk::default();
+            jwk.key_ops = Some(
+                key.usages()
+                    .iter()
+                    .map(|usage| usage.as_str().to_owned())
+                    .collect(),
+            );
+            jwk.ext = Some(key.extractable());
+            export_jwk(key, &mut jwk).map_err(operation_error)?;
+            Ok(ExportedKey::Jwk(jwk))
+        },
+        KeyFormat::Spki | KeyFormat::Pkcs8 => Err(Error::NotSupported),
+    }
+}
+
+fn export_jwk(key: &Key, jwk: &mut Jwk) -> Result<(), ErrorStack> {
+    match (key.algorithm(), key.handle()) {
+        (&CryptoKeyAlgorithm::Aes { length, .. }, &Handle::Secret(ref bytes)) => {
+            jwk.kty = Some(String::from("oct"));
+            jwk.k = base64url_encode(bytes);
+            jwk.alg = Some(format!("A{}GCM", length));
+        },
+        (&CryptoKeyAlgorithm::Hmac { hash, .. }, &Handle::Secret(ref bytes)) => {
+            jwk.kty = Some(String::from("oct"));
+            jwk.k = base64url_encode(bytes);
+            jwk.alg = Some(format!("HS{}", hash.jwk_suffix()));
+        },
+        (&CryptoKeyAlgorithm::RsaHashed { name, hash, .. }, handle) => {
+            jwk.alg = Some(rsa_jwk_alg(name, hash));
+            match *handle {
+                Handle::Public(ref pkey) => rsa_public_jwk(&pkey.rsa()?, jwk),
+                Handle::Private(ref pkey) => {
+                    let rsa = pkey.rsa()?;
+                    rsa_public_jwk(&rsa, jwk);
+                    jwk.d = base64url_encode(&rsa.d().to_vec());
+                    jwk.p = rsa.p().and_then(|p| base64url_encode(&p.to_vec()));
+                    jwk.q = rsa.q().and_then(|q| base64url_encode(&q.to_vec()));
+                    jwk.dp = rsa.dmp1().and_then(|dp| base64url_encode(&dp.to_vec()));
+                    jwk.dq = rsa.dmq1().and_then(|dq| base64url_encode(&dq.to_vec()));
+                    jwk.qi = rsa.iqmp().and_then(|qi| base64url_encode(&qi.to_vec()));
+                },
+                Handle::Secret(_) => {},
+            }
+        },
+        (&CryptoKeyAlgorithm::Ec { name, curve }, handle) => {
+            if name == AlgorithmName::Ecdsa {
+                jwk.alg = Some(String::from(curve.jwk_ecdsa_alg()));
+            }
+            match *handle {
+                Handle::Public(ref pkey) => ec_public_jwk(&pkey.ec_key()?, curve, jwk)?,
+                Handle::Private(ref pkey) => {
+                    let ec_key = pkey.ec_key()?;
+                    ec_public_jwk(&ec_key, curve, jwk)?;
+                    let d = left_pad(ec_key.private_key().to_vec(), curve.coordinate_size());
+                    jwk.d = base64url_encode(&d);
+                },
+                Handle::Secret(_) => {},
+            }
+        },
+        _ => {},
+    }
+    Ok(())
+}
+
+fn rsa_public_jwk<T: HasPublic>(rsa: &RsaRef<T>, jwk: &mut Jwk) {
+    jwk.kty = Some(String::from("RSA"));
+    jwk.n = base64url_encode(&rsa.n().to_vec());
+    jwk.e = base64url_encode(&rsa.e().to_vec());
+}
+
+fn ec_public_jwk<T: HasPublic>(
+    ec_key: &EcKeyRef<T>,
+    curve: NamedCurve,
+    jwk: &mut Jwk,
+) -> Result<(), ErrorStack> {
+    let mut context = BigNumContext::new()?;
+    let mut x = BigNum::new()?;
+    let mut y = BigNum::new()?;
+    ec_key
+        .public_key()
+        .affine_coordinates_gfp(ec_key.group(), &mut x, &mut y, &mut context)?;
+    jwk.kty = Some(String::from("EC"));
+    jwk.crv = Some(String::from(curve.as_str()));
+    jwk.x = base64url_encode(&left_pad(x.to_vec(), curve.coordinate_size()));
+    jwk.y = base64url_encode(&left_pad(y.to_vec(), curve.coordinate_size()));
+    Ok(())
+}
@@ -0,0 +1,71 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::TaskAttributionTimingBinding::TaskAttributionTimingMethods;
+use crate::dom::bindings::reflector::reflect_dom_object;
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::performanceentry::PerformanceEntry;
+use dom_struct::dom_struct;
+
+/// <https://w3c.github.io/longtasks/#sec-TaskAttributionTiming>
+#[dom_struct]
+pub struct TaskAttributionTiming {
+    entry: PerformanceEntry,
+    container_type: DOMString,
+    container_src: DOMString,
+    container_id: DOMString,
+    container_name: DOMString,
+}
+
+impl TaskAttributionTiming {
+    fn new_inherited(container_type: DOMString) -> TaskAttributionTiming {
+        TaskAttributionTiming {
+            entry: PerformanceEntry::new_inherited(
+                DOMString::from("unknown"),
+                DOMString::from("taskattribution"),
+                0.,
+                0.,
+            ),
+            container_type,
+            container_src: DOMString::new(),
+            container_id: DOMString::new(),
+            container_name: DOMString::new(),
+        }
+    }
+
+    /// Creates the attribution of a long task to the window's own browsing context, which is
+    /// the only culprit the script thread can currently tell apart.
+    pub fn new_for_window(global: &GlobalScope) -> DomRoot<TaskAttributionTiming> {
+        reflect_dom_object(
+            Box::new(TaskAttributionTiming::new_inherited(DOMString::from(
+                "window",
+            ))),
+            global,
+        )
+    }
+}
+
+impl TaskAttributionTimingMethods for TaskAttributionTiming {
+    // https://w3c.github.io/longtasks/#dom-taskattributiontiming-containertype
+    fn ContainerType(&self) -> DOMString {
+        self.container_type.clone()
+    }
+
+    // https://w3c.github.io/longtasks/#dom-taskattributiontiming-containersrc
+    fn ContainerSrc(&self) -> DOMString {
+        self.container_src.clone()
+    }
+
+    // https://w3c.github.io/longtasks/#dom-taskattributiontiming-containerid
+    fn ContainerId(&self) -> DOMString {
+        self.container_id.clone()
+    }
+
+    // https://w3c.github.io/longtasks/#dom-taskattributiontiming-containername
+    fn ContainerName(&self) -> DOMString {
+        self.container_name.clone()
+    }
+}
@@ -262,6 +262,18 @@ impl<'a, E: TextControlElement> TextControlSelection<'a, E> {
         Ok(())
     }
 
+    /// The text the user has selected, which `getSelection().toString()` reports while
+    /// the control is focused.
+    pub fn selected_text(&self) -> Option<DOMString> {
+        if !self.element.selection_api_applies() {
+            return None;
+        }
+        self.textinput
+            .borrow()
+            .get_selection_text()
+            .map(DOMString::from)
+    }
+
     fn start(&self) -> u32 {
         let UTF8Bytes(offset) = self.textinput.borrow().selection_start_offset();
         offset as u32
@@ -310,6 +322,7 @@ impl<'a, E: TextControlElement> TextControlSelection<'a, E> {
                     EventCancelable::NotCancelable,
                     &window,
                 );
+            queue_selectionchange(self.element);
         }
 
         self.element
@@ -317,3 +330,19 @@ impl<'a, E: TextControlElement> TextControlSelection<'a, E> {
             .dirty(NodeDamage::OtherNodeDamage);
     }
 }
+
+/// Queues a `selectionchange` event at a text control whose selection has changed.
+/// https://w3c.github.io/selection-api/#selectionchange-event
+pub fn queue_selectionchange<E: TextControlElement>(element: &E) {
+    let window = window_from_node(element);
+    window
+        .task_manager()
+        .user_interaction_task_source()
+        .queue_event(
+            &element.upcast::<EventTarget>(),
+            atom!("selectionchange"),
+            EventBubbles::Bubbles,
+            EventCancelable::NotCancelable,
+            &window,
+        );
+}
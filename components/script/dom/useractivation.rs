@@ -0,0 +1,39 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::UserActivationBinding::UserActivationMethods;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::window::Window;
+use dom_struct::dom_struct;
+
+/// <https://html.spec.whatwg.org/multipage/#the-useractivation-interface>
+#[dom_struct]
+pub struct UserActivation {
+    reflector_: Reflector,
+}
+
+impl UserActivation {
+    fn new_inherited() -> UserActivation {
+        UserActivation {
+            reflector_: Reflector::new(),
+        }
+    }
+
+    pub fn new(window: &Window) -> DomRoot<UserActivation> {
+        reflect_dom_object(Box::new(UserActivation::new_inherited()), window)
+    }
+}
+
+impl UserActivationMethods for UserActivation {
+    // https://html.spec.whatwg.org/multipage/#dom-useractivation-hasbeenactive
+    fn HasBeenActive(&self) -> bool {
+        self.global().as_window().has_sticky_activation()
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-useractivation-isactive
+    fn IsActive(&self) -> bool {
+        self.global().as_window().has_transient_activation()
+    }
+}
@@ -0,0 +1,130 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::DocumentBinding::DocumentMethods;
+use crate::dom::bindings::codegen::Bindings::ElementBinding::ElementMethods;
+use crate::dom::bindings::codegen::Bindings::ScrollTimelineBinding::ScrollAxis;
+use crate::dom::bindings::codegen::Bindings::ViewTimelineBinding::{
+    ViewTimelineMethods, ViewTimelineOptions,
+};
+use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
+use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::reflector::reflect_dom_object;
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::element::Element;
+use crate::dom::node::{document_from_node, window_from_node, Node};
+use crate::dom::scrolltimeline::ScrollTimeline;
+use crate::dom::window::Window;
+use app_units::Au;
+use dom_struct::dom_struct;
+use euclid::default::{Rect, Size2D, Vector2D};
+
+/// <https://drafts.csswg.org/scroll-animations-1/#viewtimeline-interface>
+#[dom_struct]
+pub struct ViewTimeline {
+    timeline: ScrollTimeline,
+    subject: Dom<Element>,
+}
+
+impl ViewTimeline {
+    fn new_inherited(subject: &Element, axis: ScrollAxis) -> ViewTimeline {
+        ViewTimeline {
+            timeline: ScrollTimeline::new_inherited(None, axis),
+            subject: Dom::from_ref(subject),
+        }
+    }
+
+    pub fn new(window: &Window, subject: &Element, axis: ScrollAxis) -> DomRoot<Self> {
+        reflect_dom_object(Box::new(ViewTimeline::new_inherited(subject, axis)), window)
+    }
+
+    // https://drafts.csswg.org/scroll-animations-1/#dom-viewtimeline-viewtimeline
+    #[allow(non_snake_case)]
+    pub fn Constructor(window: &Window, options: &ViewTimelineOptions) -> Fallible<DomRoot<Self>> {
+        let subject = match options.subject {
+            Some(ref subject) => subject,
+            None => return Err(Error::Type("A view timeline needs a subject".to_owned())),
+        };
+        Ok(ViewTimeline::new(window, subject, options.axis))
+    }
+
+    /// The nearest ancestor of the subject with a scrolling box, or the root element, whose
+    /// scroll container is the viewport.
+    pub fn source(&self) -> Option<DomRoot<Element>> {
+        let document = document_from_node(&*self.subject);
+        let root = document.GetDocumentElement()?;
+        let source = self
+            .subject
+            .upcast::<Node>()
+            .ancestors()
+            .filter_map(DomRoot::downcast::<Element>)
+            .take_while(|element| *element != root)
+            .find(|element| element.has_scrolling_box());
+        Some(source.unwrap_or(root))
+    }
+
+    /// The progress of this timeline as a percentage, or `None` if it is inactive.
+    ///
+    /// The progress goes from the subject starting to enter the scrollport of the source to
+    /// the subject having completely left it.
+    pub fn current_time(&self) -> Option<f64> {
+        // https://drafts.csswg.org/scroll-animations-1/#view-timeline-progress
+        //
+        // FIXME: The block and inline axes are taken to be the vertical and horizontal axes,
+        // whatever the writing mode is, and the view timeline insets are not supported.
+        let source = self.source()?;
+        let window = window_from_node(&*self.subject);
+        let is_root = document_from_node(&*source).GetDocumentElement().as_ref() == Some(&source);
+
+        // Both boxes are in the coordinates of the page when nothing is scrolled.
+        let subject = self.subject.upcast::<Node>().bounding_content_box_or_zero();
+        let (scrollport, scroll_position) = if is_root {
+            let scroll_position = Vector2D::new(window.ScrollX() as f64, window.ScrollY() as f64);
+            (window.current_viewport(), scroll_position)
+        } else {
+            let border_box = source.upcast::<Node>().bounding_content_box_or_zero();
+            let client_rect = source.upcast::<Node>().client_rect();
+            let scrollport = Rect::new(
+                border_box.origin +
+                    Vector2D::new(
+                        Au::from_px(client_rect.origin.x),
+                        Au::from_px(client_rect.origin.y),
+                    ),
+                Size2D::new(
+                    Au::from_px(client_rect.size.width),
+                    Au::from_px(client_rect.size.height),
+                ),
+            );
+            let scroll_position = Vector2D::new(source.ScrollLeft(), source.ScrollTop());
+            (scrollport, scroll_position)
+        };
+        let (position, scrollport, subject) = match self.timeline.axis() {
+            ScrollAxis::Block | ScrollAxis::Y => (
+                scroll_position.y,
+                (scrollport.origin.y, scrollport.max_y()),
+                (subject.origin.y, subject.max_y()),
+            ),
+            ScrollAxis::Inline | ScrollAxis::X => (
+                scroll_position.x,
+                (scrollport.origin.x, scrollport.max_x()),
+                (subject.origin.x, subject.max_x()),
+            ),
+        };
+
+        let start = (subject.0 - scrollport.1).to_f64_px();
+        let end = (subject.1 - scrollport.0).to_f64_px();
+        if end <= start {
+            return None;
+        }
+        Some(((position - start) / (end - start)).max(0.).min(1.) * 100.)
+    }
+}
+
+impl ViewTimelineMethods for ViewTimeline {
+    // https://drafts.csswg.org/scroll-animations-1/#dom-viewtimeline-subject
+    fn Subject(&self) -> DomRoot<Element> {
+        DomRoot::from_ref(&*self.subject)
+    }
+}
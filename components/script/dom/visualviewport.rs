@@ -0,0 +1,96 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::VisualViewportBinding::VisualViewportMethods;
+use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
+use crate::dom::bindings::num::Finite;
+use crate::dom::bindings::reflector::reflect_dom_object;
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::eventtarget::EventTarget;
+use crate::dom::window::Window;
+use dom_struct::dom_struct;
+
+/// The part of the layout viewport of a window which is visible on the screen. Pinch-zooming
+/// scales the visual viewport without laying out the page again, so it gets smaller than the
+/// layout viewport, which keeps the size the page was laid out with.
+///
+/// Pinch zooms keep the top left corner of the layout viewport in place, so the visual viewport
+/// is always at the top left corner of the layout viewport.
+#[dom_struct]
+pub struct VisualViewport {
+    eventtarget: EventTarget,
+    window: Dom<Window>,
+}
+
+impl VisualViewport {
+    fn new_inherited(window: &Window) -> VisualViewport {
+        VisualViewport {
+            eventtarget: EventTarget::new_inherited(),
+            window: Dom::from_ref(window),
+        }
+    }
+
+    pub fn new(window: &Window) -> DomRoot<VisualViewport> {
+        reflect_dom_object(Box::new(VisualViewport::new_inherited(window)), window)
+    }
+
+    /// Runs `f` with the window, or returns 0 if the document of the window is not fully active.
+    fn if_fully_active(&self, f: impl FnOnce(&Window) -> f64) -> Finite<f64> {
+        let value = if self.window.Document().is_fully_active() {
+            f(&self.window)
+        } else {
+            0.
+        };
+        Finite::wrap(value)
+    }
+}
+
+impl VisualViewportMethods for VisualViewport {
+    // https://drafts.csswg.org/cssom-view/#dom-visualviewport-offsetleft
+    fn OffsetLeft(&self) -> Finite<f64> {
+        self.if_fully_active(|_| 0.)
+    }
+
+    // https://drafts.csswg.org/cssom-view/#dom-visualviewport-offsettop
+    fn OffsetTop(&self) -> Finite<f64> {
+        self.if_fully_active(|_| 0.)
+    }
+
+    // https://drafts.csswg.org/cssom-view/#dom-visualviewport-pageleft
+    fn PageLeft(&self) -> Finite<f64> {
+        self.if_fully_active(|window| window.ScrollX() as f64)
+    }
+
+    // https://drafts.csswg.org/cssom-view/#dom-visualviewport-pagetop
+    fn PageTop(&self) -> Finite<f64> {
+        self.if_fully_active(|window| window.ScrollY() as f64)
+    }
+
+    // https://drafts.csswg.org/cssom-view/#dom-visualviewport-width
+    fn Width(&self) -> Finite<f64> {
+        self.if_fully_active(|window| {
+            let width = window.window_size().initial_viewport.width;
+            (width / window.pinch_zoom()) as f64
+        })
+    }
+
+    // https://drafts.csswg.org/cssom-view/#dom-visualviewport-height
+    fn Height(&self) -> Finite<f64> {
+        self.if_fully_active(|window| {
+            let height = window.window_size().initial_viewport.height;
+            (height / window.pinch_zoom()) as f64
+        })
+    }
+
+    // https://drafts.csswg.org/cssom-view/#dom-visualviewport-scale
+    fn Scale(&self) -> Finite<f64> {
+        self.if_fully_active(|window| window.pinch_zoom() as f64)
+    }
+
+    // https://drafts.csswg.org/cssom-view/#dom-visualviewport-onresize
+    event_handler!(resize, GetOnresize, SetOnresize);
+
+    // https://drafts.csswg.org/cssom-view/#dom-visualviewport-onscroll
+    event_handler!(scroll, GetOnscroll, SetOnscroll);
+}
@@ -0,0 +1,62 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::WakeLockBinding::{WakeLockMethods, WakeLockType};
+use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
+use crate::dom::bindings::error::Error;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::promise::Promise;
+use crate::dom::wakelocksentinel::WakeLockSentinel;
+use crate::realms::InRealm;
+use dom_struct::dom_struct;
+use std::rc::Rc;
+
+#[dom_struct]
+pub struct WakeLock {
+    reflector_: Reflector,
+}
+
+impl WakeLock {
+    fn new_inherited() -> WakeLock {
+        WakeLock {
+            reflector_: Reflector::new(),
+        }
+    }
+
+    pub fn new(global: &GlobalScope) -> DomRoot<WakeLock> {
+        reflect_dom_object(Box::new(WakeLock::new_inherited()), global)
+    }
+}
+
+impl WakeLockMethods for WakeLock {
+    // https://w3c.github.io/screen-wake-lock/#the-request-method
+    fn Request(&self, _type: WakeLockType, comp: InRealm) -> Rc<Promise> {
+        let global = self.global();
+        let promise = Promise::new_in_current_realm(&global, comp);
+        let window = global.as_window();
+        let document = window.Document();
+
+        // TODO Step 1-3: Check the screen-wake-lock permissions policy and the permission state.
+
+        // Step 4
+        if !document.is_fully_active() {
+            promise.reject_error(Error::NotAllowed);
+            return promise;
+        }
+
+        // Step 5
+        if !window.visible() {
+            promise.reject_error(Error::NotAllowed);
+            return promise;
+        }
+
+        // Step 6-7
+        let sentinel = WakeLockSentinel::new(&global);
+        document.acquire_wake_lock(&sentinel);
+        promise.resolve_native(&sentinel);
+        promise
+    }
+}
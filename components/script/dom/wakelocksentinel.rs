@@ -0,0 +1,79 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::WakeLockBinding::WakeLockType;
+use crate::dom::bindings::codegen::Bindings::WakeLockSentinelBinding::WakeLockSentinelMethods;
+use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::eventtarget::EventTarget;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::promise::Promise;
+use crate::realms::InRealm;
+use dom_struct::dom_struct;
+use std::cell::Cell;
+use std::rc::Rc;
+
+/// A handle to a screen wake lock, which keeps the screen on until it is released, or the
+/// document which acquired it is hidden.
+#[dom_struct]
+pub struct WakeLockSentinel {
+    eventtarget: EventTarget,
+    released: Cell<bool>,
+}
+
+impl WakeLockSentinel {
+    fn new_inherited() -> WakeLockSentinel {
+        WakeLockSentinel {
+            eventtarget: EventTarget::new_inherited(),
+            released: Cell::new(false),
+        }
+    }
+
+    pub fn new(global: &GlobalScope) -> DomRoot<WakeLockSentinel> {
+        reflect_dom_object(Box::new(WakeLockSentinel::new_inherited()), global)
+    }
+
+    /// <https://w3c.github.io/screen-wake-lock/#dfn-release-a-wake-lock>
+    pub fn release_wake_lock(&self) {
+        // Step 1-4
+        self.global().as_window().Document().remove_wake_lock(self);
+
+        // Step 5
+        self.released.set(true);
+
+        // Step 6
+        self.upcast::<EventTarget>().fire_event(atom!("release"));
+    }
+}
+
+impl WakeLockSentinelMethods for WakeLockSentinel {
+    // https://w3c.github.io/screen-wake-lock/#dom-wakelocksentinel-released
+    fn Released(&self) -> bool {
+        self.released.get()
+    }
+
+    // https://w3c.github.io/screen-wake-lock/#dom-wakelocksentinel-type
+    // Only screen wake locks exist, so the type is not stored.
+    fn Type(&self) -> WakeLockType {
+        WakeLockType::Screen
+    }
+
+    // https://w3c.github.io/screen-wake-lock/#the-release-method
+    fn Release(&self, comp: InRealm) -> Rc<Promise> {
+        // Step 1
+        if !self.released.get() {
+            self.release_wake_lock();
+        }
+
+        // Step 2-3
+        let promise = Promise::new_in_current_realm(&self.global(), comp);
+        promise.resolve_native(&());
+        promise
+    }
+
+    // https://w3c.github.io/screen-wake-lock/#dom-wakelocksentinel-onrelease
+    event_handler!(release, GetOnrelease, SetOnrelease);
+}
@@ -2,17 +2,23 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use crate::dom::bindings::callback::ExceptionHandling;
 use crate::dom::bindings::cell::DomRefCell;
 use crate::dom::bindings::codegen::Bindings::DocumentBinding::{
     DocumentMethods, DocumentReadyState,
 };
+use crate::dom::bindings::codegen::Bindings::FileSystemHandleBinding::OpenFilePickerOptions;
 use crate::dom::bindings::codegen::Bindings::HistoryBinding::HistoryBinding::HistoryMethods;
 use crate::dom::bindings::codegen::Bindings::MediaQueryListBinding::MediaQueryListBinding::MediaQueryListMethods;
+use crate::dom::bindings::codegen::Bindings::PermissionStatusBinding::PermissionState;
 use crate::dom::bindings::codegen::Bindings::RequestBinding::RequestInit;
 use crate::dom::bindings::codegen::Bindings::VoidFunctionBinding::VoidFunction;
 use crate::dom::bindings::codegen::Bindings::WindowBinding::{
     self, FrameRequestCallback, WindowMethods, WindowPostMessageOptions,
 };
+use crate::dom::bindings::codegen::Bindings::WindowBinding::{
+    IdleRequestCallback, IdleRequestOptions,
+};
 use crate::dom::bindings::codegen::Bindings::WindowBinding::{ScrollBehavior, ScrollToOptions};
 use crate::dom::bindings::codegen::UnionTypes::{RequestOrUSVString, StringOrFunction};
 use crate::dom::bindings::error::{Error, ErrorResult, Fallible};
@@ -30,13 +36,18 @@ use crate::dom::bluetooth::BluetoothExtraPermissionData;
 use crate::dom::crypto::Crypto;
 use crate::dom::cssstyledeclaration::{CSSModificationAccess, CSSStyleDeclaration, CSSStyleOwner};
 use crate::dom::customelementregistry::CustomElementRegistry;
+use crate::dom::devicemotionevent::DeviceMotionEvent;
+use crate::dom::deviceorientationevent::{sensor_permissions, DeviceOrientationEvent};
 use crate::dom::document::{AnimationFrameCallback, Document};
 use crate::dom::element::Element;
 use crate::dom::event::{Event, EventStatus};
 use crate::dom::eventtarget::EventTarget;
+use crate::dom::filesystemfilehandle::{file_picker_filter, FileSystemFileHandle};
+use crate::dom::filesystemhandle::file_manager_request;
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::hashchangeevent::HashChangeEvent;
 use crate::dom::history::History;
+use crate::dom::idledeadline::IdleDeadline;
 use crate::dom::location::Location;
 use crate::dom::mediaquerylist::{MediaQueryList, MediaQueryListMatchState};
 use crate::dom::mediaquerylistevent::MediaQueryListEvent;
@@ -45,10 +56,13 @@ use crate::dom::navigator::Navigator;
 use crate::dom::node::{document_from_node, from_untrusted_node_address, Node, NodeDamage};
 use crate::dom::performance::Performance;
 use crate::dom::promise::Promise;
+use crate::dom::scheduler::Scheduler;
 use crate::dom::screen::Screen;
 use crate::dom::selection::Selection;
+use crate::dom::speechsynthesis::SpeechSynthesis;
 use crate::dom::storage::Storage;
 use crate::dom::testrunner::TestRunner;
+use crate::dom::visualviewport::VisualViewport;
 use crate::dom::webglrenderingcontext::WebGLCommandSender;
 use crate::dom::windowproxy::WindowProxy;
 use crate::dom::worklet::Worklet;
@@ -64,7 +78,7 @@ use crate::script_thread::{ImageCacheMsg, MainThreadScriptChan, MainThreadScript
 use crate::script_thread::{ScriptThread, SendableMainThreadScriptChan};
 use crate::task_manager::TaskManager;
 use crate::task_source::{TaskSource, TaskSourceName};
-use crate::timers::{IsInterval, TimerCallback};
+use crate::timers::{IsInterval, OneshotTimerCallback, OneshotTimerHandle, TimerCallback};
 use crate::webdriver_handlers::jsval_to_webdriver;
 use app_units::Au;
 use base64;
@@ -74,8 +88,10 @@ use crossbeam_channel::{unbounded, Sender, TryRecvError};
 use cssparser::{Parser, ParserInput, SourceLocation};
 use devtools_traits::{ScriptToDevtoolsControlMsg, TimelineMarker, TimelineMarkerType};
 use dom_struct::dom_struct;
-use embedder_traits::{EmbedderMsg, EventLoopWaker, PromptDefinition, PromptOrigin, PromptResult};
-use euclid::default::{Point2D as UntypedPoint2D, Rect as UntypedRect};
+use embedder_traits::{BatteryStatus, DeviceSensor, EmbedderMsg, EventLoopWaker};
+use embedder_traits::{GeolocationPosition, NetworkInformation, PromptDefinition, PromptOrigin};
+use embedder_traits::{PromptResult, SensorReading};
+use euclid::default::{Point2D as UntypedPoint2D, Rect as UntypedRect, Size2D as UntypedSize2D};
 use euclid::{Point2D, Rect, Scale, Size2D, Vector2D};
 use ipc_channel::ipc::{channel, IpcSender};
 use ipc_channel::router::ROUTER;
@@ -90,6 +106,7 @@ use js::rust::wrappers::JS_DefineProperty;
 use js::rust::{CustomAutoRooter, CustomAutoRooterGuard, HandleValue};
 use media::WindowGLContext;
 use msg::constellation_msg::{BrowsingContextId, PipelineId};
+use net_traits::filemanager_thread::{FileManagerThreadMsg, FileSystemLocator};
 use net_traits::image_cache::{ImageCache, ImageResponder, ImageResponse};
 use net_traits::image_cache::{PendingImageId, PendingImageResponse};
 use net_traits::storage_thread::StorageType;
@@ -107,17 +124,19 @@ use script_layout_interface::{PendingImageState, TrustedNodeAddress};
 use script_traits::webdriver_msg::{WebDriverJSError, WebDriverJSResult};
 use script_traits::{ConstellationControlMsg, DocumentState, HistoryEntryReplacement, LoadData};
 use script_traits::{
-    ScriptMsg, ScriptToConstellationChan, ScrollState, StructuredSerializedData, TimerEventId,
+    MsDuration, ScriptMsg, ScriptToConstellationChan, ScrollState, StructuredSerializedData,
+    TimerEventId, UserActivationUpdate,
 };
 use script_traits::{TimerSchedulerMsg, WebrenderIpcSender, WindowSizeData, WindowSizeType};
 use selectors::attr::CaseSensitivity;
+use servo_atoms::Atom;
 use servo_geometry::{f32_rect_to_au_rect, MaxRect};
 use servo_url::{Host, ImmutableOrigin, MutableOrigin, ServoUrl};
 use std::borrow::Cow;
 use std::borrow::ToOwned;
 use std::cell::Cell;
 use std::collections::hash_map::Entry;
-use std::collections::{HashMap, HashSet};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::default::Default;
 use std::env;
 use std::fs;
@@ -126,10 +145,12 @@ use std::mem;
 use std::rc::Rc;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 use style::dom::OpaqueNode;
 use style::error_reporting::{ContextualParseError, ParseErrorReporter};
 use style::media_queries;
 use style::parser::ParserContext as CssParserContext;
+use style::properties::longhands::scroll_behavior;
 use style::properties::{ComputedValues, PropertyId};
 use style::selector_parser::PseudoElement;
 use style::str::HTML_SPACE_CHARACTERS;
@@ -140,6 +161,54 @@ use webrender_api::units::{DeviceIntPoint, DeviceIntSize, LayoutPixel};
 use webrender_api::{DocumentId, ExternalScrollId};
 use webvr_traits::WebVRMsg;
 
+/// The longest an idle period can be, in milliseconds, so that the event loop stays responsive
+/// to new input.
+///
+/// <https://w3c.github.io/requestidlecallback/#why50>
+const MAX_IDLE_PERIOD: f64 = 50.;
+
+/// How long the user interacting with a page lets it use the APIs which require a transient
+/// activation.
+///
+/// <https://html.spec.whatwg.org/multipage/#transient-activation-duration>
+const TRANSIENT_ACTIVATION_DURATION: Duration = Duration::from_secs(5);
+
+/// <https://html.spec.whatwg.org/multipage/#last-activation-timestamp>
+#[derive(Clone, Copy, JSTraceable, PartialEq)]
+enum LastActivationTimestamp {
+    /// The user never interacted with this window or with the ones it propagates activation
+    /// to, which the spec represents as positive infinity.
+    Never,
+    /// The transient activation was consumed, which the spec represents as negative infinity.
+    Consumed,
+    At(Instant),
+}
+
+/// An idle callback registered with `requestIdleCallback()`.
+#[derive(JSTraceable, MallocSizeOf)]
+struct IdleRequest {
+    handle: u32,
+    #[ignore_malloc_size_of = "Rc is hard"]
+    callback: Rc<IdleRequestCallback>,
+    /// The timer that invokes the callback if it has not been invoked by then.
+    timeout: Option<OneshotTimerHandle>,
+}
+
+/// A timer that invokes an idle callback once its timeout has passed, if it has not been
+/// invoked in an idle period by then.
+#[derive(JSTraceable, MallocSizeOf)]
+pub struct IdleRequestTimeoutCallback {
+    #[ignore_malloc_size_of = "non-owning"]
+    window: Trusted<Window>,
+    handle: u32,
+}
+
+impl IdleRequestTimeoutCallback {
+    pub fn invoke(self) {
+        self.window.root().invoke_idle_callback_timeout(self.handle);
+    }
+}
+
 /// Current state of the window object
 #[derive(Clone, Copy, Debug, JSTraceable, MallocSizeOf, PartialEq)]
 enum WindowState {
@@ -170,6 +239,20 @@ pub enum ReflowReason {
     IFrameLoadEvent,
     MissingExplicitReflow,
     ElementStateChanged,
+    Print,
+    MediaPreferencesChanged,
+    TextZoomChanged,
+}
+
+/// The responses to the geometry queries made for each node, which stay valid until the
+/// document changes or the current task ends.
+#[derive(Default, JSTraceable, MallocSizeOf)]
+#[unrooted_must_root_lint::must_root]
+struct GeometryQueryCache {
+    content_boxes: HashMap<OpaqueNode, Option<UntypedRect<Au>>>,
+    content_box_lists: HashMap<OpaqueNode, Vec<UntypedRect<Au>>>,
+    client_rects: HashMap<OpaqueNode, UntypedRect<i32>>,
+    offset_parents: HashMap<OpaqueNode, (Option<Dom<Element>>, UntypedRect<Au>)>,
 }
 
 #[dom_struct]
@@ -179,6 +262,8 @@ pub struct Window {
     script_chan: MainThreadScriptChan,
     task_manager: TaskManager,
     navigator: MutNullableDom<Navigator>,
+    /// https://wicg.github.io/speech-api/#dom-window-speechsynthesis
+    speech_synthesis: MutNullableDom<SpeechSynthesis>,
     #[ignore_malloc_size_of = "Arc"]
     image_cache: Arc<dyn ImageCache>,
     #[ignore_malloc_size_of = "channels are hard"]
@@ -192,6 +277,8 @@ pub struct Window {
     navigation_start: Cell<u64>,
     navigation_start_precise: Cell<u64>,
     screen: MutNullableDom<Screen>,
+    visual_viewport: MutNullableDom<VisualViewport>,
+    scheduler: MutNullableDom<Scheduler>,
     session_storage: MutNullableDom<Storage>,
     local_storage: MutNullableDom<Storage>,
     status: DomRefCell<DOMString>,
@@ -226,6 +313,10 @@ pub struct Window {
     /// The current size of the window, in pixels.
     window_size: Cell<WindowSizeData>,
 
+    /// The scale of the pinch zoom of the top-level browsing context, which the visual viewport
+    /// exposes. Always 1 in nested browsing contexts.
+    pinch_zoom: Cell<f32>,
+
     /// A handle for communicating messages to the bluetooth thread.
     #[ignore_malloc_size_of = "channels are hard"]
     bluetooth_thread: IpcSender<BluetoothRequest>,
@@ -244,6 +335,12 @@ pub struct Window {
     /// A counter of the number of pending reflows for this window.
     pending_reflow_count: Cell<u32>,
 
+    /// The window size of the last reflow, if any.
+    last_reflow_window_size: Cell<Option<WindowSizeData>>,
+
+    /// The responses to the geometry queries made since the document last changed.
+    geometry_query_cache: DomRefCell<GeometryQueryCache>,
+
     /// A channel for communicating results of async scripts back to the webdriver server
     #[ignore_malloc_size_of = "channels are hard"]
     webdriver_script_chan: DomRefCell<Option<IpcSender<WebDriverJSResult>>>,
@@ -261,6 +358,17 @@ pub struct Window {
     /// All the MediaQueryLists we need to update
     media_query_lists: DOMTracker<MediaQueryList>,
 
+    /// The MediaQueryLists with `change` listeners, which are kept alive until they have none.
+    observed_media_query_lists: DomRefCell<Vec<Dom<MediaQueryList>>>,
+
+    /// The sensors of the device whose readings the constellation sends to this window.
+    #[ignore_malloc_size_of = "defined in embedder_traits"]
+    watched_sensors: DomRefCell<Vec<DeviceSensor>>,
+
+    /// <https://html.spec.whatwg.org/multipage/#last-activation-timestamp>
+    #[ignore_malloc_size_of = "Defined in std"]
+    last_activation_timestamp: Cell<LastActivationTimestamp>,
+
     test_runner: MutNullableDom<TestRunner>,
 
     /// A handle for communicating messages to the WebGL thread, if available.
@@ -334,6 +442,15 @@ pub struct Window {
     event_loop_waker: Option<Box<dyn EventLoopWaker>>,
 
     visible: Cell<bool>,
+
+    /// <https://w3c.github.io/requestidlecallback/#dfn-idle-callback-identifier>
+    idle_callback_ident: Cell<u32>,
+
+    /// <https://w3c.github.io/requestidlecallback/#dfn-list-of-idle-request-callbacks>
+    idle_request_callbacks: DomRefCell<VecDeque<IdleRequest>>,
+
+    /// <https://w3c.github.io/requestidlecallback/#dfn-list-of-runnable-idle-callbacks>
+    runnable_idle_callbacks: DomRefCell<VecDeque<IdleRequest>>,
 }
 
 impl Window {
@@ -444,6 +561,11 @@ impl Window {
         Some(&self.error_reporter)
     }
 
+    /// Returns an error reporter which can be sent to the threads parsing stylesheets.
+    pub fn clone_css_error_reporter(&self) -> CSSErrorReporter {
+        self.error_reporter.clone()
+    }
+
     /// Sets a new list of scroll offsets.
     ///
     /// This is called when layout gives us new ones and WebRender is in use.
@@ -607,6 +729,11 @@ impl WindowMethods for Window {
 
     // https://html.spec.whatwg.org/multipage/#dom-alert
     fn Alert(&self, s: DOMString) {
+        // https://wicg.github.io/nav-speculation/prerendering.html#patch-modals
+        if self.Document().is_prerendering() {
+            return;
+        }
+
         // Print to the console.
         // Ensure that stderr doesn't trample through the alert() we use to
         // communicate test results (see executorservo.py in wptrunner).
@@ -629,6 +756,10 @@ impl WindowMethods for Window {
 
     // https://html.spec.whatwg.org/multipage/#dom-confirm
     fn Confirm(&self, s: DOMString) -> bool {
+        if self.Document().is_prerendering() {
+            return false;
+        }
+
         let (sender, receiver) =
             ProfiledIpc::channel(self.global().time_profiler_chan().clone()).unwrap();
         let prompt = PromptDefinition::OkCancel(s.to_string(), sender);
@@ -639,6 +770,10 @@ impl WindowMethods for Window {
 
     // https://html.spec.whatwg.org/multipage/#dom-prompt
     fn Prompt(&self, message: DOMString, default: DOMString) -> Option<DOMString> {
+        if self.Document().is_prerendering() {
+            return None;
+        }
+
         let (sender, receiver) =
             ProfiledIpc::channel(self.global().time_profiler_chan().clone()).unwrap();
         let prompt = PromptDefinition::Input(message.to_string(), default.to_string(), sender);
@@ -647,6 +782,28 @@ impl WindowMethods for Window {
         receiver.recv().unwrap().map(|s| s.into())
     }
 
+    // https://html.spec.whatwg.org/multipage/#dom-print
+    fn Print(&self) {
+        let document = self.Document();
+        if !document.is_fully_active() {
+            return;
+        }
+
+        // https://html.spec.whatwg.org/multipage/#printing-steps
+        self.upcast::<EventTarget>()
+            .fire_event(atom!("beforeprint"));
+        let page_size = Size2D::new(
+            pref!(layout.print.page_width) as f32,
+            pref!(layout.print.page_height) as f32,
+        );
+        if let Some(pdf) = self.print_query(page_size) {
+            self.send_to_embedder(EmbedderMsg::PrintDocument(pdf));
+        }
+        // Lay the document out for the screen again.
+        self.reflow(ReflowGoal::Full, ReflowReason::Print);
+        self.upcast::<EventTarget>().fire_event(atom!("afterprint"));
+    }
+
     // https://html.spec.whatwg.org/multipage/#dom-window-stop
     fn Stop(&self) {
         // TODO: Cancel ongoing navigation.
@@ -661,6 +818,11 @@ impl WindowMethods for Window {
         target: DOMString,
         features: DOMString,
     ) -> Option<DomRoot<WindowProxy>> {
+        // Prerendered documents cannot open windows that the user would see.
+        // https://wicg.github.io/nav-speculation/prerendering.html#patch-window-open
+        if self.Document().is_prerendering() {
+            return None;
+        }
         self.window_proxy().open(url, target, features)
     }
 
@@ -834,6 +996,11 @@ impl WindowMethods for Window {
         self.navigator.or_init(|| Navigator::new(self))
     }
 
+    // https://wicg.github.io/speech-api/#dom-window-speechsynthesis
+    fn SpeechSynthesis(&self) -> DomRoot<SpeechSynthesis> {
+        self.speech_synthesis.or_init(|| SpeechSynthesis::new(self))
+    }
+
     // https://html.spec.whatwg.org/multipage/#dom-windowtimers-settimeout
     fn SetTimeout(
         &self,
@@ -943,17 +1110,47 @@ impl WindowMethods for Window {
         })
     }
 
+    // https://wicg.github.io/scheduling-apis/#dom-windoworworkerglobalscope-scheduler
+    fn Scheduler(&self) -> DomRoot<Scheduler> {
+        self.scheduler.or_init(|| Scheduler::new(self.upcast()))
+    }
+
     // https://html.spec.whatwg.org/multipage/#globaleventhandlers
     global_event_handlers!();
 
     // https://html.spec.whatwg.org/multipage/#windoweventhandlers
     window_event_handlers!();
 
+    // https://w3c.github.io/deviceorientation/#dom-window-ondeviceorientation
+    event_handler!(
+        deviceorientation,
+        GetOndeviceorientation,
+        SetOndeviceorientation
+    );
+
+    // https://w3c.github.io/deviceorientation/#dom-window-ondeviceorientationabsolute
+    event_handler!(
+        deviceorientationabsolute,
+        GetOndeviceorientationabsolute,
+        SetOndeviceorientationabsolute
+    );
+
+    // https://w3c.github.io/deviceorientation/#dom-window-ondevicemotion
+    event_handler!(devicemotion, GetOndevicemotion, SetOndevicemotion);
+
     // https://developer.mozilla.org/en-US/docs/Web/API/Window/screen
     fn Screen(&self) -> DomRoot<Screen> {
         self.screen.or_init(|| Screen::new(self))
     }
 
+    // https://drafts.csswg.org/cssom-view/#dom-window-visualviewport
+    fn GetVisualViewport(&self) -> Option<DomRoot<VisualViewport>> {
+        if !self.Document().is_fully_active() {
+            return None;
+        }
+        Some(self.visual_viewport.or_init(|| VisualViewport::new(self)))
+    }
+
     // https://html.spec.whatwg.org/multipage/#dom-windowbase64-btoa
     fn Btoa(&self, btoa: DOMString) -> Fallible<DOMString> {
         base64_btoa(btoa)
@@ -1183,7 +1380,9 @@ impl WindowMethods for Window {
     // https://drafts.csswg.org/cssom-view/#dom-window-resizeto
     fn ResizeTo(&self, width: i32, height: i32) {
         // Step 1
-        //TODO determine if this operation is allowed
+        if !self.may_move_or_resize() {
+            return;
+        }
         let dpr = self.device_pixel_ratio();
         let size = Size2D::new(width, height).to_f32() * dpr;
         self.send_to_embedder(EmbedderMsg::ResizeTo(size.to_i32()));
@@ -1202,7 +1401,9 @@ impl WindowMethods for Window {
     // https://drafts.csswg.org/cssom-view/#dom-window-moveto
     fn MoveTo(&self, x: i32, y: i32) {
         // Step 1
-        //TODO determine if this operation is allowed
+        if !self.may_move_or_resize() {
+            return;
+        }
         let dpr = self.device_pixel_ratio();
         let point = Point2D::new(x, y).to_f32() * dpr;
         let msg = EmbedderMsg::MoveTo(point.to_i32());
@@ -1320,6 +1521,82 @@ impl WindowMethods for Window {
     fn GetSelection(&self) -> Option<DomRoot<Selection>> {
         self.document.get().and_then(|d| d.GetSelection())
     }
+
+    // https://w3c.github.io/requestidlecallback/#dom-window-requestidlecallback
+    fn RequestIdleCallback(
+        &self,
+        callback: Rc<IdleRequestCallback>,
+        options: &IdleRequestOptions,
+    ) -> u32 {
+        // Steps 2-4.
+        let handle = self.idle_callback_ident.get().wrapping_add(1);
+        self.idle_callback_ident.set(handle);
+
+        // Step 6.
+        let timeout = if options.timeout > 0 {
+            let callback = IdleRequestTimeoutCallback {
+                window: Trusted::new(self),
+                handle,
+            };
+            Some(self.upcast::<GlobalScope>().schedule_callback(
+                OneshotTimerCallback::IdleRequestTimeout(callback),
+                MsDuration::new(options.timeout as u64),
+            ))
+        } else {
+            None
+        };
+
+        // Step 5.
+        self.idle_request_callbacks
+            .borrow_mut()
+            .push_back(IdleRequest {
+                handle,
+                callback,
+                timeout,
+            });
+
+        // Step 7.
+        handle
+    }
+
+    // https://w3c.github.io/requestidlecallback/#dom-window-cancelidlecallback
+    fn CancelIdleCallback(&self, handle: u32) {
+        if let Some(request) = self.take_idle_request(handle) {
+            if let Some(timeout) = request.timeout {
+                self.upcast::<GlobalScope>().unschedule_callback(timeout);
+            }
+        }
+    }
+
+    // https://wicg.github.io/file-system-access/#dom-window-showopenfilepicker
+    fn ShowOpenFilePicker(&self, options: &OpenFilePickerOptions, comp: InRealm) -> Rc<Promise> {
+        let global = self.upcast::<GlobalScope>();
+        let promise = Promise::new_in_current_realm(global, comp);
+
+        // Steps 3-4.
+        if !self.has_transient_activation() {
+            promise.reject_error(Error::Security);
+            return promise;
+        }
+
+        let filter = file_picker_filter(options);
+        let result = file_manager_request(global, |sender| {
+            FileManagerThreadMsg::PickFiles(filter, options.multiple, sender, None)
+        });
+        match result {
+            Ok(files) => {
+                let handles: Vec<_> = files
+                    .into_iter()
+                    .map(|(id, name)| {
+                        FileSystemFileHandle::new(global, FileSystemLocator::Picked(id), name)
+                    })
+                    .collect();
+                promise.resolve_native(&handles);
+            },
+            Err(error) => promise.reject_error(error),
+        }
+        promise
+    }
 }
 
 impl Window {
@@ -1491,7 +1768,7 @@ impl Window {
             y,
             global_scope.pipeline_id().root_scroll_id(),
             behavior,
-            None,
+            self.Document().GetDocumentElement().as_deref(),
         );
     }
 
@@ -1501,24 +1778,58 @@ impl Window {
         x: f32,
         y: f32,
         scroll_id: ExternalScrollId,
-        _behavior: ScrollBehavior,
-        _element: Option<&Element>,
+        behavior: ScrollBehavior,
+        element: Option<&Element>,
     ) {
-        // TODO Step 1
-        // TODO(mrobinson, #18709): Add smooth scrolling support to WebRender so that we can
-        // properly process ScrollBehavior here.
+        // Step 1
+        let smooth = match behavior {
+            ScrollBehavior::Smooth => true,
+            ScrollBehavior::Instant => false,
+            ScrollBehavior::Auto => element
+                .and_then(|element| element.style())
+                .map_or(false, |style| {
+                    style.get_box().scroll_behavior == scroll_behavior::T::Smooth
+                }),
+        };
+        let scroll_state = ScrollState {
+            scroll_id,
+            scroll_offset: Vector2D::new(-x, -y),
+        };
+
+        // Step 2
+        if smooth {
+            // The compositor animates the scroll, and tells layout and script about the scroll
+            // offsets on the way like it does for scrolls of the user.
+            self.send_to_constellation(ScriptMsg::SmoothScroll(scroll_state));
+            return;
+        }
+
+        // Step 3
         self.layout_chan
-            .send(Msg::UpdateScrollStateFromScript(ScrollState {
-                scroll_id,
-                scroll_offset: Vector2D::new(-x, -y),
-            }))
+            .send(Msg::UpdateScrollStateFromScript(scroll_state))
             .unwrap();
     }
 
     pub fn update_viewport_for_scroll(&self, x: f32, y: f32) {
-        let size = self.current_viewport.get().size;
-        let new_viewport = Rect::new(Point2D::new(Au::from_f32_px(x), Au::from_f32_px(y)), size);
-        self.current_viewport.set(new_viewport)
+        let old_viewport = self.current_viewport.get();
+        let new_viewport = Rect::new(
+            Point2D::new(Au::from_f32_px(x), Au::from_f32_px(y)),
+            old_viewport.size,
+        );
+        self.current_viewport.set(new_viewport);
+
+        // The visual viewport moves over the document with the layout viewport.
+        if new_viewport.origin != old_viewport.origin {
+            self.queue_visual_viewport_event(atom!("scroll"));
+        }
+    }
+
+    /// Only the windows that script opened can be moved and resized by script, so that pages
+    /// can't move or resize the windows the user opened.
+    /// <https://drafts.csswg.org/cssom-view/#dom-window-moveto> step 1.
+    fn may_move_or_resize(&self) -> bool {
+        self.undiscarded_window_proxy()
+            .map_or(false, |window_proxy| window_proxy.is_auxiliary())
     }
 
     pub fn device_pixel_ratio(&self) -> Scale<f32, CSSPixel, DevicePixel> {
@@ -1602,6 +1913,21 @@ impl Window {
 
         let document = self.Document();
 
+        // Custom highlights and spelling errors dirty the nodes whose highlights or errors
+        // changed, which must happen before layout is checked for changes.
+        document.update_highlighted_text();
+        document.update_spelling_errors();
+
+        // Geometry queries made while the layout is clean don't change it, so the responses
+        // to the queries made since the last change stay valid.
+        let layout_is_clean = !document.needs_layout() &&
+            self.last_reflow_window_size.get() == Some(self.window_size.get());
+        if !layout_is_clean || !reflow_goal.is_geometry_query() {
+            *self.geometry_query_cache.borrow_mut() = Default::default();
+        }
+        self.last_reflow_window_size
+            .set(Some(self.window_size.get()));
+
         let stylesheets_changed = document.flush_stylesheets_for_reflow();
 
         // If this reflow is for display, ensure webgl canvases are composited with
@@ -1619,6 +1945,7 @@ impl Window {
             },
             document: self.Document().upcast::<Node>().to_trusted_node_address(),
             stylesheets_changed,
+            layout_is_clean,
             window_size: self.window_size.get(),
             origin: self.origin().immutable().clone(),
             reflow_goal,
@@ -1780,27 +2107,71 @@ impl Window {
         &*self.layout_rpc
     }
 
+    /// Whether the responses to the geometry queries made since the last reflow are still
+    /// valid, which is the case until the document or the window size changes.
+    fn geometry_query_cache_is_valid(&self) -> bool {
+        !self.Document().needs_layout() &&
+            self.last_reflow_window_size.get() == Some(self.window_size.get())
+    }
+
+    /// Forgets the responses to the geometry queries made so far, so that the queries of the
+    /// next task see the changes made to the layout by animations and loads in between.
+    pub fn clear_geometry_query_cache(&self) {
+        *self.geometry_query_cache.borrow_mut() = Default::default();
+    }
+
     pub fn content_box_query(&self, node: &Node) -> Option<UntypedRect<Au>> {
+        if self.geometry_query_cache_is_valid() {
+            let cache = self.geometry_query_cache.borrow();
+            if let Some(rect) = cache.content_boxes.get(&node.to_opaque()) {
+                return *rect;
+            }
+        }
         if !self.layout_reflow(QueryMsg::ContentBoxQuery(node.to_opaque())) {
             return None;
         }
         let ContentBoxResponse(rect) = self.layout_rpc.content_box();
+        self.geometry_query_cache
+            .borrow_mut()
+            .content_boxes
+            .insert(node.to_opaque(), rect);
         rect
     }
 
     pub fn content_boxes_query(&self, node: &Node) -> Vec<UntypedRect<Au>> {
+        if self.geometry_query_cache_is_valid() {
+            let cache = self.geometry_query_cache.borrow();
+            if let Some(rects) = cache.content_box_lists.get(&node.to_opaque()) {
+                return rects.clone();
+            }
+        }
         if !self.layout_reflow(QueryMsg::ContentBoxesQuery(node.to_opaque())) {
             return vec![];
         }
         let ContentBoxesResponse(rects) = self.layout_rpc.content_boxes();
+        self.geometry_query_cache
+            .borrow_mut()
+            .content_box_lists
+            .insert(node.to_opaque(), rects.clone());
         rects
     }
 
     pub fn client_rect_query(&self, node: &Node) -> UntypedRect<i32> {
+        if self.geometry_query_cache_is_valid() {
+            let cache = self.geometry_query_cache.borrow();
+            if let Some(rect) = cache.client_rects.get(&node.to_opaque()) {
+                return *rect;
+            }
+        }
         if !self.layout_reflow(QueryMsg::ClientRectQuery(node.to_opaque())) {
             return Rect::zero();
         }
-        self.layout_rpc.node_geometry().client_rect
+        let rect = self.layout_rpc.node_geometry().client_rect;
+        self.geometry_query_cache
+            .borrow_mut()
+            .client_rects
+            .insert(node.to_opaque(), rect);
+        rect
     }
 
     pub fn scroll_area_query(&self, node: &Node) -> UntypedRect<i32> {
@@ -1838,7 +2209,7 @@ impl Window {
             y_.to_f32().unwrap_or(0.0f32),
             scroll_id,
             behavior,
-            None,
+            node.downcast::<Element>(),
         );
     }
 
@@ -1865,8 +2236,27 @@ impl Window {
         self.layout_rpc.inner_window_dimensions()
     }
 
+    /// Prints the document on pages of `page_size` CSS pixels, returning the PDF file.
+    pub fn print_query(&self, page_size: UntypedSize2D<f32>) -> Option<Vec<u8>> {
+        if !self.layout_reflow(QueryMsg::PrintQuery(page_size)) {
+            return None;
+        }
+        self.layout_rpc.printed_document()
+    }
+
     #[allow(unsafe_code)]
     pub fn offset_parent_query(&self, node: &Node) -> (Option<DomRoot<Element>>, UntypedRect<Au>) {
+        if self.geometry_query_cache_is_valid() {
+            let cache = self.geometry_query_cache.borrow();
+            if let Some((element, rect)) = cache.offset_parents.get(&node.to_opaque()) {
+                return (
+                    element
+                        .as_ref()
+                        .map(|element| DomRoot::from_ref(&**element)),
+                    *rect,
+                );
+            }
+        }
         if !self.layout_reflow(QueryMsg::OffsetParentQuery(node.to_opaque())) {
             return (None, Rect::zero());
         }
@@ -1880,6 +2270,13 @@ impl Window {
             let node = unsafe { from_untrusted_node_address(js_runtime.rt(), parent_node_address) };
             DomRoot::downcast(node)
         });
+        self.geometry_query_cache
+            .borrow_mut()
+            .offset_parents
+            .insert(
+                node.to_opaque(),
+                (element.as_deref().map(Dom::from_ref), response.rect),
+            );
         (element, response.rect)
     }
 
@@ -1901,6 +2298,25 @@ impl Window {
         self.layout_rpc.text_index()
     }
 
+    pub fn text_index_at_point_query(
+        &self,
+        node: &Node,
+        point: UntypedPoint2D<f32>,
+    ) -> TextIndexResponse {
+        if !self.layout_reflow(QueryMsg::TextIndexAtPointQuery(node.to_opaque(), point)) {
+            return TextIndexResponse(None);
+        }
+        self.layout_rpc.text_index()
+    }
+
+    pub fn text_boxes_query(&self, node: &Node, start: usize, end: usize) -> Vec<UntypedRect<Au>> {
+        if !self.layout_reflow(QueryMsg::TextBoxesQuery(node.to_opaque(), start, end)) {
+            return vec![];
+        }
+        let ContentBoxesResponse(rects) = self.layout_rpc.content_boxes();
+        rects
+    }
+
     #[allow(unsafe_code)]
     pub fn init_window_proxy(&self, window_proxy: &WindowProxy) {
         assert!(self.window_proxy.get().is_none());
@@ -2028,6 +2444,28 @@ impl Window {
         self.window_size.get()
     }
 
+    pub fn pinch_zoom(&self) -> f32 {
+        self.pinch_zoom.get()
+    }
+
+    /// Changes the scale of the visual viewport after the user pinch-zoomed the page.
+    pub fn set_pinch_zoom(&self, pinch_zoom: f32) {
+        if self.pinch_zoom.get() == pinch_zoom {
+            return;
+        }
+        self.pinch_zoom.set(pinch_zoom);
+        self.queue_visual_viewport_event(atom!("resize"));
+    }
+
+    /// Queues a `resize` or `scroll` event for the visual viewport, if script ever asked for it.
+    pub fn queue_visual_viewport_event(&self, name: Atom) {
+        if let Some(visual_viewport) = self.visual_viewport.get() {
+            self.task_manager()
+                .dom_manipulation_task_source()
+                .queue_simple_event(visual_viewport.upcast(), name, self);
+        }
+    }
+
     pub fn get_url(&self) -> ServoUrl {
         self.Document().url()
     }
@@ -2163,6 +2601,10 @@ impl Window {
     /// Evaluate media query lists and report changes
     /// <https://drafts.csswg.org/cssom-view/#evaluate-media-queries-and-report-changes>
     pub fn evaluate_media_queries_and_report_changes(&self) {
+        self.observed_media_query_lists
+            .borrow_mut()
+            .retain(|mql| mql.upcast::<EventTarget>().has_listeners_for(&atom!("change")));
+
         rooted_vec!(let mut mql_list);
         self.media_query_lists.for_each(|mql| {
             if let MediaQueryListMatchState::Changed(_) = mql.evaluate_changes() {
@@ -2185,6 +2627,165 @@ impl Window {
         self.Document().react_to_environment_changes();
     }
 
+    /// Restyles the document and reports the changes of its media queries after the settings
+    /// of the user that media queries can query changed.
+    pub fn media_preferences_changed(&self) {
+        // Neither the document nor the window size changed, but the layout has to be redone.
+        self.last_reflow_window_size.set(None);
+        self.force_reflow(ReflowGoal::Full, ReflowReason::MediaPreferencesChanged);
+        self.evaluate_media_queries_and_report_changes();
+    }
+
+    /// Reports the new states of the battery and of the network connection to the objects of
+    /// the navigator that the page obtained.
+    pub fn device_status_changed(
+        &self,
+        battery_status: BatteryStatus,
+        network_information: NetworkInformation,
+    ) {
+        if let Some(navigator) = self.navigator.get() {
+            navigator.device_status_changed(battery_status, network_information);
+        }
+    }
+
+    /// Reports a position acquired by the embedder to the geolocation object of the page.
+    pub fn position_changed(&self, position: Result<GeolocationPosition, String>) {
+        if let Some(navigator) = self.navigator.get() {
+            navigator.position_changed(position);
+        }
+    }
+
+    /// Keeps a MediaQueryList with `change` listeners alive, so that they keep being notified
+    /// after script dropped the list.
+    pub fn observe_media_query_list(&self, mql: &MediaQueryList) {
+        let mut observed = self.observed_media_query_lists.borrow_mut();
+        if !observed.iter().any(|observed| *observed == mql) {
+            observed.push(Dom::from_ref(mql));
+        }
+    }
+
+    /// Starts watching the sensor of the device that a `deviceorientation`,
+    /// `deviceorientationabsolute` or `devicemotion` listener was added for.
+    pub fn listener_added(&self, ty: &Atom) {
+        let sensor = match sensor_for_event(ty) {
+            Some(sensor) => sensor,
+            None => return,
+        };
+        if !self.watched_sensors.borrow().contains(&sensor) {
+            let mut sensors = self.watched_sensors.borrow().clone();
+            sensors.push(sensor);
+            self.watch_sensors(sensors);
+        }
+    }
+
+    fn watch_sensors(&self, sensors: Vec<DeviceSensor>) {
+        if !pref!(dom.device_orientation.enabled) || *self.watched_sensors.borrow() == sensors {
+            return;
+        }
+        *self.watched_sensors.borrow_mut() = sensors.clone();
+        self.send_to_constellation(ScriptMsg::WatchDeviceSensors(sensors));
+    }
+
+    /// Fires the event for a reading of a sensor of the device, unless the document is hidden
+    /// or the user denied a permission that the sensor requires. The sensors that lost their
+    /// listeners stop being watched.
+    /// <https://w3c.github.io/deviceorientation/#deviceorientation>
+    /// <https://w3c.github.io/deviceorientation/#devicemotion>
+    pub fn sensor_reading(&self, reading: SensorReading) {
+        let target = self.upcast::<EventTarget>();
+        let sensors = self
+            .watched_sensors
+            .borrow()
+            .iter()
+            .cloned()
+            .filter(|sensor| target.has_listeners_for(&event_for_sensor(*sensor)))
+            .collect();
+        self.watch_sensors(sensors);
+
+        let global = self.upcast::<GlobalScope>();
+        let sensor = reading.sensor();
+        let permission_denied = {
+            let results = global.permission_state_invocation_results().borrow();
+            sensor_permissions(sensor)
+                .iter()
+                .any(|name| results.get(&name.to_string()) == Some(&PermissionState::Denied))
+        };
+        if !self.visible() || permission_denied {
+            return;
+        }
+
+        let type_ = event_for_sensor(sensor);
+        match reading {
+            SensorReading::Orientation(orientation) => {
+                let event = DeviceOrientationEvent::new(global, type_, false, false, orientation);
+                event.upcast::<Event>().fire(target);
+            },
+            SensorReading::Motion(motion) => {
+                let event = DeviceMotionEvent::new(global, type_, false, false, motion);
+                event.upcast::<Event>().fire(target);
+            },
+        }
+    }
+
+    /// Records that the user interacted with the page through an activation-triggering input
+    /// event.
+    /// <https://html.spec.whatwg.org/multipage/#activation-notification>
+    pub fn notify_activation(&self) {
+        // Step 3.
+        self.update_user_activation(UserActivationUpdate::Notified);
+        // Steps 4-5, for the windows of the ancestor browsing contexts and of the same origin
+        // descendants, which can live in other script threads.
+        self.send_to_constellation(ScriptMsg::UpdateUserActivation(
+            UserActivationUpdate::Notified,
+        ));
+    }
+
+    /// Applies an activation notification or consumption that happened in this window or in
+    /// another window it propagates to.
+    pub fn update_user_activation(&self, update: UserActivationUpdate) {
+        match update {
+            UserActivationUpdate::Notified => {
+                self.last_activation_timestamp
+                    .set(LastActivationTimestamp::At(Instant::now()));
+            },
+            UserActivationUpdate::Consumed => {
+                if self.last_activation_timestamp.get() != LastActivationTimestamp::Never {
+                    self.last_activation_timestamp
+                        .set(LastActivationTimestamp::Consumed);
+                }
+            },
+        }
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#sticky-activation>
+    pub fn has_sticky_activation(&self) -> bool {
+        self.last_activation_timestamp.get() != LastActivationTimestamp::Never
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#transient-activation>
+    pub fn has_transient_activation(&self) -> bool {
+        match self.last_activation_timestamp.get() {
+            LastActivationTimestamp::At(timestamp) => {
+                timestamp.elapsed() < TRANSIENT_ACTIVATION_DURATION
+            },
+            LastActivationTimestamp::Never | LastActivationTimestamp::Consumed => false,
+        }
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#consume-user-activation>
+    pub fn consume_user_activation(&self) {
+        // Step 1.
+        if self.last_activation_timestamp.get() == LastActivationTimestamp::Never {
+            return;
+        }
+        // Steps 2-5, for this window and the windows of every other fully active browsing
+        // context of its top-level browsing context.
+        self.update_user_activation(UserActivationUpdate::Consumed);
+        self.send_to_constellation(ScriptMsg::UpdateUserActivation(
+            UserActivationUpdate::Consumed,
+        ));
+    }
+
     /// Slow down/speed up timers based on visibility.
     pub fn alter_resource_utilization(&self, visible: bool) {
         self.visible.set(visible);
@@ -2192,6 +2793,8 @@ impl Window {
             self.upcast::<GlobalScope>().speed_up_timers();
         } else {
             self.upcast::<GlobalScope>().slow_down_timers();
+            // https://w3c.github.io/screen-wake-lock/#handling-document-loss-of-visibility
+            self.Document().release_wake_locks();
         }
     }
 
@@ -2199,6 +2802,90 @@ impl Window {
         self.visible.get()
     }
 
+    /// Whether there are idle callbacks waiting for an idle period.
+    pub fn has_idle_callbacks(&self) -> bool {
+        !self.idle_request_callbacks.borrow().is_empty() ||
+            !self.runnable_idle_callbacks.borrow().is_empty()
+    }
+
+    /// <https://w3c.github.io/requestidlecallback/#start-an-idle-period-algorithm>
+    ///
+    /// Returns the deadline of the idle period, in milliseconds of `time::precise_time_ns()`.
+    pub fn start_idle_period(&self) -> f64 {
+        // Steps 2-4.
+        let deadline = self.idle_period_deadline(time::precise_time_ns() as f64 / 1_000_000.);
+
+        // Steps 5-6.
+        let mut idle_request_callbacks = self.idle_request_callbacks.borrow_mut();
+        self.runnable_idle_callbacks
+            .borrow_mut()
+            .extend(idle_request_callbacks.drain(..));
+
+        deadline
+    }
+
+    /// The end of an idle period starting at `now`, which is at the latest 50ms from now, and
+    /// before the next timer or the next rendering opportunity of this window.
+    pub fn idle_period_deadline(&self, now: f64) -> f64 {
+        let mut deadline = now + MAX_IDLE_PERIOD;
+        if let Some(time) = self.upcast::<GlobalScope>().time_until_next_timer() {
+            deadline = deadline.min(now + time.get() as f64);
+        }
+        if let Some(document) = self.document.get() {
+            if let Some(time) = document.next_rendering_opportunity(now) {
+                deadline = deadline.min(time);
+            }
+        }
+        deadline
+    }
+
+    /// <https://w3c.github.io/requestidlecallback/#invoke-idle-callbacks-algorithm>
+    ///
+    /// Invokes the first runnable idle callback with the deadline of the current idle period,
+    /// returning whether there was one. The event loop is in charge of not invoking callbacks
+    /// after the deadline.
+    pub fn invoke_idle_callback(&self, deadline: f64) -> bool {
+        let request = match self.runnable_idle_callbacks.borrow_mut().pop_front() {
+            Some(request) => request,
+            None => return false,
+        };
+        if let Some(timeout) = request.timeout {
+            self.upcast::<GlobalScope>().unschedule_callback(timeout);
+        }
+        let deadline = IdleDeadline::new(self, deadline, false);
+        let _ = request
+            .callback
+            .Call__(&deadline, ExceptionHandling::Report);
+        true
+    }
+
+    /// <https://w3c.github.io/requestidlecallback/#invoke-idle-callback-timeout-algorithm>
+    fn invoke_idle_callback_timeout(&self, handle: u32) {
+        // Steps 1-2. The callback was cancelled or invoked already if it is not in either list.
+        let request = match self.take_idle_request(handle) {
+            Some(request) => request,
+            None => return,
+        };
+        // Steps 3-5.
+        let now = time::precise_time_ns() as f64 / 1_000_000.;
+        let deadline = IdleDeadline::new(self, now, true);
+        let _ = request
+            .callback
+            .Call__(&deadline, ExceptionHandling::Report);
+    }
+
+    /// Removes the idle callback with `handle` from the list of idle request callbacks or the
+    /// list of runnable idle callbacks.
+    fn take_idle_request(&self, handle: u32) -> Option<IdleRequest> {
+        for list in &[&self.idle_request_callbacks, &self.runnable_idle_callbacks] {
+            let mut list = list.borrow_mut();
+            if let Some(index) = list.iter().position(|request| request.handle == handle) {
+                return list.remove(index);
+            }
+        }
+        None
+    }
+
     pub fn unminified_js_dir(&self) -> Option<String> {
         self.unminified_js_dir.borrow().clone()
     }
@@ -2294,6 +2981,7 @@ impl Window {
             image_cache_chan,
             image_cache,
             navigator: Default::default(),
+            speech_synthesis: Default::default(),
             location: Default::default(),
             history: Default::default(),
             custom_element_registry: Default::default(),
@@ -2303,6 +2991,8 @@ impl Window {
             navigation_start: Cell::new(navigation_start),
             navigation_start_precise: Cell::new(navigation_start_precise),
             screen: Default::default(),
+            visual_viewport: Default::default(),
+            scheduler: Default::default(),
             session_storage: Default::default(),
             local_storage: Default::default(),
             status: DomRefCell::new(DOMString::new()),
@@ -2316,9 +3006,12 @@ impl Window {
             layout_chan,
             layout_rpc,
             window_size: Cell::new(window_size),
+            pinch_zoom: Cell::new(1.0),
             current_viewport: Cell::new(Rect::zero()),
             suppress_reflow: Cell::new(true),
             pending_reflow_count: Default::default(),
+            last_reflow_window_size: Default::default(),
+            geometry_query_cache: Default::default(),
             current_state: Cell::new(WindowState::Alive),
             devtools_marker_sender: Default::default(),
             devtools_markers: Default::default(),
@@ -2326,6 +3019,9 @@ impl Window {
             error_reporter,
             scroll_offsets: Default::default(),
             media_query_lists: DOMTracker::new(),
+            observed_media_query_lists: Default::default(),
+            watched_sensors: Default::default(),
+            last_activation_timestamp: Cell::new(LastActivationTimestamp::Never),
             test_runner: Default::default(),
             webgl_chan,
             webvr_chan,
@@ -2347,6 +3043,9 @@ impl Window {
             player_context,
             event_loop_waker,
             visible: Cell::new(true),
+            idle_callback_ident: Cell::new(0),
+            idle_request_callbacks: Default::default(),
+            runnable_idle_callbacks: Default::default(),
         });
 
         unsafe { WindowBinding::Wrap(JSContext::from_ptr(runtime.cx()), win) }
@@ -2357,6 +3056,25 @@ impl Window {
     }
 }
 
+/// The sensor of the device that listeners of the given event wait for readings of.
+fn sensor_for_event(ty: &Atom) -> Option<DeviceSensor> {
+    match *ty {
+        atom!("deviceorientation") => Some(DeviceSensor::Orientation),
+        atom!("deviceorientationabsolute") => Some(DeviceSensor::AbsoluteOrientation),
+        atom!("devicemotion") => Some(DeviceSensor::Motion),
+        _ => None,
+    }
+}
+
+/// The event fired for the readings of the given sensor of the device.
+fn event_for_sensor(sensor: DeviceSensor) -> Atom {
+    match sensor {
+        DeviceSensor::Orientation => atom!("deviceorientation"),
+        DeviceSensor::AbsoluteOrientation => atom!("deviceorientationabsolute"),
+        DeviceSensor::Motion => atom!("devicemotion"),
+    }
+}
+
 fn should_move_clip_rect(clip_rect: UntypedRect<Au>, new_viewport: UntypedRect<f32>) -> bool {
     let clip_rect = UntypedRect::new(
         Point2D::new(
@@ -2397,8 +3115,11 @@ fn debug_reflow_events(id: PipelineId, reflow_goal: &ReflowGoal, reason: &Reflow
             &QueryMsg::OffsetParentQuery(_n) => "\tOffsetParentQuery",
             &QueryMsg::StyleQuery(_n) => "\tStyleQuery",
             &QueryMsg::TextIndexQuery(..) => "\tTextIndexQuery",
+            &QueryMsg::TextIndexAtPointQuery(..) => "\tTextIndexAtPointQuery",
+            &QueryMsg::TextBoxesQuery(..) => "\tTextBoxesQuery",
             &QueryMsg::ElementInnerTextQuery(_) => "\tElementInnerTextQuery",
             &QueryMsg::InnerWindowDimensionsQuery(_) => "\tInnerWindowDimensionsQuery",
+            &QueryMsg::PrintQuery(_) => "\tPrintQuery",
         },
     });
 
@@ -2423,6 +3144,9 @@ fn debug_reflow_events(id: PipelineId, reflow_goal: &ReflowGoal, reason: &Reflow
         ReflowReason::IFrameLoadEvent => "\tIFrameLoadEvent",
         ReflowReason::MissingExplicitReflow => "\tMissingExplicitReflow",
         ReflowReason::ElementStateChanged => "\tElementStateChanged",
+        ReflowReason::Print => "\tPrint",
+        ReflowReason::MediaPreferencesChanged => "\tMediaPreferencesChanged",
+        ReflowReason::TextZoomChanged => "\tTextZoomChanged",
     });
 
     println!("{}", debug_msg);
@@ -52,6 +52,7 @@ use script_traits::{
     AuxiliaryBrowsingContextLoadInfo, HistoryEntryReplacement, LoadData, LoadOrigin,
 };
 use script_traits::{NewLayoutInfo, ScriptMsg};
+use servo_config::pref;
 use servo_url::ServoUrl;
 use std::cell::Cell;
 use std::ptr;
@@ -270,6 +271,13 @@ impl WindowProxy {
             .and_then(|id| ScriptThread::find_document(id))
             .and_then(|doc| Some(DomRoot::from_ref(doc.window())))
             .unwrap();
+        // Opening a popup consumes the activation of the user interaction which triggered it,
+        // without which the popup blocker refuses it.
+        if !window.has_transient_activation() && pref!(dom.popup_blocker.enabled) {
+            info!("Blocked a popup which was opened without user activation.");
+            return None;
+        }
+        window.consume_user_activation();
         let msg = EmbedderMsg::AllowOpeningBrowser(chan);
         window.send_to_embedder(msg);
         if port.recv().unwrap() {
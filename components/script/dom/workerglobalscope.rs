@@ -20,6 +20,7 @@ use crate::dom::dedicatedworkerglobalscope::DedicatedWorkerGlobalScope;
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::performance::Performance;
 use crate::dom::promise::Promise;
+use crate::dom::scheduler::Scheduler;
 use crate::dom::serviceworkerglobalscope::ServiceWorkerGlobalScope;
 use crate::dom::window::{base64_atob, base64_btoa};
 use crate::dom::workerlocation::WorkerLocation;
@@ -34,6 +35,7 @@ use crate::task_source::file_reading::FileReadingTaskSource;
 use crate::task_source::networking::NetworkingTaskSource;
 use crate::task_source::performance_timeline::PerformanceTimelineTaskSource;
 use crate::task_source::port_message::PortMessageQueue;
+use crate::task_source::posted_task::PostedTaskSource;
 use crate::task_source::remote_event::RemoteEventTaskSource;
 use crate::task_source::timer::TimerTaskSource;
 use crate::task_source::websocket::WebsocketTaskSource;
@@ -110,6 +112,7 @@ pub struct WorkerGlobalScope {
 
     navigation_start_precise: u64,
     performance: MutNullableDom<Performance>,
+    scheduler: MutNullableDom<Scheduler>,
 }
 
 impl WorkerGlobalScope {
@@ -150,6 +153,7 @@ impl WorkerGlobalScope {
             from_devtools_receiver,
             navigation_start_precise: precise_time_ns(),
             performance: Default::default(),
+            scheduler: Default::default(),
         }
     }
 
@@ -366,6 +370,11 @@ impl WorkerGlobalScopeMethods for WorkerGlobalScope {
         })
     }
 
+    // https://wicg.github.io/scheduling-apis/#dom-windoworworkerglobalscope-scheduler
+    fn Scheduler(&self) -> DomRoot<Scheduler> {
+        self.scheduler.or_init(|| Scheduler::new(self.upcast()))
+    }
+
     // https://html.spec.whatwg.org/multipage/#dom-origin
     fn Origin(&self) -> USVString {
         USVString(
@@ -442,6 +451,10 @@ impl WorkerGlobalScope {
         TimerTaskSource(self.script_chan(), self.pipeline_id())
     }
 
+    pub fn posted_task_source(&self) -> PostedTaskSource {
+        PostedTaskSource(self.script_chan(), self.pipeline_id())
+    }
+
     pub fn remote_event_task_source(&self) -> RemoteEventTaskSource {
         RemoteEventTaskSource(self.script_chan(), self.pipeline_id())
     }
@@ -476,4 +489,11 @@ impl WorkerGlobalScope {
             closing.store(true, Ordering::SeqCst);
         }
     }
+
+    /// Releases the locks that the worker holds or waits for, once its event loop stopped.
+    pub fn release_locks(&self) {
+        if let Some(navigator) = self.navigator.get() {
+            navigator.release_locks();
+        }
+    }
 }
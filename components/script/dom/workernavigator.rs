@@ -7,8 +7,10 @@ use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
 use crate::dom::bindings::root::{DomRoot, MutNullableDom};
 use crate::dom::bindings::str::DOMString;
 use crate::dom::gpu::GPU;
+use crate::dom::lockmanager::LockManager;
 use crate::dom::navigatorinfo;
 use crate::dom::permissions::Permissions;
+use crate::dom::storagemanager::StorageManager;
 use crate::dom::workerglobalscope::WorkerGlobalScope;
 use dom_struct::dom_struct;
 
@@ -18,6 +20,8 @@ pub struct WorkerNavigator {
     reflector_: Reflector,
     permissions: MutNullableDom<Permissions>,
     gpu: MutNullableDom<GPU>,
+    storage: MutNullableDom<StorageManager>,
+    locks: MutNullableDom<LockManager>,
 }
 
 impl WorkerNavigator {
@@ -26,12 +30,21 @@ impl WorkerNavigator {
             reflector_: Reflector::new(),
             permissions: Default::default(),
             gpu: Default::default(),
+            storage: Default::default(),
+            locks: Default::default(),
         }
     }
 
     pub fn new(global: &WorkerGlobalScope) -> DomRoot<WorkerNavigator> {
         reflect_dom_object(Box::new(WorkerNavigator::new_inherited()), global)
     }
+
+    /// Releases the locks of the worker, which is closing.
+    pub fn release_locks(&self) {
+        if let Some(locks) = self.locks.get() {
+            locks.release_all();
+        }
+    }
 }
 
 impl WorkerNavigatorMethods for WorkerNavigator {
@@ -100,4 +113,14 @@ impl WorkerNavigatorMethods for WorkerNavigator {
     fn Gpu(&self) -> DomRoot<GPU> {
         self.gpu.or_init(|| GPU::new(&self.global()))
     }
+
+    // https://storage.spec.whatwg.org/#dom-navigatorstorage-storage
+    fn Storage(&self) -> DomRoot<StorageManager> {
+        self.storage.or_init(|| StorageManager::new(&self.global()))
+    }
+
+    // https://w3c.github.io/web-locks/#dom-navigatorlocks-locks
+    fn Locks(&self) -> DomRoot<LockManager> {
+        self.locks.or_init(|| LockManager::new(&self.global()))
+    }
 }
@@ -2,15 +2,26 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use crate::dom::bindings::codegen::Bindings::DocumentBinding::DocumentMethods;
+use crate::dom::bindings::codegen::Bindings::HTMLTemplateElementBinding::HTMLTemplateElementMethods;
 use crate::dom::bindings::codegen::Bindings::XMLSerializerBinding::XMLSerializerMethods;
-use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::error::{Error, ErrorResult, Fallible};
+use crate::dom::bindings::inheritance::{Castable, CharacterDataTypeId, NodeTypeId, TextTypeId};
 use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
 use crate::dom::bindings::root::{Dom, DomRoot};
 use crate::dom::bindings::str::DOMString;
+use crate::dom::bindings::xmlname::{xml_name_type, XMLName};
+use crate::dom::characterdata::CharacterData;
+use crate::dom::document::Document;
+use crate::dom::documenttype::DocumentType;
+use crate::dom::element::Element;
+use crate::dom::htmltemplateelement::HTMLTemplateElement;
 use crate::dom::node::Node;
+use crate::dom::processinginstruction::ProcessingInstruction;
 use crate::dom::window::Window;
 use dom_struct::dom_struct;
-use xml5ever::serialize::{serialize, SerializeOpts, TraversalScope};
+use html5ever::{LocalName, Namespace};
+use std::collections::{HashMap, HashSet};
 
 #[dom_struct]
 pub struct XMLSerializer {
@@ -37,20 +48,476 @@ impl XMLSerializer {
 }
 
 impl XMLSerializerMethods for XMLSerializer {
-    // https://w3c.github.io/DOM-Parsing/#the-xmlserializer-interface
+    // https://w3c.github.io/DOM-Parsing/#dom-xmlserializer-serializetostring
     fn SerializeToString(&self, root: &Node) -> Fallible<DOMString> {
-        let mut writer = vec![];
-        match serialize(
-            &mut writer,
-            &root,
-            SerializeOpts {
-                traversal_scope: TraversalScope::IncludeNode,
-            },
-        ) {
-            Ok(_) => Ok(DOMString::from(String::from_utf8(writer).unwrap())),
-            Err(_) => Err(Error::Type(String::from(
-                "root must be a Node or an Attr object",
-            ))),
+        serialize_xml(root, false)
+    }
+}
+
+/// <https://w3c.github.io/DOM-Parsing/#dfn-concept-serialize-xml>
+pub fn serialize_xml(node: &Node, require_well_formed: bool) -> Fallible<DOMString> {
+    let mut serialization = XmlSerialization::new(require_well_formed);
+    serialization.serialize_node(node, &ns!(), &initial_namespace_prefix_map())?;
+    Ok(DOMString::from(serialization.markup))
+}
+
+/// Serializes the children of `element`, in the namespaces that are in scope for them, as
+/// `innerHTML` does in XML documents.
+pub fn serialize_xml_children(element: &Element, require_well_formed: bool) -> Fallible<DOMString> {
+    let mut map = initial_namespace_prefix_map();
+    record_namespace_information(element, &mut map, &mut HashMap::new());
+
+    let node = match element.downcast::<HTMLTemplateElement>() {
+        Some(template) => DomRoot::upcast(template.Content()),
+        None => DomRoot::from_ref(element.upcast::<Node>()),
+    };
+    let mut serialization = XmlSerialization::new(require_well_formed);
+    for child in node.children() {
+        serialization.serialize_node(&child, element.namespace(), &map)?;
+    }
+    Ok(DOMString::from(serialization.markup))
+}
+
+/// The prefixes that are declared for each namespace, in the order they were declared in.
+/// The null namespace is `ns!()`.
+/// <https://w3c.github.io/DOM-Parsing/#dfn-namespace-prefix-map>
+type NamespacePrefixMap = HashMap<Namespace, Vec<String>>;
+
+/// The map that the serialization starts with, in which only the `xml` prefix is declared.
+fn initial_namespace_prefix_map() -> NamespacePrefixMap {
+    let mut map = HashMap::new();
+    map.insert(ns!(xml), vec!["xml".to_owned()]);
+    map
+}
+
+/// <https://w3c.github.io/DOM-Parsing/#dfn-retrieving-a-preferred-prefix-string>
+fn preferred_prefix(
+    map: &NamespacePrefixMap,
+    preferred_prefix: Option<&str>,
+    namespace: &Namespace,
+) -> Option<String> {
+    let candidates = map.get(namespace)?;
+    candidates
+        .iter()
+        .find(|prefix| Some(prefix.as_str()) == preferred_prefix)
+        .or_else(|| candidates.last())
+        .cloned()
+}
+
+/// Adds the namespaces that the attributes of `element` declare prefixes for to the map, and
+/// returns the default namespace that they declare.
+/// <https://w3c.github.io/DOM-Parsing/#recording-the-namespace-information>
+fn record_namespace_information(
+    element: &Element,
+    map: &mut NamespacePrefixMap,
+    local_prefixes: &mut HashMap<String, Namespace>,
+) -> Option<Namespace> {
+    let mut default_namespace = None;
+    for attr in element.attrs().iter() {
+        if *attr.namespace() != ns!(xmlns) {
+            continue;
+        }
+        let namespace = Namespace::from(&**attr.value());
+        if attr.prefix().is_none() {
+            default_namespace = Some(namespace);
+            continue;
+        }
+
+        let prefix = attr.local_name().to_string();
+        if namespace == ns!(xml) {
+            continue;
+        }
+        let prefixes = map.entry(namespace.clone()).or_default();
+        if prefixes.contains(&prefix) {
+            continue;
+        }
+        prefixes.push(prefix.clone());
+        local_prefixes.insert(prefix, namespace);
+    }
+    default_namespace
+}
+
+/// Whether the string matches the `Char*` production of XML.
+/// <https://www.w3.org/TR/xml/#NT-Char>
+fn is_xml_chars(s: &str) -> bool {
+    s.chars().all(|c| match c {
+        '\t' | '\n' | '\r' => true,
+        '\u{FFFE}' | '\u{FFFF}' => false,
+        c => c >= ' ',
+    })
+}
+
+/// Whether the string matches the `PubidChar*` production of XML.
+/// <https://www.w3.org/TR/xml/#NT-PubidChar>
+fn is_pubid_chars(s: &str) -> bool {
+    s.chars().all(|c| match c {
+        ' ' | '\r' | '\n' | 'a'..='z' | 'A'..='Z' | '0'..='9' => true,
+        c => "-'()+,./:=?;!*#@$_%".contains(c),
+    })
+}
+
+fn is_xml_name(s: &str) -> bool {
+    xml_name_type(s) != XMLName::InvalidXMLName
+}
+
+/// <https://w3c.github.io/DOM-Parsing/#dfn-serializing-an-attribute-value>
+fn escape_attribute_value(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('"', "&quot;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// The state of an XML serialization.
+/// <https://w3c.github.io/DOM-Parsing/#dfn-xml-serialization>
+struct XmlSerialization {
+    markup: String,
+    /// <https://w3c.github.io/DOM-Parsing/#dfn-generated-namespace-prefix-index>
+    prefix_index: u32,
+    /// Whether nodes that can't be serialized to well-formed XML are errors, rather than
+    /// being serialized as they are.
+    require_well_formed: bool,
+}
+
+impl XmlSerialization {
+    fn new(require_well_formed: bool) -> XmlSerialization {
+        XmlSerialization {
+            markup: String::new(),
+            prefix_index: 1,
+            require_well_formed,
+        }
+    }
+
+    /// Fails when well-formed XML is required and the condition for it doesn't hold.
+    fn check_well_formed(&self, well_formed: bool) -> ErrorResult {
+        if self.require_well_formed && !well_formed {
+            return Err(Error::InvalidState);
+        }
+        Ok(())
+    }
+
+    /// <https://w3c.github.io/DOM-Parsing/#dfn-generating-a-prefix>
+    fn generate_prefix(&mut self, map: &mut NamespacePrefixMap, namespace: &Namespace) -> String {
+        let prefix = format!("ns{}", self.prefix_index);
+        self.prefix_index += 1;
+        map.entry(namespace.clone())
+            .or_default()
+            .push(prefix.clone());
+        prefix
+    }
+
+    /// <https://w3c.github.io/DOM-Parsing/#dfn-xml-serialization-algorithm>
+    fn serialize_node(
+        &mut self,
+        node: &Node,
+        namespace: &Namespace,
+        map: &NamespacePrefixMap,
+    ) -> ErrorResult {
+        match node.type_id() {
+            NodeTypeId::Element(_) => {
+                self.serialize_element(node.downcast::<Element>().unwrap(), namespace, map)
+            },
+            NodeTypeId::Document(_) => {
+                let document = node.downcast::<Document>().unwrap();
+                self.check_well_formed(document.GetDocumentElement().is_some())?;
+                self.serialize_children(node, namespace, map)
+            },
+            NodeTypeId::DocumentFragment(_) => self.serialize_children(node, namespace, map),
+            NodeTypeId::DocumentType => {
+                let doctype = node.downcast::<DocumentType>().unwrap();
+                let public_id = doctype.public_id();
+                let system_id = doctype.system_id();
+                self.check_well_formed(is_pubid_chars(public_id))?;
+                self.check_well_formed(
+                    is_xml_chars(system_id) &&
+                        !(system_id.contains('"') && system_id.contains('\'')),
+                )?;
+
+                self.markup.push_str("<!DOCTYPE ");
+                self.markup.push_str(doctype.name());
+                if !public_id.is_empty() {
+                    self.markup.push_str(" PUBLIC \"");
+                    self.markup.push_str(public_id);
+                    self.markup.push('"');
+                }
+                if !system_id.is_empty() {
+                    if public_id.is_empty() {
+                        self.markup.push_str(" SYSTEM");
+                    }
+                    self.markup.push_str(" \"");
+                    self.markup.push_str(system_id);
+                    self.markup.push('"');
+                }
+                self.markup.push('>');
+                Ok(())
+            },
+            NodeTypeId::CharacterData(CharacterDataTypeId::Comment) => {
+                let data = node.downcast::<CharacterData>().unwrap().data();
+                self.check_well_formed(
+                    is_xml_chars(&data) && !data.contains("--") && !data.ends_with('-'),
+                )?;
+
+                self.markup.push_str("<!--");
+                self.markup.push_str(&data);
+                self.markup.push_str("-->");
+                Ok(())
+            },
+            NodeTypeId::CharacterData(CharacterDataTypeId::Text(TextTypeId::CDATASection)) => {
+                let data = node.downcast::<CharacterData>().unwrap().data();
+                self.check_well_formed(is_xml_chars(&data) && !data.contains("]]>"))?;
+
+                self.markup.push_str("<![CDATA[");
+                self.markup.push_str(&data);
+                self.markup.push_str("]]>");
+                Ok(())
+            },
+            NodeTypeId::CharacterData(CharacterDataTypeId::Text(_)) => {
+                let data = node.downcast::<CharacterData>().unwrap().data();
+                self.check_well_formed(is_xml_chars(&data))?;
+
+                for c in data.chars() {
+                    match c {
+                        '&' => self.markup.push_str("&amp;"),
+                        '<' => self.markup.push_str("&lt;"),
+                        '>' => self.markup.push_str("&gt;"),
+                        c => self.markup.push(c),
+                    }
+                }
+                Ok(())
+            },
+            NodeTypeId::CharacterData(CharacterDataTypeId::ProcessingInstruction) => {
+                let pi = node.downcast::<ProcessingInstruction>().unwrap();
+                let target = pi.target();
+                let data = pi.upcast::<CharacterData>().data();
+                self.check_well_formed(
+                    !target.contains(':') && !target.eq_ignore_ascii_case("xml"),
+                )?;
+                self.check_well_formed(is_xml_chars(&data) && !data.contains("?>"))?;
+
+                self.markup.push_str("<?");
+                self.markup.push_str(target);
+                self.markup.push(' ');
+                self.markup.push_str(&data);
+                self.markup.push_str("?>");
+                Ok(())
+            },
+            // Attributes are only serialized as part of their element.
+            NodeTypeId::Attr => Ok(()),
+        }
+    }
+
+    fn serialize_children(
+        &mut self,
+        node: &Node,
+        namespace: &Namespace,
+        map: &NamespacePrefixMap,
+    ) -> ErrorResult {
+        for child in node.children() {
+            self.serialize_node(&child, namespace, map)?;
+        }
+        Ok(())
+    }
+
+    /// <https://w3c.github.io/DOM-Parsing/#xml-serializing-an-element-node>
+    fn serialize_element(
+        &mut self,
+        element: &Element,
+        namespace: &Namespace,
+        map: &NamespacePrefixMap,
+    ) -> ErrorResult {
+        let local_name = element.local_name();
+        // Step 1.
+        self.check_well_formed(!local_name.contains(':') && is_xml_name(local_name))?;
+
+        // Steps 2-7.
+        self.markup.push('<');
+        let mut ignore_namespace_definition_attribute = false;
+        let mut map = map.clone();
+        let mut local_prefixes = HashMap::new();
+
+        // Step 8.
+        let local_default_namespace =
+            record_namespace_information(element, &mut map, &mut local_prefixes);
+
+        // Steps 9-10.
+        let mut inherited_namespace = namespace.clone();
+        let element_namespace = element.namespace();
+
+        // Steps 11-12. The namespace declaration that the serialization adds, if any, follows
+        // the qualified name.
+        let mut namespace_declaration = None;
+        let qualified_name = if inherited_namespace == *element_namespace {
+            if local_default_namespace.is_some() {
+                ignore_namespace_definition_attribute = true;
+            }
+            if *element_namespace == ns!(xml) {
+                format!("xml:{}", local_name)
+            } else {
+                local_name.to_string()
+            }
+        } else {
+            let prefix = element.prefix().as_ref().map(|prefix| prefix.to_string());
+            let mut candidate_prefix =
+                preferred_prefix(&map, prefix.as_ref().map(String::as_str), element_namespace);
+            if prefix.as_ref().map_or(false, |prefix| prefix == "xmlns") {
+                self.check_well_formed(false)?;
+                candidate_prefix = prefix.clone();
+            }
+
+            if let Some(candidate_prefix) = candidate_prefix {
+                if let Some(ref default_namespace) = local_default_namespace {
+                    if *default_namespace != ns!(xml) {
+                        inherited_namespace = default_namespace.clone();
+                    }
+                }
+                format!("{}:{}", candidate_prefix, local_name)
+            } else if let Some(mut prefix) = prefix {
+                if local_prefixes.contains_key(&prefix) {
+                    prefix = self.generate_prefix(&mut map, element_namespace);
+                } else {
+                    map.entry(element_namespace.clone())
+                        .or_default()
+                        .push(prefix.clone());
+                }
+                namespace_declaration = Some(format!(
+                    " xmlns:{}=\"{}\"",
+                    prefix,
+                    escape_attribute_value(element_namespace)
+                ));
+                if let Some(ref default_namespace) = local_default_namespace {
+                    inherited_namespace = default_namespace.clone();
+                }
+                format!("{}:{}", prefix, local_name)
+            } else if local_default_namespace.as_ref() != Some(element_namespace) {
+                ignore_namespace_definition_attribute = true;
+                namespace_declaration = Some(format!(
+                    " xmlns=\"{}\"",
+                    escape_attribute_value(element_namespace)
+                ));
+                inherited_namespace = element_namespace.clone();
+                local_name.to_string()
+            } else {
+                inherited_namespace = element_namespace.clone();
+                local_name.to_string()
+            }
+        };
+        self.markup.push_str(&qualified_name);
+        if let Some(namespace_declaration) = namespace_declaration {
+            self.markup.push_str(&namespace_declaration);
+        }
+
+        // Step 13.
+        self.serialize_attributes(
+            element,
+            &mut map,
+            &local_prefixes,
+            ignore_namespace_definition_attribute,
+        )?;
+
+        // Steps 14-17.
+        let node = element.upcast::<Node>();
+        let html_namespace = *element_namespace == ns!(html);
+        if node.children_count() == 0 && (!html_namespace || element.is_void()) {
+            if html_namespace {
+                self.markup.push_str(" /");
+            } else {
+                self.markup.push('/');
+            }
+            self.markup.push('>');
+            return Ok(());
+        }
+        self.markup.push('>');
+
+        // Steps 18-19.
+        match element.downcast::<HTMLTemplateElement>() {
+            Some(template) => {
+                let content = template.Content();
+                self.serialize_children(content.upcast(), &inherited_namespace, &map)?;
+            },
+            None => self.serialize_children(node, &inherited_namespace, &map)?,
+        }
+
+        // Step 20.
+        self.markup.push_str("</");
+        self.markup.push_str(&qualified_name);
+        self.markup.push('>');
+        Ok(())
+    }
+
+    /// <https://w3c.github.io/DOM-Parsing/#dfn-xml-serialization-of-the-attributes>
+    fn serialize_attributes(
+        &mut self,
+        element: &Element,
+        map: &mut NamespacePrefixMap,
+        local_prefixes: &HashMap<String, Namespace>,
+        ignore_namespace_definition_attribute: bool,
+    ) -> ErrorResult {
+        let mut local_names: HashSet<(Namespace, LocalName)> = HashSet::new();
+        for attr in element.attrs().iter() {
+            let attribute_namespace = attr.namespace();
+            let attr_local_name = attr.local_name();
+            let value = attr.value();
+
+            // Steps 3.1-3.2.
+            let unique = local_names.insert((attribute_namespace.clone(), attr_local_name.clone()));
+            self.check_well_formed(unique)?;
+
+            // Steps 3.3-3.5.
+            let mut candidate_prefix = None;
+            if *attribute_namespace != ns!() {
+                let prefix = attr.prefix().map(|prefix| &**prefix);
+                candidate_prefix = preferred_prefix(map, prefix, attribute_namespace);
+                if *attribute_namespace == ns!(xmlns) {
+                    let declared_namespace = Namespace::from(&**value);
+                    let redundant = declared_namespace == ns!(xml) ||
+                        match prefix {
+                            None => ignore_namespace_definition_attribute,
+                            Some(_) => {
+                                local_prefixes.get(&**attr_local_name) != Some(&declared_namespace)
+                            },
+                        };
+                    if redundant {
+                        continue;
+                    }
+                    self.check_well_formed(declared_namespace != ns!(xmlns))?;
+                    if prefix.is_some() {
+                        self.check_well_formed(!value.is_empty())?;
+                    }
+                    if prefix == Some("xmlns") {
+                        candidate_prefix = Some("xmlns".to_owned());
+                    }
+                } else if candidate_prefix.is_none() {
+                    let prefix = self.generate_prefix(map, attribute_namespace);
+                    self.markup.push_str(" xmlns:");
+                    self.markup.push_str(&prefix);
+                    self.markup.push_str("=\"");
+                    self.markup
+                        .push_str(&escape_attribute_value(attribute_namespace));
+                    self.markup.push('"');
+                    candidate_prefix = Some(prefix);
+                }
+            }
+
+            // Steps 3.6-3.7.
+            self.markup.push(' ');
+            if let Some(candidate_prefix) = candidate_prefix {
+                self.markup.push_str(&candidate_prefix);
+                self.markup.push(':');
+            }
+
+            // Steps 3.8-3.9.
+            self.check_well_formed(
+                !attr_local_name.contains(':') &&
+                    is_xml_name(attr_local_name) &&
+                    !(&**attr_local_name == "xmlns" && *attribute_namespace == ns!()),
+            )?;
+            self.check_well_formed(is_xml_chars(&value))?;
+            self.markup.push_str(attr_local_name);
+            self.markup.push_str("=\"");
+            self.markup.push_str(&escape_attribute_value(&value));
+            self.markup.push('"');
         }
+        Ok(())
     }
 }
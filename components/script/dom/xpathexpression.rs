@@ -0,0 +1,72 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::callback::ExceptionHandling::Rethrow;
+use crate::dom::bindings::codegen::Bindings::XPathEvaluatorBinding::XPathNSResolver;
+use crate::dom::bindings::codegen::Bindings::XPathExpressionBinding::XPathExpressionMethods;
+use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::node::Node;
+use crate::dom::window::Window;
+use crate::dom::xpathresult::XPathResult;
+use crate::xpath::{self, Expr};
+use dom_struct::dom_struct;
+use html5ever::Namespace;
+use std::rc::Rc;
+
+/// <https://dom.spec.whatwg.org/#interface-xpathexpression>
+#[dom_struct]
+pub struct XPathExpression {
+    reflector_: Reflector,
+    window: Dom<Window>,
+    #[ignore_malloc_size_of = "Defined in xpath"]
+    expr: Expr,
+}
+
+impl XPathExpression {
+    fn new_inherited(window: &Window, expr: Expr) -> XPathExpression {
+        XPathExpression {
+            reflector_: Reflector::new(),
+            window: Dom::from_ref(window),
+            expr: expr,
+        }
+    }
+
+    /// Parses the expression, resolving its namespace prefixes with the resolver.
+    /// <https://www.w3.org/TR/DOM-Level-3-XPath/xpath.html#XPathEvaluator-createExpression>
+    pub fn create(
+        window: &Window,
+        expression: DOMString,
+        resolver: Option<Rc<XPathNSResolver>>,
+    ) -> Fallible<DomRoot<XPathExpression>> {
+        let expr = xpath::parse(&expression, &mut |prefix| {
+            let resolver = resolver.as_ref().ok_or(Error::Namespace)?;
+            let namespace =
+                resolver.LookupNamespaceURI_(window, Some(DOMString::from(prefix)), Rethrow)?;
+            match namespace {
+                Some(ref namespace) if !namespace.is_empty() => Ok(Namespace::from(&**namespace)),
+                _ => Err(Error::Namespace),
+            }
+        })?;
+        Ok(reflect_dom_object(
+            Box::new(XPathExpression::new_inherited(window, expr)),
+            window,
+        ))
+    }
+}
+
+impl XPathExpressionMethods for XPathExpression {
+    // https://dom.spec.whatwg.org/#dom-xpathexpression-evaluate
+    fn Evaluate(
+        &self,
+        context_node: &Node,
+        type_: u16,
+        _result: Option<&XPathResult>,
+    ) -> Fallible<DomRoot<XPathResult>> {
+        let value = xpath::evaluate(&self.expr, context_node)?;
+        XPathResult::new(&self.window, &context_node.owner_doc(), type_, value)
+    }
+}
@@ -0,0 +1,221 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::XPathResultBinding::XPathResultConstants;
+use crate::dom::bindings::codegen::Bindings::XPathResultBinding::XPathResultMethods;
+use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::document::Document;
+use crate::dom::node::Node;
+use crate::dom::window::Window;
+use crate::xpath::Value;
+use dom_struct::dom_struct;
+use std::cell::Cell;
+
+#[derive(JSTraceable, MallocSizeOf)]
+#[unrooted_must_root_lint::must_root]
+enum XPathResultValue {
+    Number(f64),
+    String(DOMString),
+    Boolean(bool),
+    /// The nodes of a node-set result, in document order.
+    Nodes(Vec<Dom<Node>>),
+}
+
+/// <https://dom.spec.whatwg.org/#interface-xpathresult>
+#[dom_struct]
+pub struct XPathResult {
+    reflector_: Reflector,
+    result_type: u16,
+    value: XPathResultValue,
+    /// The document of the context node, whose mutations invalidate iterator results.
+    document: Dom<Document>,
+    /// The version of the document when the result was created.
+    document_version: u64,
+    /// The index of the node that `iterateNext()` returns next.
+    iterator_index: Cell<usize>,
+}
+
+impl XPathResult {
+    #[allow(unrooted_must_root)]
+    fn new_inherited(
+        result_type: u16,
+        value: XPathResultValue,
+        document: &Document,
+    ) -> XPathResult {
+        XPathResult {
+            reflector_: Reflector::new(),
+            result_type: result_type,
+            value: value,
+            document: Dom::from_ref(document),
+            document_version: document.upcast::<Node>().inclusive_descendants_version(),
+            iterator_index: Cell::new(0),
+        }
+    }
+
+    /// Creates the result of the given type from the value an expression evaluated to,
+    /// converting it as required.
+    /// <https://www.w3.org/TR/DOM-Level-3-XPath/xpath.html#XPathEvaluator-evaluate>
+    pub fn new(
+        window: &Window,
+        document: &Document,
+        result_type: u16,
+        value: Value,
+    ) -> Fallible<DomRoot<XPathResult>> {
+        let result_type = match (result_type, &value) {
+            (XPathResultConstants::ANY_TYPE, &Value::NodeSet(_)) => {
+                XPathResultConstants::UNORDERED_NODE_ITERATOR_TYPE
+            },
+            (XPathResultConstants::ANY_TYPE, &Value::Number(_)) => {
+                XPathResultConstants::NUMBER_TYPE
+            },
+            (XPathResultConstants::ANY_TYPE, &Value::String(_)) => {
+                XPathResultConstants::STRING_TYPE
+            },
+            (XPathResultConstants::ANY_TYPE, &Value::Boolean(_)) => {
+                XPathResultConstants::BOOLEAN_TYPE
+            },
+            (result_type, _) => result_type,
+        };
+        let value = match result_type {
+            XPathResultConstants::NUMBER_TYPE => XPathResultValue::Number(value.number()),
+            XPathResultConstants::STRING_TYPE => {
+                XPathResultValue::String(DOMString::from(value.string()))
+            },
+            XPathResultConstants::BOOLEAN_TYPE => XPathResultValue::Boolean(value.boolean()),
+            XPathResultConstants::UNORDERED_NODE_ITERATOR_TYPE
+                ..=XPathResultConstants::FIRST_ORDERED_NODE_TYPE => {
+                let mut nodes = match value {
+                    Value::NodeSet(nodes) => nodes,
+                    _ => {
+                        return Err(Error::Type(
+                            "The expression does not evaluate to a node-set".to_owned(),
+                        ));
+                    },
+                };
+                if result_type >= XPathResultConstants::ANY_UNORDERED_NODE_TYPE {
+                    nodes.truncate(1);
+                }
+                XPathResultValue::Nodes(nodes.iter().map(|node| Dom::from_ref(&**node)).collect())
+            },
+            _ => return Err(Error::NotSupported),
+        };
+        Ok(reflect_dom_object(
+            Box::new(XPathResult::new_inherited(result_type, value, document)),
+            window,
+        ))
+    }
+
+    fn is_iterator(&self) -> bool {
+        self.result_type == XPathResultConstants::UNORDERED_NODE_ITERATOR_TYPE ||
+            self.result_type == XPathResultConstants::ORDERED_NODE_ITERATOR_TYPE
+    }
+
+    fn is_snapshot(&self) -> bool {
+        self.result_type == XPathResultConstants::UNORDERED_NODE_SNAPSHOT_TYPE ||
+            self.result_type == XPathResultConstants::ORDERED_NODE_SNAPSHOT_TYPE
+    }
+
+    fn nodes(&self) -> &[Dom<Node>] {
+        match self.value {
+            XPathResultValue::Nodes(ref nodes) => nodes,
+            _ => &[],
+        }
+    }
+}
+
+fn wrong_type_error() -> Error {
+    Error::Type("The result is not of the requested type".to_owned())
+}
+
+impl XPathResultMethods for XPathResult {
+    // https://dom.spec.whatwg.org/#dom-xpathresult-resulttype
+    fn ResultType(&self) -> u16 {
+        self.result_type
+    }
+
+    // https://dom.spec.whatwg.org/#dom-xpathresult-numbervalue
+    fn GetNumberValue(&self) -> Fallible<f64> {
+        match self.value {
+            XPathResultValue::Number(number) => Ok(number),
+            _ => Err(wrong_type_error()),
+        }
+    }
+
+    // https://dom.spec.whatwg.org/#dom-xpathresult-stringvalue
+    fn GetStringValue(&self) -> Fallible<DOMString> {
+        match self.value {
+            XPathResultValue::String(ref string) => Ok(string.clone()),
+            _ => Err(wrong_type_error()),
+        }
+    }
+
+    // https://dom.spec.whatwg.org/#dom-xpathresult-booleanvalue
+    fn GetBooleanValue(&self) -> Fallible<bool> {
+        match self.value {
+            XPathResultValue::Boolean(boolean) => Ok(boolean),
+            _ => Err(wrong_type_error()),
+        }
+    }
+
+    // https://dom.spec.whatwg.org/#dom-xpathresult-singlenodevalue
+    fn GetSingleNodeValue(&self) -> Fallible<Option<DomRoot<Node>>> {
+        if self.result_type != XPathResultConstants::ANY_UNORDERED_NODE_TYPE &&
+            self.result_type != XPathResultConstants::FIRST_ORDERED_NODE_TYPE
+        {
+            return Err(wrong_type_error());
+        }
+        Ok(self.nodes().first().map(|node| DomRoot::from_ref(&**node)))
+    }
+
+    // https://dom.spec.whatwg.org/#dom-xpathresult-invaliditeratorstate
+    fn InvalidIteratorState(&self) -> bool {
+        let version = self
+            .document
+            .upcast::<Node>()
+            .inclusive_descendants_version();
+        self.is_iterator() && version != self.document_version
+    }
+
+    // https://dom.spec.whatwg.org/#dom-xpathresult-snapshotlength
+    fn GetSnapshotLength(&self) -> Fallible<u32> {
+        if !self.is_snapshot() {
+            return Err(wrong_type_error());
+        }
+        Ok(self.nodes().len() as u32)
+    }
+
+    // https://dom.spec.whatwg.org/#dom-xpathresult-iteratenext
+    fn IterateNext(&self) -> Fallible<Option<DomRoot<Node>>> {
+        if !self.is_iterator() {
+            return Err(wrong_type_error());
+        }
+        if self.InvalidIteratorState() {
+            return Err(Error::InvalidState);
+        }
+        let index = self.iterator_index.get();
+        let node = self
+            .nodes()
+            .get(index)
+            .map(|node| DomRoot::from_ref(&**node));
+        if node.is_some() {
+            self.iterator_index.set(index + 1);
+        }
+        Ok(node)
+    }
+
+    // https://dom.spec.whatwg.org/#dom-xpathresult-snapshotitem
+    fn SnapshotItem(&self, index: u32) -> Fallible<Option<DomRoot<Node>>> {
+        if !self.is_snapshot() {
+            return Err(wrong_type_error());
+        }
+        Ok(self
+            .nodes()
+            .get(index as usize)
+            .map(|node| DomRoot::from_ref(&**node)))
+    }
+}
@@ -24,7 +24,6 @@ use crate::dom::window::Window;
 use crate::dom::xrsession::XRSession;
 use crate::dom::xrtest::XRTest;
 use crate::realms::InRealm;
-use crate::script_thread::ScriptThread;
 use crate::task_source::TaskSource;
 use dom_struct::dom_struct;
 use ipc_channel::ipc::{self as ipc_crate, IpcReceiver, IpcSender};
@@ -180,7 +179,7 @@ impl XRSystemMethods for XRSystem {
         let promise = Promise::new_in_current_realm(&global, comp);
 
         if mode != XRSessionMode::Inline {
-            if !ScriptThread::is_user_interacting() {
+            if !window.has_transient_activation() {
                 promise.reject_error(Error::Security);
                 return promise;
             }
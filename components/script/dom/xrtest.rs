@@ -16,7 +16,6 @@ use crate::dom::bindings::root::{Dom, DomRoot};
 use crate::dom::fakexrdevice::{get_origin, get_views, FakeXRDevice};
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::promise::Promise;
-use crate::script_thread::ScriptThread;
 use crate::task_source::TaskSource;
 use dom_struct::dom_struct;
 use ipc_channel::ipc::IpcSender;
@@ -152,9 +151,11 @@ impl XRTestMethods for XRTest {
 
     /// https://github.com/immersive-web/webxr-test-api/blob/master/explainer.md
     fn SimulateUserActivation(&self, f: Rc<Function>) {
-        ScriptThread::set_user_interacting(true);
+        let global = self.global();
+        let window = global.as_window();
+        window.notify_activation();
         let _ = f.Call__(vec![], ExceptionHandling::Rethrow);
-        ScriptThread::set_user_interacting(false);
+        window.consume_user_activation();
     }
 
     /// https://github.com/immersive-web/webxr-test-api/blob/master/explainer.md
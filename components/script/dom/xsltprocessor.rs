@@ -0,0 +1,278 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::document_loader::DocumentLoader;
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::DocumentBinding::DocumentMethods;
+use crate::dom::bindings::codegen::Bindings::NodeBinding::NodeMethods;
+use crate::dom::bindings::codegen::Bindings::XSLTProcessorBinding::XSLTProcessorMethods;
+use crate::dom::bindings::codegen::UnionTypes::UnrestrictedDoubleOrBooleanOrStringOrNode as XSLTParameterValue;
+use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::document::{Document, DocumentSource, HasBrowsingContext, IsHTMLDocument};
+use crate::dom::documentfragment::DocumentFragment;
+use crate::dom::element::{CustomElementCreationMode, Element, ElementCreator};
+use crate::dom::node::Node;
+use crate::dom::text::Text;
+use crate::dom::window::Window;
+use crate::xpath::{self, Value};
+use crate::xslt::{self, OutputMethod, Stylesheet};
+use dom_struct::dom_struct;
+use html5ever::{LocalName, Namespace, Prefix, QualName};
+use script_traits::DocumentActivity;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+/// The namespace of the element that contains the result of transformations into text, in
+/// the documents that `transformToDocument()` creates.
+const TRANSFORMIIX_NAMESPACE: &str = "http://www.mozilla.org/TransforMiix";
+
+#[derive(JSTraceable, MallocSizeOf)]
+#[unrooted_must_root_lint::must_root]
+enum Parameter {
+    Number(f64),
+    Boolean(bool),
+    String(DOMString),
+    Node(Dom<Node>),
+}
+
+/// <https://dom.spec.whatwg.org/#interface-xsltprocessor>
+#[dom_struct]
+pub struct XSLTProcessor {
+    reflector_: Reflector,
+    window: Dom<Window>,
+    /// The stylesheet that was imported, as compiled then.
+    #[ignore_malloc_size_of = "Defined in xslt"]
+    stylesheet: DomRefCell<Option<Rc<Stylesheet>>>,
+    /// The values of the top-level parameters of the stylesheet, by expanded name.
+    parameters: DomRefCell<HashMap<(Namespace, LocalName), Parameter>>,
+}
+
+impl XSLTProcessor {
+    fn new_inherited(window: &Window) -> XSLTProcessor {
+        XSLTProcessor {
+            reflector_: Reflector::new(),
+            window: Dom::from_ref(window),
+            stylesheet: DomRefCell::new(None),
+            parameters: DomRefCell::new(HashMap::new()),
+        }
+    }
+
+    pub fn new(window: &Window) -> DomRoot<XSLTProcessor> {
+        reflect_dom_object(Box::new(XSLTProcessor::new_inherited(window)), window)
+    }
+
+    // https://dom.spec.whatwg.org/#dom-xsltprocessor-xsltprocessor
+    #[allow(non_snake_case)]
+    pub fn Constructor(window: &Window) -> DomRoot<XSLTProcessor> {
+        XSLTProcessor::new(window)
+    }
+
+    /// Transforms a node with the imported stylesheet, into a fragment owned by `document`.
+    fn transform(
+        &self,
+        source: &Node,
+        document: &Document,
+        html: bool,
+    ) -> Fallible<DomRoot<DocumentFragment>> {
+        // The stylesheet can be replaced by the scripts that run as the result tree is
+        // built, so it isn't borrowed while it is executed.
+        let stylesheet = self
+            .stylesheet
+            .borrow()
+            .clone()
+            .ok_or(Error::InvalidState)?;
+        let parameters = self
+            .parameters
+            .borrow()
+            .iter()
+            .map(|(name, parameter)| {
+                let value = match *parameter {
+                    Parameter::Number(number) => Value::Number(number),
+                    Parameter::Boolean(boolean) => Value::Boolean(boolean),
+                    Parameter::String(ref string) => Value::String(string.to_string()),
+                    Parameter::Node(ref node) => Value::NodeSet(vec![DomRoot::from_ref(&**node)]),
+                };
+                (name.clone(), value)
+            })
+            .collect();
+        xslt::transform(&stylesheet, source, &parameters, document, html)
+    }
+
+    fn output_method(&self) -> Option<OutputMethod> {
+        self.stylesheet
+            .borrow()
+            .as_ref()
+            .and_then(|stylesheet| stylesheet.output_method)
+    }
+
+    /// Creates the document of the result of `transformToDocument()`.
+    fn create_document(&self, source: &Node, method: OutputMethod) -> DomRoot<Document> {
+        let source_document = source.owner_doc();
+        let (is_html_document, content_type) = match method {
+            OutputMethod::Html => (IsHTMLDocument::HTMLDocument, "text/html"),
+            _ => (IsHTMLDocument::NonHTMLDocument, "application/xml"),
+        };
+        Document::new(
+            &self.window,
+            HasBrowsingContext::No,
+            Some(source_document.url()),
+            source_document.origin().clone(),
+            is_html_document,
+            Some(content_type.parse().unwrap()),
+            None,
+            DocumentActivity::Inactive,
+            DocumentSource::NotFromParser,
+            DocumentLoader::new(&*source_document.loader()),
+            None,
+            None,
+            Default::default(),
+        )
+    }
+}
+
+impl XSLTProcessorMethods for XSLTProcessor {
+    // https://dom.spec.whatwg.org/#dom-xsltprocessor-importstylesheet
+    fn ImportStylesheet(&self, style: &Node) -> Fallible<()> {
+        if !style.is::<Document>() && !style.is::<Element>() {
+            return Err(Error::Type(
+                "The stylesheet must be a document or an element".to_owned(),
+            ));
+        }
+        let stylesheet = xslt::compile(style)?;
+        *self.stylesheet.borrow_mut() = Some(Rc::new(stylesheet));
+        Ok(())
+    }
+
+    // https://dom.spec.whatwg.org/#dom-xsltprocessor-transformtofragment
+    fn TransformToFragment(
+        &self,
+        source: &Node,
+        output: &Document,
+    ) -> Fallible<DomRoot<DocumentFragment>> {
+        let method = self.output_method();
+        let html =
+            method == Some(OutputMethod::Html) || (method.is_none() && output.is_html_document());
+        let fragment = self.transform(source, output, html)?;
+        if method == Some(OutputMethod::Text) {
+            let text = Text::new(
+                DOMString::from(xpath::string_value(fragment.upcast())),
+                output,
+            );
+            Node::replace_all(Some(text.upcast()), fragment.upcast());
+        }
+        Ok(fragment)
+    }
+
+    // https://dom.spec.whatwg.org/#dom-xsltprocessor-transformtodocument
+    fn TransformToDocument(&self, source: &Node) -> Fallible<DomRoot<Document>> {
+        let method = self.output_method();
+        let mut document = self.create_document(source, method.unwrap_or(OutputMethod::Xml));
+        let mut fragment = self.transform(source, &document, method == Some(OutputMethod::Html))?;
+
+        // Without an output method, results whose root is an `html` element are HTML
+        // documents, which is only known once they are built.
+        // https://www.w3.org/TR/1999/REC-xslt-19991116#output
+        if method.is_none() {
+            let root = fragment.upcast::<Node>().children().find(|child| {
+                child.is::<Element>() ||
+                    (child.is::<Text>() && !xpath::string_value(child).trim().is_empty())
+            });
+            let is_html = root
+                .as_ref()
+                .and_then(|root| root.downcast::<Element>())
+                .map_or(false, |root| {
+                    *root.namespace() == ns!() && root.local_name().eq_ignore_ascii_case("html")
+                });
+            if is_html {
+                document = self.create_document(source, OutputMethod::Html);
+                fragment = self.transform(source, &document, true)?;
+            }
+        }
+
+        if method == Some(OutputMethod::Text) {
+            let name = QualName::new(
+                Some(Prefix::from("transformiix")),
+                Namespace::from(TRANSFORMIIX_NAMESPACE),
+                LocalName::from("result"),
+            );
+            let root = Element::create(
+                name,
+                None,
+                &document,
+                ElementCreator::ScriptCreated,
+                CustomElementCreationMode::Synchronous,
+            );
+            let text = Text::new(
+                DOMString::from(xpath::string_value(fragment.upcast())),
+                &document,
+            );
+            root.upcast::<Node>().AppendChild(text.upcast())?;
+            document.upcast::<Node>().AppendChild(root.upcast())?;
+            return Ok(document);
+        }
+
+        // Documents can't have text children.
+        for child in fragment.upcast::<Node>().children() {
+            if !child.is::<Text>() {
+                document.upcast::<Node>().AppendChild(&child)?;
+            }
+        }
+        Ok(document)
+    }
+
+    // https://dom.spec.whatwg.org/#dom-xsltprocessor-setparameter
+    fn SetParameter(
+        &self,
+        namespace_uri: DOMString,
+        local_name: DOMString,
+        value: XSLTParameterValue,
+    ) {
+        let parameter = match value {
+            XSLTParameterValue::UnrestrictedDouble(number) => Parameter::Number(number),
+            XSLTParameterValue::Boolean(boolean) => Parameter::Boolean(boolean),
+            XSLTParameterValue::String(string) => Parameter::String(string),
+            XSLTParameterValue::Node(node) => Parameter::Node(Dom::from_ref(&*node)),
+        };
+        let name = (Namespace::from(namespace_uri), LocalName::from(local_name));
+        self.parameters.borrow_mut().insert(name, parameter);
+    }
+
+    // https://dom.spec.whatwg.org/#dom-xsltprocessor-getparameter
+    fn GetParameter(
+        &self,
+        namespace_uri: DOMString,
+        local_name: DOMString,
+    ) -> Option<XSLTParameterValue> {
+        let name = (Namespace::from(namespace_uri), LocalName::from(local_name));
+        let parameters = self.parameters.borrow();
+        let value = match *parameters.get(&name)? {
+            Parameter::Number(number) => XSLTParameterValue::UnrestrictedDouble(number),
+            Parameter::Boolean(boolean) => XSLTParameterValue::Boolean(boolean),
+            Parameter::String(ref string) => XSLTParameterValue::String(string.clone()),
+            Parameter::Node(ref node) => XSLTParameterValue::Node(DomRoot::from_ref(&**node)),
+        };
+        Some(value)
+    }
+
+    // https://dom.spec.whatwg.org/#dom-xsltprocessor-removeparameter
+    fn RemoveParameter(&self, namespace_uri: DOMString, local_name: DOMString) {
+        let name = (Namespace::from(namespace_uri), LocalName::from(local_name));
+        self.parameters.borrow_mut().remove(&name);
+    }
+
+    // https://dom.spec.whatwg.org/#dom-xsltprocessor-clearparameters
+    fn ClearParameters(&self) {
+        self.parameters.borrow_mut().clear();
+    }
+
+    // https://dom.spec.whatwg.org/#dom-xsltprocessor-reset
+    fn Reset(&self) {
+        *self.stylesheet.borrow_mut() = None;
+        self.parameters.borrow_mut().clear();
+    }
+}
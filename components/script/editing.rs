@@ -0,0 +1,1836 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Editing of `contenteditable` regions.
+//!
+//! The caret of an editing host is the document's collapsed `Selection`. Key presses are
+//! turned into DOM mutations around it, bracketed by the `beforeinput` and `input` events
+//! of <https://w3c.github.io/input-events/>.
+
+use crate::dom::bindings::codegen::Bindings::CharacterDataBinding::CharacterDataMethods;
+use crate::dom::bindings::codegen::Bindings::CompositionEventBinding::CompositionEventMethods;
+use crate::dom::bindings::codegen::Bindings::DocumentBinding::DocumentMethods;
+use crate::dom::bindings::codegen::Bindings::EventBinding::EventMethods;
+use crate::dom::bindings::codegen::Bindings::KeyboardEventBinding::KeyboardEventMethods;
+use crate::dom::bindings::codegen::Bindings::NodeBinding::NodeBinding::NodeMethods;
+use crate::dom::bindings::codegen::Bindings::RangeBinding::RangeMethods;
+use crate::dom::bindings::codegen::Bindings::SelectionBinding::SelectionMethods;
+use crate::dom::bindings::codegen::Bindings::TextBinding::TextMethods;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::characterdata::CharacterData;
+use crate::dom::compositionevent::CompositionEvent;
+use crate::dom::document::Document;
+use crate::dom::documentfragment::DocumentFragment;
+use crate::dom::element::{CustomElementCreationMode, Element, ElementCreator};
+use crate::dom::event::{Event, EventStatus};
+use crate::dom::eventtarget::EventTarget;
+use crate::dom::htmlbrelement::HTMLBRElement;
+use crate::dom::htmldivelement::HTMLDivElement;
+use crate::dom::inputevent::InputEvent;
+use crate::dom::keyboardevent::KeyboardEvent;
+use crate::dom::node::{document_from_node, window_from_node, Node, ShadowIncluding};
+use crate::dom::range::Range;
+use crate::dom::selection::Selection;
+use crate::dom::text::Text;
+use crate::textinput::CMD_OR_CONTROL;
+use euclid::default::Point2D;
+use html5ever::{LocalName, QualName};
+use keyboard_types::{Key, KeyState, Modifiers, ShortcutMatcher};
+use script_layout_interface::rpc::TextIndexResponse;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// The kind of change an editing operation makes, as reported by `InputEvent.inputType`.
+/// https://w3c.github.io/input-events/#interface-InputEvent-Attributes
+#[derive(Clone, Copy, Debug, JSTraceable, MallocSizeOf, PartialEq)]
+pub enum InputType {
+    InsertText,
+    InsertReplacementText,
+    InsertCompositionText,
+    InsertLineBreak,
+    InsertParagraph,
+    DeleteContentBackward,
+    DeleteContentForward,
+    FormatBold,
+    FormatItalic,
+    FormatUnderline,
+    HistoryUndo,
+    HistoryRedo,
+}
+
+impl InputType {
+    pub fn as_str(&self) -> &'static str {
+        match *self {
+            InputType::InsertText => "insertText",
+            InputType::InsertReplacementText => "insertReplacementText",
+            InputType::InsertCompositionText => "insertCompositionText",
+            InputType::InsertLineBreak => "insertLineBreak",
+            InputType::InsertParagraph => "insertParagraph",
+            InputType::DeleteContentBackward => "deleteContentBackward",
+            InputType::DeleteContentForward => "deleteContentForward",
+            InputType::FormatBold => "formatBold",
+            InputType::FormatItalic => "formatItalic",
+            InputType::FormatUnderline => "formatUnderline",
+            InputType::HistoryUndo => "historyUndo",
+            InputType::HistoryRedo => "historyRedo",
+        }
+    }
+}
+
+/// The `document.execCommand` commands that are supported.
+/// https://w3c.github.io/editing/docs/execCommand/#commands
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Command {
+    Bold,
+    Italic,
+    Underline,
+    InsertText,
+    InsertParagraph,
+    Undo,
+    Redo,
+}
+
+impl Command {
+    pub fn from_name(name: &str) -> Option<Command> {
+        match &*name.to_ascii_lowercase() {
+            "bold" => Some(Command::Bold),
+            "italic" => Some(Command::Italic),
+            "underline" => Some(Command::Underline),
+            "inserttext" => Some(Command::InsertText),
+            "insertparagraph" => Some(Command::InsertParagraph),
+            "undo" => Some(Command::Undo),
+            "redo" => Some(Command::Redo),
+            _ => None,
+        }
+    }
+
+    fn input_type(&self) -> InputType {
+        match *self {
+            Command::Bold => InputType::FormatBold,
+            Command::Italic => InputType::FormatItalic,
+            Command::Underline => InputType::FormatUnderline,
+            Command::InsertText => InputType::InsertText,
+            Command::InsertParagraph => InputType::InsertParagraph,
+            Command::Undo => InputType::HistoryUndo,
+            Command::Redo => InputType::HistoryRedo,
+        }
+    }
+
+    fn format(&self) -> Option<Format> {
+        match *self {
+            Command::Bold => Some(Format::Bold),
+            Command::Italic => Some(Format::Italic),
+            Command::Underline => Some(Format::Underline),
+            _ => None,
+        }
+    }
+}
+
+/// An inline style that the formatting commands toggle.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Format {
+    Bold,
+    Italic,
+    Underline,
+}
+
+impl Format {
+    fn local_name(&self) -> LocalName {
+        match *self {
+            Format::Bold => local_name!("b"),
+            Format::Italic => local_name!("i"),
+            Format::Underline => local_name!("u"),
+        }
+    }
+
+    fn is_applied_by(&self, element: &Element) -> bool {
+        match (*self, element.local_name()) {
+            (Format::Bold, &local_name!("b")) |
+            (Format::Bold, &local_name!("strong")) |
+            (Format::Italic, &local_name!("i")) |
+            (Format::Italic, &local_name!("em")) |
+            (Format::Underline, &local_name!("u")) => true,
+            _ => false,
+        }
+    }
+}
+
+/// The text an IME is composing, which is shown in the document until it is committed.
+#[derive(JSTraceable, MallocSizeOf)]
+#[unrooted_must_root_lint::must_root]
+pub struct Composition {
+    text: Dom<Text>,
+    /// The UTF-16 offset of the composed text into `text`.
+    start: u32,
+    /// The UTF-16 length of the composed text.
+    length: u32,
+}
+
+/// The most undo steps kept per document.
+const MAX_UNDO_STEPS: usize = 100;
+
+/// The contents of an editing host and the caret inside it, as restored by undo and redo.
+#[derive(JSTraceable, MallocSizeOf)]
+#[unrooted_must_root_lint::must_root]
+struct EditingSnapshot {
+    host: Dom<Node>,
+    contents: Dom<DocumentFragment>,
+    /// The child indices leading from `host` to the caret's node, and the caret's offset.
+    caret: Option<(Vec<u32>, u32)>,
+}
+
+/// The undo and redo stacks shared by the editing hosts of a document.
+#[derive(Default, JSTraceable, MallocSizeOf)]
+#[unrooted_must_root_lint::must_root]
+pub struct EditingHistory {
+    undo: Vec<EditingSnapshot>,
+    redo: Vec<EditingSnapshot>,
+    /// The operation behind the newest undo step, so that consecutive typing is undone at once.
+    last_input_type: Option<InputType>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Direction {
+    Backward,
+    Forward,
+}
+
+/// How far `Selection.modify()` moves a selection's focus.
+/// https://w3c.github.io/selection-api/#dom-selection-modify
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Granularity {
+    Character,
+    Word,
+    ParagraphBoundary,
+    DocumentBoundary,
+}
+
+impl Granularity {
+    pub fn from_name(name: &str) -> Option<Granularity> {
+        match &*name.to_ascii_lowercase() {
+            "character" => Some(Granularity::Character),
+            "word" => Some(Granularity::Word),
+            "paragraphboundary" => Some(Granularity::ParagraphBoundary),
+            "documentboundary" => Some(Granularity::DocumentBoundary),
+            _ => None,
+        }
+    }
+}
+
+/// What a key press inside an editing host asks for.
+enum EditAction {
+    Input(InputType, Option<DOMString>),
+    MoveCaret(Direction, bool),
+}
+
+/// Performs the default action of a `keydown` event dispatched to the editing host `host`.
+/// Returns whether the key was consumed by the editor.
+pub fn handle_keydown(host: &Element, event: &KeyboardEvent) -> bool {
+    // Keys that drive an IME are handled by it, and reach us as composition events.
+    if event.IsComposing() {
+        return false;
+    }
+
+    let key = event.key();
+    let mut mods = event.modifiers();
+    let extend = mods.contains(Modifiers::SHIFT);
+    mods.remove(Modifiers::SHIFT);
+
+    let action = ShortcutMatcher::new(KeyState::Down, key.clone(), mods)
+        .shortcut(Modifiers::empty(), Key::Enter, || {
+            let input_type = if extend {
+                InputType::InsertLineBreak
+            } else {
+                InputType::InsertParagraph
+            };
+            Some(EditAction::Input(input_type, None))
+        })
+        .shortcut(Modifiers::empty(), Key::Backspace, || {
+            Some(EditAction::Input(InputType::DeleteContentBackward, None))
+        })
+        .shortcut(Modifiers::empty(), Key::Delete, || {
+            Some(EditAction::Input(InputType::DeleteContentForward, None))
+        })
+        .shortcut(Modifiers::empty(), Key::ArrowLeft, || {
+            Some(EditAction::MoveCaret(Direction::Backward, extend))
+        })
+        .shortcut(Modifiers::empty(), Key::ArrowRight, || {
+            Some(EditAction::MoveCaret(Direction::Forward, extend))
+        })
+        .shortcut(CMD_OR_CONTROL, 'Z', || {
+            let input_type = if extend {
+                InputType::HistoryRedo
+            } else {
+                InputType::HistoryUndo
+            };
+            Some(EditAction::Input(input_type, None))
+        })
+        .shortcut(CMD_OR_CONTROL, 'Y', || {
+            Some(EditAction::Input(InputType::HistoryRedo, None))
+        })
+        .shortcut(CMD_OR_CONTROL, 'B', || {
+            Some(EditAction::Input(InputType::FormatBold, None))
+        })
+        .shortcut(CMD_OR_CONTROL, 'I', || {
+            Some(EditAction::Input(InputType::FormatItalic, None))
+        })
+        .shortcut(CMD_OR_CONTROL, 'U', || {
+            Some(EditAction::Input(InputType::FormatUnderline, None))
+        })
+        .otherwise(|| match key {
+            Key::Character(ref c) if !mods.intersects(Modifiers::CONTROL | Modifiers::META) => {
+                Some(EditAction::Input(
+                    InputType::InsertText,
+                    Some(DOMString::from(c.as_str())),
+                ))
+            },
+            _ => None,
+        })
+        .unwrap();
+    let action = match action {
+        Some(action) => action,
+        None => return false,
+    };
+
+    let document = document_from_node(host);
+    let selection = match document.GetSelection() {
+        Some(selection) => selection,
+        None => return false,
+    };
+    let host = host.upcast::<Node>();
+    ensure_caret_in_host(host, &selection);
+
+    match action {
+        EditAction::Input(input_type, data) => {
+            perform_edit(host, &selection, input_type, data);
+        },
+        EditAction::MoveCaret(direction, extend) => move_caret(host, &selection, direction, extend),
+    }
+    document.update_editing_caret();
+    true
+}
+
+/// Focuses the editing host `host` after a click on `target`, one of its inclusive
+/// descendants, and moves the caret under the pointer.
+pub fn handle_click(host: &Element, target: &Node, point_in_target: Option<Point2D<f32>>) {
+    let document = document_from_node(host);
+    document.request_focus(host);
+    end_undo_coalescing(&document);
+    let selection = match document.GetSelection() {
+        Some(selection) => selection,
+        None => return,
+    };
+    if !fire_selectstart(target) {
+        return;
+    }
+
+    if let (Some(text), Some(point)) = (target.downcast::<Text>(), point_in_target) {
+        let TextIndexResponse(index) = window_from_node(target).text_index_query(target, point);
+        if let Some(index) = index {
+            let offset = utf8_to_utf16_offset(&text.upcast::<CharacterData>().data(), index);
+            let _ = selection.Collapse(Some(target), offset);
+            document.update_editing_caret();
+            return;
+        }
+    }
+
+    if !selection_is_in(host.upcast(), &selection) {
+        let (node, offset) = last_caret_position(target);
+        let _ = selection.Collapse(Some(&node), offset);
+    }
+    document.update_editing_caret();
+}
+
+/// Replaces the text between two UTF-16 offsets of `text`, in the editing host `host`, as
+/// picking the correction of a misspelled word does.
+pub fn replace_text(host: &Element, text: &Text, start: u32, end: u32, data: DOMString) -> bool {
+    let document = document_from_node(host);
+    let selection = match document.GetSelection() {
+        Some(selection) => selection,
+        None => return false,
+    };
+    end_undo_coalescing(&document);
+    if selection
+        .SetBaseAndExtent(text.upcast(), start, text.upcast(), end)
+        .is_err()
+    {
+        return false;
+    }
+    let changed = perform_edit(
+        host.upcast(),
+        &selection,
+        InputType::InsertReplacementText,
+        Some(data),
+    );
+    document.update_editing_caret();
+    changed
+}
+
+/// The text node holding a collapsed selection inside the focused editing host, and
+/// the caret's UTF-8 offset into its data.
+pub fn focused_caret(document: &Document) -> Option<(DomRoot<Text>, usize)> {
+    let focused = document.get_focused_element()?;
+    let host = focused.upcast::<Node>().editing_host()?;
+    if host != focused {
+        return None;
+    }
+    let range = document.GetSelection()?.GetRangeAt(0).ok()?;
+    if !range.Collapsed() {
+        return None;
+    }
+    let node = range.StartContainer();
+    if !host.upcast::<Node>().is_inclusive_ancestor_of(&node) {
+        return None;
+    }
+    let text = DomRoot::downcast::<Text>(node)?;
+    let offset = utf16_to_utf8_offset(&text.upcast::<CharacterData>().data(), range.StartOffset());
+    Some((text, offset))
+}
+
+/// Runs an editing operation between its `beforeinput` and `input` events.
+/// https://w3c.github.io/input-events/#event-order-during-editing
+pub fn perform_edit(
+    host: &Node,
+    selection: &Selection,
+    input_type: InputType,
+    data: Option<DOMString>,
+) -> bool {
+    let status = fire_input_event(host, "beforeinput", input_type, &data, false);
+    if let EventStatus::Canceled = status {
+        return false;
+    }
+
+    let changed = apply_edit(host, selection, input_type, data.as_ref());
+    if changed {
+        fire_input_event(host, "input", input_type, &data, false);
+    }
+    changed
+}
+
+/// Shows the text of a composition event in the editing host `host`, and commits it
+/// once the composition ends.
+/// https://w3c.github.io/uievents/#events-composition-input-events
+#[allow(unrooted_must_root)]
+pub fn handle_composition(host: &Element, event: &CompositionEvent) {
+    let document = document_from_node(host);
+    let selection = match document.GetSelection() {
+        Some(selection) => selection,
+        None => return,
+    };
+    let host = host.upcast::<Node>();
+    let data = Some(event.Data());
+    let type_ = event.upcast::<Event>().type_();
+
+    if type_ == atom!("compositionstart") {
+        *document.editing_composition().borrow_mut() = None;
+        ensure_caret_in_host(host, &selection);
+        if !selection.IsCollapsed() {
+            perform_edit(host, &selection, InputType::DeleteContentBackward, None);
+        }
+    } else if type_ == atom!("compositionupdate") {
+        fire_input_event(
+            host,
+            "beforeinput",
+            InputType::InsertCompositionText,
+            &data,
+            true,
+        );
+        if replace_composition(host, &selection, event.Data()) {
+            fire_input_event(host, "input", InputType::InsertCompositionText, &data, true);
+        }
+    } else if type_ == atom!("compositionend") {
+        // Take the composed text out again, so that committing it is a single undo step.
+        replace_composition(host, &selection, DOMString::new());
+        *document.editing_composition().borrow_mut() = None;
+        let committed = with_undo_step(host, &selection, InputType::InsertCompositionText, || {
+            insert_text(host, &selection, data.as_ref().map_or("", |data| &**data))
+        });
+        if committed {
+            fire_input_event(
+                host,
+                "input",
+                InputType::InsertCompositionText,
+                &data,
+                false,
+            );
+        }
+    }
+    document.update_editing_caret();
+}
+
+/// Replaces the text being composed in `host` with `data`, starting a composition at
+/// the caret if there is none.
+#[allow(unrooted_must_root)]
+fn replace_composition(host: &Node, selection: &Selection, data: DOMString) -> bool {
+    let document = document_from_node(host);
+    let length = data.encode_utf16().count() as u32;
+    let current = document
+        .editing_composition()
+        .borrow()
+        .as_ref()
+        .map(|composition| {
+            (
+                DomRoot::from_ref(&*composition.text),
+                composition.start,
+                composition.length,
+            )
+        });
+
+    let (text, start) = match current {
+        Some((text, start, old_length)) if host.is_inclusive_ancestor_of(text.upcast()) => {
+            let characterdata = text.upcast::<CharacterData>();
+            if characterdata.ReplaceData(start, old_length, data).is_err() {
+                return false;
+            }
+            let _ = selection.Collapse(Some(text.upcast()), start + length);
+            (text, start)
+        },
+        _ => {
+            if length == 0 || !insert_text(host, selection, &data) {
+                return false;
+            }
+            let text = match caret(selection).and_then(|(node, _)| DomRoot::downcast::<Text>(node))
+            {
+                Some(text) => text,
+                None => return false,
+            };
+            let start = selection.FocusOffset() - length;
+            (text, start)
+        },
+    };
+    *document.editing_composition().borrow_mut() = Some(Composition {
+        text: Dom::from_ref(&*text),
+        start,
+        length,
+    });
+    true
+}
+
+/// Applies an editing operation to the DOM, recording an undo step for it.
+fn apply_edit(
+    host: &Node,
+    selection: &Selection,
+    input_type: InputType,
+    data: Option<&DOMString>,
+) -> bool {
+    let document = document_from_node(host);
+    let text = data.map_or("", |data| &**data);
+    match input_type {
+        InputType::HistoryUndo => undo(&document, selection),
+        InputType::HistoryRedo => redo(&document, selection),
+        InputType::InsertText |
+        InputType::InsertReplacementText |
+        InputType::InsertCompositionText => with_undo_step(host, selection, input_type, || {
+            insert_text(host, selection, text)
+        }),
+        InputType::InsertLineBreak => with_undo_step(host, selection, input_type, || {
+            insert_line_break(host, selection)
+        }),
+        InputType::InsertParagraph => with_undo_step(host, selection, input_type, || {
+            insert_paragraph(host, selection)
+        }),
+        InputType::DeleteContentBackward => with_undo_step(host, selection, input_type, || {
+            delete(host, selection, Direction::Backward)
+        }),
+        InputType::DeleteContentForward => with_undo_step(host, selection, input_type, || {
+            delete(host, selection, Direction::Forward)
+        }),
+        InputType::FormatBold => with_undo_step(host, selection, input_type, || {
+            toggle_format(host, selection, Format::Bold)
+        }),
+        InputType::FormatItalic => with_undo_step(host, selection, input_type, || {
+            toggle_format(host, selection, Format::Italic)
+        }),
+        InputType::FormatUnderline => with_undo_step(host, selection, input_type, || {
+            toggle_format(host, selection, Format::Underline)
+        }),
+    }
+}
+
+/// https://w3c.github.io/editing/docs/execCommand/#execcommand()
+pub fn exec_command(document: &Document, command_id: &str, value: DOMString) -> bool {
+    // Steps 1-2.
+    let command = match Command::from_name(command_id) {
+        Some(command) => command,
+        None => return false,
+    };
+    if !command_enabled(document, command) {
+        return false;
+    }
+    let selection = match document.GetSelection() {
+        Some(selection) => selection,
+        None => return false,
+    };
+
+    let input_type = command.input_type();
+    let data = match command {
+        Command::InsertText => Some(value),
+        _ => None,
+    };
+    let changed = match command {
+        Command::Undo => undo(document, &selection),
+        Command::Redo => redo(document, &selection),
+        _ => match active_editing_host(&selection) {
+            Some(host) => apply_edit(&host, &selection, input_type, data.as_ref()),
+            None => false,
+        },
+    };
+    if changed {
+        if let Some(host) = active_editing_host(&selection) {
+            fire_input_event(&host, "input", input_type, &data, false);
+        }
+        document.update_editing_caret();
+    }
+    true
+}
+
+/// https://w3c.github.io/editing/docs/execCommand/#querycommandenabled()
+pub fn query_command_enabled(document: &Document, command_id: &str) -> bool {
+    Command::from_name(command_id).map_or(false, |command| command_enabled(document, command))
+}
+
+/// https://w3c.github.io/editing/docs/execCommand/#querycommandindeterm()
+pub fn query_command_indeterm(document: &Document, command_id: &str) -> bool {
+    let format = match Command::from_name(command_id).and_then(|command| command.format()) {
+        Some(format) => format,
+        None => return false,
+    };
+    let (host, range) = match active_range(document) {
+        Some(active) => active,
+        None => return false,
+    };
+    let texts = selected_texts(&host, &range);
+    let formatted = texts
+        .iter()
+        .filter(|text| formatting_ancestor(&host, text.upcast(), format).is_some())
+        .count();
+    formatted > 0 && formatted < texts.len()
+}
+
+/// https://w3c.github.io/editing/docs/execCommand/#querycommandstate()
+pub fn query_command_state(document: &Document, command_id: &str) -> bool {
+    let format = match Command::from_name(command_id).and_then(|command| command.format()) {
+        Some(format) => format,
+        None => return false,
+    };
+    let (host, range) = match active_range(document) {
+        Some(active) => active,
+        None => return false,
+    };
+    if range.Collapsed() {
+        return formatting_ancestor(&host, &range.StartContainer(), format).is_some();
+    }
+    let texts = selected_texts(&host, &range);
+    !texts.is_empty() &&
+        texts
+            .iter()
+            .all(|text| formatting_ancestor(&host, text.upcast(), format).is_some())
+}
+
+/// https://w3c.github.io/editing/docs/execCommand/#querycommandsupported()
+pub fn query_command_supported(command_id: &str) -> bool {
+    Command::from_name(command_id).is_some()
+}
+
+/// https://w3c.github.io/editing/docs/execCommand/#querycommandvalue()
+pub fn query_command_value(document: &Document, command_id: &str) -> DOMString {
+    match Command::from_name(command_id).and_then(|command| command.format()) {
+        Some(_) if query_command_state(document, command_id) => DOMString::from("true"),
+        Some(_) => DOMString::from("false"),
+        None => DOMString::new(),
+    }
+}
+
+fn command_enabled(document: &Document, command: Command) -> bool {
+    match command {
+        Command::Undo => !document.editing_history().borrow().undo.is_empty(),
+        Command::Redo => !document.editing_history().borrow().redo.is_empty(),
+        _ => active_range(document).is_some(),
+    }
+}
+
+/// The selection's range and the editing host it starts in.
+fn active_range(document: &Document) -> Option<(DomRoot<Node>, DomRoot<Range>)> {
+    let selection = document.GetSelection()?;
+    let host = active_editing_host(&selection)?;
+    let range = selection.GetRangeAt(0).ok()?;
+    Some((host, range))
+}
+
+fn active_editing_host(selection: &Selection) -> Option<DomRoot<Node>> {
+    let range = selection.GetRangeAt(0).ok()?;
+    let host = range.StartContainer().editing_host()?;
+    Some(DomRoot::upcast(host))
+}
+
+fn fire_input_event(
+    host: &Node,
+    type_: &str,
+    input_type: InputType,
+    data: &Option<DOMString>,
+    is_composing: bool,
+) -> EventStatus {
+    let window = window_from_node(host);
+    // https://w3c.github.io/input-events/#interface-InputEvent-Attributes
+    let cancelable = type_ == "beforeinput" && input_type != InputType::InsertCompositionText;
+    let event = InputEvent::new(
+        &window,
+        DOMString::from(type_),
+        true,
+        cancelable,
+        Some(&window),
+        0,
+        data.clone(),
+        is_composing,
+        DOMString::from(input_type.as_str()),
+    );
+    let status = event.upcast::<Event>().fire(host.upcast());
+    if type_ == "input" {
+        if let Some(host) = host.downcast::<Element>() {
+            document_from_node(host).request_spellcheck(host);
+        }
+    }
+    status
+}
+
+fn insert_text(host: &Node, selection: &Selection, data: &str) -> bool {
+    delete_selection(host, selection);
+    let (node, offset) = match caret(selection) {
+        Some(caret) => caret,
+        None => return false,
+    };
+
+    let (text, offset) = match DomRoot::downcast::<Text>(node.clone()) {
+        Some(text) => (text, offset),
+        None => {
+            // Continue a neighbouring text node, or create one at the caret.
+            let before = offset
+                .checked_sub(1)
+                .and_then(|index| node.children().nth(index as usize))
+                .and_then(DomRoot::downcast::<Text>);
+            let after = node
+                .children()
+                .nth(offset as usize)
+                .and_then(DomRoot::downcast::<Text>);
+            match (before, after) {
+                (Some(text), _) => {
+                    let len = text.upcast::<Node>().len();
+                    (text, len)
+                },
+                (None, Some(text)) => (text, 0),
+                (None, None) => {
+                    let text = Text::new(DOMString::new(), &document_from_node(host));
+                    let child = node.children().nth(offset as usize);
+                    if node.InsertBefore(text.upcast(), child.as_deref()).is_err() {
+                        return false;
+                    }
+                    (text, 0)
+                },
+            }
+        },
+    };
+
+    let characterdata = text.upcast::<CharacterData>();
+    if characterdata
+        .InsertData(offset, DOMString::from(data))
+        .is_err()
+    {
+        return false;
+    }
+    let end = offset + data.encode_utf16().count() as u32;
+    let _ = selection.Collapse(Some(text.upcast()), end);
+    remove_placeholder(host, text.upcast());
+    true
+}
+
+fn insert_line_break(host: &Node, selection: &Selection) -> bool {
+    delete_selection(host, selection);
+    let (node, offset) = match caret(selection) {
+        Some(caret) => caret,
+        None => return false,
+    };
+
+    let (parent, reference) = match node.downcast::<Text>() {
+        Some(text) => {
+            let parent = match node.GetParentNode() {
+                Some(parent) => parent,
+                None => return false,
+            };
+            if offset == 0 {
+                (parent, Some(node.clone()))
+            } else if offset >= node.len() {
+                (parent, node.GetNextSibling())
+            } else {
+                match text.SplitText(offset) {
+                    Ok(rest) => (parent, Some(DomRoot::upcast(rest))),
+                    Err(_) => return false,
+                }
+            }
+        },
+        None => (node.clone(), node.children().nth(offset as usize)),
+    };
+
+    let document = document_from_node(host);
+    let br = HTMLBRElement::new(local_name!("br"), None, &document);
+    let br = br.upcast::<Node>();
+    if parent.InsertBefore(br, reference.as_deref()).is_err() {
+        return false;
+    }
+
+    // A line break that ends its block needs another one after it for the new, empty
+    // line to be rendered.
+    let block = block_of(host, br);
+    let ends_block = match next_leaf(host, &parent, br.index() + 1) {
+        Some(next) => block_of(host, &next) != block,
+        None => true,
+    };
+    if ends_block {
+        let placeholder = HTMLBRElement::new(local_name!("br"), None, &document);
+        let _ = parent.InsertBefore(placeholder.upcast(), br.GetNextSibling().as_deref());
+    }
+
+    let (node, offset) = match reference.as_ref().and_then(|node| node.downcast::<Text>()) {
+        Some(text) => (DomRoot::from_ref(text.upcast::<Node>()), 0),
+        None => caret_after(br),
+    };
+    let _ = selection.Collapse(Some(&node), offset);
+    true
+}
+
+fn insert_paragraph(host: &Node, selection: &Selection) -> bool {
+    delete_selection(host, selection);
+    let (mut node, mut offset) = match caret(selection) {
+        Some(caret) => caret,
+        None => return false,
+    };
+
+    let mut block = block_of(host, &node);
+    if &*block == host {
+        // Inline content directly inside the host gets a paragraph of its own first.
+        let (div, position) = wrap_inline_run(host, &node, offset);
+        block = div;
+        if let Some((div_node, div_offset)) = position {
+            node = div_node;
+            offset = div_offset;
+        }
+    }
+
+    // Everything from the caret to the end of the block moves to a new block after it.
+    let document = document_from_node(host);
+    let range = Range::new(&document, &node, offset, &block, block.len());
+    let contents = match range.ExtractContents() {
+        Ok(contents) => contents,
+        Err(_) => return false,
+    };
+    let new_block = match block.CloneNode(false) {
+        Ok(new_block) => new_block,
+        Err(_) => return false,
+    };
+    if let Some(element) = new_block.downcast::<Element>() {
+        element.remove_attribute(&ns!(), &local_name!("id"));
+    }
+    let parent = match block.GetParentNode() {
+        Some(parent) => parent,
+        None => return false,
+    };
+    if new_block.AppendChild(contents.upcast()).is_err() ||
+        parent
+            .InsertBefore(&new_block, block.GetNextSibling().as_deref())
+            .is_err()
+    {
+        return false;
+    }
+
+    ensure_placeholder(&block);
+    ensure_placeholder(&new_block);
+    let (node, offset) = first_caret_position(&new_block);
+    let _ = selection.Collapse(Some(&node), offset);
+    true
+}
+
+fn delete(host: &Node, selection: &Selection, direction: Direction) -> bool {
+    if delete_selection(host, selection) {
+        return true;
+    }
+    let (node, offset) = match caret(selection) {
+        Some(caret) => caret,
+        None => return false,
+    };
+
+    // Within a text node, remove the adjacent grapheme cluster.
+    if let Some(text) = node.downcast::<Text>() {
+        let data = text.upcast::<CharacterData>().data().to_string();
+        let range = match direction {
+            Direction::Backward if offset > 0 => Some((previous_grapheme(&data, offset), offset)),
+            Direction::Forward if offset < node.len() => {
+                Some((offset, next_grapheme(&data, offset)))
+            },
+            _ => None,
+        };
+        if let Some((start, end)) = range {
+            return delete_text(host, selection, text, start, end);
+        }
+    }
+
+    let block = block_of(host, &node);
+    match direction {
+        Direction::Backward => {
+            let prev = match previous_leaf(host, &node, offset) {
+                Some(prev) => prev,
+                None => return false,
+            };
+            if block_of(host, &prev) != block {
+                let next = match node.downcast::<Text>() {
+                    Some(_) => node.clone(),
+                    None => next_leaf(host, &node, offset)
+                        .filter(|next| block_of(host, next) == block)
+                        .unwrap_or_else(|| block.clone()),
+                };
+                let (node, offset) = join_blocks(host, &prev, &next);
+                let _ = selection.Collapse(Some(&node), offset);
+                return true;
+            }
+            match prev.downcast::<Text>() {
+                Some(text) => {
+                    let data = text.upcast::<CharacterData>().data().to_string();
+                    let end = prev.len();
+                    delete_text(host, selection, text, previous_grapheme(&data, end), end)
+                },
+                None => remove_leaf(selection, &prev, &node, offset),
+            }
+        },
+        Direction::Forward => {
+            let next = match next_leaf(host, &node, offset) {
+                Some(next) => next,
+                None => return false,
+            };
+            if block_of(host, &next) != block {
+                let prev = match node.downcast::<Text>() {
+                    Some(_) => node.clone(),
+                    None => previous_leaf(host, &node, offset)
+                        .filter(|prev| block_of(host, prev) == block)
+                        .unwrap_or_else(|| block.clone()),
+                };
+                let (node, offset) = join_blocks(host, &prev, &next);
+                let _ = selection.Collapse(Some(&node), offset);
+                return true;
+            }
+            match next.downcast::<Text>() {
+                Some(text) => {
+                    let data = text.upcast::<CharacterData>().data().to_string();
+                    delete_text(host, selection, text, 0, next_grapheme(&data, 0))
+                },
+                None => remove_leaf(selection, &next, &node, offset),
+            }
+        },
+    }
+}
+
+/// Removes the UTF-16 range `start..end` from `text` and leaves the caret at `start`.
+fn delete_text(host: &Node, selection: &Selection, text: &Text, start: u32, end: u32) -> bool {
+    if text
+        .upcast::<CharacterData>()
+        .DeleteData(start, end - start)
+        .is_err()
+    {
+        return false;
+    }
+    let _ = selection.Collapse(Some(text.upcast()), start);
+    ensure_placeholder(&block_of(host, text.upcast()));
+    true
+}
+
+/// Removes an element leaf next to the caret at `node`/`offset`.
+fn remove_leaf(selection: &Selection, leaf: &Node, node: &Node, offset: u32) -> bool {
+    let parent = match leaf.GetParentNode() {
+        Some(parent) => parent,
+        None => return false,
+    };
+    let index = leaf.index();
+    leaf.remove_self();
+    if node.is::<Text>() {
+        let _ = selection.Collapse(Some(node), offset);
+    } else {
+        let _ = selection.Collapse(Some(&parent), index);
+    }
+    true
+}
+
+/// Deletes the contents of a non-collapsed selection, joining the blocks at either end.
+/// Returns false if there was nothing selected.
+fn delete_selection(host: &Node, selection: &Selection) -> bool {
+    let range = match selection.GetRangeAt(0) {
+        Ok(range) if !range.Collapsed() => range,
+        _ => return false,
+    };
+    let start_block = block_of(host, &range.StartContainer());
+    let end_block = block_of(host, &range.EndContainer());
+    if range.DeleteContents().is_err() {
+        return false;
+    }
+
+    let (node, offset) = (range.StartContainer(), range.StartOffset());
+    let _ = selection.Collapse(Some(&node), offset);
+    if start_block != end_block && end_block.is_connected() {
+        let prev = match node.downcast::<Text>() {
+            Some(_) if offset > 0 => Some(node.clone()),
+            _ => previous_leaf(host, &node, offset),
+        };
+        let next = next_leaf(host, &node, offset);
+        if let (Some(prev), Some(next)) = (prev, next) {
+            if block_of(host, &next) == end_block {
+                let (node, offset) = join_blocks(host, &prev, &next);
+                let _ = selection.Collapse(Some(&node), offset);
+            }
+        }
+    }
+    ensure_placeholder(&start_block);
+    true
+}
+
+/// Moves the line of inline content holding `next` up to the end of the block holding
+/// `prev`, removing whatever is left empty. Returns the caret position at the join.
+fn join_blocks(host: &Node, prev: &Node, next: &Node) -> (DomRoot<Node>, u32) {
+    let prev_block = block_of(host, prev);
+    let next_block = block_of(host, next);
+
+    // The moved content goes right after the outermost inline ancestor of `prev`.
+    let (parent, reference) = if *prev_block == *prev {
+        (prev_block.clone(), None)
+    } else {
+        let anchor = inline_anchor(&prev_block, prev);
+        (prev_block.clone(), anchor.GetNextSibling())
+    };
+
+    // The content to move runs up to the next block or line break.
+    let mut moved = vec![];
+    let mut line_break = None;
+    if *next_block != *next {
+        let anchor = inline_anchor(&next_block, next);
+        for sibling in anchor.inclusively_following_siblings() {
+            if is_block(&sibling) {
+                break;
+            }
+            if sibling.is::<HTMLBRElement>() {
+                line_break = Some(sibling);
+                break;
+            }
+            moved.push(sibling);
+        }
+    }
+    if let Some(line_break) = line_break {
+        line_break.remove_self();
+    }
+
+    // A line break ending the previous block only kept an empty line open.
+    let prev_text_len = prev.downcast::<Text>().map(|_| prev.len());
+    if prev.is::<HTMLBRElement>() {
+        prev.remove_self();
+    }
+
+    for node in &moved {
+        let _ = parent.InsertBefore(node, reference.as_deref());
+    }
+
+    let mut empty = Some(next_block);
+    while let Some(node) = empty {
+        if &*node == host || has_leaves(&node) || node.is_inclusive_ancestor_of(&parent) {
+            break;
+        }
+        empty = node.GetParentNode();
+        node.remove_self();
+    }
+
+    if let Some(len) = prev_text_len {
+        return (DomRoot::from_ref(prev), len);
+    }
+    match moved.first() {
+        Some(first) => first_caret_position(first),
+        None => last_caret_position(&parent),
+    }
+}
+
+/// Wraps the run of inline children of `host` around the caret in a new `div`, returning
+/// it along with the caret's new position when that was given relative to `host`.
+fn wrap_inline_run(
+    host: &Node,
+    node: &Node,
+    offset: u32,
+) -> (DomRoot<Node>, Option<(DomRoot<Node>, u32)>) {
+    let (before, after) = if node == host {
+        (
+            offset
+                .checked_sub(1)
+                .and_then(|index| host.children().nth(index as usize)),
+            host.children().nth(offset as usize),
+        )
+    } else {
+        let anchor = inline_anchor(host, node);
+        let after = anchor.GetNextSibling();
+        (Some(anchor), after)
+    };
+
+    let mut run: Vec<DomRoot<Node>> = before
+        .map(|before| {
+            before
+                .inclusively_preceding_siblings()
+                .take_while(|sibling| !is_block(sibling))
+                .collect()
+        })
+        .unwrap_or_default();
+    run.reverse();
+    let caret_offset = run.len() as u32;
+    if let Some(ref after) = after {
+        run.extend(
+            after
+                .inclusively_following_siblings()
+                .take_while(|sibling| !is_block(sibling)),
+        );
+    }
+
+    let reference = match run.last() {
+        Some(last) => last.GetNextSibling(),
+        None => after,
+    };
+    let document = document_from_node(host);
+    let div = HTMLDivElement::new(local_name!("div"), None, &document);
+    let div = DomRoot::upcast::<Node>(div);
+    let _ = host.InsertBefore(&div, reference.as_deref());
+    for child in &run {
+        let _ = div.AppendChild(child);
+    }
+
+    let position = if node == host {
+        Some((div.clone(), caret_offset))
+    } else {
+        None
+    };
+    (div, position)
+}
+
+fn move_caret(host: &Node, selection: &Selection, direction: Direction, extend: bool) {
+    end_undo_coalescing(&document_from_node(host));
+    let range = match selection.GetRangeAt(0) {
+        Ok(range) => range,
+        Err(_) => return,
+    };
+    if !extend && !range.Collapsed() {
+        let (node, offset) = match direction {
+            Direction::Backward => (range.StartContainer(), range.StartOffset()),
+            Direction::Forward => (range.EndContainer(), range.EndOffset()),
+        };
+        let _ = selection.Collapse(Some(&node), offset);
+        return;
+    }
+
+    let node = match selection.GetFocusNode() {
+        Some(node) => node,
+        None => return,
+    };
+    if extend && range.Collapsed() && !fire_selectstart(&node) {
+        return;
+    }
+    let (node, offset) = match adjacent_position(host, &node, selection.FocusOffset(), direction) {
+        Some(position) => position,
+        None => return,
+    };
+    if extend {
+        let _ = selection.Extend(&node, offset);
+    } else {
+        let _ = selection.Collapse(Some(&node), offset);
+    }
+}
+
+/// The caret position one grapheme cluster or line boundary away from `node`/`offset`.
+fn adjacent_position(
+    host: &Node,
+    node: &Node,
+    offset: u32,
+    direction: Direction,
+) -> Option<(DomRoot<Node>, u32)> {
+    if let Some(text) = node.downcast::<Text>() {
+        let data = text.upcast::<CharacterData>().data().to_string();
+        match direction {
+            Direction::Backward if offset > 0 => {
+                return Some((DomRoot::from_ref(node), previous_grapheme(&data, offset)));
+            },
+            Direction::Forward if offset < node.len() => {
+                return Some((DomRoot::from_ref(node), next_grapheme(&data, offset)));
+            },
+            _ => {},
+        }
+    }
+
+    let block = block_of(host, node);
+    match direction {
+        Direction::Backward => {
+            let prev = previous_leaf(host, node, offset)?;
+            if block_of(host, &prev) != block {
+                // The end of the previous line.
+                return Some(if prev.is::<HTMLBRElement>() {
+                    caret_before(&prev)
+                } else {
+                    caret_after(&prev)
+                });
+            }
+            match prev.downcast::<Text>() {
+                Some(text) => {
+                    let data = text.upcast::<CharacterData>().data().to_string();
+                    let offset = previous_grapheme(&data, prev.len());
+                    Some((prev, offset))
+                },
+                None => Some(caret_before(&prev)),
+            }
+        },
+        Direction::Forward => {
+            let next = next_leaf(host, node, offset)?;
+            if block_of(host, &next) != block {
+                return Some(caret_before(&next));
+            }
+            match next.downcast::<Text>() {
+                Some(text) => {
+                    let data = text.upcast::<CharacterData>().data().to_string();
+                    let offset = next_grapheme(&data, 0);
+                    Some((next, offset))
+                },
+                None => Some(caret_after(&next)),
+            }
+        },
+    }
+}
+
+/// The caret position that `Selection.modify()` moves `node`/`offset` to, staying
+/// inside `root`.
+pub fn modified_position(
+    root: &Node,
+    node: &Node,
+    offset: u32,
+    direction: Direction,
+    granularity: Granularity,
+) -> Option<(DomRoot<Node>, u32)> {
+    match granularity {
+        Granularity::Character => adjacent_position(root, node, offset, direction),
+        Granularity::Word => word_position(root, node, offset, direction),
+        Granularity::ParagraphBoundary => {
+            let block = block_of(root, node);
+            Some(match direction {
+                Direction::Backward => first_caret_position(&block),
+                Direction::Forward => last_caret_position(&block),
+            })
+        },
+        Granularity::DocumentBoundary => Some(match direction {
+            Direction::Backward => first_caret_position(root),
+            Direction::Forward => last_caret_position(root),
+        }),
+    }
+}
+
+/// The caret position past the next word in `direction`, crossing into following
+/// text nodes until one is found.
+fn word_position(
+    root: &Node,
+    node: &Node,
+    offset: u32,
+    direction: Direction,
+) -> Option<(DomRoot<Node>, u32)> {
+    let (mut node, mut offset) = (DomRoot::from_ref(node), offset);
+    let mut moved = false;
+    loop {
+        if let Some(text) = node.downcast::<Text>() {
+            let data = text.upcast::<CharacterData>().data().to_string();
+            if let Some(boundary) = word_boundary(&data, offset, direction) {
+                return Some((node, boundary));
+            }
+            offset = match direction {
+                Direction::Backward => 0,
+                Direction::Forward => node.len(),
+            };
+            moved = true;
+        }
+
+        let leaf = match direction {
+            Direction::Backward => previous_leaf(root, &node, offset),
+            Direction::Forward => next_leaf(root, &node, offset),
+        };
+        match leaf {
+            Some(ref leaf) if leaf.is::<Text>() => {
+                offset = match direction {
+                    Direction::Backward => leaf.len(),
+                    Direction::Forward => 0,
+                };
+                node = leaf.clone();
+            },
+            // Other leaves, like line breaks, end the word.
+            Some(leaf) => {
+                return Some(match direction {
+                    Direction::Backward => caret_before(&leaf),
+                    Direction::Forward => caret_after(&leaf),
+                });
+            },
+            None if moved => return Some((node, offset)),
+            None => return None,
+        }
+    }
+}
+
+/// The UTF-16 offset just past the next word of `text` from `offset` in `direction`.
+fn word_boundary(text: &str, offset: u32, direction: Direction) -> Option<u32> {
+    let byte = utf16_to_utf8_offset(text, offset);
+    let is_word = |segment: &str| segment.chars().any(char::is_alphanumeric);
+    let boundary = match direction {
+        Direction::Backward => text[..byte]
+            .split_word_bound_indices()
+            .rev()
+            .find(|&(_, segment)| is_word(segment))
+            .map(|(index, _)| index),
+        Direction::Forward => text[byte..]
+            .split_word_bound_indices()
+            .find(|&(_, segment)| is_word(segment))
+            .map(|(index, segment)| byte + index + segment.len()),
+    }?;
+    Some(utf8_to_utf16_offset(text, boundary))
+}
+
+/// Fires `selectstart` at `target` before the user starts a new selection, and returns
+/// whether the selection may go ahead.
+/// https://w3c.github.io/selection-api/#selectstart-event
+fn fire_selectstart(target: &Node) -> bool {
+    let event = target
+        .upcast::<EventTarget>()
+        .fire_bubbling_cancelable_event(atom!("selectstart"));
+    !event.DefaultPrevented()
+}
+
+/// Wraps the text of the selection in `format`, or unwraps it if all of it already has it.
+fn toggle_format(host: &Node, selection: &Selection, format: Format) -> bool {
+    let texts = split_selected_texts(host, selection);
+    let (first, last) = match (texts.first(), texts.last()) {
+        (Some(first), Some(last)) => (first.clone(), last.clone()),
+        _ => return false,
+    };
+    let applied = texts
+        .iter()
+        .all(|text| formatting_ancestor(host, text.upcast(), format).is_some());
+
+    for text in &texts {
+        let node = text.upcast::<Node>();
+        if applied {
+            while let Some(ancestor) = formatting_ancestor(host, node, format) {
+                hoist_out_of(&ancestor, node);
+            }
+        } else if formatting_ancestor(host, node, format).is_none() {
+            wrap_in_format(node, format);
+        }
+    }
+
+    let last = last.upcast::<Node>();
+    let _ = selection.SetBaseAndExtent(first.upcast(), 0, last, last.len());
+    true
+}
+
+/// The outermost ancestor of `node` inside `host` that applies `format`.
+fn formatting_ancestor(host: &Node, node: &Node, format: Format) -> Option<DomRoot<Node>> {
+    node.ancestors()
+        .take_while(|ancestor| &**ancestor != host)
+        .filter(|ancestor| {
+            ancestor
+                .downcast::<Element>()
+                .map_or(false, |element| format.is_applied_by(element))
+        })
+        .last()
+}
+
+fn wrap_in_format(node: &Node, format: Format) {
+    let parent = match node.GetParentNode() {
+        Some(parent) => parent,
+        None => return,
+    };
+    // Extend a wrapper right before the node rather than starting another one.
+    if let Some(previous) = node.GetPreviousSibling() {
+        let is_wrapper = previous
+            .downcast::<Element>()
+            .map_or(false, |element| format.is_applied_by(element));
+        if is_wrapper {
+            let _ = previous.AppendChild(node);
+            return;
+        }
+    }
+    let wrapper = Element::create(
+        QualName::new(None, ns!(html), format.local_name()),
+        None,
+        &document_from_node(node),
+        ElementCreator::ScriptCreated,
+        CustomElementCreationMode::Synchronous,
+    );
+    let _ = parent.InsertBefore(wrapper.upcast(), Some(node));
+    let _ = wrapper.upcast::<Node>().AppendChild(node);
+}
+
+/// Splits `container` around its child holding `node`, leaving that child next to it.
+fn hoist_out_of(container: &Node, node: &Node) {
+    let parent = match container.GetParentNode() {
+        Some(parent) => parent,
+        None => return,
+    };
+    let anchor = inline_anchor(container, node);
+    let following: Vec<_> = anchor.following_siblings().collect();
+    if !following.is_empty() {
+        if let Ok(rest) = container.CloneNode(false) {
+            let _ = parent.InsertBefore(&rest, container.GetNextSibling().as_deref());
+            for sibling in following {
+                let _ = rest.AppendChild(&sibling);
+            }
+        }
+    }
+    let _ = parent.InsertBefore(&anchor, container.GetNextSibling().as_deref());
+    if container.GetFirstChild().is_none() {
+        container.remove_self();
+    }
+}
+
+/// Splits the text nodes at the ends of the selection so that the text it covers is held
+/// by whole nodes, and returns those.
+fn split_selected_texts(host: &Node, selection: &Selection) -> Vec<DomRoot<Text>> {
+    let range = match selection.GetRangeAt(0) {
+        Ok(range) if !range.Collapsed() => range,
+        _ => return vec![],
+    };
+    let (mut start, mut start_offset) = (range.StartContainer(), range.StartOffset());
+    let (mut end, mut end_offset) = (range.EndContainer(), range.EndOffset());
+    if !host.is_inclusive_ancestor_of(&start) || !host.is_inclusive_ancestor_of(&end) {
+        return vec![];
+    }
+
+    // Split the end first, so that the start offset stays valid if both are in one node.
+    if let Some(text) = end.downcast::<Text>() {
+        if end_offset > 0 && end_offset < end.len() {
+            let _ = text.SplitText(end_offset);
+        }
+    }
+    if let Some(text) = start.downcast::<Text>() {
+        if start_offset > 0 && start_offset < start.len() {
+            if let Ok(rest) = text.SplitText(start_offset) {
+                let rest = DomRoot::upcast::<Node>(rest);
+                if start == end {
+                    end = rest.clone();
+                    end_offset -= start_offset;
+                }
+                start = rest;
+                start_offset = 0;
+            }
+        }
+    }
+
+    let range = Range::new(
+        &document_from_node(host),
+        &start,
+        start_offset,
+        &end,
+        end_offset,
+    );
+    selected_texts(host, &range)
+}
+
+/// The text nodes inside `host` that `range` covers some of.
+fn selected_texts(host: &Node, range: &Range) -> Vec<DomRoot<Text>> {
+    let (start, start_offset) = (range.StartContainer(), range.StartOffset());
+    let (end, end_offset) = (range.EndContainer(), range.EndOffset());
+    leaves(host)
+        .into_iter()
+        .filter(|node| {
+            range.IntersectsNode(node) &&
+                !(*node == start && start_offset == node.len()) &&
+                !(*node == end && end_offset == 0)
+        })
+        .filter_map(DomRoot::downcast::<Text>)
+        .collect()
+}
+
+/// Runs `edit`, first saving the state of `host` for undo unless it continues the
+/// previous step.
+#[allow(unrooted_must_root)]
+fn with_undo_step<F>(host: &Node, selection: &Selection, input_type: InputType, edit: F) -> bool
+where
+    F: FnOnce() -> bool,
+{
+    let document = document_from_node(host);
+    let pushed = {
+        let mut history = document.editing_history().borrow_mut();
+        let coalesce = input_type == InputType::InsertText &&
+            history.last_input_type == Some(InputType::InsertText) &&
+            history
+                .undo
+                .last()
+                .map_or(false, |step| &*step.host == host);
+        history.last_input_type = Some(input_type);
+        if !coalesce {
+            history.undo.push(take_snapshot(host, selection));
+            if history.undo.len() > MAX_UNDO_STEPS {
+                history.undo.remove(0);
+            }
+        }
+        !coalesce
+    };
+
+    let changed = edit();
+    let mut history = document.editing_history().borrow_mut();
+    if changed {
+        history.redo.clear();
+    } else if pushed {
+        history.undo.pop();
+        history.last_input_type = None;
+    }
+    changed
+}
+
+fn end_undo_coalescing(document: &Document) {
+    document.editing_history().borrow_mut().last_input_type = None;
+}
+
+#[allow(unrooted_must_root)]
+fn undo(document: &Document, selection: &Selection) -> bool {
+    let (host, contents, caret) = {
+        let mut history = document.editing_history().borrow_mut();
+        let step = match history.undo.pop() {
+            Some(step) => step,
+            None => return false,
+        };
+        history.redo.push(take_snapshot(&step.host, selection));
+        history.last_input_type = None;
+        (
+            DomRoot::from_ref(&*step.host),
+            DomRoot::from_ref(&*step.contents),
+            step.caret,
+        )
+    };
+    restore_snapshot(&host, &contents, caret, selection);
+    true
+}
+
+#[allow(unrooted_must_root)]
+fn redo(document: &Document, selection: &Selection) -> bool {
+    let (host, contents, caret) = {
+        let mut history = document.editing_history().borrow_mut();
+        let step = match history.redo.pop() {
+            Some(step) => step,
+            None => return false,
+        };
+        history.undo.push(take_snapshot(&step.host, selection));
+        history.last_input_type = None;
+        (
+            DomRoot::from_ref(&*step.host),
+            DomRoot::from_ref(&*step.contents),
+            step.caret,
+        )
+    };
+    restore_snapshot(&host, &contents, caret, selection);
+    true
+}
+
+#[allow(unrooted_must_root)]
+fn take_snapshot(host: &Node, selection: &Selection) -> EditingSnapshot {
+    let contents = DocumentFragment::new(&document_from_node(host));
+    for child in host.children() {
+        if let Ok(clone) = child.CloneNode(true) {
+            let _ = contents.upcast::<Node>().AppendChild(&clone);
+        }
+    }
+    let caret = caret(selection)
+        .filter(|&(ref node, _)| host.is_inclusive_ancestor_of(node))
+        .map(|(node, offset)| {
+            let mut path: Vec<u32> = node
+                .inclusive_ancestors(ShadowIncluding::No)
+                .take_while(|ancestor| &**ancestor != host)
+                .map(|ancestor| ancestor.index())
+                .collect();
+            path.reverse();
+            (path, offset)
+        });
+    EditingSnapshot {
+        host: Dom::from_ref(host),
+        contents: Dom::from_ref(&*contents),
+        caret,
+    }
+}
+
+fn restore_snapshot(
+    host: &Node,
+    contents: &DocumentFragment,
+    caret: Option<(Vec<u32>, u32)>,
+    selection: &Selection,
+) {
+    Node::replace_all(Some(contents.upcast()), host);
+
+    let (node, offset) = match caret {
+        Some((path, offset)) => {
+            let node = path
+                .iter()
+                .fold(Some(DomRoot::from_ref(host)), |node, &index| {
+                    node.and_then(|node| node.children().nth(index as usize))
+                });
+            match node {
+                Some(node) => {
+                    let offset = offset.min(node.len());
+                    (node, offset)
+                },
+                None => first_caret_position(host),
+            }
+        },
+        None => first_caret_position(host),
+    };
+    let _ = selection.Collapse(Some(&node), offset);
+}
+
+/// Moves a selection that is missing or reaches outside `host` to the start of it.
+fn ensure_caret_in_host(host: &Node, selection: &Selection) {
+    if !selection_is_in(host, selection) {
+        let (node, offset) = first_caret_position(host);
+        let _ = selection.Collapse(Some(&node), offset);
+    }
+}
+
+fn selection_is_in(host: &Node, selection: &Selection) -> bool {
+    match selection.GetRangeAt(0) {
+        Ok(range) => {
+            host.is_inclusive_ancestor_of(&range.StartContainer()) &&
+                host.is_inclusive_ancestor_of(&range.EndContainer())
+        },
+        Err(_) => false,
+    }
+}
+
+fn caret(selection: &Selection) -> Option<(DomRoot<Node>, u32)> {
+    let range = selection.GetRangeAt(0).ok()?;
+    Some((range.StartContainer(), range.StartOffset()))
+}
+
+fn caret_before(leaf: &Node) -> (DomRoot<Node>, u32) {
+    if leaf.is::<Text>() {
+        return (DomRoot::from_ref(leaf), 0);
+    }
+    match leaf.GetParentNode() {
+        Some(parent) => (parent, leaf.index()),
+        None => (DomRoot::from_ref(leaf), 0),
+    }
+}
+
+fn caret_after(leaf: &Node) -> (DomRoot<Node>, u32) {
+    if leaf.is::<Text>() {
+        return (DomRoot::from_ref(leaf), leaf.len());
+    }
+    match leaf.GetParentNode() {
+        Some(parent) => (parent, leaf.index() + 1),
+        None => (DomRoot::from_ref(leaf), 0),
+    }
+}
+
+fn first_caret_position(node: &Node) -> (DomRoot<Node>, u32) {
+    match leaves(node).into_iter().next() {
+        Some(leaf) => caret_before(&leaf),
+        None => (DomRoot::from_ref(node), 0),
+    }
+}
+
+fn last_caret_position(node: &Node) -> (DomRoot<Node>, u32) {
+    match leaves(node).pop() {
+        // A trailing line break does not start a line of its own.
+        Some(ref leaf) if leaf.is::<HTMLBRElement>() => caret_before(leaf),
+        Some(leaf) => caret_after(&leaf),
+        None => (DomRoot::from_ref(node), node.len()),
+    }
+}
+
+/// Gives a block left without content a `<br>`, so that it keeps its line.
+fn ensure_placeholder(block: &Node) {
+    if !block.is_connected() || block.is::<Text>() || has_leaves(block) {
+        return;
+    }
+    let br = HTMLBRElement::new(local_name!("br"), None, &document_from_node(block));
+    let _ = block.AppendChild(br.upcast());
+}
+
+/// Drops the placeholder `<br>` of a block once `text` gives it content.
+fn remove_placeholder(host: &Node, text: &Node) {
+    let leaves = leaves(&block_of(host, text));
+    if let [ref first, ref placeholder] = leaves[..] {
+        if &**first == text && placeholder.is::<HTMLBRElement>() {
+            placeholder.remove_self();
+        }
+    }
+}
+
+/// The nearest block-level inclusive ancestor of `node` inside `host`, or `host` itself.
+fn block_of(host: &Node, node: &Node) -> DomRoot<Node> {
+    node.inclusive_ancestors(ShadowIncluding::No)
+        .take_while(|ancestor| &**ancestor != host)
+        .find(|ancestor| is_block(ancestor))
+        .unwrap_or_else(|| DomRoot::from_ref(host))
+}
+
+/// The inclusive ancestor of `node` that is a child of `container`.
+fn inline_anchor(container: &Node, node: &Node) -> DomRoot<Node> {
+    node.inclusive_ancestors(ShadowIncluding::No)
+        .find(|ancestor| ancestor.GetParentNode().as_deref() == Some(container))
+        .unwrap_or_else(|| DomRoot::from_ref(node))
+}
+
+fn is_block(node: &Node) -> bool {
+    let element = match node.downcast::<Element>() {
+        Some(element) => element,
+        None => return false,
+    };
+    match *element.local_name() {
+        local_name!("address") |
+        local_name!("article") |
+        local_name!("aside") |
+        local_name!("blockquote") |
+        local_name!("dd") |
+        local_name!("div") |
+        local_name!("dl") |
+        local_name!("dt") |
+        local_name!("fieldset") |
+        local_name!("figcaption") |
+        local_name!("figure") |
+        local_name!("footer") |
+        local_name!("form") |
+        local_name!("h1") |
+        local_name!("h2") |
+        local_name!("h3") |
+        local_name!("h4") |
+        local_name!("h5") |
+        local_name!("h6") |
+        local_name!("header") |
+        local_name!("hr") |
+        local_name!("li") |
+        local_name!("main") |
+        local_name!("nav") |
+        local_name!("ol") |
+        local_name!("p") |
+        local_name!("pre") |
+        local_name!("section") |
+        local_name!("table") |
+        local_name!("td") |
+        local_name!("th") |
+        local_name!("tr") |
+        local_name!("ul") => true,
+        _ => false,
+    }
+}
+
+/// Whether the caret can sit next to `node`: non-empty text, line breaks, replaced
+/// content and empty blocks.
+fn is_leaf(node: &Node) -> bool {
+    if let Some(text) = node.downcast::<Text>() {
+        let data = text.upcast::<CharacterData>().data();
+        // Whitespace spanning lines is source formatting rather than content.
+        return !data.is_empty() &&
+            !(data.contains('\n') && data.chars().all(|c| c.is_ascii_whitespace()));
+    }
+    let element = match node.downcast::<Element>() {
+        Some(element) => element,
+        None => return false,
+    };
+    if node.GetFirstChild().is_some() {
+        return false;
+    }
+    if is_block(node) {
+        return true;
+    }
+    match *element.local_name() {
+        local_name!("br") |
+        local_name!("img") |
+        local_name!("input") |
+        local_name!("textarea") |
+        local_name!("select") |
+        local_name!("button") |
+        local_name!("iframe") |
+        local_name!("canvas") |
+        local_name!("video") |
+        local_name!("audio") |
+        local_name!("embed") |
+        local_name!("object") => true,
+        _ => false,
+    }
+}
+
+/// The leaves below `root`, in tree order.
+fn leaves(root: &Node) -> Vec<DomRoot<Node>> {
+    root.traverse_preorder(ShadowIncluding::No)
+        .filter(|node| &**node != root && is_leaf(node))
+        .collect()
+}
+
+fn has_leaves(root: &Node) -> bool {
+    root.traverse_preorder(ShadowIncluding::No)
+        .any(|node| &*node != root && is_leaf(&node))
+}
+
+/// Whether `leaf` lies entirely before the position `node`/`offset`.
+fn precedes_position(leaf: &Node, node: &Node, offset: u32) -> bool {
+    if leaf == node {
+        return false;
+    }
+    if node.is::<CharacterData>() {
+        return leaf.is_before(node);
+    }
+    match node.children().nth(offset as usize) {
+        Some(child) => leaf.is_before(&child),
+        None => node.is_inclusive_ancestor_of(leaf) || leaf.is_before(node),
+    }
+}
+
+fn previous_leaf(host: &Node, node: &Node, offset: u32) -> Option<DomRoot<Node>> {
+    leaves(host)
+        .into_iter()
+        .rev()
+        .find(|leaf| precedes_position(leaf, node, offset))
+}
+
+fn next_leaf(host: &Node, node: &Node, offset: u32) -> Option<DomRoot<Node>> {
+    leaves(host)
+        .into_iter()
+        .find(|leaf| &**leaf != node && !precedes_position(leaf, node, offset))
+}
+
+/// The UTF-16 offset of the grapheme cluster boundary before `offset` in `text`.
+fn previous_grapheme(text: &str, offset: u32) -> u32 {
+    let byte = utf16_to_utf8_offset(text, offset);
+    let previous = text[..byte]
+        .grapheme_indices(true)
+        .next_back()
+        .map_or(0, |(index, _)| index);
+    utf8_to_utf16_offset(text, previous)
+}
+
+/// The UTF-16 offset of the grapheme cluster boundary after `offset` in `text`.
+fn next_grapheme(text: &str, offset: u32) -> u32 {
+    let byte = utf16_to_utf8_offset(text, offset);
+    let next = text[byte..]
+        .graphemes(true)
+        .next()
+        .map_or(byte, |grapheme| byte + grapheme.len());
+    utf8_to_utf16_offset(text, next)
+}
+
+/// Converts an offset in UTF-16 code units, as used by the DOM, to a byte offset.
+pub fn utf16_to_utf8_offset(text: &str, offset: u32) -> usize {
+    let mut units = 0;
+    for (index, c) in text.char_indices() {
+        if units >= offset as usize {
+            return index;
+        }
+        units += c.len_utf16();
+    }
+    text.len()
+}
+
+/// Converts a byte offset, as used by layout, to an offset in UTF-16 code units.
+pub fn utf8_to_utf16_offset(text: &str, offset: usize) -> u32 {
+    let mut offset = offset.min(text.len());
+    while !text.is_char_boundary(offset) {
+        offset -= 1;
+    }
+    text[..offset].encode_utf16().count() as u32
+}
@@ -43,7 +43,6 @@ use std::sync::{Arc, Mutex};
 struct FetchContext {
     fetch_promise: Option<TrustedPromise>,
     response_object: Trusted<Response>,
-    body: Vec<u8>,
     resource_timing: ResourceFetchTiming,
 }
 
@@ -106,6 +105,8 @@ fn request_init_from_request(request: NetTraitsRequest) -> RequestBuilder {
         headers: request.headers.clone(),
         unsafe_request: request.unsafe_request,
         body: request.body.clone(),
+        keep_alive: request.keep_alive,
+        preload: request.preload,
         service_workers_mode: ServiceWorkersMode::All,
         destination: request.destination,
         synchronous: request.synchronous,
@@ -127,6 +128,7 @@ fn request_init_from_request(request: NetTraitsRequest) -> RequestBuilder {
         url_list: vec![],
         parser_metadata: request.parser_metadata,
         initiator: request.initiator,
+        priority: request.priority,
         csp_list: None,
     }
 }
@@ -171,7 +173,6 @@ pub fn Fetch(
     let fetch_context = Arc::new(Mutex::new(FetchContext {
         fetch_promise: Some(TrustedPromise::new(promise.clone())),
         response_object: Trusted::new(&*response),
-        body: vec![],
         resource_timing: ResourceFetchTiming::new(timing_type),
     }));
     let listener = NetworkListener {
@@ -254,20 +255,22 @@ impl FetchResponseListener for FetchContext {
                 },
             },
         }
+        self.response_object.root().start_receiving_body();
+
         // Step 4.3
         promise.resolve_native(&self.response_object.root());
         self.fetch_promise = Some(TrustedPromise::new(promise));
     }
 
-    fn process_response_chunk(&mut self, mut chunk: Vec<u8>) {
+    fn process_response_chunk(&mut self, chunk: Vec<u8>) {
         self.response_object.root().stream_chunk(chunk.as_slice());
-        self.body.append(&mut chunk);
     }
 
-    fn process_response_eof(&mut self, _response: Result<ResourceFetchTiming, NetworkError>) {
+    fn process_response_eof(&mut self, response: Result<ResourceFetchTiming, NetworkError>) {
+        let result = response.map(|_| ());
         let response = self.response_object.root();
         let _ac = enter_realm(&*response);
-        response.finish(mem::replace(&mut self.body, vec![]));
+        response.finish(result);
         // TODO
         // ... trailerObject is not supported in Servo yet.
     }
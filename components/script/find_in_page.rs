@@ -0,0 +1,256 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Searching the rendered text of a document on behalf of the embedder.
+//!
+//! Matches are found per text node in tree order, descending into same-origin iframes, and
+//! are highlighted by layout in the same way as a selection.
+
+use crate::dom::bindings::codegen::Bindings::NodeBinding::NodeMethods;
+use crate::dom::bindings::codegen::Bindings::WindowBinding::ScrollBehavior;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::root::{Dom, DomRoot, LayoutDom};
+use crate::dom::characterdata::{CharacterData, LayoutCharacterDataHelpers};
+use crate::dom::document::Document;
+use crate::dom::htmliframeelement::HTMLIFrameElement;
+use crate::dom::node::{document_from_node, window_from_node, Node, NodeDamage, ShadowIncluding};
+use crate::dom::text::Text;
+use crate::script_thread::ScriptThread;
+use embedder_traits::{EmbedderMsg, FindOptions};
+use std::ops::Range;
+
+/// A match of the find-in-page query, as a UTF-8 byte range of a single text node.
+#[derive(Clone, JSTraceable, MallocSizeOf)]
+#[unrooted_must_root_lint::must_root]
+pub struct FindMatch {
+    text: Dom<Text>,
+    start: usize,
+    end: usize,
+}
+
+impl FindMatch {
+    /// The highlighted range of `text`, if this match is in it and still fits its data.
+    #[allow(unsafe_code)]
+    pub unsafe fn range_for_layout(&self, text: LayoutDom<Text>) -> Option<Range<usize>> {
+        if self.text.to_layout() != text {
+            return None;
+        }
+        let data = text.upcast::<CharacterData>().data_for_layout();
+        if !data.is_char_boundary(self.start) || !data.is_char_boundary(self.end) {
+            return None;
+        }
+        Some(self.start..self.end)
+    }
+}
+
+/// The find-in-page search of a top-level document.
+#[derive(JSTraceable, MallocSizeOf)]
+#[unrooted_must_root_lint::must_root]
+pub struct FindState {
+    query: String,
+    case_sensitive: bool,
+    matches: Vec<FindMatch>,
+    /// The index into `matches` of the match that was scrolled to.
+    active: Option<usize>,
+}
+
+/// Searches `document` and its same-origin iframes for `query`, and reports the result to
+/// the embedder. Repeating the previous search moves on to the next (or previous) match.
+#[allow(unrooted_must_root)]
+pub fn find(document: &Document, query: String, options: FindOptions) {
+    let mut state = document.find_state().borrow_mut();
+    let previous = match state.take() {
+        Some(previous) => {
+            clear_highlights(&previous);
+            if previous.query == query && previous.case_sensitive == options.case_sensitive {
+                previous.active
+            } else {
+                None
+            }
+        },
+        None => None,
+    };
+
+    // The document may have changed since the last search, so it is always searched again.
+    let mut matches = vec![];
+    if !query.is_empty() {
+        let query: Vec<char> = query.chars().collect();
+        search_document(
+            document,
+            document,
+            &query,
+            options.case_sensitive,
+            &mut matches,
+        );
+    }
+    let count = matches.len();
+    let active = match (count, previous) {
+        (0, _) => None,
+        (_, Some(previous)) if options.backwards => Some((previous + count - 1) % count),
+        (_, Some(previous)) => Some((previous + 1) % count),
+        (_, None) if options.backwards => Some(count - 1),
+        (_, None) => Some(0),
+    };
+
+    let new_state = FindState {
+        query,
+        case_sensitive: options.case_sensitive,
+        matches,
+        active,
+    };
+    set_highlights(&new_state);
+    if let Some(active) = active {
+        scroll_into_view(new_state.matches[active].text.upcast());
+    }
+    *state = Some(new_state);
+
+    document
+        .window()
+        .send_to_embedder(EmbedderMsg::FindInPageResult(active, count));
+}
+
+/// Removes the highlights of the search of `document`, if there is one.
+#[allow(unrooted_must_root)]
+pub fn stop_finding(document: &Document) {
+    if let Some(state) = document.find_state().borrow_mut().take() {
+        clear_highlights(&state);
+    }
+}
+
+/// Appends the matches in the rendered text of `document` to `matches`, descending into the
+/// iframes whose document is same origin with `top_document`.
+#[allow(unrooted_must_root)]
+fn search_document(
+    top_document: &Document,
+    document: &Document,
+    query: &[char],
+    case_sensitive: bool,
+    matches: &mut Vec<FindMatch>,
+) {
+    for node in document
+        .upcast::<Node>()
+        .traverse_preorder(ShadowIncluding::No)
+    {
+        if let Some(iframe) = node.downcast::<HTMLIFrameElement>() {
+            let content_document = iframe.pipeline_id().and_then(ScriptThread::find_document);
+            if let Some(content_document) = content_document {
+                if top_document
+                    .origin()
+                    .same_origin_domain(content_document.origin())
+                {
+                    search_document(
+                        top_document,
+                        &content_document,
+                        query,
+                        case_sensitive,
+                        matches,
+                    );
+                }
+            }
+            continue;
+        }
+        let text = match node.downcast::<Text>() {
+            Some(text) => text,
+            None => continue,
+        };
+        let found = find_in_text(
+            &text.upcast::<CharacterData>().data(),
+            query,
+            case_sensitive,
+        );
+        // Only text that layout produced boxes for is rendered, and inert text can't be found.
+        if found.is_empty() ||
+            node.bounding_content_box().is_none() ||
+            node
+                .GetParentElement()
+                .map_or(false, |parent| parent.is_inert())
+        {
+            continue;
+        }
+        matches.extend(found.into_iter().map(|(start, end)| FindMatch {
+            text: Dom::from_ref(text),
+            start,
+            end,
+        }));
+    }
+}
+
+/// The UTF-8 byte ranges of the non-overlapping occurrences of `query` in `data`.
+fn find_in_text(data: &str, query: &[char], case_sensitive: bool) -> Vec<(usize, usize)> {
+    let chars: Vec<(usize, char)> = data.char_indices().collect();
+    let mut found = vec![];
+    let mut index = 0;
+    while index + query.len() <= chars.len() {
+        let is_match = query
+            .iter()
+            .zip(&chars[index..])
+            .all(|(&q, &(_, c))| chars_match(q, c, case_sensitive));
+        if !is_match {
+            index += 1;
+            continue;
+        }
+        let end = chars
+            .get(index + query.len())
+            .map_or(data.len(), |&(offset, _)| offset);
+        found.push((chars[index].0, end));
+        index += query.len();
+    }
+    found
+}
+
+fn chars_match(a: char, b: char, case_sensitive: bool) -> bool {
+    a == b || (!case_sensitive && a.to_lowercase().eq(b.to_lowercase()))
+}
+
+/// Hands the matches of `state` to the documents they are in, for layout to paint. Layout
+/// highlights a single range per text node, so the active match wins over the others.
+#[allow(unrooted_must_root)]
+fn set_highlights(state: &FindState) {
+    for (index, find_match) in state.matches.iter().enumerate() {
+        let document = document_from_node(&*find_match.text);
+        let mut highlights = document.find_highlights().borrow_mut();
+        let existing = highlights
+            .iter()
+            .position(|highlight| highlight.text == find_match.text);
+        match existing {
+            Some(existing) if state.active == Some(index) => {
+                highlights[existing] = find_match.clone();
+            },
+            Some(_) => continue,
+            None => highlights.push(find_match.clone()),
+        }
+        find_match
+            .text
+            .upcast::<Node>()
+            .dirty(NodeDamage::OtherNodeDamage);
+    }
+}
+
+fn clear_highlights(state: &FindState) {
+    for find_match in &state.matches {
+        let document = document_from_node(&*find_match.text);
+        document.find_highlights().borrow_mut().clear();
+        find_match
+            .text
+            .upcast::<Node>()
+            .dirty(NodeDamage::OtherNodeDamage);
+    }
+}
+
+/// Scrolls the viewport of `node` and of the frames around it until `node` is visible.
+fn scroll_into_view(node: &Node) {
+    let rect = node.bounding_content_box_or_zero();
+    let window = window_from_node(node);
+    let viewport = window.current_viewport();
+    if !viewport.contains_rect(&rect) {
+        // Center the match in the viewport.
+        let x = rect.origin.x + (rect.size.width - viewport.size.width) / 2;
+        let y = rect.origin.y + (rect.size.height - viewport.size.height) / 2;
+        window.scroll(x.to_f64_px(), y.to_f64_px(), ScrollBehavior::Auto);
+    }
+    let frame_element = window.window_proxy().frame_element().map(DomRoot::from_ref);
+    if let Some(frame_element) = frame_element {
+        scroll_into_view(frame_element.upcast());
+    }
+}
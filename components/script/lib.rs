@@ -54,10 +54,16 @@ extern crate style;
 #[macro_use]
 mod task;
 #[warn(deprecated)]
+mod accessibility;
+#[warn(deprecated)]
+mod autofill;
+#[warn(deprecated)]
 mod body;
 #[warn(deprecated)]
 pub mod clipboard_provider;
 #[warn(deprecated)]
+mod context_menu;
+#[warn(deprecated)]
 mod devtools;
 #[warn(deprecated)]
 pub mod document_loader;
@@ -66,10 +72,14 @@ pub mod document_loader;
 mod dom;
 #[warn(deprecated)]
 mod canvas_state;
+#[warn(deprecated)]
+mod editing;
 mod euclidext;
 #[warn(deprecated)]
 pub mod fetch;
 #[warn(deprecated)]
+mod find_in_page;
+#[warn(deprecated)]
 mod image_listener;
 #[warn(deprecated)]
 mod init;
@@ -82,6 +92,10 @@ mod microtask;
 #[warn(deprecated)]
 mod network_listener;
 #[warn(deprecated)]
+mod password_manager;
+#[warn(deprecated)]
+mod reader_mode;
+#[warn(deprecated)]
 mod realms;
 #[warn(deprecated)]
 mod script_module;
@@ -93,6 +107,10 @@ pub mod script_thread;
 #[warn(deprecated)]
 mod serviceworker_manager;
 #[warn(deprecated)]
+mod speculation_rules;
+#[warn(deprecated)]
+mod spellcheck;
+#[warn(deprecated)]
 mod serviceworkerjob;
 #[warn(deprecated)]
 mod stylesheet_loader;
@@ -114,6 +132,10 @@ mod timers;
 mod unpremultiplytable;
 #[warn(deprecated)]
 mod webdriver_handlers;
+#[warn(deprecated)]
+mod xpath;
+#[warn(deprecated)]
+mod xslt;
 
 pub use init::{init, init_service_workers};
 pub use script_runtime::JSEngineSetup;
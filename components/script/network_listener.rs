@@ -39,11 +39,6 @@ pub fn submit_timing<T: ResourceTimingListener + FetchResponseListener>(listener
     }
 
     let (initiator_type, url) = listener.resource_timing_information();
-    if initiator_type == InitiatorType::Other {
-        warn!("Ignoring InitiatorType::Other resource {:?}", url);
-        return;
-    }
-
     submit_timing_data(
         &listener.resource_timing_global(),
         url,
@@ -59,7 +54,7 @@ pub fn submit_timing_data(
     resource_timing: &ResourceFetchTiming,
 ) {
     let performance_entry =
-        PerformanceResourceTiming::new(global, url, initiator_type, None, resource_timing);
+        PerformanceResourceTiming::new(global, url, initiator_type, resource_timing);
     global
         .performance()
         .queue_entry(performance_entry.upcast::<PerformanceEntry>());
@@ -0,0 +1,159 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! The integration with the password manager of the embedder.
+//!
+//! The embedder is told when a top-level document gets a password field, so that it can fill
+//! in the credentials saved for the origin of the document, and when a form with a password is
+//! submitted, so that it can offer to save them. The username field of a password field is the
+//! field of its form marked with `autocomplete="username"`, or else the last text field before
+//! it. Passwords are only sent to the embedder if a preference allows it.
+
+use crate::autofill;
+use crate::dom::bindings::codegen::Bindings::HTMLInputElementBinding::HTMLInputElementMethods;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::document::Document;
+use crate::dom::element::Element;
+use crate::dom::htmlformelement::{FormControl, HTMLFormElement};
+use crate::dom::htmlinputelement::{HTMLInputElement, InputType};
+use crate::dom::node::{document_from_node, Node, ShadowIncluding};
+use embedder_traits::{Credentials, EmbedderMsg, LoginSubmission};
+use html5ever::LocalName;
+use servo_url::ServoUrl;
+
+/// Whether the `autocomplete` attribute of `input` ends with `token`.
+fn has_autocomplete_token(input: &HTMLInputElement, token: &str) -> bool {
+    input
+        .upcast::<Element>()
+        .get_string_attribute(&LocalName::from("autocomplete"))
+        .split_ascii_whitespace()
+        .last()
+        .map_or(false, |last| last.eq_ignore_ascii_case(token))
+}
+
+/// The input elements of `document` whose form owner is `form`, in tree order.
+fn inputs(document: &Document, form: Option<&HTMLFormElement>) -> Vec<DomRoot<HTMLInputElement>> {
+    document
+        .upcast::<Node>()
+        .traverse_preorder(ShadowIncluding::No)
+        .filter_map(DomRoot::downcast::<HTMLInputElement>)
+        .filter(|input| input.form_owner().as_deref() == form)
+        .collect()
+}
+
+/// The username field of `password`, one of `inputs`.
+fn username_field(
+    inputs: &[DomRoot<HTMLInputElement>],
+    password: &HTMLInputElement,
+) -> Option<DomRoot<HTMLInputElement>> {
+    if let Some(input) = inputs
+        .iter()
+        .find(|input| has_autocomplete_token(input, "username"))
+    {
+        return Some(input.clone());
+    }
+    inputs
+        .iter()
+        .take_while(|input| &***input != password)
+        .filter(|input| match input.input_type() {
+            InputType::Email | InputType::Tel | InputType::Text => true,
+            _ => false,
+        })
+        .last()
+        .cloned()
+}
+
+/// Tells the embedder that the document of `input`, a password field that was connected, has a
+/// password field, unless it was told already or the document isn't top-level.
+pub fn password_field_added(input: &HTMLInputElement) {
+    let document = document_from_node(input);
+    let window = document.window();
+    if !window.is_top_level() || document.note_password_field() {
+        return;
+    }
+    let origin = document.origin().immutable().ascii_serialization();
+    window.send_to_embedder(EmbedderMsg::PasswordFieldFound(origin));
+}
+
+/// Tells the embedder about the credentials of `form`, which is being submitted to `action`,
+/// if it has a password that isn't empty.
+pub fn form_submitted(form: &HTMLFormElement, action: &ServoUrl) {
+    let document = document_from_node(form);
+    let inputs = inputs(&document, Some(form));
+    let passwords: Vec<_> = inputs
+        .iter()
+        .filter(|input| input.input_type() == InputType::Password && !input.Value().is_empty())
+        .collect();
+    let first_password = match passwords.first() {
+        Some(password) => password,
+        None => return,
+    };
+    // Forms to sign up or to change the password have several password fields, the last of
+    // which has the new password.
+    let new_password = passwords.len() > 1 ||
+        passwords
+            .iter()
+            .any(|input| has_autocomplete_token(input, "new-password"));
+    let password = if new_password {
+        passwords.last().unwrap()
+    } else {
+        first_password
+    };
+    let username = username_field(&inputs, first_password)
+        .map(|input| String::from(input.Value()))
+        .filter(|username| !username.is_empty());
+    let password = if pref!(dom.password_manager.include_passwords) {
+        Some(String::from(password.Value()))
+    } else {
+        None
+    };
+    let submission = LoginSubmission {
+        origin: document.origin().immutable().ascii_serialization(),
+        action_origin: action.origin().ascii_serialization(),
+        username,
+        password,
+        new_password,
+    };
+    document
+        .window()
+        .send_to_embedder(EmbedderMsg::LoginSubmitted(submission));
+}
+
+/// Fills in the username and the password of the first login form of `document`, except for
+/// the fields that aren't empty. Fields for new passwords are left alone. Nothing is filled in
+/// unless the document is of the origin that the credentials were saved for, since it may have
+/// navigated since the embedder chose them.
+pub fn fill_credentials(document: &Document, credentials: Credentials) {
+    let Credentials {
+        origin,
+        username,
+        password,
+    } = credentials;
+    if document.origin().immutable().ascii_serialization() != origin {
+        return warn!("Credentials for {} not filled in another origin.", origin);
+    }
+    let password_field = document
+        .upcast::<Node>()
+        .traverse_preorder(ShadowIncluding::No)
+        .filter_map(DomRoot::downcast::<HTMLInputElement>)
+        .find(|input| {
+            input.input_type() == InputType::Password &&
+                !has_autocomplete_token(input, "new-password")
+        });
+    let password_field = match password_field {
+        Some(password_field) => password_field,
+        None => return,
+    };
+    let inputs = inputs(document, password_field.form_owner().as_deref());
+    if let Some(username_field) = username_field(&inputs, &password_field) {
+        if username_field.Value().is_empty() {
+            autofill::fill(username_field.upcast(), DOMString::from(username));
+        }
+    }
+    if password_field.Value().is_empty() {
+        autofill::fill(password_field.upcast(), DOMString::from(password));
+    }
+}
@@ -0,0 +1,521 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Extraction of the main article of a document, for embedders that offer a reader view.
+//!
+//! This is a simplified version of the scoring of Mozilla's Readability library: paragraphs
+//! award points to their parent and grandparent, the element with the best score after
+//! accounting for the density of links in it is taken to be the article, and its content is
+//! rebuilt as simple HTML without scripts, forms, navigation and other boilerplate.
+
+use crate::dom::bindings::codegen::Bindings::DocumentBinding::DocumentMethods;
+use crate::dom::bindings::codegen::Bindings::NodeBinding::NodeMethods;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::characterdata::CharacterData;
+use crate::dom::document::Document;
+use crate::dom::element::Element;
+use crate::dom::node::{Node, ShadowIncluding};
+use crate::dom::text::Text;
+use embedder_traits::ReaderArticle;
+use html5ever::LocalName;
+use servo_url::ServoUrl;
+use std::fmt::Write;
+
+/// Documents whose article has less text than this many characters do not get a reader view.
+const MIN_ARTICLE_LENGTH: usize = 140;
+
+/// Paragraphs with less text than this do not add to the score of their ancestors.
+const MIN_PARAGRAPH_LENGTH: usize = 25;
+
+/// Elements whose text is scored as a paragraph.
+static PARAGRAPH_TAGS: &[&str] = &["h2", "h3", "h4", "h5", "h6", "p", "pre", "section", "td"];
+
+/// Elements that are never part of an article, along with all of their content.
+static REMOVED_TAGS: &[&str] = &[
+    "aside", "button", "canvas", "embed", "footer", "form", "iframe", "input", "nav", "noscript",
+    "object", "script", "select", "style", "svg", "template", "textarea",
+];
+
+/// Elements that are kept in the content of the article. Other elements are replaced by their
+/// content, or by a paragraph if they only contain inline content.
+static KEPT_TAGS: &[&str] = &[
+    "a",
+    "abbr",
+    "b",
+    "blockquote",
+    "br",
+    "caption",
+    "cite",
+    "code",
+    "dd",
+    "del",
+    "dl",
+    "dt",
+    "em",
+    "figcaption",
+    "figure",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "hr",
+    "i",
+    "img",
+    "ins",
+    "kbd",
+    "li",
+    "mark",
+    "ol",
+    "p",
+    "pre",
+    "q",
+    "s",
+    "small",
+    "strong",
+    "sub",
+    "sup",
+    "table",
+    "tbody",
+    "td",
+    "tfoot",
+    "th",
+    "thead",
+    "tr",
+    "u",
+    "ul",
+];
+
+/// Elements that start a new block of content.
+static BLOCK_TAGS: &[&str] = &[
+    "article",
+    "blockquote",
+    "div",
+    "dl",
+    "figure",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "hr",
+    "main",
+    "ol",
+    "p",
+    "pre",
+    "section",
+    "table",
+    "ul",
+];
+
+/// Elements without content.
+static VOID_TAGS: &[&str] = &["br", "hr", "img"];
+
+/// Parts of class names and identifiers of elements that are unlikely to be articles.
+static UNLIKELY_NAMES: &[&str] = &[
+    "ad-break",
+    "advert",
+    "banner",
+    "breadcrumb",
+    "combx",
+    "comment",
+    "community",
+    "cookie",
+    "disqus",
+    "extra",
+    "footer",
+    "header",
+    "menu",
+    "modal",
+    "popup",
+    "promo",
+    "related",
+    "remark",
+    "replies",
+    "rss",
+    "share",
+    "shoutbox",
+    "sidebar",
+    "skyscraper",
+    "social",
+    "sponsor",
+    "subscribe",
+];
+
+/// Parts of class names and identifiers that make an element more likely to be the article.
+static POSITIVE_NAMES: &[&str] = &[
+    "article", "blog", "body", "content", "entry", "hentry", "main", "page", "post", "story",
+    "text",
+];
+
+/// Parts of class names and identifiers that make an element less likely to be the article.
+static NEGATIVE_NAMES: &[&str] = &[
+    "comment", "contact", "foot", "footnote", "hidden", "masthead", "media", "meta", "promo",
+    "related", "scroll", "share", "shopping", "sidebar", "sponsor", "tags", "tool", "widget",
+];
+
+/// Separators between the title of an article and the name of the site it is on.
+static TITLE_SEPARATORS: &[&str] = &[" | ", " - ", " – ", " — ", " :: ", " » "];
+
+struct Candidate {
+    element: DomRoot<Element>,
+    score: f64,
+}
+
+/// Extracts the main article of `document`, if it looks like it has one.
+pub fn extract_article(document: &Document) -> Option<ReaderArticle> {
+    let body = document.GetBody()?;
+    let body = body.upcast::<Node>();
+
+    let mut candidates = score_candidates(body);
+    for candidate in &mut candidates {
+        candidate.score *= 1. - link_density(candidate.element.upcast());
+    }
+    let top = candidates
+        .iter()
+        .max_by(|a, b| a.score.partial_cmp(&b.score).unwrap())
+        .map(|candidate| (DomRoot::from_ref(&*candidate.element), candidate.score));
+
+    // Siblings of the best candidate that score well or read like paragraphs are part of
+    // the article too, as articles are often split between several containers.
+    let mut article = vec![];
+    match top {
+        Some((top, top_score)) => {
+            let threshold = (top_score * 0.2).max(10.);
+            let parent = top.upcast::<Node>().GetParentNode();
+            let siblings = parent
+                .as_ref()
+                .map_or(vec![], |parent| parent.children().collect());
+            for sibling in siblings {
+                let element = match DomRoot::downcast::<Element>(sibling.clone()) {
+                    Some(element) => element,
+                    None => continue,
+                };
+                let score = candidates
+                    .iter()
+                    .find(|candidate| candidate.element == element)
+                    .map_or(0., |candidate| candidate.score);
+                let is_paragraph = element.local_name() == &local_name!("p") &&
+                    text_length(&sibling) > 80 &&
+                    link_density(&sibling) < 0.25;
+                if element == top || score >= threshold || is_paragraph {
+                    article.push(sibling);
+                }
+            }
+        },
+        None => article.push(DomRoot::from_ref(body)),
+    }
+
+    let length = article.iter().map(|node| text_length(node)).sum();
+    if length < MIN_ARTICLE_LENGTH {
+        return None;
+    }
+    let base_url = document.base_url();
+    let mut content = String::new();
+    for node in &article {
+        write_content(node, &base_url, &mut content);
+    }
+
+    Some(ReaderArticle {
+        title: article_title(document, body),
+        byline: article_byline(document),
+        content,
+        length,
+    })
+}
+
+/// Scores the parents and grandparents of the paragraphs in `body`.
+fn score_candidates(body: &Node) -> Vec<Candidate> {
+    let mut candidates: Vec<Candidate> = vec![];
+    for node in body.traverse_preorder(ShadowIncluding::No) {
+        let is_paragraph = node.downcast::<Element>().map_or(false, |element| {
+            PARAGRAPH_TAGS.contains(&&**element.local_name())
+        });
+        if !is_paragraph || is_in_boilerplate(&node) {
+            continue;
+        }
+
+        let mut text = String::new();
+        collect_text(&node, &mut text);
+        let text = text.trim();
+        let length = text.chars().count();
+        if length < MIN_PARAGRAPH_LENGTH {
+            continue;
+        }
+        // Longer paragraphs with more clauses are more likely to be prose.
+        let commas = text.matches(',').count();
+        let score = 1. + commas as f64 + (length / 100).min(3) as f64;
+
+        let ancestors = node
+            .ancestors()
+            .take(2)
+            .filter_map(DomRoot::downcast::<Element>);
+        for (level, ancestor) in ancestors.enumerate() {
+            let share = if level == 0 { score } else { score / 2. };
+            match candidates
+                .iter_mut()
+                .find(|candidate| candidate.element == ancestor)
+            {
+                Some(candidate) => candidate.score += share,
+                None => {
+                    let score = initial_score(&ancestor) + share;
+                    candidates.push(Candidate {
+                        element: ancestor,
+                        score,
+                    });
+                },
+            }
+        }
+    }
+    candidates
+}
+
+/// The score that an element starts out with, based on its tag and its names.
+fn initial_score(element: &Element) -> f64 {
+    let tag_score = match &**element.local_name() {
+        "div" | "article" | "main" => 5.,
+        "blockquote" | "pre" | "td" => 3.,
+        "address" | "dd" | "dl" | "dt" | "form" | "li" | "ol" | "ul" => -3.,
+        "h1" | "h2" | "h3" | "h4" | "h5" | "h6" | "th" => -5.,
+        _ => 0.,
+    };
+    let names = element_names(element);
+    let mut name_score = 0.;
+    if NEGATIVE_NAMES.iter().any(|name| names.contains(name)) {
+        name_score -= 25.;
+    }
+    if POSITIVE_NAMES.iter().any(|name| names.contains(name)) {
+        name_score += 25.;
+    }
+    tag_score + name_score
+}
+
+/// The lowercase class names and identifier of `element`.
+fn element_names(element: &Element) -> String {
+    let class = element.get_string_attribute(&local_name!("class"));
+    let id = element.get_string_attribute(&local_name!("id"));
+    format!("{} {}", class, id).to_lowercase()
+}
+
+/// Whether `element` and its content are boilerplate that is never part of an article.
+fn is_boilerplate(element: &Element) -> bool {
+    let tag = &**element.local_name();
+    if REMOVED_TAGS.contains(&tag) ||
+        element.has_attribute(&local_name!("hidden")) ||
+        &*element.get_string_attribute(&LocalName::from("aria-hidden")) == "true"
+    {
+        return true;
+    }
+    if tag == "body" || tag == "article" || tag == "main" || tag == "a" {
+        return false;
+    }
+    let names = element_names(element);
+    UNLIKELY_NAMES.iter().any(|name| names.contains(name)) &&
+        !POSITIVE_NAMES.iter().any(|name| names.contains(name))
+}
+
+/// Whether `node` is boilerplate or inside of it.
+fn is_in_boilerplate(node: &Node) -> bool {
+    node.inclusive_ancestors(ShadowIncluding::No)
+        .filter_map(DomRoot::downcast::<Element>)
+        .any(|element| is_boilerplate(&element))
+}
+
+/// Appends the text of `node` that is not boilerplate to `text`.
+fn collect_text(node: &Node, text: &mut String) {
+    if let Some(data) = node.downcast::<Text>() {
+        text.push_str(&data.upcast::<CharacterData>().data());
+        return;
+    }
+    if node
+        .downcast::<Element>()
+        .map_or(false, |element| is_boilerplate(element))
+    {
+        return;
+    }
+    for child in node.children() {
+        collect_text(&child, text);
+    }
+}
+
+/// The number of characters of text in `node`, without the surrounding whitespace.
+fn text_length(node: &Node) -> usize {
+    let mut text = String::new();
+    collect_text(node, &mut text);
+    text.trim().chars().count()
+}
+
+/// The fraction of the text in `node` that is the text of links.
+fn link_density(node: &Node) -> f64 {
+    let length = text_length(node);
+    if length == 0 {
+        return 0.;
+    }
+    let link_length: usize = node
+        .traverse_preorder(ShadowIncluding::No)
+        .filter(|node| {
+            node.downcast::<Element>()
+                .map_or(false, |element| element.local_name() == &local_name!("a"))
+        })
+        .map(|link| text_length(&link))
+        .sum();
+    link_length as f64 / length as f64
+}
+
+/// The title of the document without the name of the site, or its first heading.
+fn article_title(document: &Document, body: &Node) -> String {
+    let title = document.Title().to_string();
+    if title.is_empty() {
+        let heading = body.traverse_preorder(ShadowIncluding::No).find(|node| {
+            node.downcast::<Element>()
+                .map_or(false, |element| element.local_name() == &local_name!("h1"))
+        });
+        let mut text = String::new();
+        if let Some(heading) = heading {
+            collect_text(&heading, &mut text);
+        }
+        return text.trim().to_owned();
+    }
+
+    let separator = TITLE_SEPARATORS
+        .iter()
+        .filter_map(|separator| title.rfind(separator))
+        .max();
+    match separator {
+        // Titles that are too short without the site name are kept whole.
+        Some(index) if title[..index].split_whitespace().count() >= 3 => {
+            title[..index].trim().to_owned()
+        },
+        _ => title,
+    }
+}
+
+/// The author of the document, from its metadata or from an element that looks like a byline.
+fn article_byline(document: &Document) -> Option<String> {
+    let elements = document
+        .upcast::<Node>()
+        .traverse_preorder(ShadowIncluding::No)
+        .filter_map(DomRoot::downcast::<Element>);
+    for element in elements {
+        let tag = element.local_name();
+        if tag == &local_name!("meta") {
+            let name = element.get_string_attribute(&local_name!("name"));
+            if name.eq_ignore_ascii_case("author") {
+                let content = element.get_string_attribute(&local_name!("content"));
+                if !content.trim().is_empty() {
+                    return Some(content.trim().to_owned());
+                }
+            }
+            continue;
+        }
+
+        let is_byline = &*element.get_string_attribute(&local_name!("rel")) == "author" || {
+            let names = element_names(&element);
+            names.contains("byline") || names.contains("author")
+        };
+        if !is_byline {
+            continue;
+        }
+        let mut text = String::new();
+        collect_text(element.upcast(), &mut text);
+        let text = text.trim();
+        if !text.is_empty() && text.chars().count() < 100 {
+            return Some(text.to_owned());
+        }
+    }
+    None
+}
+
+/// Appends `node` to `html` as simple HTML, without boilerplate and with absolute URLs.
+fn write_content(node: &Node, base_url: &ServoUrl, html: &mut String) {
+    if let Some(text) = node.downcast::<Text>() {
+        write_escaped(&text.upcast::<CharacterData>().data(), html);
+        return;
+    }
+    let element = match node.downcast::<Element>() {
+        Some(element) => element,
+        None => return,
+    };
+    if is_boilerplate(element) {
+        return;
+    }
+
+    let tag = &**element.local_name();
+    let tag = if KEPT_TAGS.contains(&tag) {
+        tag
+    } else if node.children().any(|child| {
+        child.downcast::<Element>().map_or(false, |element| {
+            BLOCK_TAGS.contains(&&**element.local_name())
+        })
+    }) {
+        for child in node.children() {
+            write_content(&child, base_url, html);
+        }
+        return;
+    } else if BLOCK_TAGS.contains(&tag) {
+        // Containers of inline content, which are often used instead of paragraphs.
+        "p"
+    } else {
+        for child in node.children() {
+            write_content(&child, base_url, html);
+        }
+        return;
+    };
+
+    let _ = write!(html, "<{}", tag);
+    match tag {
+        "a" => write_url_attribute(element, &local_name!("href"), base_url, html),
+        "img" => {
+            write_url_attribute(element, &local_name!("src"), base_url, html);
+            html.push_str(" alt=\"");
+            write_escaped(&element.get_string_attribute(&local_name!("alt")), html);
+            html.push('"');
+        },
+        _ => {},
+    }
+    html.push('>');
+    if VOID_TAGS.contains(&tag) {
+        return;
+    }
+    for child in node.children() {
+        write_content(&child, base_url, html);
+    }
+    let _ = write!(html, "</{}>", tag);
+}
+
+fn write_url_attribute(
+    element: &Element,
+    name: &LocalName,
+    base_url: &ServoUrl,
+    html: &mut String,
+) {
+    let url = match base_url.join(&element.get_string_attribute(name)) {
+        Ok(url) => url,
+        Err(_) => return,
+    };
+    // The reader view does not run script.
+    if url.scheme() == "javascript" {
+        return;
+    }
+    let _ = write!(html, " {}=\"", name);
+    write_escaped(url.as_str(), html);
+    html.push('"');
+}
+
+/// Appends `text` to `html`, escaped for use in text and in quoted attribute values.
+fn write_escaped(text: &str, html: &mut String) {
+    for character in text.chars() {
+        match character {
+            '&' => html.push_str("&amp;"),
+            '<' => html.push_str("&lt;"),
+            '>' => html.push_str("&gt;"),
+            '"' => html.push_str("&quot;"),
+            '\u{a0}' => html.push_str("&nbsp;"),
+            _ => html.push(character),
+        }
+    }
+}
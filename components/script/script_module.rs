@@ -19,7 +19,7 @@ use crate::dom::bindings::settings_stack::AutoIncumbentScript;
 use crate::dom::bindings::str::DOMString;
 use crate::dom::bindings::trace::RootedTraceableBox;
 use crate::dom::document::Document;
-use crate::dom::element::Element;
+use crate::dom::element::{fetch_priority_for_element, Element};
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::htmlscriptelement::{HTMLScriptElement, ScriptId};
 use crate::dom::htmlscriptelement::{ScriptOrigin, ScriptType, SCRIPT_JS_MIMES};
@@ -60,7 +60,7 @@ use js::rust::RootedObjectVectorWrapper;
 use js::rust::{Handle, HandleValue};
 use mime::Mime;
 use net_traits::request::{CredentialsMode, Destination, ParserMetadata};
-use net_traits::request::{Referrer, RequestBuilder, RequestMode};
+use net_traits::request::{Referrer, RequestBuilder, RequestMode, RequestPriority};
 use net_traits::{FetchMetadata, Metadata};
 use net_traits::{FetchResponseListener, NetworkError};
 use net_traits::{ResourceFetchTiming, ResourceTimingType};
@@ -1232,6 +1232,14 @@ pub fn fetch_single_module_script(
         ModuleOwner::Window(script) => Some(document_from_node(&*script.root())),
     };
 
+    // Descendant module scripts are fetched with the `auto` priority.
+    let priority = match &owner {
+        ModuleOwner::Window(script) if top_level_module_fetch => {
+            fetch_priority_for_element(script.root().upcast())
+        },
+        _ => RequestPriority::Auto,
+    };
+
     // Step 7-8.
     let request = RequestBuilder::new(url.clone())
         .destination(destination.clone())
@@ -1240,6 +1248,7 @@ pub fn fetch_single_module_script(
         .parser_metadata(parser_metadata)
         .integrity_metadata(integrity_metadata.clone())
         .credentials_mode(credentials_mode)
+        .priority(priority)
         .mode(mode);
 
     let context = Arc::new(Mutex::new(ModuleContext {
@@ -857,6 +857,30 @@ impl Deref for JSContext {
 
 pub struct StreamConsumer(*mut JSStreamConsumer);
 
+/// The reasons for which a `StreamConsumer` can stop receiving the body of a response, which
+/// SpiderMonkey hands back to `report_stream_error` to reject the compilation.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[repr(usize)]
+pub enum StreamConsumerError {
+    /// The fetch of the response body failed.
+    Network = 1,
+}
+
+impl StreamConsumerError {
+    fn from_code(error_code: usize) -> Option<StreamConsumerError> {
+        match error_code {
+            1 => Some(StreamConsumerError::Network),
+            _ => None,
+        }
+    }
+
+    fn message(&self) -> &'static str {
+        match *self {
+            StreamConsumerError::Network => "Network error while fetching the response body",
+        }
+    }
+}
+
 #[allow(unsafe_code)]
 impl StreamConsumer {
     pub fn consume_chunk(&self, stream: &[u8]) -> bool {
@@ -973,7 +997,15 @@ unsafe extern "C" fn consume_stream(
             );
             return false;
         }
-        unwrapped_source.set_stream_consumer(Some(StreamConsumer(_consumer)));
+
+        let consumer = StreamConsumer(_consumer);
+        let url = unwrapped_source.Url();
+        if !url.is_empty() {
+            consumer.note_response_urls(Some(url.to_string()), None);
+        }
+
+        // Steps 2.7 to 2.9 The body is fed to the compilation as it is received.
+        unwrapped_source.set_stream_consumer(consumer);
     } else {
         //Step 3 Upon rejection of source, return with reason.
         throw_dom_exception(
@@ -988,10 +1020,20 @@ unsafe extern "C" fn consume_stream(
 
 #[allow(unsafe_code)]
 unsafe extern "C" fn report_stream_error(_cx: *mut RawJSContext, error_code: usize) {
-    error!(
-        "Error initializing StreamConsumer: {:?}",
-        RUST_js_GetErrorMessage(ptr::null_mut(), error_code as u32)
-    );
+    let error = match StreamConsumerError::from_code(error_code) {
+        Some(error) => error,
+        None => {
+            error!(
+                "Error initializing StreamConsumer: {:?}",
+                RUST_js_GetErrorMessage(ptr::null_mut(), error_code as u32)
+            );
+            return;
+        },
+    };
+    let cx = JSContext::from_ptr(_cx);
+    let in_realm_proof = AlreadyInRealm::assert_for_cx(cx);
+    let global = GlobalScope::from_context(*cx, InRealm::Already(&in_realm_proof));
+    throw_dom_exception(cx, &global, Error::Type(error.message().to_string()));
 }
 
 pub struct Runnable(*mut JSRunnable);
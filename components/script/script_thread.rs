@@ -17,6 +17,8 @@
 //! a page runs its course and the script thread returns to processing events in the main event
 //! loop.
 
+use crate::accessibility;
+use crate::autofill;
 use crate::devtools;
 use crate::document_loader::DocumentLoader;
 use crate::dom::bindings::cell::DomRefCell;
@@ -67,7 +69,10 @@ use crate::dom::worker::TrustedWorkerAddress;
 use crate::dom::worklet::WorkletThreadPool;
 use crate::dom::workletglobalscope::WorkletGlobalScopeInit;
 use crate::fetch::FetchCanceller;
+use crate::find_in_page;
 use crate::microtask::{Microtask, MicrotaskQueue};
+use crate::password_manager;
+use crate::reader_mode;
 use crate::realms::enter_realm;
 use crate::script_runtime::{get_reports, new_rt_and_cx, JSContext, Runtime, ScriptPort};
 use crate::script_runtime::{CommonScriptMsg, ScriptChan, ScriptThreadEventCategory};
@@ -81,6 +86,7 @@ use crate::task_source::media_element::MediaElementTaskSource;
 use crate::task_source::networking::NetworkingTaskSource;
 use crate::task_source::performance_timeline::PerformanceTimelineTaskSource;
 use crate::task_source::port_message::PortMessageQueue;
+use crate::task_source::posted_task::PostedTaskSource;
 use crate::task_source::remote_event::RemoteEventTaskSource;
 use crate::task_source::timer::TimerTaskSource;
 use crate::task_source::user_interaction::UserInteractionTaskSource;
@@ -94,21 +100,25 @@ use crossbeam_channel::{unbounded, Receiver, Sender};
 use devtools_traits::CSSError;
 use devtools_traits::{DevtoolScriptControlMsg, DevtoolsPageInfo};
 use devtools_traits::{ScriptToDevtoolsControlMsg, WorkerId};
-use embedder_traits::{EmbedderMsg, EventLoopWaker};
+use embedder_traits::{BatteryStatus, Credentials, EmbedderMsg, EventLoopWaker, FindOptions};
+use embedder_traits::{GeolocationPosition, NetworkInformation, SensorReading};
 use euclid::default::{Point2D, Rect};
 use euclid::Vector2D;
 use headers::ReferrerPolicy as ReferrerPolicyHeader;
 use headers::{HeaderMapExt, LastModified};
+use http::header::{HeaderMap, CONTENT_DISPOSITION};
 use hyper_serde::Serde;
 use ipc_channel::ipc::{self, IpcSender};
 use ipc_channel::router::ROUTER;
 use js::glue::GetWindowProxyClass;
 use js::jsapi::JS_SetWrapObjectCallbacks;
+use js::jsapi::{GCReason, JSGCInvocationKind, NonIncrementalGC, PrepareForFullGC};
+use js::jsapi::{IncrementalGCSlice, IsIncrementalGCInProgress, JS_MaybeGC};
 use js::jsapi::{JSTracer, SetWindowProxyClass};
 use js::jsval::UndefinedValue;
 use js::rust::ParentRuntime;
 use media::WindowGLContext;
-use metrics::{PaintTimeMetrics, MAX_TASK_NS};
+use metrics::{PaintTimeMetrics, ToMs, MAX_TASK_NS};
 use mime::{self, Mime};
 use msg::constellation_msg::{
     BackgroundHangMonitor, BackgroundHangMonitorRegister, ScriptHangAnnotation,
@@ -117,9 +127,11 @@ use msg::constellation_msg::{BrowsingContextId, HistoryStateId, PipelineId};
 use msg::constellation_msg::{HangAnnotation, MonitoredComponentId, MonitoredComponentType};
 use msg::constellation_msg::{PipelineNamespace, TopLevelBrowsingContextId};
 use net_traits::image_cache::{ImageCache, PendingImageResponse};
-use net_traits::request::{CredentialsMode, Destination, RedirectMode, RequestBuilder};
+use net_traits::request::{CredentialsMode, Destination, RedirectMode, Referrer, RequestBuilder};
 use net_traits::storage_thread::StorageType;
-use net_traits::{FetchMetadata, FetchResponseListener, FetchResponseMsg};
+use net_traits::{
+    CoreResourceMsg, FetchMetadata, FetchResponseListener, FetchResponseMsg, IpcSend,
+};
 use net_traits::{
     Metadata, NetworkError, ReferrerPolicy, ResourceFetchTiming, ResourceThreads,
     ResourceTimingType,
@@ -130,21 +142,24 @@ use profile_traits::time::{self as profile_time, profile, ProfilerCategory};
 use script_layout_interface::message::{self, LayoutThreadInit, Msg, ReflowGoal};
 use script_traits::webdriver_msg::WebDriverScriptCommand;
 use script_traits::CompositorEvent::{
-    CompositionEvent, KeyboardEvent, MouseButtonEvent, MouseMoveEvent, ResizeEvent, TouchEvent,
-    WheelEvent,
+    CompositionEvent, KeyboardEvent, MouseButtonEvent, MouseMoveEvent, PointerLockMoveEvent,
+    ResizeEvent, TouchEvent, WheelEvent,
 };
 use script_traits::StructuredSerializedData;
 use script_traits::{CompositorEvent, ConstellationControlMsg};
 use script_traits::{
-    DiscardBrowsingContext, DocumentActivity, EventResult, HistoryEntryReplacement,
+    DiscardBrowsingContext, DocumentActivity, EventResult, FrameTiming, HistoryEntryReplacement,
 };
 use script_traits::{InitialScriptState, JsEvalResult, LayoutMsg, LoadData, LoadOrigin};
-use script_traits::{MediaSessionActionType, MouseButton, MouseEventType, NewLayoutInfo};
+use script_traits::{LargestContentfulPaintCandidate, LayoutShift};
+use script_traits::{MediaSessionActionDetails, MouseButton, MouseEventType, NewLayoutInfo};
 use script_traits::{Painter, ProgressiveWebMetricType, ScriptMsg, ScriptThreadFactory};
 use script_traits::{ScriptToConstellationChan, TimerSchedulerMsg};
 use script_traits::{TouchEventType, TouchId, UntrustedNodeAddress, WheelDelta};
-use script_traits::{UpdatePipelineIdReason, WebrenderIpcSender, WindowSizeData, WindowSizeType};
+use script_traits::{UpdatePipelineIdReason, UserActivationUpdate, WebrenderIpcSender};
+use script_traits::{WindowSizeData, WindowSizeType};
 use servo_atoms::Atom;
+use servo_config::{pref, set_pref};
 use servo_url::{ImmutableOrigin, MutableOrigin, ServoUrl};
 use std::borrow::Cow;
 use std::cell::Cell;
@@ -162,6 +177,7 @@ use std::thread;
 use std::time::{Duration, SystemTime};
 use style::dom::OpaqueNode;
 use style::thread_state::{self, ThreadState};
+use style_traits::{ColorSchemePreference, MediaPreferences};
 use time::{at_utc, get_time, precise_time_ns, Timespec};
 use url::Position;
 use webrender_api::units::LayoutPixel;
@@ -170,6 +186,10 @@ use webvr_traits::{WebVREvent, WebVRMsg};
 
 pub type ImageCacheMsg = (PipelineId, PendingImageResponse);
 
+/// How long to wait before starting another idle period when idle callbacks are left after one,
+/// in milliseconds.
+const IDLE_PERIOD_RETRY_DELAY: u64 = 16;
+
 thread_local!(static SCRIPT_THREAD_ROOT: Cell<Option<*const ScriptThread>> = Cell::new(None));
 
 pub unsafe fn trace_thread(tr: *mut JSTracer) {
@@ -205,6 +225,8 @@ struct InProgressLoad {
     activity: DocumentActivity,
     /// Window is visible.
     is_visible: bool,
+    /// Whether the document is prerendered for a navigation that did not happen yet.
+    is_prerendering: bool,
     /// The requested URL of the load.
     url: ServoUrl,
     /// The origin for the document
@@ -248,6 +270,7 @@ impl InProgressLoad {
             window_size: window_size,
             activity: DocumentActivity::FullyActive,
             is_visible: true,
+            is_prerendering: false,
             url: url,
             origin: origin,
             navigation_start: (current_time.sec * 1000 + current_time.nsec as i64 / 1000000) as u64,
@@ -569,6 +592,8 @@ pub struct ScriptThread {
 
     port_message_sender: Box<dyn ScriptChan>,
 
+    posted_task_sender: Box<dyn ScriptChan>,
+
     timer_task_sender: Box<dyn ScriptChan>,
 
     remote_event_task_sender: Box<dyn ScriptChan>,
@@ -693,9 +718,6 @@ pub struct ScriptThread {
 
     /// A set of all nodes ever created in this script thread
     node_ids: DomRefCell<HashSet<String>>,
-
-    /// Code is running as a consequence of a user interaction
-    is_user_interacting: Cell<bool>,
 }
 
 /// In the event of thread panic, all data on the stack runs its destructor. However, there
@@ -1053,24 +1075,6 @@ impl ScriptThread {
         })
     }
 
-    pub fn set_user_interacting(interacting: bool) {
-        SCRIPT_THREAD_ROOT.with(|root| {
-            if let Some(script_thread) = root.get() {
-                let script_thread = unsafe { &*script_thread };
-                script_thread.is_user_interacting.set(interacting);
-            }
-        });
-    }
-
-    pub fn is_user_interacting() -> bool {
-        SCRIPT_THREAD_ROOT.with(|root| {
-            root.get().map_or(false, |script_thread| {
-                let script_thread = unsafe { &*script_thread };
-                script_thread.is_user_interacting.get()
-            })
-        })
-    }
-
     pub fn get_fully_active_document_ids() -> HashSet<PipelineId> {
         SCRIPT_THREAD_ROOT.with(|root| {
             root.get().map_or(HashSet::new(), |script_thread| {
@@ -1094,6 +1098,35 @@ impl ScriptThread {
         })
     }
 
+    /// Measures the memory used by the JavaScript runtime of this script thread, which is shared
+    /// by the documents of its agent cluster, and returns it along with the documents which have
+    /// the given origin.
+    pub fn agent_cluster_memory(origin: &MutableOrigin) -> (usize, Vec<DomRoot<Document>>) {
+        SCRIPT_THREAD_ROOT.with(|root| {
+            root.get().map_or((0, vec![]), |script_thread| {
+                let script_thread = unsafe { &*script_thread };
+                let bytes: usize = get_reports(*script_thread.get_cx(), String::new())
+                    .into_iter()
+                    .filter(|report| {
+                        report
+                            .path
+                            .last()
+                            .map_or(true, |name| name != "unused" && name != "decommitted")
+                    })
+                    .map(|report| report.size)
+                    .sum();
+                let documents = script_thread
+                    .documents
+                    .borrow()
+                    .iter()
+                    .map(|(_, document)| document)
+                    .filter(|document| document.origin().same_origin(origin))
+                    .collect();
+                (bytes, documents)
+            })
+        })
+    }
+
     pub fn find_window_proxy(id: BrowsingContextId) -> Option<DomRoot<WindowProxy>> {
         SCRIPT_THREAD_ROOT.with(|root| {
             root.get().and_then(|script_thread| {
@@ -1322,6 +1355,7 @@ impl ScriptThread {
             port_message_sender: boxed_script_sender.clone(),
             file_reading_task_sender: boxed_script_sender.clone(),
             performance_timeline_task_sender: boxed_script_sender.clone(),
+            posted_task_sender: boxed_script_sender.clone(),
             timer_task_sender: boxed_script_sender.clone(),
             remote_event_task_sender: boxed_script_sender.clone(),
 
@@ -1383,7 +1417,6 @@ impl ScriptThread {
             event_loop_waker: state.event_loop_waker,
 
             node_ids: Default::default(),
-            is_user_interacting: Cell::new(false),
         }
     }
 
@@ -1426,6 +1459,16 @@ impl ScriptThread {
         // Store new resizes, and gather all other events.
         let mut sequential = vec![];
 
+        // Run idle callbacks and garbage collection slices while there is nothing else to do,
+        // and wake up again to run the ones that are left once their idle period is over.
+        let idle_callbacks_left = self.perform_idle_periods();
+        let idle_gc_left = self.perform_idle_gc_slice();
+        let idle_period_timer = if idle_callbacks_left || idle_gc_left {
+            crossbeam_channel::after(Duration::from_millis(IDLE_PERIOD_RETRY_DELAY))
+        } else {
+            crossbeam_channel::never()
+        };
+
         // Notify the background-hang-monitor we are waiting for an event.
         self.background_hang_monitor
             .as_ref()
@@ -1446,12 +1489,13 @@ impl ScriptThread {
             recv(self.devtools_chan.as_ref().map(|_| &self.devtools_port).unwrap_or(&crossbeam_channel::never())) -> msg
                 => FromDevtools(msg.unwrap()),
             recv(self.image_cache_port) -> msg => FromImageCache(msg.unwrap()),
+            recv(idle_period_timer) -> _ => return true,
         };
         debug!("Got event.");
 
         // Squash any pending resize, reflow, animation tick, and mouse-move events in the queue.
         let mut mouse_move_event_index = None;
-        let mut animation_ticks = HashSet::new();
+        let mut animation_ticks = HashMap::new();
         loop {
             // https://html.spec.whatwg.org/multipage/#event-loop-processing-model step 7
             match event {
@@ -1506,11 +1550,16 @@ impl ScriptThread {
                         self.handle_set_scroll_state(id, &scroll_state);
                     })
                 },
-                FromConstellation(ConstellationControlMsg::TickAllAnimations(pipeline_id)) => {
+                FromConstellation(ConstellationControlMsg::TickAllAnimations(pipeline_id, _)) => {
                     // step 7.8
-                    if !animation_ticks.contains(&pipeline_id) {
-                        animation_ticks.insert(pipeline_id);
-                        sequential.push(event);
+                    // The ticks of the video refreshes that a busy script thread missed are
+                    // dropped, so that it catches up with the latest one.
+                    match animation_ticks.get(&pipeline_id) {
+                        None => {
+                            animation_ticks.insert(pipeline_id, sequential.len());
+                            sequential.push(event);
+                        },
+                        Some(&index) => sequential[index] = event,
                     }
                 },
                 FromConstellation(ConstellationControlMsg::SendEvent(_, MouseMoveEvent(..))) => {
@@ -1579,6 +1628,12 @@ impl ScriptThread {
             // https://html.spec.whatwg.org/multipage/#event-loop-processing-model step 6
             self.perform_a_microtask_checkpoint();
 
+            // Layout may change in between tasks without the documents changing, for example
+            // when animations tick, so geometry queries only reuse the responses of this task.
+            for (_, document) in self.documents.borrow().iter() {
+                document.window().clear_geometry_query_cache();
+            }
+
             if let Some(retval) = result {
                 return retval;
             }
@@ -1625,6 +1680,88 @@ impl ScriptThread {
         true
     }
 
+    /// Whether the event loop has tasks or messages waiting to be handled.
+    fn has_pending_tasks(&self) -> bool {
+        self.task_queue.has_pending_tasks() ||
+            !self.control_port.is_empty() ||
+            !self.devtools_port.is_empty() ||
+            !self.image_cache_port.is_empty()
+    }
+
+    /// <https://w3c.github.io/requestidlecallback/#start-an-idle-period-algorithm>
+    ///
+    /// Starts an idle period for each window with idle callbacks and invokes them until the
+    /// deadline of the period, for as long as the event loop has nothing else to do. Returns
+    /// whether any idle callbacks are left for later idle periods.
+    fn perform_idle_periods(&self) -> bool {
+        let windows: Vec<DomRoot<Window>> = self
+            .documents
+            .borrow()
+            .iter()
+            .filter(|(_, document)| {
+                document.is_fully_active() && document.window().has_idle_callbacks()
+            })
+            .map(|(_, document)| DomRoot::from_ref(document.window()))
+            .collect();
+
+        for window in &windows {
+            if self.has_pending_tasks() {
+                break;
+            }
+            let deadline = window.start_idle_period();
+
+            // https://w3c.github.io/requestidlecallback/#invoke-idle-callbacks-algorithm
+            while (precise_time_ns() as f64 / 1_000_000.) < deadline && !self.has_pending_tasks() {
+                let invoked = self.profile_event(
+                    ScriptThreadEventCategory::ScriptEvent,
+                    Some(window.pipeline_id()),
+                    || window.invoke_idle_callback(deadline),
+                );
+                if !invoked {
+                    break;
+                }
+                self.perform_a_microtask_checkpoint();
+            }
+        }
+
+        windows.iter().any(|window| window.has_idle_callbacks())
+    }
+
+    /// Runs a slice of incremental garbage collection in the time left before the next timer or
+    /// rendering opportunity of the windows of this thread, so that the collector does its work
+    /// between frames instead of whenever allocations trigger it. Collections are started here
+    /// too, for the zones that are getting close to their allocation threshold. Returns whether
+    /// a collection is still in progress, so that it goes on in the next idle period.
+    #[allow(unsafe_code)]
+    fn perform_idle_gc_slice(&self) -> bool {
+        if !pref!(js.mem.gc.idle.enabled) || self.has_pending_tasks() {
+            return false;
+        }
+
+        let now = precise_time_ns() as f64 / 1_000_000.;
+        let deadline = self
+            .documents
+            .borrow()
+            .iter()
+            .filter(|(_, document)| document.is_fully_active())
+            .map(|(_, document)| document.window().idle_period_deadline(now))
+            .fold(now + pref!(js.mem.gc.idle.slice_ms) as f64, f64::min);
+        let budget = (deadline - now).floor() as i64;
+
+        let cx = *self.get_cx();
+        unsafe {
+            if budget >= pref!(js.mem.gc.idle.min_slice_ms) {
+                if !IsIncrementalGCInProgress(cx) {
+                    JS_MaybeGC(cx);
+                }
+                if IsIncrementalGCInProgress(cx) {
+                    IncrementalGCSlice(cx, GCReason::INTER_SLICE_GC, budget);
+                }
+            }
+            IsIncrementalGCInProgress(cx)
+        }
+    }
+
     fn categorize_msg(&self, msg: &MixedMessage) -> ScriptThreadEventCategory {
         match *msg {
             MixedMessage::FromConstellation(ref inner_msg) => match *inner_msg {
@@ -1706,6 +1843,7 @@ impl ScriptThread {
                     GetTitle(id) => Some(id),
                     SetDocumentActivity(id, ..) => Some(id),
                     ChangeFrameVisibilityStatus(id, ..) => Some(id),
+                    SetPrerendering(id, ..) => Some(id),
                     NotifyVisibilityChange(id, ..) => Some(id),
                     NavigateIframe(id, ..) => Some(id),
                     PostMessage { target: id, .. } => Some(id),
@@ -1714,7 +1852,8 @@ impl ScriptThread {
                     RemoveHistoryStates(id, ..) => Some(id),
                     FocusIFrame(id, ..) => Some(id),
                     WebDriverScriptCommand(id, ..) => Some(id),
-                    TickAllAnimations(id) => Some(id),
+                    TickAllAnimations(id, _) => Some(id),
+                    SetPinchZoom(id, ..) => Some(id),
                     // FIXME https://github.com/servo/servo/issues/15079
                     TransitionEnd(..) => None,
                     WebFontLoaded(id) => Some(id),
@@ -1728,8 +1867,25 @@ impl ScriptThread {
                     Reload(id, ..) => Some(id),
                     WebVREvents(id, ..) => Some(id),
                     PaintMetric(..) => None,
+                    LargestContentfulPaint(..) => None,
+                    LayoutShift(..) => None,
                     ExitFullScreen(id, ..) => Some(id),
+                    ExitPointerLock(id) => Some(id),
                     MediaSessionAction(..) => None,
+                    Find(id, ..) => Some(id),
+                    StopFinding(id) => Some(id),
+                    ExtractArticle(id) => Some(id),
+                    RequestAccessibilityTree(id) => Some(id),
+                    RequestFormFields(id) => Some(id),
+                    FillFormFields(id, ..) => Some(id),
+                    FillCredentials(id, ..) => Some(id),
+                    MemoryPressure => None,
+                    MediaPreferencesChanged(..) => None,
+                    DeviceStatusChanged(..) => None,
+                    UpdatePosition(id, ..) => Some(id),
+                    SensorReading(id, ..) => Some(id),
+                    SetTextZoom(..) => None,
+                    UpdateUserActivation(id, ..) => Some(id),
                 }
             },
             MixedMessage::FromDevtools(_) => None,
@@ -1821,6 +1977,13 @@ impl ScriptThread {
             }
             doc.record_tti_if_necessary();
         }
+        // https://w3c.github.io/longtasks/#report-long-tasks
+        if end - start > MAX_TASK_NS {
+            let document = pipeline_id.and_then(|id| self.documents.borrow().find_document(id));
+            if let Some(document) = document {
+                document.window().Performance().queue_long_task(start, end);
+            }
+        }
         value
     }
 
@@ -1868,6 +2031,9 @@ impl ScriptThread {
             ConstellationControlMsg::ChangeFrameVisibilityStatus(pipeline_id, visible) => {
                 self.handle_visibility_change_msg(pipeline_id, visible)
             },
+            ConstellationControlMsg::SetPrerendering(pipeline_id, prerendering) => {
+                self.handle_set_prerendering_msg(pipeline_id, prerendering)
+            },
             ConstellationControlMsg::NotifyVisibilityChange(
                 parent_pipeline_id,
                 browsing_context_id,
@@ -1917,8 +2083,11 @@ impl ScriptThread {
             ConstellationControlMsg::WebDriverScriptCommand(pipeline_id, msg) => {
                 self.handle_webdriver_msg(pipeline_id, msg)
             },
-            ConstellationControlMsg::TickAllAnimations(pipeline_id) => {
-                self.handle_tick_all_animations(pipeline_id)
+            ConstellationControlMsg::TickAllAnimations(pipeline_id, timing) => {
+                self.handle_tick_all_animations(pipeline_id, timing)
+            },
+            ConstellationControlMsg::SetPinchZoom(pipeline_id, pinch_zoom) => {
+                self.handle_set_pinch_zoom(pipeline_id, pinch_zoom)
             },
             ConstellationControlMsg::TransitionEnd(unsafe_node, name, duration) => {
                 self.handle_transition_event(unsafe_node, name, duration)
@@ -1952,9 +2121,56 @@ impl ScriptThread {
             ConstellationControlMsg::PaintMetric(pipeline_id, metric_type, metric_value) => {
                 self.handle_paint_metric(pipeline_id, metric_type, metric_value)
             },
+            ConstellationControlMsg::LargestContentfulPaint(pipeline_id, time, candidate) => {
+                self.handle_largest_contentful_paint(pipeline_id, time, candidate)
+            },
+            ConstellationControlMsg::LayoutShift(pipeline_id, time, shift) => {
+                self.handle_layout_shift(pipeline_id, time, shift)
+            },
             ConstellationControlMsg::MediaSessionAction(pipeline_id, action) => {
                 self.handle_media_session_action(pipeline_id, action)
             },
+            ConstellationControlMsg::Find(pipeline_id, string, options) => {
+                self.handle_find(pipeline_id, string, options)
+            },
+            ConstellationControlMsg::StopFinding(pipeline_id) => {
+                self.handle_stop_finding(pipeline_id)
+            },
+            ConstellationControlMsg::ExtractArticle(pipeline_id) => {
+                self.handle_extract_article(pipeline_id)
+            },
+            ConstellationControlMsg::RequestAccessibilityTree(pipeline_id) => {
+                self.handle_request_accessibility_tree(pipeline_id)
+            },
+            ConstellationControlMsg::RequestFormFields(pipeline_id) => {
+                self.handle_request_form_fields(pipeline_id)
+            },
+            ConstellationControlMsg::FillFormFields(pipeline_id, values) => {
+                self.handle_fill_form_fields(pipeline_id, values)
+            },
+            ConstellationControlMsg::FillCredentials(pipeline_id, credentials) => {
+                self.handle_fill_credentials(pipeline_id, credentials)
+            },
+            ConstellationControlMsg::MemoryPressure => self.handle_memory_pressure(),
+            ConstellationControlMsg::MediaPreferencesChanged(preferences) => {
+                self.handle_media_preferences_changed(preferences)
+            },
+            ConstellationControlMsg::DeviceStatusChanged(battery_status, network_information) => {
+                self.handle_device_status_changed(battery_status, network_information)
+            },
+            ConstellationControlMsg::UpdatePosition(pipeline_id, position) => {
+                self.handle_update_position(pipeline_id, position)
+            },
+            ConstellationControlMsg::SensorReading(pipeline_id, reading) => {
+                self.handle_sensor_reading(pipeline_id, reading)
+            },
+            ConstellationControlMsg::SetTextZoom(zoom) => self.handle_set_text_zoom(zoom),
+            ConstellationControlMsg::UpdateUserActivation(pipeline_id, update) => {
+                self.handle_update_user_activation(pipeline_id, update)
+            },
+            ConstellationControlMsg::ExitPointerLock(pipeline_id) => {
+                self.handle_exit_pointer_lock(pipeline_id)
+            },
             msg @ ConstellationControlMsg::AttachLayout(..) |
             msg @ ConstellationControlMsg::Viewport(..) |
             msg @ ConstellationControlMsg::SetScrollState(..) |
@@ -2011,12 +2227,54 @@ impl ScriptThread {
             DevtoolScriptControlMsg::ModifyAttribute(id, node_id, modifications) => {
                 devtools::handle_modify_attribute(&*documents, id, node_id, modifications)
             },
+            DevtoolScriptControlMsg::SetNodeValue(id, node_id, value) => {
+                devtools::handle_set_node_value(&*documents, id, node_id, value)
+            },
+            DevtoolScriptControlMsg::RemoveNode(id, node_id, reply) => {
+                devtools::handle_remove_node(&*documents, id, node_id, reply)
+            },
+            DevtoolScriptControlMsg::GetOuterHTML(id, node_id, reply) => {
+                devtools::handle_get_outer_html(&*documents, id, node_id, reply)
+            },
+            DevtoolScriptControlMsg::SetOuterHTML(id, node_id, html) => {
+                devtools::handle_set_outer_html(&*documents, id, node_id, html)
+            },
             DevtoolScriptControlMsg::WantsLiveNotifications(id, to_send) => match documents
                 .find_window(id)
             {
                 Some(window) => devtools::handle_wants_live_notifications(window.upcast(), to_send),
                 None => return warn!("Message sent to closed pipeline {}.", id),
             },
+            DevtoolScriptControlMsg::WantsDomMutations(id, to_send) => {
+                match documents.find_document(id) {
+                    Some(document) => devtools::handle_wants_dom_mutations(&document, to_send),
+                    None => return warn!("Message sent to closed pipeline {}.", id),
+                }
+            },
+            DevtoolScriptControlMsg::GetStyleSheets(id, reply) => {
+                devtools::handle_get_stylesheets(&*documents, id, reply)
+            },
+            DevtoolScriptControlMsg::GetStyleSheetText(id, index, reply) => {
+                devtools::handle_get_stylesheet_text(&*documents, id, index, reply)
+            },
+            DevtoolScriptControlMsg::SetStyleSheetText(id, index, text) => {
+                devtools::handle_set_stylesheet_text(&*documents, id, index, text)
+            },
+            DevtoolScriptControlMsg::ToggleStyleSheetDisabled(id, index, reply) => {
+                devtools::handle_toggle_stylesheet_disabled(&*documents, id, index, reply)
+            },
+            DevtoolScriptControlMsg::GetAppliedRules(id, node_id, reply) => {
+                devtools::handle_get_applied_rules(&*documents, id, node_id, reply)
+            },
+            DevtoolScriptControlMsg::GetComputedStyle(id, node_id, reply) => {
+                devtools::handle_get_computed_style(&*documents, id, node_id, reply)
+            },
+            DevtoolScriptControlMsg::ModifyRule(id, rule, modifications, reply) => {
+                devtools::handle_modify_rule(&*documents, id, rule, modifications, reply)
+            },
+            DevtoolScriptControlMsg::SetRuleText(id, rule, text, reply) => {
+                devtools::handle_set_rule_text(&*documents, id, rule, text, reply)
+            },
             DevtoolScriptControlMsg::SetTimelineMarkers(id, marker_types, reply) => {
                 devtools::handle_set_timeline_markers(&*documents, id, marker_types, reply)
             },
@@ -2027,6 +2285,9 @@ impl ScriptThread {
                 devtools::handle_request_animation_frame(&*documents, id, name)
             },
             DevtoolScriptControlMsg::Reload(id) => devtools::handle_reload(&*documents, id),
+            DevtoolScriptControlMsg::SetNetworkConditions(id, conditions) => {
+                devtools::handle_set_network_conditions(&*documents, id, conditions)
+            },
         }
     }
 
@@ -2296,6 +2557,13 @@ impl ScriptThread {
         }
     }
 
+    fn handle_exit_pointer_lock(&self, id: PipelineId) {
+        let document = self.documents.borrow().find_document(id);
+        if let Some(document) = document {
+            document.exit_pointer_lock();
+        }
+    }
+
     fn handle_viewport(&self, id: PipelineId, rect: Rect<f32>) {
         let document = self.documents.borrow().find_document(id);
         if let Some(document) = document {
@@ -2454,7 +2722,11 @@ impl ScriptThread {
         let window = self.documents.borrow().find_window(id);
         match window {
             Some(window) => {
+                let was_visible = window.visible();
                 window.alter_resource_utilization(visible);
+                if was_visible != visible {
+                    window.Document().visibility_state_changed();
+                }
                 return;
             },
             None => {
@@ -2469,6 +2741,25 @@ impl ScriptThread {
         warn!("change visibility message sent to nonexistent pipeline");
     }
 
+    /// Marks the document of a pipeline as prerendered, or activates it once the browsing
+    /// context navigated to it.
+    fn handle_set_prerendering_msg(&self, id: PipelineId, prerendering: bool) {
+        let document = self.documents.borrow().find_document(id);
+        if let Some(document) = document {
+            if prerendering {
+                document.set_prerendering(true);
+            } else {
+                document.activate_prerendered_document();
+            }
+            return;
+        }
+        let mut loads = self.incomplete_loads.borrow_mut();
+        match loads.iter_mut().find(|load| load.pipeline_id == id) {
+            Some(load) => load.is_prerendering = prerendering,
+            None => warn!("prerendering message sent to nonexistent pipeline"),
+        }
+    }
+
     /// Handles activity change message
     fn handle_set_document_activity_msg(&self, id: PipelineId, activity: DocumentActivity) {
         debug!(
@@ -2661,23 +2952,30 @@ impl ScriptThread {
                         status: Some((204..=205, _)),
                         ..
                     }) => {
-                        // If we have an existing window that is being navigated:
-                        if let Some(window) = self.documents.borrow().find_window(id.clone()) {
-                            let window_proxy = window.window_proxy();
-                            // https://html.spec.whatwg.org/multipage/
-                            // #navigating-across-documents:delaying-load-events-mode-2
-                            if window_proxy.parent().is_some() {
-                                // The user agent must take this nested browsing context
-                                // out of the delaying load events mode
-                                // when this navigation algorithm later matures,
-                                // or when it terminates (whether due to having run all the steps,
-                                // or being canceled, or being aborted), whichever happens first.
-                                window_proxy.stop_delaying_load_events_mode();
-                            }
-                        }
-                        self.script_sender
-                            .send((id.clone(), ScriptMsg::AbortLoadUrl))
-                            .unwrap();
+                        self.abort_navigation(id);
+                        return None;
+                    },
+                    // The responses that are not to be rendered are downloaded instead.
+                    Some(ref metadata) if should_download(metadata) => {
+                        let load = self.incomplete_loads.borrow_mut().remove(idx);
+                        let request = RequestBuilder::new(metadata.final_url.clone())
+                            .credentials_mode(CredentialsMode::Include)
+                            .use_url_credentials(true)
+                            .origin(load.origin.immutable().clone())
+                            .referrer(
+                                metadata
+                                    .referrer
+                                    .clone()
+                                    .map_or(Referrer::NoReferrer, Referrer::ReferrerUrl),
+                            )
+                            .referrer_policy(metadata.referrer_policy);
+                        let msg = CoreResourceMsg::Download(
+                            request,
+                            Some(load.top_level_browsing_context_id),
+                        );
+                        let _ = self.resource_threads.send(msg);
+                        // Dropping the load cancels the fetch of the response.
+                        self.abort_navigation(id);
                         return None;
                     },
                     _ => (),
@@ -2693,6 +2991,27 @@ impl ScriptThread {
         }
     }
 
+    /// Leaves the document that was being navigated from in place.
+    fn abort_navigation(&self, id: &PipelineId) {
+        // If we have an existing window that is being navigated:
+        if let Some(window) = self.documents.borrow().find_window(id.clone()) {
+            let window_proxy = window.window_proxy();
+            // https://html.spec.whatwg.org/multipage/
+            // #navigating-across-documents:delaying-load-events-mode-2
+            if window_proxy.parent().is_some() {
+                // The user agent must take this nested browsing context
+                // out of the delaying load events mode
+                // when this navigation algorithm later matures,
+                // or when it terminates (whether due to having run all the steps,
+                // or being canceled, or being aborted), whichever happens first.
+                window_proxy.stop_delaying_load_events_mode();
+            }
+        }
+        self.script_sender
+            .send((id.clone(), ScriptMsg::AbortLoadUrl))
+            .unwrap();
+    }
+
     pub fn handle_get_registration(
         &self,
         scope_url: &ServoUrl,
@@ -2784,6 +3103,10 @@ impl ScriptThread {
         PortMessageQueue(self.port_message_sender.clone(), pipeline_id)
     }
 
+    pub fn posted_task_source(&self, pipeline_id: PipelineId) -> PostedTaskSource {
+        PostedTaskSource(self.posted_task_sender.clone(), pipeline_id)
+    }
+
     pub fn file_reading_task_source(&self, pipeline_id: PipelineId) -> FileReadingTaskSource {
         FileReadingTaskSource(self.file_reading_task_sender.clone(), pipeline_id)
     }
@@ -2912,12 +3235,20 @@ impl ScriptThread {
     }
 
     /// Handles when layout thread finishes all animation in one tick
-    fn handle_tick_all_animations(&self, id: PipelineId) {
+    fn handle_tick_all_animations(&self, id: PipelineId, timing: FrameTiming) {
         let document = match { self.documents.borrow().find_document(id) } {
             Some(document) => document,
             None => return warn!("Message sent to closed pipeline {}.", id),
         };
-        document.run_the_animation_frame_callbacks();
+        document.run_the_animation_frame_callbacks(timing);
+    }
+
+    fn handle_set_pinch_zoom(&self, id: PipelineId, pinch_zoom: f32) {
+        let window = match { self.documents.borrow().find_window(id) } {
+            Some(window) => window,
+            None => return warn!("Message sent to closed pipeline {}.", id),
+        };
+        window.set_pinch_zoom(pinch_zoom);
     }
 
     /// Handles firing of transition events.
@@ -3192,6 +3523,7 @@ impl ScriptThread {
             self.performance_timeline_task_source(incomplete.pipeline_id)
                 .clone(),
             self.port_message_queue(incomplete.pipeline_id),
+            self.posted_task_source(incomplete.pipeline_id),
             self.user_interaction_task_source(incomplete.pipeline_id),
             self.remote_event_task_source(incomplete.pipeline_id),
             self.timer_task_source(incomplete.pipeline_id),
@@ -3350,6 +3682,20 @@ impl ScriptThread {
 
         document.set_https_state(metadata.https_state);
         document.set_navigation_start(incomplete.navigation_start_precise);
+        document.set_prerendering(incomplete.is_prerendering);
+
+        // Nested documents are only isolated if their parent is.
+        let parent_is_isolated = incomplete.parent_info.map_or(true, |parent_id| {
+            self.documents
+                .borrow()
+                .find_document(parent_id)
+                .map_or(true, |parent| parent.is_cross_origin_isolated())
+        });
+        let cross_origin_isolated = metadata.headers.as_ref().map_or(false, |headers| {
+            header_has_value(headers, "cross-origin-opener-policy", "same-origin") &&
+                header_has_value(headers, "cross-origin-embedder-policy", "require-corp")
+        });
+        document.set_cross_origin_isolated(parent_is_isolated && cross_origin_isolated);
 
         if is_html_document == IsHTMLDocument::NonHTMLDocument {
             ServoParser::parse_xml_document(&document, parse_input, final_url);
@@ -3419,8 +3765,6 @@ impl ScriptThread {
             );
         }
 
-        // Assuming all CompositionEvent are generated by user interactions.
-        ScriptThread::set_user_interacting(true);
         match event {
             ResizeEvent(new_size, size_type) => {
                 self.handle_resize_event(pipeline_id, new_size, size_type);
@@ -3534,6 +3878,14 @@ impl ScriptThread {
                 }
             },
 
+            PointerLockMoveEvent(movement, pressed_mouse_buttons) => {
+                let document = match { self.documents.borrow().find_document(pipeline_id) } {
+                    Some(document) => document,
+                    None => return warn!("Message sent to closed pipeline {}.", pipeline_id),
+                };
+                document.handle_pointer_lock_move_event(movement, pressed_mouse_buttons);
+            },
+
             WheelEvent(delta, point, node_address) => {
                 self.handle_wheel_event(pipeline_id, delta, point, node_address);
             },
@@ -3554,8 +3906,6 @@ impl ScriptThread {
                 document.dispatch_composition_event(composition_event);
             },
         }
-
-        ScriptThread::set_user_interacting(false);
     }
 
     fn handle_mouse_event(
@@ -3701,11 +4051,14 @@ impl ScriptThread {
             window.window_size(),
             new_size
         );
+        let viewport_size_changed =
+            window.window_size().initial_viewport != new_size.initial_viewport;
         window.set_window_size(new_size);
         window.force_reflow(ReflowGoal::Full, ReflowReason::WindowResize);
 
         // http://dev.w3.org/csswg/cssom-view/#resizing-viewports
-        if size_type == WindowSizeType::Resize {
+        // A change of the device pixel ratio alone doesn't resize the viewport.
+        if size_type == WindowSizeType::Resize && viewport_size_changed {
             let uievent = UIEvent::new(
                 &window,
                 DOMString::from("resize"),
@@ -3715,6 +4068,7 @@ impl ScriptThread {
                 0i32,
             );
             uievent.upcast::<Event>().fire(window.upcast());
+            window.queue_visual_viewport_event(atom!("resize"));
         }
 
         // https://html.spec.whatwg.org/multipage/#event-loop-processing-model
@@ -3913,7 +4267,48 @@ impl ScriptThread {
         }
     }
 
-    fn handle_media_session_action(&self, pipeline_id: PipelineId, action: MediaSessionActionType) {
+    fn handle_largest_contentful_paint(
+        &self,
+        pipeline_id: PipelineId,
+        render_time: u64,
+        candidate: LargestContentfulPaintCandidate,
+    ) {
+        let document = match self.documents.borrow().find_document(pipeline_id) {
+            Some(document) => document,
+            None => return,
+        };
+        if let Some(node) = find_painted_node(&document, candidate.node) {
+            document
+                .window()
+                .Performance()
+                .queue_largest_contentful_paint(render_time.to_ms(), &node, candidate.size);
+        }
+    }
+
+    fn handle_layout_shift(&self, pipeline_id: PipelineId, time: u64, shift: LayoutShift) {
+        let document = match self.documents.borrow().find_document(pipeline_id) {
+            Some(document) => document,
+            None => return,
+        };
+        let sources: Vec<_> = shift
+            .sources
+            .iter()
+            .filter_map(|source| {
+                find_painted_node(&document, source.node)
+                    .map(|node| (node, source.previous_rect, source.current_rect))
+            })
+            .collect();
+        document
+            .window()
+            .Performance()
+            .queue_layout_shift(time, shift.value, &sources);
+    }
+
+    fn handle_media_session_action(
+        &self,
+        pipeline_id: PipelineId,
+        action: MediaSessionActionDetails,
+    ) {
         if let Some(window) = self.documents.borrow().find_window(pipeline_id) {
             let media_session = window.Navigator().MediaSession();
             media_session.handle_action(action);
@@ -3922,6 +4317,186 @@ impl ScriptThread {
         };
     }
 
+    fn handle_find(&self, pipeline_id: PipelineId, string: String, options: FindOptions) {
+        match self.documents.borrow().find_document(pipeline_id) {
+            Some(document) => find_in_page::find(&document, string, options),
+            None => warn!("Find sent to nonexistent pipeline {}", pipeline_id),
+        }
+    }
+
+    fn handle_stop_finding(&self, pipeline_id: PipelineId) {
+        if let Some(document) = self.documents.borrow().find_document(pipeline_id) {
+            find_in_page::stop_finding(&document);
+        }
+    }
+
+    fn handle_extract_article(&self, pipeline_id: PipelineId) {
+        match self.documents.borrow().find_document(pipeline_id) {
+            Some(document) => {
+                let article = reader_mode::extract_article(&document);
+                document
+                    .window()
+                    .send_to_embedder(EmbedderMsg::ReaderModeArticle(article));
+            },
+            None => warn!(
+                "ExtractArticle sent to nonexistent pipeline {}",
+                pipeline_id
+            ),
+        }
+    }
+
+    fn handle_request_accessibility_tree(&self, pipeline_id: PipelineId) {
+        match self.documents.borrow().find_document(pipeline_id) {
+            Some(document) => {
+                let tree = accessibility::accessibility_tree(&document);
+                document
+                    .window()
+                    .send_to_embedder(EmbedderMsg::AccessibilityTree(tree));
+            },
+            None => warn!(
+                "RequestAccessibilityTree sent to nonexistent pipeline {}",
+                pipeline_id
+            ),
+        }
+    }
+
+    fn handle_request_form_fields(&self, pipeline_id: PipelineId) {
+        match self.documents.borrow().find_document(pipeline_id) {
+            Some(document) => {
+                let fields = autofill::form_fields(&document);
+                document
+                    .window()
+                    .send_to_embedder(EmbedderMsg::FormFields(fields));
+            },
+            None => warn!(
+                "RequestFormFields sent to nonexistent pipeline {}",
+                pipeline_id
+            ),
+        }
+    }
+
+    fn handle_fill_form_fields(&self, pipeline_id: PipelineId, values: Vec<(String, String)>) {
+        // The documents are not kept borrowed while the events that are fired run script.
+        let document = self.documents.borrow().find_document(pipeline_id);
+        match document {
+            Some(document) => autofill::fill_form_fields(&document, values),
+            None => warn!(
+                "FillFormFields sent to nonexistent pipeline {}",
+                pipeline_id
+            ),
+        }
+    }
+
+    fn handle_fill_credentials(&self, pipeline_id: PipelineId, credentials: Credentials) {
+        let document = self.documents.borrow().find_document(pipeline_id);
+        match document {
+            Some(document) => password_manager::fill_credentials(&document, credentials),
+            None => warn!(
+                "FillCredentials sent to nonexistent pipeline {}",
+                pipeline_id
+            ),
+        }
+    }
+
+    /// Frees as much memory as possible when the system is running low on it: drops the
+    /// decoded images which aren't displayed, then runs a shrinking garbage collection, which
+    /// compacts the JS heap and releases its empty arenas.
+    #[allow(unsafe_code)]
+    fn handle_memory_pressure(&self) {
+        for (_, document) in self.documents.borrow().iter() {
+            document.window().image_cache().purge();
+        }
+        unsafe {
+            PrepareForFullGC(*self.get_cx());
+            NonIncrementalGC(
+                *self.get_cx(),
+                JSGCInvocationKind::GC_SHRINK,
+                GCReason::MEM_PRESSURE,
+            );
+        }
+    }
+
+    /// Stores the new settings of the user in the preferences of this process, which style
+    /// reads them from, and restyles the active documents.
+    fn handle_media_preferences_changed(&self, preferences: MediaPreferences) {
+        let color_scheme = match preferences.color_scheme {
+            ColorSchemePreference::Light => "light",
+            ColorSchemePreference::Dark => "dark",
+        };
+        set_pref!(layout.prefers_color_scheme, color_scheme.to_owned());
+        set_pref!(layout.prefers_reduced_motion, preferences.reduced_motion);
+        set_pref!(layout.forced_colors, preferences.forced_colors);
+
+        let documents: Vec<DomRoot<Document>> = self
+            .documents
+            .borrow()
+            .iter()
+            .map(|(_, document)| document)
+            .filter(|document| document.is_fully_active())
+            .collect();
+        for document in documents {
+            document.window().media_preferences_changed();
+        }
+    }
+
+    fn handle_device_status_changed(
+        &self,
+        battery_status: BatteryStatus,
+        network_information: NetworkInformation,
+    ) {
+        let documents: Vec<DomRoot<Document>> = self
+            .documents
+            .borrow()
+            .iter()
+            .map(|(_, document)| document)
+            .filter(|document| document.is_fully_active())
+            .collect();
+        for document in documents {
+            document
+                .window()
+                .device_status_changed(battery_status, network_information);
+        }
+    }
+
+    fn handle_update_position(
+        &self,
+        pipeline_id: PipelineId,
+        position: Result<GeolocationPosition, String>,
+    ) {
+        if let Some(window) = self.documents.borrow().find_window(pipeline_id) {
+            window.position_changed(position);
+        }
+    }
+
+    fn handle_sensor_reading(&self, pipeline_id: PipelineId, reading: SensorReading) {
+        if let Some(window) = self.documents.borrow().find_window(pipeline_id) {
+            window.sensor_reading(reading);
+        }
+    }
+
+    fn handle_update_user_activation(&self, pipeline_id: PipelineId, update: UserActivationUpdate) {
+        if let Some(window) = self.documents.borrow().find_window(pipeline_id) {
+            window.update_user_activation(update);
+        }
+    }
+
+    /// Stores the new text zoom in the preferences of this process, which style reads it from,
+    /// and restyles the active documents, whose font sizes all change.
+    fn handle_set_text_zoom(&self, zoom: f32) {
+        set_pref!(layout.text_zoom, zoom as f64);
+
+        let documents: Vec<DomRoot<Document>> = self
+            .documents
+            .borrow()
+            .iter()
+            .map(|(_, document)| document)
+            .filter(|document| document.is_fully_active())
+            .collect();
+        for document in documents {
+            self.rebuild_and_force_reflow(&document, ReflowReason::TextZoomChanged);
+        }
+    }
+
     pub fn enqueue_microtask(job: Microtask) {
         SCRIPT_THREAD_ROOT.with(|root| {
             let script_thread = unsafe { &*root.get().unwrap() };
@@ -3964,3 +4539,52 @@ fn dom_last_modified(tm: &SystemTime) -> String {
         .unwrap()
         .to_string()
 }
+
+/// Whether the given header is present, and its value is the given token, ignoring any
+/// parameters of the value.
+fn header_has_value(headers: &HeaderMap, name: &str, token: &str) -> bool {
+    headers
+        .get(name)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.split(';').next())
+        .map_or(false, |value| value.trim() == token)
+}
+
+/// Whether the response to a navigation is to be saved to a file instead of being rendered,
+/// because it is an attachment, or there is no way to render its type. The text types that
+/// are not rendered as such are still shown as unknown content.
+/// <https://html.spec.whatwg.org/multipage/#process-a-navigate-response>
+fn should_download(metadata: &Metadata) -> bool {
+    let is_attachment = metadata
+        .headers
+        .as_ref()
+        .and_then(|headers| headers.get(CONTENT_DISPOSITION))
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.split(';').next())
+        .map_or(false, |value| {
+            value.trim().eq_ignore_ascii_case("attachment")
+        });
+    if is_attachment {
+        return true;
+    }
+    let mime: Mime = match metadata.content_type {
+        Some(ref content_type) => content_type.clone().into_inner().into(),
+        None => return false,
+    };
+    match (mime.type_(), mime.subtype()) {
+        (mime::TEXT, _) | (mime::IMAGE, _) | (mime::APPLICATION, mime::XML) => false,
+        (mime::APPLICATION, subtype) => {
+            !(subtype.as_str() == "xhtml" && mime.suffix() == Some(mime::XML))
+        },
+        _ => true,
+    }
+}
+
+/// Finds the node of the given document that layout painted at the given address. Paint
+/// metrics are reported asynchronously, so the node may have been removed and collected since.
+fn find_painted_node(document: &Document, address: UntrustedNodeAddress) -> Option<DomRoot<Node>> {
+    document
+        .upcast::<Node>()
+        .traverse_preorder(ShadowIncluding::Yes)
+        .find(|node| node.to_untrusted_node_address() == address)
+}
@@ -0,0 +1,120 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! The speculation rules that documents list in `<script type=speculationrules>`, which name
+//! the URLs that the user is likely to navigate to next.
+//! <https://wicg.github.io/nav-speculation/speculation-rules.html>
+
+use serde_json::{Map, Value};
+use servo_url::ServoUrl;
+
+/// The keys that a rule may have. Rules with other keys are invalid.
+const RULE_KEYS: &[&str] = &[
+    "source",
+    "urls",
+    "requires",
+    "relative_to",
+    "referrer_policy",
+    "eagerness",
+    "expects_no_vary_search",
+    "tag",
+];
+
+/// <https://wicg.github.io/nav-speculation/speculation-rules.html#speculation-rule-set>
+#[derive(Clone, Default, JSTraceable, MallocSizeOf, PartialEq)]
+pub struct SpeculationRuleSet {
+    /// The URLs whose documents are fetched ahead of a navigation to them.
+    pub prefetch: Vec<ServoUrl>,
+    /// The URLs whose documents are loaded and rendered ahead of a navigation to them.
+    pub prerender: Vec<ServoUrl>,
+}
+
+/// Parses the text of a speculation rules script, resolving its URLs against the base URL of
+/// the document. Invalid rules are skipped, but text that is not a JSON object is an error.
+/// <https://wicg.github.io/nav-speculation/speculation-rules.html#parse-a-speculation-rule-set-string>
+pub fn parse_speculation_rule_set(
+    input: &str,
+    base_url: &ServoUrl,
+) -> Result<SpeculationRuleSet, String> {
+    let parsed: Value = serde_json::from_str(input).map_err(|error| error.to_string())?;
+    let parsed = match parsed {
+        Value::Object(parsed) => parsed,
+        _ => return Err("the speculation rules are not a JSON object".to_owned()),
+    };
+    Ok(SpeculationRuleSet {
+        prefetch: parse_rules(&parsed, "prefetch", base_url),
+        prerender: parse_rules(&parsed, "prerender", base_url),
+    })
+}
+
+fn parse_rules(parsed: &Map<String, Value>, key: &str, base_url: &ServoUrl) -> Vec<ServoUrl> {
+    let rules = match parsed.get(key) {
+        Some(Value::Array(rules)) => rules,
+        Some(_) => {
+            warn!(
+                "Ignoring the {} speculation rules, which are not a list.",
+                key
+            );
+            return vec![];
+        },
+        None => return vec![],
+    };
+    let mut urls = vec![];
+    for rule in rules {
+        match parse_rule(rule, base_url) {
+            Ok(rule_urls) => {
+                for url in rule_urls {
+                    if !urls.contains(&url) {
+                        urls.push(url);
+                    }
+                }
+            },
+            Err(error) => warn!("Ignoring a {} speculation rule: {}.", key, error),
+        }
+    }
+    urls
+}
+
+/// Returns the URLs that a list rule lists. Document rules, which match the links of the
+/// document, are not supported.
+/// <https://wicg.github.io/nav-speculation/speculation-rules.html#parse-a-speculation-rule>
+fn parse_rule(rule: &Value, base_url: &ServoUrl) -> Result<Vec<ServoUrl>, String> {
+    let rule = match rule {
+        Value::Object(rule) => rule,
+        _ => return Err("the rule is not an object".to_owned()),
+    };
+    if let Some(key) = rule.keys().find(|key| !RULE_KEYS.contains(&key.as_str())) {
+        return Err(format!("the rule has the unknown key {:?}", key));
+    }
+    match rule.get("source") {
+        Some(Value::String(ref source)) if source == "list" => {},
+        None if rule.contains_key("urls") => {},
+        _ => return Err("only list rules are supported".to_owned()),
+    }
+    // Rules that require more than a plain fetch, such as one that hides the IP address of
+    // the client, cannot be honored.
+    match rule.get("requires") {
+        Some(Value::Array(ref requirements)) if requirements.is_empty() => {},
+        None => {},
+        _ => return Err("the requirements of the rule are not supported".to_owned()),
+    }
+    let urls = match rule.get("urls") {
+        Some(Value::Array(ref urls)) => urls,
+        _ => return Err("the rule has no list of URLs".to_owned()),
+    };
+    let mut parsed_urls = vec![];
+    for url in urls {
+        let url = match url {
+            Value::String(ref url) => url,
+            _ => return Err("a URL of the rule is not a string".to_owned()),
+        };
+        // URLs that do not parse are skipped, as are those that cannot be fetched over HTTP.
+        if let Ok(url) = base_url.join(url) {
+            if url.scheme() == "http" || url.scheme() == "https" {
+                parsed_urls.push(url);
+            }
+        }
+    }
+    Ok(parsed_urls)
+}
@@ -0,0 +1,320 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Checking the spelling of the editable text of a document with the spell checker of the
+//! embedder.
+//!
+//! The text controls and editing hosts are checked whole once they get the focus and after
+//! each edit, and layout underlines the misspelled words with a squiggle. The word being typed
+//! is only checked once the caret leaves it.
+
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::HTMLFormElementBinding::SelectionMode;
+use crate::dom::bindings::codegen::Bindings::HTMLInputElementBinding::HTMLInputElementMethods;
+use crate::dom::bindings::codegen::Bindings::HTMLTextAreaElementBinding::HTMLTextAreaElementMethods;
+use crate::dom::bindings::codegen::Bindings::NodeBinding::NodeMethods;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::refcounted::Trusted;
+use crate::dom::bindings::root::{Dom, DomRoot, LayoutDom};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::characterdata::CharacterData;
+use crate::dom::element::Element;
+use crate::dom::eventtarget::EventTarget;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::htmlinputelement::{HTMLInputElement, InputType};
+use crate::dom::htmltextareaelement::HTMLTextAreaElement;
+use crate::dom::node::{document_from_node, window_from_node, Node, NodeDamage, ShadowIncluding};
+use crate::dom::text::Text;
+use crate::editing::{self, utf8_to_utf16_offset};
+use embedder_traits::ContextMenuAction;
+use euclid::default::Point2D;
+use profile_traits::ipc as profiled_ipc;
+use script_layout_interface::rpc::TextIndexResponse;
+use script_traits::ScriptMsg;
+use std::ops::Range;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// A misspelled word, as a UTF-8 byte range of a text node or of the value of a text control.
+#[derive(JSTraceable, MallocSizeOf, PartialEq)]
+#[unrooted_must_root_lint::must_root]
+pub struct SpellingError {
+    node: Dom<Node>,
+    start: usize,
+    end: usize,
+}
+
+impl SpellingError {
+    /// The misspelled range of `node`, if this error is in it.
+    #[allow(unsafe_code)]
+    pub unsafe fn range_for_layout(&self, node: LayoutDom<Node>) -> Option<Range<usize>> {
+        if self.node.to_layout() != node {
+            return None;
+        }
+        Some(self.start..self.end)
+    }
+}
+
+/// Whether the spelling of the text of `element` is checked, according to the `spellcheck`
+/// attribute of its closest inclusive ancestor that has a valid one. It is checked by default.
+/// <https://html.spec.whatwg.org/multipage/#spelling-and-grammar-checking>
+pub fn is_enabled(element: &Element) -> bool {
+    let ancestors = element
+        .upcast::<Node>()
+        .inclusive_ancestors(ShadowIncluding::No)
+        .filter_map(DomRoot::downcast::<Element>);
+    for ancestor in ancestors {
+        let value = match ancestor.get_attribute(&ns!(), &local_name!("spellcheck")) {
+            Some(attr) => attr.value().to_ascii_lowercase(),
+            None => continue,
+        };
+        match &*value {
+            "" | "true" => return true,
+            "false" => return false,
+            _ => {},
+        }
+    }
+    true
+}
+
+/// The element whose text is checked together with the text of `node`: the text control that
+/// it is, or the editing host that it is in.
+pub fn checked_element(node: &Node) -> Option<DomRoot<Element>> {
+    if let Some(input) = node.downcast::<HTMLInputElement>() {
+        return match input.input_type() {
+            InputType::Text | InputType::Search => Some(DomRoot::from_ref(input.upcast())),
+            _ => None,
+        };
+    }
+    if node.is::<HTMLTextAreaElement>() {
+        return Some(DomRoot::from_ref(node.downcast::<Element>().unwrap()));
+    }
+    node.editing_host()
+}
+
+/// The nodes that hold the text of `element` to check, with their text.
+fn checked_text(element: &Element) -> Vec<(DomRoot<Node>, DOMString)> {
+    if let Some(area) = element.downcast::<HTMLTextAreaElement>() {
+        return vec![(DomRoot::from_ref(element.upcast()), area.Value())];
+    }
+    if let Some(input) = element.downcast::<HTMLInputElement>() {
+        return vec![(DomRoot::from_ref(element.upcast()), input.Value())];
+    }
+    element
+        .upcast::<Node>()
+        .traverse_preorder(ShadowIncluding::No)
+        .filter(|node| {
+            node.is::<Text>() &&
+                node.editing_host().as_deref() == Some(element) &&
+                node
+                    .GetParentElement()
+                    .map_or(true, |parent| is_enabled(&parent))
+        })
+        .map(|node| {
+            let data = node.downcast::<CharacterData>().unwrap().data().clone();
+            (node, data)
+        })
+        .collect()
+}
+
+/// The text of `node`, a text node or a text control.
+fn text_of(node: &Node) -> DOMString {
+    if let Some(data) = node.downcast::<CharacterData>() {
+        data.data().clone()
+    } else if let Some(area) = node.downcast::<HTMLTextAreaElement>() {
+        area.Value()
+    } else if let Some(input) = node.downcast::<HTMLInputElement>() {
+        input.Value()
+    } else {
+        DOMString::new()
+    }
+}
+
+/// The UTF-8 byte ranges of the words of `text` whose spelling is checked, which leaves out
+/// the ones with digits.
+fn words(text: &str) -> Vec<Range<usize>> {
+    text.split_word_bound_indices()
+        .filter(|&(_, word)| {
+            word.chars().any(char::is_alphabetic) && !word.chars().any(char::is_numeric)
+        })
+        .map(|(start, word)| start..start + word.len())
+        .collect()
+}
+
+/// The node that holds the caret of `element`, with the UTF-8 offset of the caret in its
+/// text, if `element` is focused and has a collapsed selection.
+fn caret(element: &Element) -> Option<(DomRoot<Node>, usize)> {
+    let document = document_from_node(element);
+    if document.get_focused_element().as_deref() != Some(element) {
+        return None;
+    }
+    let (start, end) = if let Some(area) = element.downcast::<HTMLTextAreaElement>() {
+        (area.GetSelectionStart()?, area.GetSelectionEnd()?)
+    } else if let Some(input) = element.downcast::<HTMLInputElement>() {
+        (input.GetSelectionStart()?, input.GetSelectionEnd()?)
+    } else {
+        let (text, offset) = editing::focused_caret(&document)?;
+        return Some((DomRoot::upcast(text), offset));
+    };
+    if start != end {
+        return None;
+    }
+    let text = text_of(element.upcast());
+    Some((
+        DomRoot::from_ref(element.upcast()),
+        editing::utf16_to_utf8_offset(&text, start),
+    ))
+}
+
+/// Checks the spelling of the text of `element`, and replaces its spelling errors in `errors`,
+/// dirtying the nodes whose errors changed.
+#[allow(unrooted_must_root)]
+pub fn check(element: &Element, errors: &DomRefCell<Vec<SpellingError>>) {
+    let text = if element.upcast::<Node>().is_connected() && is_enabled(element) {
+        checked_text(element)
+    } else {
+        vec![]
+    };
+    let caret = caret(element);
+    let mut candidates = vec![];
+    for (index, &(ref node, ref data)) in text.iter().enumerate() {
+        for range in words(data) {
+            let typing = caret.as_ref().map_or(false, |&(ref caret_node, offset)| {
+                caret_node == node && range.start <= offset && offset <= range.end
+            });
+            if !typing {
+                candidates.push((index, range));
+            }
+        }
+    }
+
+    let misspelled = if candidates.is_empty() {
+        vec![]
+    } else {
+        let words = candidates
+            .iter()
+            .map(|&(index, ref range)| text[index].1[range.clone()].to_owned())
+            .collect();
+        let window = window_from_node(element);
+        let global = window.upcast::<GlobalScope>();
+        let (sender, receiver) =
+            profiled_ipc::channel(global.time_profiler_chan().clone()).unwrap();
+        window.send_to_constellation(ScriptMsg::CheckSpelling(element.get_lang(), words, sender));
+        receiver.recv().unwrap_or_default()
+    };
+    let new_errors: Vec<SpellingError> = candidates
+        .into_iter()
+        .zip(misspelled)
+        .filter(|&(_, misspelled)| misspelled)
+        .map(|((index, range), _)| SpellingError {
+            node: Dom::from_ref(&*text[index].0),
+            start: range.start,
+            end: range.end,
+        })
+        .collect();
+
+    let mut errors = errors.borrow_mut();
+    let root = element.upcast::<Node>();
+    let (old_errors, mut kept): (Vec<_>, Vec<_>) = errors
+        .drain(..)
+        .partition(|error| root.is_inclusive_ancestor_of(&error.node));
+    if old_errors != new_errors {
+        for error in old_errors.iter().chain(new_errors.iter()) {
+            error.node.dirty(NodeDamage::OtherNodeDamage);
+        }
+    }
+    // Errors in nodes that left the document are never painted again.
+    kept.retain(|error| error.node.is_connected());
+    kept.extend(new_errors);
+    *errors = kept;
+}
+
+/// A misspelled word that the user pressed with the secondary button, with the corrections to
+/// suggest for it in the context menu.
+#[derive(Clone)]
+pub struct Misspelling {
+    /// The text node or the text control that the word is in.
+    target: Trusted<Node>,
+    /// The UTF-8 byte range of the word in the text of `target`.
+    range: Range<usize>,
+    pub word: String,
+    pub suggestions: Vec<String>,
+}
+
+/// The misspelled word at `point_in_target` in `target`, a text node or a text control, if any.
+pub fn misspelling_at(target: &Node, point_in_target: Point2D<f32>) -> Option<Misspelling> {
+    let document = document_from_node(target);
+    let window = window_from_node(target);
+    let TextIndexResponse(index) = window.text_index_query(target, point_in_target);
+    let index = index?;
+    let range = document
+        .spelling_errors()
+        .borrow()
+        .iter()
+        .find(|error| &*error.node == target && error.start <= index && index < error.end)
+        .map(|error| error.start..error.end)?;
+    let word = text_of(target).get(range.clone())?.to_owned();
+    let lang = target
+        .inclusive_ancestors(ShadowIncluding::No)
+        .filter_map(DomRoot::downcast::<Element>)
+        .next()
+        .map_or(String::new(), |element| element.get_lang());
+
+    let global = window.upcast::<GlobalScope>();
+    let (sender, receiver) = profiled_ipc::channel(global.time_profiler_chan().clone()).unwrap();
+    window.send_to_constellation(ScriptMsg::GetSpellingSuggestions(
+        lang,
+        word.clone(),
+        sender,
+    ));
+    Some(Misspelling {
+        target: Trusted::new(target),
+        range,
+        word,
+        suggestions: receiver.recv().unwrap_or_default(),
+    })
+}
+
+impl Misspelling {
+    /// Carries out what the user picked in the context menu for the word, unless the text
+    /// changed since.
+    pub fn apply(&self, action: ContextMenuAction) {
+        let target = self.target.root();
+        let text = text_of(&target);
+        if text.get(self.range.clone()) != Some(&*self.word) {
+            return;
+        }
+        let element = match checked_element(&target) {
+            Some(element) => element,
+            None => return,
+        };
+        match action {
+            ContextMenuAction::AddToDictionary => {
+                window_from_node(&*target)
+                    .send_to_constellation(ScriptMsg::AddToDictionary(self.word.clone()));
+            },
+            ContextMenuAction::ReplaceMisspelling(replacement) => {
+                let start = utf8_to_utf16_offset(&text, self.range.start);
+                let end = utf8_to_utf16_offset(&text, self.range.end);
+                let replacement = DOMString::from(replacement);
+                if let Some(text) = target.downcast::<Text>() {
+                    editing::replace_text(&element, text, start, end, replacement);
+                } else {
+                    let replaced = if let Some(area) = target.downcast::<HTMLTextAreaElement>() {
+                        area.SetRangeText_(replacement, start, end, SelectionMode::End)
+                    } else if let Some(input) = target.downcast::<HTMLInputElement>() {
+                        input.SetRangeText_(replacement, start, end, SelectionMode::End)
+                    } else {
+                        return;
+                    };
+                    if replaced.is_ok() {
+                        target
+                            .upcast::<EventTarget>()
+                            .fire_bubbling_event(atom!("input"));
+                    }
+                }
+            },
+        }
+        document_from_node(&*target).request_spellcheck(&element);
+    }
+}
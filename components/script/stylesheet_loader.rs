@@ -8,7 +8,7 @@ use crate::dom::bindings::refcounted::Trusted;
 use crate::dom::bindings::reflector::DomObject;
 use crate::dom::bindings::root::DomRoot;
 use crate::dom::document::Document;
-use crate::dom::element::Element;
+use crate::dom::element::{fetch_priority_for_element, Element};
 use crate::dom::eventtarget::EventTarget;
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::htmlelement::HTMLElement;
@@ -18,13 +18,14 @@ use crate::dom::performanceresourcetiming::InitiatorType;
 use crate::dom::shadowroot::ShadowRoot;
 use crate::fetch::create_a_potential_cors_request;
 use crate::network_listener::{self, NetworkListener, PreInvoke, ResourceTimingListener};
+use crate::task_source::TaskSource;
 use cssparser::SourceLocation;
 use encoding_rs::UTF_8;
 use ipc_channel::ipc;
 use ipc_channel::router::ROUTER;
 use mime::{self, Mime};
 use msg::constellation_msg::PipelineId;
-use net_traits::request::{CorsSettings, Destination, Referrer, RequestBuilder};
+use net_traits::request::{CorsSettings, Destination, Referrer, RequestBuilder, RequestPriority};
 use net_traits::{
     FetchMetadata, FetchResponseListener, FilteredMetadata, Metadata, NetworkError, ReferrerPolicy,
 };
@@ -36,6 +37,7 @@ use servo_url::ServoUrl;
 use std::mem;
 use std::sync::atomic::AtomicBool;
 use std::sync::Mutex;
+use style::global_style_data::STYLE_THREAD_POOL;
 use style::media_queries::MediaList;
 use style::parser::ParserContext;
 use style::shared_lock::{Locked, SharedRwLock};
@@ -46,6 +48,10 @@ use style::stylesheets::{
 };
 use style::values::CssUrl;
 
+/// The size in bytes from which linked stylesheets are parsed on the style thread pool rather
+/// than on the script thread.
+const OFF_THREAD_PARSING_THRESHOLD: usize = 64 * 1024;
+
 pub trait StylesheetOwner {
     /// Returns whether this element was inserted by the parser (i.e., it should
     /// trigger a document-load-blocking load).
@@ -119,75 +125,202 @@ impl FetchResponseListener for StylesheetContext {
     }
 
     fn process_response_eof(&mut self, status: Result<ResourceFetchTiming, NetworkError>) {
+        let end = StylesheetLoadEnd {
+            elem: self.elem.clone(),
+            document: self.document.clone(),
+            shadow_root: self.shadow_root.clone(),
+            url: self.url.clone(),
+            origin_clean: self.origin_clean,
+            request_generation_id: self.request_generation_id,
+        };
+
+        if status.is_err() {
+            return end.finish(false, None, false);
+        }
+        let metadata = match self.metadata.take() {
+            Some(meta) => meta,
+            None => return,
+        };
+        let is_css = metadata.content_type.map_or(false, |ct| {
+            let mime: Mime = ct.into_inner().into();
+            mime.type_() == mime::TEXT && mime.subtype() == mime::CSS
+        });
+
+        let data = if is_css {
+            mem::replace(&mut self.data, vec![])
+        } else {
+            vec![]
+        };
+
+        // TODO: Get the actual value. http://dev.w3.org/csswg/css-syntax/#environment-encoding
+        let environment_encoding = UTF_8;
+        let protocol_encoding_label = metadata.charset.as_ref().map(|s| &**s);
+        let final_url = metadata.final_url;
+
+        // FIXME: Revisit once consensus is reached at:
+        // https://github.com/whatwg/html/issues/1142
+        let successful = metadata.status.map_or(false, |(code, _)| code == 200);
+
         let elem = self.elem.root();
         let document = self.document.root();
-        let mut successful = false;
+        let win = window_from_node(&*elem);
+
+        let loader = StylesheetLoader::for_element(&elem);
+        match self.source {
+            StylesheetContextSource::LinkElement { ref mut media } => {
+                if !end.is_stylesheet_load_applicable() {
+                    return end.finish(true, None, successful);
+                }
+                let media = media.take().unwrap();
+                let shared_lock = document.style_shared_lock().clone();
+                let quirks_mode = document.quirks_mode();
+
+                if data.len() >= OFF_THREAD_PARSING_THRESHOLD {
+                    if let Some(pool) = STYLE_THREAD_POOL.pool().as_ref() {
+                        let protocol_encoding_label = protocol_encoding_label.map(String::from);
+                        let error_reporter = win.clone_css_error_reporter();
+                        let (task_source, canceller) =
+                            win.task_manager().networking_task_source_with_canceller();
+                        pool.spawn(move || {
+                            let loader = DeferredStylesheetLoader::default();
+                            let sheet = Arc::new(Stylesheet::from_bytes(
+                                &data,
+                                final_url,
+                                protocol_encoding_label.as_ref().map(|s| &**s),
+                                Some(environment_encoding),
+                                Origin::Author,
+                                media,
+                                shared_lock,
+                                Some(&loader),
+                                Some(&error_reporter),
+                                quirks_mode,
+                            ));
+                            let imports = loader.imports.into_inner().unwrap();
+                            let _ = task_source.queue_with_canceller(
+                                task!(stylesheet_parsed: move || {
+                                    end.load_imports(imports);
+                                    end.finish(true, Some(sheet), successful);
+                                }),
+                                &canceller,
+                            );
+                        });
+                        return;
+                    }
+                }
+
+                let sheet = Arc::new(Stylesheet::from_bytes(
+                    &data,
+                    final_url,
+                    protocol_encoding_label,
+                    Some(environment_encoding),
+                    Origin::Author,
+                    media,
+                    shared_lock,
+                    Some(&loader),
+                    win.css_error_reporter(),
+                    quirks_mode,
+                ));
+                end.finish(true, Some(sheet), successful);
+            },
+            StylesheetContextSource::Import(ref stylesheet) => {
+                Stylesheet::update_from_bytes(
+                    &stylesheet,
+                    &data,
+                    protocol_encoding_label,
+                    Some(environment_encoding),
+                    final_url,
+                    Some(&loader),
+                    win.css_error_reporter(),
+                );
+                end.finish(true, None, successful);
+            },
+        }
+    }
 
-        if status.is_ok() {
-            let metadata = match self.metadata.take() {
-                Some(meta) => meta,
-                None => return,
-            };
-            let is_css = metadata.content_type.map_or(false, |ct| {
-                let mime: Mime = ct.into_inner().into();
-                mime.type_() == mime::TEXT && mime.subtype() == mime::CSS
-            });
+    fn resource_timing_mut(&mut self) -> &mut ResourceFetchTiming {
+        &mut self.resource_timing
+    }
 
-            let data = if is_css {
-                mem::replace(&mut self.data, vec![])
-            } else {
-                vec![]
-            };
+    fn resource_timing(&self) -> &ResourceFetchTiming {
+        &self.resource_timing
+    }
+
+    fn submit_resource_timing(&mut self) {
+        network_listener::submit_timing(self)
+    }
+}
 
-            // TODO: Get the actual value. http://dev.w3.org/csswg/css-syntax/#environment-encoding
-            let environment_encoding = UTF_8;
-            let protocol_encoding_label = metadata.charset.as_ref().map(|s| &**s);
-            let final_url = metadata.final_url;
+impl ResourceTimingListener for StylesheetContext {
+    fn resource_timing_information(&self) -> (InitiatorType, ServoUrl) {
+        let initiator_type = InitiatorType::LocalName(
+            self.elem
+                .root()
+                .upcast::<Element>()
+                .local_name()
+                .to_string(),
+        );
+        (initiator_type, self.url.clone())
+    }
+
+    fn resource_timing_global(&self) -> DomRoot<GlobalScope> {
+        document_from_node(&*self.elem.root()).global()
+    }
+}
 
-            let win = window_from_node(&*elem);
+/// The end of the load of an external stylesheet, which runs on the script thread once its
+/// stylesheet has been parsed.
+struct StylesheetLoadEnd {
+    elem: Trusted<HTMLElement>,
+    document: Trusted<Document>,
+    shadow_root: Option<Trusted<ShadowRoot>>,
+    url: ServoUrl,
+    origin_clean: bool,
+    request_generation_id: Option<RequestGenerationId>,
+}
 
-            let loader = StylesheetLoader::for_element(&elem);
-            match self.source {
-                StylesheetContextSource::LinkElement { ref mut media } => {
+impl StylesheetLoadEnd {
+    /// Whether the generations of the load and the element match up, else we risk applying
+    /// the wrong stylesheet when responses come out-of-order.
+    fn is_stylesheet_load_applicable(&self) -> bool {
+        let elem = self.elem.root();
+        match elem.downcast::<HTMLLinkElement>() {
+            Some(link) => self
+                .request_generation_id
+                .map_or(true, |gen| gen == link.get_request_generation_id()),
+            None => true,
+        }
+    }
+
+    /// Starts the loads of the `@import` rules of a stylesheet parsed off the script thread.
+    fn load_imports(&self, imports: Vec<(ServoUrl, Arc<Stylesheet>)>) {
+        if !self.is_stylesheet_load_applicable() {
+            return;
+        }
+        let elem = self.elem.root();
+        let loader = StylesheetLoader::for_element(&elem);
+        for (url, sheet) in imports {
+            // TODO (mrnayak) : Whether we should use the original loader's CORS
+            // setting? Fix this when spec has more details.
+            let source = StylesheetContextSource::Import(sheet);
+            loader.load(source, url, None, "".to_owned());
+        }
+    }
+
+    /// Finishes the load, setting the parsed stylesheet of a link element, if any, once the
+    /// stylesheet has been fetched.
+    fn finish(self, fetched: bool, sheet: Option<Arc<Stylesheet>>, successful: bool) {
+        let elem = self.elem.root();
+        let document = self.document.root();
+
+        if fetched {
+            if let Some(sheet) = sheet {
+                if self.is_stylesheet_load_applicable() {
                     let link = elem.downcast::<HTMLLinkElement>().unwrap();
-                    // We must first check whether the generations of the context and the element match up,
-                    // else we risk applying the wrong stylesheet when responses come out-of-order.
-                    let is_stylesheet_load_applicable = self
-                        .request_generation_id
-                        .map_or(true, |gen| gen == link.get_request_generation_id());
-                    if is_stylesheet_load_applicable {
-                        let shared_lock = document.style_shared_lock().clone();
-                        let sheet = Arc::new(Stylesheet::from_bytes(
-                            &data,
-                            final_url,
-                            protocol_encoding_label,
-                            Some(environment_encoding),
-                            Origin::Author,
-                            media.take().unwrap(),
-                            shared_lock,
-                            Some(&loader),
-                            win.css_error_reporter(),
-                            document.quirks_mode(),
-                        ));
-
-                        if link.is_alternate() {
-                            sheet.set_disabled(true);
-                        }
-
-                        link.set_stylesheet(sheet);
+                    if link.is_alternate() {
+                        sheet.set_disabled(true);
                     }
-                },
-                StylesheetContextSource::Import(ref stylesheet) => {
-                    Stylesheet::update_from_bytes(
-                        &stylesheet,
-                        &data,
-                        protocol_encoding_label,
-                        Some(environment_encoding),
-                        final_url,
-                        Some(&loader),
-                        win.css_error_reporter(),
-                    );
-                },
+                    link.set_stylesheet(sheet);
+                }
             }
 
             if let Some(ref shadow_root) = self.shadow_root {
@@ -195,10 +328,6 @@ impl FetchResponseListener for StylesheetContext {
             } else {
                 document.invalidate_stylesheets();
             }
-
-            // FIXME: Revisit once consensus is reached at:
-            // https://github.com/whatwg/html/issues/1142
-            successful = metadata.status.map_or(false, |(code, _)| code == 200);
         }
 
         let owner = elem
@@ -221,35 +350,6 @@ impl FetchResponseListener for StylesheetContext {
             elem.upcast::<EventTarget>().fire_event(event);
         }
     }
-
-    fn resource_timing_mut(&mut self) -> &mut ResourceFetchTiming {
-        &mut self.resource_timing
-    }
-
-    fn resource_timing(&self) -> &ResourceFetchTiming {
-        &self.resource_timing
-    }
-
-    fn submit_resource_timing(&mut self) {
-        network_listener::submit_timing(self)
-    }
-}
-
-impl ResourceTimingListener for StylesheetContext {
-    fn resource_timing_information(&self) -> (InitiatorType, ServoUrl) {
-        let initiator_type = InitiatorType::LocalName(
-            self.elem
-                .root()
-                .upcast::<Element>()
-                .local_name()
-                .to_string(),
-        );
-        (initiator_type, self.url.clone())
-    }
-
-    fn resource_timing_global(&self) -> DomRoot<GlobalScope> {
-        document_from_node(&*self.elem.root()).global()
-    }
 }
 
 pub struct StylesheetLoader<'a> {
@@ -314,6 +414,13 @@ impl<'a> StylesheetLoader<'a> {
         let referrer_policy = owner
             .referrer_policy()
             .or_else(|| document.get_referrer_policy());
+        // Only `<link>` has a `fetchpriority` attribute; `@import` rules are fetched with the
+        // `auto` priority.
+        let priority = if self.elem.is::<HTMLLinkElement>() {
+            fetch_priority_for_element(self.elem.upcast())
+        } else {
+            RequestPriority::Auto
+        };
         owner.increment_pending_loads_count();
         if owner.parser_inserted() {
             document.increment_script_blocking_stylesheet_count();
@@ -327,6 +434,7 @@ impl<'a> StylesheetLoader<'a> {
             Referrer::ReferrerUrl(document.url()),
             referrer_policy,
             integrity_metadata,
+            priority,
         );
 
         document.fetch_async(LoadType::Stylesheet(url), request, action_sender);
@@ -343,6 +451,7 @@ pub(crate) fn stylesheet_fetch_request(
     referrer: Referrer,
     referrer_policy: Option<ReferrerPolicy>,
     integrity_metadata: String,
+    priority: RequestPriority,
 ) -> RequestBuilder {
     create_a_potential_cors_request(url, Destination::Style, cors_setting, None)
         .origin(origin)
@@ -350,6 +459,7 @@ pub(crate) fn stylesheet_fetch_request(
         .referrer(Some(referrer))
         .referrer_policy(referrer_policy)
         .integrity_metadata(integrity_metadata)
+        .priority(priority)
 }
 
 impl<'a> StyleStylesheetLoader for StylesheetLoader<'a> {
@@ -363,27 +473,7 @@ impl<'a> StyleStylesheetLoader for StylesheetLoader<'a> {
         lock: &SharedRwLock,
         media: Arc<Locked<MediaList>>,
     ) -> Arc<Locked<ImportRule>> {
-        let sheet = Arc::new(Stylesheet {
-            contents: StylesheetContents {
-                rules: CssRules::new(Vec::new(), lock),
-                origin: context.stylesheet_origin,
-                url_data: RwLock::new(context.url_data.clone()),
-                quirks_mode: context.quirks_mode,
-                namespaces: RwLock::new(Namespaces::default()),
-                source_map_url: RwLock::new(None),
-                source_url: RwLock::new(None),
-            },
-            media: media,
-            shared_lock: lock.clone(),
-            disabled: AtomicBool::new(false),
-        });
-
-        let stylesheet = ImportSheet(sheet.clone());
-        let import = ImportRule {
-            url,
-            source_location,
-            stylesheet,
-        };
+        let (import, sheet) = new_import_rule(url, source_location, context, lock, media);
 
         let url = match import.url.url().cloned() {
             Some(url) => url,
@@ -392,9 +482,66 @@ impl<'a> StyleStylesheetLoader for StylesheetLoader<'a> {
 
         // TODO (mrnayak) : Whether we should use the original loader's CORS
         // setting? Fix this when spec has more details.
-        let source = StylesheetContextSource::Import(sheet.clone());
+        let source = StylesheetContextSource::Import(sheet);
         self.load(source, url, None, "".to_owned());
 
         Arc::new(lock.wrap(import))
     }
 }
+
+/// Creates an `@import` rule, along with its stylesheet which stays empty until the imported
+/// stylesheet is loaded.
+fn new_import_rule(
+    url: CssUrl,
+    source_location: SourceLocation,
+    context: &ParserContext,
+    lock: &SharedRwLock,
+    media: Arc<Locked<MediaList>>,
+) -> (ImportRule, Arc<Stylesheet>) {
+    let sheet = Arc::new(Stylesheet {
+        contents: StylesheetContents {
+            rules: CssRules::new(Vec::new(), lock),
+            origin: context.stylesheet_origin,
+            url_data: RwLock::new(context.url_data.clone()),
+            quirks_mode: context.quirks_mode,
+            namespaces: RwLock::new(Namespaces::default()),
+            source_map_url: RwLock::new(None),
+            source_url: RwLock::new(None),
+        },
+        media: media,
+        shared_lock: lock.clone(),
+        disabled: AtomicBool::new(false),
+    });
+
+    let stylesheet = ImportSheet(sheet.clone());
+    let import = ImportRule {
+        url,
+        source_location,
+        stylesheet,
+    };
+    (import, sheet)
+}
+
+/// A loader for the `@import` rules of a stylesheet parsed off the script thread, which records
+/// the stylesheets to load once the parsed stylesheet is back on the script thread.
+#[derive(Default)]
+struct DeferredStylesheetLoader {
+    imports: Mutex<Vec<(ServoUrl, Arc<Stylesheet>)>>,
+}
+
+impl StyleStylesheetLoader for DeferredStylesheetLoader {
+    fn request_stylesheet(
+        &self,
+        url: CssUrl,
+        source_location: SourceLocation,
+        context: &ParserContext,
+        lock: &SharedRwLock,
+        media: Arc<Locked<MediaList>>,
+    ) -> Arc<Locked<ImportRule>> {
+        let (import, sheet) = new_import_rule(url, source_location, context, lock, media);
+        if let Some(url) = import.url.url().cloned() {
+            self.imports.lock().unwrap().push((url, sheet));
+        }
+        Arc::new(lock.wrap(import))
+    }
+}
@@ -11,6 +11,7 @@ use crate::task_source::media_element::MediaElementTaskSource;
 use crate::task_source::networking::NetworkingTaskSource;
 use crate::task_source::performance_timeline::PerformanceTimelineTaskSource;
 use crate::task_source::port_message::PortMessageQueue;
+use crate::task_source::posted_task::PostedTaskSource;
 use crate::task_source::remote_event::RemoteEventTaskSource;
 use crate::task_source::timer::TimerTaskSource;
 use crate::task_source::user_interaction::UserInteractionTaskSource;
@@ -51,6 +52,8 @@ pub struct TaskManager {
     #[ignore_malloc_size_of = "task sources are hard"]
     port_message_queue: PortMessageQueue,
     #[ignore_malloc_size_of = "task sources are hard"]
+    posted_task_source: PostedTaskSource,
+    #[ignore_malloc_size_of = "task sources are hard"]
     user_interaction_task_source: UserInteractionTaskSource,
     #[ignore_malloc_size_of = "task sources are hard"]
     remote_event_task_source: RemoteEventTaskSource,
@@ -69,6 +72,7 @@ impl TaskManager {
         networking_task_source: NetworkingTaskSource,
         performance_timeline_task_source: PerformanceTimelineTaskSource,
         port_message_queue: PortMessageQueue,
+        posted_task_source: PostedTaskSource,
         user_interaction_task_source: UserInteractionTaskSource,
         remote_event_task_source: RemoteEventTaskSource,
         timer_task_source: TimerTaskSource,
@@ -82,6 +86,7 @@ impl TaskManager {
             networking_task_source,
             performance_timeline_task_source,
             port_message_queue,
+            posted_task_source,
             user_interaction_task_source,
             remote_event_task_source,
             timer_task_source,
@@ -154,6 +159,14 @@ impl TaskManager {
         PortMessage
     );
 
+    task_source_functions!(
+        self,
+        posted_task_source_with_canceller,
+        posted_task_source,
+        PostedTaskSource,
+        PostedTask
+    );
+
     task_source_functions!(
         self,
         remote_event_task_source_with_canceller,
@@ -125,7 +125,8 @@ impl<T: QueuedTaskConversion> TaskQueue<T> {
                     None => return false,
                 };
                 match task_source {
-                    TaskSourceName::PerformanceTimeline => return true,
+                    TaskSourceName::PerformanceTimeline |
+                    TaskSourceName::BackgroundPostedTask => return true,
                     _ => {
                         // A task that will not be throttled, start counting "business"
                         self.taken_task_counter
@@ -183,6 +184,11 @@ impl<T: QueuedTaskConversion> TaskQueue<T> {
         self.recv()
     }
 
+    /// Whether there are tasks waiting to be taken from the queue, or to be received on the port.
+    pub fn has_pending_tasks(&self) -> bool {
+        !self.msg_queue.borrow().is_empty() || !self.port.is_empty()
+    }
+
     /// Drain the queue for the current iteration of the event-loop.
     /// Holding-back throttles above a given high-water mark.
     pub fn take_tasks(&self, first_msg: T) {
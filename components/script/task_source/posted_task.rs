@@ -0,0 +1,72 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::SchedulerBinding::TaskPriority;
+use crate::dom::globalscope::GlobalScope;
+use crate::script_runtime::{CommonScriptMsg, ScriptChan, ScriptThreadEventCategory};
+use crate::task::{TaskCanceller, TaskOnce};
+use crate::task_source::{TaskSource, TaskSourceName};
+use msg::constellation_msg::PipelineId;
+use std::fmt;
+use std::result::Result;
+
+/// <https://wicg.github.io/scheduling-apis/#posted-task-task-source>
+#[derive(JSTraceable)]
+pub struct PostedTaskSource(pub Box<dyn ScriptChan + Send + 'static>, pub PipelineId);
+
+impl Clone for PostedTaskSource {
+    fn clone(&self) -> PostedTaskSource {
+        PostedTaskSource(self.0.clone(), self.1.clone())
+    }
+}
+
+impl fmt::Debug for PostedTaskSource {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "PostedTaskSource(...)")
+    }
+}
+
+impl TaskSource for PostedTaskSource {
+    const NAME: TaskSourceName = TaskSourceName::PostedTask;
+
+    fn queue_with_canceller<T>(&self, task: T, canceller: &TaskCanceller) -> Result<(), ()>
+    where
+        T: TaskOnce + 'static,
+    {
+        let msg = CommonScriptMsg::Task(
+            ScriptThreadEventCategory::ScriptEvent,
+            Box::new(canceller.wrap_task(task)),
+            Some(self.1),
+            PostedTaskSource::NAME,
+        );
+        self.0.send(msg).map_err(|_| ())
+    }
+}
+
+impl PostedTaskSource {
+    /// Queues a task for a posted task of the given priority. Background tasks are queued
+    /// as low priority tasks of the event loop, which are held back while it is busy.
+    pub fn queue_with_priority<T>(
+        &self,
+        task: T,
+        priority: TaskPriority,
+        global: &GlobalScope,
+    ) -> Result<(), ()>
+    where
+        T: TaskOnce + 'static,
+    {
+        let name = match priority {
+            TaskPriority::User_blocking | TaskPriority::User_visible => TaskSourceName::PostedTask,
+            TaskPriority::Background => TaskSourceName::BackgroundPostedTask,
+        };
+        let canceller = global.task_canceller(name.clone());
+        let msg = CommonScriptMsg::Task(
+            ScriptThreadEventCategory::ScriptEvent,
+            Box::new(canceller.wrap_task(task)),
+            Some(self.1),
+            name,
+        );
+        self.0.send(msg).map_err(|_| ())
+    }
+}
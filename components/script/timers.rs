@@ -7,10 +7,13 @@ use crate::dom::bindings::cell::DomRefCell;
 use crate::dom::bindings::codegen::Bindings::FunctionBinding::Function;
 use crate::dom::bindings::reflector::DomObject;
 use crate::dom::bindings::str::DOMString;
-use crate::dom::document::FakeRequestAnimationFrameCallback;
 use crate::dom::eventsource::EventSourceTimeoutCallback;
+use crate::dom::geolocation::GeolocationTimeoutCallback;
 use crate::dom::globalscope::GlobalScope;
+use crate::dom::idledetector::IdleDetectorPollCallback;
+use crate::dom::scheduler::SchedulerTaskDelayCallback;
 use crate::dom::testbinding::TestBindingCallback;
+use crate::dom::window::IdleRequestTimeoutCallback;
 use crate::dom::xmlhttprequest::XHRTimeoutCallback;
 use euclid::Length;
 use ipc_channel::ipc::IpcSender;
@@ -75,7 +78,10 @@ pub enum OneshotTimerCallback {
     EventSourceTimeout(EventSourceTimeoutCallback),
     JsTimer(JsTimerTask),
     TestBindingCallback(TestBindingCallback),
-    FakeRequestAnimationFrame(FakeRequestAnimationFrameCallback),
+    IdleRequestTimeout(IdleRequestTimeoutCallback),
+    SchedulerTaskDelay(SchedulerTaskDelayCallback),
+    GeolocationTimeout(GeolocationTimeoutCallback),
+    IdleDetectorPoll(IdleDetectorPollCallback),
 }
 
 impl OneshotTimerCallback {
@@ -85,7 +91,10 @@ impl OneshotTimerCallback {
             OneshotTimerCallback::EventSourceTimeout(callback) => callback.invoke(),
             OneshotTimerCallback::JsTimer(task) => task.invoke(this, js_timers),
             OneshotTimerCallback::TestBindingCallback(callback) => callback.invoke(),
-            OneshotTimerCallback::FakeRequestAnimationFrame(callback) => callback.invoke(),
+            OneshotTimerCallback::IdleRequestTimeout(callback) => callback.invoke(),
+            OneshotTimerCallback::SchedulerTaskDelay(callback) => callback.invoke(),
+            OneshotTimerCallback::GeolocationTimeout(callback) => callback.invoke(),
+            OneshotTimerCallback::IdleDetectorPoll(callback) => callback.invoke(),
         }
     }
 }
@@ -175,6 +184,21 @@ impl OneshotTimers {
         }
     }
 
+    /// The time until the next timer is due, if there is one and timers are not suspended.
+    pub fn time_until_next_timer(&self) -> Option<MsDuration> {
+        if self.suspended_since.get().is_some() {
+            return None;
+        }
+        let timers = self.timers.borrow();
+        let next_timer = timers.last()?;
+        let base_time = self.base_time();
+        Some(if next_timer.scheduled_for > base_time {
+            next_timer.scheduled_for - base_time
+        } else {
+            Length::new(0)
+        })
+    }
+
     fn is_next_timer(&self, handle: OneshotTimerHandle) -> bool {
         match self.timers.borrow().last() {
             None => false,
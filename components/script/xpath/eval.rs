@@ -0,0 +1,882 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! The evaluation of parsed XPath expressions against the DOM.
+//!
+//! <https://www.w3.org/TR/1999/REC-xpath-19991116/#data-model>
+
+use super::parser::{Axis, BinaryOperator, Expr, Function, NodeTest, PathStart, Step};
+use crate::dom::attr::Attr;
+use crate::dom::bindings::codegen::Bindings::NodeBinding::NodeConstants;
+use crate::dom::bindings::codegen::Bindings::NodeBinding::NodeMethods;
+use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::inheritance::{Castable, CharacterDataTypeId, NodeTypeId};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::characterdata::CharacterData;
+use crate::dom::element::Element;
+use crate::dom::node::{Node, ShadowIncluding};
+use crate::dom::processinginstruction::ProcessingInstruction;
+use crate::dom::text::Text;
+use std::cmp::Ordering;
+
+/// The result of evaluating an XPath expression.
+/// <https://www.w3.org/TR/1999/REC-xpath-19991116/#section-Expressions>
+#[derive(Clone)]
+pub enum Value {
+    /// The nodes in document order, without duplicates.
+    NodeSet(Vec<DomRoot<Node>>),
+    Boolean(bool),
+    Number(f64),
+    String(String),
+}
+
+impl Value {
+    /// <https://www.w3.org/TR/1999/REC-xpath-19991116/#function-boolean>
+    pub fn boolean(&self) -> bool {
+        match *self {
+            Value::NodeSet(ref nodes) => !nodes.is_empty(),
+            Value::Boolean(boolean) => boolean,
+            Value::Number(number) => number != 0. && !number.is_nan(),
+            Value::String(ref string) => !string.is_empty(),
+        }
+    }
+
+    /// <https://www.w3.org/TR/1999/REC-xpath-19991116/#function-number>
+    pub fn number(&self) -> f64 {
+        match *self {
+            Value::NodeSet(_) => string_to_number(&self.string()),
+            Value::Boolean(boolean) => {
+                if boolean {
+                    1.
+                } else {
+                    0.
+                }
+            },
+            Value::Number(number) => number,
+            Value::String(ref string) => string_to_number(string),
+        }
+    }
+
+    /// <https://www.w3.org/TR/1999/REC-xpath-19991116/#function-string>
+    pub fn string(&self) -> String {
+        match *self {
+            Value::NodeSet(ref nodes) => nodes
+                .first()
+                .map_or(String::new(), |node| string_value(node)),
+            Value::Boolean(boolean) => boolean.to_string(),
+            Value::Number(number) => number_to_string(number),
+            Value::String(ref string) => string.clone(),
+        }
+    }
+
+    pub fn into_node_set(self) -> Fallible<Vec<DomRoot<Node>>> {
+        match self {
+            Value::NodeSet(nodes) => Ok(nodes),
+            _ => Err(Error::Type(
+                "The expression doesn't evaluate to nodes".to_owned(),
+            )),
+        }
+    }
+}
+
+/// <https://www.w3.org/TR/1999/REC-xpath-19991116/#dt-string-value>
+pub fn string_value(node: &Node) -> String {
+    match node.type_id() {
+        NodeTypeId::Document(_) | NodeTypeId::DocumentFragment(_) | NodeTypeId::Element(_) => node
+            .traverse_preorder(ShadowIncluding::No)
+            .filter_map(DomRoot::downcast::<Text>)
+            .map(|text| text.upcast::<CharacterData>().data().to_string())
+            .collect(),
+        NodeTypeId::Attr => node.downcast::<Attr>().unwrap().value().to_string(),
+        NodeTypeId::CharacterData(_) => {
+            node.downcast::<CharacterData>().unwrap().data().to_string()
+        },
+        NodeTypeId::DocumentType => String::new(),
+    }
+}
+
+/// <https://www.w3.org/TR/1999/REC-xpath-19991116/#function-number>
+fn string_to_number(string: &str) -> f64 {
+    let string = string.trim_matches(|c| c == ' ' || c == '\t' || c == '\r' || c == '\n');
+    let digits = if string.starts_with('-') {
+        &string[1..]
+    } else {
+        string
+    };
+    let valid = !digits.is_empty() &&
+        digits != "." &&
+        digits.chars().all(|c| c.is_ascii_digit() || c == '.') &&
+        digits.matches('.').count() <= 1;
+    if !valid {
+        return std::f64::NAN;
+    }
+    string.parse().unwrap_or(std::f64::NAN)
+}
+
+/// <https://www.w3.org/TR/1999/REC-xpath-19991116/#function-string>
+fn number_to_string(number: f64) -> String {
+    if number.is_nan() {
+        return "NaN".to_owned();
+    }
+    if number.is_infinite() {
+        return if number > 0. { "Infinity" } else { "-Infinity" }.to_owned();
+    }
+    if number == 0. {
+        return "0".to_owned();
+    }
+    // Rust doesn't use exponents, and drops the fraction of integers.
+    number.to_string()
+}
+
+/// <https://www.w3.org/TR/1999/REC-xpath-19991116/#function-round>
+fn round(number: f64) -> f64 {
+    if number.is_nan() || number.is_infinite() {
+        return number;
+    }
+    if number < 0. && number >= -0.5 {
+        return -0.;
+    }
+    (number + 0.5).floor()
+}
+
+/// The root of the tree of a node, which for an attribute is the one of its element.
+fn root(node: &Node) -> DomRoot<Node> {
+    let node = match node.downcast::<Attr>().and_then(|attr| attr.owner()) {
+        Some(owner) => DomRoot::upcast(owner),
+        None => DomRoot::from_ref(node),
+    };
+    node.inclusive_ancestors(ShadowIncluding::No)
+        .last()
+        .unwrap()
+}
+
+/// Orders nodes in document order.
+fn document_order(a: &DomRoot<Node>, b: &DomRoot<Node>) -> Ordering {
+    if a == b {
+        return Ordering::Equal;
+    }
+    if a.CompareDocumentPosition(b) & NodeConstants::DOCUMENT_POSITION_FOLLOWING != 0 {
+        Ordering::Less
+    } else {
+        Ordering::Greater
+    }
+}
+
+/// Sorts nodes in document order and removes their duplicates, as node-sets are.
+pub fn into_document_order(mut nodes: Vec<DomRoot<Node>>) -> Vec<DomRoot<Node>> {
+    nodes.sort_by(document_order);
+    nodes.dedup();
+    nodes
+}
+
+/// <https://www.w3.org/TR/1999/REC-xpath-19991116/#dt-context-position>
+struct Context {
+    node: DomRoot<Node>,
+    position: usize,
+    size: usize,
+}
+
+/// What the expressions of a stylesheet refer to besides their context.
+/// <https://www.w3.org/TR/1999/REC-xslt-19991116#section-Expressions>
+pub trait Environment {
+    /// The value of the variable or the parameter in scope with the given name.
+    fn variable(&self, name: &str) -> Option<Value>;
+
+    /// The node that the instruction being executed processes, which `current()` returns.
+    fn current_node(&self) -> DomRoot<Node>;
+
+    /// The nodes of the tree of `root` whose key with the given name has the given value.
+    fn key(&self, name: &str, value: &str, root: &Node) -> Fallible<Vec<DomRoot<Node>>>;
+
+    /// Whether the instruction with the given qualified name is supported.
+    fn element_available(&self, name: &str) -> bool;
+}
+
+/// Evaluates an expression with the given context node.
+pub fn evaluate(expr: &Expr, context_node: &Node) -> Fallible<Value> {
+    let evaluator = Evaluator {
+        is_html_document: context_node.owner_doc().is_html_document(),
+        environment: None,
+    };
+    let context = Context {
+        node: DomRoot::from_ref(context_node),
+        position: 1,
+        size: 1,
+    };
+    evaluator.evaluate(expr, &context)
+}
+
+/// Evaluates an expression of a stylesheet with the given context.
+pub fn evaluate_in_stylesheet(
+    expr: &Expr,
+    context_node: &Node,
+    position: usize,
+    size: usize,
+    environment: &dyn Environment,
+) -> Fallible<Value> {
+    let evaluator = Evaluator {
+        is_html_document: context_node.owner_doc().is_html_document(),
+        environment: Some(environment),
+    };
+    let context = Context {
+        node: DomRoot::from_ref(context_node),
+        position,
+        size,
+    };
+    evaluator.evaluate(expr, &context)
+}
+
+/// Whether a node matches a pattern of a stylesheet, which it does if the pattern selects it
+/// with one of its ancestors or itself as the context node.
+/// <https://www.w3.org/TR/1999/REC-xslt-19991116#patterns>
+pub fn matches_pattern(
+    pattern: &Expr,
+    node: &Node,
+    environment: &dyn Environment,
+) -> Fallible<bool> {
+    let node = DomRoot::from_ref(node);
+    for context_node in axis_nodes(Axis::AncestorOrSelf, &node) {
+        let value = evaluate_in_stylesheet(pattern, &context_node, 1, 1, environment)?;
+        if let Value::NodeSet(nodes) = value {
+            if nodes.contains(&node) {
+                return Ok(true);
+            }
+        }
+    }
+    Ok(false)
+}
+
+struct Evaluator<'a> {
+    /// Whether the names of HTML elements are matched without regard to case, as in HTML
+    /// documents.
+    /// <https://html.spec.whatwg.org/multipage/#interactions-with-xpath-and-xslt>
+    is_html_document: bool,
+    /// The environment of the expressions of stylesheets.
+    environment: Option<&'a dyn Environment>,
+}
+
+impl<'a> Evaluator<'a> {
+    fn environment(&self) -> Fallible<&'a dyn Environment> {
+        // The parser only accepts variables and the functions of XSLT in stylesheets.
+        self.environment.ok_or(Error::Syntax)
+    }
+
+    fn evaluate(&self, expr: &Expr, context: &Context) -> Fallible<Value> {
+        match *expr {
+            Expr::Binary(ref lhs, operator, ref rhs) => self.binary(lhs, operator, rhs, context),
+            Expr::Negate(ref expr) => Ok(Value::Number(-self.evaluate(expr, context)?.number())),
+            Expr::Path(ref start, ref steps) => {
+                let mut nodes = match *start {
+                    PathStart::Root => vec![root(&context.node)],
+                    PathStart::ContextNode => vec![context.node.clone()],
+                    PathStart::Expr(ref expr) => self.evaluate(expr, context)?.into_node_set()?,
+                };
+                for step in steps {
+                    nodes = self.step(step, &nodes)?;
+                }
+                Ok(Value::NodeSet(nodes))
+            },
+            Expr::Filter(ref expr, ref predicates) => {
+                let mut nodes = self.evaluate(expr, context)?.into_node_set()?;
+                for predicate in predicates {
+                    nodes = self.filter(predicate, nodes)?;
+                }
+                Ok(Value::NodeSet(nodes))
+            },
+            Expr::Literal(ref literal) => Ok(Value::String(literal.clone())),
+            Expr::Number(number) => Ok(Value::Number(number)),
+            Expr::Function(function, ref arguments) => self.function(function, arguments, context),
+            Expr::Variable(ref name) => self.environment()?.variable(name).ok_or(Error::Syntax),
+        }
+    }
+
+    fn binary(
+        &self,
+        lhs: &Expr,
+        operator: BinaryOperator,
+        rhs: &Expr,
+        context: &Context,
+    ) -> Fallible<Value> {
+        // The operands of `and` and `or` are evaluated lazily.
+        match operator {
+            BinaryOperator::Or => {
+                let value = self.evaluate(lhs, context)?.boolean() ||
+                    self.evaluate(rhs, context)?.boolean();
+                return Ok(Value::Boolean(value));
+            },
+            BinaryOperator::And => {
+                let value = self.evaluate(lhs, context)?.boolean() &&
+                    self.evaluate(rhs, context)?.boolean();
+                return Ok(Value::Boolean(value));
+            },
+            _ => {},
+        }
+
+        let lhs = self.evaluate(lhs, context)?;
+        let rhs = self.evaluate(rhs, context)?;
+        let value = match operator {
+            BinaryOperator::Add => Value::Number(lhs.number() + rhs.number()),
+            BinaryOperator::Subtract => Value::Number(lhs.number() - rhs.number()),
+            BinaryOperator::Multiply => Value::Number(lhs.number() * rhs.number()),
+            BinaryOperator::Divide => Value::Number(lhs.number() / rhs.number()),
+            BinaryOperator::Modulo => Value::Number(lhs.number() % rhs.number()),
+            BinaryOperator::Union => {
+                let mut nodes = lhs.into_node_set()?;
+                nodes.extend(rhs.into_node_set()?);
+                Value::NodeSet(into_document_order(nodes))
+            },
+            _ => Value::Boolean(compare(operator, &lhs, &rhs)),
+        };
+        Ok(value)
+    }
+
+    /// Selects the nodes of a location step from each of the context nodes.
+    /// <https://www.w3.org/TR/1999/REC-xpath-19991116/#location-paths>
+    fn step(&self, step: &Step, context_nodes: &[DomRoot<Node>]) -> Fallible<Vec<DomRoot<Node>>> {
+        let mut result = vec![];
+        for context_node in context_nodes {
+            // The nodes are in the order of the axis, which the proximity positions of the
+            // predicates follow.
+            let mut nodes: Vec<_> = axis_nodes(step.axis, context_node)
+                .into_iter()
+                .filter(|node| self.matches(&step.node_test, step.axis, node))
+                .collect();
+            for predicate in &step.predicates {
+                nodes = self.filter(predicate, nodes)?;
+            }
+            result.extend(nodes);
+        }
+        Ok(into_document_order(result))
+    }
+
+    /// Keeps the nodes for which the predicate holds.
+    /// <https://www.w3.org/TR/1999/REC-xpath-19991116/#predicates>
+    fn filter(&self, predicate: &Expr, nodes: Vec<DomRoot<Node>>) -> Fallible<Vec<DomRoot<Node>>> {
+        let size = nodes.len();
+        let mut result = vec![];
+        for (index, node) in nodes.into_iter().enumerate() {
+            let context = Context {
+                node,
+                position: index + 1,
+                size,
+            };
+            let matches = match self.evaluate(predicate, &context)? {
+                Value::Number(number) => number == context.position as f64,
+                value => value.boolean(),
+            };
+            if matches {
+                result.push(context.node);
+            }
+        }
+        Ok(result)
+    }
+
+    /// <https://www.w3.org/TR/1999/REC-xpath-19991116/#node-tests>
+    fn matches(&self, node_test: &NodeTest, axis: Axis, node: &Node) -> bool {
+        match *node_test {
+            NodeTest::Name {
+                ref namespace,
+                ref local_name,
+            } => {
+                // The principal node type of the axis.
+                let (node_namespace, node_local_name) = if axis == Axis::Attribute {
+                    match node.downcast::<Attr>() {
+                        Some(attr) => (attr.namespace(), attr.local_name()),
+                        None => return false,
+                    }
+                } else {
+                    match node.downcast::<Element>() {
+                        Some(element) => (element.namespace(), element.local_name()),
+                        None => return false,
+                    }
+                };
+
+                // Names without prefix match HTML elements in HTML documents, regardless of
+                // their case.
+                let html_element = self.is_html_document &&
+                    axis != Axis::Attribute &&
+                    *node_namespace == ns!(html);
+                let namespace_matches = match *namespace {
+                    Some(ref namespace) => node_namespace == namespace,
+                    None if local_name.is_none() => true,
+                    None => *node_namespace == ns!() || html_element,
+                };
+                namespace_matches &&
+                    local_name.as_ref().map_or(true, |local_name| {
+                        if html_element && namespace.is_none() {
+                            node_local_name.eq_ignore_ascii_case(local_name)
+                        } else {
+                            node_local_name == local_name
+                        }
+                    })
+            },
+            NodeTest::Node => true,
+            NodeTest::Text => node.is::<Text>(),
+            NodeTest::Comment => {
+                node.type_id() == NodeTypeId::CharacterData(CharacterDataTypeId::Comment)
+            },
+            NodeTest::ProcessingInstruction(ref target) => {
+                match node.downcast::<ProcessingInstruction>() {
+                    Some(pi) => target
+                        .as_ref()
+                        .map_or(true, |target| **pi.target() == *target),
+                    None => false,
+                }
+            },
+        }
+    }
+
+    /// <https://www.w3.org/TR/1999/REC-xpath-19991116/#corelib>
+    fn function(
+        &self,
+        function: Function,
+        arguments: &[Expr],
+        context: &Context,
+    ) -> Fallible<Value> {
+        let argument = |index: usize| -> Fallible<Value> {
+            match arguments.get(index) {
+                Some(argument) => self.evaluate(argument, context),
+                // The functions whose argument is optional default to the context node.
+                None => Ok(Value::NodeSet(vec![context.node.clone()])),
+            }
+        };
+        let string_argument = |index: usize| -> Fallible<String> { Ok(argument(index)?.string()) };
+
+        let value = match function {
+            Function::Last => Value::Number(context.size as f64),
+            Function::Position => Value::Number(context.position as f64),
+            Function::Count => Value::Number(argument(0)?.into_node_set()?.len() as f64),
+            Function::Id => {
+                let ids = match argument(0)? {
+                    Value::NodeSet(nodes) => nodes
+                        .iter()
+                        .map(|node| string_value(node))
+                        .collect::<Vec<_>>()
+                        .join(" "),
+                    value => value.string(),
+                };
+                let root = root(&context.node);
+                let elements = ids
+                    .split_whitespace()
+                    .filter_map(|id| {
+                        root.traverse_preorder(ShadowIncluding::No)
+                            .filter_map(DomRoot::downcast::<Element>)
+                            .find(|element| element.get_id().map_or(false, |atom| &*atom == id))
+                    })
+                    .map(DomRoot::upcast)
+                    .collect();
+                Value::NodeSet(into_document_order(elements))
+            },
+            Function::LocalName | Function::NamespaceUri | Function::Name => {
+                let nodes = argument(0)?.into_node_set()?;
+                let string = nodes.first().map_or(String::new(), |node| {
+                    let (namespace, name) = expanded_name(node);
+                    match function {
+                        Function::NamespaceUri => namespace,
+                        Function::LocalName => name.1,
+                        _ => match name.0 {
+                            Some(prefix) => format!("{}:{}", prefix, name.1),
+                            None => name.1,
+                        },
+                    }
+                });
+                Value::String(string)
+            },
+            Function::String => Value::String(string_argument(0)?),
+            Function::Concat => {
+                let mut string = String::new();
+                for index in 0..arguments.len() {
+                    string.push_str(&string_argument(index)?);
+                }
+                Value::String(string)
+            },
+            Function::StartsWith => {
+                Value::Boolean(string_argument(0)?.starts_with(&*string_argument(1)?))
+            },
+            Function::Contains => {
+                Value::Boolean(string_argument(0)?.contains(&*string_argument(1)?))
+            },
+            Function::SubstringBefore => {
+                let string = string_argument(0)?;
+                let pattern = string_argument(1)?;
+                let substring = string.find(&*pattern).map_or("", |index| &string[..index]);
+                Value::String(substring.to_owned())
+            },
+            Function::SubstringAfter => {
+                let string = string_argument(0)?;
+                let pattern = string_argument(1)?;
+                let substring = string
+                    .find(&*pattern)
+                    .map_or("", |index| &string[index + pattern.len()..]);
+                Value::String(substring.to_owned())
+            },
+            Function::Substring => {
+                let string = string_argument(0)?;
+                let start = round(argument(1)?.number());
+                let end = match arguments.get(2) {
+                    Some(_) => start + round(argument(2)?.number()),
+                    None => std::f64::INFINITY,
+                };
+                // The positions of the characters count from 1, and the comparisons fail for
+                // NaN.
+                let substring = string
+                    .chars()
+                    .enumerate()
+                    .filter(|&(index, _)| {
+                        let position = (index + 1) as f64;
+                        position >= start && position < end
+                    })
+                    .map(|(_, c)| c)
+                    .collect();
+                Value::String(substring)
+            },
+            Function::StringLength => Value::Number(string_argument(0)?.chars().count() as f64),
+            Function::NormalizeSpace => {
+                let string = string_argument(0)?;
+                Value::String(string.split_whitespace().collect::<Vec<_>>().join(" "))
+            },
+            Function::Translate => {
+                let string = string_argument(0)?;
+                let from: Vec<char> = string_argument(1)?.chars().collect();
+                let to: Vec<char> = string_argument(2)?.chars().collect();
+                let translated = string
+                    .chars()
+                    .filter_map(|c| match from.iter().position(|&from| from == c) {
+                        Some(index) => to.get(index).cloned(),
+                        None => Some(c),
+                    })
+                    .collect();
+                Value::String(translated)
+            },
+            Function::Boolean => Value::Boolean(argument(0)?.boolean()),
+            Function::Not => Value::Boolean(!argument(0)?.boolean()),
+            Function::True => Value::Boolean(true),
+            Function::False => Value::Boolean(false),
+            Function::Lang => {
+                let lang = string_argument(0)?.to_lowercase();
+                let node_lang = context
+                    .node
+                    .inclusive_ancestors(ShadowIncluding::No)
+                    .filter_map(DomRoot::downcast::<Element>)
+                    .filter_map(|element| {
+                        element
+                            .get_attribute(&ns!(xml), &local_name!("lang"))
+                            .map(|attr| attr.value().to_lowercase())
+                    })
+                    .next();
+                Value::Boolean(node_lang.map_or(false, |node_lang| {
+                    node_lang == lang ||
+                        (node_lang.starts_with(&*lang) &&
+                            node_lang[lang.len()..].starts_with('-'))
+                }))
+            },
+            Function::Number => Value::Number(argument(0)?.number()),
+            Function::Sum => {
+                let nodes = argument(0)?.into_node_set()?;
+                let sum = nodes
+                    .iter()
+                    .map(|node| string_to_number(&string_value(node)))
+                    .sum();
+                Value::Number(sum)
+            },
+            Function::Floor => Value::Number(argument(0)?.number().floor()),
+            Function::Ceiling => Value::Number(argument(0)?.number().ceil()),
+            Function::Round => Value::Number(round(argument(0)?.number())),
+            Function::Current => Value::NodeSet(vec![self.environment()?.current_node()]),
+            Function::Key => {
+                let name = string_argument(0)?;
+                let values = match argument(1)? {
+                    Value::NodeSet(nodes) => nodes.iter().map(|node| string_value(node)).collect(),
+                    value => vec![value.string()],
+                };
+                let root = root(&context.node);
+                let mut nodes = vec![];
+                for value in values {
+                    nodes.extend(self.environment()?.key(&name, &value, &root)?);
+                }
+                Value::NodeSet(into_document_order(nodes))
+            },
+            Function::FormatNumber => {
+                Value::String(format_number(argument(0)?.number(), &string_argument(1)?))
+            },
+            Function::GenerateId => {
+                let nodes = argument(0)?.into_node_set()?;
+                let id = nodes.first().map_or(String::new(), |node| {
+                    format!("id{}", node.unique_id().replace('-', ""))
+                });
+                Value::String(id)
+            },
+            // No entities are declared by the documents of the DOM.
+            Function::UnparsedEntityUri => Value::String(String::new()),
+            Function::SystemProperty => match &*string_argument(0)? {
+                "xsl:version" => Value::Number(1.),
+                "xsl:vendor" => Value::String("Servo".to_owned()),
+                "xsl:vendor-url" => Value::String("https://servo.org/".to_owned()),
+                _ => Value::String(String::new()),
+            },
+            Function::ElementAvailable => {
+                Value::Boolean(self.environment()?.element_available(&string_argument(0)?))
+            },
+            Function::FunctionAvailable => {
+                Value::Boolean(Function::is_available(&string_argument(0)?))
+            },
+        };
+        Ok(value)
+    }
+}
+
+/// Formats a number with a pattern of the default decimal format.
+/// <https://www.w3.org/TR/1999/REC-xslt-19991116#format-number>
+fn format_number(number: f64, pattern: &str) -> String {
+    if number.is_nan() {
+        return "NaN".to_owned();
+    }
+
+    // Negative numbers use the pattern after the `;` if there is one, and the positive
+    // pattern preceded by a minus sign otherwise.
+    let mut patterns = pattern.splitn(2, ';');
+    let positive = patterns.next().unwrap_or("");
+    let (pattern, minus) = match patterns.next() {
+        Some(negative) if number < 0. => (negative, ""),
+        _ => (positive, if number < 0. { "-" } else { "" }),
+    };
+    let is_pattern_char = |c: char| matches!(c, '#' | '0' | ',' | '.');
+    let start = pattern.find(is_pattern_char).unwrap_or(pattern.len());
+    let end = pattern
+        .rfind(is_pattern_char)
+        .map_or(start, |index| index + 1);
+    let (prefix, digits, suffix) = (&pattern[..start], &pattern[start..end], &pattern[end..]);
+
+    let mut number = number.abs();
+    if prefix.contains('%') || suffix.contains('%') {
+        number *= 100.;
+    } else if prefix.contains('\u{2030}') || suffix.contains('\u{2030}') {
+        number *= 1000.;
+    }
+    if number.is_infinite() {
+        return format!("{}{}Infinity{}", minus, prefix, suffix);
+    }
+
+    let (integer_digits, fraction_digits) = match digits.find('.') {
+        Some(index) => (&digits[..index], &digits[index + 1..]),
+        None => (digits, ""),
+    };
+    let min_integer_digits = integer_digits.matches('0').count();
+    let grouping_size = integer_digits
+        .rfind(',')
+        .map(|index| integer_digits[index + 1..].len());
+    let min_fraction_digits = fraction_digits.matches('0').count();
+    let max_fraction_digits = fraction_digits.matches(|c| c == '0' || c == '#').count();
+
+    let formatted = format!("{:.*}", max_fraction_digits, number);
+    let mut parts = formatted.splitn(2, '.');
+    let integer = parts.next().unwrap_or("").trim_start_matches('0');
+    let mut fraction = parts.next().unwrap_or("").to_owned();
+    while fraction.len() > min_fraction_digits && fraction.ends_with('0') {
+        fraction.pop();
+    }
+
+    let mut integer: Vec<char> = integer.chars().collect();
+    let min_integer_digits = if fraction.is_empty() {
+        min_integer_digits.max(1)
+    } else {
+        min_integer_digits
+    };
+    while integer.len() < min_integer_digits {
+        integer.insert(0, '0');
+    }
+    let mut grouped = String::new();
+    for (index, digit) in integer.iter().enumerate() {
+        let remaining = integer.len() - index;
+        if index > 0 && grouping_size.map_or(false, |size| size > 0 && remaining % size == 0) {
+            grouped.push(',');
+        }
+        grouped.push(*digit);
+    }
+    if !fraction.is_empty() {
+        grouped.push('.');
+        grouped.push_str(&fraction);
+    }
+    format!("{}{}{}{}", minus, prefix, grouped, suffix)
+}
+
+/// The namespace, the prefix and the local name of a node, for `namespace-uri()`, `name()`
+/// and `local-name()`.
+/// <https://www.w3.org/TR/1999/REC-xpath-19991116/#dt-expanded-name>
+fn expanded_name(node: &Node) -> (String, (Option<String>, String)) {
+    if let Some(element) = node.downcast::<Element>() {
+        let prefix = element.prefix().as_ref().map(|prefix| prefix.to_string());
+        return (
+            element.namespace().to_string(),
+            (prefix, element.local_name().to_string()),
+        );
+    }
+    if let Some(attr) = node.downcast::<Attr>() {
+        let prefix = attr.prefix().map(|prefix| prefix.to_string());
+        return (
+            attr.namespace().to_string(),
+            (prefix, attr.local_name().to_string()),
+        );
+    }
+    if let Some(pi) = node.downcast::<ProcessingInstruction>() {
+        return (String::new(), (None, pi.target().to_string()));
+    }
+    (String::new(), (None, String::new()))
+}
+
+/// The nodes of an axis, in the order of the axis.
+/// <https://www.w3.org/TR/1999/REC-xpath-19991116/#axes>
+fn axis_nodes(axis: Axis, node: &DomRoot<Node>) -> Vec<DomRoot<Node>> {
+    // Attributes are children of no node, but their parent is their element.
+    let attr_owner = node
+        .downcast::<Attr>()
+        .and_then(|attr| attr.owner())
+        .map(DomRoot::upcast::<Node>);
+    let parent = attr_owner.clone().or_else(|| node.GetParentNode());
+
+    match axis {
+        Axis::Child => {
+            if attr_owner.is_some() {
+                return vec![];
+            }
+            node.children().collect()
+        },
+        Axis::Descendant | Axis::DescendantOrSelf => {
+            let mut nodes = vec![];
+            if axis == Axis::DescendantOrSelf {
+                nodes.push(node.clone());
+            }
+            if attr_owner.is_none() {
+                nodes.extend(node.traverse_preorder(ShadowIncluding::No).skip(1));
+            }
+            nodes
+        },
+        Axis::Parent => parent.into_iter().collect(),
+        Axis::Ancestor | Axis::AncestorOrSelf => {
+            let mut nodes = vec![];
+            if axis == Axis::AncestorOrSelf {
+                nodes.push(node.clone());
+            }
+            if let Some(parent) = parent {
+                nodes.extend(parent.inclusive_ancestors(ShadowIncluding::No));
+            }
+            nodes
+        },
+        Axis::FollowingSibling => {
+            if attr_owner.is_some() {
+                return vec![];
+            }
+            node.following_siblings().collect()
+        },
+        Axis::PrecedingSibling => {
+            if attr_owner.is_some() {
+                return vec![];
+            }
+            node.preceding_siblings().collect()
+        },
+        Axis::Following => {
+            // The nodes after the context node that aren't its descendants, which for an
+            // attribute include the descendants of its element.
+            let mut nodes = vec![];
+            let start = match attr_owner {
+                Some(ref owner) => {
+                    nodes.extend(owner.traverse_preorder(ShadowIncluding::No).skip(1));
+                    owner.clone()
+                },
+                None => node.clone(),
+            };
+            for ancestor in start.inclusive_ancestors(ShadowIncluding::No) {
+                for sibling in ancestor.following_siblings() {
+                    nodes.extend(sibling.traverse_preorder(ShadowIncluding::No));
+                }
+            }
+            nodes
+        },
+        Axis::Preceding => {
+            // The nodes before the context node that aren't its ancestors, nearest first.
+            let mut nodes = vec![];
+            let start = attr_owner.unwrap_or_else(|| node.clone());
+            for ancestor in start.inclusive_ancestors(ShadowIncluding::No) {
+                for sibling in ancestor.preceding_siblings() {
+                    let mut subtree: Vec<_> =
+                        sibling.traverse_preorder(ShadowIncluding::No).collect();
+                    subtree.reverse();
+                    nodes.extend(subtree);
+                }
+            }
+            nodes
+        },
+        Axis::Attribute => match node.downcast::<Element>() {
+            Some(element) => element
+                .attrs()
+                .iter()
+                .map(|attr| DomRoot::upcast(DomRoot::from_ref(&**attr)))
+                .collect(),
+            None => vec![],
+        },
+        // Namespace nodes aren't part of the DOM.
+        Axis::Namespace => vec![],
+        Axis::SelfAxis => vec![node.clone()],
+    }
+}
+
+/// Compares two values with an equality or relational operator.
+/// <https://www.w3.org/TR/1999/REC-xpath-19991116/#booleans>
+fn compare(operator: BinaryOperator, lhs: &Value, rhs: &Value) -> bool {
+    match (lhs, rhs) {
+        // Node-sets are compared to booleans as booleans.
+        (&Value::NodeSet(ref nodes), &Value::Boolean(_)) => {
+            compare_atomic(operator, &Value::Boolean(!nodes.is_empty()), rhs)
+        },
+        (&Value::Boolean(_), &Value::NodeSet(ref nodes)) => {
+            compare_atomic(operator, lhs, &Value::Boolean(!nodes.is_empty()))
+        },
+        (&Value::NodeSet(ref lhs), &Value::NodeSet(ref rhs)) => {
+            let rhs: Vec<_> = rhs.iter().map(|node| string_value(node)).collect();
+            lhs.iter().any(|lhs| {
+                let lhs = Value::String(string_value(lhs));
+                rhs.iter()
+                    .any(|rhs| compare_atomic(operator, &lhs, &Value::String(rhs.clone())))
+            })
+        },
+        (&Value::NodeSet(ref nodes), other) => nodes.iter().any(|node| {
+            let node = node_value(node, other);
+            compare_atomic(operator, &node, other)
+        }),
+        (other, &Value::NodeSet(ref nodes)) => nodes.iter().any(|node| {
+            let node = node_value(node, other);
+            compare_atomic(operator, other, &node)
+        }),
+        (lhs, rhs) => compare_atomic(operator, lhs, rhs),
+    }
+}
+
+/// The value that a node is compared to another value as, which is its string value
+/// converted to the type of the other value.
+fn node_value(node: &Node, other: &Value) -> Value {
+    let string = string_value(node);
+    match *other {
+        Value::Number(_) => Value::Number(string_to_number(&string)),
+        _ => Value::String(string),
+    }
+}
+
+/// Compares two values that aren't node-sets.
+fn compare_atomic(operator: BinaryOperator, lhs: &Value, rhs: &Value) -> bool {
+    match operator {
+        BinaryOperator::Equal | BinaryOperator::NotEqual => {
+            let equal = match (lhs, rhs) {
+                (&Value::Boolean(_), _) | (_, &Value::Boolean(_)) => lhs.boolean() == rhs.boolean(),
+                (&Value::Number(_), _) | (_, &Value::Number(_)) => lhs.number() == rhs.number(),
+                _ => lhs.string() == rhs.string(),
+            };
+            equal == (operator == BinaryOperator::Equal)
+        },
+        BinaryOperator::Less => lhs.number() < rhs.number(),
+        BinaryOperator::LessOrEqual => lhs.number() <= rhs.number(),
+        BinaryOperator::Greater => lhs.number() > rhs.number(),
+        BinaryOperator::GreaterOrEqual => lhs.number() >= rhs.number(),
+        _ => unreachable!("Not a comparison operator"),
+    }
+}
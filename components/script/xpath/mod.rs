@@ -0,0 +1,19 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! XPath 1.0 expressions, as evaluated by `document.evaluate()`.
+//!
+//! An expression is parsed once into an [`Expr`], whose namespace prefixes are resolved
+//! when it is created, and can then be evaluated against any context node into a [`Value`].
+//!
+//! <https://www.w3.org/TR/1999/REC-xpath-19991116/>
+//! <https://dom.spec.whatwg.org/#xpath>
+
+mod eval;
+mod parser;
+
+pub use self::eval::{evaluate, evaluate_in_stylesheet, matches_pattern};
+pub use self::eval::{into_document_order, string_value, Environment, Value};
+pub use self::parser::{parse, parse_in_stylesheet};
+pub use self::parser::{Axis, BinaryOperator, Expr, Function, NodeTest, PathStart};
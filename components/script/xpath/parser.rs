@@ -0,0 +1,945 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! The lexer and the recursive descent parser of XPath expressions.
+//!
+//! <https://www.w3.org/TR/1999/REC-xpath-19991116/#exprlex>
+
+use crate::dom::bindings::error::{Error, Fallible};
+use html5ever::{LocalName, Namespace};
+
+/// <https://www.w3.org/TR/1999/REC-xpath-19991116/#NT-Expr>
+#[derive(Debug)]
+pub enum Expr {
+    Binary(Box<Expr>, BinaryOperator, Box<Expr>),
+    Negate(Box<Expr>),
+    /// A path, which selects nodes with its steps from the root of the context node, the
+    /// context node itself, or the nodes that an expression evaluates to.
+    Path(PathStart, Vec<Step>),
+    /// A primary expression filtered by predicates.
+    Filter(Box<Expr>, Vec<Expr>),
+    Literal(String),
+    Number(f64),
+    Function(Function, Vec<Expr>),
+    /// A reference to a variable of a stylesheet, by its qualified name.
+    Variable(String),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BinaryOperator {
+    Or,
+    And,
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Modulo,
+    Union,
+}
+
+#[derive(Debug)]
+pub enum PathStart {
+    /// An absolute location path.
+    Root,
+    /// A relative location path.
+    ContextNode,
+    /// A filter expression followed by `/` or `//`.
+    Expr(Box<Expr>),
+}
+
+/// <https://www.w3.org/TR/1999/REC-xpath-19991116/#NT-Step>
+#[derive(Debug)]
+pub struct Step {
+    pub axis: Axis,
+    pub node_test: NodeTest,
+    pub predicates: Vec<Expr>,
+}
+
+impl Step {
+    /// The step that `//` abbreviates.
+    fn descendant_or_self() -> Step {
+        Step {
+            axis: Axis::DescendantOrSelf,
+            node_test: NodeTest::Node,
+            predicates: vec![],
+        }
+    }
+}
+
+/// <https://www.w3.org/TR/1999/REC-xpath-19991116/#axes>
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Axis {
+    Ancestor,
+    AncestorOrSelf,
+    Attribute,
+    Child,
+    Descendant,
+    DescendantOrSelf,
+    Following,
+    FollowingSibling,
+    Namespace,
+    Parent,
+    Preceding,
+    PrecedingSibling,
+    SelfAxis,
+}
+
+impl Axis {
+    fn from_name(name: &str) -> Option<Axis> {
+        Some(match name {
+            "ancestor" => Axis::Ancestor,
+            "ancestor-or-self" => Axis::AncestorOrSelf,
+            "attribute" => Axis::Attribute,
+            "child" => Axis::Child,
+            "descendant" => Axis::Descendant,
+            "descendant-or-self" => Axis::DescendantOrSelf,
+            "following" => Axis::Following,
+            "following-sibling" => Axis::FollowingSibling,
+            "namespace" => Axis::Namespace,
+            "parent" => Axis::Parent,
+            "preceding" => Axis::Preceding,
+            "preceding-sibling" => Axis::PrecedingSibling,
+            "self" => Axis::SelfAxis,
+            _ => return None,
+        })
+    }
+
+    /// Whether the axis contains the nodes before the context node, whose proximity
+    /// positions count in reverse document order.
+    pub fn is_reverse(&self) -> bool {
+        match *self {
+            Axis::Ancestor | Axis::AncestorOrSelf | Axis::Preceding | Axis::PrecedingSibling => {
+                true
+            },
+            _ => false,
+        }
+    }
+}
+
+/// <https://www.w3.org/TR/1999/REC-xpath-19991116/#node-tests>
+#[derive(Debug)]
+pub enum NodeTest {
+    /// A name test, which matches nodes of the principal node type of the axis. A missing
+    /// namespace stands for a name without prefix, and a missing local name for `*`.
+    Name {
+        namespace: Option<Namespace>,
+        local_name: Option<LocalName>,
+    },
+    Node,
+    Text,
+    Comment,
+    ProcessingInstruction(Option<String>),
+}
+
+/// The functions of the core function library, and the ones that stylesheets add to it.
+/// <https://www.w3.org/TR/1999/REC-xpath-19991116/#corelib>
+/// <https://www.w3.org/TR/1999/REC-xslt-19991116#add-func>
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Function {
+    Last,
+    Position,
+    Count,
+    Id,
+    LocalName,
+    NamespaceUri,
+    Name,
+    String,
+    Concat,
+    StartsWith,
+    Contains,
+    SubstringBefore,
+    SubstringAfter,
+    Substring,
+    StringLength,
+    NormalizeSpace,
+    Translate,
+    Boolean,
+    Not,
+    True,
+    False,
+    Lang,
+    Number,
+    Sum,
+    Floor,
+    Ceiling,
+    Round,
+    Current,
+    Key,
+    FormatNumber,
+    GenerateId,
+    UnparsedEntityUri,
+    SystemProperty,
+    ElementAvailable,
+    FunctionAvailable,
+}
+
+impl Function {
+    fn from_name(name: &str) -> Option<Function> {
+        Some(match name {
+            "last" => Function::Last,
+            "position" => Function::Position,
+            "count" => Function::Count,
+            "id" => Function::Id,
+            "local-name" => Function::LocalName,
+            "namespace-uri" => Function::NamespaceUri,
+            "name" => Function::Name,
+            "string" => Function::String,
+            "concat" => Function::Concat,
+            "starts-with" => Function::StartsWith,
+            "contains" => Function::Contains,
+            "substring-before" => Function::SubstringBefore,
+            "substring-after" => Function::SubstringAfter,
+            "substring" => Function::Substring,
+            "string-length" => Function::StringLength,
+            "normalize-space" => Function::NormalizeSpace,
+            "translate" => Function::Translate,
+            "boolean" => Function::Boolean,
+            "not" => Function::Not,
+            "true" => Function::True,
+            "false" => Function::False,
+            "lang" => Function::Lang,
+            "number" => Function::Number,
+            "sum" => Function::Sum,
+            "floor" => Function::Floor,
+            "ceiling" => Function::Ceiling,
+            "round" => Function::Round,
+            "current" => Function::Current,
+            "key" => Function::Key,
+            "format-number" => Function::FormatNumber,
+            "generate-id" => Function::GenerateId,
+            "unparsed-entity-uri" => Function::UnparsedEntityUri,
+            "system-property" => Function::SystemProperty,
+            "element-available" => Function::ElementAvailable,
+            "function-available" => Function::FunctionAvailable,
+            _ => return None,
+        })
+    }
+
+    /// Whether the function is only available in stylesheets.
+    fn is_xslt(&self) -> bool {
+        match *self {
+            Function::Current |
+            Function::Key |
+            Function::FormatNumber |
+            Function::GenerateId |
+            Function::UnparsedEntityUri |
+            Function::SystemProperty |
+            Function::ElementAvailable |
+            Function::FunctionAvailable => true,
+            _ => false,
+        }
+    }
+
+    /// Whether stylesheets can call the function with the given name.
+    pub fn is_available(name: &str) -> bool {
+        Function::from_name(name).is_some()
+    }
+
+    /// The least and the most arguments that the function takes.
+    fn arity(&self) -> (usize, usize) {
+        match *self {
+            Function::Last |
+            Function::Position |
+            Function::True |
+            Function::False |
+            Function::Current => (0, 0),
+            Function::LocalName |
+            Function::NamespaceUri |
+            Function::Name |
+            Function::String |
+            Function::StringLength |
+            Function::NormalizeSpace |
+            Function::Number |
+            Function::GenerateId => (0, 1),
+            Function::Count |
+            Function::Id |
+            Function::Boolean |
+            Function::Not |
+            Function::Lang |
+            Function::Sum |
+            Function::Floor |
+            Function::Ceiling |
+            Function::Round |
+            Function::UnparsedEntityUri |
+            Function::SystemProperty |
+            Function::ElementAvailable |
+            Function::FunctionAvailable => (1, 1),
+            Function::StartsWith |
+            Function::Contains |
+            Function::SubstringBefore |
+            Function::SubstringAfter |
+            Function::Key => (2, 2),
+            Function::Substring | Function::FormatNumber => (2, 3),
+            Function::Translate => (3, 3),
+            Function::Concat => (2, usize::max_value()),
+        }
+    }
+}
+
+/// <https://www.w3.org/TR/1999/REC-xpath-19991116/#NT-ExprToken>
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    LeftParen,
+    RightParen,
+    LeftBracket,
+    RightBracket,
+    Dot,
+    DotDot,
+    At,
+    Comma,
+    DoubleColon,
+    Slash,
+    DoubleSlash,
+    Pipe,
+    Plus,
+    Minus,
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+    Multiply,
+    And,
+    Or,
+    Mod,
+    Div,
+    /// A name test, with the prefix and the local name, which is missing for `*`.
+    NameTest(Option<String>, Option<String>),
+    NodeType(String),
+    FunctionName(String),
+    AxisName(String),
+    Literal(String),
+    Number(f64),
+    VariableReference(String),
+}
+
+impl Token {
+    /// Whether a `*` or a name after this token is an operator.
+    /// <https://www.w3.org/TR/1999/REC-xpath-19991116/#exprlex>
+    fn precedes_operator(&self) -> bool {
+        match *self {
+            Token::At |
+            Token::DoubleColon |
+            Token::LeftParen |
+            Token::LeftBracket |
+            Token::Comma |
+            Token::Slash |
+            Token::DoubleSlash |
+            Token::Pipe |
+            Token::Plus |
+            Token::Minus |
+            Token::Equal |
+            Token::NotEqual |
+            Token::Less |
+            Token::LessOrEqual |
+            Token::Greater |
+            Token::GreaterOrEqual |
+            Token::Multiply |
+            Token::And |
+            Token::Or |
+            Token::Mod |
+            Token::Div => false,
+            _ => true,
+        }
+    }
+}
+
+fn is_name_start_char(c: char) -> bool {
+    match c {
+        'A'..='Z' |
+        '_' |
+        'a'..='z' |
+        '\u{C0}'..='\u{D6}' |
+        '\u{D8}'..='\u{F6}' |
+        '\u{F8}'..='\u{2FF}' |
+        '\u{370}'..='\u{37D}' |
+        '\u{37F}'..='\u{1FFF}' |
+        '\u{200C}'..='\u{200D}' |
+        '\u{2070}'..='\u{218F}' |
+        '\u{2C00}'..='\u{2FEF}' |
+        '\u{3001}'..='\u{D7FF}' |
+        '\u{F900}'..='\u{FDCF}' |
+        '\u{FDF0}'..='\u{FFFD}' |
+        '\u{10000}'..='\u{EFFFF}' => true,
+        _ => false,
+    }
+}
+
+fn is_name_char(c: char) -> bool {
+    is_name_start_char(c) ||
+        match c {
+            '-' | '.' | '0'..='9' | '\u{B7}' | '\u{300}'..='\u{36F}' | '\u{203F}'..='\u{2040}' => {
+                true
+            },
+            _ => false,
+        }
+}
+
+fn is_whitespace(c: char) -> bool {
+    match c {
+        ' ' | '\t' | '\r' | '\n' => true,
+        _ => false,
+    }
+}
+
+struct Lexer<'a> {
+    chars: std::iter::Peekable<std::str::Chars<'a>>,
+    tokens: Vec<Token>,
+}
+
+impl<'a> Lexer<'a> {
+    fn skip_whitespace(&mut self) {
+        while self.chars.peek().map_or(false, |&c| is_whitespace(c)) {
+            self.chars.next();
+        }
+    }
+
+    /// <https://www.w3.org/TR/REC-xml-names/#NT-NCName>
+    fn ncname(&mut self) -> Fallible<String> {
+        let mut name = String::new();
+        match self.chars.peek() {
+            Some(&c) if is_name_start_char(c) => {},
+            _ => return Err(Error::Syntax),
+        }
+        while let Some(&c) = self.chars.peek() {
+            if !is_name_char(c) {
+                break;
+            }
+            name.push(c);
+            self.chars.next();
+        }
+        Ok(name)
+    }
+
+    fn number(&mut self, mut number: String) -> Fallible<f64> {
+        while let Some(&c) = self.chars.peek() {
+            if !c.is_ascii_digit() && !(c == '.' && !number.contains('.')) {
+                break;
+            }
+            number.push(c);
+            self.chars.next();
+        }
+        number.parse().map_err(|_| Error::Syntax)
+    }
+
+    fn literal(&mut self, quote: char) -> Fallible<String> {
+        let mut literal = String::new();
+        loop {
+            match self.chars.next() {
+                Some(c) if c == quote => return Ok(literal),
+                Some(c) => literal.push(c),
+                None => return Err(Error::Syntax),
+            }
+        }
+    }
+
+    /// Lexes a name, which is a name test, a node type, a function name, an axis name or an
+    /// operator name depending on the tokens around it.
+    fn name(&mut self, first: String) -> Fallible<Token> {
+        let operator_context = self
+            .tokens
+            .last()
+            .map_or(false, |token| token.precedes_operator());
+        if operator_context {
+            return match &*first {
+                "and" => Ok(Token::And),
+                "or" => Ok(Token::Or),
+                "mod" => Ok(Token::Mod),
+                "div" => Ok(Token::Div),
+                _ => Err(Error::Syntax),
+            };
+        }
+
+        // A single colon makes a qualified name, unlike the `::` after an axis name.
+        let mut prefix = None;
+        let mut local_name = Some(first);
+        if self.chars.peek() == Some(&':') {
+            let mut lookahead = self.chars.clone();
+            lookahead.next();
+            if lookahead.peek() != Some(&':') {
+                self.chars.next();
+                prefix = local_name.take();
+                if self.chars.peek() == Some(&'*') {
+                    self.chars.next();
+                } else {
+                    local_name = Some(self.ncname()?);
+                }
+            }
+        }
+
+        self.skip_whitespace();
+        let mut lookahead = self.chars.clone();
+        match (lookahead.next(), lookahead.next()) {
+            (Some('('), _) => {
+                let name = match (prefix, local_name) {
+                    (None, Some(name)) => name,
+                    _ => return Err(Error::Syntax),
+                };
+                match &*name {
+                    "comment" | "text" | "processing-instruction" | "node" => {
+                        Ok(Token::NodeType(name))
+                    },
+                    _ => Ok(Token::FunctionName(name)),
+                }
+            },
+            (Some(':'), Some(':')) => match (prefix, local_name) {
+                (None, Some(name)) => Ok(Token::AxisName(name)),
+                _ => Err(Error::Syntax),
+            },
+            _ => Ok(Token::NameTest(prefix, local_name)),
+        }
+    }
+
+    fn next_token(&mut self) -> Fallible<Option<Token>> {
+        self.skip_whitespace();
+        let c = match self.chars.next() {
+            Some(c) => c,
+            None => return Ok(None),
+        };
+        let token = match c {
+            '(' => Token::LeftParen,
+            ')' => Token::RightParen,
+            '[' => Token::LeftBracket,
+            ']' => Token::RightBracket,
+            '@' => Token::At,
+            ',' => Token::Comma,
+            '|' => Token::Pipe,
+            '+' => Token::Plus,
+            '-' => Token::Minus,
+            '=' => Token::Equal,
+            ':' if self.chars.peek() == Some(&':') => {
+                self.chars.next();
+                Token::DoubleColon
+            },
+            '/' if self.chars.peek() == Some(&'/') => {
+                self.chars.next();
+                Token::DoubleSlash
+            },
+            '/' => Token::Slash,
+            '!' if self.chars.peek() == Some(&'=') => {
+                self.chars.next();
+                Token::NotEqual
+            },
+            '<' if self.chars.peek() == Some(&'=') => {
+                self.chars.next();
+                Token::LessOrEqual
+            },
+            '<' => Token::Less,
+            '>' if self.chars.peek() == Some(&'=') => {
+                self.chars.next();
+                Token::GreaterOrEqual
+            },
+            '>' => Token::Greater,
+            '.' if self.chars.peek() == Some(&'.') => {
+                self.chars.next();
+                Token::DotDot
+            },
+            '.' if self.chars.peek().map_or(false, |c| c.is_ascii_digit()) => {
+                Token::Number(self.number(c.to_string())?)
+            },
+            '.' => Token::Dot,
+            '0'..='9' => Token::Number(self.number(c.to_string())?),
+            '"' | '\'' => Token::Literal(self.literal(c)?),
+            '$' => {
+                let mut name = self.ncname()?;
+                if self.chars.peek() == Some(&':') {
+                    self.chars.next();
+                    name.push(':');
+                    name.push_str(&self.ncname()?);
+                }
+                Token::VariableReference(name)
+            },
+            '*' => {
+                let operator_context = self
+                    .tokens
+                    .last()
+                    .map_or(false, |token| token.precedes_operator());
+                if operator_context {
+                    Token::Multiply
+                } else {
+                    Token::NameTest(None, None)
+                }
+            },
+            c if is_name_start_char(c) => {
+                let mut name = c.to_string();
+                while let Some(&c) = self.chars.peek() {
+                    if !is_name_char(c) {
+                        break;
+                    }
+                    name.push(c);
+                    self.chars.next();
+                }
+                self.name(name)?
+            },
+            _ => return Err(Error::Syntax),
+        };
+        Ok(Some(token))
+    }
+}
+
+fn tokenize(expression: &str) -> Fallible<Vec<Token>> {
+    let mut lexer = Lexer {
+        chars: expression.chars().peekable(),
+        tokens: vec![],
+    };
+    while let Some(token) = lexer.next_token()? {
+        lexer.tokens.push(token);
+    }
+    Ok(lexer.tokens)
+}
+
+/// Parses an XPath expression. The prefixes of the name tests are resolved to namespaces
+/// as they are parsed.
+pub fn parse(
+    expression: &str,
+    resolve_prefix: &mut dyn FnMut(&str) -> Fallible<Namespace>,
+) -> Fallible<Expr> {
+    parse_expr(expression, resolve_prefix, false)
+}
+
+/// Parses an XPath expression of a stylesheet, which can also refer to variables and call
+/// the functions of XSLT.
+pub fn parse_in_stylesheet(
+    expression: &str,
+    resolve_prefix: &mut dyn FnMut(&str) -> Fallible<Namespace>,
+) -> Fallible<Expr> {
+    parse_expr(expression, resolve_prefix, true)
+}
+
+fn parse_expr(
+    expression: &str,
+    resolve_prefix: &mut dyn FnMut(&str) -> Fallible<Namespace>,
+    in_stylesheet: bool,
+) -> Fallible<Expr> {
+    let tokens = tokenize(expression)?;
+    let mut parser = Parser {
+        tokens,
+        position: 0,
+        resolve_prefix,
+        in_stylesheet,
+    };
+    let expr = parser.expr()?;
+    if parser.position != parser.tokens.len() {
+        return Err(Error::Syntax);
+    }
+    Ok(expr)
+}
+
+struct Parser<'a> {
+    tokens: Vec<Token>,
+    position: usize,
+    resolve_prefix: &'a mut dyn FnMut(&str) -> Fallible<Namespace>,
+    in_stylesheet: bool,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    /// Consumes the next token if it is the given one.
+    fn eat(&mut self, token: &Token) -> bool {
+        if self.peek() == Some(token) {
+            self.position += 1;
+            return true;
+        }
+        false
+    }
+
+    fn expect(&mut self, token: &Token) -> Fallible<()> {
+        if !self.eat(token) {
+            return Err(Error::Syntax);
+        }
+        Ok(())
+    }
+
+    /// Parses the operands of left-associative binary operators, with `operand` parsing the
+    /// expressions of higher precedence.
+    fn binary(
+        &mut self,
+        operators: &[(Token, BinaryOperator)],
+        operand: fn(&mut Self) -> Fallible<Expr>,
+    ) -> Fallible<Expr> {
+        let mut expr = operand(self)?;
+        'operators: loop {
+            for &(ref token, operator) in operators {
+                if self.eat(token) {
+                    expr = Expr::Binary(Box::new(expr), operator, Box::new(operand(self)?));
+                    continue 'operators;
+                }
+            }
+            return Ok(expr);
+        }
+    }
+
+    /// <https://www.w3.org/TR/1999/REC-xpath-19991116/#NT-OrExpr>
+    fn expr(&mut self) -> Fallible<Expr> {
+        self.binary(&[(Token::Or, BinaryOperator::Or)], Self::and_expr)
+    }
+
+    fn and_expr(&mut self) -> Fallible<Expr> {
+        self.binary(&[(Token::And, BinaryOperator::And)], Self::equality_expr)
+    }
+
+    fn equality_expr(&mut self) -> Fallible<Expr> {
+        self.binary(
+            &[
+                (Token::Equal, BinaryOperator::Equal),
+                (Token::NotEqual, BinaryOperator::NotEqual),
+            ],
+            Self::relational_expr,
+        )
+    }
+
+    fn relational_expr(&mut self) -> Fallible<Expr> {
+        self.binary(
+            &[
+                (Token::Less, BinaryOperator::Less),
+                (Token::LessOrEqual, BinaryOperator::LessOrEqual),
+                (Token::Greater, BinaryOperator::Greater),
+                (Token::GreaterOrEqual, BinaryOperator::GreaterOrEqual),
+            ],
+            Self::additive_expr,
+        )
+    }
+
+    fn additive_expr(&mut self) -> Fallible<Expr> {
+        self.binary(
+            &[
+                (Token::Plus, BinaryOperator::Add),
+                (Token::Minus, BinaryOperator::Subtract),
+            ],
+            Self::multiplicative_expr,
+        )
+    }
+
+    fn multiplicative_expr(&mut self) -> Fallible<Expr> {
+        self.binary(
+            &[
+                (Token::Multiply, BinaryOperator::Multiply),
+                (Token::Div, BinaryOperator::Divide),
+                (Token::Mod, BinaryOperator::Modulo),
+            ],
+            Self::unary_expr,
+        )
+    }
+
+    /// <https://www.w3.org/TR/1999/REC-xpath-19991116/#NT-UnaryExpr>
+    fn unary_expr(&mut self) -> Fallible<Expr> {
+        if self.eat(&Token::Minus) {
+            return Ok(Expr::Negate(Box::new(self.unary_expr()?)));
+        }
+        self.binary(&[(Token::Pipe, BinaryOperator::Union)], Self::path_expr)
+    }
+
+    /// <https://www.w3.org/TR/1999/REC-xpath-19991116/#NT-PathExpr>
+    fn path_expr(&mut self) -> Fallible<Expr> {
+        let is_filter_expr = match self.peek() {
+            Some(Token::VariableReference(_)) |
+            Some(Token::LeftParen) |
+            Some(Token::Literal(_)) |
+            Some(Token::Number(_)) |
+            Some(Token::FunctionName(_)) => true,
+            _ => false,
+        };
+        if !is_filter_expr {
+            return self.location_path();
+        }
+
+        let primary = self.primary_expr()?;
+        let predicates = self.predicates()?;
+        let filter = if predicates.is_empty() {
+            primary
+        } else {
+            Expr::Filter(Box::new(primary), predicates)
+        };
+
+        let mut steps = vec![];
+        match self.peek() {
+            Some(Token::Slash) => {},
+            Some(Token::DoubleSlash) => steps.push(Step::descendant_or_self()),
+            _ => return Ok(filter),
+        }
+        self.position += 1;
+        self.relative_location_path(&mut steps)?;
+        Ok(Expr::Path(PathStart::Expr(Box::new(filter)), steps))
+    }
+
+    /// <https://www.w3.org/TR/1999/REC-xpath-19991116/#NT-LocationPath>
+    fn location_path(&mut self) -> Fallible<Expr> {
+        let mut steps = vec![];
+        if self.eat(&Token::Slash) {
+            // A lone `/` selects the root.
+            if self.starts_step() {
+                self.relative_location_path(&mut steps)?;
+            }
+            return Ok(Expr::Path(PathStart::Root, steps));
+        }
+        if self.eat(&Token::DoubleSlash) {
+            steps.push(Step::descendant_or_self());
+            self.relative_location_path(&mut steps)?;
+            return Ok(Expr::Path(PathStart::Root, steps));
+        }
+        self.relative_location_path(&mut steps)?;
+        Ok(Expr::Path(PathStart::ContextNode, steps))
+    }
+
+    fn starts_step(&self) -> bool {
+        match self.peek() {
+            Some(Token::Dot) |
+            Some(Token::DotDot) |
+            Some(Token::At) |
+            Some(Token::AxisName(_)) |
+            Some(Token::NameTest(..)) |
+            Some(Token::NodeType(_)) => true,
+            _ => false,
+        }
+    }
+
+    /// <https://www.w3.org/TR/1999/REC-xpath-19991116/#NT-RelativeLocationPath>
+    fn relative_location_path(&mut self, steps: &mut Vec<Step>) -> Fallible<()> {
+        loop {
+            steps.push(self.step()?);
+            match self.peek() {
+                Some(Token::Slash) => {},
+                Some(Token::DoubleSlash) => steps.push(Step::descendant_or_self()),
+                _ => return Ok(()),
+            }
+            self.position += 1;
+        }
+    }
+
+    /// <https://www.w3.org/TR/1999/REC-xpath-19991116/#NT-Step>
+    fn step(&mut self) -> Fallible<Step> {
+        if self.eat(&Token::Dot) {
+            return Ok(Step {
+                axis: Axis::SelfAxis,
+                node_test: NodeTest::Node,
+                predicates: vec![],
+            });
+        }
+        if self.eat(&Token::DotDot) {
+            return Ok(Step {
+                axis: Axis::Parent,
+                node_test: NodeTest::Node,
+                predicates: vec![],
+            });
+        }
+
+        let axis = match self.peek() {
+            Some(Token::At) => {
+                self.position += 1;
+                Axis::Attribute
+            },
+            Some(Token::AxisName(name)) => {
+                let axis = Axis::from_name(name).ok_or(Error::Syntax)?;
+                self.position += 1;
+                self.expect(&Token::DoubleColon)?;
+                axis
+            },
+            _ => Axis::Child,
+        };
+        let node_test = self.node_test()?;
+        let predicates = self.predicates()?;
+        Ok(Step {
+            axis,
+            node_test,
+            predicates,
+        })
+    }
+
+    /// <https://www.w3.org/TR/1999/REC-xpath-19991116/#NT-NodeTest>
+    fn node_test(&mut self) -> Fallible<NodeTest> {
+        match self.next() {
+            Some(Token::NameTest(prefix, local_name)) => {
+                let namespace = match prefix {
+                    Some(prefix) => Some((self.resolve_prefix)(&prefix)?),
+                    None => None,
+                };
+                Ok(NodeTest::Name {
+                    namespace,
+                    local_name: local_name.map(LocalName::from),
+                })
+            },
+            Some(Token::NodeType(node_type)) => {
+                self.expect(&Token::LeftParen)?;
+                let node_test = match &*node_type {
+                    "comment" => NodeTest::Comment,
+                    "text" => NodeTest::Text,
+                    "node" => NodeTest::Node,
+                    _ => match self.peek() {
+                        Some(Token::Literal(target)) => {
+                            let target = target.clone();
+                            self.position += 1;
+                            NodeTest::ProcessingInstruction(Some(target))
+                        },
+                        _ => NodeTest::ProcessingInstruction(None),
+                    },
+                };
+                self.expect(&Token::RightParen)?;
+                Ok(node_test)
+            },
+            _ => Err(Error::Syntax),
+        }
+    }
+
+    fn predicates(&mut self) -> Fallible<Vec<Expr>> {
+        let mut predicates = vec![];
+        while self.eat(&Token::LeftBracket) {
+            predicates.push(self.expr()?);
+            self.expect(&Token::RightBracket)?;
+        }
+        Ok(predicates)
+    }
+
+    /// <https://www.w3.org/TR/1999/REC-xpath-19991116/#NT-PrimaryExpr>
+    fn primary_expr(&mut self) -> Fallible<Expr> {
+        match self.next() {
+            Some(Token::LeftParen) => {
+                let expr = self.expr()?;
+                self.expect(&Token::RightParen)?;
+                Ok(expr)
+            },
+            Some(Token::Literal(literal)) => Ok(Expr::Literal(literal)),
+            Some(Token::Number(number)) => Ok(Expr::Number(number)),
+            Some(Token::FunctionName(name)) => {
+                let function = Function::from_name(&name)
+                    .filter(|function| self.in_stylesheet || !function.is_xslt())
+                    .ok_or(Error::Syntax)?;
+                self.expect(&Token::LeftParen)?;
+                let mut arguments = vec![];
+                if !self.eat(&Token::RightParen) {
+                    loop {
+                        arguments.push(self.expr()?);
+                        if self.eat(&Token::RightParen) {
+                            break;
+                        }
+                        self.expect(&Token::Comma)?;
+                    }
+                }
+                let (min, max) = function.arity();
+                if arguments.len() < min || arguments.len() > max {
+                    return Err(Error::Syntax);
+                }
+                Ok(Expr::Function(function, arguments))
+            },
+            // Variables are only bound by stylesheets.
+            Some(Token::VariableReference(name)) if self.in_stylesheet => Ok(Expr::Variable(name)),
+            _ => Err(Error::Syntax),
+        }
+    }
+}
@@ -0,0 +1,17 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! XSLT 1.0 transformations, as performed by `XSLTProcessor`.
+//!
+//! A stylesheet is compiled into a [`Stylesheet`] when it is imported, whose templates are
+//! then executed to build a result tree from each source node that it transforms. Its
+//! expressions are the ones of the `xpath` module.
+//!
+//! <https://www.w3.org/TR/1999/REC-xslt-19991116>
+
+mod stylesheet;
+mod transform;
+
+pub use self::stylesheet::{compile, OutputMethod, Stylesheet};
+pub use self::transform::transform;
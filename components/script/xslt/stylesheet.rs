@@ -0,0 +1,783 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! The compilation of the elements of a stylesheet into templates of instructions.
+//!
+//! <https://www.w3.org/TR/1999/REC-xslt-19991116#section-Stylesheet-Structure>
+
+use crate::dom::bindings::codegen::Bindings::DocumentBinding::DocumentMethods;
+use crate::dom::bindings::codegen::Bindings::NodeBinding::NodeMethods;
+use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::characterdata::CharacterData;
+use crate::dom::document::Document;
+use crate::dom::element::Element;
+use crate::dom::node::{Node, ShadowIncluding};
+use crate::dom::text::Text;
+use crate::xpath::{self, Axis, BinaryOperator, Expr, Function, NodeTest, PathStart};
+use html5ever::{LocalName, Namespace, QualName};
+
+pub const XSLT_NAMESPACE: &str = "http://www.w3.org/1999/XSL/Transform";
+
+/// The instructions of XSLT that stylesheets can use.
+/// <https://www.w3.org/TR/1999/REC-xslt-19991116#element-syntax-summary>
+pub const INSTRUCTIONS: &[&str] = &[
+    "apply-imports",
+    "apply-templates",
+    "attribute",
+    "call-template",
+    "choose",
+    "comment",
+    "copy",
+    "copy-of",
+    "element",
+    "fallback",
+    "for-each",
+    "if",
+    "message",
+    "number",
+    "processing-instruction",
+    "text",
+    "value-of",
+    "variable",
+];
+
+/// A compiled stylesheet.
+#[derive(Default)]
+pub struct Stylesheet {
+    /// The templates, in the order of the stylesheet.
+    pub templates: Vec<Template>,
+    /// The top-level variables and parameters, in the order of the stylesheet.
+    pub variables: Vec<GlobalVariable>,
+    pub keys: Vec<Key>,
+    pub attribute_sets: Vec<AttributeSet>,
+    /// The elements whose whitespace-only text nodes are stripped from the source tree.
+    pub strip_space: Vec<NameTest>,
+    /// The elements that `strip_space` doesn't apply to.
+    pub preserve_space: Vec<NameTest>,
+    /// The output method, unless it is determined by the result tree.
+    pub output_method: Option<OutputMethod>,
+}
+
+/// <https://www.w3.org/TR/1999/REC-xslt-19991116#section-Defining-Template-Rules>
+pub struct Template {
+    /// The alternatives of the pattern of the template, with their priorities.
+    pub patterns: Vec<(Expr, f64)>,
+    pub name: Option<String>,
+    pub mode: Option<String>,
+    pub params: Vec<Variable>,
+    pub body: Vec<Instruction>,
+}
+
+/// A variable or a parameter, whose name is the qualified name that expressions refer to it
+/// with.
+/// <https://www.w3.org/TR/1999/REC-xslt-19991116#variables>
+pub struct Variable {
+    pub name: String,
+    pub value: VariableValue,
+}
+
+pub enum VariableValue {
+    Expr(Expr),
+    /// A result tree fragment, or the empty string without instructions.
+    Content(Vec<Instruction>),
+}
+
+pub struct GlobalVariable {
+    pub variable: Variable,
+    /// The expanded name of a parameter, which the processor can set.
+    pub parameter: Option<(Namespace, LocalName)>,
+}
+
+/// <https://www.w3.org/TR/1999/REC-xslt-19991116#key>
+pub struct Key {
+    pub name: String,
+    pub pattern: Expr,
+    pub use_: Expr,
+}
+
+/// <https://www.w3.org/TR/1999/REC-xslt-19991116#attribute-sets>
+pub struct AttributeSet {
+    pub name: String,
+    pub attribute_sets: Vec<String>,
+    pub attributes: Vec<Instruction>,
+}
+
+/// A name test of `xsl:strip-space` or `xsl:preserve-space`, where a missing local name
+/// stands for `*`.
+pub struct NameTest {
+    pub namespace: Option<Namespace>,
+    pub local_name: Option<LocalName>,
+}
+
+impl NameTest {
+    pub fn matches(&self, element: &Element) -> bool {
+        self.namespace
+            .as_ref()
+            .map_or(true, |namespace| element.namespace() == namespace) &&
+            self
+                .local_name
+                .as_ref()
+                .map_or(true, |local_name| element.local_name() == local_name)
+    }
+}
+
+/// <https://www.w3.org/TR/1999/REC-xslt-19991116#output>
+#[derive(Clone, Copy, PartialEq)]
+pub enum OutputMethod {
+    Xml,
+    Html,
+    Text,
+}
+
+/// An attribute value template, whose expressions are in braces.
+/// <https://www.w3.org/TR/1999/REC-xslt-19991116#attribute-value-templates>
+pub struct Avt(pub Vec<AvtPart>);
+
+pub enum AvtPart {
+    Text(String),
+    Expr(Expr),
+}
+
+/// <https://www.w3.org/TR/1999/REC-xslt-19991116#sorting>
+pub struct Sort {
+    pub select: Expr,
+    pub order: Option<Avt>,
+    pub data_type: Option<Avt>,
+}
+
+/// The namespaces in scope of an instruction that computes a name, by prefix.
+pub type Namespaces = Vec<(Option<String>, Namespace)>;
+
+/// <https://www.w3.org/TR/1999/REC-xslt-19991116#section-Creating-the-Result-Tree>
+pub enum Instruction {
+    Text(String),
+    LiteralElement {
+        name: QualName,
+        attributes: Vec<(QualName, Avt)>,
+        attribute_sets: Vec<String>,
+        body: Vec<Instruction>,
+    },
+    ApplyTemplates {
+        select: Option<Expr>,
+        mode: Option<String>,
+        sorts: Vec<Sort>,
+        params: Vec<Variable>,
+    },
+    CallTemplate {
+        name: String,
+        params: Vec<Variable>,
+    },
+    ValueOf(Expr),
+    ForEach {
+        select: Expr,
+        sorts: Vec<Sort>,
+        body: Vec<Instruction>,
+    },
+    If {
+        test: Expr,
+        body: Vec<Instruction>,
+    },
+    Choose {
+        cases: Vec<(Expr, Vec<Instruction>)>,
+        otherwise: Vec<Instruction>,
+    },
+    Element {
+        name: Avt,
+        namespace: Option<Avt>,
+        namespaces: Namespaces,
+        attribute_sets: Vec<String>,
+        body: Vec<Instruction>,
+    },
+    Attribute {
+        name: Avt,
+        namespace: Option<Avt>,
+        namespaces: Namespaces,
+        body: Vec<Instruction>,
+    },
+    Comment(Vec<Instruction>),
+    ProcessingInstruction {
+        name: Avt,
+        body: Vec<Instruction>,
+    },
+    Copy {
+        attribute_sets: Vec<String>,
+        body: Vec<Instruction>,
+    },
+    CopyOf(Expr),
+    Variable(Variable),
+    Number {
+        value: Option<Expr>,
+        count: Option<Expr>,
+        format: Avt,
+    },
+    Message {
+        terminate: bool,
+        body: Vec<Instruction>,
+    },
+    /// Instructions that are executed in place of an unsupported one.
+    Fallback(Vec<Instruction>),
+}
+
+/// Compiles the stylesheet of a document or an element.
+pub fn compile(node: &Node) -> Fallible<Stylesheet> {
+    let root = match node.downcast::<Document>() {
+        Some(document) => document.GetDocumentElement(),
+        None => node.downcast::<Element>().map(DomRoot::from_ref),
+    };
+    let root = root.ok_or(Error::Syntax)?;
+
+    let mut stylesheet = Stylesheet::default();
+    if is_xslt(&root, "stylesheet") || is_xslt(&root, "transform") {
+        for child in root.upcast::<Node>().children() {
+            if let Some(element) = child.downcast::<Element>() {
+                top_level_element(&mut stylesheet, element)?;
+            }
+        }
+    } else {
+        // A literal result element is the body of a template that matches the root.
+        // https://www.w3.org/TR/1999/REC-xslt-19991116#result-element-stylesheet
+        stylesheet.templates.push(Template {
+            patterns: vec![(Expr::Path(PathStart::Root, vec![]), 0.5)],
+            name: None,
+            mode: None,
+            params: vec![],
+            body: vec![instruction(&root)?],
+        });
+    }
+    Ok(stylesheet)
+}
+
+fn is_xslt(element: &Element, local_name: &str) -> bool {
+    *element.namespace() == Namespace::from(XSLT_NAMESPACE) && &**element.local_name() == local_name
+}
+
+fn attribute(element: &Element, local_name: &str) -> Option<String> {
+    element
+        .get_attribute(&ns!(), &LocalName::from(local_name))
+        .map(|attr| (**attr.value()).to_owned())
+}
+
+fn required_attribute(element: &Element, local_name: &str) -> Fallible<String> {
+    attribute(element, local_name).ok_or(Error::Syntax)
+}
+
+fn resolve_prefix(element: &Element, prefix: &str) -> Fallible<Namespace> {
+    element
+        .upcast::<Node>()
+        .LookupNamespaceURI(Some(DOMString::from(prefix)))
+        .map(|namespace| Namespace::from(&*namespace))
+        .ok_or(Error::Namespace)
+}
+
+fn expression(element: &Element, expression: &str) -> Fallible<Expr> {
+    xpath::parse_in_stylesheet(expression, &mut |prefix| resolve_prefix(element, prefix))
+}
+
+fn is_whitespace(string: &str) -> bool {
+    string
+        .chars()
+        .all(|c| c == ' ' || c == '\t' || c == '\r' || c == '\n')
+}
+
+/// Splits the value of a `use-attribute-sets` attribute.
+fn attribute_sets(element: &Element, local_name: &str) -> Vec<String> {
+    attribute(element, local_name).map_or(vec![], |names| {
+        names.split_whitespace().map(str::to_owned).collect()
+    })
+}
+
+/// Parses an attribute value template.
+fn avt(element: &Element, value: &str) -> Fallible<Avt> {
+    let mut parts = vec![];
+    let mut text = String::new();
+    let mut chars = value.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                text.push('{');
+            },
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                text.push('}');
+            },
+            '}' => return Err(Error::Syntax),
+            '{' => {
+                // The expression ends at the first `}` that isn't in a literal.
+                let mut source = String::new();
+                let mut quote = None;
+                loop {
+                    match (chars.next(), quote) {
+                        (Some('}'), None) => break,
+                        (Some(c), None) if c == '"' || c == '\'' => {
+                            quote = Some(c);
+                            source.push(c);
+                        },
+                        (Some(c), Some(q)) if c == q => {
+                            quote = None;
+                            source.push(c);
+                        },
+                        (Some(c), _) => source.push(c),
+                        (None, _) => return Err(Error::Syntax),
+                    }
+                }
+                if !text.is_empty() {
+                    parts.push(AvtPart::Text(std::mem::replace(&mut text, String::new())));
+                }
+                parts.push(AvtPart::Expr(expression(element, &source)?));
+            },
+            c => text.push(c),
+        }
+    }
+    if !text.is_empty() {
+        parts.push(AvtPart::Text(text));
+    }
+    Ok(Avt(parts))
+}
+
+/// Whether an expression is a pattern, which only selects nodes with the child and the
+/// attribute axes from the root, an element with an id or a key.
+/// <https://www.w3.org/TR/1999/REC-xslt-19991116#NT-Pattern>
+fn is_pattern(expr: &Expr) -> bool {
+    match *expr {
+        Expr::Binary(ref lhs, BinaryOperator::Union, ref rhs) => is_pattern(lhs) && is_pattern(rhs),
+        Expr::Path(ref start, ref steps) => {
+            let valid_start = match *start {
+                PathStart::Root | PathStart::ContextNode => true,
+                PathStart::Expr(ref expr) => is_pattern(expr),
+            };
+            valid_start &&
+                steps.iter().all(|step| match step.axis {
+                    Axis::Child | Axis::Attribute => true,
+                    // What `//` abbreviates.
+                    Axis::DescendantOrSelf => {
+                        matches!(step.node_test, NodeTest::Node) && step.predicates.is_empty()
+                    },
+                    _ => false,
+                })
+        },
+        Expr::Function(Function::Id, ref arguments) |
+        Expr::Function(Function::Key, ref arguments) => arguments
+            .iter()
+            .all(|argument| matches!(*argument, Expr::Literal(_))),
+        _ => false,
+    }
+}
+
+/// Splits a pattern into its alternatives.
+fn alternatives(expr: Expr, alternatives: &mut Vec<Expr>) {
+    match expr {
+        Expr::Binary(lhs, BinaryOperator::Union, rhs) => {
+            self::alternatives(*lhs, alternatives);
+            self::alternatives(*rhs, alternatives);
+        },
+        expr => alternatives.push(expr),
+    }
+}
+
+/// The priority of a pattern without union, which is lower for the less specific ones.
+/// <https://www.w3.org/TR/1999/REC-xslt-19991116#conflict>
+fn default_priority(pattern: &Expr) -> f64 {
+    match *pattern {
+        Expr::Path(PathStart::ContextNode, ref steps)
+            if steps.len() == 1 && steps[0].predicates.is_empty() =>
+        {
+            match steps[0].node_test {
+                NodeTest::Name {
+                    local_name: Some(_),
+                    ..
+                } |
+                NodeTest::ProcessingInstruction(Some(_)) => 0.,
+                NodeTest::Name {
+                    namespace: Some(_),
+                    local_name: None,
+                } => -0.25,
+                _ => -0.5,
+            }
+        },
+        _ => 0.5,
+    }
+}
+
+fn pattern(element: &Element, value: &str) -> Fallible<Expr> {
+    let pattern = expression(element, value)?;
+    if !is_pattern(&pattern) {
+        return Err(Error::Syntax);
+    }
+    Ok(pattern)
+}
+
+fn name_tests(element: &Element) -> Fallible<Vec<NameTest>> {
+    let elements = required_attribute(element, "elements")?;
+    elements
+        .split_whitespace()
+        .map(|name| {
+            let (prefix, local_name) = match name.find(':') {
+                Some(index) => (Some(&name[..index]), &name[index + 1..]),
+                None => (None, name),
+            };
+            let namespace = match prefix {
+                Some(prefix) => Some(resolve_prefix(element, prefix)?),
+                None if local_name == "*" => None,
+                None => Some(ns!()),
+            };
+            let local_name = match local_name {
+                "*" => None,
+                local_name => Some(LocalName::from(local_name)),
+            };
+            Ok(NameTest {
+                namespace,
+                local_name,
+            })
+        })
+        .collect()
+}
+
+/// <https://www.w3.org/TR/1999/REC-xslt-19991116#stylesheet-element>
+fn top_level_element(stylesheet: &mut Stylesheet, element: &Element) -> Fallible<()> {
+    // Elements in other namespaces are ignored.
+    if *element.namespace() != Namespace::from(XSLT_NAMESPACE) {
+        return Ok(());
+    }
+    match &**element.local_name() {
+        "template" => {
+            let name = attribute(element, "name");
+            let priority = match attribute(element, "priority") {
+                Some(priority) => Some(priority.trim().parse().map_err(|_| Error::Syntax)?),
+                None => None,
+            };
+            let mut patterns = vec![];
+            if let Some(value) = attribute(element, "match") {
+                let mut exprs = vec![];
+                alternatives(pattern(element, &value)?, &mut exprs);
+                for expr in exprs {
+                    let priority = priority.unwrap_or_else(|| default_priority(&expr));
+                    patterns.push((expr, priority));
+                }
+            } else if name.is_none() {
+                return Err(Error::Syntax);
+            }
+            let mut params = vec![];
+            for child in element.upcast::<Node>().children() {
+                if let Some(child) = child.downcast::<Element>() {
+                    if is_xslt(child, "param") {
+                        params.push(variable(child)?);
+                    }
+                }
+            }
+            stylesheet.templates.push(Template {
+                patterns,
+                name,
+                mode: attribute(element, "mode"),
+                params,
+                body: body(element, &["param"])?,
+            });
+        },
+        "variable" | "param" => {
+            let parameter = if is_xslt(element, "param") {
+                let name = required_attribute(element, "name")?;
+                let (namespace, local_name) = match name.find(':') {
+                    Some(index) => (resolve_prefix(element, &name[..index])?, &name[index + 1..]),
+                    None => (ns!(), &*name),
+                };
+                Some((namespace, LocalName::from(local_name)))
+            } else {
+                None
+            };
+            stylesheet.variables.push(GlobalVariable {
+                variable: variable(element)?,
+                parameter,
+            });
+        },
+        "output" => {
+            stylesheet.output_method = match attribute(element, "method").as_ref().map(|m| &**m) {
+                Some("xml") => Some(OutputMethod::Xml),
+                Some("html") => Some(OutputMethod::Html),
+                Some("text") => Some(OutputMethod::Text),
+                _ => stylesheet.output_method,
+            };
+        },
+        "key" => stylesheet.keys.push(Key {
+            name: required_attribute(element, "name")?,
+            pattern: pattern(element, &required_attribute(element, "match")?)?,
+            use_: expression(element, &required_attribute(element, "use")?)?,
+        }),
+        "attribute-set" => {
+            let mut attributes = vec![];
+            for child in element.upcast::<Node>().children() {
+                if let Some(child) = child.downcast::<Element>() {
+                    if !is_xslt(child, "attribute") {
+                        return Err(Error::Syntax);
+                    }
+                    attributes.push(instruction(child)?);
+                }
+            }
+            stylesheet.attribute_sets.push(AttributeSet {
+                name: required_attribute(element, "name")?,
+                attribute_sets: attribute_sets(element, "use-attribute-sets"),
+                attributes,
+            });
+        },
+        "strip-space" => stylesheet.strip_space.extend(name_tests(element)?),
+        "preserve-space" => stylesheet.preserve_space.extend(name_tests(element)?),
+        // Other stylesheets can't be loaded synchronously.
+        "import" | "include" => return Err(Error::NotSupported),
+        "decimal-format" | "namespace-alias" => {},
+        _ => return Err(Error::Syntax),
+    }
+    Ok(())
+}
+
+/// <https://www.w3.org/TR/1999/REC-xslt-19991116#variables>
+fn variable(element: &Element) -> Fallible<Variable> {
+    let value = match attribute(element, "select") {
+        Some(select) => VariableValue::Expr(expression(element, &select)?),
+        None => VariableValue::Content(body(element, &[])?),
+    };
+    Ok(Variable {
+        name: required_attribute(element, "name")?,
+        value,
+    })
+}
+
+fn params(element: &Element) -> Fallible<Vec<Variable>> {
+    let mut params = vec![];
+    for child in element.upcast::<Node>().children() {
+        if let Some(child) = child.downcast::<Element>() {
+            if is_xslt(child, "with-param") {
+                params.push(variable(child)?);
+            }
+        }
+    }
+    Ok(params)
+}
+
+fn sorts(element: &Element) -> Fallible<Vec<Sort>> {
+    let mut sorts = vec![];
+    for child in element.upcast::<Node>().children() {
+        if let Some(child) = child.downcast::<Element>() {
+            if is_xslt(child, "sort") {
+                let select = attribute(child, "select").unwrap_or_else(|| ".".to_owned());
+                sorts.push(Sort {
+                    select: expression(child, &select)?,
+                    order: attribute(child, "order")
+                        .map(|order| avt(child, &order))
+                        .transpose()?,
+                    data_type: attribute(child, "data-type")
+                        .map(|data_type| avt(child, &data_type))
+                        .transpose()?,
+                });
+            }
+        }
+    }
+    Ok(sorts)
+}
+
+/// The namespaces in scope of an element, as declared by it and its ancestors.
+fn namespaces(element: &Element) -> Namespaces {
+    let mut namespaces: Namespaces = vec![];
+    for ancestor in element
+        .upcast::<Node>()
+        .inclusive_ancestors(ShadowIncluding::No)
+        .filter_map(DomRoot::downcast::<Element>)
+    {
+        for attr in ancestor.attrs().iter() {
+            if *attr.namespace() != ns!(xmlns) {
+                continue;
+            }
+            let prefix = match attr.prefix() {
+                Some(_) => Some(attr.local_name().to_string()),
+                None => None,
+            };
+            // The declarations of the nearest elements take precedence.
+            if namespaces
+                .iter()
+                .all(|&(ref declared, _)| *declared != prefix)
+            {
+                namespaces.push((prefix, Namespace::from(&**attr.value())));
+            }
+        }
+    }
+    namespaces
+}
+
+/// Compiles the children of an element, but the XSLT elements with the given names, which
+/// the element handles itself.
+fn body(element: &Element, skipped: &[&str]) -> Fallible<Vec<Instruction>> {
+    let preserve_whitespace = is_xslt(element, "text");
+    let mut instructions = vec![];
+    for child in element.upcast::<Node>().children() {
+        if let Some(text) = child.downcast::<Text>() {
+            let data = text.upcast::<CharacterData>().data();
+            // Whitespace-only text is stripped from stylesheets.
+            if preserve_whitespace || !is_whitespace(&data) {
+                instructions.push(Instruction::Text(data.to_string()));
+            }
+        } else if let Some(child) = child.downcast::<Element>() {
+            if skipped.iter().any(|name| is_xslt(child, name)) {
+                continue;
+            }
+            instructions.push(instruction(child)?);
+        }
+    }
+    Ok(instructions)
+}
+
+/// Compiles an instruction or a literal result element.
+fn instruction(element: &Element) -> Fallible<Instruction> {
+    if *element.namespace() != Namespace::from(XSLT_NAMESPACE) {
+        return literal_element(element);
+    }
+    let instruction = match &**element.local_name() {
+        "text" => Instruction::Text(element.upcast::<Node>().child_text_content().to_string()),
+        "apply-templates" => Instruction::ApplyTemplates {
+            select: attribute(element, "select")
+                .map(|select| expression(element, &select))
+                .transpose()?,
+            mode: attribute(element, "mode"),
+            sorts: sorts(element)?,
+            params: params(element)?,
+        },
+        "call-template" => Instruction::CallTemplate {
+            name: required_attribute(element, "name")?,
+            params: params(element)?,
+        },
+        "value-of" => Instruction::ValueOf(expression(
+            element,
+            &required_attribute(element, "select")?,
+        )?),
+        "for-each" => Instruction::ForEach {
+            select: expression(element, &required_attribute(element, "select")?)?,
+            sorts: sorts(element)?,
+            body: body(element, &["sort"])?,
+        },
+        "if" => Instruction::If {
+            test: expression(element, &required_attribute(element, "test")?)?,
+            body: body(element, &[])?,
+        },
+        "choose" => {
+            let mut cases = vec![];
+            let mut otherwise = vec![];
+            for child in element.upcast::<Node>().children() {
+                if let Some(child) = child.downcast::<Element>() {
+                    if is_xslt(child, "when") {
+                        let test = expression(child, &required_attribute(child, "test")?)?;
+                        cases.push((test, body(child, &[])?));
+                    } else if is_xslt(child, "otherwise") {
+                        otherwise = body(child, &[])?;
+                    } else {
+                        return Err(Error::Syntax);
+                    }
+                }
+            }
+            Instruction::Choose { cases, otherwise }
+        },
+        "element" => Instruction::Element {
+            name: avt(element, &required_attribute(element, "name")?)?,
+            namespace: attribute(element, "namespace")
+                .map(|namespace| avt(element, &namespace))
+                .transpose()?,
+            namespaces: namespaces(element),
+            attribute_sets: attribute_sets(element, "use-attribute-sets"),
+            body: body(element, &[])?,
+        },
+        "attribute" => Instruction::Attribute {
+            name: avt(element, &required_attribute(element, "name")?)?,
+            namespace: attribute(element, "namespace")
+                .map(|namespace| avt(element, &namespace))
+                .transpose()?,
+            namespaces: namespaces(element),
+            body: body(element, &[])?,
+        },
+        "comment" => Instruction::Comment(body(element, &[])?),
+        "processing-instruction" => Instruction::ProcessingInstruction {
+            name: avt(element, &required_attribute(element, "name")?)?,
+            body: body(element, &[])?,
+        },
+        "copy" => Instruction::Copy {
+            attribute_sets: attribute_sets(element, "use-attribute-sets"),
+            body: body(element, &[])?,
+        },
+        "copy-of" => Instruction::CopyOf(expression(
+            element,
+            &required_attribute(element, "select")?,
+        )?),
+        "variable" | "param" => Instruction::Variable(variable(element)?),
+        "number" => Instruction::Number {
+            value: attribute(element, "value")
+                .map(|value| expression(element, &value))
+                .transpose()?,
+            count: attribute(element, "count")
+                .map(|count| pattern(element, &count))
+                .transpose()?,
+            format: avt(
+                element,
+                &attribute(element, "format").unwrap_or_else(|| "1".to_owned()),
+            )?,
+        },
+        "message" => Instruction::Message {
+            terminate: attribute(element, "terminate").map_or(false, |t| t == "yes"),
+            body: body(element, &[])?,
+        },
+        // Without imports, there are no imported templates to apply.
+        "apply-imports" => Instruction::Fallback(vec![]),
+        // The fallback of supported instructions is ignored.
+        "fallback" => Instruction::Fallback(vec![]),
+        _ => {
+            // Unknown instructions are replaced by their fallback, if any.
+            // https://www.w3.org/TR/1999/REC-xslt-19991116#fallback
+            let mut fallback = None;
+            for child in element.upcast::<Node>().children() {
+                if let Some(child) = child.downcast::<Element>() {
+                    if is_xslt(child, "fallback") {
+                        fallback = Some(body(child, &[])?);
+                    }
+                }
+            }
+            return fallback.map(Instruction::Fallback).ok_or(Error::Syntax);
+        },
+    };
+    Ok(instruction)
+}
+
+/// <https://www.w3.org/TR/1999/REC-xslt-19991116#literal-result-element>
+fn literal_element(element: &Element) -> Fallible<Instruction> {
+    let name = QualName::new(
+        element.prefix().clone(),
+        element.namespace().clone(),
+        element.local_name().clone(),
+    );
+    let mut attributes = vec![];
+    for attr in element.attrs().iter() {
+        // The attributes in the XSLT namespace are for the processor. Namespace
+        // declarations aren't copied either, as the result tree is serialized with the ones
+        // that its names need.
+        if *attr.namespace() == Namespace::from(XSLT_NAMESPACE) || *attr.namespace() == ns!(xmlns) {
+            continue;
+        }
+        let name = QualName::new(
+            attr.prefix().cloned(),
+            attr.namespace().clone(),
+            attr.local_name().clone(),
+        );
+        attributes.push((name, avt(element, &attr.value())?));
+    }
+    let attribute_sets = element
+        .get_attribute(
+            &Namespace::from(XSLT_NAMESPACE),
+            &LocalName::from("use-attribute-sets"),
+        )
+        .map_or(vec![], |attr| {
+            attr.value().split_whitespace().map(str::to_owned).collect()
+        });
+    Ok(Instruction::LiteralElement {
+        name,
+        attributes,
+        attribute_sets,
+        body: body(element, &[])?,
+    })
+}
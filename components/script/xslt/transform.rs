@@ -0,0 +1,950 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! The execution of the templates of a stylesheet, which builds a result tree from a source
+//! tree.
+//!
+//! <https://www.w3.org/TR/1999/REC-xslt-19991116#section-Template-Rules>
+
+use super::stylesheet::{Avt, AvtPart, Instruction, Namespaces, Sort, Stylesheet, Template};
+use super::stylesheet::{Variable, VariableValue, INSTRUCTIONS};
+use crate::dom::attr::Attr;
+use crate::dom::bindings::codegen::Bindings::CharacterDataBinding::CharacterDataMethods;
+use crate::dom::bindings::codegen::Bindings::DocumentBinding::DocumentMethods;
+use crate::dom::bindings::codegen::Bindings::ElementBinding::ElementMethods;
+use crate::dom::bindings::codegen::Bindings::NodeBinding::NodeMethods;
+use crate::dom::bindings::codegen::UnionTypes::StringOrElementCreationOptions;
+use crate::dom::bindings::error::{Error, ErrorResult, Fallible};
+use crate::dom::bindings::inheritance::{Castable, NodeTypeId};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::characterdata::CharacterData;
+use crate::dom::comment::Comment;
+use crate::dom::document::Document;
+use crate::dom::documentfragment::DocumentFragment;
+use crate::dom::element::{CustomElementCreationMode, Element, ElementCreator};
+use crate::dom::node::{CloneChildrenFlag, Node, ShadowIncluding};
+use crate::dom::processinginstruction::ProcessingInstruction;
+use crate::dom::text::Text;
+use crate::xpath::{self, Environment, Expr, Value};
+use html5ever::{LocalName, Namespace, QualName};
+use std::cell::Cell;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+/// How deep templates can be applied and called within each other.
+const MAX_DEPTH: usize = 500;
+
+/// Transforms a source node with a stylesheet, into a fragment of the result tree owned by
+/// `document`. Elements without namespace are created as HTML elements if `html` is set.
+pub fn transform(
+    stylesheet: &Stylesheet,
+    source: &Node,
+    parameters: &HashMap<(Namespace, LocalName), Value>,
+    document: &Document,
+    html: bool,
+) -> Fallible<DomRoot<DocumentFragment>> {
+    let mut transform = Transform {
+        stylesheet,
+        document,
+        html,
+        globals: vec![],
+        depth: Cell::new(0),
+    };
+
+    // The top-level variables can refer to the ones before them.
+    let root = source
+        .inclusive_ancestors(ShadowIncluding::No)
+        .last()
+        .unwrap();
+    let mut globals = vec![];
+    for global in &stylesheet.variables {
+        let parameter = global
+            .parameter
+            .as_ref()
+            .and_then(|name| parameters.get(name));
+        let value = match parameter {
+            Some(value) => value.clone(),
+            None => {
+                let context = Context {
+                    node: root.clone(),
+                    position: 1,
+                    size: 1,
+                    variables: globals.clone(),
+                };
+                transform.variable_value(&global.variable, &context)?
+            },
+        };
+        globals.push((global.variable.name.clone(), value));
+    }
+    transform.globals = globals;
+
+    let fragment = DocumentFragment::new(document);
+    transform.apply_templates(
+        vec![DomRoot::from_ref(source)],
+        None,
+        &[],
+        fragment.upcast(),
+    )?;
+    Ok(fragment)
+}
+
+/// <https://www.w3.org/TR/1999/REC-xslt-19991116#dt-current-node>
+#[derive(Clone)]
+struct Context {
+    node: DomRoot<Node>,
+    position: usize,
+    size: usize,
+    /// The local variables and parameters in scope.
+    variables: Vec<(String, Value)>,
+}
+
+struct Transform<'a> {
+    stylesheet: &'a Stylesheet,
+    document: &'a Document,
+    html: bool,
+    globals: Vec<(String, Value)>,
+    depth: Cell<usize>,
+}
+
+/// The environment of the expressions evaluated with a context.
+struct Scope<'a, 'b> {
+    transform: &'b Transform<'a>,
+    context: &'b Context,
+}
+
+impl<'a, 'b> Environment for Scope<'a, 'b> {
+    fn variable(&self, name: &str) -> Option<Value> {
+        self.context
+            .variables
+            .iter()
+            .rev()
+            .chain(self.transform.globals.iter())
+            .find(|&&(ref variable, _)| variable == name)
+            .map(|&(_, ref value)| value.clone())
+    }
+
+    fn current_node(&self) -> DomRoot<Node> {
+        self.context.node.clone()
+    }
+
+    fn key(&self, name: &str, value: &str, root: &Node) -> Fallible<Vec<DomRoot<Node>>> {
+        self.transform.key(name, value, root)
+    }
+
+    fn element_available(&self, name: &str) -> bool {
+        name.starts_with("xsl:") && INSTRUCTIONS.contains(&&name[4..])
+    }
+}
+
+impl<'a> Transform<'a> {
+    fn evaluate(&self, expr: &Expr, context: &Context) -> Fallible<Value> {
+        let scope = Scope {
+            transform: self,
+            context,
+        };
+        xpath::evaluate_in_stylesheet(expr, &context.node, context.position, context.size, &scope)
+    }
+
+    fn matches(&self, pattern: &Expr, node: &Node) -> Fallible<bool> {
+        let context = Context {
+            node: DomRoot::from_ref(node),
+            position: 1,
+            size: 1,
+            variables: vec![],
+        };
+        let scope = Scope {
+            transform: self,
+            context: &context,
+        };
+        xpath::matches_pattern(pattern, node, &scope)
+    }
+
+    /// Evaluates an attribute value template.
+    fn avt(&self, avt: &Avt, context: &Context) -> Fallible<String> {
+        let mut string = String::new();
+        for part in &avt.0 {
+            match *part {
+                AvtPart::Text(ref text) => string.push_str(text),
+                AvtPart::Expr(ref expr) => string.push_str(&self.evaluate(expr, context)?.string()),
+            }
+        }
+        Ok(string)
+    }
+
+    /// <https://www.w3.org/TR/1999/REC-xslt-19991116#key>
+    fn key(&self, name: &str, value: &str, root: &Node) -> Fallible<Vec<DomRoot<Node>>> {
+        let mut nodes = vec![];
+        for key in self.stylesheet.keys.iter().filter(|key| key.name == name) {
+            for node in root.traverse_preorder(ShadowIncluding::No) {
+                let mut candidates = vec![node.clone()];
+                if let Some(element) = node.downcast::<Element>() {
+                    candidates.extend(
+                        element
+                            .attrs()
+                            .iter()
+                            .map(|attr| DomRoot::upcast(DomRoot::from_ref(&**attr))),
+                    );
+                }
+                for candidate in candidates {
+                    if !self.matches(&key.pattern, &candidate)? {
+                        continue;
+                    }
+                    let context = Context {
+                        node: candidate.clone(),
+                        position: 1,
+                        size: 1,
+                        variables: vec![],
+                    };
+                    let matches = match self.evaluate(&key.use_, &context)? {
+                        Value::NodeSet(nodes) => {
+                            nodes.iter().any(|node| xpath::string_value(node) == value)
+                        },
+                        other => other.string() == value,
+                    };
+                    if matches {
+                        nodes.push(candidate);
+                    }
+                }
+            }
+        }
+        Ok(nodes)
+    }
+
+    /// Whether a text node of the source tree is stripped from it, as its whitespace isn't
+    /// preserved.
+    /// <https://www.w3.org/TR/1999/REC-xslt-19991116#strip>
+    fn is_stripped(&self, node: &Node) -> bool {
+        let text = match node.downcast::<Text>() {
+            Some(text) => text,
+            None => return false,
+        };
+        let parent = match node.GetParentElement() {
+            Some(parent) => parent,
+            None => return false,
+        };
+        let data = text.upcast::<CharacterData>().data();
+        data.chars()
+            .all(|c| c == ' ' || c == '\t' || c == '\r' || c == '\n') &&
+            self
+                .stylesheet
+                .strip_space
+                .iter()
+                .any(|test| test.matches(&parent)) &&
+            !self
+                .stylesheet
+                .preserve_space
+                .iter()
+                .any(|test| test.matches(&parent))
+    }
+
+    fn children(&self, node: &Node) -> Vec<DomRoot<Node>> {
+        node.children()
+            .filter(|child| !self.is_stripped(child))
+            .collect()
+    }
+
+    fn select(&self, select: &Expr, context: &Context) -> Fallible<Vec<DomRoot<Node>>> {
+        let mut nodes = self.evaluate(select, context)?.into_node_set()?;
+        nodes.retain(|node| !self.is_stripped(node));
+        Ok(nodes)
+    }
+
+    /// Sorts the nodes selected by an instruction.
+    /// <https://www.w3.org/TR/1999/REC-xslt-19991116#sorting>
+    fn sort(
+        &self,
+        nodes: Vec<DomRoot<Node>>,
+        sorts: &[Sort],
+        context: &Context,
+    ) -> Fallible<Vec<DomRoot<Node>>> {
+        if sorts.is_empty() {
+            return Ok(nodes);
+        }
+        let mut orders = vec![];
+        for sort in sorts {
+            let descending = match sort.order {
+                Some(ref order) => self.avt(order, context)? == "descending",
+                None => false,
+            };
+            let numeric = match sort.data_type {
+                Some(ref data_type) => self.avt(data_type, context)? == "number",
+                None => false,
+            };
+            orders.push((descending, numeric));
+        }
+
+        // The sort keys are evaluated with each node as the current node.
+        let size = nodes.len();
+        let mut keyed = vec![];
+        for (index, node) in nodes.into_iter().enumerate() {
+            let context = Context {
+                node,
+                position: index + 1,
+                size,
+                variables: context.variables.clone(),
+            };
+            let mut keys = vec![];
+            for sort in sorts {
+                keys.push(self.evaluate(&sort.select, &context)?.string());
+            }
+            keyed.push((keys, context.node));
+        }
+        keyed.sort_by(|a, b| {
+            for (index, &(descending, numeric)) in orders.iter().enumerate() {
+                let ordering = if numeric {
+                    compare_numbers(
+                        Value::String(a.0[index].clone()).number(),
+                        Value::String(b.0[index].clone()).number(),
+                    )
+                } else {
+                    a.0[index].cmp(&b.0[index])
+                };
+                let ordering = if descending {
+                    ordering.reverse()
+                } else {
+                    ordering
+                };
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+            }
+            Ordering::Equal
+        });
+        Ok(keyed.into_iter().map(|(_, node)| node).collect())
+    }
+
+    fn variable_value(&self, variable: &Variable, context: &Context) -> Fallible<Value> {
+        match variable.value {
+            VariableValue::Expr(ref expr) => self.evaluate(expr, context),
+            VariableValue::Content(ref body) if body.is_empty() => Ok(Value::String(String::new())),
+            VariableValue::Content(ref body) => {
+                // Result tree fragments are node-sets of a document fragment.
+                let fragment = DocumentFragment::new(self.document);
+                self.execute(body, context, fragment.upcast())?;
+                Ok(Value::NodeSet(vec![DomRoot::upcast(fragment)]))
+            },
+        }
+    }
+
+    fn params(&self, params: &[Variable], context: &Context) -> Fallible<Vec<(String, Value)>> {
+        params
+            .iter()
+            .map(|param| Ok((param.name.clone(), self.variable_value(param, context)?)))
+            .collect()
+    }
+
+    /// The template rule for a node with the highest priority, the last one of which wins.
+    /// <https://www.w3.org/TR/1999/REC-xslt-19991116#conflict>
+    fn find_template(&self, node: &Node, mode: Option<&str>) -> Fallible<Option<&'a Template>> {
+        let mut best: Option<(&'a Template, f64)> = None;
+        for template in &self.stylesheet.templates {
+            if template.mode.as_ref().map(|mode| &**mode) != mode {
+                continue;
+            }
+            for &(ref pattern, priority) in &template.patterns {
+                if best.map_or(false, |(_, best)| priority < best) {
+                    continue;
+                }
+                if self.matches(pattern, node)? {
+                    best = Some((template, priority));
+                }
+            }
+        }
+        Ok(best.map(|(template, _)| template))
+    }
+
+    /// <https://www.w3.org/TR/1999/REC-xslt-19991116#section-Applying-Template-Rules>
+    fn apply_templates(
+        &self,
+        nodes: Vec<DomRoot<Node>>,
+        mode: Option<&str>,
+        params: &[(String, Value)],
+        parent: &Node,
+    ) -> ErrorResult {
+        let size = nodes.len();
+        for (index, node) in nodes.into_iter().enumerate() {
+            let context = Context {
+                node,
+                position: index + 1,
+                size,
+                variables: vec![],
+            };
+            match self.find_template(&context.node, mode)? {
+                Some(template) => self.call_template(template, &context, params, parent)?,
+                None => self.built_in_template(&context, mode, parent)?,
+            }
+        }
+        Ok(())
+    }
+
+    /// <https://www.w3.org/TR/1999/REC-xslt-19991116#built-in-rule>
+    fn built_in_template(
+        &self,
+        context: &Context,
+        mode: Option<&str>,
+        parent: &Node,
+    ) -> ErrorResult {
+        match context.node.type_id() {
+            NodeTypeId::Document(_) | NodeTypeId::DocumentFragment(_) | NodeTypeId::Element(_) => {
+                self.apply_templates(self.children(&context.node), mode, &[], parent)
+            },
+            NodeTypeId::Attr => self.add_text(parent, &xpath::string_value(&context.node)),
+            _ if context.node.is::<Text>() => {
+                self.add_text(parent, &xpath::string_value(&context.node))
+            },
+            // Comments and processing instructions.
+            _ => Ok(()),
+        }
+    }
+
+    /// Instantiates a template with the current node of the context, binding its
+    /// parameters to the given values or their default ones.
+    fn call_template(
+        &self,
+        template: &Template,
+        context: &Context,
+        params: &[(String, Value)],
+        parent: &Node,
+    ) -> ErrorResult {
+        if self.depth.get() >= MAX_DEPTH {
+            return Err(Error::Range(
+                "Too much recursion in the stylesheet".to_owned(),
+            ));
+        }
+        self.depth.set(self.depth.get() + 1);
+
+        let mut context = Context {
+            variables: vec![],
+            ..context.clone()
+        };
+        let mut result = Ok(());
+        for param in &template.params {
+            let value = match params.iter().find(|&&(ref name, _)| *name == param.name) {
+                Some(&(_, ref value)) => value.clone(),
+                None => match self.variable_value(param, &context) {
+                    Ok(value) => value,
+                    Err(error) => {
+                        result = Err(error);
+                        break;
+                    },
+                },
+            };
+            context.variables.push((param.name.clone(), value));
+        }
+        if result.is_ok() {
+            result = self.execute(&template.body, &context, parent);
+        }
+
+        self.depth.set(self.depth.get() - 1);
+        result
+    }
+
+    fn use_attribute_sets(
+        &self,
+        names: &[String],
+        context: &Context,
+        parent: &Node,
+    ) -> ErrorResult {
+        for name in names {
+            let sets = self
+                .stylesheet
+                .attribute_sets
+                .iter()
+                .filter(|set| set.name == *name);
+            for set in sets {
+                if self.depth.get() >= MAX_DEPTH {
+                    return Err(Error::Range(
+                        "Too much recursion in the stylesheet".to_owned(),
+                    ));
+                }
+                self.depth.set(self.depth.get() + 1);
+                let result = self.use_attribute_sets(&set.attribute_sets, context, parent);
+                self.depth.set(self.depth.get() - 1);
+                result?;
+
+                // Only the global variables are in scope of attribute sets.
+                let context = Context {
+                    variables: vec![],
+                    ..context.clone()
+                };
+                self.execute(&set.attributes, &context, parent)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// The string value of the nodes that instructions create, for the instructions whose
+    /// content is text.
+    fn text_content(&self, body: &[Instruction], context: &Context) -> Fallible<String> {
+        let fragment = DocumentFragment::new(self.document);
+        self.execute(body, context, fragment.upcast())?;
+        Ok(xpath::string_value(fragment.upcast()))
+    }
+
+    fn add_text(&self, parent: &Node, text: &str) -> ErrorResult {
+        if text.is_empty() {
+            return Ok(());
+        }
+        // Adjacent text is merged into one node.
+        if let Some(last) = parent.GetLastChild() {
+            if let Some(last) = last.downcast::<CharacterData>() {
+                if last.is::<Text>() {
+                    last.AppendData(DOMString::from(text));
+                    return Ok(());
+                }
+            }
+        }
+        let text = self.document.CreateTextNode(DOMString::from(text));
+        parent.AppendChild(text.upcast()).map(|_| ())
+    }
+
+    /// Adds an attribute to the element being created, unless children were already added
+    /// to it, which is an error that is recovered from by ignoring the attribute.
+    fn add_attribute(
+        &self,
+        parent: &Node,
+        namespace: Namespace,
+        name: &str,
+        value: String,
+    ) -> ErrorResult {
+        let element = match parent.downcast::<Element>() {
+            Some(element) if !parent.HasChildNodes() => element,
+            _ => return Ok(()),
+        };
+        element.SetAttributeNS(
+            Node::namespace_to_string(namespace),
+            DOMString::from(name),
+            DOMString::from(value),
+        )
+    }
+
+    /// The namespace of a computed name, given the namespace that the instruction computed,
+    /// if any, or the namespaces in scope of the instruction.
+    fn namespace(
+        &self,
+        name: &str,
+        namespace: Option<String>,
+        namespaces: &Namespaces,
+        is_element: bool,
+    ) -> Fallible<Namespace> {
+        if let Some(namespace) = namespace {
+            return Ok(Namespace::from(namespace));
+        }
+        let prefix = name.find(':').map(|index| name[..index].to_owned());
+        // Names without prefix are in the default namespace for elements only.
+        if prefix.is_none() && !is_element {
+            return Ok(ns!());
+        }
+        match namespaces
+            .iter()
+            .find(|&&(ref declared, _)| *declared == prefix)
+        {
+            Some(&(_, ref namespace)) => Ok(namespace.clone()),
+            None if prefix.is_none() => Ok(ns!()),
+            None => Err(Error::Namespace),
+        }
+    }
+
+    fn result_namespace(&self, namespace: &Namespace) -> Namespace {
+        if self.html && *namespace == ns!() {
+            ns!(html)
+        } else {
+            namespace.clone()
+        }
+    }
+
+    fn create_element(&self, name: &QualName) -> DomRoot<Element> {
+        let name = QualName::new(
+            name.prefix.clone(),
+            self.result_namespace(&name.ns),
+            name.local.clone(),
+        );
+        Element::create(
+            name,
+            None,
+            self.document,
+            ElementCreator::ScriptCreated,
+            CustomElementCreationMode::Synchronous,
+        )
+    }
+
+    /// Copies a node of the source tree into the result tree, as `xsl:copy-of` does.
+    /// <https://www.w3.org/TR/1999/REC-xslt-19991116#copy-of>
+    fn copy_of(&self, node: &Node, parent: &Node) -> ErrorResult {
+        match node.type_id() {
+            NodeTypeId::Document(_) | NodeTypeId::DocumentFragment(_) => {
+                for child in node.children() {
+                    self.copy_of(&child, parent)?;
+                }
+                Ok(())
+            },
+            NodeTypeId::Attr => {
+                let (namespace, name) = attr_name(node);
+                self.add_attribute(parent, namespace, &name, xpath::string_value(node))
+            },
+            NodeTypeId::DocumentType => Ok(()),
+            _ if node.is::<Text>() => self.add_text(parent, &xpath::string_value(node)),
+            _ => {
+                let copy = Node::clone(node, Some(self.document), CloneChildrenFlag::CloneChildren);
+                parent.AppendChild(&copy).map(|_| ())
+            },
+        }
+    }
+
+    /// Executes instructions in turn, with the variables that they bind in scope of the ones
+    /// after them.
+    fn execute(
+        &self,
+        instructions: &[Instruction],
+        context: &Context,
+        parent: &Node,
+    ) -> ErrorResult {
+        for (index, instruction) in instructions.iter().enumerate() {
+            if let Instruction::Variable(ref variable) = *instruction {
+                let value = self.variable_value(variable, context)?;
+                let mut context = context.clone();
+                context.variables.push((variable.name.clone(), value));
+                return self.execute(&instructions[index + 1..], &context, parent);
+            }
+            self.instruction(instruction, context, parent)?;
+        }
+        Ok(())
+    }
+
+    fn instruction(
+        &self,
+        instruction: &Instruction,
+        context: &Context,
+        parent: &Node,
+    ) -> ErrorResult {
+        match *instruction {
+            Instruction::Text(ref text) => self.add_text(parent, text),
+            Instruction::LiteralElement {
+                ref name,
+                ref attributes,
+                ref attribute_sets,
+                ref body,
+            } => {
+                let element = self.create_element(name);
+                parent.AppendChild(element.upcast())?;
+                self.use_attribute_sets(attribute_sets, context, element.upcast())?;
+                for &(ref name, ref value) in attributes {
+                    let qualified_name = match name.prefix {
+                        Some(ref prefix) => format!("{}:{}", prefix, name.local),
+                        None => name.local.to_string(),
+                    };
+                    let value = self.avt(value, context)?;
+                    self.add_attribute(element.upcast(), name.ns.clone(), &qualified_name, value)?;
+                }
+                self.execute(body, context, element.upcast())
+            },
+            Instruction::ApplyTemplates {
+                ref select,
+                ref mode,
+                ref sorts,
+                ref params,
+            } => {
+                let nodes = match *select {
+                    Some(ref select) => self.select(select, context)?,
+                    None => self.children(&context.node),
+                };
+                let nodes = self.sort(nodes, sorts, context)?;
+                let params = self.params(params, context)?;
+                let mode = mode.as_ref().map(|mode| &**mode);
+                self.apply_templates(nodes, mode, &params, parent)
+            },
+            Instruction::CallTemplate {
+                ref name,
+                ref params,
+            } => {
+                let template = self
+                    .stylesheet
+                    .templates
+                    .iter()
+                    .rev()
+                    .find(|template| template.name.as_ref() == Some(name))
+                    .ok_or(Error::Syntax)?;
+                let params = self.params(params, context)?;
+                self.call_template(template, context, &params, parent)
+            },
+            Instruction::ValueOf(ref select) => {
+                self.add_text(parent, &self.evaluate(select, context)?.string())
+            },
+            Instruction::ForEach {
+                ref select,
+                ref sorts,
+                ref body,
+            } => {
+                let nodes = self.select(select, context)?;
+                let nodes = self.sort(nodes, sorts, context)?;
+                let size = nodes.len();
+                for (index, node) in nodes.into_iter().enumerate() {
+                    let context = Context {
+                        node,
+                        position: index + 1,
+                        size,
+                        variables: context.variables.clone(),
+                    };
+                    self.execute(body, &context, parent)?;
+                }
+                Ok(())
+            },
+            Instruction::If { ref test, ref body } => {
+                if self.evaluate(test, context)?.boolean() {
+                    self.execute(body, context, parent)?;
+                }
+                Ok(())
+            },
+            Instruction::Choose {
+                ref cases,
+                ref otherwise,
+            } => {
+                for &(ref test, ref body) in cases {
+                    if self.evaluate(test, context)?.boolean() {
+                        return self.execute(body, context, parent);
+                    }
+                }
+                self.execute(otherwise, context, parent)
+            },
+            Instruction::Element {
+                ref name,
+                ref namespace,
+                ref namespaces,
+                ref attribute_sets,
+                ref body,
+            } => {
+                let name = self.avt(name, context)?;
+                let namespace = match *namespace {
+                    Some(ref namespace) => Some(self.avt(namespace, context)?),
+                    None => None,
+                };
+                let namespace = self.namespace(&name, namespace, namespaces, true)?;
+                let element = self.document.CreateElementNS(
+                    Node::namespace_to_string(self.result_namespace(&namespace)),
+                    DOMString::from(name),
+                    StringOrElementCreationOptions::String(DOMString::new()),
+                )?;
+                parent.AppendChild(element.upcast())?;
+                self.use_attribute_sets(attribute_sets, context, element.upcast())?;
+                self.execute(body, context, element.upcast())
+            },
+            Instruction::Attribute {
+                ref name,
+                ref namespace,
+                ref namespaces,
+                ref body,
+            } => {
+                let name = self.avt(name, context)?;
+                let namespace = match *namespace {
+                    Some(ref namespace) => Some(self.avt(namespace, context)?),
+                    None => None,
+                };
+                let namespace = self.namespace(&name, namespace, namespaces, false)?;
+                let value = self.text_content(body, context)?;
+                self.add_attribute(parent, namespace, &name, value)
+            },
+            Instruction::Comment(ref body) => {
+                let data = self.text_content(body, context)?;
+                let comment = Comment::new(DOMString::from(data), self.document);
+                parent.AppendChild(comment.upcast()).map(|_| ())
+            },
+            Instruction::ProcessingInstruction { ref name, ref body } => {
+                let target = self.avt(name, context)?;
+                let data = self.text_content(body, context)?;
+                let pi = self
+                    .document
+                    .CreateProcessingInstruction(DOMString::from(target), DOMString::from(data))?;
+                parent.AppendChild(pi.upcast()).map(|_| ())
+            },
+            Instruction::Copy {
+                ref attribute_sets,
+                ref body,
+            } => {
+                // https://www.w3.org/TR/1999/REC-xslt-19991116#copying
+                let node = &context.node;
+                match node.type_id() {
+                    NodeTypeId::Document(_) | NodeTypeId::DocumentFragment(_) => {
+                        self.execute(body, context, parent)
+                    },
+                    NodeTypeId::Element(_) => {
+                        let element = node.downcast::<Element>().unwrap();
+                        let name = QualName::new(
+                            element.prefix().clone(),
+                            element.namespace().clone(),
+                            element.local_name().clone(),
+                        );
+                        let copy = self.create_element(&name);
+                        parent.AppendChild(copy.upcast())?;
+                        self.use_attribute_sets(attribute_sets, context, copy.upcast())?;
+                        self.execute(body, context, copy.upcast())
+                    },
+                    _ => self.copy_of(node, parent),
+                }
+            },
+            Instruction::CopyOf(ref select) => match self.evaluate(select, context)? {
+                Value::NodeSet(nodes) => {
+                    for node in nodes {
+                        self.copy_of(&node, parent)?;
+                    }
+                    Ok(())
+                },
+                value => self.add_text(parent, &value.string()),
+            },
+            Instruction::Variable(_) => unreachable!("Variables are bound by execute()"),
+            Instruction::Number {
+                ref value,
+                ref count,
+                ref format,
+            } => {
+                // https://www.w3.org/TR/1999/REC-xslt-19991116#number
+                let number = match *value {
+                    Some(ref value) => {
+                        let number = self.evaluate(value, context)?.number();
+                        if !(number >= 0.5) || number.is_infinite() {
+                            return self.add_text(parent, &Value::Number(number).string());
+                        }
+                        (number + 0.5).floor() as u64
+                    },
+                    None => match self.count(&context.node, count.as_ref())? {
+                        Some(position) => position,
+                        None => return Ok(()),
+                    },
+                };
+                let format = self.avt(format, context)?;
+                self.add_text(parent, &format_integer(number, &format))
+            },
+            Instruction::Message {
+                terminate,
+                ref body,
+            } => {
+                let message = self.text_content(body, context)?;
+                info!("xsl:message: {}", message);
+                if terminate {
+                    return Err(Error::Abort);
+                }
+                Ok(())
+            },
+            Instruction::Fallback(ref body) => self.execute(body, context, parent),
+        }
+    }
+
+    /// The position of a node among its siblings for `xsl:number`, counting the nodes that
+    /// match the `count` pattern, or the ones of the same type and name by default.
+    fn count(&self, node: &Node, count: Option<&Expr>) -> Fallible<Option<u64>> {
+        let matches = |candidate: &Node| -> Fallible<bool> {
+            match count {
+                Some(pattern) => self.matches(pattern, candidate),
+                None => Ok(same_kind(node, candidate)),
+            }
+        };
+        for ancestor in node.inclusive_ancestors(ShadowIncluding::No) {
+            if !matches(&ancestor)? {
+                continue;
+            }
+            let mut position = 1;
+            for sibling in ancestor.preceding_siblings() {
+                if matches(&sibling)? {
+                    position += 1;
+                }
+            }
+            return Ok(Some(position));
+        }
+        Ok(None)
+    }
+}
+
+/// The namespace and the qualified name of an attribute.
+fn attr_name(node: &Node) -> (Namespace, String) {
+    let attr = node.downcast::<Attr>().unwrap();
+    let name = match attr.prefix() {
+        Some(prefix) => format!("{}:{}", prefix, attr.local_name()),
+        None => attr.local_name().to_string(),
+    };
+    (attr.namespace().clone(), name)
+}
+
+/// Whether two nodes have the same type and expanded name.
+fn same_kind(a: &Node, b: &Node) -> bool {
+    if a.type_id() != b.type_id() {
+        return false;
+    }
+    if let (Some(a), Some(b)) = (a.downcast::<Element>(), b.downcast::<Element>()) {
+        return a.namespace() == b.namespace() && a.local_name() == b.local_name();
+    }
+    if let (Some(a), Some(b)) = (
+        a.downcast::<ProcessingInstruction>(),
+        b.downcast::<ProcessingInstruction>(),
+    ) {
+        return a.target() == b.target();
+    }
+    true
+}
+
+/// Orders numbers with NaN first, as sort keys of the number data type.
+fn compare_numbers(a: f64, b: f64) -> Ordering {
+    a.partial_cmp(&b)
+        .unwrap_or_else(|| a.is_nan().cmp(&b.is_nan()).reverse())
+}
+
+/// Formats an integer with the format token of `xsl:number`, between the punctuation
+/// around it.
+/// <https://www.w3.org/TR/1999/REC-xslt-19991116#convert>
+fn format_integer(number: u64, format: &str) -> String {
+    let start = format.find(char::is_alphanumeric).unwrap_or(format.len());
+    let end = format[start..]
+        .find(|c: char| !c.is_alphanumeric())
+        .map_or(format.len(), |index| start + index);
+    let (prefix, token, suffix) = (&format[..start], &format[start..end], &format[end..]);
+    let formatted = match token {
+        "a" | "A" => {
+            // 1 is "a", 26 is "z" and 27 is "aa".
+            let first = if token == "A" { b'A' } else { b'a' };
+            let mut letters = vec![];
+            let mut number = number;
+            while number > 0 {
+                number -= 1;
+                letters.insert(0, (first + (number % 26) as u8) as char);
+                number /= 26;
+            }
+            letters.into_iter().collect()
+        },
+        "i" | "I" if number < 4000 => {
+            const NUMERALS: &[(u64, &str)] = &[
+                (1000, "m"),
+                (900, "cm"),
+                (500, "d"),
+                (400, "cd"),
+                (100, "c"),
+                (90, "xc"),
+                (50, "l"),
+                (40, "xl"),
+                (10, "x"),
+                (9, "ix"),
+                (5, "v"),
+                (4, "iv"),
+                (1, "i"),
+            ];
+            let mut numerals = String::new();
+            let mut number = number;
+            for &(value, numeral) in NUMERALS {
+                while number >= value {
+                    numerals.push_str(numeral);
+                    number -= value;
+                }
+            }
+            if token == "I" {
+                numerals.to_uppercase()
+            } else {
+                numerals
+            }
+        },
+        // A decimal token is padded to its length, as `01` is.
+        token if !token.is_empty() && token.chars().all(|c| c.is_ascii_digit()) => {
+            format!("{:0width$}", number, width = token.len())
+        },
+        _ => number.to_string(),
+    };
+    format!("{}{}{}", prefix, formatted, suffix)
+}
@@ -6,7 +6,7 @@ use crate::rpc::LayoutRPC;
 use crate::{OpaqueStyleAndLayoutData, PendingImage, TrustedNodeAddress};
 use app_units::Au;
 use crossbeam_channel::{Receiver, Sender};
-use euclid::default::{Point2D, Rect};
+use euclid::default::{Point2D, Rect, Size2D};
 use gfx_traits::Epoch;
 use ipc_channel::ipc::{IpcReceiver, IpcSender};
 use metrics::PaintTimeMetrics;
@@ -25,7 +25,7 @@ use style::context::QuirksMode;
 use style::dom::OpaqueNode;
 use style::properties::PropertyId;
 use style::selector_parser::PseudoElement;
-use style::stylesheets::Stylesheet;
+use style::stylesheets::{CssRule, Stylesheet};
 
 /// Asynchronous messages that script can send to layout.
 pub enum Msg {
@@ -37,6 +37,10 @@ pub enum Msg {
     /// Removes a stylesheet from the document.
     RemoveStylesheet(ServoArc<Stylesheet>),
 
+    /// Notes that the given rule was inserted into or removed from a stylesheet of the
+    /// document.
+    StylesheetRuleChanged(ServoArc<Stylesheet>, CssRule),
+
     /// Change the quirks mode.
     SetQuirksMode(QuirksMode),
 
@@ -119,6 +123,12 @@ pub enum QueryMsg {
     NodeScrollGeometryQuery(OpaqueNode),
     OffsetParentQuery(OpaqueNode),
     TextIndexQuery(OpaqueNode, Point2D<f32>),
+    /// The text index under a point given in the coordinate space of the document, rather
+    /// than relative to the node's first fragment.
+    TextIndexAtPointQuery(OpaqueNode, Point2D<f32>),
+    /// The boxes of the text between two offsets within a text node, as in the
+    /// `getClientRects()` call of ranges.
+    TextBoxesQuery(OpaqueNode, usize, usize),
     NodesFromPointQuery(Point2D<f32>, NodesFromPointQueryType),
 
     // FIXME(nox): The following queries use the TrustedNodeAddress to
@@ -129,6 +139,9 @@ pub enum QueryMsg {
     StyleQuery(TrustedNodeAddress),
     ElementInnerTextQuery(TrustedNodeAddress),
     InnerWindowDimensionsQuery(BrowsingContextId),
+    /// Lay the document out for `@media print` on pages of the given size in CSS pixels, and
+    /// print it to a PDF file.
+    PrintQuery(Size2D<f32>),
 }
 
 /// Any query to perform with this reflow.
@@ -148,6 +161,8 @@ impl ReflowGoal {
             ReflowGoal::LayoutQuery(ref querymsg, _) => match *querymsg {
                 QueryMsg::NodesFromPointQuery(..) |
                 QueryMsg::TextIndexQuery(..) |
+                QueryMsg::TextIndexAtPointQuery(..) |
+                QueryMsg::TextBoxesQuery(..) |
                 QueryMsg::InnerWindowDimensionsQuery(_) |
                 QueryMsg::ElementInnerTextQuery(_) => true,
                 QueryMsg::ContentBoxQuery(_) |
@@ -157,6 +172,32 @@ impl ReflowGoal {
                 QueryMsg::NodeScrollIdQuery(_) |
                 QueryMsg::ResolvedStyleQuery(..) |
                 QueryMsg::OffsetParentQuery(_) |
+                QueryMsg::PrintQuery(_) |
+                QueryMsg::StyleQuery(_) => false,
+            },
+        }
+    }
+
+    /// Returns true if the given ReflowQuery only asks for the geometry of boxes, which can be
+    /// answered from the last layout of a document that didn't change since.
+    pub fn is_geometry_query(&self) -> bool {
+        match *self {
+            ReflowGoal::Full | ReflowGoal::TickAnimations => false,
+            ReflowGoal::LayoutQuery(ref querymsg, _) => match *querymsg {
+                QueryMsg::ContentBoxQuery(_) |
+                QueryMsg::ContentBoxesQuery(_) |
+                QueryMsg::ClientRectQuery(_) |
+                QueryMsg::NodeScrollGeometryQuery(_) |
+                QueryMsg::OffsetParentQuery(_) => true,
+                QueryMsg::NodesFromPointQuery(..) |
+                QueryMsg::TextIndexQuery(..) |
+                QueryMsg::TextIndexAtPointQuery(..) |
+                QueryMsg::TextBoxesQuery(..) |
+                QueryMsg::ElementInnerTextQuery(_) |
+                QueryMsg::NodeScrollIdQuery(_) |
+                QueryMsg::ResolvedStyleQuery(..) |
+                QueryMsg::InnerWindowDimensionsQuery(_) |
+                QueryMsg::PrintQuery(_) |
                 QueryMsg::StyleQuery(_) => false,
             },
         }
@@ -170,6 +211,8 @@ impl ReflowGoal {
             ReflowGoal::LayoutQuery(ref querymsg, _) => match *querymsg {
                 QueryMsg::NodesFromPointQuery(..) |
                 QueryMsg::TextIndexQuery(..) |
+                QueryMsg::TextIndexAtPointQuery(..) |
+                QueryMsg::TextBoxesQuery(..) |
                 QueryMsg::ElementInnerTextQuery(_) => true,
                 QueryMsg::ContentBoxQuery(_) |
                 QueryMsg::ContentBoxesQuery(_) |
@@ -179,6 +222,7 @@ impl ReflowGoal {
                 QueryMsg::ResolvedStyleQuery(..) |
                 QueryMsg::OffsetParentQuery(_) |
                 QueryMsg::InnerWindowDimensionsQuery(_) |
+                QueryMsg::PrintQuery(_) |
                 QueryMsg::StyleQuery(_) => false,
             },
         }
@@ -208,6 +252,9 @@ pub struct ScriptReflow {
     pub document: TrustedNodeAddress,
     /// Whether the document's stylesheets have changed since the last script reflow.
     pub stylesheets_changed: bool,
+    /// Whether neither the document, its styles nor the window size changed since the last
+    /// script reflow.
+    pub layout_is_clean: bool,
     /// The current window size.
     pub window_size: WindowSizeData,
     /// The channel that we send a notification to.
@@ -43,6 +43,8 @@ pub trait LayoutRPC {
     fn element_inner_text(&self) -> String;
     /// Get the dimensions of an iframe's inner window.
     fn inner_window_dimensions(&self) -> Option<Size2D<f32, CSSPixel>>;
+    /// Takes the PDF file that the document was printed to, if layout supports printing.
+    fn printed_document(&self) -> Option<Vec<u8>>;
 }
 
 pub struct ContentBoxResponse(pub Option<Rect<Au>>);
@@ -16,6 +16,7 @@ use msg::constellation_msg::{BrowsingContextId, PipelineId};
 use net_traits::image::base::{Image, ImageMetadata};
 use range::Range;
 use servo_arc::Arc;
+use servo_atoms::Atom;
 use servo_url::ServoUrl;
 use std::fmt::Debug;
 use std::sync::Arc as StdArc;
@@ -252,6 +253,52 @@ pub trait ThreadSafeLayoutNode:
         }
     }
 
+    /// The custom highlights of the text in this node, as in `highlights()`, with the style
+    /// of their `::highlight()` pseudo-element. Highlights without style rules paint nothing,
+    /// so they are left out.
+    fn highlight_styles(
+        &self,
+        context: &SharedStyleContext,
+    ) -> Vec<(Range<ByteIndex>, Arc<ComputedValues>)> {
+        let highlights = self.highlights();
+        if highlights.is_empty() {
+            return vec![];
+        }
+        let parent = match unsafe { self.unsafe_get() }
+            .parent_node()
+            .and_then(|parent| parent.as_element())
+        {
+            Some(parent) => parent,
+            None => return vec![],
+        };
+        let parent_data = match parent.borrow_data() {
+            Some(parent_data) => parent_data,
+            None => return vec![],
+        };
+        highlights
+            .into_iter()
+            .filter_map(|(name, range)| {
+                let pseudo = PseudoElement::Highlight(name);
+                let style = context.stylist.lazily_compute_pseudo_element_style(
+                    &context.guards,
+                    parent,
+                    &pseudo,
+                    RuleInclusion::All,
+                    parent_data.styles.primary(),
+                    /* is_probe = */ true,
+                    &ServoMetricsProvider,
+                    Some(&|other: &PseudoElement| *other == pseudo),
+                )?;
+                Some((range, style))
+            })
+            .collect()
+    }
+
+    /// The style of the `::backdrop` of this node, if it is an element with one.
+    fn backdrop_style(&self) -> Option<Arc<ComputedValues>> {
+        self.as_element().and_then(|el| el.backdrop_style())
+    }
+
     fn is_ignorable_whitespace(&self, context: &SharedStyleContext) -> bool;
 
     /// Returns true if this node contributes content. This is used in the implementation of
@@ -273,6 +320,14 @@ pub trait ThreadSafeLayoutNode:
     /// If the insertion point is within this node, returns it. Otherwise, returns `None`.
     fn selection(&self) -> Option<Range<ByteIndex>>;
 
+    /// The names of the custom highlights of the text in this node and the ranges that they
+    /// cover, from the highlight painted lowest to the one painted highest.
+    fn highlights(&self) -> Vec<(Atom, Range<ByteIndex>)>;
+
+    /// The ranges of the misspelled words of the text in this node, if it is a text node or a
+    /// text control.
+    fn spelling_errors(&self) -> Vec<Range<ByteIndex>>;
+
     /// If this is an image element, returns its URL. If this is not an image element, fails.
     fn image_url(&self) -> Option<ServoUrl>;
 
@@ -466,6 +521,15 @@ pub trait ThreadSafeLayoutElement:
             .clone()
     }
 
+    #[inline]
+    fn backdrop_style(&self) -> Option<Arc<ComputedValues>> {
+        self.style_data()
+            .styles
+            .pseudos
+            .get(&PseudoElement::Backdrop)
+            .cloned()
+    }
+
     /// Returns the already resolved style of the node.
     ///
     /// This differs from `style(ctx)` in that if the pseudo-element has not yet
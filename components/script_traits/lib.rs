@@ -28,7 +28,8 @@ use bluetooth_traits::BluetoothRequest;
 use canvas_traits::webgl::WebGLPipeline;
 use crossbeam_channel::{Receiver, RecvTimeoutError, Sender};
 use devtools_traits::{DevtoolScriptControlMsg, ScriptToDevtoolsControlMsg, WorkerId};
-use embedder_traits::EventLoopWaker;
+use embedder_traits::{BatteryStatus, Credentials, EventLoopWaker, FindOptions};
+use embedder_traits::{GeolocationPosition, NetworkInformation, OverscrollBehavior, SensorReading};
 use euclid::{default::Point2D, Length, Rect, Scale, Size2D, UnknownUnit, Vector2D};
 use gfx_traits::Epoch;
 use http::HeaderMap;
@@ -42,7 +43,7 @@ use log::warn;
 use media::WindowGLContext;
 use msg::constellation_msg::BackgroundHangMonitorRegister;
 use msg::constellation_msg::{
-    BlobId, BrowsingContextId, HistoryStateId, MessagePortId, PipelineId,
+    BlobId, BrowsingContextId, HistoryStateId, LockRequestId, MessagePortId, PipelineId,
 };
 use msg::constellation_msg::{PipelineNamespaceId, TopLevelBrowsingContextId};
 use net_traits::image::base::Image;
@@ -63,12 +64,14 @@ use std::fmt;
 use std::sync::atomic::AtomicBool;
 use std::sync::Arc;
 use style_traits::CSSPixel;
+use style_traits::MediaPreferences;
 use style_traits::SpeculativePainter;
 use webrender_api::units::{
-    DeviceIntSize, DevicePixel, LayoutPixel, LayoutPoint, LayoutSize, WorldPoint,
+    DeviceIntSize, DevicePixel, LayoutPixel, LayoutPoint, LayoutRect, LayoutSize, WorldPoint,
 };
-use webrender_api::{BuiltDisplayList, DocumentId, ExternalScrollId, ImageKey, ScrollClamping};
-use webrender_api::{BuiltDisplayListDescriptor, HitTestFlags, HitTestResult, ResourceUpdate};
+use webrender_api::{BuiltDisplayList, ColorF, DocumentId, ExternalScrollId, ImageKey};
+use webrender_api::{BuiltDisplayListDescriptor, HitTestFlags, HitTestResult};
+use webrender_api::{ResourceUpdate, ScrollClamping};
 use webvr_traits::{WebVREvent, WebVRMsg};
 
 pub use crate::script_msg::{
@@ -76,8 +79,9 @@ pub use crate::script_msg::{
     ServiceWorkerMsg,
 };
 pub use crate::script_msg::{
-    EventResult, IFrameSize, IFrameSizeMsg, LayoutMsg, LogEntry, ScriptMsg,
+    EventResult, IFrameSize, IFrameSizeMsg, LayoutMsg, LogEntry, ScriptMsg, SpeechAction,
 };
+pub use embedder_traits::{MediaSessionActionDetails, MediaSessionActionType};
 
 /// The address of a node. Layout sends these back. They must be validated via
 /// `from_untrusted_node_address` before they can be used, because we do not trust layout.
@@ -132,6 +136,8 @@ pub enum LayoutControlMsg {
     GetWebFontLoadState(IpcSender<bool>),
     /// Send the paint time for a specific epoch to the layout thread.
     PaintMetric(Epoch, u64),
+    /// The system is running low on memory: flush the font and text shaping caches.
+    MemoryPressure,
 }
 
 /// The origin where a given load was initiated.
@@ -274,6 +280,41 @@ pub enum ProgressiveWebMetricType {
     TimeToInteractive,
 }
 
+/// The text or image with the largest area painted in the viewport of a page so far.
+///
+/// <https://w3c.github.io/largest-contentful-paint/#sec-largest-contentful-paint-candidate>
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct LargestContentfulPaintCandidate {
+    /// The node whose content was painted.
+    pub node: UntrustedNodeAddress,
+    /// The painted area of the content within the viewport, in square CSS pixels.
+    pub size: u64,
+}
+
+/// The movement of the nodes whose visual representation changed position between two frames.
+///
+/// <https://wicg.github.io/layout-instability/#sec-layout-shift>
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct LayoutShift {
+    /// The layout shift score of the frame.
+    pub value: f64,
+    /// The nodes that contributed the most to the shift.
+    pub sources: Vec<LayoutShiftSource>,
+}
+
+/// A node that contributed to a layout shift.
+///
+/// <https://wicg.github.io/layout-instability/#sec-layout-shift-attribution>
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct LayoutShiftSource {
+    /// The node that shifted.
+    pub node: UntrustedNodeAddress,
+    /// The visual representation of the node in the viewport in the previous frame.
+    pub previous_rect: LayoutRect,
+    /// The visual representation of the node in the viewport in the current frame.
+    pub current_rect: LayoutRect,
+}
+
 /// The reason why the pipeline id of an iframe is being updated.
 #[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, MallocSizeOf, PartialEq, Serialize)]
 pub enum UpdatePipelineIdReason {
@@ -301,6 +342,8 @@ pub enum ConstellationControlMsg {
     ResizeInactive(PipelineId, WindowSizeData),
     /// Window switched from fullscreen mode.
     ExitFullScreen(PipelineId),
+    /// The pointer locked by the document of this pipeline was released by the user.
+    ExitPointerLock(PipelineId),
     /// Notifies the script that the document associated with this pipeline should 'unload'.
     UnloadDocument(PipelineId),
     /// Notifies the script that a pipeline should be closed.
@@ -322,6 +365,9 @@ pub enum ConstellationControlMsg {
     SetDocumentActivity(PipelineId, DocumentActivity),
     /// Notifies script thread whether frame is visible
     ChangeFrameVisibilityStatus(PipelineId, bool),
+    /// Notifies script thread whether the document of a pipeline is prerendered, or was
+    /// activated by a navigation
+    SetPrerendering(PipelineId, bool),
     /// Notifies script thread that frame visibility change is complete
     /// PipelineId is for the parent, BrowsingContextId is for the nested browsing context
     NotifyVisibilityChange(PipelineId, BrowsingContextId, bool),
@@ -367,8 +413,12 @@ pub enum ConstellationControlMsg {
     FocusIFrame(PipelineId, BrowsingContextId),
     /// Passes a webdriver command to the script thread for execution
     WebDriverScriptCommand(PipelineId, WebDriverScriptCommand),
-    /// Notifies script thread that all animations are done
-    TickAllAnimations(PipelineId),
+    /// Notifies script thread that all animations are done, and that the animation frame
+    /// callbacks can run for the given video refresh.
+    TickAllAnimations(PipelineId, FrameTiming),
+    /// Notifies the script thread that the scale of the pinch zoom of a top-level document
+    /// changed.
+    SetPinchZoom(PipelineId, f32),
     /// Notifies the script thread of a transition end
     TransitionEnd(UntrustedNodeAddress, String, f64),
     /// Notifies the script thread that a new Web font has been loaded, and thus the page should be
@@ -401,8 +451,45 @@ pub enum ConstellationControlMsg {
     WebVREvents(PipelineId, Vec<WebVREvent>),
     /// Notifies the script thread about a new recorded paint metric.
     PaintMetric(PipelineId, ProgressiveWebMetricType, u64),
+    /// Notifies the script thread that a new largest contentful paint candidate was painted,
+    /// with the time of the paint relative to the navigation start.
+    LargestContentfulPaint(PipelineId, u64, LargestContentfulPaintCandidate),
+    /// Notifies the script thread that the nodes of a page shifted between two frames, with the
+    /// `time::precise_time_ns()` time of the frame.
+    LayoutShift(PipelineId, u64, LayoutShift),
     /// Notifies the media session about a user requested media session action.
-    MediaSessionAction(PipelineId, MediaSessionActionType),
+    MediaSessionAction(PipelineId, MediaSessionActionDetails),
+    /// Search the rendered text of the given page and its same-origin iframes.
+    Find(PipelineId, String, FindOptions),
+    /// Clear the find-in-page state of the given page.
+    StopFinding(PipelineId),
+    /// Extract the main article of the given page for a reader view.
+    ExtractArticle(PipelineId),
+    /// Build the accessibility tree of the given page and its same-origin iframes.
+    RequestAccessibilityTree(PipelineId),
+    /// Find the form controls of the given page that can be autofilled.
+    RequestFormFields(PipelineId),
+    /// Fill in form controls of the given page, by identifier, as if the user had typed
+    /// the values.
+    FillFormFields(PipelineId, Vec<(String, String)>),
+    /// Fill in the username and the password of the login form of the given page, if it is of
+    /// the origin of the credentials.
+    FillCredentials(PipelineId, Credentials),
+    /// The system is running low on memory: purge the caches of every page of the script
+    /// thread and run a shrinking garbage collection.
+    MemoryPressure,
+    /// The settings of the user that pages can query with media features changed.
+    MediaPreferencesChanged(MediaPreferences),
+    /// The state of the battery or of the network connection of the device changed.
+    DeviceStatusChanged(BatteryStatus, NetworkInformation),
+    /// A new position of the device was acquired for the given pipeline, or could not be.
+    UpdatePosition(PipelineId, Result<GeolocationPosition, String>),
+    /// The orientation or the motion of the device was read for the given pipeline.
+    SensorReading(PipelineId, SensorReading),
+    /// The factor that the font sizes of the pages are scaled by changed.
+    SetTextZoom(f32),
+    /// The user activation of another window of the frame tree of the given pipeline changed.
+    UpdateUserActivation(PipelineId, UserActivationUpdate),
 }
 
 impl fmt::Debug for ConstellationControlMsg {
@@ -423,6 +510,7 @@ impl fmt::Debug for ConstellationControlMsg {
             GetTitle(..) => "GetTitle",
             SetDocumentActivity(..) => "SetDocumentActivity",
             ChangeFrameVisibilityStatus(..) => "ChangeFrameVisibilityStatus",
+            SetPrerendering(..) => "SetPrerendering",
             NotifyVisibilityChange(..) => "NotifyVisibilityChange",
             NavigateIframe(..) => "NavigateIframe",
             PostMessage { .. } => "PostMessage",
@@ -432,6 +520,7 @@ impl fmt::Debug for ConstellationControlMsg {
             FocusIFrame(..) => "FocusIFrame",
             WebDriverScriptCommand(..) => "WebDriverScriptCommand",
             TickAllAnimations(..) => "TickAllAnimations",
+            SetPinchZoom(..) => "SetPinchZoom",
             TransitionEnd(..) => "TransitionEnd",
             WebFontLoaded(..) => "WebFontLoaded",
             DispatchIFrameLoadEvent { .. } => "DispatchIFrameLoadEvent",
@@ -440,8 +529,25 @@ impl fmt::Debug for ConstellationControlMsg {
             Reload(..) => "Reload",
             WebVREvents(..) => "WebVREvents",
             PaintMetric(..) => "PaintMetric",
+            LargestContentfulPaint(..) => "LargestContentfulPaint",
+            LayoutShift(..) => "LayoutShift",
             ExitFullScreen(..) => "ExitFullScreen",
+            ExitPointerLock(..) => "ExitPointerLock",
             MediaSessionAction(..) => "MediaSessionAction",
+            Find(..) => "Find",
+            StopFinding(..) => "StopFinding",
+            ExtractArticle(..) => "ExtractArticle",
+            RequestAccessibilityTree(..) => "RequestAccessibilityTree",
+            RequestFormFields(..) => "RequestFormFields",
+            FillFormFields(..) => "FillFormFields",
+            FillCredentials(..) => "FillCredentials",
+            MemoryPressure => "MemoryPressure",
+            MediaPreferencesChanged(..) => "MediaPreferencesChanged",
+            DeviceStatusChanged(..) => "DeviceStatusChanged",
+            UpdatePosition(..) => "UpdatePosition",
+            SensorReading(..) => "SensorReading",
+            SetTextZoom(..) => "SetTextZoom",
+            UpdateUserActivation(..) => "UpdateUserActivation",
         };
         write!(formatter, "ConstellationControlMsg::{}", variant)
     }
@@ -557,6 +663,13 @@ pub enum CompositorEvent {
         // Bitmask of MouseButton values representing the currently pressed buttons
         u16,
     ),
+    /// The mouse was moved by the given distance while the pointer is locked, which hides
+    /// the cursor and keeps it in place.
+    PointerLockMoveEvent(
+        Vector2D<f32>,
+        // Bitmask of MouseButton values representing the currently pressed buttons
+        u16,
+    ),
     /// A touch event was generated with a touch ID and location.
     TouchEvent(
         TouchEventType,
@@ -763,11 +876,21 @@ pub struct IFrameLoadInfoWithData {
     pub window_size: WindowSizeData,
 }
 
+/// The video refresh that the animation frame callbacks of a document are run for.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+pub struct FrameTiming {
+    /// When the video refresh happened, in `time::precise_time_ns()`, which every document
+    /// ticked for it converts to its own time origin.
+    pub vsync_time: u64,
+    /// The estimated time between two video refreshes, in nanoseconds.
+    pub frame_interval: u64,
+}
+
 /// Specifies whether the script or layout thread needs to be ticked for animation.
 #[derive(Debug, Deserialize, Serialize)]
 pub enum AnimationTickType {
-    /// The script thread.
-    Script,
+    /// The script thread, for the given video refresh.
+    Script(FrameTiming),
     /// The layout thread.
     Layout,
 }
@@ -781,6 +904,73 @@ pub struct ScrollState {
     pub scroll_offset: Vector2D<f32, LayoutPixel>,
 }
 
+/// The snap positions of a scroll container with a `scroll-snap-type`, that the compositor
+/// snaps the scroll container to when a scroll ends.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct ScrollSnapContainer {
+    /// The ID of the scroll root.
+    pub scroll_id: ExternalScrollId,
+    /// Whether scrolls always end at a snap position, rather than only when they end close to one.
+    pub mandatory: bool,
+    /// The size of the scrollport of the scroll container.
+    pub scrollport_size: LayoutSize,
+    /// The horizontal scroll positions that scrolls snap to.
+    pub x_positions: Vec<f32>,
+    /// The vertical scroll positions that scrolls snap to.
+    pub y_positions: Vec<f32>,
+}
+
+/// A scroll container and its `overscroll-behavior`, that the compositor uses to stop scrolls
+/// from chaining to the ancestors of the scroll container.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct OverscrollContainer {
+    /// The ID of the scroll root.
+    pub scroll_id: ExternalScrollId,
+    /// The ID of the scroll root of the nearest scroll container around this one, or `None` for
+    /// the viewport.
+    pub parent_scroll_id: Option<ExternalScrollId>,
+    /// The scrollport of the scroll container when none of the scroll containers are scrolled.
+    pub scrollport: LayoutRect,
+    /// The overscroll behavior along the horizontal axis.
+    pub behavior_x: OverscrollBehavior,
+    /// The overscroll behavior along the vertical axis.
+    pub behavior_y: OverscrollBehavior,
+}
+
+/// The `scrollbar-width` of a scroll container.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub enum ScrollbarWidth {
+    /// The default width of the scrollbars.
+    Auto,
+    /// Thinner scrollbars than the default ones.
+    Thin,
+    /// No scrollbars, though the scroll container can still be scrolled.
+    None,
+}
+
+/// A scroll container whose overlay scrollbars the compositor paints while the user scrolls.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct ScrollbarContainer {
+    /// The ID of the scroll root.
+    pub scroll_id: ExternalScrollId,
+    /// The ID of the scroll root of the nearest scroll container around this one, or `None` for
+    /// the viewport.
+    pub parent_scroll_id: Option<ExternalScrollId>,
+    /// The scrollport of the scroll container when none of the scroll containers are scrolled.
+    pub scrollport: LayoutRect,
+    /// The size of the scrollable area of the scroll container.
+    pub content_size: LayoutSize,
+    /// Whether the user can scroll the scroll container horizontally.
+    pub scrollable_x: bool,
+    /// Whether the user can scroll the scroll container vertically.
+    pub scrollable_y: bool,
+    /// The width of the scrollbars.
+    pub width: ScrollbarWidth,
+    /// The colors of the thumb and of the track of the scrollbars, or `None` for the default
+    /// colors.
+    pub colors: Option<(ColorF, ColorF)>,
+}
+
 /// Data about the window size.
 #[derive(Clone, Copy, Debug, Deserialize, MallocSizeOf, PartialEq, Serialize)]
 pub struct WindowSizeData {
@@ -1042,50 +1232,66 @@ impl Clone for BroadcastMsg {
     }
 }
 
-/// The type of MediaSession action.
-/// https://w3c.github.io/mediasession/#enumdef-mediasessionaction
-#[derive(Clone, Debug, Deserialize, Eq, Hash, MallocSizeOf, PartialEq, Serialize)]
-pub enum MediaSessionActionType {
-    /// The action intent is to resume playback.
-    Play,
-    /// The action intent is to pause the currently active playback.
-    Pause,
-    /// The action intent is to move the playback time backward by a short period (i.e. a few
-    /// seconds).
-    SeekBackward,
-    /// The action intent is to move the playback time forward by a short period (i.e. a few
-    /// seconds).
-    SeekForward,
-    /// The action intent is to either start the current playback from the beginning if the
-    /// playback has a notion, of beginning, or move to the previous item in the playlist if the
-    /// playback has a notion of playlist.
-    PreviousTrack,
-    /// The action is to move to the playback to the next item in the playlist if the playback has
-    /// a notion of playlist.
-    NextTrack,
-    /// The action intent is to skip the advertisement that is currently playing.
-    SkipAd,
-    /// The action intent is to stop the playback and clear the state if appropriate.
-    Stop,
-    /// The action intent is to move the playback time to a specific time.
-    SeekTo,
-}
-
-impl From<i32> for MediaSessionActionType {
-    fn from(value: i32) -> MediaSessionActionType {
-        match value {
-            1 => MediaSessionActionType::Play,
-            2 => MediaSessionActionType::Pause,
-            3 => MediaSessionActionType::SeekBackward,
-            4 => MediaSessionActionType::SeekForward,
-            5 => MediaSessionActionType::PreviousTrack,
-            6 => MediaSessionActionType::NextTrack,
-            7 => MediaSessionActionType::SkipAd,
-            8 => MediaSessionActionType::Stop,
-            9 => MediaSessionActionType::SeekTo,
-            _ => panic!("Unknown MediaSessionActionType"),
-        }
-    }
+/// A change of the user activation of a window, which the windows of the same frame tree
+/// follow.
+/// <https://html.spec.whatwg.org/multipage/#user-activation-processing-model>
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum UserActivationUpdate {
+    /// The user activated the window, which activates its ancestors and its same origin
+    /// descendants.
+    Notified,
+    /// An API consumed the transient activation of the window, which is consumed in the whole
+    /// frame tree.
+    Consumed,
+}
+
+/// The mode of a lock of the Web Locks API.
+/// <https://w3c.github.io/web-locks/#enumdef-lockmode>
+#[derive(Clone, Copy, Debug, Deserialize, Eq, MallocSizeOf, PartialEq, Serialize)]
+pub enum LockMode {
+    /// The lock may be held with other shared locks of the same name.
+    Shared,
+    /// The lock is the only one of its name that is held.
+    Exclusive,
+}
+
+/// A request for a lock, which the lock manager of the origin grants once no held lock
+/// conflicts with it.
+/// <https://w3c.github.io/web-locks/#lock-request>
+#[derive(Debug, Deserialize, Serialize)]
+pub struct LockRequest {
+    pub id: LockRequestId,
+    pub origin: ImmutableOrigin,
+    pub name: String,
+    pub mode: LockMode,
+    /// The id of the environment that requested the lock, which `query()` reports.
+    pub client_id: String,
+    /// Whether the request is dropped rather than queued if it can't be granted at once.
+    pub if_available: bool,
+    /// Whether the held locks of the same name are released to grant the request at once.
+    pub steal: bool,
+    /// Where the events of the request go.
+    pub sender: IpcSender<LockEvent>,
+}
+
+/// What happened to a request for a lock.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum LockEvent {
+    /// The lock is held until it is released.
+    Granted,
+    /// The request asked for the lock only if it was available, and it wasn't.
+    NotAvailable,
+    /// The lock was held, and was released for a request that stole it.
+    Stolen,
+}
+
+/// The state of a held or pending lock, as reported by `query()`.
+/// <https://w3c.github.io/web-locks/#dictdef-lockinfo>
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct LockInfo {
+    pub name: String,
+    pub mode: LockMode,
+    pub client_id: String,
 }
 
 /// The set of WebRender operations that can be initiated by the content process.
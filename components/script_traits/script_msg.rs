@@ -9,32 +9,44 @@ use crate::DocumentState;
 use crate::IFrameLoadInfoWithData;
 use crate::LayoutControlMsg;
 use crate::LoadData;
+use crate::LockInfo;
+use crate::LockRequest;
 use crate::MessagePortMsg;
+use crate::OverscrollContainer;
 use crate::PortMessageTask;
+use crate::ScrollSnapContainer;
+use crate::ScrollState;
+use crate::ScrollbarContainer;
 use crate::StructuredSerializedData;
+use crate::UserActivationUpdate;
 use crate::WindowSizeType;
 use crate::WorkerGlobalScopeInit;
 use crate::WorkerScriptLoadOrigin;
 use canvas_traits::canvas::{CanvasId, CanvasMsg};
 use devtools_traits::{ScriptToDevtoolsControlMsg, WorkerId};
-use embedder_traits::{EmbedderMsg, MediaSessionEvent};
+use embedder_traits::{
+    BatteryStatus, CertificateErrorId, DeviceSensor, EmbedderMsg, MediaSessionEvent,
+    NetworkInformation, SpeechEvent, SpeechUtterance, SpeechVoice,
+};
 use euclid::default::Size2D as UntypedSize2D;
 use euclid::Size2D;
 use gfx_traits::Epoch;
 use ipc_channel::ipc::{IpcReceiver, IpcSender};
 use msg::constellation_msg::{
-    BroadcastChannelRouterId, BrowsingContextId, MessagePortId, MessagePortRouterId, PipelineId,
-    TopLevelBrowsingContextId,
+    BroadcastChannelRouterId, BrowsingContextId, LockRequestId, MessagePortId, MessagePortRouterId,
+    PipelineId, TopLevelBrowsingContextId,
 };
-use msg::constellation_msg::{HistoryStateId, TraversalDirection};
+use msg::constellation_msg::{HistoryStateId, NetworkConditions, TraversalDirection};
 use net_traits::request::RequestBuilder;
 use net_traits::storage_thread::StorageType;
+use net_traits::webauthn::AuthenticatorMsg;
 use net_traits::CoreResourceMsg;
 use servo_url::ImmutableOrigin;
 use servo_url::ServoUrl;
 use smallvec::SmallVec;
 use std::collections::{HashMap, VecDeque};
 use std::fmt;
+use std::time::Duration;
 use style_traits::viewport::ViewportConstraints;
 use style_traits::CSSPixel;
 use webgpu::{wgpu, WebGPUResponseResult};
@@ -70,6 +82,14 @@ pub enum LayoutMsg {
     PendingPaintMetric(PipelineId, Epoch),
     /// Notifies the constellation that the viewport has been constrained in some manner
     ViewportConstrained(PipelineId, ViewportConstraints),
+    /// Inform the constellation of the snap positions of the scroll containers of a pipeline.
+    ScrollSnapContainers(PipelineId, Vec<ScrollSnapContainer>),
+    /// Inform the constellation of the overscroll behavior of the scroll containers of a
+    /// pipeline, starting with its viewport.
+    OverscrollContainers(PipelineId, Vec<OverscrollContainer>),
+    /// Inform the constellation of the scrollbars of the scroll containers of a pipeline,
+    /// starting with its viewport.
+    ScrollbarContainers(PipelineId, Vec<ScrollbarContainer>),
 }
 
 impl fmt::Debug for LayoutMsg {
@@ -80,6 +100,9 @@ impl fmt::Debug for LayoutMsg {
             IFrameSizes(..) => "IFrameSizes",
             PendingPaintMetric(..) => "PendingPaintMetric",
             ViewportConstrained(..) => "ViewportConstrained",
+            ScrollSnapContainers(..) => "ScrollSnapContainers",
+            OverscrollContainers(..) => "OverscrollContainers",
+            ScrollbarContainers(..) => "ScrollbarContainers",
         };
         write!(formatter, "LayoutMsg::{}", variant)
     }
@@ -116,6 +139,15 @@ pub enum HistoryEntryReplacement {
     Disabled,
 }
 
+/// What the speech synthesizer should do with the utterance being spoken.
+/// <https://wicg.github.io/speech-api/#tts-methods>
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub enum SpeechAction {
+    Pause,
+    Resume,
+    Cancel,
+}
+
 /// Messages from the script to the constellation.
 #[derive(Deserialize, Serialize)]
 pub enum ScriptMsg {
@@ -159,8 +191,35 @@ pub enum ScriptMsg {
     /// Broadcast a message to all same-origin broadcast channels,
     /// excluding the source of the broadcast.
     ScheduleBroadcast(BroadcastChannelRouterId, BroadcastMsg),
+    /// Queues a request for a lock with the lock manager of its origin.
+    RequestLock(LockRequest),
+    /// Releases the lock granted for a request, or drops the request if it is still pending.
+    ReleaseLock(ImmutableOrigin, LockRequestId),
+    /// Gets the held locks and the pending requests of the lock manager of an origin.
+    QueryLocks(ImmutableOrigin, IpcSender<(Vec<LockInfo>, Vec<LockInfo>)>),
     /// Forward a message to the embedder.
     ForwardToEmbedder(EmbedderMsg),
+    /// Locks the pointer to the document of this pipeline, or releases it.
+    SetPointerLock(bool),
+    /// Whether the document of this pipeline holds screen wake locks.
+    SetScreenWakeLock(bool),
+    /// The user activation of the window of this pipeline changed, which the other windows of
+    /// its frame tree follow.
+    UpdateUserActivation(UserActivationUpdate),
+    /// Gets how long ago the user last interacted with the browser.
+    GetUserIdleTime(IpcSender<Duration>),
+    /// Gets the voices that utterances can be spoken with.
+    GetSpeechVoices(IpcSender<Vec<SpeechVoice>>),
+    /// Speaks an utterance, interrupting the one being spoken, and reports its progress.
+    Speak(SpeechUtterance, IpcSender<SpeechEvent>),
+    /// Pauses, resumes or cancels speaking, if this pipeline spoke last.
+    SpeechAction(SpeechAction),
+    /// Tells which of the given words of a language are misspelled.
+    CheckSpelling(String, Vec<String>, IpcSender<Vec<bool>>),
+    /// Gets the corrections to suggest for a misspelled word of a language.
+    GetSpellingSuggestions(String, String, IpcSender<Vec<String>>),
+    /// Adds a word to the personal dictionary of the user.
+    AddToDictionary(String),
     /// Requests are sent to constellation and fetches are checked manually
     /// for cross-origin loads
     InitiateNavigateRequest(RequestBuilder, /* cancellation_chan */ IpcReceiver<()>),
@@ -271,8 +330,32 @@ pub enum ScriptMsg {
     GetClientWindow(IpcSender<(DeviceIntSize, DeviceIntPoint)>),
     /// Get the screen size (pixel)
     GetScreenSize(IpcSender<DeviceIntSize>),
+    /// Get the state of the battery of the device
+    GetBatteryStatus(IpcSender<BatteryStatus>),
+    /// Get the state of the network connection of the device
+    GetNetworkInformation(IpcSender<NetworkInformation>),
+    /// Start sending the positions of the device to this pipeline, with a high accuracy or
+    /// not, or stop with `None`
+    WatchPosition(Option<bool>),
+    /// Send the readings of the given sensors of the device to this pipeline, replacing the
+    /// sensors it listened to before
+    WatchDeviceSensors(Vec<DeviceSensor>),
+    /// Prerender the given loads, replacing the prerenders that this top-level document asked
+    /// for before
+    Prerender(Vec<LoadData>),
+    /// The certificate of this document failed to verify, and an error page is shown instead.
+    /// The embedder decides whether to trust the certificate and reload the document.
+    CertificateError(CertificateErrorId),
     /// Get the available screen size (pixel)
     GetScreenAvailSize(IpcSender<DeviceIntSize>),
+    /// Animate a scroll node to the given scroll offset, for a scroll with smooth behavior.
+    SmoothScroll(ScrollState),
+    /// Make the network of the top-level browsing context of this pipeline behave like under
+    /// the given conditions, or like it really does, as the developer tools asked for.
+    SetNetworkConditions(Option<NetworkConditions>),
+    /// Ask the authenticator of the public key credentials to make a credential or an
+    /// assertion, for a relying party that this document may use
+    ToAuthenticator(AuthenticatorMsg),
     /// Notifies the constellation about media session events
     /// (i.e. when there is metadata for the active media session, playback state changes...).
     MediaSessionEvent(PipelineId, MediaSessionEvent),
@@ -302,7 +385,20 @@ impl fmt::Debug for ScriptMsg {
             RemoveBroadcastChannelNameInRouter(..) => "RemoveBroadcastChannelNameInRouter",
             NewBroadcastChannelNameInRouter(..) => "NewBroadcastChannelNameInRouter",
             ScheduleBroadcast(..) => "ScheduleBroadcast",
+            RequestLock(..) => "RequestLock",
+            ReleaseLock(..) => "ReleaseLock",
+            QueryLocks(..) => "QueryLocks",
             ForwardToEmbedder(..) => "ForwardToEmbedder",
+            SetPointerLock(..) => "SetPointerLock",
+            SetScreenWakeLock(..) => "SetScreenWakeLock",
+            UpdateUserActivation(..) => "UpdateUserActivation",
+            GetUserIdleTime(..) => "GetUserIdleTime",
+            GetSpeechVoices(..) => "GetSpeechVoices",
+            Speak(..) => "Speak",
+            SpeechAction(..) => "SpeechAction",
+            CheckSpelling(..) => "CheckSpelling",
+            GetSpellingSuggestions(..) => "GetSpellingSuggestions",
+            AddToDictionary(..) => "AddToDictionary",
             InitiateNavigateRequest(..) => "InitiateNavigateRequest",
             BroadcastStorageEvent(..) => "BroadcastStorageEvent",
             ChangeRunningAnimationsState(..) => "ChangeRunningAnimationsState",
@@ -337,7 +433,16 @@ impl fmt::Debug for ScriptMsg {
             RegisterServiceWorker(..) => "RegisterServiceWorker",
             GetClientWindow(..) => "GetClientWindow",
             GetScreenSize(..) => "GetScreenSize",
+            GetBatteryStatus(..) => "GetBatteryStatus",
+            GetNetworkInformation(..) => "GetNetworkInformation",
+            WatchPosition(..) => "WatchPosition",
+            Prerender(..) => "Prerender",
+            CertificateError(..) => "CertificateError",
+            WatchDeviceSensors(..) => "WatchDeviceSensors",
             GetScreenAvailSize(..) => "GetScreenAvailSize",
+            SmoothScroll(..) => "SmoothScroll",
+            SetNetworkConditions(..) => "SetNetworkConditions",
+            ToAuthenticator(..) => "ToAuthenticator",
             MediaSessionEvent(..) => "MediaSessionEvent",
             RequestAdapter(..) => "RequestAdapter",
         };
@@ -65,7 +65,7 @@ fn webdriver(_port: u16, _constellation: Sender<ConstellationMsg>) {}
 
 use bluetooth::BluetoothThreadFactory;
 use bluetooth_traits::BluetoothRequest;
-use canvas::{SurfaceProviders, WebGLComm, WebGlExecutor};
+use canvas::{GpuCanvasInit, SurfaceProviders, WebGLComm, WebGlExecutor};
 use canvas_traits::webgl::WebGLThreads;
 use compositing::compositor_thread::{
     CompositorProxy, CompositorReceiver, InitialCompositorState, Msg,
@@ -83,7 +83,10 @@ use constellation::content_process_sandbox_profile;
 use constellation::{Constellation, InitialConstellationState, UnprivilegedPipelineContent};
 use constellation::{FromCompositorLogger, FromScriptLogger};
 use crossbeam_channel::{unbounded, Sender};
-use embedder_traits::{EmbedderMsg, EmbedderProxy, EmbedderReceiver, EventLoopWaker};
+use embedder_traits::SpeechSynthesisProvider;
+use embedder_traits::SpellCheckProvider;
+use embedder_traits::{DeviceStatusProvider, EmbedderMsg, EmbedderProxy, EmbedderReceiver};
+use embedder_traits::{EventLoopWaker, PositionProvider, ProtocolRegistry, SensorProvider};
 use env_logger::Builder as EnvLoggerBuilder;
 use euclid::{Scale, Size2D};
 #[cfg(all(
@@ -465,7 +468,7 @@ where
         let mut webxr_main_thread = webxr::MainThreadRegistry::new(event_loop_waker)
             .expect("Failed to create WebXR device registry");
 
-        let (webgl_threads, webgl_extras) = create_webgl_threads(
+        let (webgl_threads, webgl_extras, canvas_gpu_init) = create_webgl_threads(
             &*window,
             &mut webrender,
             webrender_api_sender.clone(),
@@ -532,10 +535,17 @@ where
             webxr_main_thread.registry(),
             player_context,
             webgl_threads,
+            canvas_gpu_init,
             webvr_chan,
             webvr_constellation_sender,
             glplayer_threads,
             event_loop_waker,
+            embedder.device_status_provider(),
+            embedder.position_provider(),
+            embedder.sensor_provider(),
+            embedder.speech_synthesis_provider(),
+            embedder.spell_check_provider(),
+            embedder.protocol_registry(),
             window_size,
             pending_wr_frame.clone(),
         );
@@ -604,6 +614,16 @@ where
                 }
             },
 
+            WindowEvent::CertificateErrorResponse(id, trusted) => {
+                let msg = ConstellationMsg::CertificateErrorResponse(id, trusted);
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!(
+                        "Sending CertificateErrorResponse to constellation failed ({:?}).",
+                        e
+                    );
+                }
+            },
+
             WindowEvent::LoadUrl(top_level_browsing_context_id, url) => {
                 let msg = ConstellationMsg::LoadUrl(top_level_browsing_context_id, url);
                 if let Err(e) = self.constellation_chan.send(msg) {
@@ -620,6 +640,10 @@ where
                 self.compositor.on_mouse_window_move_event_class(cursor);
             },
 
+            WindowEvent::PointerLockMove(movement) => {
+                self.compositor.on_pointer_lock_move_event(movement);
+            },
+
             WindowEvent::Touch(event_type, identifier, location) => {
                 self.compositor
                     .on_touch_event(event_type, identifier, location);
@@ -641,6 +665,17 @@ where
                 self.compositor.on_zoom_reset_window_event();
             },
 
+            WindowEvent::SetZoom(zoom) => {
+                self.compositor.on_set_zoom_window_event(zoom);
+            },
+
+            WindowEvent::SetTextZoom(zoom) => {
+                let msg = ConstellationMsg::SetTextZoom(zoom);
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!("Sending SetTextZoom to constellation failed ({:?}).", e);
+                }
+            },
+
             WindowEvent::PinchZoom(magnification) => {
                 self.compositor.on_pinch_zoom_window_event(magnification);
             },
@@ -660,6 +695,16 @@ where
                 }
             },
 
+            WindowEvent::IMEComposition(composition_event) => {
+                let msg = ConstellationMsg::IMECompositionEvent(composition_event);
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!(
+                        "Sending composition event to constellation failed ({:?}).",
+                        e
+                    );
+                }
+            },
+
             WindowEvent::Quit => {
                 self.compositor.maybe_start_shutting_down();
             },
@@ -671,6 +716,13 @@ where
                 }
             },
 
+            WindowEvent::ExitPointerLock(top_level_browsing_context_id) => {
+                let msg = ConstellationMsg::ExitPointerLock(top_level_browsing_context_id);
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!("Sending exit pointer lock to constellation failed ({:?}).", e);
+                }
+            },
+
             WindowEvent::Reload(top_level_browsing_context_id) => {
                 let msg = ConstellationMsg::Reload(top_level_browsing_context_id);
                 if let Err(e) = self.constellation_chan.send(msg) {
@@ -760,6 +812,117 @@ where
                     );
                 }
             },
+
+            WindowEvent::Find(top_level_browsing_context_id, string, options) => {
+                let msg = ConstellationMsg::Find(top_level_browsing_context_id, string, options);
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!("Sending Find to constellation failed ({:?}).", e);
+                }
+            },
+
+            WindowEvent::StopFinding(top_level_browsing_context_id) => {
+                let msg = ConstellationMsg::StopFinding(top_level_browsing_context_id);
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!("Sending StopFinding to constellation failed ({:?}).", e);
+                }
+            },
+
+            WindowEvent::ExtractArticle(top_level_browsing_context_id) => {
+                let msg = ConstellationMsg::ExtractArticle(top_level_browsing_context_id);
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!("Sending ExtractArticle to constellation failed ({:?}).", e);
+                }
+            },
+
+            WindowEvent::RequestAccessibilityTree(top_level_browsing_context_id) => {
+                let msg = ConstellationMsg::RequestAccessibilityTree(top_level_browsing_context_id);
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!(
+                        "Sending RequestAccessibilityTree to constellation failed ({:?}).",
+                        e
+                    );
+                }
+            },
+
+            WindowEvent::RequestFormFields(top_level_browsing_context_id) => {
+                let msg = ConstellationMsg::RequestFormFields(top_level_browsing_context_id);
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!("Sending RequestFormFields to constellation failed ({:?}).", e);
+                }
+            },
+
+            WindowEvent::FillFormFields(top_level_browsing_context_id, values) => {
+                let msg = ConstellationMsg::FillFormFields(top_level_browsing_context_id, values);
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!("Sending FillFormFields to constellation failed ({:?}).", e);
+                }
+            },
+
+            WindowEvent::FillCredentials(top_level_browsing_context_id, credentials) => {
+                let msg =
+                    ConstellationMsg::FillCredentials(top_level_browsing_context_id, credentials);
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!("Sending FillCredentials to constellation failed ({:?}).", e);
+                }
+            },
+
+            WindowEvent::MemoryPressure => {
+                let msg = ConstellationMsg::MemoryPressure;
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!("Sending MemoryPressure to constellation failed ({:?}).", e);
+                }
+            },
+
+            WindowEvent::MediaPreferencesChanged(preferences) => {
+                let msg = ConstellationMsg::MediaPreferencesChanged(preferences);
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!(
+                        "Sending MediaPreferencesChanged to constellation failed ({:?}).",
+                        e
+                    );
+                }
+            },
+
+            WindowEvent::DeviceStatusChanged => {
+                let msg = ConstellationMsg::DeviceStatusChanged;
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!("Sending DeviceStatusChanged to constellation failed ({:?}).", e);
+                }
+            },
+
+            WindowEvent::PositionChanged(position) => {
+                let msg = ConstellationMsg::PositionChanged(position);
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!("Sending PositionChanged to constellation failed ({:?}).", e);
+                }
+            },
+
+            WindowEvent::SensorReading(reading) => {
+                let msg = ConstellationMsg::SensorReading(reading);
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!("Sending SensorReading to constellation failed ({:?}).", e);
+                }
+            },
+
+            WindowEvent::SetNetworkConditions(top_level_browsing_context_id, conditions) => {
+                let msg = ConstellationMsg::SetNetworkConditions(
+                    top_level_browsing_context_id,
+                    conditions,
+                );
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!(
+                        "Sending SetNetworkConditions to constellation failed ({:?}).",
+                        e
+                    );
+                }
+            },
+
+            WindowEvent::DownloadAction(id, action) => {
+                let msg = ConstellationMsg::DownloadAction(id, action);
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!("Sending DownloadAction to constellation failed ({:?}).", e);
+                }
+            },
         }
     }
 
@@ -872,10 +1035,17 @@ fn create_constellation(
     webxr_registry: webxr_api::Registry,
     player_context: WindowGLContext,
     webgl_threads: Option<WebGLThreads>,
+    canvas_gpu_init: Option<GpuCanvasInit>,
     webvr_chan: Option<IpcSender<WebVRMsg>>,
     webvr_constellation_sender: Option<Sender<Sender<ConstellationMsg>>>,
     glplayer_threads: Option<GLPlayerThreads>,
     event_loop_waker: Option<Box<dyn EventLoopWaker>>,
+    device_status_provider: Option<Box<dyn DeviceStatusProvider>>,
+    position_provider: Option<Box<dyn PositionProvider>>,
+    sensor_provider: Option<Box<dyn SensorProvider>>,
+    speech_synthesis_provider: Option<Box<dyn SpeechSynthesisProvider>>,
+    spell_check_provider: Option<Box<dyn SpellCheckProvider>>,
+    protocols: ProtocolRegistry,
     initial_window_size: WindowSizeData,
     pending_wr_frame: Arc<AtomicBool>,
 ) -> (Sender<ConstellationMsg>, SWManagerSenders) {
@@ -893,6 +1063,7 @@ fn create_constellation(
         embedder_proxy.clone(),
         config_dir,
         opts.certificate_path.clone(),
+        protocols,
     );
     let font_cache_thread = FontCacheThread::new(
         public_resource_threads.sender(),
@@ -920,10 +1091,16 @@ fn create_constellation(
         glplayer_threads,
         player_context,
         event_loop_waker,
+        device_status_provider,
+        position_provider,
+        sensor_provider,
+        speech_synthesis_provider,
+        spell_check_provider,
         pending_wr_frame,
     };
 
-    let (canvas_chan, ipc_canvas_chan) = canvas::canvas_paint_thread::CanvasPaintThread::start();
+    let (canvas_chan, ipc_canvas_chan) =
+        canvas::canvas_paint_thread::CanvasPaintThread::start(canvas_gpu_init);
 
     let (constellation_chan, from_swmanager_sender) = Constellation::<
         script_layout_interface::message::Msg,
@@ -1070,6 +1247,7 @@ fn create_webgl_threads<W>(
 ) -> (
     Option<WebGLThreads>,
     Option<(SurfaceProviders, WebGlExecutor)>,
+    Option<GpuCanvasInit>,
 )
 where
     W: WindowMethods + 'static + ?Sized,
@@ -1084,7 +1262,7 @@ where
                 Ok(a) => a,
                 Err(e) => {
                     warn!("Failed to create software graphics context: {:?}", e);
-                    return (None, None);
+                    return (None, None, None);
                 },
             };
             (Device::Software(device), Context::Software(context))
@@ -1093,7 +1271,7 @@ where
                 Ok(a) => a,
                 Err(e) => {
                     warn!("Failed to create hardware graphics context: {:?}", e);
-                    return (None, None);
+                    return (None, None, None);
                 },
             };
             (Device::Hardware(device), Context::Hardware(context))
@@ -1104,7 +1282,7 @@ where
         Ok(a) => a,
         Err(e) => {
             warn!("Failed to create graphics context: {:?}", e);
-            return (None, None);
+            return (None, None, None);
         },
     };
 
@@ -1113,6 +1291,17 @@ where
         gleam::gl::GlType::Gles => sparkle::gl::GlType::Gles,
     };
 
+    // The canvas thread draws with its own context, on the adapter of the WebGL contexts.
+    let canvas_gpu_init = if pref!(dom.canvas.gpu.enabled) {
+        Some(GpuCanvasInit {
+            connection: device.connection(),
+            adapter: device.adapter(),
+            api_type: gl_type,
+        })
+    } else {
+        None
+    };
+
     let WebGLComm {
         webgl_threads,
         webxr_swap_chains,
@@ -1144,5 +1333,6 @@ where
     (
         Some(webgl_threads),
         Some((webxr_surface_providers, webgl_executor)),
+        canvas_gpu_init,
     )
 }
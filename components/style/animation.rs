@@ -15,11 +15,11 @@ use crate::font_metrics::FontMetricsProvider;
 use crate::properties::animated_properties::AnimatedProperty;
 use crate::properties::longhands::animation_direction::computed_value::single_value::T as AnimationDirection;
 use crate::properties::longhands::animation_play_state::computed_value::single_value::T as AnimationPlayState;
-use crate::properties::{self, CascadeMode, ComputedValues, LonghandId};
+use crate::properties::{self, style_structs, CascadeMode, ComputedValues, LonghandId};
 use crate::stylesheets::keyframes_rule::{KeyframesAnimation, KeyframesStep, KeyframesStepValue};
 use crate::stylesheets::Origin;
 use crate::timer::Timer;
-use crate::values::computed::box_::TransitionProperty;
+use crate::values::computed::box_::{AnimationTimeline, TransitionProperty};
 use crate::values::computed::Time;
 use crate::values::computed::TimingFunction;
 use crate::values::generics::box_::AnimationIterationCount;
@@ -78,6 +78,12 @@ pub struct KeyframesAnimationState {
     pub current_direction: AnimationDirection,
     /// Werther this keyframe animation is outdated due to a restyle.
     pub expired: bool,
+    /// The timeline that drives this animation.
+    pub timeline: AnimationTimeline,
+    /// The progress of the scroll or view progress timeline that drives this animation, from 0
+    /// to 1, or `None` if the timeline is inactive, for example because its scroll container
+    /// can't scroll. This is updated by layout, from the scroll positions it knows about.
+    pub timeline_progress: Option<f64>,
     /// The original cascade style, needed to compute the generated keyframes of
     /// the animation.
     pub cascade_style: Arc<ComputedValues>,
@@ -146,6 +152,7 @@ impl KeyframesAnimationState {
         let old_direction = self.current_direction;
         let old_running_state = self.running_state.clone();
         let old_iteration_state = self.iteration_state.clone();
+        let old_timeline_progress = self.timeline_progress;
         *self = other.clone();
 
         let mut new_started_at = old_started_at;
@@ -180,6 +187,16 @@ impl KeyframesAnimationState {
 
         self.current_direction = old_direction;
         self.started_at = new_started_at;
+        if self.timeline == other.timeline {
+            self.timeline_progress = old_timeline_progress;
+        }
+    }
+
+    /// Whether this animation is driven by scrolling rather than by time, in which case it
+    /// never ends.
+    #[inline]
+    pub fn is_scroll_driven(&self) -> bool {
+        self.timeline.is_scroll_driven()
     }
 }
 
@@ -194,6 +211,8 @@ impl fmt::Debug for KeyframesAnimationState {
             .field("direction", &self.direction)
             .field("current_direction", &self.current_direction)
             .field("expired", &self.expired)
+            .field("timeline", &self.timeline)
+            .field("timeline_progress", &self.timeline_progress)
             .field("cascade_style", &())
             .finish()
     }
@@ -519,6 +538,19 @@ where
     }
 }
 
+/// The timeline of the animation at the given index of `animation-name`.
+#[cfg(feature = "servo")]
+fn animation_timeline(box_style: &style_structs::Box, index: usize) -> AnimationTimeline {
+    box_style.animation_timeline_mod(index)
+}
+
+/// The timeline of the animation at the given index of `animation-name`. Gecko doesn't
+/// support `animation-timeline`.
+#[cfg(feature = "gecko")]
+fn animation_timeline(_: &style_structs::Box, _: usize) -> AnimationTimeline {
+    AnimationTimeline::Auto
+}
+
 /// Triggers animations for a given node looking at the animation property
 /// values.
 pub fn maybe_start_animations<E>(
@@ -541,8 +573,12 @@ where
         };
 
         debug!("maybe_start_animations: name={}", name);
+        let timeline = animation_timeline(box_style, i);
+        if timeline == AnimationTimeline::None {
+            continue;
+        }
         let total_duration = box_style.animation_duration_mod(i).seconds();
-        if total_duration == 0. {
+        if total_duration == 0. && !timeline.is_scroll_driven() {
             continue;
         }
 
@@ -561,10 +597,15 @@ where
             continue;
         }
 
-        let delay = box_style.animation_delay_mod(i).seconds();
-        let now = context.timer.seconds();
-        let animation_start = now + delay as f64;
-        let duration = box_style.animation_duration_mod(i).seconds();
+        // The whole range of a scroll-driven timeline is a single iteration of the animation,
+        // which is given a duration of one second for the keyframes to be computed with.
+        let (animation_start, duration, delay) = if timeline.is_scroll_driven() {
+            (0., 1., 0.)
+        } else {
+            let delay = box_style.animation_delay_mod(i).seconds();
+            let now = context.timer.seconds();
+            (now + delay as f64, total_duration, delay)
+        };
         let iteration_state = match box_style.animation_iteration_count_mod(i) {
             AnimationIterationCount::Infinite => KeyframesIterationState::Infinite,
             AnimationIterationCount::Number(n) => KeyframesIterationState::Finite(0.0, n),
@@ -600,6 +641,8 @@ where
                     direction: animation_direction,
                     current_direction: initial_direction,
                     expired: false,
+                    timeline,
+                    timeline_progress: None,
                     cascade_style: new_style.clone(),
                 },
             ))
@@ -684,9 +727,17 @@ where
             let duration = state.duration;
             let started_at = state.started_at;
 
-            let now = match state.running_state {
-                KeyframesRunningState::Running => context.timer.seconds(),
-                KeyframesRunningState::Paused(progress) => started_at + duration * progress,
+            let now = if state.is_scroll_driven() {
+                // An animation whose timeline is inactive has no effect.
+                match state.timeline_progress {
+                    Some(progress) => started_at + duration * progress,
+                    None => return AnimationUpdate::Regular,
+                }
+            } else {
+                match state.running_state {
+                    KeyframesRunningState::Running => context.timer.seconds(),
+                    KeyframesRunningState::Paused(progress) => started_at + duration * progress,
+                }
             };
 
             debug_assert!(!animation.steps.is_empty());
@@ -701,7 +752,11 @@ where
                 None => return AnimationUpdate::AnimationCanceled,
             };
 
-            let total_duration = style.get_box().animation_duration_mod(index).seconds() as f64;
+            let total_duration = if state.is_scroll_driven() {
+                duration
+            } else {
+                style.get_box().animation_duration_mod(index).seconds() as f64
+            };
             if total_duration == 0. {
                 return AnimationUpdate::AnimationCanceled;
             }
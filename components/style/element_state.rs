@@ -106,7 +106,8 @@ bitflags! {
         const IN_STYLEEDITOR_TRANSITIONING_STATE = 1 << 37;
         /// Non-standard & undocumented.
         const IN_INCREMENT_SCRIPT_LEVEL_STATE = 1 << 38;
-        /// Non-standard: https://developer.mozilla.org/en-US/docs/Web/CSS/:-moz-focusring
+        /// <https://drafts.csswg.org/selectors-4/#the-focus-visible-pseudo>, and the non-standard
+        /// https://developer.mozilla.org/en-US/docs/Web/CSS/:-moz-focusring
         const IN_FOCUSRING_STATE = 1 << 39;
         /// Non-standard & undocumented.
         const IN_HANDLER_CLICK_TO_PLAY_STATE = 1 << 40;
@@ -14,7 +14,7 @@ use crate::invalidation::element::restyle_hints::RestyleHint;
 use crate::media_queries::Device;
 use crate::selector_parser::{SelectorImpl, Snapshot, SnapshotMap};
 use crate::shared_lock::SharedRwLockReadGuard;
-use crate::stylesheets::{CssRule, StylesheetInDocument};
+use crate::stylesheets::{CssRule, EffectiveRules, RulesIterator, StylesheetInDocument};
 use crate::Atom;
 use crate::CaseSensitivityExt;
 use crate::LocalName as SelectorLocalName;
@@ -22,6 +22,7 @@ use fxhash::FxHasher;
 use selectors::attr::CaseSensitivity;
 use selectors::parser::{Component, LocalName, Selector};
 use std::hash::BuildHasherDefault;
+use std::slice;
 
 type FxHashSet<K> = HashSet<K, BuildHasherDefault<FxHasher>>;
 
@@ -177,6 +178,41 @@ impl StylesheetInvalidationSet {
         debug!(" > fully_invalid: {}", self.fully_invalid);
     }
 
+    /// Collects the invalidations for a rule which was inserted into or removed
+    /// from the given stylesheet, including the rules nested in it.
+    pub fn rule_changed<S>(
+        &mut self,
+        device: &Device,
+        stylesheet: &S,
+        rule: &CssRule,
+        guard: &SharedRwLockReadGuard,
+    ) where
+        S: StylesheetInDocument,
+    {
+        debug!("StylesheetInvalidationSet::rule_changed");
+        if self.fully_invalid {
+            debug!(" > Fully invalid already");
+            return;
+        }
+
+        if !stylesheet.enabled() || !stylesheet.is_effective_for_device(device, guard) {
+            debug!(" > Stylesheet was not effective");
+            return;
+        }
+
+        let quirks_mode = stylesheet.quirks_mode(guard);
+        let rules =
+            RulesIterator::<EffectiveRules>::new(device, quirks_mode, guard, slice::from_ref(rule));
+        for rule in rules {
+            self.collect_invalidations_for_rule(rule, guard, device);
+            if self.fully_invalid {
+                self.invalid_scopes.clear();
+                self.invalid_elements.clear();
+                break;
+            }
+        }
+    }
+
     /// Clears the invalidation set, invalidating elements as needed if
     /// `document_element` is provided.
     ///
@@ -381,7 +381,7 @@ fn application_when_ignoring_colors(
     // is the right decision.
     match *declaration {
         PropertyDeclaration::BackgroundColor(ref color) => {
-            if color.is_transparent() {
+            if color.is_transparent() || color.is_system() {
                 return DeclarationApplication::Apply;
             }
             let color = builder.device.default_background_color();
@@ -390,7 +390,7 @@ fn application_when_ignoring_colors(
             )
         }
         PropertyDeclaration::Color(ref color) => {
-            if color.0.is_transparent() {
+            if color.0.is_transparent() || color.0.is_system() {
                 return DeclarationApplication::Apply;
             }
             if builder.get_parent_inherited_text().clone_color().alpha != 0 {
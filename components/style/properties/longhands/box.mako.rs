@@ -338,6 +338,20 @@ ${helpers.predefined_type(
     allowed_in_keyframe_block=False,
 )}
 
+${helpers.predefined_type(
+    "animation-timeline",
+    "AnimationTimeline",
+    "computed::AnimationTimeline::auto()",
+    engines="servo-2013 servo-2020",
+    servo_2020_pref="layout.2020.unimplemented",
+    initial_specified_value="specified::AnimationTimeline::auto()",
+    vector=True,
+    need_index=True,
+    animation_value_type="none",
+    spec="https://drafts.csswg.org/css-animations-2/#animation-timeline",
+    allowed_in_keyframe_block=False,
+)}
+
 <% transform_extra_prefixes = "moz:layout.css.prefixes.transforms webkit" %>
 
 ${helpers.predefined_type(
@@ -449,7 +463,7 @@ ${helpers.predefined_type(
 ${helpers.single_keyword(
     "scroll-behavior",
     "auto smooth",
-    engines="gecko",
+    engines="gecko servo-2013 servo-2020",
     spec="https://drafts.csswg.org/cssom-view/#propdef-scroll-behavior",
     animation_value_type="discrete",
     gecko_enum_prefix="StyleScrollBehavior",
@@ -459,7 +473,7 @@ ${helpers.predefined_type(
     "scroll-snap-align",
     "ScrollSnapAlign",
     "computed::ScrollSnapAlign::none()",
-    engines="gecko",
+    engines="gecko servo-2013 servo-2020",
     spec="https://drafts.csswg.org/css-scroll-snap-1/#scroll-snap-align",
     animation_value_type="discrete",
 )}
@@ -468,7 +482,7 @@ ${helpers.predefined_type(
     "scroll-snap-type",
     "ScrollSnapType",
     "computed::ScrollSnapType::none()",
-    engines="gecko",
+    engines="gecko servo-2013 servo-2020",
     spec="https://drafts.csswg.org/css-scroll-snap-1/#scroll-snap-type",
     animation_value_type="discrete",
 )}
@@ -478,7 +492,7 @@ ${helpers.predefined_type(
         "overscroll-behavior-" + axis,
         "OverscrollBehavior",
         "computed::OverscrollBehavior::Auto",
-        engines="gecko",
+        engines="gecko servo-2013 servo-2020",
         needs_context=False,
         logical_group="overscroll-behavior",
         logical=logical,
@@ -75,9 +75,10 @@ ${helpers.predefined_type(
     "scrollbar-color",
     "ui::ScrollbarColor",
     "Default::default()",
-    engines="gecko",
+    engines="gecko servo-2013",
     spec="https://drafts.csswg.org/css-scrollbars-1/#scrollbar-color",
     gecko_pref="layout.css.scrollbar-color.enabled",
+    servo_2013_pref="layout.scrollbars.enabled",
     # Surprisingly, yes the computed value of scrollbar-color has no effect on
     # Gecko scrollbar elements, since the value only matters on the scrollable
     # element itself.
@@ -87,3 +88,13 @@ ${helpers.predefined_type(
     ignored_when_colors_disabled=True,
     enabled_in="chrome",
 )}
+
+${helpers.predefined_type(
+    "color-scheme",
+    "ColorScheme",
+    "computed::ColorScheme::normal()",
+    engines="servo-2013 servo-2020",
+    initial_specified_value="specified::ColorScheme::normal()",
+    animation_value_type="discrete",
+    spec="https://drafts.csswg.org/css-color-adjust/#color-scheme-prop",
+)}
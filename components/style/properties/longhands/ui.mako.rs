@@ -24,10 +24,11 @@ ${helpers.single_keyword(
 ${helpers.single_keyword(
     "scrollbar-width",
     "auto thin none",
-    engines="gecko",
+    engines="gecko servo-2013",
     gecko_enum_prefix="StyleScrollbarWidth",
     animation_value_type="discrete",
     gecko_pref="layout.css.scrollbar-width.enabled",
+    servo_2013_pref="layout.scrollbars.enabled",
     enabled_in="chrome",
     spec="https://drafts.csswg.org/css-scrollbars-1/#scrollbar-width"
 )}
@@ -1313,6 +1313,12 @@ impl LonghandId {
             LonghandId::MozScriptLevel |
             % endif
 
+            % if engine in ["servo-2013", "servo-2020"]:
+            // Needed to compute the system colors, which depend on the used
+            // color scheme.
+            LonghandId::ColorScheme |
+            % endif
+
             // Needed to compute the first available font, in order to
             // compute font-relative units correctly.
             LonghandId::FontSize |
@@ -312,7 +312,7 @@ ${helpers.two_properties_shorthand(
     "overscroll-behavior-x",
     "overscroll-behavior-y",
     "specified::OverscrollBehavior::parse",
-    engines="gecko",
+    engines="gecko servo-2013 servo-2020",
     needs_context=False,
     gecko_pref="layout.css.overscroll-behavior.enabled",
     spec="https://wicg.github.io/overscroll-behavior/#overscroll-behavior-properties",
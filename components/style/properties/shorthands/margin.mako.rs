@@ -36,7 +36,7 @@ ${helpers.four_sides_shorthand(
     "scroll-margin",
     "scroll-margin-%s",
     "specified::Length::parse",
-    engines="gecko",
+    engines="gecko servo-2013 servo-2020",
     spec="https://drafts.csswg.org/css-scroll-snap-1/#propdef-scroll-margin",
 )}
 
@@ -45,7 +45,7 @@ ${helpers.two_properties_shorthand(
     "scroll-margin-block-start",
     "scroll-margin-block-end",
     "specified::Length::parse",
-    engines="gecko",
+    engines="gecko servo-2013 servo-2020",
     spec="https://drafts.csswg.org/css-scroll-snap-1/#propdef-scroll-margin-block",
 )}
 
@@ -54,6 +54,6 @@ ${helpers.two_properties_shorthand(
     "scroll-margin-inline-start",
     "scroll-margin-inline-end",
     "specified::Length::parse",
-    engines="gecko",
+    engines="gecko servo-2013 servo-2020",
     spec="https://drafts.csswg.org/css-scroll-snap-1/#propdef-scroll-margin-inline",
 )}
@@ -35,7 +35,7 @@ ${helpers.four_sides_shorthand(
     "scroll-padding",
     "scroll-padding-%s",
     "specified::NonNegativeLengthPercentageOrAuto::parse",
-    engines="gecko",
+    engines="gecko servo-2013 servo-2020",
     spec="https://drafts.csswg.org/css-scroll-snap-1/#propdef-scroll-padding"
 )}
 
@@ -44,7 +44,7 @@ ${helpers.two_properties_shorthand(
     "scroll-padding-block-start",
     "scroll-padding-block-end",
     "specified::NonNegativeLengthPercentageOrAuto::parse",
-    engines="gecko",
+    engines="gecko servo-2013 servo-2020",
     spec="https://drafts.csswg.org/css-scroll-snap-1/#propdef-scroll-padding-block"
 )}
 
@@ -53,7 +53,7 @@ ${helpers.two_properties_shorthand(
     "scroll-padding-inline-start",
     "scroll-padding-inline-end",
     "specified::NonNegativeLengthPercentageOrAuto::parse",
-    engines="gecko",
+    engines="gecko servo-2013 servo-2020",
     spec="https://drafts.csswg.org/css-scroll-snap-1/#propdef-scroll-padding-inline"
 )}
 
@@ -11,15 +11,17 @@ use crate::media_queries::media_feature_expression::RangeOrOperator;
 use crate::media_queries::MediaType;
 use crate::properties::ComputedValues;
 use crate::values::computed::CSSPixelLength;
+use crate::values::specified::color::{ColorScheme, ColorSchemeFlags, SystemColor};
 use crate::values::specified::font::FONT_MEDIUM_PX;
 use crate::values::KeyframesName;
 use app_units::Au;
 use cssparser::RGBA;
 use euclid::default::Size2D as UntypedSize2D;
 use euclid::{Scale, SideOffsets2D, Size2D};
+use servo_config::pref;
 use std::sync::atomic::{AtomicBool, AtomicIsize, Ordering};
 use style_traits::viewport::ViewportConstraints;
-use style_traits::{CSSPixel, DevicePixel};
+use style_traits::{CSSPixel, ColorSchemePreference, DevicePixel, MediaPreferences};
 
 /// A device is a structure that represents the current media a given document
 /// is displayed in.
@@ -54,6 +56,10 @@ pub struct Device {
     /// The CssEnvironment object responsible of getting CSS environment
     /// variables.
     environment: CssEnvironment,
+    /// The settings of the user that the user preference media features query.
+    preferences: MediaPreferences,
+    /// The factor that the user asked the font sizes to be scaled by.
+    text_zoom: f32,
 }
 
 impl Device {
@@ -72,6 +78,8 @@ impl Device {
             used_root_font_size: AtomicBool::new(false),
             used_viewport_units: AtomicBool::new(false),
             environment: CssEnvironment,
+            preferences: current_preferences(),
+            text_zoom: pref!(layout.text_zoom) as f32,
         }
     }
 
@@ -155,25 +163,103 @@ impl Device {
         self.media_type.clone()
     }
 
-    /// Returns whether document colors are enabled.
+    /// Returns whether document colors are enabled, which is the case unless the colors are
+    /// forced by a high contrast mode.
     pub fn use_document_colors(&self) -> bool {
-        true
+        !self.preferences.forced_colors
     }
 
     /// Returns the default background color.
     pub fn default_background_color(&self) -> RGBA {
-        RGBA::new(255, 255, 255, 255)
+        self.system_color(SystemColor::Canvas, &ColorScheme::normal())
     }
 
     /// Returns the default color color.
     pub fn default_color(&self) -> RGBA {
-        RGBA::new(0, 0, 0, 255)
+        self.system_color(SystemColor::Canvastext, &ColorScheme::normal())
+    }
+
+    /// Returns whether an element with the given `color-scheme` uses the dark color scheme: the
+    /// scheme of the system when the colors are forced, and otherwise the preferred scheme of
+    /// the user if the element supports it, or the only scheme the element supports.
+    ///
+    /// <https://drafts.csswg.org/css-color-adjust/#determine-the-used-color-scheme>
+    pub fn uses_dark_color_scheme(&self, color_scheme: &ColorScheme) -> bool {
+        let prefers_dark = self.preferences.color_scheme == ColorSchemePreference::Dark;
+        if self.preferences.forced_colors {
+            return prefers_dark;
+        }
+        let bits = color_scheme.bits();
+        if prefers_dark {
+            bits.contains(ColorSchemeFlags::DARK)
+        } else {
+            bits.contains(ColorSchemeFlags::DARK) && !bits.contains(ColorSchemeFlags::LIGHT)
+        }
+    }
+
+    /// Returns the value of a system color, from the palette of the color scheme used by an
+    /// element with the given `color-scheme`.
+    ///
+    /// <https://drafts.csswg.org/css-color-4/#css-system-colors>
+    pub fn system_color(&self, color: SystemColor, color_scheme: &ColorScheme) -> RGBA {
+        let dark = self.uses_dark_color_scheme(color_scheme);
+        let (r, g, b) = match (color, dark) {
+            (SystemColor::Canvas, false) | (SystemColor::Field, false) => (255, 255, 255),
+            (SystemColor::Canvas, true) => (18, 18, 18),
+            (SystemColor::Canvastext, false) |
+            (SystemColor::Buttontext, false) |
+            (SystemColor::Fieldtext, false) |
+            (SystemColor::Marktext, _) => (0, 0, 0),
+            (SystemColor::Canvastext, true) |
+            (SystemColor::Buttontext, true) |
+            (SystemColor::Fieldtext, true) |
+            (SystemColor::Highlighttext, _) => (255, 255, 255),
+            (SystemColor::Linktext, false) => (0, 0, 238),
+            (SystemColor::Linktext, true) => (158, 158, 255),
+            (SystemColor::Visitedtext, false) => (85, 26, 139),
+            (SystemColor::Visitedtext, true) => (208, 173, 240),
+            (SystemColor::Activetext, false) => (238, 0, 0),
+            (SystemColor::Activetext, true) => (255, 158, 158),
+            (SystemColor::Buttonface, false) => (239, 239, 239),
+            (SystemColor::Buttonface, true) | (SystemColor::Buttonborder, true) => (107, 107, 107),
+            (SystemColor::Buttonborder, false) => (118, 118, 118),
+            (SystemColor::Field, true) => (59, 59, 59),
+            (SystemColor::Highlight, _) => (51, 144, 255),
+            (SystemColor::Graytext, _) => (128, 128, 128),
+            (SystemColor::Mark, _) => (255, 255, 0),
+        };
+        RGBA::new(r, g, b, 255)
+    }
+
+    /// Applies text zoom to a font-size or line-height value.
+    #[inline]
+    pub fn zoom_text(&self, size: Au) -> Au {
+        size.scale_by(self.text_zoom)
     }
 
     /// Returns safe area insets
     pub fn safe_area_insets(&self) -> SideOffsets2D<f32, CSSPixel> {
         SideOffsets2D::zero()
     }
+
+    /// Returns the settings of the user that the user preference media features query.
+    pub fn preferences(&self) -> MediaPreferences {
+        self.preferences
+    }
+}
+
+/// The settings of the user, which the embedder keeps up to date in the preferences of every
+/// process.
+fn current_preferences() -> MediaPreferences {
+    let color_scheme = match &*pref!(layout.prefers_color_scheme) {
+        "dark" => ColorSchemePreference::Dark,
+        _ => ColorSchemePreference::Light,
+    };
+    MediaPreferences {
+        color_scheme,
+        reduced_motion: pref!(layout.prefers_reduced_motion),
+        forced_colors: pref!(layout.forced_colors),
+    }
 }
 
 /// https://drafts.csswg.org/mediaqueries-4/#width
@@ -203,9 +289,60 @@ fn eval_scan(_: &Device, _: Option<Scan>) -> bool {
     false
 }
 
+#[derive(Clone, Copy, Debug, FromPrimitive, Parse, ToCss)]
+#[repr(u8)]
+enum PrefersColorScheme {
+    Light,
+    Dark,
+}
+
+/// https://drafts.csswg.org/mediaqueries-5/#prefers-color-scheme
+fn eval_prefers_color_scheme(device: &Device, query_value: Option<PrefersColorScheme>) -> bool {
+    let color_scheme = device.preferences().color_scheme;
+    match query_value {
+        Some(PrefersColorScheme::Light) => color_scheme == ColorSchemePreference::Light,
+        Some(PrefersColorScheme::Dark) => color_scheme == ColorSchemePreference::Dark,
+        // There is no `no-preference` value, so the feature always matches in the boolean
+        // context.
+        None => true,
+    }
+}
+
+#[derive(Clone, Copy, Debug, FromPrimitive, Parse, ToCss)]
+#[repr(u8)]
+enum PrefersReducedMotion {
+    NoPreference,
+    Reduce,
+}
+
+#[derive(Clone, Copy, Debug, FromPrimitive, Parse, ToCss)]
+#[repr(u8)]
+enum ForcedColors {
+    None,
+    Active,
+}
+
+/// https://drafts.csswg.org/mediaqueries-5/#forced-colors
+fn eval_forced_colors(device: &Device, query_value: Option<ForcedColors>) -> bool {
+    let forced_colors = device.preferences().forced_colors;
+    match query_value {
+        Some(ForcedColors::None) => !forced_colors,
+        Some(ForcedColors::Active) | None => forced_colors,
+    }
+}
+
+/// https://drafts.csswg.org/mediaqueries-5/#prefers-reduced-motion
+fn eval_prefers_reduced_motion(device: &Device, query_value: Option<PrefersReducedMotion>) -> bool {
+    let reduced_motion = device.preferences().reduced_motion;
+    match query_value {
+        Some(PrefersReducedMotion::NoPreference) => !reduced_motion,
+        Some(PrefersReducedMotion::Reduce) | None => reduced_motion,
+    }
+}
+
 lazy_static! {
     /// A list with all the media features that Servo supports.
-    pub static ref MEDIA_FEATURES: [MediaFeatureDescription; 2] = [
+    pub static ref MEDIA_FEATURES: [MediaFeatureDescription; 5] = [
         feature!(
             atom!("width"),
             AllowsRanges::Yes,
@@ -218,5 +355,23 @@ lazy_static! {
             keyword_evaluator!(eval_scan, Scan),
             ParsingRequirements::empty(),
         ),
+        feature!(
+            atom!("prefers-color-scheme"),
+            AllowsRanges::No,
+            keyword_evaluator!(eval_prefers_color_scheme, PrefersColorScheme),
+            ParsingRequirements::empty(),
+        ),
+        feature!(
+            atom!("prefers-reduced-motion"),
+            AllowsRanges::No,
+            keyword_evaluator!(eval_prefers_reduced_motion, PrefersReducedMotion),
+            ParsingRequirements::empty(),
+        ),
+        feature!(
+            atom!("forced-colors"),
+            AllowsRanges::No,
+            keyword_evaluator!(eval_forced_colors, ForcedColors),
+            ParsingRequirements::empty(),
+        ),
     ];
 }
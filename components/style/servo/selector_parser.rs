@@ -22,7 +22,6 @@ use selectors::attr::{AttrSelectorOperation, CaseSensitivity, NamespaceConstrain
 use selectors::parser::{SelectorParseErrorKind, Visit};
 use selectors::visitor::SelectorVisitor;
 use std::fmt;
-use std::mem;
 use std::ops::{Deref, DerefMut};
 use style_traits::{ParseError, StyleParseErrorKind};
 
@@ -31,12 +30,12 @@ use style_traits::{ParseError, StyleParseErrorKind};
 /// NB: If you add to this list, be sure to update `each_simple_pseudo_element` too.
 #[derive(Clone, Debug, Eq, Hash, MallocSizeOf, PartialEq, ToShmem)]
 #[allow(missing_docs)]
-#[repr(usize)]
 pub enum PseudoElement {
     // Eager pseudos. Keep these first so that eager_index() works.
     After = 0,
     Before,
     Selection,
+    Backdrop,
     // If/when :first-letter is added, update is_first_letter accordingly.
 
     // If/when :first-line is added, update is_first_line accordingly.
@@ -47,6 +46,8 @@ pub enum PseudoElement {
     // APPLIES_TO_PLACEHOLDER properties expect!
 
     // Non-eager pseudos.
+    /// The `::highlight()` of the custom highlight with the given name.
+    Highlight(Atom),
     DetailsSummary,
     DetailsContent,
     ServoText,
@@ -61,8 +62,8 @@ pub enum PseudoElement {
     ServoInlineAbsolute,
 }
 
-/// The count of all pseudo-elements.
-pub const PSEUDO_COUNT: usize = PseudoElement::ServoInlineAbsolute as usize + 1;
+/// The count of all pseudo-elements. Keep this in sync with `index()`.
+pub const PSEUDO_COUNT: usize = 17;
 
 impl ::selectors::parser::PseudoElement for PseudoElement {
     type Impl = SelectorImpl;
@@ -74,10 +75,17 @@ impl ToCss for PseudoElement {
         W: fmt::Write,
     {
         use self::PseudoElement::*;
+        if let Highlight(ref name) = *self {
+            dest.write_str("::highlight(")?;
+            serialize_identifier(name, dest)?;
+            return dest.write_str(")");
+        }
+
         dest.write_str(match *self {
             After => "::after",
             Before => "::before",
             Selection => "::selection",
+            Backdrop => "::backdrop",
             DetailsSummary => "::-servo-details-summary",
             DetailsContent => "::-servo-details-content",
             ServoText => "::-servo-text",
@@ -90,12 +98,13 @@ impl ToCss for PseudoElement {
             ServoAnonymousBlock => "::-servo-anonymous-block",
             ServoInlineBlockWrapper => "::-servo-inline-block-wrapper",
             ServoInlineAbsolute => "::-servo-inline-absolute",
+            Highlight(_) => unreachable!(),
         })
     }
 }
 
 /// The number of eager pseudo-elements. Keep this in sync with cascade_type.
-pub const EAGER_PSEUDO_COUNT: usize = 3;
+pub const EAGER_PSEUDO_COUNT: usize = 4;
 
 impl PseudoElement {
     /// Gets the canonical index of this eagerly-cascaded pseudo-element.
@@ -106,9 +115,31 @@ impl PseudoElement {
     }
 
     /// An index for this pseudo-element to be indexed in an enumerated array.
+    ///
+    /// All the `::highlight()` pseudo-elements share an index, rules for them are told apart
+    /// by the pseudo-element matching function of the lazy cascade.
     #[inline]
     pub fn index(&self) -> usize {
-        self.clone() as usize
+        use self::PseudoElement::*;
+        match *self {
+            After => 0,
+            Before => 1,
+            Selection => 2,
+            Backdrop => 3,
+            Highlight(_) => 4,
+            DetailsSummary => 5,
+            DetailsContent => 6,
+            ServoText => 7,
+            ServoInputText => 8,
+            ServoTableWrapper => 9,
+            ServoAnonymousTableWrapper => 10,
+            ServoAnonymousTable => 11,
+            ServoAnonymousTableRow => 12,
+            ServoAnonymousTableCell => 13,
+            ServoAnonymousBlock => 14,
+            ServoInlineBlockWrapper => 15,
+            ServoInlineAbsolute => 16,
+        }
     }
 
     /// An array of `None`, one per pseudo-element.
@@ -119,10 +150,13 @@ impl PseudoElement {
     /// Creates a pseudo-element from an eager index.
     #[inline]
     pub fn from_eager_index(i: usize) -> Self {
-        assert!(i < EAGER_PSEUDO_COUNT);
-        let result: PseudoElement = unsafe { mem::transmute(i) };
-        debug_assert!(result.is_eager());
-        result
+        match i {
+            0 => PseudoElement::After,
+            1 => PseudoElement::Before,
+            2 => PseudoElement::Selection,
+            3 => PseudoElement::Backdrop,
+            _ => panic!("Not an eager pseudo-element index: {}", i),
+        }
     }
 
     /// Whether the current pseudo element is ::before or ::after.
@@ -217,10 +251,13 @@ impl PseudoElement {
     #[inline]
     pub fn cascade_type(&self) -> PseudoElementCascadeType {
         match *self {
-            PseudoElement::After | PseudoElement::Before | PseudoElement::Selection => {
-                PseudoElementCascadeType::Eager
+            PseudoElement::After |
+            PseudoElement::Before |
+            PseudoElement::Selection |
+            PseudoElement::Backdrop => PseudoElementCascadeType::Eager,
+            PseudoElement::Highlight(_) | PseudoElement::DetailsSummary => {
+                PseudoElementCascadeType::Lazy
             },
-            PseudoElement::DetailsSummary => PseudoElementCascadeType::Lazy,
             PseudoElement::DetailsContent |
             PseudoElement::ServoText |
             PseudoElement::ServoInputText |
@@ -282,6 +319,7 @@ pub enum NonTSPseudoClass {
     Disabled,
     Enabled,
     Focus,
+    FocusVisible,
     Fullscreen,
     Hover,
     Indeterminate,
@@ -307,7 +345,10 @@ impl ::selectors::parser::NonTSPseudoClass for NonTSPseudoClass {
     fn is_user_action_state(&self) -> bool {
         matches!(
             *self,
-            NonTSPseudoClass::Active | NonTSPseudoClass::Hover | NonTSPseudoClass::Focus
+            NonTSPseudoClass::Active |
+                NonTSPseudoClass::Hover |
+                NonTSPseudoClass::Focus |
+                NonTSPseudoClass::FocusVisible
         )
     }
 
@@ -337,6 +378,7 @@ impl ToCss for NonTSPseudoClass {
             Disabled => ":disabled",
             Enabled => ":enabled",
             Focus => ":focus",
+            FocusVisible => ":focus-visible",
             Fullscreen => ":fullscreen",
             Hover => ":hover",
             Indeterminate => ":indeterminate",
@@ -371,6 +413,7 @@ impl NonTSPseudoClass {
         match *self {
             Active => ElementState::IN_ACTIVE_STATE,
             Focus => ElementState::IN_FOCUS_STATE,
+            FocusVisible => ElementState::IN_FOCUSRING_STATE,
             Fullscreen => ElementState::IN_FULLSCREEN_STATE,
             Hover => ElementState::IN_HOVER_STATE,
             Defined => ElementState::IN_DEFINED_STATE,
@@ -443,6 +486,7 @@ impl<'a, 'i> ::selectors::Parser<'i> for SelectorParser<'a> {
             "disabled" => Disabled,
             "enabled" => Enabled,
             "focus" => Focus,
+            "focus-visible" => FocusVisible,
             "fullscreen" => Fullscreen,
             "hover" => Hover,
             "indeterminate" => Indeterminate,
@@ -492,6 +536,7 @@ impl<'a, 'i> ::selectors::Parser<'i> for SelectorParser<'a> {
             "before" => Before,
             "after" => After,
             "selection" => Selection,
+            "backdrop" => Backdrop,
             "-servo-details-summary" => {
                 if !self.in_user_agent_stylesheet() {
                     return Err(location.new_custom_error(SelectorParseErrorKind::UnexpectedIdent(name.clone())))
@@ -571,6 +616,21 @@ impl<'a, 'i> ::selectors::Parser<'i> for SelectorParser<'a> {
         Ok(pseudo_element)
     }
 
+    fn parse_functional_pseudo_element<'t>(
+        &self,
+        name: CowRcStr<'i>,
+        parser: &mut CssParser<'i, 't>,
+    ) -> Result<PseudoElement, ParseError<'i>> {
+        let pseudo_element = match_ignore_ascii_case! { &name,
+            "highlight" => {
+                PseudoElement::Highlight(Atom::from(parser.expect_ident()?.as_ref()))
+            },
+            _ => return Err(parser.new_custom_error(SelectorParseErrorKind::UnexpectedIdent(name.clone()))),
+        };
+
+        Ok(pseudo_element)
+    }
+
     fn default_namespace(&self) -> Option<Namespace> {
         self.namespaces.default.as_ref().map(|ns| ns.clone())
     }
@@ -9,7 +9,8 @@ use crate::invalidation::stylesheets::StylesheetInvalidationSet;
 use crate::media_queries::Device;
 use crate::selector_parser::SnapshotMap;
 use crate::shared_lock::SharedRwLockReadGuard;
-use crate::stylesheets::{Origin, OriginSet, OriginSetIterator, PerOrigin, StylesheetInDocument};
+use crate::stylesheets::StylesheetInDocument;
+use crate::stylesheets::{CssRule, Origin, OriginSet, OriginSetIterator, PerOrigin};
 use std::{mem, slice};
 
 /// Entry for a StylesheetSet.
@@ -436,6 +437,27 @@ macro_rules! sheet_set_methods {
             let collection = self.collection_for(&sheet, guard);
             collection.remove(&sheet)
         }
+
+        /// Notes that a rule was inserted into or removed from a stylesheet of
+        /// the set.
+        ///
+        /// Unlike `force_dirty`, this only invalidates the elements the rule
+        /// may apply to.
+        pub fn rule_changed(
+            &mut self,
+            device: Option<&Device>,
+            sheet: &S,
+            rule: &CssRule,
+            guard: &SharedRwLockReadGuard,
+        ) {
+            debug!(concat!($set_name, "::rule_changed"));
+            if let Some(device) = device {
+                self.invalidations.rule_changed(device, sheet, rule, guard);
+            }
+
+            let collection = self.collection_for(sheet, guard);
+            collection.set_data_validity_at_least(DataValidity::FullyInvalid);
+        }
     };
 }
 
@@ -29,8 +29,8 @@ use crate::stylesheets::viewport_rule::{self, MaybeNew, ViewportRule};
 use crate::stylesheets::StyleRule;
 use crate::stylesheets::StylesheetInDocument;
 #[cfg(feature = "gecko")]
-use crate::stylesheets::{CounterStyleRule, FontFaceRule, FontFeatureValuesRule, PageRule};
-use crate::stylesheets::{CssRule, Origin, OriginSet, PerOrigin, PerOriginIter};
+use crate::stylesheets::{CounterStyleRule, FontFaceRule, FontFeatureValuesRule};
+use crate::stylesheets::{CssRule, Origin, OriginSet, PageRule, PerOrigin, PerOriginIter};
 use crate::thread_state::{self, ThreadState};
 use crate::{Atom, LocalName, Namespace, WeakAtom};
 use fallible::FallibleVec;
@@ -575,6 +575,18 @@ impl Stylist {
             .insert_stylesheet_before(Some(&self.device), sheet, before_sheet, guard)
     }
 
+    /// Notes that a rule was inserted into or removed from a given stylesheet,
+    /// so that only the elements it may apply to are restyled.
+    pub fn rule_changed(
+        &mut self,
+        sheet: &StylistSheet,
+        rule: &CssRule,
+        guard: &SharedRwLockReadGuard,
+    ) {
+        self.stylesheets
+            .rule_changed(Some(&self.device), sheet, rule, guard)
+    }
+
     /// Marks a given stylesheet origin as dirty, due to, for example, changes
     /// in the declarations that affect a given rule.
     ///
@@ -1404,7 +1416,7 @@ pub struct ExtraStyleData {
     pub counter_styles: PrecomputedHashMap<Atom, Arc<Locked<CounterStyleRule>>>,
 
     /// A map of effective page rules.
-    #[cfg(feature = "gecko")]
+    #[cfg_attr(feature = "servo", ignore_malloc_size_of = "Arc")]
     pub pages: Vec<Arc<Locked<PageRule>>>,
 }
 
@@ -1434,22 +1446,22 @@ impl ExtraStyleData {
         let name = rule.read_with(guard).name().0.clone();
         self.counter_styles.insert(name, rule.clone());
     }
+}
 
+impl ExtraStyleData {
     /// Add the given @page rule.
     fn add_page(&mut self, rule: &Arc<Locked<PageRule>>) {
         self.pages.push(rule.clone());
     }
-}
 
-impl ExtraStyleData {
     fn clear(&mut self) {
         #[cfg(feature = "gecko")]
         {
             self.font_faces.clear();
             self.font_feature_values.clear();
             self.counter_styles.clear();
-            self.pages.clear();
         }
+        self.pages.clear();
     }
 }
 
@@ -2128,7 +2140,6 @@ impl CascadeData {
                 CssRule::CounterStyle(ref rule) => {
                     self.extra_data.add_counter_style(guard, rule);
                 },
-                #[cfg(feature = "gecko")]
                 CssRule::Page(ref rule) => {
                     self.extra_data.add_page(rule);
                 },
@@ -70,7 +70,7 @@ pub enum GenericImage<Gradient, MozImageRect, ImageUrl> {
 
     /// A paint worklet image.
     /// <https://drafts.css-houdini.org/css-paint-api/>
-    #[cfg(feature = "servo-layout-2013")]
+    #[cfg(any(feature = "servo-layout-2013", feature = "servo-layout-2020"))]
     PaintWorklet(PaintWorklet),
 }
 
@@ -330,7 +330,7 @@ where
             Image::Url(ref url) => url.to_css(dest),
             Image::Gradient(ref gradient) => gradient.to_css(dest),
             Image::Rect(ref rect) => rect.to_css(dest),
-            #[cfg(feature = "servo-layout-2013")]
+            #[cfg(any(feature = "servo-layout-2013", feature = "servo-layout-2020"))]
             Image::PaintWorklet(ref paint_worklet) => paint_worklet.to_css(dest),
             #[cfg(feature = "gecko")]
             Image::Element(ref selector) => {
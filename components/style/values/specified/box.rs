@@ -763,6 +763,196 @@ impl Parse for AnimationName {
     }
 }
 
+/// The axis of a scroll container that drives a scroll or view progress timeline.
+///
+/// https://drafts.csswg.org/scroll-animations-1/#scroll-notation
+#[allow(missing_docs)]
+#[cfg_attr(feature = "servo", derive(Deserialize, Serialize))]
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Eq,
+    Hash,
+    MallocSizeOf,
+    Parse,
+    PartialEq,
+    SpecifiedValueInfo,
+    ToComputedValue,
+    ToCss,
+    ToResolvedValue,
+    ToShmem,
+)]
+#[repr(u8)]
+pub enum ScrollAxis {
+    Block,
+    Inline,
+    X,
+    Y,
+}
+
+/// The scroll container whose scroll position drives a `scroll()` timeline.
+///
+/// https://drafts.csswg.org/scroll-animations-1/#typedef-scroller
+#[allow(missing_docs)]
+#[cfg_attr(feature = "servo", derive(Deserialize, Serialize))]
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Eq,
+    Hash,
+    MallocSizeOf,
+    Parse,
+    PartialEq,
+    SpecifiedValueInfo,
+    ToComputedValue,
+    ToCss,
+    ToResolvedValue,
+    ToShmem,
+)]
+#[repr(u8)]
+pub enum Scroller {
+    /// The nearest scroll container around the element.
+    Nearest,
+    /// The scroll container of the document, that is, the viewport.
+    Root,
+    /// The element itself.
+    #[css(keyword = "self")]
+    SelfElement,
+}
+
+/// A value for the `animation-timeline` property.
+///
+/// https://drafts.csswg.org/css-animations-2/#animation-timeline
+#[cfg_attr(feature = "servo", derive(Deserialize, Serialize))]
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Eq,
+    Hash,
+    MallocSizeOf,
+    PartialEq,
+    SpecifiedValueInfo,
+    ToComputedValue,
+    ToResolvedValue,
+    ToShmem,
+)]
+#[value_info(other_values = "auto,none,scroll,view")]
+pub enum AnimationTimeline {
+    /// The animation is driven by the time of the document.
+    Auto,
+    /// The animation isn't driven by any timeline, so it has no effect.
+    None,
+    /// The animation is driven by the scroll position of a scroll container.
+    Scroll(ScrollAxis, Scroller),
+    /// The animation is driven by how far the element is through the scrollport of its
+    /// nearest scroll container.
+    View(ScrollAxis),
+}
+
+impl AnimationTimeline {
+    /// Returns the `auto` value.
+    #[inline]
+    pub fn auto() -> Self {
+        AnimationTimeline::Auto
+    }
+
+    /// Whether this timeline is driven by scrolling rather than by time.
+    #[inline]
+    pub fn is_scroll_driven(&self) -> bool {
+        match *self {
+            AnimationTimeline::Scroll(..) | AnimationTimeline::View(..) => true,
+            AnimationTimeline::Auto | AnimationTimeline::None => false,
+        }
+    }
+}
+
+impl Parse for AnimationTimeline {
+    fn parse<'i, 't>(
+        context: &ParserContext,
+        input: &mut Parser<'i, 't>,
+    ) -> Result<Self, ParseError<'i>> {
+        if input.try(|input| input.expect_ident_matching("auto")).is_ok() {
+            return Ok(AnimationTimeline::Auto);
+        }
+        if input.try(|input| input.expect_ident_matching("none")).is_ok() {
+            return Ok(AnimationTimeline::None);
+        }
+
+        let location = input.current_source_location();
+        let function = input.expect_function()?.clone();
+        input.parse_nested_block(|input| {
+            match_ignore_ascii_case! { &function,
+                "scroll" => {
+                    let mut axis = None;
+                    let mut scroller = None;
+                    loop {
+                        if axis.is_none() {
+                            axis = input.try(|input| ScrollAxis::parse(context, input)).ok();
+                            if axis.is_some() {
+                                continue;
+                            }
+                        }
+                        if scroller.is_none() {
+                            scroller = input.try(|input| Scroller::parse(context, input)).ok();
+                            if scroller.is_some() {
+                                continue;
+                            }
+                        }
+                        break;
+                    }
+                    Ok(AnimationTimeline::Scroll(
+                        axis.unwrap_or(ScrollAxis::Block),
+                        scroller.unwrap_or(Scroller::Nearest),
+                    ))
+                },
+                "view" => {
+                    let axis = input.try(|input| ScrollAxis::parse(context, input)).ok();
+                    Ok(AnimationTimeline::View(axis.unwrap_or(ScrollAxis::Block)))
+                },
+                _ => Err(location.new_custom_error(
+                    StyleParseErrorKind::UnexpectedFunction(function.clone())
+                )),
+            }
+        })
+    }
+}
+
+impl ToCss for AnimationTimeline {
+    fn to_css<W>(&self, dest: &mut CssWriter<W>) -> fmt::Result
+    where
+        W: Write,
+    {
+        match *self {
+            AnimationTimeline::Auto => dest.write_str("auto"),
+            AnimationTimeline::None => dest.write_str("none"),
+            AnimationTimeline::Scroll(axis, scroller) => {
+                // Serialize the shortest form, omitting the default scroller and axis.
+                dest.write_str("scroll(")?;
+                if scroller != Scroller::Nearest {
+                    scroller.to_css(dest)?;
+                    if axis != ScrollAxis::Block {
+                        dest.write_str(" ")?;
+                    }
+                }
+                if axis != ScrollAxis::Block {
+                    axis.to_css(dest)?;
+                }
+                dest.write_str(")")
+            },
+            AnimationTimeline::View(axis) => {
+                dest.write_str("view(")?;
+                if axis != ScrollAxis::Block {
+                    axis.to_css(dest)?;
+                }
+                dest.write_str(")")
+            },
+        }
+    }
+}
+
 /// https://drafts.csswg.org/css-scroll-snap-1/#snap-axis
 #[allow(missing_docs)]
 #[cfg_attr(feature = "servo", derive(Deserialize, Serialize))]
@@ -831,8 +1021,10 @@ pub enum ScrollSnapStrictness {
 )]
 #[repr(C)]
 pub struct ScrollSnapType {
-    axis: ScrollSnapAxis,
-    strictness: ScrollSnapStrictness,
+    /// The axes that scrolls snap along.
+    pub axis: ScrollSnapAxis,
+    /// Whether scrolls snap, and how strictly.
+    pub strictness: ScrollSnapStrictness,
 }
 
 impl ScrollSnapType {
@@ -926,8 +1118,10 @@ pub enum ScrollSnapAlignKeyword {
 )]
 #[repr(C)]
 pub struct ScrollSnapAlign {
-    block: ScrollSnapAlignKeyword,
-    inline: ScrollSnapAlignKeyword,
+    /// The alignment of the snap area along the block axis.
+    pub block: ScrollSnapAlignKeyword,
+    /// The alignment of the snap area along the inline axis.
+    pub inline: ScrollSnapAlignKeyword,
 }
 
 impl ScrollSnapAlign {
@@ -11,6 +11,7 @@ use crate::parser::{Parse, ParserContext};
 use crate::values::computed::{Color as ComputedColor, Context, ToComputedValue};
 use crate::values::generics::color::{Color as GenericColor, ColorOrAuto as GenericColorOrAuto};
 use crate::values::specified::calc::CalcNode;
+use crate::values::CustomIdent;
 use cssparser::{AngleOrNumber, Color as CSSParserColor, Parser, Token, RGBA};
 use cssparser::{BasicParseErrorKind, NumberOrPercentage, ParseErrorKind};
 use itoa;
@@ -34,7 +35,6 @@ pub enum Color {
     /// A complex color value from computed value
     Complex(ComputedColor),
     /// A system color
-    #[cfg(feature = "gecko")]
     System(SystemColor),
     /// Quirksmode-only rule for inheriting color from the body
     #[cfg(feature = "gecko")]
@@ -244,6 +244,40 @@ pub enum SystemColor {
     End, // Just for array-indexing purposes.
 }
 
+/// System colors.
+///
+/// <https://drafts.csswg.org/css-color-4/#css-system-colors>
+#[allow(missing_docs)]
+#[cfg(feature = "servo")]
+#[derive(Clone, Copy, Debug, MallocSizeOf, Parse, PartialEq, ToCss, ToShmem)]
+#[repr(u8)]
+pub enum SystemColor {
+    Canvas,
+    Canvastext,
+    Linktext,
+    Visitedtext,
+    Activetext,
+    Buttonface,
+    Buttontext,
+    Buttonborder,
+    Field,
+    Fieldtext,
+    Highlight,
+    Highlighttext,
+    Graytext,
+    Mark,
+    Marktext,
+}
+
+#[cfg(feature = "servo")]
+impl SystemColor {
+    #[inline]
+    fn compute(&self, cx: &Context) -> ComputedColor {
+        let color_scheme = cx.builder.get_inherited_ui().clone_color_scheme();
+        ComputedColor::rgba(cx.device().system_color(*self, &color_scheme))
+    }
+}
+
 #[cfg(feature = "gecko")]
 impl SystemColor {
     #[inline]
@@ -360,11 +394,8 @@ impl Parse for Color {
                 },
             }),
             Err(e) => {
-                #[cfg(feature = "gecko")]
-                {
-                    if let Ok(system) = input.try(|i| SystemColor::parse(context, i)) {
-                        return Ok(Color::System(system));
-                    }
+                if let Ok(system) = input.try(|i| SystemColor::parse(context, i)) {
+                    return Ok(Color::System(system));
                 }
 
                 match e.kind {
@@ -395,7 +426,6 @@ impl ToCss for Color {
                 parsed: ref rgba, ..
             } => rgba.to_css(dest),
             Color::Complex(_) => Ok(()),
-            #[cfg(feature = "gecko")]
             Color::System(system) => system.to_css(dest),
             #[cfg(feature = "gecko")]
             Color::InheritFromBodyQuirk => Ok(()),
@@ -527,6 +557,12 @@ impl Color {
             _ => false,
         }
     }
+
+    /// Returns true if the color is a system color, which is kept when the colors of the
+    /// document are forced.
+    pub fn is_system(&self) -> bool {
+        matches!(*self, Color::System(..))
+    }
 }
 
 #[cfg(feature = "gecko")]
@@ -545,7 +581,6 @@ impl Color {
             Color::CurrentColor => ComputedColor::currentcolor(),
             Color::Numeric { ref parsed, .. } => ComputedColor::rgba(*parsed),
             Color::Complex(ref complex) => *complex,
-            #[cfg(feature = "gecko")]
             Color::System(system) => system.compute(_context?),
             #[cfg(feature = "gecko")]
             Color::InheritFromBodyQuirk => {
@@ -649,3 +684,125 @@ impl Parse for ColorPropertyValue {
 
 /// auto | <color>
 pub type ColorOrAuto = GenericColorOrAuto<Color>;
+
+bitflags! {
+    /// The color schemes an element supports.
+    #[derive(Default, MallocSizeOf, SpecifiedValueInfo, ToComputedValue, ToResolvedValue, ToShmem)]
+    #[repr(C)]
+    pub struct ColorSchemeFlags: u8 {
+        /// Whether the element supports the light color scheme.
+        const LIGHT = 1 << 0;
+        /// Whether the element supports the dark color scheme.
+        const DARK = 1 << 1;
+        /// Whether the user agent may not force another color scheme on the element.
+        const ONLY = 1 << 2;
+    }
+}
+
+/// Specified value of the `color-scheme` property.
+///
+/// `normal` is represented by an empty `idents` list.
+///
+/// <https://drafts.csswg.org/css-color-adjust/#color-scheme-prop>
+#[derive(
+    Clone,
+    Debug,
+    Default,
+    MallocSizeOf,
+    PartialEq,
+    SpecifiedValueInfo,
+    ToComputedValue,
+    ToResolvedValue,
+    ToShmem,
+)]
+#[value_info(other_values = "normal,light,dark,only")]
+pub struct ColorScheme {
+    /// The color schemes, as they were specified, so that the unknown ones are preserved.
+    idents: crate::OwnedSlice<CustomIdent>,
+    /// The color schemes among the above that the user agent knows about.
+    bits: ColorSchemeFlags,
+}
+
+impl ColorScheme {
+    /// Returns the initial value of `color-scheme`.
+    #[inline]
+    pub fn normal() -> Self {
+        Self::default()
+    }
+
+    /// Returns the color schemes supported by the element.
+    #[inline]
+    pub fn bits(&self) -> ColorSchemeFlags {
+        self.bits
+    }
+}
+
+impl Parse for ColorScheme {
+    /// normal | [ light | dark | <custom-ident> ]+ && only?
+    fn parse<'i, 't>(
+        _: &ParserContext,
+        input: &mut Parser<'i, 't>,
+    ) -> Result<Self, ParseError<'i>> {
+        let mut idents = vec![];
+        let mut bits = ColorSchemeFlags::empty();
+        let mut location = input.current_source_location();
+        while let Ok(ident) = input.try(|i| i.expect_ident_cloned()) {
+            let mut is_only = false;
+            match_ignore_ascii_case! { &ident,
+                "normal" => {
+                    if idents.is_empty() && bits.is_empty() {
+                        return Ok(Self::normal());
+                    }
+                    return Err(location.new_custom_error(StyleParseErrorKind::UnspecifiedError));
+                },
+                "light" => bits.insert(ColorSchemeFlags::LIGHT),
+                "dark" => bits.insert(ColorSchemeFlags::DARK),
+                "only" => is_only = true,
+                _ => {},
+            };
+            if is_only {
+                if bits.contains(ColorSchemeFlags::ONLY) {
+                    return Err(location.new_custom_error(StyleParseErrorKind::UnspecifiedError));
+                }
+                bits.insert(ColorSchemeFlags::ONLY);
+                // `only` is allowed either at the beginning or at the end of the list.
+                if !idents.is_empty() {
+                    break;
+                }
+            } else {
+                idents.push(CustomIdent::from_ident(location, &ident, &[])?);
+            }
+            location = input.current_source_location();
+        }
+        if idents.is_empty() {
+            return Err(input.new_custom_error(StyleParseErrorKind::UnspecifiedError));
+        }
+        Ok(Self {
+            idents: idents.into(),
+            bits,
+        })
+    }
+}
+
+impl ToCss for ColorScheme {
+    fn to_css<W>(&self, dest: &mut CssWriter<W>) -> fmt::Result
+    where
+        W: Write,
+    {
+        if self.idents.is_empty() {
+            return dest.write_str("normal");
+        }
+        let mut first = true;
+        for ident in self.idents.iter() {
+            if !first {
+                dest.write_char(' ')?;
+            }
+            first = false;
+            ident.to_css(dest)?;
+        }
+        if self.bits.contains(ColorSchemeFlags::ONLY) {
+            dest.write_str(" only")?;
+        }
+        Ok(())
+    }
+}
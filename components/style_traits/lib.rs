@@ -67,6 +67,35 @@ impl PinchZoomFactor {
     }
 }
 
+/// The color scheme that the user prefers, from the settings of the system or of the embedder.
+///
+/// <https://drafts.csswg.org/mediaqueries-5/#prefers-color-scheme>
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "servo", derive(Deserialize, Serialize, MallocSizeOf))]
+pub enum ColorSchemePreference {
+    /// The user prefers light backgrounds with dark text.
+    Light,
+    /// The user prefers dark backgrounds with light text.
+    Dark,
+}
+
+/// The settings of the user that pages can query with the user preference media features.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "servo", derive(Deserialize, Serialize, MallocSizeOf))]
+pub struct MediaPreferences {
+    /// The color scheme that the user prefers.
+    pub color_scheme: ColorSchemePreference,
+    /// Whether the user asked for the motion of the pages to be minimized.
+    ///
+    /// <https://drafts.csswg.org/mediaqueries-5/#prefers-reduced-motion>
+    pub reduced_motion: bool,
+    /// Whether a high contrast mode of the system forces the colors of the pages to the
+    /// system colors.
+    ///
+    /// <https://drafts.csswg.org/mediaqueries-5/#forced-colors>
+    pub forced_colors: bool,
+}
+
 /// One CSS "px" in the coordinate system of the "initial viewport":
 /// <http://www.w3.org/TR/css-device-adapt/#initial-viewport>
 ///
@@ -105,7 +105,13 @@ impl App {
             glutin::Event::Awakened => {
                 self.event_queue.borrow_mut().push(WindowEvent::Idle);
             },
-            glutin::Event::DeviceEvent { .. } => {},
+            glutin::Event::DeviceEvent { event, .. } => {
+                WINDOWS.with(|windows| {
+                    for window in windows.borrow().values() {
+                        window.winit_device_event_to_servo_event(event.clone());
+                    }
+                });
+            },
 
             // Window level events
             glutin::Event::WindowEvent {
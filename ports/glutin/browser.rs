@@ -8,8 +8,11 @@ use euclid::{Point2D, Vector2D};
 use keyboard_types::{Key, KeyboardEvent, Modifiers, ShortcutMatcher};
 use servo::compositing::windowing::{WebRenderDebugOption, WindowEvent};
 use servo::embedder_traits::{
-    EmbedderMsg, FilterPattern, PermissionRequest, PromptDefinition, PromptOrigin, PromptResult,
-    PermissionPrompt,
+    EmbedderMsg, FilterPattern, FindOptions, PermissionRequest, PromptDefinition, PromptOrigin,
+    PromptResult, PermissionPrompt, ReaderArticle, AccessibilityNode, ShareResult,
+    CertificateError, ClientCertificateRequest, ClientIdentityChoice, WebResourceResponseMsg,
+    DownloadEvent, PictureInPictureEvent, MediaSessionActionType, MediaSessionEvent,
+    MediaSessionPlaybackState, ProtocolHandlerRequest,
 };
 use servo::msg::constellation_msg::TopLevelBrowsingContextId as BrowserId;
 use servo::msg::constellation_msg::TraversalDirection;
@@ -24,6 +27,7 @@ use std::env;
 use std::fs::File;
 use std::io::Write;
 use std::mem;
+use std::path::PathBuf;
 use std::rc::Rc;
 use std::thread;
 use std::time::Duration;
@@ -49,6 +53,11 @@ pub struct Browser<Window: WindowPortsMethods + ?Sized> {
     event_queue: Vec<WindowEvent>,
     clipboard_ctx: Option<ClipboardContext>,
     shutdown_requested: bool,
+    /// The text last searched for with the find-in-page shortcut.
+    find_query: Option<String>,
+    /// Whether the media session of the page is playing, which tells what the play/pause
+    /// media key does.
+    media_playing: bool,
 }
 
 enum LoadingState {
@@ -80,6 +89,8 @@ where
             },
             event_queue: Vec::new(),
             shutdown_requested: false,
+            find_query: None,
+            media_playing: false,
         }
     }
 
@@ -107,6 +118,17 @@ where
     /// Handle key events before sending them to Servo.
     fn handle_key_from_window(&mut self, key_event: KeyboardEvent) {
         ShortcutMatcher::from_event(key_event.clone())
+            .shortcut(CMD_OR_CONTROL | Modifiers::SHIFT, 'R', || {
+                if let Some(id) = self.browser_id {
+                    self.event_queue.push(WindowEvent::ExtractArticle(id));
+                }
+            })
+            .shortcut(CMD_OR_CONTROL | Modifiers::SHIFT, 'A', || {
+                if let Some(id) = self.browser_id {
+                    self.event_queue
+                        .push(WindowEvent::RequestAccessibilityTree(id));
+                }
+            })
             .shortcut(CMD_OR_CONTROL, 'R', || {
                 if let Some(id) = self.browser_id {
                     self.event_queue.push(WindowEvent::Reload(id));
@@ -131,6 +153,28 @@ where
             .shortcut(CMD_OR_CONTROL, 'Q', || {
                 self.event_queue.push(WindowEvent::Quit);
             })
+            .shortcut(CMD_OR_CONTROL, 'F', || {
+                let query = self.find_query.clone().unwrap_or_default();
+                let title = "Find in page";
+                let input = tinyfiledialogs::input_box(title, title, &tiny_dialog_escape(&query));
+                if let (Some(input), Some(id)) = (input, self.browser_id) {
+                    if input.is_empty() {
+                        self.event_queue.push(WindowEvent::StopFinding(id));
+                        self.find_query = None;
+                    } else {
+                        let options = FindOptions::default();
+                        self.event_queue
+                            .push(WindowEvent::Find(id, input.clone(), options));
+                        self.find_query = Some(input);
+                    }
+                }
+            })
+            .shortcut(CMD_OR_CONTROL | Modifiers::SHIFT, 'G', || {
+                self.find_again(true);
+            })
+            .shortcut(CMD_OR_CONTROL, 'G', || {
+                self.find_again(false);
+            })
             .shortcut(CMD_OR_CONTROL, 'P', || {
                 let rate = env::var("SAMPLING_RATE")
                     .ok()
@@ -177,7 +221,11 @@ where
             })
             .shortcut(Modifiers::empty(), Key::Escape, || {
                 let state = self.window.get_fullscreen();
-                if state {
+                if self.window.get_pointer_lock() {
+                    if let Some(id) = self.browser_id {
+                        self.event_queue.push(WindowEvent::ExitPointerLock(id));
+                    }
+                } else if state {
                     if let Some(id) = self.browser_id {
                         let event = WindowEvent::ExitFullScreen(id);
                         self.event_queue.push(event);
@@ -186,9 +234,49 @@ where
                     self.event_queue.push(WindowEvent::Quit);
                 }
             })
+            .shortcut(Modifiers::empty(), Key::MediaPlayPause, || {
+                let action = if self.media_playing {
+                    MediaSessionActionType::Pause
+                } else {
+                    MediaSessionActionType::Play
+                };
+                self.media_session_action(action);
+            })
+            .shortcut(Modifiers::empty(), Key::MediaPlay, || {
+                self.media_session_action(MediaSessionActionType::Play);
+            })
+            .shortcut(Modifiers::empty(), Key::MediaPause, || {
+                self.media_session_action(MediaSessionActionType::Pause);
+            })
+            .shortcut(Modifiers::empty(), Key::MediaStop, || {
+                self.media_session_action(MediaSessionActionType::Stop);
+            })
+            .shortcut(Modifiers::empty(), Key::MediaTrackNext, || {
+                self.media_session_action(MediaSessionActionType::NextTrack);
+            })
+            .shortcut(Modifiers::empty(), Key::MediaTrackPrevious, || {
+                self.media_session_action(MediaSessionActionType::PreviousTrack);
+            })
+            .shortcut(Modifiers::empty(), Key::MediaFastForward, || {
+                self.media_session_action(MediaSessionActionType::SeekForward);
+            })
+            .shortcut(Modifiers::empty(), Key::MediaRewind, || {
+                self.media_session_action(MediaSessionActionType::SeekBackward);
+            })
             .otherwise(|| self.platform_handle_key(key_event));
     }
 
+    /// Move to the next or previous match of the last find-in-page search.
+    fn find_again(&mut self, backwards: bool) {
+        if let (Some(query), Some(id)) = (self.find_query.clone(), self.browser_id) {
+            let options = FindOptions {
+                backwards,
+                ..FindOptions::default()
+            };
+            self.event_queue.push(WindowEvent::Find(id, query, options));
+        }
+    }
+
     #[cfg(not(target_os = "win"))]
     fn platform_handle_key(&mut self, key_event: KeyboardEvent) {
         if let Some(id) = self.browser_id {
@@ -270,6 +358,12 @@ where
             });
     }
 
+    /// Forwards a media key to the media session of the page, rather than to the page itself.
+    fn media_session_action(&mut self, action: MediaSessionActionType) {
+        self.event_queue
+            .push(WindowEvent::MediaSessionAction(action.into()));
+    }
+
     fn scroll_window_from_key(&mut self, scroll_location: ScrollLocation, phase: TouchEventType) {
         let event = WindowEvent::Scroll(scroll_location, Point2D::zero(), phase);
         self.event_queue.push(event);
@@ -296,11 +390,16 @@ where
                     let title = format!("{} - Servo", title);
                     self.window.set_title(&title);
                 },
+                // Only the browser which is shown can move or resize the window.
                 EmbedderMsg::MoveTo(point) => {
-                    self.window.set_position(point);
+                    if browser_id.is_some() && browser_id == self.browser_id {
+                        self.window.set_position(point);
+                    }
                 },
                 EmbedderMsg::ResizeTo(size) => {
-                    self.window.set_inner_size(size);
+                    if browser_id.is_some() && browser_id == self.browser_id {
+                        self.window.set_inner_size(size);
+                    }
                 },
                 EmbedderMsg::Prompt(definition, origin) => {
                     let res = if opts::get().headless {
@@ -452,6 +551,13 @@ where
                 EmbedderMsg::SetFullscreenState(state) => {
                     self.window.set_fullscreen(state);
                 },
+                EmbedderMsg::SetPointerLock(locked) => {
+                    self.window.set_pointer_lock(locked);
+                },
+                EmbedderMsg::SetScreenWakeLock(locked) => {
+                    // winit has no way to inhibit the screensaver.
+                    debug!("SetScreenWakeLock received: {}", locked);
+                },
                 EmbedderMsg::LoadStart => {
                     self.loading_state = Some(LoadingState::Connecting);
                 },
@@ -504,6 +610,13 @@ where
                 EmbedderMsg::HideIME => {
                     debug!("HideIME received");
                 },
+                EmbedderMsg::IMECaretRect(rect) => {
+                    self.window.set_ime_position(rect.bottom_left());
+                },
+                EmbedderMsg::FindInPageResult(active, count) => match active {
+                    Some(active) => info!("Find in page: match {} of {}", active + 1, count),
+                    None => info!("Find in page: no matches"),
+                },
                 EmbedderMsg::ReportProfile(bytes) => {
                     let filename = env::var("PROFILE_OUTPUT").unwrap_or("samples.json".to_string());
                     let result = File::create(&filename).and_then(|mut f| f.write_all(&bytes));
@@ -511,9 +624,68 @@ where
                         error!("Failed to store profile: {}", e);
                     }
                 },
-                EmbedderMsg::MediaSessionEvent(_) => {
-                    debug!("MediaSessionEvent received");
-                    // TODO(ferjm): MediaSession support for Glutin based browsers.
+                EmbedderMsg::PrintDocument(bytes) => {
+                    let filename = env::var("PRINT_OUTPUT").unwrap_or("print.pdf".to_string());
+                    let result = File::create(&filename).and_then(|mut f| f.write_all(&bytes));
+                    match result {
+                        Ok(()) => info!("Printed document to {}", filename),
+                        Err(e) => error!("Failed to store printed document: {}", e),
+                    }
+                },
+                EmbedderMsg::AccessibilityTree(tree) => {
+                    // There is no bridge to the accessibility API of the platform yet, so the
+                    // tree is logged for debugging.
+                    log_accessibility_tree(&tree, 0);
+                },
+                EmbedderMsg::FormFields(fields) => {
+                    // There are no saved addresses or cards to fill in in this port yet.
+                    debug!("FormFields received with {} fields", fields.len());
+                },
+                EmbedderMsg::PasswordFieldFound(origin) => {
+                    // There are no saved credentials in this port yet.
+                    debug!("PasswordFieldFound received for {}", origin);
+                },
+                EmbedderMsg::LoginSubmitted(submission) => {
+                    debug!("LoginSubmitted received for {}", submission.origin);
+                },
+                EmbedderMsg::OverscrollBehaviorChanged(..) => {
+                    // Desktop windows have no overscroll effects of their own.
+                },
+                EmbedderMsg::ReaderModeArticle(article) => {
+                    let article = match article {
+                        Some(article) => article,
+                        None => {
+                            info!("No article to show in reader view");
+                            continue;
+                        },
+                    };
+                    // Show the article as a local page, which can be left with the back button.
+                    let path = env::temp_dir().join("servo-reader-view.html");
+                    let result = File::create(&path)
+                        .and_then(|mut f| f.write_all(reader_view_html(&article).as_bytes()));
+                    if let Err(e) = result {
+                        error!("Failed to store reader view: {}", e);
+                        continue;
+                    }
+                    if let (Ok(url), Some(id)) = (ServoUrl::from_file_path(&path), self.browser_id)
+                    {
+                        self.event_queue.push(WindowEvent::LoadUrl(id, url));
+                    }
+                },
+                EmbedderMsg::MediaSessionEvent(event) => match event {
+                    MediaSessionEvent::PlaybackStateChange(state) => {
+                        self.media_playing = match state {
+                            MediaSessionPlaybackState::Playing => true,
+                            MediaSessionPlaybackState::Paused |
+                            MediaSessionPlaybackState::None_ => false,
+                        };
+                    },
+                    MediaSessionEvent::SetMetadata(metadata) => {
+                        debug!("Now playing {} by {}", metadata.title, metadata.artist);
+                    },
+                    // The media keys are the only controls, and they don't change.
+                    MediaSessionEvent::SetPositionState(_) |
+                    MediaSessionEvent::SetActionHandlers(_) => {},
                 },
                 EmbedderMsg::OnDevtoolsStarted(port) => {
                     match port {
@@ -521,11 +693,180 @@ where
                         Err(()) => error!("Error running devtools server"),
                     }
                 },
+                EmbedderMsg::Share(_data, sender) => {
+                    // There is no bridge to the share sheets of the desktop platforms yet.
+                    debug!("Share received");
+                    let _ = sender.send(ShareResult::Failed);
+                },
+                EmbedderMsg::EnterPictureInPicture(request) => {
+                    // There are no floating windows for videos in this port yet.
+                    debug!("EnterPictureInPicture received");
+                    let _ = request.events.send(PictureInPictureEvent::Closed);
+                },
+                EmbedderMsg::ShowContextMenu(menu, sender) => {
+                    // There are no native menus in this port yet.
+                    debug!("ShowContextMenu received for {:?}", menu);
+                    let _ = sender.send(None);
+                },
+                EmbedderMsg::CertificateError(error) => {
+                    let id = error.id;
+                    let trusted = !opts::get().headless && trust_certificate(error);
+                    self.event_queue
+                        .push(WindowEvent::CertificateErrorResponse(id, trusted));
+                },
+                EmbedderMsg::SelectClientCertificate(request, sender) => {
+                    let choice = if opts::get().headless {
+                        None
+                    } else {
+                        select_client_identity(request)
+                    };
+                    if let Err(e) = sender.send(choice) {
+                        let reason =
+                            format!("Failed to send SelectClientCertificate response: {}", e);
+                        self.event_queue.push(WindowEvent::SendError(None, reason));
+                    };
+                },
+                EmbedderMsg::WebResourceRequested(_request, sender) => {
+                    if let Err(e) = sender.send(WebResourceResponseMsg::Continue) {
+                        let reason =
+                            format!("Failed to send WebResourceRequested response: {}", e);
+                        self.event_queue.push(WindowEvent::SendError(None, reason));
+                    };
+                },
+                EmbedderMsg::RegisterProtocolHandler(request, sender) => {
+                    let allowed = !opts::get().headless && allow_protocol_handler(request);
+                    if let Err(e) = sender.send(allowed) {
+                        let reason =
+                            format!("Failed to send RegisterProtocolHandler response: {}", e);
+                        self.event_queue.push(WindowEvent::SendError(None, reason));
+                    };
+                },
+                EmbedderMsg::AllowDownload(_id, url, suggested_path, sender) => {
+                    let path = if opts::get().headless {
+                        None
+                    } else {
+                        choose_download_path(&url, suggested_path)
+                    };
+                    if let Err(e) = sender.send(path) {
+                        let reason = format!("Failed to send AllowDownload response: {}", e);
+                        self.event_queue.push(WindowEvent::SendError(None, reason));
+                    };
+                },
+                EmbedderMsg::Download(id, event) => match event {
+                    DownloadEvent::Started { url, path, .. } => {
+                        info!("Downloading {} to {}", url, path.display())
+                    },
+                    DownloadEvent::Finished => info!("Download {:?} finished", id),
+                    DownloadEvent::Failed(reason) => warn!("Download {:?} failed: {}", id, reason),
+                    _ => {},
+                },
             }
         }
     }
 }
 
+/// Asks the user whether to trust a certificate that failed to verify.
+fn trust_certificate(error: CertificateError) -> bool {
+    let message = format!(
+        "The certificate of {} could not be verified: {}\n\n\
+         Subject: {}\nIssuer: {}\nValid from {} until {}\nSHA-256 fingerprint: {}\n\n\
+         Do you want to trust this certificate and continue?",
+        error.host,
+        error.reason,
+        error.subject,
+        error.issuer,
+        error.not_before,
+        error.not_after,
+        error.fingerprint,
+    );
+    // Most of the details are chosen by the server.
+    let message = tiny_dialog_escape(&message);
+    thread::Builder::new()
+        .name("display certificate error dialog".to_owned())
+        .spawn(move || {
+            let result = tinyfiledialogs::message_box_yes_no(
+                "Certificate error", &message, MessageBoxIcon::Warning, YesNo::No,
+            );
+            match result {
+                YesNo::Yes => true,
+                YesNo::No => false,
+            }
+        })
+        .unwrap()
+        .join()
+        .expect("Thread spawning failed")
+}
+
+/// Asks the user whether a site may handle the links of a scheme.
+fn allow_protocol_handler(request: ProtocolHandlerRequest) -> bool {
+    let host = request.url.host_str().unwrap_or("").to_owned();
+    let message = match request.current_url {
+        Some(current_url) => format!(
+            "Do you want {} to open the {} links instead of {}?",
+            host,
+            request.scheme,
+            current_url.host_str().unwrap_or(""),
+        ),
+        None => format!("Do you want {} to open the {} links?", host, request.scheme),
+    };
+    let message = tiny_dialog_escape(&message);
+    thread::Builder::new()
+        .name("display protocol handler dialog".to_owned())
+        .spawn(move || {
+            let result = tinyfiledialogs::message_box_yes_no(
+                "Protocol handler", &message, MessageBoxIcon::Question, YesNo::No,
+            );
+            match result {
+                YesNo::Yes => true,
+                YesNo::No => false,
+            }
+        })
+        .unwrap()
+        .join()
+        .expect("Thread spawning failed")
+}
+
+/// Asks the user where to save a download, if they want it at all.
+fn choose_download_path(url: &ServoUrl, suggested_path: PathBuf) -> Option<PathBuf> {
+    let title = tiny_dialog_escape(&format!("Save {}", url));
+    let suggested_path = suggested_path.to_string_lossy().into_owned();
+    thread::Builder::new()
+        .name("display save file dialog".to_owned())
+        .spawn(move || tinyfiledialogs::save_file_dialog(&title, &suggested_path))
+        .unwrap()
+        .join()
+        .expect("Thread spawning failed")
+        .map(PathBuf::from)
+}
+
+/// Asks the user which identity of the configured store to authenticate with, offering them
+/// one at a time. There is no access to the keychain of the platform yet.
+fn select_client_identity(request: ClientCertificateRequest) -> Option<ClientIdentityChoice> {
+    for (index, identity) in request.identities.iter().enumerate() {
+        let message = format!(
+            "{} asks for a certificate to identify you.\n\n\
+             Certificate: {}\nSubject: {}\nIssuer: {}\nValid until {}\n\n\
+             Do you want to identify yourself with this certificate?",
+            request.host, identity.name, identity.subject, identity.issuer, identity.not_after,
+        );
+        let message = tiny_dialog_escape(&message);
+        let chosen = thread::Builder::new()
+            .name("display client certificate dialog".to_owned())
+            .spawn(move || {
+                tinyfiledialogs::message_box_yes_no(
+                    "Client certificate", &message, MessageBoxIcon::Question, YesNo::No,
+                )
+            })
+            .unwrap()
+            .join()
+            .expect("Thread spawning failed");
+        if let YesNo::Yes = chosen {
+            return Some(ClientIdentityChoice::Stored(index));
+        }
+    }
+    None
+}
+
 #[cfg(target_os = "linux")]
 fn prompt_user(prompt: PermissionPrompt) -> PermissionRequest {
     if opts::get().headless {
@@ -645,6 +986,50 @@ fn sanitize_url(request: &str) -> Option<ServoUrl> {
         })
 }
 
+fn log_accessibility_tree(node: &AccessibilityNode, depth: usize) {
+    let mut line = format!(
+        "{:indent$}{:?} {:?}",
+        "",
+        node.role,
+        node.name,
+        indent = depth * 2
+    );
+    if let Some(ref value) = node.value {
+        line.push_str(&format!(" value={:?}", value));
+    }
+    if let Some(level) = node.level {
+        line.push_str(&format!(" level={}", level));
+    }
+    if node.states != Default::default() {
+        line.push_str(&format!(" {:?}", node.states));
+    }
+    debug!("{}", line);
+    for child in &node.children {
+        log_accessibility_tree(child, depth + 1);
+    }
+}
+
+fn reader_view_html(article: &ReaderArticle) -> String {
+    fn escape(text: &str) -> String {
+        text.replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;")
+    }
+    let byline = match article.byline {
+        Some(ref byline) => format!("<p class=byline>{}</p>", escape(byline)),
+        None => String::new(),
+    };
+    format!(
+        "<!DOCTYPE html><meta charset=utf-8><title>{title}</title>\
+         <style>body {{ max-width: 40em; margin: 2em auto; padding: 0 1em; \
+         font: 20px/1.6 serif; }} img {{ max-width: 100%; }} \
+         .byline {{ font-style: italic; }}</style><h1>{title}</h1>{byline}{content}",
+        title = escape(&article.title),
+        byline = byline,
+        content = article.content,
+    )
+}
+
 // This is a mitigation for #25498, not a verified solution.
 // There may be codepaths in tinyfiledialog.c that this is
 // inadquate against, as it passes the string via shell to
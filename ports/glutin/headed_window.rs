@@ -34,7 +34,7 @@ use servo::servo_config::{opts, pref};
 use servo::servo_geometry::DeviceIndependentPixel;
 use servo::style_traits::DevicePixel;
 use servo::webrender_api::ScrollLocation;
-use servo::webrender_api::units::{DeviceIntPoint, DeviceIntRect, DeviceIntSize};
+use servo::webrender_api::units::{DeviceIntPoint, DeviceIntRect, DeviceIntSize, DeviceVector2D};
 use servo_media::player::context::{GlApi, GlContext as PlayerGLContext, NativeDisplay};
 use std::cell::{Cell, RefCell};
 use std::mem;
@@ -72,6 +72,7 @@ pub struct Window {
     last_pressed: Cell<Option<KeyboardEvent>>,
     animation_state: Cell<AnimationState>,
     fullscreen: Cell<bool>,
+    pointer_locked: Cell<bool>,
     gl: Rc<dyn gl::Gl>,
     xr_rotation: Cell<Rotation3D<f32, UnknownUnit, UnknownUnit>>,
     xr_translation: Cell<Vector3D<f32, UnknownUnit>>,
@@ -206,6 +207,7 @@ impl Window {
             gl: gl.clone(),
             animation_state: Cell::new(AnimationState::Idle),
             fullscreen: Cell::new(false),
+            pointer_locked: Cell::new(false),
             inner_size: Cell::new(inner_size),
             primary_monitor,
             screen_size,
@@ -409,6 +411,12 @@ impl WindowPortsMethods for Window {
             .set_position(LogicalPosition::new(point.x.into(), point.y.into()))
     }
 
+    fn set_ime_position(&self, point: DeviceIntPoint) {
+        let point = point.to_f32() / self.device_hidpi_factor();
+        self.gl_context.borrow_mut().window()
+            .set_ime_position(LogicalPosition::new(point.x.into(), point.y.into()))
+    }
+
     fn set_fullscreen(&self, state: bool) {
         if self.fullscreen.get() != state {
             self.gl_context.borrow_mut().window()
@@ -421,6 +429,20 @@ impl WindowPortsMethods for Window {
         return self.fullscreen.get();
     }
 
+    fn set_pointer_lock(&self, locked: bool) {
+        let gl_context = self.gl_context.borrow();
+        let window = gl_context.window();
+        if let Err(error) = window.grab_cursor(locked) {
+            warn!("Failed to grab the cursor ({})", error);
+        }
+        window.hide_cursor(locked);
+        self.pointer_locked.set(locked);
+    }
+
+    fn get_pointer_lock(&self) -> bool {
+        self.pointer_locked.get()
+    }
+
     fn set_cursor(&self, cursor: Cursor) {
         use glutin::MouseCursor;
 
@@ -472,6 +494,17 @@ impl WindowPortsMethods for Window {
         self.gl_context.borrow().window().id()
     }
 
+    fn winit_device_event_to_servo_event(&self, event: glutin::DeviceEvent) {
+        if let glutin::DeviceEvent::MouseMotion { delta: (dx, dy) } = event {
+            if self.pointer_locked.get() {
+                let movement = DeviceVector2D::new(dx as f32, dy as f32);
+                self.event_queue
+                    .borrow_mut()
+                    .push(WindowEvent::PointerLockMove(movement));
+            }
+        }
+    }
+
     fn winit_event_to_servo_event(&self, event: glutin::WindowEvent) {
         match event {
             glutin::WindowEvent::ReceivedCharacter(ch) => self.handle_received_character(ch),
@@ -485,6 +518,10 @@ impl WindowPortsMethods for Window {
                 let pos = position.to_physical(self.device_hidpi_factor().get() as f64);
                 let (x, y): (i32, i32) = pos.into();
                 self.mouse_pos.set(Point2D::new(x, y));
+                // The movements of the locked pointer are reported by the device events.
+                if self.pointer_locked.get() {
+                    return;
+                }
                 self.event_queue
                     .borrow_mut()
                     .push(WindowEvent::MouseWindowMoveEventClass(Point2D::new(
@@ -554,6 +591,15 @@ impl WindowPortsMethods for Window {
                     self.event_queue.borrow_mut().push(WindowEvent::Resize);
                 }
             },
+            glutin::WindowEvent::HiDpiFactorChanged(hidpi_factor) => {
+                // The window was moved to a monitor with another pixel density, so the size of
+                // its framebuffer changed while its size in device independent pixels didn't.
+                let size = self.gl_context.borrow().window().get_inner_size();
+                if let Some(size) = size {
+                    self.gl_context.borrow_mut().resize(size.to_physical(hidpi_factor));
+                }
+                self.event_queue.borrow_mut().push(WindowEvent::Resize);
+            },
             _ => {},
         }
     }
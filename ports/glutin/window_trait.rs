@@ -20,10 +20,16 @@ pub trait WindowPortsMethods: WindowMethods + webxr::glwindow::GlWindow {
     fn page_height(&self) -> f32;
     fn get_fullscreen(&self) -> bool;
     fn winit_event_to_servo_event(&self, event: glutin::WindowEvent);
+    fn winit_device_event_to_servo_event(&self, _event: glutin::DeviceEvent) {}
     fn is_animating(&self) -> bool;
     fn set_title(&self, _title: &str) {}
     fn set_inner_size(&self, _size: DeviceIntSize) {}
     fn set_position(&self, _point: DeviceIntPoint) {}
     fn set_fullscreen(&self, _state: bool) {}
+    fn set_pointer_lock(&self, _locked: bool) {}
+    fn get_pointer_lock(&self) -> bool {
+        false
+    }
     fn set_cursor(&self, _cursor: Cursor) {}
+    fn set_ime_position(&self, _point: DeviceIntPoint) {}
 }
@@ -8,9 +8,11 @@ extern crate log;
 pub mod gl_glue;
 
 pub use servo::embedder_traits::{
-    MediaSessionPlaybackState, PermissionPrompt, PermissionRequest, PromptResult,
+    FindOptions, MediaSessionPlaybackState, PermissionPrompt, PermissionRequest, PromptResult,
 };
-pub use servo::script_traits::{MediaSessionActionType, MouseButton};
+pub use servo::keyboard_types::CompositionState;
+pub use servo::msg::constellation_msg::NetworkConditions;
+pub use servo::script_traits::{MediaSessionActionDetails, MediaSessionActionType, MouseButton};
 
 use getopts::Options;
 use servo::canvas::{SurfaceProviders, WebGlExecutor};
@@ -19,14 +21,18 @@ use servo::compositing::windowing::{
     WindowMethods,
 };
 use servo::embedder_traits::resources::{self, Resource, ResourceReaderMethods};
-use servo::embedder_traits::{EmbedderMsg, MediaSessionEvent, PromptDefinition, PromptOrigin};
+use servo::embedder_traits::{
+    ClientIdentityChoice, EmbedderMsg, MediaSessionEvent, PictureInPictureEvent,
+    PromptDefinition, PromptOrigin, ShareResult, WebResourceResponseMsg,
+};
 use servo::euclid::{Point2D, Rect, Scale, Size2D, Vector2D};
-use servo::keyboard_types::{Key, KeyState, KeyboardEvent};
+use servo::keyboard_types::{CompositionEvent, Key, KeyState, KeyboardEvent};
 use servo::msg::constellation_msg::TraversalDirection;
 use servo::script_traits::{TouchEventType, TouchId};
 use servo::servo_config::opts;
 use servo::servo_config::{pref, set_pref};
 use servo::servo_url::ServoUrl;
+use servo::style_traits::{ColorSchemePreference, MediaPreferences};
 use servo::webrender_api::units::DevicePixel;
 use servo::webrender_api::ScrollLocation;
 use servo::webvr::{VRExternalShmemPtr, VRMainThreadHeartbeat, VRService, VRServiceManager};
@@ -459,6 +465,37 @@ impl ServoGlue {
         self.process_event(WindowEvent::PinchZoom(factor))
     }
 
+    /// Set the page zoom, where 1.0 is the default size. The pages are laid out again with the
+    /// new size of a CSS pixel.
+    pub fn set_page_zoom(&mut self, zoom: f32) -> Result<(), &'static str> {
+        info!("set_page_zoom: {}", zoom);
+        self.process_event(WindowEvent::SetZoom(zoom))
+    }
+
+    /// Reset the page zoom to the default size.
+    pub fn reset_page_zoom(&mut self) -> Result<(), &'static str> {
+        info!("reset_page_zoom");
+        self.process_event(WindowEvent::ResetZoom)
+    }
+
+    /// Scale the font sizes of the pages, without zooming the rest of their content, where 1.0
+    /// is the default size.
+    pub fn set_text_zoom(&mut self, zoom: f32) -> Result<(), &'static str> {
+        info!("set_text_zoom: {}", zoom);
+        self.process_event(WindowEvent::SetTextZoom(zoom))
+    }
+
+    /// Make the network of the browser behave like under the given conditions, to test how
+    /// pages load over slow or missing connections, or like it really does with `None`.
+    pub fn set_network_conditions(
+        &mut self,
+        conditions: Option<NetworkConditions>,
+    ) -> Result<(), &'static str> {
+        info!("set_network_conditions: {:?}", conditions);
+        let browser_id = self.get_browser_id()?;
+        self.process_event(WindowEvent::SetNetworkConditions(browser_id, conditions))
+    }
+
     /// Perform a click.
     pub fn click(&mut self, x: f32, y: f32) -> Result<(), &'static str> {
         let mouse_event = MouseWindowEvent::Click(MouseButton::Left, Point2D::new(x, y));
@@ -484,11 +521,43 @@ impl ServoGlue {
         self.process_event(WindowEvent::Keyboard(key_event))
     }
 
+    /// Start, update or commit the text of an IME composition.
+    pub fn ime_composition(
+        &mut self,
+        state: CompositionState,
+        data: String,
+    ) -> Result<(), &'static str> {
+        let composition_event = CompositionEvent { state, data };
+        self.process_event(WindowEvent::IMEComposition(composition_event))
+    }
+
+    /// Search the page for the given text, or move to the next match if the search
+    /// is repeated.
+    pub fn find(&mut self, query: String, options: FindOptions) -> Result<(), &'static str> {
+        info!("find");
+        let browser_id = self.get_browser_id()?;
+        self.process_event(WindowEvent::Find(browser_id, query, options))
+    }
+
+    /// Clear the highlights of the last search.
+    pub fn stop_finding(&mut self) -> Result<(), &'static str> {
+        info!("stop_finding");
+        let browser_id = self.get_browser_id()?;
+        self.process_event(WindowEvent::StopFinding(browser_id))
+    }
+
     pub fn media_session_action(
         &mut self,
         action: MediaSessionActionType,
     ) -> Result<(), &'static str> {
         info!("Media session action {:?}", action);
+        self.process_event(WindowEvent::MediaSessionAction(action.into()))
+    }
+
+    pub fn media_session_seek_to(&mut self, time: f64) -> Result<(), &'static str> {
+        info!("Media session seek to {}", time);
+        let mut action = MediaSessionActionDetails::from(MediaSessionActionType::SeekTo);
+        action.seek_time = Some(time);
         self.process_event(WindowEvent::MediaSessionAction(action))
     }
 
@@ -503,6 +572,34 @@ impl ServoGlue {
         }
     }
 
+    /// Free as much memory as possible, e.g. when the system asks the app to trim its memory.
+    pub fn memory_pressure(&mut self) -> Result<(), &'static str> {
+        info!("memory_pressure");
+        self.process_event(WindowEvent::MemoryPressure)
+    }
+
+    /// Let the pages know that the color scheme, the motion or the contrast settings of the
+    /// system changed.
+    pub fn change_media_preferences(
+        &mut self,
+        dark_color_scheme: bool,
+        reduced_motion: bool,
+        forced_colors: bool,
+    ) -> Result<(), &'static str> {
+        info!("change_media_preferences");
+        let color_scheme = if dark_color_scheme {
+            ColorSchemePreference::Dark
+        } else {
+            ColorSchemePreference::Light
+        };
+        let preferences = MediaPreferences {
+            color_scheme,
+            reduced_motion,
+            forced_colors,
+        };
+        self.process_event(WindowEvent::MediaPreferencesChanged(preferences))
+    }
+
     fn process_event(&mut self, event: WindowEvent) -> Result<(), &'static str> {
         self.events.push(event);
         if !self.batch_mode {
@@ -670,13 +767,100 @@ impl ServoGlue {
                                 position_state.position,
                                 position_state.playback_rate,
                             ),
+                        // The hosts always offer the same controls.
+                        MediaSessionEvent::SetActionHandlers(_) => {},
                     };
                 },
                 EmbedderMsg::OnDevtoolsStarted(port) => {
                     self.callbacks.host_callbacks.on_devtools_started(port);
                 },
+                EmbedderMsg::Share(_data, sender) => {
+                    // The hosts have no way to show a share sheet yet.
+                    let _ = sender.send(ShareResult::Failed);
+                },
+                EmbedderMsg::EnterPictureInPicture(request) => {
+                    // The hosts have no way to show a floating window yet.
+                    let _ = request.events.send(PictureInPictureEvent::Closed);
+                },
+                EmbedderMsg::ShowContextMenu(_menu, sender) => {
+                    // The hosts have no way to show a menu yet.
+                    let _ = sender.send(None);
+                },
+                EmbedderMsg::CertificateError(error) => {
+                    let message = format!(
+                        "The certificate of {} could not be verified: {}\n\n\
+                         Issuer: {}\nSHA-256 fingerprint: {}\n\n\
+                         Do you want to trust this certificate and continue?",
+                        error.host, error.reason, error.issuer, error.fingerprint,
+                    );
+                    let trusted = match self.callbacks.host_callbacks.prompt_yes_no(message, false)
+                    {
+                        PromptResult::Primary => true,
+                        PromptResult::Secondary | PromptResult::Dismissed => false,
+                    };
+                    let window_event = WindowEvent::CertificateErrorResponse(error.id, trusted);
+                    self.events.push(window_event);
+                    let _ = self.perform_updates();
+                },
+                EmbedderMsg::SelectClientCertificate(request, sender) => {
+                    // The hosts are offered the identities of the configured store one at a
+                    // time, and have no way to provide one of their own yet.
+                    let mut choice = None;
+                    for (index, identity) in request.identities.iter().enumerate() {
+                        let message = format!(
+                            "{} asks for a certificate to identify you.\n\n\
+                             Certificate: {}\nIssuer: {}\n\n\
+                             Do you want to identify yourself with this certificate?",
+                            request.host, identity.name, identity.issuer,
+                        );
+                        let result = self.callbacks.host_callbacks.prompt_yes_no(message, false);
+                        if let PromptResult::Primary = result {
+                            choice = Some(ClientIdentityChoice::Stored(index));
+                            break;
+                        }
+                    }
+                    let _ = sender.send(choice);
+                },
+                EmbedderMsg::WebResourceRequested(_request, sender) => {
+                    // The hosts have no way to intercept requests yet.
+                    let _ = sender.send(WebResourceResponseMsg::Continue);
+                },
+                EmbedderMsg::RegisterProtocolHandler(request, sender) => {
+                    let host = request.url.host_str().unwrap_or("").to_owned();
+                    let message = match request.current_url {
+                        Some(current_url) => format!(
+                            "Do you want {} to open the {} links instead of {}?",
+                            host,
+                            request.scheme,
+                            current_url.host_str().unwrap_or(""),
+                        ),
+                        None => {
+                            format!("Do you want {} to open the {} links?", host, request.scheme)
+                        },
+                    };
+                    let allowed = match self.callbacks.host_callbacks.prompt_yes_no(message, false)
+                    {
+                        PromptResult::Primary => true,
+                        PromptResult::Secondary | PromptResult::Dismissed => false,
+                    };
+                    let _ = sender.send(allowed);
+                },
+                EmbedderMsg::AllowDownload(_id, url, suggested_path, sender) => {
+                    // The hosts have no way to pick another path yet.
+                    let message = format!(
+                        "Do you want to save {} to {}?",
+                        url,
+                        suggested_path.display()
+                    );
+                    let path = match self.callbacks.host_callbacks.prompt_yes_no(message, false) {
+                        PromptResult::Primary => Some(suggested_path),
+                        PromptResult::Secondary | PromptResult::Dismissed => None,
+                    };
+                    let _ = sender.send(path);
+                },
                 EmbedderMsg::Status(..) |
                 EmbedderMsg::SelectFiles(..) |
+                EmbedderMsg::Download(..) |
                 EmbedderMsg::MoveTo(..) |
                 EmbedderMsg::ResizeTo(..) |
                 EmbedderMsg::Keyboard(..) |
@@ -684,6 +868,17 @@ impl ServoGlue {
                 EmbedderMsg::NewFavicon(..) |
                 EmbedderMsg::HeadParsed |
                 EmbedderMsg::SetFullscreenState(..) |
+                EmbedderMsg::SetPointerLock(..) |
+                EmbedderMsg::SetScreenWakeLock(..) |
+                EmbedderMsg::IMECaretRect(..) |
+                EmbedderMsg::FindInPageResult(..) |
+                EmbedderMsg::PrintDocument(..) |
+                EmbedderMsg::ReaderModeArticle(..) |
+                EmbedderMsg::AccessibilityTree(..) |
+                EmbedderMsg::FormFields(..) |
+                EmbedderMsg::PasswordFieldFound(..) |
+                EmbedderMsg::LoginSubmitted(..) |
+                EmbedderMsg::OverscrollBehaviorChanged(..) |
                 EmbedderMsg::Panic(..) |
                 EmbedderMsg::ReportProfile(..) => {},
             }
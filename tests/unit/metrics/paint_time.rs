@@ -7,6 +7,9 @@ use ipc_channel::ipc;
 use metrics::{PaintTimeMetrics, ProfilerMetadataFactory, ProgressiveWebMetric};
 use msg::constellation_msg::TEST_PIPELINE_ID;
 use profile_traits::time::{ProfilerChan, TimerMetadata};
+use script_traits::{
+    ConstellationControlMsg, LargestContentfulPaintCandidate, UntrustedNodeAddress,
+};
 use servo_url::ServoUrl;
 
 struct DummyProfilerMetadataFactory {}
@@ -64,6 +67,7 @@ fn test_common(display_list_is_contentful: bool, epoch: Epoch) -> PaintTimeMetri
         &dummy_profiler_metadata_factory,
         epoch,
         display_list_is_contentful,
+        None,
     );
 
     // Should not set any metric until navigation start is set.
@@ -122,3 +126,87 @@ fn test_first_contentful_paint_setter() {
         "first paint is set"
     );
 }
+
+#[test]
+fn test_paint_metrics_of_skipped_epochs() {
+    let paint_time_metrics = test_common(true, Epoch(0));
+    let dummy_profiler_metadata_factory = DummyProfilerMetadataFactory {};
+    paint_time_metrics.maybe_observe_paint_time(
+        &dummy_profiler_metadata_factory,
+        Epoch(1),
+        false,
+        None,
+    );
+
+    // The frame of the first epoch was never presented on its own.
+    let now = time::precise_time_ns();
+    paint_time_metrics.maybe_set_metric(Epoch(2), now);
+    assert!(
+        paint_time_metrics.get_first_paint().is_some(),
+        "first paint is set"
+    );
+    assert_eq!(
+        paint_time_metrics.get_first_contentful_paint(),
+        paint_time_metrics.get_first_paint(),
+        "first contentful paint is set to the presentation time"
+    );
+}
+
+#[test]
+fn test_largest_contentful_paint_candidates() {
+    let (sender, _) = ipc::channel().unwrap();
+    let profiler_chan = ProfilerChan(sender);
+    let (layout_sender, _) = ipc::channel().unwrap();
+    let (script_sender, script_receiver) = ipc::channel().unwrap();
+    let mut paint_time_metrics = PaintTimeMetrics::new(
+        TEST_PIPELINE_ID,
+        profiler_chan,
+        layout_sender,
+        script_sender,
+        ServoUrl::parse("about:blank").unwrap(),
+    );
+    paint_time_metrics.set_navigation_start(time::precise_time_ns());
+    let dummy_profiler_metadata_factory = DummyProfilerMetadataFactory {};
+    let candidate = |size| LargestContentfulPaintCandidate {
+        node: UntrustedNodeAddress::from_id(1),
+        size,
+    };
+    let reported_sizes = || {
+        let mut sizes = vec![];
+        while let Ok(msg) = script_receiver.try_recv() {
+            if let ConstellationControlMsg::LargestContentfulPaint(_, _, candidate) = msg {
+                sizes.push(candidate.size);
+            }
+        }
+        sizes
+    };
+
+    // Only the largest of the candidates painted by a frame is reported.
+    for (epoch, size) in vec![(0, 100), (1, 50), (2, 200)] {
+        paint_time_metrics.maybe_observe_paint_time(
+            &dummy_profiler_metadata_factory,
+            Epoch(epoch),
+            true,
+            Some(candidate(size)),
+        );
+    }
+    paint_time_metrics.maybe_set_metric(Epoch(2), time::precise_time_ns());
+    assert_eq!(reported_sizes(), vec![200]);
+
+    // Candidates which aren't larger than the reported one are ignored, even after all the
+    // other paint metrics were set.
+    paint_time_metrics.maybe_observe_paint_time(
+        &dummy_profiler_metadata_factory,
+        Epoch(3),
+        true,
+        Some(candidate(200)),
+    );
+    paint_time_metrics.maybe_observe_paint_time(
+        &dummy_profiler_metadata_factory,
+        Epoch(4),
+        true,
+        Some(candidate(300)),
+    );
+    paint_time_metrics.maybe_set_metric(Epoch(4), time::precise_time_ns());
+    assert_eq!(reported_sizes(), vec![300]);
+}
@@ -20,3 +20,25 @@ fn test_unexhausted_transform() {
     assert_parser_exhausted!(transform::parse, "rotate(70deg)foo", false);
     assert_parser_exhausted!(transform::parse, "rotate(70deg) foo", false);
 }
+
+#[test]
+fn test_animation_timeline() {
+    use style::properties::longhands::animation_timeline;
+    assert_roundtrip_with_context!(animation_timeline::parse, "auto");
+    assert_roundtrip_with_context!(animation_timeline::parse, "none, auto");
+    assert_roundtrip_with_context!(animation_timeline::parse, "scroll()");
+    assert_roundtrip_with_context!(
+        animation_timeline::parse,
+        "scroll(nearest block)",
+        "scroll()"
+    );
+    assert_roundtrip_with_context!(
+        animation_timeline::parse,
+        "scroll(x root)",
+        "scroll(root x)"
+    );
+    assert_roundtrip_with_context!(animation_timeline::parse, "scroll(self)");
+    assert_roundtrip_with_context!(animation_timeline::parse, "view(inline)");
+    assert!(parse(animation_timeline::parse, "scroll(root root)").is_err());
+    assert!(parse(animation_timeline::parse, "view(root)").is_err());
+}
@@ -7,6 +7,8 @@ importScripts("interfaces.js");
 
 // IMPORTANT: Do not change the list below without review from a DOM peer!
 test_interfaces([
+  "AbortController",
+  "AbortSignal",
   "Blob",
   "BroadcastChannel",
   "CanvasGradient",
@@ -20,6 +22,7 @@ test_interfaces([
   "DOMRect",
   "DOMRectReadOnly",
   "DOMStringList",
+  "CryptoKey",
   "CustomEvent",
   "DedicatedWorkerGlobalScope",
   "DOMException",
@@ -50,6 +53,11 @@ test_interfaces([
   "PromiseRejectionEvent",
   "Request",
   "Response",
+  "Scheduler",
+  "SubtleCrypto",
+  "TaskController",
+  "TaskPriorityChangeEvent",
+  "TaskSignal",
   "TextDecoder",
   "TextEncoder",
   "URL",